@@ -0,0 +1,88 @@
+//! Harvesting cell parameters from a directory: full vs selective parsing.
+//!
+//! Run with `cargo bench --bench harvest`. Each file under
+//! `tests/example_cifs` is parsed in full and with a selection of just the
+//! `_cell_*` and `_symmetry_*` items of the first block; the best of several
+//! rounds is reported. [`extract_items`] is timed against just reading the
+//! files, to show how close extraction comes to being bound by I/O.
+
+use cif_parser::{extract_items, Document, ExtractOptions, ParseOptions, Selection};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const ROUNDS: usize = 5;
+
+fn cif_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(cif_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Best time over `ROUNDS` runs of `run`
+fn best_of(mut run: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Best time over `ROUNDS` runs of parsing every file with `options`
+fn best_time(files: &[PathBuf], options: &ParseOptions) -> Duration {
+    best_of(|| {
+        for file in files {
+            // Some examples are malformed; a failed parse still costs time
+            let _ = std::hint::black_box(Document::from_file_with_options(file, options));
+        }
+    })
+}
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    let files = cif_files(&dir);
+
+    let full = ParseOptions::new();
+    let harvest = ParseOptions::new()
+        .select(Selection::FirstBlockOnly)
+        .select(Selection::TagPrefixes(vec![
+            "_cell_".to_string(),
+            "_symmetry_".to_string(),
+        ]));
+
+    let full_time = best_time(&files, &full);
+    let harvest_time = best_time(&files, &harvest);
+    let read_time = best_of(|| {
+        for file in &files {
+            let _ = std::hint::black_box(std::fs::read(file));
+        }
+    });
+    let tags = ["_cell_volume", "_symmetry_space_group_name_H-M"];
+    let extract = ExtractOptions::new().first_block_only(true);
+    let extract_time = best_of(|| {
+        std::hint::black_box(extract_items(&files, &tags, &extract));
+    });
+
+    println!("{} files from {}", files.len(), dir.display());
+    println!("full parse:      {full_time:>10.2?}");
+    println!("cell harvest:    {harvest_time:>10.2?}");
+    println!(
+        "speedup:         {:>9.1}x",
+        full_time.as_secs_f64() / harvest_time.as_secs_f64()
+    );
+    println!("read only:       {read_time:>10.2?}");
+    println!(
+        "extract:         {extract_time:>10.2?} ({} threads)",
+        extract.threads
+    );
+}
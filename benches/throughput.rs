@@ -0,0 +1,93 @@
+//! Parsing, lookup, column, and writing throughput on fixed inputs.
+//!
+//! Run with `cargo bench --bench throughput`. The small-molecule input is
+//! `tests/example_cifs/abiraterone_acetate`, with its reflection list; the
+//! large mmCIF is made by [`generate_mmcif`] so no large file is kept in
+//! the repository. Published baselines are in `docs/benchmarks.md`; the
+//! Python-side conversions are benchmarked in `python/benchmarks`.
+
+use cif_parser::testing::generate_mmcif;
+use cif_parser::{CifDocumentRef, Document};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::path::Path;
+
+const LARGE_ATOMS: usize = 20_000;
+const LARGE_REFLECTIONS: usize = 50_000;
+
+fn small_molecule() -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/example_cifs/abiraterone_acetate/abiraterone_acetate.cif");
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let small = small_molecule();
+    let large = generate_mmcif(LARGE_ATOMS, LARGE_REFLECTIONS);
+
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(small.len() as u64));
+    group.bench_function("small_molecule", |b| {
+        b.iter(|| Document::parse(black_box(&small)).unwrap())
+    });
+    group.throughput(Throughput::Bytes(large.len() as u64));
+    group.bench_function("large_mmcif", |b| {
+        b.iter(|| Document::parse(black_box(&large)).unwrap())
+    });
+    group.bench_function("large_mmcif_borrowed", |b| {
+        b.iter(|| CifDocumentRef::parse(black_box(&large)).unwrap())
+    });
+    group.finish();
+}
+
+fn columns(c: &mut Criterion) {
+    let doc = Document::parse(&generate_mmcif(LARGE_ATOMS, LARGE_REFLECTIONS)).unwrap();
+    let atoms = doc.blocks[0].find_loop("_atom_site.id").unwrap();
+
+    let mut group = c.benchmark_group("columns");
+    group.throughput(Throughput::Elements(atoms.len() as u64));
+    group.bench_function("cartn_x_numeric", |b| {
+        b.iter(|| {
+            atoms
+                .get_column(black_box("_atom_site.Cartn_x"))
+                .unwrap()
+                .into_iter()
+                .map(|value| value.as_numeric())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+fn find_loop(c: &mut Criterion) {
+    let doc = Document::parse(&generate_mmcif(LARGE_ATOMS, LARGE_REFLECTIONS)).unwrap();
+    let block = &doc.blocks[0];
+
+    let mut group = c.benchmark_group("find_loop");
+    group.bench_function("first_loop", |b| {
+        b.iter(|| block.find_loop(black_box("_atom_site.group_PDB")))
+    });
+    group.bench_function("last_tag", |b| {
+        b.iter(|| block.find_loop(black_box("_refln.F_meas_sigma_au")))
+    });
+    group.bench_function("missing", |b| {
+        b.iter(|| block.find_loop(black_box("_struct.title")))
+    });
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let small = Document::parse(&small_molecule()).unwrap();
+    let large = Document::parse(&generate_mmcif(LARGE_ATOMS, LARGE_REFLECTIONS)).unwrap();
+
+    let mut group = c.benchmark_group("write");
+    group.bench_function("small_molecule", |b| {
+        b.iter(|| black_box(&small).to_string())
+    });
+    group.sample_size(10);
+    group.bench_function("large_mmcif", |b| b.iter(|| black_box(&large).to_string()));
+    group.finish();
+}
+
+criterion_group!(benches, parse, columns, find_loop, write);
+criterion_main!(benches);
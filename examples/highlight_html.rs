@@ -0,0 +1,102 @@
+// Render a CIF file as syntax-highlighted HTML using the tokenizer
+// Run with: cargo run --example highlight_html [path/to/file.cif] > out.html
+
+use cif_parser::lexer::{TokenKind, Tokenizer};
+use std::error::Error;
+
+const SAMPLE: &str = r#"#\#CIF_2.0
+data_example
+_publ_section_title 'Crystal structure of "X"'
+_cell_length_a 10.234(3)
+_journal_coden_ASTM ?
+loop_
+_atom_site_label
+_atom_site_fract_x
+C1 0.1234(5)
+O1 0.2345(6)
+_refine_special_details
+;
+Multi-line text field.
+;
+_example_table {'a':1 'b':[2 3]}
+"#;
+
+const STYLE: &str = "\
+.cif-block { color: #8b0000; font-weight: bold; }
+.cif-save { color: #8b4513; font-weight: bold; }
+.cif-loop { color: #00008b; font-weight: bold; }
+.cif-tag { color: #006400; }
+.cif-string { color: #b8860b; }
+.cif-text { color: #b8860b; background: #fdf6e3; }
+.cif-value { color: #000000; }
+.cif-delimiter { color: #800080; }
+.cif-comment { color: #808080; font-style: italic; }";
+
+/// CSS class for a token kind, or None for text left unstyled
+fn css_class(kind: TokenKind) -> Option<&'static str> {
+    match kind {
+        TokenKind::BlockHeader => Some("cif-block"),
+        TokenKind::SaveHeader | TokenKind::SaveEnd => Some("cif-save"),
+        TokenKind::LoopKeyword => Some("cif-loop"),
+        TokenKind::Tag => Some("cif-tag"),
+        TokenKind::QuotedString => Some("cif-string"),
+        TokenKind::TextField => Some("cif-text"),
+        TokenKind::UnquotedValue => Some("cif-value"),
+        TokenKind::Delimiter => Some("cif-delimiter"),
+        TokenKind::Comment => Some("cif-comment"),
+        TokenKind::Whitespace => None,
+    }
+}
+
+fn escape(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\u{FEFF}' => {}
+            _ => out.push(ch),
+        }
+    }
+}
+
+fn render(input: &str) -> String {
+    let mut body = String::new();
+    let mut end = 0;
+    for token in Tokenizer::new(input) {
+        match token {
+            Ok(token) => {
+                end = token.byte_range.end;
+                match css_class(token.kind) {
+                    Some(class) => {
+                        body.push_str(&format!("<span class=\"{class}\">"));
+                        escape(token.text, &mut body);
+                        body.push_str("</span>");
+                    }
+                    None => escape(token.text, &mut body),
+                }
+            }
+            Err(e) => {
+                // Show the rest unstyled so the page still has the whole file
+                eprintln!("warning: {e}");
+                break;
+            }
+        }
+    }
+    escape(&input[end..], &mut body);
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <style>\n{STYLE}\n</style>\n</head>\n<body>\n<pre>{body}</pre>\n</body>\n</html>\n"
+    )
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let input = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => SAMPLE.to_string(),
+    };
+    print!("{}", render(&input));
+    Ok(())
+}
@@ -0,0 +1,82 @@
+// Read tags as your own types by implementing FromCifValue or Converter
+// Run with: cargo run --example typed_values
+
+use cif_parser::{CifValue, ConversionError, Converter, Document, FromCifValue};
+use std::error::Error;
+
+const SAMPLE: &str = r#"
+data_example
+_exptl_crystal_face_hkl      '(1 -1 0)'
+_diffrn_ambient_temperature  293(2)
+_diffrn_measured_temperature '20.5 C'
+_exptl_crystal_colour        'pale yellow'
+"#;
+
+/// A Miller index triple, written as `'1 -1 0'` or `'(1 -1 0)'`
+#[derive(Debug, PartialEq)]
+struct MillerIndex(i32, i32, i32);
+
+impl FromCifValue for MillerIndex {
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        let invalid = || ConversionError::new(value, "a Miller index");
+        let text = value.as_string().ok_or_else(invalid)?;
+        let text = text.trim().trim_start_matches('(').trim_end_matches(')');
+        let indices: Vec<i32> = text
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid())?;
+        match indices[..] {
+            [h, k, l] => Ok(MillerIndex(h, k, l)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Reads a temperature in kelvin, or with a `K` or `C` suffix, into the
+/// unit the caller wants
+struct Temperature {
+    celsius: bool,
+}
+
+impl Converter<f64> for Temperature {
+    fn convert(&self, value: &CifValue) -> Result<f64, ConversionError> {
+        let invalid = || ConversionError::new(value, "a temperature");
+        let text = value.as_string().unwrap_or_default().trim();
+        let kelvin = if let Some(number) = text.strip_suffix('K') {
+            number.trim().parse().map_err(|_| invalid())?
+        } else if let Some(number) = text.strip_suffix('C') {
+            number.trim().parse::<f64>().map_err(|_| invalid())? + 273.15
+        } else {
+            // No unit: kelvin, as the dictionaries use, possibly with an su
+            f64::from_cif_value(value)?
+        };
+        Ok(if self.celsius {
+            kelvin - 273.15
+        } else {
+            kelvin
+        })
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let doc = Document::parse(SAMPLE)?;
+    let block = doc.first_block().ok_or("no data block")?;
+
+    let face: Option<MillerIndex> = block.get_as("_exptl_crystal_face_hkl")?;
+    println!("Face: {face:?}");
+
+    let celsius = Temperature { celsius: true };
+    let ambient = block.get_with("_diffrn_ambient_temperature", &celsius)?;
+    let measured = block.get_with("_diffrn_measured_temperature", &celsius)?;
+    if let (Some(ambient), Some(measured)) = (ambient, measured) {
+        println!("Ambient: {ambient:.1} °C, measured: {measured:.1} °C");
+    }
+
+    // Errors name the tag and show the token that could not be read
+    if let Err(e) = block.get_as::<MillerIndex>("_exptl_crystal_colour") {
+        println!("Error: {e}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! Fuzz target: parsing arbitrary bytes must return `Ok` or `Err`, never panic.
+//!
+//! Run with `cargo +nightly fuzz run parse` from the repository root. Distilled
+//! crashing inputs belong in `tests/fuzz_regressions.rs`.
+
+#![no_main]
+
+use cif_parser::{CifValue, Document, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+
+    let _ = Document::parse(&input);
+    let _ = Document::parse_with_options(&input, &ParseOptions::new().lenient(true));
+    let _ = CifValue::parse_value(&input);
+});
@@ -0,0 +1,70 @@
+"""Benchmarks for converting parsed CIF data to Python objects.
+
+Run with `just python-bench`, or from python/:
+`uv run pytest benchmarks --benchmark-only`. The inputs are example files
+from tests/example_cifs: a small molecule with its reflections, and a
+protein mmCIF. The Rust-side operations are benchmarked in benches/.
+"""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+EXAMPLES = Path(__file__).parents[2] / "tests" / "example_cifs"
+SMALL_MOLECULE = EXAMPLES / "abiraterone_acetate" / "abiraterone_acetate.cif"
+MMCIF = EXAMPLES / "hen_egg_white_lysozyme" / "ccdc_hen_egg_white_lysozyme.cif"
+
+
+@pytest.fixture(scope="module")
+def small_molecule():
+    return cif_parser.parse_file(str(SMALL_MOLECULE))
+
+
+@pytest.fixture(scope="module")
+def refln_loop(small_molecule):
+    return small_molecule.blocks[1].find_loop("_refln_index_h")
+
+
+@pytest.mark.parametrize(
+    "path", [SMALL_MOLECULE, MMCIF], ids=["small_molecule", "mmcif"]
+)
+def test_parse_file(benchmark, path):
+    doc = benchmark(cif_parser.parse_file, str(path))
+    assert doc.blocks
+
+
+def test_document_to_dict(benchmark, small_molecule):
+    data = benchmark(small_molecule.to_dict)
+    assert data["blocks"]
+
+
+def test_large_block_to_dict(benchmark):
+    lines = ["data_big", "loop_"]
+    lines += [f"_atom_site_{name}" for name in ("label", "type_symbol", "fract_x")]
+    lines += [f"C{i} C 0.{i:05d}" for i in range(10_000)]
+    block = cif_parser.parse("\n".join(lines) + "\n").first_block()
+
+    data = benchmark(block.to_dict)
+    assert len(data["loops"][0]["rows"]) == 10_000
+
+
+def test_loop_to_columns(benchmark, small_molecule):
+    block = small_molecule.blocks[1]
+    data = benchmark(block.to_dict, loops="columns")
+    assert data["loops"]
+
+
+def test_column_to_python(benchmark, refln_loop):
+    def convert():
+        column = refln_loop.get_column("_refln_intensity_meas")
+        return [value.to_python() for value in column]
+
+    values = benchmark(convert)
+    assert len(values) == len(refln_loop)
+
+
+def test_column_buffer(benchmark, refln_loop):
+    buffer = benchmark(refln_loop.column_buffer, "_refln_intensity_meas")
+    assert len(memoryview(buffer).cast("B")) == 8 * len(refln_loop)
@@ -23,33 +23,135 @@ Classes:
     Document: Root container for CIF data
     Block: Data block containing items, loops, and frames
     Loop: Tabular data structure
+    ColumnBuffer: Read-only float64 buffer over a numeric loop column
+    Category: One category of a block as a table of items and loop columns
     Frame: Save frame container
     Value: Individual CIF value with type information
+    ValueSource: Named tuple recording where a Value was read from
+    DocumentSource: File path, hash, and parse time of a Document
+    Snapshot: Read-only copy of a Document for restoring it
+    Transaction: Context manager restoring a Document on an exception
+    Dictionary: Item definitions of a DDLm dictionary
+    DictionaryStack: Dictionaries layered for Document.validate()
+    Publication: Authors, title, and journal reference of a block
+    Author: Publication author with family/given names
+    Identifiers: Database codes and DOIs of a block
+    Experimental: Crystal, temperature, absorption, and density of a block
+    Measurement: Standard reflections, scans, and instruments of a block
+    RefinementModel: Parameters, restraints, weights, and hydrogens of a block
+    UnitCell: Cell parameters with sus, volume, and distances
+    SymOp: A symmetry operation, parsed from x,y,z notation
+    PointGroup: Point group, Laue class, and crystal system of a block
+    SpaceGroup: Space group operations from a Hall or Hermann-Mauguin symbol or number
+    ChainMap: Label and author residue numbering of a PDBx block
+    ReflectionData: Measured reflections of a block
+    MergedReflections: Merged reflections with R_merge and per-shell statistics
+    Comparison: Result of compare(), with the difference in sigmas
+    StructureComparison: Result of compare_structures(), atom by atom
+    Structure: Cell, symmetry, and atom sites of a block
+    SiteSymmetry: Site symmetry and multiplicity of an atom site
+    CoercionReport: Result of Block.check_schema()
+    Writer: Streaming writer for CIF files too large to build as a Document
 
 Functions:
     parse(content): Parse CIF content from string
     parse_file(path): Parse CIF file
+    iter_stream(path): Iterate over the entries of concatenated CIFs
+    extract(paths, tags): Pull single-valued tags out of many files
+    compare(a, b, su_a=None, su_b=None): Compare numbers with sus
+    compare_structures(block_a, block_b): Compare two structures atom by atom
+    parse_element(symbol): Read an atom type symbol such as 'Fe3+'
+    category_of(tag): Category of a tag, e.g. 'atom_site_aniso'
+    format(text): Format CIF text canonically, keeping comments
 """
 
 from ._cif_parser import (
+    Author,
     Block,
+    Category,
+    ChainMap,
+    CoercionReport,
+    ColumnBuffer,
+    Comparison,
+    Dictionary,
+    DictionaryStack,
     Document,
+    DocumentSource,
+    Experimental,
     Frame,
+    Identifiers,
     Loop,
+    Measurement,
+    MergedReflections,
+    PointGroup,
+    Publication,
+    ReflectionData,
+    RefinementModel,
+    SiteSymmetry,
+    Snapshot,
+    SpaceGroup,
+    Structure,
+    StructureComparison,
+    SymOp,
+    Transaction,
+    UnitCell,
     Value,
+    ValueSource,
+    Writer,
     __version__,
+    category_of,
+    compare,
+    compare_structures,
+    extract,
+    format,
+    iter_stream,
     parse,
+    parse_element,
     parse_file,
 )
 
 __all__ = [
     "Document",
+    "DocumentSource",
+    "Snapshot",
+    "Transaction",
+    "Dictionary",
+    "DictionaryStack",
     "Block",
     "Loop",
+    "ColumnBuffer",
+    "Category",
     "Frame",
     "Value",
+    "ValueSource",
+    "Publication",
+    "Author",
+    "Identifiers",
+    "Experimental",
+    "Measurement",
+    "RefinementModel",
+    "UnitCell",
+    "SymOp",
+    "PointGroup",
+    "SpaceGroup",
+    "ChainMap",
+    "Structure",
+    "SiteSymmetry",
+    "ReflectionData",
+    "MergedReflections",
+    "Comparison",
+    "StructureComparison",
+    "CoercionReport",
+    "Writer",
     "parse",
     "parse_file",
+    "iter_stream",
+    "extract",
+    "compare",
+    "compare_structures",
+    "parse_element",
+    "category_of",
+    "format",
     "__version__",
 ]
 
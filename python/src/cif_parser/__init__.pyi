@@ -21,11 +21,45 @@ Example usage with type checking:
                 print(f"Cell length: {length}")
 """
 
-from typing import Iterator, overload
+import os
+from datetime import datetime
+from typing import (
+    TYPE_CHECKING,
+    Any,
+    Iterator,
+    Literal,
+    NamedTuple,
+    Sequence,
+    TypedDict,
+    overload,
+)
+
+if TYPE_CHECKING:
+    import pandas
 
 __version__: str
 __author__: str
 
+StrPath = str | bytes | os.PathLike[str] | os.PathLike[bytes]
+"""A file path. Bytes are decoded as os.fsdecode() does, so names that are
+not valid UTF-8 can be given as bytes, or as str from os.listdir()."""
+
+class ValueSource(NamedTuple):
+    """
+    Where a Value was read from.
+
+    Attributes:
+        block: Data block name, or None for a loop or frame detached from a block
+        frame: Save frame name, or None outside a save frame
+        tag: Tag the value belongs to
+        row: Loop row index, or None for a plain data item
+    """
+
+    block: str | None
+    frame: str | None
+    tag: str
+    row: int | None
+
 class Value:
     """
     Represents a single value in a CIF file with runtime type detection.
@@ -50,6 +84,25 @@ class Value:
         """Returns True if this value contains text."""
         ...
 
+    @property
+    def is_large(self) -> bool:
+        """
+        Returns True if this is text above the parse's large_value_threshold.
+
+        Large values are not returned by text or to_python(); read them with
+        read_large().
+        """
+        ...
+
+    @property
+    def large_size(self) -> int | None:
+        """Length in bytes of a large value's text, or None if not large."""
+        ...
+
+    def read_large(self) -> str | None:
+        """Read a large value's text, or None if this is not a large value."""
+        ...
+
     @property
     def is_numeric(self) -> bool:
         """Returns True if this value is numeric."""
@@ -57,12 +110,20 @@ class Value:
 
     @property
     def is_unknown(self) -> bool:
-        """Returns True if this is the special value '?' (unknown)."""
+        """
+        Returns True if this is the special value '?' (unknown).
+
+        Only a bare ? is unknown; a quoted '?' is the text "?".
+        """
         ...
 
     @property
     def is_not_applicable(self) -> bool:
-        """Returns True if this is the special value '.' (not applicable)."""
+        """
+        Returns True if this is the special value '.' (not applicable).
+
+        Only a bare . is not applicable; a quoted '.' is the text ".".
+        """
         ...
 
     @property
@@ -80,11 +141,57 @@ class Value:
         """
         Get the numeric value if is_numeric is True, otherwise None.
 
+        A number written with an su, such as 1.234(5), gives its value; su
+        gives the su.
+
         Returns:
             The numeric value as float, or None if this is not numeric.
         """
         ...
 
+    @property
+    def numeric_with_su(self) -> tuple[float, float | None] | None:
+        """
+        Get the value as a number with its standard uncertainty.
+
+        Returns:
+            (value, su), with su None if none is given, or None if this is
+            not a number.
+
+        Example:
+            >>> doc = cif_parser.parse("data_x\n_cell_length_a 5.431(2)\n")
+            >>> doc.first_block().get_item("_cell_length_a").numeric_with_su
+            (5.431, 0.002)
+        """
+        ...
+
+    @property
+    def su(self) -> float | None:
+        """
+        Get the standard uncertainty of a number written like 5.431(2).
+
+        Returns:
+            The su, or None if the value has none.
+        """
+        ...
+
+    def normalized_text(self, markup: Literal["unicode", "cif", "keep"] = "unicode") -> str | None:
+        """
+        Get the text cleaned up for display, or None if this is not text.
+
+        Joins hard-wrapped lines (keeping paragraphs apart), collapses
+        whitespace, and repairs UTF-8 text mis-decoded as Windows-1252.
+
+        Args:
+            markup: "unicode" converts CIF markup (\\'e, \\a, \\%, ^2^, ~2~)
+                to Unicode, "cif" converts Unicode to CIF markup, and "keep"
+                leaves it alone
+
+        Raises:
+            ValueError: If markup is not one of the accepted values
+        """
+        ...
+
     @property
     def value_type(self) -> str:
         """
@@ -102,7 +209,18 @@ class Value:
         Returns:
             - str if text value
             - float if numeric value
-            - None if unknown or not applicable
+            - None if unknown or not applicable, or a large value (see
+              read_large())
+        """
+        ...
+
+    @property
+    def source(self) -> ValueSource | None:
+        """
+        Provenance of this value.
+
+        Set for values obtained from a Block, Frame, or Loop; None for
+        detached values.
         """
         ...
 
@@ -111,13 +229,32 @@ class Value:
         ...
 
     def __repr__(self) -> str:
-        """Debug representation of the value."""
+        """Debug representation, e.g. Value(1.234, _cell_length_a)."""
         ...
 
     def __eq__(self, other: object) -> bool:
-        """Check equality with another Value."""
+        """Check equality with another Value (source is ignored)."""
+        ...
+
+class ColumnBuffer:
+    """
+    A read-only buffer of float64 over one loop column, from
+    Loop.column_buffer().
+
+    Supports the buffer protocol, so memoryview() and numpy.frombuffer()
+    read the numbers in place. The buffer owns its numbers and stays valid
+    after the loop and document are gone.
+    """
+
+    @property
+    def tag(self) -> str:
+        """The tag of the column."""
         ...
 
+    def __len__(self) -> int: ...
+    def __buffer__(self, flags: int, /) -> memoryview: ...
+    def __repr__(self) -> str: ...
+
 class Loop:
     """
     Represents a loop structure (tabular data) in a CIF file.
@@ -135,6 +272,43 @@ class Loop:
         value = loop.get(row=0, col=1)
     """
 
+    def __init__(self, tags: list[str] | None = None) -> None:
+        """
+        Create an empty loop, to fill with add_row() and add to a block
+        with Block.add_loop().
+
+        Loops taken from a block are copies: changing one leaves the block
+        as it was.
+
+        Example:
+            >>> atoms = Loop(["_atom_site_label", "_atom_site_fract_x"])
+            >>> atoms.add_row(["C1", 0.125])
+            >>> block.add_loop(atoms)
+        """
+        ...
+
+    def add_row(self, values: Sequence[WritableValue]) -> None:
+        """
+        Append a row with one value per tag: numbers, text, None for "?",
+        and lists and dicts for CIF 2.0.
+
+        Raises:
+            ValueError: If the row has the wrong length.
+            TypeError: If a value has no CIF form.
+        """
+        ...
+
+    def add_column(self, tag: str, values: Sequence[WritableValue]) -> None:
+        """
+        Append a column with one value per row. A loop without tags takes
+        its rows from the column.
+
+        Raises:
+            ValueError: If the loop has the tag already, or the number of
+                values is not the number of rows.
+        """
+        ...
+
     @property
     def tags(self) -> list[str]:
         """Get the column tags (headers)."""
@@ -187,9 +361,25 @@ class Loop:
         """
         ...
 
+    def column_buffer(self, tag: str) -> ColumnBuffer:
+        """
+        Get a numeric column as a read-only float64 buffer, without copying.
+
+        The numbers are converted once per column and cached on the loop;
+        ? and . become NaN, and numbers with an su give their value.
+
+        Example:
+            >>> intensities = numpy.frombuffer(loop.column_buffer("_refln_intensity_meas"))
+
+        Raises:
+            KeyError: If the tag is not in the loop
+            TypeError: If a value is not a number; the message names its row
+        """
+        ...
+
     def get_row_dict(self, row: int) -> dict[str, Value] | None:
         """
-        Get a row as a dictionary mapping tags to values.
+        Get a row as a dictionary mapping tags to values, in column order.
 
         Args:
             row: Row index (0-based)
@@ -199,6 +389,45 @@ class Loop:
         """
         ...
 
+    @property
+    def common_prefix(self) -> str | None:
+        """
+        Category prefix shared by all tags, with its separator.
+
+        `_atom_site_` for core CIF tags, `_atom_site.` for mmCIF tags, or
+        None if the tags mix categories.
+        """
+        ...
+
+    @overload
+    def iter_dicts(
+        self, native: Literal[True] = True, strip_prefix: bool = True
+    ) -> Iterator[dict[str, Any]]: ...
+    @overload
+    def iter_dicts(
+        self, native: Literal[False], strip_prefix: bool = True
+    ) -> Iterator[dict[str, Value]]: ...
+    def iter_dicts(
+        self, native: bool = True, strip_prefix: bool = True
+    ) -> Iterator[dict[str, Any]] | Iterator[dict[str, Value]]:
+        """
+        Iterate over rows as dictionaries.
+
+        Args:
+            native: Plain Python values (str, float, None for ?/., list, dict)
+                instead of Value objects
+            strip_prefix: Key rows by tag without common_prefix ("fract_x"),
+                falling back to full tags when there is no common prefix
+
+        Columns are converted once before iteration, so this is the fast
+        way to walk a large loop.
+
+        Example:
+            for atom in loop.iter_dicts():
+                print(atom["label"], atom["fract_x"])
+        """
+        ...
+
     def rows(self) -> list[list[Value]]:
         """
         Get all rows as lists of values.
@@ -208,6 +437,43 @@ class Loop:
         """
         ...
 
+    def to_pandas(self, strip_prefix: bool = False) -> pandas.DataFrame:
+        """
+        Convert to a pandas DataFrame with one column per tag.
+
+        Numeric columns become float64, with sus dropped and NaN for ? and
+        .; other columns hold Python objects, with None for ? and . .
+
+        Args:
+            strip_prefix: Name the columns without common_prefix, as
+                iter_dicts() does (fract_x rather than _atom_site_fract_x)
+
+        Raises:
+            ImportError: If pandas is not installed.
+
+        Example:
+            >>> frame = block.find_loop("_atom_site_label").to_pandas(strip_prefix=True)
+            >>> frame["fract_x"].mean()
+        """
+        ...
+
+    def partition_by(self, tag: str, keep_column: bool = True) -> dict[str, Loop]:
+        """
+        Split the rows into one loop per value of a column.
+
+        Args:
+            tag: The column whose values name the parts, e.g. '_diffrn_id'
+            keep_column: Whether the parts keep the `tag` column
+
+        Returns:
+            Loops keyed by the text of the value, in the order the values
+            first appear, each with its rows in their original order.
+
+        Raises:
+            KeyError: If the loop has no `tag` column.
+        """
+        ...
+
     def __len__(self) -> int:
         """Get the number of rows."""
         ...
@@ -234,6 +500,83 @@ class Loop:
         """Debug representation."""
         ...
 
+class Category:
+    """
+    One category of a block read as a table, from Block.category().
+
+    Data items of the category are repeated on every row, so mmCIF can be
+    queried the same way whether a category was written as items or as a
+    loop. Values a short loop does not have are missing. Attributes are the
+    parts of the tags after the category (Cartn_x of _atom_site.Cartn_x,
+    fract_x of _atom_site_fract_x), matched case-insensitively.
+
+    Example:
+        atoms = block.category("atom_site")
+        for row in atoms.rows():
+            print(row["label_atom_id"].text, row["Cartn_x"].numeric)
+    """
+
+    @property
+    def name(self) -> str:
+        """Category name, without the leading underscore."""
+        ...
+
+    @property
+    def tags(self) -> list[str]:
+        """Tags as spelled in the file: data items, then loop columns."""
+        ...
+
+    @property
+    def attributes(self) -> list[str]:
+        """Attributes of the columns, in the order of tags."""
+        ...
+
+    def __len__(self) -> int:
+        """Get the number of rows; 1 for a category of data items."""
+        ...
+
+    def get(self, row: int, attribute: str) -> Value | None:
+        """Get the value of an attribute in a row."""
+        ...
+
+    def column(self, attribute: str) -> list[Value] | None:
+        """
+        Get every value of an attribute, one per row.
+
+        Returns None if the category has no such attribute or a loop
+        holding it is short.
+        """
+        ...
+
+    def row(self, row: int) -> dict[str, Value] | None:
+        """Get a row as a dictionary mapping attributes to values."""
+        ...
+
+    def rows(self) -> list[dict[str, Value]]:
+        """Get every row as a dictionary, as from row()."""
+        ...
+
+    def to_loop(self) -> Loop:
+        """
+        Get the table as a loop with the full tags, data items repeated on
+        every row and ? where a short loop has no value.
+        """
+        ...
+
+    def to_pandas(self) -> pandas.DataFrame:
+        """
+        Convert to a pandas DataFrame with one column per attribute, as
+        Loop.to_pandas() does.
+
+        Raises:
+            ImportError: If pandas is not installed.
+        """
+        ...
+
+    def __repr__(self) -> str:
+        """Debug representation."""
+        ...
+
 class Frame:
     """
     Represents a save frame in a CIF file.
@@ -265,13 +608,18 @@ class Frame:
             key: Tag name (e.g., "_frame_category")
 
         Returns:
-            The value for the tag, or None if not found.
+            The value for the tag, the first value of a one-column loop of
+            that tag, or None if not found.
         """
         ...
 
+    def get_all(self, tag: str) -> list[Value]:
+        """Get every value of a tag, from the data item or any loop column."""
+        ...
+
     def items(self) -> dict[str, Value]:
         """
-        Get all data items as a dictionary.
+        Get all data items as a dictionary, in file order.
 
         Returns:
             Dictionary mapping tag names to values.
@@ -313,8 +661,47 @@ class Block:
 
     Data blocks are the primary organizational unit, containing items,
     loops, and save frames.
+
+    Blocks taken from a Document share it, so a change made through one
+    (repair_mixed_loops) is seen by the document and its other blocks.
+    Changes are serialized, and each method reads one consistent snapshot,
+    so threads never see a change half done.
+
+    Example:
+        >>> block["_cell_length_a"] = 5.431
+        >>> del block["_cell_measurement_temperature"]
     """
 
+    def __setitem__(self, tag: str, value: WritableValue) -> None:
+        """
+        Set a data item to a number, text, None for "?", or a list or dict
+        for CIF 2.0. A new item goes after the others.
+
+        Raises:
+            ValueError: If the tag is a loop column or not a data name.
+            TypeError: If the value has no CIF form.
+        """
+        ...
+
+    def __delitem__(self, tag: str) -> None:
+        """
+        Remove a data item.
+
+        Raises:
+            KeyError: If there is no such item.
+        """
+        ...
+
+    def add_loop(self, loop: Loop) -> None:
+        """
+        Add a copy of a loop after the others.
+
+        Raises:
+            ValueError: If a tag of the loop is already a data item or a
+                column of another loop.
+        """
+        ...
+
     @property
     def name(self) -> str:
         """Get the block name (from 'data_name' header)."""
@@ -322,7 +709,7 @@ class Block:
 
     @property
     def item_keys(self) -> list[str]:
-        """Get all item tag names in this block."""
+        """Get all item tag names in this block, in file order."""
         ...
 
     @property
@@ -343,161 +730,2630 @@ class Block:
             key: Tag name (e.g., "_cell_length_a")
 
         Returns:
-            The value for the tag, or None if not found.
+            The value for the tag, or None if not found. Loop columns,
+            including repeated items collapsed with
+            duplicate_tags="collapse", are not data items; use get_all.
         """
         ...
 
-    def items(self) -> dict[str, Value]:
+    def get_float(self, tag: str) -> float | None:
         """
-        Get all data items as a dictionary.
+        Get a data item as a float, dropping any su.
 
-        Returns:
-            Dictionary mapping tag names to values.
+        Returns None if the tag is absent or its value is ? or ., as do
+        get_int, get_str, and get_bool.
+
+        Raises:
+            ValueError: If the value is not a number.
         """
         ...
 
-    def get_loop(self, index: int) -> Loop | None:
+    def get_int(self, tag: str) -> int | None:
         """
-        Get a loop by index.
+        Get a data item as an int.
 
-        Args:
-            index: Loop index (0-based)
+        Raises:
+            ValueError: If the value is not a whole number.
+        """
+        ...
 
-        Returns:
-            The loop at the specified index, or None if out of bounds.
+    def get_str(self, tag: str) -> str | None:
+        """
+        Get a data item as a string.
+
+        Numbers are formatted again in their shortest form, so ``007`` gives
+        ``"7"`` and ``1.50`` gives ``"1.5"``; only a text field keeps digits
+        as written.
+
+        Raises:
+            ValueError: If the value is a list or table.
         """
         ...
 
-    def find_loop(self, tag: str) -> Loop | None:
+    def get_bool(self, tag: str) -> bool | None:
         """
-        Find the first loop containing a specific tag.
+        Get a data item as a bool, from yes/no, y/n, true/false, or 1/0.
+
+        Raises:
+            ValueError: For any other value.
+        """
+        ...
+
+    def get(self, tag: str, search_frames: bool = False) -> Value | None:
+        """
+        Get a data item, optionally searching save frames too.
 
         Args:
-            tag: Tag name to search for
+            tag: Tag name (e.g., "_scan_id")
+            search_frames: If the block lacks the tag, search its save frames
+                in file order; the first frame with it wins
 
         Returns:
-            The first loop containing the tag, or None if not found.
+            The value, whose source names the frame it came from, or None.
         """
         ...
 
-    def get_frame(self, index: int) -> Frame | None:
+    def walk(self) -> list[tuple[list[str], str | None, Value | Loop]]:
         """
-        Get a save frame by index.
+        Every data item and loop in the block and its save frames.
+
+        Entries come as (path, tag, value) tuples: the block's items and
+        loops first, then each frame's, with items before loops. path names
+        the frames containing the entry and is empty for the block itself.
+        Loops come as (path, None, loop).
+
+        Example:
+            for path, tag, value in block.walk():
+                print("/".join(path), tag, value)
+        """
+        ...
+
+    def get_all(self, tag: str) -> list[Value]:
+        """
+        Get every value of a tag, whether stored as a data item, repeated
+        items collapsed into a loop, or a loop column.
 
         Args:
-            index: Frame index (0-based)
+            tag: Tag name (e.g., "_publ_author_name")
 
         Returns:
-            The frame at the specified index, or None if out of bounds.
+            The data item's value, then the values of each loop column with
+            that tag, in order; empty if the tag is not in the block.
         """
         ...
 
-    def get_loop_tags(self) -> list[str]:
+    def items(self) -> dict[str, Value]:
         """
-        Get all loop tags from all loops in this block.
+        Get all data items as a dictionary, in file order.
 
         Returns:
-            List of all tag names used in loops.
+            Dictionary mapping tag names to values.
         """
         ...
 
-    def __str__(self) -> str:
-        """String representation."""
-        ...
+    def items_ordered(self) -> list[tuple[str, Value]]:
+        """
+        Get all data items as (tag, value) pairs in file order.
 
-    def __repr__(self) -> str:
-        """Debug representation."""
+        Example:
+            >>> [tag for tag, _ in block.items_ordered()][:2]
+            ['_chemical_name_common', '_cell_length_a']
+        """
         ...
 
-class Document:
-    """
-    Represents a complete CIF document (root container).
+    def __iter__(self) -> Iterator[str]:
+        """
+        Iterate over tags: data items in file order, then the columns of
+        each loop in order. Save frames are not included.
 
-    A document contains one or more data blocks. This is the entry point
-    for parsing CIF files.
+        Example:
+            >>> for tag in block:
+            ...     print(tag, block.get_all(tag))
+        """
+        ...
+
+    def get_loop(self, index: int) -> Loop | None:
+        """
+        Get a loop by index.
+
+        Args:
+            index: Loop index (0-based)
+
+        Returns:
+            The loop at the specified index, or None if out of bounds.
+        """
+        ...
+
+    def find_loop(self, tag: str) -> Loop | None:
+        """
+        Find the first loop containing a specific tag.
+
+        Args:
+            tag: Tag name to search for
+
+        Returns:
+            The first loop containing the tag, or None if not found.
+        """
+        ...
+
+    def get_frame(self, index: int) -> Frame | None:
+        """
+        Get a save frame by index.
+
+        Args:
+            index: Frame index (0-based)
+
+        Returns:
+            The frame at the specified index, or None if out of bounds.
+        """
+        ...
+
+    def get_loop_tags(self) -> list[str]:
+        """
+        Get all loop tags from all loops in this block.
+
+        Returns:
+            List of all tag names used in loops.
+        """
+        ...
+
+    def datasets(self, id_tag: str) -> dict[str, Block]:
+        """
+        Split a block holding several datasets by an id column.
+
+        Every loop with an `id_tag` column is split by its value, as by
+        `Loop.partition_by`. Each dataset's block has this block's name,
+        items, and frames, the loops without `id_tag`, and its own rows of
+        the loops with it.
+
+        Args:
+            id_tag: The column naming the dataset of each row, e.g. '_diffrn_id'
+
+        Returns:
+            Blocks keyed by dataset id, in the order the ids first appear;
+            empty if no loop has an `id_tag` column.
+        """
+        ...
+
+    def to_dict(
+        self,
+        values: Literal["native", "raw"] = "native",
+        include_loops: bool = True,
+        include_frames: bool = True,
+        loops: Literal["rows", "columns"] = "rows",
+    ) -> dict[str, Any]:
+        """
+        Convert the whole block to plain, JSON-serializable Python data.
+
+        The conversion happens in a single pass in Rust.
+
+        Args:
+            values: "native" maps ? and . to None; "raw" keeps them as strings,
+                where they cannot be told apart from quoted '?' and '.' text
+            include_loops: Include a "loops" list in the output
+            include_frames: Include a "frames" list (converted recursively)
+            loops: "rows" gives {"tags": [...], "rows": [[...]]} per loop;
+                "columns" gives {tag: [values]} per loop
+
+        Returns:
+            {"name": str, "items": {...}, "loops": [...], "frames": [...]}
+        """
+        ...
+
+    def publication(self) -> Publication:
+        """
+        Gather publication metadata from the _publ_* and _journal_* items.
+
+        Handles both looped and single-item _publ_author_name.
+        """
+        ...
+
+    def identifiers(self) -> Identifiers:
+        """
+        Gather database codes and DOIs.
+
+        Reads _database_code_* (or _database.code_*), _cod_database_code,
+        _journal_paper_doi, _database_dataset_doi, and the mmCIF
+        _database_2 and primary _citation rows, as items or loop columns.
+        """
+        ...
+
+    def experimental(self) -> Experimental:
+        """
+        Gather the crystal, temperature, absorption, and density items.
+
+        Reads _exptl_crystal_*, _exptl_absorpt_*, and
+        _diffrn_ambient_temperature in their core or mmCIF spellings.
+
+        Example:
+            >>> exptl = block.experimental()
+            >>> exptl.temperature
+            (150.0, 2.0)
+            >>> exptl.check()
+            []
+        """
+        ...
+
+    def measurement(self) -> Measurement:
+        """
+        Gather the standard reflections, scan, and instruments.
+
+        Reads _diffrn_standards_*, _diffrn_measurement_*, _diffrn_detector*,
+        _diffrn_radiation_monochromator, and _diffrn_reflns_number in their
+        core or mmCIF spellings; the decay is read from either
+        _diffrn_standards_decay_% or _diffrn_standards_decay_percent.
+
+        Example:
+            >>> measurement = block.measurement()
+            >>> measurement.standards_decay_percent
+            (12.5, None)
+            >>> [w["id"] for w in measurement.warnings()]
+            ['standards-decay']
+        """
+        ...
+
+    def refinement_model(self) -> RefinementModel:
+        """
+        Gather the refinement model: parameter, restraint, and constraint
+        counts, the weighting scheme, extinction, the Flack parameter, the
+        hydrogen treatment, and the rows of _restr_* loops.
+
+        Example:
+            >>> model = block.refinement_model()
+            >>> model.weight_a, model.weight_b
+            (0.0478, 0.2987)
+            >>> model.hydrogen_treatment
+            'constr'
+        """
+        ...
+
+    def repair_mixed_loops(self, categories: list[str] | None = None) -> list[LoopSplit]:
+        """
+        Split loops that mix tag categories (e.g. _atom_site_* and
+        _atom_site_aniso_*) into one loop per category, in place.
+
+        A label or id column is copied into each new loop under its
+        category (_atom_site_aniso_label), so the loops stay joinable.
+        Categories are found as by category_of().
+
+        Args:
+            categories: Category names to add to the built-in core CIF ones,
+                e.g. ["pd_meas", "pd_proc"]
+
+        Returns:
+            One entry per loop that was split
+        """
+        ...
+
+    def category(self, name: str, categories: list[str] | None = None) -> Category | None:
+        """
+        Read one category as a table merging its data items and loop
+        columns, e.g. block.category("atom_site").
+
+        Categories are found as by category_of(); save frames are not
+        searched.
+
+        Args:
+            name: Category name, with or without the leading underscore
+            categories: Category names to add to the built-in core CIF ones,
+                e.g. ["pd_meas", "pd_proc"]
+
+        Returns:
+            The category, or None if the block has no tags in it
+        """
+        ...
+
+    def category_to_pandas(
+        self, name: str, categories: list[str] | None = None
+    ) -> pandas.DataFrame | None:
+        """
+        Read one category as a pandas DataFrame with one column per
+        attribute, e.g. block.category_to_pandas("atom_site").
+
+        Shortcut for category(name, categories).to_pandas(); None if the
+        block has no tags in the category.
+
+        Raises:
+            ImportError: If pandas is not installed.
+        """
+        ...
+
+    def categories(self) -> list[str]:
+        """
+        Categories of the data items and loop columns, without the leading
+        underscore, in the order they first appear (items before loops).
+        """
+        ...
+
+    def unit_cell(self) -> UnitCell | None:
+        """
+        Unit cell from _cell_length_* and _cell_angle_* (or the mmCIF
+        _cell.length_a forms), with their sus.
+
+        Returns None unless all six parameters are numbers.
+        """
+        ...
+
+    def symmetry_operations(self) -> list[SymOp]:
+        """
+        Symmetry operations of _space_group_symop_operation_xyz (or
+        _symmetry_equiv_pos_as_xyz, or their mmCIF forms), in file order.
+
+        Operations that cannot be parsed are skipped; an empty list means
+        the block lists none.
+        """
+        ...
+
+    def space_group(self) -> SpaceGroup | None:
+        """
+        Space group named by _space_group_name_Hall,
+        _space_group_name_H-M_alt, or _space_group_IT_number (or their
+        older _symmetry_ and mmCIF forms), tried in that order.
+
+        Use its operations when symmetry_operations() is empty. The number
+        is only used when there is no Hermann-Mauguin symbol, since a symbol
+        missing from the table may name a non-standard setting. Returns None
+        if nothing resolves.
+        """
+        ...
+
+    def point_group(self) -> PointGroup | None:
+        """
+        Point group of the symmetry operations in
+        _space_group_symop_operation_xyz (or _symmetry_equiv_pos_as_xyz).
+
+        Conflicts with the cell metric or the declared space group number are
+        listed in the result's warnings. Returns None if there are no
+        operations or they do not form a point group.
+        """
+        ...
+
+    def atom_sites(self) -> list[AtomSite] | None:
+        """
+        Atom sites of the _atom_site loop (or mmCIF _atom_site.id), in file
+        order, with fractional coordinates.
+
+        Cartesian coordinates are converted with the cell; sites with neither
+        are left out. Returns None unless the block has a unit cell.
+        """
+        ...
+
+    def structure(self) -> Structure | None:
+        """
+        Cell, symmetry operations, and atom sites of this block, for
+        geometry such as site symmetries.
+
+        Returns None unless the block has a unit cell.
+        """
+        ...
+
+    def chain_map(self) -> ChainMap | None:
+        """
+        Residues of a PDBx block in label (label_asym_id, label_seq_id) and
+        author (auth_asym_id, auth_seq_id, pdbx_PDB_ins_code) numbering.
+
+        Read from _atom_site and, when present, _pdbx_poly_seq_scheme.
+        Returns None if the block has neither.
+        """
+        ...
+
+    def select(self, auth_chain: str, auth_seq: tuple[int, int] | None = None) -> Loop | None:
+        """
+        The _atom_site rows of an author chain and residue range.
+
+        Args:
+            auth_chain: auth_asym_id of the chain, e.g. 'A'
+            auth_seq: First and last auth_seq_id, inclusive; all residues
+                if not given. Residues with insertion codes are selected by
+                their number.
+
+        Returns:
+            A loop with all the _atom_site columns, or None if the block has
+            no _atom_site loop. Without author ids, label ids are matched.
+        """
+        ...
+
+    def reflections(self) -> ReflectionData | None:
+        """
+        Measured reflections of this block.
+
+        Reads the unmerged _diffrn_refln loop (intensity_net with
+        intensity_u or intensity_sigma) if present, otherwise _refln
+        (intensity_meas or F_squared_meas with its sigma), in core or mmCIF
+        spelling. Rows with non-integer indices or a non-numeric intensity
+        are skipped. Returns None if there is no such loop.
+
+        Example:
+            >>> merged = block.reflections().merge(block.point_group())
+            >>> merged.r_merge
+            0.1176
+        """
+        ...
+
+    def orientation_matrix(self) -> list[list[float]] | None:
+        """
+        Orientation matrix UB in 1/Å, as three rows.
+
+        Element [i][j] is _diffrn_orient_matrix_UB_{i+1}{j+1}, so that
+        UB @ (h, k, l) is the reciprocal-lattice vector. Returns None unless
+        all nine elements are numbers.
+        """
+        ...
+
+    def goniometer_axes(self) -> list[GoniometerAxis]:
+        """
+        Goniometer axes from the imgCIF _axis loop, in order.
+
+        If there is a _diffrn_measurement_axis loop, only the axes it names
+        are returned; otherwise those with _axis.equipment "goniometer".
+        """
+        ...
+
+    def check_ub(self, tolerance: float = 0.01) -> UbCheck | None:
+        """
+        Check the orientation matrix against the unit cell.
+
+        |det(UB)| should equal 1/V; tolerance is the largest relative
+        difference accepted. Returns None without both a matrix and a cell.
+
+        Example:
+            >>> check = block.check_ub()
+            >>> check["consistent"], check["discrepancy"]
+            (True, -1.7e-05)
+        """
+        ...
+
+    def sanity_check(self) -> list[SanityIssue]:
+        """
+        Check common quantities against fixed physical limits.
+
+        Covers cell lengths and angles, cell volume, occupancies, fractional
+        coordinates, U(iso), the wavelength (with a separate range for
+        electrons), Z, the formula weight against the formula sum, and the
+        values of enumerated tags (id "vocabulary", see
+        check_vocabularies()). An empty list means nothing looked wrong.
+
+        Example:
+            >>> for issue in block.sanity_check():
+            ...     print(issue["tag"], issue["value"], issue["rule"])
+            _cell_length_a 1234.5 cell length must be in (0.5, 1000) Å
+        """
+        ...
+
+    def validate(self, stack: DictionaryStack) -> list[ValidationIssue]:
+        """Check the block's values against a dictionary stack; see
+        Document.validate()."""
+        ...
+
+    def provenance(
+        self, *, prefixes: Sequence[str] | None = None, n_sigma: float = 1.0
+    ) -> list[Provenance]:
+        """
+        Original values recorded by aggregators, each with the current value.
+
+        Finds items such as _cod_original_cell_volume under `prefixes`
+        (by default _cod_original_ and _iucr_original_), and pairs each with
+        the tag it stands for. Numbers differ when more than `n_sigma` sus
+        apart, anything else when the text differs.
+
+        Example:
+            >>> for p in block.provenance():
+            ...     print(p["tag"], p["current"], p["original"], p["differs"])
+            _symmetry_space_group_name_H-M P 1 21/c 1 P 21/c True
+        """
+        ...
+
+    def restore_original(
+        self, tag: str, *, prefixes: Sequence[str] | None = None
+    ) -> Provenance:
+        """
+        Put back the original value of a tag, such as "_cell_volume".
+
+        The original replaces the current value, its own item is removed,
+        and a line is added to _audit_update_record. Returns the values as
+        they were.
+
+        Raises:
+            ValueError: If no original value is recorded for the tag.
+        """
+        ...
+
+    def check_vocabularies(self) -> list[VocabIssue]:
+        """
+        Values of enumerated core tags that are not in their vocabulary.
+
+        Covers _atom_site_adp_type, _atom_site_calc_flag,
+        _refine_ls_hydrogen_treatment and the other _refine_ls codes,
+        _exptl_absorpt_correction_type, the _exptl_crystal_colour_*
+        descriptors, centring types, and crystal systems, in core or mmCIF
+        spellings. Case is ignored, so "UANI" passes.
+
+        Example:
+            >>> block.check_vocabularies()
+            [{'tag': '_atom_site_adp_type', 'row': 1, 'value': 'Uanl', 'suggestion': 'Uani', 'allowed': [...]}]
+        """
+        ...
+
+    def check_schema(
+        self,
+        schema: dict[str, Literal["float", "float_su", "int", "date", "bool", "text"] | list[str]],
+    ) -> CoercionReport:
+        """
+        Check that the values of each tag convert to the type the schema gives.
+
+        Every value is tried, not just the first, so the report lists each
+        missing tag, each tag whose values are all ? or ., and each value that
+        does not convert, with its loop row. Tags match without regard to
+        case. "float_su" accepts numbers with standard uncertainties, such
+        as 5.431(2); a list gives the allowed codes of an enumeration.
+
+        Raises:
+            ValueError: If a type name is not one of those above
+
+        Example:
+            >>> report = block.check_schema({
+            ...     "_cell_length_a": "float_su",
+            ...     "_space_group_IT_number": "int",
+            ...     "_atom_site_adp_type": ["Uani", "Uiso"],
+            ... })
+            >>> assert report.ok, str(report)
+        """
+        ...
+
+    def to_core_cif(self, *, strict: bool = False) -> Block:
+        """
+        Translate an mmCIF structure (cell, symmetry, atom sites, ADPs,
+        formula) into core CIF tags.
+
+        Cartesian coordinates are converted to fractional ones with the cell.
+
+        Args:
+            strict: Raise ValueError instead of warning when tags could not
+                be translated
+
+        Warns:
+            UserWarning: Listing the tags that could not be translated
+        """
+        ...
+
+    def to_mmcif(self, *, strict: bool = False) -> Block:
+        """
+        Translate a core CIF structure (cell, symmetry, atom sites, ADPs,
+        formula) into mmCIF tags.
+
+        Fractional coordinates are converted to Cartesian ones with the cell.
+
+        Args:
+            strict: Raise ValueError instead of warning when tags could not
+                be translated
+
+        Warns:
+            UserWarning: Listing the tags that could not be translated
+        """
+        ...
+
+    def __str__(self) -> str:
+        """String representation."""
+        ...
+
+    def __repr__(self) -> str:
+        """Debug representation."""
+        ...
+
+class AtomSite(TypedDict):
+    """An atom site from Block.atom_sites()."""
+
+    label: str
+    type_symbol: str | None
+    fract: list[float] | None
+    """Fractional coordinates x, y, z; None for a site given only in
+    Cartesian coordinates in a block without a unit cell."""
+    cartn: list[float] | None
+    """Cartesian coordinates x, y, z in Å, if given."""
+    fract_su: list[float]
+    """Sus of the coordinates, 0 when not given."""
+    occupancy: float
+    """1 when not given."""
+    u_iso: float | None
+    """U(iso) or U(eq) in Å², converted from B if only that is given."""
+    u_aniso: list[float] | None
+    """U11, U22, U33, U12, U13, U23 in Å² from the _atom_site_aniso loop,
+    converted from B if only that is given."""
+    adp_type: str | None
+    """_atom_site_adp_type in the dictionary's spelling ("Uani" for "UANI"),
+    or as written if it is not in the vocabulary."""
+    disorder_group: str | None
+    """_atom_site_disorder_group, the disorder part of the site."""
+
+class Tie(TypedDict):
+    """A group of sites with identical values, from Structure.detect_ties()."""
+
+    quantity: Literal["coordinates", "u_iso", "u_aniso"]
+    """What the sites share: EXYZ-like coordinates or EADP-like ADPs."""
+    labels: list[str]
+    """Labels of the sites, in file order."""
+    symmetry_required: bool
+    """True for sites on the same special position with no free coordinate,
+    which coincide by symmetry rather than by a constraint."""
+    across_parts: bool
+    """Whether the sites are in more than one disorder group."""
+    documented: bool | None
+    """Whether _refine_special_details names every site; None without it."""
+
+class GoniometerAxis(TypedDict):
+    """A goniometer axis from Block.goniometer_axes()."""
+
+    id: str
+    type: str | None
+    """"rotation", "translation", or "general"."""
+    vector: list[float] | None
+    """Unit vector along the axis in the laboratory frame."""
+    depends_on: str | None
+    """The axis this one is mounted on."""
+
+class UbCheck(TypedDict):
+    """Result of Block.check_ub()."""
+
+    determinant: float
+    """|det(UB)| in 1/Å³."""
+    reciprocal_volume: float
+    """1/V of the unit cell in 1/Å³."""
+    discrepancy: float
+    """Relative difference, |det(UB)|·V - 1."""
+    sigmas: float | None
+    """The difference in units of the su of 1/V, or None without cell sus."""
+    consistent: bool
+
+class SiteDifference(TypedDict):
+    """One matched atom in StructureComparison.sites."""
+
+    label_a: str
+    label_b: str
+    displacement: tuple[float, float, float]
+    """Cartesian shift from the first position to the second, in Å."""
+    distance: float
+    """Length of the displacement, in Å."""
+    u_iso: float | None
+    """Change in U(iso or equiv), or None unless both structures give one."""
+    occupancy: float
+    """Change in occupancy."""
+
+class ParsedElement(TypedDict):
+    """Result of parse_element()."""
+
+    element: str
+    """Element symbol; deuterium and tritium are "H"."""
+    atomic_number: int
+    charge: int | None
+    isotope: int | None
+    """Mass number for "D" (2) and "T" (3), otherwise None."""
+
+class SanityIssue(TypedDict):
+    """A suspect value found by Block.sanity_check()."""
+
+    id: str
+    """Short name of the rule, such as "cell-length"; stable across releases."""
+    tag: str
+    row: int | None
+    """Loop row, or None for a data item."""
+    value: str
+    """The value as written in CIF."""
+    rule: str
+    """The rule it breaks."""
+
+class VocabIssue(TypedDict):
+    """A value outside its vocabulary, from Block.check_vocabularies()."""
+
+    tag: str
+    row: int | None
+    """Loop row, or None for a data item."""
+    value: str
+    """The value as written in CIF."""
+    suggestion: str | None
+    """The nearest allowed value, if one is at most two edits away."""
+    allowed: list[str]
+
+class Definition(TypedDict):
+    """An item definition from a DDLm dictionary."""
+
+    id: str
+    """_definition.id, such as "_cell.length_a"."""
+    dictionary: str
+    """Title of the dictionary the definition is from."""
+    frame: str
+    """Name of the save frame holding the definition."""
+    category: str | None
+    type: str | None
+    """_type.contents, such as "Real", "Integer", or "Code"."""
+    container: str | None
+    units: str | None
+    enumeration: list[str]
+    """Allowed values, or empty if not restricted."""
+    range: tuple[float | None, float | None] | None
+    """Lowest and highest allowed values; None for an open end."""
+    description: str | None
+    """_description.text, trimmed."""
+    aliases: list[str]
+
+class ValidationIssue(TypedDict):
+    """A value that breaks its definition, from Document.validate()."""
+
+    block: str
+    frame: str | None
+    tag: str
+    row: int | None
+    """Loop row, or None for a data item."""
+    value: str
+    kind: Literal["type", "enumeration", "range"]
+    message: str
+    """What the definition requires, such as "should be one of d, calc"."""
+    dictionary: str
+    """Title of the dictionary whose definition was used."""
+    definition: str
+    """Save frame of that definition."""
+    category: str | None
+
+class Provenance(TypedDict):
+    """An original value and the current one, from Block.provenance()."""
+
+    original_tag: str
+    """The tag holding the original, such as _cod_original_cell_volume."""
+    tag: str
+    original: Any
+    current: Any
+    """The current value, or None if the block has no such item."""
+    differs: bool
+    sigmas: float | None
+    """Difference in sus, if both values are numbers."""
+
+class QueryHit(TypedDict):
+    """A value picked by Document.query()."""
+
+    block: str
+    tag: str
+    row: int | None
+    """Loop row, or None for a data item."""
+    value: Any
+
+class SchemaMismatch(TypedDict):
+    """A value that Block.check_schema() could not convert."""
+
+    tag: str
+    row: int | None
+    """Loop row, or None for a data item."""
+    token: str
+    """The value as written in CIF."""
+    expected: str
+    """Description of the expected type."""
+
+class TagMismatch(TypedDict):
+    row: int | None
+    token: str
+
+class TagCheck(TypedDict):
+    """What Block.check_schema() found for one tag."""
+
+    status: Literal["ok", "missing", "unknown", "mismatch"]
+    expected: str
+    unknown: int
+    """Number of ? and . values."""
+    mismatches: list[TagMismatch]
+
+class LoopSplit(TypedDict):
+    """A loop split by Block.repair_mixed_loops()."""
+
+    tags: list[str]
+    """Tags of the original loop."""
+    categories: list[str]
+    """Categories of the new loops, in order."""
+    key: str | None
+    """Key column copied into each new loop, if any."""
+
+class Author:
+    """An author, with the name split using the CIF "Family, Given" convention."""
+
+    @property
+    def name(self) -> str:
+        """Name as written, with footnote markers removed."""
+        ...
+
+    @property
+    def family(self) -> str:
+        """Family name."""
+        ...
+
+    @property
+    def given(self) -> str | None:
+        """Given names or initials."""
+        ...
+
+    @property
+    def address(self) -> str | None:
+        """Postal address."""
+        ...
+
+    @property
+    def email(self) -> str | None:
+        """Email address."""
+        ...
+
+    @property
+    def orcid(self) -> str | None:
+        """ORCID identifier."""
+        ...
+
+class Publication:
+    """
+    Publication metadata from a data block.
+
+    Example:
+        publ = block.publication()
+        for author in publ.authors:
+            print(author.family, author.given)
+        item = publ.to_csl_json()
+    """
+
+    @property
+    def title(self) -> str | None:
+        """_publ_section_title with line folding removed and whitespace collapsed."""
+        ...
+
+    @property
+    def authors(self) -> list[Author]:
+        """Authors in file order."""
+        ...
+
+    @property
+    def contact_author(self) -> Author | None:
+        """Contact author (_publ_contact_author_*)."""
+        ...
+
+    @property
+    def journal(self) -> str | None:
+        """Full journal name (_journal_name_full)."""
+        ...
+
+    @property
+    def volume(self) -> str | None:
+        """Journal volume."""
+        ...
+
+    @property
+    def issue(self) -> str | None:
+        """Journal issue."""
+        ...
+
+    @property
+    def year(self) -> int | None:
+        """Publication year."""
+        ...
+
+    @property
+    def pages(self) -> str | None:
+        """Page range as "first-last", or the first page alone."""
+        ...
+
+    @property
+    def doi(self) -> str | None:
+        """DOI (_journal_paper_doi)."""
+        ...
+
+    def to_csl_json(self) -> dict[str, Any]:
+        """
+        Convert to a Citation Style Language item (CSL-JSON).
+
+        The item uses the block name as its id and type "article-journal";
+        fields absent from the CIF are omitted.
+        """
+        ...
+
+class Identifiers:
+    """
+    Database codes and DOIs from a data block.
+
+    Absent codes are None; when a code is given more than once, the first
+    is kept. Codes written as bare numbers lose leading zeros.
+
+    Example:
+        ids = block.identifiers()
+        if ids.csd:
+            print("CSD refcode", ids.csd)
+    """
+
+    @property
+    def csd(self) -> str | None:
+        """Cambridge Structural Database refcode (_database_code_CSD)."""
+        ...
+
+    @property
+    def icsd(self) -> str | None:
+        """ICSD collection code (_database_code_ICSD)."""
+        ...
+
+    @property
+    def cod(self) -> str | None:
+        """Crystallography Open Database code (_cod_database_code)."""
+        ...
+
+    @property
+    def ccdc_deposition(self) -> str | None:
+        """CCDC deposition number as written (_database_code_depnum_ccdc_archive)."""
+        ...
+
+    @property
+    def pdb(self) -> str | None:
+        """Protein Data Bank code (the PDB row of _database_2)."""
+        ...
+
+    @property
+    def doi(self) -> str | None:
+        """DOI of the paper (_journal_paper_doi or the primary citation)."""
+        ...
+
+    @property
+    def dataset_doi(self) -> str | None:
+        """DOI of the dataset (_database_dataset_doi or _database_2.pdbx_DOI)."""
+        ...
+
+    @property
+    def others(self) -> list[tuple[str, str]]:
+        """Other codes as (tag or database name, code) pairs."""
+        ...
+
+    def to_dict(self) -> dict[str, str]:
+        """Identifiers that are present, keyed by kind name."""
+        ...
+
+    def __bool__(self) -> bool:
+        """Whether any identifier was found."""
+        ...
+
+class Experimental:
+    """
+    Experimental conditions from a data block.
+
+    Numbers are (value, su) tuples, with su None when not given; absent
+    values and ?/. are None.
+    """
+
+    @property
+    def colour(self) -> str | None:
+        """Crystal colour (_exptl_crystal_colour)."""
+        ...
+
+    @property
+    def habit(self) -> str | None:
+        """Crystal habit (_exptl_crystal_description)."""
+        ...
+
+    @property
+    def size_min(self) -> tuple[float, float | None] | None:
+        """Smallest crystal dimension in mm (_exptl_crystal_size_min)."""
+        ...
+
+    @property
+    def size_mid(self) -> tuple[float, float | None] | None:
+        """Middle crystal dimension in mm (_exptl_crystal_size_mid)."""
+        ...
+
+    @property
+    def size_max(self) -> tuple[float, float | None] | None:
+        """Largest crystal dimension in mm (_exptl_crystal_size_max)."""
+        ...
+
+    @property
+    def crystal_volume(self) -> float | None:
+        """Volume of the crystal's bounding box in mm³, if all three sizes are given."""
+        ...
+
+    @property
+    def temperature(self) -> tuple[float, float | None] | None:
+        """Temperature in K (_diffrn_ambient_temperature)."""
+        ...
+
+    @property
+    def absorption_mu(self) -> tuple[float, float | None] | None:
+        """Absorption coefficient in mm⁻¹ (_exptl_absorpt_coefficient_mu)."""
+        ...
+
+    @property
+    def absorption_correction(self) -> str | None:
+        """Absorption correction type (_exptl_absorpt_correction_type)."""
+        ...
+
+    @property
+    def t_min(self) -> tuple[float, float | None] | None:
+        """Minimum transmission (_exptl_absorpt_correction_T_min)."""
+        ...
+
+    @property
+    def t_max(self) -> tuple[float, float | None] | None:
+        """Maximum transmission (_exptl_absorpt_correction_T_max)."""
+        ...
+
+    @property
+    def density_measured(self) -> tuple[float, float | None] | None:
+        """Measured density in g/cm³ (_exptl_crystal_density_meas)."""
+        ...
+
+    @property
+    def density_calculated(self) -> tuple[float, float | None] | None:
+        """Reported density in g/cm³ (_exptl_crystal_density_diffrn)."""
+        ...
+
+    @property
+    def density_from_formula(self) -> float | None:
+        """Density in g/cm³ from the formula weight, Z, and cell volume."""
+        ...
+
+    def check(self) -> list[SanityIssue]:
+        """
+        Values that disagree with each other.
+
+        The reported density must be within 1% of density_from_formula, and
+        T_min must not exceed T_max.
+        """
+        ...
+
+class Measurement:
+    """
+    How the intensities of a data block were measured.
+
+    Numbers are (value, su) tuples, with su None when not given; absent
+    values and ?/. are None.
+    """
+
+    @property
+    def standards_number(self) -> int | None:
+        """Number of standard reflections (_diffrn_standards_number)."""
+        ...
+
+    @property
+    def standards_interval_count(self) -> int | None:
+        """Reflections between standards (_diffrn_standards_interval_count)."""
+        ...
+
+    @property
+    def standards_interval_time(self) -> tuple[float, float | None] | None:
+        """Minutes between standards (_diffrn_standards_interval_time)."""
+        ...
+
+    @property
+    def standards_decay_percent(self) -> tuple[float, float | None] | None:
+        """Decay of the standards in percent (_diffrn_standards_decay_%)."""
+        ...
+
+    @property
+    def scan_method(self) -> str | None:
+        """Scan method (_diffrn_measurement_method)."""
+        ...
+
+    @property
+    def scan_width(self) -> tuple[float, float | None] | None:
+        """Scan width in degrees (_diffrn_measurement_scan_width)."""
+        ...
+
+    @property
+    def scan_time(self) -> tuple[float, float | None] | None:
+        """Seconds per reflection or frame (_diffrn_measurement_scan_time)."""
+        ...
+
+    @property
+    def details(self) -> str | None:
+        """Free text about the scans (_diffrn_measurement_details)."""
+        ...
+
+    @property
+    def device(self) -> str | None:
+        """Diffractometer (_diffrn_measurement_device)."""
+        ...
+
+    @property
+    def device_type(self) -> str | None:
+        """Make and model of the diffractometer (_diffrn_measurement_device_type)."""
+        ...
+
+    @property
+    def detector(self) -> str | None:
+        """Detector (_diffrn_detector)."""
+        ...
+
+    @property
+    def detector_type(self) -> str | None:
+        """Make and model of the detector (_diffrn_detector_type)."""
+        ...
+
+    @property
+    def monochromator(self) -> str | None:
+        """Monochromator (_diffrn_radiation_monochromator)."""
+        ...
+
+    @property
+    def reflections_measured(self) -> int | None:
+        """Reflections measured (_diffrn_reflns_number)."""
+        ...
+
+    @property
+    def total_exposure_estimate(self) -> float | None:
+        """
+        Estimated time spent measuring in seconds, for serial diffractometers.
+
+        The scan time for each reflection, plus for each standard every
+        standards_interval_count reflections; None without a scan time and
+        reflection count.
+        """
+        ...
+
+    def warnings(self, decay_threshold: float = 10.0) -> list[SanityIssue]:
+        """
+        Signs of a poor measurement.
+
+        The standards are flagged (id "standards-decay") when they decayed
+        by more than decay_threshold percent.
+        """
+        ...
+
+class Restraint(TypedDict):
+    """One row of a _restr_distance, _restr_angle, or _restr_torsion loop."""
+
+    kind: Literal["distance", "angle", "torsion"]
+    atoms: list[str]
+    target: tuple[float, float | None] | None
+    difference: float | None
+
+class RefinementModel:
+    """
+    Refinement model of a data block.
+
+    Numbers are (value, su) tuples, with su None when not given; absent
+    values and ?/. are None.
+    """
+
+    @property
+    def parameters(self) -> int | None:
+        """Number of refined parameters (_refine_ls_number_parameters)."""
+        ...
+
+    @property
+    def restraints(self) -> int | None:
+        """Number of restraints (_refine_ls_number_restraints)."""
+        ...
+
+    @property
+    def constraints(self) -> int | None:
+        """Number of constraints (_refine_ls_number_constraints)."""
+        ...
+
+    @property
+    def weighting_scheme(self) -> str | None:
+        """Weighting scheme, such as 'calc' (_refine_ls_weighting_scheme)."""
+        ...
+
+    @property
+    def weighting_details(self) -> str | None:
+        """Weighting scheme as written (_refine_ls_weighting_details)."""
+        ...
+
+    @property
+    def weight_a(self) -> float | None:
+        """Coefficient a of a SHELXL scheme w=1/[σ²(Fo²)+(aP)²+bP]."""
+        ...
+
+    @property
+    def weight_b(self) -> float | None:
+        """Coefficient b of a SHELXL scheme w=1/[σ²(Fo²)+(aP)²+bP]."""
+        ...
+
+    @property
+    def extinction_method(self) -> str | None:
+        """Extinction correction method (_refine_ls_extinction_method)."""
+        ...
+
+    @property
+    def extinction_coef(self) -> tuple[float, float | None] | None:
+        """Extinction coefficient (_refine_ls_extinction_coef)."""
+        ...
+
+    @property
+    def flack(self) -> tuple[float, float | None] | None:
+        """Flack parameter (_refine_ls_abs_structure_Flack)."""
+        ...
+
+    @property
+    def hydrogen_treatment(
+        self,
+    ) -> Literal["refall", "refxyz", "refU", "noref", "constr", "mixed", "undef"] | str | None:
+        """Hydrogen treatment code (_refine_ls_hydrogen_treatment)."""
+        ...
+
+    @property
+    def special_details(self) -> str | None:
+        """Notes on the refinement (_refine_special_details)."""
+        ...
+
+    @property
+    def restraint_list(self) -> list[Restraint]:
+        """Rows of the _restr_distance, _restr_angle, and _restr_torsion loops."""
+        ...
+
+class Structure:
+    """
+    A crystal structure: the cell, symmetry operations, and atom sites.
+
+    Example:
+        structure = block.structure()
+        for site in structure.site_symmetries():
+            if site.is_special:
+                print(site.label, site.multiplicity, site.symbol)
+    """
+
+    @property
+    def cell(self) -> UnitCell:
+        """The unit cell."""
+        ...
+
+    @property
+    def symmetry_count(self) -> int:
+        """Number of symmetry operations (1 if the block lists none)."""
+        ...
+
+    @property
+    def sites(self) -> list[AtomSite]:
+        """Atom sites in file order, as from Block.atom_sites()."""
+        ...
+
+    def site_symmetries(self, tolerance: float = 0.01) -> list[SiteSymmetry]:
+        """
+        Site symmetry and multiplicity of every atom site, in file order.
+
+        An operation stabilizes a site if it maps it to within tolerance Å
+        of itself, allowing lattice translations; the multiplicity is the
+        number of operations divided by the number that stabilize the site.
+
+        Args:
+            tolerance: Distance in Å within which an image counts as the site
+        """
+        ...
+
+    def detect_ties(self, tolerance: float = 1e-9) -> list[Tie]:
+        """
+        Groups of differently labelled sites whose coordinates, U_iso, or
+        anisotropic tensors are identical, as SHELXL's EXYZ and EADP
+        constraints leave them.
+
+        Values and their sus must agree within tolerance; values without an
+        su were not refined and are skipped, except coordinates fixed by
+        symmetry, which are reported with symmetry_required=True.
+
+        Args:
+            tolerance: Largest difference between tied values, and their sus
+
+        Example:
+            >>> for tie in block.structure().detect_ties():
+            ...     if not tie["symmetry_required"] and tie["documented"] is False:
+            ...         print(tie["quantity"], tie["labels"])
+        """
+        ...
+
+    @property
+    def reduced_to_p1(self) -> bool:
+        """Whether supercell() dropped the symmetry operations of the original."""
+        ...
+
+    def wrap_coordinates(self) -> Structure:
+        """A copy with every site moved into the cell, coordinates in [0, 1)."""
+        ...
+
+    def shift_origin(self, shift: tuple[float, float, float]) -> Structure:
+        """
+        A copy with shift added to the fractional coordinates of every site.
+
+        The symmetry operations are rewritten for the new origin, so they
+        still map the sites onto each other.
+        """
+        ...
+
+    def supercell(self, na: int, nb: int, nc: int) -> Structure:
+        """
+        The structure repeated na × nb × nc times, in P1.
+
+        Every atom of the cell is generated from the symmetry operations
+        first. Copies are labelled "<label>_<n>"; occupancies and
+        displacement parameters carry over.
+
+        Raises:
+            ValueError: If na, nb, or nc is zero
+
+        Example:
+            xyz = block.structure().supercell(2, 2, 1).to_xyz()
+        """
+        ...
+
+    def to_xyz(self) -> str:
+        """The sites as extended XYZ, with Cartesian coordinates in Å."""
+        ...
+
+class SiteSymmetry:
+    """
+    Site symmetry of one atom site, from Structure.site_symmetries().
+
+    The multiplicity is checked against _atom_site_site_symmetry_multiplicity
+    when the file gives it; Block.sanity_check() reports disagreements.
+    """
+
+    @property
+    def label(self) -> str:
+        """Label of the site."""
+        ...
+
+    @property
+    def multiplicity(self) -> int:
+        """Number of equivalent positions in the cell (Wyckoff multiplicity)."""
+        ...
+
+    @property
+    def is_special(self) -> bool:
+        """Whether an operation other than the identity maps the site onto itself."""
+        ...
+
+    @property
+    def stabilizer_order(self) -> int:
+        """Number of operations that map the site onto itself."""
+        ...
+
+    @property
+    def symbol(self) -> str | None:
+        """
+        Point group symbol of the site symmetry, such as "mm2", without the
+        orientation of the International Tables' oriented symbols.
+
+        None if the operations found do not form a group, which means the
+        tolerance is too loose.
+        """
+        ...
+
+    @property
+    def declared_multiplicity(self) -> int | None:
+        """_atom_site_site_symmetry_multiplicity as given in the file."""
+        ...
+
+    @property
+    def mismatch(self) -> tuple[int, int] | None:
+        """The declared and computed multiplicity, if they differ."""
+        ...
+
+class ChainMap:
+    """
+    Residues of a PDBx block in label and author numbering.
+
+    Example:
+        chains = block.chain_map()
+        chains.auth_to_label("H", 52, "A")  # ('A', 4)
+        chains.label_to_auth("D")           # [('H', 401, None), ...]
+    """
+
+    def auth_to_label(
+        self, chain: str, seq: int, ins_code: str | None = None
+    ) -> tuple[str, int | None] | None:
+        """
+        (label_asym_id, label_seq_id) of an author residue, or None.
+
+        label_seq_id is None for ligands and waters.
+        """
+        ...
+
+    def label_to_auth(self, asym: str, seq: int | None = None) -> list[tuple[str, int, str | None]]:
+        """
+        (auth_asym_id, auth_seq_id, ins_code) of the author residues with
+        these label ids.
+
+        A polymer residue has one; seq=None gives every ligand or water
+        residue of the entity instance, in file order.
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of residues."""
+        ...
+
+class SymOp:
+    """
+    A symmetry operation: a rotation in the basis of the cell, then a
+    translation in fractions of the cell edges.
+
+    Example:
+        op = SymOp("-x, y+1/2, -z+1/2")
+        op.apply([0.1, 0.2, 0.3])  # [-0.1, 0.7, 0.2]
+        str(op.compose(op))        # "x,y+1,z"
+    """
+
+    def __init__(self, xyz: str) -> None:
+        """
+        Parse an operation in x,y,z notation.
+
+        Raises:
+            ValueError: If it cannot be parsed.
+        """
+        ...
+
+    @property
+    def rotation(self) -> list[list[int]]:
+        """Rotation matrix as a 3×3 nested list, as rows."""
+        ...
+
+    @property
+    def translation(self) -> list[float]:
+        """Translation in fractions of the cell edges."""
+        ...
+
+    def apply(self, frac: Sequence[float]) -> list[float]:
+        """Apply the operation to fractional coordinates."""
+        ...
+
+    def compose(self, other: SymOp) -> SymOp:
+        """The operation applying other first and then this one; translations are not reduced into the cell."""
+        ...
+
+    def __eq__(self, other: object) -> bool: ...
+
+class SpaceGroup:
+    """
+    A space group and all of its symmetry operations, from a Hall symbol,
+    a Hermann-Mauguin symbol, or an International Tables number.
+
+    The bundled table covers the 230 groups in their standard settings,
+    both origin choices, hexagonal and rhombohedral axes, and the other
+    monoclinic cell choices and unique axis c.
+
+    Example:
+        group = SpaceGroup.from_hermann_mauguin("P 21/n")
+        group.number                         # 14
+        group.hall                           # "-P 2yn"
+        [str(op) for op in group.operations] # ["x,y,z", "-x,-y,-z", ...]
+    """
+
+    @staticmethod
+    def from_hall(symbol: str) -> SpaceGroup | None:
+        """
+        Generate a space group from any Hall symbol, such as "-P 2ybc",
+        "P 31 2c (0 0 1)", or Jana2006's "-I 4bd;2ab;3".
+
+        Returns None if the symbol cannot be read.
+        """
+        ...
+
+    @staticmethod
+    def from_hermann_mauguin(symbol: str) -> SpaceGroup | None:
+        """
+        Look up a Hermann-Mauguin symbol, such as "P 21/c", "P 1 21/n 1",
+        "Fd-3m:2", or "R -3 :R".
+
+        Spaces and case are ignored. Without a setting, origin choice 1 and
+        hexagonal axes are used. Returns None for symbols not in the table,
+        including orthorhombic groups in non-standard settings.
+        """
+        ...
+
+    @staticmethod
+    def from_number(number: int) -> SpaceGroup | None:
+        """Standard setting of a space group number (origin choice 1, hexagonal axes), or None outside 1-230."""
+        ...
+
+    @property
+    def number(self) -> int | None:
+        """International Tables number, or None for a Hall symbol not in the table."""
+        ...
+
+    @property
+    def hermann_mauguin(self) -> str | None:
+        """Hermann-Mauguin symbol with its setting, such as "F d -3 m :2"."""
+        ...
+
+    @property
+    def hall(self) -> str:
+        """Hall symbol the operations were generated from."""
+        ...
+
+    @property
+    def operations(self) -> list[SymOp]:
+        """All operations, identity first, then repeated with each centring translation; translations are in [0, 1)."""
+        ...
+
+    def __len__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+
+class PointGroup:
+    """
+    Crystallographic point group derived from symmetry operations.
+
+    Example:
+        group = block.point_group()
+        print(group.symbol, group.laue_class, group.crystal_system)
+        if group.is_chiral:
+            print("structure may be chiral")
+    """
+
+    @property
+    def symbol(self) -> str:
+        """Hermann-Mauguin symbol, such as "2/m" or "-42m"."""
+        ...
+
+    @property
+    def order(self) -> int:
+        """Number of distinct rotations."""
+        ...
+
+    @property
+    def laue_class(self) -> str:
+        """Laue class, such as "mmm"."""
+        ...
+
+    @property
+    def crystal_system(self) -> Literal[
+        "triclinic", "monoclinic", "orthorhombic", "tetragonal", "trigonal", "hexagonal", "cubic"
+    ]:
+        """Crystal system."""
+        ...
+
+    @property
+    def is_centrosymmetric(self) -> bool:
+        """Whether the group contains the inversion."""
+        ...
+
+    @property
+    def is_chiral(self) -> bool:
+        """Whether the group has only proper rotations (a Sohncke group)."""
+        ...
+
+    @property
+    def is_polar(self) -> bool:
+        """Whether the group is one of the ten polar point groups."""
+        ...
+
+    @property
+    def warnings(self) -> list[str]:
+        """Conflicts with the cell metric or the declared space group number."""
+        ...
+
+class ReflectionData:
+    """
+    Measured reflections of a block, from Block.reflections().
+
+    len(data) is the number of reflections read.
+    """
+
+    @property
+    def category(self) -> Literal["diffrn_refln", "refln"]:
+        """Loop the reflections came from."""
+        ...
+
+    @property
+    def reflections(self) -> list[tuple[tuple[int, int, int], float, float | None]]:
+        """Reflections as ((h, k, l), intensity, sigma) tuples, in file order."""
+        ...
+
+    def merge(
+        self,
+        point_group: PointGroup,
+        *,
+        anomalous: bool = False,
+        shells: int | list[float] | None = None,
+    ) -> MergedReflections:
+        """
+        Merge symmetry-equivalent reflections and compute merging statistics.
+
+        Each index is mapped to the largest of its equivalents under the
+        point group, Friedel mates included unless anomalous is True, and
+        the observations of each unique reflection are averaged with 1/σ²
+        weights (equal weights if any su is missing).
+
+        Args:
+            point_group: Point group, usually block.point_group()
+            anomalous: Keep hkl and -h-k-l apart
+            shells: Number of resolution shells with equal numbers of
+                unique reflections (default 10), or d-spacings in Å to
+                split shells at
+
+        Raises:
+            TypeError: If shells is neither an int nor a list of floats
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+class MergeStatistics(TypedDict):
+    """
+    Merging statistics over all reflections or one resolution shell.
+
+    R factors cover the unique reflections measured more than once and are
+    None if there are none; d_max and d_min are None without a cell.
+    """
+
+    d_max: float | None
+    d_min: float | None
+    observations: int
+    unique: int
+    multiplicity: float
+    r_merge: float | None
+    r_meas: float | None
+    r_pim: float | None
+    mean_i_over_sigma: float | None
+
+class MergedReflection(TypedDict):
+    """One unique reflection after merging."""
+
+    hkl: tuple[int, int, int]
+    intensity: float
+    sigma: float | None
+    multiplicity: int
+    d_spacing: float | None
+
+class MergedReflections:
+    """
+    Unique reflections and merging statistics, from ReflectionData.merge().
+
+    Example:
+        merged = block.reflections().merge(block.point_group(), shells=[2.0, 1.0])
+        for shell in merged.shells:
+            print(shell["d_min"], shell["r_merge"], shell["multiplicity"])
+    """
+
+    @property
+    def r_merge(self) -> float | None:
+        """Σ|I - Ī| / ΣI over reflections measured more than once."""
+        ...
+
+    @property
+    def r_meas(self) -> float | None:
+        """Multiplicity-independent R factor."""
+        ...
+
+    @property
+    def r_pim(self) -> float | None:
+        """Precision-indicating R factor."""
+        ...
+
+    @property
+    def mean_i_over_sigma(self) -> float | None:
+        """Mean Ī/σ(Ī) over the unique reflections."""
+        ...
+
+    @property
+    def multiplicity(self) -> float:
+        """Observations per unique reflection."""
+        ...
+
+    @property
+    def overall(self) -> MergeStatistics:
+        """Statistics over all reflections."""
+        ...
+
+    @property
+    def shells(self) -> list[MergeStatistics]:
+        """Statistics per shell, low resolution first; empty without a cell."""
+        ...
+
+    @property
+    def reflections(self) -> list[MergedReflection]:
+        """Unique reflections, sorted by indices."""
+        ...
+
+    def __len__(self) -> int: ...
+
+class CoercionReport:
+    """
+    Result of Block.check_schema(), one entry per schema tag.
+
+    A report is truthy when every tag is present, has a known value, and
+    converts. str(report) lists every tag with its outcome.
+
+    Example:
+        report = block.check_schema({"_cell_length_a": "float_su"})
+        assert report.ok, str(report)
+        print(report["_cell_length_a"]["status"])
+    """
+
+    @property
+    def ok(self) -> bool:
+        """Whether every tag is present, converts, and has a known value."""
+        ...
+
+    @property
+    def missing(self) -> list[str]:
+        """Tags that are not in the block."""
+        ...
+
+    @property
+    def unknown(self) -> list[str]:
+        """Tags whose values are all ? or .."""
+        ...
+
+    @property
+    def mismatched(self) -> list[str]:
+        """Tags with at least one value that does not convert."""
+        ...
+
+    @property
+    def mismatches(self) -> list[SchemaMismatch]:
+        """Every value that does not convert."""
+        ...
+
+    def to_dict(self) -> dict[str, TagCheck]:
+        """Reports keyed by tag, in schema order."""
+        ...
+
+    def __getitem__(self, tag: str) -> TagCheck:
+        """The report for one tag; raises KeyError if it was not in the schema."""
+        ...
+
+    def __bool__(self) -> bool: ...
+
+class UnitCell:
+    """
+    Unit cell parameters with standard uncertainties (sus).
+
+    Lengths are in Å and angles in degrees. Derived quantities propagate the
+    cell sus to first order.
+
+    Example:
+        cell = block.unit_cell()
+        print(cell.volume, cell.volume_su)
+        d, su = cell.distance([0, 0, 0], [0.5, 0.5, 0], with_su=True)
+    """
+
+    @property
+    def a(self) -> float:
+        """Length of a in Å."""
+        ...
+
+    @property
+    def b(self) -> float:
+        """Length of b in Å."""
+        ...
+
+    @property
+    def c(self) -> float:
+        """Length of c in Å."""
+        ...
+
+    @property
+    def alpha(self) -> float:
+        """Angle between b and c in degrees."""
+        ...
+
+    @property
+    def beta(self) -> float:
+        """Angle between a and c in degrees."""
+        ...
+
+    @property
+    def gamma(self) -> float:
+        """Angle between a and b in degrees."""
+        ...
+
+    @property
+    def su(self) -> tuple[float, float, float, float, float, float]:
+        """Sus of (a, b, c, alpha, beta, gamma); 0 when not given."""
+        ...
+
+    @property
+    def volume(self) -> float:
+        """Cell volume in Å³."""
+        ...
+
+    @property
+    def volume_su(self) -> float:
+        """Su of the volume, propagated from the cell sus."""
+        ...
+
+    @property
+    def metric_tensor(self) -> list[list[float]]:
+        """
+        Metric tensor G in Å², the dot products of the cell vectors.
+
+        A 3×3 nested list; numpy.array(cell.metric_tensor) gives an array.
+        """
+        ...
+
+    @property
+    def reciprocal_metric_tensor(self) -> list[list[float]]:
+        """Reciprocal metric tensor G* in Å⁻², the inverse of G (3×3)."""
+        ...
+
+    @property
+    def orthogonalization_matrix(self) -> list[list[float]]:
+        """Matrix taking fractional to Cartesian coordinates (3×3); its columns are the cell vectors."""
+        ...
+
+    @property
+    def fractionalization_matrix(self) -> list[list[float]]:
+        """Matrix taking Cartesian to fractional coordinates (3×3), the inverse of the orthogonalization matrix."""
+        ...
+
+    def frac_to_cart(self, frac: Sequence[float]) -> list[float]:
+        """Convert fractional coordinates to Cartesian coordinates in Å."""
+        ...
+
+    def cart_to_frac(self, cart: Sequence[float]) -> list[float]:
+        """Convert Cartesian coordinates in Å to fractional coordinates."""
+        ...
+
+    @overload
+    def distance(
+        self, a: Sequence[float], b: Sequence[float], *, with_su: Literal[False] = False
+    ) -> float: ...
+    @overload
+    def distance(
+        self, a: Sequence[float], b: Sequence[float], *, with_su: Literal[True]
+    ) -> tuple[float, float]: ...
+    def distance(
+        self, a: Sequence[float], b: Sequence[float], *, with_su: bool = False
+    ) -> float | tuple[float, float]:
+        """
+        Distance in Å between two fractional positions.
+
+        Args:
+            a: First position in fractional coordinates
+            b: Second position in fractional coordinates
+            with_su: Also return the su propagated from the cell sus (the
+                positions are taken as exact)
+
+        Returns:
+            The distance, or (distance, su) when with_su is true
+        """
+        ...
+
+class LoopMemory(TypedDict):
+    """Memory used by a loop, from Document.memory_footprint()."""
+
+    block: str
+    """Name of the block containing the loop."""
+    frame: str | None
+    """Name of the save frame containing the loop, if any."""
+    first_tag: str
+    """First tag, to identify the loop."""
+    rows: int
+    columns: int
+    bytes: int
+
+class BlockMemory(TypedDict):
+    """Memory used by a block, from Document.memory_footprint()."""
+
+    name: str
+    bytes: int
+    """Everything in the block, including its frames."""
+    items_bytes: int
+    """Data items (the map, tags, and values)."""
+    frames_bytes: int
+    """Save frames, including their items and loops."""
+    loops: list[LoopMemory]
+    """The block's own loops, in block order."""
+
+class MemoryReport(TypedDict):
+    """Memory used by a document, from Document.memory_footprint()."""
+
+    total_bytes: int
+    blocks: list[BlockMemory]
+    largest_loops: list[LoopMemory]
+    """The largest loops in the document (at most ten), largest first."""
+
+ValueKind = Literal["text", "numeric", "unknown", "not_applicable", "list", "table"]
+
+class FlatEntry(TypedDict):
+    """One value of a flattened document, from Document.flatten()."""
+
+    path: str
+    """Where the value is, such as "x/_atom_site_label[3]"."""
+    block: str
+    frame: str | None
+    """The save frame holding the value, or None."""
+    tag: str
+    row: int | None
+    """Loop row, or None for a data item."""
+    text: str
+    """The value as text; numbers as written by this library, ? and . as is."""
+    truncated: bool
+    number: float | None
+    """The number, for numbers and text with an su such as "5.431(2)"."""
+    kind: ValueKind
+
+RedactionProfile = Literal["pre_publication", "strip_embedded_files"]
+
+class Redaction(TypedDict):
+    """One redacted item or loop column, from Document.redact()."""
+
+    block: str
+    frame: str | None
+    """The save frame holding the tag, or None."""
+    tag: str
+    values: int
+    """Values replaced with ? or removed; values already ? are not counted."""
+    removed: bool
+
+class DocumentSource:
+    """
+    Where a document was parsed from.
+
+    Set for documents read with parse_file() or Document.from_file().
+
+    Example:
+        doc = cif_parser.parse_file("structure.cif")
+        print(doc.source.path, doc.source.sha256, doc.source.parsed_at)
+    """
+
+    @property
+    def path(self) -> str | None:
+        """File the document was read from. A name that is not valid UTF-8
+        is decoded as os.fsdecode() does; os.fsencode() gives back its bytes."""
+        ...
+
+    @property
+    def byte_len(self) -> int:
+        """Length of the content in bytes."""
+        ...
+
+    @property
+    def sha256(self) -> str:
+        """SHA-256 of the content, as lowercase hex."""
+        ...
+
+    @property
+    def parsed_at(self) -> datetime:
+        """When the document was parsed (timezone-aware, UTC)."""
+        ...
+
+class Snapshot:
+    """
+    A read-only copy of a document, from Document.snapshot().
+
+    Snapshots keep their own copy; later changes to the document do not
+    affect them.
+    """
+
+    @property
+    def block_names(self) -> list[str]:
+        """Block names, in order."""
+        ...
+
+    @property
+    def bytes(self) -> int:
+        """Estimated bytes held by the snapshot."""
+        ...
+
+    def to_document(self) -> Document:
+        """A new document with the snapshot's contents."""
+        ...
+
+    def __len__(self) -> int:
+        """Number of blocks."""
+        ...
+
+class Transaction:
+    """Context manager from Document.transaction(); entering it returns the
+    document, and leaving it by an exception restores the document."""
+
+    def __enter__(self) -> Document: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: object | None,
+    ) -> bool: ...
+
+class Dictionary:
+    """
+    The item definitions of a DDLm dictionary, such as cif_core.dic.
+
+    Tags are looked up in any case, by their DDLm name, their aliases, and
+    the DDLm name with its dot replaced by an underscore.
+    """
+
+    @staticmethod
+    def parse(content: str) -> Dictionary:
+        """Parse a dictionary from text."""
+        ...
+
+    @staticmethod
+    def from_file(path: StrPath) -> Dictionary:
+        """Parse a dictionary file."""
+        ...
+
+    @property
+    def title(self) -> str:
+        """_dictionary.title, or the data block's name without one."""
+        ...
+
+    @property
+    def version(self) -> str | None:
+        """_dictionary.version, if given."""
+        ...
+
+    def get(self, tag: str) -> Definition | None:
+        """The definition of a tag, or None."""
+        ...
+
+    def __contains__(self, tag: str) -> bool: ...
+    def __len__(self) -> int:
+        """Number of item definitions."""
+        ...
+
+class DictionaryStack:
+    """
+    Dictionaries layered so that later ones override earlier ones, tag by
+    tag, such as the core dictionary with a local extension on top.
+
+    Example:
+        >>> stack = DictionaryStack([core, local])
+        >>> stack.describe("_cell_length_a")["units"]
+        'angstroms'
+        >>> for issue in doc.validate(stack):
+        ...     print(issue["tag"], issue["message"], issue["dictionary"])
+    """
+
+    def __init__(self, dictionaries: Sequence[Dictionary] | None = None) -> None:
+        """Create a stack from dictionaries, bottom first."""
+        ...
+
+    def push(self, dictionary: Dictionary) -> None:
+        """Add a dictionary on top."""
+        ...
+
+    @property
+    def titles(self) -> list[str]:
+        """Titles of the dictionaries, bottom first."""
+        ...
+
+    def describe(self, tag: str) -> Definition | None:
+        """The definition of a tag in force, including its description, or
+        None if no dictionary defines it."""
+        ...
+
+    def definitions_of(self, tag: str) -> list[Definition]:
+        """Every definition of a tag, the one in force first."""
+        ...
+
+    def __len__(self) -> int:
+        """Number of dictionaries."""
+        ...
+
+class Document:
+    """
+    Represents a complete CIF document (root container).
+
+    A document contains one or more data blocks. This is the entry point
+    for parsing CIF files.
+
+    Example:
+        doc = Document.parse(cif_string)
+        # or
+        doc = Document.from_file("structure.cif")
+
+        for block in doc:
+            print(block.name)
+
+        # or built from scratch
+        doc = Document()
+        block = doc.add_block("si")
+        block["_cell_length_a"] = 5.431
+    """
+
+    def __init__(self, *, version: Literal["1.1", "2.0"] = "1.1") -> None:
+        """
+        Create an empty document, written as CIF 1.1 or 2.0, to fill with
+        add_block().
+
+        Raises:
+            ValueError: If the version is not "1.1" or "2.0".
+        """
+        ...
+
+    def add_block(self, name: str) -> Block:
+        """
+        Add an empty data block after the others.
+
+        Returns:
+            The block, sharing the document, to fill with block[tag] = value
+            and add_loop().
+
+        Raises:
+            ValueError: If a block has the name already.
+        """
+        ...
+
+    @staticmethod
+    def parse(
+        content: str,
+        *,
+        tags: list[str] | None = None,
+        blocks: list[str] | None = None,
+        first_block_only: bool = False,
+        duplicate_tags: Literal["last", "error", "collapse"] = "last",
+        lenient: bool = False,
+        large_value_threshold: int | None = None,
+    ) -> Document:
+        """
+        Parse a CIF document from a string.
+
+        Selecting with tags, blocks, or first_block_only skips everything
+        else unparsed and marks the document partial.
+
+        Args:
+            content: CIF file content as string
+            tags: Prefixes of the tags to keep (loops are kept whole if any tag matches)
+            blocks: Names of the blocks to keep
+            first_block_only: Keep only the first data block
+            duplicate_tags: How to handle a tag repeated as a data item: keep the
+                "last" value, raise an "error", or "collapse" the values into a
+                one-column loop (recorded in Document.warnings)
+            lenient: Recover from malformed input, recording warnings, and keep
+                non-CIF text around the blocks as Document.preamble and
+                Document.trailer instead of raising
+            large_value_threshold: Length in bytes above which text is kept as a
+                large value, shared rather than copied and read with
+                Value.read_large()
+
+        Returns:
+            Parsed document
+
+        Raises:
+            ValueError: If parsing fails due to invalid CIF syntax
+        """
+        ...
+
+    @staticmethod
+    def from_file(
+        path: StrPath,
+        *,
+        tags: list[str] | None = None,
+        blocks: list[str] | None = None,
+        first_block_only: bool = False,
+        duplicate_tags: Literal["last", "error", "collapse"] = "last",
+        lenient: bool = False,
+        large_value_threshold: int | None = None,
+    ) -> Document:
+        """
+        Parse a CIF document from a file.
+
+        Args:
+            path: Path to CIF file
+            tags: Prefixes of the tags to keep (loops are kept whole if any tag matches)
+            blocks: Names of the blocks to keep
+            first_block_only: Keep only the first data block
+            duplicate_tags: How to handle a tag repeated as a data item: keep the
+                "last" value, raise an "error", or "collapse" the values into a
+                one-column loop (recorded in Document.warnings)
+            lenient: Recover from malformed input, recording warnings, and keep
+                non-CIF text around the blocks as Document.preamble and
+                Document.trailer instead of raising
+            large_value_threshold: Length in bytes above which text is kept as a
+                large value, shared rather than copied and read with
+                Value.read_large()
+
+        Returns:
+            Parsed document
+
+        Raises:
+            IOError: If file cannot be read
+            ValueError: If parsing fails due to invalid CIF syntax
+        """
+        ...
+
+    @property
+    def warnings(self) -> list[str]:
+        """Problems the parser recovered from, such as collapsed repeated tags."""
+        ...
+
+    @property
+    def partial(self) -> bool:
+        """Whether only selected content was parsed."""
+        ...
+
+    @property
+    def preamble(self) -> str | None:
+        """
+        Non-CIF text before the first block, such as an instrument banner.
+
+        Only set when parsed with lenient=True; strict parsing rejects it.
+        """
+        ...
+
+    @property
+    def trailer(self) -> str | None:
+        """
+        Non-CIF text after the last block, such as a checksum after #END.
+
+        Only set when parsed with lenient=True; strict parsing rejects it.
+        """
+        ...
+
+    @property
+    def bare_frames(self) -> bool:
+        """
+        Whether the first block was made up to hold save frames written
+        before any data block, as in a dictionary fragment.
+
+        Only set when parsed with lenient=True. The frames are written back
+        without the block's heading.
+        """
+        ...
+
+    @property
+    def source(self) -> DocumentSource | None:
+        """Where the document was parsed from, or None for parsed strings."""
+        ...
+
+    @property
+    def blocks(self) -> list[Block]:
+        """Get all data blocks in this document."""
+        ...
+
+    @property
+    def block_names(self) -> list[str]:
+        """Get the names of all blocks in this document."""
+        ...
+
+    def get_block(self, index: int) -> Block | None:
+        """
+        Get a block by index.
+
+        Args:
+            index: Block index (0-based)
+
+        Returns:
+            The block at the specified index, or None if out of bounds.
+        """
+        ...
+
+    def get_block_by_name(self, name: str) -> Block | None:
+        """
+        Get a block by name.
+
+        Args:
+            name: Block name (without 'data_' prefix)
+
+        Returns:
+            The block with the specified name, or None if not found.
+        """
+        ...
+
+    def first_block(self) -> Block | None:
+        """
+        Get the first block (common for single-block CIF files).
+
+        Returns:
+            The first block, or None if document is empty.
+        """
+        ...
+
+    def main_block(self, *, strict: bool = False) -> Block:
+        """
+        The block that get(), find_loop(), atom_sites(), unit_cell(), and
+        point_group() on the document work on.
+
+        By default this is the first block holding data (a structure,
+        reflections, a cell, a powder pattern, ...), so that a leading global
+        or publication-only block is skipped, or else the first block.
+
+        Args:
+            strict: Require the block to be the only one, or the only one
+                holding data
+
+        Raises:
+            ValueError: If the document is empty, or with strict=True if the
+                choice is ambiguous. The message names the blocks.
+        """
+        ...
+
+    def only_block(self) -> Block:
+        """
+        The only block of the document.
+
+        Raises:
+            ValueError: Unless there is exactly one block; the message names
+                the blocks.
+        """
+        ...
+
+    def get(
+        self, tag: str, search_frames: bool = False, *, strict: bool = False
+    ) -> Value | None:
+        """Shortcut for main_block(strict=strict).get(tag, search_frames)."""
+        ...
+
+    def find_loop(self, tag: str, *, strict: bool = False) -> Loop | None:
+        """Shortcut for main_block(strict=strict).find_loop(tag)."""
+        ...
 
-    Example:
-        doc = Document.parse(cif_string)
-        # or
-        doc = Document.from_file("structure.cif")
+    def atom_sites(self, *, strict: bool = False) -> list[AtomSite] | None:
+        """Shortcut for main_block(strict=strict).atom_sites()."""
+        ...
 
-        for block in doc:
-            print(block.name)
-    """
+    def unit_cell(self, *, strict: bool = False) -> UnitCell | None:
+        """Shortcut for main_block(strict=strict).unit_cell()."""
+        ...
 
-    @staticmethod
-    def parse(content: str) -> Document:
+    def point_group(self, *, strict: bool = False) -> PointGroup | None:
+        """Shortcut for main_block(strict=strict).point_group()."""
+        ...
+
+    def category(self, name: str, *, strict: bool = False) -> Category | None:
+        """Shortcut for main_block(strict=strict).category(name)."""
+        ...
+
+    def space_group(self, *, strict: bool = False) -> SpaceGroup | None:
+        """Shortcut for main_block(strict=strict).space_group()."""
+        ...
+
+    def find_by_identifier(
+        self,
+        kind: Literal["csd", "icsd", "cod", "ccdc_deposition", "pdb", "doi", "dataset_doi"],
+        code: str,
+    ) -> Block | None:
         """
-        Parse a CIF document from a string.
+        Find the first block with the given identifier.
 
-        Args:
-            content: CIF file content as string
+        Codes are compared ignoring case and leading zeros; DOIs may carry a
+        "doi:" or "https://doi.org/" prefix, and CCDC numbers a "CCDC" prefix.
+
+        Raises:
+            ValueError: If kind is not one of the names above.
+        """
+        ...
+
+    def to_dict(
+        self,
+        values: Literal["native", "raw"] = "native",
+        include_loops: bool = True,
+        include_frames: bool = True,
+        loops: Literal["rows", "columns"] = "rows",
+    ) -> dict[str, Any]:
+        """
+        Convert the whole document to plain, JSON-serializable Python data.
+
+        Arguments are the same as for Block.to_dict().
 
         Returns:
-            Parsed document
+            {"version": "1.1" | "2.0", "blocks": [block dicts...]}
+        """
+        ...
+
+    def to_json(self) -> str:
+        """
+        Convert the document to CIF-JSON text, the COMCIFS convention for
+        CIF as JSON.
+
+        Block, frame and data names are lowercased. Every data name has a
+        list of values, one for a data item and one per row for a loop
+        column; ? is the string "\\?" and . is null. Frames are under
+        "Frames" in their block.
 
         Raises:
-            ValueError: If parsing fails due to invalid CIF syntax
+            ValueError: If two blocks, or two frames of a block, have names
+                differing only in case.
+
+        Example:
+            >>> data = json.loads(doc.to_json())["CIF-JSON"]
+            >>> data["x"]["_cell_length_a"]
+            [5.431]
         """
         ...
 
-    @staticmethod
-    def from_file(path: str) -> Document:
+    def memory_footprint(self) -> MemoryReport:
         """
-        Parse a CIF document from a file.
+        Estimate the memory used by the parsed document.
 
-        Args:
-            path: Path to CIF file
+        Sizes are in bytes and count allocated capacity, not just the data
+        in use. Only the Rust-side data is counted, not Python wrappers.
 
         Returns:
-            Parsed document
+            The total, a breakdown per block, and the ten largest loops.
 
-        Raises:
-            IOError: If file cannot be read
-            ValueError: If parsing fails due to invalid CIF syntax
+        Example:
+            >>> report = doc.memory_footprint()
+            >>> report["largest_loops"][0]["first_tag"]
+            '_refln_index_h'
         """
         ...
 
-    @property
-    def blocks(self) -> list[Block]:
-        """Get all data blocks in this document."""
+    def flatten(
+        self,
+        *,
+        include_loops: bool = True,
+        max_text_len: int | None = None,
+        lowercase_tags: bool = False,
+    ) -> list[FlatEntry]:
+        """
+        Flatten the document into one entry per value, for search indexing.
+
+        Paths are "block/_tag" for a data item, "block/_tag[row]" for a
+        loop value, and "block/save_frame/_tag" inside a save frame. A tag
+        in more than one loop of a block carries on the row numbering, so
+        paths are unique.
+
+        Args:
+            include_loops: Include loop values; leave them out to keep the
+                number of entries small.
+            max_text_len: Cut longer text to this many characters and
+                append "…".
+            lowercase_tags: Lowercase tags in paths and entries.
+
+        Example:
+            >>> doc.flatten(include_loops=False)[0]["path"]
+            'x/_cell_length_a'
+        """
         ...
 
-    @property
-    def block_names(self) -> list[str]:
-        """Get the names of all blocks in this document."""
+    def to_ndjson(
+        self,
+        path: StrPath,
+        *,
+        include_loops: bool = True,
+        max_text_len: int | None = None,
+        lowercase_tags: bool = False,
+    ) -> None:
+        """
+        Write flatten()'s entries as newline-delimited JSON, one object per
+        line, for the bulk APIs of Elasticsearch and OpenSearch.
+
+        Raises:
+            IOError: If the file cannot be written.
+        """
+    def write(self, path: StrPath) -> None:
+        """
+        Write the document to a file as CIF text, the same as str(doc).
+
+        save() takes layout and number-format options.
+
+        Raises:
+            ValueError: If a number is infinite or NaN. Nothing is written.
+            IOError: If the file cannot be written.
+
+        Example:
+            >>> doc.write("out.cif")
+            >>> cif_parser.parse_file("out.cif").first_block().name == doc.first_block().name
+            True
+        """
         ...
 
-    def get_block(self, index: int) -> Block | None:
+    def save(
+        self,
+        path: StrPath,
+        *,
+        style: Literal["core", "mmcif"] = "core",
+        float_format: dict[str, int | str] | None = None,
+        su_rounding: bool = False,
+    ) -> None:
         """
-        Get a block by index.
+        Write the document to a file as CIF text.
 
         Args:
-            index: Block index (0-based)
+            path: File to write.
+            style: "mmcif" lays out blocks with dotted tags the PDBx way.
+            float_format: Maps tag patterns such as "_atom_site_fract_*" to
+                how their numbers are written: an int of decimals, ".4f",
+                ".6g" for significant digits, or "shortest". The pattern "*"
+                applies to all other numbers. An exact tag beats a pattern,
+                and a longer pattern a shorter one.
+            su_rounding: Round numbers that have an "_su" item or column to
+                the precision of their su.
+
+        Numbers are otherwise written in their shortest round-trip form.
 
-        Returns:
-            The block at the specified index, or None if out of bounds.
+        Raises:
+            ValueError: If a number is infinite or NaN, or a format is not
+                understood. Nothing is written.
+            IOError: If the file cannot be written.
+
+        Example:
+            >>> doc.save("out.cif", float_format={"_atom_site_fract_*": 5})
         """
         ...
 
-    def get_block_by_name(self, name: str) -> Block | None:
+    def redact(
+        self,
+        profile: RedactionProfile | Sequence[RedactionProfile] | None = None,
+        *,
+        tags: Sequence[str] | None = None,
+        remove: bool = False,
+    ) -> list[Redaction]:
         """
-        Get a block by name.
+        Redact sensitive values in place before sharing the file.
+
+        "pre_publication" covers author and contact names, addresses, and
+        emails, and *_special_details fields; "strip_embedded_files" covers
+        SHELX .res, .hkl, and .fab files and their checksums. tags adds tag
+        patterns, where * matches any characters and ? one character,
+        ignoring case.
 
         Args:
-            name: Block name (without 'data_' prefix)
+            profile: One or more built-in profiles.
+            tags: More tag patterns.
+            remove: Remove matching items and loop columns instead of
+                replacing their values with ?.
 
         Returns:
-            The block with the specified name, or None if not found.
+            What was changed, one entry per item or loop column.
+
+        Raises:
+            ValueError: If a profile name is unknown.
+
+        Example:
+            >>> changes = doc.redact(profile="pre_publication")
+            >>> changes[0]["tag"]
+            '_publ_contact_author_name'
         """
         ...
 
-    def first_block(self) -> Block | None:
+    def snapshot(self) -> Snapshot:
         """
-        Get the first block (common for single-block CIF files).
+        Take a read-only snapshot of the document, for restore().
 
-        Returns:
-            The first block, or None if document is empty.
+        Example:
+            >>> before = doc.snapshot()
+            >>> doc.redact(profile="pre_publication")
+            >>> doc.restore(before)
+        """
+        ...
+
+    def restore(self, snapshot: Snapshot) -> None:
+        """Put back the contents recorded by snapshot(), replacing everything
+        in the document, so that writing it gives the same text as before."""
+        ...
+
+    def validate(self, stack: DictionaryStack) -> list[ValidationIssue]:
+        """
+        Check every block against a dictionary stack.
+
+        Each issue names the dictionary and save frame whose definition was
+        used. Tags no dictionary defines are not checked; ? and . are
+        always allowed.
+        """
+        ...
+
+    def query(self, selector: str) -> list[QueryHit]:
+        """
+        Values picked by a selector.
+
+        A selector names blocks (block[name], block[glob*], block[0],
+        block[-1], or block[*]), then an item tag or a loop
+        (loop(_atom_site)), then optionally loop rows by a column's value
+        (.rows[occupancy<1 & type_symbol=C]) and the columns to return
+        (.label or .(label, occupancy)). ? and . values only match =? and
+        =., and never a comparison with a number.
+
+        Raises:
+            ValueError: If the selector does not parse; its `position`
+                attribute is the 1-indexed character of the problem.
+
+        Example:
+            >>> [h["value"] for h in doc.query("loop(_atom_site).rows[occupancy<1].label")]
+            ['C2', 'N1']
+        """
+        ...
+
+    def transaction(self) -> Transaction:
+        """
+        A context manager that puts the document back as it was if the with
+        block raises. The exception is not suppressed.
+
+        Example:
+            >>> with doc.transaction():
+            ...     doc.redact(profile="pre_publication")
+            ...     check(doc)  # raising here undoes the redaction
+        """
+        ...
+
+    def kind(self) -> list[str]:
+        """Guess what kind of file this is from the tags it uses.
+
+        Returns every kind matched, from ``small-molecule``, ``mmcif``,
+        ``structure-factors``, ``powder``, ``dictionary``, ``magcif``,
+        ``imgcif``, and ``template``. A file may match several kinds or
+        none.
+
+        Example:
+            >>> doc.kind()
+            ['small-molecule']
         """
         ...
 
@@ -519,16 +3375,173 @@ class Document:
         ...
 
     def __str__(self) -> str:
-        """String representation."""
+        """The document as CIF text, as write() writes it."""
         ...
 
     def __repr__(self) -> str:
         """Debug representation."""
         ...
 
+class Comparison:
+    """
+    Result of compare(): whether two numbers agree, and by how many sigmas
+    they differ.
+
+    Truthy when the numbers agree.
+    """
+
+    @property
+    def equal(self) -> bool:
+        """Whether the numbers differ by at most n_sigma."""
+        ...
+
+    @property
+    def sigmas(self) -> float | None:
+        """
+        Absolute difference in units of the combined su, or None if a
+        number or su is not finite or an su is negative.
+
+        Without any su, relative_tolerance counts as n_sigma.
+        """
+        ...
+
+    def __bool__(self) -> bool: ...
+    def __repr__(self) -> str: ...
+
+class StructureComparison:
+    """
+    Result of compare_structures(): how each atom moved between two versions
+    of a structure, and which atoms are in only one of them.
+    """
+
+    @property
+    def sites(self) -> list[SiteDifference]:
+        """Matched atoms, in the order of the first structure."""
+        ...
+
+    @property
+    def only_in_a(self) -> list[str]:
+        """Labels of atoms only in the first structure."""
+        ...
+
+    @property
+    def only_in_b(self) -> list[str]:
+        """Labels of atoms only in the second structure."""
+        ...
+
+    @property
+    def origin_shift(self) -> tuple[float, float, float]:
+        """
+        Fractional origin of the second structure relative to the first;
+        zero unless fitted with origin_shift=True.
+        """
+        ...
+
+    @property
+    def rmsd(self) -> float:
+        """Root-mean-square distance over the matched atoms, in Å."""
+        ...
+
+    def __repr__(self) -> str: ...
+
 # Module-level convenience functions
 
-def parse(content: str) -> Document:
+WritableValue = str | float | int | None | list["WritableValue"] | dict[str, "WritableValue"]
+
+class Writer:
+    """
+    Write a CIF file piece by piece, without building a Document.
+
+    Items and loop rows go to the file as they are given, quoted as
+    Document writes them, so a loop of millions of rows takes no more
+    memory than one row. Calls must follow the structure of the file:
+    begin_block, items and loops (begin_loop, write_row..., end_loop),
+    end_block. A call out of order, a bad tag, or a row of the wrong width
+    raises ValueError.
+
+    Values are numbers, str, None (written as ?), and, with version="2.0",
+    lists and dicts of these.
+
+    Example:
+        >>> with cif_parser.Writer("hkl.cif") as w:
+        ...     w.begin_block("hkl")
+        ...     w.begin_loop(["_refln_index_h", "_refln_index_k", "_refln_index_l"])
+        ...     for h, k, l in indices:
+        ...         w.write_row([h, k, l])
+        ...     w.end_loop()
+        ...     w.end_block()
+    """
+
+    def __init__(
+        self,
+        path: StrPath,
+        *,
+        version: Literal["1.1", "2.0"] = "1.1",
+        style: Literal["core", "mmcif"] = "core",
+    ) -> None:
+        """
+        Create or truncate path for writing.
+
+        Args:
+            path: File to write
+            version: CIF version of the output
+            style: "mmcif" leaves out the CIF 1.1 header and writes a #
+                line after each category
+
+        Raises:
+            IOError: If the file cannot be created
+            ValueError: If version or style is not one of the accepted values
+        """
+        ...
+
+    def begin_block(self, name: str) -> None:
+        """Start a data block."""
+        ...
+
+    def write_item(self, tag: str, value: WritableValue) -> None:
+        """Write one data item of the open block."""
+        ...
+
+    def begin_loop(self, tags: list[str]) -> None:
+        """Start a loop with these column tags."""
+        ...
+
+    def write_row(self, values: Sequence[WritableValue]) -> None:
+        """Write one row of the open loop, one value per column."""
+        ...
+
+    def end_loop(self) -> None:
+        """End the open loop."""
+        ...
+
+    def end_block(self) -> None:
+        """End the open block."""
+        ...
+
+    def close(self) -> None:
+        """
+        Flush and close the file. Closing twice does nothing.
+
+        Raises:
+            ValueError: If a block or loop is still open
+        """
+        ...
+
+    def __enter__(self) -> Writer: ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool:
+        """Close the file; after an exception, without checking for open blocks."""
+        ...
+
+def parse(
+    content: str,
+    *,
+    tags: list[str] | None = None,
+    blocks: list[str] | None = None,
+    first_block_only: bool = False,
+    duplicate_tags: Literal["last", "error", "collapse"] = "last",
+    lenient: bool = False,
+    large_value_threshold: int | None = None,
+) -> Document:
     """
     Parse a CIF document from a string.
 
@@ -536,6 +3549,18 @@ def parse(content: str) -> Document:
 
     Args:
         content: CIF file content as string
+        tags: Prefixes of the tags to keep (loops are kept whole if any tag matches)
+        blocks: Names of the blocks to keep
+        first_block_only: Keep only the first data block
+        duplicate_tags: How to handle a tag repeated as a data item: keep the
+            "last" value, raise an "error", or "collapse" the values into a
+            one-column loop (recorded in Document.warnings)
+        lenient: Recover from malformed input, recording warnings, and keep
+            non-CIF text around the blocks as Document.preamble and
+            Document.trailer instead of raising
+        large_value_threshold: Length in bytes above which text is kept as a
+            large value, shared rather than copied and read with
+            Value.read_large()
 
     Returns:
         Parsed document
@@ -557,14 +3582,43 @@ def parse(content: str) -> Document:
     """
     ...
 
-def parse_file(path: str) -> Document:
+def parse_file(
+    path: StrPath,
+    *,
+    tags: list[str] | None = None,
+    blocks: list[str] | None = None,
+    first_block_only: bool = False,
+    duplicate_tags: Literal["last", "error", "collapse"] = "last",
+    lenient: bool = False,
+    large_value_threshold: int | None = None,
+    cache_dir: StrPath | None = None,
+) -> Document:
     """
     Parse a CIF document from a file.
 
     This is a convenience function equivalent to Document.from_file().
+    A file ending in .bcif is decoded as BinaryCIF, and gzip, bzip2, and
+    Zstandard files (1abc.cif.gz, .bz2, .zst) are decompressed first.
 
     Args:
         path: Path to CIF file
+        tags: Prefixes of the tags to keep (loops are kept whole if any tag matches)
+        blocks: Names of the blocks to keep
+        first_block_only: Keep only the first data block
+        duplicate_tags: How to handle a tag repeated as a data item: keep the
+            "last" value, raise an "error", or "collapse" the values into a
+            one-column loop (recorded in Document.warnings)
+        lenient: Recover from malformed input, recording warnings, and keep
+            non-CIF text around the blocks as Document.preamble and
+            Document.trailer instead of raising
+        large_value_threshold: Length in bytes above which text is kept as a
+            large value, shared rather than copied and read with
+            Value.read_large()
+        cache_dir: Directory for a persistent parse cache, such as
+            "~/.cache/cif-parser". Parsed documents are stored keyed by the
+            file's content hash, so an unchanged file loads without parsing.
+            Unreadable entries are rewritten; the least recently used are
+            removed once the cache passes 1 GiB.
 
     Returns:
         Parsed document
@@ -579,5 +3633,183 @@ def parse_file(path: str) -> Document:
         doc = cif_parser.parse_file("structure.cif")
         for block in doc:
             print(f"Block: {block.name}")
+
+        # Only the cell and symmetry items of the first block
+        doc = cif_parser.parse_file(
+            "structure.cif", tags=["_cell_", "_symmetry_"], first_block_only=True
+        )
+    """
+    ...
+
+def iter_stream(
+    path: StrPath,
+    *,
+    errors: Literal["warn", "ignore", "raise"] = "warn",
+) -> Iterator[Document]:
+    """
+    Iterate over a file of concatenated CIFs, one entry at a time.
+
+    Each data block is parsed as its own single-block Document when the
+    next data_ line is reached, so the file is never read into memory
+    whole. data_ lines inside text fields are content, not new entries.
+
+    Args:
+        path: File to read
+        errors: What to do with an entry that fails to parse: "warn" issues
+            a UserWarning and carries on, "ignore" skips it silently, and
+            "raise" raises ValueError, ending the iteration
+
+    Example:
+        >>> for doc in cif_parser.iter_stream("cod-dump.cif"):
+        ...     print(doc.first_block().name)
+    """
+    ...
+
+def extract(
+    paths: Sequence[StrPath],
+    tags: Sequence[str],
+    *,
+    first_block_only: bool = False,
+    threads: int | None = None,
+) -> list[dict[str, Any]]:
+    """
+    Extract single-valued tags from many files, parsing them in parallel.
+
+    Each file is parsed selectively, keeping only the requested tags, so
+    the rest of it is skipped unparsed. A tag's value is its data item, or
+    its value in a loop with exactly one row; tags match ignoring case.
+
+    Args:
+        paths: Files to read
+        tags: Tags to extract
+        first_block_only: Read only the first data block of each file
+        threads: Worker threads (default: one per CPU)
+
+    Returns:
+        One dict per data block, in the order of paths, with "path",
+        "block", "error" (None unless the file failed to parse), and each
+        tag mapped to its Value or None. A file that fails to parse gives
+        one dict with "block" None and its error.
+
+    Example:
+        >>> rows = cif_parser.extract(files, ["_cell_volume"])
+        >>> rows[0]["_cell_volume"].numeric
+        1459.43
+    """
+    ...
+
+def compare(
+    a: float,
+    b: float,
+    *,
+    su_a: float | None = None,
+    su_b: float | None = None,
+    n_sigma: float = 3.0,
+    relative_tolerance: float = 1e-3,
+) -> Comparison:
+    """
+    Compare two numbers, each with an optional standard uncertainty (su).
+
+    The sus are combined in quadrature, and the numbers are equal when they
+    differ by at most n_sigma of that. When neither has an su, they are
+    equal within relative_tolerance of the larger magnitude instead.
+
+    Example:
+        >>> cif_parser.compare(1.5406, 1.5410, su_a=0.0003).sigmas
+        1.333...
+    """
+    ...
+
+def compare_structures(
+    block_a: Block,
+    block_b: Block,
+    *,
+    by: Literal["label", "proximity"] = "label",
+    max_distance: float = 0.5,
+    origin_shift: bool = False,
+) -> StructureComparison:
+    """
+    Compare the structures of two blocks atom by atom.
+
+    Both are reduced to one site per set of symmetry-equivalent positions
+    first, so a structure expanded to P1 compares with its asymmetric unit.
+    Sites are paired by label, or with by="proximity" to the nearest site of
+    the same element within max_distance Å. With origin_shift=True, the
+    origin along the polar axes of the space group is fitted first.
+
+    Raises:
+        ValueError: If a block has no unit cell
+
+    Example:
+        >>> result = cif_parser.compare_structures(old.blocks[0], new.blocks[0])
+        >>> result.rmsd
+        0.0046...
+    """
+    ...
+
+def category_of(tag: str, categories: list[str] | None = None) -> str:
+    """
+    Category of a tag, lowercased and without the leading underscore.
+
+    For mmCIF-style tags this is the part before the dot. For core CIF tags
+    it is the longest known category that prefixes the tag, from a built-in
+    table of core CIF categories plus any given in `categories`; tags of
+    other dictionaries fall back to their first word.
+
+    Args:
+        tag: Tag name, e.g. "_atom_site_aniso_U_11"
+        categories: Category names to add to the built-in ones
+
+    Example:
+        >>> cif_parser.category_of("_atom_site_aniso_U_11")
+        'atom_site_aniso'
+        >>> cif_parser.category_of("_pd_proc_2theta_corrected")
+        'pd'
+        >>> cif_parser.category_of("_pd_proc_2theta_corrected", ["pd_proc"])
+        'pd_proc'
+    """
+    ...
+
+def format(text: str, *, align: bool = True, comments: bool = True) -> str:
+    """
+    Format CIF text canonically, keeping its comments.
+
+    Items come in tag order with their values lined up, loop values are
+    padded into columns, and each comment is written on its own line before
+    the item, loop, row, or heading it was written with. Formatting the
+    result again gives the same text, and it parses to the same data.
+
+    Args:
+        text: CIF text
+        align: Pad tags and loop values into columns
+        comments: Write the comments back
+
+    Raises:
+        ValueError: If the text does not parse
+
+    Example:
+        >>> print(cif_parser.format("data_x\n_b 2 # note\n_long_tag 1\n"))
+        #\\#CIF_1.1
+        <BLANKLINE>
+        data_x
+        # note
+        _b        2
+        _long_tag 1
+    """
+    ...
+
+def parse_element(symbol: str) -> ParsedElement:
+    """
+    Read an atom type symbol, as in _atom_type_symbol.
+
+    The element is matched case-insensitively, the charge may be written
+    either way round ("O2-" or "O-2"), and "D" and "T" are hydrogen isotopes.
+
+    Raises:
+        ValueError: If the symbol names no element or has a malformed charge
+
+    Example:
+        >>> cif_parser.parse_element("FE3+")
+        {'element': 'Fe', 'atomic_number': 26, 'charge': 3, 'isotope': None}
     """
     ...
@@ -0,0 +1,65 @@
+"""Tests for changing documents and building them from scratch."""
+
+import cif_parser
+import pytest
+
+
+def test_build_from_scratch():
+    """A document built in code is written as CIF and parses back."""
+    doc = cif_parser.Document()
+    block = doc.add_block("si")
+    block["_cell_length_a"] = 5.431
+    block["_title"] = "two words"
+    block["_unknown"] = None
+
+    atoms = cif_parser.Loop(["_atom_site_label"])
+    atoms.add_row(["Si1"])
+    atoms.add_row(["Si2"])
+    atoms.add_column("_atom_site_fract_x", [0.125, 0.5])
+    block.add_loop(atoms)
+
+    again = cif_parser.parse(str(doc)).first_block()
+    assert again.get_item("_cell_length_a").numeric == 5.431
+    assert again.get_item("_title").text == "two words"
+    assert again.get_item("_unknown").is_unknown
+    loop = again.find_loop("_atom_site_fract_x")
+    assert len(loop) == 2
+    assert loop.get(1, 0).text == "Si2"
+
+
+def test_changes_are_seen_by_the_document():
+    """Blocks share their document, so changes reach it."""
+    doc = cif_parser.parse("data_x\n_title old\n_cell_length_a 5\n")
+    block = doc.first_block()
+    block["_title"] = "new"
+    del block["_cell_length_a"]
+    assert doc.first_block().item_keys == ["_title"]
+    assert doc.first_block().get_item("_title").text == "new"
+    with pytest.raises(KeyError):
+        del block["_cell_length_a"]
+
+
+def test_invalid_changes_raise():
+    """Changes that would make the document unwritable are refused."""
+    doc = cif_parser.parse("data_x\nloop_\n_a\n1\n")
+    block = doc.first_block()
+    with pytest.raises(ValueError) as info:
+        block["_a"] = 2
+    assert info.value.code == "E0005"
+    with pytest.raises(ValueError):
+        block.add_loop(cif_parser.Loop(["_a"]))
+    with pytest.raises(ValueError):
+        doc.add_block("x")
+    with pytest.raises(ValueError):
+        cif_parser.Loop(["_a", "_b"]).add_row([1])
+    with pytest.raises(ValueError):
+        cif_parser.Document(version="3.0")
+
+
+def test_cif2_document():
+    """A CIF 2.0 document holds lists and tables."""
+    doc = cif_parser.Document(version="2.0")
+    doc.add_block("x")["_list"] = [1, "a b"]
+    again = cif_parser.parse(str(doc))
+    assert again.is_cif2
+    assert again.first_block().get_item("_list").is_list
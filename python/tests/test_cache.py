@@ -0,0 +1,34 @@
+"""Tests for the persistent parse cache."""
+
+import cif_parser
+
+
+def test_cached_parse_matches_plain_parse(tmp_path):
+    """A cached load gives the same document, and stores one entry."""
+    path = tmp_path / "a.cif"
+    path.write_text("data_a\n_cell_length_a 5.431\nloop_\n_x\n1\n2\n")
+    cache = tmp_path / "cache"
+
+    first = cif_parser.parse_file(str(path), cache_dir=str(cache))
+    second = cif_parser.parse_file(str(path), cache_dir=str(cache))
+    plain = cif_parser.parse_file(str(path))
+    assert first.first_block().get_item("_cell_length_a").numeric == 5.431
+    assert second.to_dict() == plain.to_dict()
+    assert second.source.sha256 == plain.source.sha256
+    assert len(list(cache.glob("*.cifc"))) == 1
+
+
+def test_cache_follows_options_and_content(tmp_path):
+    """Different selections and edited content get their own entries."""
+    path = tmp_path / "a.cif"
+    path.write_text("data_a\n_cell_length_a 5.0\n_title x\n")
+    cache = str(tmp_path / "cache")
+
+    doc = cif_parser.parse_file(str(path), tags=["_cell"], cache_dir=cache)
+    assert doc.first_block().get_item("_title") is None
+    doc = cif_parser.parse_file(str(path), cache_dir=cache)
+    assert doc.first_block().get_item("_title") is not None
+
+    path.write_text("data_a\n_cell_length_a 6.0\n")
+    doc = cif_parser.parse_file(str(path), cache_dir=cache)
+    assert doc.first_block().get_item("_cell_length_a").numeric == 6.0
@@ -0,0 +1,71 @@
+"""Tests for finding the category of a tag and reading categories as tables."""
+
+from pathlib import Path
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures"
+TAGS = FIXTURES / "category" / "tags.tsv"
+
+
+def test_longest_known_category():
+    """Multi-word core categories win over their shorter prefixes."""
+    assert cif_parser.category_of("_atom_site_aniso_U_11") == "atom_site_aniso"
+    assert cif_parser.category_of("_atom_site_fract_x") == "atom_site"
+    assert cif_parser.category_of("_Geom_Bond_distance") == "geom_bond"
+    assert cif_parser.category_of("_atom_site.Cartn_x") == "atom_site"
+
+
+def test_real_tags():
+    """Every tag in the labelled table gets its category."""
+    for line in TAGS.read_text().splitlines():
+        if line.startswith("#"):
+            continue
+        tag, category = line.split("\t")
+        assert cif_parser.category_of(tag) == category, tag
+
+
+def test_registered_categories():
+    """Extra categories extend the table for tags of other dictionaries."""
+    assert cif_parser.category_of("_pd_proc_2theta_corrected") == "pd"
+    assert cif_parser.category_of("_pd_proc_2theta_corrected", ["pd_proc"]) == "pd_proc"
+
+    block = cif_parser.parse(
+        "data_x\nloop_\n_pd_meas_2theta_scan\n_pd_proc_intensity_net\n10 4\n"
+    ).first_block()
+    assert block.repair_mixed_loops() == []
+    splits = block.repair_mixed_loops(["_pd_meas", "pd_proc"])
+    assert splits[0]["categories"] == ["_pd_meas", "_pd_proc"]
+    assert len(block.loops) == 2
+
+
+def test_mmcif_category_table():
+    """A category reads the same whether written as items or as a loop."""
+    block = cif_parser.parse_file(str(FIXTURES / "mmcif_fragment.cif")).first_block()
+    assert block.categories()[:3] == ["entry", "cell", "symmetry"]
+
+    atoms = block.category("atom_site")
+    assert repr(atoms) == "Category(name='atom_site', rows=10)"
+    assert atoms.attributes[10] == "Cartn_x"
+    assert atoms.get(7, "label_alt_id").text == "A"
+    assert atoms.column("cartn_x")[9].numeric == 10.25
+    assert atoms.row(0)["type_symbol"].source.row == 0
+    assert len(atoms.to_loop()) == 10
+
+    cell = block.category("_cell")
+    assert len(cell) == 1
+    assert cell.row(0)["length_b"].numeric == 18.65
+    assert cell.get(0, "length_b").source.row is None
+    assert block.category("pdbx_struct_oper_list") is None
+
+
+def test_items_repeated_on_loop_rows():
+    """Data items of a category with a loop appear on every row."""
+    block = cif_parser.parse(
+        "data_x\n_atom_type_scat_source 'Intl. Tables'\n"
+        "loop_\n_atom_type_symbol\nC\nO\n"
+    ).first_block()
+    types = block.category("atom_type")
+    assert types.tags == ["_atom_type_scat_source", "_atom_type_symbol"]
+    assert [row["scat_source"].text for row in types.rows()] == ["Intl. Tables"] * 2
+    assert types.to_loop().tags == types.tags
@@ -0,0 +1,82 @@
+"""Tests for unit cells and su propagation."""
+
+import pytest
+
+import cif_parser
+
+ALBITE = """
+data_albite
+_cell_length_a 7.13(6)
+_cell_length_b 7.38(5)
+_cell_length_c 7.64(4)
+_cell_angle_alpha 115.17(11)
+_cell_angle_beta 107.2(3)
+_cell_angle_gamma 100.60(19)
+_cell_volume 325(4)
+"""
+
+CUBIC = """
+data_cubic
+_cell.length_a 10.0
+_cell.length_a_esd 0.01
+_cell.length_b 10.0
+_cell.length_b_esd 0.01
+_cell.length_c 10.0
+_cell.length_c_esd 0.01
+_cell.angle_alpha 90
+_cell.angle_beta 90
+_cell.angle_gamma 90
+"""
+
+
+def test_parameters_and_su():
+    """Cell parameters and their sus are read from core CIF tags."""
+    cell = cif_parser.parse(ALBITE).first_block().unit_cell()
+    assert cell.a == pytest.approx(7.13)
+    assert cell.gamma == pytest.approx(100.60)
+    assert cell.su == pytest.approx((0.06, 0.05, 0.04, 0.11, 0.3, 0.19))
+
+
+def test_volume_su_matches_published():
+    """The propagated volume su agrees with the one in the file."""
+    cell = cif_parser.parse(ALBITE).first_block().unit_cell()
+    assert cell.volume == pytest.approx(325, abs=4)
+    assert cell.volume_su == pytest.approx(4, abs=0.5)
+
+
+def test_mmcif_cell_and_distance_su():
+    """mmCIF _esd tags are read, and distances carry the cell su."""
+    cell = cif_parser.parse(CUBIC).first_block().unit_cell()
+    assert cell.volume == pytest.approx(1000)
+    assert cell.distance([0, 0, 0], [0.5, 0, 0]) == pytest.approx(5.0)
+    d, su = cell.distance([0, 0, 0], [0.5, 0, 0], with_su=True)
+    assert d == pytest.approx(5.0)
+    assert su == pytest.approx(0.005)
+
+
+def test_coordinate_conversion():
+    """Fractional and Cartesian coordinates convert both ways."""
+    cell = cif_parser.parse(CUBIC).first_block().unit_cell()
+    assert cell.frac_to_cart([0.1, 0.2, 0.3]) == pytest.approx([1.0, 2.0, 3.0])
+    assert cell.cart_to_frac([1.0, 2.0, 3.0]) == pytest.approx([0.1, 0.2, 0.3])
+
+
+def test_missing_cell_is_none():
+    """Blocks without a complete cell give None."""
+    block = cif_parser.parse("data_x\n_cell_length_a 5.0\n").first_block()
+    assert block.unit_cell() is None
+    assert "UnitCell(a=10" in repr(cif_parser.parse(CUBIC).first_block().unit_cell())
+
+
+def test_metric_tensor_and_matrices():
+    """The matrices are nested lists that agree with each other."""
+    cell = cif_parser.parse(CUBIC).first_block().unit_cell()
+    assert cell.metric_tensor == pytest.approx(
+        [[100.0, 0.0, 0.0], [0.0, 100.0, 0.0], [0.0, 0.0, 100.0]], abs=1e-9
+    )
+    g_star = cell.reciprocal_metric_tensor
+    assert g_star[0][0] == pytest.approx(0.01)
+    m = cell.orthogonalization_matrix
+    f = cell.fractionalization_matrix
+    assert m[0][0] == pytest.approx(10.0)
+    assert f[0][0] == pytest.approx(0.1)
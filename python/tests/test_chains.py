@@ -0,0 +1,45 @@
+"""Tests for the label and author residue numbering of PDBx blocks."""
+
+from pathlib import Path
+
+import cif_parser
+
+FIXTURE = Path(__file__).parents[2] / "tests" / "fixtures" / "chains" / "antibody_fragment.cif"
+
+
+def antibody():
+    return cif_parser.parse_file(str(FIXTURE)).first_block()
+
+
+def test_auth_to_label():
+    """Insertion codes and heteroatoms map to their label ids."""
+    chains = antibody().chain_map()
+    assert chains.auth_to_label("H", 52) == ("A", 3)
+    assert chains.auth_to_label("H", 52, "B") == ("A", 5)
+    assert chains.auth_to_label("H", 301) == ("C", None)
+    assert chains.auth_to_label("A", 50) is None
+    assert len(chains) == 14
+
+
+def test_label_to_auth():
+    """Label ids map back, with every water of a chain for seq None."""
+    chains = antibody().chain_map()
+    assert chains.label_to_auth("A", 4) == [("H", 52, "A")]
+    assert chains.label_to_auth("D") == [("H", 401, None), ("H", 402, None)]
+    assert chains.label_to_auth("B", 4) == [("L", 4, None)]
+
+
+def test_select():
+    """Atom sites are selected by author chain and residue range."""
+    block = antibody()
+    selected = block.select(auth_chain="H", auth_seq=(52, 53))
+    assert len(selected) == 8
+    assert selected.tags == block.get_loop(0).tags
+    assert len(block.select("L")) == 7
+
+
+def test_no_atom_sites():
+    """Blocks without atom sites have no chain map."""
+    block = cif_parser.parse("data_x\n_cell_length_a 5\n").first_block()
+    assert block.chain_map() is None
+    assert block.select("A", (1, 2)) is None
@@ -0,0 +1,41 @@
+"""Tests for Document.to_json, CIF-JSON output."""
+
+import json
+
+import pytest
+
+import cif_parser
+
+CIF = """data_X
+_cell_length_a 5.431
+_cell_volume ?
+loop_
+_atom_site_label
+_atom_site_occupancy
+Si1 1
+O1 .
+"""
+
+
+def test_cif_json_layout():
+    """Blocks hold lists of values under lowercased names, with metadata."""
+    data = json.loads(cif_parser.parse(CIF).to_json())["CIF-JSON"]
+    assert data["Metadata"]["schema-name"] == "CIF-JSON"
+    assert data["Metadata"]["cif-version"] == "1.1"
+    block = data["x"]
+    assert block["_cell_length_a"] == [5.431]
+    assert block["_atom_site_label"] == ["Si1", "O1"]
+
+
+def test_special_values():
+    """? is the string '\\?' and . is null."""
+    block = json.loads(cif_parser.parse(CIF).to_json())["CIF-JSON"]["x"]
+    assert block["_cell_volume"] == ["\\?"]
+    assert block["_atom_site_occupancy"] == [1, None]
+
+
+def test_names_differing_only_in_case():
+    """Blocks that would share a lowercased key raise rather than drop one."""
+    doc = cif_parser.parse("data_a\n_x 1\ndata_A\n_y 2\n")
+    with pytest.raises(ValueError, match="CIF-JSON key"):
+        doc.to_json()
@@ -0,0 +1,95 @@
+"""Tests for read-only column buffers shared with memoryview and numpy."""
+
+import math
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+MIXED = Path(__file__).parents[2] / "tests" / "fixtures" / "mixed_loop.cif"
+
+CIF = """data_refl
+loop_
+_refln_index_h
+_refln_intensity_meas
+_refln_status
+1 120.5(3) o
+2 ?        o
+3 8.25     x
+"""
+
+
+@pytest.fixture
+def loop():
+    return cif_parser.parse(CIF).first_block().get_loop(0)
+
+
+def test_memoryview(loop):
+    """The buffer is a one-dimensional array of doubles."""
+    view = memoryview(loop.column_buffer("_refln_intensity_meas"))
+    assert view.format == "d"
+    assert view.itemsize == 8
+    assert view.shape == (3,)
+    assert view.readonly
+    assert view[0] == 120.5
+    assert math.isnan(view[1])
+    assert view[2] == 8.25
+
+
+def test_buffer_is_read_only(loop):
+    """Writing through the view fails and leaves the loop unchanged."""
+    view = memoryview(loop.column_buffer("_refln_intensity_meas"))
+    with pytest.raises(TypeError):
+        view[0] = 0.0
+    assert loop.get_by_tag(0, "_refln_intensity_meas").text == "120.5(3)"
+
+
+def test_numpy_view_is_read_only(loop):
+    """numpy reads the buffer in place, but cannot write to it."""
+    np = pytest.importorskip("numpy")
+    array = np.frombuffer(loop.column_buffer("_refln_intensity_meas"))
+    assert array.dtype == np.float64
+    assert not array.flags.writeable
+    with pytest.raises(ValueError):
+        array[0] = 0.0
+    assert memoryview(loop.column_buffer("_refln_intensity_meas"))[0] == 120.5
+
+
+def test_buffer_is_cached(loop):
+    """Asking twice shares the same numbers."""
+    first = memoryview(loop.column_buffer("_refln_index_h"))
+    second = memoryview(loop.column_buffer("_refln_index_h"))
+    assert first.tolist() == second.tolist() == [1.0, 2.0, 3.0]
+    assert len(loop.column_buffer("_refln_index_h")) == 3
+
+
+def test_buffer_outlives_document():
+    """The buffer keeps its numbers after the document is gone."""
+    buffer = cif_parser.parse(CIF).first_block().get_loop(0).column_buffer("_refln_index_h")
+    assert memoryview(buffer).tolist() == [1.0, 2.0, 3.0]
+
+
+def test_non_numeric_column(loop):
+    """A column with text names the first row that is not a number."""
+    with pytest.raises(TypeError, match="row 0 is o"):
+        loop.column_buffer("_refln_status")
+    with pytest.raises(KeyError):
+        loop.column_buffer("_refln_missing")
+
+
+def test_changed_block_gives_fresh_buffers():
+    """Loops taken after a change have their own buffers."""
+    block = cif_parser.parse_file(str(MIXED)).first_block()
+    before = block.find_loop("_atom_site_label")
+    u11 = memoryview(before.column_buffer("_atom_site_aniso_U_11")).tolist()
+
+    block.repair_mixed_loops()
+
+    after = block.find_loop("_atom_site_label")
+    with pytest.raises(KeyError):
+        after.column_buffer("_atom_site_aniso_U_11")
+    aniso = block.find_loop("_atom_site_aniso_U_11")
+    assert memoryview(aniso.column_buffer("_atom_site_aniso_U_11")).tolist() == u11
+    # The old loop is a snapshot, and its buffer still works
+    assert memoryview(before.column_buffer("_atom_site_aniso_U_11")).tolist() == u11
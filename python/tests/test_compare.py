@@ -0,0 +1,40 @@
+"""Tests for comparing numbers with standard uncertainties."""
+
+import math
+
+import cif_parser
+
+
+def test_sigmas_from_one_su():
+    """The difference is measured in the su of the number that has one."""
+    result = cif_parser.compare(1.5406, 1.5410, su_a=0.0003)
+    assert math.isclose(result.sigmas, 4 / 3)
+    assert result.equal
+    assert result
+
+    assert not cif_parser.compare(1.5406, 1.5410, su_a=0.0001)
+
+
+def test_sus_combined_in_quadrature():
+    """Two sus add in quadrature, and n_sigma is the inclusive limit."""
+    result = cif_parser.compare(0.0, 10.0, su_a=3.0, su_b=4.0, n_sigma=2.0)
+    assert result.sigmas == 2.0
+    assert result.equal
+    assert not cif_parser.compare(0.0, 10.0, su_a=3.0, su_b=4.0, n_sigma=1.5)
+
+
+def test_without_su_uses_relative_tolerance():
+    """Numbers without sus are equal within the relative tolerance."""
+    assert cif_parser.compare(999.5, 1000.0)
+    assert not cif_parser.compare(998.0, 1000.0)
+    assert cif_parser.compare(998.0, 1000.0, relative_tolerance=0.01)
+    assert cif_parser.compare(999.5, 1000.0, su_a=0.0, su_b=0.0)
+
+
+def test_incomparable():
+    """A negative su or a NaN cannot be compared."""
+    result = cif_parser.compare(1.0, 1.0, su_a=-1.0)
+    assert result.sigmas is None
+    assert not result.equal
+    assert cif_parser.compare(float("nan"), 1.0).sigmas is None
+    assert "incomparable" in repr(result)
@@ -0,0 +1,39 @@
+"""Tests for reading compressed CIF files."""
+
+import bz2
+import gzip
+
+import pytest
+
+import cif_parser
+
+CIF = b"data_1abc\n_cell_length_a 5.431\nloop_\n_atom_site_label\nSi1\nO1\n"
+
+
+@pytest.mark.parametrize(
+    "name, compress",
+    [("1abc.cif.gz", gzip.compress), ("1abc.cif.bz2", bz2.compress)],
+)
+def test_compressed_file_reads_as_plain(tmp_path, name, compress):
+    """parse_file decompresses .gz and .bz2 files."""
+    path = tmp_path / name
+    path.write_bytes(compress(CIF))
+    doc = cif_parser.parse_file(path)
+    assert doc.first_block().get_item("_cell_length_a").numeric == 5.431
+    assert len(doc.first_block().loops[0]) == 2
+    assert doc.source.byte_len == path.stat().st_size
+
+
+def test_zstd_file(tmp_path):
+    """parse_file decompresses .zst files."""
+    zstandard = pytest.importorskip("zstandard")
+    path = tmp_path / "1abc.cif.zst"
+    path.write_bytes(zstandard.ZstdCompressor().compress(CIF))
+    assert cif_parser.parse_file(path).first_block().name == "1abc"
+
+
+def test_gzip_content_without_extension(tmp_path):
+    """Compressed content is found from its first bytes."""
+    path = tmp_path / "1abc.cif"
+    path.write_bytes(gzip.compress(CIF))
+    assert cif_parser.parse_file(path).first_block().name == "1abc"
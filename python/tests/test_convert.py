@@ -0,0 +1,42 @@
+"""Tests for mmCIF <-> core CIF translation."""
+
+import warnings
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+FRAGMENT = Path(__file__).parents[2] / "tests" / "fixtures" / "mmcif_fragment.cif"
+
+
+def test_to_core_cif_warns_about_untranslated_tags():
+    """Untranslatable tags are reported, and the rest is still returned."""
+    block = cif_parser.parse_file(str(FRAGMENT)).first_block()
+    with pytest.warns(UserWarning, match="label_entity_id"):
+        core = block.to_core_cif()
+    assert core.get_item("_cell_length_a").numeric == pytest.approx(40.96)
+    labels = [v.text for v in core.find_loop("_atom_site_label").get_column("_atom_site_label")]
+    assert labels[0] == "N_THR1_A"
+
+
+def test_strict_raises():
+    """strict=True turns the report into an error."""
+    block = cif_parser.parse_file(str(FRAGMENT)).first_block()
+    with pytest.raises(ValueError, match="label_entity_id"):
+        block.to_core_cif(strict=True)
+
+
+def test_round_trip_preserves_coordinates():
+    """mmCIF -> core -> mmCIF keeps Cartesian coordinates to 1e-4 A."""
+    block = cif_parser.parse_file(str(FRAGMENT)).first_block()
+    with warnings.catch_warnings():
+        warnings.simplefilter("ignore")
+        core = block.to_core_cif()
+    mmcif = core.to_mmcif(strict=True)
+
+    for axis in "xyz":
+        tag = f"_atom_site.Cartn_{axis}"
+        before = [v.numeric for v in block.find_loop(tag).get_column(tag)]
+        after = [v.numeric for v in mmcif.find_loop(tag).get_column(tag)]
+        assert after == pytest.approx(before, abs=1e-4)
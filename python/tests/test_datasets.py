@@ -0,0 +1,53 @@
+"""Tests for splitting loops and blocks into datasets by an id column."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures" / "datasets"
+
+
+def two_scans():
+    return cif_parser.parse_file(str(FIXTURES / "two_scans.cif")).first_block()
+
+
+def test_datasets():
+    """Each scan gets its own rows, the shared loops, and the block's items."""
+    block = two_scans()
+    datasets = block.datasets("_diffrn_id")
+    assert list(datasets) == ["scan1", "scan2"]
+
+    scan1 = datasets["scan1"]
+    assert scan1.name == "lab6_heating"
+    assert scan1.get_item("_cell_length_a").numeric == pytest.approx(4.1569)
+    counts = scan1.find_loop("_pd_meas_counts_total").get_column("_pd_meas_counts_total")
+    assert [value.numeric for value in counts] == [102, 98, 131]
+
+    scan2 = datasets["scan2"]
+    assert scan2.find_loop("_pd_peak_2theta_centroid") is None
+    assert len(scan2.find_loop("_atom_type_symbol")) == 2
+
+
+def test_no_datasets():
+    """A block without the id column has no datasets."""
+    assert two_scans().datasets("_pd_block_id") == {}
+
+
+def test_partition_by():
+    """A loop splits by one column, which can be left out."""
+    loop = two_scans().find_loop("_pd_meas_2theta_scan")
+    parts = loop.partition_by("_diffrn_id", keep_column=False)
+    assert list(parts) == ["scan1", "scan2"]
+    assert parts["scan2"].tags == ["_pd_meas_2theta_scan", "_pd_meas_counts_total"]
+    assert len(parts["scan2"]) == 3
+    assert "_diffrn_id" in loop.partition_by("_diffrn_id")["scan1"].tags
+
+
+def test_partition_by_missing_tag():
+    """Splitting by a column the loop lacks raises KeyError."""
+    loop = two_scans().find_loop("_atom_type_symbol")
+    with pytest.raises(KeyError) as info:
+        loop.partition_by("_diffrn_id")
+    assert info.value.code == "E0011"
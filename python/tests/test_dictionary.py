@@ -0,0 +1,72 @@
+"""Tests for layered dictionaries and dictionary validation."""
+
+from pathlib import Path
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures" / "dictionary"
+
+
+def load():
+    core = cif_parser.Dictionary.from_file(FIXTURES / "core_mini.dic")
+    local = cif_parser.Dictionary.from_file(FIXTURES / "local.dic")
+    return core, local
+
+
+def test_read_dictionary():
+    """Item definitions are read and found by any of their names."""
+    core, _ = load()
+    assert core.title == "CORE_MINI"
+    assert core.version == "3.3.0"
+    assert len(core) == 4
+    assert "_CELL_LENGTH_A" in core
+    assert "_cell.length_b" not in core
+    assert core.get("_symmetry_Int_Tables_number")["range"] == (1.0, 230.0)
+
+
+def test_describe_uses_topmost_definition():
+    """The local dictionary overrides the core one for the tags it defines."""
+    core, local = load()
+    stack = cif_parser.DictionaryStack([core, local])
+    assert stack.titles == ["CORE_MINI", "LOCAL_EXT"]
+
+    flag = stack.describe("_atom_site_calc_flag")
+    assert flag["dictionary"] == "LOCAL_EXT"
+    assert "riding" in flag["enumeration"]
+    assert flag["description"].startswith("As in the core dictionary")
+
+    cell = stack.describe("_cell_length_a")
+    assert cell["dictionary"] == "CORE_MINI"
+    assert cell["type"] == "Real"
+    assert cell["units"] == "angstroms"
+    assert cell["description"] == "Unit-cell length a corresponding to the structure reported."
+    assert stack.describe("_undefined_item") is None
+
+    layers = stack.definitions_of("_atom_site.calc_flag")
+    assert [d["dictionary"] for d in layers] == ["LOCAL_EXT", "CORE_MINI"]
+
+
+def test_validate_records_definition():
+    """Each issue names the dictionary and save frame it was checked against."""
+    core, local = load()
+    doc = cif_parser.parse_file(str(FIXTURES / "sample.cif"))
+
+    core_only = cif_parser.DictionaryStack()
+    core_only.push(core)
+    riding = [i for i in doc.validate(core_only) if i["value"] == "riding"]
+    assert len(riding) == 1
+    assert riding[0]["row"] == 1
+    assert riding[0]["kind"] == "enumeration"
+    assert riding[0]["dictionary"] == "CORE_MINI"
+    assert riding[0]["definition"] == "atom_site.calc_flag"
+    assert riding[0]["category"] == "atom_site"
+
+    issues = doc.validate(cif_parser.DictionaryStack([core, local]))
+    assert not any(i["value"] == "riding" for i in issues)
+    guess = next(i for i in issues if i["value"] == "guess")
+    assert guess["dictionary"] == "LOCAL_EXT"
+    assert guess["definition"] == "local_atom_site.calc_flag"
+    assert len(issues) == 5
+
+    second = doc["second"].validate(cif_parser.DictionaryStack([core, local]))
+    assert [(i["tag"], i["kind"]) for i in second] == [("_cell.length_a", "type")]
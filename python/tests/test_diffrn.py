@@ -0,0 +1,55 @@
+"""Tests for orientation matrices and goniometer axes."""
+
+from pathlib import Path
+
+import cif_parser
+
+ROOT = Path(__file__).parents[2] / "tests"
+ALBITE = ROOT / "example_cifs" / "albite" / "pets2_albite.cif_pets"
+IMGCIF = ROOT / "fixtures" / "imgcif_axes.cif"
+
+
+def test_orientation_matrix():
+    """UB is returned as rows, and agrees with the cell."""
+    block = cif_parser.parse_file(str(ALBITE)).first_block()
+    ub = block.orientation_matrix()
+    assert ub[0] == [-0.13002, 0.03768, -0.02551]
+    assert ub[2][2] == -0.15420
+
+    check = block.check_ub()
+    assert check["consistent"]
+    assert check["sigmas"] is None  # the PETS2 cell has no sus
+    assert abs(check["discrepancy"]) < 1e-3
+    assert abs(check["determinant"] - check["reciprocal_volume"]) < 1e-6
+
+
+def test_check_ub_tolerance():
+    """A tight tolerance flags a small discrepancy."""
+    block = cif_parser.parse_file(str(ALBITE)).first_block()
+    assert not block.check_ub(tolerance=1e-6)["consistent"]
+
+
+def test_missing_matrix():
+    """Blocks without a UB matrix give None."""
+    block = cif_parser.parse("data_x\n_cell_length_a 5\n").first_block()
+    assert block.orientation_matrix() is None
+    assert block.check_ub() is None
+    assert block.goniometer_axes() == []
+
+
+def test_goniometer_axes():
+    """Axes named by the measurement loop come back in order."""
+    block = cif_parser.parse_file(str(IMGCIF)).first_block()
+    axes = block.goniometer_axes()
+    assert [axis["id"] for axis in axes] == [
+        "GONIOMETER_OMEGA",
+        "GONIOMETER_KAPPA",
+        "GONIOMETER_PHI",
+    ]
+    assert axes[0] == {
+        "id": "GONIOMETER_OMEGA",
+        "type": "rotation",
+        "vector": [1.0, 0.0, 0.0],
+        "depends_on": None,
+    }
+    assert axes[2]["depends_on"] == "GONIOMETER_KAPPA"
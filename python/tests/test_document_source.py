@@ -0,0 +1,44 @@
+"""Tests for Document.source file provenance."""
+
+import hashlib
+from datetime import datetime, timezone
+
+import pytest
+
+import cif_parser
+
+
+def test_parsed_string_has_no_source():
+    """Documents parsed from strings don't know where they came from."""
+    doc = cif_parser.parse("data_a\n_x 1\n")
+    assert doc.source is None
+
+
+def test_file_source(simple_cif):
+    """parse_file records the path, size, hash, and parse time."""
+    before = datetime.now(timezone.utc)
+    doc = cif_parser.parse_file(str(simple_cif))
+    content = simple_cif.read_bytes()
+
+    source = doc.source
+    assert isinstance(source, cif_parser.DocumentSource)
+    assert source.path == str(simple_cif)
+    assert source.byte_len == len(content)
+    assert source.sha256 == hashlib.sha256(content).hexdigest()
+    assert source.parsed_at.tzinfo is not None
+    assert source.parsed_at >= before.replace(microsecond=0)
+
+
+def test_error_includes_path(tmp_path):
+    """Errors from files name the file."""
+    path = tmp_path / "broken.cif"
+    path.write_text("data_a\n_x 'unclosed\n")
+    with pytest.raises(ValueError, match="broken.cif"):
+        cif_parser.parse_file(str(path))
+
+
+def test_missing_file_error_includes_path(tmp_path):
+    """IO errors name the missing file."""
+    path = tmp_path / "missing.cif"
+    with pytest.raises(IOError, match="missing.cif"):
+        cif_parser.parse_file(str(path))
@@ -0,0 +1,48 @@
+"""Tests for repeated data item tags."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+AUTHORS = Path(__file__).parents[2] / "tests" / "fixtures" / "repeated_authors.cif"
+
+
+def test_collapse_repeated_authors():
+    """Repeated authors become a one-column loop, in order."""
+    doc = cif_parser.parse_file(str(AUTHORS), duplicate_tags="collapse")
+    block = doc.first_block()
+    names = [v.text for v in block.get_all("_publ_author_name")]
+    assert names == ["Smith, Jane A.", "Jones, K.", "O'Brien, P. Q."]
+    assert block.find_loop("_publ_author_name").tags == ["_publ_author_name"]
+    assert len(doc.warnings) == 2
+    assert "_publ_author_name" in doc.warnings[0]
+
+
+def test_get_item_ignores_loop_columns():
+    """Collapsed tags and real one-column loops are read with get_all."""
+    doc = cif_parser.parse_file(str(AUTHORS), duplicate_tags="collapse")
+    assert doc.first_block().get_item("_publ_author_name") is None
+    block = cif_parser.parse("data_x\nloop_ _publ_author_name 'Smith, J.' 'Doe, A.'\n").first_block()
+    assert block.get_item("_publ_author_name") is None
+    assert len(block.get_all("_publ_author_name")) == 2
+
+
+def test_get_all_items_and_loops():
+    """get_all returns single items and loop columns alike."""
+    block = cif_parser.parse("data_x\n_title t\nloop_\n_n\n_m\n1 2\n3 4\n").first_block()
+    assert [v.text for v in block.get_all("_title")] == ["t"]
+    assert [v.numeric for v in block.get_all("_m")] == [2.0, 4.0]
+    assert block.get_all("_missing") == []
+
+
+def test_default_and_error_policies():
+    """The default keeps the last value; "error" raises."""
+    doc = cif_parser.parse_file(str(AUTHORS))
+    assert doc.first_block().get_item("_publ_author_name").text == "O'Brien, P. Q."
+    assert doc.warnings == []
+    with pytest.raises(ValueError, match="Duplicate tag"):
+        cif_parser.parse_file(str(AUTHORS), duplicate_tags="error")
+    with pytest.raises(ValueError, match="duplicate_tags must be"):
+        cif_parser.parse("data_x\n", duplicate_tags="first")
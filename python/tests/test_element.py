@@ -0,0 +1,40 @@
+"""Tests for reading atom type symbols."""
+
+import pytest
+
+import cif_parser
+
+
+@pytest.mark.parametrize(
+    ("symbol", "element", "charge"),
+    [
+        ("Fe3+", "Fe", 3),
+        ("O-2", "O", -2),
+        ("FE2+", "Fe", 2),
+        ("Na+", "Na", 1),
+        ("cl", "Cl", None),
+    ],
+)
+def test_charge_conventions(symbol, element, charge):
+    """Both charge orders and any case give the same element and charge."""
+    parsed = cif_parser.parse_element(symbol)
+    assert parsed["element"] == element
+    assert parsed["charge"] == charge
+    assert parsed["isotope"] is None
+
+
+def test_hydrogen_isotopes():
+    """D and T are hydrogen, with their mass numbers."""
+    assert cif_parser.parse_element("D") == {
+        "element": "H",
+        "atomic_number": 1,
+        "charge": None,
+        "isotope": 2,
+    }
+    assert cif_parser.parse_element("T")["isotope"] == 3
+
+
+def test_nonsense_raises():
+    """Unknown symbols raise ValueError naming the token."""
+    with pytest.raises(ValueError, match="'Wat'"):
+        cif_parser.parse_element("Wat")
@@ -0,0 +1,26 @@
+"""Tests for files with byte-order marks, CRLF newlines, and UTF-16."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+ENCODINGS = Path(__file__).parents[2] / "tests" / "fixtures" / "encodings"
+
+
+def test_newline_styles_and_bom_parse_the_same():
+    """BOM and CR/CRLF variants give the same items and text fields."""
+    reference = cif_parser.parse_file(str(ENCODINGS / "utf8_lf.cif")).first_block()
+    for name in ["utf8_crlf.cif", "utf8_cr.cif", "utf8_bom_crlf.cif"]:
+        block = cif_parser.parse_file(str(ENCODINGS / name)).first_block()
+        assert block.name == "encoding", name
+        assert block.item_keys == reference.item_keys, name
+        comment = block.get_item("_publ_section_comment").text
+        assert comment == "First line of the comment.\nSecond line, after a line break."
+
+
+def test_utf16_is_reported():
+    """A UTF-16 file gets an error that names the encoding."""
+    with pytest.raises(ValueError, match="UTF-16 encoded"):
+        cif_parser.parse_file(str(ENCODINGS / "utf16le_crlf.cif"))
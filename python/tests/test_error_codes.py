@@ -0,0 +1,82 @@
+"""Tests for the codes and exception types of parse errors."""
+
+import pytest
+
+import cif_parser
+
+
+@pytest.mark.parametrize(
+    ("call", "code", "exception"),
+    [
+        (lambda: cif_parser.parse("data_a\n_x 'a' 'b' 'c\n"), "E0001", ValueError),
+        (lambda: cif_parser.parse_file("does-not-exist.cif"), "E0002", OSError),
+        (
+            lambda: cif_parser.parse("data_a\n_x 1\n_x 2\n", duplicate_tags="error"),
+            "E0005",
+            ValueError,
+        ),
+        (lambda: cif_parser.parse("data_a\nloop_\n_x\n_y\n1 2 3\n"), "E0006", ValueError),
+        (
+            lambda: cif_parser.parse("#\\#CIF_2.0\ndata_a\n_x " + "[" * 1000 + "]" * 1000),
+            "E0007",
+            ValueError,
+        ),
+        (lambda: cif_parser.parse("data_a\n_x 1\n2\n"), "E0008", ValueError),
+        (lambda: cif_parser.parse_element("Wat"), "E0009", ValueError),
+        (
+            lambda: cif_parser.parse("data_a\nloop_\n_x\n1\n")[0].get_loop(0).partition_by("_y"),
+            "E0011",
+            KeyError,
+        ),
+    ],
+)
+def test_code_and_exception_type(call, code, exception):
+    """Each error is raised as its built-in exception with its code."""
+    with pytest.raises(exception) as info:
+        call()
+    assert info.value.code == code
+    assert f"[{code}]" in str(info.value)
+
+
+def test_location_attributes():
+    """Errors carry the line and column they point at."""
+    with pytest.raises(ValueError) as info:
+        cif_parser.parse("data_a\nloop_\n_x\n_y\n1 2 3\n")
+    assert (info.value.line, info.value.column) == (2, 1)
+    assert info.value.path is None
+
+
+def test_offset_and_excerpt():
+    """Errors point into the input by byte offset and quote the line."""
+    content = "data_\u00e9\nloop_\n_x\n_y\n1 2 3\n"
+    with pytest.raises(ValueError) as info:
+        cif_parser.parse(content)
+    assert info.value.offset == len("data_\u00e9\n".encode())
+    assert info.value.excerpt == "  |\n2 | loop_\n  | ^"
+
+    with pytest.raises(ValueError) as info:
+        cif_parser.parse_element("Wat")
+    assert info.value.offset is None
+    assert info.value.excerpt is None
+
+
+def test_path_attribute(tmp_path):
+    """Errors from files name the file."""
+    path = tmp_path / "broken.cif"
+    path.write_text("data_a\n_x 1\n2\n")
+    with pytest.raises(ValueError) as info:
+        cif_parser.parse_file(str(path))
+    assert info.value.path == str(path)
+    assert info.value.line == 3
+    assert info.value.excerpt == "  |\n3 | 2\n  | ^"
+    assert str(info.value).startswith(f"{path}: [E0008]")
+
+
+def test_encoding_error(tmp_path):
+    """Files that are not UTF-8 raise UnicodeError, which is a ValueError."""
+    path = tmp_path / "latin1.cif"
+    path.write_bytes(b"data_a\n_x caf\xe9\n")
+    with pytest.raises(UnicodeError) as info:
+        cif_parser.parse_file(str(path))
+    assert info.value.code == "E0003"
+    assert isinstance(info.value, ValueError)
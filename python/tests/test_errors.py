@@ -344,3 +344,33 @@ class TestEdgeCases:
         assert descriptions[0].text == "single quoted"
         assert descriptions[1].text == "double quoted"
         assert descriptions[2].text == "unquoted"
+
+
+class TestHostileInput:
+    """Inputs distilled from fuzzing must raise exceptions, never abort."""
+
+    @pytest.mark.parametrize(
+        "content",
+        [
+            "data_a\n_x 'unterminated",
+            "data_a\n_x\n;unterminated text field",
+            "data_a\n_",
+            "data_a\nloop_\n",
+            "data_a\r\r\r_x",
+            "#\\#CIF_2.0\ndata_a\n_x [1 2",
+            "#\\#CIF_2.0\ndata_a\n_x " + "[" * 100_000 + "]" * 100_000 + "\n",
+            "data_a\n_x " + "{" * 100_000 + "\n",
+        ],
+    )
+    def test_malformed_input_raises(self, content):
+        """Malformed input either parses or raises ValueError."""
+        try:
+            cif_parser.parse(content)
+        except ValueError:
+            pass
+
+    def test_deep_nesting_reports_limit(self):
+        """Excessive list nesting is reported as a parse error."""
+        content = "#\\#CIF_2.0\ndata_a\n_x " + "[" * 1000 + "]" * 1000 + "\n"
+        with pytest.raises(ValueError, match="nesting"):
+            cif_parser.parse(content)
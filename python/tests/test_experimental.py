@@ -0,0 +1,37 @@
+"""Tests for experimental conditions and the density check."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+EXPERIMENTAL = Path(__file__).parents[2] / "tests" / "fixtures" / "experimental"
+
+
+def test_core_names():
+    """Core names give values with sus, and a consistent density."""
+    block = cif_parser.parse_file(str(EXPERIMENTAL / "core.cif")).first_block()
+    exptl = block.experimental()
+    assert exptl.colour == "colourless"
+    assert exptl.habit == "plate"
+    assert exptl.temperature == (150.0, 2.0)
+    assert exptl.size_max == (0.42, None)
+    assert exptl.crystal_volume == pytest.approx(0.0084)
+    assert exptl.density_from_formula == pytest.approx(1.3061, abs=1e-4)
+    assert exptl.check() == []
+
+
+def test_mmcif_names_and_check():
+    """mmCIF names are read, and disagreeing values are reported."""
+    block = cif_parser.parse_file(str(EXPERIMENTAL / "mmcif.cif")).first_block()
+    exptl = block.experimental()
+    assert exptl.temperature == (100.0, 1.0)
+    assert exptl.crystal_volume is None
+    issues = exptl.check()
+    assert [issue["tag"] for issue in issues] == [
+        "_exptl_crystal_density_diffrn",
+        "_exptl_absorpt_correction_T_min",
+    ]
+    assert issues[0]["value"] == "1.45"
+    assert repr(exptl) == "Experimental(colour='colourless', habit='prism', temperature=100)"
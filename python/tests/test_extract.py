@@ -0,0 +1,30 @@
+"""Tests for extracting tags from many files at once."""
+
+from pathlib import Path
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures"
+TAGS = ["_cell_volume", "_space_group_IT_number"]
+
+
+def test_one_dict_per_block():
+    """Each block gives a dict of its tag values, matched ignoring case."""
+    rows = cif_parser.extract([FIXTURES / "extract_blocks.cif"], TAGS)
+    assert [row["block"] for row in rows] == ["first", "second"]
+    assert rows[0]["_space_group_IT_number"].numeric == 14
+    assert rows[0]["error"] is None
+    assert rows[1]["_cell_volume"].numeric == 410.2
+    assert rows[1]["_space_group_IT_number"] is None
+
+
+def test_errors_are_kept_inline():
+    """A file that fails to parse keeps its place with the error."""
+    paths = [str(FIXTURES / "missing.cif"), str(FIXTURES / "extract_blocks.cif")]
+    rows = cif_parser.extract(paths, TAGS, first_block_only=True, threads=2)
+    assert len(rows) == 2
+    assert rows[0]["path"] == paths[0]
+    assert rows[0]["block"] is None
+    assert "missing.cif" in rows[0]["error"]
+    assert rows[0]["_cell_volume"] is None
+    assert rows[1]["block"] == "first"
@@ -0,0 +1,48 @@
+"""Tests for flattening documents into entries for search indexing."""
+
+import json
+from pathlib import Path
+
+import cif_parser
+
+SEARCH = Path(__file__).parents[2] / "tests" / "fixtures" / "flatten" / "search.cif"
+
+
+def test_flatten_paths_and_values():
+    """Entries have unique paths, text, numbers, and kinds."""
+    doc = cif_parser.parse_file(str(SEARCH))
+    entries = doc.flatten()
+    paths = [entry["path"] for entry in entries]
+    assert len(paths) == len(set(paths))
+
+    by_path = {entry["path"]: entry for entry in entries}
+    a = by_path["Sample_1/_cell_length_a"]
+    assert a["text"] == "6.1224(4)"
+    assert a["number"] == 6.1224
+    assert a["kind"] == "text"
+    assert a["row"] is None
+    assert by_path["Sample_1/_diffrn_scan_id[2]"]["text"] == "3"
+    assert by_path["Sample_1/save_scan_settings/_scan_exposure_time"]["frame"] == "scan_settings"
+
+
+def test_flatten_options():
+    """Loops can be left out, text cut short, and tags lowercased."""
+    doc = cif_parser.parse_file(str(SEARCH))
+    assert all(entry["row"] is None for entry in doc.flatten(include_loops=False))
+
+    short = {e["path"]: e for e in doc.flatten(max_text_len=10)}
+    abstract = short["Sample_1/_publ_section_abstract"]
+    assert abstract["truncated"]
+    assert abstract["text"] == "Blue tricl…"
+
+    lower = doc.flatten(lowercase_tags=True)
+    assert any(entry["path"] == "second/_cell_length_a" for entry in lower)
+
+
+def test_to_ndjson(tmp_path):
+    """One JSON object per line, the same as flatten()."""
+    doc = cif_parser.parse_file(str(SEARCH))
+    out = tmp_path / "entries.ndjson"
+    doc.to_ndjson(out, include_loops=False)
+    lines = [json.loads(line) for line in out.read_text(encoding="utf-8").splitlines()]
+    assert lines == doc.flatten(include_loops=False)
@@ -0,0 +1,40 @@
+"""Tests for canonical formatting of CIF text."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures" / "formatter"
+
+
+def test_format_matches_expected_output():
+    """A commented file formats to the expected text, comments included."""
+    text = (FIXTURES / "commented.cif").read_text()
+    expected = (FIXTURES / "commented_formatted.cif").read_text()
+    assert cif_parser.format(text) == expected
+
+
+def test_format_is_idempotent():
+    """Formatting formatted text changes nothing and keeps the data."""
+    text = (FIXTURES / "lists.cif").read_text()
+    once = cif_parser.format(text)
+    assert cif_parser.format(once) == once
+    assert cif_parser.parse(once).to_dict() == cif_parser.parse(text).to_dict()
+    assert "# in a list in the third row" in once
+
+
+def test_format_options():
+    """Alignment and comments can be turned off."""
+    text = "data_x\n_b 2 # note\n_long_tag 1\n"
+    assert "_b        2\n" in cif_parser.format(text)
+    plain = cif_parser.format(text, align=False, comments=False)
+    assert "_b 2\n" in plain
+    assert "#" not in plain.replace("#\\#CIF_1.1", "")
+
+
+def test_format_rejects_invalid_cif():
+    """Text that does not parse raises ValueError."""
+    with pytest.raises(ValueError):
+        cif_parser.format("data_x\n_a\n")
@@ -0,0 +1,34 @@
+"""Tests for dictionary fragments written as bare save frames."""
+
+from pathlib import Path
+
+import cif_parser
+import pytest
+
+FIXTURE = Path(__file__).parents[2] / "tests" / "fixtures" / "fragment" / "diffrn_extras.dic"
+
+
+def test_bare_frames_need_lenient_parsing():
+    """Strict parsing rejects frames before any data block."""
+    with pytest.raises(ValueError, match="allow_bare_frames"):
+        cif_parser.parse_file(FIXTURE)
+
+
+def test_fragment_frames_in_synthetic_block():
+    """The frames go in a block named after the file, with a warning."""
+    doc = cif_parser.parse_file(FIXTURE, lenient=True)
+    assert doc.bare_frames
+    assert doc.block_names == ["diffrn_extras"]
+    assert doc.first_block().num_frames == 2
+    assert len(doc.warnings) == 1
+
+    text = FIXTURE.read_text()
+    assert cif_parser.parse(text, lenient=True).block_names == ["_bare_frames_"]
+    assert not cif_parser.parse("data_a\n_x 1\n", lenient=True).bare_frames
+
+
+def test_dictionary_reads_fragment():
+    """Dictionaries accept fragments without being asked."""
+    dictionary = cif_parser.Dictionary.from_file(FIXTURE)
+    assert dictionary.title == "diffrn_extras"
+    assert "_diffrn_oven_setpoint" in dictionary
@@ -0,0 +1,54 @@
+"""Tests for database codes and DOIs."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+TESTS = Path(__file__).parents[2] / "tests"
+IDENTIFIERS = TESTS / "fixtures" / "identifiers"
+
+
+def test_cod_file():
+    """A COD file gives its COD code and paper DOI."""
+    doc = cif_parser.parse_file(str(TESTS / "example_cifs" / "urea" / "cod_urea.cif"))
+    ids = doc.first_block().identifiers()
+    assert ids.cod == "9011998"
+    assert ids.doi == "10.1107/S0021889875010965"
+    assert ids.csd is None
+    assert ids.others == [("_database_code_amcsd", "12843")]
+
+
+def test_csd_derived_file():
+    """CSD refcode and CCDC deposition number are read and searchable."""
+    doc = cif_parser.parse_file(str(IDENTIFIERS / "csd_paracetamol.cif"))
+    ids = doc.first_block().identifiers()
+    assert ids.csd == "HXACAN01"
+    assert ids.ccdc_deposition == "CCDC 1169420"
+    assert ids.to_dict() == {
+        "csd": "HXACAN01",
+        "ccdc_deposition": "CCDC 1169420",
+        "doi": "10.1107/S0567740876005669",
+    }
+    assert doc.find_by_identifier("ccdc_deposition", "1169420").name == "HXACAN01"
+    assert doc.find_by_identifier("csd", "OTHER") is None
+
+
+def test_pdbx_database_2():
+    """The _database_2 loop gives the PDB code and the entry DOI."""
+    doc = cif_parser.parse_file(str(IDENTIFIERS / "pdbx_database_2.cif"))
+    ids = doc.first_block().identifiers()
+    assert ids.pdb == "1CRN"
+    assert ids.dataset_doi == "10.2210/pdb1crn/pdb"
+    assert ids.doi == "10.1038/290107a0"
+    assert ids.others == [("WWPDB", "D_1000173897")]
+    assert "pdb='1CRN'" in repr(ids)
+
+
+def test_no_identifiers_and_bad_kind():
+    """A block without codes is falsy; unknown kinds are rejected."""
+    doc = cif_parser.parse("data_x\n_a 1\n")
+    assert not doc.first_block().identifiers()
+    with pytest.raises(ValueError, match="Unknown identifier kind"):
+        doc.find_by_identifier("isbn", "1")
@@ -0,0 +1,29 @@
+"""Tests for classifying files by kind."""
+
+from pathlib import Path
+
+import cif_parser
+
+KINDS = Path(__file__).parents[2] / "tests" / "fixtures" / "kinds"
+
+
+def test_kind_of_each_fixture():
+    """Each fixture is recognised as its kind."""
+    for name, kind in [
+        ("small_molecule", "small-molecule"),
+        ("mmcif", "mmcif"),
+        ("structure_factors", "structure-factors"),
+        ("powder", "powder"),
+        ("dictionary", "dictionary"),
+        ("imgcif", "imgcif"),
+        ("template", "template"),
+    ]:
+        doc = cif_parser.parse_file(str(KINDS / f"{name}.cif"))
+        assert doc.kind() == [kind], name
+
+
+def test_several_kinds_and_none():
+    """A magnetic structure is also a structure; a bare block is no kind."""
+    doc = cif_parser.parse_file(str(KINDS / "magcif.cif"))
+    assert doc.kind() == ["small-molecule", "magcif"]
+    assert cif_parser.parse("data_x\n_a 1\n").kind() == []
@@ -0,0 +1,42 @@
+"""Tests for text kept as large values above a size threshold."""
+
+import cif_parser
+
+LINE = "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVphYmNkZWZnaGlqa2xtbm9wcXJzdHV2d3h5ejAxMjM0"
+MAP = "\n".join([LINE] * 100)
+CIF = f"data_map\n_cell_length_a 5.43\n_map_data\n;\n{MAP}\n;\n_title 'short'\n"
+
+
+def test_large_value_is_read_on_request():
+    """Text above the threshold is left out of to_python() until read."""
+    doc = cif_parser.parse(CIF, large_value_threshold=1000)
+    value = doc.first_block().get_item("_map_data")
+    assert value.is_large
+    assert not value.is_text
+    assert value.value_type == "large_text"
+    assert value.to_python() is None
+    assert value.text is None
+    assert value.large_size == len(MAP)
+    assert value.read_large() == MAP
+
+
+def test_small_values_are_unchanged():
+    """Values under the threshold, and all values without one, are plain."""
+    doc = cif_parser.parse(CIF, large_value_threshold=1000)
+    title = doc.first_block().get_item("_title")
+    assert not title.is_large
+    assert title.large_size is None
+    assert title.read_large() is None
+    assert title.to_python() == "short"
+
+    plain = cif_parser.parse(CIF).first_block().get_item("_map_data")
+    assert not plain.is_large
+    assert plain.text == MAP
+
+
+def test_parse_file_threshold(tmp_path):
+    """parse_file accepts the threshold too."""
+    path = tmp_path / "map.cif"
+    path.write_text(CIF)
+    doc = cif_parser.parse_file(str(path), large_value_threshold=1000)
+    assert doc.first_block().get_item("_map_data").read_large() == MAP
@@ -0,0 +1,23 @@
+"""Tests for lenient recovery from hand-editing mistakes."""
+
+import pytest
+
+import cif_parser
+
+EDITED = "data_a\n_name Sodium chloride\n_note 'unclosed\nloop_ _x _y 1 2 3\n"
+
+
+def test_hand_edits_raise_by_default():
+    """Strict parsing rejects an unquoted value with a space in it."""
+    with pytest.raises(ValueError):
+        cif_parser.Document.parse(EDITED)
+
+
+def test_hand_edits_are_repaired_leniently():
+    """Each repair is applied and recorded as a warning."""
+    doc = cif_parser.Document.parse(EDITED, lenient=True)
+    block = doc.first_block()
+    assert block.get_item("_name").text == "Sodium chloride"
+    assert block.get_item("_note").text == "unclosed"
+    assert len(block.loops[0]) == 2
+    assert len(doc.warnings) == 3
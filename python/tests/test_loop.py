@@ -244,6 +244,91 @@ class TestLoopIteration:
             assert first_pass[i]["_bond_type"].text == second_pass[i]["_bond_type"].text
 
 
+class TestRowDicts:
+    """Test iter_dicts() and common_prefix."""
+
+    def test_common_prefix(self, atom_loop, bond_loop):
+        """The shared category prefix includes its separator."""
+        assert atom_loop.common_prefix == "_atom_site_"
+        assert bond_loop.common_prefix == "_bond_"
+
+    def test_native_values_keyed_by_suffix(self, atom_loop):
+        """Rows are plain dicts of native values keyed by attribute."""
+        rows = list(atom_loop.iter_dicts())
+        assert len(rows) == 5
+        assert rows[0] == {
+            "label": "C1",
+            "type_symbol": "C",
+            "fract_x": pytest.approx(0.1234),
+            "fract_y": pytest.approx(0.2345),
+            "fract_z": pytest.approx(0.3456),
+            "occupancy": pytest.approx(1.0),
+        }
+        assert rows[2]["occupancy"] == pytest.approx(0.95)
+
+    def test_full_tags_and_value_objects(self, bond_loop):
+        """strip_prefix=False keeps full tags; native=False gives Values."""
+        rows = list(bond_loop.iter_dicts(native=False, strip_prefix=False))
+        assert set(rows[0]) == {"_bond_type", "_bond_length"}
+        value = rows[1]["_bond_type"]
+        assert isinstance(value, cif_parser.Value)
+        assert value.text == "double"
+        assert value.source.row == 1
+
+    def test_null_values_become_none(self):
+        """? and . are None in native mode."""
+        loop = cif_parser.parse("data_t\nloop_\n_x_a\n_x_b\n? .\n").first_block().loops[0]
+        assert list(loop.iter_dicts()) == [{"a": None, "b": None}]
+
+    def test_mixed_categories_fall_back_to_full_tags(self):
+        """A loop mixing categories has no prefix, so keys are full tags."""
+        cif = """data_t
+loop_
+_atom_site_label
+_atom_site_U_iso_or_equiv
+_atom_site_aniso_U_11
+C1 0.02 0.021
+"""
+        loop = cif_parser.parse(cif).first_block().loops[0]
+        assert loop.common_prefix is None
+        row = next(loop.iter_dicts())
+        assert list(row) == [
+            "_atom_site_label",
+            "_atom_site_U_iso_or_equiv",
+            "_atom_site_aniso_U_11",
+        ]
+
+    def test_mmcif_dotted_prefix(self):
+        """mmCIF tags share the category and the dot."""
+        cif = """data_t
+loop_
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.Cartn_x
+1 C 1.5
+2 O -0.25
+"""
+        loop = cif_parser.parse(cif).first_block().loops[0]
+        assert loop.common_prefix == "_atom_site."
+        rows = list(loop.iter_dicts())
+        assert rows == [
+            {"id": 1.0, "type_symbol": "C", "Cartn_x": 1.5},
+            {"id": 2.0, "type_symbol": "O", "Cartn_x": -0.25},
+        ]
+
+    def test_iterator_is_exhausted_once(self, bond_loop):
+        """Each call starts a fresh iterator."""
+        rows = bond_loop.iter_dicts()
+        assert len(list(rows)) == 3
+        assert list(rows) == []
+        assert len(list(bond_loop.iter_dicts())) == 3
+
+    def test_empty_loop(self):
+        """A zero-row loop yields nothing."""
+        loop = cif_parser.parse("data_t\nloop_\n_a\n_b\n").first_block().loops[0]
+        assert list(loop.iter_dicts()) == []
+
+
 class TestLoopStringRepresentation:
     """Test string representation methods."""
 
@@ -260,3 +345,36 @@ class TestLoopStringRepresentation:
         assert len(r) > 0
         # repr should contain useful debug info
         assert "Loop" in r or "loop" in r.lower()
+
+
+class TestEmptyLoop:
+    """Test loops with tags but no rows."""
+
+    @pytest.fixture
+    def empty_loop(self):
+        doc = cif_parser.parse("data_t\nloop_\n_a\n_b\n")
+        loop = doc.first_block().find_loop("_a")
+        assert loop is not None
+        return loop
+
+    def test_empty_loop_accessors(self, empty_loop):
+        """A zero-row loop has its tags and no rows."""
+        assert empty_loop.tags == ["_a", "_b"]
+        assert len(empty_loop) == 0
+        assert empty_loop.is_empty()
+        assert empty_loop.rows() == []
+        assert list(empty_loop) == []
+        assert empty_loop.get_column("_a") == []
+        assert empty_loop.get(0, 0) is None
+        assert empty_loop.get_row_dict(0) is None
+
+    def test_empty_loop_to_dict(self):
+        """to_dict keeps the tags of a zero-row loop in both layouts."""
+        block = cif_parser.parse("data_t\nloop_\n_a\n_b\n").first_block()
+        assert block.to_dict()["loops"] == [{"tags": ["_a", "_b"], "rows": []}]
+        assert block.to_dict(loops="columns")["loops"] == [{"_a": [], "_b": []}]
+
+    def test_tagless_loop_is_an_error(self):
+        """A bare loop_ keyword is rejected with a clear message."""
+        with pytest.raises(ValueError, match="no tags"):
+            cif_parser.parse("data_t\nloop_\n")
@@ -0,0 +1,81 @@
+"""Tests for the first-block shortcuts on Document."""
+
+import pytest
+
+import cif_parser
+
+STRUCTURE = """
+data_nacl
+_cell_length_a 5.64
+_cell_length_b 5.64
+_cell_length_c 5.64
+_cell_angle_alpha 90
+_cell_angle_beta 90
+_cell_angle_gamma 90
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+Na1 Na 0 0 0
+Cl1 Cl 0.5(1) 0.5 0.5
+"""
+
+GLOBAL = "data_global\n_publ_section_title 'Sodium chloride'\n"
+
+
+def test_single_block_shortcuts():
+    """Shortcuts on a one-block document act on that block."""
+    doc = cif_parser.parse(STRUCTURE)
+    assert doc.main_block(strict=True).name == "nacl"
+    assert doc.only_block().name == "nacl"
+    assert doc.get("_cell_length_a").numeric == 5.64
+    assert doc.get("_cell_volume") is None
+    assert doc.find_loop("_atom_site_label").tags[0] == "_atom_site_label"
+    assert doc.unit_cell().a == 5.64
+    assert doc.point_group() is None
+
+
+def test_atom_sites():
+    """Sites come back as dicts with fractional coordinates."""
+    sites = cif_parser.parse(STRUCTURE).atom_sites()
+    assert [site["label"] for site in sites] == ["Na1", "Cl1"]
+    assert sites[1]["fract"] == [0.5, 0.5, 0.5]
+    assert sites[1]["fract_su"] == [pytest.approx(0.1), 0.0, 0.0]
+    assert sites[1]["occupancy"] == 1.0
+    assert sites[1]["u_iso"] is None
+    assert cif_parser.parse(GLOBAL).first_block().atom_sites() is None
+
+
+def test_publication_block_is_skipped():
+    """A leading global block does not hide the structure."""
+    doc = cif_parser.parse(GLOBAL + STRUCTURE)
+    assert doc.main_block().name == "nacl"
+    assert doc.main_block(strict=True).name == "nacl"
+    assert doc.get("_cell_length_a", strict=True).numeric == 5.64
+    assert doc.get("_publ_section_title") is None
+    with pytest.raises(ValueError, match=r"2 blocks \(global, nacl\), not one"):
+        doc.only_block()
+
+
+def test_ambiguous_documents():
+    """Several structures are ambiguous only with strict=True."""
+    doc = cif_parser.parse(STRUCTURE + STRUCTURE.replace("nacl", "kcl"))
+    assert doc.main_block().name == "nacl"
+    with pytest.raises(ValueError, match=r"2 hold data \(nacl, kcl\)"):
+        doc.unit_cell(strict=True)
+
+    metadata = cif_parser.parse(GLOBAL + "data_journal\n_journal_year 2020\n")
+    assert metadata.main_block().name == "global"
+    with pytest.raises(ValueError, match="none holds data"):
+        metadata.main_block(strict=True)
+
+
+def test_empty_document():
+    """An empty document always raises."""
+    doc = cif_parser.parse("")
+    with pytest.raises(ValueError, match="no data blocks"):
+        doc.get("_cell_length_a")
+    with pytest.raises(ValueError, match="no data blocks"):
+        doc.only_block()
@@ -0,0 +1,49 @@
+"""Tests for standard reflections, scans, and instruments of a measurement."""
+
+from pathlib import Path
+
+import cif_parser
+
+MEASUREMENT = Path(__file__).parents[2] / "tests" / "fixtures" / "measurement"
+
+
+def test_serial_diffractometer():
+    """A CAD-4 file gives its standards, scan, and an exposure estimate."""
+    block = cif_parser.parse_file(str(MEASUREMENT / "cad4.cif")).first_block()
+    measurement = block.measurement()
+    assert measurement.standards_number == 3
+    assert measurement.standards_interval_count == 200
+    assert measurement.standards_decay_percent[0] == 14.2
+    assert measurement.scan_method == "\\w/2\\q scans"
+    assert measurement.scan_time == (45.0, None)
+    assert measurement.device_type == "Enraf-Nonius CAD-4"
+    assert measurement.monochromator == "graphite"
+    assert measurement.reflections_measured == 2484
+    assert measurement.total_exposure_estimate == (2484 + 36) * 45.0
+
+
+def test_decay_warning():
+    """A decay above the threshold is reported like a sanity issue."""
+    block = cif_parser.parse_file(str(MEASUREMENT / "cad4.cif")).first_block()
+    measurement = block.measurement()
+    warnings = measurement.warnings()
+    assert [w["id"] for w in warnings] == ["standards-decay"]
+    assert warnings[0]["tag"] == "_diffrn_standards_decay_%"
+    assert warnings[0]["value"] == "14.2(3)"
+    assert warnings[0]["row"] is None
+    assert measurement.warnings(decay_threshold=15.0) == []
+
+
+def test_area_detector():
+    """A CCD file has instruments but no standards or exposure estimate."""
+    block = cif_parser.parse_file(str(MEASUREMENT / "ccd.cif")).first_block()
+    measurement = block.measurement()
+    assert measurement.standards_number == 0
+    assert measurement.standards_decay_percent is None
+    assert measurement.detector == "CCD area detector"
+    assert measurement.detector_type == "Bruker APEX-II"
+    assert measurement.total_exposure_estimate is None
+    assert measurement.warnings() == []
+    assert repr(measurement) == (
+        "Measurement(device_type='Bruker APEX-II CCD', scan_method='\\f and \\w scans')"
+    )
@@ -0,0 +1,26 @@
+"""Tests for document memory reports."""
+
+import cif_parser
+
+
+def test_memory_footprint():
+    """The report breaks the total down by block and lists the loops."""
+    rows = "\n".join(f"{n} label{n}" for n in range(200))
+    doc = cif_parser.parse(
+        f"data_a\n_title x\nloop_\n_n\n_label\n{rows}\n"
+        "save_f\nloop_\n_m\n1\n2\nsave_\n"
+    )
+    report = doc.memory_footprint()
+
+    assert report["total_bytes"] >= report["blocks"][0]["bytes"]
+    block = report["blocks"][0]
+    assert block["name"] == "a"
+    assert block["items_bytes"] > 0
+    assert block["frames_bytes"] > 0
+    assert [loop["first_tag"] for loop in block["loops"]] == ["_n"]
+
+    largest = report["largest_loops"]
+    assert [loop["first_tag"] for loop in largest] == ["_n", "_m"]
+    assert (largest[0]["rows"], largest[0]["columns"]) == (200, 2)
+    assert largest[1]["frame"] == "f"
+    assert largest[0]["bytes"] > largest[1]["bytes"]
@@ -0,0 +1,46 @@
+"""Tests for iterating over a block's tags in file order."""
+
+from pathlib import Path
+
+import cif_parser
+
+ORDERED = Path(__file__).parents[2] / "tests" / "fixtures" / "ordered_items.cif"
+
+ITEMS = [
+    "_symmetry_space_group_name_H-M",
+    "_cell_length_c",
+    "_cell_length_a",
+    "_cell_length_b",
+    "_chemical_name_common",
+    "_audit_creation_method",
+]
+
+
+def test_items_ordered():
+    """Items come back as (tag, value) pairs in file order."""
+    block = cif_parser.parse_file(str(ORDERED)).first_block()
+    pairs = block.items_ordered()
+    assert [tag for tag, _ in pairs] == ITEMS
+    assert pairs[2][1].numeric == 7.41
+    assert block.item_keys == ITEMS
+
+
+def test_iterating_a_block_yields_tags():
+    """Iterating a block yields item tags, then loop columns."""
+    block = cif_parser.parse_file(str(ORDERED)).first_block()
+    assert list(block) == ITEMS + [
+        "_atom_site_label",
+        "_atom_site_fract_x",
+        "_geom_bond_atom_site_label_1",
+        "_geom_bond_atom_site_label_2",
+        "_geom_bond_distance",
+    ]
+    assert "_restr_target" not in list(block)
+
+
+def test_dicts_follow_file_order():
+    """items() and row dicts keep the file's order rather than hash order."""
+    block = cif_parser.parse_file(str(ORDERED)).first_block()
+    assert list(block.items()) == ITEMS
+    atoms = block.find_loop("_atom_site_label")
+    assert list(atoms.get_row_dict(0)) == ["_atom_site_label", "_atom_site_fract_x"]
@@ -0,0 +1,57 @@
+"""Tests for converting loops and categories to pandas DataFrames."""
+
+import math
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+pd = pytest.importorskip("pandas")
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures"
+
+CIF = """data_refl
+loop_
+_refln_index_h
+_refln_intensity_meas
+_refln_status
+1 120.5(3) o
+2 ?        .
+3 8.25     x
+"""
+
+
+def test_loop_to_pandas():
+    """Tags become columns, numbers floats, and ? and . NaN or None."""
+    frame = cif_parser.parse(CIF).first_block().get_loop(0).to_pandas()
+    assert list(frame.columns) == [
+        "_refln_index_h",
+        "_refln_intensity_meas",
+        "_refln_status",
+    ]
+    assert frame["_refln_intensity_meas"].dtype == "float64"
+    assert frame["_refln_intensity_meas"][0] == 120.5
+    assert math.isnan(frame["_refln_intensity_meas"][1])
+    assert list(frame["_refln_status"]) == ["o", None, "x"]
+
+
+def test_strip_prefix():
+    """Columns can be named without the shared category prefix."""
+    loop = cif_parser.parse(CIF).first_block().get_loop(0)
+    frame = loop.to_pandas(strip_prefix=True)
+    assert list(frame.columns) == ["index_h", "intensity_meas", "status"]
+
+
+def test_category_to_pandas():
+    """A category becomes a DataFrame keyed by attribute."""
+    block = cif_parser.parse_file(str(FIXTURES / "mmcif_fragment.cif")).first_block()
+    atoms = block.category_to_pandas("atom_site")
+    assert len(atoms) == 10
+    assert atoms["Cartn_x"].iloc[9] == 10.25
+    assert atoms["label_alt_id"].iloc[7] == "A"
+
+    cell = block.category("cell").to_pandas()
+    assert len(cell) == 1
+    assert cell["length_b"][0] == 18.65
+    assert block.category_to_pandas("pdbx_struct_oper_list") is None
@@ -0,0 +1,71 @@
+"""Tests for path arguments: PathLike, bytes, and names that are not UTF-8."""
+
+import os
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+CONTENT = "data_cafe\n_cell_length_a 5.431\n"
+
+
+def non_utf8_file(tmp_path):
+    """Write a CIF named caf\\xe9.cif, or skip where the file system refuses it."""
+    name = os.fsencode(tmp_path) + b"/caf\xe9.cif"
+    try:
+        with open(name, "w") as f:
+            f.write(CONTENT)
+    except (OSError, UnicodeError):
+        pytest.skip("file system does not allow names that are not UTF-8")
+    return name
+
+
+def test_pathlike_and_bytes_paths(tmp_path):
+    """Paths can be given as str, pathlib.Path, or bytes."""
+    path = tmp_path / "a.cif"
+    path.write_text(CONTENT)
+    for arg in (str(path), path, os.fsencode(path)):
+        doc = cif_parser.parse_file(arg)
+        assert doc.first_block().name == "cafe"
+        assert doc.source.path == str(path)
+    assert cif_parser.Document.from_file(path).first_block().name == "cafe"
+
+
+def test_non_utf8_name_round_trips(tmp_path):
+    """A name that is not UTF-8 parses, and its path gives back the bytes."""
+    name = non_utf8_file(tmp_path)
+    doc = cif_parser.parse_file(name)
+    assert doc.first_block().name == "cafe"
+    assert os.fsencode(doc.source.path) == name
+
+    # The str form os.listdir() gives works too
+    decoded = os.fsdecode(name)
+    assert cif_parser.parse_file(decoded).source.path == decoded
+
+    records = cif_parser.extract([name], ["_cell_length_a"])
+    assert os.fsencode(records[0]["path"]) == name
+
+
+@pytest.mark.skipif(os.name == "nt", reason="bytes paths must be UTF-8 on Windows")
+def test_error_path_round_trips(tmp_path):
+    """The error for a missing file carries its path, bytes and all."""
+    name = os.fsencode(tmp_path) + b"/missing\xe9.cif"
+    with pytest.raises(OSError) as info:
+        cif_parser.parse_file(name)
+    assert info.value.code == "E0002"
+    assert os.fsencode(info.value.path) == name
+
+
+def test_writer_error_has_path(tmp_path):
+    """Opening a file for writing in a missing directory names the file."""
+    path = tmp_path / "no-such-dir" / "out.cif"
+    with pytest.raises(OSError) as info:
+        cif_parser.Writer(path)
+    assert info.value.path == str(path)
+
+
+def test_bad_path_type():
+    """Anything other than str, bytes, or PathLike is a TypeError."""
+    with pytest.raises(TypeError):
+        cif_parser.parse_file(42)
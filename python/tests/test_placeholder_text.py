@@ -0,0 +1,57 @@
+"""Tests that quoted '?' and '.' stay text, apart from the bare markers."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+FIXTURE = Path(__file__).parents[2] / "tests" / "fixtures" / "placeholder_text.cif"
+
+
+@pytest.fixture
+def block():
+    return cif_parser.parse_file(str(FIXTURE)).first_block()
+
+
+def test_items(block):
+    """Quoted markers are text; bare ones are unknown or not applicable."""
+    quoted = block.get_item("_chemical_name_common")
+    assert quoted.is_text and not quoted.is_unknown
+    assert quoted.text == "?"
+    assert str(quoted) == "'?'"
+    assert block.get_item("_chemical_name_systematic").text == "."
+    assert block.get_item("_chemical_melting_point").is_unknown
+    assert block.get_item("_chemical_compound_source").is_not_applicable
+
+
+def test_loop_columns(block):
+    """Columns keep the distinction, and text is not a number."""
+    atoms = block.find_loop("_atom_site_label")
+    flags = atoms.get_column("_atom_site_calc_flag")
+    assert [value.is_unknown for value in flags] == [False, True, False]
+    assert [value.text for value in flags] == ["?", None, "?"]
+    with pytest.raises(TypeError, match="row 0"):
+        atoms.column_buffer("_atom_site_calc_flag")
+
+
+def test_to_dict(block):
+    """Native values map only the bare markers to None."""
+    data = block.to_dict()
+    assert data["items"]["_chemical_name_common"] == "?"
+    assert data["items"]["_chemical_melting_point"] is None
+
+
+def test_writer_quotes_text_markers(tmp_path):
+    """The string "?" is written quoted; None is written as a bare ?."""
+    path = tmp_path / "markers.cif"
+    with cif_parser.Writer(path) as w:
+        w.begin_block("x")
+        w.write_item("_text", "?")
+        w.write_item("_unknown", None)
+        w.end_block()
+
+    assert "_text '?'" in path.read_text()
+    block = cif_parser.parse_file(str(path)).first_block()
+    assert block.get_item("_text").text == "?"
+    assert block.get_item("_unknown").is_unknown
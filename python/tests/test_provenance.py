@@ -0,0 +1,58 @@
+"""Tests for original values kept by aggregators such as COD."""
+
+from pathlib import Path
+
+import cif_parser
+import pytest
+
+FIXTURE = Path(__file__).parents[2] / "tests" / "fixtures" / "provenance" / "cod_normalized.cif"
+
+
+def load():
+    return cif_parser.parse_file(FIXTURE)
+
+
+def test_provenance_pairs_originals():
+    """Each original is paired with the tag it stands for."""
+    block = load().first_block()
+    found = {p["original_tag"]: p for p in block.provenance()}
+    assert sorted(found) == [
+        "_cod_original_cell_volume",
+        "_cod_original_formula_sum",
+        "_cod_original_sg_symbol_H-M",
+        "_iucr_original_cell_length_a",
+    ]
+
+    volume = found["_cod_original_cell_volume"]
+    assert volume["tag"] == "_cell_volume"
+    assert not volume["differs"]
+    assert volume["sigmas"] == pytest.approx(0.5)
+
+    sg = found["_cod_original_sg_symbol_H-M"]
+    assert sg["tag"] == "_symmetry_space_group_name_H-M"
+    assert sg["original"] == "P 21/c"
+    assert sg["current"] == "P 1 21/c 1"
+    assert sg["differs"]
+
+
+def test_provenance_prefixes():
+    """Only the given prefixes are looked at."""
+    block = load().first_block()
+    found = block.provenance(prefixes=["_iucr_original_"])
+    assert [p["tag"] for p in found] == ["_cell_length_a"]
+    assert found[0]["differs"]
+
+
+def test_restore_original():
+    """The original replaces the current value and the change is recorded."""
+    doc = load()
+    block = doc.first_block()
+    restored = block.restore_original("_symmetry_space_group_name_H-M")
+    assert restored["current"] == "P 1 21/c 1"
+
+    assert block.get("_symmetry_space_group_name_H-M") == "P 21/c"
+    assert block.get("_cod_original_sg_symbol_H-M") is None
+    assert "P 21/c" in block.get("_audit_update_record")
+
+    with pytest.raises(ValueError):
+        block.restore_original("_cell_length_b")
@@ -0,0 +1,94 @@
+"""Tests for Block.publication() and CSL-JSON export."""
+
+import json
+
+import cif_parser
+
+PAPER_CIF = """
+data_paper
+_publ_section_title
+;
+Crystal structure of
+   urea at 100 K
+;
+_journal_name_full 'Acta Crystallographica Section E'
+_journal_volume 75
+_journal_issue 3
+_journal_year 2019
+_journal_page_first 123
+_journal_page_last 130
+_journal_paper_doi 10.1107/S2056989019000000
+_publ_contact_author_name 'Smith, Jane A.'
+_publ_contact_author_email jane@example.org
+loop_
+_publ_author_name
+_publ_author_address
+'Smith, Jane A.^a^' 'Dept. of Chemistry'
+'Jones, B.*' ?
+"""
+
+
+def _publication():
+    return cif_parser.parse(PAPER_CIF).first_block().publication()
+
+
+def test_title_and_journal():
+    """Title is unfolded and journal fields are read as text."""
+    publ = _publication()
+    assert publ.title == "Crystal structure of urea at 100 K"
+    assert publ.journal == "Acta Crystallographica Section E"
+    assert publ.volume == "75"
+    assert publ.issue == "3"
+    assert publ.year == 2019
+    assert publ.pages == "123-130"
+    assert publ.doi == "10.1107/S2056989019000000"
+
+
+def test_author_loop():
+    """Looped authors are split into family/given with markers removed."""
+    authors = _publication().authors
+    assert [a.family for a in authors] == ["Smith", "Jones"]
+    assert [a.given for a in authors] == ["Jane A.", "B."]
+    assert authors[0].name == "Smith, Jane A."
+    assert authors[0].address == "Dept. of Chemistry"
+    assert authors[1].address is None
+
+
+def test_single_author_item():
+    """A lone _publ_author_name item gives one author."""
+    block = cif_parser.parse("data_a\n_publ_author_name 'Doe, John'\n").first_block()
+    authors = block.publication().authors
+    assert len(authors) == 1
+    assert authors[0].family == "Doe"
+
+
+def test_contact_author():
+    """Contact author fields are collected separately."""
+    contact = _publication().contact_author
+    assert contact.family == "Smith"
+    assert contact.email == "jane@example.org"
+
+
+def test_missing_metadata():
+    """Blocks without publication items give empty fields."""
+    publ = cif_parser.parse("data_a\n_x 1\n").first_block().publication()
+    assert publ.title is None
+    assert publ.authors == []
+    assert publ.to_csl_json() == {"id": "a", "type": "article-journal"}
+
+
+def test_csl_json():
+    """CSL-JSON output uses CSL field names and is JSON serializable."""
+    item = _publication().to_csl_json()
+    assert item["id"] == "paper"
+    assert item["type"] == "article-journal"
+    assert item["title"] == "Crystal structure of urea at 100 K"
+    assert item["author"] == [
+        {"family": "Smith", "given": "Jane A."},
+        {"family": "Jones", "given": "B."},
+    ]
+    assert item["container-title"] == "Acta Crystallographica Section E"
+    assert item["page"] == "123-130"
+    assert item["issued"] == {"date-parts": [[2019]]}
+    assert item["DOI"] == "10.1107/S2056989019000000"
+    assert json.loads(json.dumps(item)) == item
@@ -0,0 +1,44 @@
+"""Tests for the query selector language."""
+
+from pathlib import Path
+
+import cif_parser
+import pytest
+
+FIXTURE = Path(__file__).parents[2] / "tests" / "fixtures" / "query" / "two_phases.cif"
+
+
+def labels(doc, predicate):
+    hits = doc.query(f"block[0].loop(_atom_site).rows[{predicate}].label")
+    return [hit["value"] for hit in hits]
+
+
+def test_query_returns_native_values():
+    """Hits carry where they were found and the value as Python."""
+    doc = cif_parser.parse_file(FIXTURE)
+    hits = doc.query("block[phase_*]._cell_length_a")
+    assert [(h["block"], h["tag"], h["row"]) for h in hits] == [
+        ("phase_a", "_cell_length_a", None),
+        ("phase_b", "_cell_length_a", None),
+    ]
+    temperature = doc.query("block[-1]._diffrn_radiation_wavelength")[0]["value"]
+    assert temperature == pytest.approx(0.71073)
+
+
+def test_row_predicates():
+    """Rows are picked by comparing a column with a number or word."""
+    doc = cif_parser.parse_file(FIXTURE)
+    assert labels(doc, "occupancy<1") == ["C2", "N1"]
+    assert labels(doc, "type_symbol=C & occupancy<1") == ["C2"]
+    assert labels(doc, "label~o*") == ["O1"]
+    assert labels(doc, "occupancy=?") == ["O1"]
+    assert labels(doc, "U_iso_or_equiv<1") == []
+
+
+def test_bad_selector_gives_position():
+    """A selector that does not parse points at the character."""
+    doc = cif_parser.parse_file(FIXTURE)
+    with pytest.raises(ValueError) as error:
+        doc.query("block[*].loop(_atom_site).rows[occupancy 1]")
+    assert error.value.code == "E0013"
+    assert error.value.position == 42
@@ -0,0 +1,64 @@
+"""Tests for redacting sensitive values by profile and tag pattern."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures" / "redact"
+
+
+def prepublication():
+    return cif_parser.parse_file(str(FIXTURES / "prepublication.cif"))
+
+
+def test_redact_pre_publication():
+    """Author and contact details become ?; other values are kept."""
+    doc = prepublication()
+    changes = doc.redact(profile="pre_publication")
+
+    tags = [change["tag"] for change in changes]
+    assert "_publ_contact_author_email" in tags
+    assert "_exptl_special_details" in tags
+    # Already ? in the file, so not changed
+    assert "_refine_special_details" not in tags
+    assert all(not change["removed"] for change in changes)
+
+    block = doc.first_block()
+    assert block.get_item("_publ_contact_author_email").is_unknown
+    names = block.find_loop("_publ_author_name").get_column("_publ_author_name")
+    assert all(value.is_unknown for value in names)
+    assert block.get_item("_cell_length_b").text == "7.8901(4)"
+    assert "TITL" in block.get_item("_shelx_res_file").text
+
+
+def test_redact_remove_with_profiles_and_tags():
+    """remove=True takes out items, loop columns, and emptied loops."""
+    doc = prepublication()
+    changes = doc.redact(
+        profile=["pre_publication", "strip_embedded_files"],
+        tags=["_audit_creation_*"],
+        remove=True,
+    )
+    assert all(change["removed"] for change in changes)
+    authors = next(c for c in changes if c["tag"] == "_publ_author_name")
+    assert authors == {
+        "block": "compound_7",
+        "frame": None,
+        "tag": "_publ_author_name",
+        "values": 2,
+        "removed": True,
+    }
+
+    block = doc.first_block()
+    assert block.get_item("_shelx_hkl_file") is None
+    assert block.get_item("_audit_creation_method") is None
+    assert block.find_loop("_publ_author_name") is None
+    assert block.find_loop("_atom_site_label") is not None
+
+
+def test_redact_unknown_profile():
+    """An unknown profile name raises ValueError listing the known ones."""
+    with pytest.raises(ValueError, match="pre_publication"):
+        prepublication().redact(profile="pre-publication")
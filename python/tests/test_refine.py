@@ -0,0 +1,57 @@
+"""Tests for the refinement model of a block."""
+
+from pathlib import Path
+
+import cif_parser
+
+REFINE = Path(__file__).parents[2] / "tests" / "fixtures" / "refine"
+
+
+def model(name):
+    return cif_parser.parse_file(str(REFINE / name)).first_block().refinement_model()
+
+
+def test_shelxl_weights_are_exact():
+    """The a and b coefficients of the SHELXL scheme are read as written."""
+    chiral = model("shelxl_chiral.cif")
+    assert (chiral.weight_a, chiral.weight_b) == (0.0478, 0.2987)
+    restrained = model("shelxl_restrained.cif")
+    assert (restrained.weight_a, restrained.weight_b) == (0.1024, 12.4516)
+
+
+def test_counts_and_parameters():
+    """Counts, extinction, Flack parameter, and hydrogens are typed."""
+    chiral = model("shelxl_chiral.cif")
+    assert chiral.parameters == 235
+    assert chiral.restraints == 0
+    assert chiral.constraints is None
+    assert chiral.flack == (-0.02, 0.04)
+    assert chiral.hydrogen_treatment == "constr"
+    assert chiral.extinction_coef is None
+    assert chiral.special_details.startswith("Refinement of F^2^")
+
+    restrained = model("shelxl_restrained.cif")
+    assert restrained.extinction_coef == (0.0041, 0.0007)
+    assert restrained.hydrogen_treatment == "mixed"
+    assert "restraints=42" in repr(restrained)
+
+
+def test_restraint_list():
+    """Rows of the _restr_* loops are listed with their targets."""
+    restraints = model("shelxl_restrained.cif").restraint_list
+    assert len(restraints) == 3
+    assert restraints[0] == {
+        "kind": "distance",
+        "atoms": ["C11", "C12"],
+        "target": (1.54, 0.01),
+        "difference": 0.003,
+    }
+    assert restraints[2]["kind"] == "angle"
+
+
+def test_no_refinement_items():
+    """A block without refinement items gives an empty model."""
+    empty = cif_parser.parse("data_x\n_a 1\n").first_block().refinement_model()
+    assert empty.parameters is None
+    assert empty.weight_a is None
+    assert empty.restraint_list == []
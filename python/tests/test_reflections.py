@@ -0,0 +1,63 @@
+"""Tests for reading reflections and merging equivalents."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+ROOT = Path(__file__).parents[2] / "tests"
+PETS = ROOT / "example_cifs" / "glycine" / "pets2_glycine.cif_pets"
+EXPECTED = ROOT / "fixtures" / "reflections" / "glycine_merging.cif"
+
+
+@pytest.fixture(scope="module")
+def glycine():
+    data = cif_parser.parse_file(str(PETS)).first_block().reflections()
+    expected = cif_parser.parse_file(str(EXPECTED)).first_block()
+    return data, expected
+
+
+def test_reflections_are_read(glycine):
+    """The PETS2 _refln loop is read row by row."""
+    data, _ = glycine
+    assert data.category == "refln"
+    assert len(data) == 9242
+    assert data.reflections[0] == ((3, 2, -7), 56.49, 29.49)
+
+
+def test_merge_matches_reported_statistics(glycine):
+    """R factors and shells agree with the quoted statistics."""
+    data, expected = glycine
+    merged = data.merge(expected.point_group(), shells=[2.0, 1.2, 0.9, 0.7])
+    assert len(merged) == 736
+    for name, tag in [
+        ("r_merge", "_reflns.pdbx_Rmerge_I_obs"),
+        ("r_meas", "_reflns.pdbx_Rrim_I_all"),
+        ("r_pim", "_reflns.pdbx_Rpim_I_all"),
+    ]:
+        quoted = expected.get_item(tag).numeric
+        assert getattr(merged, name) == pytest.approx(quoted, abs=5e-5)
+    assert merged.multiplicity == pytest.approx(12.56, abs=5e-3)
+
+    shells = merged.shells
+    assert [shell["unique"] for shell in shells] == [25, 85, 142, 271, 213]
+    assert shells[0]["r_merge"] == pytest.approx(0.0381, abs=5e-5)
+    assert shells[-1]["d_min"] == pytest.approx(0.524, abs=5e-4)
+
+
+def test_shell_count_and_anomalous(glycine):
+    """An int gives equal-count shells; anomalous keeps Friedel mates apart."""
+    data, expected = glycine
+    group = expected.point_group()
+    assert len(data.merge(group, shells=4).shells) == 4
+    assert len(data.merge(group, anomalous=True)) >= 736
+    first = data.merge(group).reflections[0]
+    assert set(first) == {"hkl", "intensity", "sigma", "multiplicity", "d_spacing"}
+    with pytest.raises(TypeError):
+        data.merge(group, shells="fine")
+
+
+def test_no_reflections():
+    """Blocks without a reflection loop give None."""
+    assert cif_parser.parse("data_x\n_cell_length_a 5\n").first_block().reflections() is None
@@ -0,0 +1,29 @@
+"""Tests for splitting loops that mix categories."""
+
+from pathlib import Path
+
+import cif_parser
+
+MIXED = Path(__file__).parents[2] / "tests" / "fixtures" / "mixed_loop.cif"
+
+
+def test_repair_mixed_loops():
+    """The atom site loop is split and the label copied into the aniso loop."""
+    block = cif_parser.parse_file(str(MIXED)).first_block()
+    splits = block.repair_mixed_loops()
+    assert len(splits) == 1
+    assert splits[0]["categories"] == ["_atom_site", "_atom_site_aniso"]
+    assert splits[0]["key"] == "_atom_site_label"
+    assert "_atom_site_aniso_U_11" in splits[0]["tags"]
+
+    aniso = block.find_loop("_atom_site_aniso_label")
+    labels = [v.text for v in aniso.get_column("_atom_site_aniso_label")]
+    assert labels == ["Si1", "O1", "O2"]
+    assert "_atom_site_aniso_U_11" not in block.find_loop("_atom_site_label").tags
+
+
+def test_repair_is_noop_on_clean_block():
+    """Blocks without mixed loops report nothing."""
+    block = cif_parser.parse("data_x\nloop_\n_a_b\n_a_c\n1 2\n").first_block()
+    assert block.repair_mixed_loops() == []
+    assert len(block.loops) == 1
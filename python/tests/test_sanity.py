@@ -0,0 +1,35 @@
+"""Tests for the built-in sanity checks."""
+
+from pathlib import Path
+
+import cif_parser
+
+ROOT = Path(__file__).parents[2] / "tests"
+ISSUES = ROOT / "fixtures" / "sanity_issues.cif"
+UREA = ROOT / "example_cifs" / "urea" / "cod_urea.cif"
+
+
+def test_issues_are_dicts():
+    """Each issue names the tag, row, value, and rule."""
+    issues = cif_parser.parse_file(str(ISSUES)).first_block().sanity_check()
+    assert len(issues) == 11
+    assert issues[0] == {
+        "tag": "_cell_length_b",
+        "row": None,
+        "value": "1200",
+        "rule": "cell length must be in (0.5, 1000) Å",
+    }
+    occupancy = next(i for i in issues if i["tag"] == "_atom_site_occupancy")
+    assert occupancy["row"] == 2
+    assert occupancy["rule"] == "occupancy must be in (0, 1.001]"
+
+
+def test_clean_file_has_no_issues():
+    """A published structure passes."""
+    assert cif_parser.parse_file(str(UREA)).first_block().sanity_check() == []
+
+
+def test_picometre_cell_length():
+    """A cell length in pm instead of Å is caught."""
+    block = cif_parser.parse("data_x\n_cell_length_a 1234.5\n").first_block()
+    assert [i["tag"] for i in block.sanity_check()] == ["_cell_length_a"]
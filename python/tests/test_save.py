@@ -0,0 +1,73 @@
+"""Tests for writing documents with Document.save."""
+
+import cif_parser
+import pytest
+
+CIF = """data_refined
+_cell_length_a 10.523456789
+_cell_length_a_su 0.0012
+_cell_angle_beta 101.123456789
+loop_
+_atom_site_label
+_atom_site_fract_x
+_atom_site_fract_y
+C1 0.123456789 0.987654321
+"""
+
+
+def test_save_writes_shortest_numbers_by_default(tmp_path):
+    """Numbers come back exactly as they were read."""
+    doc = cif_parser.parse(CIF)
+    path = tmp_path / "out.cif"
+    doc.save(path)
+    again = cif_parser.parse_file(path)
+    assert again.first_block().get("_cell_length_a") == 10.523456789
+
+
+def test_float_format_by_tag_pattern(tmp_path):
+    """Patterns pick decimals or significant digits; '*' sets the rest."""
+    doc = cif_parser.parse(CIF)
+    path = tmp_path / "out.cif"
+    doc.save(
+        path,
+        float_format={"_atom_site_fract_*": 5, "_cell_angle_beta": ".4f", "*": ".3g"},
+    )
+    text = path.read_text()
+    assert "C1 0.12346 0.98765\n" in text
+    assert "_cell_angle_beta 101.1235\n" in text
+    assert "_cell_length_a 10.5\n" in text
+
+
+def test_su_rounding(tmp_path):
+    """A number with an _su item is rounded to the su's precision."""
+    doc = cif_parser.parse(CIF)
+    path = tmp_path / "out.cif"
+    doc.save(path, su_rounding=True)
+    assert "_cell_length_a 10.5235\n" in path.read_text()
+
+
+def test_nan_is_rejected(tmp_path):
+    """CIF cannot write NaN, so nothing is written."""
+    doc = cif_parser.parse("data_x\n_cell_volume nan\n")
+    path = tmp_path / "out.cif"
+    with pytest.raises(ValueError, match="NaN") as info:
+        doc.save(path)
+    assert info.value.code == "E0008"
+    assert not path.exists()
+
+
+def test_unknown_format_is_rejected(tmp_path):
+    """A format that is not an int, '.Nf', '.Ng', or 'shortest' raises."""
+    doc = cif_parser.parse(CIF)
+    with pytest.raises(ValueError, match="unknown number format"):
+        doc.save(tmp_path / "out.cif", float_format={"*": "%.3f"})
+
+
+def test_write_and_str(tmp_path):
+    """write() writes the text str() gives, which parses back."""
+    doc = cif_parser.parse(CIF)
+    path = tmp_path / "out.cif"
+    doc.write(path)
+    assert path.read_text() == str(doc)
+    again = cif_parser.parse(str(doc))
+    assert again.first_block().get("_cell_angle_beta") == 101.123456789
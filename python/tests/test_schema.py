@@ -0,0 +1,80 @@
+"""Tests for checking blocks against application schemas."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+TYPED = Path(__file__).parents[2] / "tests" / "fixtures" / "schema" / "typed.cif"
+
+
+@pytest.fixture
+def block():
+    return cif_parser.parse_file(str(TYPED)).first_block()
+
+
+def test_report_lists_every_problem(block):
+    """Missing, unknown, and mismatched tags are all reported."""
+    report = block.check_schema(
+        {
+            "_audit_creation_date": "date",
+            "_cell_length_a": "float_su",
+            "_cell_measurement_temperature": "float",
+            "_space_group_IT_number": "int",
+            "_atom_site_occupancy": "float",
+            "_atom_site_calc_flag": ["d", "calc", "c", "dum"],
+            "_diffrn_radiation_wavelength": "float",
+        }
+    )
+    assert not report
+    assert not report.ok
+    assert report.missing == ["_diffrn_radiation_wavelength"]
+    assert report.unknown == ["_cell_measurement_temperature"]
+    assert report.mismatched == [
+        "_audit_creation_date",
+        "_atom_site_occupancy",
+        "_atom_site_calc_flag",
+    ]
+    assert report.mismatches[1] == {
+        "tag": "_atom_site_occupancy",
+        "row": 4,
+        "token": "0.5(1)",
+        "expected": "number",
+    }
+    assert report["_cell_length_a"] == {
+        "status": "ok",
+        "expected": "number with optional su",
+        "unknown": 0,
+        "mismatches": [],
+    }
+    assert report["_atom_site_occupancy"]["unknown"] == 1
+    assert list(report.to_dict()) == [
+        "_audit_creation_date",
+        "_cell_length_a",
+        "_cell_measurement_temperature",
+        "_space_group_IT_number",
+        "_atom_site_occupancy",
+        "_atom_site_calc_flag",
+        "_diffrn_radiation_wavelength",
+    ]
+    assert "_atom_site_calc_flag: expected one of d, calc, c, dum, found riding (row 3)" in str(
+        report
+    )
+
+
+def test_passing_schema_is_truthy(block):
+    """A report with nothing wrong is truthy, so it can be asserted on."""
+    report = block.check_schema({"_cell_length_a": "float_su", "_atom_site_label": "text"})
+    assert report, str(report)
+    assert repr(report) == "CoercionReport(ok=2, missing=0, unknown=0, mismatched=0)"
+
+
+def test_bad_schema(block):
+    """Unknown type names and non-string codes are rejected."""
+    with pytest.raises(ValueError, match="Unknown schema type 'double'"):
+        block.check_schema({"_cell_length_a": "double"})
+    with pytest.raises(TypeError, match="type name or a list"):
+        block.check_schema({"_cell_length_a": 3})
+    with pytest.raises(KeyError):
+        block.check_schema({"_cell_length_a": "float_su"})["_cell_length_b"]
@@ -0,0 +1,55 @@
+"""Tests for selective parsing (tags=, blocks=, first_block_only=)."""
+
+import cif_parser
+
+MULTI_BLOCK = """\
+data_first
+_cell_length_a 5.0
+_title 'first block'
+loop_
+_atom_site_label
+_atom_site_fract_x
+C1 0.1
+C2 0.2
+
+data_second
+_cell_length_a 7.0
+"""
+
+
+def test_full_parse_is_not_partial():
+    """Without a selection the document is complete."""
+    doc = cif_parser.parse(MULTI_BLOCK)
+    assert not doc.partial
+    assert len(doc) == 2
+
+
+def test_tags(simple_cif):
+    """Only items whose tags start with a selected prefix are kept."""
+    doc = cif_parser.parse_file(str(simple_cif), tags=["_cell_"])
+    assert doc.partial
+    block = doc.first_block()
+    assert len(block.item_keys) == 6
+    assert block.get_item("_title") is None
+
+
+def test_tags_keep_whole_loops():
+    """A loop is kept with all columns when any of its tags matches."""
+    doc = cif_parser.parse(MULTI_BLOCK, tags=["_atom_site_fract"])
+    block = doc.first_block()
+    assert len(block.loops) == 1
+    assert block.loops[0].get_column("_atom_site_label")[0].text == "C1"
+
+
+def test_blocks():
+    """Blocks are selected by name."""
+    doc = cif_parser.Document.parse(MULTI_BLOCK, blocks=["SECOND"])
+    assert doc.block_names == ["second"]
+
+
+def test_first_block_only_skips_broken_later_blocks(tmp_path):
+    """Content after the first block is never parsed."""
+    path = tmp_path / "later_error.cif"
+    path.write_text(MULTI_BLOCK + "data_third\n_x 'unclosed\n")
+    doc = cif_parser.Document.from_file(str(path), first_block_only=True)
+    assert doc.block_names == ["first"]
@@ -0,0 +1,40 @@
+"""Tests for site symmetries and multiplicities of atom sites."""
+
+from pathlib import Path
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures" / "site_symmetry"
+
+
+def test_rock_salt():
+    """Na and Cl both sit on m-3m sites of multiplicity 4."""
+    block = cif_parser.parse_file(str(FIXTURES / "nacl.cif")).first_block()
+    structure = block.structure()
+    assert structure.symmetry_count == 192
+    for site in structure.site_symmetries():
+        assert site.multiplicity == 4
+        assert site.is_special
+        assert site.symbol == "m-3m"
+        assert site.stabilizer_order == 48
+        assert site.mismatch is None
+
+
+def test_rutile():
+    """Ti on 2a, O on 4f, and a general position, with one wrong declaration."""
+    block = cif_parser.parse_file(str(FIXTURES / "rutile.cif")).first_block()
+    sites = block.structure().site_symmetries()
+    assert [(s.label, s.multiplicity, s.is_special) for s in sites] == [
+        ("Ti1", 2, True),
+        ("O1", 4, True),
+        ("O2", 16, False),
+    ]
+    assert sites[1].declared_multiplicity == 2
+    assert sites[1].mismatch == (2, 4)
+    issues = block.sanity_check()
+    assert [issue["id"] for issue in issues] == ["site-multiplicity"]
+
+
+def test_no_cell():
+    """Blocks without a unit cell have no structure."""
+    assert cif_parser.parse("data_x\n_chemical_name_common x\n").first_block().structure() is None
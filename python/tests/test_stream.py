@@ -0,0 +1,35 @@
+"""Tests for iterating over concatenated CIF streams."""
+
+import warnings
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+STREAM = Path(__file__).parents[2] / "tests" / "fixtures" / "concatenated.cif"
+
+
+def test_broken_entry_is_skipped_with_warning():
+    """The broken second entry warns; the other three come through intact."""
+    with pytest.warns(UserWarning, match="3 tags but 5 values"):
+        docs = list(cif_parser.iter_stream(STREAM))
+    assert [doc.first_block().name for doc in docs] == ["1000000", "1000002", "1000003"]
+    assert all(len(doc) == 1 for doc in docs)
+    fcf = docs[1].first_block().get_item("_iucr_refine_fcf_details").text
+    assert "data_embedded_fcf" in fcf
+
+
+def test_errors_ignore_and_raise():
+    """errors="ignore" is silent; errors="raise" stops at the broken entry."""
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        assert len(list(cif_parser.iter_stream(str(STREAM), errors="ignore"))) == 3
+
+    stream = cif_parser.iter_stream(STREAM, errors="raise")
+    assert next(stream).first_block().name == "1000000"
+    with pytest.raises(ValueError, match="line 24"):
+        next(stream)
+
+    with pytest.raises(ValueError, match="errors must be"):
+        cif_parser.iter_stream(STREAM, errors="skip")
@@ -0,0 +1,94 @@
+"""Tests for comparing two structures atom by atom."""
+
+import math
+
+import pytest
+
+import cif_parser
+
+P21 = """
+data_p21
+_cell_length_a 5.1
+_cell_length_b 6.2
+_cell_length_c 7.3
+_cell_angle_alpha 90
+_cell_angle_beta 101.5
+_cell_angle_gamma 90
+loop_
+_space_group_symop_operation_xyz
+'x, y, z'
+'-x, y+1/2, -z'
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+_atom_site_U_iso_or_equiv
+{rows}
+"""
+
+SITES = [
+    ("S1", "S", 0.2134, 0.1021, 0.3317),
+    ("O1", "O", 0.3561, 0.2284, 0.4190),
+    ("O2", "O", 0.0427, 0.1676, 0.2052),
+]
+
+
+def block(dy=0.0, suffix="", u_iso=0.02, sites=SITES):
+    rows = "\n".join(
+        f"{label}{suffix} {symbol} {x} {y + dy:.4f} {z} {u_iso}"
+        for label, symbol, x, y, z in sites
+    )
+    return cif_parser.parse(P21.format(rows=rows)).blocks[0]
+
+
+def test_identical_blocks():
+    """A structure compared with itself has no differences."""
+    result = cif_parser.compare_structures(block(), block())
+    assert len(result.sites) == 3
+    assert result.rmsd == 0.0
+    assert result.only_in_a == []
+    assert result.only_in_b == []
+    assert result.sites[0]["label_a"] == "S1"
+    assert result.sites[0]["u_iso"] == 0.0
+
+
+def test_differences_per_atom():
+    """Moved atoms and changed ADPs are reported per atom."""
+    result = cif_parser.compare_structures(block(), block(dy=0.01, u_iso=0.025))
+    site = result.sites[1]
+    assert math.isclose(site["distance"], 0.062)
+    assert math.isclose(site["displacement"][1], 0.062)
+    assert math.isclose(site["u_iso"], 0.005)
+    assert site["occupancy"] == 0.0
+    assert math.isclose(result.rmsd, 0.062)
+
+
+def test_origin_shift_is_recovered():
+    """A shift along the polar b axis is found when matching by proximity."""
+    result = cif_parser.compare_structures(
+        block(), block(dy=0.13, suffix="A"), by="proximity", origin_shift=True
+    )
+    assert len(result.sites) == 3
+    assert result.rmsd < 1e-9
+    assert result.origin_shift[0] == 0.0
+    assert math.isclose(result.origin_shift[1], 0.13)
+    assert result.sites[2]["label_b"] == "O2A"
+
+
+def test_unmatched_atoms():
+    """Atoms in one structure only are listed by label."""
+    result = cif_parser.compare_structures(block(), block(sites=SITES[:2]))
+    assert result.only_in_a == ["O2"]
+    assert result.only_in_b == []
+    assert "matched=2" in repr(result)
+
+
+def test_errors():
+    """A block without a cell, or an unknown matching, is rejected."""
+    no_cell = cif_parser.parse("data_x\n_a 1\n").blocks[0]
+    with pytest.raises(ValueError, match="no unit cell"):
+        cif_parser.compare_structures(block(), no_cell)
+    with pytest.raises(ValueError, match="by must be"):
+        cif_parser.compare_structures(block(), block(), by="name")
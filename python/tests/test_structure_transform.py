@@ -0,0 +1,49 @@
+"""Tests for wrapping coordinates, shifting the origin, and supercells."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+FIXTURES = Path(__file__).parents[2] / "tests" / "fixtures" / "site_symmetry"
+
+
+def rock_salt():
+    return cif_parser.parse_file(str(FIXTURES / "nacl.cif")).first_block().structure()
+
+
+def test_supercell():
+    """A 2×2×1 rock salt supercell has 32 atoms, all in P1."""
+    structure = rock_salt()
+    supercell = structure.supercell(2, 2, 1)
+    assert len(supercell.sites) == 32
+    assert supercell.symmetry_count == 1
+    assert supercell.reduced_to_p1
+    assert not structure.reduced_to_p1
+    assert supercell.cell.volume == pytest.approx(4 * structure.cell.volume)
+    assert supercell.sites[0]["label"] == "Na1_1"
+
+
+def test_supercell_of_zero_cells():
+    """Repeating a cell zero times is an error."""
+    with pytest.raises(ValueError):
+        rock_salt().supercell(0, 1, 1)
+
+
+def test_wrap_and_shift_return_copies():
+    """wrap_coordinates and shift_origin leave the original structure alone."""
+    structure = rock_salt()
+    shifted = structure.shift_origin((0.5, 0.5, 1.5))
+    assert shifted.sites[0]["fract"] == [0.5, 0.5, 1.5]
+    assert shifted.wrap_coordinates().sites[0]["fract"] == [0.5, 0.5, 0.5]
+    assert structure.sites[0]["fract"] == [0.0, 0.0, 0.0]
+
+
+def test_to_xyz():
+    """Extended XYZ has a count line, the lattice, and one line per atom."""
+    lines = rock_salt().supercell(1, 1, 1).to_xyz().splitlines()
+    assert lines[0] == "8"
+    assert lines[1].startswith('Lattice="5.6')
+    assert lines[2].split()[0] == "Na"
+    assert len(lines) == 10
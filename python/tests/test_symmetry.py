@@ -0,0 +1,82 @@
+"""Tests for symmetry operations, the point groups they generate, and space groups."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+TESTS = Path(__file__).parents[2] / "tests"
+
+
+def point_group(path):
+    doc = cif_parser.parse_file(str(TESTS / path))
+    return next(g for g in (b.point_group() for b in doc) if g is not None)
+
+
+def test_crystal_systems():
+    """Structures in several crystal systems give their point group."""
+    for path, symbol, system in [
+        ("example_cifs/carbamazepine/jana2006_carbamazepine.cif", "2/m", "monoclinic"),
+        ("fixtures/symmetry/tetragonal_rutile.cif", "4/mmm", "tetragonal"),
+        ("example_cifs/quartz/ccdc_quartz.cif", "32", "trigonal"),
+        ("fixtures/symmetry/hexagonal_magnesium.cif", "6/mmm", "hexagonal"),
+    ]:
+        group = point_group(path)
+        assert (group.symbol, group.crystal_system) == (symbol, system), path
+        assert group.warnings == []
+
+
+def test_properties():
+    """Quartz is chiral; carbamazepine is centrosymmetric."""
+    quartz = point_group("example_cifs/quartz/ccdc_quartz.cif")
+    assert quartz.laue_class == "-3m"
+    assert quartz.is_chiral and not quartz.is_centrosymmetric
+    carbamazepine = point_group("example_cifs/carbamazepine/jana2006_carbamazepine.cif")
+    assert carbamazepine.is_centrosymmetric and not carbamazepine.is_polar
+    assert repr(carbamazepine) == "PointGroup('2/m', monoclinic)"
+
+
+def test_conflicts_and_missing():
+    """Conflicts are warnings; a block without operations gives None."""
+    group = point_group("fixtures/symmetry/conflicts.cif")
+    assert len(group.warnings) == 2
+    assert "hexagonal metric" in group.warnings[0]
+    assert cif_parser.parse("data_x\n_a 1\n").first_block().point_group() is None
+
+
+def test_symmetry_operations():
+    """Operations parse, apply, compose, and print in x,y,z notation."""
+    doc = cif_parser.parse_file(str(TESTS / "example_cifs/carbamazepine/jana2006_carbamazepine.cif"))
+    ops = next(o for o in (b.symmetry_operations() for b in doc) if o)
+    assert len(ops) == 4
+    assert ops[0] == cif_parser.SymOp("x,y,z")
+
+    screw = cif_parser.SymOp("-x, y+1/2, -z+1/2")
+    assert screw.rotation == [[-1, 0, 0], [0, 1, 0], [0, 0, -1]]
+    assert screw.translation == [0.0, 0.5, 0.5]
+    assert screw.apply([0.1, 0.2, 0.3]) == pytest.approx([-0.1, 0.7, 0.2])
+    assert str(screw.compose(screw)) == "x,y+1,z+1"
+    assert repr(screw) == "SymOp('-x,y+1/2,-z+1/2')"
+    with pytest.raises(ValueError, match="not a symmetry operation"):
+        cif_parser.SymOp("x,y")
+
+
+def test_space_group():
+    """Space groups resolve from symbols and numbers, matching listed operations."""
+    group = cif_parser.SpaceGroup.from_hermann_mauguin("P 21/n")
+    assert (group.number, group.hall, len(group)) == (14, "-P 2yn", 4)
+    assert group.operations[0] == cif_parser.SymOp("x,y,z")
+    assert cif_parser.SpaceGroup.from_hermann_mauguin("Fd-3m:2").hermann_mauguin == "F d -3 m :2"
+    assert cif_parser.SpaceGroup.from_number(227) == cif_parser.SpaceGroup.from_hermann_mauguin("F d -3 m")
+    assert cif_parser.SpaceGroup.from_hall("-I 4bd;2ab;3").number == 230
+    assert cif_parser.SpaceGroup.from_hermann_mauguin("P n 21 a") is None
+    assert repr(group) == "SpaceGroup('-P 2yn')"
+
+    doc = cif_parser.parse_file(str(TESTS / "example_cifs/quartz/ccdc_quartz.cif"))
+    listed = {str(op) for op in doc.first_block().symmetry_operations()}
+    assert {str(op) for op in doc.space_group().operations} == listed
+
+    block = cif_parser.parse("data_x\n_space_group_IT_number 19\n").first_block()
+    assert block.space_group().hermann_mauguin == "P 21 21 21"
+    assert cif_parser.parse("data_x\n_a 1\n").first_block().space_group() is None
@@ -0,0 +1,41 @@
+"""Tests for free-text normalization."""
+
+import pytest
+
+import cif_parser
+
+CIF = r"""
+data_x
+_publ_section_title
+;
+  Structure of  Ni(H~2~O)~6~^2+^
+  by Dvo\<r\'ak
+
+  Measured at 90\% tilt
+;
+_cell_length_a 5.0
+"""
+
+
+def test_normalized_text_converts_markup():
+    """Lines are joined per paragraph and markup becomes Unicode."""
+    value = cif_parser.parse(CIF).first_block().get_item("_publ_section_title")
+    assert value.normalized_text() == (
+        "Structure of Ni(H₂O)₆²⁺ by Dvořák\n\nMeasured at 90° tilt"
+    )
+
+
+def test_markup_modes():
+    """Markup can be kept as written or produced from Unicode."""
+    value = cif_parser.parse(CIF).first_block().get_item("_publ_section_title")
+    assert value.normalized_text(markup="keep").startswith("Structure of Ni(H~2~O)")
+    unicode_value = cif_parser.parse("data_x\n_title 'Müller, 90°'\n").first_block()
+    assert unicode_value.get_item("_title").normalized_text(markup="cif") == 'M\\"uller, 90\\%'
+
+
+def test_non_text_and_bad_mode():
+    """Numbers give None, and an unknown mode raises."""
+    block = cif_parser.parse(CIF).first_block()
+    assert block.get_item("_cell_length_a").normalized_text() is None
+    with pytest.raises(ValueError, match="markup"):
+        block.get_item("_publ_section_title").normalized_text(markup="html")
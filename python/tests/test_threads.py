@@ -0,0 +1,64 @@
+"""Tests for changing a document while other threads read it."""
+
+import threading
+from pathlib import Path
+
+import cif_parser
+
+MIXED = Path(__file__).parents[2] / "tests" / "fixtures" / "mixed_loop.cif"
+
+
+def test_blocks_share_their_document():
+    """A change made through one block is seen by the document and its other blocks."""
+    doc = cif_parser.parse_file(str(MIXED))
+    block = doc.first_block()
+    other = doc[0]
+    loop = block.get_loop(0)
+
+    block.repair_mixed_loops()
+
+    assert len(other.loops) == 3
+    assert len(doc.first_block().loops) == 3
+    # Loops are snapshots taken before the change
+    assert "_atom_site_aniso_U_11" in loop.tags
+
+
+def test_mutation_while_iterating_loop():
+    """Readers see each loop either before or after a repair, never in between."""
+    original = cif_parser.parse_file(str(MIXED)).first_block()
+    before = [loop.tags for loop in original.loops]
+    original.repair_mixed_loops()
+    after = [loop.tags for loop in original.loops]
+
+    errors = []
+
+    for _ in range(20):
+        doc = cif_parser.parse_file(str(MIXED))
+        block = doc.first_block()
+        started = threading.Event()
+
+        def read():
+            try:
+                started.set()
+                for _ in range(50):
+                    assert [loop.tags for loop in block.loops] in (before, after)
+                    loop = block.get_loop(0)
+                    rows = list(loop)
+                    assert len(rows) == len(loop)
+                    assert all(sorted(row) == sorted(loop.tags) for row in rows)
+            except Exception as error:  # noqa: BLE001 - reported below
+                errors.append(error)
+
+        def write():
+            started.wait()
+            doc[0].repair_mixed_loops()
+
+        threads = [threading.Thread(target=read), threading.Thread(target=write)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+
+        assert [loop.tags for loop in doc.first_block().loops] == after
+
+    assert errors == []
@@ -0,0 +1,43 @@
+"""Tests for finding EXYZ/EADP-style ties between atom sites."""
+
+from pathlib import Path
+
+import cif_parser
+
+FIXTURE = Path(__file__).parents[2] / "tests" / "fixtures" / "ties" / "disordered.cif"
+
+
+def structure():
+    return cif_parser.parse_file(FIXTURE).first_block().structure()
+
+
+def test_detect_ties():
+    """Constraints and symmetry coincidences are both found, and told apart."""
+    ties = structure().detect_ties()
+    found = [(t["quantity"], t["labels"]) for t in ties]
+    assert found == [
+        ("coordinates", ["Na1", "K1"]),
+        ("coordinates", ["Fe1", "Co1"]),
+        ("u_iso", ["O9A", "O9B"]),
+        ("u_aniso", ["Fe1", "Co1"]),
+        ("u_aniso", ["C5A", "C5B"]),
+    ]
+    assert ties[0]["symmetry_required"]
+    assert not ties[1]["symmetry_required"]
+    assert ties[1]["documented"] is True
+    assert ties[2]["documented"] is False
+    assert ties[4]["across_parts"]
+
+
+def test_tolerance():
+    """A looser tolerance groups coordinates that differ in the last digit."""
+    loose = structure().detect_ties(tolerance=1e-3)
+    assert ("coordinates", ["O9A", "O10"]) in [(t["quantity"], t["labels"]) for t in loose]
+
+
+def test_sites_carry_aniso_and_disorder_group():
+    """Sites have their anisotropic tensor and disorder part."""
+    sites = {site["label"]: site for site in structure().sites}
+    assert sites["Fe1"]["u_aniso"][0] == 0.0141
+    assert sites["Na1"]["u_aniso"] is None
+    assert sites["C5B"]["disorder_group"] == "2"
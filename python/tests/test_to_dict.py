@@ -0,0 +1,132 @@
+"""Tests for Block.to_dict() and Document.to_dict()."""
+
+import json
+
+import pytest
+
+import cif_parser
+
+NESTED_CIF = """
+data_nested
+_known 1.5
+_unknown ?
+_inapplicable .
+loop_
+_atom_site_label
+_atom_site_occupancy
+C1 1.0
+N1 ?
+save_frame1
+_frame_item value
+loop_
+_col
+a
+b
+save_
+"""
+
+
+@pytest.fixture
+def nested_block():
+    """Block with items, a loop with a missing value, and a frame."""
+    return cif_parser.parse(NESTED_CIF).first_block()
+
+
+class TestBlockToDict:
+    """Test Block.to_dict()."""
+
+    def test_native_values(self, nested_block):
+        """Native mode maps ? and . to None."""
+        data = nested_block.to_dict()
+        assert data["name"] == "nested"
+        assert data["items"] == {
+            "_known": 1.5,
+            "_unknown": None,
+            "_inapplicable": None,
+        }
+
+    def test_raw_values(self, nested_block):
+        """Raw mode keeps the sentinel strings."""
+        data = nested_block.to_dict(values="raw")
+        assert data["items"]["_unknown"] == "?"
+        assert data["items"]["_inapplicable"] == "."
+        assert data["loops"][0]["rows"][1] == ["N1", "?"]
+
+    def test_loops_as_rows(self, nested_block):
+        """Default loop layout is tags plus rows."""
+        loop = nested_block.to_dict()["loops"][0]
+        assert loop["tags"] == ["_atom_site_label", "_atom_site_occupancy"]
+        assert loop["rows"] == [["C1", 1.0], ["N1", None]]
+
+    def test_loops_as_columns(self, nested_block):
+        """Column layout maps each tag to its values."""
+        loop = nested_block.to_dict(loops="columns")["loops"][0]
+        assert loop == {
+            "_atom_site_label": ["C1", "N1"],
+            "_atom_site_occupancy": [1.0, None],
+        }
+
+    def test_frames_recursive(self, nested_block):
+        """Frames are converted with their items and loops."""
+        frames = nested_block.to_dict()["frames"]
+        assert len(frames) == 1
+        assert frames[0]["name"] == "frame1"
+        assert frames[0]["items"] == {"_frame_item": "value"}
+        assert frames[0]["loops"] == [{"tags": ["_col"], "rows": [["a"], ["b"]]}]
+
+    def test_exclusions(self, nested_block):
+        """Loops and frames can be left out."""
+        data = nested_block.to_dict(include_loops=False, include_frames=False)
+        assert "loops" not in data
+        assert "frames" not in data
+
+    def test_json_serializable(self, loops_doc):
+        """Output can be passed straight to json.dumps."""
+        block = loops_doc.first_block()
+        assert json.loads(json.dumps(block.to_dict())) == block.to_dict()
+
+    def test_invalid_arguments(self, nested_block):
+        """Unknown modes raise ValueError."""
+        with pytest.raises(ValueError):
+            nested_block.to_dict(values="typed")
+        with pytest.raises(ValueError):
+            nested_block.to_dict(loops="table")
+
+    def test_large_block(self):
+        """Every row of a 10k-atom loop is converted; its speed is measured
+        in benchmarks/."""
+        lines = [
+            "data_big",
+            "loop_",
+            "_atom_site_label",
+            "_atom_site_type_symbol",
+            "_atom_site_fract_x",
+            "_atom_site_fract_y",
+            "_atom_site_fract_z",
+            "_atom_site_occupancy",
+        ]
+        lines += [f"C{i} C 0.{i:05d} 0.5 0.25 1.0" for i in range(10_000)]
+        block = cif_parser.parse("\n".join(lines) + "\n").first_block()
+
+        rows = block.to_dict()["loops"][0]["rows"]
+        assert len(rows) == 10_000
+        assert rows[-1] == ["C9999", "C", 0.09999, 0.5, 0.25, 1.0]
+
+
+class TestDocumentToDict:
+    """Test Document.to_dict()."""
+
+    def test_document_layout(self, nested_block):
+        """Document output wraps block dicts with the version."""
+        doc = cif_parser.parse(NESTED_CIF + "data_second\n_x 1\n")
+        data = doc.to_dict()
+        assert data["version"] == "1.1"
+        assert [b["name"] for b in data["blocks"]] == ["nested", "second"]
+        assert data["blocks"][0] == nested_block.to_dict()
+
+    def test_cif2_version(self):
+        """CIF 2.0 lists and tables become lists and dicts."""
+        doc = cif_parser.parse("#\\#CIF_2.0\ndata_t\n_list [1 ? 'a']\n")
+        data = doc.to_dict()
+        assert data["version"] == "2.0"
+        assert data["blocks"][0]["items"]["_list"] == [1.0, None, "a"]
@@ -0,0 +1,44 @@
+"""Tests for non-CIF text before the first block and after the last."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+TRAILER = Path(__file__).parents[2] / "tests" / "fixtures" / "trailer"
+
+
+def test_end_marker_trailer_raises_with_its_line():
+    """Strict parsing rejects a checksum after #END and says where it is."""
+    with pytest.raises(ValueError, match="line 27"):
+        cif_parser.parse_file(str(TRAILER / "end_marker.cif"))
+
+
+def test_end_marker_trailer_is_kept_leniently():
+    """The trailer is kept verbatim and not read as loop rows."""
+    doc = cif_parser.parse_file(str(TRAILER / "end_marker.cif"), lenient=True)
+    assert doc.trailer.startswith("#END\n")
+    assert "CHECKSUM  MD5  4c5dd84d7f55a1f7a3cfa2b3f1d7e2c9\n" in doc.trailer
+    assert doc.preamble is None
+    assert len(doc.first_block().loops[0]) == 4
+    assert any("kept as the trailer" in w for w in doc.warnings)
+
+
+def test_instrument_banner_is_kept_leniently():
+    """A banner before the first block becomes the preamble."""
+    with pytest.raises(ValueError, match="before the first data block"):
+        cif_parser.parse_file(str(TRAILER / "instrument_banner.cif"))
+
+    doc = cif_parser.parse_file(str(TRAILER / "instrument_banner.cif"), lenient=True)
+    assert doc.preamble.startswith("*** Bruker APEX3 v2019.1-0 ***\n")
+    assert doc.trailer is None
+    assert doc.first_block().name == "sad"
+
+
+def test_plain_documents_have_neither():
+    """Comments around the blocks are not a preamble or trailer."""
+    doc = cif_parser.parse("# header\ndata_a\n_x 1\n#END\n", lenient=True)
+    assert doc.preamble is None
+    assert doc.trailer is None
+    assert doc.warnings == []
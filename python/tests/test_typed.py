@@ -0,0 +1,36 @@
+"""Tests for reading block items as Python types."""
+
+import pytest
+
+import cif_parser
+
+CIF = "data_x\n_cell_length_a 5.431(2)\n_z 4\n_name NaCl\n_flag yes\n_t ?\n_u .\n"
+
+
+def test_typed_getters():
+    """Each getter converts the value, dropping any su."""
+    block = cif_parser.Document.parse(CIF).first_block()
+    assert block.get_float("_cell_length_a") == 5.431
+    assert block.get_int("_z") == 4
+    assert block.get_str("_name") == "NaCl"
+    assert block.get_str("_z") == "4"
+    assert block.get_bool("_flag") is True
+
+
+def test_unknown_and_absent_are_none():
+    """? and . read as None, as an absent tag does."""
+    block = cif_parser.Document.parse(CIF).first_block()
+    for tag in ["_t", "_u", "_missing"]:
+        assert block.get_float(tag) is None
+        assert block.get_int(tag) is None
+        assert block.get_str(tag) is None
+        assert block.get_bool(tag) is None
+
+
+def test_unreadable_values_raise():
+    """A value of the wrong kind raises ValueError naming the tag."""
+    block = cif_parser.Document.parse(CIF).first_block()
+    with pytest.raises(ValueError, match="_name: 'NaCl' is not a number"):
+        block.get_float("_name")
+    with pytest.raises(ValueError, match="is not an integer"):
+        block.get_int("_cell_length_a")
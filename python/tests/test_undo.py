@@ -0,0 +1,48 @@
+"""Tests for document snapshots, restore, and transactions."""
+
+from pathlib import Path
+
+import pytest
+
+import cif_parser
+
+EDIT = Path(__file__).parents[2] / "tests" / "fixtures" / "undo" / "edit.cif"
+DRAFT = Path(__file__).parents[2] / "tests" / "fixtures" / "redact" / "prepublication.cif"
+
+
+def test_snapshot_and_restore():
+    """Restoring a snapshot gives back the same contents."""
+    doc = cif_parser.parse_file(str(DRAFT))
+    original = doc.to_dict()
+    snapshot = doc.snapshot()
+    assert snapshot.block_names == doc.block_names
+    assert len(snapshot) == len(doc)
+
+    doc.redact(profile="pre_publication", remove=True)
+    assert doc.to_dict() != original
+
+    doc.restore(snapshot)
+    assert doc.to_dict() == original
+    assert snapshot.to_document().to_dict() == original
+
+
+def test_transaction_rolls_back_on_exception():
+    """A with block that raises leaves the document as it was."""
+    doc = cif_parser.parse_file(str(DRAFT))
+    original = doc.to_dict()
+
+    with pytest.raises(RuntimeError):
+        with doc.transaction():
+            doc.redact(profile="pre_publication")
+            raise RuntimeError("check failed")
+    assert doc.to_dict() == original
+
+    with doc.transaction() as same:
+        same.redact(profile="pre_publication")
+    assert doc.to_dict() != original
+
+
+def test_snapshot_size():
+    """Snapshots report their size."""
+    doc = cif_parser.parse_file(str(EDIT))
+    assert doc.snapshot().bytes > 0
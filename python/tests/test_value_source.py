@@ -0,0 +1,95 @@
+"""Tests for Value.source provenance."""
+
+import cif_parser
+
+SOURCE_CIF = """
+data_prov
+_cell_length_a 1.234
+loop_
+_atom_site_label
+_atom_site_occupancy
+C1 1.0
+N1 0.5
+save_frame1
+_frame_item value
+loop_
+_col
+a
+save_
+"""
+
+
+def _block():
+    return cif_parser.parse(SOURCE_CIF).first_block()
+
+
+def test_block_item_source():
+    """Block items know their block and tag but have no row."""
+    value = _block().get_item("_cell_length_a")
+    assert value.source == ("prov", None, "_cell_length_a", None)
+    assert value.source.block == "prov"
+    assert value.source.row is None
+
+
+def test_block_items_dict_source():
+    """Values from items() carry the same provenance as get_item()."""
+    items = _block().items()
+    assert items["_cell_length_a"].source.tag == "_cell_length_a"
+
+
+def test_loop_value_sources():
+    """Loop accessors record the row each value came from."""
+    loop = _block().find_loop("_atom_site_label")
+
+    by_tag = loop.get_by_tag(1, "_atom_site_occupancy")
+    assert by_tag.source == ("prov", None, "_atom_site_occupancy", 1)
+
+    column = loop.get_column("_atom_site_label")
+    assert [v.source.row for v in column] == [0, 1]
+
+    row = loop.get_row_dict(0)
+    assert row["_atom_site_occupancy"].source.tag == "_atom_site_occupancy"
+    assert row["_atom_site_occupancy"].source.row == 0
+
+    assert loop.get(1, 0).source.tag == "_atom_site_label"
+
+
+def test_iteration_sources():
+    """Rows yielded by iterating a loop carry provenance."""
+    loop = _block().loops[0]
+    rows = [row["_atom_site_label"].source.row for row in loop]
+    assert rows == [0, 1]
+
+
+def test_frame_sources():
+    """Values inside save frames record the frame name."""
+    frame = _block().frames[0]
+    assert frame.get_item("_frame_item").source == (
+        "prov",
+        "frame1",
+        "_frame_item",
+        None,
+    )
+    assert frame.get_loop(0).get_by_tag(0, "_col").source.frame == "frame1"
+
+
+def test_repr_includes_source():
+    """repr shows the tag, plus the row for loop values."""
+    block = _block()
+    assert repr(block.get_item("_cell_length_a")) == "Value(1.234, _cell_length_a)"
+    label = block.find_loop("_atom_site_label").get_by_tag(0, "_atom_site_label")
+    assert repr(label) == "Value('C1', _atom_site_label[0])"
+
+
+def test_source_is_named_tuple():
+    """ValueSource is exported and behaves as a tuple."""
+    source = _block().get_item("_cell_length_a").source
+    assert isinstance(source, cif_parser.ValueSource)
+    block, frame, tag, row = source
+    assert (block, frame, tag, row) == ("prov", None, "_cell_length_a", None)
+
+
+def test_equality_ignores_source():
+    """Values with the same content compare equal regardless of origin."""
+    doc = cif_parser.parse("data_a\n_x 1\ndata_b\n_y 1\n")
+    assert doc[0].get_item("_x") == doc[1].get_item("_y")
@@ -144,6 +144,23 @@ def test_scientific_notation():
     assert value.numeric == pytest.approx(0.000123)
 
 
+def test_standard_uncertainty():
+    """Numbers written with an su are read with it."""
+    cif = "data_test\n_a 1.234(5)\n_b 2.5\n_c C1"
+    block = cif_parser.parse(cif).first_block()
+
+    value, su = block.get_item("_a").numeric_with_su
+    assert value == pytest.approx(1.234)
+    assert su == pytest.approx(0.005)
+    assert block.get_item("_a").su == pytest.approx(0.005)
+    assert block.get_item("_a").value_type == "numeric"
+    assert block.get_item("_a").is_numeric
+    assert block.get_item("_a").numeric == pytest.approx(1.234)
+    assert block.get_item("_b").numeric_with_su == (2.5, None)
+    assert block.get_item("_b").su is None
+    assert block.get_item("_c").numeric_with_su is None
+
+
 def test_quoted_string():
     """Test quoted string values."""
     cif = "data_test\n_item 'quoted string'"
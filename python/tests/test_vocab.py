@@ -0,0 +1,46 @@
+"""Tests for enumerated core values and typo suggestions."""
+
+from pathlib import Path
+
+import cif_parser
+
+TYPOS = Path(__file__).parents[2] / "tests" / "fixtures" / "vocab" / "typos.cif"
+
+
+def test_check_vocabularies():
+    """Out-of-vocabulary values come with the nearest allowed value."""
+    block = cif_parser.parse_file(str(TYPOS)).first_block()
+    issues = block.check_vocabularies()
+    adp = issues[0]
+    assert adp["tag"] == "_atom_site_adp_type"
+    assert adp["row"] == 2
+    assert adp["value"] == "Uanl"
+    assert adp["suggestion"] == "Uani"
+    assert "Biso" in adp["allowed"]
+    suggestions = {issue["value"]: issue["suggestion"] for issue in issues}
+    assert suggestions["multiscan"] == "multi-scan"
+    assert suggestions["X"] is None
+
+
+def test_case_is_ignored():
+    """UANI, uiso, and CONSTR are accepted."""
+    block = cif_parser.parse_file(str(TYPOS)).first_block()
+    values = [issue["value"] for issue in block.check_vocabularies()]
+    assert "UANI" not in values
+    assert "uiso" not in values
+    assert "CONSTR" not in values
+
+
+def test_issues_in_sanity_check():
+    """Vocabulary issues are reported with the other sanity issues."""
+    block = cif_parser.parse_file(str(TYPOS)).first_block()
+    vocabulary = [i for i in block.sanity_check() if i["id"] == "vocabulary"]
+    assert len(vocabulary) == 6
+    assert vocabulary[0]["rule"].endswith("did you mean Uani?")
+
+
+def test_atom_site_adp_type_is_canonical():
+    """adp_type is the dictionary's spelling, or the value as written."""
+    block = cif_parser.parse_file(str(TYPOS)).first_block()
+    adp = [site["adp_type"] for site in block.atom_sites()]
+    assert adp == ["Uani", "Uiso", "Uanl", "Uiso", None]
@@ -0,0 +1,52 @@
+"""Tests for searching and walking save frames."""
+
+import cif_parser
+
+SCANS = """data_run
+_title 'whole run'
+loop_
+_axis_id
+omega
+phi
+save_scan1
+_scan_id 1
+loop_
+_scan_point
+0.0
+0.5
+save_
+save_scan2
+_scan_id 2
+_scan_detector pilatus
+save_
+"""
+
+
+def test_get_searches_frames_on_request():
+    """Frames are searched only with search_frames, first frame first."""
+    block = cif_parser.parse(SCANS).first_block()
+    assert block.get("_scan_id") is None
+    assert block.get("_title").text == "whole run"
+
+    scan_id = block.get("_scan_id", search_frames=True)
+    assert scan_id.numeric == 1.0
+    assert scan_id.source.frame == "scan1"
+    assert block.get("_scan_detector", search_frames=True).text == "pilatus"
+    assert block.get("_missing", search_frames=True) is None
+
+
+def test_walk():
+    """walk() lists the block's entries, then each frame's, with paths."""
+    block = cif_parser.parse(SCANS).first_block()
+    entries = block.walk()
+    assert [(path, tag) for path, tag, _ in entries] == [
+        ([], "_title"),
+        ([], None),
+        (["scan1"], "_scan_id"),
+        (["scan1"], None),
+        (["scan2"], "_scan_id"),
+        (["scan2"], "_scan_detector"),
+    ]
+    assert entries[1][2].tags == ["_axis_id"]
+    assert entries[3][2].tags == ["_scan_point"]
+    assert entries[4][2].source.frame == "scan2"
@@ -0,0 +1,74 @@
+"""Tests for writing CIF files row by row with Writer."""
+
+import pytest
+
+import cif_parser
+
+
+def test_loop_rows_reparse(tmp_path):
+    """Rows written one at a time parse back to the same loop."""
+    path = tmp_path / "hkl.cif"
+    rows = 50_000
+    with cif_parser.Writer(path) as w:
+        w.begin_block("hkl")
+        w.write_item("_diffrn_radiation_wavelength", 0.0251)
+        w.write_item("_title", "calculated structure factors")
+        w.begin_loop(["_refln_index_h", "_refln_index_k", "_refln_F_calc", "_note"])
+        for i in range(rows):
+            w.write_row([i % 101 - 50, i // 101, i * 0.5, None if i % 2 else "ok"])
+        w.end_loop()
+        w.end_block()
+
+    block = cif_parser.parse_file(str(path)).first_block()
+    assert block.get_item("_title").text == "calculated structure factors"
+    loop = block.find_loop("_refln_index_h")
+    assert len(loop) == rows
+    assert loop.get_column("_refln_F_calc")[3].numeric == 1.5
+    assert loop.get_column("_note")[1].is_unknown
+
+
+def test_quoting_and_cif2_values(tmp_path):
+    """Text is quoted as needed, and CIF 2.0 lists are written."""
+    path = tmp_path / "x.cif"
+    with cif_parser.Writer(path, version="2.0") as w:
+        w.begin_block("x")
+        w.write_item("_name", "two words")
+        w.write_item("_number_text", "1.5")
+        w.write_item("_list", [1, "a b"])
+        w.end_block()
+
+    text = path.read_text()
+    assert text.startswith("#\\#CIF_2.0\n")
+    assert "_name 'two words'\n" in text
+    block = cif_parser.parse_file(str(path)).first_block()
+    assert block.get_item("_number_text").is_text
+    assert block.get_item("_list").is_list
+
+
+def test_out_of_order_calls(tmp_path):
+    """Calls that break the file structure raise ValueError."""
+    w = cif_parser.Writer(tmp_path / "bad.cif")
+    with pytest.raises(ValueError, match="out of order"):
+        w.write_item("_a", 1)
+    w.begin_block("x")
+    w.begin_loop(["_a", "_b"])
+    with pytest.raises(ValueError, match="2 columns"):
+        w.write_row([1])
+    with pytest.raises(ValueError, match="out of order"):
+        w.close()
+    w.end_loop()
+    w.end_block()
+    w.close()
+    with pytest.raises(ValueError, match="closed"):
+        w.begin_block("y")
+
+
+def test_unsupported_values(tmp_path):
+    """Values with no CIF form are rejected."""
+    with cif_parser.Writer(tmp_path / "x.cif") as w:
+        w.begin_block("x")
+        with pytest.raises(TypeError):
+            w.write_item("_flag", True)
+        with pytest.raises(TypeError):
+            w.write_item("_obj", object())
+        w.end_block()
@@ -0,0 +1,207 @@
+//! Comparing measured numbers with their standard uncertainties.
+//!
+//! Whether two numbers agree depends on how well they are known: 1.5406(3)
+//! and 1.5410 differ by 1.3σ, while 1.5406(1) and 1.5410 differ by 4σ.
+//! [`compare`] measures the difference in units of the two sus combined in
+//! quadrature, and falls back to a relative tolerance when neither number
+//! has an su.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::approx::{compare, Comparison};
+//!
+//! match compare((1.5406, Some(0.0003)), (1.5410, None), 3.0) {
+//!     Comparison::Equal { sigmas } => assert!((sigmas - 1.333).abs() < 1e-3),
+//!     other => panic!("{other:?}"),
+//! }
+//! assert!(!compare((1.5406, Some(0.0001)), (1.5410, None), 3.0).is_equal());
+//! ```
+
+use std::fmt;
+
+/// The outcome of comparing two numbers, from [`ValueComparison::compare`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// The numbers agree: they differ by at most `n_sigma` σ
+    Equal {
+        /// Absolute difference in units of σ
+        sigmas: f64,
+    },
+    /// The numbers differ by more than `n_sigma` σ
+    Differs {
+        /// Absolute difference in units of σ
+        sigmas: f64,
+    },
+    /// A number or su is not finite, an su is negative, or `n_sigma` is
+    /// not positive
+    Incomparable,
+}
+
+impl Comparison {
+    /// The absolute difference in units of σ, unless incomparable
+    pub fn sigmas(&self) -> Option<f64> {
+        match *self {
+            Comparison::Equal { sigmas } | Comparison::Differs { sigmas } => Some(sigmas),
+            Comparison::Incomparable => None,
+        }
+    }
+
+    /// Whether the numbers agree
+    pub fn is_equal(&self) -> bool {
+        matches!(self, Comparison::Equal { .. })
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Comparison::Equal { sigmas } => write!(f, "equal ({sigmas:.1}σ)"),
+            Comparison::Differs { sigmas } => write!(f, "differs by {sigmas:.1}σ"),
+            Comparison::Incomparable => write!(f, "incomparable"),
+        }
+    }
+}
+
+/// Settings for comparing numbers that may have sus.
+///
+/// The su of the difference is the two sus combined in quadrature, a
+/// missing su counting as zero. When that is zero, because neither number
+/// has an su, `relative_tolerance` times the larger magnitude stands in for
+/// `n_sigma` σ, so the numbers are equal when they are that close.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueComparison {
+    /// Largest relative difference accepted between numbers without sus
+    pub relative_tolerance: f64,
+}
+
+impl Default for ValueComparison {
+    fn default() -> Self {
+        ValueComparison {
+            relative_tolerance: 1e-3,
+        }
+    }
+}
+
+impl ValueComparison {
+    /// Create the default settings, a relative tolerance of 0.1%
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the relative tolerance used for numbers without sus
+    pub fn relative_tolerance(mut self, relative_tolerance: f64) -> Self {
+        self.relative_tolerance = relative_tolerance;
+        self
+    }
+
+    /// Compare `a` and `b`, each a number and its su.
+    ///
+    /// They are equal when they differ by at most `n_sigma` σ, inclusive.
+    pub fn compare(
+        &self,
+        a: (f64, Option<f64>),
+        b: (f64, Option<f64>),
+        n_sigma: f64,
+    ) -> Comparison {
+        let (su_a, su_b) = (a.1.unwrap_or(0.0), b.1.unwrap_or(0.0));
+        let inputs = [a.0, b.0, su_a, su_b, n_sigma, self.relative_tolerance];
+        if inputs.iter().any(|x| !x.is_finite()) || su_a < 0.0 || su_b < 0.0 || n_sigma <= 0.0 {
+            return Comparison::Incomparable;
+        }
+
+        let difference = (a.0 - b.0).abs();
+        let su = su_a.hypot(su_b);
+        let (sigmas, equal) = if su > 0.0 {
+            let sigmas = difference / su;
+            (sigmas, sigmas <= n_sigma)
+        } else {
+            let limit = self.relative_tolerance * a.0.abs().max(b.0.abs());
+            let sigmas = match difference {
+                0.0 => 0.0,
+                _ => n_sigma * difference / limit,
+            };
+            (sigmas, difference <= limit)
+        };
+        if equal {
+            Comparison::Equal { sigmas }
+        } else {
+            Comparison::Differs { sigmas }
+        }
+    }
+}
+
+/// Compare `a` and `b` with the default settings; see
+/// [`ValueComparison::compare`].
+pub fn compare(a: (f64, Option<f64>), b: (f64, Option<f64>), n_sigma: f64) -> Comparison {
+    ValueComparison::new().compare(a, b, n_sigma)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_su_combined_in_quadrature() {
+        // σ = √(3² + 4²) = 5
+        let comparison = compare((0.0, Some(3.0)), (7.5, Some(4.0)), 3.0);
+        assert_eq!(comparison, Comparison::Equal { sigmas: 1.5 });
+        let comparison = compare((0.0, Some(3.0)), (20.0, Some(4.0)), 3.0);
+        assert_eq!(comparison, Comparison::Differs { sigmas: 4.0 });
+    }
+
+    #[test]
+    fn test_one_sided_su() {
+        assert_eq!(
+            compare((10.0, Some(0.5)), (11.0, None), 3.0),
+            Comparison::Equal { sigmas: 2.0 }
+        );
+        assert_eq!(
+            compare((11.0, None), (10.0, Some(0.25)), 3.0),
+            Comparison::Differs { sigmas: 4.0 }
+        );
+    }
+
+    #[test]
+    fn test_zero_su_uses_relative_tolerance() {
+        // 0.05% apart: equal within the default 0.1%
+        let comparison = compare((999.5, Some(0.0)), (1000.0, None), 3.0);
+        assert!(comparison.is_equal());
+        assert!((comparison.sigmas().unwrap() - 1.5).abs() < 1e-9);
+        // 0.2% apart
+        let comparison = compare((1000.0, None), (998.0, Some(0.0)), 3.0);
+        assert!(!comparison.is_equal());
+        assert!((comparison.sigmas().unwrap() - 6.0).abs() < 1e-9);
+        assert_eq!(
+            compare((0.0, None), (0.0, None), 3.0),
+            Comparison::Equal { sigmas: 0.0 }
+        );
+    }
+
+    #[test]
+    fn test_exactly_at_threshold_is_equal() {
+        assert_eq!(
+            compare((0.0, Some(3.0)), (10.0, Some(4.0)), 2.0),
+            Comparison::Equal { sigmas: 2.0 }
+        );
+        assert!(!compare((0.0, Some(3.0)), (10.0, Some(4.0)), 1.999).is_equal());
+
+        let relative = ValueComparison::new().relative_tolerance(0.5);
+        assert!(relative.compare((2.0, None), (4.0, None), 3.0).is_equal());
+        assert!(!relative.compare((2.0, None), (4.001, None), 3.0).is_equal());
+    }
+
+    #[test]
+    fn test_incomparable() {
+        for (a, b, n_sigma) in [
+            ((f64::NAN, None), (1.0, None), 3.0),
+            ((1.0, Some(f64::INFINITY)), (1.0, None), 3.0),
+            ((1.0, Some(-0.1)), (1.0, None), 3.0),
+            ((1.0, None), (1.0, None), 0.0),
+        ] {
+            let comparison = compare(a, b, n_sigma);
+            assert_eq!(comparison, Comparison::Incomparable);
+            assert_eq!(comparison.sigmas(), None);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Loops written as Apache Arrow IPC files.
+//!
+//! Enabled by the `arrow` feature, and behind `cif arrow`.
+//! [`CifLoop::to_record_batch`] turns a loop into an Arrow record batch with
+//! one column per tag, and [`CifLoop::write_arrow_ipc`] writes that batch to
+//! an Arrow IPC file (Feather v2), which pandas, polars, DuckDB, and Spark
+//! read directly.
+//!
+//! Each column's type is inferred from its values, ignoring `?` and `.`,
+//! which become nulls:
+//!
+//! - `Int64` if every value is a whole number without an su
+//! - `Float64` if every value is a number, with an extra `Float64` column
+//!   named `<tag>_su` holding the sus if any value has one
+//! - `Utf8` otherwise, with numbers written back as text
+//!
+//! The schema metadata records the tags in loop order as a JSON array under
+//! `cif.tags`, and, when [`ArrowOptions`] gives them, the block name under
+//! `cif.block` and the source file and its SHA-256 under `cif.source` and
+//! `cif.sha256`.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::arrow::ArrowOptions;
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_x
+//! loop_
+//! _atom_site_label
+//! _atom_site_fract_x
+//! _atom_site_occupancy
+//! C1 0.123(4) 1
+//! C2 0.5 ?
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let batch = doc.blocks[0].loops[0]
+//!     .to_record_batch(&ArrowOptions::new().block("x"))
+//!     .unwrap();
+//!
+//! let names: Vec<&str> = batch
+//!     .schema_ref()
+//!     .fields()
+//!     .iter()
+//!     .map(|field| field.name().as_str())
+//!     .collect();
+//! assert_eq!(
+//!     names,
+//!     ["_atom_site_label", "_atom_site_fract_x", "_atom_site_fract_x_su", "_atom_site_occupancy"]
+//! );
+//! assert_eq!(batch.column(3).null_count(), 1);
+//! assert_eq!(batch.schema_ref().metadata()["cif.block"], "x");
+//! ```
+
+use crate::ast::{CifLoop, CifValue, CifVersion, DocumentSource};
+use crate::error::CifError;
+use crate::writer::inline_value;
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Largest whole number an `f64` holds exactly, 2⁵³
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// What [`CifLoop::to_record_batch`] records about where a loop came from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArrowOptions {
+    block: Option<String>,
+    source: Option<DocumentSource>,
+    su_columns: bool,
+}
+
+impl ArrowOptions {
+    /// No block or source, with su columns
+    pub fn new() -> Self {
+        ArrowOptions {
+            su_columns: true,
+            ..Default::default()
+        }
+    }
+
+    /// Record the name of the block the loop is in
+    pub fn block(mut self, name: impl Into<String>) -> Self {
+        self.block = Some(name.into());
+        self
+    }
+
+    /// Record the file the loop was read from and its content hash
+    pub fn source(mut self, source: &DocumentSource) -> Self {
+        self.source = Some(source.clone());
+        self
+    }
+
+    /// Whether numeric columns with sus get a `<tag>_su` column; with
+    /// `false` the sus are dropped (default `true`)
+    pub fn su_columns(mut self, su_columns: bool) -> Self {
+        self.su_columns = su_columns;
+        self
+    }
+}
+
+/// The Arrow type a column is written as
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Int64,
+    /// With an su column if any value has an su
+    Float64 {
+        su: bool,
+    },
+    Utf8,
+}
+
+/// The narrowest type that holds every value of a column
+fn infer(values: &[&CifValue]) -> ColumnType {
+    let mut column_type = ColumnType::Int64;
+    for value in values {
+        if matches!(value, CifValue::Unknown | CifValue::NotApplicable) {
+            continue;
+        }
+        let Some((number, su)) = value.as_numeric_with_su() else {
+            return ColumnType::Utf8;
+        };
+        let whole = matches!(value, CifValue::Numeric(_))
+            && number.fract() == 0.0
+            && number.abs() <= MAX_EXACT_INTEGER;
+        column_type = match column_type {
+            ColumnType::Int64 if whole => ColumnType::Int64,
+            ColumnType::Int64 => ColumnType::Float64 { su: su.is_some() },
+            ColumnType::Float64 { su: had_su } => ColumnType::Float64 {
+                su: had_su || su.is_some(),
+            },
+            ColumnType::Utf8 => ColumnType::Utf8,
+        };
+    }
+    column_type
+}
+
+/// Text of a value in a `Utf8` column, or `None` for `?` and `.`
+fn text(value: &CifValue) -> Option<String> {
+    match value {
+        CifValue::Unknown | CifValue::NotApplicable => None,
+        CifValue::Numeric(n) => Some(n.to_string()),
+        value => match value.as_string() {
+            Some(text) => Some(text.to_string()),
+            None => Some(inline_value(value, CifVersion::V2_0)),
+        },
+    }
+}
+
+fn arrow_error(error: ArrowError) -> CifError {
+    match error {
+        ArrowError::IoError(_, error) => CifError::Io(error),
+        error => CifError::Io(std::io::Error::other(error)),
+    }
+}
+
+/// `metadata` as the map [`Schema`] takes, iterating in key order.
+///
+/// The IPC writer records metadata in the map's iteration order, which
+/// depends on its hasher's random keys, so the same loop would give files
+/// differing from run to run. Hashers are tried until one iterates in key
+/// order; with the few keys written, a handful of tries find one.
+fn in_key_order(metadata: BTreeMap<String, String>) -> HashMap<String, String> {
+    loop {
+        let map: HashMap<String, String> = metadata.clone().into_iter().collect();
+        if map.keys().eq(metadata.keys()) {
+            return map;
+        }
+    }
+}
+
+impl CifLoop {
+    /// The loop as an Arrow record batch, one row per loop row.
+    ///
+    /// See the [module documentation](crate::arrow) for the column types
+    /// and the schema metadata.
+    pub fn to_record_batch(&self, options: &ArrowOptions) -> Result<RecordBatch, CifError> {
+        let mut fields = Vec::new();
+        let mut columns: Vec<ArrayRef> = Vec::new();
+        for tag in &self.tags {
+            let values = self.get_column(tag).unwrap_or_default();
+            match infer(&values) {
+                ColumnType::Int64 => {
+                    let array: Int64Array = values
+                        .iter()
+                        .map(|v| v.as_numeric().map(|n| n as i64))
+                        .collect();
+                    fields.push(Field::new(tag, DataType::Int64, true));
+                    columns.push(Arc::new(array));
+                }
+                ColumnType::Float64 { su } => {
+                    let numbers: Vec<Option<(f64, Option<f64>)>> =
+                        values.iter().map(|v| v.as_numeric_with_su()).collect();
+                    let array: Float64Array = numbers.iter().map(|n| n.map(|(x, _)| x)).collect();
+                    fields.push(Field::new(tag, DataType::Float64, true));
+                    columns.push(Arc::new(array));
+                    if su && options.su_columns {
+                        let sus: Float64Array =
+                            numbers.iter().map(|n| n.and_then(|(_, su)| su)).collect();
+                        fields.push(Field::new(format!("{tag}_su"), DataType::Float64, true));
+                        columns.push(Arc::new(sus));
+                    }
+                }
+                ColumnType::Utf8 => {
+                    let array: StringArray = values.iter().map(|v| text(v)).collect();
+                    fields.push(Field::new(tag, DataType::Utf8, true));
+                    columns.push(Arc::new(array));
+                }
+            }
+        }
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert(
+            "cif.tags".to_string(),
+            serde_json::to_string(&self.tags).unwrap_or_default(),
+        );
+        if let Some(block) = &options.block {
+            metadata.insert("cif.block".to_string(), block.clone());
+        }
+        if let Some(source) = &options.source {
+            metadata.insert("cif.sha256".to_string(), source.sha256.clone());
+            if let Some(path) = &source.path {
+                metadata.insert("cif.source".to_string(), path.display().to_string());
+            }
+        }
+        let metadata = in_key_order(metadata);
+        let schema = Schema::new_with_metadata(fields, metadata);
+        RecordBatch::try_new(Arc::new(schema), columns).map_err(arrow_error)
+    }
+
+    /// Write the loop to an Arrow IPC file (Feather v2) at `path`.
+    ///
+    /// The file holds the single record batch from
+    /// [`to_record_batch`](Self::to_record_batch).
+    pub fn write_arrow_ipc(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ArrowOptions,
+    ) -> Result<(), CifError> {
+        let batch = self.to_record_batch(options)?;
+        let file = BufWriter::new(File::create(path)?);
+        let mut writer = FileWriter::try_new(file, batch.schema_ref()).map_err(arrow_error)?;
+        writer.write(&batch).map_err(arrow_error)?;
+        writer.finish().map_err(arrow_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer() {
+        let column = |values: &[&str]| {
+            let values: Vec<CifValue> = values.iter().map(|v| CifValue::parse_value(v)).collect();
+            infer(&values.iter().collect::<Vec<_>>())
+        };
+        assert_eq!(column(&["1", "2", "?"]), ColumnType::Int64);
+        assert_eq!(
+            column(&["1", "2.5", "."]),
+            ColumnType::Float64 { su: false }
+        );
+        assert_eq!(column(&["1", "0.25(3)"]), ColumnType::Float64 { su: true });
+        assert_eq!(column(&["1", "C1"]), ColumnType::Utf8);
+        assert_eq!(column(&["?", "."]), ColumnType::Int64);
+        // Text with an su is not a whole number, even without a decimal point
+        assert_eq!(column(&["12(3)"]), ColumnType::Float64 { su: true });
+    }
+}
@@ -1,7 +1,7 @@
 //! Data block structures in CIF files.
 
 use super::{CifFrame, CifLoop, CifValue};
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 /// Represents a data block in a CIF file.
 ///
@@ -37,6 +37,13 @@ use std::collections::HashMap;
 /// - `DATA_STRUCTURE` → name is `"STRUCTURE"` (case-insensitive parsing)
 /// - `global_` → name is `""` (empty string for global blocks)
 ///
+/// # Order
+///
+/// Items keep the order they appear in the file, and loops and frames are
+/// kept in file order too. [`iter_items`](Self::iter_items) and
+/// [`iter_all_tags`](Self::iter_all_tags) are the ordered views the rest of
+/// the crate uses.
+///
 /// # Access Methods
 ///
 /// ```
@@ -55,11 +62,13 @@ use std::collections::HashMap;
 /// let all_tags = block.get_loop_tags();
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CifBlock {
     /// Block name (extracted from `data_name` header)
     pub name: String,
-    /// Data items (key-value pairs) in this block
-    pub items: HashMap<String, CifValue>,
+    /// Data items (key-value pairs) in this block, in file order
+    #[cfg_attr(feature = "serde", serde(with = "super::items_serde"))]
+    pub items: IndexMap<String, CifValue>,
     /// Loop structures (tabular data) in this block
     pub loops: Vec<CifLoop>,
     /// Save frames (named sub-containers) in this block
@@ -71,7 +80,7 @@ impl CifBlock {
     pub fn new(name: String) -> Self {
         CifBlock {
             name,
-            items: HashMap::new(),
+            items: IndexMap::new(),
             loops: Vec::new(),
             frames: Vec::new(),
         }
@@ -87,10 +96,39 @@ impl CifBlock {
     /// # let block = doc.first_block().unwrap();
     /// let value = block.get_item("_item");
     /// ```
+    ///
+    /// Loop columns are not data items, including the one-column loops that
+    /// [`DuplicateTags::Collapse`](crate::DuplicateTags::Collapse) makes of
+    /// repeated items; [`get_all`](Self::get_all) returns their values.
     pub fn get_item(&self, tag: &str) -> Option<&CifValue> {
         self.items.get(tag)
     }
 
+    /// Get every value of a tag, whether it is a data item or a loop column
+    ///
+    /// Values from all loops containing the tag are returned in order, after
+    /// the data item if there is one. Save frames are not searched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let cif = "data_test\n_title x\nloop_\n_n\n1\n2\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let block = doc.first_block().unwrap();
+    ///
+    /// assert_eq!(block.get_all("_title").len(), 1);
+    /// assert_eq!(block.get_all("_n").len(), 2);
+    /// assert!(block.get_all("_missing").is_empty());
+    /// ```
+    pub fn get_all(&self, tag: &str) -> Vec<&CifValue> {
+        let columns = self
+            .loops
+            .iter()
+            .filter_map(|loop_| loop_.get_column(tag))
+            .flatten();
+        self.items.get(tag).into_iter().chain(columns).collect()
+    }
+
     /// Find a loop containing a specific tag
     ///
     /// # Examples
@@ -154,4 +192,177 @@ impl CifBlock {
     pub fn items_iter(&self) -> impl Iterator<Item = (&String, &CifValue)> {
         self.items.iter()
     }
+
+    /// Iterate over data items in file order
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let cif = "data_test\n_zeta 1\n_alpha 2\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let block = doc.first_block().unwrap();
+    ///
+    /// let tags: Vec<&str> = block.iter_items().map(|(tag, _)| tag).collect();
+    /// assert_eq!(tags, ["_zeta", "_alpha"]);
+    /// ```
+    pub fn iter_items(&self) -> impl Iterator<Item = (&str, &CifValue)> {
+        self.items.iter().map(|(tag, value)| (tag.as_str(), value))
+    }
+
+    /// Iterate over the tags of this block: data items in file order, then
+    /// the columns of each loop in order
+    ///
+    /// Unlike [`all_tags`](Self::all_tags), save frames are not included.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{Document, TagLocation};
+    ///
+    /// let cif = "data_test\nloop_\n_x\n1\n_title t\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let tags: Vec<_> = doc.first_block().unwrap().iter_all_tags().collect();
+    ///
+    /// assert_eq!(
+    ///     tags,
+    ///     [("_title", TagLocation::Item), ("_x", TagLocation::Loop(0))]
+    /// );
+    /// ```
+    pub fn iter_all_tags(&self) -> impl Iterator<Item = (&str, TagLocation)> {
+        ordered_tags(&self.items, &self.loops)
+    }
+
+    /// Get a data item, searching save frames when the block lacks it
+    ///
+    /// The block's own items come first; after that, frames are searched
+    /// depth-first in file order and the first frame with the tag wins. Each
+    /// container is searched as [`get_item`](Self::get_item) does. Use
+    /// [`get_item_recursive_all`](Self::get_item_recursive_all) to see
+    /// whether more than one container has the tag.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let cif = "data_test\n_title t\nsave_scan1\n_scan_id 1\nsave_\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let block = doc.first_block().unwrap();
+    ///
+    /// assert!(block.get_item("_scan_id").is_none());
+    /// assert!(block.get_item_recursive("_scan_id").is_some());
+    /// ```
+    pub fn get_item_recursive(&self, tag: &str) -> Option<&CifValue> {
+        self.get_item(tag)
+            .or_else(|| self.frames.iter().find_map(|frame| frame.get_item(tag)))
+    }
+
+    /// Get a data item from the block and from every save frame that has it
+    ///
+    /// Hits come in the order [`get_item_recursive`](Self::get_item_recursive)
+    /// searches, each with the names of the frames containing it (empty for
+    /// the block itself). More than one hit means the tag is ambiguous.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let cif = "data_test\nsave_a\n_scan_id 1\nsave_\nsave_b\n_scan_id 2\nsave_\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let hits = doc.first_block().unwrap().get_item_recursive_all("_scan_id");
+    ///
+    /// let paths: Vec<_> = hits.iter().map(|(path, _)| path.clone()).collect();
+    /// assert_eq!(paths, [["a"], ["b"]]);
+    /// ```
+    pub fn get_item_recursive_all(&self, tag: &str) -> Vec<(Vec<&str>, &CifValue)> {
+        let frames = self
+            .frames
+            .iter()
+            .filter_map(|frame| Some((vec![frame.name.as_str()], frame.get_item(tag)?)));
+        self.get_item(tag)
+            .map(|value| (Vec::new(), value))
+            .into_iter()
+            .chain(frames)
+            .collect()
+    }
+
+    /// Find a loop containing a tag, searching save frames when the block
+    /// has none, in the same order as
+    /// [`get_item_recursive`](Self::get_item_recursive)
+    pub fn find_loop_recursive(&self, tag: &str) -> Option<&CifLoop> {
+        self.find_loop(tag)
+            .or_else(|| self.frames.iter().find_map(|frame| frame.find_loop(tag)))
+    }
+
+    /// Iterate over every data item and loop, in the block and its save
+    /// frames
+    ///
+    /// Each entry comes with the names of the frames containing it, empty
+    /// for the block itself. The block comes first, then each frame in file
+    /// order; within each, items come in file order before loops. CIF does
+    /// not allow save frames to nest, so a path holds at most one name.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{Document, WalkEntry};
+    ///
+    /// let cif = "data_test\n_title t\nsave_scan1\nloop_\n_n\n1\n2\nsave_\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let entries: Vec<_> = doc.first_block().unwrap().walk().collect();
+    ///
+    /// assert!(matches!(entries[0], (ref path, WalkEntry::Item("_title", _)) if path.is_empty()));
+    /// assert!(matches!(entries[1], (ref path, WalkEntry::Loop(_)) if path == &["scan1"]));
+    /// ```
+    pub fn walk(&self) -> impl Iterator<Item = (Vec<&str>, WalkEntry<'_>)> {
+        let frames = self.frames.iter().flat_map(|frame| {
+            container_entries(&frame.items, &frame.loops)
+                .map(move |entry| (vec![frame.name.as_str()], entry))
+        });
+        container_entries(&self.items, &self.loops)
+            .map(|entry| (Vec::new(), entry))
+            .chain(frames)
+    }
+}
+
+/// A data item or loop found by [`CifBlock::walk`]
+#[derive(Debug, Clone, Copy)]
+pub enum WalkEntry<'a> {
+    /// A data item, with its tag
+    Item(&'a str, &'a CifValue),
+    /// A loop
+    Loop(&'a CifLoop),
+}
+
+/// The items of a block or frame in order, then its loops
+fn container_entries<'a>(
+    items: &'a IndexMap<String, CifValue>,
+    loops: &'a [CifLoop],
+) -> impl Iterator<Item = WalkEntry<'a>> {
+    items
+        .iter()
+        .map(|(tag, value)| WalkEntry::Item(tag, value))
+        .chain(loops.iter().map(WalkEntry::Loop))
+}
+
+/// Where a tag from [`CifBlock::iter_all_tags`] or
+/// [`CifFrame::iter_all_tags`] is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagLocation {
+    /// A data item
+    Item,
+    /// A column of the loop at this index in `loops`
+    Loop(usize),
+}
+
+/// Items in order, then the columns of each loop
+pub(super) fn ordered_tags<'a>(
+    items: &'a IndexMap<String, CifValue>,
+    loops: &'a [CifLoop],
+) -> impl Iterator<Item = (&'a str, TagLocation)> {
+    let columns = loops.iter().enumerate().flat_map(|(index, loop_)| {
+        loop_
+            .tags
+            .iter()
+            .map(move |tag| (tag.as_str(), TagLocation::Loop(index)))
+    });
+    items
+        .keys()
+        .map(|tag| (tag.as_str(), TagLocation::Item))
+        .chain(columns)
 }
@@ -0,0 +1,130 @@
+//! Semantic equality between CIF structures.
+//!
+//! Two documents are *semantically* equal when they describe the same data,
+//! even if they would be written differently. This is the comparison used to
+//! check that writing and re-parsing a document loses nothing.
+//!
+//! The rules are:
+//! - Blocks, loops, frames, and loop rows must appear in the same order
+//! - Items are compared as sets (the order they were written in is irrelevant)
+//! - A [`CifValue::Text`] equals a [`CifValue::Numeric`] or
+//!   [`CifValue::NumericSu`] when the text reads as that number, since
+//!   quoting does not change how CIF interprets a number
+//! - Parse warnings are ignored
+
+use super::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue};
+use crate::uncertainty;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+impl CifValue {
+    /// Compare two values by meaning rather than representation.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::CifValue;
+    ///
+    /// let text = CifValue::Text("1.50".to_string());
+    /// assert!(text.semantically_equal(&CifValue::Numeric(1.5)));
+    /// assert!(!CifValue::Text("?".to_string()).semantically_equal(&CifValue::Unknown));
+    /// ```
+    pub fn semantically_equal(&self, other: &CifValue) -> bool {
+        match (self, other) {
+            (CifValue::Numeric(a), CifValue::Numeric(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (
+                CifValue::NumericSu { value: a, su: su_a },
+                CifValue::NumericSu { value: b, su: su_b },
+            ) => {
+                CifValue::Numeric(*a).semantically_equal(&CifValue::Numeric(*b))
+                    && CifValue::Numeric(*su_a).semantically_equal(&CifValue::Numeric(*su_b))
+            }
+            (CifValue::Text(s), number) | (number, CifValue::Text(s)) if number.is_numeric() => {
+                match uncertainty::parse(s) {
+                    Some((value, None)) => CifValue::Numeric(value).semantically_equal(number),
+                    Some((value, Some(su))) => {
+                        CifValue::NumericSu { value, su }.semantically_equal(number)
+                    }
+                    None => false,
+                }
+            }
+            (CifValue::List(a), CifValue::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.semantically_equal(y))
+            }
+            (CifValue::Table(a), CifValue::Table(b)) => tables_equal(a, b),
+            _ => self == other,
+        }
+    }
+}
+
+impl CifLoop {
+    /// Compare tags and rows, in order, using [`CifValue::semantically_equal`]
+    pub fn semantically_equal(&self, other: &CifLoop) -> bool {
+        self.tags == other.tags
+            && self.values.len() == other.values.len()
+            && self.values.iter().zip(&other.values).all(|(a, b)| {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.semantically_equal(y))
+            })
+    }
+}
+
+impl CifFrame {
+    /// Compare name, items, and loops
+    pub fn semantically_equal(&self, other: &CifFrame) -> bool {
+        self.name == other.name
+            && items_equal(&self.items, &other.items)
+            && loops_equal(&self.loops, &other.loops)
+    }
+}
+
+impl CifBlock {
+    /// Compare name, items, loops, and frames
+    pub fn semantically_equal(&self, other: &CifBlock) -> bool {
+        self.name == other.name
+            && items_equal(&self.items, &other.items)
+            && loops_equal(&self.loops, &other.loops)
+            && self.frames.len() == other.frames.len()
+            && self
+                .frames
+                .iter()
+                .zip(&other.frames)
+                .all(|(a, b)| a.semantically_equal(b))
+    }
+}
+
+impl CifDocument {
+    /// Compare version and blocks, ignoring parse warnings
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let a = Document::parse("data_x\n_a 1\n_b two\n").unwrap();
+    /// let b = Document::parse("data_x\n_b 'two'\n_a 1.0\n").unwrap();
+    /// assert!(a.semantically_equal(&b));
+    /// ```
+    pub fn semantically_equal(&self, other: &CifDocument) -> bool {
+        self.version == other.version
+            && self.blocks.len() == other.blocks.len()
+            && self
+                .blocks
+                .iter()
+                .zip(&other.blocks)
+                .all(|(a, b)| a.semantically_equal(b))
+    }
+}
+
+fn items_equal(a: &IndexMap<String, CifValue>, b: &IndexMap<String, CifValue>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(tag, value)| b.get(tag).is_some_and(|v| value.semantically_equal(v)))
+}
+
+fn tables_equal(a: &HashMap<String, CifValue>, b: &HashMap<String, CifValue>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(key, value)| b.get(key).is_some_and(|v| value.semantically_equal(v)))
+}
+
+fn loops_equal(a: &[CifLoop], b: &[CifLoop]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.semantically_equal(y))
+}
@@ -1,9 +1,16 @@
 //! CIF document (root container) structures.
 
-use super::CifBlock;
-use crate::error::CifError;
+use super::{CifBlock, DocumentSource};
+use crate::compression::{uncompressed_path, Compression};
+use crate::corpus::long_path;
+use crate::error::{CifError, CifWarning};
+use crate::parser::bare::block_name;
+use crate::parser::incremental;
+use crate::parser::{DecimalComma, DocumentStream, ParseOptions};
 use std::fs;
+use std::io::{BufRead, BufReader};
 use std::path::Path;
+use std::sync::Arc;
 
 /// CIF specification version detected or specified for a document.
 ///
@@ -37,6 +44,7 @@ use std::path::Path;
 /// assert_eq!(doc.version, CifVersion::V1_1);
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CifVersion {
     /// CIF 1.1 specification
     ///
@@ -116,6 +124,8 @@ impl std::fmt::Display for CifVersion {
 ///
 /// Each structure gets its own [`CifBlock`] with independent data.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CifDocument {
     /// All data blocks in this document
     pub blocks: Vec<CifBlock>,
@@ -125,6 +135,53 @@ pub struct CifDocument {
     /// Defaults to CIF 1.1 for backward compatibility.
     /// Set to CIF 2.0 if the file contains the magic comment `#\#CIF_2.0`.
     pub version: CifVersion,
+
+    /// Recoverable problems encountered while parsing in lenient mode
+    ///
+    /// Always empty for documents parsed in strict mode or built in code,
+    /// except for the note that bare save frames were
+    /// [allowed](ParseOptions::allow_bare_frames).
+    pub warnings: Vec<CifWarning>,
+
+    /// Where the content came from, if known
+    ///
+    /// Set by [`from_file`](Self::from_file) or [`set_source`](Self::set_source).
+    /// This is runtime metadata only and is never written out as CIF, nor
+    /// serialized with the `serde` feature.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub source: Option<DocumentSource>,
+
+    /// Whether this document holds only part of its input
+    ///
+    /// Set when parsed with a [`Selection`](crate::Selection); content that was
+    /// not selected is absent rather than empty.
+    pub partial: bool,
+
+    /// Non-CIF text before the first data block, such as an instrument banner
+    ///
+    /// Only set in lenient mode; comments and the version magic don't count.
+    /// Written back out by [`WriteOptions::preamble_and_trailer`](crate::writer::WriteOptions::preamble_and_trailer).
+    pub preamble: Option<String>,
+
+    /// Non-CIF text after the last data block, such as a checksum after `#END`
+    ///
+    /// Only set in lenient mode, verbatim from its start to the end of input.
+    pub trailer: Option<String>,
+
+    /// Whether the first block was made up to hold save frames written
+    /// before any data block
+    ///
+    /// Set when parsing a dictionary fragment with
+    /// [`ParseOptions::allow_bare_frames`]; the writer then leaves out the
+    /// block's heading, as long as the block holds nothing but frames.
+    pub bare_frames: bool,
+
+    /// Numbers read from decimal-comma tokens such as `1,5406`, with the
+    /// original tokens
+    ///
+    /// Only set in lenient mode. See
+    /// [`restore_decimal_commas`](Self::restore_decimal_commas).
+    pub decimal_commas: Vec<DecimalComma>,
 }
 
 impl Default for CifDocument {
@@ -139,6 +196,13 @@ impl CifDocument {
         CifDocument {
             blocks: Vec::new(),
             version: CifVersion::default(),
+            warnings: Vec::new(),
+            source: None,
+            partial: false,
+            preamble: None,
+            trailer: None,
+            bare_frames: false,
+            decimal_commas: Vec::new(),
         }
     }
 
@@ -147,6 +211,13 @@ impl CifDocument {
         CifDocument {
             blocks: Vec::new(),
             version,
+            warnings: Vec::new(),
+            source: None,
+            partial: false,
+            preamble: None,
+            trailer: None,
+            bare_frames: false,
+            decimal_commas: Vec::new(),
         }
     }
 
@@ -171,17 +242,265 @@ impl CifDocument {
         crate::parser::document::parse_file(input)
     }
 
+    /// Parse a CIF document from a string with explicit [`ParseOptions`]
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{Document, ParseOptions};
+    ///
+    /// let cif = "data_test\nloop_\n_a\n1\nstop_\n";
+    /// assert!(Document::parse(cif).is_err());
+    ///
+    /// let doc = Document::parse_with_options(cif, &ParseOptions::new().lenient(true)).unwrap();
+    /// assert_eq!(doc.blocks[0].loops[0].len(), 1);
+    /// assert_eq!(doc.warnings.len(), 1);
+    /// ```
+    pub fn parse_with_options(input: &str, options: &ParseOptions) -> Result<Self, CifError> {
+        crate::parser::document::parse_file_with_options(input, options)
+    }
+
+    /// Parse a CIF document from the raw bytes of a file
+    ///
+    /// The bytes must be UTF-8, optionally with a byte-order mark. UTF-16
+    /// input (recognised by its byte-order mark) is rejected; see
+    /// [`from_bytes_with_options`](Self::from_bytes_with_options) to
+    /// transcode it.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::from_bytes(b"\xEF\xBB\xBFdata_test\r\n_item value\r\n").unwrap();
+    /// assert_eq!(doc.blocks[0].name, "test");
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CifError> {
+        Self::from_bytes_with_options(bytes, &ParseOptions::default())
+    }
+
+    /// Parse a CIF document from raw bytes with explicit [`ParseOptions`]
+    ///
+    /// In lenient mode, UTF-16 input with a byte-order mark is transcoded,
+    /// with a warning. Bytes that are not UTF-8 are accepted, as U+FFFD,
+    /// only inside a [`preamble`](Self::preamble) or [`trailer`](Self::trailer).
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{Document, ParseOptions};
+    ///
+    /// let utf16: Vec<u8> = "\u{FEFF}data_test\n"
+    ///     .encode_utf16()
+    ///     .flat_map(u16::to_le_bytes)
+    ///     .collect();
+    /// assert!(Document::from_bytes(&utf16).is_err());
+    ///
+    /// let options = ParseOptions::new().lenient(true);
+    /// let doc = Document::from_bytes_with_options(&utf16, &options).unwrap();
+    /// assert_eq!(doc.blocks[0].name, "test");
+    /// assert_eq!(doc.warnings.len(), 1);
+    /// ```
+    pub fn from_bytes_with_options(bytes: &[u8], options: &ParseOptions) -> Result<Self, CifError> {
+        let (content, warning) = match crate::parser::encoding::decode(bytes, options) {
+            Ok(decoded) => decoded,
+            Err(error) if options.lenient && std::str::from_utf8(bytes).is_err() => {
+                return Self::from_bytes_with_binary_edges(bytes, options, error);
+            }
+            Err(error) => return Err(error),
+        };
+        let mut doc = Self::parse_with_options(&content, options)?;
+        doc.warnings.splice(0..0, warning);
+        Ok(doc)
+    }
+
+    /// Lenient parse of bytes that are not UTF-8, keeping `error` unless
+    /// every invalid byte is in the preamble or trailer
+    fn from_bytes_with_binary_edges(
+        bytes: &[u8],
+        options: &ParseOptions,
+        error: CifError,
+    ) -> Result<Self, CifError> {
+        let (content, replaced) = crate::parser::encoding::decode_lossy(bytes);
+        let Ok(mut doc) = Self::parse_with_options(&content, options) else {
+            return Err(error);
+        };
+        let outside = [&doc.preamble, &doc.trailer]
+            .into_iter()
+            .flatten()
+            .map(|text| text.matches('\u{FFFD}').count())
+            .sum::<usize>();
+        if outside < replaced {
+            return Err(error);
+        }
+        doc.warnings.insert(
+            0,
+            CifWarning::new(
+                "File is not valid UTF-8 outside its data blocks; invalid bytes replaced",
+            ),
+        );
+        Ok(doc)
+    }
+
     /// Parse a CIF document from a file
     ///
+    /// The document's [`source`](Self::source) records the path, size, and
+    /// hash of the file, and errors and warnings carry the path. The file is
+    /// decoded as [`from_bytes`](Self::from_bytes) describes.
+    ///
+    /// Any path the operating system accepts can be given, including names
+    /// that are not valid UTF-8 and, on Windows, paths longer than
+    /// `MAX_PATH` (see [`long_path`](crate::corpus::long_path)).
+    ///
+    /// With the `bcif` feature, a file ending in `.bcif` is decoded as
+    /// BinaryCIF (see `cif_parser::bcif`). A gzip, bzip2, or Zstandard
+    /// file is decompressed first if its feature is enabled (see
+    /// [`compression`](crate::compression)). For files of hundreds of
+    /// megabytes, the `mmap` feature adds `from_file_mmap`, which parses the
+    /// file in place.
+    ///
     /// # Examples
     /// ```no_run
     /// use cif_parser::Document;
     ///
     /// let doc = Document::from_file("structure.cif").unwrap();
+    /// println!("{}", doc.source.unwrap().sha256);
     /// ```
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CifError> {
-        let content = fs::read_to_string(path)?;
-        Self::parse(&content)
+        Self::from_file_with_options(path, &ParseOptions::default())
+    }
+
+    /// Parse a CIF document from a file with explicit [`ParseOptions`]
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cif_parser::{Document, ParseOptions, Selection};
+    ///
+    /// let options = ParseOptions::new().select(Selection::FirstBlockOnly);
+    /// let doc = Document::from_file_with_options("structure.cif", &options).unwrap();
+    /// ```
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<Self, CifError> {
+        let path = path.as_ref();
+        let content = fs::read(long_path(path)).map_err(|e| CifError::from(e).with_path(path))?;
+        let mut doc = Self::from_file_content(&content, path, options)?;
+        doc.set_source(DocumentSource::new(&content).with_path(path));
+        // The fragment's file names the block that holds its frames
+        let synthetic = doc.blocks.first_mut().filter(|_| doc.bare_frames);
+        if let (Some(block), Some(stem)) = (synthetic, uncompressed_path(path).file_stem()) {
+            block.name = block_name(&stem.to_string_lossy());
+        }
+        Ok(doc)
+    }
+
+    /// Parse the content of a file, decompressed if it is compressed and as
+    /// BinaryCIF if its name says so, with the path on errors
+    pub(crate) fn from_file_content(
+        content: &[u8],
+        path: &Path,
+        options: &ParseOptions,
+    ) -> Result<Self, CifError> {
+        let decompressed;
+        let content = match Compression::detect(path, content) {
+            Some(format) => {
+                decompressed = format.decompress(content).map_err(|e| e.with_path(path))?;
+                &decompressed[..]
+            }
+            None => content,
+        };
+        #[cfg(feature = "bcif")]
+        if crate::bcif::is_bcif_path(&uncompressed_path(path)) {
+            return Self::from_bcif(content).map_err(|e| e.with_path(path));
+        }
+        Self::from_bytes_with_options(content, options).map_err(|e| e.with_path(path))
+    }
+
+    /// Parse a CIF document from a reader: a socket, stdin, an archive
+    /// entry, or a buffer
+    ///
+    /// The bytes are decoded as [`from_bytes`](Self::from_bytes) describes
+    /// and parsed a piece at a time as they are read, each piece ending at
+    /// a `data_` line about a megabyte on, so the input is never held in
+    /// memory whole: the peak memory is that of the document and one
+    /// piece. gzip, bzip2, and Zstandard input is decompressed as it is
+    /// read, if its feature is enabled (see
+    /// [`compression`](crate::compression)). The document, and any error,
+    /// are those of parsing the whole input at once, except that an error
+    /// found only across blocks, such as a block name used twice, has no
+    /// [`span`](CifError::span), and a syntax error past the first piece
+    /// quotes the grammar's message with the lines of its piece numbered
+    /// from where it starts. To read a large file without building the
+    /// document, use [`CifReader`](crate::reader::CifReader) or
+    /// [`parse_multi_stream`](Self::parse_multi_stream).
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    /// use std::io::Cursor;
+    ///
+    /// let doc = Document::from_reader(Cursor::new(b"data_test\n_item value\n")).unwrap();
+    /// assert_eq!(doc.blocks[0].name, "test");
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, CifError> {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Parse a CIF document from a reader with explicit [`ParseOptions`]
+    ///
+    /// As with [`from_reader`](Self::from_reader), the input is parsed a
+    /// piece at a time as it is read.
+    pub fn from_reader_with_options<R: BufRead>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> Result<Self, CifError> {
+        match Compression::from_magic(reader.fill_buf()?) {
+            Some(format) => incremental::parse(BufReader::new(format.reader(reader)?), options),
+            None => incremental::parse(reader, options),
+        }
+    }
+
+    /// Parse a stream of concatenated CIF files, one document per data block
+    ///
+    /// Each block is parsed once the next `data_` line is reached, so the
+    /// stream is never held in memory whole. An entry that fails to parse
+    /// gives an `Err` and the iterator carries on with the next one; see
+    /// [`DocumentStream`] for how entries are found.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let stream = "data_a\n_x 1\n#End of data_a\ndata_b\n_y 'broken\ndata_c\n_z 3\n";
+    /// let entries: Vec<_> = Document::parse_multi_stream(stream.as_bytes()).collect();
+    ///
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(entries[0].as_ref().unwrap().blocks[0].name, "a");
+    /// assert!(entries[1].is_err());
+    /// assert_eq!(entries[2].as_ref().unwrap().blocks[0].name, "c");
+    /// ```
+    pub fn parse_multi_stream<R: BufRead>(reader: R) -> DocumentStream<R> {
+        Self::parse_multi_stream_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Parse a stream of concatenated CIF files with explicit [`ParseOptions`]
+    ///
+    /// The options apply to each entry separately.
+    pub fn parse_multi_stream_with_options<R: BufRead>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> DocumentStream<R> {
+        DocumentStream::new(reader, options.clone())
+    }
+
+    /// Record where this document came from
+    ///
+    /// If the source has a path, existing warnings are tagged with it.
+    pub fn set_source(&mut self, source: DocumentSource) {
+        if let Some(path) = &source.path {
+            for warning in &mut self.warnings {
+                warning.path = Some(path.clone());
+            }
+        }
+        self.source = Some(source);
     }
 
     /// Get a block by name
@@ -212,6 +531,54 @@ impl CifDocument {
         self.blocks.first()
     }
 
+    /// Wrap this document in an [`Arc`] for sharing across threads
+    ///
+    /// Use [`SharedBlock`](crate::SharedBlock) and [`SharedLoop`](crate::SharedLoop)
+    /// to hand out cheap references into the shared document.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::{Document, SharedBlock};
+    /// # let doc = Document::parse("data_test\n_item value\n").unwrap();
+    /// let shared = doc.into_shared();
+    /// let block = SharedBlock::new(shared, 0).unwrap();
+    /// assert_eq!(block.name, "test");
+    /// ```
+    pub fn into_shared(self) -> Arc<CifDocument> {
+        Arc::new(self)
+    }
+
+    /// Mutably borrow a block of a shared document, copying the document
+    /// first if any other handle still refers to it.
+    ///
+    /// This is [`Arc::make_mut`] applied to one block: the caller's `Arc` ends
+    /// up pointing at a document it owns alone, so [`SharedBlock`] and
+    /// [`SharedLoop`] handles taken earlier keep seeing the unmodified
+    /// document. Returns `None` if `index` is out of range, without copying.
+    ///
+    /// [`SharedBlock`]: crate::SharedBlock
+    /// [`SharedLoop`]: crate::SharedLoop
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::{Document, SharedBlock};
+    /// let mut shared = Document::parse("data_test\n_item value\n")
+    ///     .unwrap()
+    ///     .into_shared();
+    /// let before = SharedBlock::new(shared.clone(), 0).unwrap();
+    ///
+    /// Document::make_mut_block(&mut shared, 0).unwrap().name = "renamed".into();
+    ///
+    /// assert_eq!(before.name, "test");
+    /// assert_eq!(shared.blocks[0].name, "renamed");
+    /// ```
+    pub fn make_mut_block(doc: &mut Arc<CifDocument>, index: usize) -> Option<&mut CifBlock> {
+        if index >= doc.blocks.len() {
+            return None;
+        }
+        Arc::make_mut(doc).blocks.get_mut(index)
+    }
+
     /// Iterate over all blocks
     pub fn blocks_iter(&self) -> impl Iterator<Item = &CifBlock> {
         self.blocks.iter()
@@ -0,0 +1,292 @@
+//! Building and changing documents in code.
+//!
+//! The fields of [`CifDocument`], [`CifBlock`], and [`CifLoop`] are public,
+//! but writing to them directly can leave a document that cannot be written
+//! as CIF: a tag that is both a data item and a loop column, a loop with
+//! rows of different lengths, two blocks with one name. The methods here
+//! refuse such changes with an error and leave the document as it was.
+//!
+//! [`DocumentBuilder`] puts a document together from scratch in one
+//! expression, keeping the first error for [`build`](DocumentBuilder::build).
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{CifLoop, CifValue, Document};
+//!
+//! let mut doc = Document::parse("data_si\n_cell_length_a 5.43\n").unwrap();
+//! let block = &mut doc.blocks[0];
+//! block.set_item("_cell_length_a", CifValue::Numeric(5.431)).unwrap();
+//! block.set_item("_symmetry_space_group_name_H-M", CifValue::Text("F d -3 m".into())).unwrap();
+//!
+//! let mut atoms = CifLoop::new(vec!["_atom_site_label".into()]);
+//! atoms.add_row(vec![CifValue::Text("Si1".into())]).unwrap();
+//! atoms.add_column("_atom_site_fract_x", vec![CifValue::Numeric(0.125)]).unwrap();
+//! block.add_loop(atoms).unwrap();
+//!
+//! // A loop column cannot also be a data item
+//! assert!(block.set_item("_atom_site_label", CifValue::Unknown).is_err());
+//! assert_eq!(block.remove_item("_cell_length_a"), Some(CifValue::Numeric(5.431)));
+//! ```
+
+use super::{CifBlock, CifDocument, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+
+/// Fail unless `tag` is a data name: `_` followed by non-blank characters
+fn check_tag(tag: &str) -> Result<(), CifError> {
+    if tag.len() > 1 && tag.starts_with('_') && !tag.contains(char::is_whitespace) {
+        Ok(())
+    } else {
+        Err(CifError::invalid_structure(format!(
+            "'{tag}' is not a data name: it must start with '_' and have no blanks"
+        )))
+    }
+}
+
+fn duplicate(tag: &str) -> CifError {
+    CifError::DuplicateTag {
+        tag: tag.to_string(),
+        location: None,
+        span: None,
+    }
+}
+
+impl CifBlock {
+    /// Set a data item, replacing any value it had, and return the old value
+    ///
+    /// A new item goes after the others; a replaced one keeps its place.
+    /// Returns [`CifError::DuplicateTag`] if the tag is a loop column, and
+    /// [`CifError::InvalidStructure`] if it is not a data name.
+    pub fn set_item(
+        &mut self,
+        tag: impl Into<String>,
+        value: CifValue,
+    ) -> Result<Option<CifValue>, CifError> {
+        let tag = tag.into();
+        check_tag(&tag)?;
+        if self.find_loop(&tag).is_some() {
+            return Err(duplicate(&tag));
+        }
+        Ok(self.items.insert(tag, value))
+    }
+
+    /// Remove a data item and return its value, keeping the order of the
+    /// others. Loop columns are left alone.
+    pub fn remove_item(&mut self, tag: &str) -> Option<CifValue> {
+        self.items.shift_remove(tag)
+    }
+
+    /// Add a loop after the others and return it
+    ///
+    /// Returns [`CifError::DuplicateTag`] if a tag of the loop is a data
+    /// item or in another loop, or appears twice in it;
+    /// [`CifError::RowLength`] if a row does not have one value per tag;
+    /// and [`CifError::InvalidStructure`] if the loop has no tags or a tag
+    /// is not a data name.
+    pub fn add_loop(&mut self, loop_: CifLoop) -> Result<&mut CifLoop, CifError> {
+        if loop_.tags.is_empty() {
+            return Err(CifError::invalid_structure("a loop needs at least one tag"));
+        }
+        for (i, tag) in loop_.tags.iter().enumerate() {
+            check_tag(tag)?;
+            if loop_.tags[..i].contains(tag)
+                || self.items.contains_key(tag)
+                || self.find_loop(tag).is_some()
+            {
+                return Err(duplicate(tag));
+            }
+        }
+        if let Some(row) = loop_
+            .values
+            .iter()
+            .find(|row| row.len() != loop_.tags.len())
+        {
+            return Err(CifError::RowLength {
+                expected: loop_.tags.len(),
+                found: row.len(),
+            });
+        }
+        self.loops.push(loop_);
+        Ok(self.loops.last_mut().expect("a loop was just added"))
+    }
+}
+
+impl CifLoop {
+    /// Add a row after the others, with one value per tag
+    ///
+    /// Returns [`CifError::RowLength`] if the row has the wrong length,
+    /// including any row for a loop with no tags. This is `add_row` in
+    /// Python too.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{CifLoop, CifValue};
+    ///
+    /// let mut loop_ = CifLoop::new(vec!["_a".into(), "_b".into()]);
+    /// loop_.add_row(vec![CifValue::Numeric(1.0), CifValue::Unknown]).unwrap();
+    /// assert_eq!(loop_.len(), 1);
+    /// assert!(loop_.add_row(vec![CifValue::Numeric(2.0)]).is_err());
+    /// ```
+    pub fn add_row(&mut self, row: Vec<CifValue>) -> Result<(), CifError> {
+        self.push_row(row)
+    }
+
+    /// Add a column after the others, with one value per row
+    ///
+    /// A loop without tags takes its rows from the column. Returns
+    /// [`CifError::DuplicateTag`] if the loop has the tag already, and
+    /// [`CifError::InvalidStructure`] if the tag is not a data name or the
+    /// number of values is not the number of rows.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{CifLoop, CifValue};
+    ///
+    /// let mut loop_ = CifLoop::new(Vec::new());
+    /// loop_.add_column("_n", vec![CifValue::Numeric(1.0), CifValue::Numeric(2.0)]).unwrap();
+    /// assert_eq!(loop_.len(), 2);
+    /// assert!(loop_.add_column("_m", vec![CifValue::Unknown]).is_err());
+    /// ```
+    pub fn add_column(
+        &mut self,
+        tag: impl Into<String>,
+        values: Vec<CifValue>,
+    ) -> Result<(), CifError> {
+        let tag = tag.into();
+        check_tag(&tag)?;
+        if self.tags.contains(&tag) {
+            return Err(duplicate(&tag));
+        }
+        if self.tags.is_empty() {
+            self.values = values.into_iter().map(|value| vec![value]).collect();
+        } else if values.len() != self.len() {
+            return Err(CifError::invalid_structure(format!(
+                "column {tag} has {} values for a loop of {} rows",
+                values.len(),
+                self.len()
+            )));
+        } else {
+            for (row, value) in self.values.iter_mut().zip(values) {
+                row.push(value);
+            }
+        }
+        self.tags.push(tag);
+        Ok(())
+    }
+}
+
+impl CifDocument {
+    /// Add a block after the others and return it
+    ///
+    /// Returns [`CifError::InvalidStructure`] if another block has the same
+    /// name, or the name is empty or has blanks.
+    pub fn add_block(&mut self, block: CifBlock) -> Result<&mut CifBlock, CifError> {
+        if block.name.is_empty() || block.name.contains(char::is_whitespace) {
+            return Err(CifError::invalid_structure(format!(
+                "'{}' is not a block name: it must be non-empty and have no blanks",
+                block.name
+            )));
+        }
+        if self.get_block(&block.name).is_some() {
+            return Err(CifError::invalid_structure(format!(
+                "the document already has a block named '{}'",
+                block.name
+            )));
+        }
+        self.blocks.push(block);
+        Ok(self.blocks.last_mut().expect("a block was just added"))
+    }
+}
+
+/// Puts a [`CifDocument`] together from scratch.
+///
+/// Items and loops go into the block most recently started with
+/// [`block`](Self::block). The checks of [`CifBlock::set_item`],
+/// [`CifBlock::add_loop`], and [`CifDocument::add_block`] apply; the first
+/// error is kept, later calls are ignored, and [`build`](Self::build)
+/// returns it.
+///
+/// # Examples
+/// ```
+/// use cif_parser::{CifValue, CifVersion, DocumentBuilder};
+///
+/// let doc = DocumentBuilder::new()
+///     .version(CifVersion::V2_0)
+///     .block("si")
+///     .item("_cell_length_a", CifValue::Numeric(5.431))
+///     .loop_(
+///         &["_atom_site_label", "_atom_site_fract_x"],
+///         [vec![CifValue::Text("Si1".into()), CifValue::Numeric(0.125)]],
+///     )
+///     .build()
+///     .unwrap();
+///
+/// assert!(doc.to_string().starts_with("#\\#CIF_2.0"));
+/// assert_eq!(doc.blocks[0].loops[0].len(), 1);
+/// assert!(DocumentBuilder::new().item("_orphan", CifValue::Unknown).build().is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct DocumentBuilder {
+    doc: CifDocument,
+    error: Option<CifError>,
+}
+
+impl DocumentBuilder {
+    /// Start an empty CIF 1.1 document
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the CIF version the document is written as
+    pub fn version(mut self, version: CifVersion) -> Self {
+        self.doc.version = version;
+        self
+    }
+
+    /// Start a new data block
+    pub fn block(mut self, name: impl Into<String>) -> Self {
+        self.apply(|doc| doc.add_block(CifBlock::new(name.into())).map(drop));
+        self
+    }
+
+    /// Add a data item to the current block
+    pub fn item(mut self, tag: impl Into<String>, value: CifValue) -> Self {
+        self.apply(|doc| current(doc)?.set_item(tag, value).map(drop));
+        self
+    }
+
+    /// Add a loop with `tags` and `rows` to the current block
+    pub fn loop_<T: AsRef<str>>(
+        mut self,
+        tags: &[T],
+        rows: impl IntoIterator<Item = Vec<CifValue>>,
+    ) -> Self {
+        self.apply(|doc| {
+            let mut loop_ = CifLoop::new(tags.iter().map(|t| t.as_ref().to_string()).collect());
+            loop_.values = rows.into_iter().collect();
+            current(doc)?.add_loop(loop_).map(drop)
+        });
+        self
+    }
+
+    /// The document, or the first error met while building it
+    pub fn build(self) -> Result<CifDocument, CifError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.doc),
+        }
+    }
+
+    fn apply(&mut self, step: impl FnOnce(&mut CifDocument) -> Result<(), CifError>) {
+        if self.error.is_none() {
+            self.error = step(&mut self.doc).err();
+        }
+    }
+}
+
+/// The block items and loops are added to
+fn current(doc: &mut CifDocument) -> Result<&mut CifBlock, CifError> {
+    doc.blocks
+        .last_mut()
+        .ok_or_else(|| CifError::invalid_structure("items and loops need a block first"))
+}
@@ -1,7 +1,8 @@
 //! Save frame structures in CIF files.
 
+use super::block::{ordered_tags, TagLocation};
 use super::{CifLoop, CifValue};
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 /// Represents a save frame in a CIF file.
 ///
@@ -36,11 +37,13 @@ use std::collections::HashMap;
 /// Save frames are contained within data blocks and can contain the same
 /// types of content (data items and loops) but cannot contain other save frames.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CifFrame {
     /// Name of the save frame (from `save_name`)
     pub name: String,
-    /// Data items (key-value pairs) within this frame
-    pub items: HashMap<String, CifValue>,
+    /// Data items (key-value pairs) within this frame, in file order
+    #[cfg_attr(feature = "serde", serde(with = "super::items_serde"))]
+    pub items: IndexMap<String, CifValue>,
     /// Loop structures within this frame
     pub loops: Vec<CifLoop>,
 }
@@ -50,16 +53,27 @@ impl CifFrame {
     pub fn new(name: String) -> Self {
         CifFrame {
             name,
-            items: HashMap::new(),
+            items: IndexMap::new(),
             loops: Vec::new(),
         }
     }
 
-    /// Get a data item value by tag name
+    /// Get a data item value by tag name; loop columns are not searched
+    /// (see [`CifBlock::get_item`](crate::CifBlock::get_item))
     pub fn get_item(&self, tag: &str) -> Option<&CifValue> {
         self.items.get(tag)
     }
 
+    /// Get every value of a tag, whether it is a data item or a loop column
+    pub fn get_all(&self, tag: &str) -> Vec<&CifValue> {
+        let columns = self
+            .loops
+            .iter()
+            .filter_map(|loop_| loop_.get_column(tag))
+            .flatten();
+        self.items.get(tag).into_iter().chain(columns).collect()
+    }
+
     /// Find a loop containing a specific tag
     pub fn find_loop(&self, tag: &str) -> Option<&CifLoop> {
         self.loops
@@ -75,4 +89,15 @@ impl CifFrame {
                 .flat_map(|l| l.tags.iter().map(|s| s.as_str())),
         )
     }
+
+    /// Iterate over data items in file order
+    pub fn iter_items(&self) -> impl Iterator<Item = (&str, &CifValue)> {
+        self.items.iter().map(|(tag, value)| (tag.as_str(), value))
+    }
+
+    /// Iterate over the tags of this frame: data items in file order, then
+    /// the columns of each loop in order
+    pub fn iter_all_tags(&self) -> impl Iterator<Item = (&str, TagLocation)> {
+        ordered_tags(&self.items, &self.loops)
+    }
 }
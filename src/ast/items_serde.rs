@@ -0,0 +1,41 @@
+//! Serde support for the ordered data items of blocks and frames.
+//!
+//! Items are written as a map in file order and read back in the order
+//! they are given, so a round trip keeps the order tags were written in.
+
+use super::CifValue;
+use indexmap::IndexMap;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserializer, Serializer};
+use std::fmt;
+
+pub(super) fn serialize<S: Serializer>(
+    items: &IndexMap<String, CifValue>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(items)
+}
+
+pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<IndexMap<String, CifValue>, D::Error> {
+    deserializer.deserialize_map(ItemsVisitor)
+}
+
+struct ItemsVisitor;
+
+impl<'de> Visitor<'de> for ItemsVisitor {
+    type Value = IndexMap<String, CifValue>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of tags to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut items = IndexMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((tag, value)) = map.next_entry()? {
+            items.insert(tag, value);
+        }
+        Ok(items)
+    }
+}
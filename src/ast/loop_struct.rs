@@ -1,6 +1,7 @@
 //! Loop structures representing tabular data in CIF files.
 
 use super::CifValue;
+use crate::error::CifError;
 
 /// Represents a loop structure in a CIF file (tabular data).
 ///
@@ -46,10 +47,22 @@ use super::CifValue;
 /// # Validation
 ///
 /// The parser ensures that:
+/// - Every loop has at least one tag (a bare `loop_` is an error)
 /// - Number of values is divisible by number of tags
 /// - Each row has exactly the right number of values
 /// - Empty loops (tags but no values) are valid
+///
+/// # Empty Loops
+///
+/// A loop with tags but no rows is an empty table, as found in template
+/// CIFs. It behaves like any other loop: [`CifBlock::find_loop`] finds it,
+/// [`get_column`](Self::get_column) gives an empty `Vec`, [`rows`](Self::rows)
+/// yields nothing, [`add_row`](Self::add_row) adds its first row, and the
+/// writer emits its header with no values.
+///
+/// [`CifBlock::find_loop`]: crate::CifBlock::find_loop
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CifLoop {
     /// Column names/headers (CIF tags starting with `_`)
     pub tags: Vec<String>,
@@ -59,19 +72,47 @@ pub struct CifLoop {
 
 impl Default for CifLoop {
     fn default() -> Self {
-        Self::new()
+        Self::new(Vec::new())
     }
 }
 
 impl CifLoop {
-    /// Create a new empty loop
-    pub fn new() -> Self {
+    /// Create a loop with the given tags and no rows
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{CifLoop, CifValue};
+    ///
+    /// let mut loop_ = CifLoop::new(vec!["_atom_site_label".to_string()]);
+    /// assert!(loop_.is_empty());
+    /// assert_eq!(loop_.get_column("_atom_site_label"), Some(vec![]));
+    ///
+    /// loop_.add_row(vec![CifValue::Text("C1".to_string())]).unwrap();
+    /// assert_eq!(loop_.len(), 1);
+    /// ```
+    pub fn new(tags: Vec<String>) -> Self {
         CifLoop {
-            tags: Vec::new(),
+            tags,
             values: Vec::new(),
         }
     }
 
+    /// Append a row, which must have one value per tag; the same as
+    /// [`add_row`](Self::add_row)
+    ///
+    /// Returns [`CifError::RowLength`] if the row has the wrong
+    /// length, including any row for a loop with no tags.
+    pub fn push_row(&mut self, row: Vec<CifValue>) -> Result<(), CifError> {
+        if self.tags.is_empty() || row.len() != self.tags.len() {
+            return Err(CifError::RowLength {
+                expected: self.tags.len(),
+                found: row.len(),
+            });
+        }
+        self.values.push(row);
+        Ok(())
+    }
+
     /// Get the number of rows in the loop
     pub fn len(&self) -> usize {
         self.values.len()
@@ -128,6 +169,40 @@ impl CifLoop {
         Some(self.values.iter().map(|row| &row[col]).collect())
     }
 
+    /// Get a column as contiguous numbers, with `?` and `.` as NaN
+    ///
+    /// Numbers with an su, such as `5.431(2)`, give their value. Returns
+    /// `None` if the tag doesn't exist, and `Some(Err(row))` for the first
+    /// row whose value is not a number.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// # let cif = "data_test\nloop_\n_x\n_label\n1.5(2) C1\n? C2\n";
+    /// # let doc = Document::parse(cif).unwrap();
+    /// # let loop_ = &doc.blocks[0].loops[0];
+    /// let x = loop_.numeric_column("_x").unwrap().unwrap();
+    /// assert_eq!(x[0], 1.5);
+    /// assert!(x[1].is_nan());
+    /// assert_eq!(loop_.numeric_column("_label"), Some(Err(0)));
+    /// ```
+    pub fn numeric_column(&self, tag: &str) -> Option<Result<Vec<f64>, usize>> {
+        let col = self.tags.iter().position(|t| t == tag)?;
+        Some(
+            self.values
+                .iter()
+                .enumerate()
+                .map(|(row, values)| match &values[col] {
+                    CifValue::Unknown | CifValue::NotApplicable => Ok(f64::NAN),
+                    value => value
+                        .as_numeric_with_su()
+                        .map(|(number, _)| number)
+                        .ok_or(row),
+                })
+                .collect(),
+        )
+    }
+
     /// Iterate over rows as vectors of values
     ///
     /// # Examples
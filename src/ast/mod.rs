@@ -10,10 +10,10 @@
 //! ```text
 //! CifDocument
 //!  └─ CifBlock (data blocks)
-//!      ├─ items: HashMap<String, CifValue>  (key-value pairs)
+//!      ├─ items: IndexMap<String, CifValue> (key-value pairs, in order)
 //!      ├─ loops: Vec<CifLoop>               (tabular data)
 //!      └─ frames: Vec<CifFrame>             (named sub-containers)
-//!          ├─ items: HashMap<String, CifValue>
+//!          ├─ items: IndexMap<String, CifValue>
 //!          └─ loops: Vec<CifLoop>
 //! ```
 //!
@@ -25,13 +25,20 @@
 //! - **Public fields**: Direct field access for flexibility (struct types)
 
 pub mod block;
+mod compare;
 pub mod document;
+pub mod edit;
 pub mod frame;
+#[cfg(feature = "serde")]
+mod items_serde;
 pub mod loop_struct;
+pub mod source;
 pub mod value;
 
-pub use block::CifBlock;
+pub use block::{CifBlock, TagLocation, WalkEntry};
 pub use document::{CifDocument, CifVersion};
+pub use edit::DocumentBuilder;
 pub use frame::CifFrame;
 pub use loop_struct::CifLoop;
+pub use source::DocumentSource;
 pub use value::CifValue;
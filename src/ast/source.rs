@@ -0,0 +1,61 @@
+//! Where a document's content came from.
+
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Runtime metadata about the input a [`CifDocument`](super::CifDocument) was
+/// parsed from.
+///
+/// Set automatically by [`CifDocument::from_file`](super::CifDocument::from_file)
+/// and by hand with [`CifDocument::set_source`](super::CifDocument::set_source).
+/// It is never written back out as CIF.
+///
+/// # Examples
+/// ```
+/// use cif_parser::{Document, DocumentSource};
+///
+/// let content = "data_test\n_item value\n";
+/// let mut doc = Document::parse(content).unwrap();
+/// doc.set_source(DocumentSource::new(content.as_bytes()).with_path("test.cif"));
+///
+/// let source = doc.source.as_ref().unwrap();
+/// assert_eq!(source.byte_len, content.len());
+/// assert_eq!(source.sha256.len(), 64);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSource {
+    /// File the content was read from, if any
+    pub path: Option<PathBuf>,
+    /// Length of the content in bytes
+    pub byte_len: usize,
+    /// SHA-256 of the content, as lowercase hex
+    pub sha256: String,
+    /// When the content was parsed
+    pub parsed_at: SystemTime,
+}
+
+impl DocumentSource {
+    /// Describe `content`, timestamped now and with no path
+    pub fn new(content: &[u8]) -> Self {
+        let digest = Sha256::digest(content);
+        let mut sha256 = String::with_capacity(64);
+        for byte in digest {
+            let _ = write!(sha256, "{byte:02x}");
+        }
+
+        DocumentSource {
+            path: None,
+            byte_len: content.len(),
+            sha256,
+            parsed_at: SystemTime::now(),
+        }
+    }
+
+    /// Record the file the content was read from
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+}
@@ -1,5 +1,7 @@
 //! CIF value types with automatic type detection.
 
+use std::sync::Arc;
+
 /// Represents a single value in a CIF file with automatic type detection.
 ///
 /// CIF values come in many forms and require careful parsing to handle quotes,
@@ -11,9 +13,17 @@
 /// ## CIF 1.1 and 2.0:
 /// - **Text**: String values, including quoted strings and text fields
 /// - **Numeric**: Floating-point numbers (integers are stored as f64)
+/// - **NumericSu**: Numbers written with a standard uncertainty, `1.234(5)`
 /// - **Unknown**: The special value `?` indicating missing/unknown data
 /// - **NotApplicable**: The special value `.` indicating not applicable
 ///
+///   Only the bare tokens are markers; a quoted `'?'` or `'.'`, or a text
+///   field holding one, is the text `"?"` or `"."`, and is written back
+///   quoted
+/// - **LargeText**: Text longer than
+///   [`ParseOptions::large_value_threshold`](crate::ParseOptions::large_value_threshold),
+///   stored once and shared
+///
 /// ## CIF 2.0 only:
 /// - **List**: Ordered collection of values `[value1 value2 value3]`
 /// - **Table**: Key-value pairs `{key1:value1 key2:value2}`
@@ -35,6 +45,10 @@
 ///
 /// // CIF 1.1 values
 /// assert_eq!(CifValue::parse_value("123.45"), CifValue::Numeric(123.45));
+/// assert_eq!(
+///     CifValue::parse_value("5.431(2)"),
+///     CifValue::NumericSu { value: 5.431, su: 0.002 }
+/// );
 /// assert_eq!(CifValue::parse_value("'hello'"), CifValue::Text("hello".to_string()));
 /// assert_eq!(CifValue::parse_value("?"), CifValue::Unknown);
 /// assert_eq!(CifValue::parse_value("."), CifValue::NotApplicable);
@@ -51,17 +65,34 @@
 /// ```
 ///
 /// These are automatically detected and the semicolon delimiters are removed.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CifValue {
     // ===== CIF 1.1 Value Types =====
     /// String value (from quoted strings, unquoted strings, or text fields)
     Text(String),
     /// Numeric value (both integers and floats are stored as f64)
     Numeric(f64),
+    /// Number written with its standard uncertainty, such as `1.234(5)`.
+    ///
+    /// The su is scaled to the last digits of the number, so `1.234(5)` has
+    /// `su` 0.005. [`as_numeric`](Self::as_numeric) gives the value alone.
+    NumericSu {
+        /// The number itself
+        value: f64,
+        /// Its standard uncertainty
+        su: f64,
+    },
     /// Unknown value (represented as `?` in CIF files)
     Unknown,
     /// Not applicable value (represented as `.` in CIF files)
     NotApplicable,
+    /// Text above the large-value threshold, such as an embedded map.
+    ///
+    /// Cloning shares the text rather than copying it. It compares equal to
+    /// a [`Text`](Self::Text) with the same content, and
+    /// [`as_string`](Self::as_string) reads it like one.
+    LargeText(Arc<str>),
 
     // ===== CIF 2.0 Value Types =====
     /// List of values (CIF 2.0 only)
@@ -71,7 +102,9 @@ pub enum CifValue {
 
     /// Table/dictionary of key-value pairs (CIF 2.0 only)
     /// Example: `{key1:value1 key2:value2}`
-    /// Keys must be quoted strings, values can be any CIF value type
+    /// Keys must be quoted strings, values can be any CIF value type.
+    /// The map is unordered; [`table_entries`](Self::table_entries) gives
+    /// the entries sorted by key, the order the writer uses.
     Table(std::collections::HashMap<String, CifValue>),
 }
 
@@ -133,7 +166,10 @@ impl CifValue {
     /// The semicolons and surrounding whitespace are removed.
     fn extract_content(s: &str) -> &str {
         // Handle quoted strings
-        if (s.starts_with('\'') && s.ends_with('\'')) || (s.starts_with('"') && s.ends_with('"')) {
+        if s.len() >= 2
+            && ((s.starts_with('\'') && s.ends_with('\''))
+                || (s.starts_with('"') && s.ends_with('"')))
+        {
             &s[1..s.len() - 1]
         }
         // Handle text fields (semicolon-delimited)
@@ -153,17 +189,20 @@ impl CifValue {
     /// - Floats: `123.45`
     /// - Scientific notation: `1.23e-4`
     /// - Signs: `-123.45`
+    /// - Standard uncertainties: `1.234(5)`
     ///
     /// If parsing fails, the string is stored as [`CifValue::Text`].
     fn parse_numeric_or_text(s: &str) -> Self {
-        if let Ok(num) = s.parse::<f64>() {
-            CifValue::Numeric(num)
-        } else {
-            CifValue::Text(s.to_string())
+        // Padded text is not a number, though uncertainty::parse would trim it
+        match crate::uncertainty::parse(s).filter(|_| s.trim() == s) {
+            Some((value, None)) => CifValue::Numeric(value),
+            Some((value, Some(su))) if value.is_finite() => CifValue::NumericSu { value, su },
+            _ => CifValue::Text(s.to_string()),
         }
     }
 
-    /// Get the value as a string reference, if it's a Text variant.
+    /// Get the value as a string reference, if it's a Text or LargeText
+    /// variant.
     ///
     /// # Examples
     /// ```
@@ -178,11 +217,20 @@ impl CifValue {
     pub fn as_string(&self) -> Option<&str> {
         match self {
             CifValue::Text(s) => Some(s),
+            CifValue::LargeText(s) => Some(s),
             _ => None,
         }
     }
 
-    /// Get the value as a number, if it's a Numeric variant.
+    /// Whether this is text stored as [`LargeText`](Self::LargeText)
+    pub fn is_large(&self) -> bool {
+        matches!(self, CifValue::LargeText(_))
+    }
+
+    /// Get the value as a number, if it's a Numeric or NumericSu variant.
+    ///
+    /// The su of a NumericSu is dropped; see
+    /// [`as_numeric_with_su`](Self::as_numeric_with_su).
     ///
     /// # Examples
     /// ```
@@ -190,17 +238,44 @@ impl CifValue {
     ///
     /// let val = CifValue::Numeric(42.0);
     /// assert_eq!(val.as_numeric(), Some(42.0));
+    /// assert_eq!(CifValue::parse_value("5.431(2)").as_numeric(), Some(5.431));
     ///
     /// let text = CifValue::Text("hello".to_string());
     /// assert_eq!(text.as_numeric(), None);
     /// ```
     pub fn as_numeric(&self) -> Option<f64> {
         match self {
-            CifValue::Numeric(n) => Some(*n),
+            CifValue::Numeric(n) | CifValue::NumericSu { value: n, .. } => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a number with its su, if it is numeric.
+    ///
+    /// The su is `None` for a number written without one.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::CifValue;
+    ///
+    /// let value = CifValue::parse_value("5.431(2)");
+    /// assert_eq!(value.as_numeric_with_su(), Some((5.431, Some(0.002))));
+    /// assert_eq!(CifValue::Numeric(2.0).as_numeric_with_su(), Some((2.0, None)));
+    /// assert_eq!(CifValue::Unknown.as_numeric_with_su(), None);
+    /// ```
+    pub fn as_numeric_with_su(&self) -> Option<(f64, Option<f64>)> {
+        match self {
+            CifValue::Numeric(n) => Some((*n, None)),
+            CifValue::NumericSu { value, su } => Some((*value, Some(*su))),
             _ => None,
         }
     }
 
+    /// Whether this is a number, with or without an su
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, CifValue::Numeric(_) | CifValue::NumericSu { .. })
+    }
+
     /// Get the value as a list, if it's a List variant (CIF 2.0 only).
     ///
     /// # Examples
@@ -305,7 +380,7 @@ impl CifValue {
         }
     }
 
-    /// Get an iterator over table keys.
+    /// Get an iterator over table keys, in sorted order.
     ///
     /// Returns `Some(iterator)` if this is a Table, `None` otherwise.
     ///
@@ -320,13 +395,37 @@ impl CifValue {
     /// let table = CifValue::Table(map);
     ///
     /// let keys: Vec<&str> = table.as_table_keys().unwrap().collect();
-    /// assert_eq!(keys.len(), 2);
+    /// assert_eq!(keys, ["key1", "key2"]);
     /// ```
     pub fn as_table_keys(&self) -> Option<impl Iterator<Item = &str>> {
-        match self {
-            CifValue::Table(table) => Some(table.keys().map(|s| s.as_str())),
-            _ => None,
-        }
+        self.table_entries()
+            .map(|entries| entries.into_iter().map(|(key, _)| key))
+    }
+
+    /// Get the entries of a table sorted by key (CIF 2.0 only).
+    ///
+    /// Iterating the map itself visits entries in an order that changes from
+    /// run to run; use this for anything that is output.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::CifValue;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert("b".to_string(), CifValue::Numeric(2.0));
+    /// map.insert("a".to_string(), CifValue::Numeric(1.0));
+    /// let table = CifValue::Table(map);
+    /// let keys: Vec<&str> = table.table_entries().unwrap().iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, ["a", "b"]);
+    /// ```
+    pub fn table_entries(&self) -> Option<Vec<(&str, &CifValue)>> {
+        let CifValue::Table(table) = self else {
+            return None;
+        };
+        let mut entries: Vec<_> = table.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        Some(entries)
     }
 
     /// Get a value from a table by key.
@@ -356,6 +455,62 @@ impl CifValue {
     }
 }
 
+// Written out by hand so that text compares the same whether it is large or
+// not
+impl PartialEq for CifValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                CifValue::Text(_) | CifValue::LargeText(_),
+                CifValue::Text(_) | CifValue::LargeText(_),
+            ) => self.as_string() == other.as_string(),
+            (CifValue::Numeric(a), CifValue::Numeric(b)) => a == b,
+            (
+                CifValue::NumericSu { value: a, su: su_a },
+                CifValue::NumericSu { value: b, su: su_b },
+            ) => a == b && su_a == su_b,
+            (CifValue::Unknown, CifValue::Unknown) => true,
+            (CifValue::NotApplicable, CifValue::NotApplicable) => true,
+            (CifValue::List(a), CifValue::List(b)) => a == b,
+            (CifValue::Table(a), CifValue::Table(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+// Written out by hand so that tables print sorted by key, as the writer
+// does, rather than in hash order, and large text prints its length only
+impl std::fmt::Debug for CifValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CifValue::Text(s) => f.debug_tuple("Text").field(s).finish(),
+            CifValue::LargeText(s) => write!(f, "LargeText(<{} bytes>)", s.len()),
+            CifValue::Numeric(n) => f.debug_tuple("Numeric").field(n).finish(),
+            CifValue::NumericSu { value, su } => f
+                .debug_struct("NumericSu")
+                .field("value", value)
+                .field("su", su)
+                .finish(),
+            CifValue::Unknown => f.write_str("Unknown"),
+            CifValue::NotApplicable => f.write_str("NotApplicable"),
+            CifValue::List(values) => f.debug_tuple("List").field(values).finish(),
+            CifValue::Table(_) => {
+                let entries = self.table_entries().unwrap_or_default();
+                f.debug_tuple("Table").field(&DebugTable(&entries)).finish()
+            }
+        }
+    }
+}
+
+/// Table entries printed as a map
+struct DebugTable<'a>(&'a [(&'a str, &'a CifValue)]);
+
+impl std::fmt::Debug for DebugTable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter().copied()).finish()
+    }
+}
+
 // Implement standard FromStr trait
 impl std::str::FromStr for CifValue {
     type Err = std::convert::Infallible; // This method never fails
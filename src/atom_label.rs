@@ -0,0 +1,446 @@
+//! Core CIF atom labels: element, number, and suffix.
+//!
+//! Small-molecule `_atom_site_label` values are conventionally an element
+//! symbol, a number, and an optional suffix: `C12A` for a disordered carbon,
+//! `O1W` for a water oxygen, `H1A` for the first hydrogen on `C1`.
+//! [`AtomLabel::parse`] splits a label into these parts, and [`relabel`]
+//! regenerates labels when structures are merged and labels collide.
+//! [`natural_cmp`] sorts labels the way people read them (`C2` before `C10`),
+//! and [`CifBlock::canonical_atom_site_order`] uses it to put the atom sites
+//! of a block in a deterministic order.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::atom_label::{relabel, AtomLabel, RelabelScheme};
+//!
+//! let label = AtomLabel::parse("O1W");
+//! assert_eq!(label.element.as_deref(), Some("O"));
+//! assert_eq!(label.number, Some(1));
+//! assert_eq!(label.suffix, "W");
+//!
+//! let merged = ["C1", "O1", "C1", "C2"];
+//! assert_eq!(
+//!     relabel(&merged, RelabelScheme::ResolveCollisions),
+//!     ["C1", "O1", "C3", "C2"]
+//! );
+//! ```
+
+use crate::ast::{CifBlock, CifLoop};
+use crate::category::category_of;
+use crate::element::ElementSymbol;
+use crate::formula::atomic_weight;
+use crate::publication::value_text;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// An atom label split into element, number, and suffix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomLabel {
+    /// The label as written
+    pub label: String,
+    /// Element symbol guessed from the leading letters, if they start with one
+    pub element: Option<String>,
+    /// The digits following the element
+    pub number: Option<u32>,
+    /// Everything after the number, such as a disorder or water marker
+    pub suffix: String,
+}
+
+impl AtomLabel {
+    /// Split a label into element, number, and suffix.
+    ///
+    /// The element is one or two leading letters that name an element. A
+    /// lowercase second letter is read as part of the symbol when that gives
+    /// an element (`Ca1` is calcium, `C1a` is carbon with suffix `a`); an
+    /// uppercase one only when the first letter alone is not an element, so
+    /// `CA1` is carbon, as in macromolecular atom names, and `ZN1` is zinc.
+    /// Labels that do not start with an element still have their leading
+    /// letters skipped before the number is read.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::atom_label::AtomLabel;
+    ///
+    /// let label = AtomLabel::parse("Cl2B");
+    /// assert_eq!(label.element.as_deref(), Some("Cl"));
+    /// assert_eq!(label.number, Some(2));
+    /// assert_eq!(label.suffix, "B");
+    /// ```
+    pub fn parse(label: &str) -> Self {
+        let element = guess_element(label);
+        let skip = match &element {
+            Some((_, len)) => *len,
+            None => label.bytes().take_while(u8::is_ascii_alphabetic).count(),
+        };
+        let rest = &label[skip..];
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let number = rest[..digits].parse().ok();
+
+        AtomLabel {
+            label: label.to_string(),
+            element: element.map(|(symbol, _)| symbol),
+            number,
+            suffix: rest[digits..].to_string(),
+        }
+    }
+}
+
+/// How [`relabel`] chooses new labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelabelScheme {
+    /// Number every atom of an element from 1 in order, dropping suffixes
+    Sequential,
+    /// Keep the first use of every label; later duplicates get the lowest
+    /// number not yet used with their element and suffix
+    ResolveCollisions,
+}
+
+/// Generate new labels for a list of atoms.
+///
+/// The result is deterministic for a given input order, so relabelling the
+/// same list twice gives the same labels. Labels that do not start with an
+/// element keep their leading letters (or `X`, if there are none) as the
+/// element part.
+pub fn relabel<S: AsRef<str>>(labels: &[S], scheme: RelabelScheme) -> Vec<String> {
+    let parsed: Vec<AtomLabel> = labels
+        .iter()
+        .map(|l| AtomLabel::parse(l.as_ref()))
+        .collect();
+    match scheme {
+        RelabelScheme::Sequential => {
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            parsed
+                .iter()
+                .map(|label| {
+                    let element = element_part(label);
+                    let count = counts.entry(element.clone()).or_default();
+                    *count += 1;
+                    format!("{element}{count}")
+                })
+                .collect()
+        }
+        RelabelScheme::ResolveCollisions => {
+            let mut taken: HashSet<String> = parsed.iter().map(|l| l.label.clone()).collect();
+            let mut seen: HashSet<&str> = HashSet::new();
+            let mut next: HashMap<(String, &str), u32> = HashMap::new();
+            parsed
+                .iter()
+                .map(|label| {
+                    if seen.insert(&label.label) {
+                        return label.label.clone();
+                    }
+                    let element = element_part(label);
+                    let number = next.entry((element.clone(), &label.suffix)).or_insert(0);
+                    let renamed = loop {
+                        *number += 1;
+                        let candidate = format!("{element}{number}{}", label.suffix);
+                        if !taken.contains(&candidate) {
+                            break candidate;
+                        }
+                    };
+                    taken.insert(renamed.clone());
+                    renamed
+                })
+                .collect()
+        }
+    }
+}
+
+/// Labels that occur more than once, each listed once in order of first use
+pub fn duplicate_labels<S: AsRef<str>>(labels: &[S]) -> Vec<&str> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for label in labels {
+        *counts.entry(label.as_ref()).or_default() += 1;
+    }
+    let mut reported = HashSet::new();
+    labels
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|label| counts[label] > 1 && reported.insert(*label))
+        .collect()
+}
+
+/// Compare strings with runs of digits ordered by their numeric value, so
+/// that `C2` sorts before `C10`.
+///
+/// Other characters compare as in `str::cmp`. Strings that differ only in
+/// leading zeros (`C01`, `C1`) are ordered by plain comparison, so the order
+/// is total and consistent with equality.
+///
+/// # Examples
+/// ```
+/// use cif_parser::natural_cmp;
+///
+/// let mut labels = vec!["C10", "C2", "C1A", "C1", "H10B", "H2A"];
+/// labels.sort_by(|a, b| natural_cmp(a, b));
+/// assert_eq!(labels, ["C1", "C1A", "C2", "C10", "H2A", "H10B"]);
+/// ```
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut x, mut y) = (a.as_bytes(), b.as_bytes());
+    while let (Some(&p), Some(&q)) = (x.first(), y.first()) {
+        if p.is_ascii_digit() && q.is_ascii_digit() {
+            let (p_len, q_len) = (digit_run(x), digit_run(y));
+            let p_num = trim_zeros(&x[..p_len]);
+            let q_num = trim_zeros(&y[..q_len]);
+            let order = p_num.len().cmp(&q_num.len()).then(p_num.cmp(q_num));
+            if order != Ordering::Equal {
+                return order;
+            }
+            (x, y) = (&x[p_len..], &y[q_len..]);
+        } else {
+            if p != q {
+                return p.cmp(&q);
+            }
+            (x, y) = (&x[1..], &y[1..]);
+        }
+    }
+    x.len().cmp(&y.len()).then_with(|| a.cmp(b))
+}
+
+fn digit_run(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|&&b| b == b'0').count();
+    &digits[zeros..]
+}
+
+/// How [`CifBlock::canonical_atom_site_order`] orders elements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ElementOrder {
+    /// Heaviest element first, by standard atomic weight
+    #[default]
+    HeavyFirst,
+    /// Element symbols in alphabetical order
+    Alphabetical,
+}
+
+impl CifBlock {
+    /// Sort the rows of the `_atom_site` loop by element, then by label in
+    /// natural order, so that structures built in different orders read the
+    /// same.
+    ///
+    /// The element comes from `_atom_site_type_symbol`, read with
+    /// [`ElementSymbol::parse`], or is guessed from the label; sites with
+    /// neither sort last. The sort is stable. Rows of the `_atom_site_aniso`
+    /// loop are put in the same order as the sites they refer to. Only core
+    /// CIF loops, which have an `_atom_site_label` column, are sorted; mmCIF
+    /// atom lists keep their residue order.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::atom_label::ElementOrder;
+    /// use cif_parser::Document;
+    ///
+    /// let cif = "data_x\nloop_\n_atom_site_label\n_atom_site_type_symbol\n\
+    ///            C10 C\nO1 O\nC2 C\nFe1 Fe3+\n";
+    /// let mut doc = Document::parse(cif).unwrap();
+    /// let block = &mut doc.blocks[0];
+    /// block.canonical_atom_site_order(ElementOrder::HeavyFirst);
+    ///
+    /// let labels = block.loops[0].get_column("_atom_site_label").unwrap();
+    /// let labels: Vec<_> = labels.iter().filter_map(|v| v.as_string()).collect();
+    /// assert_eq!(labels, ["Fe1", "O1", "C2", "C10"]);
+    /// ```
+    pub fn canonical_atom_site_order(&mut self, order: ElementOrder) {
+        let Some(sites) = self
+            .loops
+            .iter_mut()
+            .find(|loop_| in_category(loop_, "_atom_site"))
+        else {
+            return;
+        };
+        let Some(label_col) = attribute_column(sites, "_atom_site", "label") else {
+            return;
+        };
+        let type_col = attribute_column(sites, "_atom_site", "type_symbol");
+
+        let keys: Vec<(Option<String>, String)> = sites
+            .values
+            .iter()
+            .map(|row| {
+                let label = value_text(&row[label_col]).unwrap_or_default();
+                let element = type_col
+                    .and_then(|col| value_text(&row[col]))
+                    .and_then(|symbol| ElementSymbol::parse(&symbol).ok())
+                    .map(|symbol| symbol.element.symbol().to_string())
+                    .or_else(|| AtomLabel::parse(&label).element);
+                (element, label)
+            })
+            .collect();
+        let mut rows: Vec<usize> = (0..keys.len()).collect();
+        rows.sort_by(|&a, &b| {
+            let ((a_element, a_label), (b_element, b_label)) = (&keys[a], &keys[b]);
+            compare_elements(a_element.as_deref(), b_element.as_deref(), order)
+                .then_with(|| natural_cmp(a_label, b_label))
+        });
+        let mut values: Vec<_> = std::mem::take(&mut sites.values)
+            .into_iter()
+            .map(Some)
+            .collect();
+        sites.values = rows.iter().filter_map(|&i| values[i].take()).collect();
+
+        let rank: HashMap<String, usize> = sites
+            .values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| Some((value_text(&row[label_col])?, i)))
+            .rev()
+            .collect();
+        let Some(aniso) = self
+            .loops
+            .iter_mut()
+            .find(|loop_| in_category(loop_, "_atom_site_aniso"))
+        else {
+            return;
+        };
+        if let Some(col) = attribute_column(aniso, "_atom_site_aniso", "label") {
+            aniso.values.sort_by_key(|row| {
+                value_text(&row[col])
+                    .and_then(|label| rank.get(&label).copied())
+                    .unwrap_or(usize::MAX)
+            });
+        }
+    }
+}
+
+/// Whether every tag of a loop belongs to `category`
+fn in_category(loop_: &CifLoop, category: &str) -> bool {
+    !loop_.tags.is_empty() && loop_.tags.iter().all(|tag| category_of(tag) == category)
+}
+
+/// Index of the column for `attribute` of `category`, core or mmCIF style
+fn attribute_column(loop_: &CifLoop, category: &str, attribute: &str) -> Option<usize> {
+    loop_.tags.iter().position(|tag| {
+        let tag = tag.to_ascii_lowercase();
+        tag.strip_prefix(category)
+            .and_then(|rest| rest.strip_prefix(['_', '.']))
+            == Some(attribute)
+    })
+}
+
+fn compare_elements(a: Option<&str>, b: Option<&str>, order: ElementOrder) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => match order {
+            ElementOrder::HeavyFirst => {
+                let weight = |symbol| atomic_weight(symbol).unwrap_or(0.0);
+                weight(b).total_cmp(&weight(a)).then_with(|| a.cmp(b))
+            }
+            ElementOrder::Alphabetical => a.cmp(b),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Element symbol at the start of a label, and how many bytes it takes
+fn guess_element(label: &str) -> Option<(String, usize)> {
+    let mut chars = label.chars();
+    let first = chars.next().filter(char::is_ascii_alphabetic)?;
+    let one = first.to_ascii_uppercase().to_string();
+    let two = chars
+        .next()
+        .filter(char::is_ascii_alphabetic)
+        .map(|second| (second, format!("{one}{}", second.to_ascii_lowercase())));
+    let is_element = |symbol: &str| atomic_weight(symbol).is_some();
+
+    match two {
+        Some((second, symbol)) if second.is_ascii_lowercase() && is_element(&symbol) => {
+            Some((symbol, 2))
+        }
+        _ if is_element(&one) => Some((one, 1)),
+        Some((_, symbol)) if is_element(&symbol) => Some((symbol, 2)),
+        _ => None,
+    }
+}
+
+/// The element of a label, or its leading letters when there is none
+fn element_part(label: &AtomLabel) -> String {
+    if let Some(element) = &label.element {
+        return element.clone();
+    }
+    let letters: String = label
+        .label
+        .chars()
+        .take_while(char::is_ascii_alphabetic)
+        .collect();
+    if letters.is_empty() {
+        "X".to_string()
+    } else {
+        letters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(label: &str) -> (Option<String>, Option<u32>, String) {
+        let label = AtomLabel::parse(label);
+        (label.element, label.number, label.suffix)
+    }
+
+    #[test]
+    fn test_two_letter_elements() {
+        assert_eq!(parts("Ca1"), (Some("Ca".into()), Some(1), "".into()));
+        assert_eq!(parts("C1a"), (Some("C".into()), Some(1), "a".into()));
+        assert_eq!(parts("Cl12"), (Some("Cl".into()), Some(12), "".into()));
+        assert_eq!(parts("CA1"), (Some("C".into()), None, "A1".into()));
+        assert_eq!(parts("ZN1"), (Some("Zn".into()), Some(1), "".into()));
+        assert_eq!(parts("OW1"), (Some("O".into()), None, "W1".into()));
+        // Not an element, so the lowercase letter is a suffix
+        assert_eq!(parts("Cg1"), (Some("C".into()), None, "g1".into()));
+    }
+
+    #[test]
+    fn test_water_and_disorder_suffixes() {
+        assert_eq!(parts("O1W"), (Some("O".into()), Some(1), "W".into()));
+        assert_eq!(parts("C12A"), (Some("C".into()), Some(12), "A".into()));
+        assert_eq!(parts("H1A'"), (Some("H".into()), Some(1), "A'".into()));
+        assert_eq!(parts("N3_2"), (Some("N".into()), Some(3), "_2".into()));
+    }
+
+    #[test]
+    fn test_labels_without_element() {
+        assert_eq!(parts("Q1"), (None, Some(1), "".into()));
+        assert_eq!(parts("12"), (None, Some(12), "".into()));
+        assert_eq!(parts(""), (None, None, "".into()));
+    }
+
+    #[test]
+    fn test_sequential_relabel() {
+        let labels = ["C5", "O1W", "C1A", "Ca2", "Q7", "C1B"];
+        assert_eq!(
+            relabel(&labels, RelabelScheme::Sequential),
+            ["C1", "O1", "C2", "Ca1", "Q1", "C3"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_collisions_is_stable() {
+        let labels = ["C1", "C2", "C1A", "C1", "C1A", "O1", "C1"];
+        let once = relabel(&labels, RelabelScheme::ResolveCollisions);
+        assert_eq!(once, ["C1", "C2", "C1A", "C3", "C2A", "O1", "C4"]);
+        assert!(duplicate_labels(&once).is_empty());
+        assert_eq!(relabel(&once, RelabelScheme::ResolveCollisions), once);
+        assert_eq!(duplicate_labels(&labels), ["C1", "C1A"]);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        use Ordering::*;
+        assert_eq!(natural_cmp("C2", "C10"), Less);
+        assert_eq!(natural_cmp("C10", "C10A"), Less);
+        assert_eq!(natural_cmp("C1B", "C1A"), Greater);
+        assert_eq!(natural_cmp("C01", "C1"), Less);
+        assert_eq!(natural_cmp("C1", "C1"), Equal);
+        assert_eq!(natural_cmp("O1W", "O10"), Less);
+        assert_eq!(
+            natural_cmp("H99999999999999999999", "H100000000000000000000"),
+            Less
+        );
+    }
+}
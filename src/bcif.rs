@@ -0,0 +1,640 @@
+//! BinaryCIF, the MessagePack encoding of mmCIF that the PDB distributes.
+//!
+//! Enabled by the `bcif` feature. [`CifDocument::from_bcif`] decodes a
+//! BinaryCIF file into the same model as a text CIF, and
+//! [`CifDocument::to_bcif`] encodes one; [`CifDocument::from_file`] reads
+//! files ending in `.bcif` this way.
+//!
+//! A BinaryCIF file holds blocks of categories, each a table of columns.
+//! A column's values are stored as bytes together with the list of
+//! encodings that made them, undone in reverse order when reading:
+//!
+//! - `ByteArray`: numbers as little-endian bytes of a given type
+//! - `FixedPoint` and `IntervalQuantization`: floats stored as integers
+//! - `Delta`: integers as differences from the one before
+//! - `RunLength`: integers as pairs of value and repeat count
+//! - `IntegerPacking`: integers in one or two bytes, larger ones as sums
+//! - `StringArray`: text as indices into a table of distinct strings
+//!
+//! An optional mask marks values as `.` (1) or `?` (2).
+//!
+//! Categories of one row become data items and the others loops, with tags
+//! `_category.column`. Strings that read as numbers become numbers, as they
+//! do in text CIF. Written categories are found with
+//! [`CifBlock::categories`], so core CIF tags such as `_cell_length_a` come
+//! back as `_cell.length_a`. A tag that is a category alone, such as `_x`,
+//! is written as a column with an empty name and comes back unchanged.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{CifDocument, CifValue};
+//!
+//! let cif = "data_1abc\n_cell.length_a 40.96\nloop_\n_atom_site.id\n\
+//!            _atom_site.label_atom_id\n_atom_site.label_alt_id\n1 N .\n2 CA A\n";
+//! let doc = CifDocument::parse(cif).unwrap();
+//! let bytes = doc.to_bcif().unwrap();
+//!
+//! let back = CifDocument::from_bcif(&bytes).unwrap();
+//! let atoms = &back.blocks[0].loops[0];
+//! assert_eq!(atoms.get_by_tag(1, "_atom_site.label_atom_id"), Some(&CifValue::Text("CA".into())));
+//! assert_eq!(atoms.get_by_tag(0, "_atom_site.label_alt_id"), Some(&CifValue::NotApplicable));
+//! assert_eq!(back.blocks[0].get_item("_cell.length_a"), Some(&CifValue::Numeric(40.96)));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::uncertainty;
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+use rmpv::Value as Msg;
+
+/// Version of the BinaryCIF format written
+pub const BCIF_VERSION: &str = "0.3.0";
+
+// Data types of `ByteArray`
+const INT8: i64 = 1;
+const INT16: i64 = 2;
+const INT32: i64 = 3;
+const UINT8: i64 = 4;
+const UINT16: i64 = 5;
+const UINT32: i64 = 6;
+const FLOAT32: i64 = 32;
+const FLOAT64: i64 = 33;
+
+// Values of a mask
+const PRESENT: i64 = 0;
+const NOT_APPLICABLE: i64 = 1;
+const UNKNOWN: i64 = 2;
+
+impl CifDocument {
+    /// Decode a BinaryCIF file.
+    ///
+    /// Fails with [`CifError::InvalidStructure`] if the bytes are not
+    /// MessagePack laid out as BinaryCIF, or use an unknown encoding.
+    pub fn from_bcif(bytes: &[u8]) -> Result<Self, CifError> {
+        let file = rmpv::decode::read_value(&mut &bytes[..])
+            .map_err(|error| invalid(format!("not MessagePack: {error}")))?;
+        let mut doc = CifDocument::new();
+        for block in array(&file["dataBlocks"], "dataBlocks")? {
+            doc.blocks.push(block_from_bcif(block)?);
+        }
+        Ok(doc)
+    }
+
+    /// Encode the document as BinaryCIF.
+    ///
+    /// Each category of a block, as [`CifBlock::category`] reads it,
+    /// becomes a table. Fails with [`CifError::InvalidStructure`] for save
+    /// frames, and [`CifError::InvalidValue`] for CIF 2.0 lists and tables,
+    /// which BinaryCIF cannot hold.
+    pub fn to_bcif(&self) -> Result<Vec<u8>, CifError> {
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| block_to_bcif(block, self.version))
+            .collect::<Result<Vec<_>, _>>()?;
+        let file = map([
+            ("version", BCIF_VERSION.into()),
+            (
+                "encoder",
+                concat!("cif-parser ", env!("CARGO_PKG_VERSION")).into(),
+            ),
+            ("dataBlocks", Msg::Array(blocks)),
+        ]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &file)
+            .map_err(|error| CifError::Io(std::io::Error::other(error.to_string())))?;
+        Ok(bytes)
+    }
+}
+
+/// Whether a path names a BinaryCIF file, by its extension
+pub(crate) fn is_bcif_path(path: &std::path::Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("bcif"))
+}
+
+fn invalid(message: impl std::fmt::Display) -> CifError {
+    CifError::invalid_structure(format!("BinaryCIF: {message}"))
+}
+
+fn array<'a>(value: &'a Msg, what: &str) -> Result<&'a Vec<Msg>, CifError> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid(format!("{what} is not an array")))
+}
+
+fn string<'a>(value: &'a Msg, what: &str) -> Result<&'a str, CifError> {
+    value
+        .as_str()
+        .ok_or_else(|| invalid(format!("{what} is not a string")))
+}
+
+fn integer(value: &Msg, what: &str) -> Result<i64, CifError> {
+    value
+        .as_i64()
+        .ok_or_else(|| invalid(format!("{what} is not an integer")))
+}
+
+fn map<const N: usize>(entries: [(&str, Msg); N]) -> Msg {
+    Msg::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect(),
+    )
+}
+
+// ===== Reading =====
+
+/// A column's values partway through decoding
+enum Data {
+    Bytes(Vec<u8>),
+    Integers(Vec<i64>),
+    Floats(Vec<f64>),
+    Strings(Vec<Option<String>>),
+}
+
+impl Data {
+    fn len(&self) -> usize {
+        match self {
+            Data::Bytes(bytes) => bytes.len(),
+            Data::Integers(values) => values.len(),
+            Data::Floats(values) => values.len(),
+            Data::Strings(values) => values.len(),
+        }
+    }
+
+    fn into_integers(self, kind: &str) -> Result<Vec<i64>, CifError> {
+        match self {
+            Data::Integers(values) => Ok(values),
+            _ => Err(invalid(format!("{kind} needs integers"))),
+        }
+    }
+
+    fn value(&self, row: usize) -> CifValue {
+        match self {
+            Data::Bytes(bytes) => CifValue::Numeric(bytes[row].into()),
+            Data::Integers(values) => CifValue::Numeric(values[row] as f64),
+            Data::Floats(values) => CifValue::Numeric(values[row]),
+            Data::Strings(values) => match &values[row] {
+                Some(text) => match uncertainty::parse(text).filter(|_| text.trim() == text) {
+                    Some((value, None)) if value.is_finite() => CifValue::Numeric(value),
+                    Some((value, Some(su))) if value.is_finite() => {
+                        CifValue::NumericSu { value, su }
+                    }
+                    _ => CifValue::Text(text.clone()),
+                },
+                None => CifValue::Unknown,
+            },
+        }
+    }
+}
+
+fn block_from_bcif(block: &Msg) -> Result<CifBlock, CifError> {
+    let mut result = CifBlock::new(string(&block["header"], "header")?.to_string());
+    for category in array(&block["categories"], "categories")? {
+        let name = string(&category["name"], "category name")?;
+        let category_tag = format!("_{}", name.trim_start_matches('_'));
+        let rows = usize::try_from(integer(&category["rowCount"], "rowCount")?)
+            .map_err(|_| invalid(format!("{name} has a negative rowCount")))?;
+        let mut columns = Vec::new();
+        for column in array(&category["columns"], "columns")? {
+            // A tag with no column, such as `_x`, is written with an empty one
+            let tag = match string(&column["name"], "column name")? {
+                "" => category_tag.clone(),
+                column => format!("{category_tag}.{column}"),
+            };
+            let values = decode(&column["data"])?;
+            let mask = match &column["mask"] {
+                Msg::Nil => None,
+                mask => Some(decode(mask)?.into_integers("mask")?),
+            };
+            if values.len() != rows || mask.as_ref().is_some_and(|mask| mask.len() != rows) {
+                return Err(invalid(format!("{tag} does not have {rows} values")));
+            }
+            let values: Vec<CifValue> = (0..rows)
+                .map(
+                    |row| match mask.as_ref().map_or(PRESENT, |mask| mask[row]) {
+                        NOT_APPLICABLE => CifValue::NotApplicable,
+                        UNKNOWN => CifValue::Unknown,
+                        _ => values.value(row),
+                    },
+                )
+                .collect();
+            columns.push((tag, values));
+        }
+        if rows == 1 {
+            for (tag, mut values) in columns {
+                result.items.insert(tag, values.remove(0));
+            }
+        } else {
+            let mut loop_ = CifLoop::new(columns.iter().map(|(tag, _)| tag.clone()).collect());
+            loop_.values = (0..rows)
+                .map(|row| {
+                    columns
+                        .iter()
+                        .map(|(_, values)| values[row].clone())
+                        .collect()
+                })
+                .collect();
+            result.loops.push(loop_);
+        }
+    }
+    Ok(result)
+}
+
+/// Undo the encodings of an `EncodedData` map, last first
+fn decode(encoded: &Msg) -> Result<Data, CifError> {
+    let bytes = encoded["data"]
+        .as_slice()
+        .ok_or_else(|| invalid("encoded data is not binary"))?;
+    decode_with(&encoded["encoding"], bytes.to_vec())
+}
+
+fn decode_with(encodings: &Msg, bytes: Vec<u8>) -> Result<Data, CifError> {
+    let mut data = Data::Bytes(bytes);
+    for encoding in array(encodings, "encoding")?.iter().rev() {
+        data = decode_step(encoding, data)?;
+    }
+    Ok(data)
+}
+
+fn decode_step(encoding: &Msg, data: Data) -> Result<Data, CifError> {
+    let kind = string(&encoding["kind"], "encoding kind")?;
+    let number = |key: &str| {
+        encoding[key]
+            .as_f64()
+            .ok_or_else(|| invalid(format!("{kind} has no {key}")))
+    };
+    let size = |key: &str| {
+        usize::try_from(integer(&encoding[key], key)?)
+            .map_err(|_| invalid(format!("{kind} has a negative {key}")))
+    };
+    Ok(match kind {
+        "ByteArray" => {
+            let Data::Bytes(bytes) = data else {
+                return Err(invalid("ByteArray needs bytes"));
+            };
+            from_bytes(&bytes, integer(&encoding["type"], "type")?)?
+        }
+        "FixedPoint" => {
+            let factor = number("factor")?;
+            let values = data.into_integers(kind)?;
+            Data::Floats(values.into_iter().map(|v| v as f64 / factor).collect())
+        }
+        "IntervalQuantization" => {
+            let (min, max, steps) = (number("min")?, number("max")?, number("numSteps")?);
+            let delta = (max - min) / (steps - 1.0);
+            let values = data.into_integers(kind)?;
+            Data::Floats(values.into_iter().map(|v| min + delta * v as f64).collect())
+        }
+        "RunLength" => {
+            let total = size("srcSize")?;
+            let pairs = data.into_integers(kind)?;
+            let mut values = Vec::new();
+            for pair in pairs.chunks(2) {
+                let [value, count] = *pair else {
+                    return Err(invalid("RunLength has an odd number of values"));
+                };
+                let count = usize::try_from(count).unwrap_or(0);
+                if values.len() + count > total {
+                    return Err(invalid("RunLength gives more than srcSize values"));
+                }
+                values.extend(std::iter::repeat_n(value, count));
+            }
+            Data::Integers(values)
+        }
+        "Delta" => {
+            let mut previous = integer(&encoding["origin"], "origin")?;
+            let mut values = data.into_integers(kind)?;
+            for value in &mut values {
+                previous = previous.wrapping_add(*value);
+                *value = previous;
+            }
+            Data::Integers(values)
+        }
+        "IntegerPacking" => {
+            let (upper, lower) = match (
+                integer(&encoding["byteCount"], "byteCount")?,
+                encoding["isUnsigned"].as_bool().unwrap_or(false),
+            ) {
+                (1, true) => (0xFF, i64::MIN),
+                (1, false) => (0x7F, -0x80),
+                (2, true) => (0xFFFF, i64::MIN),
+                (2, false) => (0x7FFF, -0x8000),
+                (count, _) => return Err(invalid(format!("IntegerPacking byteCount {count}"))),
+            };
+            let packed = data.into_integers(kind)?;
+            let mut values = Vec::with_capacity(packed.len());
+            let mut sum = 0;
+            for value in packed {
+                sum += value;
+                if value != upper && value != lower {
+                    values.push(sum);
+                    sum = 0;
+                }
+            }
+            Data::Integers(values)
+        }
+        "StringArray" => {
+            let Data::Bytes(bytes) = data else {
+                return Err(invalid("StringArray needs bytes"));
+            };
+            let text: Vec<char> = string(&encoding["stringData"], "stringData")?
+                .chars()
+                .collect();
+            let offsets = encoding["offsets"]
+                .as_slice()
+                .ok_or_else(|| invalid("StringArray offsets are not binary"))?;
+            let offsets = decode_with(&encoding["offsetEncoding"], offsets.to_vec())?
+                .into_integers("offsets")?;
+            let strings = offsets
+                .windows(2)
+                .map(|pair| {
+                    let range = usize::try_from(pair[0]).ok()?..usize::try_from(pair[1]).ok()?;
+                    Some(text.get(range)?.iter().collect::<String>())
+                })
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| invalid("StringArray offsets are out of range"))?;
+            let indices =
+                decode_with(&encoding["dataEncoding"], bytes)?.into_integers("indices")?;
+            let values = indices
+                .into_iter()
+                .map(|index| match usize::try_from(index) {
+                    Ok(index) => strings.get(index).cloned().map(Some),
+                    Err(_) => Some(None),
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid("StringArray index out of range"))?;
+            Data::Strings(values)
+        }
+        other => return Err(invalid(format!("unknown encoding {other}"))),
+    })
+}
+
+fn from_bytes(bytes: &[u8], kind: i64) -> Result<Data, CifError> {
+    fn read<const N: usize, T>(
+        bytes: &[u8],
+        convert: fn([u8; N]) -> T,
+    ) -> Result<Vec<T>, CifError> {
+        if !bytes.len().is_multiple_of(N) {
+            return Err(invalid(format!(
+                "{} bytes do not divide into {N}s",
+                bytes.len()
+            )));
+        }
+        Ok(bytes
+            .chunks_exact(N)
+            .map(|chunk| convert(chunk.try_into().expect("chunks of N")))
+            .collect())
+    }
+    Ok(match kind {
+        INT8 => Data::Integers(read(bytes, |b: [u8; 1]| i8::from_le_bytes(b).into())?),
+        INT16 => Data::Integers(read(bytes, |b| i16::from_le_bytes(b).into())?),
+        INT32 => Data::Integers(read(bytes, |b| i32::from_le_bytes(b).into())?),
+        UINT8 => Data::Integers(bytes.iter().map(|&b| b.into()).collect()),
+        UINT16 => Data::Integers(read(bytes, |b| u16::from_le_bytes(b).into())?),
+        UINT32 => Data::Integers(read(bytes, |b| u32::from_le_bytes(b).into())?),
+        FLOAT32 => Data::Floats(read(bytes, |b| f32::from_le_bytes(b).into())?),
+        FLOAT64 => Data::Floats(read(bytes, f64::from_le_bytes)?),
+        other => return Err(invalid(format!("unknown ByteArray type {other}"))),
+    })
+}
+
+// ===== Writing =====
+
+fn block_to_bcif(block: &CifBlock, version: CifVersion) -> Result<Msg, CifError> {
+    if let Some(frame) = block.frames.first() {
+        return Err(invalid(format!(
+            "save frame {} cannot be written",
+            frame.name
+        )));
+    }
+    let mut categories = Vec::new();
+    for name in block.categories() {
+        let Some(category) = block.category(&name) else {
+            continue;
+        };
+        let mut columns = Vec::new();
+        for attribute in category.attributes() {
+            let values: Vec<&CifValue> = (0..category.len())
+                .map(|row| category.get(row, attribute).unwrap_or(&CifValue::Unknown))
+                .collect();
+            columns.push(column_to_bcif(attribute, &values, version)?);
+        }
+        categories.push(map([
+            ("name", category.name().into()),
+            ("columns", Msg::Array(columns)),
+            ("rowCount", category.len().into()),
+        ]));
+    }
+    Ok(map([
+        ("header", block.name.as_str().into()),
+        ("categories", Msg::Array(categories)),
+    ]))
+}
+
+fn column_to_bcif(name: &str, values: &[&CifValue], version: CifVersion) -> Result<Msg, CifError> {
+    let mask: Vec<i64> = values
+        .iter()
+        .map(|value| match value {
+            CifValue::NotApplicable => NOT_APPLICABLE,
+            CifValue::Unknown => UNKNOWN,
+            _ => PRESENT,
+        })
+        .collect();
+    let present = || {
+        values
+            .iter()
+            .filter(|value| !matches!(value, CifValue::Unknown | CifValue::NotApplicable))
+    };
+    let numbers: Option<Vec<f64>> = present().map(|value| value.as_numeric()).collect();
+    let data = match numbers {
+        Some(numbers)
+            if numbers
+                .iter()
+                .all(|n| n.fract() == 0.0 && n.abs() <= i32::MAX as f64) =>
+        {
+            let integers = values
+                .iter()
+                .map(|value| value.as_numeric().unwrap_or(0.0) as i64)
+                .collect();
+            encode_integers(integers)
+        }
+        Some(_) => {
+            let bytes = values
+                .iter()
+                .flat_map(|value| value.as_numeric().unwrap_or(0.0).to_le_bytes())
+                .collect();
+            encoded(vec![byte_array(FLOAT64)], bytes)
+        }
+        None => encode_strings(values, version)?,
+    };
+    let mask = if mask.iter().any(|&kind| kind != PRESENT) {
+        encode_integers(mask)
+    } else {
+        Msg::Nil
+    };
+    Ok(map([("name", name.into()), ("data", data), ("mask", mask)]))
+}
+
+fn encoded(encoding: Vec<Msg>, bytes: Vec<u8>) -> Msg {
+    map([
+        ("encoding", Msg::Array(encoding)),
+        ("data", Msg::Binary(bytes)),
+    ])
+}
+
+fn byte_array(kind: i64) -> Msg {
+    map([("kind", "ByteArray".into()), ("type", kind.into())])
+}
+
+/// Integers as `Delta`, `RunLength`, `IntegerPacking` and `ByteArray`
+fn encode_integers(values: Vec<i64>) -> Msg {
+    let (encoding, bytes) = integer_encoding(values);
+    encoded(encoding, bytes)
+}
+
+fn integer_encoding(values: Vec<i64>) -> (Vec<Msg>, Vec<u8>) {
+    let size = values.len();
+    let origin = values.first().copied().unwrap_or(0);
+    let mut previous = origin;
+    let deltas: Vec<i64> = values
+        .iter()
+        .map(|&value| {
+            let delta = value - previous;
+            previous = value;
+            delta
+        })
+        .collect();
+
+    let mut runs: Vec<i64> = Vec::new();
+    for delta in deltas {
+        match runs.as_mut_slice() {
+            [.., value, count] if *value == delta => *count += 1,
+            _ => runs.extend([delta, 1]),
+        }
+    }
+
+    let unsigned = runs.iter().all(|&value| value >= 0);
+    let byte_count = if packed_len(&runs, 1, unsigned) <= 2 * packed_len(&runs, 2, unsigned) {
+        1
+    } else {
+        2
+    };
+    let (upper, lower) = limits(byte_count, unsigned);
+    let mut packed = Vec::new();
+    for &value in &runs {
+        let mut rest = value;
+        while rest >= upper {
+            packed.push(upper);
+            rest -= upper;
+        }
+        while rest <= lower {
+            packed.push(lower);
+            rest -= lower;
+        }
+        packed.push(rest);
+    }
+    let (kind, bytes): (i64, Vec<u8>) = match (byte_count, unsigned) {
+        (1, true) => (UINT8, packed.iter().map(|&v| v as u8).collect()),
+        (1, false) => (INT8, packed.iter().map(|&v| v as i8 as u8).collect()),
+        (_, true) => (
+            UINT16,
+            packed
+                .iter()
+                .flat_map(|&v| (v as u16).to_le_bytes())
+                .collect(),
+        ),
+        (_, false) => (
+            INT16,
+            packed
+                .iter()
+                .flat_map(|&v| (v as i16).to_le_bytes())
+                .collect(),
+        ),
+    };
+    let encoding = vec![
+        map([
+            ("kind", "Delta".into()),
+            ("origin", origin.into()),
+            ("srcType", INT32.into()),
+        ]),
+        map([
+            ("kind", "RunLength".into()),
+            ("srcType", INT32.into()),
+            ("srcSize", size.into()),
+        ]),
+        map([
+            ("kind", "IntegerPacking".into()),
+            ("byteCount", byte_count.into()),
+            ("isUnsigned", unsigned.into()),
+            ("srcSize", runs.len().into()),
+        ]),
+        byte_array(kind),
+    ];
+    (encoding, bytes)
+}
+
+/// The largest and smallest packed values, which carry on into the next
+fn limits(byte_count: i64, unsigned: bool) -> (i64, i64) {
+    match (byte_count, unsigned) {
+        (1, true) => (0xFF, i64::MIN),
+        (1, false) => (0x7F, -0x80),
+        (_, true) => (0xFFFF, i64::MIN),
+        (_, false) => (0x7FFF, -0x8000),
+    }
+}
+
+fn packed_len(values: &[i64], byte_count: i64, unsigned: bool) -> usize {
+    let (upper, lower) = limits(byte_count, unsigned);
+    values
+        .iter()
+        .map(|&value| match value {
+            0.. => (value / upper) as usize + 1,
+            _ => (value / lower) as usize + 1,
+        })
+        .sum()
+}
+
+/// Text as a `StringArray`, numbers as CIF writes them
+fn encode_strings(values: &[&CifValue], version: CifVersion) -> Result<Msg, CifError> {
+    let mut table: IndexMap<String, i64> = IndexMap::new();
+    let mut indices = Vec::with_capacity(values.len());
+    for value in values {
+        let text = match value {
+            CifValue::Unknown | CifValue::NotApplicable => {
+                indices.push(-1);
+                continue;
+            }
+            CifValue::List(_) | CifValue::Table(_) => {
+                return Err(CifError::InvalidValue {
+                    kind: "BinaryCIF value",
+                    value: inline_value(value, version),
+                });
+            }
+            CifValue::Numeric(_) | CifValue::NumericSu { .. } => inline_value(value, version),
+            text => text.as_string().unwrap_or_default().to_string(),
+        };
+        let next = table.len() as i64;
+        indices.push(*table.entry(text).or_insert(next));
+    }
+    let mut offsets = vec![0];
+    let mut text = String::new();
+    for string in table.keys() {
+        text.push_str(string);
+        offsets.push(offsets[offsets.len() - 1] + string.chars().count() as i64);
+    }
+    let (data_encoding, data) = integer_encoding(indices);
+    let (offset_encoding, offsets) = integer_encoding(offsets);
+    let encoding = map([
+        ("kind", "StringArray".into()),
+        ("dataEncoding", Msg::Array(data_encoding)),
+        ("stringData", text.into()),
+        ("offsetEncoding", Msg::Array(offset_encoding)),
+        ("offsets", Msg::Binary(offsets)),
+    ]);
+    Ok(encoded(vec![encoding], data))
+}
@@ -0,0 +1,177 @@
+//! Throughput of the main operations on one CIF file.
+//!
+//! Enabled by the `bench` feature, and behind `cif bench <file>`. [`run`]
+//! times parsing (also [borrowed](crate::borrowed)), looking up loops, reading loop columns as numbers, and
+//! writing, taking the best of several rounds of each; [`Measurement`]
+//! turns the times into MB/s and rows/s. The criterion suite in `benches/`
+//! covers the same operations on fixed inputs, for tracking regressions.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::bench;
+//!
+//! let cif = "data_x\nloop_\n_a\n_b\n1 2\n3 4\n";
+//! let measurements = bench::run(cif, 3).unwrap();
+//! assert_eq!(measurements[0].name, "parse");
+//! assert_eq!(measurements[0].rows, 2);
+//! print!("{}", bench::table(&measurements));
+//! ```
+
+use crate::ast::CifDocument;
+use crate::borrowed::CifDocumentRef;
+use crate::error::CifError;
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// The best time of one operation over the whole file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// Which operation: `parse`, `parse_ref`, `find_loop`, `columns`, or
+    /// `write`
+    pub name: &'static str,
+    /// Best time over all rounds
+    pub time: Duration,
+    /// Bytes of CIF text the operation covers; 0 for `find_loop`, which
+    /// reads none
+    pub bytes: usize,
+    /// Loop rows the operation covers; for `find_loop`, the lookups made
+    pub rows: usize,
+}
+
+impl Measurement {
+    /// Throughput in megabytes (10⁶ bytes) per second
+    pub fn mb_per_s(&self) -> f64 {
+        self.bytes as f64 / 1e6 / self.time.as_secs_f64()
+    }
+
+    /// Loop rows (or lookups) per second
+    pub fn rows_per_s(&self) -> f64 {
+        self.rows as f64 / self.time.as_secs_f64()
+    }
+}
+
+/// Time each operation on `content`, keeping the best of `rounds` runs.
+///
+/// Fails if `content` does not parse.
+pub fn run(content: &str, rounds: usize) -> Result<Vec<Measurement>, CifError> {
+    let doc = CifDocument::parse(content)?;
+    let rounds = rounds.max(1);
+    let bytes = content.len();
+    let rows: usize = doc
+        .blocks
+        .iter()
+        .flat_map(|block| &block.loops)
+        .map(|loop_| loop_.len())
+        .sum();
+    let tags: Vec<(usize, &str)> = doc
+        .blocks
+        .iter()
+        .enumerate()
+        .flat_map(|(i, block)| {
+            block
+                .loops
+                .iter()
+                .flat_map(move |l| &l.tags)
+                .map(move |t| (i, t.as_str()))
+        })
+        .collect();
+
+    let parse = best_of(rounds, || {
+        let _ = black_box(CifDocument::parse(black_box(content)));
+    });
+    let parse_ref = best_of(rounds, || {
+        let _ = black_box(CifDocumentRef::parse(black_box(content)));
+    });
+    let find_loop = best_of(rounds, || {
+        for &(block, tag) in &tags {
+            black_box(doc.blocks[block].find_loop(black_box(tag)));
+        }
+    });
+    let columns = best_of(rounds, || {
+        for loop_ in doc.blocks.iter().flat_map(|block| &block.loops) {
+            for tag in &loop_.tags {
+                let column: Vec<Option<f64>> = loop_
+                    .get_column(tag)
+                    .into_iter()
+                    .flatten()
+                    .map(|value| value.as_numeric())
+                    .collect();
+                black_box(column);
+            }
+        }
+    });
+    let write = best_of(rounds, || {
+        black_box(doc.to_string());
+    });
+
+    Ok(vec![
+        Measurement {
+            name: "parse",
+            time: parse,
+            bytes,
+            rows,
+        },
+        Measurement {
+            name: "parse_ref",
+            time: parse_ref,
+            bytes,
+            rows,
+        },
+        Measurement {
+            name: "find_loop",
+            time: find_loop,
+            bytes: 0,
+            rows: tags.len(),
+        },
+        Measurement {
+            name: "columns",
+            time: columns,
+            bytes,
+            rows,
+        },
+        Measurement {
+            name: "write",
+            time: write,
+            bytes,
+            rows,
+        },
+    ])
+}
+
+/// The measurements as an aligned text table, one line per operation
+pub fn table(measurements: &[Measurement]) -> String {
+    let mut out = format!(
+        "{:<10} {:>12} {:>10} {:>14}\n",
+        "operation", "time", "MB/s", "rows/s"
+    );
+    for m in measurements {
+        let mb_per_s = if m.bytes == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}", m.mb_per_s())
+        };
+        let _ = writeln!(
+            out,
+            "{:<10} {:>12.2?} {:>10} {:>14.0}",
+            m.name,
+            m.time,
+            mb_per_s,
+            m.rows_per_s()
+        );
+    }
+    out
+}
+
+/// Best time over `rounds` runs of `f`
+fn best_of(rounds: usize, mut f: impl FnMut()) -> Duration {
+    (0..rounds)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
@@ -0,0 +1,850 @@
+// Command-line tools for CIF files
+//
+// Usage: cif check [--sanity] [--strict-1.1] [--format text|json|sarif|junit] <file>...
+//        cif stats <file>
+//        cif census [--json] [--threads N] <dir>
+//        cif extract --tags <tag,...> [--first-block] [--threads N] [-o <file>] <dir>
+//        cif redact (--profile <name> | --tag <pattern>)... [--remove] [-o <file>] <file>
+//        cif fmt [--check | --write] [--no-align] <file>...
+//        cif query <selector> <file>...
+//        cif upgrade --to 2.0 [--keep-markup] [-o <file>] <file>
+//        cif arrow [--block <name>] (--loop <tag> -o <file> | --all-loops -o <dir>) <file>
+//                                          (with the `arrow` feature)
+//        cif bench [--rounds N] <file>     (with the `bench` feature)
+
+use cif_parser::corpus::{census_files, cif_files, extract_csv};
+use cif_parser::formatter::{format_str, FormatOptions};
+use cif_parser::redact::{redact_text, RedactionPolicy};
+use cif_parser::{
+    extract_items, Conformance, Document, ExtractOptions, ParseOptions, Query, Report,
+    UpgradeOptions,
+};
+use std::process::ExitCode;
+use std::{env, fs, thread};
+
+const USAGE: &str = "\
+Usage: cif <command> [args]
+
+Commands:
+  check [--sanity] [--strict-1.1] [--format text|json|sarif|junit] <file>...
+                  Parse each file and print its kind and any warnings; with
+                  --sanity, also check cell, atom-site, and other values for
+                  physical sense; with --strict-1.1, fail on anything the
+                  CIF 1.1 specification does not allow, such as lines over
+                  2048 characters or non-ASCII text. Other formats than
+                  text print every finding with its rule ID and position,
+                  as one JSON, SARIF 2.1.0, or JUnit XML document
+  stats <file>    Print the memory used by the parsed file
+  census [--json] [--threads N] <dir>
+                  Print tag usage statistics for every .cif and .mmcif file
+                  under <dir>, as CSV (default) or JSON
+  extract --tags <tag,...> [--first-block] [--threads N] [-o <file>] <dir>
+                  Write the value of each tag in every data block of every
+                  .cif and .mmcif file under <dir> as CSV, to <file> or to
+                  standard output; with --first-block, read only the first
+                  block of each file
+  redact (--profile <name> | --tag <pattern>)... [--remove] [-o <file>] <file>
+                  Replace the values of matching tags with ?, or with
+                  --remove take out the items and loop columns, and write
+                  the result to <file> or to standard output, keeping
+                  everything else as it was; print what was changed to
+                  standard error. Profiles are pre_publication (author and
+                  contact details, special details) and strip_embedded_files
+                  (SHELX .res, .hkl, and .fab files); patterns may use * and ?
+  fmt [--check | --write] [--no-align] <file>...
+                  Print each file formatted canonically: items in tag order,
+                  values and loop columns lined up, comments kept. With
+                  --write, rewrite the files in place; with --check, print
+                  the files that are not formatted and fail if there are any.
+                  --no-align leaves values unpadded
+  query <selector> <file>...
+                  Print the values <selector> picks from each file, one per
+                  line as <file>: <block> <tag>[<row>] = <value>, and fail if
+                  there are none. A selector such as
+                  'block[*].loop(_atom_site).rows[occupancy<1].label' picks
+                  blocks by name, glob, or index, then an item tag or a loop,
+                  then loop rows by a column's value, then columns
+  upgrade --to 2.0 [--keep-markup] [-o <file>] <file>
+                  Write <file> as CIF 2.0, to <file> or to standard output,
+                  with markup such as \\'e, \\%a, and ^2^ in text turned
+                  into Unicode unless --keep-markup is given; print each
+                  changed value to standard error, and fail without writing
+                  if a value cannot be written as CIF 2.0
+  arrow [--block <name>] (--loop <tag> -o <file> | --all-loops -o <dir>) <file>
+                  Write the loop holding <tag>, or of category <tag> (such
+                  as _atom_site), to an Arrow IPC (Feather v2) file; with
+                  --all-loops, write every loop to <dir> as
+                  <block>.<category>.arrow. Loops are taken from the first
+                  block holding data unless --block names one (or, with
+                  --all-loops, from every block); needs the `arrow` feature
+  bench [--rounds N] <file>
+                  Time parsing, loop lookup, column reads, and writing of
+                  <file>, best of N rounds (default 5), and print MB/s and
+                  rows/s; needs the `bench` feature";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["check", options @ ..] => match CheckArgs::parse(options) {
+            Some(check_args) => check(check_args),
+            None => usage_error(),
+        },
+        ["stats", file] => stats(file),
+        ["census", options @ ..] => match CensusArgs::parse(options) {
+            Some(census_args) => census(census_args),
+            None => usage_error(),
+        },
+        ["extract", options @ ..] => match ExtractArgs::parse(options) {
+            Some(extract_args) => extract(extract_args),
+            None => usage_error(),
+        },
+        ["redact", options @ ..] => match RedactArgs::parse(options) {
+            Some(redact_args) => redact(redact_args),
+            None => usage_error(),
+        },
+        ["fmt", options @ ..] => match FmtArgs::parse(options) {
+            Some(fmt_args) => fmt(fmt_args),
+            None => usage_error(),
+        },
+        ["query", selector, files @ ..] if !files.is_empty() => query(selector, files),
+        ["upgrade", options @ ..] => match UpgradeArgs::parse(options) {
+            Some(upgrade_args) => upgrade(upgrade_args),
+            None => usage_error(),
+        },
+        ["arrow", options @ ..] => match ArrowArgs::parse(options) {
+            Some(arrow_args) => arrow(arrow_args),
+            None => usage_error(),
+        },
+        ["bench", options @ ..] => match BenchArgs::parse(options) {
+            Some(bench_args) => bench(bench_args),
+            None => usage_error(),
+        },
+        ["-h" | "--help" | "help"] => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        _ => usage_error(),
+    }
+}
+
+fn usage_error() -> ExitCode {
+    eprintln!("{USAGE}");
+    ExitCode::from(2)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Sarif,
+    Junit,
+}
+
+struct CheckArgs<'a> {
+    files: Vec<&'a str>,
+    sanity: bool,
+    conformance: Conformance,
+    format: Format,
+}
+
+impl<'a> CheckArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut files = Vec::new();
+        let mut sanity = false;
+        let mut conformance = Conformance::Unchecked;
+        let mut format = Format::Text;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--sanity" => sanity = true,
+                "--strict-1.1" => conformance = Conformance::Strict11,
+                "--format" => {
+                    format = match *args.next()? {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        "sarif" => Format::Sarif,
+                        "junit" => Format::Junit,
+                        _ => return None,
+                    }
+                }
+                _ if !arg.starts_with('-') => files.push(arg),
+                _ => return None,
+            }
+        }
+        if files.is_empty() {
+            return None;
+        }
+        Some(CheckArgs {
+            files,
+            sanity,
+            conformance,
+            format,
+        })
+    }
+}
+
+/// Fails if any file does not parse or, with `sanity`, has a suspect value
+fn check(args: CheckArgs) -> ExitCode {
+    if args.format != Format::Text {
+        return check_report(args);
+    }
+    let options = ParseOptions::new().conformance(args.conformance);
+    let mut ok = true;
+    for &file in &args.files {
+        let doc = match Document::from_file_with_options(file, &options) {
+            Ok(doc) => doc,
+            Err(e) => {
+                eprintln!("{file}: {e}");
+                // Syntax errors quote the line in their message already
+                if let (Some(excerpt), false) = (e.excerpt(), e.code() == "E0001") {
+                    eprintln!("{excerpt}");
+                }
+                ok = false;
+                continue;
+            }
+        };
+        println!("{file}: kind: {}", doc.kind());
+        for warning in &doc.warnings {
+            println!("{file}: {warning}");
+        }
+        if !args.sanity {
+            continue;
+        }
+        for block in &doc.blocks {
+            for issue in block.sanity_check() {
+                println!("{file}: data_{}: {issue}", block.name);
+                ok = false;
+            }
+        }
+    }
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// The same checks, printed as one machine-readable report
+fn check_report(args: CheckArgs) -> ExitCode {
+    let options = ParseOptions::new().conformance(args.conformance);
+    let mut report = Report::new();
+    for &file in &args.files {
+        report.begin_file(file);
+        match Document::from_file_with_options(file, &options) {
+            Ok(doc) => {
+                report.add_warnings(&doc.warnings);
+                if args.sanity {
+                    for block in &doc.blocks {
+                        report.add_sanity_issues(&block.name, &block.sanity_check());
+                    }
+                }
+            }
+            Err(e) => report.add_error(&e),
+        }
+        // Positions are only needed for findings on values; files that are
+        // not UTF-8 keep them unset
+        if let Ok(content) = fs::read_to_string(file) {
+            report.locate(&content);
+        }
+    }
+    let output = match args.format {
+        Format::Json => report.to_json(),
+        Format::Sarif => report.to_sarif(),
+        Format::Junit => report.to_junit_xml(),
+        Format::Text => unreachable!("text is printed as the files are checked"),
+    };
+    println!("{}", output.trim_end());
+    if report.has_errors() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn stats(file: &str) -> ExitCode {
+    match Document::from_file(file) {
+        Ok(doc) => {
+            println!("{file}: {} blocks", doc.blocks.len());
+            print!("{}", doc.memory_footprint());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{file}: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct CensusArgs<'a> {
+    dir: &'a str,
+    json: bool,
+    threads: usize,
+}
+
+impl<'a> CensusArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut dir = None;
+        let mut json = false;
+        let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--json" => json = true,
+                "--threads" => threads = args.next()?.parse().ok().filter(|&n| n > 0)?,
+                _ if dir.is_none() && !arg.starts_with('-') => dir = Some(arg),
+                _ => return None,
+            }
+        }
+        Some(CensusArgs {
+            dir: dir?,
+            json,
+            threads,
+        })
+    }
+}
+
+fn census(args: CensusArgs) -> ExitCode {
+    let files = match cif_files(args.dir) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}: {e}", args.dir);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Files that fail to parse are reported and left out
+    let (census, errors) = census_files(&files, args.threads);
+    for error in &errors {
+        eprintln!("{error}");
+    }
+    eprintln!(
+        "{} files, {} parsed, {} tags",
+        files.len(),
+        census.documents,
+        census.tags.len()
+    );
+    if args.json {
+        println!("{}", census.to_json());
+    } else {
+        print!("{}", census.to_csv());
+    }
+    ExitCode::SUCCESS
+}
+
+struct ExtractArgs<'a> {
+    dir: &'a str,
+    tags: Vec<&'a str>,
+    output: Option<&'a str>,
+    options: ExtractOptions,
+}
+
+impl<'a> ExtractArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut dir = None;
+        let mut tags = Vec::new();
+        let mut output = None;
+        let mut options = ExtractOptions::new();
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--tags" => tags.extend(args.next()?.split(',').filter(|tag| !tag.is_empty())),
+                "--first-block" => options = options.first_block_only(true),
+                "--threads" => {
+                    options = options.threads(args.next()?.parse().ok().filter(|&n| n > 0)?)
+                }
+                "-o" | "--output" => output = Some(*args.next()?),
+                _ if dir.is_none() && !arg.starts_with('-') => dir = Some(arg),
+                _ => return None,
+            }
+        }
+        if tags.is_empty() {
+            return None;
+        }
+        Some(ExtractArgs {
+            dir: dir?,
+            tags,
+            output,
+            options,
+        })
+    }
+}
+
+fn extract(args: ExtractArgs) -> ExitCode {
+    let files = match cif_files(args.dir) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}: {e}", args.dir);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Files that fail to parse keep their row, with the error in the last column
+    let records = extract_items(&files, &args.tags, &args.options);
+    let failed = records
+        .iter()
+        .filter(|record| record.error.is_some())
+        .count();
+    eprintln!(
+        "{} files, {} rows, {} failed",
+        files.len(),
+        records.len(),
+        failed
+    );
+    let csv = extract_csv(&records, &args.tags);
+    match args.output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, csv) {
+                eprintln!("{output}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{csv}"),
+    }
+    ExitCode::SUCCESS
+}
+
+struct RedactArgs<'a> {
+    file: &'a str,
+    policy: RedactionPolicy,
+    output: Option<&'a str>,
+}
+
+impl<'a> RedactArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut file = None;
+        let mut policy = RedactionPolicy::new();
+        let mut output = None;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--profile" => policy = policy.profile(args.next()?.parse().ok()?),
+                "--tag" => policy = policy.tag(*args.next()?),
+                "--remove" => policy = policy.remove_items(true),
+                "-o" | "--output" => output = Some(*args.next()?),
+                _ if file.is_none() && !arg.starts_with('-') => file = Some(arg),
+                _ => return None,
+            }
+        }
+        if policy.patterns.is_empty() {
+            return None;
+        }
+        Some(RedactArgs {
+            file: file?,
+            policy,
+            output,
+        })
+    }
+}
+
+fn redact(args: RedactArgs) -> ExitCode {
+    let redacted = fs::read_to_string(args.file)
+        .map_err(|e| e.to_string())
+        .and_then(|input| redact_text(&input, &args.policy).map_err(|e| e.to_string()));
+    let (text, report) = match redacted {
+        Ok(redacted) => redacted,
+        Err(e) => {
+            eprintln!("{}: {e}", args.file);
+            return ExitCode::FAILURE;
+        }
+    };
+    for redaction in &report.redactions {
+        eprintln!("{}: {redaction}", args.file);
+    }
+    match args.output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, text) {
+                eprintln!("{output}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{text}"),
+    }
+    ExitCode::SUCCESS
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FmtMode {
+    Print,
+    Check,
+    Write,
+}
+
+struct FmtArgs<'a> {
+    files: Vec<&'a str>,
+    mode: FmtMode,
+    options: FormatOptions,
+}
+
+impl<'a> FmtArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut files = Vec::new();
+        let mut mode = FmtMode::Print;
+        let mut options = FormatOptions::new();
+        for &arg in args {
+            match arg {
+                "--check" if mode == FmtMode::Print => mode = FmtMode::Check,
+                "--write" | "-w" if mode == FmtMode::Print => mode = FmtMode::Write,
+                "--no-align" => options = options.align(false),
+                _ if !arg.starts_with('-') => files.push(arg),
+                _ => return None,
+            }
+        }
+        if files.is_empty() {
+            return None;
+        }
+        Some(FmtArgs {
+            files,
+            mode,
+            options,
+        })
+    }
+}
+
+/// Fails if any file does not parse or, with `--check`, is not formatted
+fn fmt(args: FmtArgs) -> ExitCode {
+    let mut ok = true;
+    for &file in &args.files {
+        let formatted = fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|input| {
+                let formatted = format_str(&input, &args.options).map_err(|e| e.to_string())?;
+                Ok((formatted != input, formatted))
+            });
+        let (changed, text) = match formatted {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("{file}: {e}");
+                ok = false;
+                continue;
+            }
+        };
+        match args.mode {
+            FmtMode::Print => print!("{text}"),
+            FmtMode::Check if changed => {
+                println!("{file}: not formatted");
+                ok = false;
+            }
+            FmtMode::Check => {}
+            FmtMode::Write if changed => {
+                if let Err(e) = fs::write(file, text) {
+                    eprintln!("{file}: {e}");
+                    ok = false;
+                }
+            }
+            FmtMode::Write => {}
+        }
+    }
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Fails if the selector does not parse, a file does not parse, or nothing
+/// matches
+fn query(selector: &str, files: &[&str]) -> ExitCode {
+    let query = match Query::parse(selector) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut ok = true;
+    let mut found = false;
+    for &file in files {
+        let doc = match Document::from_file(file) {
+            Ok(doc) => doc,
+            Err(e) => {
+                eprintln!("{file}: {e}");
+                ok = false;
+                continue;
+            }
+        };
+        for hit in query.evaluate(&doc) {
+            println!("{file}: {hit}");
+            found = true;
+        }
+    }
+    if ok && found {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+struct UpgradeArgs<'a> {
+    file: &'a str,
+    options: UpgradeOptions,
+    output: Option<&'a str>,
+}
+
+impl<'a> UpgradeArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut file = None;
+        let mut to = None;
+        let mut options = UpgradeOptions::new();
+        let mut output = None;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--to" => to = Some(*args.next()?),
+                "--keep-markup" => options = options.keep_markup(true),
+                "-o" | "--output" => output = Some(*args.next()?),
+                _ if file.is_none() && !arg.starts_with('-') => file = Some(arg),
+                _ => return None,
+            }
+        }
+        // CIF 2.0 is the only version to upgrade to
+        if to != Some("2.0") {
+            return None;
+        }
+        Some(UpgradeArgs {
+            file: file?,
+            options,
+            output,
+        })
+    }
+}
+
+fn upgrade(args: UpgradeArgs) -> ExitCode {
+    let doc = match Document::from_file(args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            eprintln!("{}: {e}", args.file);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (upgraded, report) = doc.upgrade_to_cif2(&args.options);
+    for change in &report.changes {
+        eprintln!("{}: {change}", args.file);
+    }
+    if !report.problems.is_empty() {
+        for problem in &report.problems {
+            eprintln!("{}: {problem}", args.file);
+        }
+        return ExitCode::FAILURE;
+    }
+    match args.output {
+        Some(output) => {
+            if let Err(e) = fs::write(output, upgraded.to_string()) {
+                eprintln!("{output}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{upgraded}"),
+    }
+    ExitCode::SUCCESS
+}
+
+// Only read when built with the `arrow` feature
+#[cfg_attr(not(feature = "arrow"), allow(dead_code))]
+struct ArrowArgs<'a> {
+    file: &'a str,
+    block: Option<&'a str>,
+    /// A tag of the loop, or its category; `None` for all loops
+    table: Option<&'a str>,
+    output: &'a str,
+}
+
+impl<'a> ArrowArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut file = None;
+        let mut block = None;
+        let mut table = None;
+        let mut all_loops = false;
+        let mut output = None;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--block" => block = Some(*args.next()?),
+                "--loop" => table = Some(*args.next()?),
+                "--all-loops" => all_loops = true,
+                "-o" | "--output" => output = Some(*args.next()?),
+                _ if file.is_none() && !arg.starts_with('-') => file = Some(arg),
+                _ => return None,
+            }
+        }
+        // Exactly one of --loop and --all-loops
+        if table.is_some() == all_loops {
+            return None;
+        }
+        Some(ArrowArgs {
+            file: file?,
+            block,
+            table,
+            output: output?,
+        })
+    }
+}
+
+#[cfg(feature = "arrow")]
+fn arrow(args: ArrowArgs) -> ExitCode {
+    use cif_parser::arrow::ArrowOptions;
+    use cif_parser::category::category_of;
+    use std::path::Path;
+
+    let doc = match Document::from_file(args.file) {
+        Ok(doc) => doc,
+        Err(e) => {
+            eprintln!("{}: {e}", args.file);
+            return ExitCode::FAILURE;
+        }
+    };
+    let blocks: Vec<&cif_parser::Block> = match (args.block, args.table) {
+        (Some(name), _) => doc.get_block(name).into_iter().collect(),
+        (None, Some(_)) => doc.main_block().into_iter().collect(),
+        (None, None) => doc.blocks.iter().collect(),
+    };
+    if blocks.is_empty() {
+        eprintln!(
+            "{}: no block {}",
+            args.file,
+            args.block.unwrap_or("holding data")
+        );
+        return ExitCode::FAILURE;
+    }
+    let options = |block: &cif_parser::Block| {
+        let options = ArrowOptions::new().block(&block.name);
+        match &doc.source {
+            Some(source) => options.source(source),
+            None => options,
+        }
+    };
+
+    let written = match args.table {
+        Some(table) => {
+            let block = blocks[0];
+            let wanted = table.to_lowercase();
+            let found = block.loops.iter().find(|loop_| {
+                loop_.tags.iter().any(|tag| tag.eq_ignore_ascii_case(table))
+                    || loop_
+                        .tags
+                        .first()
+                        .is_some_and(|tag| category_of(tag) == wanted)
+            });
+            let Some(loop_) = found else {
+                eprintln!("{}: no loop {table} in data_{}", args.file, block.name);
+                return ExitCode::FAILURE;
+            };
+            loop_
+                .write_arrow_ipc(args.output, &options(block))
+                .map(|()| vec![args.output.to_string()])
+        }
+        None => write_all_loops(&blocks, Path::new(args.output), options),
+    };
+    match written {
+        Ok(paths) => {
+            for path in paths {
+                eprintln!("wrote {path}");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}: {e}", args.output);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Write every loop of `blocks` into `dir`, returning the paths written
+#[cfg(feature = "arrow")]
+fn write_all_loops(
+    blocks: &[&cif_parser::Block],
+    dir: &std::path::Path,
+    options: impl Fn(&cif_parser::Block) -> cif_parser::arrow::ArrowOptions,
+) -> Result<Vec<String>, cif_parser::CifError> {
+    use cif_parser::category::category_of;
+
+    fs::create_dir_all(dir)?;
+    let mut names: Vec<String> = Vec::new();
+    for block in blocks {
+        for loop_ in &block.loops {
+            let category = loop_
+                .tags
+                .first()
+                .map(|tag| category_of(tag))
+                .unwrap_or_default();
+            // Block names may hold any printing character
+            let block_name = block.name.replace(['/', '\\'], "_");
+            let stem = format!("{block_name}.{}", category.trim_start_matches('_'));
+            let mut name = format!("{stem}.arrow");
+            let mut n = 1;
+            while names.contains(&name) {
+                n += 1;
+                name = format!("{stem}.{n}.arrow");
+            }
+            loop_.write_arrow_ipc(dir.join(&name), &options(block))?;
+            names.push(name);
+        }
+    }
+    Ok(names
+        .iter()
+        .map(|name| dir.join(name).display().to_string())
+        .collect())
+}
+
+#[cfg(not(feature = "arrow"))]
+fn arrow(_: ArrowArgs) -> ExitCode {
+    eprintln!("cif arrow needs the `arrow` feature: cargo run --release --features arrow --bin cif -- arrow ...");
+    ExitCode::from(2)
+}
+
+// Only read when built with the `bench` feature
+#[cfg_attr(not(feature = "bench"), allow(dead_code))]
+struct BenchArgs<'a> {
+    file: &'a str,
+    rounds: usize,
+}
+
+impl<'a> BenchArgs<'a> {
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut file = None;
+        let mut rounds = 5;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--rounds" => rounds = args.next()?.parse().ok().filter(|&n| n > 0)?,
+                _ if file.is_none() && !arg.starts_with('-') => file = Some(arg),
+                _ => return None,
+            }
+        }
+        Some(BenchArgs {
+            file: file?,
+            rounds,
+        })
+    }
+}
+
+#[cfg(feature = "bench")]
+fn bench(args: BenchArgs) -> ExitCode {
+    let content = match fs::read_to_string(args.file) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}: {e}", args.file);
+            return ExitCode::FAILURE;
+        }
+    };
+    match cif_parser::bench::run(&content, args.rounds) {
+        Ok(measurements) => {
+            println!(
+                "{}: {} bytes, best of {} rounds",
+                args.file,
+                content.len(),
+                args.rounds
+            );
+            print!("{}", cif_parser::bench::table(&measurements));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}: {e}", args.file);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(feature = "bench"))]
+fn bench(_: BenchArgs) -> ExitCode {
+    eprintln!("cif bench needs the `bench` feature: cargo run --release --features bench --bin cif -- bench <file>");
+    ExitCode::from(2)
+}
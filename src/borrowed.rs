@@ -0,0 +1,428 @@
+//! Parsing without copying: a document whose text borrows from the input.
+//!
+//! [`CifDocument::parse`](crate::CifDocument::parse) gives every tag, block
+//! name, and text value its own `String`. For a 200 MB mmCIF file, that
+//! means millions of small allocations holding a second copy of the file.
+//! [`CifDocumentRef::parse`] instead keeps `&str` slices of the input for
+//! names and tags, and [`Cow`] for text values, which is borrowed except for
+//! text fields whose CR line terminators are rewritten as LF. Numbers are
+//! parsed as they are for [`CifValue`].
+//!
+//! The input is read from the [`lexer`](crate::lexer)'s tokens, as
+//! [`CifReader`](crate::reader::CifReader) reads it, not by the grammar;
+//! the document and errors are still those of the grammar's default
+//! parse. It takes no [`ParseOptions`](crate::ParseOptions): parsing is
+//! strict, and a repeated tag keeps its last value. Convert to an owned
+//! [`CifDocument`] with [`into_owned`](CifDocumentRef::into_owned) for
+//! editing, writing, or anything else that needs one.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{CifDocumentRef, CifValueRef};
+//! use std::borrow::Cow;
+//!
+//! let cif = "data_1abc\n_entry.id 1ABC\nloop_\n_atom_site.id\n_atom_site.label_atom_id\n\
+//!            1 N\n2 CA\n";
+//! let doc = CifDocumentRef::parse(cif).unwrap();
+//! let block = &doc.blocks[0];
+//! assert_eq!(block.name, "1abc");
+//!
+//! let atoms = block.find_loop("_atom_site.label_atom_id").unwrap();
+//! let name = atoms.get_by_tag(1, "_atom_site.label_atom_id").unwrap();
+//! assert_eq!(name.as_str(), Some("CA"));
+//! assert!(matches!(name, CifValueRef::Text(Cow::Borrowed(_))));
+//!
+//! let owned = doc.into_owned();
+//! assert_eq!(owned.blocks[0].loops[0].len(), 2);
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::lexer::TokenKind;
+use crate::parser::document::{detect_version, MAX_NESTING_DEPTH};
+use crate::parser::statements::{compound, compound_len, Sink, Statements, Tokens};
+use crate::parser::value::token_value_ref;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A value borrowing its text from the input; the counterpart of
+/// [`CifValue`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CifValueRef<'a> {
+    /// Text, borrowed unless its line terminators were rewritten
+    Text(Cow<'a, str>),
+    /// A number
+    Numeric(f64),
+    /// A number with its standard uncertainty, such as `1.234(5)`
+    NumericSu {
+        /// The number itself
+        value: f64,
+        /// Its standard uncertainty
+        su: f64,
+    },
+    /// `?`
+    Unknown,
+    /// `.`
+    NotApplicable,
+    /// A CIF 2.0 list
+    List(Vec<CifValueRef<'a>>),
+    /// A CIF 2.0 table
+    Table(HashMap<Cow<'a, str>, CifValueRef<'a>>),
+}
+
+impl<'a> CifValueRef<'a> {
+    /// The text, if this is text
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CifValueRef::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The number, if this is a number, without any su
+    pub fn as_numeric(&self) -> Option<f64> {
+        match self {
+            CifValueRef::Numeric(n) | CifValueRef::NumericSu { value: n, .. } => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The value with its text copied
+    pub fn into_owned(self) -> CifValue {
+        match self {
+            CifValueRef::Text(text) => CifValue::Text(text.into_owned()),
+            CifValueRef::Numeric(n) => CifValue::Numeric(n),
+            CifValueRef::NumericSu { value, su } => CifValue::NumericSu { value, su },
+            CifValueRef::Unknown => CifValue::Unknown,
+            CifValueRef::NotApplicable => CifValue::NotApplicable,
+            CifValueRef::List(values) => {
+                CifValue::List(values.into_iter().map(Self::into_owned).collect())
+            }
+            CifValueRef::Table(entries) => CifValue::Table(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<CifValueRef<'a>> for CifValue {
+    fn from(value: CifValueRef<'a>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// A loop borrowing from the input; the counterpart of [`CifLoop`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CifLoopRef<'a> {
+    pub tags: Vec<&'a str>,
+    /// One row per loop packet, each with one value per tag
+    pub values: Vec<Vec<CifValueRef<'a>>>,
+}
+
+impl<'a> CifLoopRef<'a> {
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the loop has no rows
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The value at a row and column
+    pub fn get(&self, row: usize, col: usize) -> Option<&CifValueRef<'a>> {
+        self.values.get(row)?.get(col)
+    }
+
+    /// The value at a row, in the column of `tag`
+    pub fn get_by_tag(&self, row: usize, tag: &str) -> Option<&CifValueRef<'a>> {
+        let col = self.tags.iter().position(|t| *t == tag)?;
+        self.get(row, col)
+    }
+
+    /// The loop with its text copied
+    pub fn into_owned(self) -> CifLoop {
+        let mut loop_ = CifLoop::new(self.tags.into_iter().map(String::from).collect());
+        loop_.values = self
+            .values
+            .into_iter()
+            .map(|row| row.into_iter().map(CifValueRef::into_owned).collect())
+            .collect();
+        loop_
+    }
+}
+
+/// A save frame borrowing from the input; the counterpart of [`CifFrame`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CifFrameRef<'a> {
+    /// Name without `save_`
+    pub name: &'a str,
+    pub items: IndexMap<&'a str, CifValueRef<'a>>,
+    pub loops: Vec<CifLoopRef<'a>>,
+}
+
+impl<'a> CifFrameRef<'a> {
+    /// The value of a data item
+    pub fn get_item(&self, tag: &str) -> Option<&CifValueRef<'a>> {
+        self.items.get(tag)
+    }
+
+    /// The frame with its text copied
+    pub fn into_owned(self) -> CifFrame {
+        let mut frame = CifFrame::new(self.name.to_string());
+        frame.items = owned_items(self.items);
+        frame.loops = self.loops.into_iter().map(CifLoopRef::into_owned).collect();
+        frame
+    }
+}
+
+/// A data block borrowing from the input; the counterpart of [`CifBlock`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CifBlockRef<'a> {
+    /// Name without `data_`, empty for `global_`
+    pub name: &'a str,
+    pub items: IndexMap<&'a str, CifValueRef<'a>>,
+    pub loops: Vec<CifLoopRef<'a>>,
+    pub frames: Vec<CifFrameRef<'a>>,
+}
+
+impl<'a> CifBlockRef<'a> {
+    /// The value of a data item
+    pub fn get_item(&self, tag: &str) -> Option<&CifValueRef<'a>> {
+        self.items.get(tag)
+    }
+
+    /// The first loop with a column for `tag`
+    pub fn find_loop(&self, tag: &str) -> Option<&CifLoopRef<'a>> {
+        self.loops.iter().find(|loop_| loop_.tags.contains(&tag))
+    }
+
+    /// The save frame with this name
+    pub fn get_frame(&self, name: &str) -> Option<&CifFrameRef<'a>> {
+        self.frames.iter().find(|frame| frame.name == name)
+    }
+
+    /// The block with its text copied
+    pub fn into_owned(self) -> CifBlock {
+        let mut block = CifBlock::new(self.name.to_string());
+        block.items = owned_items(self.items);
+        block.loops = self.loops.into_iter().map(CifLoopRef::into_owned).collect();
+        block.frames = self
+            .frames
+            .into_iter()
+            .map(CifFrameRef::into_owned)
+            .collect();
+        block
+    }
+}
+
+/// A document borrowing from the input; the counterpart of [`CifDocument`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CifDocumentRef<'a> {
+    pub version: CifVersion,
+    pub blocks: Vec<CifBlockRef<'a>>,
+}
+
+impl<'a> CifDocumentRef<'a> {
+    /// Parse CIF text, borrowing from it.
+    ///
+    /// The version comes from a `#\#CIF_2.0` magic comment on the first
+    /// line. The document is the one [`CifDocument::parse`] gives, and so
+    /// are the errors: input that does not read is parsed again with the
+    /// grammar to report them.
+    pub fn parse(input: &'a str) -> Result<Self, CifError> {
+        let version = detect_version(input);
+        let mut statements = Statements::new(Builder::default(), version);
+        if let Err(error) = read(input, version, &mut statements) {
+            return Err(CifDocument::parse(input)
+                .err()
+                .unwrap_or_else(|| error.with_source(input)));
+        }
+        Ok(CifDocumentRef {
+            version,
+            blocks: statements.sink.blocks,
+        })
+    }
+
+    /// The first data block
+    pub fn first_block(&self) -> Option<&CifBlockRef<'a>> {
+        self.blocks.first()
+    }
+
+    /// The block with this name
+    pub fn get_block(&self, name: &str) -> Option<&CifBlockRef<'a>> {
+        self.blocks.iter().find(|block| block.name == name)
+    }
+
+    /// The document with its text copied
+    pub fn into_owned(self) -> CifDocument {
+        let mut doc = CifDocument::new();
+        doc.version = self.version;
+        doc.blocks = self
+            .blocks
+            .into_iter()
+            .map(CifBlockRef::into_owned)
+            .collect();
+        doc
+    }
+}
+
+impl<'a> From<CifDocumentRef<'a>> for CifDocument {
+    fn from(doc: CifDocumentRef<'a>) -> Self {
+        doc.into_owned()
+    }
+}
+
+fn owned_items(items: IndexMap<&str, CifValueRef<'_>>) -> IndexMap<String, CifValue> {
+    items
+        .into_iter()
+        .map(|(tag, value)| (tag.to_string(), value.into_owned()))
+        .collect()
+}
+
+/// Feed the tokens of `input` to `statements`
+fn read<'a>(
+    input: &'a str,
+    version: CifVersion,
+    statements: &mut Statements<Builder<'a>>,
+) -> Result<(), CifError> {
+    let mut tokens = Tokens::new(input, 1);
+    let mut end = (1, 1);
+    while let Some(token) = tokens.next().transpose()? {
+        let at = (token.line, token.col);
+        end = at;
+        match token.kind {
+            TokenKind::QuotedString | TokenKind::TextField | TokenKind::UnquotedValue => {
+                statements.value(token.text, at, || {
+                    token_value_ref(token.kind, token.text, version)
+                        .expect("a value token")
+                        .map_err(|error| error.at_location(at.0, at.1))
+                })?;
+            }
+            TokenKind::Delimiter if matches!(token.text, "[" | "{") => {
+                // The tokens up to the matching close, which end the value
+                let mut inner = Vec::new();
+                let mut depth = 1;
+                while depth > 0 {
+                    let Some(next) = tokens.next().transpose()? else {
+                        return Err(CifError::Unterminated {
+                            construct: "list or table".to_string(),
+                            location: Some(at),
+                            span: None,
+                        });
+                    };
+                    match next.text {
+                        _ if next.kind != TokenKind::Delimiter => {}
+                        "[" | "{" => depth += 1,
+                        "]" | "}" => depth -= 1,
+                        _ => {}
+                    }
+                    if depth > MAX_NESTING_DEPTH {
+                        return Err(CifError::NestingTooDeep {
+                            max_depth: MAX_NESTING_DEPTH,
+                            location: Some((next.line, next.col)),
+                            span: None,
+                        });
+                    }
+                    inner.push(next);
+                }
+                compound_len(token.text, &inner, 1)?;
+                let close = inner.pop().expect("the closing delimiter");
+                end = (close.line, close.col);
+                statements.value(token.text, at, || {
+                    compound(&token, &inner, close.byte_range.end, input, version, 1)
+                })?;
+            }
+            _ => statements.token(&token, at, |name| name)?,
+        }
+    }
+    statements.finish(end)
+}
+
+/// The blocks [`Statements`] finds, with their text borrowed
+#[derive(Default)]
+struct Builder<'a> {
+    blocks: Vec<CifBlockRef<'a>>,
+    /// The open save frame
+    frame: Option<CifFrameRef<'a>>,
+}
+
+impl<'a> Builder<'a> {
+    /// The items and loops of the open save frame, or else of the last block
+    fn container(
+        &mut self,
+    ) -> (
+        &mut IndexMap<&'a str, CifValueRef<'a>>,
+        &mut Vec<CifLoopRef<'a>>,
+    ) {
+        match &mut self.frame {
+            Some(frame) => (&mut frame.items, &mut frame.loops),
+            None => {
+                let block = self.blocks.last_mut().expect("items are inside a block");
+                (&mut block.items, &mut block.loops)
+            }
+        }
+    }
+}
+
+impl<'a> Sink for Builder<'a> {
+    type Name = &'a str;
+    type Value = CifValueRef<'a>;
+
+    fn block_start(&mut self, name: &'a str) {
+        self.blocks.push(CifBlockRef {
+            name,
+            items: IndexMap::new(),
+            loops: Vec::new(),
+            frames: Vec::new(),
+        });
+    }
+
+    fn block_end(&mut self) {}
+
+    fn frame_start(&mut self, name: &'a str) {
+        self.frame = Some(CifFrameRef {
+            name,
+            items: IndexMap::new(),
+            loops: Vec::new(),
+        });
+    }
+
+    fn frame_end(&mut self) {
+        let frame = self.frame.take().expect("started by frame_start");
+        let block = self.blocks.last_mut().expect("frames are inside a block");
+        block.frames.push(frame);
+    }
+
+    fn item(&mut self, tag: &'a str, value: CifValueRef<'a>) {
+        // As in the parser, a repeated tag keeps its last value, in the
+        // place of the last
+        let items = self.container().0;
+        items.shift_remove(tag);
+        items.insert(tag, value);
+    }
+
+    fn loop_start(&mut self, tags: Vec<&'a str>) {
+        self.container().1.push(CifLoopRef {
+            tags,
+            values: Vec::new(),
+        });
+    }
+
+    fn loop_row(&mut self, row: Vec<CifValueRef<'a>>) {
+        let loops = self.container().1;
+        loops
+            .last_mut()
+            .expect("started by loop_start")
+            .values
+            .push(row);
+    }
+
+    fn loop_end(&mut self) {}
+}
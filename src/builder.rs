@@ -1,6 +1,7 @@
 //! Builder utilities for constructing CIF blocks with proper state management.
 
 use crate::ast::{CifBlock, CifFrame, CifLoop, CifValue};
+use indexmap::IndexMap;
 
 /// Internal helper for building CIF blocks while managing pending loop state.
 ///
@@ -49,7 +50,7 @@ use crate::ast::{CifBlock, CifFrame, CifLoop, CifValue};
 /// builder.start_loop(incomplete_loop);
 ///
 /// // Add a data item - this automatically finalizes the pending loop
-/// builder.add_item("_other_item".to_string(), value);
+/// builder.add_item(|items, _loops| items.insert("_other_item".to_string(), value));
 ///
 /// // The pending loop has been safely added to the block
 /// let block = builder.finish();
@@ -90,10 +91,14 @@ impl BlockBuilder {
         &mut self.block
     }
 
-    /// Finalize any pending loop and add a data item
-    pub(crate) fn add_item(&mut self, tag: String, value: CifValue) {
+    /// Finalize any pending loop and add a data item with `insert`, which is
+    /// given the block's items and loops (a repeated tag may become a loop)
+    pub(crate) fn add_item<T>(
+        &mut self,
+        insert: impl FnOnce(&mut IndexMap<String, CifValue>, &mut Vec<CifLoop>) -> T,
+    ) -> T {
         self.finalize_pending_loop();
-        self.block.items.insert(tag, value);
+        insert(&mut self.block.items, &mut self.block.loops)
     }
 
     /// Finalize any pending loop and start a new one
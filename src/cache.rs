@@ -0,0 +1,654 @@
+//! Persistent parse cache keyed by content hash.
+//!
+//! Workflows that open the same files again and again, such as a notebook
+//! re-run over a corpus, spend most of their time parsing.
+//! [`CifDocument::from_file_cached`] keeps each parsed document on disk,
+//! keyed by the SHA-256 of the file's content and the [`ParseOptions`], so
+//! an unchanged file is decoded instead of parsed. An edited file has a new
+//! hash and is parsed again.
+//!
+//! Entries use a small binary format that starts with a version number and
+//! ends with a checksum. An entry that cannot be read, whether written by
+//! another version of this crate, truncated, or corrupted, is ignored and
+//! rewritten. The cache never makes a load fail: if the directory cannot be
+//! written, the document is parsed as usual. Once the entries take more than
+//! [`CacheOptions::max_bytes`], the least recently used are removed.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::cache::{self, CacheOptions, CacheStatus};
+//! use cif_parser::ParseOptions;
+//!
+//! let dir = std::env::temp_dir().join(format!("cif-cache-doc-{}", std::process::id()));
+//! std::fs::create_dir_all(&dir).unwrap();
+//! let path = dir.join("si.cif");
+//! std::fs::write(&path, "data_si\n_cell_length_a 5.431\n").unwrap();
+//!
+//! let options = CacheOptions::new(dir.join("cache"));
+//! let (_, status) = cache::load(&path, &ParseOptions::default(), &options).unwrap();
+//! assert_eq!(status, CacheStatus::Miss);
+//!
+//! let (doc, status) = cache::load(&path, &ParseOptions::default(), &options).unwrap();
+//! assert_eq!(status, CacheStatus::Hit);
+//! assert_eq!(doc.blocks[0].name, "si");
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion, DocumentSource};
+use crate::corpus::long_path;
+use crate::error::{CifError, CifWarning};
+use crate::parser::{DecimalComma, ParseOptions};
+use indexmap::IndexMap;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Version of the entry format; bump whenever the encoding changes
+const CACHE_FORMAT_VERSION: u32 = 6;
+
+/// First bytes of every entry
+const MAGIC: &[u8; 8] = b"CIFPCACH";
+
+/// File extension of entries; other files in the directory are left alone
+const EXTENSION: &str = "cifc";
+
+/// Deepest list or table nesting an entry may hold
+const MAX_DEPTH: usize = 256;
+
+/// Default size limit of a cache directory (1 GiB)
+const DEFAULT_MAX_BYTES: u64 = 1 << 30;
+
+/// Where and how much to cache (see [`CifDocument::from_file_cached`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheOptions {
+    /// Directory holding the entries; created when first written
+    pub dir: PathBuf,
+    /// Total size of the entries above which the least recently used are
+    /// removed
+    pub max_bytes: u64,
+}
+
+impl CacheOptions {
+    /// Cache in `dir`, holding up to 1 GiB
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CacheOptions {
+            dir: dir.into(),
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Set the total size of the entries above which the least recently
+    /// used are removed
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+/// How a cached load was served (see [`load`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// Decoded from the cache without parsing
+    Hit,
+    /// Not in the cache; parsed and stored
+    Miss,
+    /// In the cache but unreadable; parsed and stored again
+    Invalid,
+}
+
+impl CifDocument {
+    /// Parse a CIF file, reusing the result of an earlier parse of the same
+    /// content from the cache in [`CacheOptions::dir`]
+    ///
+    /// The document is the same as [`from_file`](Self::from_file) gives,
+    /// including its [`source`](Self::source). See the [`cache`](crate::cache)
+    /// module for how entries are stored and evicted.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cif_parser::{CacheOptions, Document};
+    ///
+    /// let cache = CacheOptions::new("/tmp/cif-cache");
+    /// let doc = Document::from_file_cached("structure.cif", &cache).unwrap();
+    /// ```
+    pub fn from_file_cached<P: AsRef<Path>>(
+        path: P,
+        cache: &CacheOptions,
+    ) -> Result<Self, CifError> {
+        Self::from_file_cached_with_options(path, &ParseOptions::default(), cache)
+    }
+
+    /// Parse a CIF file with explicit [`ParseOptions`], reusing a cached
+    /// result
+    ///
+    /// Documents parsed with different options are cached separately.
+    pub fn from_file_cached_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+        cache: &CacheOptions,
+    ) -> Result<Self, CifError> {
+        load(path, options, cache).map(|(doc, _)| doc)
+    }
+}
+
+/// Load a CIF file through the cache, reporting whether it was parsed.
+///
+/// This is what [`CifDocument::from_file_cached_with_options`] calls. Errors
+/// come only from reading or parsing the file; failed parses are not cached.
+pub fn load<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+    cache: &CacheOptions,
+) -> Result<(CifDocument, CacheStatus), CifError> {
+    let path = path.as_ref();
+    let content = fs::read(long_path(path)).map_err(|e| CifError::from(e).with_path(path))?;
+    let source = DocumentSource::new(&content).with_path(path);
+    let entry = cache.dir.join(format!(
+        "{}.{EXTENSION}",
+        entry_key(&source.sha256, options)
+    ));
+
+    let status = match fs::read(&entry) {
+        Ok(bytes) => match decode(&bytes) {
+            Some(mut doc) => {
+                touch(&entry);
+                doc.set_source(source);
+                return Ok((doc, CacheStatus::Hit));
+            }
+            None => CacheStatus::Invalid,
+        },
+        Err(_) => CacheStatus::Miss,
+    };
+
+    let mut doc = CifDocument::from_file_content(&content, path, options)?;
+    // Best effort: a cache that cannot be written only costs the next parse
+    let _ = store(cache, &entry, &encode(&doc));
+    doc.set_source(source);
+    Ok((doc, status))
+}
+
+/// File name stem of the entry for content with this hash, parsed with
+/// these options
+fn entry_key(content_sha256: &str, options: &ParseOptions) -> String {
+    // Threads change how a document is parsed, not what it is
+    let options = ParseOptions {
+        threads: 0,
+        ..options.clone()
+    };
+    let digest = Sha256::digest(format!("{content_sha256}\n{options:?}"));
+    let mut key = String::with_capacity(64);
+    for byte in digest {
+        let _ = write!(key, "{byte:02x}");
+    }
+    key
+}
+
+/// Mark an entry as just used, for eviction
+fn touch(entry: &Path) {
+    if let Ok(file) = File::options().write(true).open(entry) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Write an entry atomically, then evict old entries to fit the size limit
+fn store(cache: &CacheOptions, entry: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if bytes.len() as u64 > cache.max_bytes {
+        return Ok(());
+    }
+    fs::create_dir_all(&cache.dir)?;
+    // Writing to a temporary name first means readers never see half an entry
+    let temp = entry.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temp, bytes)?;
+    if let Err(e) = fs::rename(&temp, entry) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    evict(cache, entry)
+}
+
+/// Remove the least recently used entries until the total fits, keeping
+/// `keep`
+fn evict(cache: &CacheOptions, keep: &Path) -> std::io::Result<()> {
+    let mut entries = Vec::new();
+    let mut total = 0u64;
+    for dir_entry in fs::read_dir(&cache.dir)? {
+        let path = dir_entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        total += metadata.len();
+        let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        entries.push((used, metadata.len(), path));
+    }
+    entries.sort();
+
+    for (_, len, path) in entries {
+        if total <= cache.max_bytes {
+            break;
+        }
+        if path != keep && fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+    Ok(())
+}
+
+// ===== Encoding =====
+
+fn encode(doc: &CifDocument) -> Vec<u8> {
+    let mut out = Encoder(Vec::new());
+    out.0.extend_from_slice(MAGIC);
+    out.0.extend_from_slice(&CACHE_FORMAT_VERSION.to_le_bytes());
+    out.str(env!("CARGO_PKG_VERSION"));
+
+    out.u8(match doc.version {
+        CifVersion::V1_1 => 0,
+        CifVersion::V2_0 => 1,
+    });
+    out.u8(doc.partial as u8);
+    out.u8(doc.bare_frames as u8);
+    out.len(doc.warnings.len());
+    for warning in &doc.warnings {
+        out.str(&warning.message);
+        match warning.location {
+            Some((line, col)) => {
+                out.u8(1);
+                out.len(line);
+                out.len(col);
+            }
+            None => out.u8(0),
+        }
+    }
+    for edge in [&doc.preamble, &doc.trailer] {
+        match edge {
+            Some(text) => {
+                out.u8(1);
+                out.str(text);
+            }
+            None => out.u8(0),
+        }
+    }
+    out.len(doc.decimal_commas.len());
+    for record in &doc.decimal_commas {
+        out.str(&record.block);
+        match &record.frame {
+            Some(frame) => {
+                out.u8(1);
+                out.str(frame);
+            }
+            None => out.u8(0),
+        }
+        out.str(&record.tag);
+        match record.row {
+            Some(row) => {
+                out.u8(1);
+                out.len(row);
+            }
+            None => out.u8(0),
+        }
+        out.str(&record.original);
+    }
+    out.len(doc.blocks.len());
+    for block in &doc.blocks {
+        out.str(&block.name);
+        out.items(&block.items);
+        out.loops(&block.loops);
+        out.len(block.frames.len());
+        for frame in &block.frames {
+            out.str(&frame.name);
+            out.items(&frame.items);
+            out.loops(&frame.loops);
+        }
+    }
+
+    let checksum = Sha256::digest(&out.0);
+    out.0.extend_from_slice(&checksum);
+    out.0
+}
+
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, byte: u8) {
+        self.0.push(byte);
+    }
+
+    fn len(&mut self, n: usize) {
+        self.0.extend_from_slice(&(n as u64).to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    fn items(&mut self, items: &IndexMap<String, CifValue>) {
+        self.len(items.len());
+        for (tag, value) in items {
+            self.str(tag);
+            self.value(value);
+        }
+    }
+
+    fn loops(&mut self, loops: &[CifLoop]) {
+        self.len(loops.len());
+        for loop_ in loops {
+            self.len(loop_.tags.len());
+            for tag in &loop_.tags {
+                self.str(tag);
+            }
+            self.len(loop_.values.len());
+            for row in &loop_.values {
+                self.len(row.len());
+                for value in row {
+                    self.value(value);
+                }
+            }
+        }
+    }
+
+    fn value(&mut self, value: &CifValue) {
+        match value {
+            CifValue::Text(s) => {
+                self.u8(0);
+                self.str(s);
+            }
+            CifValue::Numeric(n) => {
+                self.u8(1);
+                self.0.extend_from_slice(&n.to_bits().to_le_bytes());
+            }
+            CifValue::NumericSu { value, su } => {
+                self.u8(7);
+                self.0.extend_from_slice(&value.to_bits().to_le_bytes());
+                self.0.extend_from_slice(&su.to_bits().to_le_bytes());
+            }
+            CifValue::Unknown => self.u8(2),
+            CifValue::NotApplicable => self.u8(3),
+            CifValue::List(values) => {
+                self.u8(4);
+                self.len(values.len());
+                for value in values {
+                    self.value(value);
+                }
+            }
+            CifValue::LargeText(s) => {
+                self.u8(6);
+                self.str(s);
+            }
+            CifValue::Table(table) => {
+                self.u8(5);
+                self.len(table.len());
+                // Sorted, so that the same document always encodes the same
+                for (key, value) in value.table_entries().unwrap_or_default() {
+                    self.str(key);
+                    self.value(value);
+                }
+            }
+        }
+    }
+}
+
+// ===== Decoding =====
+
+/// Decode an entry, or `None` if it is from another version or damaged
+fn decode(bytes: &[u8]) -> Option<CifDocument> {
+    let (body, checksum) = bytes.split_at_checked(bytes.len().checked_sub(32)?)?;
+    if Sha256::digest(body).as_slice() != checksum {
+        return None;
+    }
+    let mut input = Decoder { bytes: body };
+    if input.take(MAGIC.len())? != MAGIC
+        || input.take(4)? != CACHE_FORMAT_VERSION.to_le_bytes()
+        || input.str()? != env!("CARGO_PKG_VERSION")
+    {
+        return None;
+    }
+
+    let mut doc = CifDocument::new();
+    doc.version = match input.u8()? {
+        0 => CifVersion::V1_1,
+        1 => CifVersion::V2_0,
+        _ => return None,
+    };
+    doc.partial = input.bool()?;
+    doc.bare_frames = input.bool()?;
+    for _ in 0..input.len()? {
+        let message = input.str()?;
+        let mut warning = CifWarning::new(message);
+        if input.bool()? {
+            warning.location = Some((input.len()?, input.len()?));
+        }
+        doc.warnings.push(warning);
+    }
+    for edge in [&mut doc.preamble, &mut doc.trailer] {
+        if input.bool()? {
+            *edge = Some(input.str()?);
+        }
+    }
+    for _ in 0..input.len()? {
+        let block = input.str()?;
+        let frame = if input.bool()? {
+            Some(input.str()?)
+        } else {
+            None
+        };
+        let tag = input.str()?;
+        let row = if input.bool()? {
+            Some(input.len()?)
+        } else {
+            None
+        };
+        doc.decimal_commas.push(DecimalComma {
+            block,
+            frame,
+            tag,
+            row,
+            original: input.str()?,
+        });
+    }
+    for _ in 0..input.len()? {
+        let mut block = CifBlock::new(input.str()?);
+        block.items = input.items()?;
+        block.loops = input.loops()?;
+        for _ in 0..input.len()? {
+            let mut frame = CifFrame::new(input.str()?);
+            frame.items = input.items()?;
+            frame.loops = input.loops()?;
+            block.frames.push(frame);
+        }
+        doc.blocks.push(block);
+    }
+    input.bytes.is_empty().then_some(doc)
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        let (head, rest) = self.bytes.split_at_checked(n)?;
+        self.bytes = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// A count or position; counts larger than the remaining input are
+    /// rejected, since every element takes at least one byte
+    fn len(&mut self) -> Option<usize> {
+        let n = usize::try_from(self.u64()?).ok()?;
+        (n <= self.bytes.len()).then_some(n)
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let n = self.len()?;
+        String::from_utf8(self.take(n)?.to_vec()).ok()
+    }
+
+    fn items(&mut self) -> Option<IndexMap<String, CifValue>> {
+        let n = self.len()?;
+        let mut items = IndexMap::with_capacity(n);
+        for _ in 0..n {
+            let tag = self.str()?;
+            items.insert(tag, self.value(0)?);
+        }
+        Some(items)
+    }
+
+    fn loops(&mut self) -> Option<Vec<CifLoop>> {
+        let n = self.len()?;
+        let mut loops = Vec::with_capacity(n);
+        for _ in 0..n {
+            let tags = (0..self.len()?)
+                .map(|_| self.str())
+                .collect::<Option<Vec<_>>>()?;
+            let mut loop_ = CifLoop::new(tags);
+            for _ in 0..self.len()? {
+                let row = (0..self.len()?)
+                    .map(|_| self.value(0))
+                    .collect::<Option<Vec<_>>>()?;
+                loop_.values.push(row);
+            }
+            loops.push(loop_);
+        }
+        Some(loops)
+    }
+
+    fn value(&mut self, depth: usize) -> Option<CifValue> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        Some(match self.u8()? {
+            0 => CifValue::Text(self.str()?),
+            1 => CifValue::Numeric(f64::from_bits(self.u64()?)),
+            2 => CifValue::Unknown,
+            3 => CifValue::NotApplicable,
+            4 => CifValue::List(
+                (0..self.len()?)
+                    .map(|_| self.value(depth + 1))
+                    .collect::<Option<_>>()?,
+            ),
+            5 => {
+                let n = self.len()?;
+                let mut table = HashMap::with_capacity(n);
+                for _ in 0..n {
+                    let key = self.str()?;
+                    table.insert(key, self.value(depth + 1)?);
+                }
+                CifValue::Table(table)
+            }
+            6 => CifValue::LargeText(self.str()?.into()),
+            7 => CifValue::NumericSu {
+                value: f64::from_bits(self.u64()?),
+                su: f64::from_bits(self.u64()?),
+            },
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "#\\#CIF_2.0\ndata_a\n_t 'x y'\n_n 1.5(2)\n_u ?\n_na .\n\
+                          _list [1 [2 'b' ] {'k':3}]\nloop_\n_c\n_d\n1 a\n2 b\n\
+                          save_f\n_s z\nsave_\n";
+
+    #[test]
+    fn test_round_trip() {
+        let doc = CifDocument::parse(SAMPLE).unwrap();
+        let decoded = decode(&encode(&doc)).unwrap();
+        assert_eq!(decoded.version, CifVersion::V2_0);
+        assert!(decoded.semantically_equal(&doc));
+        let tags = |d: &CifDocument| d.blocks[0].items.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(tags(&decoded), tags(&doc));
+    }
+
+    #[test]
+    fn test_round_trip_warnings_partial_and_trailer() {
+        let mut doc = CifDocument::new();
+        doc.partial = true;
+        doc.warnings.push(CifWarning::new("one").at_location(3, 4));
+        doc.warnings.push(CifWarning::new("two"));
+        doc.trailer = Some("#END\nCHECKSUM 1\n".to_string());
+        let decoded = decode(&encode(&doc)).unwrap();
+        assert!(decoded.partial);
+        assert_eq!(decoded.warnings, doc.warnings);
+        assert_eq!(decoded.preamble, None);
+        assert_eq!(decoded.trailer, doc.trailer);
+    }
+
+    #[test]
+    fn test_round_trip_decimal_commas() {
+        let cif = "data_a
+_cell_length_a 5,43
+save_f
+loop_
+_atom_site_fract_x
+0,5
+save_
+";
+        let doc = CifDocument::parse_with_options(cif, &ParseOptions::new().lenient(true)).unwrap();
+        assert_eq!(doc.decimal_commas.len(), 2);
+        let decoded = decode(&encode(&doc)).unwrap();
+        assert_eq!(decoded.decimal_commas, doc.decimal_commas);
+    }
+
+    #[test]
+    fn test_round_trip_large_text() {
+        let cif = "data_a\n_map\n;\nQUJDREVGR0g=\n;\n";
+        let options = ParseOptions::new().large_value_threshold(4);
+        let doc = CifDocument::parse_with_options(cif, &options).unwrap();
+        let decoded = decode(&encode(&doc)).unwrap();
+        assert!(decoded.blocks[0].items["_map"].is_large());
+        assert_eq!(decoded.blocks[0].items, doc.blocks[0].items);
+    }
+
+    #[test]
+    fn test_damaged_entries_are_rejected() {
+        let bytes = encode(&CifDocument::parse(SAMPLE).unwrap());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_none());
+        assert!(decode(&[]).is_none());
+
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+        assert!(decode(&flipped).is_none());
+    }
+
+    #[test]
+    fn test_key_depends_on_options() {
+        let lenient = ParseOptions::new().lenient(true);
+        assert_ne!(
+            entry_key("abc", &ParseOptions::default()),
+            entry_key("abc", &lenient)
+        );
+        assert_eq!(entry_key("abc", &lenient), entry_key("abc", &lenient));
+        assert_eq!(
+            entry_key("abc", &lenient),
+            entry_key("abc", &lenient.clone().threads(4))
+        );
+    }
+}
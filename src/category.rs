@@ -0,0 +1,715 @@
+//! Tag categories and repair of loops that mix them.
+//!
+//! Every looped tag belongs to a category, and a loop may only hold tags of
+//! one category. Some legacy files break this, for example by appending the
+//! `_atom_site_aniso_*` columns to the `_atom_site_*` loop.
+//! [`CifLoop::split_by_category`] partitions such a loop into one loop per
+//! category, and [`CifBlock::repair_mixed_loops`] does this in place for a
+//! whole block.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_legacy
+//! loop_
+//! _atom_site_label
+//! _atom_site_fract_x
+//! _atom_site_aniso_U_11
+//! C1 0.1 0.02
+//! C2 0.2 0.03
+//! ";
+//! let mut doc = Document::parse(cif).unwrap();
+//! let block = &mut doc.blocks[0];
+//! let splits = block.repair_mixed_loops();
+//!
+//! assert_eq!(splits[0].categories, ["_atom_site", "_atom_site_aniso"]);
+//! assert_eq!(block.loops.len(), 2);
+//! // The label is copied so the loops can still be joined
+//! assert_eq!(
+//!     block.loops[1].tags,
+//!     ["_atom_site_aniso_label", "_atom_site_aniso_U_11"]
+//! );
+//! ```
+//!
+//! [`CifBlock::category`] reads one category as a table, merging its data
+//! items and loop columns, which is how mmCIF is usually queried:
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "data_x\n_cell.length_a 5.1\nloop_\n_atom_site.id\n_atom_site.Cartn_x\n1 1.5\n2 2.5\n";
+//! let doc = Document::parse(cif).unwrap();
+//! let atoms = doc.blocks[0].category("atom_site").unwrap();
+//!
+//! assert_eq!(atoms.len(), 2);
+//! assert_eq!(atoms.attributes().collect::<Vec<_>>(), ["id", "Cartn_x"]);
+//! assert_eq!(atoms.get(1, "cartn_x").unwrap().as_numeric(), Some(2.5));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifLoop, CifValue};
+use crate::publication::value_text;
+
+/// Core CIF categories whose names contain underscores, so that the longest
+/// one matching a tag can be picked (`_atom_site_aniso_U_11` belongs to
+/// `atom_site_aniso`, not `atom_site`). The names are those of the DDLm core
+/// dictionary, which splits some DDL1 categories (`refine_ls` from `refine`).
+const CORE_CATEGORIES: &[&str] = &[
+    "atom_analytical",
+    "atom_analytical_mass_loss",
+    "atom_analytical_source",
+    "atom_scat_versus_stol",
+    "atom_site",
+    "atom_site_aniso",
+    "atom_sites",
+    "atom_sites_cartn_transform",
+    "atom_sites_fract_transform",
+    "atom_type",
+    "atom_type_scat",
+    "audit",
+    "audit_author",
+    "audit_conform",
+    "audit_contact_author",
+    "audit_link",
+    "audit_support",
+    "cell",
+    "cell_measurement",
+    "cell_measurement_refln",
+    "chemical",
+    "chemical_conn_atom",
+    "chemical_conn_bond",
+    "chemical_formula",
+    "citation",
+    "citation_author",
+    "citation_editor",
+    "computing",
+    "database",
+    "database_code",
+    "diffrn",
+    "diffrn_attenuator",
+    "diffrn_detector",
+    "diffrn_measurement",
+    "diffrn_orient_matrix",
+    "diffrn_orient_refln",
+    "diffrn_radiation",
+    "diffrn_radiation_wavelength",
+    "diffrn_refln",
+    "diffrn_reflns",
+    "diffrn_reflns_class",
+    "diffrn_scale_group",
+    "diffrn_source",
+    "diffrn_standard_refln",
+    "diffrn_standards",
+    "exptl",
+    "exptl_absorpt",
+    "exptl_crystal",
+    "exptl_crystal_face",
+    "geom",
+    "geom_angle",
+    "geom_bond",
+    "geom_contact",
+    "geom_hbond",
+    "geom_torsion",
+    "journal",
+    "journal_coeditor",
+    "journal_date",
+    "journal_index",
+    "journal_techeditor",
+    "publ",
+    "publ_author",
+    "publ_body",
+    "publ_contact_author",
+    "publ_manuscript",
+    "publ_manuscript_incl",
+    "publ_section",
+    "refine",
+    "refine_diff",
+    "refine_ls",
+    "refine_ls_class",
+    "refln",
+    "reflns",
+    "reflns_class",
+    "reflns_scale",
+    "reflns_shell",
+    "space_group",
+    "space_group_symop",
+    "space_group_wyckoff",
+    "symmetry",
+    "symmetry_equiv",
+    "valence_param",
+    "valence_ref",
+];
+
+/// Multi-word categories of the restraints and twinning dictionaries, and
+/// of PETS electron-diffraction zone axes, whose tags core CIF files carry
+/// too
+const EXTENSION_CATEGORIES: &[&str] = &[
+    "diffrn_zone_axis",
+    "restr_angle",
+    "restr_distance",
+    "restr_equal_angle",
+    "restr_equal_angle_class",
+    "restr_equal_distance",
+    "restr_equal_distance_class",
+    "restr_equal_torsion",
+    "restr_equal_torsion_class",
+    "restr_torsion",
+    "twin",
+    "twin_individual",
+    "twin_refln",
+];
+
+/// Core CIF tags whose names do not start with their category
+const IRREGULAR_TAGS: &[(&str, &str)] = &[
+    ("diffrn_measured_fraction_theta_full", "diffrn_reflns"),
+    ("diffrn_measured_fraction_theta_max", "diffrn_reflns"),
+];
+
+/// Works out which category an underscore-style tag belongs to.
+///
+/// For mmCIF-style tags the category is the part before the dot. For core
+/// CIF tags the boundary between category and attribute is not marked, so
+/// the resolver picks the longest known category that prefixes the tag,
+/// from a built-in table of core CIF categories (and a few from the
+/// restraints and twinning dictionaries) plus any registered with
+/// [`register`](Self::register) or read from a dictionary with
+/// [`register_dictionary`](Self::register_dictionary). A tag with no known
+/// category falls back to its first word (`_pd_proc_2theta` → `_pd`), which
+/// keeps the loops of other dictionaries together.
+///
+/// [`category_of`] uses the built-in table alone.
+///
+/// # Examples
+/// ```
+/// use cif_parser::category::CategoryResolver;
+///
+/// let mut resolver = CategoryResolver::new();
+/// assert_eq!(resolver.category_of("_atom_site_aniso_U_11"), "_atom_site_aniso");
+/// assert_eq!(resolver.category_of("_pd_proc_2theta_corrected"), "_pd");
+///
+/// resolver.register("pd_proc");
+/// assert_eq!(resolver.category_of("_pd_proc_2theta_corrected"), "_pd_proc");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CategoryResolver {
+    /// Registered categories, lowercase, without the leading underscore
+    extra: Vec<String>,
+}
+
+impl CategoryResolver {
+    /// A resolver with the built-in categories only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a category, such as `pd_proc` or `_pd_proc`; case is ignored
+    pub fn register(&mut self, category: &str) {
+        let name = category.trim_start_matches('_').to_lowercase();
+        if !name.is_empty() && !self.is_known(&name) {
+            self.extra.push(name);
+        }
+    }
+
+    /// Add every category a DDL dictionary defines, returning how many were
+    /// new.
+    ///
+    /// Categories are read from DDL1 `_category` items, DDLm save frames
+    /// with `_definition.scope Category`, and DDL2 `_category.id` items.
+    pub fn register_dictionary(&mut self, dictionary: &CifDocument) -> usize {
+        let before = self.extra.len();
+        for block in &dictionary.blocks {
+            if let Some(name) = block.get_item("_category").and_then(value_text) {
+                if !name.eq_ignore_ascii_case("category_overview") {
+                    self.register(&name);
+                }
+            }
+            for frame in &block.frames {
+                let scope = frame.get_item("_definition.scope").and_then(value_text);
+                if scope.is_some_and(|scope| scope.eq_ignore_ascii_case("category")) {
+                    if let Some(name) = frame.get_item("_definition.id").and_then(value_text) {
+                        self.register(&name);
+                    }
+                }
+                if let Some(name) = frame.get_item("_category.id").and_then(value_text) {
+                    self.register(&name);
+                }
+            }
+        }
+        self.extra.len() - before
+    }
+
+    /// Every category known: the built-in ones, then those registered
+    pub fn categories(&self) -> impl Iterator<Item = &str> {
+        CORE_CATEGORIES
+            .iter()
+            .chain(EXTENSION_CATEGORIES)
+            .copied()
+            .chain(self.extra.iter().map(String::as_str))
+    }
+
+    /// The category of a tag, lowercased and with its leading underscore.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::category::CategoryResolver;
+    ///
+    /// let resolver = CategoryResolver::new();
+    /// assert_eq!(resolver.category_of("_atom_site.Cartn_x"), "_atom_site");
+    /// assert_eq!(resolver.category_of("_atom_sites_fract_tran_matrix_11"), "_atom_sites");
+    /// assert_eq!(resolver.category_of("_diffrn_radiation_wavelength"), "_diffrn_radiation_wavelength");
+    /// ```
+    pub fn category_of(&self, tag: &str) -> String {
+        let tag = tag.to_lowercase();
+        if let Some((category, _)) = tag.split_once('.') {
+            return category.to_string();
+        }
+        let name = tag.strip_prefix('_').unwrap_or(&tag);
+        if let Some((_, category)) = IRREGULAR_TAGS
+            .iter()
+            .find(|(irregular, _)| *irregular == name)
+        {
+            return format!("_{category}");
+        }
+        let known = self
+            .categories()
+            .filter(|category| {
+                name.strip_prefix(category)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+            })
+            .max_by_key(|category| category.len());
+        match known {
+            Some(category) => format!("_{category}"),
+            None => format!("_{}", name.split('_').next().unwrap_or_default()),
+        }
+    }
+
+    fn is_known(&self, name: &str) -> bool {
+        self.categories().any(|category| category == name)
+    }
+}
+
+/// The category of a tag, lowercased and with its leading underscore, by
+/// the built-in table of a [`CategoryResolver`].
+///
+/// For mmCIF-style tags this is the part before the dot
+/// (`_atom_site.Cartn_x` → `_atom_site`). For core CIF tags it is the
+/// longest known core category that prefixes the tag
+/// (`_atom_site_aniso_U_11` → `_atom_site_aniso`); tags from other
+/// dictionaries fall back to their first word (`_pd_proc_2theta` → `_pd`),
+/// which keeps their loops together.
+///
+/// # Examples
+/// ```
+/// use cif_parser::category::category_of;
+///
+/// assert_eq!(category_of("_atom_site_fract_x"), "_atom_site");
+/// assert_eq!(category_of("_atom_site_aniso_U_11"), "_atom_site_aniso");
+/// assert_eq!(category_of("_atom_sites_fract_tran_matrix_11"), "_atom_sites");
+/// assert_eq!(category_of("_atom_site_anisotrop.U[1][1]"), "_atom_site_anisotrop");
+/// ```
+pub fn category_of(tag: &str) -> String {
+    CategoryResolver::new().category_of(tag)
+}
+
+/// A loop that [`CifBlock::repair_mixed_loops`] split up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopSplit {
+    /// Tags of the original loop
+    pub tags: Vec<String>,
+    /// Categories of the loops it was split into, in order
+    pub categories: Vec<String>,
+    /// Key column copied into each new loop, if the loop had one
+    pub key: Option<String>,
+}
+
+/// The data items and loop columns of one category in a block, read as a
+/// single table.
+///
+/// A category given as data items is a table of one row. Items of a
+/// category that also has loop columns are repeated on every row, and a
+/// loop shorter than the others has no values in the rows it lacks.
+/// Attributes are the parts of the tags after the category and its
+/// separator (`Cartn_x` of `_atom_site.Cartn_x`, `fract_x` of
+/// `_atom_site_fract_x`), matched case-insensitively.
+#[derive(Debug, Clone)]
+pub struct Category<'a> {
+    name: String,
+    columns: Vec<CategoryColumn<'a>>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct CategoryColumn<'a> {
+    tag: &'a str,
+    values: ColumnValues<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum ColumnValues<'a> {
+    Item(&'a CifValue),
+    Loop(&'a CifLoop, usize),
+}
+
+impl<'a> Category<'a> {
+    /// Category name, lowercased and with its leading underscore
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the category has no rows, as for a loop without values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Tags of the columns as spelled in the file: data items in file
+    /// order, then loop columns
+    pub fn tags(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.columns.iter().map(|column| column.tag)
+    }
+
+    /// Attributes of the columns, in the order of [`tags`](Self::tags)
+    pub fn attributes(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.columns
+            .iter()
+            .map(|column| attribute(column.tag, &self.name))
+    }
+
+    /// Whether the category has an attribute
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.column_index(name).is_some()
+    }
+
+    /// The value of an attribute in a row
+    pub fn get(&self, row: usize, name: &str) -> Option<&'a CifValue> {
+        let column = &self.columns[self.column_index(name)?];
+        column.value(row).filter(|_| row < self.len)
+    }
+
+    /// Every value of an attribute, one per row; `None` if the category
+    /// has no such attribute or a loop holding it is short
+    pub fn column(&self, name: &str) -> Option<Vec<&'a CifValue>> {
+        let column = &self.columns[self.column_index(name)?];
+        (0..self.len).map(|row| column.value(row)).collect()
+    }
+
+    /// The attributes and values of a row, skipping values a short loop
+    /// does not have
+    pub fn row(&self, row: usize) -> Option<Vec<(&'a str, &'a CifValue)>> {
+        if row >= self.len {
+            return None;
+        }
+        Some(
+            self.columns
+                .iter()
+                .filter_map(|column| Some((attribute(column.tag, &self.name), column.value(row)?)))
+                .collect(),
+        )
+    }
+
+    /// The table as a loop with the full tags, items repeated on every row
+    /// and `?` where a short loop has no value
+    pub fn to_loop(&self) -> CifLoop {
+        CifLoop {
+            tags: self.tags().map(str::to_string).collect(),
+            values: (0..self.len)
+                .map(|row| {
+                    self.columns
+                        .iter()
+                        .map(|column| column.value(row).cloned().unwrap_or(CifValue::Unknown))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|column| attribute(column.tag, &self.name).eq_ignore_ascii_case(name))
+    }
+}
+
+impl<'a> CategoryColumn<'a> {
+    fn value(&self, row: usize) -> Option<&'a CifValue> {
+        match self.values {
+            ColumnValues::Item(value) => Some(value),
+            ColumnValues::Loop(loop_, column) => loop_.get(row, column),
+        }
+    }
+}
+
+/// The part of a tag after its category and separator
+fn attribute<'t>(tag: &'t str, category: &str) -> &'t str {
+    tag.get(category.len() + 1..).unwrap_or_default()
+}
+
+impl CifLoop {
+    /// Partition the columns by category into one loop per category.
+    ///
+    /// Categories are taken from `category_of` (usually [`category_of`]) and
+    /// keep the order in which they first appear. If the loop has a key
+    /// column (a `label` or `id` attribute, such as `_atom_site_label`), it is
+    /// copied to the front of every other loop under that loop's category
+    /// (`_atom_site_aniso_label`) so the rows stay joinable, unless the
+    /// category already has that attribute.
+    ///
+    /// A loop with a single category is returned unchanged, as the only
+    /// element.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::category::category_of;
+    /// use cif_parser::Document;
+    ///
+    /// let cif = "data_x\nloop_\n_atom_site.id\n_atom_site.Cartn_x\n_atom_site_anisotrop.U[1][1]\n1 1.0 0.02\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let loops = doc.blocks[0].loops[0].split_by_category(category_of);
+    ///
+    /// assert_eq!(loops[1].tags, ["_atom_site_anisotrop.id", "_atom_site_anisotrop.U[1][1]"]);
+    /// ```
+    pub fn split_by_category(&self, category_of: impl Fn(&str) -> String) -> Vec<CifLoop> {
+        let categories: Vec<String> = self.tags.iter().map(|tag| category_of(tag)).collect();
+        let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+        for (column, category) in categories.iter().enumerate() {
+            match groups.iter_mut().find(|(c, _)| *c == category.as_str()) {
+                Some((_, columns)) => columns.push(column),
+                None => groups.push((category, vec![column])),
+            }
+        }
+        if groups.len() < 2 {
+            return vec![self.clone()];
+        }
+
+        let key = self.key_column(&categories);
+        groups
+            .into_iter()
+            .map(|(category, columns)| {
+                let mut tags: Vec<String> = columns.iter().map(|&c| self.tags[c].clone()).collect();
+                let mut columns = columns;
+                if let Some(key) = key.filter(|&key| categories[key] != category) {
+                    let key_tag = &self.tags[key];
+                    let separator = if key_tag.contains('.') { "." } else { "_" };
+                    let name = attribute(key_tag, &categories[key]);
+                    let has_key = tags
+                        .iter()
+                        .any(|tag| attribute(tag, category).eq_ignore_ascii_case(name));
+                    if !has_key {
+                        let prefix = tags[0].get(..category.len()).unwrap_or(category);
+                        tags.insert(0, format!("{prefix}{separator}{name}"));
+                        columns.insert(0, key);
+                    }
+                }
+                CifLoop {
+                    tags,
+                    values: self
+                        .values
+                        .iter()
+                        .map(|row| columns.iter().map(|&c| row[c].clone()).collect())
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The category prefix shared by every tag, with its separator.
+    ///
+    /// This is `_atom_site.` for mmCIF tags and `_atom_site_` for core CIF
+    /// tags, using [`category_of`], so stripping it from a tag leaves the
+    /// attribute (`fract_x`). The prefix is spelled as in the first tag and
+    /// matched case-insensitively. Returns `None` for a loop that mixes
+    /// categories or has a tag that is just the category name.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let cif = "data_x\nloop_\n_atom_site.id\n_atom_site.Cartn_x\n1 1.0\n";
+    /// let doc = Document::parse(cif).unwrap();
+    ///
+    /// assert_eq!(doc.blocks[0].loops[0].common_prefix(), Some("_atom_site."));
+    /// ```
+    pub fn common_prefix(&self) -> Option<&str> {
+        let first = self.tags.first()?;
+        let category = category_of(first);
+        let prefix = first
+            .get(..category.len() + 1)
+            .filter(|prefix| prefix[..category.len()].eq_ignore_ascii_case(&category))?;
+        self.tags
+            .iter()
+            .all(|tag| {
+                tag.len() > prefix.len()
+                    && tag
+                        .get(..prefix.len())
+                        .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+                    && category_of(tag) == category
+            })
+            .then_some(prefix)
+    }
+
+    /// Index of the column that identifies the rows: a `label` attribute if
+    /// there is one, otherwise an `id`
+    fn key_column(&self, categories: &[String]) -> Option<usize> {
+        let find = |name: &str| {
+            self.tags
+                .iter()
+                .zip(categories)
+                .position(|(tag, category)| attribute(tag, category).eq_ignore_ascii_case(name))
+        };
+        find("label").or_else(|| find("id"))
+    }
+}
+
+impl CifBlock {
+    /// One category of the block as a table, by name (`atom_site`,
+    /// `_atom_site` or `_ATOM_SITE`), using [`category_of`].
+    ///
+    /// Returns `None` if the block has no tags in the category. Save
+    /// frames are not searched.
+    pub fn category(&self, name: &str) -> Option<Category<'_>> {
+        self.category_with(name, &CategoryResolver::new())
+    }
+
+    /// [`category`](Self::category) with the categories of `resolver`, for
+    /// files that use categories of other dictionaries
+    pub fn category_with(&self, name: &str, resolver: &CategoryResolver) -> Option<Category<'_>> {
+        let name = format!("_{}", name.trim_start_matches('_').to_lowercase());
+        let mut columns: Vec<CategoryColumn<'_>> = Vec::new();
+        let wanted = |columns: &[CategoryColumn<'_>], tag: &str| {
+            resolver.category_of(tag) == name
+                && !columns
+                    .iter()
+                    .any(|column| column.tag.eq_ignore_ascii_case(tag))
+        };
+        for (tag, value) in self.iter_items() {
+            if wanted(&columns, tag) {
+                columns.push(CategoryColumn {
+                    tag,
+                    values: ColumnValues::Item(value),
+                });
+            }
+        }
+        // Items alone make one row; otherwise the longest loop decides
+        let mut len = (!columns.is_empty()).then_some(1);
+        let mut looped = false;
+        for loop_ in &self.loops {
+            for (index, tag) in loop_.tags.iter().enumerate() {
+                if wanted(&columns, tag) {
+                    columns.push(CategoryColumn {
+                        tag,
+                        values: ColumnValues::Loop(loop_, index),
+                    });
+                    len = Some(match len {
+                        Some(len) if looped => len.max(loop_.len()),
+                        _ => loop_.len(),
+                    });
+                    looped = true;
+                }
+            }
+        }
+        Some(Category {
+            name,
+            len: len?,
+            columns,
+        })
+    }
+
+    /// The categories of the block's data items and loop columns, in the
+    /// order they first appear (items before loops), using [`category_of`]
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = Vec::new();
+        let tags = self.iter_items().map(|(tag, _)| tag).chain(
+            self.loops
+                .iter()
+                .flat_map(|loop_| loop_.tags.iter().map(String::as_str)),
+        );
+        for tag in tags {
+            let category = category_of(tag);
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+        categories
+    }
+
+    /// Split every loop that mixes categories, using [`category_of`].
+    ///
+    /// The new loops take the place of the original one. Returns a
+    /// [`LoopSplit`] for each loop that was split; loops with a single
+    /// category are left alone.
+    pub fn repair_mixed_loops(&mut self) -> Vec<LoopSplit> {
+        self.repair_mixed_loops_with(&CategoryResolver::new())
+    }
+
+    /// [`repair_mixed_loops`](Self::repair_mixed_loops) with the categories
+    /// of `resolver`, for files that use categories of other dictionaries
+    pub fn repair_mixed_loops_with(&mut self, resolver: &CategoryResolver) -> Vec<LoopSplit> {
+        let category_of = |tag: &str| resolver.category_of(tag);
+        let mut splits = Vec::new();
+        let mut loops = Vec::with_capacity(self.loops.len());
+        for loop_ in std::mem::take(&mut self.loops) {
+            let parts = loop_.split_by_category(category_of);
+            if parts.len() > 1 {
+                let categories: Vec<String> = loop_.tags.iter().map(|t| category_of(t)).collect();
+                let copied = parts
+                    .iter()
+                    .flat_map(|part| &part.tags)
+                    .any(|tag| !loop_.tags.contains(tag));
+                let key = loop_
+                    .key_column(&categories)
+                    .filter(|_| copied)
+                    .map(|key| loop_.tags[key].clone());
+                splits.push(LoopSplit {
+                    categories: parts.iter().map(|p| category_of(&p.tags[0])).collect(),
+                    tags: loop_.tags,
+                    key,
+                });
+            }
+            loops.extend(parts);
+        }
+        self.loops = loops;
+        splits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_of() {
+        for (tag, category) in [
+            ("_atom_site_label", "_atom_site"),
+            ("_ATOM_SITE_ANISO_LABEL", "_atom_site_aniso"),
+            ("_atom_sites_solution_primary", "_atom_sites"),
+            ("_geom_bond_distance", "_geom_bond"),
+            ("_geom_angle", "_geom_angle"),
+            ("_symmetry_equiv_pos_as_xyz", "_symmetry_equiv"),
+            ("_diffrn_reflns_number", "_diffrn_reflns"),
+            ("_pd_proc_2theta_corrected", "_pd"),
+            ("_pdbx_struct_oper_list.id", "_pdbx_struct_oper_list"),
+        ] {
+            assert_eq!(category_of(tag), category, "{tag}");
+        }
+    }
+
+    #[test]
+    fn test_single_category_is_unchanged() {
+        let loop_ = CifLoop {
+            tags: vec![
+                "_geom_bond_atom_site_label_1".into(),
+                "_geom_bond_distance".into(),
+            ],
+            values: vec![],
+        };
+        let parts = loop_.split_by_category(category_of);
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].tags, loop_.tags);
+    }
+}
@@ -0,0 +1,393 @@
+//! Unit cell geometry with standard uncertainties.
+//!
+//! [`UnitCell`] holds the six cell parameters with their standard
+//! uncertainties (sus) and derives the volume, Cartesian coordinates, and
+//! interatomic distances. The `*_with_su` methods propagate the cell sus into
+//! the derived quantity to first order, using analytic partial derivatives.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_albite
+//! _cell_length_a 7.13(6)
+//! _cell_length_b 7.38(5)
+//! _cell_length_c 7.64(4)
+//! _cell_angle_alpha 115.17(11)
+//! _cell_angle_beta 107.2(3)
+//! _cell_angle_gamma 100.60(19)
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let cell = doc.first_block().unwrap().unit_cell().unwrap();
+//!
+//! let (volume, su) = cell.volume_with_su();
+//! assert_eq!(cif_parser::uncertainty::format(volume, su), "325(4)");
+//! ```
+
+use crate::ast::{CifBlock, CifValue};
+
+pub(crate) type Mat3 = [[f64; 3]; 3];
+
+/// The six parameters of a unit cell, with their standard uncertainties.
+///
+/// Lengths are in ångströms and angles in degrees. Cartesian coordinates use
+/// the standard PDB frame: **a** along x and **b** in the xy plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitCell {
+    /// Length of **a**
+    pub a: f64,
+    /// Length of **b**
+    pub b: f64,
+    /// Length of **c**
+    pub c: f64,
+    /// Angle between **b** and **c**, in degrees
+    pub alpha: f64,
+    /// Angle between **a** and **c**, in degrees
+    pub beta: f64,
+    /// Angle between **a** and **b**, in degrees
+    pub gamma: f64,
+    /// Sus of a, b, c, alpha, beta, gamma, in the same units (0 when unknown)
+    pub su: [f64; 6],
+}
+
+impl UnitCell {
+    /// Create a cell with no uncertainties
+    pub fn new(a: f64, b: f64, c: f64, alpha: f64, beta: f64, gamma: f64) -> Self {
+        UnitCell {
+            a,
+            b,
+            c,
+            alpha,
+            beta,
+            gamma,
+            su: [0.0; 6],
+        }
+    }
+
+    /// Set the sus of a, b, c, alpha, beta, gamma
+    pub fn with_su(mut self, su: [f64; 6]) -> Self {
+        self.su = su;
+        self
+    }
+
+    /// Read the cell from a block's `_cell_length_*` and `_cell_angle_*`
+    /// items, or their mmCIF forms (`_cell.length_a` with `_cell.length_a_esd`).
+    ///
+    /// Returns `None` unless all six parameters are numbers.
+    pub fn from_block(block: &CifBlock) -> Option<Self> {
+        let names = [
+            "length_a",
+            "length_b",
+            "length_c",
+            "angle_alpha",
+            "angle_beta",
+            "angle_gamma",
+        ];
+        let mut parameters = [(0.0, 0.0); 6];
+        for (parameter, name) in parameters.iter_mut().zip(names) {
+            *parameter = match block.get_item(&format!("_cell_{name}")) {
+                Some(value) => {
+                    let (value, su) = value.as_numeric_with_su()?;
+                    (value, su.unwrap_or(0.0))
+                }
+                None => {
+                    let value = block.get_item(&format!("_cell.{name}"))?.as_numeric()?;
+                    let su = block
+                        .get_item(&format!("_cell.{name}_esd"))
+                        .and_then(CifValue::as_numeric);
+                    (value, su.unwrap_or(0.0))
+                }
+            };
+        }
+        let [a, b, c, alpha, beta, gamma] = parameters.map(|(value, _)| value);
+        Some(UnitCell::new(a, b, c, alpha, beta, gamma).with_su(parameters.map(|(_, su)| su)))
+    }
+
+    fn cosines(&self) -> [f64; 3] {
+        [self.alpha, self.beta, self.gamma].map(|angle| angle.to_radians().cos())
+    }
+
+    /// `1 - cos²α - cos²β - cos²γ + 2 cosα cosβ cosγ`, so that `V = abc√D`
+    fn volume_factor(&self) -> f64 {
+        let [ca, cb, cg] = self.cosines();
+        1.0 - ca * ca - cb * cb - cg * cg + 2.0 * ca * cb * cg
+    }
+
+    /// Cell volume in Å³
+    pub fn volume(&self) -> f64 {
+        self.a * self.b * self.c * self.volume_factor().sqrt()
+    }
+
+    /// Cell volume and its su, propagated from the cell sus
+    pub fn volume_with_su(&self) -> (f64, f64) {
+        let volume = self.volume();
+        let [ca, cb, cg] = self.cosines();
+        let [sa, sb, sg] =
+            [self.alpha, self.beta, self.gamma].map(|angle| angle.to_radians().sin());
+        // ∂V/∂α = abc·sinα·(cosα − cosβ·cosγ)/√D, per radian
+        let abc_over_root = self.a * self.b * self.c / self.volume_factor().sqrt();
+        let derivatives = [
+            volume / self.a,
+            volume / self.b,
+            volume / self.c,
+            abc_over_root * sa * (ca - cb * cg),
+            abc_over_root * sb * (cb - ca * cg),
+            abc_over_root * sg * (cg - ca * cb),
+        ];
+        (volume, combine(&derivatives, &self.radian_su()))
+    }
+
+    /// Sus with the angles converted to radians, to match the derivatives
+    fn radian_su(&self) -> [f64; 6] {
+        let mut su = self.su;
+        for angle in &mut su[3..] {
+            *angle = angle.to_radians();
+        }
+        su
+    }
+
+    /// Matrix taking fractional coordinates to Cartesian ones; its columns
+    /// are the cell vectors
+    pub fn orthogonalization_matrix(&self) -> [[f64; 3]; 3] {
+        let [ca, cb, cg] = self.cosines();
+        let sg = self.gamma.to_radians().sin();
+        let (a, b, c) = (self.a, self.b, self.c);
+        [
+            [a, b * cg, c * cb],
+            [0.0, b * sg, c * (ca - cb * cg) / sg],
+            [0.0, 0.0, self.volume() / (a * b * sg)],
+        ]
+    }
+
+    /// Matrix taking Cartesian coordinates to fractional ones, the inverse of
+    /// the [orthogonalization matrix](Self::orthogonalization_matrix); its
+    /// rows are the reciprocal cell vectors
+    ///
+    /// Returns NaNs for a degenerate cell.
+    pub fn fractionalization_matrix(&self) -> [[f64; 3]; 3] {
+        invert(&self.orthogonalization_matrix()).unwrap_or([[f64::NAN; 3]; 3])
+    }
+
+    /// Metric tensor `G`, the dot products of the cell vectors in Å², so that
+    /// the squared length of a fractional vector `x` is `xᵀGx`
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::UnitCell;
+    /// let g = UnitCell::new(4.0, 5.0, 6.0, 90.0, 90.0, 120.0).metric_tensor();
+    /// assert!((g[0][1] + 10.0).abs() < 1e-12); // a·b = ab cos γ
+    /// assert_eq!(g[2][2], 36.0);
+    /// ```
+    pub fn metric_tensor(&self) -> [[f64; 3]; 3] {
+        let [ca, cb, cg] = self.cosines();
+        let (a, b, c) = (self.a, self.b, self.c);
+        [
+            [a * a, a * b * cg, a * c * cb],
+            [a * b * cg, b * b, b * c * ca],
+            [a * c * cb, b * c * ca, c * c],
+        ]
+    }
+
+    /// Reciprocal metric tensor `G*`, the inverse of `G`, in Å⁻², so that
+    /// `1/d² = hᵀG*h` for a reflection `h`
+    ///
+    /// Returns NaNs for a degenerate cell.
+    pub fn reciprocal_metric_tensor(&self) -> [[f64; 3]; 3] {
+        invert(&self.metric_tensor()).unwrap_or([[f64::NAN; 3]; 3])
+    }
+
+    /// Cartesian coordinates (Å) of a fractional position
+    pub fn frac_to_cart(&self, frac: [f64; 3]) -> [f64; 3] {
+        apply(&self.orthogonalization_matrix(), frac)
+    }
+
+    /// Fractional coordinates of a Cartesian position (Å)
+    ///
+    /// Returns NaNs for a degenerate cell.
+    pub fn cart_to_frac(&self, cart: [f64; 3]) -> [f64; 3] {
+        apply(&self.fractionalization_matrix(), cart)
+    }
+
+    /// Interplanar spacing in Å of the reflection `hkl`
+    ///
+    /// Returns NaN for a degenerate cell and infinity for `0 0 0`.
+    pub fn d_spacing(&self, hkl: [i32; 3]) -> f64 {
+        let Some(frac) = invert(&self.orthogonalization_matrix()) else {
+            return f64::NAN;
+        };
+        // The rows of the fractionalization matrix are the reciprocal vectors
+        let s = [0, 1, 2].map(|j| (0..3).map(|i| f64::from(hkl[i]) * frac[i][j]).sum::<f64>());
+        1.0 / (s[0] * s[0] + s[1] * s[1] + s[2] * s[2]).sqrt()
+    }
+
+    /// Distance in Å between two fractional positions
+    pub fn distance(&self, frac1: [f64; 3], frac2: [f64; 3]) -> f64 {
+        let [dx, dy, dz] = [0, 1, 2].map(|i| frac2[i] - frac1[i]);
+        let [ca, cb, cg] = self.cosines();
+        let (a, b, c) = (self.a, self.b, self.c);
+        (dx * dx * a * a
+            + dy * dy * b * b
+            + dz * dz * c * c
+            + 2.0 * dx * dy * a * b * cg
+            + 2.0 * dx * dz * a * c * cb
+            + 2.0 * dy * dz * b * c * ca)
+            .sqrt()
+    }
+
+    /// Distance between two fractional positions and its su, propagated from
+    /// the cell sus (the positions are taken as exact)
+    pub fn distance_with_su(&self, frac1: [f64; 3], frac2: [f64; 3]) -> (f64, f64) {
+        let distance = self.distance(frac1, frac2);
+        if distance == 0.0 {
+            return (0.0, 0.0);
+        }
+        let [dx, dy, dz] = [0, 1, 2].map(|i| frac2[i] - frac1[i]);
+        let [ca, cb, cg] = self.cosines();
+        let [sa, sb, sg] =
+            [self.alpha, self.beta, self.gamma].map(|angle| angle.to_radians().sin());
+        let (a, b, c) = (self.a, self.b, self.c);
+        // Derivatives of d = √(ΔᵀGΔ) with respect to each cell parameter
+        let derivatives = [
+            dx * (dx * a + dy * b * cg + dz * c * cb),
+            dy * (dy * b + dx * a * cg + dz * c * ca),
+            dz * (dz * c + dx * a * cb + dy * b * ca),
+            -dy * dz * b * c * sa,
+            -dx * dz * a * c * sb,
+            -dx * dy * a * b * sg,
+        ]
+        .map(|d| d / distance);
+        (distance, combine(&derivatives, &self.radian_su()))
+    }
+}
+
+impl CifBlock {
+    /// The unit cell of this block, if all six parameters are given.
+    ///
+    /// See [`UnitCell::from_block`] for the tags read.
+    pub fn unit_cell(&self) -> Option<UnitCell> {
+        UnitCell::from_block(self)
+    }
+}
+
+/// First-order su from partial derivatives and independent input sus
+fn combine(derivatives: &[f64; 6], su: &[f64; 6]) -> f64 {
+    derivatives
+        .iter()
+        .zip(su)
+        .map(|(d, s)| (d * s).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+pub(crate) fn apply(m: &Mat3, v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+pub(crate) fn multiply(a: &Mat3, b: &Mat3) -> Mat3 {
+    [0, 1, 2].map(|i| [0, 1, 2].map(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum()))
+}
+
+pub(crate) fn transpose(m: &Mat3) -> Mat3 {
+    [0, 1, 2].map(|i| [0, 1, 2].map(|j| m[j][i]))
+}
+
+pub(crate) fn determinant(m: &Mat3) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+pub(crate) fn invert(m: &Mat3) -> Option<Mat3> {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = determinant(m);
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+    Some([0, 1, 2].map(|i| [0, 1, 2].map(|j| cofactor(j, i) / det)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uncertainty::propagate;
+
+    fn triclinic() -> UnitCell {
+        UnitCell::new(7.13, 7.38, 7.64, 115.17, 107.2, 100.6)
+            .with_su([0.06, 0.05, 0.04, 0.11, 0.3, 0.19])
+    }
+
+    fn as_values(cell: &UnitCell) -> Vec<(f64, f64)> {
+        let p = [cell.a, cell.b, cell.c, cell.alpha, cell.beta, cell.gamma];
+        p.into_iter().zip(cell.su).collect()
+    }
+
+    fn from_values(x: &[f64]) -> UnitCell {
+        UnitCell::new(x[0], x[1], x[2], x[3], x[4], x[5])
+    }
+
+    #[test]
+    fn test_volume_su_matches_numerical_propagation() {
+        let cell = triclinic();
+        let (volume, su) = cell.volume_with_su();
+        let (expected, expected_su) = propagate(&as_values(&cell), |x| from_values(x).volume());
+        assert!((volume - expected).abs() < 1e-9);
+        assert!((su - expected_su).abs() < 1e-6 * expected_su);
+    }
+
+    #[test]
+    fn test_distance_su_matches_numerical_propagation() {
+        let cell = triclinic();
+        let (p, q) = ([0.1, 0.2, 0.3], [0.35, -0.1, 0.55]);
+        let (distance, su) = cell.distance_with_su(p, q);
+        let (expected, expected_su) =
+            propagate(&as_values(&cell), |x| from_values(x).distance(p, q));
+        assert!((distance - expected).abs() < 1e-9);
+        assert!((su - expected_su).abs() < 1e-6 * expected_su);
+    }
+
+    #[test]
+    fn test_distance_matches_cartesian() {
+        let cell = triclinic();
+        let (p, q) = ([0.1, 0.2, 0.3], [0.35, -0.1, 0.55]);
+        let (cp, cq) = (cell.frac_to_cart(p), cell.frac_to_cart(q));
+        let cartesian = (0..3).map(|i| (cq[i] - cp[i]).powi(2)).sum::<f64>().sqrt();
+        assert!((cell.distance(p, q) - cartesian).abs() < 1e-12);
+
+        let back = cell.cart_to_frac(cp);
+        for i in 0..3 {
+            assert!((back[i] - p[i]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_cubic_cell() {
+        let cell = UnitCell::new(10.0, 10.0, 10.0, 90.0, 90.0, 90.0)
+            .with_su([0.01, 0.01, 0.01, 0.0, 0.0, 0.0]);
+        let (volume, su) = cell.volume_with_su();
+        assert!((volume - 1000.0).abs() < 1e-9);
+        // σ_V = V·√3·σ_a/a
+        assert!((su - 1000.0 * 3f64.sqrt() * 0.001).abs() < 1e-9);
+        assert_eq!(cell.distance_with_su([0.0; 3], [0.0; 3]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_d_spacing_matches_monoclinic_formula() {
+        let cell = UnitCell::new(5.0893, 11.8124, 5.4668, 90.0, 112.0058, 90.0);
+        let beta = cell.beta.to_radians();
+        for hkl in [[1, 0, 0], [0, 2, 0], [3, 2, -7], [-2, 1, 4]] {
+            let [h, k, l] = hkl.map(f64::from);
+            let inverse_square = (h * h / (cell.a * cell.a) + l * l / (cell.c * cell.c)
+                - 2.0 * h * l * beta.cos() / (cell.a * cell.c))
+                / beta.sin().powi(2)
+                + k * k / (cell.b * cell.b);
+            assert!((cell.d_spacing(hkl) - inverse_square.sqrt().recip()).abs() < 1e-12);
+        }
+        assert_eq!(cell.d_spacing([0, 0, 0]), f64::INFINITY);
+    }
+}
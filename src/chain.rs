@@ -0,0 +1,246 @@
+//! Mapping between the label and author residue numbering of PDBx/mmCIF.
+//!
+//! A PDBx atom site is numbered twice. The `label_*` ids are assigned by the
+//! archive: `label_asym_id` names one instance of one entity (a polymer
+//! chain, a ligand, or the waters of a chain) and `label_seq_id` counts the
+//! residues of a polymer from 1, with `.` for everything else. The `auth_*`
+//! ids are the author's: `auth_asym_id` is the chain of the PDB format, shared
+//! by a protein chain and the ligands and waters bound to it, and
+//! `auth_seq_id` with `pdbx_PDB_ins_code` is the residue number of the
+//! literature, which may start anywhere, skip, or repeat with insertion
+//! codes (`52`, `52A`, `52B`).
+//!
+//! [`CifBlock::chain_mapping`] reads the pairs from `_atom_site` and, when
+//! present, `_pdbx_poly_seq_scheme` (which also lists residues without
+//! atoms); [`ChainMap`] converts between them. [`CifBlock::select_auth`]
+//! picks the atom sites of a residue range by author numbering.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_1ABC
+//! loop_
+//! _atom_site.id
+//! _atom_site.label_atom_id
+//! _atom_site.label_asym_id
+//! _atom_site.label_seq_id
+//! _atom_site.auth_asym_id
+//! _atom_site.auth_seq_id
+//! _atom_site.pdbx_PDB_ins_code
+//! 1 CA A 1 H 52 ?
+//! 2 CA A 2 H 52 A
+//! 3 O  C . H 301 ?
+//! ";
+//! let block = &Document::parse(cif).unwrap().blocks[0];
+//! let map = block.chain_mapping().unwrap();
+//!
+//! let label = map.auth_to_label("H", 52, Some("A")).unwrap();
+//! assert_eq!((label.asym.as_str(), label.seq), ("A", Some(2)));
+//! assert_eq!(map.label_to_auth("C", None)[0].seq, 301);
+//!
+//! let residue_52 = block.select_auth("H", 52..=52).unwrap();
+//! assert_eq!(residue_52.len(), 2);
+//! ```
+
+use crate::ast::{CifBlock, CifLoop, CifValue};
+use crate::publication::value_text;
+use indexmap::IndexMap;
+use std::ops::RangeInclusive;
+
+/// A residue in the author's numbering: chain, number, and insertion code
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuthResidue {
+    /// `auth_asym_id`, the chain of the PDB format
+    pub chain: String,
+    /// `auth_seq_id`
+    pub seq: i64,
+    /// `pdbx_PDB_ins_code`, or `None` for `?` and `.`
+    pub ins_code: Option<String>,
+}
+
+/// A residue in the archive's numbering: entity instance and position
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LabelResidue {
+    /// `label_asym_id`
+    pub asym: String,
+    /// `label_seq_id`, or `None` for `.`, as for ligands and waters
+    pub seq: Option<i64>,
+}
+
+/// The residues of a PDBx block in both numberings (see
+/// [`CifBlock::chain_mapping`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChainMap {
+    to_label: IndexMap<AuthResidue, LabelResidue>,
+    to_auth: IndexMap<LabelResidue, Vec<AuthResidue>>,
+}
+
+impl ChainMap {
+    fn insert(&mut self, auth: AuthResidue, label: LabelResidue) {
+        if self.to_label.contains_key(&auth) {
+            return;
+        }
+        self.to_auth
+            .entry(label.clone())
+            .or_default()
+            .push(auth.clone());
+        self.to_label.insert(auth, label);
+    }
+
+    /// The label ids of the author's residue `seq` with `ins_code` in
+    /// `chain`
+    pub fn auth_to_label(
+        &self,
+        chain: &str,
+        seq: i64,
+        ins_code: Option<&str>,
+    ) -> Option<&LabelResidue> {
+        self.to_label.get(&AuthResidue {
+            chain: chain.to_string(),
+            seq,
+            ins_code: ins_code.map(str::to_string),
+        })
+    }
+
+    /// The author's residues with label ids `asym` and `seq`.
+    ///
+    /// A polymer residue has one. Residues numbered `.` share their label
+    /// ids with the rest of their entity instance, so `seq` of `None` gives
+    /// them all, such as every water of a chain, in file order.
+    pub fn label_to_auth(&self, asym: &str, seq: Option<i64>) -> &[AuthResidue] {
+        self.to_auth
+            .get(&LabelResidue {
+                asym: asym.to_string(),
+                seq,
+            })
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Every residue in both numberings, in file order
+    pub fn residues(&self) -> impl Iterator<Item = (&AuthResidue, &LabelResidue)> {
+        self.to_label.iter()
+    }
+
+    /// Number of residues
+    pub fn len(&self) -> usize {
+        self.to_label.len()
+    }
+
+    /// Whether there are no residues
+    pub fn is_empty(&self) -> bool {
+        self.to_label.is_empty()
+    }
+}
+
+/// A whole number written as a number, such as a sequence id
+fn integer(value: &CifValue) -> Option<i64> {
+    value
+        .as_numeric()
+        .filter(|n| n.fract() == 0.0)
+        .map(|n| n as i64)
+}
+
+/// Add the pairs of one loop; `columns` are the label asym, label seq, auth
+/// chain, auth seq, and insertion code tags
+fn add_pairs(map: &mut ChainMap, loop_: &CifLoop, columns: [&str; 5]) {
+    let [asym, label_seq, chain, auth_seq, ins_code] = columns.map(|tag| loop_.get_column(tag));
+    let (Some(asym), Some(chain), Some(auth_seq)) = (asym, chain, auth_seq) else {
+        return;
+    };
+    for row in 0..loop_.len() {
+        let (Some(asym), Some(chain), Some(seq)) = (
+            value_text(asym[row]),
+            value_text(chain[row]),
+            integer(auth_seq[row]),
+        ) else {
+            continue;
+        };
+        let label = LabelResidue {
+            asym,
+            seq: label_seq.as_ref().and_then(|column| integer(column[row])),
+        };
+        let auth = AuthResidue {
+            chain,
+            seq,
+            ins_code: ins_code.as_ref().and_then(|column| value_text(column[row])),
+        };
+        map.insert(auth, label);
+    }
+}
+
+impl CifBlock {
+    /// The residues of a PDBx/mmCIF block in label and author numbering.
+    ///
+    /// Pairs come from the atom sites, then from `_pdbx_poly_seq_scheme`
+    /// for residues with no atoms; atom sites of later models add nothing
+    /// new. Atom sites without a chain or whole-number `auth_seq_id` are
+    /// skipped. Returns `None` if the block has neither loop.
+    pub fn chain_mapping(&self) -> Option<ChainMap> {
+        let atoms = self.find_loop("_atom_site.auth_asym_id");
+        let scheme = self.find_loop("_pdbx_poly_seq_scheme.asym_id");
+        if atoms.is_none() && scheme.is_none() {
+            return None;
+        }
+        let mut map = ChainMap::default();
+        if let Some(atoms) = atoms {
+            add_pairs(
+                &mut map,
+                atoms,
+                [
+                    "_atom_site.label_asym_id",
+                    "_atom_site.label_seq_id",
+                    "_atom_site.auth_asym_id",
+                    "_atom_site.auth_seq_id",
+                    "_atom_site.pdbx_PDB_ins_code",
+                ],
+            );
+        }
+        if let Some(scheme) = scheme {
+            add_pairs(
+                &mut map,
+                scheme,
+                [
+                    "_pdbx_poly_seq_scheme.asym_id",
+                    "_pdbx_poly_seq_scheme.seq_id",
+                    "_pdbx_poly_seq_scheme.pdb_strand_id",
+                    "_pdbx_poly_seq_scheme.pdb_seq_num",
+                    "_pdbx_poly_seq_scheme.pdb_ins_code",
+                ],
+            );
+        }
+        Some(map)
+    }
+
+    /// The `_atom_site` rows of the author's chain `chain` with an
+    /// `auth_seq_id` in `seq`, as a loop with all the columns.
+    ///
+    /// Residues with insertion codes are selected by their number, so
+    /// `50..=55` includes `52A`. Files without author ids are matched on
+    /// `label_asym_id` and `label_seq_id` instead, as PDBx allows. Returns
+    /// `None` if the block has no `_atom_site` loop with chain and sequence
+    /// ids.
+    pub fn select_auth(&self, chain: &str, seq: RangeInclusive<i64>) -> Option<CifLoop> {
+        let atoms = self
+            .find_loop("_atom_site.auth_asym_id")
+            .or_else(|| self.find_loop("_atom_site.label_asym_id"))?;
+        let column = |names: [&str; 2]| {
+            names
+                .iter()
+                .find_map(|name| atoms.get_column(&format!("_atom_site.{name}")))
+        };
+        let chains = column(["auth_asym_id", "label_asym_id"])?;
+        let seqs = column(["auth_seq_id", "label_seq_id"])?;
+
+        let mut selected = CifLoop::new(atoms.tags.clone());
+        for (row, values) in atoms.values.iter().enumerate() {
+            let in_chain = value_text(chains[row]).is_some_and(|c| c == chain);
+            if in_chain && integer(seqs[row]).is_some_and(|n| seq.contains(&n)) {
+                selected.values.push(values.clone());
+            }
+        }
+        Some(selected)
+    }
+}
@@ -0,0 +1,297 @@
+//! CIF-JSON, the COMCIFS convention for CIF data as JSON.
+//!
+//! [`CifDocument::to_cif_json`] writes a document as
+//!
+//! ```json
+//! {"CIF-JSON": {
+//!   "Metadata": {"cif-version": "1.1", "schema-name": "CIF-JSON", ...},
+//!   "block": {"_tag": [value, ...], ..., "Frames": {"frame": {...}}}
+//! }}
+//! ```
+//!
+//! Block, frame and data names are lowercased, as CIF names are
+//! case-insensitive; blocks or frames whose names differ only in case are
+//! an error, as they would share a key. Every data name has an array of
+//! values: one for a data item, one per row for a loop column. Numbers are
+//! JSON numbers, text is a string, `?` is the string `"\\?"` and `.` is
+//! `null`; CIF 2.0 lists and tables are arrays and objects. A number with
+//! an su is a string in CIF notation, such as `"5.431(2)"`, as JSON numbers
+//! cannot hold the su.
+//!
+//! CIF-JSON does not record which data names were looped together.
+//! [`CifDocument::from_cif_json`] reads a name with one value as a data
+//! item, and puts names with any other number of values into loops by
+//! [category](crate::category::category_of) and length. Strings that read
+//! as numbers become numbers, as a quoted number in CIF does, so the output
+//! of tools that write every value as a string reads the same.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{CifDocument, CifValue};
+//!
+//! let cif = "data_x\n_cell_length_a 5.431\n_cell_volume ?\n\
+//!            loop_\n_atom_site_label\n_atom_site_occupancy\nSi1 1\nO1 .\n";
+//! let doc = CifDocument::parse(cif).unwrap();
+//! let json = doc.to_cif_json().unwrap();
+//! assert!(json.contains(r#""_atom_site_occupancy":[1,null]"#));
+//!
+//! let back = CifDocument::from_cif_json(&json).unwrap();
+//! assert_eq!(back.blocks[0].get_item("_cell_volume"), Some(&CifValue::Unknown));
+//! assert_eq!(back.blocks[0].loops[0].len(), 2);
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
+use crate::category::category_of;
+use crate::error::CifError;
+use crate::uncertainty;
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+use serde_json::{json, Map, Value as Json};
+
+/// The key of the top-level object
+pub const CIF_JSON_KEY: &str = "CIF-JSON";
+
+/// Where the CIF-JSON schema is published, recorded in the metadata
+pub const SCHEMA_URI: &str = "http://www.iucr.org/resources/cif/cif-json.json";
+
+/// The text standing for `?`, which `null` (standing for `.`) cannot
+const UNKNOWN: &str = "\\?";
+
+impl CifDocument {
+    /// The document as CIF-JSON text, on one line.
+    ///
+    /// See the [module documentation](crate::cif_json) for the layout.
+    /// Fails as [`to_cif_json_value`](Self::to_cif_json_value) does.
+    pub fn to_cif_json(&self) -> Result<String, CifError> {
+        Ok(self.to_cif_json_value()?.to_string())
+    }
+
+    /// The document as a CIF-JSON value, for embedding in other JSON.
+    ///
+    /// Fails with [`CifError::InvalidStructure`] if two blocks, or two
+    /// frames of a block, have names differing only in case: both would
+    /// have the same lowercased key, and one would be lost.
+    pub fn to_cif_json_value(&self) -> Result<Json, CifError> {
+        let mut top = Map::new();
+        top.insert(
+            "Metadata".to_string(),
+            json!({
+                "cif-version": match self.version {
+                    CifVersion::V1_1 => "1.1",
+                    CifVersion::V2_0 => "2.0",
+                },
+                "schema-name": "CIF-JSON",
+                "schema-version": "1.0.0",
+                "schema-uri": SCHEMA_URI,
+            }),
+        );
+        for block in &self.blocks {
+            let mut object = container_json(&block.items, &block.loops);
+            if !block.frames.is_empty() {
+                let mut frames = Map::new();
+                for frame in &block.frames {
+                    let json = container_json(&frame.items, &frame.loops);
+                    insert_named(&mut frames, &frame.name, json, || {
+                        format!("save_{} in data_{}", frame.name, block.name)
+                    })?;
+                }
+                object.insert("Frames".to_string(), Json::Object(frames));
+            }
+            insert_named(&mut top, &block.name, object, || {
+                format!("data_{}", block.name)
+            })?;
+        }
+        Ok(json!({ CIF_JSON_KEY: top }))
+    }
+
+    /// Read a document from CIF-JSON text.
+    ///
+    /// The version comes from the metadata's `cif-version`, CIF 1.1 if it
+    /// is missing. Fails with [`CifError::InvalidStructure`] if the text is
+    /// not JSON or not laid out as CIF-JSON.
+    pub fn from_cif_json(text: &str) -> Result<Self, CifError> {
+        let json: Json = serde_json::from_str(text).map_err(|error| {
+            CifError::invalid_structure(format!("invalid JSON: {error}"))
+                .at_location(error.line(), error.column())
+        })?;
+        Self::from_cif_json_value(&json)
+    }
+
+    /// Read a document from a CIF-JSON value
+    pub fn from_cif_json_value(json: &Json) -> Result<Self, CifError> {
+        let top = json
+            .get(CIF_JSON_KEY)
+            .and_then(Json::as_object)
+            .ok_or_else(|| CifError::invalid_structure(format!("no \"{CIF_JSON_KEY}\" object")))?;
+        let mut doc = CifDocument::new();
+        for (name, value) in top {
+            if name == "Metadata" {
+                if value.get("cif-version").and_then(Json::as_str) == Some("2.0") {
+                    doc.version = CifVersion::V2_0;
+                }
+                continue;
+            }
+            let object = as_object(value, name)?;
+            let (items, loops) = container_from_json(object, name)?;
+            let mut block = CifBlock::new(name.clone());
+            block.items = items;
+            block.loops = loops;
+            if let Some(frames) = object.get("Frames") {
+                for (frame_name, frame) in as_object(frames, "Frames")? {
+                    let (items, loops) =
+                        container_from_json(as_object(frame, frame_name)?, frame_name)?;
+                    let mut frame = CifFrame::new(frame_name.clone());
+                    frame.items = items;
+                    frame.loops = loops;
+                    block.frames.push(frame);
+                }
+            }
+            doc.blocks.push(block);
+        }
+        Ok(doc)
+    }
+}
+
+/// Add a block or frame under its lowercased name, failing if one before
+/// it has that key. `container` names it in the error.
+fn insert_named(
+    map: &mut Map<String, Json>,
+    name: &str,
+    object: Map<String, Json>,
+    container: impl Fn() -> String,
+) -> Result<(), CifError> {
+    let key = name.to_lowercase();
+    if map.contains_key(&key) {
+        return Err(CifError::invalid_structure(format!(
+            "{} has the CIF-JSON key \"{key}\" of an earlier name differing only in case",
+            container()
+        )));
+    }
+    map.insert(key, Json::Object(object));
+    Ok(())
+}
+
+/// The data names of a block or frame with their values; a name given
+/// more than once keeps all its values, in order
+fn container_json(items: &IndexMap<String, CifValue>, loops: &[CifLoop]) -> Map<String, Json> {
+    let mut object = Map::new();
+    let mut add = |tag: &str, values: Vec<Json>| match object
+        .entry(tag.to_lowercase())
+        .or_insert_with(|| Json::Array(Vec::new()))
+    {
+        Json::Array(existing) => existing.extend(values),
+        _ => unreachable!("every data name holds an array"),
+    };
+    for (tag, value) in items {
+        add(tag, vec![value_json(value)]);
+    }
+    for loop_ in loops {
+        for (col, tag) in loop_.tags.iter().enumerate() {
+            add(
+                tag,
+                loop_
+                    .values
+                    .iter()
+                    .map(|row| value_json(&row[col]))
+                    .collect(),
+            );
+        }
+    }
+    object
+}
+
+fn value_json(value: &CifValue) -> Json {
+    match value {
+        CifValue::Text(text) => Json::String(text.clone()),
+        CifValue::LargeText(text) => Json::String(text.to_string()),
+        // Whole numbers without a fraction, as they are usually written
+        CifValue::Numeric(n) if n.fract() == 0.0 && n.abs() < 2f64.powi(53) => json!(*n as i64),
+        CifValue::Numeric(n) => json!(n),
+        // JSON numbers have no su, so these stay in CIF notation
+        CifValue::NumericSu { .. } => Json::String(inline_value(value, CifVersion::V2_0)),
+        CifValue::Unknown => Json::String(UNKNOWN.to_string()),
+        CifValue::NotApplicable => Json::Null,
+        CifValue::List(values) => Json::Array(values.iter().map(value_json).collect()),
+        CifValue::Table(entries) => {
+            // Sorted, so the same table always gives the same text
+            let mut keys: Vec<&String> = entries.keys().collect();
+            keys.sort();
+            Json::Object(
+                keys.into_iter()
+                    .map(|key| (key.clone(), value_json(&entries[key])))
+                    .collect(),
+            )
+        }
+    }
+}
+
+type Container = (IndexMap<String, CifValue>, Vec<CifLoop>);
+
+/// The items and loops of a block or frame object: names with one value
+/// are items, the others are loop columns grouped by category and length
+fn container_from_json(object: &Map<String, Json>, name: &str) -> Result<Container, CifError> {
+    let mut items = IndexMap::new();
+    let mut loops: Vec<(String, CifLoop)> = Vec::new();
+    for (tag, values) in object {
+        if tag == "Frames" {
+            continue;
+        }
+        let Json::Array(values) = values else {
+            return Err(CifError::invalid_structure(format!(
+                "{name}: the values of {tag} are not an array"
+            )));
+        };
+        let values: Vec<CifValue> = values.iter().map(value_from_json).collect();
+        if let [value] = &values[..] {
+            items.insert(tag.clone(), value.clone());
+            continue;
+        }
+        let category = category_of(tag);
+        let found = loops
+            .iter_mut()
+            .find(|(other, loop_)| *other == category && loop_.len() == values.len());
+        let loop_ = match found {
+            Some((_, loop_)) => loop_,
+            None => {
+                loops.push((category, CifLoop::new(Vec::new())));
+                &mut loops.last_mut().expect("just pushed").1
+            }
+        };
+        loop_.tags.push(tag.clone());
+        if loop_.tags.len() == 1 {
+            loop_.values = values.into_iter().map(|value| vec![value]).collect();
+        } else {
+            for (row, value) in loop_.values.iter_mut().zip(values) {
+                row.push(value);
+            }
+        }
+    }
+    Ok((items, loops.into_iter().map(|(_, loop_)| loop_).collect()))
+}
+
+fn value_from_json(json: &Json) -> CifValue {
+    match json {
+        Json::Null => CifValue::NotApplicable,
+        Json::String(text) if text == UNKNOWN => CifValue::Unknown,
+        Json::String(text) => match uncertainty::parse(text).filter(|_| text.trim() == text) {
+            Some((n, None)) if n.is_finite() => CifValue::Numeric(n),
+            Some((value, Some(su))) if value.is_finite() => CifValue::NumericSu { value, su },
+            _ => CifValue::Text(text.clone()),
+        },
+        Json::Number(n) => CifValue::Numeric(n.as_f64().unwrap_or(f64::NAN)),
+        Json::Bool(b) => CifValue::Text(b.to_string()),
+        Json::Array(values) => CifValue::List(values.iter().map(value_from_json).collect()),
+        Json::Object(entries) => CifValue::Table(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), value_from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn as_object<'a>(json: &'a Json, name: &str) -> Result<&'a Map<String, Json>, CifError> {
+    json.as_object()
+        .ok_or_else(|| CifError::invalid_structure(format!("{name} is not an object")))
+}
@@ -0,0 +1,115 @@
+//! Compressed CIF files, as archives and the PDB ship them.
+//!
+//! [`CifDocument::from_file`](crate::CifDocument::from_file) decompresses
+//! gzip (`.gz`), bzip2 (`.bz2`), and Zstandard (`.zst`) files before
+//! parsing, each behind a feature of the same name (`gzip`, `bzip2`,
+//! `zstd`, or `compression` for all three). The format is found from the
+//! file's first bytes, or from its extension if they match none.
+//!
+//! A compressed file whose feature is not enabled is an error rather than
+//! being parsed as text. The document's [`source`](crate::CifDocument::source)
+//! records the compressed file as it is on disk.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::compression::Compression;
+//! use std::path::Path;
+//!
+//! assert_eq!(Compression::from_magic(&[0x1f, 0x8b, 8, 0]), Some(Compression::Gzip));
+//! assert_eq!(Compression::from_extension(Path::new("1abc.cif.zst")), Some(Compression::Zstd));
+//! assert_eq!(Compression::detect(Path::new("1abc.cif"), b"data_1abc\n"), None);
+//! ```
+
+use crate::error::CifError;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A compression format a CIF file may be stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// gzip, `.gz`
+    Gzip,
+    /// bzip2, `.bz2`
+    Bzip2,
+    /// Zstandard, `.zst`
+    Zstd,
+}
+
+impl Compression {
+    /// The format of a file with this path and content: from its first
+    /// bytes, or else from its extension
+    pub fn detect(path: &Path, content: &[u8]) -> Option<Self> {
+        Self::from_magic(content).or_else(|| Self::from_extension(path))
+    }
+
+    /// The format whose magic number starts `content`
+    pub fn from_magic(content: &[u8]) -> Option<Self> {
+        match content {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [b'B', b'Z', b'h', b'1'..=b'9', ..] => Some(Compression::Bzip2),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The format a path's extension names, in any case
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "bz2" => Some(Compression::Bzip2),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The feature that reads this format
+    pub fn feature(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Bzip2 => "bzip2",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Decompress `content`, all of its members or frames.
+    ///
+    /// Fails with [`CifError::Io`] if the data is corrupt, and with
+    /// [`CifError::InvalidStructure`] if the format's feature is not
+    /// enabled.
+    pub fn decompress(self, content: &[u8]) -> Result<Vec<u8>, CifError> {
+        let mut output = Vec::new();
+        self.reader(content)?.read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    /// A reader of the decompressed bytes of `compressed`, which fails as
+    /// [`decompress`](Self::decompress) does
+    pub fn reader<'a, R: Read + 'a>(self, compressed: R) -> Result<Box<dyn Read + 'a>, CifError> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(compressed))),
+            #[cfg(feature = "bzip2")]
+            Compression::Bzip2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(compressed))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(compressed)?)),
+            #[allow(unreachable_patterns)]
+            _ => {
+                drop(compressed);
+                Err(CifError::invalid_structure(format!(
+                    "file is compressed with {0}; enable the `{0}` feature to read it",
+                    self.feature()
+                )))
+            }
+        }
+    }
+}
+
+/// `path` without a compression extension: `1abc.cif` for `1abc.cif.gz`
+pub(crate) fn uncompressed_path(path: &Path) -> PathBuf {
+    match Compression::from_extension(path) {
+        Some(_) => path.with_extension(""),
+        None => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,395 @@
+//! Conformance to the letter of the CIF 1.1 specification.
+//!
+//! The parser accepts much that the CIF 1.1 syntax specification forbids:
+//! long lines, Unicode, repeated data names, and values that begin with a
+//! reserved character. A deposition service that must pass on only valid
+//! CIF 1.1 can parse with
+//! [`ParseOptions::conformance`](crate::ParseOptions::conformance) set to
+//! [`Conformance::Strict11`], which rejects the first violation as a
+//! [`CifError::NonConformant`] naming the [`ConformanceRule`] and the clause
+//! of the specification it breaks. [`CifDocument::check_conformance`] checks
+//! a document built in code before it is written, and lists every violation.
+//!
+//! The rules:
+//!
+//! | Rule                                              | Requirement                                   |
+//! |---------------------------------------------------|-----------------------------------------------|
+//! | [`LineLength`](ConformanceRule::LineLength)       | lines of at most 2048 characters              |
+//! | [`TagLength`](ConformanceRule::TagLength)         | data names of at most 75 characters           |
+//! | [`Ascii`](ConformanceRule::Ascii)                 | printable ASCII, tab, and line terminators    |
+//! | [`ContainerName`](ConformanceRule::ContainerName) | unique, non-empty block and frame names of at most 75 characters without whitespace |
+//! | [`DuplicateTag`](ConformanceRule::DuplicateTag)   | each data name once per block or frame, in any case |
+//! | [`LossyValue`](ConformanceRule::LossyValue)       | values CIF 1.1 can write: no lists or tables, no text with a line starting with `;` |
+//! | [`ReservedWord`](ConformanceRule::ReservedWord)   | no unquoted value starting with `$`           |
+//!
+//! Unquoted values that are reserved words, or start with `_`, `#`, a quote,
+//! `[`, or `]`, are rejected by the grammar in every mode.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{CifError, Conformance, ConformanceRule, Document, ParseOptions};
+//!
+//! let options = ParseOptions::new().conformance(Conformance::Strict11);
+//! let error = Document::parse_with_options("data_a\n_x $ref\n", &options).unwrap_err();
+//! assert!(matches!(
+//!     error,
+//!     CifError::NonConformant { rule: ConformanceRule::ReservedWord, location: Some((2, 4)), .. }
+//! ));
+//!
+//! // The same file parses by default, and can be checked afterwards
+//! let doc = Document::parse("data_a\n_x $ref\n_X 1\n").unwrap();
+//! let errors = doc.check_conformance(Conformance::Strict11);
+//! assert_eq!(errors.len(), 1);
+//! assert!(errors[0].to_string().contains("CIF 1.1 syntax, data names"));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue};
+use crate::error::CifError;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+
+/// Longest line CIF 1.1 allows, in characters
+pub const MAX_LINE_LENGTH: usize = 2048;
+
+/// Longest data name, data block name, or save frame name CIF 1.1 allows,
+/// in characters
+pub const MAX_NAME_LENGTH: usize = 75;
+
+/// How closely parsing follows the specification (see
+/// [`ParseOptions::conformance`](crate::ParseOptions::conformance)).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Conformance {
+    /// Accept what the parser can read; the other options decide the rest
+    #[default]
+    Unchecked,
+    /// Reject anything the CIF 1.1 syntax specification forbids
+    Strict11,
+}
+
+/// A rule of the CIF 1.1 syntax specification, broken by a
+/// [`CifError::NonConformant`] error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConformanceRule {
+    /// A line is longer than [`MAX_LINE_LENGTH`]
+    LineLength,
+    /// A data name is longer than [`MAX_NAME_LENGTH`]
+    TagLength,
+    /// A character outside printable ASCII, tab, and line terminators
+    Ascii,
+    /// A data block or save frame name is empty, too long, contains
+    /// whitespace, or is used twice
+    ContainerName,
+    /// A data name appears twice in a block or save frame
+    DuplicateTag,
+    /// A value CIF 1.1 cannot write without changing it
+    LossyValue,
+    /// An unquoted value starts with a reserved character
+    ReservedWord,
+}
+
+impl ConformanceRule {
+    /// The clause of the CIF 1.1 syntax specification the rule comes from,
+    /// as given at the end of the error message
+    pub fn clause(self) -> &'static str {
+        match self {
+            ConformanceRule::LineLength => "CIF 1.1 syntax, line length: at most 2048 characters",
+            ConformanceRule::TagLength => "CIF 1.1 syntax, data names: at most 75 characters",
+            ConformanceRule::Ascii => {
+                "CIF 1.1 syntax, character set: printable ASCII, tab, and newline only"
+            }
+            ConformanceRule::ContainerName => {
+                "CIF 1.1 syntax, data block and save frame names: unique, \
+                 1 to 75 non-blank characters"
+            }
+            ConformanceRule::DuplicateTag => {
+                "CIF 1.1 syntax, data names: unique within a data block or save frame, \
+                 ignoring case"
+            }
+            ConformanceRule::LossyValue => {
+                "CIF 1.1 syntax, values: no lists or tables, and no line of a text field \
+                 may start with a semicolon"
+            }
+            ConformanceRule::ReservedWord => {
+                "CIF 1.1 syntax, reserved characters: an unquoted value may not start \
+                 with '$'"
+            }
+        }
+    }
+}
+
+/// Whether CIF 1.1 allows `c` anywhere in a file
+fn is_cif11_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | ' '..='~')
+}
+
+/// The first character CIF 1.1 does not allow
+fn first_non_ascii(text: &str) -> Option<char> {
+    text.chars().find(|&c| !is_cif11_char(c))
+}
+
+fn non_ascii_error(what: &str, c: char) -> CifError {
+    CifError::non_conformant(
+        ConformanceRule::Ascii,
+        format!("{what} has the character U+{:04X}", u32::from(c)),
+    )
+}
+
+/// The first line longer than CIF 1.1 allows, or character it does not
+/// allow, in the raw input
+pub(crate) fn check_lines(input: &str) -> Result<(), CifError> {
+    let mut line = 1;
+    let mut column = 0;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            line += 1;
+            column = 0;
+            continue;
+        }
+        column += 1;
+        if !is_cif11_char(c) {
+            return Err(non_ascii_error("Line", c).at_location(line, column));
+        }
+        if column == MAX_LINE_LENGTH + 1 {
+            let length = column
+                + chars
+                    .clone()
+                    .take_while(|&c| c != '\n' && c != '\r')
+                    .count();
+            return Err(CifError::non_conformant(
+                ConformanceRule::LineLength,
+                format!("Line has {length} characters"),
+            )
+            .at_location(line, column));
+        }
+    }
+    Ok(())
+}
+
+/// Why CIF 1.1 does not allow `tag` as a data name, if it does not
+pub(crate) fn tag_error(tag: &str) -> Option<CifError> {
+    if let Some(c) = first_non_ascii(tag) {
+        return Some(non_ascii_error(&format!("Data name '{tag}'"), c));
+    }
+    let length = tag.chars().count();
+    (length > MAX_NAME_LENGTH).then(|| {
+        CifError::non_conformant(
+            ConformanceRule::TagLength,
+            format!("Data name '{tag}' has {length} characters"),
+        )
+    })
+}
+
+/// Why CIF 1.1 does not allow `name` for a data block or, with `frame`, a
+/// save frame, if it does not
+pub(crate) fn name_error(name: &str, frame: bool) -> Option<CifError> {
+    let what = if frame { "Save frame" } else { "Data block" };
+    if let Some(c) = first_non_ascii(name) {
+        return Some(non_ascii_error(&format!("{what} name '{name}'"), c));
+    }
+    let length = name.chars().count();
+    let problem = if name.is_empty() {
+        format!("{what} has no name")
+    } else if length > MAX_NAME_LENGTH {
+        format!("{what} name '{name}' has {length} characters")
+    } else if name.contains(char::is_whitespace) {
+        format!("{what} name '{name}' contains whitespace")
+    } else {
+        return None;
+    };
+    Some(CifError::non_conformant(
+        ConformanceRule::ContainerName,
+        problem,
+    ))
+}
+
+/// Why an unquoted value cannot be read as written under CIF 1.1, if it
+/// cannot
+pub(crate) fn unquoted_error(token: &str) -> Option<CifError> {
+    token.starts_with('$').then(|| {
+        CifError::non_conformant(
+            ConformanceRule::ReservedWord,
+            format!("Unquoted value '{token}' starts with '$'"),
+        )
+    })
+}
+
+/// Why CIF 1.1 cannot hold `value`, the value of `tag`, if it cannot
+fn value_error(tag: &str, value: &CifValue) -> Option<CifError> {
+    let text = match value {
+        CifValue::Text(text) => text.as_str(),
+        CifValue::LargeText(text) => text,
+        CifValue::List(_) => return Some(lossy(tag, "is a list")),
+        CifValue::Table(_) => return Some(lossy(tag, "is a table")),
+        CifValue::Numeric(_)
+        | CifValue::NumericSu { .. }
+        | CifValue::Unknown
+        | CifValue::NotApplicable => return None,
+    };
+    if let Some(c) = first_non_ascii(text) {
+        return Some(non_ascii_error(&format!("Value of '{tag}'"), c));
+    }
+    if let Some(line) = text
+        .lines()
+        .find(|line| line.chars().count() > MAX_LINE_LENGTH)
+    {
+        return Some(CifError::non_conformant(
+            ConformanceRule::LineLength,
+            format!(
+                "Value of '{tag}' has a line of {} characters",
+                line.chars().count()
+            ),
+        ));
+    }
+    text.lines()
+        .skip(1)
+        .any(|line| line.starts_with(';'))
+        .then(|| {
+            lossy(
+                tag,
+                "has a line starting with ';', which would end its text field",
+            )
+        })
+}
+
+fn lossy(tag: &str, problem: &str) -> CifError {
+    CifError::non_conformant(
+        ConformanceRule::LossyValue,
+        format!("Value of '{tag}' {problem}"),
+    )
+}
+
+/// Violations in the data names and values of one block or frame
+fn check_container(
+    container: &str,
+    items: &IndexMap<String, CifValue>,
+    loops: &[CifLoop],
+    errors: &mut Vec<CifError>,
+) {
+    let mut seen = HashSet::new();
+    let tags = items.keys().chain(loops.iter().flat_map(|l| &l.tags));
+    for tag in tags {
+        errors.extend(tag_error(tag));
+        if !seen.insert(tag.to_lowercase()) {
+            errors.push(CifError::non_conformant(
+                ConformanceRule::DuplicateTag,
+                format!("Data name '{tag}' appears twice in {container}"),
+            ));
+        }
+    }
+    for (tag, value) in items {
+        errors.extend(value_error(tag, value));
+    }
+    for loop_ in loops {
+        for row in &loop_.values {
+            for (tag, value) in loop_.tags.iter().zip(row) {
+                errors.extend(value_error(tag, value));
+            }
+        }
+    }
+}
+
+/// The names used more than once, ignoring case, each given once as first
+/// repeated
+fn repeated_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    names
+        .filter(|name| {
+            let lower = name.to_lowercase();
+            !seen.insert(lower.clone()) && reported.insert(lower)
+        })
+        .collect()
+}
+
+fn check_frames(block: &CifBlock, errors: &mut Vec<CifError>) {
+    for frame in &block.frames {
+        errors.extend(name_error(&frame.name, true));
+        let CifFrame { name, items, loops } = frame;
+        check_container(&format!("save_{name}"), items, loops, errors);
+    }
+    for name in repeated_names(block.frames.iter().map(|f| f.name.as_str())) {
+        errors.push(CifError::non_conformant(
+            ConformanceRule::ContainerName,
+            format!(
+                "Save frame name '{name}' is used twice in data_{}",
+                block.name
+            ),
+        ));
+    }
+}
+
+impl CifDocument {
+    /// Every way the document breaks `conformance`, in document order.
+    ///
+    /// Under [`Conformance::Strict11`] these are the names, data names, and
+    /// values CIF 1.1 does not allow or cannot write; a document with none
+    /// can be written as valid CIF 1.1. The errors are all
+    /// [`CifError::NonConformant`], without locations. Under
+    /// [`Conformance::Unchecked`] the list is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Block, CifError, Conformance, ConformanceRule, Document, Value};
+    ///
+    /// let mut doc = Document::new();
+    /// let mut block = Block::new("sample".to_string());
+    /// block.items.insert("_title".to_string(), Value::Text("Café".to_string()));
+    /// doc.blocks.push(block);
+    ///
+    /// let errors = doc.check_conformance(Conformance::Strict11);
+    /// assert_eq!(errors.len(), 1);
+    /// assert!(matches!(
+    ///     errors[0],
+    ///     CifError::NonConformant { rule: ConformanceRule::Ascii, .. }
+    /// ));
+    /// ```
+    pub fn check_conformance(&self, conformance: Conformance) -> Vec<CifError> {
+        let mut errors = Vec::new();
+        if conformance == Conformance::Unchecked {
+            return errors;
+        }
+        for block in &self.blocks {
+            errors.extend(name_error(&block.name, false));
+            let container = format!("data_{}", block.name);
+            check_container(&container, &block.items, &block.loops, &mut errors);
+            check_frames(block, &mut errors);
+        }
+        for name in repeated_names(self.blocks.iter().map(|b| b.name.as_str())) {
+            errors.push(CifError::non_conformant(
+                ConformanceRule::ContainerName,
+                format!("Data block name '{name}' is used twice"),
+            ));
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_lines() {
+        assert!(check_lines("data_a\r\n_x 'tab\tok'\r\n").is_ok());
+        let long = format!("data_a\n_x {}\n", "y".repeat(MAX_LINE_LENGTH));
+        let error = check_lines(&long).unwrap_err();
+        assert_eq!(error.location(), Some((2, MAX_LINE_LENGTH + 1)));
+        assert!(error.to_string().contains("Line has 2051 characters"));
+        assert!(check_lines(&long[..long.len() - 4]).is_ok());
+    }
+
+    #[test]
+    fn test_repeated_names() {
+        assert_eq!(
+            repeated_names(["a", "B", "A", "b", "a"].into_iter()),
+            ["A", "b"]
+        );
+    }
+}
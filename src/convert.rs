@@ -0,0 +1,1409 @@
+//! Translation between PDBx/mmCIF and core CIF descriptions of a structure.
+//!
+//! [`mmcif_to_core`] and [`core_to_mmcif`] translate the structural subset of
+//! a data block: unit cell, symmetry and space group, atom sites, anisotropic
+//! displacement parameters, and chemical formula. Anything else in the block
+//! is listed in a [`ConversionReport`] rather than dropped silently.
+//!
+//! # Coordinates
+//!
+//! mmCIF gives Cartesian coordinates (`_atom_site.Cartn_x`) in the standard
+//! PDB frame, with **a** along x and **b** in the xy plane. They are converted
+//! to fractional coordinates with the unit cell, or with
+//! `_atom_sites.fract_transf_matrix` when the block has no cell (the matrix is
+//! usually rounded to fewer digits than the cell). Anisotropic displacement
+//! parameters are transformed between the Cartesian frame of mmCIF and the
+//! reciprocal-axis frame of core CIF in the same way.
+//!
+//! Standard uncertainties of items that are copied across (cell lengths,
+//! occupancies, ...) move between the `1.234(5)` notation of core CIF and the
+//! `_esd` items of mmCIF. Those of transformed quantities (coordinates and
+//! anisotropic parameters) are reported, not converted.
+//!
+//! # Atom labels
+//!
+//! mmCIF identifies an atom by atom name, residue, sequence number, chain,
+//! and alternate location, most of them twice (`label_*` ids and author
+//! `auth_*` ids). Core CIF has a single `_atom_site_label`, so these are
+//! collapsed into `atom_RESseq_chain[_alt]` (e.g. `CA_ALA12_A`), preferring
+//! the author ids used in the literature. The reverse direction splits labels
+//! of this form back into ids; any other label becomes the atom name.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_1ABC
+//! _entry.id 1ABC
+//! _cell.length_a 10.0
+//! _cell.length_a_esd 0.002
+//! _cell.length_b 10.0
+//! _cell.length_c 10.0
+//! _cell.angle_alpha 90
+//! _cell.angle_beta 90
+//! _cell.angle_gamma 90
+//! loop_
+//! _atom_site.id
+//! _atom_site.type_symbol
+//! _atom_site.label_atom_id
+//! _atom_site.label_comp_id
+//! _atom_site.label_asym_id
+//! _atom_site.label_seq_id
+//! _atom_site.Cartn_x
+//! _atom_site.Cartn_y
+//! _atom_site.Cartn_z
+//! 1 N N GLY A 1 1.0 2.0 3.0
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let core = doc.blocks[0].to_core_cif().unwrap();
+//!
+//! assert_eq!(
+//!     core.get_item("_cell_length_a").unwrap().as_numeric_with_su(),
+//!     Some((10.0, Some(0.002)))
+//! );
+//! let atoms = core.find_loop("_atom_site_label").unwrap();
+//! assert_eq!(atoms.get_by_tag(0, "_atom_site_label").unwrap().as_string(), Some("N_GLY1_A"));
+//! assert_eq!(atoms.get_by_tag(0, "_atom_site_fract_z").unwrap().as_numeric(), Some(0.3));
+//! ```
+
+use crate::ast::{CifBlock, CifLoop, CifValue};
+use crate::category::category_of;
+use crate::cell::{apply, invert, multiply, transpose, Mat3, UnitCell};
+use crate::uncertainty;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// One tag, or a group of tags, that could not be translated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionIssue {
+    /// Tag in the input block, as written
+    pub tag: String,
+    /// Why it was not translated
+    pub reason: String,
+}
+
+impl fmt::Display for ConversionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.tag, self.reason)
+    }
+}
+
+/// The result of a conversion that could not translate everything.
+///
+/// The block holds everything that was translated, so callers that accept a
+/// partial result can use it directly.
+#[derive(Debug, Clone)]
+pub struct ConversionReport {
+    /// The translated part of the input
+    pub block: Box<CifBlock>,
+    /// Input tags that were not translated, in file order where known
+    pub issues: Vec<ConversionIssue>,
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} tag(s) in block '{}' not translated",
+            self.issues.len(),
+            self.block.name
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConversionReport {}
+
+impl CifBlock {
+    /// Translate an mmCIF structure into core CIF tags (see [`mmcif_to_core`])
+    pub fn to_core_cif(&self) -> Result<CifBlock, ConversionReport> {
+        mmcif_to_core(self)
+    }
+
+    /// Translate a core CIF structure into mmCIF tags (see [`core_to_mmcif`])
+    pub fn to_mmcif(&self) -> Result<CifBlock, ConversionReport> {
+        core_to_mmcif(self)
+    }
+}
+
+/// Translate the structure in an mmCIF block into core CIF tags.
+///
+/// Only the first model of a multi-model block is translated.
+///
+/// # Errors
+///
+/// Returns a [`ConversionReport`], holding the translated block, when any
+/// tag in the input could not be translated.
+pub fn mmcif_to_core(block: &CifBlock) -> Result<CifBlock, ConversionReport> {
+    let direction = Direction::ToCore;
+    let mut src = Source::new(block);
+    let mut out = CifBlock::new(block.name.clone());
+
+    if let Some(entry) = src.value("_entry.id") {
+        if text(entry).is_some_and(|id| id.eq_ignore_ascii_case(&block.name)) {
+            src.mark("_entry.id");
+        }
+    }
+    for tag in [
+        "_cell.entry_id",
+        "_symmetry.entry_id",
+        "_atom_sites.entry_id",
+    ] {
+        src.mark(tag);
+    }
+
+    let basis = Basis::from_cell(&src, direction).or_else(|| Basis::from_transf_matrix(&src));
+    for tag in transf_tags() {
+        src.mark(&tag);
+    }
+    for group in SIMPLE_GROUPS {
+        translate_simple(&mut src, &mut out, group, direction);
+    }
+    let ids = atoms_to_core(&mut src, &mut out, basis.as_ref());
+    aniso_to_core(&mut src, &mut out, basis.as_ref(), &ids);
+
+    src.finish(out, direction)
+}
+
+/// Translate the structure in a core CIF block into mmCIF tags.
+///
+/// The block name becomes `_entry.id`, and atoms are written as a single
+/// model.
+///
+/// # Errors
+///
+/// Returns a [`ConversionReport`], holding the translated block, when any
+/// tag in the input could not be translated.
+pub fn core_to_mmcif(block: &CifBlock) -> Result<CifBlock, ConversionReport> {
+    let direction = Direction::ToMmcif;
+    let mut src = Source::new(block);
+    let mut out = CifBlock::new(block.name.clone());
+    let entry_id = || CifValue::Text(block.name.clone());
+
+    out.items.insert("_entry.id".to_string(), entry_id());
+    let basis = Basis::from_cell(&src, direction);
+    for group in SIMPLE_GROUPS {
+        let looped = translate_simple(&mut src, &mut out, group, direction);
+        if looped == Some(false) {
+            if let Some(category) = group[0].1.split_once('.').map(|(c, _)| c) {
+                if category == "_cell" || category == "_symmetry" {
+                    out.items.insert(format!("{category}.entry_id"), entry_id());
+                }
+            }
+        }
+    }
+    if let Some(basis) = &basis {
+        out.items
+            .insert("_atom_sites.entry_id".to_string(), entry_id());
+        for (i, row) in basis.frac.iter().enumerate() {
+            for (j, &m) in row.iter().enumerate() {
+                out.items.insert(
+                    format!("_atom_sites.fract_transf_matrix[{}][{}]", i + 1, j + 1),
+                    CifValue::Numeric(round(m, 6)),
+                );
+            }
+            out.items.insert(
+                format!("_atom_sites.fract_transf_vector[{}]", i + 1),
+                CifValue::Numeric(basis.shift[i]),
+            );
+        }
+    }
+    let ids = atoms_to_mmcif(&mut src, &mut out, basis.as_ref());
+    aniso_to_mmcif(&mut src, &mut out, basis.as_ref(), &ids);
+
+    src.finish(out, direction)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    ToCore,
+    ToMmcif,
+}
+
+impl Direction {
+    /// The (input, output) tags of a (core, mmCIF) pair
+    fn pair<'t>(self, core: &'t str, mmcif: &'t str) -> (&'t str, &'t str) {
+        match self {
+            Direction::ToCore => (mmcif, core),
+            Direction::ToMmcif => (core, mmcif),
+        }
+    }
+
+    fn target(self) -> &'static str {
+        match self {
+            Direction::ToCore => "core CIF",
+            Direction::ToMmcif => "mmCIF",
+        }
+    }
+}
+
+/// A (core, mmCIF, may carry an su) tag pair translated value for value
+type Pair = (&'static str, &'static str, bool);
+
+/// Categories translated value for value, as single items or loops
+const SIMPLE_GROUPS: &[&[Pair]] = &[
+    &[
+        ("_cell_length_a", "_cell.length_a", true),
+        ("_cell_length_b", "_cell.length_b", true),
+        ("_cell_length_c", "_cell.length_c", true),
+        ("_cell_angle_alpha", "_cell.angle_alpha", true),
+        ("_cell_angle_beta", "_cell.angle_beta", true),
+        ("_cell_angle_gamma", "_cell.angle_gamma", true),
+        ("_cell_volume", "_cell.volume", true),
+        ("_cell_formula_units_Z", "_cell.Z_PDB", false),
+    ],
+    &[
+        (
+            "_symmetry_space_group_name_H-M",
+            "_symmetry.space_group_name_H-M",
+            false,
+        ),
+        (
+            "_symmetry_space_group_name_Hall",
+            "_symmetry.space_group_name_Hall",
+            false,
+        ),
+        (
+            "_symmetry_Int_Tables_number",
+            "_symmetry.Int_Tables_number",
+            false,
+        ),
+        ("_symmetry_cell_setting", "_symmetry.cell_setting", false),
+    ],
+    &[
+        (
+            "_space_group_name_H-M_alt",
+            "_space_group.name_H-M_alt",
+            false,
+        ),
+        ("_space_group_name_Hall", "_space_group.name_Hall", false),
+        ("_space_group_IT_number", "_space_group.IT_number", false),
+        (
+            "_space_group_crystal_system",
+            "_space_group.crystal_system",
+            false,
+        ),
+    ],
+    &[
+        ("_symmetry_equiv_pos_site_id", "_symmetry_equiv.id", false),
+        (
+            "_symmetry_equiv_pos_as_xyz",
+            "_symmetry_equiv.pos_as_xyz",
+            false,
+        ),
+    ],
+    &[
+        ("_space_group_symop_id", "_space_group_symop.id", false),
+        (
+            "_space_group_symop_operation_xyz",
+            "_space_group_symop.operation_xyz",
+            false,
+        ),
+    ],
+    &[
+        ("_chemical_formula_sum", "_chemical_formula.sum", false),
+        (
+            "_chemical_formula_moiety",
+            "_chemical_formula.moiety",
+            false,
+        ),
+        (
+            "_chemical_formula_weight",
+            "_chemical_formula.weight",
+            false,
+        ),
+    ],
+];
+
+/// Atom site columns translated value for value
+const ATOM_COLUMNS: &[Pair] = &[
+    ("_atom_site_occupancy", "_atom_site.occupancy", true),
+    (
+        "_atom_site_B_iso_or_equiv",
+        "_atom_site.B_iso_or_equiv",
+        true,
+    ),
+    (
+        "_atom_site_U_iso_or_equiv",
+        "_atom_site.U_iso_or_equiv",
+        true,
+    ),
+];
+
+/// Residues written as `ATOM` records; everything else is `HETATM`
+const STANDARD_RESIDUES: &[&str] = &[
+    "ALA", "ARG", "ASN", "ASP", "CYS", "GLN", "GLU", "GLY", "HIS", "ILE", "LEU", "LYS", "MET",
+    "PHE", "PRO", "SER", "THR", "TRP", "TYR", "VAL", "A", "C", "G", "U", "DA", "DC", "DG", "DT",
+];
+
+/// Anisotropic tensor components in core order, with their mmCIF indices
+const TENSOR_COMPONENTS: [(&str, usize, usize); 6] = [
+    ("11", 0, 0),
+    ("22", 1, 1),
+    ("33", 2, 2),
+    ("12", 0, 1),
+    ("13", 0, 2),
+    ("23", 1, 2),
+];
+
+/// mmCIF atom id columns made redundant by the `_atom_site_anisotrop.id` link
+const ANISO_ID_COLUMNS: &[&str] = &[
+    "pdbx_label_atom_id",
+    "pdbx_label_alt_id",
+    "pdbx_label_comp_id",
+    "pdbx_label_asym_id",
+    "pdbx_label_seq_id",
+    "pdbx_PDB_ins_code",
+    "pdbx_auth_atom_id",
+    "pdbx_auth_comp_id",
+    "pdbx_auth_asym_id",
+    "pdbx_auth_seq_id",
+];
+
+/// Rows of one category, from a loop or from single items.
+struct Table<'a> {
+    tags: Vec<&'a str>,
+    rows: Vec<Vec<&'a CifValue>>,
+    looped: bool,
+}
+
+impl<'a> Table<'a> {
+    fn position(&self, tag: &str) -> Option<usize> {
+        self.tags.iter().position(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Read access to the input block that remembers which tags were used.
+struct Source<'a> {
+    block: &'a CifBlock,
+    used: HashSet<String>,
+    issues: Vec<ConversionIssue>,
+}
+
+impl<'a> Source<'a> {
+    fn new(block: &'a CifBlock) -> Self {
+        Source {
+            block,
+            used: HashSet::new(),
+            issues: Vec::new(),
+        }
+    }
+
+    /// The first loop with a matching tag, or else all matching single items
+    fn table(&self, matches: impl Fn(&str) -> bool) -> Option<Table<'a>> {
+        if let Some(loop_) = self
+            .block
+            .loops
+            .iter()
+            .find(|l| l.tags.iter().any(|t| matches(t)))
+        {
+            return Some(Table {
+                tags: loop_.tags.iter().map(String::as_str).collect(),
+                rows: loop_
+                    .values
+                    .iter()
+                    .map(|row| row.iter().collect())
+                    .collect(),
+                looped: true,
+            });
+        }
+
+        let mut items: Vec<(&'a String, &'a CifValue)> = self
+            .block
+            .items
+            .iter()
+            .filter(|(tag, _)| matches(tag))
+            .collect();
+        if items.is_empty() {
+            return None;
+        }
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        Some(Table {
+            tags: items.iter().map(|(tag, _)| tag.as_str()).collect(),
+            rows: vec![items.iter().map(|(_, value)| *value).collect()],
+            looped: false,
+        })
+    }
+
+    /// Take a column of `table`, marking its tag as translated
+    fn take<'t>(&mut self, table: &Table<'t>, tag: &str) -> Option<Vec<&'t CifValue>> {
+        let col = table.position(tag)?;
+        self.mark(tag);
+        Some(table.rows.iter().map(|row| row[col]).collect())
+    }
+
+    /// A value from a single item or the first row of a loop, without marking it
+    fn value(&self, tag: &str) -> Option<&'a CifValue> {
+        self.block
+            .items
+            .iter()
+            .find(|(t, _)| t.eq_ignore_ascii_case(tag))
+            .map(|(_, value)| value)
+            .or_else(|| {
+                self.block.loops.iter().find_map(|l| {
+                    let col = l.tags.iter().position(|t| t.eq_ignore_ascii_case(tag))?;
+                    l.values.first()?.get(col)
+                })
+            })
+    }
+
+    fn mark(&mut self, tag: &str) {
+        self.used.insert(tag.to_ascii_lowercase());
+    }
+
+    /// Record that `tag` was seen but could not be translated
+    fn report(&mut self, tag: &str, reason: impl Into<String>) {
+        self.mark(tag);
+        self.issues.push(ConversionIssue {
+            tag: tag.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    /// Report the column of `table` named `tag`, if there is one
+    fn report_column(&mut self, table: &Table, tag: &str, reason: &str) {
+        if let Some(col) = table.position(tag) {
+            self.report(table.tags[col], reason);
+        }
+    }
+
+    /// Report every tag that was neither translated nor already reported
+    fn finish(
+        mut self,
+        block: CifBlock,
+        direction: Direction,
+    ) -> Result<CifBlock, ConversionReport> {
+        let mut leftovers: Vec<&str> = self
+            .block
+            .items
+            .keys()
+            .map(String::as_str)
+            .filter(|tag| !self.used.contains(&tag.to_ascii_lowercase()))
+            .collect();
+        leftovers.sort_unstable();
+        leftovers.extend(
+            self.block
+                .loops
+                .iter()
+                .flat_map(|l| l.tags.iter().map(String::as_str))
+                .filter(|tag| !self.used.contains(&tag.to_ascii_lowercase())),
+        );
+        let reason = format!("no {} equivalent", direction.target());
+        for tag in leftovers {
+            self.issues.push(ConversionIssue {
+                tag: tag.to_string(),
+                reason: reason.clone(),
+            });
+        }
+        for frame in &self.block.frames {
+            self.issues.push(ConversionIssue {
+                tag: format!("save_{}", frame.name),
+                reason: "save frames are not translated".to_string(),
+            });
+        }
+
+        if self.issues.is_empty() {
+            Ok(block)
+        } else {
+            Err(ConversionReport {
+                block: Box::new(block),
+                issues: self.issues,
+            })
+        }
+    }
+}
+
+/// Translated columns, written as items when the input had single items
+fn emit(out: &mut CifBlock, looped: bool, columns: Vec<(String, Vec<CifValue>)>) {
+    let rows = columns.first().map_or(0, |(_, values)| values.len());
+    if columns.is_empty() {
+        return;
+    }
+    if !looped && rows == 1 {
+        for (tag, mut values) in columns {
+            out.items.insert(tag, values.remove(0));
+        }
+        return;
+    }
+
+    let mut loop_ = CifLoop::new(Vec::with_capacity(columns.len()));
+    let mut values: Vec<Vec<CifValue>> = vec![Vec::with_capacity(columns.len()); rows];
+    for (tag, column) in columns {
+        loop_.tags.push(tag);
+        for (row, value) in values.iter_mut().zip(column) {
+            row.push(value);
+        }
+    }
+    loop_.values = values;
+    out.loops.push(loop_);
+}
+
+/// Translate one column of `rows`, moving standard uncertainties between
+/// `1.234(5)` values in core CIF and a separate `_esd` column in mmCIF
+fn translate_column(
+    src: &mut Source,
+    table: &Table,
+    rows: &[usize],
+    (core, mmcif, su): Pair,
+    direction: Direction,
+) -> Vec<(String, Vec<CifValue>)> {
+    let (from, to) = direction.pair(core, mmcif);
+    let Some(values) = src.take(table, from) else {
+        return Vec::new();
+    };
+    let values: Vec<&CifValue> = rows.iter().map(|&row| values[row]).collect();
+    if !su {
+        return vec![(to.to_string(), values.into_iter().cloned().collect())];
+    }
+
+    match direction {
+        Direction::ToCore => {
+            let esd = src.take(table, &format!("{from}_esd"));
+            let merged = values
+                .iter()
+                .zip(rows)
+                .map(|(value, &row)| {
+                    match (
+                        value.as_numeric(),
+                        esd.as_ref().and_then(|e| e[row].as_numeric()),
+                    ) {
+                        // Rounded by the rule of 19, as CIF writes an su
+                        (Some(v), Some(su)) if su > 0.0 => {
+                            CifValue::parse_value(&uncertainty::format(v, su))
+                        }
+                        _ => (*value).clone(),
+                    }
+                })
+                .collect();
+            vec![(to.to_string(), merged)]
+        }
+        Direction::ToMmcif => {
+            let (numbers, sus): (Vec<CifValue>, Vec<CifValue>) = values
+                .iter()
+                .map(|value| match value.as_numeric_with_su() {
+                    Some((v, su)) => (
+                        CifValue::Numeric(v),
+                        su.map_or(CifValue::Unknown, CifValue::Numeric),
+                    ),
+                    None => ((*value).clone(), CifValue::Unknown),
+                })
+                .unzip();
+            let mut columns = vec![(to.to_string(), numbers)];
+            if sus.iter().any(|su| *su != CifValue::Unknown) {
+                columns.push((format!("{to}_esd"), sus));
+            }
+            columns
+        }
+    }
+}
+
+/// Translate one category of value-for-value pairs; returns whether it was looped
+fn translate_simple(
+    src: &mut Source,
+    out: &mut CifBlock,
+    group: &[Pair],
+    direction: Direction,
+) -> Option<bool> {
+    let table = src.table(|tag| {
+        group.iter().any(|&(core, mmcif, su)| {
+            let from = direction.pair(core, mmcif).0;
+            tag.eq_ignore_ascii_case(from)
+                || (su
+                    && direction == Direction::ToCore
+                    && tag.len() == from.len() + 4
+                    && tag[..from.len()].eq_ignore_ascii_case(from)
+                    && tag[from.len()..].eq_ignore_ascii_case("_esd"))
+        })
+    })?;
+    let rows: Vec<usize> = (0..table.rows.len()).collect();
+    let columns = group
+        .iter()
+        .flat_map(|&pair| translate_column(src, &table, &rows, pair, direction))
+        .collect();
+    emit(out, table.looped, columns);
+    Some(table.looped)
+}
+
+/// mmCIF ids of one atom site
+#[derive(Debug, Default, PartialEq)]
+struct AtomIds {
+    atom: String,
+    residue: Option<String>,
+    seq: Option<String>,
+    chain: Option<String>,
+    alt: Option<String>,
+}
+
+impl AtomIds {
+    /// Collapse into a core CIF label: `atom_RESseq_chain[_alt]`
+    fn label(&self) -> String {
+        let mut label = self.atom.clone();
+        if let (Some(residue), Some(chain)) = (&self.residue, &self.chain) {
+            label.push('_');
+            label.push_str(residue);
+            label.push_str(self.seq.as_deref().unwrap_or(""));
+            label.push('_');
+            label.push_str(chain);
+            if let Some(alt) = &self.alt {
+                label.push('_');
+                label.push_str(alt);
+            }
+        }
+        label
+    }
+
+    /// Split a label written by [`AtomIds::label`]; other labels are atom names
+    fn from_label(label: &str) -> Self {
+        let parts: Vec<&str> = label.split('_').collect();
+        if let [atom, residue, chain, rest @ ..] = parts.as_slice() {
+            let split = residue
+                .find(|c: char| c.is_ascii_digit() || c == '-')
+                .filter(|&i| i > 0 && residue[..i].bytes().all(|b| b.is_ascii_alphanumeric()));
+            if let (Some(split), true) = (split, rest.len() <= 1) {
+                let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+                return AtomIds {
+                    atom: atom.to_string(),
+                    residue: Some(residue[..split].to_string()),
+                    seq: non_empty(&residue[split..]),
+                    chain: non_empty(chain),
+                    alt: rest.first().and_then(|alt| non_empty(alt)),
+                };
+            }
+        }
+        AtomIds {
+            atom: label.to_string(),
+            ..AtomIds::default()
+        }
+    }
+}
+
+/// Translate `_atom_site.*`; returns the core label of each `_atom_site.id`
+fn atoms_to_core(
+    src: &mut Source,
+    out: &mut CifBlock,
+    basis: Option<&Basis>,
+) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+    let Some(table) = src.table(|tag| has_prefix(tag, "_atom_site.")) else {
+        return labels;
+    };
+
+    let mut rows: Vec<usize> = (0..table.rows.len()).collect();
+    if let Some(models) = src.take(&table, "_atom_site.pdbx_PDB_model_num") {
+        if let Some(&first) = models.first() {
+            rows.retain(|&row| models[row] == first);
+            let dropped = models.len() - rows.len();
+            if dropped > 0 {
+                let first = text(first).unwrap_or_default();
+                src.report(
+                    "_atom_site.pdbx_PDB_model_num",
+                    format!("only model {first} translated; {dropped} atom sites in other models dropped"),
+                );
+            }
+        }
+    }
+
+    let mut column = |name: &str| src.take(&table, &format!("_atom_site.{name}"));
+    let id = column("id");
+    let group = column("group_PDB");
+    let atom = [column("auth_atom_id"), column("label_atom_id")];
+    let residue = [column("auth_comp_id"), column("label_comp_id")];
+    let seq = [column("auth_seq_id"), column("label_seq_id")];
+    let chain = [column("auth_asym_id"), column("label_asym_id")];
+    let alt = [column("pdbx_auth_alt_id"), column("label_alt_id")];
+    let insertion = [column("pdbx_PDB_ins_code")];
+    let type_symbol = column("type_symbol");
+    // The first known id of an atom: author ids are preferred
+    let pick = |columns: &[Option<Vec<&CifValue>>], row: usize| {
+        columns
+            .iter()
+            .flatten()
+            .find_map(|column| text(column[row]))
+    };
+
+    let mut seen = HashSet::new();
+    let mut label_column = Vec::with_capacity(rows.len());
+    let mut mismatched_group = false;
+    for &row in &rows {
+        let serial = id.as_ref().and_then(|c| text(c[row]));
+        let ids = AtomIds {
+            atom: pick(&atom, row)
+                .or_else(|| serial.clone())
+                .unwrap_or_else(|| (row + 1).to_string()),
+            seq: match (pick(&seq, row), pick(&insertion, row)) {
+                (Some(seq), Some(ins)) => Some(seq + &ins),
+                (seq, _) => seq,
+            },
+            residue: pick(&residue, row),
+            chain: pick(&chain, row),
+            alt: pick(&alt, row),
+        };
+        if let (Some(group), Some(residue)) = (&group, &ids.residue) {
+            mismatched_group |= text(group[row]).as_deref() != Some(record_type(residue));
+        }
+
+        let mut label = ids.label();
+        if !seen.insert(label.clone()) {
+            label = format!(
+                "{label}_{}",
+                serial.clone().unwrap_or_else(|| (row + 1).to_string())
+            );
+            seen.insert(label.clone());
+        }
+        if let Some(serial) = serial {
+            labels.insert(serial, label.clone());
+        }
+        label_column.push(CifValue::Text(label));
+    }
+    if mismatched_group {
+        src.report(
+            "_atom_site.group_PDB",
+            "ATOM/HETATM record types that differ from the residue type have no core CIF equivalent",
+        );
+    }
+
+    let mut columns = vec![("_atom_site_label".to_string(), label_column)];
+    if let Some(symbols) = type_symbol {
+        let symbols = rows.iter().map(|&row| symbols[row].clone()).collect();
+        columns.push(("_atom_site_type_symbol".to_string(), symbols));
+    }
+    columns.extend(coordinates_to_core(src, &table, &rows, basis));
+    for &pair in ATOM_COLUMNS {
+        columns.extend(translate_column(
+            src,
+            &table,
+            &rows,
+            pair,
+            Direction::ToCore,
+        ));
+    }
+    emit(out, table.looped, columns);
+    labels
+}
+
+/// Fractional coordinate columns from mmCIF fractional or Cartesian coordinates
+fn coordinates_to_core(
+    src: &mut Source,
+    table: &Table,
+    rows: &[usize],
+    basis: Option<&Basis>,
+) -> Vec<(String, Vec<CifValue>)> {
+    let fract: [Pair; 3] = [
+        ("_atom_site_fract_x", "_atom_site.fract_x", true),
+        ("_atom_site_fract_y", "_atom_site.fract_y", true),
+        ("_atom_site_fract_z", "_atom_site.fract_z", true),
+    ];
+    if fract
+        .iter()
+        .all(|&(_, mmcif, _)| table.position(mmcif).is_some())
+    {
+        return fract
+            .iter()
+            .flat_map(|&pair| translate_column(src, table, rows, pair, Direction::ToCore))
+            .collect();
+    }
+
+    let tags = ["x", "y", "z"].map(|a| format!("_atom_site.Cartn_{a}"));
+    let Some(basis) = basis else {
+        for tag in &tags {
+            src.report_column(table, tag, "no unit cell to convert Cartesian coordinates");
+        }
+        return Vec::new();
+    };
+    let Some(cartesian) = tags
+        .iter()
+        .map(|tag| src.take(table, tag))
+        .collect::<Option<Vec<_>>>()
+    else {
+        for tag in &tags {
+            src.report_column(table, tag, "incomplete Cartesian coordinates");
+        }
+        return Vec::new();
+    };
+    for tag in &tags {
+        src.report_column(
+            table,
+            &format!("{tag}_esd"),
+            "standard uncertainties of transformed coordinates are not converted",
+        );
+    }
+
+    let mut fract_values: [Vec<CifValue>; 3] = Default::default();
+    for &row in rows {
+        let point = [0, 1, 2].map(|axis| cartesian[axis][row].as_numeric());
+        let converted = match point {
+            [Some(x), Some(y), Some(z)] => basis
+                .to_fractional([x, y, z])
+                .map(|f| CifValue::Numeric(round(f, 7))),
+            _ => [CifValue::Unknown, CifValue::Unknown, CifValue::Unknown],
+        };
+        for (column, value) in fract_values.iter_mut().zip(converted) {
+            column.push(value);
+        }
+    }
+    fract
+        .map(|(core, _, _)| core.to_string())
+        .into_iter()
+        .zip(fract_values)
+        .collect()
+}
+
+/// Translate `_atom_site_anisotrop.*`, linked to atom sites by id
+fn aniso_to_core(
+    src: &mut Source,
+    out: &mut CifBlock,
+    basis: Option<&Basis>,
+    labels: &HashMap<String, String>,
+) {
+    let Some(table) = src.table(|tag| has_prefix(tag, "_atom_site_anisotrop.")) else {
+        return;
+    };
+    let Some(ids) = src.take(&table, "_atom_site_anisotrop.id") else {
+        return;
+    };
+    for column in ANISO_ID_COLUMNS {
+        src.take(&table, &format!("_atom_site_anisotrop.{column}"));
+    }
+
+    let rows: Vec<usize> = (0..ids.len())
+        .filter(|&row| text(ids[row]).is_some_and(|id| labels.contains_key(&id)))
+        .collect();
+    if rows.len() < ids.len() {
+        src.report(
+            table.tags[table.position("_atom_site_anisotrop.id").unwrap_or(0)],
+            format!(
+                "{} rows refer to atom sites that were not translated",
+                ids.len() - rows.len()
+            ),
+        );
+    }
+
+    let mut columns = vec![(
+        "_atom_site_aniso_label".to_string(),
+        rows.iter()
+            .map(|&row| CifValue::Text(labels[&text(ids[row]).unwrap_or_default()].clone()))
+            .collect(),
+    )];
+    if let Some(symbols) = src.take(&table, "_atom_site_anisotrop.type_symbol") {
+        let symbols = rows.iter().map(|&row| symbols[row].clone()).collect();
+        columns.push(("_atom_site_aniso_type_symbol".to_string(), symbols));
+    }
+    for kind in ["U", "B"] {
+        let tags = TENSOR_COMPONENTS
+            .map(|(_, i, j)| format!("_atom_site_anisotrop.{kind}[{}][{}]", i + 1, j + 1));
+        if tags.iter().all(|tag| table.position(tag).is_none()) {
+            continue;
+        }
+        for tag in &tags {
+            src.report_column(
+                &table,
+                &format!("{tag}_esd"),
+                "standard uncertainties of transformed displacement parameters are not converted",
+            );
+        }
+        let Some(basis) = basis else {
+            for tag in &tags {
+                src.report_column(
+                    &table,
+                    tag,
+                    "no unit cell to transform displacement parameters",
+                );
+            }
+            continue;
+        };
+        let Some(cartesian) = tags
+            .iter()
+            .map(|tag| src.take(&table, tag))
+            .collect::<Option<Vec<_>>>()
+        else {
+            for tag in &tags {
+                src.report_column(&table, tag, "incomplete anisotropic displacement tensor");
+            }
+            continue;
+        };
+
+        let tensors = rows.iter().map(|&row| {
+            let components = [0, 1, 2, 3, 4, 5].map(|c| cartesian[c][row].as_numeric());
+            components
+                .iter()
+                .all(Option::is_some)
+                .then(|| basis.adp_to_core(symmetric(components.map(Option::unwrap_or_default))))
+        });
+        let names = TENSOR_COMPONENTS.map(|(name, _, _)| format!("_atom_site_aniso_{kind}_{name}"));
+        columns.extend(tensor_columns(names, tensors));
+    }
+    emit(out, true, columns);
+}
+
+/// Columns of tensor components, in [`TENSOR_COMPONENTS`] order
+fn tensor_columns(
+    tags: [String; 6],
+    tensors: impl Iterator<Item = Option<Mat3>>,
+) -> Vec<(String, Vec<CifValue>)> {
+    let mut columns = tags.map(|tag| (tag, Vec::new()));
+    for tensor in tensors {
+        for ((_, values), &(_, i, j)) in columns.iter_mut().zip(&TENSOR_COMPONENTS) {
+            values.push(tensor.map_or(CifValue::Unknown, |t| CifValue::Numeric(round(t[i][j], 6))));
+        }
+    }
+    columns.into()
+}
+
+/// Translate `_atom_site_*`; returns the mmCIF id of each core label
+fn atoms_to_mmcif(
+    src: &mut Source,
+    out: &mut CifBlock,
+    basis: Option<&Basis>,
+) -> HashMap<String, String> {
+    let mut ids = HashMap::new();
+    let Some(table) = src.table(|tag| category_of(tag) == "_atom_site") else {
+        return ids;
+    };
+    let all_rows: Vec<usize> = (0..table.rows.len()).collect();
+
+    let labels = src.take(&table, "_atom_site_label");
+    let type_symbol = src.take(&table, "_atom_site_type_symbol");
+    let atoms: Vec<AtomIds> = all_rows
+        .iter()
+        .map(|&row| {
+            let label = labels
+                .as_ref()
+                .and_then(|c| text(c[row]))
+                .unwrap_or_else(|| (row + 1).to_string());
+            ids.insert(label.clone(), (row + 1).to_string());
+            AtomIds::from_label(&label)
+        })
+        .collect();
+
+    let Some(cartesian) = coordinates_to_mmcif(src, &table, basis) else {
+        return ids;
+    };
+
+    let id_column = |f: &dyn Fn(&AtomIds) -> Option<String>, missing: CifValue| -> Vec<CifValue> {
+        atoms
+            .iter()
+            .map(|atom| f(atom).map_or(missing.clone(), CifValue::Text))
+            .collect()
+    };
+    let group = id_column(
+        &|a| Some(record_type(a.residue.as_deref().unwrap_or("")).to_string()),
+        CifValue::Unknown,
+    );
+    let atom = id_column(&|a| Some(a.atom.clone()), CifValue::Unknown);
+    let alt = id_column(&|a| a.alt.clone(), CifValue::NotApplicable);
+    let residue = id_column(&|a| a.residue.clone(), CifValue::Unknown);
+    let chain = id_column(&|a| a.chain.clone(), CifValue::Unknown);
+    let (seq, insertion): (Vec<CifValue>, Vec<CifValue>) = atoms
+        .iter()
+        .map(|a| match &a.seq {
+            Some(seq) => {
+                let split = seq
+                    .char_indices()
+                    .skip(1)
+                    .find(|(_, c)| !c.is_ascii_digit())
+                    .map_or(seq.len(), |(i, _)| i);
+                let number = CifValue::parse_value(&seq[..split]);
+                let code = if split < seq.len() {
+                    CifValue::Text(seq[split..].to_string())
+                } else {
+                    CifValue::Unknown
+                };
+                (number, code)
+            }
+            None => (CifValue::NotApplicable, CifValue::Unknown),
+        })
+        .unzip();
+
+    let tag = |name: &str| format!("_atom_site.{name}");
+    let mut columns = vec![
+        (tag("group_PDB"), group),
+        (
+            tag("id"),
+            all_rows
+                .iter()
+                .map(|&row| CifValue::Numeric((row + 1) as f64))
+                .collect(),
+        ),
+    ];
+    if let Some(symbols) = type_symbol {
+        columns.push((tag("type_symbol"), symbols.into_iter().cloned().collect()));
+    }
+    columns.extend([
+        (tag("label_atom_id"), atom.clone()),
+        (tag("label_alt_id"), alt),
+        (tag("label_comp_id"), residue.clone()),
+        (tag("label_asym_id"), chain.clone()),
+        (tag("label_seq_id"), seq.clone()),
+        (tag("pdbx_PDB_ins_code"), insertion),
+    ]);
+    columns.extend(cartesian);
+    for &pair in ATOM_COLUMNS {
+        columns.extend(translate_column(
+            src,
+            &table,
+            &all_rows,
+            pair,
+            Direction::ToMmcif,
+        ));
+    }
+    columns.extend([
+        (tag("auth_seq_id"), seq),
+        (tag("auth_comp_id"), residue),
+        (tag("auth_asym_id"), chain),
+        (tag("auth_atom_id"), atom),
+        (
+            tag("pdbx_PDB_model_num"),
+            vec![CifValue::Numeric(1.0); all_rows.len()],
+        ),
+    ]);
+    emit(out, true, columns);
+    ids
+}
+
+/// Cartesian coordinate columns from core fractional or Cartesian coordinates
+fn coordinates_to_mmcif(
+    src: &mut Source,
+    table: &Table,
+    basis: Option<&Basis>,
+) -> Option<Vec<(String, Vec<CifValue>)>> {
+    let axes = ["x", "y", "z"];
+    let output = axes.map(|a| format!("_atom_site.Cartn_{a}"));
+    let cartn = axes.map(|a| format!("_atom_site_Cartn_{a}"));
+    let fract = axes.map(|a| format!("_atom_site_fract_{a}"));
+
+    let (tags, basis) = if cartn.iter().all(|tag| table.position(tag).is_some()) {
+        (cartn, None)
+    } else if let Some(basis) = basis {
+        (fract, Some(basis))
+    } else {
+        for tag in &fract {
+            src.report_column(table, tag, "no unit cell to convert fractional coordinates");
+        }
+        return None;
+    };
+    let Some(columns) = tags
+        .iter()
+        .map(|tag| src.take(table, tag))
+        .collect::<Option<Vec<_>>>()
+    else {
+        for tag in &tags {
+            src.report_column(table, tag, "incomplete coordinates");
+        }
+        return None;
+    };
+
+    let mut cartesian: [Vec<CifValue>; 3] = Default::default();
+    let mut has_su = [false; 3];
+    let rows = columns[0].iter().zip(&columns[1]).zip(&columns[2]);
+    for ((x, y), z) in rows {
+        let point = [x, y, z].map(|value| value.as_numeric_with_su());
+        for (axis, coordinate) in point.iter().enumerate() {
+            has_su[axis] |= coordinate.is_some_and(|(_, su)| su.is_some());
+        }
+        let converted = match point {
+            [Some((x, _)), Some((y, _)), Some((z, _))] => {
+                let xyz = match basis {
+                    Some(basis) => basis.to_cartesian([x, y, z]),
+                    None => [x, y, z],
+                };
+                xyz.map(|c| CifValue::Numeric(round(c, 5)))
+            }
+            _ => [CifValue::Unknown, CifValue::Unknown, CifValue::Unknown],
+        };
+        for (column, value) in cartesian.iter_mut().zip(converted) {
+            column.push(value);
+        }
+    }
+    for (tag, _) in tags.iter().zip(has_su).filter(|(_, su)| *su) {
+        src.issues.push(ConversionIssue {
+            tag: tag.clone(),
+            reason: "standard uncertainties of transformed coordinates are not converted"
+                .to_string(),
+        });
+    }
+    Some(output.into_iter().zip(cartesian).collect())
+}
+
+/// Translate `_atom_site_aniso_*`, linked to atom sites by label
+fn aniso_to_mmcif(
+    src: &mut Source,
+    out: &mut CifBlock,
+    basis: Option<&Basis>,
+    ids: &HashMap<String, String>,
+) {
+    let Some(table) = src.table(|tag| category_of(tag) == "_atom_site_aniso") else {
+        return;
+    };
+    let Some(labels) = src.take(&table, "_atom_site_aniso_label") else {
+        return;
+    };
+    let rows: Vec<usize> = (0..labels.len())
+        .filter(|&row| text(labels[row]).is_some_and(|label| ids.contains_key(&label)))
+        .collect();
+    if rows.len() < labels.len() {
+        src.report(
+            table.tags[table.position("_atom_site_aniso_label").unwrap_or(0)],
+            format!(
+                "{} rows refer to atom sites that were not translated",
+                labels.len() - rows.len()
+            ),
+        );
+    }
+
+    let mut columns = vec![(
+        "_atom_site_anisotrop.id".to_string(),
+        rows.iter()
+            .map(|&row| CifValue::parse_value(&ids[&text(labels[row]).unwrap_or_default()]))
+            .collect(),
+    )];
+    if let Some(symbols) = src.take(&table, "_atom_site_aniso_type_symbol") {
+        let symbols = rows.iter().map(|&row| symbols[row].clone()).collect();
+        columns.push(("_atom_site_anisotrop.type_symbol".to_string(), symbols));
+    }
+    for kind in ["U", "B"] {
+        let tags = TENSOR_COMPONENTS.map(|(name, _, _)| format!("_atom_site_aniso_{kind}_{name}"));
+        if tags.iter().all(|tag| table.position(tag).is_none()) {
+            continue;
+        }
+        let Some(basis) = basis else {
+            for tag in &tags {
+                src.report_column(
+                    &table,
+                    tag,
+                    "no unit cell to transform displacement parameters",
+                );
+            }
+            continue;
+        };
+        let Some(core) = tags
+            .iter()
+            .map(|tag| src.take(&table, tag))
+            .collect::<Option<Vec<_>>>()
+        else {
+            for tag in &tags {
+                src.report_column(&table, tag, "incomplete anisotropic displacement tensor");
+            }
+            continue;
+        };
+
+        let mut has_su = false;
+        let tensors: Vec<Option<Mat3>> = rows
+            .iter()
+            .map(|&row| {
+                let components = [0, 1, 2, 3, 4, 5].map(|c| core[c][row].as_numeric_with_su());
+                has_su |= components.iter().flatten().any(|(_, su)| su.is_some());
+                components.iter().all(Option::is_some).then(|| {
+                    basis.adp_to_cartesian(symmetric(components.map(|c| c.map_or(0.0, |(v, _)| v))))
+                })
+            })
+            .collect();
+        if has_su {
+            src.issues.push(ConversionIssue {
+                tag: format!("_atom_site_aniso_{kind}_*"),
+                reason: "standard uncertainties of transformed displacement parameters are not converted"
+                    .to_string(),
+            });
+        }
+        let names = TENSOR_COMPONENTS
+            .map(|(_, i, j)| format!("_atom_site_anisotrop.{kind}[{}][{}]", i + 1, j + 1));
+        columns.extend(tensor_columns(names, tensors.into_iter()));
+    }
+    emit(out, true, columns);
+}
+
+/// `_atom_sites.fract_transf_*` tags, which restate the unit cell
+fn transf_tags() -> impl Iterator<Item = String> {
+    (1..=3).flat_map(|i| {
+        (1..=3)
+            .map(move |j| format!("_atom_sites.fract_transf_matrix[{i}][{j}]"))
+            .chain([format!("_atom_sites.fract_transf_vector[{i}]")])
+    })
+}
+
+/// `ATOM` for standard polymer residues, `HETATM` otherwise
+fn record_type(residue: &str) -> &'static str {
+    if STANDARD_RESIDUES
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(residue))
+    {
+        "ATOM"
+    } else {
+        "HETATM"
+    }
+}
+
+fn has_prefix(tag: &str, prefix: &str) -> bool {
+    tag.as_bytes()
+        .get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix.as_bytes()))
+}
+
+/// A value as text, or `None` for `?` and `.`
+fn text(value: &CifValue) -> Option<String> {
+    match value {
+        CifValue::Numeric(n) => Some(n.to_string()),
+        CifValue::NumericSu { value, su } => Some(uncertainty::format_exact(*value, *su)),
+        _ => value.as_string().map(String::from),
+    }
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    // Adding zero turns -0.0 into 0.0, which is written as `0`
+    (value * scale).round() / scale + 0.0
+}
+
+/// Conversion between fractional and Cartesian coordinates for one cell.
+#[derive(Debug)]
+struct Basis {
+    /// Fractional to Cartesian (columns are the cell vectors)
+    ortho: Mat3,
+    /// Cartesian to fractional
+    frac: Mat3,
+    /// Fractional origin shift: `f = frac · x + shift`
+    shift: [f64; 3],
+}
+
+impl Basis {
+    /// The standard PDB frame for a cell read from the block
+    fn from_cell(src: &Source, direction: Direction) -> Option<Self> {
+        let parameters = SIMPLE_GROUPS[0][..6]
+            .iter()
+            .map(|&(core, mmcif, _)| {
+                let tag = direction.pair(core, mmcif).0;
+                src.value(tag)
+                    .and_then(CifValue::as_numeric_with_su)
+                    .map(|(v, _)| v)
+            })
+            .collect::<Option<Vec<f64>>>()?;
+        let [a, b, c, alpha, beta, gamma] = parameters[..] else {
+            return None;
+        };
+        let cell = UnitCell::new(a, b, c, alpha, beta, gamma);
+        let volume = cell.volume();
+        if volume.is_nan() || volume <= 0.0 {
+            return None;
+        }
+        let ortho = cell.orthogonalization_matrix();
+        Some(Basis {
+            frac: invert(&ortho)?,
+            ortho,
+            shift: [0.0; 3],
+        })
+    }
+
+    /// The frame given by `_atom_sites.fract_transf_matrix`, when complete
+    fn from_transf_matrix(src: &Source) -> Option<Self> {
+        let mut frac = [[0.0; 3]; 3];
+        for (i, row) in frac.iter_mut().enumerate() {
+            for (j, m) in row.iter_mut().enumerate() {
+                let tag = format!("_atom_sites.fract_transf_matrix[{}][{}]", i + 1, j + 1);
+                *m = src.value(&tag)?.as_numeric()?;
+            }
+        }
+        let shift = [1, 2, 3].map(|i| {
+            src.value(&format!("_atom_sites.fract_transf_vector[{i}]"))
+                .and_then(CifValue::as_numeric)
+                .unwrap_or(0.0)
+        });
+        Some(Basis {
+            ortho: invert(&frac)?,
+            frac,
+            shift,
+        })
+    }
+
+    fn to_fractional(&self, cartesian: [f64; 3]) -> [f64; 3] {
+        let f = apply(&self.frac, cartesian);
+        [0, 1, 2].map(|i| f[i] + self.shift[i])
+    }
+
+    fn to_cartesian(&self, fractional: [f64; 3]) -> [f64; 3] {
+        apply(
+            &self.ortho,
+            [0, 1, 2].map(|i| fractional[i] - self.shift[i]),
+        )
+    }
+
+    /// Lengths of the reciprocal cell vectors (the rows of `frac`)
+    fn reciprocal_lengths(&self) -> [f64; 3] {
+        self.frac
+            .map(|row| row.iter().map(|m| m * m).sum::<f64>().sqrt())
+    }
+
+    /// Cartesian tensor to the core CIF frame: `U_ij = (F U F^T)_ij / (a*_i a*_j)`
+    fn adp_to_core(&self, cartesian: Mat3) -> Mat3 {
+        let star = multiply(&multiply(&self.frac, &cartesian), &transpose(&self.frac));
+        let r = self.reciprocal_lengths();
+        [0, 1, 2].map(|i| [0, 1, 2].map(|j| star[i][j] / (r[i] * r[j])))
+    }
+
+    /// Core CIF tensor to the Cartesian frame: `A (N U N) A^T`
+    fn adp_to_cartesian(&self, core: Mat3) -> Mat3 {
+        let r = self.reciprocal_lengths();
+        let star = [0, 1, 2].map(|i| [0, 1, 2].map(|j| core[i][j] * r[i] * r[j]));
+        multiply(&multiply(&self.ortho, &star), &transpose(&self.ortho))
+    }
+}
+
+/// Symmetric tensor from components in [`TENSOR_COMPONENTS`] order
+fn symmetric([t11, t22, t33, t12, t13, t23]: [f64; 6]) -> Mat3 {
+    [[t11, t12, t13], [t12, t22, t23], [t13, t23, t33]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basis_round_trip() {
+        let basis = Basis::from_transf_matrix(&Source::new(&CifBlock::new(String::new())));
+        assert!(basis.is_none());
+
+        let mut block = CifBlock::new("t".into());
+        for (tag, value) in [
+            ("_cell_length_a", 5.0),
+            ("_cell_length_b", 7.0),
+            ("_cell_length_c", 9.0),
+            ("_cell_angle_alpha", 80.0),
+            ("_cell_angle_beta", 100.0),
+            ("_cell_angle_gamma", 110.0),
+        ] {
+            block.items.insert(tag.into(), CifValue::Numeric(value));
+        }
+        let basis = Basis::from_cell(&Source::new(&block), Direction::ToMmcif).unwrap();
+
+        let fract = [0.1, 0.25, 0.7];
+        let back = basis.to_fractional(basis.to_cartesian(fract));
+        for (a, b) in fract.iter().zip(back) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        let u = symmetric([0.02, 0.03, 0.04, 0.001, -0.002, 0.003]);
+        let back = basis.adp_to_core(basis.adp_to_cartesian(u));
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((u[i][j] - back[i][j]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_atom_labels() {
+        let ids = AtomIds {
+            atom: "CA".into(),
+            residue: Some("ALA".into()),
+            seq: Some("12A".into()),
+            chain: Some("B".into()),
+            alt: Some("2".into()),
+        };
+        assert_eq!(ids.label(), "CA_ALA12A_B_2");
+        assert_eq!(AtomIds::from_label("CA_ALA12A_B_2"), ids);
+        assert_eq!(AtomIds::from_label("C1").atom, "C1");
+        assert_eq!(AtomIds::from_label("C1_2").residue, None);
+    }
+}
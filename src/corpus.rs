@@ -0,0 +1,687 @@
+//! Working with many documents: tag usage statistics and bulk extraction.
+//!
+//! [`TagCensus`] accumulates, for every tag seen, how often it occurs,
+//! whether it is used as a data item or a loop column, what kinds of values
+//! it holds, and a few examples. Memory use does not grow with the corpus:
+//! distinct values are tracked only up to a cutoff per tag. Censuses built on
+//! separate threads can be combined with [`TagCensus::merge`], and
+//! [`census_files`] does this for a list of files.
+//!
+//! Tags are compared case-insensitively, as CIF requires, and reported in
+//! lowercase.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::corpus::TagCensus;
+//! use cif_parser::Document;
+//!
+//! let mut census = TagCensus::new();
+//! census.add(&Document::parse("data_a\n_cell_length_a 5.1\nloop_\n_x\n1\nA\n").unwrap());
+//! census.add(&Document::parse("data_b\n_CELL_LENGTH_A 6.2\n").unwrap());
+//!
+//! let cell = &census.tags["_cell_length_a"];
+//! assert_eq!((cell.documents, cell.as_item, cell.numeric), (2, 2, 2));
+//! let x = &census.tags["_x"];
+//! assert_eq!((x.in_loop, x.values, x.numeric_ratio()), (1, 2, Some(0.5)));
+//! ```
+//!
+//! [`extract_items`] pulls a few single-valued tags out of every file, using
+//! selective parsing so that the rest of each file is skipped unparsed.
+
+use crate::ast::{CifDocument, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::parser::{ParseOptions, Selection};
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+use serde_json::json;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io, thread};
+
+/// Default number of distinct values tracked per tag
+pub const DEFAULT_DISTINCT_CAP: usize = 1000;
+
+/// Number of example values kept per tag
+const EXAMPLES: usize = 3;
+
+/// Usage of one tag across a corpus.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagStats {
+    /// Documents in which the tag appears
+    pub documents: u64,
+    /// Data blocks and save frames in which it appears as a data item
+    pub as_item: u64,
+    /// Data blocks and save frames in which it appears as a loop column
+    pub in_loop: u64,
+    /// Values seen (a loop column contributes one per row)
+    pub values: u64,
+    /// Numeric values
+    pub numeric: u64,
+    /// Text values
+    pub text: u64,
+    /// `?` and `.` values
+    pub null: u64,
+    /// CIF 2.0 lists and tables
+    pub compound: u64,
+    /// Distinct values, or `None` once there were more than the cap
+    pub distinct: Option<BTreeSet<String>>,
+    /// The first few distinct values in sorted order (sorted so that merged
+    /// censuses agree with one built in a single pass)
+    pub examples: BTreeSet<String>,
+}
+
+impl TagStats {
+    /// Fraction of the non-null, non-compound values that are numbers
+    pub fn numeric_ratio(&self) -> Option<f64> {
+        let typed = self.numeric + self.text;
+        (typed > 0).then(|| self.numeric as f64 / typed as f64)
+    }
+
+    /// Number of distinct values, or `None` if there were more than the cap
+    pub fn distinct_count(&self) -> Option<usize> {
+        self.distinct.as_ref().map(BTreeSet::len)
+    }
+
+    fn record(&mut self, value: &CifValue, cap: usize) {
+        self.values += 1;
+        let key = match value {
+            CifValue::Unknown | CifValue::NotApplicable => {
+                self.null += 1;
+                return;
+            }
+            CifValue::Numeric(_) | CifValue::NumericSu { .. } => {
+                self.numeric += 1;
+                inline_value(value, CifVersion::V2_0)
+            }
+            CifValue::Text(s) => {
+                self.text += 1;
+                s.clone()
+            }
+            // Too big to keep as an example, and never repeated
+            CifValue::LargeText(s) => {
+                self.text += 1;
+                format!("<{} bytes>", s.len())
+            }
+            CifValue::List(_) | CifValue::Table(_) => {
+                self.compound += 1;
+                inline_value(value, CifVersion::V2_0)
+            }
+        };
+        self.remember(key, cap);
+    }
+
+    fn remember(&mut self, key: String, cap: usize) {
+        if self.examples.len() < EXAMPLES || self.examples.last().is_some_and(|last| key < *last) {
+            self.remember_example(key.clone());
+        }
+        if let Some(distinct) = &mut self.distinct {
+            distinct.insert(key);
+            if distinct.len() > cap {
+                self.distinct = None;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: TagStats, cap: usize) {
+        self.documents += other.documents;
+        self.as_item += other.as_item;
+        self.in_loop += other.in_loop;
+        self.values += other.values;
+        self.numeric += other.numeric;
+        self.text += other.text;
+        self.null += other.null;
+        self.compound += other.compound;
+        for example in other.examples {
+            self.remember_example(example);
+        }
+        self.distinct = match (self.distinct.take(), other.distinct) {
+            (Some(mut ours), Some(theirs)) => {
+                ours.extend(theirs);
+                (ours.len() <= cap).then_some(ours)
+            }
+            _ => None,
+        };
+    }
+
+    fn remember_example(&mut self, key: String) {
+        self.examples.insert(key);
+        if self.examples.len() > EXAMPLES {
+            self.examples.pop_last();
+        }
+    }
+}
+
+/// Tag usage statistics accumulated over a corpus of documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagCensus {
+    /// Documents added
+    pub documents: u64,
+    /// Statistics per lowercased tag, in tag order
+    pub tags: BTreeMap<String, TagStats>,
+    /// Distinct values tracked per tag before giving up on that tag
+    pub distinct_cap: usize,
+}
+
+impl Default for TagCensus {
+    fn default() -> Self {
+        TagCensus {
+            documents: 0,
+            tags: BTreeMap::new(),
+            distinct_cap: DEFAULT_DISTINCT_CAP,
+        }
+    }
+}
+
+impl TagCensus {
+    /// Create an empty census with the default distinct-value cap
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many distinct values are tracked per tag
+    pub fn distinct_cap(mut self, cap: usize) -> Self {
+        self.distinct_cap = cap;
+        self
+    }
+
+    /// Add every data item and loop column of a document, including those
+    /// in save frames
+    pub fn add(&mut self, doc: &CifDocument) {
+        self.documents += 1;
+        let mut seen = HashSet::new();
+        for block in &doc.blocks {
+            self.add_container(&block.items, &block.loops, &mut seen);
+            for frame in &block.frames {
+                self.add_container(&frame.items, &frame.loops, &mut seen);
+            }
+        }
+    }
+
+    fn add_container(
+        &mut self,
+        items: &IndexMap<String, CifValue>,
+        loops: &[CifLoop],
+        seen: &mut HashSet<String>,
+    ) {
+        let cap = self.distinct_cap;
+        for (tag, value) in items {
+            let stats = self.stats(tag, seen);
+            stats.as_item += 1;
+            stats.record(value, cap);
+        }
+        for loop_ in loops {
+            for (column, tag) in loop_.tags.iter().enumerate() {
+                let stats = self.stats(tag, seen);
+                stats.in_loop += 1;
+                for row in &loop_.values {
+                    stats.record(&row[column], cap);
+                }
+            }
+        }
+    }
+
+    /// Statistics for a tag, counting the document the first time it is seen
+    fn stats(&mut self, tag: &str, seen: &mut HashSet<String>) -> &mut TagStats {
+        let tag = tag.to_lowercase();
+        let stats = self.tags.entry(tag.clone()).or_insert_with(|| TagStats {
+            distinct: Some(BTreeSet::new()),
+            ..TagStats::default()
+        });
+        if seen.insert(tag) {
+            stats.documents += 1;
+        }
+        stats
+    }
+
+    /// Combine another census into this one.
+    ///
+    /// The result is the same as adding all of the other census's documents
+    /// to this one. The smaller of the two distinct-value caps is kept.
+    pub fn merge(&mut self, other: TagCensus) {
+        self.documents += other.documents;
+        self.distinct_cap = self.distinct_cap.min(other.distinct_cap);
+        let cap = self.distinct_cap;
+        for (tag, theirs) in other.tags {
+            match self.tags.get_mut(&tag) {
+                Some(ours) => ours.merge(theirs, cap),
+                None => {
+                    self.tags.insert(tag, theirs);
+                }
+            }
+        }
+        for stats in self.tags.values_mut() {
+            if stats.distinct_count().is_some_and(|n| n > cap) {
+                stats.distinct = None;
+            }
+        }
+    }
+
+    /// One CSV row per tag, with a header row.
+    ///
+    /// `distinct` is empty when the tag had more distinct values than the cap,
+    /// and `examples` are joined with `|`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "tag,documents,as_item,in_loop,values,numeric,text,null,compound,\
+             numeric_ratio,distinct,examples\n",
+        );
+        for (tag, s) in &self.tags {
+            let examples: Vec<&str> = s.examples.iter().map(String::as_str).collect();
+            let row = [
+                csv_field(tag),
+                s.documents.to_string(),
+                s.as_item.to_string(),
+                s.in_loop.to_string(),
+                s.values.to_string(),
+                s.numeric.to_string(),
+                s.text.to_string(),
+                s.null.to_string(),
+                s.compound.to_string(),
+                s.numeric_ratio()
+                    .map(|r| format!("{r:.3}"))
+                    .unwrap_or_default(),
+                s.distinct_count()
+                    .map(|n| n.to_string())
+                    .unwrap_or_default(),
+                csv_field(&examples.join("|")),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// The census as a JSON object: `{"documents": n, "distinct_cap": n,
+    /// "tags": {tag: {...}}}`, with `distinct` null when over the cap
+    pub fn to_json(&self) -> String {
+        let tags: serde_json::Map<String, serde_json::Value> = self
+            .tags
+            .iter()
+            .map(|(tag, s)| {
+                let stats = json!({
+                    "documents": s.documents,
+                    "as_item": s.as_item,
+                    "in_loop": s.in_loop,
+                    "values": s.values,
+                    "numeric": s.numeric,
+                    "text": s.text,
+                    "null": s.null,
+                    "compound": s.compound,
+                    "numeric_ratio": s.numeric_ratio(),
+                    "distinct": s.distinct_count(),
+                    "examples": s.examples,
+                });
+                (tag.clone(), stats)
+            })
+            .collect();
+        json!({
+            "documents": self.documents,
+            "distinct_cap": self.distinct_cap,
+            "tags": tags,
+        })
+        .to_string()
+    }
+}
+
+/// Quote a CSV field if it contains a comma, quote, or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// CIF files under a directory, recursively, in sorted order.
+///
+/// Files ending in `.cif` or `.mmcif` (in any case) are included. Paths are
+/// kept as the operating system gives them, so names that are not valid
+/// UTF-8 are found and opened like any other. An error names the directory
+/// that could not be read.
+pub fn cif_files(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.as_ref().to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let in_dir = |e: io::Error| io::Error::new(e.kind(), format!("{}: {e}", dir.display()));
+        for entry in fs::read_dir(long_path(&dir)).map_err(in_dir)? {
+            let entry = entry.map_err(in_dir)?;
+            let path = dir.join(entry.file_name());
+            if fs::metadata(long_path(&path)).is_ok_and(|m| m.is_dir()) {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("cif") || ext.eq_ignore_ascii_case("mmcif")
+            }) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Paths at least this long need the extended-length prefix on Windows:
+/// `MAX_PATH` (260) less room for an 8.3 file name, the limit for
+/// directories
+#[cfg(windows)]
+const LONG_PATH: usize = 248;
+
+/// `path` in a form the operating system opens whatever its length.
+///
+/// On Windows, a path of 248 characters or more is made absolute and given
+/// the `\\?\` prefix (`\\?\UNC\` for a network share), which lifts the
+/// `MAX_PATH` limit. Anywhere else, and for shorter or already prefixed
+/// paths, `path` is returned as is. Used by [`cif_files`] and
+/// [`CifDocument::from_file`]; the paths they report are the ones given.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(windows)]
+    {
+        use std::path::{Component, Prefix};
+
+        if path.as_os_str().len() >= LONG_PATH {
+            let Ok(absolute) = std::path::absolute(path) else {
+                return Cow::Borrowed(path);
+            };
+            let mut components = absolute.components();
+            let mut long = match components.next() {
+                Some(Component::Prefix(prefix)) => match prefix.kind() {
+                    Prefix::Disk(_) => {
+                        let mut long = std::ffi::OsString::from(r"\\?\");
+                        long.push(prefix.as_os_str());
+                        PathBuf::from(long)
+                    }
+                    Prefix::UNC(server, share) => {
+                        let mut long = std::ffi::OsString::from(r"\\?\UNC\");
+                        long.push(server);
+                        long.push("\\");
+                        long.push(share);
+                        PathBuf::from(long)
+                    }
+                    // Already verbatim, or a device
+                    _ => return Cow::Borrowed(path),
+                },
+                _ => return Cow::Borrowed(path),
+            };
+            long.extend(components);
+            return Cow::Owned(long);
+        }
+    }
+    Cow::Borrowed(path)
+}
+
+/// Split `paths` into one contiguous run per worker thread and apply `work`
+/// to each run, returning the results in the order of `paths`
+fn per_worker<P: Sync, T: Send>(
+    paths: &[P],
+    threads: usize,
+    work: impl Fn(&[P]) -> T + Sync,
+) -> Vec<T> {
+    let chunk = paths.len().div_ceil(threads.max(1)).max(1);
+    let work = &work;
+    thread::scope(|scope| {
+        let workers: Vec<_> = paths
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || work(chunk)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("corpus worker panicked"))
+            .collect()
+    })
+}
+
+/// Build a census of files, parsing them on `threads` worker threads.
+///
+/// Each worker keeps its own census and the results are merged, so only one
+/// document per worker is in memory at a time. Files that fail to parse are
+/// skipped and their errors returned.
+pub fn census_files<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    threads: usize,
+) -> (TagCensus, Vec<CifError>) {
+    let results = per_worker(paths, threads, |chunk| {
+        let mut census = TagCensus::new();
+        let mut errors = Vec::new();
+        for path in chunk {
+            match CifDocument::from_file(path) {
+                Ok(doc) => census.add(&doc),
+                Err(e) => errors.push(e),
+            }
+        }
+        (census, errors)
+    });
+
+    let mut census = TagCensus::new();
+    let mut errors = Vec::new();
+    for (part, part_errors) in results {
+        census.merge(part);
+        errors.extend(part_errors);
+    }
+    (census, errors)
+}
+
+/// Options for [`extract_items`].
+#[derive(Debug, Clone)]
+pub struct ExtractOptions {
+    /// Read only the first data block of each file
+    pub first_block_only: bool,
+    /// Parse leniently (see [`ParseOptions::lenient`])
+    pub lenient: bool,
+    /// Number of worker threads
+    pub threads: usize,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            first_block_only: false,
+            lenient: false,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+impl ExtractOptions {
+    /// Create the default options: every block, strict parsing, one thread
+    /// per available CPU
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read only the first data block of each file
+    pub fn first_block_only(mut self, first_block_only: bool) -> Self {
+        self.first_block_only = first_block_only;
+        self
+    }
+
+    /// Enable or disable lenient parsing
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Set the number of worker threads
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+/// The values extracted from one data block, from [`extract_items`].
+#[derive(Debug)]
+pub struct ExtractRecord {
+    /// File the block came from
+    pub path: PathBuf,
+    /// Name of the data block, or `None` if the file has no blocks or failed
+    /// to parse
+    pub block: Option<String>,
+    /// Each requested tag, in the order requested, with its value if the
+    /// block has it
+    pub values: IndexMap<String, Option<CifValue>>,
+    /// Why the file could not be parsed
+    pub error: Option<CifError>,
+}
+
+impl ExtractRecord {
+    fn new(path: &Path, block: Option<String>, tags: &[&str]) -> Self {
+        ExtractRecord {
+            path: path.to_path_buf(),
+            block,
+            values: tags.iter().map(|tag| (tag.to_string(), None)).collect(),
+            error: None,
+        }
+    }
+}
+
+/// Extract single-valued tags from many files.
+///
+/// Each file is parsed selectively, keeping only the requested tags, on
+/// `options.threads` worker threads. There is one record per data block, in
+/// the order of `paths`. A tag's value is its data item, or its value in a
+/// loop with exactly one row; tags are matched ignoring case. A file that
+/// cannot be read or parsed gives a single record carrying the error, so one
+/// bad file does not stop the batch.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cif_parser::corpus::{cif_files, extract_csv};
+/// use cif_parser::{extract_items, ExtractOptions};
+///
+/// let files = cif_files("structures").unwrap();
+/// let tags = ["_cell_volume", "_space_group_IT_number"];
+/// let records = extract_items(&files, &tags, &ExtractOptions::new().first_block_only(true));
+/// print!("{}", extract_csv(&records, &tags));
+/// ```
+pub fn extract_items<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    tags: &[&str],
+    options: &ExtractOptions,
+) -> Vec<ExtractRecord> {
+    let mut parse = ParseOptions::new()
+        .lenient(options.lenient)
+        .select(Selection::TagPrefixes(
+            tags.iter().map(|tag| tag.to_string()).collect(),
+        ));
+    if options.first_block_only {
+        parse = parse.select(Selection::FirstBlockOnly);
+    }
+
+    per_worker(paths, options.threads, |chunk| {
+        let mut records = Vec::new();
+        for path in chunk {
+            let path = path.as_ref();
+            match CifDocument::from_file_with_options(path, &parse) {
+                Ok(doc) if doc.blocks.is_empty() => {
+                    records.push(ExtractRecord::new(path, None, tags));
+                }
+                Ok(doc) => {
+                    for block in &doc.blocks {
+                        let mut record = ExtractRecord::new(path, Some(block.name.clone()), tags);
+                        for (tag, value) in &mut record.values {
+                            *value = single_value(&block.items, &block.loops, tag);
+                        }
+                        records.push(record);
+                    }
+                }
+                Err(error) => {
+                    let mut record = ExtractRecord::new(path, None, tags);
+                    record.error = Some(error);
+                    records.push(record);
+                }
+            }
+        }
+        records
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// The value of `tag` as a data item or in a one-row loop, ignoring case
+fn single_value(
+    items: &IndexMap<String, CifValue>,
+    loops: &[CifLoop],
+    tag: &str,
+) -> Option<CifValue> {
+    let item = items
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(tag))
+        .map(|(_, value)| value);
+    let column = || {
+        loops
+            .iter()
+            .filter(|loop_| loop_.len() == 1)
+            .find_map(|loop_| {
+                let column = loop_
+                    .tags
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(tag))?;
+                loop_.get(0, column)
+            })
+    };
+    item.or_else(column).cloned()
+}
+
+/// Write extraction results as CSV.
+///
+/// The columns are `path`, `block`, one per tag, and `error`. Missing
+/// values are empty cells, text is written as is, and `?` and `.` are kept.
+/// Text that is just `?` or `.` is written quoted, as `'?'`, so that it
+/// stays distinct from the markers.
+pub fn extract_csv(records: &[ExtractRecord], tags: &[&str]) -> String {
+    let mut csv = String::from("path,block");
+    for tag in tags {
+        csv.push(',');
+        csv.push_str(&csv_field(tag));
+    }
+    csv.push_str(",error\n");
+    for record in records {
+        let mut row = vec![
+            csv_field(&record.path.display().to_string()),
+            csv_field(record.block.as_deref().unwrap_or("")),
+        ];
+        for tag in tags {
+            let value = record.values.get(*tag).and_then(Option::as_ref);
+            row.push(value.map_or(String::new(), |value| csv_field(&value_text(value))));
+        }
+        let error = record.error.as_ref().map(ToString::to_string);
+        row.push(csv_field(error.as_deref().unwrap_or("")));
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// A value as plain text: text unquoted unless it would read as `?` or `.`,
+/// anything else as CIF would write it
+fn value_text(value: &CifValue) -> String {
+    match value {
+        CifValue::Text(s) if s != "?" && s != "." => s.clone(),
+        other => inline_value(other, CifVersion::V2_0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct_values_are_capped() {
+        let mut census = TagCensus::new().distinct_cap(3);
+        let rows: String = (0..5).map(|n| format!("v{n}\n")).collect();
+        census.add(&CifDocument::parse(&format!("data_a\nloop_\n_x\n{rows}")).unwrap());
+        let x = &census.tags["_x"];
+        assert_eq!(x.distinct_count(), None);
+        assert_eq!(x.values, 5);
+        assert_eq!(x.examples.iter().collect::<Vec<_>>(), ["v0", "v1", "v2"]);
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,149 @@
+//! Splitting a block that holds several datasets by an id column.
+//!
+//! In-situ and time-resolved experiments often write many datasets into one
+//! block, with every loop carrying a column such as `_diffrn_id`,
+//! `_pd_block_id`, or a scan number that says which dataset each row
+//! belongs to. [`CifLoop::partition_by`] splits one loop on such a column,
+//! and [`CifBlock::datasets`] splits every loop that has it, giving one
+//! block per dataset.
+//!
+//! Ids are the text of the values; numbers are written as the writer would
+//! write them, and `?` and `.` are ids of their own. Datasets are in the
+//! order their ids first appear, and rows keep their order within each.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_run
+//! _pd_spec_description 'LaB6 standard'
+//! loop_
+//! _diffrn_id
+//! _pd_meas_2theta_scan
+//! _pd_meas_counts_total
+//! scan1 20.00 102
+//! scan2 20.00 110
+//! scan1 20.02 98
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let datasets = doc.blocks[0].datasets("_diffrn_id");
+//!
+//! assert_eq!(datasets.keys().collect::<Vec<_>>(), ["scan1", "scan2"]);
+//! let scan1 = &datasets["scan1"];
+//! assert_eq!(scan1.loops[0].len(), 2);
+//! assert!(scan1.get_item("_pd_spec_description").is_some());
+//! ```
+
+use crate::ast::{CifBlock, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+
+/// The id a row belongs to
+fn id_text(value: &CifValue) -> String {
+    match value.as_string() {
+        Some(text) => text.to_string(),
+        None => inline_value(value, CifVersion::V2_0),
+    }
+}
+
+impl CifLoop {
+    /// Split the rows into one loop per value of the `tag` column, keyed by
+    /// that value's text.
+    ///
+    /// The loops keep the rows' order and, with `keep_column`, the `tag`
+    /// column; without it the column is left out. Returns
+    /// [`CifError::MissingTag`] if the loop has no `tag` column.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let cif = "data_x\nloop_\n_scan\n_t\n1 295\n2 450\n1 300\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let scans = doc.blocks[0].loops[0].partition_by("_scan", false).unwrap();
+    ///
+    /// assert_eq!(scans["1"].tags, ["_t"]);
+    /// assert_eq!(scans["1"].get_column("_t").unwrap().len(), 2);
+    /// assert_eq!(scans["2"].len(), 1);
+    /// ```
+    pub fn partition_by(
+        &self,
+        tag: &str,
+        keep_column: bool,
+    ) -> Result<IndexMap<String, CifLoop>, CifError> {
+        let column =
+            self.tags
+                .iter()
+                .position(|t| t == tag)
+                .ok_or_else(|| CifError::MissingTag {
+                    tag: tag.to_string(),
+                })?;
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| keep_column || i != column)
+            .map(|(_, t)| t.clone())
+            .collect();
+
+        let mut parts: IndexMap<String, CifLoop> = IndexMap::new();
+        for row in &self.values {
+            let part = parts
+                .entry(id_text(&row[column]))
+                .or_insert_with(|| CifLoop::new(tags.clone()));
+            let values = row
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| keep_column || i != column)
+                .map(|(_, value)| value.clone())
+                .collect();
+            part.values.push(values);
+        }
+        Ok(parts)
+    }
+}
+
+impl CifBlock {
+    /// One block per dataset, splitting every loop with an `id_tag` column
+    /// by [`partition_by`](CifLoop::partition_by), keyed by the id.
+    ///
+    /// Each dataset's block has the block's name, items, and save frames,
+    /// the loops without `id_tag` as they are, and its own rows of the loops
+    /// with it, `id_tag` column included. A dataset has no part of a loop
+    /// where its id never appears. Empty if no loop has an `id_tag` column.
+    pub fn datasets(&self, id_tag: &str) -> IndexMap<String, CifBlock> {
+        let parts: Vec<Option<IndexMap<String, CifLoop>>> = self
+            .loops
+            .iter()
+            .map(|loop_| loop_.partition_by(id_tag, true).ok())
+            .collect();
+
+        let mut datasets: IndexMap<String, CifBlock> = IndexMap::new();
+        for id in parts.iter().flatten().flat_map(IndexMap::keys) {
+            datasets.entry(id.clone()).or_insert_with(|| CifBlock {
+                name: self.name.clone(),
+                items: self.items.clone(),
+                loops: Vec::new(),
+                frames: self.frames.clone(),
+            });
+        }
+        for (loop_, parts) in self.loops.iter().zip(parts) {
+            match parts {
+                Some(parts) => {
+                    for (id, part) in parts {
+                        datasets[&id].loops.push(part);
+                    }
+                }
+                None => {
+                    for dataset in datasets.values_mut() {
+                        dataset.loops.push(loop_.clone());
+                    }
+                }
+            }
+        }
+        datasets
+    }
+}
@@ -0,0 +1,537 @@
+//! DDLm dictionaries, layered, and validation of documents against them.
+//!
+//! A [`CifDictionary`] holds the item definitions of a DDLm dictionary such
+//! as the core dictionary `cif_core.dic`: one save frame per item, giving
+//! its type (`_type.contents`), units, allowed values
+//! (`_enumeration_set.state`), numeric range (`_enumeration.range`), and
+//! description. Category frames are skipped.
+//!
+//! A [`DictionaryStack`] layers dictionaries, such as the core dictionary
+//! with a local extension on top. Later dictionaries override earlier ones
+//! tag by tag, so a local dictionary can narrow or widen one item's allowed
+//! values and leave the rest alone. [`CifDocument::validate`] checks every
+//! defined tag of a document against the stack, and each
+//! [`ValidationIssue`] names the dictionary and save frame whose definition
+//! it used.
+//!
+//! Tags are looked up without regard to case, under the definition's name
+//! (`_cell.length_a`), its aliases (`_alias.definition_id`), and the same
+//! name with the dot replaced by an underscore (`_cell_length_a`), so files
+//! written with CIF 1.1 names are checked against DDLm definitions. Tags no
+//! dictionary defines are not reported.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::dictionary::{CifDictionary, DictionaryStack};
+//! use cif_parser::Document;
+//!
+//! let dic = "\
+//! #\\#CIF_2.0
+//! data_MINI
+//! _dictionary.title MINI
+//! save_cell.length_a
+//! _definition.id '_cell.length_a'
+//! _name.category_id cell
+//! _type.contents Real
+//! _enumeration.range 0.0:
+//! _units.code angstroms
+//! save_
+//! ";
+//! let dictionary = CifDictionary::parse(dic).unwrap();
+//! let mut stack = DictionaryStack::new();
+//! stack.push(dictionary);
+//!
+//! let doc = Document::parse("data_x\n_cell_length_a -5.2\n").unwrap();
+//! let issues = doc.validate(&stack);
+//! assert_eq!(issues[0].tag, "_cell_length_a");
+//! assert_eq!(issues[0].dictionary, "MINI");
+//! assert_eq!(issues[0].definition, "cell.length_a");
+//!
+//! let definition = stack.definition_of("_CELL_LENGTH_A").unwrap();
+//! assert_eq!(definition.units.as_deref(), Some("angstroms"));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::parser::ParseOptions;
+use crate::uncertainty;
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// One item definition from a dictionary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    /// `_definition.id`, such as `_cell.length_a`
+    pub id: String,
+    /// Title of the dictionary the definition is from
+    pub dictionary: String,
+    /// Name of the save frame holding the definition
+    pub frame: String,
+    /// `_name.category_id`, such as `cell`
+    pub category: Option<String>,
+    /// `_type.contents`, such as `Real`, `Integer`, `Code`, or `Text`
+    pub contents: Option<String>,
+    /// `_type.container`, such as `Single` or `List`
+    pub container: Option<String>,
+    /// `_units.code`, such as `angstroms`
+    pub units: Option<String>,
+    /// `_enumeration_set.state`: the allowed values, if restricted
+    pub enumeration: Vec<String>,
+    /// `_enumeration.range`: the lowest and highest allowed values, either
+    /// of which may be open
+    pub range: Option<(Option<f64>, Option<f64>)>,
+    /// `_description.text`, trimmed
+    pub description: Option<String>,
+    /// `_alias.definition_id`: other names of the item, such as
+    /// `_cell_length_a`
+    pub aliases: Vec<String>,
+}
+
+impl Definition {
+    /// Read the definition in `frame` of `dictionary`, or `None` if the
+    /// frame defines a category or defines nothing
+    fn from_frame(frame: &CifFrame, dictionary: &str) -> Option<Self> {
+        let id = frame.get_item("_definition.id").and_then(value_text)?;
+        let scope = frame.get_item("_definition.scope").and_then(value_text);
+        if scope.is_some_and(|scope| scope.eq_ignore_ascii_case("category")) {
+            return None;
+        }
+        let text = |tag: &str| frame.get_item(tag).and_then(value_text);
+        let column = |tag: &str| -> Vec<String> {
+            frame
+                .get_all(tag)
+                .into_iter()
+                .filter_map(value_text)
+                .collect()
+        };
+        Some(Definition {
+            id,
+            dictionary: dictionary.to_string(),
+            frame: frame.name.clone(),
+            category: text("_name.category_id"),
+            contents: text("_type.contents"),
+            container: text("_type.container"),
+            units: text("_units.code"),
+            enumeration: column("_enumeration_set.state"),
+            range: text("_enumeration.range").and_then(|range| parse_range(&range)),
+            description: text("_description.text").map(|text| text.trim().to_string()),
+            aliases: column("_alias.definition_id"),
+        })
+    }
+
+    /// Names the definition is found under, lowercased
+    fn keys(&self) -> Vec<String> {
+        let id = self.id.to_lowercase();
+        let mut keys = vec![id.replacen('.', "_", 1), id];
+        keys.extend(self.aliases.iter().map(|alias| alias.to_lowercase()));
+        keys
+    }
+
+    /// What is wrong with `value` under this definition, if anything
+    fn check(&self, value: &CifValue) -> Option<(ValidationKind, String)> {
+        if matches!(value, CifValue::Unknown | CifValue::NotApplicable) {
+            return None;
+        }
+        let contents = self.contents.as_deref().unwrap_or_default().to_lowercase();
+        let number = value.as_numeric_with_su().map(|(number, _)| number);
+        match contents.as_str() {
+            "real" if number.is_none() => {
+                return Some((ValidationKind::Type, "should be a number".into()));
+            }
+            "integer" | "count" | "index" if number.is_none_or(|n| n.fract() != 0.0) => {
+                return Some((ValidationKind::Type, "should be a whole number".into()));
+            }
+            "count" if number.is_some_and(|n| n < 0.0) => {
+                return Some((ValidationKind::Range, "should be 0 or more".into()));
+            }
+            "index" if number.is_some_and(|n| n < 1.0) => {
+                return Some((ValidationKind::Range, "should be 1 or more".into()));
+            }
+            _ => {}
+        }
+        if !self.enumeration.is_empty() {
+            let text = value_text(value)?;
+            if !self
+                .enumeration
+                .iter()
+                .any(|state| state.eq_ignore_ascii_case(&text))
+            {
+                return Some((
+                    ValidationKind::Enumeration,
+                    format!("should be one of {}", self.enumeration.join(", ")),
+                ));
+            }
+        }
+        if let (Some((low, high)), Some(number)) = (self.range, number) {
+            if low.is_some_and(|low| number < low) || high.is_some_and(|high| number > high) {
+                let bound = |b: Option<f64>| b.map(|b| b.to_string()).unwrap_or_default();
+                return Some((
+                    ValidationKind::Range,
+                    format!("should be in the range {}:{}", bound(low), bound(high)),
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Text of a value, trimmed, with numbers formatted back to strings and
+/// `?`, `.`, and blank text as `None`
+fn value_text(value: &CifValue) -> Option<String> {
+    match value {
+        CifValue::Numeric(n) => Some(n.to_string()),
+        CifValue::NumericSu { value, su } => Some(uncertainty::format_exact(*value, *su)),
+        _ => value
+            .as_string()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string),
+    }
+}
+
+/// Read an `_enumeration.range` such as `0.0:`, `1:230`, or `:90`
+fn parse_range(range: &str) -> Option<(Option<f64>, Option<f64>)> {
+    let (low, high) = range.trim().split_once(':')?;
+    let bound = |s: &str| -> Option<Option<f64>> {
+        match s.trim() {
+            "" => Some(None),
+            s => s.parse().ok().map(Some),
+        }
+    };
+    Some((bound(low)?, bound(high)?))
+}
+
+/// The item definitions of one DDLm dictionary.
+#[derive(Debug, Clone, Default)]
+pub struct CifDictionary {
+    /// `_dictionary.title`, or the data block's name without one
+    pub title: String,
+    /// `_dictionary.version`, if given
+    pub version: Option<String>,
+    /// Item definitions, in file order, keyed by `_definition.id` as
+    /// written
+    pub definitions: IndexMap<String, Definition>,
+    /// Index into `definitions` by every name an item is found under
+    index: HashMap<String, usize>,
+}
+
+/// Dictionaries are read leniently enough to take fragments of bare frames
+fn fragment_options() -> ParseOptions {
+    ParseOptions::new().allow_bare_frames(true)
+}
+
+impl CifDictionary {
+    /// Read the definitions of the first data block of a parsed dictionary
+    ///
+    /// Fails if the document has no data block.
+    pub fn from_document(doc: &CifDocument) -> Result<Self, CifError> {
+        let block = doc
+            .blocks
+            .first()
+            .ok_or_else(|| CifError::invalid_structure("dictionary has no data block"))?;
+        Ok(Self::from_block(block))
+    }
+
+    /// Parse a dictionary from text
+    ///
+    /// A fragment of bare save frames, with no `data_` heading, is read as
+    /// one block named `_bare_frames_` (see
+    /// [`ParseOptions::allow_bare_frames`]).
+    pub fn parse(content: &str) -> Result<Self, CifError> {
+        let doc = CifDocument::parse_with_options(content, &fragment_options())?;
+        Self::from_document(&doc)
+    }
+
+    /// Parse a dictionary file, such as `cif_core.dic`
+    ///
+    /// A fragment of bare save frames is read as one block named after the
+    /// file, which is also its title unless it gives one.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CifError> {
+        let path = path.as_ref();
+        let doc = CifDocument::from_file_with_options(path, &fragment_options())?;
+        Self::from_document(&doc).map_err(|e| e.with_path(path))
+    }
+
+    fn from_block(block: &CifBlock) -> Self {
+        let title = block
+            .get_item("_dictionary.title")
+            .and_then(value_text)
+            .unwrap_or_else(|| block.name.clone());
+        let mut dictionary = CifDictionary {
+            version: block.get_item("_dictionary.version").and_then(value_text),
+            title,
+            ..CifDictionary::default()
+        };
+        for frame in &block.frames {
+            if let Some(definition) = Definition::from_frame(frame, &dictionary.title) {
+                dictionary.insert(definition);
+            }
+        }
+        dictionary
+    }
+
+    /// Add a definition, replacing any with the same `_definition.id`
+    pub fn insert(&mut self, definition: Definition) {
+        let keys = definition.keys();
+        let (position, _) = self
+            .definitions
+            .insert_full(definition.id.clone(), definition);
+        for key in keys {
+            self.index.insert(key, position);
+        }
+    }
+
+    /// The definition of `tag`, in any case and by any of its names
+    pub fn get(&self, tag: &str) -> Option<&Definition> {
+        let position = *self.index.get(&tag.to_lowercase())?;
+        self.definitions.get_index(position).map(|(_, d)| d)
+    }
+
+    /// Number of item definitions
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    /// Whether the dictionary defines no items
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+/// Dictionaries layered so that later ones override earlier ones, tag by
+/// tag.
+///
+/// Dictionaries are shared, so clones of a stack are cheap.
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryStack {
+    dictionaries: Vec<Arc<CifDictionary>>,
+}
+
+impl DictionaryStack {
+    /// Create an empty stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a dictionary on top, overriding the definitions of the tags it
+    /// defines
+    pub fn push(&mut self, dictionary: impl Into<Arc<CifDictionary>>) {
+        self.dictionaries.push(dictionary.into());
+    }
+
+    /// The dictionaries, bottom first
+    pub fn dictionaries(&self) -> &[Arc<CifDictionary>] {
+        &self.dictionaries
+    }
+
+    /// The definition of `tag` in force: the one from the topmost
+    /// dictionary that defines it
+    pub fn definition_of(&self, tag: &str) -> Option<&Definition> {
+        self.dictionaries.iter().rev().find_map(|d| d.get(tag))
+    }
+
+    /// Every definition of `tag`, the one in force first, for seeing what a
+    /// local dictionary overrides
+    pub fn definitions_of(&self, tag: &str) -> Vec<&Definition> {
+        self.dictionaries
+            .iter()
+            .rev()
+            .filter_map(|d| d.get(tag))
+            .collect()
+    }
+}
+
+impl FromIterator<CifDictionary> for DictionaryStack {
+    fn from_iter<I: IntoIterator<Item = CifDictionary>>(iter: I) -> Self {
+        DictionaryStack {
+            dictionaries: iter.into_iter().map(Arc::new).collect(),
+        }
+    }
+}
+
+/// What kind of problem a [`ValidationIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationKind {
+    /// The value is not of the definition's `_type.contents`
+    Type,
+    /// The value is not one of the `_enumeration_set.state` values
+    Enumeration,
+    /// The number is outside the `_enumeration.range`
+    Range,
+}
+
+impl ValidationKind {
+    /// Every kind
+    pub const ALL: [ValidationKind; 3] = [
+        ValidationKind::Type,
+        ValidationKind::Enumeration,
+        ValidationKind::Range,
+    ];
+
+    /// Name of the kind: `type`, `enumeration`, or `range`
+    pub fn name(self) -> &'static str {
+        match self {
+            ValidationKind::Type => "type",
+            ValidationKind::Enumeration => "enumeration",
+            ValidationKind::Range => "range",
+        }
+    }
+}
+
+impl fmt::Display for ValidationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A value that breaks the definition of its tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// Name of the data block
+    pub block: String,
+    /// Name of the save frame, if the value is in one
+    pub frame: Option<String>,
+    /// The tag, as written in the document
+    pub tag: String,
+    /// Loop row, or `None` for a data item
+    pub row: Option<usize>,
+    /// The value, as text
+    pub value: String,
+    /// What is wrong
+    pub kind: ValidationKind,
+    /// What the definition requires, such as `should be one of d, calc`
+    pub message: String,
+    /// Title of the dictionary whose definition was used
+    pub dictionary: String,
+    /// Save frame of that definition
+    pub definition: String,
+    /// The definition's category, which places a loop or item in the
+    /// dictionary
+    pub category: Option<String>,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "data_{}", self.block)?;
+        if let Some(frame) = &self.frame {
+            write!(f, " save_{frame}")?;
+        }
+        write!(f, " {}", self.tag)?;
+        if let Some(row) = self.row {
+            write!(f, " row {row}")?;
+        }
+        write!(
+            f,
+            ": '{}' {} ({}, save_{})",
+            self.value, self.message, self.dictionary, self.definition
+        )
+    }
+}
+
+/// Checks the values of one block or frame
+struct Validator<'a> {
+    stack: &'a DictionaryStack,
+    block: &'a str,
+    frame: Option<&'a str>,
+    issues: &'a mut Vec<ValidationIssue>,
+}
+
+impl Validator<'_> {
+    fn items(&mut self, items: &IndexMap<String, CifValue>) {
+        for (tag, value) in items {
+            self.check(tag, None, value);
+        }
+    }
+
+    fn loops(&mut self, loops: &[CifLoop]) {
+        for loop_ in loops {
+            for (column, tag) in loop_.tags.iter().enumerate() {
+                if self.stack.definition_of(tag).is_none() {
+                    continue;
+                }
+                for (row, values) in loop_.values.iter().enumerate() {
+                    if let Some(value) = values.get(column) {
+                        self.check(tag, Some(row), value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check(&mut self, tag: &str, row: Option<usize>, value: &CifValue) {
+        let Some(definition) = self.stack.definition_of(tag) else {
+            return;
+        };
+        let Some((kind, message)) = definition.check(value) else {
+            return;
+        };
+        self.issues.push(ValidationIssue {
+            block: self.block.to_string(),
+            frame: self.frame.map(str::to_string),
+            tag: tag.to_string(),
+            row,
+            value: value_text(value).unwrap_or_else(|| inline_value(value, CifVersion::V2_0)),
+            kind,
+            message,
+            dictionary: definition.dictionary.clone(),
+            definition: definition.frame.clone(),
+            category: definition.category.clone(),
+        });
+    }
+}
+
+impl CifBlock {
+    /// Check every value of a tag the stack defines, including those in
+    /// save frames. See the [module documentation](crate::dictionary).
+    pub fn validate(&self, stack: &DictionaryStack) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut validator = Validator {
+            stack,
+            block: &self.name,
+            frame: None,
+            issues: &mut issues,
+        };
+        validator.items(&self.items);
+        validator.loops(&self.loops);
+        for frame in &self.frames {
+            let mut validator = Validator {
+                stack,
+                block: &self.name,
+                frame: Some(&frame.name),
+                issues: &mut issues,
+            };
+            validator.items(&frame.items);
+            validator.loops(&frame.loops);
+        }
+        issues
+    }
+}
+
+impl CifDocument {
+    /// Check every block against the stack, as [`CifBlock::validate`] does
+    pub fn validate(&self, stack: &DictionaryStack) -> Vec<ValidationIssue> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.validate(stack))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("0.0:"), Some((Some(0.0), None)));
+        assert_eq!(parse_range("1:230"), Some((Some(1.0), Some(230.0))));
+        assert_eq!(parse_range(":90"), Some((None, Some(90.0))));
+        assert_eq!(parse_range("x:1"), None);
+        assert_eq!(parse_range("5"), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! Diffraction geometry: the orientation matrix and goniometer axes.
+//!
+//! Single-crystal data CIFs record the UB matrix that takes Miller indices
+//! to reciprocal-space coordinates in the diffractometer frame
+//! (`_diffrn_orient_matrix_UB_11` … `_33`), and imgCIF headers describe the
+//! goniometer as a chain of axes (`_axis.*`). [`CifBlock::orientation_matrix`]
+//! and [`CifBlock::goniometer_axes`] read these back, and
+//! [`CifBlock::check_ub`] compares the matrix with the unit cell.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_cubic
+//! _cell_length_a 10
+//! _cell_length_b 10
+//! _cell_length_c 10
+//! _cell_angle_alpha 90
+//! _cell_angle_beta 90
+//! _cell_angle_gamma 90
+//! _diffrn_orient_matrix_UB_11 0.1
+//! _diffrn_orient_matrix_UB_12 0
+//! _diffrn_orient_matrix_UB_13 0
+//! _diffrn_orient_matrix_UB_21 0
+//! _diffrn_orient_matrix_UB_22 0
+//! _diffrn_orient_matrix_UB_23 -0.1
+//! _diffrn_orient_matrix_UB_31 0
+//! _diffrn_orient_matrix_UB_32 0.1
+//! _diffrn_orient_matrix_UB_33 0
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let block = doc.first_block().unwrap();
+//!
+//! assert_eq!(block.orientation_matrix().unwrap()[1], [0.0, 0.0, -0.1]);
+//! assert!(block.check_ub(1e-6).unwrap().consistent);
+//! ```
+
+use crate::approx::compare;
+use crate::ast::{CifBlock, CifLoop};
+use crate::cell::determinant;
+use crate::publication::value_text;
+
+/// One axis of a goniometer or detector, from an imgCIF `_axis` loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoniometerAxis {
+    /// Axis identifier, e.g. `GONIOMETER_OMEGA`
+    pub id: String,
+    /// `rotation`, `translation`, or `general`, if given
+    pub kind: Option<String>,
+    /// Unit vector along the axis in the laboratory frame, if given
+    pub vector: Option<[f64; 3]>,
+    /// The axis this one is mounted on, if any
+    pub depends_on: Option<String>,
+}
+
+/// Comparison of an orientation matrix with the unit cell, from
+/// [`CifBlock::check_ub`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UbCheck {
+    /// |det(UB)|, in Å⁻³
+    pub determinant: f64,
+    /// 1/V of the unit cell, in Å⁻³
+    pub reciprocal_volume: f64,
+    /// Relative difference, `|det(UB)|·V − 1`
+    pub discrepancy: f64,
+    /// The difference in units of the su of 1/V, or `None` if the cell has
+    /// no sus
+    pub sigmas: Option<f64>,
+    /// Whether the discrepancy is within the tolerance
+    pub consistent: bool,
+}
+
+/// Read a loop column under its mmCIF (`_axis.id`) or core (`_axis_id`) name
+fn column(loop_: &CifLoop, category: &str, attribute: &str, row: usize) -> Option<String> {
+    loop_
+        .get_by_tag(row, &format!("{category}.{attribute}"))
+        .or_else(|| loop_.get_by_tag(row, &format!("{category}_{attribute}")))
+        .and_then(value_text)
+}
+
+/// Find a loop by its mmCIF or core tag
+fn find_loop<'a>(block: &'a CifBlock, category: &str, attribute: &str) -> Option<&'a CifLoop> {
+    block
+        .find_loop(&format!("{category}.{attribute}"))
+        .or_else(|| block.find_loop(&format!("{category}_{attribute}")))
+}
+
+impl CifBlock {
+    /// The orientation matrix UB, in Å⁻¹, as rows.
+    ///
+    /// Element `[i][j]` is `_diffrn_orient_matrix_UB_{i+1}{j+1}` (or the
+    /// mmCIF `_diffrn_orient_matrix.UB[i+1][j+1]`), so that `UB · (h, k, l)`
+    /// gives the reciprocal-lattice vector. Returns `None` unless all nine
+    /// elements are numbers.
+    pub fn orientation_matrix(&self) -> Option<[[f64; 3]; 3]> {
+        let mut ub = [[0.0; 3]; 3];
+        for (i, row) in ub.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                let (i, j) = (i + 1, j + 1);
+                *element = self
+                    .get_item(&format!("_diffrn_orient_matrix_UB_{i}{j}"))
+                    .or_else(|| self.get_item(&format!("_diffrn_orient_matrix.UB[{i}][{j}]")))?
+                    .as_numeric()?;
+            }
+        }
+        Some(ub)
+    }
+
+    /// The goniometer axes, in the order they are listed.
+    ///
+    /// Axes are read from the imgCIF `_axis` loop. If the block has a
+    /// `_diffrn_measurement_axis` loop, only the axes it names are returned;
+    /// otherwise those whose `_axis.equipment` is `goniometer` (or all of
+    /// them, without that column). A `_diffrn_measurement_axis` loop with no
+    /// `_axis` loop gives the axis ids alone.
+    pub fn goniometer_axes(&self) -> Vec<GoniometerAxis> {
+        let measured: Option<Vec<String>> = find_loop(self, "_diffrn_measurement_axis", "axis_id")
+            .map(|loop_| {
+                (0..loop_.len())
+                    .filter_map(|row| column(loop_, "_diffrn_measurement_axis", "axis_id", row))
+                    .collect()
+            });
+
+        let Some(axes) = find_loop(self, "_axis", "id") else {
+            return measured
+                .unwrap_or_default()
+                .into_iter()
+                .map(|id| GoniometerAxis {
+                    id,
+                    kind: None,
+                    vector: None,
+                    depends_on: None,
+                })
+                .collect();
+        };
+
+        (0..axes.len())
+            .filter_map(|row| {
+                let text = |attribute: &str| column(axes, "_axis", attribute, row);
+                let id = text("id")?;
+                let wanted = match (&measured, text("equipment")) {
+                    (Some(ids), _) => ids.contains(&id),
+                    (None, Some(equipment)) => equipment.eq_ignore_ascii_case("goniometer"),
+                    (None, None) => true,
+                };
+                if !wanted {
+                    return None;
+                }
+                let component = |i: usize| {
+                    axes.get_by_tag(row, &format!("_axis.vector[{i}]"))
+                        .or_else(|| axes.get_by_tag(row, &format!("_axis_vector[{i}]")))?
+                        .as_numeric()
+                };
+                let vector = match [1, 2, 3].map(component) {
+                    [Some(x), Some(y), Some(z)] => Some([x, y, z]),
+                    _ => None,
+                };
+                Some(GoniometerAxis {
+                    id,
+                    kind: text("type"),
+                    vector,
+                    depends_on: text("depends_on"),
+                })
+            })
+            .collect()
+    }
+
+    /// Check the orientation matrix against the unit cell.
+    ///
+    /// The determinant of UB is the volume of the reciprocal cell, so
+    /// |det(UB)| should equal 1/V; `tolerance` is the largest relative
+    /// difference accepted. A large discrepancy usually means the matrix and
+    /// cell come from different refinements, or that the matrix is scaled
+    /// (by the wavelength or 2π) rather than in Å⁻¹. The difference is also
+    /// given in σ, using the su of 1/V propagated from the cell sus. Returns
+    /// `None` without both a matrix and a cell.
+    pub fn check_ub(&self, tolerance: f64) -> Option<UbCheck> {
+        let ub = self.orientation_matrix()?;
+        let (volume, volume_su) = self.unit_cell()?.volume_with_su();
+        let determinant = determinant(&ub).abs();
+        let discrepancy = determinant * volume - 1.0;
+        let reciprocal_volume = 1.0 / volume;
+        let reciprocal_su = volume_su / (volume * volume);
+        let sigmas = (reciprocal_su > 0.0)
+            .then(|| {
+                compare(
+                    (determinant, None),
+                    (reciprocal_volume, Some(reciprocal_su)),
+                    3.0,
+                )
+                .sigmas()
+            })
+            .flatten();
+        Some(UbCheck {
+            determinant,
+            reciprocal_volume,
+            discrepancy,
+            sigmas,
+            consistent: discrepancy.abs() <= tolerance,
+        })
+    }
+}
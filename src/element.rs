@@ -0,0 +1,357 @@
+//! Chemical elements and atom type symbols.
+//!
+//! `_atom_type_symbol` and `_atom_site_type_symbol` name an element with an
+//! optional charge, but real files write the charge both ways round (`O2-`,
+//! `O-2`), in any case (`FE2+`), and use `D` and `T` for deuterium and
+//! tritium. [`ElementSymbol::parse`] reads all of these into an [`Element`],
+//! a charge, and an [`Isotope`].
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::element::{Element, ElementSymbol, Isotope};
+//!
+//! let iron = ElementSymbol::parse("FE3+").unwrap();
+//! assert_eq!(iron.element.symbol(), "Fe");
+//! assert_eq!(iron.charge, Some(3));
+//!
+//! let oxide = ElementSymbol::parse("O-2").unwrap();
+//! assert_eq!(oxide.to_string(), "O2-");
+//!
+//! let deuterium = ElementSymbol::parse("D").unwrap();
+//! assert_eq!(deuterium.element, Element::from_symbol("H").unwrap());
+//! assert_eq!(deuterium.isotope, Some(Isotope::Deuterium));
+//!
+//! assert!(ElementSymbol::parse("Wat").is_err());
+//! ```
+
+use crate::error::CifError;
+use crate::formula::atomic_weight;
+use std::fmt;
+
+/// A chemical element, from hydrogen to oganesson.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Element(u8);
+
+impl Element {
+    /// The element with this symbol, matched case-insensitively (`Fe`,
+    /// `FE`, `fe`)
+    pub fn from_symbol(symbol: &str) -> Option<Element> {
+        ELEMENTS
+            .iter()
+            .position(|(s, _)| s.eq_ignore_ascii_case(symbol))
+            .map(|i| Element(i as u8 + 1))
+    }
+
+    /// The element with this atomic number (1 to 118)
+    pub fn from_atomic_number(number: u8) -> Option<Element> {
+        (1..=ELEMENTS.len() as u8)
+            .contains(&number)
+            .then_some(Element(number))
+    }
+
+    /// Atomic number
+    pub fn atomic_number(self) -> u8 {
+        self.0
+    }
+
+    /// Element symbol, such as `Fe`
+    pub fn symbol(self) -> &'static str {
+        ELEMENTS[self.0 as usize - 1].0
+    }
+
+    /// English name, such as `Iron`
+    pub fn name(self) -> &'static str {
+        ELEMENTS[self.0 as usize - 1].1
+    }
+
+    /// Standard atomic weight in daltons, if known (see
+    /// [`atomic_weight`](crate::formula::atomic_weight))
+    pub fn atomic_weight(self) -> Option<f64> {
+        atomic_weight(self.symbol())
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// A hydrogen isotope with its own symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Isotope {
+    /// `D`, hydrogen-2
+    Deuterium,
+    /// `T`, hydrogen-3
+    Tritium,
+}
+
+impl Isotope {
+    /// Mass number of the isotope
+    pub fn mass_number(self) -> u8 {
+        match self {
+            Isotope::Deuterium => 2,
+            Isotope::Tritium => 3,
+        }
+    }
+
+    /// The isotope's own symbol, `D` or `T`
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Isotope::Deuterium => "D",
+            Isotope::Tritium => "T",
+        }
+    }
+}
+
+/// An atom type symbol read into element, charge, and isotope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElementSymbol {
+    /// The element; deuterium and tritium are hydrogen
+    pub element: Element,
+    /// Formal charge, if the symbol gives one
+    pub charge: Option<i8>,
+    /// Set for `D` and `T`
+    pub isotope: Option<Isotope>,
+}
+
+impl ElementSymbol {
+    /// Read an atom type symbol such as `Fe3+`, `O-2`, `Na+`, `CL` or `D`.
+    ///
+    /// The element is the leading letters, matched case-insensitively
+    /// against the whole periodic table; `D` and `T` give hydrogen with an
+    /// [`Isotope`]. A charge may follow with the sign before or after the
+    /// digits, and a bare sign means one. Anything else, such as `Wat`,
+    /// `O2` without a sign, or a fractional charge, is an error naming the
+    /// original token.
+    pub fn parse(s: &str) -> Result<ElementSymbol, CifError> {
+        let invalid = || CifError::InvalidValue {
+            kind: "atom type symbol",
+            value: s.to_string(),
+        };
+        let token = s.trim();
+        let letters = token.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let (symbol, charge) = token.split_at(letters);
+
+        let (element, isotope) = match symbol.to_ascii_uppercase().as_str() {
+            "D" => (Element(1), Some(Isotope::Deuterium)),
+            "T" => (Element(1), Some(Isotope::Tritium)),
+            _ => (Element::from_symbol(symbol).ok_or_else(invalid)?, None),
+        };
+        Ok(ElementSymbol {
+            element,
+            charge: parse_charge(charge).ok_or_else(invalid)?,
+            isotope,
+        })
+    }
+}
+
+impl fmt::Display for ElementSymbol {
+    /// The symbol as the CIF dictionaries write it, such as `Fe3+`, `O2-`,
+    /// `Na+` or `D`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.isotope {
+            Some(isotope) => f.write_str(isotope.symbol())?,
+            None => f.write_str(self.element.symbol())?,
+        }
+        match self.charge {
+            Some(charge @ (1 | -1)) => f.write_str(if charge > 0 { "+" } else { "-" }),
+            Some(charge) if charge < 0 => write!(f, "{}-", charge.unsigned_abs()),
+            Some(charge) => write!(f, "{charge}+"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Read the charge part of a type symbol: `""`, `+`, `3+` or `+3`
+fn parse_charge(s: &str) -> Option<Option<i8>> {
+    if s.is_empty() {
+        return Some(None);
+    }
+    let (digits, sign) = if let Some(digits) = s.strip_suffix(['+', '-']) {
+        (digits, &s[digits.len()..])
+    } else {
+        let digits = s.strip_prefix(['+', '-'])?;
+        (digits, &s[..1])
+    };
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let magnitude: i8 = if digits.is_empty() {
+        1
+    } else {
+        digits.parse().ok()?
+    };
+    Some(Some(if sign == "-" { -magnitude } else { magnitude }))
+}
+
+/// Symbol and name of every element, by atomic number
+const ELEMENTS: [(&str, &str); 118] = [
+    ("H", "Hydrogen"),
+    ("He", "Helium"),
+    ("Li", "Lithium"),
+    ("Be", "Beryllium"),
+    ("B", "Boron"),
+    ("C", "Carbon"),
+    ("N", "Nitrogen"),
+    ("O", "Oxygen"),
+    ("F", "Fluorine"),
+    ("Ne", "Neon"),
+    ("Na", "Sodium"),
+    ("Mg", "Magnesium"),
+    ("Al", "Aluminium"),
+    ("Si", "Silicon"),
+    ("P", "Phosphorus"),
+    ("S", "Sulfur"),
+    ("Cl", "Chlorine"),
+    ("Ar", "Argon"),
+    ("K", "Potassium"),
+    ("Ca", "Calcium"),
+    ("Sc", "Scandium"),
+    ("Ti", "Titanium"),
+    ("V", "Vanadium"),
+    ("Cr", "Chromium"),
+    ("Mn", "Manganese"),
+    ("Fe", "Iron"),
+    ("Co", "Cobalt"),
+    ("Ni", "Nickel"),
+    ("Cu", "Copper"),
+    ("Zn", "Zinc"),
+    ("Ga", "Gallium"),
+    ("Ge", "Germanium"),
+    ("As", "Arsenic"),
+    ("Se", "Selenium"),
+    ("Br", "Bromine"),
+    ("Kr", "Krypton"),
+    ("Rb", "Rubidium"),
+    ("Sr", "Strontium"),
+    ("Y", "Yttrium"),
+    ("Zr", "Zirconium"),
+    ("Nb", "Niobium"),
+    ("Mo", "Molybdenum"),
+    ("Tc", "Technetium"),
+    ("Ru", "Ruthenium"),
+    ("Rh", "Rhodium"),
+    ("Pd", "Palladium"),
+    ("Ag", "Silver"),
+    ("Cd", "Cadmium"),
+    ("In", "Indium"),
+    ("Sn", "Tin"),
+    ("Sb", "Antimony"),
+    ("Te", "Tellurium"),
+    ("I", "Iodine"),
+    ("Xe", "Xenon"),
+    ("Cs", "Caesium"),
+    ("Ba", "Barium"),
+    ("La", "Lanthanum"),
+    ("Ce", "Cerium"),
+    ("Pr", "Praseodymium"),
+    ("Nd", "Neodymium"),
+    ("Pm", "Promethium"),
+    ("Sm", "Samarium"),
+    ("Eu", "Europium"),
+    ("Gd", "Gadolinium"),
+    ("Tb", "Terbium"),
+    ("Dy", "Dysprosium"),
+    ("Ho", "Holmium"),
+    ("Er", "Erbium"),
+    ("Tm", "Thulium"),
+    ("Yb", "Ytterbium"),
+    ("Lu", "Lutetium"),
+    ("Hf", "Hafnium"),
+    ("Ta", "Tantalum"),
+    ("W", "Tungsten"),
+    ("Re", "Rhenium"),
+    ("Os", "Osmium"),
+    ("Ir", "Iridium"),
+    ("Pt", "Platinum"),
+    ("Au", "Gold"),
+    ("Hg", "Mercury"),
+    ("Tl", "Thallium"),
+    ("Pb", "Lead"),
+    ("Bi", "Bismuth"),
+    ("Po", "Polonium"),
+    ("At", "Astatine"),
+    ("Rn", "Radon"),
+    ("Fr", "Francium"),
+    ("Ra", "Radium"),
+    ("Ac", "Actinium"),
+    ("Th", "Thorium"),
+    ("Pa", "Protactinium"),
+    ("U", "Uranium"),
+    ("Np", "Neptunium"),
+    ("Pu", "Plutonium"),
+    ("Am", "Americium"),
+    ("Cm", "Curium"),
+    ("Bk", "Berkelium"),
+    ("Cf", "Californium"),
+    ("Es", "Einsteinium"),
+    ("Fm", "Fermium"),
+    ("Md", "Mendelevium"),
+    ("No", "Nobelium"),
+    ("Lr", "Lawrencium"),
+    ("Rf", "Rutherfordium"),
+    ("Db", "Dubnium"),
+    ("Sg", "Seaborgium"),
+    ("Bh", "Bohrium"),
+    ("Hs", "Hassium"),
+    ("Mt", "Meitnerium"),
+    ("Ds", "Darmstadtium"),
+    ("Rg", "Roentgenium"),
+    ("Cn", "Copernicium"),
+    ("Nh", "Nihonium"),
+    ("Fl", "Flerovium"),
+    ("Mc", "Moscovium"),
+    ("Lv", "Livermorium"),
+    ("Ts", "Tennessine"),
+    ("Og", "Oganesson"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periodic_table() {
+        for number in 1..=118 {
+            let element = Element::from_atomic_number(number).unwrap();
+            assert_eq!(Element::from_symbol(element.symbol()), Some(element));
+            assert_eq!(element.atomic_number(), number);
+        }
+        assert_eq!(Element::from_atomic_number(0), None);
+        assert_eq!(Element::from_atomic_number(119), None);
+        assert_eq!(Element::from_symbol("Og").unwrap().name(), "Oganesson");
+        assert_eq!(Element::from_symbol("D"), None);
+    }
+
+    #[test]
+    fn test_atomic_weights_cover_the_formula_table() {
+        assert_eq!(
+            Element::from_symbol("Fe").unwrap().atomic_weight(),
+            Some(55.845)
+        );
+        assert_eq!(Element::from_symbol("Og").unwrap().atomic_weight(), None);
+    }
+
+    #[test]
+    fn test_parse_charge() {
+        assert_eq!(parse_charge(""), Some(None));
+        assert_eq!(parse_charge("+"), Some(Some(1)));
+        assert_eq!(parse_charge("-"), Some(Some(-1)));
+        assert_eq!(parse_charge("2-"), Some(Some(-2)));
+        assert_eq!(parse_charge("-2"), Some(Some(-2)));
+        assert_eq!(parse_charge("+3"), Some(Some(3)));
+        assert_eq!(parse_charge("2"), None);
+        assert_eq!(parse_charge("+-"), None);
+        assert_eq!(parse_charge("1.5+"), None);
+        assert_eq!(parse_charge("200+"), None);
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for symbol in ["Fe3+", "O2-", "Na+", "Cl-", "D", "T+", "C", "Fe0+"] {
+            assert_eq!(ElementSymbol::parse(symbol).unwrap().to_string(), symbol);
+        }
+    }
+}
@@ -2,67 +2,224 @@
 //!
 //! This module defines the error types that can occur during CIF file parsing
 //! and provides conversions from underlying error types.
+//!
+//! # Error Codes
+//!
+//! Every [`CifError`] has a stable code, given by [`CifError::code`] and at
+//! the start of its message, so that tools can tell errors apart without
+//! matching on the text. Codes are never reused for a different error. The
+//! Python bindings raise each as a built-in exception with the code in its
+//! `code` attribute:
+//!
+//! | Code    | Variant                                          | Python exception |
+//! |---------|--------------------------------------------------|------------------|
+//! | `E0001` | [`Syntax`](CifError::Syntax)                     | `ValueError`     |
+//! | `E0002` | [`Io`](CifError::Io)                             | `OSError`        |
+//! | `E0003` | [`Encoding`](CifError::Encoding)                 | `UnicodeError`   |
+//! | `E0004` | [`Unterminated`](CifError::Unterminated)         | `ValueError`     |
+//! | `E0005` | [`DuplicateTag`](CifError::DuplicateTag)         | `ValueError`     |
+//! | `E0006` | [`LoopMismatch`](CifError::LoopMismatch)         | `ValueError`     |
+//! | `E0007` | [`NestingTooDeep`](CifError::NestingTooDeep)     | `ValueError`     |
+//! | `E0008` | [`InvalidStructure`](CifError::InvalidStructure) | `ValueError`     |
+//! | `E0009` | [`InvalidValue`](CifError::InvalidValue)         | `ValueError`     |
+//! | `E0010` | [`RowLength`](CifError::RowLength)               | `ValueError`     |
+//! | `E0011` | [`MissingTag`](CifError::MissingTag)             | `KeyError`       |
+//! | `E0012` | [`NonConformant`](CifError::NonConformant)       | `ValueError`     |
+//! | `E0013` | [`InvalidQuery`](CifError::InvalidQuery)         | `ValueError`     |
+//!
+//! [`InFile`](CifError::InFile) wraps any of these with the file it came
+//! from and has the code of the error inside. `UnicodeError` is a subclass
+//! of `ValueError`, so code catching `ValueError` for every parse failure
+//! keeps working.
 
+use crate::conformance::ConformanceRule;
 use crate::Rule;
+use pest::error::LineColLocation;
 use std::error::Error;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 /// Custom error type for CIF parsing with enhanced error information.
 ///
-/// # Error Categories
-///
-/// - **ParseError**: Grammar-level parsing failures (from PEST)
-/// - **IoError**: File I/O failures
-/// - **InvalidStructure**: Semantic validation failures with optional location info
-///
-/// # Location Tracking
-///
-/// Errors now include line and column information when available, making it easy
-/// to pinpoint issues in CIF files.
+/// Each variant carries what is known about where the error is: the line
+/// and column, and the tag or value involved. An error raised while parsing
+/// a whole input also has its `span`, the byte offset and the quoted line,
+/// so they can be shown without the input. [`with_path`](Self::with_path)
+/// adds the file. New variants may be added, so matches need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum CifError {
-    /// Grammar parsing error from PEST (already includes location info)
-    ParseError(String),
-    /// File I/O error
-    IoError(std::io::Error),
-    /// Semantic structure validation error with optional source location
+    /// `E0001`: the input does not follow the CIF grammar. The message is
+    /// the parser's, with the offending line and a caret under the column.
+    Syntax {
+        message: String,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0002`: a file could not be read or written
+    Io(std::io::Error),
+    /// `E0003`: the input is not UTF-8, such as a UTF-16 file or an invalid
+    /// byte
+    Encoding {
+        message: String,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0004`: a quoted string or text field is never closed; `construct`
+    /// says which, such as `text field`
+    Unterminated {
+        construct: String,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0005`: a data name appears twice in a block or save frame
+    DuplicateTag {
+        tag: String,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0006`: a loop's values do not fill a whole number of rows
+    LoopMismatch {
+        /// Number of tags in the loop
+        tags: usize,
+        /// Number of values read
+        values: usize,
+        /// The reserved word, such as `data_x`, that ended the loop mid-row
+        reserved_word: Option<String>,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0007`: lists and tables are nested deeper than the parser allows
+    NestingTooDeep {
+        max_depth: usize,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0008`: any other violation of the structure CIF requires
     InvalidStructure {
         message: String,
         location: Option<(usize, usize)>, // (line, column)
+        span: Option<Box<ErrorSpan>>,
+    },
+    /// `E0009`: a value cannot be read as what it should be; `kind` says
+    /// what, such as `atom type symbol`
+    InvalidValue { kind: &'static str, value: String },
+    /// `E0010`: a row added to a loop has the wrong number of values
+    RowLength { expected: usize, found: usize },
+    /// `E0011`: a loop has no column with the tag an operation needs
+    MissingTag { tag: String },
+    /// `E0012`: the input or document breaks a `rule` of the CIF 1.1
+    /// specification, checked with
+    /// [`Conformance::Strict11`](crate::Conformance::Strict11); the message
+    /// ends with the clause broken
+    NonConformant {
+        rule: ConformanceRule,
+        message: String,
+        location: Option<(usize, usize)>,
+        span: Option<Box<ErrorSpan>>,
     },
+    /// `E0013`: a [query](crate::query) selector cannot be parsed. The
+    /// message shows the selector with a caret under the 1-indexed
+    /// character `position`.
+    InvalidQuery {
+        message: String,
+        query: String,
+        position: usize,
+    },
+    /// Error from parsing a file, tagged with the file's path
+    InFile { path: PathBuf, error: Box<CifError> },
 }
 
 impl fmt::Display for CifError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let CifError::InFile { path, error } = self {
+            return write!(f, "{}: {error}", path.display());
+        }
+        write!(f, "[{}] ", self.code())?;
+        // Syntax messages show the line themselves
+        if let (Some((line, col)), false) = (self.location(), self.is_syntax()) {
+            write!(f, "Error at line {line}, column {col}: ")?;
+        }
         match self {
-            CifError::ParseError(msg) => write!(f, "Parse error: {msg}"),
-            CifError::IoError(err) => write!(f, "IO error: {err}"),
-            CifError::InvalidStructure { message, location } => {
-                if let Some((line, col)) = location {
-                    write!(
-                        f,
-                        "Error at line {}, column {}: Invalid CIF structure: {}",
-                        line, col, message
-                    )
-                } else {
-                    write!(f, "Invalid CIF structure: {}", message)
-                }
+            CifError::Syntax { message, .. } => write!(f, "Parse error: {message}"),
+            CifError::Io(err) => write!(f, "IO error: {err}"),
+            CifError::Encoding { message, .. } => write!(f, "{message}"),
+            CifError::Unterminated { construct, .. } => write!(f, "Unterminated {construct}"),
+            CifError::DuplicateTag { tag, .. } => write!(
+                f,
+                "Duplicate tag '{tag}': data names must be unique within a block or frame"
+            ),
+            CifError::LoopMismatch {
+                tags,
+                values,
+                reserved_word: Some(word),
+                ..
+            } => write!(
+                f,
+                "Loop has {tags} tags but {values} values: it was ended mid-row by the \
+                 reserved word '{word}', which must be quoted if it is a value"
+            ),
+            CifError::LoopMismatch { tags, values, .. } => {
+                write!(
+                    f,
+                    "Loop has {tags} tags but {values} values (not divisible)"
+                )
             }
+            CifError::NestingTooDeep { max_depth, .. } => write!(
+                f,
+                "List/table nesting exceeds the maximum depth of {max_depth}"
+            ),
+            CifError::InvalidStructure { message, .. } => {
+                write!(f, "Invalid CIF structure: {message}")
+            }
+            CifError::InvalidValue { kind, value } => write!(f, "Invalid {kind} '{value}'"),
+            CifError::RowLength { expected, found } => {
+                write!(f, "Row has {found} values but the loop has {expected} tags")
+            }
+            CifError::MissingTag { tag } => write!(f, "The loop has no tag '{tag}'"),
+            CifError::NonConformant { rule, message, .. } => {
+                write!(f, "{message} ({})", rule.clause())
+            }
+            CifError::InvalidQuery {
+                message,
+                query,
+                position,
+            } => write!(
+                f,
+                "Invalid query at character {position}: {message}\n  {query}\n  {:>position$}",
+                "^"
+            ),
+            CifError::InFile { .. } => Ok(()),
         }
     }
 }
 
-impl Error for CifError {}
+impl Error for CifError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CifError::Io(err) => Some(err),
+            CifError::InFile { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for CifError {
     fn from(err: std::io::Error) -> Self {
-        CifError::IoError(err)
+        CifError::Io(err)
     }
 }
 
 impl From<pest::error::Error<Rule>> for CifError {
     fn from(err: pest::error::Error<Rule>) -> Self {
-        CifError::ParseError(format!("{err}"))
+        let location = match err.line_col {
+            LineColLocation::Pos(start) | LineColLocation::Span(start, _) => start,
+        };
+        CifError::Syntax {
+            message: format!("{err}"),
+            location: Some(location),
+            span: None,
+        }
     }
 }
 
@@ -72,17 +229,367 @@ impl CifError {
         CifError::InvalidStructure {
             message: msg.into(),
             location: None,
+            span: None,
         }
     }
 
-    /// Add location information to this error
-    pub(crate) fn at_location(self, line: usize, col: usize) -> Self {
+    /// Create an Encoding error with the given message (no location)
+    pub(crate) fn encoding(msg: impl Into<String>) -> Self {
+        CifError::Encoding {
+            message: msg.into(),
+            location: None,
+            span: None,
+        }
+    }
+
+    /// Create a NonConformant error for `rule` (no location)
+    pub(crate) fn non_conformant(rule: ConformanceRule, message: impl Into<String>) -> Self {
+        CifError::NonConformant {
+            rule,
+            message: message.into(),
+            location: None,
+            span: None,
+        }
+    }
+
+    /// Stable code of the error, such as `E0005` for a duplicate tag.
+    ///
+    /// See the [module documentation](crate::error) for the list. A file's
+    /// error has the code of the error inside it.
+    pub fn code(&self) -> &'static str {
         match self {
-            CifError::InvalidStructure { message, .. } => CifError::InvalidStructure {
-                message,
-                location: Some((line, col)),
-            },
-            other => other, // Can't add location to ParseError or IoError
+            CifError::Syntax { .. } => "E0001",
+            CifError::Io(_) => "E0002",
+            CifError::Encoding { .. } => "E0003",
+            CifError::Unterminated { .. } => "E0004",
+            CifError::DuplicateTag { .. } => "E0005",
+            CifError::LoopMismatch { .. } => "E0006",
+            CifError::NestingTooDeep { .. } => "E0007",
+            CifError::InvalidStructure { .. } => "E0008",
+            CifError::InvalidValue { .. } => "E0009",
+            CifError::RowLength { .. } => "E0010",
+            CifError::MissingTag { .. } => "E0011",
+            CifError::NonConformant { .. } => "E0012",
+            CifError::InvalidQuery { .. } => "E0013",
+            CifError::InFile { error, .. } => error.code(),
+        }
+    }
+
+    fn is_syntax(&self) -> bool {
+        matches!(self, CifError::Syntax { .. })
+    }
+
+    /// The location field of the error, if it has one
+    fn location_mut(&mut self) -> Option<&mut Option<(usize, usize)>> {
+        match self {
+            CifError::Syntax { location, .. }
+            | CifError::Encoding { location, .. }
+            | CifError::Unterminated { location, .. }
+            | CifError::DuplicateTag { location, .. }
+            | CifError::LoopMismatch { location, .. }
+            | CifError::NestingTooDeep { location, .. }
+            | CifError::InvalidStructure { location, .. }
+            | CifError::NonConformant { location, .. } => Some(location),
+            CifError::InFile { error, .. } => error.location_mut(),
+            CifError::Io(_)
+            | CifError::InvalidValue { .. }
+            | CifError::RowLength { .. }
+            | CifError::MissingTag { .. }
+            | CifError::InvalidQuery { .. } => None,
+        }
+    }
+
+    /// The span field of the error, if it has one
+    fn span_mut(&mut self) -> Option<&mut Option<Box<ErrorSpan>>> {
+        match self {
+            CifError::Syntax { span, .. }
+            | CifError::Encoding { span, .. }
+            | CifError::Unterminated { span, .. }
+            | CifError::DuplicateTag { span, .. }
+            | CifError::LoopMismatch { span, .. }
+            | CifError::NestingTooDeep { span, .. }
+            | CifError::InvalidStructure { span, .. }
+            | CifError::NonConformant { span, .. } => Some(span),
+            CifError::InFile { error, .. } => error.span_mut(),
+            CifError::Io(_)
+            | CifError::InvalidValue { .. }
+            | CifError::RowLength { .. }
+            | CifError::MissingTag { .. }
+            | CifError::InvalidQuery { .. } => None,
+        }
+    }
+
+    /// Add location information to this error, dropping any span recorded
+    /// for the old location
+    pub(crate) fn at_location(mut self, line: usize, col: usize) -> Self {
+        // IO errors and errors in values outside a file have no location
+        if let Some(location) = self.location_mut() {
+            *location = Some((line, col));
+        }
+        if let Some(span) = self.span_mut() {
+            *span = None;
+        }
+        self
+    }
+
+    /// Move an error raised in `piece`, a part of a larger input starting
+    /// `lines` lines and `bytes` bytes into it, to its place in the whole
+    /// input, with its span there
+    pub(crate) fn in_whole(self, piece: &str, lines: usize, bytes: usize) -> Self {
+        let Some((line, col)) = self.location() else {
+            return self;
+        };
+        let span =
+            self.locate(piece)
+                .zip(self.offset_in(piece))
+                .map(|((_, text, column), offset)| {
+                    Box::new(ErrorSpan {
+                        offset: offset + bytes,
+                        excerpt: excerpt(line + lines, text, column),
+                    })
+                });
+        let mut error = self.at_location(line + lines, col);
+        if let Some(slot) = error.span_mut() {
+            *slot = span;
+        }
+        error
+    }
+
+    /// Record the byte offset and the excerpt of the error's location in
+    /// `input`, the text that was parsed, if it has a location there.
+    ///
+    /// Parsing a whole input does this before returning an error; it is
+    /// for errors whose input was read piece by piece, as by
+    /// [`CifReader`](crate::reader::CifReader).
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::reader::CifReader;
+    ///
+    /// let input = "data_a\n_x 1\n_y 'open\n";
+    /// let error = CifReader::new(input.as_bytes())
+    ///     .find_map(Result::err)
+    ///     .unwrap()
+    ///     .with_source(input);
+    /// assert_eq!(error.byte_offset(), Some(15));
+    /// ```
+    pub fn with_source(mut self, input: &str) -> Self {
+        let span = match (self.location(), self.locate(input), self.offset_in(input)) {
+            (Some((number, _)), Some((_, line, col)), Some(offset)) => Some(Box::new(ErrorSpan {
+                offset,
+                excerpt: excerpt(number, line, col),
+            })),
+            _ => None,
+        };
+        if let Some(slot) = self.span_mut() {
+            *slot = span;
+        }
+        self
+    }
+
+    /// Tag this error with the file it came from, replacing any earlier path
+    pub fn with_path(self, path: impl AsRef<Path>) -> Self {
+        let error = match self {
+            CifError::InFile { error, .. } => error,
+            other => Box::new(other),
+        };
+        CifError::InFile {
+            path: path.as_ref().to_path_buf(),
+            error,
+        }
+    }
+
+    /// Path of the file this error came from, if known
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            CifError::InFile { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Line and column the error points at, if known
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            CifError::Syntax { location, .. }
+            | CifError::Encoding { location, .. }
+            | CifError::Unterminated { location, .. }
+            | CifError::DuplicateTag { location, .. }
+            | CifError::LoopMismatch { location, .. }
+            | CifError::NestingTooDeep { location, .. }
+            | CifError::InvalidStructure { location, .. }
+            | CifError::NonConformant { location, .. } => *location,
+            CifError::InFile { error, .. } => error.location(),
+            CifError::Io(_)
+            | CifError::InvalidValue { .. }
+            | CifError::RowLength { .. }
+            | CifError::MissingTag { .. }
+            | CifError::InvalidQuery { .. } => None,
+        }
+    }
+
+    /// Where the error is in the input it was raised for, if recorded
+    pub fn span(&self) -> Option<&ErrorSpan> {
+        match self {
+            CifError::Syntax { span, .. }
+            | CifError::Encoding { span, .. }
+            | CifError::Unterminated { span, .. }
+            | CifError::DuplicateTag { span, .. }
+            | CifError::LoopMismatch { span, .. }
+            | CifError::NestingTooDeep { span, .. }
+            | CifError::InvalidStructure { span, .. }
+            | CifError::NonConformant { span, .. } => span.as_deref(),
+            CifError::InFile { error, .. } => error.span(),
+            CifError::Io(_)
+            | CifError::InvalidValue { .. }
+            | CifError::RowLength { .. }
+            | CifError::MissingTag { .. }
+            | CifError::InvalidQuery { .. } => None,
+        }
+    }
+
+    /// Byte offset in the input of the point the error's location refers to
+    ///
+    /// The input is the text that was parsed, as given: lines end at LF,
+    /// CRLF, or CR, and a leading byte-order mark is skipped, as in the
+    /// parser. `None` if the error has no location in it.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// // The loop starting at line 3 is short of a value
+    /// let input = "data_a\r\n_x 1\r\nloop_ _y _z\r\n1\r\n";
+    /// let error = Document::parse(input).unwrap_err();
+    /// assert_eq!(error.location(), Some((3, 1)));
+    /// assert_eq!(error.byte_offset(), Some(14));
+    /// ```
+    pub fn byte_offset(&self) -> Option<usize> {
+        self.span().map(|span| span.offset)
+    }
+
+    /// The line the error points at, with its number and a caret under the
+    /// column, for showing with the message
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let input = "data_a\n_x 1\n\tloop_ _y _z\n1\n";
+    /// let error = Document::parse(input).unwrap_err();
+    /// assert_eq!(error.excerpt().unwrap(), "  |\n3 | \tloop_ _y _z\n  | \t^");
+    /// ```
+    pub fn excerpt(&self) -> Option<&str> {
+        self.span().map(|span| span.excerpt.as_str())
+    }
+
+    /// Byte offset in `input` of the error's location, for errors whose
+    /// span is not recorded
+    pub(crate) fn offset_in(&self, input: &str) -> Option<usize> {
+        self.locate(input).map(|(start, line, col)| {
+            start + line.char_indices().nth(col).map_or(line.len(), |(i, _)| i)
+        })
+    }
+
+    /// Byte offset of the start of the error's line in `input`, the line
+    /// without its terminator, and the 0-indexed column in characters
+    fn locate<'a>(&self, input: &'a str) -> Option<(usize, &'a str, usize)> {
+        let (line, col) = self.location()?;
+        let bom = if input.starts_with('\u{FEFF}') {
+            '\u{FEFF}'.len_utf8()
+        } else {
+            0
+        };
+        let bytes = input.as_bytes();
+        let mut start = bom;
+        for _ in 1..line {
+            let end = start + input[start..].find(['\n', '\r'])?;
+            start = if bytes[end] == b'\r' && bytes.get(end + 1) == Some(&b'\n') {
+                end + 2
+            } else {
+                end + 1
+            };
+        }
+        let rest = &input[start..];
+        let text = &rest[..rest.find(['\n', '\r']).unwrap_or(rest.len())];
+        let col = col.checked_sub(1)?;
+        (col <= text.chars().count()).then_some((start, text, col))
+    }
+}
+
+/// Where an error is in the input it was raised for: see [`CifError::span`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorSpan {
+    /// Byte offset of the point the error's location refers to
+    pub offset: usize,
+    /// The line the error points at, with its number and a caret under the
+    /// column
+    pub excerpt: String,
+}
+
+/// The excerpt of line `number`, `line`, with a caret under the 0-indexed
+/// column `col`
+fn excerpt(number: usize, line: &str, col: usize) -> String {
+    let gutter = " ".repeat(number.to_string().len());
+    // Tabs stay tabs, so the caret lines up however they are shown
+    let indent: String = line
+        .chars()
+        .take(col)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    format!("{gutter} |\n{number} | {line}\n{gutter} | {indent}^")
+}
+
+/// A recoverable problem encountered while parsing in lenient mode.
+///
+/// Warnings never prevent a document from being produced; they record where
+/// the parser deviated from the specification so callers can audit the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CifWarning {
+    /// Human-readable description of the problem and how it was handled
+    pub message: String,
+    /// Source location as (line, column), when known
+    pub location: Option<(usize, usize)>,
+    /// Path of the file the warning came from, when known
+    pub path: Option<PathBuf>,
+}
+
+impl CifWarning {
+    /// Create a warning with the given message (no location)
+    pub fn new(message: impl Into<String>) -> Self {
+        CifWarning {
+            message: message.into(),
+            location: None,
+            path: None,
+        }
+    }
+
+    /// Add location information to this warning
+    pub fn at_location(mut self, line: usize, col: usize) -> Self {
+        self.location = Some((line, col));
+        self
+    }
+
+    /// Tag this warning with the file it came from
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+}
+
+impl fmt::Display for CifWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.display())?;
+        }
+        if let Some((line, col)) = self.location {
+            write!(
+                f,
+                "Warning at line {}, column {}: {}",
+                line, col, self.message
+            )
+        } else {
+            write!(f, "Warning: {}", self.message)
         }
     }
 }
@@ -0,0 +1,232 @@
+//! Experimental conditions: the crystal, temperature, absorption, and density.
+//!
+//! [`CifBlock::experimental`] gathers the `_exptl_crystal_*`,
+//! `_exptl_absorpt_*`, and `_diffrn_ambient_temperature` items that a
+//! structure report lists, in their core or mmCIF spellings, with sus where
+//! the values are numbers. [`Experimental::check`] compares the reported
+//! density with the one computed from the formula weight, Z, and cell volume.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_urea
+//! _exptl_crystal_colour colourless
+//! _exptl_crystal_size_max 0.30
+//! _exptl_crystal_size_mid 0.20
+//! _exptl_crystal_size_min 0.10
+//! _diffrn_ambient_temperature 150(2)
+//! _exptl_crystal_density_diffrn 1.337
+//! _chemical_formula_weight 60.06
+//! _cell_formula_units_Z 2
+//! _cell_volume 149.2(3)
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let exptl = doc.first_block().unwrap().experimental();
+//!
+//! assert_eq!(exptl.colour.as_deref(), Some("colourless"));
+//! assert_eq!(exptl.temperature, Some((150.0, Some(2.0))));
+//! assert!((exptl.crystal_volume().unwrap() - 0.006).abs() < 1e-12);
+//! assert!(exptl.check().is_empty());
+//! ```
+
+use crate::ast::{CifBlock, CifValue};
+use crate::publication::value_text;
+use crate::sanity::SanityIssue;
+use crate::uncertainty;
+
+/// Daltons per ų in g/cm³: 1 g/mol / N_A / 1 ų
+const DENSITY_FACTOR: f64 = 1.660_539;
+
+/// Largest relative difference allowed between the reported and computed
+/// densities
+const DENSITY_TOLERANCE: f64 = 0.01;
+
+/// Experimental conditions gathered from a data block.
+///
+/// Numbers come with their su, if written with one. Absent values and
+/// `?`/`.` are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Experimental {
+    /// `_exptl_crystal_colour`
+    pub colour: Option<String>,
+    /// `_exptl_crystal_description`, the crystal habit
+    pub habit: Option<String>,
+    /// `_exptl_crystal_size_min`, in mm
+    pub size_min: Option<(f64, Option<f64>)>,
+    /// `_exptl_crystal_size_mid`, in mm
+    pub size_mid: Option<(f64, Option<f64>)>,
+    /// `_exptl_crystal_size_max`, in mm
+    pub size_max: Option<(f64, Option<f64>)>,
+    /// `_diffrn_ambient_temperature`, in K
+    pub temperature: Option<(f64, Option<f64>)>,
+    /// `_exptl_absorpt_coefficient_mu`, in mm⁻¹
+    pub absorption_mu: Option<(f64, Option<f64>)>,
+    /// `_exptl_absorpt_correction_type`, such as `multi-scan`
+    pub absorption_correction: Option<String>,
+    /// `_exptl_absorpt_correction_T_min`
+    pub t_min: Option<(f64, Option<f64>)>,
+    /// `_exptl_absorpt_correction_T_max`
+    pub t_max: Option<(f64, Option<f64>)>,
+    /// `_exptl_crystal_density_meas`, in g/cm³
+    pub density_measured: Option<(f64, Option<f64>)>,
+    /// `_exptl_crystal_density_diffrn`, in g/cm³
+    pub density_calculated: Option<(f64, Option<f64>)>,
+    /// Density from the formula weight, Z, and cell volume, in g/cm³
+    pub density_from_formula: Option<f64>,
+}
+
+/// Spellings of each item: core, mmCIF, then DDLm where it differs
+const COLOUR: &[&str] = &["_exptl_crystal_colour", "_exptl_crystal.colour"];
+const HABIT: &[&str] = &["_exptl_crystal_description", "_exptl_crystal.description"];
+const SIZE_MIN: &[&str] = &["_exptl_crystal_size_min", "_exptl_crystal.size_min"];
+const SIZE_MID: &[&str] = &["_exptl_crystal_size_mid", "_exptl_crystal.size_mid"];
+const SIZE_MAX: &[&str] = &["_exptl_crystal_size_max", "_exptl_crystal.size_max"];
+const TEMPERATURE: &[&str] = &[
+    "_diffrn_ambient_temperature",
+    "_diffrn.ambient_temp",
+    "_diffrn.ambient_temperature",
+];
+const MU: &[&str] = &[
+    "_exptl_absorpt_coefficient_mu",
+    "_exptl.absorpt_coefficient_mu",
+    "_exptl_absorpt.coefficient_mu",
+];
+const CORRECTION: &[&str] = &[
+    "_exptl_absorpt_correction_type",
+    "_exptl.absorpt_correction_type",
+    "_exptl_absorpt.correction_type",
+];
+const T_MIN: &[&str] = &[
+    "_exptl_absorpt_correction_T_min",
+    "_exptl.absorpt_correction_T_min",
+    "_exptl_absorpt.correction_T_min",
+];
+const T_MAX: &[&str] = &[
+    "_exptl_absorpt_correction_T_max",
+    "_exptl.absorpt_correction_T_max",
+    "_exptl_absorpt.correction_T_max",
+];
+const DENSITY_MEAS: &[&str] = &["_exptl_crystal_density_meas", "_exptl_crystal.density_meas"];
+const DENSITY_DIFFRN: &[&str] = &[
+    "_exptl_crystal_density_diffrn",
+    "_exptl_crystal.density_diffrn",
+];
+const FORMULA_WEIGHT: &[&str] = &["_chemical_formula_weight", "_chemical_formula.weight"];
+const Z: &[&str] = &["_cell_formula_units_Z", "_cell.formula_units_Z"];
+const VOLUME: &[&str] = &["_cell_volume", "_cell.volume"];
+
+impl Experimental {
+    /// Volume of the crystal's bounding box, `min × mid × max`, in mm³
+    pub fn crystal_volume(&self) -> Option<f64> {
+        let (min, mid, max) = (self.size_min?, self.size_mid?, self.size_max?);
+        Some(min.0 * mid.0 * max.0)
+    }
+
+    /// Values that disagree with each other.
+    ///
+    /// The reported density (`_exptl_crystal_density_diffrn`) must be within
+    /// 1% of [`density_from_formula`](Self::density_from_formula), and the
+    /// minimum transmission must not exceed the maximum. An empty list means
+    /// nothing disagreed, or there was nothing to compare.
+    pub fn check(&self) -> Vec<SanityIssue> {
+        let mut issues = Vec::new();
+        if let (Some(reported), Some(computed)) =
+            (self.density_calculated, self.density_from_formula)
+        {
+            if (reported.0 - computed).abs() > DENSITY_TOLERANCE * computed {
+                issues.push(SanityIssue {
+                    id: "density",
+                    tag: DENSITY_DIFFRN[0].to_string(),
+                    row: None,
+                    value: written(reported),
+                    rule: format!(
+                        "density must be within 1% of {computed:.3} g/cm³ from the formula \
+                         weight, Z, and cell volume"
+                    ),
+                });
+            }
+        }
+        if let (Some(t_min), Some(t_max)) = (self.t_min, self.t_max) {
+            if t_min.0 > t_max.0 {
+                issues.push(SanityIssue {
+                    id: "transmission",
+                    tag: T_MIN[0].to_string(),
+                    row: None,
+                    value: written(t_min),
+                    rule: format!("T_min must not exceed T_max ({})", written(t_max)),
+                });
+            }
+        }
+        issues
+    }
+}
+
+/// A number with its su as it would be written in CIF
+pub(crate) fn written((value, su): (f64, Option<f64>)) -> String {
+    match su {
+        Some(su) => uncertainty::format(value, su),
+        None => value.to_string(),
+    }
+}
+
+impl CifBlock {
+    /// Gather the experimental conditions from this block.
+    ///
+    /// Core names such as `_exptl_crystal_size_max` and mmCIF names such as
+    /// `_exptl_crystal.size_max` are both read, with an mmCIF `_esd` item
+    /// giving the su of a number written without one. See the
+    /// [module documentation](crate::experimental) for an example.
+    pub fn experimental(&self) -> Experimental {
+        let text = |tags: &[&str]| {
+            tags.iter()
+                .find_map(|tag| self.get_item(tag).and_then(value_text))
+        };
+        let number = |tags: &[&str]| self.number_with_su(tags);
+
+        Experimental {
+            colour: text(COLOUR),
+            habit: text(HABIT),
+            size_min: number(SIZE_MIN),
+            size_mid: number(SIZE_MID),
+            size_max: number(SIZE_MAX),
+            temperature: number(TEMPERATURE),
+            absorption_mu: number(MU),
+            absorption_correction: text(CORRECTION),
+            t_min: number(T_MIN),
+            t_max: number(T_MAX),
+            density_measured: number(DENSITY_MEAS),
+            density_calculated: number(DENSITY_DIFFRN),
+            density_from_formula: self.density_from_formula(),
+        }
+    }
+
+    /// The first of `tags` that is a number, with its su
+    pub(crate) fn number_with_su(&self, tags: &[&str]) -> Option<(f64, Option<f64>)> {
+        tags.iter().find_map(|tag| {
+            let (value, su) = self.get_item(tag)?.as_numeric_with_su()?;
+            let esd = || {
+                self.get_item(&format!("{tag}_esd"))
+                    .and_then(CifValue::as_numeric)
+            };
+            Some((value, su.or_else(|| tag.contains('.').then(esd).flatten())))
+        })
+    }
+
+    /// `Z × M / V` in g/cm³, with the cell volume from its parameters when
+    /// `_cell_volume` is not given
+    fn density_from_formula(&self) -> Option<f64> {
+        let weight = self
+            .number_with_su(FORMULA_WEIGHT)
+            .map(|(weight, _)| weight)
+            .or_else(|| self.chemical_formula()?.weight())?;
+        let (z, _) = self.number_with_su(Z)?;
+        let volume = self
+            .number_with_su(VOLUME)
+            .map(|(volume, _)| volume)
+            .or_else(|| Some(self.unit_cell()?.volume()))?;
+        (volume > 0.0).then(|| z * weight * DENSITY_FACTOR / volume)
+    }
+}
@@ -0,0 +1,386 @@
+//! Flat key-value entries for search indexing.
+//!
+//! [`CifDocument::flatten`] turns a document into one [`FlatEntry`] per
+//! value, each with a path naming where the value is, the value as text, its
+//! number when it has one, and its [`ValueKind`]. Paths look like
+//!
+//! - `block/_tag` for a data item,
+//! - `block/_tag[row]` for a loop value, rows counting from 0, and
+//! - `block/save_frame/_tag` or `block/save_frame/_tag[row]` inside a save
+//!   frame.
+//!
+//! A tag that is a column of more than one loop in a block carries on the
+//! row numbering of the earlier loops, so no two entries share a path.
+//! [`locate`] finds each entry's line and column in the file's text.
+//!
+//! [`CifDocument::to_ndjson`] writes the entries as newline-delimited JSON,
+//! one object per line, as the bulk APIs of Elasticsearch and OpenSearch
+//! read them. Loops can be left out with [`FlattenOptions::include_loops`],
+//! since a large loop gives one entry per value.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::flatten::{FlattenOptions, ValueKind};
+//! use cif_parser::Document;
+//!
+//! let cif = "data_x\n_cell_length_a 5.431(2)\nloop_\n_atom_site_label\nC1\nO1\n";
+//! let doc = Document::parse(cif).unwrap();
+//! let entries = doc.flatten(&FlattenOptions::new());
+//!
+//! let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+//! assert_eq!(
+//!     paths,
+//!     ["x/_cell_length_a", "x/_atom_site_label[0]", "x/_atom_site_label[1]"]
+//! );
+//! assert_eq!(entries[0].number, Some(5.431));
+//! assert_eq!(entries[1].kind, ValueKind::Text);
+//!
+//! let items = doc.flatten(&FlattenOptions::new().include_loops(false));
+//! assert_eq!(items.len(), 1);
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifLoop, CifValue, CifVersion};
+use crate::report::value_spans;
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Appended to text cut short by [`FlattenOptions::max_text_len`]
+pub const TRUNCATION_MARK: &str = "…";
+
+/// Options for [`CifDocument::flatten`].
+#[derive(Debug, Clone)]
+pub struct FlattenOptions {
+    /// Include loop values, one entry each (default: true)
+    pub include_loops: bool,
+    /// Cut text longer than this many characters, marking it with
+    /// [`TRUNCATION_MARK`] (default: no limit)
+    pub max_text_len: Option<usize>,
+    /// Lowercase tags in paths and entries, as CIF tags are
+    /// case-insensitive (default: false)
+    pub lowercase_tags: bool,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions {
+            include_loops: true,
+            max_text_len: None,
+            lowercase_tags: false,
+        }
+    }
+}
+
+impl FlattenOptions {
+    /// Create the default options: loops included, no text limit, tags as
+    /// written
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include or leave out loop values
+    pub fn include_loops(mut self, include_loops: bool) -> Self {
+        self.include_loops = include_loops;
+        self
+    }
+
+    /// Cut text longer than `max_text_len` characters
+    pub fn max_text_len(mut self, max_text_len: usize) -> Self {
+        self.max_text_len = Some(max_text_len);
+        self
+    }
+
+    /// Lowercase tags
+    pub fn lowercase_tags(mut self, lowercase_tags: bool) -> Self {
+        self.lowercase_tags = lowercase_tags;
+        self
+    }
+}
+
+/// The kind of a flattened value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    /// Text, including text stored as a large value
+    Text,
+    /// A number
+    Numeric,
+    /// `?`
+    Unknown,
+    /// `.`
+    NotApplicable,
+    /// A CIF 2.0 list
+    List,
+    /// A CIF 2.0 table
+    Table,
+}
+
+impl ValueKind {
+    /// Every kind
+    pub const ALL: [ValueKind; 6] = [
+        ValueKind::Text,
+        ValueKind::Numeric,
+        ValueKind::Unknown,
+        ValueKind::NotApplicable,
+        ValueKind::List,
+        ValueKind::Table,
+    ];
+
+    /// The kind of `value`
+    pub fn of(value: &CifValue) -> Self {
+        match value {
+            CifValue::Text(_) | CifValue::LargeText(_) => ValueKind::Text,
+            CifValue::Numeric(_) | CifValue::NumericSu { .. } => ValueKind::Numeric,
+            CifValue::Unknown => ValueKind::Unknown,
+            CifValue::NotApplicable => ValueKind::NotApplicable,
+            CifValue::List(_) => ValueKind::List,
+            CifValue::Table(_) => ValueKind::Table,
+        }
+    }
+
+    /// Name of the kind, as used in JSON and Python: `text`, `numeric`,
+    /// `unknown`, `not_applicable`, `list`, or `table`
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueKind::Text => "text",
+            ValueKind::Numeric => "numeric",
+            ValueKind::Unknown => "unknown",
+            ValueKind::NotApplicable => "not_applicable",
+            ValueKind::List => "list",
+            ValueKind::Table => "table",
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ValueKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ValueKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = ValueKind::ALL.iter().map(|k| k.name()).collect();
+                format!(
+                    "unknown value kind '{s}', expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// One value of a flattened document (see [`CifDocument::flatten`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatEntry {
+    /// Where the value is, such as `x/_atom_site_label[3]`
+    pub path: String,
+    /// Name of the data block
+    pub block: String,
+    /// Name of the save frame, if the value is in one
+    pub frame: Option<String>,
+    /// The tag, lowercased if the options ask for it
+    pub tag: String,
+    /// Loop row, or `None` for a data item
+    pub row: Option<usize>,
+    /// The value as text: text as is, numbers as the writer writes them,
+    /// `?` and `.`, and lists and tables in CIF 2.0 syntax
+    pub text: String,
+    /// Whether `text` was cut short
+    pub truncated: bool,
+    /// The number, without its su if it has one
+    pub number: Option<f64>,
+    /// The kind of value
+    pub kind: ValueKind,
+}
+
+impl FlatEntry {
+    /// The entry as a JSON object with the field names above, `kind` by its
+    /// [name](ValueKind::name)
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "path": self.path,
+            "block": self.block,
+            "frame": self.frame,
+            "tag": self.tag,
+            "row": self.row,
+            "text": self.text,
+            "truncated": self.truncated,
+            "number": self.number,
+            "kind": self.kind.name(),
+        })
+    }
+}
+
+/// Builds the entries of one block or frame
+struct Flattener<'a> {
+    options: &'a FlattenOptions,
+    block: &'a str,
+    frame: Option<&'a str>,
+    /// Rows of each tag in earlier loops of this block or frame
+    rows_seen: HashMap<String, usize>,
+    entries: &'a mut Vec<FlatEntry>,
+}
+
+impl Flattener<'_> {
+    fn items(&mut self, items: &IndexMap<String, CifValue>) {
+        for (tag, value) in items {
+            self.push(tag, None, value);
+        }
+    }
+
+    fn loops(&mut self, loops: &[CifLoop]) {
+        if !self.options.include_loops {
+            return;
+        }
+        for loop_ in loops {
+            let first_row: Vec<usize> = loop_
+                .tags
+                .iter()
+                .map(|tag| {
+                    self.rows_seen
+                        .get(&tag.to_lowercase())
+                        .copied()
+                        .unwrap_or(0)
+                })
+                .collect();
+            for (row, values) in loop_.values.iter().enumerate() {
+                for ((tag, value), first) in loop_.tags.iter().zip(values).zip(&first_row) {
+                    self.push(tag, Some(first + row), value);
+                }
+            }
+            for (tag, first) in loop_.tags.iter().zip(first_row) {
+                self.rows_seen
+                    .insert(tag.to_lowercase(), first + loop_.len());
+            }
+        }
+    }
+
+    fn push(&mut self, tag: &str, row: Option<usize>, value: &CifValue) {
+        let tag = match self.options.lowercase_tags {
+            true => tag.to_lowercase(),
+            false => tag.to_string(),
+        };
+        let mut path = format!("{}/", self.block);
+        if let Some(frame) = self.frame {
+            path.push_str(&format!("save_{frame}/"));
+        }
+        path.push_str(&tag);
+        if let Some(row) = row {
+            path.push_str(&format!("[{row}]"));
+        }
+
+        let text = match value.as_string() {
+            Some(text) => text.to_string(),
+            None => inline_value(value, CifVersion::V2_0),
+        };
+        let (text, truncated) = match self.options.max_text_len {
+            Some(max) if text.chars().count() > max => {
+                let mut cut: String = text.chars().take(max).collect();
+                cut.push_str(TRUNCATION_MARK);
+                (cut, true)
+            }
+            _ => (text, false),
+        };
+
+        self.entries.push(FlatEntry {
+            path,
+            block: self.block.to_string(),
+            frame: self.frame.map(str::to_string),
+            tag,
+            row,
+            text,
+            truncated,
+            number: value.as_numeric(),
+            kind: ValueKind::of(value),
+        });
+    }
+}
+
+/// Append the entries of `block` and its save frames
+fn flatten_block(block: &CifBlock, options: &FlattenOptions, entries: &mut Vec<FlatEntry>) {
+    let mut flattener = Flattener {
+        options,
+        block: &block.name,
+        frame: None,
+        rows_seen: HashMap::new(),
+        entries,
+    };
+    flattener.items(&block.items);
+    flattener.loops(&block.loops);
+    for frame in &block.frames {
+        let mut flattener = Flattener {
+            options,
+            block: &block.name,
+            frame: Some(&frame.name),
+            rows_seen: HashMap::new(),
+            entries,
+        };
+        flattener.items(&frame.items);
+        flattener.loops(&frame.loops);
+    }
+}
+
+impl CifDocument {
+    /// Flatten the document into one entry per value, for search indexing.
+    ///
+    /// Each block gives its data items, then its loop values row by row,
+    /// then the same for each save frame. See the
+    /// [module documentation](crate::flatten) for the paths.
+    pub fn flatten(&self, options: &FlattenOptions) -> Vec<FlatEntry> {
+        let mut entries = Vec::new();
+        for block in &self.blocks {
+            flatten_block(block, options, &mut entries);
+        }
+        entries
+    }
+
+    /// Write [`flatten`](Self::flatten)'s entries as newline-delimited
+    /// JSON, one [object](FlatEntry::to_json) per line.
+    ///
+    /// Entries are made and written a block at a time, so the whole list
+    /// is never held in memory.
+    pub fn to_ndjson<W: Write>(&self, mut writer: W, options: &FlattenOptions) -> io::Result<()> {
+        let mut entries = Vec::new();
+        for block in &self.blocks {
+            flatten_block(block, options, &mut entries);
+            for entry in entries.drain(..) {
+                serde_json::to_writer(&mut writer, &entry.to_json())?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+/// Find each entry's value in `content`, the text the document was parsed
+/// from, as a 1-based line and column.
+///
+/// Gives `None` for an entry that is not found: one in a save frame, or one
+/// flattened from a document that has since been changed.
+pub fn locate(content: &str, entries: &[FlatEntry]) -> Vec<Option<(usize, usize)>> {
+    let spans = value_spans(content, true);
+    entries
+        .iter()
+        .map(|entry| {
+            if entry.frame.is_some() {
+                return None;
+            }
+            let key = (
+                entry.block.to_lowercase(),
+                entry.tag.to_lowercase(),
+                entry.row,
+            );
+            spans.values.get(&key).copied()
+        })
+        .collect()
+}
@@ -0,0 +1,356 @@
+//! Canonical formatting of CIF text, keeping its comments.
+//!
+//! [`format_str`] parses a document and writes it back through the same
+//! writer as [`CifDocument`]'s `Display`, so every quoting decision and
+//! number format is the writer's own. On top of that it puts items in tag
+//! order, lines up item values and loop columns, puts a blank line before
+//! each loop and save frame, and writes the input's comments back.
+//!
+//! Formatting is idempotent: formatting formatted text gives the same bytes
+//! back, and the formatted text parses to a document
+//! [semantically equal](CifDocument::semantically_equal) to the input.
+//!
+//! # Comments
+//!
+//! Each comment is kept with what follows it: a block or frame heading, a
+//! data item, a loop, or a loop row. A comment on the same line as a value,
+//! after it, is kept with that value instead. Either way the comment is
+//! written on its own line just before the heading, item, loop, or row,
+//! which moves with it when items are put in tag order. Comments after the
+//! last block are written at the end, and comments inside a list or table
+//! go before its item or row. The `#\#CIF_2.0` version line is not a
+//! comment, and is written by the writer.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::formatter::{format_str, FormatOptions};
+//!
+//! let input = "data_x\n_cell_length_b 7.2 # refined\n_cell_length_a 10.5\n";
+//! let formatted = format_str(input, &FormatOptions::new()).unwrap();
+//!
+//! assert_eq!(
+//!     formatted,
+//!     "#\\#CIF_1.1\n\ndata_x\n_cell_length_a 10.5\n# refined\n_cell_length_b 7.2\n"
+//! );
+//! assert_eq!(format_str(&formatted, &FormatOptions::new()).unwrap(), formatted);
+//! ```
+
+use crate::ast::CifDocument;
+use crate::error::CifError;
+use crate::lexer::{Token, TokenKind, Tokenizer};
+use crate::writer::{write_document, Layout, WriteOptions};
+use std::collections::HashMap;
+
+/// Options for [`format_str`].
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Pad tags so that item values line up, and loop values into columns
+    pub align: bool,
+    /// Write the input's comments back
+    pub comments: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            align: true,
+            comments: true,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Create options that align values and keep comments
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pad tags and loop values into columns (on by default)
+    pub fn align(mut self, align: bool) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Write the input's comments back (on by default)
+    pub fn comments(mut self, keep: bool) -> Self {
+        self.comments = keep;
+        self
+    }
+}
+
+/// Format CIF text canonically. See the [module documentation](self).
+///
+/// Fails if the text does not parse.
+pub fn format_str(input: &str, options: &FormatOptions) -> Result<String, CifError> {
+    let doc = CifDocument::parse(input)?;
+    let comments = match options.comments {
+        true => Comments::capture(input)?,
+        false => Comments::default(),
+    };
+    let layout = Layout {
+        align: options.align,
+        spacing: true,
+        comments: Some(&comments),
+    };
+    let mut out = String::new();
+    write_document(
+        &mut out,
+        &doc,
+        &WriteOptions::new().sort_items(true),
+        &layout,
+    )
+    .expect("writing to a String cannot fail");
+    Ok(out)
+}
+
+/// Whether `input` is already formatted, so that [`format_str`] would leave
+/// it unchanged
+pub fn is_formatted(input: &str, options: &FormatOptions) -> Result<bool, CifError> {
+    Ok(format_str(input, options)? == input)
+}
+
+/// A data block, or a save frame in one, by position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Scope {
+    block: usize,
+    frame: Option<usize>,
+}
+
+impl Scope {
+    /// The `index`th data block
+    pub(crate) fn block(index: usize) -> Self {
+        Scope {
+            block: index,
+            frame: None,
+        }
+    }
+
+    /// The `index`th save frame of this block
+    pub(crate) fn frame(self, index: usize) -> Self {
+        Scope {
+            frame: Some(index),
+            ..self
+        }
+    }
+}
+
+/// What a comment is written before
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Anchor {
+    /// A `data_` heading, by block index
+    Block(usize),
+    /// A `save_` heading
+    Frame(Scope),
+    /// The `save_` closing a frame
+    FrameEnd(Scope),
+    /// A data item, by lowercased tag
+    Item(Scope, String),
+    /// A `loop_`, by index in its block or frame
+    Loop(Scope, usize),
+    /// A row of a loop
+    Row(Scope, usize, usize),
+    /// The end of the document
+    End,
+}
+
+/// Comments of a document, by what they are written before
+#[derive(Debug, Default)]
+pub(crate) struct Comments {
+    by_anchor: HashMap<Anchor, Vec<String>>,
+}
+
+impl Comments {
+    /// The comments written before `anchor`, in input order
+    pub(crate) fn get(&self, anchor: &Anchor) -> &[String] {
+        self.by_anchor.get(anchor).map_or(&[], Vec::as_slice)
+    }
+
+    /// Find the comments of CIF text and what each belongs to, counting
+    /// blocks, frames, and loops as the parser does
+    fn capture(input: &str) -> Result<Self, CifError> {
+        let mut capture = Capture::default();
+        for token in Tokenizer::new(input) {
+            capture.token(token?);
+        }
+        let pending = std::mem::take(&mut capture.pending);
+        capture.keep(Anchor::End, pending);
+        Ok(capture.comments)
+    }
+}
+
+/// Where the tokenizer is in the document
+#[derive(Debug, Default)]
+enum Context {
+    #[default]
+    Outside,
+    /// After an item's tag; its value anchors to it
+    Item(String),
+    /// In a loop's tags
+    LoopHeader { index: usize, columns: usize },
+    /// In a loop's values, `count` of them so far
+    LoopBody {
+        index: usize,
+        columns: usize,
+        count: usize,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Capture {
+    comments: Comments,
+    /// Comments waiting for the next token to anchor them
+    pending: Vec<String>,
+    /// Anchor and last line of the previous token, for trailing comments
+    last: Option<(Anchor, usize)>,
+    blocks: usize,
+    scope: Option<Scope>,
+    frames: usize,
+    /// Loops so far in the block, and in the open frame
+    block_loops: usize,
+    frame_loops: usize,
+    context: Context,
+    /// Nesting of CIF 2.0 lists and tables
+    depth: usize,
+    seen_token: bool,
+}
+
+impl Capture {
+    fn keep(&mut self, anchor: Anchor, comments: Vec<String>) {
+        if !comments.is_empty() {
+            self.comments
+                .by_anchor
+                .entry(anchor)
+                .or_default()
+                .extend(comments);
+        }
+    }
+
+    fn token(&mut self, token: Token<'_>) {
+        match token.kind {
+            TokenKind::Whitespace => {}
+            TokenKind::Comment => {
+                let first = !self.seen_token && token.line == 1;
+                self.seen_token = true;
+                if first && token.text.starts_with("#\\#CIF_") {
+                    return;
+                }
+                let text = token.text.trim_end().to_string();
+                match &self.last {
+                    Some((anchor, line)) if *line == token.line => {
+                        let anchor = anchor.clone();
+                        self.keep(anchor, vec![text]);
+                    }
+                    _ => self.pending.push(text),
+                }
+            }
+            _ => {
+                self.seen_token = true;
+                let anchor = self.anchor(&token);
+                let pending = std::mem::take(&mut self.pending);
+                self.keep(anchor.clone(), pending);
+                let last_line = token.line + token.text.matches('\n').count();
+                self.last = Some((anchor, last_line));
+            }
+        }
+    }
+
+    /// What a token's comments belong to, updating the position
+    fn anchor(&mut self, token: &Token<'_>) -> Anchor {
+        let scope = self.scope.unwrap_or(Scope::block(0));
+        match token.kind {
+            TokenKind::BlockHeader => {
+                let index = self.blocks;
+                self.blocks += 1;
+                self.scope = Some(Scope::block(index));
+                self.frames = 0;
+                self.block_loops = 0;
+                self.context = Context::Outside;
+                Anchor::Block(index)
+            }
+            TokenKind::SaveHeader => {
+                let frame = Scope::block(scope.block).frame(self.frames);
+                self.frames += 1;
+                self.scope = Some(frame);
+                self.frame_loops = 0;
+                self.context = Context::Outside;
+                Anchor::Frame(frame)
+            }
+            TokenKind::SaveEnd => {
+                self.scope = Some(Scope::block(scope.block));
+                self.context = Context::Outside;
+                Anchor::FrameEnd(scope)
+            }
+            TokenKind::LoopKeyword if token.text.eq_ignore_ascii_case("loop_") => {
+                let loops = match scope.frame {
+                    Some(_) => &mut self.frame_loops,
+                    None => &mut self.block_loops,
+                };
+                let index = *loops;
+                *loops += 1;
+                self.context = Context::LoopHeader { index, columns: 0 };
+                Anchor::Loop(scope, index)
+            }
+            TokenKind::LoopKeyword => {
+                // `stop_` ends a loop
+                self.context = Context::Outside;
+                self.previous()
+            }
+            TokenKind::Tag => match &mut self.context {
+                Context::LoopHeader { index, columns } => {
+                    *columns += 1;
+                    Anchor::Loop(scope, *index)
+                }
+                _ => {
+                    let tag = token.text.to_lowercase();
+                    self.context = Context::Item(tag.clone());
+                    Anchor::Item(scope, tag)
+                }
+            },
+            _ => self.value(token, scope),
+        }
+    }
+
+    /// The anchor of a value token, or of a bracket or colon in a list
+    /// or table
+    fn value(&mut self, token: &Token<'_>, scope: Scope) -> Anchor {
+        if token.kind == TokenKind::Delimiter {
+            match token.text {
+                "[" | "{" => self.depth += 1,
+                "]" | "}" => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        // A list or table is one value, complete at its closing bracket
+        let complete = self.depth == 0;
+        if let Context::LoopHeader { index, columns } = self.context {
+            self.context = Context::LoopBody {
+                index,
+                columns,
+                count: 0,
+            };
+        }
+        match &mut self.context {
+            Context::LoopBody {
+                index,
+                columns,
+                count,
+            } => {
+                let row = *count / (*columns).max(1);
+                if complete {
+                    *count += 1;
+                }
+                Anchor::Row(scope, *index, row)
+            }
+            Context::Item(tag) => Anchor::Item(scope, tag.clone()),
+            Context::Outside | Context::LoopHeader { .. } => self.previous(),
+        }
+    }
+
+    /// The anchor of the previous token
+    fn previous(&self) -> Anchor {
+        self.last
+            .as_ref()
+            .map_or(Anchor::End, |(anchor, _)| anchor.clone())
+    }
+}
@@ -0,0 +1,235 @@
+//! Chemical formulae and formula weights.
+//!
+//! [`Formula::parse`] reads the element counts of a `_chemical_formula_sum`
+//! (`'C12 H10 N2 O'`), and [`Formula::weight`] adds up standard atomic
+//! weights to give the value `_chemical_formula_weight` should have.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::formula::Formula;
+//!
+//! let water = Formula::parse("H2 O").unwrap();
+//! assert_eq!(water.elements, [("H".to_string(), 2.0), ("O".to_string(), 1.0)]);
+//! assert!((water.weight().unwrap() - 18.015).abs() < 1e-9);
+//! ```
+
+use crate::ast::CifBlock;
+use crate::publication::value_text;
+
+/// Element counts of a chemical formula, in the order written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    /// Element symbol and count; counts may be fractional
+    pub elements: Vec<(String, f64)>,
+}
+
+impl Formula {
+    /// Parse a formula sum such as `C12 H10 N2 O` or `Fe0.5 Mg1.5 Si O4`.
+    ///
+    /// Elements may be separated by spaces or run together (`C6H6`); a
+    /// missing count means one, and repeated elements are added up. Returns
+    /// `None` for anything else, including brackets and charges, which only
+    /// appear in moiety formulae.
+    pub fn parse(s: &str) -> Option<Formula> {
+        let mut elements: Vec<(String, f64)> = Vec::new();
+        let mut chars = s.trim().chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            if !c.is_ascii_uppercase() {
+                return None;
+            }
+            let mut symbol = c.to_string();
+            if let Some(&lower) = chars.peek().filter(|c| c.is_ascii_lowercase()) {
+                symbol.push(lower);
+                chars.next();
+            }
+            let mut count = String::new();
+            while let Some(&digit) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                count.push(digit);
+                chars.next();
+            }
+            let count = if count.is_empty() {
+                1.0
+            } else {
+                count.parse().ok()?
+            };
+            match elements.iter_mut().find(|(s, _)| *s == symbol) {
+                Some((_, total)) => *total += count,
+                None => elements.push((symbol, count)),
+            }
+        }
+        (!elements.is_empty()).then_some(Formula { elements })
+    }
+
+    /// Formula weight in daltons, or `None` if an element is unknown
+    pub fn weight(&self) -> Option<f64> {
+        self.elements
+            .iter()
+            .map(|(symbol, count)| Some(atomic_weight(symbol)? * count))
+            .sum()
+    }
+}
+
+/// Standard atomic weight of an element (or `D` for deuterium), in daltons.
+///
+/// Values are the IUPAC abridged weights; elements with no stable isotope
+/// use the mass number of their longest-lived isotope.
+pub fn atomic_weight(symbol: &str) -> Option<f64> {
+    ATOMIC_WEIGHTS
+        .iter()
+        .find(|(s, _)| *s == symbol)
+        .map(|&(_, weight)| weight)
+}
+
+const ATOMIC_WEIGHTS: &[(&str, f64)] = &[
+    ("H", 1.008),
+    ("D", 2.014),
+    ("He", 4.0026),
+    ("Li", 6.94),
+    ("Be", 9.0122),
+    ("B", 10.81),
+    ("C", 12.011),
+    ("N", 14.007),
+    ("O", 15.999),
+    ("F", 18.998),
+    ("Ne", 20.180),
+    ("Na", 22.990),
+    ("Mg", 24.305),
+    ("Al", 26.982),
+    ("Si", 28.085),
+    ("P", 30.974),
+    ("S", 32.06),
+    ("Cl", 35.45),
+    ("Ar", 39.95),
+    ("K", 39.098),
+    ("Ca", 40.078),
+    ("Sc", 44.956),
+    ("Ti", 47.867),
+    ("V", 50.942),
+    ("Cr", 51.996),
+    ("Mn", 54.938),
+    ("Fe", 55.845),
+    ("Co", 58.933),
+    ("Ni", 58.693),
+    ("Cu", 63.546),
+    ("Zn", 65.38),
+    ("Ga", 69.723),
+    ("Ge", 72.630),
+    ("As", 74.922),
+    ("Se", 78.971),
+    ("Br", 79.904),
+    ("Kr", 83.798),
+    ("Rb", 85.468),
+    ("Sr", 87.62),
+    ("Y", 88.906),
+    ("Zr", 91.224),
+    ("Nb", 92.906),
+    ("Mo", 95.95),
+    ("Tc", 98.0),
+    ("Ru", 101.07),
+    ("Rh", 102.91),
+    ("Pd", 106.42),
+    ("Ag", 107.87),
+    ("Cd", 112.41),
+    ("In", 114.82),
+    ("Sn", 118.71),
+    ("Sb", 121.76),
+    ("Te", 127.60),
+    ("I", 126.90),
+    ("Xe", 131.29),
+    ("Cs", 132.91),
+    ("Ba", 137.33),
+    ("La", 138.91),
+    ("Ce", 140.12),
+    ("Pr", 140.91),
+    ("Nd", 144.24),
+    ("Pm", 145.0),
+    ("Sm", 150.36),
+    ("Eu", 151.96),
+    ("Gd", 157.25),
+    ("Tb", 158.93),
+    ("Dy", 162.50),
+    ("Ho", 164.93),
+    ("Er", 167.26),
+    ("Tm", 168.93),
+    ("Yb", 173.05),
+    ("Lu", 174.97),
+    ("Hf", 178.49),
+    ("Ta", 180.95),
+    ("W", 183.84),
+    ("Re", 186.21),
+    ("Os", 190.23),
+    ("Ir", 192.22),
+    ("Pt", 195.08),
+    ("Au", 196.97),
+    ("Hg", 200.59),
+    ("Tl", 204.38),
+    ("Pb", 207.2),
+    ("Bi", 208.98),
+    ("Po", 209.0),
+    ("At", 210.0),
+    ("Rn", 222.0),
+    ("Fr", 223.0),
+    ("Ra", 226.0),
+    ("Ac", 227.0),
+    ("Th", 232.04),
+    ("Pa", 231.04),
+    ("U", 238.03),
+    ("Np", 237.0),
+    ("Pu", 244.0),
+    ("Am", 243.0),
+    ("Cm", 247.0),
+    ("Bk", 247.0),
+    ("Cf", 251.0),
+];
+
+impl CifBlock {
+    /// The formula sum (`_chemical_formula_sum` or `_chemical_formula.sum`),
+    /// if it is present and parses.
+    pub fn chemical_formula(&self) -> Option<Formula> {
+        let sum = self
+            .get_item("_chemical_formula_sum")
+            .or_else(|| self.get_item("_chemical_formula.sum"))?;
+        Formula::parse(&value_text(sum)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_formula() {
+        let formula = Formula::parse("C12 H10 N2 O").unwrap();
+        assert_eq!(formula.elements.len(), 4);
+        assert_eq!(formula.elements[2], ("N".to_string(), 2.0));
+        assert_eq!(formula.elements[3], ("O".to_string(), 1.0));
+
+        let run_together = Formula::parse("C6H5Cl").unwrap();
+        assert_eq!(run_together.elements[2], ("Cl".to_string(), 1.0));
+
+        let fractional = Formula::parse("Fe0.5 Mg1.5 Si O4").unwrap();
+        assert_eq!(fractional.elements[0], ("Fe".to_string(), 0.5));
+
+        let repeated = Formula::parse("C2 H6 O C").unwrap();
+        assert_eq!(repeated.elements[0], ("C".to_string(), 3.0));
+    }
+
+    #[test]
+    fn test_parse_rejects_moieties() {
+        assert_eq!(Formula::parse(""), None);
+        assert_eq!(Formula::parse("(C6 H6)2"), None);
+        assert_eq!(Formula::parse("Cl-"), None);
+        assert_eq!(Formula::parse("c6 h6"), None);
+    }
+
+    #[test]
+    fn test_weight() {
+        let benzene = Formula::parse("C6 H6").unwrap();
+        assert!((benzene.weight().unwrap() - 78.114).abs() < 1e-9);
+        assert_eq!(Formula::parse("Xx2").unwrap().weight(), None);
+    }
+}
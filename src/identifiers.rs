@@ -0,0 +1,403 @@
+//! Database codes and DOIs that identify a structure.
+//!
+//! The same structure is often deposited in several databases, and each
+//! writes its code in its own way: `_database_code_CSD` and
+//! `_database_code_ICSD` in core CIF (or `_database.code_CSD` in DDLm),
+//! `_cod_database_code` in COD files, `_database_code_depnum_ccdc_archive`
+//! for CCDC deposition numbers, and the `_database_2` loop in mmCIF.
+//! [`CifBlock::identifiers`] gathers all of these into an [`Identifiers`],
+//! accepting each tag as a data item or as a loop column.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{Document, IdentifierKind};
+//!
+//! let cif = "\
+//! data_paracetamol
+//! _database_code_CSD HXACAN01
+//! _database_code_depnum_ccdc_archive 'CCDC 1169420'
+//! _journal_paper_doi 10.1107/S0567740876005669
+//! _database_code_NBS 123456
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let ids = doc.first_block().unwrap().identifiers();
+//!
+//! assert_eq!(ids.csd.as_deref(), Some("HXACAN01"));
+//! assert_eq!(ids.ccdc_deposition.as_deref(), Some("CCDC 1169420"));
+//! assert_eq!(ids.doi.as_deref(), Some("10.1107/S0567740876005669"));
+//! assert_eq!(ids.others, [("_database_code_NBS".to_string(), "123456".to_string())]);
+//!
+//! let block = doc.find_by_identifier(IdentifierKind::CcdcDeposition, "1169420");
+//! assert_eq!(block.unwrap().name, "paracetamol");
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, TagLocation};
+use crate::publication::value_text;
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifiers of a structure in databases and the literature.
+///
+/// Every field is optional; absent or `?`/`.` values are `None`. When a
+/// code is given more than once, the first one in file order is kept.
+///
+/// Codes written as bare numbers are read as numbers, so leading zeros are
+/// lost (`0012843` becomes `12843`). [`CifDocument::find_by_identifier`]
+/// ignores leading zeros for this reason.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Identifiers {
+    /// Cambridge Structural Database refcode (`_database_code_CSD`)
+    pub csd: Option<String>,
+    /// Inorganic Crystal Structure Database collection code
+    /// (`_database_code_ICSD`)
+    pub icsd: Option<String>,
+    /// Crystallography Open Database code (`_cod_database_code`)
+    pub cod: Option<String>,
+    /// CCDC deposition number, as written
+    /// (`_database_code_depnum_ccdc_archive`)
+    pub ccdc_deposition: Option<String>,
+    /// Protein Data Bank code (the `PDB` row of `_database_2`)
+    pub pdb: Option<String>,
+    /// DOI of the paper (`_journal_paper_doi`, or the primary
+    /// `_citation.pdbx_database_id_DOI` in mmCIF)
+    pub doi: Option<String>,
+    /// DOI of the dataset itself (`_database_dataset_doi`, or
+    /// `_database_2.pdbx_DOI` in mmCIF)
+    pub dataset_doi: Option<String>,
+    /// Codes without a field above, as `(source, code)`: the tag for
+    /// `_database_code_*` items, or the database name for `_database_2` rows
+    pub others: Vec<(String, String)>,
+}
+
+/// A kind of identifier, naming one field of [`Identifiers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdentifierKind {
+    /// [`Identifiers::csd`]
+    Csd,
+    /// [`Identifiers::icsd`]
+    Icsd,
+    /// [`Identifiers::cod`]
+    Cod,
+    /// [`Identifiers::ccdc_deposition`]
+    CcdcDeposition,
+    /// [`Identifiers::pdb`]
+    Pdb,
+    /// [`Identifiers::doi`]
+    Doi,
+    /// [`Identifiers::dataset_doi`]
+    DatasetDoi,
+}
+
+impl IdentifierKind {
+    /// Every kind, in field order
+    pub const ALL: [IdentifierKind; 7] = [
+        IdentifierKind::Csd,
+        IdentifierKind::Icsd,
+        IdentifierKind::Cod,
+        IdentifierKind::CcdcDeposition,
+        IdentifierKind::Pdb,
+        IdentifierKind::Doi,
+        IdentifierKind::DatasetDoi,
+    ];
+
+    /// Lowercase name, matching the field of [`Identifiers`]
+    pub fn name(self) -> &'static str {
+        match self {
+            IdentifierKind::Csd => "csd",
+            IdentifierKind::Icsd => "icsd",
+            IdentifierKind::Cod => "cod",
+            IdentifierKind::CcdcDeposition => "ccdc_deposition",
+            IdentifierKind::Pdb => "pdb",
+            IdentifierKind::Doi => "doi",
+            IdentifierKind::DatasetDoi => "dataset_doi",
+        }
+    }
+
+    /// Reduce a code to the form used for comparison: case is ignored,
+    /// DOIs lose any `doi:` or resolver prefix, CCDC numbers lose the
+    /// `CCDC` prefix, and numeric codes lose leading zeros
+    fn canonical(self, code: &str) -> String {
+        let mut code = code.trim().to_ascii_lowercase();
+        if matches!(self, IdentifierKind::Doi | IdentifierKind::DatasetDoi) {
+            for prefix in [
+                "https://doi.org/",
+                "http://doi.org/",
+                "http://dx.doi.org/",
+                "doi:",
+            ] {
+                if let Some(rest) = code.strip_prefix(prefix) {
+                    code = rest.trim().to_string();
+                }
+            }
+        }
+        if self == IdentifierKind::CcdcDeposition {
+            if let Some(rest) = code.strip_prefix("ccdc") {
+                code = rest.trim_start_matches([' ', '-', ':']).to_string();
+            }
+        }
+        if !code.is_empty() && code.bytes().all(|b| b.is_ascii_digit()) {
+            let trimmed = code.trim_start_matches('0');
+            return if trimmed.is_empty() { "0" } else { trimmed }.to_string();
+        }
+        code
+    }
+}
+
+impl fmt::Display for IdentifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for IdentifierKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        IdentifierKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<_> = IdentifierKind::ALL.iter().map(|k| k.name()).collect();
+                format!(
+                    "Unknown identifier kind '{s}'; expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl Identifiers {
+    /// The identifier of the given kind, if present
+    pub fn get(&self, kind: IdentifierKind) -> Option<&str> {
+        match kind {
+            IdentifierKind::Csd => self.csd.as_deref(),
+            IdentifierKind::Icsd => self.icsd.as_deref(),
+            IdentifierKind::Cod => self.cod.as_deref(),
+            IdentifierKind::CcdcDeposition => self.ccdc_deposition.as_deref(),
+            IdentifierKind::Pdb => self.pdb.as_deref(),
+            IdentifierKind::Doi => self.doi.as_deref(),
+            IdentifierKind::DatasetDoi => self.dataset_doi.as_deref(),
+        }
+    }
+
+    /// Whether no identifiers were found
+    pub fn is_empty(&self) -> bool {
+        *self == Identifiers::default()
+    }
+
+    fn slot(&mut self, kind: IdentifierKind) -> &mut Option<String> {
+        match kind {
+            IdentifierKind::Csd => &mut self.csd,
+            IdentifierKind::Icsd => &mut self.icsd,
+            IdentifierKind::Cod => &mut self.cod,
+            IdentifierKind::CcdcDeposition => &mut self.ccdc_deposition,
+            IdentifierKind::Pdb => &mut self.pdb,
+            IdentifierKind::Doi => &mut self.doi,
+            IdentifierKind::DatasetDoi => &mut self.dataset_doi,
+        }
+    }
+
+    /// Record a code unless one of the same kind was already found
+    fn set(&mut self, kind: IdentifierKind, code: String) {
+        self.slot(kind).get_or_insert(code);
+    }
+}
+
+impl CifBlock {
+    /// Gather database codes and DOIs from this block.
+    ///
+    /// Tags are matched ignoring case. See the
+    /// [module documentation](crate::identifiers) for an example.
+    pub fn identifiers(&self) -> Identifiers {
+        let mut ids = Identifiers::default();
+
+        for (tag, location) in self.iter_all_tags() {
+            let values = match location {
+                TagLocation::Item => self.items.get(tag).into_iter().collect(),
+                TagLocation::Loop(index) => self.loops[index].get_column(tag).unwrap_or_default(),
+            };
+            let lower = tag.to_ascii_lowercase();
+            for code in values.into_iter().filter_map(value_text) {
+                match core_kind(&lower) {
+                    Some(kind) => ids.set(kind, code),
+                    None if database_code_suffix(&lower).is_some() => {
+                        ids.others.push((tag.to_string(), code))
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        // mmCIF: one row per database, with the entry's DOI alongside
+        let databases = self.category_rows(&[
+            "_database_2.database_id",
+            "_database_2.database_code",
+            "_database_2.pdbx_doi",
+        ]);
+        for row in databases {
+            if let [Some(database), Some(code), doi] = row.as_slice() {
+                match database.to_ascii_uppercase().as_str() {
+                    "PDB" => ids.set(IdentifierKind::Pdb, code.clone()),
+                    "CSD" => ids.set(IdentifierKind::Csd, code.clone()),
+                    "ICSD" => ids.set(IdentifierKind::Icsd, code.clone()),
+                    "COD" => ids.set(IdentifierKind::Cod, code.clone()),
+                    _ => ids.others.push((database.clone(), code.clone())),
+                }
+                if let Some(doi) = doi {
+                    ids.set(IdentifierKind::DatasetDoi, doi.clone());
+                }
+            }
+        }
+
+        // mmCIF: the paper describing the entry is the `primary` citation
+        let citations = self.category_rows(&["_citation.id", "_citation.pdbx_database_id_doi"]);
+        for row in citations {
+            if let [Some(id), Some(doi)] = row.as_slice() {
+                if id.eq_ignore_ascii_case("primary") {
+                    ids.set(IdentifierKind::Doi, doi.clone());
+                }
+            }
+        }
+
+        ids
+    }
+
+    /// Rows of the given columns of a category, ignoring case, from the
+    /// loop holding the first tag or from single items
+    fn category_rows(&self, tags: &[&str]) -> Vec<Vec<Option<String>>> {
+        let matches = |name: &str, tag: &str| name.eq_ignore_ascii_case(tag);
+        let loop_ = self
+            .loops
+            .iter()
+            .find(|loop_| loop_.tags.iter().any(|name| matches(name, tags[0])));
+        if let Some(loop_) = loop_ {
+            let columns: Vec<Option<usize>> = tags
+                .iter()
+                .map(|tag| loop_.tags.iter().position(|name| matches(name, tag)))
+                .collect();
+            return (0..loop_.len())
+                .map(|row| {
+                    columns
+                        .iter()
+                        .map(|column| column.and_then(|c| loop_.get(row, c)).and_then(value_text))
+                        .collect()
+                })
+                .collect();
+        }
+
+        let row: Vec<Option<String>> = tags
+            .iter()
+            .map(|tag| {
+                self.items
+                    .iter()
+                    .find(|(name, _)| matches(name, tag))
+                    .and_then(|(_, value)| value_text(value))
+            })
+            .collect();
+        if row[0].is_some() {
+            vec![row]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl CifDocument {
+    /// Find the first block with the given identifier.
+    ///
+    /// Codes are compared ignoring case and leading zeros; DOIs may carry a
+    /// `doi:` or `https://doi.org/` prefix, and CCDC numbers a `CCDC` prefix.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{Document, IdentifierKind};
+    ///
+    /// let doc = Document::parse("data_a\n_cod_database_code 1000000\n").unwrap();
+    /// assert!(doc.find_by_identifier(IdentifierKind::Cod, "1000000").is_some());
+    /// assert!(doc.find_by_identifier(IdentifierKind::Csd, "1000000").is_none());
+    /// ```
+    pub fn find_by_identifier(&self, kind: IdentifierKind, code: &str) -> Option<&CifBlock> {
+        let wanted = kind.canonical(code);
+        self.blocks.iter().find(|block| {
+            block
+                .identifiers()
+                .get(kind)
+                .is_some_and(|found| kind.canonical(found) == wanted)
+        })
+    }
+}
+
+/// The named field a core CIF, DDLm, or COD tag fills, given in lowercase
+fn core_kind(tag: &str) -> Option<IdentifierKind> {
+    let kind = match tag {
+        "_cod_database_code" => IdentifierKind::Cod,
+        "_journal_paper_doi" | "_journal.paper_doi" => IdentifierKind::Doi,
+        "_database_dataset_doi" | "_database.dataset_doi" => IdentifierKind::DatasetDoi,
+        _ => match database_code_suffix(tag)? {
+            "csd" => IdentifierKind::Csd,
+            "icsd" => IdentifierKind::Icsd,
+            "cod" => IdentifierKind::Cod,
+            "depnum_ccdc_archive" => IdentifierKind::CcdcDeposition,
+            _ => return None,
+        },
+    };
+    Some(kind)
+}
+
+/// The database name in a lowercase `_database_code_*` or `_database.code_*`
+/// tag
+fn database_code_suffix(tag: &str) -> Option<&str> {
+    tag.strip_prefix("_database_code_")
+        .or_else(|| tag.strip_prefix("_database.code_"))
+        .filter(|suffix| !suffix.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_codes() {
+        let ccdc = IdentifierKind::CcdcDeposition;
+        assert_eq!(ccdc.canonical("CCDC 1169420"), "1169420");
+        assert_eq!(ccdc.canonical("ccdc-1169420"), "1169420");
+        let doi = IdentifierKind::Doi;
+        assert_eq!(
+            doi.canonical("https://doi.org/10.1107/S0567740876005669"),
+            "10.1107/s0567740876005669"
+        );
+        assert_eq!(doi.canonical("doi: 10.1/X"), "10.1/x");
+        assert_eq!(IdentifierKind::Icsd.canonical("0012843"), "12843");
+        assert_eq!(IdentifierKind::Icsd.canonical("000"), "0");
+    }
+
+    #[test]
+    fn test_kind_names_round_trip() {
+        for kind in IdentifierKind::ALL {
+            assert_eq!(kind.name().parse::<IdentifierKind>(), Ok(kind));
+        }
+        assert!("isbn".parse::<IdentifierKind>().is_err());
+    }
+
+    #[test]
+    fn test_single_item_database_2() {
+        let cif = "\
+data_1ABC
+_database_2.database_id PDB
+_database_2.database_code 1ABC
+_database_2.pdbx_DOI 10.2210/pdb1abc/pdb
+";
+        let doc = CifDocument::parse(cif).unwrap();
+        let ids = doc.first_block().unwrap().identifiers();
+        assert_eq!(ids.pdb.as_deref(), Some("1ABC"));
+        assert_eq!(ids.dataset_doi.as_deref(), Some("10.2210/pdb1abc/pdb"));
+        assert!(ids.others.is_empty());
+    }
+
+    #[test]
+    fn test_block_without_identifiers() {
+        let doc = CifDocument::parse("data_a\n_cell_length_a 5.0\n").unwrap();
+        assert!(doc.first_block().unwrap().identifiers().is_empty());
+    }
+}
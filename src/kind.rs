@@ -0,0 +1,405 @@
+//! Heuristic classification of CIF files by what they contain.
+//!
+//! The `.cif` extension covers very different files: small-molecule
+//! structures, macromolecular mmCIF, structure-factor lists, powder
+//! patterns, dictionaries, and more. [`classify`] looks at the tags a
+//! document uses and reports every [`Kind`] it matches, each with the tags
+//! that gave it away, so a program can pick a processing path before
+//! reading the file in detail.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{Document, Kind};
+//!
+//! let cif = "\
+//! data_nacl
+//! _cell_length_a 5.64
+//! loop_
+//! _atom_site_label
+//! _atom_site_fract_x
+//! Na1 0
+//! Cl1 0.5
+//! ";
+//! let kind = Document::parse(cif).unwrap().kind();
+//!
+//! assert!(kind.contains(Kind::SmallMolecule));
+//! assert_eq!(
+//!     kind.evidence(Kind::SmallMolecule).unwrap(),
+//!     ["_atom_site_fract_x", "_cell_length_a"]
+//! );
+//! assert_eq!(kind.to_string(), "small-molecule (_atom_site_fract_x, _cell_length_a)");
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifLoop, CifValue};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Most tags kept as evidence for one kind
+const MAX_EVIDENCE: usize = 3;
+
+/// A kind of CIF file that [`classify`] can recognise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Kind {
+    /// A crystal structure: atom sites with fractional coordinates and a cell
+    SmallMolecule,
+    /// A macromolecular mmCIF/PDBx file, with an `_entity` category
+    Mmcif,
+    /// Reflection data: a `_refln` loop and no atom sites
+    StructureFactors,
+    /// Powder diffraction data, with `_pd_` tags
+    Powder,
+    /// A DDL dictionary, with definitions in save frames
+    Dictionary,
+    /// A magnetic structure (magCIF)
+    MagCif,
+    /// Diffraction images or their headers (imgCIF/CBF)
+    ImgCif,
+    /// A template to be filled in: most values are `?`
+    Template,
+}
+
+impl Kind {
+    /// Every kind, in the order they are reported
+    pub const ALL: [Kind; 8] = [
+        Kind::SmallMolecule,
+        Kind::Mmcif,
+        Kind::StructureFactors,
+        Kind::Powder,
+        Kind::Dictionary,
+        Kind::MagCif,
+        Kind::ImgCif,
+        Kind::Template,
+    ];
+
+    /// Short lowercase name, e.g. `small-molecule`
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::SmallMolecule => "small-molecule",
+            Kind::Mmcif => "mmcif",
+            Kind::StructureFactors => "structure-factors",
+            Kind::Powder => "powder",
+            Kind::Dictionary => "dictionary",
+            Kind::MagCif => "magcif",
+            Kind::ImgCif => "imgcif",
+            Kind::Template => "template",
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// One kind a document matched, with the tags that triggered it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KindEvidence {
+    /// The kind
+    pub kind: Kind,
+    /// Up to three tags that triggered it, as spelled in the file
+    pub tags: Vec<String>,
+}
+
+/// The set of kinds a document matched, from [`classify`].
+///
+/// A file may match several kinds (a powder structure is both
+/// `small-molecule` and `powder`) or none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CifKind {
+    found: Vec<KindEvidence>,
+}
+
+impl CifKind {
+    /// Whether the document matched `kind`
+    pub fn contains(&self, kind: Kind) -> bool {
+        self.found.iter().any(|found| found.kind == kind)
+    }
+
+    /// Whether the document matched no kind at all
+    pub fn is_empty(&self) -> bool {
+        self.found.is_empty()
+    }
+
+    /// The kinds matched, in the order of [`Kind::ALL`]
+    pub fn kinds(&self) -> impl Iterator<Item = Kind> + '_ {
+        self.found.iter().map(|found| found.kind)
+    }
+
+    /// The tags that triggered `kind`, or `None` if it was not matched
+    pub fn evidence(&self, kind: Kind) -> Option<&[String]> {
+        self.found
+            .iter()
+            .find(|found| found.kind == kind)
+            .map(|found| found.tags.as_slice())
+    }
+
+    /// Every kind matched, with its evidence
+    pub fn iter(&self) -> impl Iterator<Item = &KindEvidence> {
+        self.found.iter()
+    }
+}
+
+impl fmt::Display for CifKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.found.is_empty() {
+            return f.write_str("unknown");
+        }
+        for (i, found) in self.found.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{} ({})", found.kind, found.tags.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Tag prefixes that mark magCIF files
+const MAGCIF_PREFIXES: &[&str] = &[
+    "_space_group_magn",
+    "_parent_space_group",
+    "_atom_site_moment",
+];
+
+/// Tag prefixes that mark imgCIF files
+const IMGCIF_PREFIXES: &[&str] = &["_array_data", "_array_structure", "_diffrn_data_frame"];
+
+/// Attributes that define items in DDLm and DDL2 dictionaries
+const DDL_ATTRIBUTES: &[&str] = &["_definition.id", "_item.name", "_category.id"];
+
+/// Whether `tag` starts with `prefix`, ignoring case
+fn has_prefix(tag: &str, prefix: &str) -> bool {
+    tag.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+/// The first few of `tags`, sorted
+fn evidence<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let tags: BTreeSet<&str> = tags.into_iter().collect();
+    tags.into_iter()
+        .take(MAX_EVIDENCE)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Every tag of a block, including those in its frames
+fn block_tags(block: &CifBlock) -> Vec<&str> {
+    block.all_tags().collect()
+}
+
+/// The first tag in `tags` equal to one of `names`, ignoring case
+fn find_tag<'a>(tags: &[&'a str], names: &[&str]) -> Option<&'a str> {
+    tags.iter()
+        .copied()
+        .find(|tag| names.iter().any(|name| tag.eq_ignore_ascii_case(name)))
+}
+
+fn small_molecule(block: &CifBlock) -> Option<Vec<String>> {
+    let tags = block_tags(block);
+    let atom = find_tag(&tags, &["_atom_site_fract_x", "_atom_site.fract_x"])?;
+    let cell = find_tag(&tags, &["_cell_length_a", "_cell.length_a"])?;
+    Some(vec![atom.to_string(), cell.to_string()])
+}
+
+fn mmcif(block: &CifBlock) -> Option<Vec<String>> {
+    let tags = block_tags(block);
+    let entity: Vec<&str> = tags
+        .iter()
+        .copied()
+        .filter(|tag| has_prefix(tag, "_entity."))
+        .collect();
+    (!entity.is_empty()).then(|| evidence(entity))
+}
+
+fn structure_factors(block: &CifBlock) -> Option<Vec<String>> {
+    let tags = block_tags(block);
+    if tags.iter().any(|tag| has_prefix(tag, "_atom_site")) {
+        return None;
+    }
+    let is_refln = |loop_: &CifLoop| {
+        loop_
+            .tags
+            .iter()
+            .any(|tag| has_prefix(tag, "_refln_index_") || has_prefix(tag, "_refln.index_"))
+    };
+    let largest = block.loops.iter().max_by_key(|loop_| loop_.len())?;
+    if !is_refln(largest) {
+        return None;
+    }
+    Some(evidence(
+        largest
+            .tags
+            .iter()
+            .map(String::as_str)
+            .filter(|tag| has_prefix(tag, "_refln")),
+    ))
+}
+
+fn with_prefixes(block: &CifBlock, prefixes: &[&str]) -> Option<Vec<String>> {
+    let tags: Vec<&str> = block_tags(block)
+        .into_iter()
+        .filter(|tag| prefixes.iter().any(|prefix| has_prefix(tag, prefix)))
+        .collect();
+    (!tags.is_empty()).then(|| evidence(tags))
+}
+
+fn dictionary(block: &CifBlock) -> Option<Vec<String>> {
+    let tags: Vec<&str> = block
+        .frames
+        .iter()
+        .flat_map(|frame| frame.all_tags())
+        .filter(|tag| {
+            DDL_ATTRIBUTES
+                .iter()
+                .any(|name| tag.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    (!tags.is_empty()).then(|| evidence(tags))
+}
+
+/// Count the values of items and loops, and collect the tags of `?` values
+fn count_unknowns<'a>(
+    items: impl Iterator<Item = (&'a String, &'a CifValue)>,
+    loops: &'a [CifLoop],
+    unknown_tags: &mut Vec<&'a str>,
+) -> (usize, usize) {
+    let (mut unknown, mut total) = (0, 0);
+    for (tag, value) in items {
+        total += 1;
+        if matches!(value, CifValue::Unknown) {
+            unknown += 1;
+            unknown_tags.push(tag);
+        }
+    }
+    for loop_ in loops {
+        for row in &loop_.values {
+            for (tag, value) in loop_.tags.iter().zip(row) {
+                total += 1;
+                if matches!(value, CifValue::Unknown) {
+                    unknown += 1;
+                    unknown_tags.push(tag);
+                }
+            }
+        }
+    }
+    (unknown, total)
+}
+
+fn template(doc: &CifDocument) -> Option<Vec<String>> {
+    let mut tags = Vec::new();
+    let (mut unknown, mut total) = (0, 0);
+    for block in &doc.blocks {
+        let (u, t) = count_unknowns(block.items.iter(), &block.loops, &mut tags);
+        (unknown, total) = (unknown + u, total + t);
+        for frame in &block.frames {
+            let (u, t) = count_unknowns(frame.items.iter(), &frame.loops, &mut tags);
+            (unknown, total) = (unknown + u, total + t);
+        }
+    }
+    (2 * unknown > total).then(|| evidence(tags))
+}
+
+/// Work out what kind of file a document is.
+///
+/// The rules are:
+///
+/// - `small-molecule`: fractional atom-site coordinates and a cell
+/// - `mmcif`: an mmCIF `_entity` category
+/// - `structure-factors`: the largest loop is a `_refln` loop with Miller
+///   indices, and there are no atom sites
+/// - `powder`: `_pd_` tags
+/// - `dictionary`: `_definition.id`, `_item.name`, or `_category.id` in a
+///   save frame
+/// - `magcif`: `_space_group_magn`, `_parent_space_group`, or
+///   `_atom_site_moment` tags
+/// - `imgcif`: `_array_data`, `_array_structure`, or `_diffrn_data_frame`
+///   tags
+/// - `template`: more than half of all values are `?`
+///
+/// A kind matches if any block matches it; the evidence comes from the
+/// first such block. Tags are matched ignoring case.
+pub fn classify(doc: &CifDocument) -> CifKind {
+    let mut found = Vec::new();
+    for kind in Kind::ALL {
+        let tags = match kind {
+            Kind::SmallMolecule => doc.blocks.iter().find_map(small_molecule),
+            Kind::Mmcif => doc.blocks.iter().find_map(mmcif),
+            Kind::StructureFactors => doc.blocks.iter().find_map(structure_factors),
+            Kind::Powder => doc.blocks.iter().find_map(|b| with_prefixes(b, &["_pd_"])),
+            Kind::Dictionary => doc.blocks.iter().find_map(dictionary),
+            Kind::MagCif => doc
+                .blocks
+                .iter()
+                .find_map(|b| with_prefixes(b, MAGCIF_PREFIXES)),
+            Kind::ImgCif => doc
+                .blocks
+                .iter()
+                .find_map(|b| with_prefixes(b, IMGCIF_PREFIXES)),
+            Kind::Template => template(doc),
+        };
+        if let Some(tags) = tags {
+            found.push(KindEvidence { kind, tags });
+        }
+    }
+    CifKind { found }
+}
+
+/// Loop tag prefixes of powder patterns, as opposed to the `_pd_block_*`
+/// links that publication blocks of pdCIF files also carry
+const POWDER_PATTERN_PREFIXES: &[&str] = &["_pd_meas_", "_pd_proc_", "_pd_calc_"];
+
+impl CifBlock {
+    /// Whether this block holds data of its own, rather than only
+    /// publication details or other metadata shared by the blocks after it.
+    ///
+    /// A block holds data if it matches one of the per-block rules of
+    /// [`classify`] (`template` aside), has a unit cell, or loops over a
+    /// powder pattern.
+    pub fn holds_data(&self) -> bool {
+        let tags = block_tags(self);
+        small_molecule(self).is_some()
+            || mmcif(self).is_some()
+            || structure_factors(self).is_some()
+            || dictionary(self).is_some()
+            || with_prefixes(self, MAGCIF_PREFIXES).is_some()
+            || with_prefixes(self, IMGCIF_PREFIXES).is_some()
+            || find_tag(&tags, &["_cell_length_a", "_cell.length_a"]).is_some()
+            || self.loops.iter().any(|loop_| {
+                loop_.tags.iter().any(|tag| {
+                    POWDER_PATTERN_PREFIXES
+                        .iter()
+                        .any(|prefix| has_prefix(tag, prefix))
+                })
+            })
+    }
+}
+
+impl CifDocument {
+    /// What kind of file this is; see [`classify`]
+    pub fn kind(&self) -> CifKind {
+        classify(self)
+    }
+
+    /// The blocks that [hold data](CifBlock::holds_data), in file order
+    pub fn data_blocks(&self) -> impl Iterator<Item = &CifBlock> {
+        self.blocks.iter().filter(|block| block.holds_data())
+    }
+
+    /// The block a script on a one-structure file wants: the first that
+    /// [holds data](CifBlock::holds_data), so that a leading global or
+    /// publication block is skipped, or else the first block.
+    ///
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let cif = "data_global\n_publ_section_title 'NaCl'\n\
+    ///            data_nacl\n_cell_length_a 5.64\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// assert_eq!(doc.main_block().unwrap().name, "nacl");
+    /// ```
+    pub fn main_block(&self) -> Option<&CifBlock> {
+        self.data_blocks().next().or(self.blocks.first())
+    }
+}
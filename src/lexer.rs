@@ -0,0 +1,461 @@
+//! Tokenizing CIF input without parsing it.
+//!
+//! [`Tokenizer`] splits CIF text into [`Token`]s with their kind, text, and
+//! position, following the same lexical rules as the grammar. It is meant for
+//! tools that need the lexical structure rather than the data: syntax
+//! highlighters, editors, and readers of dialects the parser rejects. Selective
+//! parsing uses it to skip unselected content before the grammar runs.
+//!
+//! With trivia kept (the default), the tokens cover the input exactly:
+//! concatenating their text gives back the original string. The token kinds
+//! and positions are a stable API.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::lexer::{TokenKind, Tokenizer};
+//!
+//! let tokens: Vec<_> = Tokenizer::new("data_x\n_name 'O''Brien'\n")
+//!     .skip_trivia(true)
+//!     .collect::<Result<_, _>>()
+//!     .unwrap();
+//! let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+//! assert_eq!(
+//!     kinds,
+//!     [TokenKind::BlockHeader, TokenKind::Tag, TokenKind::QuotedString]
+//! );
+//! assert_eq!(tokens[2].text, "'O''Brien'");
+//! assert_eq!((tokens[2].line, tokens[2].col), (2, 7));
+//! ```
+
+use crate::error::CifError;
+use std::ops::Range;
+
+/// The lexical category of a [`Token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// `data_name`, or the `global_` heading
+    BlockHeader,
+    /// `save_name`, opening a save frame
+    SaveHeader,
+    /// A bare `save_`, closing a save frame
+    SaveEnd,
+    /// `loop_`, or the `stop_` that some STAR files use to end one
+    LoopKeyword,
+    /// A data name such as `_cell_length_a`
+    Tag,
+    /// A single-, double-, or triple-quoted string, quotes included
+    QuotedString,
+    /// A semicolon-delimited text field, both delimiters included
+    TextField,
+    /// A whitespace-delimited value
+    UnquotedValue,
+    /// `[`, `]`, `{`, or `}` around a CIF 2.0 list or table, or the `:`
+    /// after a table key
+    Delimiter,
+    /// `#` to the end of the line, line terminator excluded
+    Comment,
+    /// Spaces, tabs, and line terminators (and a leading byte-order mark)
+    Whitespace,
+}
+
+impl TokenKind {
+    /// Whether this is whitespace or a comment
+    pub fn is_trivia(self) -> bool {
+        matches!(self, TokenKind::Whitespace | TokenKind::Comment)
+    }
+}
+
+/// A token and where it is in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    /// The token as written, delimiters included
+    pub text: &'a str,
+    /// 1-indexed line of the first character
+    pub line: usize,
+    /// 1-indexed column of the first character, counted in characters
+    pub col: usize,
+    /// Byte offsets of the token in the input
+    pub byte_range: Range<usize>,
+}
+
+/// Iterator over the tokens of a CIF string.
+///
+/// Line terminators may be LF, CRLF, or CR; each counts as one line break,
+/// as in the parser. Input that cannot be tokenized (an unterminated quoted
+/// string or text field) gives an error located at the start of the
+/// offending token, after which the iteration ends.
+///
+/// The tokenizer does not check that tokens appear in a valid order; a tag
+/// with no value, for example, is left for the parser to reject.
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    pos: usize,
+    line: usize,
+    /// Byte offset of the start of `line`
+    line_start: usize,
+    skip_trivia: bool,
+    /// Open `[` and `{` delimiters, innermost last
+    brackets: Vec<u8>,
+    /// Whether the last token was a table key, so a `:` is a delimiter
+    after_key: bool,
+    /// Offset past which no text field closes, once a search has failed
+    unclosed_from: usize,
+    done: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Tokenize `input`, including whitespace and comments
+    pub fn new(input: &'a str) -> Self {
+        Tokenizer {
+            input,
+            pos: 0,
+            line: 1,
+            line_start: 0,
+            skip_trivia: false,
+            brackets: Vec::new(),
+            after_key: false,
+            unclosed_from: input.len() + 1,
+            done: false,
+        }
+    }
+
+    /// Leave out whitespace and comment tokens
+    pub fn skip_trivia(mut self, skip: bool) -> Self {
+        self.skip_trivia = skip;
+        self
+    }
+
+    fn bytes(&self) -> &'a [u8] {
+        self.input.as_bytes()
+    }
+
+    /// Lex one token at the current position, which is before the end
+    fn token(&mut self) -> Result<Token<'a>, CifError> {
+        let (start, line) = (self.pos, self.line);
+        let col = self.input[self.line_start..start].chars().count() + 1;
+        let bytes = self.bytes();
+        let after_key = std::mem::take(&mut self.after_key);
+
+        let kind = match bytes[start] {
+            _ if start == 0 && self.input.starts_with('\u{FEFF}') => {
+                self.pos = '\u{FEFF}'.len_utf8();
+                TokenKind::Whitespace
+            }
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                self.whitespace();
+                TokenKind::Whitespace
+            }
+            b'#' => {
+                while self
+                    .bytes()
+                    .get(self.pos)
+                    .is_some_and(|&b| b != b'\n' && b != b'\r')
+                {
+                    self.pos += 1;
+                }
+                TokenKind::Comment
+            }
+            b';' if start == self.line_start => {
+                self.text_field()
+                    .ok_or_else(|| unterminated("text field", line, col))?;
+                TokenKind::TextField
+            }
+            // As in the grammar, a value starting with `;` later in a line
+            // opens a text field too, if one is closed after it
+            b';' if self.text_field().is_some() => TokenKind::TextField,
+            quote @ (b'\'' | b'"') => {
+                if self.brackets.last() == Some(&b'{') && self.table_key(quote) {
+                    self.after_key = true;
+                } else {
+                    self.quoted(quote)
+                        .ok_or_else(|| unterminated("quoted string", line, col))?;
+                }
+                TokenKind::QuotedString
+            }
+            open @ (b'[' | b'{') => {
+                self.brackets.push(open);
+                self.pos += 1;
+                TokenKind::Delimiter
+            }
+            b']' | b'}' => {
+                self.brackets.pop();
+                self.pos += 1;
+                TokenKind::Delimiter
+            }
+            b':' if after_key => {
+                self.pos += 1;
+                TokenKind::Delimiter
+            }
+            _ => {
+                self.bare();
+                let kind = bare_kind(&self.input[start..self.pos]);
+                // Names run to whitespace, brackets included, as in `_axis.vector[1]`
+                if matches!(
+                    kind,
+                    TokenKind::Tag | TokenKind::BlockHeader | TokenKind::SaveHeader
+                ) {
+                    self.name();
+                }
+                kind
+            }
+        };
+        // Inline whitespace between a table key and its `:` keeps the key pending
+        self.after_key |= after_key && kind == TokenKind::Whitespace && self.line == line;
+
+        Ok(Token {
+            kind,
+            text: &self.input[start..self.pos],
+            line,
+            col,
+            byte_range: start..self.pos,
+        })
+    }
+
+    /// Spaces, tabs, and line terminators
+    fn whitespace(&mut self) {
+        while let Some(&b) = self.bytes().get(self.pos) {
+            match b {
+                b' ' | b'\t' => self.pos += 1,
+                b'\n' | b'\r' => self.newline(),
+                _ => break,
+            }
+        }
+    }
+
+    /// Step over the line terminator at the current position
+    fn newline(&mut self) {
+        let bytes = self.bytes();
+        if bytes[self.pos] == b'\r' && bytes.get(self.pos + 1) == Some(&b'\n') {
+            self.pos += 1;
+        }
+        self.pos += 1;
+        self.line += 1;
+        self.line_start = self.pos;
+    }
+
+    /// Move to `end`, counting the line breaks passed
+    fn advance_to(&mut self, end: usize) {
+        while self.pos < end {
+            match self.bytes()[self.pos] {
+                b'\n' | b'\r' => self.newline(),
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// `;` at the start of a line, up to the next line starting with `;`
+    fn text_field(&mut self) -> Option<()> {
+        if self.pos >= self.unclosed_from {
+            return None;
+        }
+        let body = &self.bytes()[self.pos + 1..];
+        let Some(close) = body
+            .windows(2)
+            .position(|w| (w[0] == b'\n' || w[0] == b'\r') && w[1] == b';')
+        else {
+            self.unclosed_from = self.pos;
+            return None;
+        };
+        self.advance_to(self.pos + 1 + close + 2);
+        Some(())
+    }
+
+    /// A triple-quoted string, or a quoted string ending at a quote followed
+    /// by whitespace, `#`, or the end of input, or inside a list or table by
+    /// `]` or `}`
+    fn quoted(&mut self, quote: u8) -> Option<()> {
+        let bytes = self.bytes();
+        let nested = !self.brackets.is_empty();
+        let triple = [quote; 3];
+        if bytes[self.pos..].starts_with(&triple) {
+            let body = &bytes[self.pos + 3..];
+            if let Some(close) = body.windows(3).position(|w| w == triple) {
+                self.advance_to(self.pos + 3 + close + 3);
+                return Some(());
+            }
+        }
+
+        let mut i = self.pos + 1;
+        loop {
+            match bytes.get(i) {
+                None | Some(b'\n' | b'\r') => return None,
+                Some(&b)
+                    if b == quote
+                        && bytes.get(i + 1).is_none_or(|&n| {
+                            matches!(n, b' ' | b'\t' | b'\n' | b'\r' | b'#')
+                                || (nested && matches!(n, b']' | b'}'))
+                        }) =>
+                {
+                    self.pos = i + 1;
+                    return Some(());
+                }
+                Some(_) => i += 1,
+            }
+        }
+    }
+
+    /// A table key: a quoted string ending at the first matching quote and
+    /// followed by `:`. Returns false, without moving, if this is not a key.
+    fn table_key(&mut self, quote: u8) -> bool {
+        let bytes = self.bytes();
+        let triple = [quote; 3];
+        let close = if bytes[self.pos..].starts_with(&triple) {
+            let body = &bytes[self.pos + 3..];
+            body.windows(3)
+                .position(|w| w == triple)
+                .map(|close| 3 + close + 3)
+        } else {
+            let body = &bytes[self.pos + 1..];
+            body.iter()
+                .position(|&b| b == quote)
+                .filter(|&close| !body[..close].contains(&b'\n') && !body[..close].contains(&b'\r'))
+                .map(|close| 1 + close + 1)
+        };
+        let Some(len) = close else {
+            return false;
+        };
+        let after = bytes[self.pos + len..]
+            .iter()
+            .find(|&&b| b != b' ' && b != b'\t');
+        if after != Some(&b':') {
+            return false;
+        }
+        self.advance_to(self.pos + len);
+        true
+    }
+
+    /// A whitespace-delimited token, ending at whitespace or a bracket
+    fn name(&mut self) {
+        while self
+            .bytes()
+            .get(self.pos)
+            .is_some_and(|&b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn bare(&mut self) {
+        while self.bytes().get(self.pos).is_some_and(|&b| {
+            !matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'[' | b']' | b'{' | b'}')
+        }) {
+            self.pos += 1;
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token<'a>, CifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done && self.pos < self.input.len() {
+            match self.token() {
+                Ok(token) if self.skip_trivia && token.kind.is_trivia() => continue,
+                Ok(token) => return Some(Ok(token)),
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Kind of a whitespace-delimited token: a reserved word, tag, or value
+fn bare_kind(text: &str) -> TokenKind {
+    let starts = |word: &str| {
+        text.as_bytes()
+            .get(..word.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(word.as_bytes()))
+    };
+    if starts("data_") || text.eq_ignore_ascii_case("global_") {
+        TokenKind::BlockHeader
+    } else if text.eq_ignore_ascii_case("save_") {
+        TokenKind::SaveEnd
+    } else if starts("save_") {
+        TokenKind::SaveHeader
+    } else if text.eq_ignore_ascii_case("loop_") || text.eq_ignore_ascii_case("stop_") {
+        TokenKind::LoopKeyword
+    } else if text.starts_with('_') {
+        TokenKind::Tag
+    } else {
+        TokenKind::UnquotedValue
+    }
+}
+
+fn unterminated(what: &str, line: usize, col: usize) -> CifError {
+    CifError::Unterminated {
+        construct: what.to_string(),
+        location: Some((line, col)),
+        span: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(input: &str) -> Vec<&str> {
+        Tokenizer::new(input)
+            .skip_trivia(true)
+            .map_while(Result::ok)
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_token_boundaries() {
+        assert_eq!(texts("_a 'x y' b # c\n"), ["_a", "'x y'", "b"]);
+        assert_eq!(texts("_a 'it's' \"q\""), ["_a", "'it's'", "\"q\""]);
+        assert_eq!(texts("_a\n;x\n y\n;\n_b"), ["_a", ";x\n y\n;", "_b"]);
+        assert_eq!(texts("_a ;x\n;\n_b ;y"), ["_a", ";x\n;", "_b", ";y"]);
+        assert_eq!(texts("_a '''x\n'y''' _b"), ["_a", "'''x\n'y'''", "_b"]);
+        assert_eq!(texts("_a b#c"), ["_a", "b#c"]);
+        assert_eq!(texts("_v[1] [2]"), ["_v[1]", "[", "2", "]"]);
+        assert_eq!(texts("data_x{1} save_f[2]"), ["data_x{1}", "save_f[2]"]);
+    }
+
+    #[test]
+    fn test_lists_and_tables() {
+        assert_eq!(
+            texts("_a [1 'b c' {'k':[2] \"j\" : x}]"),
+            [
+                "_a", "[", "1", "'b c'", "{", "'k'", ":", "[", "2", "]", "\"j\"", ":", "x", "}",
+                "]"
+            ]
+        );
+        // Outside a table a colon is part of a value
+        assert_eq!(texts("_t 12:30 ':'"), ["_t", "12:30", "':'"]);
+        // Quoted members may end at the closing bracket or brace
+        assert_eq!(
+            texts("_a ['x' \"y\"] {'k':'v'} 'z']'"),
+            ["_a", "[", "'x'", "\"y\"", "]", "{", "'k'", ":", "'v'", "}", "'z']'"]
+        );
+    }
+
+    #[test]
+    fn test_trivia_covers_input() {
+        let input = "\u{FEFF}data_a # c\r\n_x\r;t\r\n;\n loop_ _y 1\n";
+        let joined: String = Tokenizer::new(input)
+            .map(|token| token.unwrap().text)
+            .collect();
+        assert_eq!(joined, input);
+    }
+
+    #[test]
+    fn test_errors_stop_iteration() {
+        let tokens: Vec<_> = Tokenizer::new("_a 'open\n_b 1").collect();
+        assert_eq!(tokens.len(), 3);
+        let error = tokens[2].as_ref().unwrap_err();
+        assert!(matches!(
+            error,
+            CifError::Unterminated {
+                location: Some((1, 4)),
+                ..
+            }
+        ));
+    }
+}
@@ -71,9 +71,20 @@
 //!
 //! - [`ast`] - Abstract Syntax Tree types (data structures)
 //! - [`parser`] - Parsing logic (PEST → AST conversion)
+//! - [`lexer`] - Tokenizer for tools that need tokens rather than a parse tree
 //! - [`error`] - Error types
+//! - [`shared`] - Thread-safe `Arc` handles for sharing documents across threads
 //! - `builder` - Internal state management helpers (not public)
 //!
+//! ## Thread Safety
+//!
+//! All AST types are `Send + Sync` and contain no interior mutability. A parsed
+//! document can be wrapped with [`CifDocument::into_shared`] and read from many
+//! threads at once; [`SharedBlock`] and [`SharedLoop`] provide cheap handles
+//! that can be moved into worker threads without cloning any data. Documents
+//! that are changed while shared use copy-on-write, through
+//! [`CifDocument::make_mut_block`] or a [`DocumentHandle`].
+//!
 //! ## Examples
 //!
 //! ### Basic Usage
@@ -127,9 +138,66 @@ use std::path::Path;
 
 // ===== Core Modules =====
 
+pub mod approx;
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod ast;
+pub mod atom_label;
+#[cfg(feature = "bcif")]
+pub mod bcif;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod borrowed;
+pub mod cache;
+pub mod category;
+pub mod cell;
+pub mod chain;
+#[cfg(feature = "cif-json")]
+pub mod cif_json;
+pub mod compression;
+pub mod conformance;
+pub mod convert;
+pub mod corpus;
+pub mod dataset;
+pub mod dictionary;
+pub mod diffrn;
+pub mod element;
 pub mod error;
+pub mod experimental;
+pub mod flatten;
+pub mod formatter;
+pub mod formula;
+pub mod identifiers;
+pub mod kind;
+pub mod lexer;
+pub mod measurement;
+pub mod memory;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod parser;
+pub mod provenance;
+pub mod publication;
+pub mod query;
+pub mod reader;
+pub mod redact;
+pub mod refine;
+pub mod reflections;
+pub mod report;
+pub mod sanity;
+pub mod schema;
+pub mod shared;
+pub mod space_group;
+pub mod structure;
+pub mod symmetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod text;
+pub mod typed;
+pub mod uncertainty;
+pub mod undo;
+pub mod upgrade;
+pub mod vocab;
+pub mod writer;
 
 mod builder; // Internal only
 
@@ -142,10 +210,69 @@ pub struct CIFParser;
 // ===== Re-exports =====
 
 // AST types
-pub use ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
+pub use ast::{
+    CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion, DocumentBuilder,
+    DocumentSource, TagLocation, WalkEntry,
+};
+
+// Borrowed parsing
+pub use borrowed::{CifBlockRef, CifDocumentRef, CifFrameRef, CifLoopRef, CifValueRef};
 
 // Error types
-pub use error::CifError;
+pub use error::{CifError, CifWarning, ErrorSpan};
+
+// Parser configuration
+pub use parser::{
+    DecimalComma, DialectAction, DialectHandler, DocumentStream, DuplicateTags, ParseOptions,
+    Selection,
+};
+
+// Shared handles
+pub use shared::{DocumentHandle, SharedBlock, SharedLoop};
+
+// Domain metadata
+pub use atom_label::{natural_cmp, AtomLabel, ElementOrder, RelabelScheme};
+pub use cache::{CacheOptions, CacheStatus};
+pub use category::{Category, CategoryResolver, LoopSplit};
+pub use cell::UnitCell;
+pub use chain::{AuthResidue, ChainMap, LabelResidue};
+pub use conformance::{Conformance, ConformanceRule};
+pub use corpus::{extract_items, ExtractOptions, ExtractRecord};
+pub use dictionary::{CifDictionary, Definition, DictionaryStack, ValidationIssue, ValidationKind};
+pub use diffrn::{GoniometerAxis, UbCheck};
+pub use element::{Element, ElementSymbol, Isotope};
+pub use experimental::Experimental;
+pub use flatten::{FlatEntry, FlattenOptions};
+pub use formatter::{format_str, FormatOptions};
+pub use identifiers::{IdentifierKind, Identifiers};
+pub use kind::{classify, CifKind, Kind, KindEvidence};
+pub use measurement::Measurement;
+pub use memory::MemoryReport;
+pub use provenance::{ProvenanceOptions, ProvenanceOverride};
+pub use publication::{Author, Publication};
+pub use query::{query, Query, QueryHit};
+pub use redact::{Redaction, RedactionPolicy, RedactionReport};
+pub use refine::{HydrogenTreatment, RefinementModel, Restraint, Weighting};
+pub use reflections::{
+    MergeOptions, MergeStatistics, MergedReflection, MergedReflections, Reflection, ReflectionData,
+    ShellBinning,
+};
+pub use report::{Finding, Report, Severity};
+pub use sanity::SanityIssue;
+pub use schema::{CoercionReport, ExpectedType, TagStatus};
+pub use space_group::SpaceGroup;
+pub use structure::{
+    compare_structures, AtomSite, MatchOptions, SiteDifference, SiteMatching, SiteSymmetry,
+    Structure, StructureComparison,
+};
+pub use symmetry::{CrystalSystem, PointGroupInfo, SymOp};
+pub use typed::{ConversionError, Converter, FromCifValue};
+pub use undo::{DocumentSnapshot, UndoStack};
+pub use upgrade::{UpgradeChange, UpgradeOptions, UpgradeProblem, UpgradeReport, ValuePlace};
+pub use vocab::{AdpType, VocabIssue};
+
+// Output
+pub use writer::{CifStyle, FloatFormat, NumberFormat, WriteOptions};
 
 // Convenient type aliases (matching old API)
 pub use CifBlock as Block;
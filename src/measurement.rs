@@ -0,0 +1,209 @@
+//! Measurement bookkeeping: standard reflections, scans, and instruments.
+//!
+//! [`CifBlock::measurement`] gathers how the intensities were measured: the
+//! standard reflections and how much they decayed (`_diffrn_standards_*`),
+//! the scan (`_diffrn_measurement_*`), and the diffractometer, detector, and
+//! monochromator. Serial-diffractometer files of the CAD-4 era fill in the
+//! standards and scan, area-detector files mostly the instruments; every
+//! field is optional. [`Measurement::warnings`] flags a large decay of the
+//! standards.
+//!
+//! Both spellings of the decay item, `_diffrn_standards_decay_%` and
+//! `_diffrn_standards_decay_percent`, are read, as are the mmCIF names.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_cad4
+//! _diffrn_measurement_device_type 'Enraf-Nonius CAD-4'
+//! _diffrn_measurement_method \\w/2\\q
+//! _diffrn_measurement_scan_time 60
+//! _diffrn_reflns_number 1200
+//! _diffrn_standards_number 3
+//! _diffrn_standards_interval_count 100
+//! _diffrn_standards_decay_% 12.5
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let measurement = doc.first_block().unwrap().measurement();
+//!
+//! assert_eq!(measurement.standards_number, Some(3));
+//! assert_eq!(measurement.scan_method.as_deref(), Some("\\w/2\\q"));
+//! // 1200 reflections and 3 standards every 100, a minute each
+//! assert_eq!(measurement.total_exposure_estimate(), Some(1236.0 * 60.0));
+//! assert_eq!(measurement.warnings()[0].id, "standards-decay");
+//! ```
+
+use crate::ast::CifBlock;
+use crate::experimental::written;
+use crate::publication::value_text;
+use crate::sanity::SanityIssue;
+
+/// Decay of the standard reflections, in percent, above which
+/// [`Measurement::warnings`] reports it
+pub const DEFAULT_DECAY_THRESHOLD: f64 = 10.0;
+
+/// How the intensities of a data block were measured.
+///
+/// Numbers come with their su, if written with one; counts are whole
+/// numbers. Absent values and `?`/`.` are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Measurement {
+    /// `_diffrn_standards_number`, the number of standard reflections
+    pub standards_number: Option<u32>,
+    /// `_diffrn_standards_interval_count`, reflections measured between
+    /// standards
+    pub standards_interval_count: Option<u32>,
+    /// `_diffrn_standards_interval_time`, minutes between standards
+    pub standards_interval_time: Option<(f64, Option<f64>)>,
+    /// `_diffrn_standards_decay_%` or `_diffrn_standards_decay_percent`,
+    /// the mean decrease in the intensity of the standards, in percent
+    pub standards_decay_percent: Option<(f64, Option<f64>)>,
+    /// `_diffrn_measurement_method`, such as `\w/2\q scans`
+    pub scan_method: Option<String>,
+    /// `_diffrn_measurement_scan_width`, in degrees
+    pub scan_width: Option<(f64, Option<f64>)>,
+    /// `_diffrn_measurement_scan_time`, seconds per reflection (serial
+    /// diffractometers) or per frame (area detectors)
+    pub scan_time: Option<(f64, Option<f64>)>,
+    /// `_diffrn_measurement_details`, free text about the scans
+    pub details: Option<String>,
+    /// `_diffrn_measurement_device`, such as `four-circle diffractometer`
+    pub device: Option<String>,
+    /// `_diffrn_measurement_device_type`, the make and model
+    pub device_type: Option<String>,
+    /// `_diffrn_detector`, such as `scintillation counter` or `CCD area
+    /// detector`
+    pub detector: Option<String>,
+    /// `_diffrn_detector_type`, the make and model
+    pub detector_type: Option<String>,
+    /// `_diffrn_radiation_monochromator`, such as `graphite`
+    pub monochromator: Option<String>,
+    /// `_diffrn_reflns_number`, reflections measured
+    pub reflections_measured: Option<u32>,
+}
+
+/// Spellings of each item: core, then mmCIF
+const STANDARDS_NUMBER: &[&str] = &["_diffrn_standards_number", "_diffrn_standards.number"];
+const INTERVAL_COUNT: &[&str] = &[
+    "_diffrn_standards_interval_count",
+    "_diffrn_standards.interval_count",
+];
+const INTERVAL_TIME: &[&str] = &[
+    "_diffrn_standards_interval_time",
+    "_diffrn_standards.interval_time",
+];
+const DECAY: &[&str] = &[
+    "_diffrn_standards_decay_%",
+    "_diffrn_standards_decay_percent",
+    "_diffrn_standards.decay_%",
+    "_diffrn_standards.decay_percent",
+];
+const METHOD: &[&str] = &["_diffrn_measurement_method", "_diffrn_measurement.method"];
+const SCAN_WIDTH: &[&str] = &[
+    "_diffrn_measurement_scan_width",
+    "_diffrn_measurement.scan_width",
+];
+const SCAN_TIME: &[&str] = &[
+    "_diffrn_measurement_scan_time",
+    "_diffrn_measurement.scan_time",
+];
+const DETAILS: &[&str] = &["_diffrn_measurement_details", "_diffrn_measurement.details"];
+const DEVICE: &[&str] = &["_diffrn_measurement_device", "_diffrn_measurement.device"];
+const DEVICE_TYPE: &[&str] = &[
+    "_diffrn_measurement_device_type",
+    "_diffrn_measurement.device_type",
+];
+const DETECTOR: &[&str] = &["_diffrn_detector", "_diffrn_detector.detector"];
+const DETECTOR_TYPE: &[&str] = &["_diffrn_detector_type", "_diffrn_detector.type"];
+const MONOCHROMATOR: &[&str] = &[
+    "_diffrn_radiation_monochromator",
+    "_diffrn_radiation.monochromator",
+];
+const REFLECTIONS: &[&str] = &["_diffrn_reflns_number", "_diffrn_reflns.number"];
+
+impl Measurement {
+    /// Estimated time spent measuring, in seconds: the scan time for each
+    /// reflection, plus for each standard every
+    /// [`standards_interval_count`](Self::standards_interval_count)
+    /// reflections.
+    ///
+    /// Meaningful for serial diffractometers, where the scan time is per
+    /// reflection. `None` without a scan time and reflection count.
+    pub fn total_exposure_estimate(&self) -> Option<f64> {
+        let (scan_time, _) = self.scan_time?;
+        let reflections = f64::from(self.reflections_measured?);
+        let standards = match (self.standards_number, self.standards_interval_count) {
+            (Some(number), Some(interval)) if interval > 0 => {
+                f64::from(number) * (reflections / f64::from(interval)).floor()
+            }
+            _ => 0.0,
+        };
+        Some((reflections + standards) * scan_time)
+    }
+
+    /// The decay of the standards, if its magnitude exceeds
+    /// `threshold_percent`
+    pub fn decay_warning(&self, threshold_percent: f64) -> Option<SanityIssue> {
+        let decay = self.standards_decay_percent?;
+        (decay.0.abs() > threshold_percent).then(|| SanityIssue {
+            id: "standards-decay",
+            tag: DECAY[0].to_string(),
+            row: None,
+            value: written(decay),
+            rule: format!(
+                "standard reflections should change by at most {threshold_percent}% \
+                 over the measurement"
+            ),
+        })
+    }
+
+    /// Signs of a poor measurement: for now, a decay of the standards of
+    /// more than [`DEFAULT_DECAY_THRESHOLD`] percent. An empty list means
+    /// nothing was found, or there was nothing to check.
+    pub fn warnings(&self) -> Vec<SanityIssue> {
+        self.decay_warning(DEFAULT_DECAY_THRESHOLD)
+            .into_iter()
+            .collect()
+    }
+}
+
+impl CifBlock {
+    /// Gather how the intensities were measured from this block.
+    ///
+    /// Core names such as `_diffrn_standards_interval_count` and mmCIF names
+    /// such as `_diffrn_standards.interval_count` are both read. See the
+    /// [module documentation](crate::measurement) for an example.
+    pub fn measurement(&self) -> Measurement {
+        let text = |tags: &[&str]| {
+            tags.iter()
+                .find_map(|tag| self.get_item(tag).and_then(value_text))
+        };
+        let number = |tags: &[&str]| self.number_with_su(tags);
+        let count = |tags: &[&str]| {
+            self.number_with_su(tags)
+                .map(|(n, _)| n)
+                .filter(|n| n.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(n))
+                .map(|n| n as u32)
+        };
+
+        Measurement {
+            standards_number: count(STANDARDS_NUMBER),
+            standards_interval_count: count(INTERVAL_COUNT),
+            standards_interval_time: number(INTERVAL_TIME),
+            standards_decay_percent: number(DECAY),
+            scan_method: text(METHOD),
+            scan_width: number(SCAN_WIDTH),
+            scan_time: number(SCAN_TIME),
+            details: text(DETAILS),
+            device: text(DEVICE),
+            device_type: text(DEVICE_TYPE),
+            detector: text(DETECTOR),
+            detector_type: text(DETECTOR_TYPE),
+            monochromator: text(MONOCHROMATOR),
+            reflections_measured: count(REFLECTIONS),
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Memory usage of parsed documents.
+//!
+//! [`CifDocument::memory_footprint`] estimates how many bytes a document
+//! occupies, broken down per block and per loop, for callers that keep many
+//! documents in memory. Heap sizes are taken from container capacities, not
+//! lengths, so over-allocation is included. Hash map sizes are estimated
+//! from the layout of the standard library's table (buckets of key and
+//! value plus one control byte each); allocator overhead is not counted.
+//!
+//! The AST owns all of its data: strings are not interned or shared between
+//! values, so every byte is counted exactly once. The exception is
+//! [`CifValue::LargeText`], which clones share; it is counted in full
+//! wherever it appears. Documents held through
+//! [`SharedBlock`](crate::SharedBlock) share one document and should be
+//! counted once.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let doc = Document::parse("data_a\n_title x\nloop_\n_n\n1\n2\n3\n").unwrap();
+//! let report = doc.memory_footprint();
+//!
+//! assert_eq!(report.blocks[0].name, "a");
+//! assert_eq!(report.largest_loops[0].rows, 3);
+//! assert!(report.total_bytes > report.blocks[0].bytes);
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue};
+use crate::error::CifWarning;
+use crate::parser::DecimalComma;
+use indexmap::IndexMap;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::size_of;
+
+/// How many loops [`MemoryReport::largest_loops`] lists
+const LARGEST_LOOPS: usize = 10;
+
+/// Memory used by a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryReport {
+    /// Everything: the document itself, its blocks, warnings, preamble and trailer,
+    /// decimal-comma records, and source
+    pub total_bytes: usize,
+    /// Per-block breakdown, in document order
+    pub blocks: Vec<BlockMemory>,
+    /// The largest loops in the document (at most ten), largest first
+    pub largest_loops: Vec<LoopMemory>,
+}
+
+/// Memory used by a data block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMemory {
+    /// Block name
+    pub name: String,
+    /// Everything in the block, including its frames
+    pub bytes: usize,
+    /// Data items (the map, tags, and values)
+    pub items_bytes: usize,
+    /// The block's own loops, in block order
+    pub loops: Vec<LoopMemory>,
+    /// Save frames, including their items and loops
+    pub frames_bytes: usize,
+}
+
+/// Memory used by a loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopMemory {
+    /// Name of the block containing the loop
+    pub block: String,
+    /// Name of the save frame containing the loop, if any
+    pub frame: Option<String>,
+    /// First tag, to identify the loop
+    pub first_tag: String,
+    /// Number of rows
+    pub rows: usize,
+    /// Number of columns
+    pub columns: usize,
+    /// Tags and values, including the row storage
+    pub bytes: usize,
+}
+
+impl CifDocument {
+    /// Estimate the memory used by this document, per block and per loop.
+    ///
+    /// See the [module documentation](crate::memory) for what is counted.
+    pub fn memory_footprint(&self) -> MemoryReport {
+        let blocks: Vec<BlockMemory> = self.blocks.iter().map(|b| b.memory_footprint()).collect();
+
+        let mut total_bytes = size_of::<CifDocument>()
+            + self.blocks.capacity() * size_of::<CifBlock>()
+            + blocks.iter().map(|b| b.bytes).sum::<usize>()
+            + self.warnings.capacity() * size_of::<CifWarning>();
+        for warning in &self.warnings {
+            total_bytes += warning.message.capacity();
+            total_bytes += warning.path.as_ref().map_or(0, |p| p.capacity());
+        }
+        for edge in [&self.preamble, &self.trailer].into_iter().flatten() {
+            total_bytes += edge.capacity();
+        }
+        total_bytes += self.decimal_commas.capacity() * size_of::<DecimalComma>();
+        for record in &self.decimal_commas {
+            total_bytes += record.block.capacity() + record.tag.capacity();
+            total_bytes += record.original.capacity();
+            total_bytes += record.frame.as_ref().map_or(0, |f| f.capacity());
+        }
+        if let Some(source) = &self.source {
+            total_bytes += source.sha256.capacity();
+            total_bytes += source.path.as_ref().map_or(0, |p| p.capacity());
+        }
+
+        let mut largest_loops: Vec<LoopMemory> = blocks
+            .iter()
+            .flat_map(|b| b.loops.iter().cloned())
+            .chain(self.blocks.iter().flat_map(|block| {
+                block.frames.iter().flat_map(move |frame| {
+                    frame
+                        .loops
+                        .iter()
+                        .map(move |l| l.memory_footprint(&block.name, Some(&frame.name)))
+                })
+            }))
+            .collect();
+        largest_loops.sort_by_key(|l| Reverse(l.bytes));
+        largest_loops.truncate(LARGEST_LOOPS);
+
+        MemoryReport {
+            total_bytes,
+            blocks,
+            largest_loops,
+        }
+    }
+}
+
+impl CifBlock {
+    /// Estimate the heap memory owned by this block.
+    ///
+    /// The block's own inline size is counted by whatever holds it; see
+    /// [`CifDocument::memory_footprint`].
+    pub fn memory_footprint(&self) -> BlockMemory {
+        let items_bytes = items_bytes(&self.items);
+        let loops: Vec<LoopMemory> = self
+            .loops
+            .iter()
+            .map(|l| l.memory_footprint(&self.name, None))
+            .collect();
+        let frames_bytes = self.frames.capacity() * size_of::<CifFrame>()
+            + self.frames.iter().map(frame_bytes).sum::<usize>();
+        let bytes = self.name.capacity()
+            + items_bytes
+            + self.loops.capacity() * size_of::<CifLoop>()
+            + loops.iter().map(|l| l.bytes).sum::<usize>()
+            + frames_bytes;
+        BlockMemory {
+            name: self.name.clone(),
+            bytes,
+            items_bytes,
+            loops,
+            frames_bytes,
+        }
+    }
+}
+
+impl CifLoop {
+    fn memory_footprint(&self, block: &str, frame: Option<&str>) -> LoopMemory {
+        LoopMemory {
+            block: block.to_string(),
+            frame: frame.map(str::to_string),
+            first_tag: self.tags.first().cloned().unwrap_or_default(),
+            rows: self.len(),
+            columns: self.tags.len(),
+            bytes: loop_bytes(self),
+        }
+    }
+}
+
+/// Heap bytes of a frame's name, items, and loops
+pub(crate) fn frame_bytes(frame: &CifFrame) -> usize {
+    frame.name.capacity()
+        + items_bytes(&frame.items)
+        + frame.loops.capacity() * size_of::<CifLoop>()
+        + frame.loops.iter().map(loop_bytes).sum::<usize>()
+}
+
+/// Heap bytes of a loop's tags and rows
+pub(crate) fn loop_bytes(loop_: &CifLoop) -> usize {
+    let tags = loop_.tags.capacity() * size_of::<String>()
+        + loop_.tags.iter().map(String::capacity).sum::<usize>();
+    let rows = loop_.values.capacity() * size_of::<Vec<CifValue>>()
+        + loop_
+            .values
+            .iter()
+            .map(|row| {
+                row.capacity() * size_of::<CifValue>() + row.iter().map(value_bytes).sum::<usize>()
+            })
+            .sum::<usize>();
+    tags + rows
+}
+
+/// Heap bytes of a block's or frame's items, their tags, and their values
+pub(crate) fn items_bytes(items: &IndexMap<String, CifValue>) -> usize {
+    index_map_bytes(items) + entries_bytes(items)
+}
+
+/// Heap bytes of the tags and values of a map
+fn entries_bytes<'a>(entries: impl IntoIterator<Item = (&'a String, &'a CifValue)>) -> usize {
+    entries
+        .into_iter()
+        .map(|(tag, value)| tag.capacity() + value_bytes(value))
+        .sum()
+}
+
+/// Heap bytes owned by a value (not counting the value itself)
+fn value_bytes(value: &CifValue) -> usize {
+    match value {
+        CifValue::Text(s) => s.capacity(),
+        // The text and the two reference counts
+        CifValue::LargeText(s) => s.len() + 2 * size_of::<usize>(),
+        CifValue::List(values) => {
+            values.capacity() * size_of::<CifValue>()
+                + values.iter().map(value_bytes).sum::<usize>()
+        }
+        CifValue::Table(map) => map_bytes(map) + entries_bytes(map),
+        CifValue::Numeric(_)
+        | CifValue::NumericSu { .. }
+        | CifValue::Unknown
+        | CifValue::NotApplicable => 0,
+    }
+}
+
+/// Estimated size of a hash table's allocation: one key-value slot and one
+/// control byte per bucket, plus a trailing group of control bytes
+fn map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    table_bytes(map.capacity(), size_of::<(K, V)>())
+}
+
+/// Estimated size of an ordered map's allocations: a vector of entries,
+/// each with its hash, and a hash table of indices into it
+fn index_map_bytes<K, V>(map: &IndexMap<K, V>) -> usize {
+    let capacity = map.capacity();
+    capacity * size_of::<(usize, K, V)>() + table_bytes(capacity, size_of::<usize>())
+}
+
+/// Estimated size of a hash table with room for `capacity` slots of `slot`
+/// bytes each
+fn table_bytes(capacity: usize, slot: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    // Tables of 8 or more buckets are kept at most 7/8 full
+    let buckets = if capacity < 4 {
+        4
+    } else if capacity < 8 {
+        8
+    } else {
+        (capacity * 8 / 7).next_power_of_two()
+    };
+    buckets * (slot + 1) + 16
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total: {}", human_bytes(self.total_bytes))?;
+        for block in &self.blocks {
+            writeln!(
+                f,
+                "data_{}: {} (items {}, {} loops {}, frames {})",
+                block.name,
+                human_bytes(block.bytes),
+                human_bytes(block.items_bytes),
+                block.loops.len(),
+                human_bytes(block.loops.iter().map(|l| l.bytes).sum()),
+                human_bytes(block.frames_bytes)
+            )?;
+        }
+        if !self.largest_loops.is_empty() {
+            writeln!(f, "Largest loops:")?;
+        }
+        for loop_ in &self.largest_loops {
+            let location = match &loop_.frame {
+                Some(frame) => format!("data_{}/save_{}", loop_.block, frame),
+                None => format!("data_{}", loop_.block),
+            };
+            writeln!(
+                f,
+                "  {:>10}  {} {} ({} rows x {} columns)",
+                human_bytes(loop_.bytes),
+                location,
+                loop_.first_tag,
+                loop_.rows,
+                loop_.columns
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Format a byte count with a binary unit, e.g. `1.5 KiB`
+fn human_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes(512), "512 B");
+        assert_eq!(human_bytes(1536), "1.5 KiB");
+        assert_eq!(human_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_map_bytes_grows_with_capacity() {
+        let mut map: HashMap<String, CifValue> = HashMap::new();
+        assert_eq!(map_bytes(&map), 0);
+        map.insert("_a".into(), CifValue::Unknown);
+        let small = map_bytes(&map);
+        map.reserve(100);
+        assert!(map_bytes(&map) > small * 10);
+    }
+}
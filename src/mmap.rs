@@ -0,0 +1,152 @@
+//! Memory-mapped files, parsed without reading them into memory first.
+//!
+//! A structure factor file from a large data collection can run to several
+//! hundred megabytes. [`CifDocument::from_file`](crate::CifDocument::from_file)
+//! holds the whole file as a `Vec`, then the grammar's parse tree, then the
+//! document. [`CifDocument::from_file_mmap`] instead maps the file and parses
+//! it with [`CifDocumentRef`], so the only copy the process owns is the
+//! finished document. The mapped pages belong to the operating system's file
+//! cache and can be dropped under memory pressure.
+//!
+//! To avoid even the document, keep the [`MappedFile`] and work with the
+//! borrowed document from [`MappedFile::parse`].
+//!
+//! Enabled by the `mmap` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cif_parser::mmap::MappedFile;
+//!
+//! let file = MappedFile::open("r1abcsf.cif").unwrap();
+//! let doc = file.parse().unwrap();
+//! let refln = doc.blocks[0].find_loop("_refln.index_h").unwrap();
+//! println!("{} reflections", refln.len());
+//! ```
+
+use crate::ast::{CifDocument, DocumentSource};
+use crate::borrowed::CifDocumentRef;
+use crate::compression::Compression;
+use crate::corpus::long_path;
+use crate::error::CifError;
+use crate::parser::encoding::decode;
+use crate::parser::options::ParseOptions;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// A CIF file mapped into memory
+#[derive(Debug)]
+pub struct MappedFile {
+    map: Mmap,
+    path: PathBuf,
+}
+
+impl MappedFile {
+    /// Map the file at `path`
+    ///
+    /// The file must not be changed while it is mapped: the contents seen
+    /// through the map would change with it.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CifError> {
+        let path = path.as_ref();
+        let in_file = |e: std::io::Error| CifError::from(e).with_path(path);
+        let file = File::open(long_path(path)).map_err(in_file)?;
+        // SAFETY: the map is read-only and private to this value; a file
+        // truncated by another process while mapped is documented above as
+        // the caller's to avoid, as for any reader of a file being written.
+        let map = unsafe { Mmap::map(&file) }.map_err(in_file)?;
+        Ok(MappedFile {
+            map,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// The path the file was opened with
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file's bytes, as they are on disk
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// The file's text, which must be UTF-8
+    ///
+    /// The bytes are checked but not copied. UTF-16 files are rejected, as
+    /// [`CifDocument::from_bytes`] rejects them.
+    pub fn as_str(&self) -> Result<&str, CifError> {
+        match decode(&self.map, &ParseOptions::default()) {
+            Ok((Cow::Borrowed(text), _)) => Ok(text),
+            Ok((Cow::Owned(_), _)) => unreachable!("strict decoding never transcodes"),
+            Err(error) => Err(error.with_path(&self.path)),
+        }
+    }
+
+    /// Parse the file, borrowing from the map
+    ///
+    /// Compressed files cannot be parsed in place; use
+    /// [`CifDocument::from_file_mmap`], which decompresses them first.
+    pub fn parse(&self) -> Result<CifDocumentRef<'_>, CifError> {
+        if let Some(format) = Compression::from_magic(&self.map) {
+            return Err(CifError::invalid_structure(format!(
+                "file is compressed with {}; it cannot be parsed in place",
+                format.feature()
+            ))
+            .with_path(&self.path));
+        }
+        CifDocumentRef::parse(self.as_str()?).map_err(|e| e.with_path(&self.path))
+    }
+}
+
+impl CifDocument {
+    /// Parse a CIF document from a memory-mapped file
+    ///
+    /// The file is parsed with [`CifDocumentRef`] straight from the map and
+    /// then copied into a document, which avoids holding the file, the
+    /// grammar's parse tree, and the document in memory at once. As for
+    /// [`CifDocumentRef::parse`], parsing is strict. The document's
+    /// [`source`](Self::source) is set as by [`from_file`](Self::from_file).
+    ///
+    /// A gzip, bzip2, or Zstandard file is decompressed into memory first,
+    /// if its feature is enabled. With the `bcif` feature, a `.bcif` file is
+    /// decoded as BinaryCIF.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::from_file_mmap("r1abcsf.cif").unwrap();
+    /// println!("{} blocks", doc.blocks.len());
+    /// ```
+    pub fn from_file_mmap<P: AsRef<Path>>(path: P) -> Result<Self, CifError> {
+        let path = path.as_ref();
+        let file = MappedFile::open(path)?;
+        let content = file.as_bytes();
+        let mut doc = match Compression::detect(path, content) {
+            Some(format) => {
+                let decompressed = format.decompress(content).map_err(|e| e.with_path(path))?;
+                Self::from_mapped_content(&decompressed, path)?
+            }
+            None => Self::from_mapped_content(content, path)?,
+        };
+        doc.set_source(DocumentSource::new(content).with_path(path));
+        Ok(doc)
+    }
+
+    /// Parse uncompressed file content as borrowed CIF, or as BinaryCIF if
+    /// the file's name says so
+    fn from_mapped_content(content: &[u8], path: &Path) -> Result<Self, CifError> {
+        #[cfg(feature = "bcif")]
+        if crate::bcif::is_bcif_path(&crate::compression::uncompressed_path(path)) {
+            return Self::from_bcif(content).map_err(|e| e.with_path(path));
+        }
+        let text = match decode(content, &ParseOptions::default()) {
+            Ok((text, _)) => text,
+            Err(error) => return Err(error.with_path(path)),
+        };
+        let doc = CifDocumentRef::parse(&text).map_err(|e| e.with_path(path))?;
+        Ok(doc.into_owned())
+    }
+}
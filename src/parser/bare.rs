@@ -0,0 +1,81 @@
+//! Save frames written before any data block.
+//!
+//! DDLm dictionary fragments are sometimes shipped as bare save frames, with
+//! no `data_` heading to hold them. When such frames are allowed, a heading
+//! for a synthetic block is inserted just before the first frame, on the same
+//! line so that line numbers are unchanged, and the document is marked with
+//! [`CifDocument::bare_frames`](crate::CifDocument::bare_frames) so that the
+//! writer leaves the heading out again.
+
+use crate::error::CifError;
+use crate::lexer::{TokenKind, Tokenizer};
+use crate::parser::options::ParseContext;
+
+/// Name of the synthetic block holding bare save frames, unless the document
+/// is read from a file, whose name is used instead
+pub const BARE_FRAMES_BLOCK: &str = "_bare_frames_";
+
+/// Wrap save frames that come before any data block in a synthetic block.
+///
+/// Returns the input with the heading inserted, or `None` if the input does
+/// not start with a save frame. Without [`lenient`](crate::ParseOptions::lenient)
+/// or [`allow_bare_frames`](crate::ParseOptions::allow_bare_frames), a bare
+/// frame is an error at its heading.
+pub(crate) fn wrap(input: &str, ctx: &mut ParseContext) -> Result<Option<String>, CifError> {
+    let first = Tokenizer::new(input)
+        .map_while(Result::ok)
+        .find(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment));
+    let Some(frame) = first.filter(|token| token.kind == TokenKind::SaveHeader) else {
+        return Ok(None);
+    };
+    if !ctx.options.lenient && !ctx.options.allow_bare_frames {
+        return Err(CifError::invalid_structure(
+            "Save frame before the first data block (allowed by ParseOptions::allow_bare_frames)",
+        )
+        .at_location(frame.line, frame.col));
+    }
+    ctx.warn(
+        "Save frames before the first data block; kept in a synthetic block",
+        (frame.line, frame.col),
+    );
+    let at = frame.byte_range.start;
+    Ok(Some(format!(
+        "{}data_{BARE_FRAMES_BLOCK} {}",
+        &input[..at],
+        &input[at..]
+    )))
+}
+
+/// A block name made from a file name, with whitespace, which names cannot
+/// hold, replaced by underscores
+pub(crate) fn block_name(file_stem: &str) -> String {
+    file_stem
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::options::ParseOptions;
+    use crate::CifVersion;
+
+    #[test]
+    fn test_heading_goes_before_the_first_frame() {
+        let options = ParseOptions::new().allow_bare_frames(true);
+        let mut ctx = ParseContext::new(CifVersion::V1_1, &options);
+        let wrapped = wrap("# fragment\n  save_a\n_x 1\nsave_\n", &mut ctx).unwrap();
+        assert_eq!(
+            wrapped.as_deref(),
+            Some("# fragment\n  data__bare_frames_ save_a\n_x 1\nsave_\n")
+        );
+        assert_eq!(ctx.warnings[0].location, Some((2, 3)));
+
+        assert_eq!(wrap("data_a\nsave_b\nsave_\n", &mut ctx).unwrap(), None);
+
+        let strict = ParseOptions::new();
+        let mut ctx = ParseContext::new(CifVersion::V1_1, &strict);
+        assert!(wrap("save_a\nsave_\n", &mut ctx).is_err());
+    }
+}
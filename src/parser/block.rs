@@ -1,18 +1,39 @@
 //! Data block and save frame parsing logic.
 
-use crate::ast::{CifBlock, CifFrame, CifValue, CifVersion};
+use crate::ast::{CifBlock, CifFrame, CifLoop, CifValue, CifVersion};
 use crate::builder::BlockBuilder;
+use crate::conformance::{self, ConformanceRule};
 use crate::error::CifError;
-use crate::parser::helpers::{extract_location, extract_text};
+use crate::parser::decimal_comma;
+use crate::parser::helpers::{extract_location, extract_text, token_at};
 use crate::parser::loop_parser::parse_loop;
+use crate::parser::options::{DuplicateTags, ParseContext};
 use crate::Rule;
+use indexmap::IndexMap;
 use pest::iterators::Pair;
+use std::collections::HashSet;
+use std::iter::Peekable;
 
 /// Parse a data block from the parse tree
-pub(crate) fn parse_datablock(pair: Pair<Rule>, version: CifVersion) -> Result<CifBlock, CifError> {
+pub(crate) fn parse_datablock(
+    pair: Pair<Rule>,
+    ctx: &mut ParseContext,
+) -> Result<CifBlock, CifError> {
+    let version = ctx.version;
+    let first_decimal_comma = ctx.decimal_commas.len();
     let mut builder = BlockBuilder::new(String::new());
+    let mut heading = "";
+    let mut has_content = false;
+    let mut collapsed = HashSet::new();
 
-    for inner_pair in pair.into_inner() {
+    let mut pairs = pair.into_inner().peekable();
+    while let Some(inner_pair) = pairs.next() {
+        if matches!(
+            inner_pair.as_rule(),
+            Rule::dataitem | Rule::loop_block | Rule::frame
+        ) {
+            has_content = true;
+        }
         match inner_pair.as_rule() {
             Rule::datablockheading => {
                 let location = extract_location(&inner_pair);
@@ -28,33 +49,50 @@ pub(crate) fn parse_datablock(pair: Pair<Rule>, version: CifVersion) -> Result<C
                     ).at_location(location.0, location.1));
                 }
 
+                if ctx.strict_11() {
+                    if let Some(error) = conformance::name_error(&name, false) {
+                        return Err(error.at_location(location.0, location.1));
+                    }
+                }
+
                 builder.block_mut().name = name;
+                heading = inner_pair.as_str();
             }
             Rule::dataitem => {
-                let (tag, value) = parse_dataitem(inner_pair, version)?;
-                builder.add_item(tag, value);
+                let location = extract_location(&inner_pair);
+                let (tag, value) = parse_item_line(inner_pair, &mut pairs, ctx)?;
+                builder.add_item(|items, loops| {
+                    insert_item(items, loops, &mut collapsed, tag, value, location, ctx)
+                })?;
             }
             Rule::loop_block => {
-                let loop_ = parse_loop(inner_pair, version)?;
+                let loop_ = parse_loop(inner_pair, ctx)?;
                 builder.start_loop(loop_);
             }
             Rule::frame => {
-                let frame = parse_frame(inner_pair, version)?;
+                let frame = parse_frame(inner_pair, ctx)?;
                 builder.add_frame(frame);
             }
+            Rule::stray_values => {
+                return Err(stray_values_error(&inner_pair, heading, has_content));
+            }
             _rule => {
                 // Unknown rule - safely ignored
             }
         }
     }
 
-    Ok(builder.finish())
+    let block = builder.finish();
+    for record in &mut ctx.decimal_commas[first_decimal_comma..] {
+        record.block.clone_from(&block.name);
+    }
+    Ok(block)
 }
 
 /// Parse a data item (tag-value pair) from the parse tree
 pub(crate) fn parse_dataitem(
     pair: Pair<Rule>,
-    version: CifVersion,
+    ctx: &mut ParseContext,
 ) -> Result<(String, CifValue), CifError> {
     let item_location = extract_location(&pair);
     let inner: Vec<_> = pair.into_inner().collect();
@@ -74,8 +112,28 @@ pub(crate) fn parse_dataitem(
         .find(|p| p.as_rule() == Rule::item_value || p.as_rule() == Rule::value);
 
     let tag = extract_text(tag_pair);
+    if ctx.strict_11() {
+        if let Some(error) = conformance::tag_error(&tag) {
+            let (line, col) = extract_location(tag_pair);
+            return Err(error.at_location(line, col));
+        }
+    }
+
+    // An unquoted reserved word stands where the value should be
+    if let Some(reserved) = inner.iter().find(|p| p.as_rule() == Rule::reserved_value) {
+        let (line, col) = extract_location(reserved);
+        let word = token_at(reserved.get_input(), reserved.as_span().start());
+        return Err(CifError::invalid_structure(format!(
+            "Data item '{tag}' has no value: '{word}' is a reserved word, \
+             which must be quoted if it is the value"
+        ))
+        .at_location(line, col));
+    }
+
     let value = if let Some(vp) = value_pair {
-        crate::parser::value::parse_value(vp.clone(), version)?
+        let mut value = crate::parser::value::parse_value_in(vp.clone(), ctx)?;
+        decimal_comma::recover(&tag, None, &mut value, vp, ctx);
+        value
     } else {
         CifValue::Unknown
     };
@@ -83,8 +141,142 @@ pub(crate) fn parse_dataitem(
     Ok((tag, value))
 }
 
+/// Parse a data item, joining in lenient mode an unquoted value that was
+/// split by spaces.
+///
+/// `_name Sodium chloride` reads as the item `Sodium` followed by a stray
+/// value. When every stray value is on the same line as the item's unquoted
+/// value, lenient mode takes the rest of the line as the value instead, as
+/// if it had been quoted, and consumes the stray values.
+fn parse_item_line<'i>(
+    item: Pair<'i, Rule>,
+    pairs: &mut Peekable<impl Iterator<Item = Pair<'i, Rule>>>,
+    ctx: &mut ParseContext,
+) -> Result<(String, CifValue), CifError> {
+    let (tag, value) = parse_dataitem(item.clone(), ctx)?;
+    if !ctx.options.lenient {
+        return Ok((tag, value));
+    }
+    let Some(joined) = pairs
+        .peek()
+        .filter(|next| next.as_rule() == Rule::stray_values)
+        .and_then(|stray| join_unquoted(&item, stray))
+    else {
+        return Ok((tag, value));
+    };
+    if let Some(stray) = pairs.next() {
+        ctx.warn(
+            format!("Value of '{tag}' contains spaces but is not quoted; read as '{joined}'"),
+            extract_location(&stray),
+        );
+    }
+    Ok((tag, CifValue::Text(joined)))
+}
+
+/// The text from an item's unquoted value to the last of the stray values
+/// after it, if they are all on the value's line.
+fn join_unquoted(item: &Pair<Rule>, stray: &Pair<Rule>) -> Option<String> {
+    let value = item
+        .clone()
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::item_value || p.as_rule() == Rule::value)?;
+    if value.as_str().starts_with(['\'', '"', '[', '{', ';']) {
+        return None;
+    }
+    let line = value.line_col().0;
+    let mut end = None;
+    for part in stray.clone().into_inner().flatten() {
+        if part.as_rule() == Rule::loop_value {
+            if part.line_col().0 != line {
+                return None;
+            }
+            end = Some(part.as_span().end());
+        }
+    }
+    Some(value.get_input()[value.as_span().start()..end?].to_string())
+}
+
+/// Add a data item to a block's or frame's items, applying the
+/// [`DuplicateTags`] policy if the tag is already there.
+///
+/// Collapsed values go into a one-column loop, created where the second
+/// occurrence appears; later occurrences are appended to it. `collapsed`
+/// holds the tags of the container collapsed so far, so that an item is
+/// never added to a loop the file wrote.
+fn insert_item(
+    items: &mut IndexMap<String, CifValue>,
+    loops: &mut Vec<CifLoop>,
+    collapsed: &mut HashSet<String>,
+    tag: String,
+    value: CifValue,
+    location: (usize, usize),
+    ctx: &mut ParseContext,
+) -> Result<(), CifError> {
+    let policy = ctx.options.duplicate_tags;
+    if policy == DuplicateTags::Collapse {
+        if collapsed.contains(&tag) {
+            let loop_ = loops
+                .iter_mut()
+                .rfind(|loop_| loop_.tags.len() == 1 && loop_.tags[0] == tag)
+                .expect("a collapsed tag has its loop");
+            loop_.values.push(vec![value]);
+            return Ok(());
+        }
+        if loops.iter().any(|loop_| loop_.tags.contains(&tag)) {
+            ctx.warn(
+                format!(
+                    "Tag '{tag}' is already a loop column; the item is kept apart from the loop"
+                ),
+                location,
+            );
+        }
+    }
+    match items.shift_remove_entry(&tag) {
+        None => {
+            items.insert(tag, value);
+        }
+        Some((tag, _)) if ctx.strict_11() => {
+            return Err(CifError::non_conformant(
+                ConformanceRule::DuplicateTag,
+                format!("Data name '{tag}' appears twice"),
+            )
+            .at_location(location.0, location.1));
+        }
+        Some((tag, first)) => match policy {
+            DuplicateTags::Last => {
+                if ctx.options.lenient {
+                    ctx.warn(
+                        format!("Repeated tag '{tag}'; the last value is kept"),
+                        location,
+                    );
+                }
+                items.insert(tag, value);
+            }
+            DuplicateTags::Error => {
+                return Err(CifError::DuplicateTag {
+                    tag,
+                    location: Some(location),
+                    span: None,
+                });
+            }
+            DuplicateTags::Collapse => {
+                ctx.warn(
+                    format!("Repeated tag '{tag}' collapsed into a one-column loop"),
+                    location,
+                );
+                let mut loop_ = CifLoop::new(vec![tag.clone()]);
+                loop_.values = vec![vec![first], vec![value]];
+                loops.push(loop_);
+                collapsed.insert(tag);
+            }
+        },
+    }
+    Ok(())
+}
+
 /// Parse a save frame from the parse tree
-pub(crate) fn parse_frame(pair: Pair<Rule>, version: CifVersion) -> Result<CifFrame, CifError> {
+pub(crate) fn parse_frame(pair: Pair<Rule>, ctx: &mut ParseContext) -> Result<CifFrame, CifError> {
+    let version = ctx.version;
     let frame_location = extract_location(&pair);
     let inner: Vec<_> = pair.into_inner().collect();
 
@@ -108,6 +300,15 @@ pub(crate) fn parse_frame(pair: Pair<Rule>, version: CifVersion) -> Result<CifFr
         })?;
 
     let frame_name = extract_text(&framename_pair);
+    let heading = save_heading_pair.as_str();
+
+    if !inner.iter().any(|p| p.as_rule() == Rule::endframe) {
+        return Err(CifError::invalid_structure(format!(
+            "Save frame '{frame_name}' is not closed by 'save_'; \
+             if '{heading}' is a value, it must be quoted"
+        ))
+        .at_location(frame_location.0, frame_location.1));
+    }
 
     // CIF 2.0 requires non-empty container names (CIF 1.1 allowed empty names)
     if version == CifVersion::V2_0 && frame_name.is_empty() {
@@ -117,31 +318,77 @@ pub(crate) fn parse_frame(pair: Pair<Rule>, version: CifVersion) -> Result<CifFr
         );
     }
 
+    if ctx.strict_11() {
+        if let Some(error) = conformance::name_error(&frame_name, true) {
+            return Err(error.at_location(frame_location.0, frame_location.1));
+        }
+    }
+
     let mut frame = CifFrame::new(frame_name);
+    let mut has_content = false;
+    let mut collapsed = HashSet::new();
+    let first_decimal_comma = ctx.decimal_commas.len();
 
     // Process remaining elements
-    for inner_pair in inner {
+    let mut pairs = inner.into_iter().peekable();
+    while let Some(inner_pair) = pairs.next() {
+        if matches!(inner_pair.as_rule(), Rule::dataitem | Rule::loop_block) {
+            has_content = true;
+        }
         match inner_pair.as_rule() {
             Rule::save_heading => {
                 // Already processed
             }
             Rule::dataitem => {
-                let (tag, value) = parse_dataitem(inner_pair, version)?;
-                frame.items.insert(tag, value);
+                let location = extract_location(&inner_pair);
+                let (tag, value) = parse_item_line(inner_pair, &mut pairs, ctx)?;
+                insert_item(
+                    &mut frame.items,
+                    &mut frame.loops,
+                    &mut collapsed,
+                    tag,
+                    value,
+                    location,
+                    ctx,
+                )?;
             }
             Rule::loop_block => {
-                let loop_ = parse_loop(inner_pair, version)?;
+                let loop_ = parse_loop(inner_pair, ctx)?;
                 frame.loops.push(loop_);
             }
+            Rule::stray_values => {
+                return Err(stray_values_error(&inner_pair, heading, has_content));
+            }
             _rule => {
                 // Unknown rule - safely ignored
             }
         }
     }
 
+    for record in &mut ctx.decimal_commas[first_decimal_comma..] {
+        record.frame = Some(frame.name.clone());
+    }
     Ok(frame)
 }
 
+/// Error for values found outside any loop.
+///
+/// Values straight after a heading usually mean the heading itself was an
+/// unquoted value (e.g. `data_x` in a loop row) that ended the loop early.
+fn stray_values_error(pair: &Pair<Rule>, heading: &str, has_content: bool) -> CifError {
+    let (line, col) = extract_location(pair);
+    let first = token_at(pair.get_input(), pair.as_span().start());
+    let message = if has_content {
+        format!("Value '{first}' is outside any loop and has no data name")
+    } else {
+        format!(
+            "Value '{first}' is outside any loop and has no data name; \
+             if '{heading}' before it is a value, it must be quoted"
+        )
+    };
+    CifError::invalid_structure(message).at_location(line, col)
+}
+
 /// Extract block name from a data block heading with case-insensitive parsing.
 ///
 /// # CIF Block Naming Rules
@@ -157,10 +404,14 @@ pub(crate) fn parse_frame(pair: Pair<Rule>, version: CifVersion) -> Result<CifFr
 /// - `DATA_MyProtein` → `"MyProtein"`
 /// - `data_MyProtein` → `"MyProtein"`
 fn extract_block_name(heading_str: &str) -> String {
-    let lower = heading_str.to_lowercase();
-    if lower.starts_with("data_") {
+    let has_prefix = |prefix: &str| {
+        heading_str
+            .get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+    };
+    if has_prefix("data_") {
         heading_str[5..].to_string()
-    } else if lower == "global_" {
+    } else if heading_str.eq_ignore_ascii_case("global_") {
         String::new() // Global block has no name
     } else {
         heading_str.to_string()
@@ -0,0 +1,239 @@
+//! Recovery of numbers written with a decimal comma.
+//!
+//! Software running under some European locales writes `1,5406` where CIF
+//! expects `1.5406`. Such tokens are valid CIF text, so strict parsing keeps
+//! them as [`CifValue::Text`] and numeric extraction then finds nothing.
+//!
+//! In lenient mode, a token that reads as a number once its one comma is a
+//! period, in the value of a tag known to hold numbers (cell parameters,
+//! coordinates, displacement parameters, and the like), is converted to the
+//! value the period form would give, with a warning. Each conversion is
+//! recorded as a [`DecimalComma`] on
+//! [`CifDocument::decimal_commas`](crate::CifDocument::decimal_commas) so the
+//! original tokens can be put back with
+//! [`CifDocument::restore_decimal_commas`](crate::CifDocument::restore_decimal_commas).
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{Document, ParseOptions};
+//!
+//! let cif = "data_x\n_cell_length_a 5,4307(2)\n_diffrn_radiation_wavelength 1,5406\n";
+//!
+//! let strict = Document::parse(cif).unwrap();
+//! assert_eq!(strict.blocks[0].get_item("_diffrn_radiation_wavelength").unwrap().as_string(), Some("1,5406"));
+//!
+//! let doc = Document::parse_with_options(cif, &ParseOptions::new().lenient(true)).unwrap();
+//! let block = &doc.blocks[0];
+//! assert_eq!(block.get_item("_diffrn_radiation_wavelength").unwrap().as_numeric(), Some(1.5406));
+//! assert_eq!(block.get_item("_cell_length_a").unwrap().as_numeric_with_su(), Some((5.4307, Some(0.0002))));
+//! assert_eq!(doc.warnings.len(), 2);
+//! assert_eq!(doc.decimal_commas[0].original, "5,4307(2)");
+//! ```
+
+use crate::ast::{CifDocument, CifValue};
+use crate::parser::helpers::extract_location;
+use crate::parser::options::ParseContext;
+use crate::Rule;
+use pest::iterators::Pair;
+
+/// Beginnings of the tags whose values are numbers, lowercase, with mmCIF's
+/// `.` written as `_` so one entry covers both forms
+const NUMERIC_TAGS: &[&str] = &[
+    "_cell_length_",
+    "_cell_angle_",
+    "_cell_volume",
+    "_cell_measurement_temperature",
+    "_cell_measurement_theta_",
+    "_atom_site_fract_",
+    "_atom_site_cartn_",
+    "_atom_site_u_iso_or_equiv",
+    "_atom_site_b_iso_or_equiv",
+    "_atom_site_occupancy",
+    "_atom_site_aniso_u_",
+    "_atom_site_aniso_b_",
+    "_atom_site_anisotrop_u",
+    "_atom_site_anisotrop_b",
+    "_diffrn_radiation_wavelength",
+    "_diffrn_ambient_temperature",
+    "_diffrn_reflns_theta_",
+    "_exptl_crystal_density_",
+    "_exptl_crystal_size_",
+    "_exptl_crystal_f_000",
+    "_exptl_absorpt_coefficient_mu",
+    "_exptl_absorpt_correction_t_",
+    "_chemical_formula_weight",
+    "_refine_ls_r_factor_",
+    "_refine_ls_wr_factor_",
+    "_refine_ls_goodness_of_fit_",
+    "_refine_ls_shift_su_",
+    "_refine_diff_density_",
+    "_geom_bond_distance",
+    "_geom_angle",
+    "_geom_torsion",
+];
+
+/// A value read from a decimal-comma token in lenient mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecimalComma {
+    /// Name of the data block the value is in
+    pub block: String,
+    /// Name of the save frame the value is in, if any
+    pub frame: Option<String>,
+    /// The value's tag
+    pub tag: String,
+    /// Loop row of the value, or `None` for a data item
+    pub row: Option<usize>,
+    /// The token as it appeared in the input, e.g. `1,5406`
+    pub original: String,
+}
+
+/// Whether values of `tag` are expected to be numbers
+pub fn is_numeric_tag(tag: &str) -> bool {
+    let tag = tag.to_ascii_lowercase().replace('.', "_");
+    NUMERIC_TAGS.iter().any(|prefix| tag.starts_with(prefix))
+}
+
+/// The token with its decimal comma replaced by a period, if it is a number
+/// in that form: digits, one comma, digits, then an optional exponent and su
+fn with_period(token: &str) -> Option<String> {
+    let unsigned = token.strip_prefix(['+', '-']).unwrap_or(token);
+    let (whole, rest) = unsigned.split_once(',')?;
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(whole) {
+        return None;
+    }
+    let (number, su) = match rest.split_once('(') {
+        Some((number, su)) => (number, Some(su.strip_suffix(')')?)),
+        None => (rest, None),
+    };
+    if !su.is_none_or(digits) {
+        return None;
+    }
+    let (fraction, exponent) = match number.split_once(['e', 'E']) {
+        Some((fraction, exponent)) => (fraction, Some(exponent)),
+        None => (number, None),
+    };
+    let exponent_ok = exponent.is_none_or(|e| digits(e.strip_prefix(['+', '-']).unwrap_or(e)));
+    (digits(fraction) && exponent_ok).then(|| token.replacen(',', ".", 1))
+}
+
+/// Convert `value`, just parsed from `pair` as the value of `tag` (in loop
+/// row `row`, if any), if it is a decimal-comma number where one is expected.
+///
+/// Does nothing outside lenient mode. Conversions are recorded on the
+/// context with an empty block name, which the block parser fills in.
+pub(crate) fn recover(
+    tag: &str,
+    row: Option<usize>,
+    value: &mut CifValue,
+    pair: &Pair<Rule>,
+    ctx: &mut ParseContext,
+) {
+    if !ctx.options.lenient {
+        return;
+    }
+    let CifValue::Text(token) = value else {
+        return;
+    };
+    if !token.contains(',') || !is_numeric_tag(tag) {
+        return;
+    }
+    let Some(period) = with_period(token) else {
+        return;
+    };
+    let original = std::mem::replace(value, CifValue::parse_value(&period));
+    let CifValue::Text(original) = original else {
+        unreachable!("only text values are converted");
+    };
+    ctx.warn(
+        format!("Decimal comma in '{original}' for '{tag}' read as {period}"),
+        extract_location(pair),
+    );
+    ctx.decimal_commas.push(DecimalComma {
+        block: String::new(),
+        frame: None,
+        tag: tag.to_string(),
+        row,
+        original,
+    });
+}
+
+impl CifDocument {
+    /// Put back the original tokens of values converted from decimal-comma
+    /// form (see [`decimal_commas`](Self::decimal_commas)), as text, and
+    /// clear the record.
+    ///
+    /// The document then holds what strict parsing would have produced, so
+    /// writing it reproduces the input's tokens. Values that were changed
+    /// since parsing are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{CifValue, Document, ParseOptions};
+    ///
+    /// let cif = "data_x\n_cell_angle_beta 101,25\n";
+    /// let mut doc = Document::parse_with_options(cif, &ParseOptions::new().lenient(true)).unwrap();
+    /// assert_eq!(doc.blocks[0].get_item("_cell_angle_beta"), Some(&CifValue::Numeric(101.25)));
+    ///
+    /// doc.restore_decimal_commas();
+    /// assert!(doc.to_string().contains("_cell_angle_beta 101,25\n"));
+    /// ```
+    pub fn restore_decimal_commas(&mut self) {
+        for record in std::mem::take(&mut self.decimal_commas) {
+            let Some(period) = with_period(&record.original) else {
+                continue;
+            };
+            let Some(block) = self.blocks.iter_mut().find(|b| b.name == record.block) else {
+                continue;
+            };
+            let (items, loops) = match &record.frame {
+                Some(name) => match block.frames.iter_mut().find(|f| &f.name == name) {
+                    Some(frame) => (&mut frame.items, &mut frame.loops),
+                    None => continue,
+                },
+                None => (&mut block.items, &mut block.loops),
+            };
+            let slot = match record.row {
+                None => items.get_mut(&record.tag),
+                Some(row) => loops.iter_mut().find_map(|loop_| {
+                    let col = loop_.tags.iter().position(|t| *t == record.tag)?;
+                    loop_.values.get_mut(row)?.get_mut(col)
+                }),
+            };
+            if let Some(value) = slot.filter(|v| **v == CifValue::parse_value(&period)) {
+                *value = CifValue::Text(record.original);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_period() {
+        assert_eq!(with_period("1,5406").as_deref(), Some("1.5406"));
+        assert_eq!(with_period("-0,25").as_deref(), Some("-0.25"));
+        assert_eq!(with_period("5,4307(2)").as_deref(), Some("5.4307(2)"));
+        assert_eq!(with_period("1,2e-3").as_deref(), Some("1.2e-3"));
+        assert_eq!(with_period("1,2,3"), None);
+        assert_eq!(with_period(",5"), None);
+        assert_eq!(with_period("1,"), None);
+        assert_eq!(with_period("1,5(2"), None);
+        assert_eq!(with_period("C1,C2"), None);
+    }
+
+    #[test]
+    fn test_numeric_tags() {
+        assert!(is_numeric_tag("_cell_length_a"));
+        assert!(is_numeric_tag("_cell.length_a"));
+        assert!(is_numeric_tag("_atom_site.Cartn_x"));
+        assert!(is_numeric_tag("_atom_site_fract_z"));
+        assert!(!is_numeric_tag("_atom_site_label"));
+        assert!(!is_numeric_tag("_publ_author_name"));
+    }
+}
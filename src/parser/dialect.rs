@@ -0,0 +1,357 @@
+//! Hooks for non-standard constructs in vendor CIF dialects.
+//!
+//! Some instruments write files that are almost CIF: data names without
+//! their leading underscore, or directive lines such as `$table atoms`. A
+//! [`DialectHandler`] set on [`ParseOptions::dialect`] sees every bare word
+//! that appears where a data name, `loop_`, or heading could be, and can
+//! turn it into CIF or leave it to the parser. The handler runs
+//! on the raw input before grammar parsing, in strict and lenient mode alike;
+//! without one, nothing extra is scanned.
+//!
+//! [`UnprefixedTags`] is a ready-made handler, and a reference for writing
+//! others.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::parser::dialect::UnprefixedTags;
+//! use cif_parser::{Document, ParseOptions};
+//!
+//! let cif = "data_x\ncell_length_a 5.431(2)\n_cell_length_b 5.431(2)\n";
+//! assert!(Document::parse(cif).is_err());
+//!
+//! let options = ParseOptions::new().dialect(UnprefixedTags);
+//! let doc = Document::parse_with_options(cif, &options).unwrap();
+//! assert_eq!(doc.blocks[0].items.len(), 2);
+//! assert_eq!(doc.warnings[0].location, Some((2, 1)));
+//! ```
+//!
+//! [`ParseOptions::dialect`]: crate::ParseOptions::dialect
+
+use crate::ast::CifValue;
+use crate::lexer::{Token, TokenKind, Tokenizer};
+use crate::parser::options::ParseContext;
+use crate::writer::item_text;
+use std::fmt;
+
+/// Reads tokens of a CIF dialect that the grammar does not accept.
+///
+/// The `Debug` output should tell handlers apart: it is part of the key
+/// under which [cached](crate::cache) documents are stored.
+pub trait DialectHandler: fmt::Debug + Send + Sync {
+    /// Handle a bare word found where a data name, `loop_`, or block or
+    /// frame heading could be
+    fn unrecognized(&self, statement: &Statement<'_>) -> DialectAction;
+}
+
+/// An unrecognized token and the rest of its line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Statement<'a> {
+    /// The token, such as `cell_length_a` or `$table`
+    pub token: &'a str,
+    /// The input from the start of the token to the end of its line
+    pub text: &'a str,
+    /// 1-indexed line of the token
+    pub line: usize,
+    /// 1-indexed column of the token, counted in characters
+    pub column: usize,
+    /// Whether the token could also start the next row of a loop. Handlers
+    /// should take such tokens only when they cannot be data values.
+    pub in_loop: bool,
+}
+
+/// What a [`DialectHandler`] does with an unrecognized token.
+///
+/// `consumed` counts bytes of [`Statement::text`]. The token itself is
+/// always consumed, and nothing past the end of its line is. Replacements
+/// without line breaks keep the locations of everything after them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialectAction {
+    /// Leave the token to the parser, which rejects it as usual
+    Reject,
+    /// Replace the consumed text with CIF text
+    Replace {
+        consumed: usize,
+        cif: String,
+        /// Recorded on the document at the token's location
+        warning: Option<String>,
+    },
+    /// Replace the consumed text with data items
+    Items {
+        consumed: usize,
+        /// Tags, including the leading underscore, and their values
+        items: Vec<(String, CifValue)>,
+        /// Recorded on the document at the token's location
+        warning: Option<String>,
+    },
+}
+
+/// Reads data names written without their leading underscore, as in
+/// `cell_length_a 5.431`, with a warning for each.
+///
+/// Only words that look like data names are taken: a letter followed by
+/// letters, digits, and punctuation found in names, with at least one `_` or
+/// `.`. Other words, and words that could start a loop row, are rejected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnprefixedTags;
+
+impl DialectHandler for UnprefixedTags {
+    fn unrecognized(&self, statement: &Statement<'_>) -> DialectAction {
+        let name = statement.token;
+        let looks_like_tag = !statement.in_loop
+            && name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.contains(['_', '.'])
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.-/()[]".contains(c));
+        if !looks_like_tag {
+            return DialectAction::Reject;
+        }
+        DialectAction::Replace {
+            consumed: name.len(),
+            cif: format!("_{name}"),
+            warning: Some(format!(
+                "Data name '{name}' has no leading underscore; read as '_{name}'"
+            )),
+        }
+    }
+}
+
+/// Where the tokens seen so far leave the document
+#[derive(Clone, Copy, Default, PartialEq)]
+enum State {
+    /// Before the first heading
+    #[default]
+    Outside,
+    /// Between items; `pending` when a data name still needs its value
+    Items { pending: bool },
+    /// After `loop_`, reading its data names
+    LoopTags,
+    /// Reading loop rows
+    LoopValues,
+}
+
+/// Statement positions in a token sequence
+#[derive(Default)]
+struct Scan {
+    state: State,
+    /// Open `[` and `{`: a list or table is one value
+    depth: usize,
+    /// Data names and values of the current loop
+    loop_tags: usize,
+    loop_values: usize,
+}
+
+impl Scan {
+    /// Whether `token` is a bare word where a statement could start, and if
+    /// so whether a loop row could start there too
+    fn statement(&self, token: &Token) -> Option<bool> {
+        if token.kind != TokenKind::UnquotedValue || self.depth > 0 {
+            return None;
+        }
+        match self.state {
+            State::Items { pending: false } => Some(false),
+            State::LoopTags if self.loop_tags > 0 => Some(true),
+            State::LoopValues if self.loop_values.is_multiple_of(self.loop_tags.max(1)) => {
+                Some(true)
+            }
+            _ => None,
+        }
+    }
+
+    fn advance(&mut self, token: &Token) {
+        let complete = match token.kind {
+            TokenKind::Whitespace | TokenKind::Comment => false,
+            TokenKind::Tag => {
+                if self.state == State::LoopTags {
+                    self.loop_tags += 1;
+                } else {
+                    self.state = State::Items { pending: true };
+                }
+                false
+            }
+            TokenKind::LoopKeyword => {
+                self.loop_tags = 0;
+                self.loop_values = 0;
+                self.state = if token.text.eq_ignore_ascii_case("loop_") {
+                    State::LoopTags
+                } else {
+                    State::Items { pending: false }
+                };
+                false
+            }
+            TokenKind::BlockHeader | TokenKind::SaveHeader | TokenKind::SaveEnd => {
+                self.state = State::Items { pending: false };
+                self.depth = 0;
+                false
+            }
+            TokenKind::Delimiter => match token.text {
+                "[" | "{" => {
+                    self.depth += 1;
+                    false
+                }
+                "]" | "}" => {
+                    self.depth = self.depth.saturating_sub(1);
+                    self.depth == 0
+                }
+                _ => false,
+            },
+            TokenKind::QuotedString | TokenKind::TextField | TokenKind::UnquotedValue => {
+                self.depth == 0
+            }
+        };
+        if complete {
+            self.state = match self.state {
+                State::Outside => State::Outside,
+                State::Items { .. } => State::Items { pending: false },
+                State::LoopTags | State::LoopValues => {
+                    self.loop_values += 1;
+                    State::LoopValues
+                }
+            };
+        }
+    }
+}
+
+/// Run `handler` over the input, giving the rewritten input if it changed
+/// anything.
+///
+/// Input that cannot be tokenized is left as it is from that point on, for
+/// the parser to report.
+pub(crate) fn rewrite(
+    input: &str,
+    handler: &dyn DialectHandler,
+    ctx: &mut ParseContext,
+) -> Option<String> {
+    let mut scan = Scan::default();
+    let mut rewritten: Option<String> = None;
+    // Input before this offset has been copied or replaced
+    let mut copied = 0;
+
+    for token in Tokenizer::new(input).skip_trivia(true) {
+        let Ok(token) = token else { break };
+        let start = token.byte_range.start;
+        if start < copied {
+            continue;
+        }
+        let Some(in_loop) = scan.statement(&token) else {
+            scan.advance(&token);
+            continue;
+        };
+
+        let line_end = input[start..].find('\n').map_or(input.len(), |i| start + i);
+        let statement = Statement {
+            token: token.text,
+            text: &input[start..line_end],
+            line: token.line,
+            column: token.col,
+            in_loop,
+        };
+        let (consumed, cif, warning) = match handler.unrecognized(&statement) {
+            DialectAction::Reject => {
+                scan.advance(&token);
+                continue;
+            }
+            DialectAction::Replace {
+                consumed,
+                cif,
+                warning,
+            } => (consumed, cif, warning),
+            DialectAction::Items {
+                consumed,
+                items,
+                warning,
+            } => {
+                let cif: Vec<String> = items
+                    .iter()
+                    .map(|(tag, value)| item_text(tag, value, ctx.version))
+                    .collect();
+                (consumed, cif.join(" "), warning)
+            }
+        };
+
+        let mut end = (start + consumed).clamp(token.byte_range.end, line_end);
+        while !input.is_char_boundary(end) {
+            end += 1;
+        }
+        if let Some(warning) = warning {
+            ctx.warn(warning, (token.line, token.col));
+        }
+        let out = rewritten.get_or_insert_with(|| String::with_capacity(input.len()));
+        out.push_str(&input[copied..start]);
+        out.push_str(&cif);
+        copied = end;
+        for token in Tokenizer::new(&cif).skip_trivia(true).flatten() {
+            scan.advance(&token);
+        }
+    }
+
+    rewritten.map(|mut out| {
+        out.push_str(&input[copied..]);
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseOptions;
+
+    fn rewritten(input: &str) -> (Option<String>, usize) {
+        let options = ParseOptions::new();
+        let mut ctx = ParseContext::new(crate::CifVersion::V1_1, &options);
+        let out = rewrite(input, &UnprefixedTags, &mut ctx);
+        (out, ctx.warnings.len())
+    }
+
+    #[test]
+    fn test_only_statement_positions_are_offered() {
+        // Values of items and loops, and words before the first heading
+        for input in [
+            "data_a\n_x cell_length_a\n",
+            "data_a\nloop_\n_x\n_y\n1 cell_length_a\n",
+            "banner_text\ndata_a\n_x 1\n",
+        ] {
+            assert_eq!(rewritten(input), (None, 0), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_unprefixed_tags_are_prefixed() {
+        let (out, warnings) = rewritten("data_a\ncell_length_a 5.4\nloop_\n_x\n1\n");
+        assert_eq!(
+            out.as_deref(),
+            Some("data_a\n_cell_length_a 5.4\nloop_\n_x\n1\n")
+        );
+        assert_eq!(warnings, 1);
+
+        // The value after a rewritten tag is not a statement
+        let (out, warnings) = rewritten("data_a\na_b c_d\n");
+        assert_eq!(out.as_deref(), Some("data_a\n_a_b c_d\n"));
+        assert_eq!(warnings, 1);
+
+        assert_eq!(rewritten("data_a\n_x 1\nCHECKSUM 1234\n"), (None, 0));
+    }
+
+    #[test]
+    fn test_loop_row_starts_are_offered() {
+        #[derive(Debug, Default)]
+        struct Record(std::sync::Mutex<Vec<(String, bool)>>);
+        impl DialectHandler for Record {
+            fn unrecognized(&self, statement: &Statement<'_>) -> DialectAction {
+                let seen = (statement.token.to_string(), statement.in_loop);
+                self.0.lock().unwrap().push(seen);
+                DialectAction::Reject
+            }
+        }
+
+        let record = Record::default();
+        let options = ParseOptions::new();
+        let mut ctx = ParseContext::new(crate::CifVersion::V1_1, &options);
+        let input = "data_a\nloop_\n_x\n_y\nO1 1 O2 2\n$end\n_z [a b] c\n";
+        assert_eq!(rewrite(input, &record, &mut ctx), None);
+        let seen = record.0.into_inner().unwrap();
+        let expected = [("O1", true), ("O2", true), ("$end", true), ("c", false)];
+        assert_eq!(seen, expected.map(|(t, l)| (t.to_string(), l)));
+    }
+}
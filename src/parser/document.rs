@@ -1,8 +1,18 @@
 //! Document-level parsing logic (entry point for parsing).
 
 use crate::ast::{CifDocument, CifVersion};
+use crate::conformance::{self, Conformance};
 use crate::error::CifError;
+use crate::parser::bare;
 use crate::parser::block::parse_datablock;
+use crate::parser::dialect;
+use crate::parser::encoding::normalize_line_terminators;
+use crate::parser::options::{ParseContext, ParseOptions};
+#[cfg(feature = "parallel")]
+use crate::parser::parallel;
+use crate::parser::prescan::{check_nesting_depth, close_open_quotes, normalize_unicode_spaces};
+use crate::parser::select;
+use crate::parser::trailer;
 use crate::{CIFParser, Rule};
 use pest::Parser;
 
@@ -36,6 +46,13 @@ pub fn detect_version(input: &str) -> CifVersion {
     }
 }
 
+/// Maximum nesting depth of CIF 2.0 lists and tables.
+///
+/// The PEG parser recurses once per nesting level, so unbounded nesting in
+/// hostile input would overflow the stack (an abort, not a recoverable error).
+/// Real files nest a handful of levels at most.
+pub const MAX_NESTING_DEPTH: usize = 128;
+
 /// Parse a complete CIF file from a string (auto-detects version).
 ///
 /// This is the main entry point for parsing. It:
@@ -51,21 +68,160 @@ pub fn detect_version(input: &str) -> CifVersion {
 /// assert_eq!(doc.blocks.len(), 1);
 /// ```
 pub fn parse_file(input: &str) -> Result<CifDocument, CifError> {
+    parse_file_with_options(input, &ParseOptions::default())
+}
+
+/// Parse a complete CIF file from a string using the given options.
+///
+/// A leading byte-order mark is ignored, and CR, LF, and CRLF all end a
+/// line: text fields read back with LF line endings, and locations count
+/// lines the same way whatever the file's newline style. Warnings collected
+/// in lenient mode are stored on [`CifDocument::warnings`].
+///
+/// Text before the first data block other than comments, or after the last
+/// one (from an `#END` marker, a value with no data name, or binary junk),
+/// is an error in strict mode. In lenient mode it is kept verbatim on
+/// [`CifDocument::preamble`] and [`CifDocument::trailer`] with a warning.
+///
+/// In lenient mode, numbers written with a decimal comma (`1,5406`) in tags
+/// that hold numbers are read as numbers; see the
+/// [`decimal_comma`](crate::parser::decimal_comma) module.
+///
+/// Save frames before the first data block, as in a dictionary fragment,
+/// are an error unless lenient or allowed by
+/// [`allow_bare_frames`](ParseOptions::allow_bare_frames), which puts them
+/// in a synthetic block.
+///
+/// A [`dialect`](ParseOptions::dialect) handler, if set, rewrites
+/// non-standard tokens before any of this.
+///
+/// With [`Conformance::Strict11`], anything the CIF 1.1 specification does
+/// not allow is an error; see [`ParseOptions::conformance`].
+///
+/// # Examples
+/// ```
+/// # use cif_parser::parser::document::parse_file_with_options;
+/// # use cif_parser::ParseOptions;
+/// let cif = "data_test\nloop_\n_a\n1\nstop_\n";
+/// let doc = parse_file_with_options(cif, &ParseOptions::new().lenient(true)).unwrap();
+/// assert_eq!(doc.warnings.len(), 1);
+/// ```
+pub fn parse_file_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<CifDocument, CifError> {
+    parse_input(input, options).map_err(|error| error.with_source(input))
+}
+
+/// [`parse_file_with_options`], without the errors' spans
+fn parse_input(input: &str, options: &ParseOptions) -> Result<CifDocument, CifError> {
+    // Line lengths and characters, as written, before anything is changed
+    if options.conformance == Conformance::Strict11 {
+        conformance::check_lines(input)?;
+    }
+
+    // One newline style, and no byte-order mark, for everything that follows
+    let input = input.strip_prefix('\u{FEFF}').unwrap_or(input);
+    let unified = normalize_line_terminators(input);
+    let input = unified.as_deref().unwrap_or(input);
+
     // Detect version from magic comment
     let version = detect_version(input);
 
+    // Blocks split apart and parsed on several threads, if asked for
+    #[cfg(feature = "parallel")]
+    if options.threads > 1 {
+        if let Some(doc) = parallel::parse(input, version, options) {
+            return Ok(doc);
+        }
+    }
+    parse_normalized(input, version, options)
+}
+
+/// Parse input with one newline style and no byte-order mark, as CIF of
+/// the given version unless a banner hides a CIF 2.0 magic comment
+pub(crate) fn parse_normalized(
+    input: &str,
+    version: CifVersion,
+    options: &ParseOptions,
+) -> Result<CifDocument, CifError> {
+    let mut ctx = ParseContext::new(version, options);
+
+    // Word-processor spaces (U+00A0 etc.): rejected, or replaced in lenient mode
+    let normalized = normalize_unicode_spaces(input, &mut ctx)?;
+    let input = normalized.as_deref().unwrap_or(input);
+
+    // Quoted strings running off the end of their line, closed in lenient mode
+    let closed = match options.lenient {
+        true => close_open_quotes(input, &mut ctx),
+        false => None,
+    };
+    let input = closed.as_deref().unwrap_or(input);
+
+    // Vendor constructs, turned into CIF by the caller's dialect handler
+    let rewritten = match &options.dialect {
+        Some(handler) => dialect::rewrite(input, handler.as_ref(), &mut ctx),
+        None => None,
+    };
+    let input = rewritten.as_deref().unwrap_or(input);
+
+    // Save frames with no block to hold them
+    let wrapped = bare::wrap(input, &mut ctx)?;
+    let input = wrapped.as_deref().unwrap_or(input);
+
+    // Banners before the first block and junk after the last one
+    let stripped = trailer::strip(input, &mut ctx)?;
+    let input = stripped.input.as_deref().unwrap_or(input);
+    // A banner can push the magic comment off the first line
+    if let Some(preamble) = &stripped.preamble {
+        if preamble
+            .lines()
+            .any(|line| detect_version(line) == CifVersion::V2_0)
+        {
+            ctx.version = CifVersion::V2_0;
+        }
+    }
+    let version = ctx.version;
+
+    // Skip unselected content before the grammar sees it
+    let selected = match options.select.as_slice() {
+        [] => None,
+        selection => select::reduce(input, version, selection),
+    };
+    let input = selected.as_deref().unwrap_or(input);
+
+    // Guard the recursive grammar against stack exhaustion
+    check_nesting_depth(input)?;
+
     // Parse with PEST
     let pairs = CIFParser::parse(Rule::file, input)?;
 
     // Build AST with detected version
     let mut doc = CifDocument::new_with_version(version);
+    doc.preamble = stripped.preamble;
+    doc.trailer = stripped.trailer;
+    doc.bare_frames = wrapped.is_some();
 
     for pair in pairs {
         if pair.as_rule() == Rule::file {
-            parse_file_content(pair, &mut doc, version)?;
+            parse_file_content(pair, &mut doc, &mut ctx)?;
         }
     }
 
+    if !options.select.is_empty() {
+        select::apply(&mut doc, &options.select);
+    }
+    // What only the whole document shows: names used twice, data names
+    // differing in case, and values CIF 1.1 cannot hold
+    if let Some(error) = doc
+        .check_conformance(options.conformance)
+        .into_iter()
+        .next()
+    {
+        return Err(error);
+    }
+    doc.warnings = ctx.warnings;
+    doc.decimal_commas = ctx.decimal_commas;
     Ok(doc)
 }
 
@@ -73,20 +229,20 @@ pub fn parse_file(input: &str) -> Result<CifDocument, CifError> {
 fn parse_file_content(
     pair: pest::iterators::Pair<Rule>,
     doc: &mut CifDocument,
-    version: CifVersion,
+    ctx: &mut ParseContext,
 ) -> Result<(), CifError> {
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             // file rule can contain datablock directly or through content rule
             Rule::datablock => {
-                let block = parse_datablock(inner_pair, version)?;
+                let block = parse_datablock(inner_pair, ctx)?;
                 doc.blocks.push(block);
             }
             Rule::content => {
                 // Legacy: content rule contains datablocks
                 for content_pair in inner_pair.into_inner() {
                     if content_pair.as_rule() == Rule::datablock {
-                        let block = parse_datablock(content_pair, version)?;
+                        let block = parse_datablock(content_pair, ctx)?;
                         doc.blocks.push(block);
                     }
                 }
@@ -120,6 +276,35 @@ mod tests {
         assert_eq!(doc.blocks[1].name, "second");
     }
 
+    #[test]
+    fn test_nesting_depth_limit() {
+        let deep = |n: usize| {
+            format!(
+                "#\\#CIF_2.0\ndata_a\n_x {}{}\n",
+                "[".repeat(n),
+                "]".repeat(n)
+            )
+        };
+        assert!(parse_file(&deep(MAX_NESTING_DEPTH)).is_ok());
+
+        let err = parse_file(&deep(MAX_NESTING_DEPTH + 1)).unwrap_err();
+        assert!(matches!(
+            err,
+            CifError::NestingTooDeep {
+                location: Some((3, 132)),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_nesting_check_ignores_brackets_in_text() {
+        let brackets = "[".repeat(MAX_NESTING_DEPTH + 1);
+        let cif =
+            format!("data_a\n_q '{brackets}'\n_t\n;{brackets}\n;\n# {brackets}\n_u a{brackets}\n");
+        assert!(check_nesting_depth(&cif).is_ok());
+    }
+
     #[test]
     fn test_parse_empty_file() {
         // CIF grammar allows empty files (just whitespace/comments)
@@ -0,0 +1,130 @@
+//! Byte-level input handling: encodings, byte-order marks, and line
+//! terminators.
+//!
+//! Files written on Windows or passed through email often start with a
+//! byte-order mark and end lines with CRLF, and some tools save CIF as
+//! UTF-16. These are undone here, before the grammar sees the input, so that
+//! every variant of a file parses to the same document with the same line
+//! numbers.
+
+use crate::error::{CifError, CifWarning};
+use crate::parser::options::ParseOptions;
+use std::borrow::Cow;
+
+/// The UTF-8 encoding of U+FEFF, the byte-order mark
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Decode the bytes of a CIF file into text.
+///
+/// A UTF-8 byte-order mark is left for the parser to strip. Input starting
+/// with a UTF-16 byte-order mark is transcoded in lenient mode, with a
+/// warning, and rejected in strict mode. Anything else must be UTF-8.
+pub(crate) fn decode<'a>(
+    bytes: &'a [u8],
+    options: &ParseOptions,
+) -> Result<(Cow<'a, str>, Option<CifWarning>), CifError> {
+    let utf16 = match bytes {
+        [0xFF, 0xFE, rest @ ..] => Some(("little", rest)),
+        [0xFE, 0xFF, rest @ ..] => Some(("big", rest)),
+        _ => None,
+    };
+    if let Some((endian, rest)) = utf16 {
+        if !options.lenient {
+            return Err(CifError::encoding(format!(
+                "File is UTF-16 encoded ({endian}-endian); CIF must be UTF-8. \
+                 Convert it, or parse leniently to transcode it"
+            )));
+        }
+        if rest.len() % 2 != 0 {
+            return Err(CifError::encoding(format!(
+                "File has a UTF-16 ({endian}-endian) byte-order mark but an odd number of bytes"
+            )));
+        }
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| match endian {
+                "big" => u16::from_be_bytes([pair[0], pair[1]]),
+                _ => u16::from_le_bytes([pair[0], pair[1]]),
+            })
+            .collect();
+        let text = String::from_utf16(&units).map_err(|_| {
+            CifError::encoding(format!(
+                "File has a UTF-16 ({endian}-endian) byte-order mark but is not valid UTF-16"
+            ))
+        })?;
+        let warning = CifWarning::new(format!(
+            "File is UTF-16 encoded ({endian}-endian); transcoded to UTF-8"
+        ));
+        return Ok((Cow::Owned(text), Some(warning)));
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok((Cow::Borrowed(text), None)),
+        Err(error) => {
+            let valid = &bytes[..error.valid_up_to()];
+            let valid = valid.strip_prefix(UTF8_BOM).unwrap_or(valid);
+            // Everything before the bad byte is valid, so this cannot fail
+            let before = std::str::from_utf8(valid).unwrap_or_default();
+            let before =
+                normalize_line_terminators(before).map_or(Cow::Borrowed(before), Cow::Owned);
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().map_or(0, |s| s.chars().count()) + 1;
+            Err(CifError::encoding(format!(
+                "File is not valid UTF-8: invalid byte 0x{:02X}",
+                bytes[error.valid_up_to()]
+            ))
+            .at_location(line, column))
+        }
+    }
+}
+
+/// Decode UTF-8 that has invalid bytes, replacing each bad sequence with
+/// U+FFFD, along with how many replacements were made.
+///
+/// Lenient parsing uses this to accept binary junk around the data blocks.
+pub(crate) fn decode_lossy(bytes: &[u8]) -> (String, usize) {
+    const REPLACEMENT: &[u8] = "\u{FFFD}".as_bytes();
+    let literal = bytes.windows(3).filter(|w| *w == REPLACEMENT).count();
+    let text = String::from_utf8_lossy(bytes).into_owned();
+    let replaced = text.matches('\u{FFFD}').count() - literal;
+    (text, replaced)
+}
+
+/// Rewrite CR and CRLF line terminators as LF, as CIF 2.0 specifies, so that
+/// text fields and line numbers do not depend on the file's newline style.
+///
+/// Returns `None` when the input has no CR to rewrite.
+pub(crate) fn normalize_line_terminators(input: &str) -> Option<String> {
+    if !input.contains('\r') {
+        return None;
+    }
+    Some(input.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_terminators() {
+        assert_eq!(normalize_line_terminators("a\nb"), None);
+        assert_eq!(
+            normalize_line_terminators("a\r\nb\rc\n\r\nd").as_deref(),
+            Some("a\nb\nc\n\nd")
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_location() {
+        let bytes = b"data_a\r\n_x caf\xE9\n";
+        let error = decode(bytes, &ParseOptions::new()).unwrap_err();
+        assert!(matches!(
+            error,
+            CifError::Encoding {
+                location: Some((2, 7)),
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("0xE9"));
+    }
+}
@@ -50,3 +50,25 @@ pub(crate) fn collect_rule<'a>(
 pub(crate) fn extract_location(pair: &Pair<Rule>) -> (usize, usize) {
     pair.as_span().start_pos().line_col()
 }
+
+/// The whitespace-delimited token starting at byte offset `pos` of `input`
+pub(crate) fn token_at(input: &str, pos: usize) -> &str {
+    let rest = &input[pos..];
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    &rest[..end]
+}
+
+/// Whether an unquoted token is a reserved word, which can never be a value:
+/// anything starting with `data_` or `save_`, or `loop_`, `global_`, or `stop_`
+/// (all case-insensitive)
+pub(crate) fn is_reserved_word(token: &str) -> bool {
+    let lower = token.to_ascii_lowercase();
+    lower.starts_with("data_")
+        || lower.starts_with("save_")
+        || matches!(lower.as_str(), "loop_" | "global_" | "stop_")
+}
+
+/// (line, column) of byte offset `pos` in `input`
+pub(crate) fn location_at(input: &str, pos: usize) -> (usize, usize) {
+    pest::Position::new(input, pos).map_or((0, 0), |p| p.line_col())
+}
@@ -0,0 +1,449 @@
+//! Parsing a document from a reader a piece at a time.
+//!
+//! [`CifDocument::from_reader`] does not read its input to the end before
+//! parsing it. It reads pieces of about [`PIECE_SIZE`] bytes, cut at `data_`
+//! lines outside text fields, and parses each as soon as it is read, so that
+//! only one piece of the input is in memory at a time, besides the document
+//! built so far.
+//!
+//! Each piece also takes the heading of the block after it, so that its end
+//! is parsed as it is in the whole input (a short loop before another block
+//! is blamed on its heading), and the empty block it gives is dropped. A
+//! piece after the first is parsed as CIF of the first one's version. A
+//! piece that does not parse as it would in place, as when it fails or its
+//! last heading was read as part of a value, is parsed again together with
+//! the blocks after it, so that the document and any error are those of
+//! parsing the input whole. Each time it takes at least as many bytes again,
+//! up to [`MAX_PIECE_SIZE`]; a piece that still fails then gives its error
+//! without the rest of the input being read.
+
+use crate::ast::{CifDocument, CifVersion};
+use crate::error::CifError;
+use crate::parser::options::{ParseOptions, Selection};
+use crate::parser::stream::is_data_line;
+use std::io::BufRead;
+
+/// Bytes of input read before a piece is cut at the next `data_` line
+const PIECE_SIZE: usize = 1 << 20;
+
+/// Bytes a piece that does not parse in place may grow to
+const MAX_PIECE_SIZE: usize = 64 * PIECE_SIZE;
+
+/// Magic comment put before a later piece of CIF 2.0 input
+const MAGIC_2_0: &[u8] = b"#\\#CIF_2.0\n";
+
+/// Parse the CIF read from `reader`, a piece at a time
+pub(crate) fn parse<R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+) -> Result<CifDocument, CifError> {
+    // The first block is only known to be the first in the whole input
+    let size = match options.select.contains(&Selection::FirstBlockOnly) {
+        true => usize::MAX,
+        false => PIECE_SIZE,
+    };
+    parse_in_pieces(reader, options, size, MAX_PIECE_SIZE)
+}
+
+/// [`parse`], cutting pieces after `size` bytes and growing those that do
+/// not parse in place up to `limit` bytes
+fn parse_in_pieces<R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+    size: usize,
+    limit: usize,
+) -> Result<CifDocument, CifError> {
+    let mut pieces = Pieces::new(reader, size);
+    let mut whole: Option<CifDocument> = None;
+    loop {
+        let mut piece = pieces.next_piece()?;
+        let version = whole.as_ref().map(|doc| doc.version);
+        if version == Some(CifVersion::V2_0) {
+            piece.bytes.splice(0..0, MAGIC_2_0.iter().copied());
+            piece.magic = true;
+        }
+        let doc = loop {
+            let parsed = piece
+                .parse(options)
+                .map(|mut doc| (in_place(&mut doc, &piece, version, options), doc));
+            let last = piece.next_block.is_none();
+            match parsed {
+                Ok((in_place, doc)) if in_place || last => break doc,
+                Err(error) if last || piece.bytes.len() >= limit => return Err(error),
+                Ok(_) if piece.bytes.len() >= limit => {
+                    let message = format!(
+                        "the blocks starting here could not be parsed apart from the rest of \
+                         the input within {limit} bytes"
+                    );
+                    return Err(
+                        CifError::invalid_structure(message).at_location(piece.first_line, 1)
+                    );
+                }
+                _ => pieces.grow(&mut piece)?,
+            }
+        };
+        match whole.as_mut() {
+            Some(whole) => append(whole, doc, piece.line_shift()),
+            None => whole = Some(doc),
+        }
+        if piece.next_block.is_none() {
+            break;
+        }
+    }
+
+    // Checks across pieces, such as for block names used twice
+    let whole = whole.expect("at least one piece");
+    match whole
+        .check_conformance(options.conformance)
+        .into_iter()
+        .next()
+    {
+        Some(error) => Err(error),
+        None => Ok(whole),
+    }
+}
+
+/// Whether `doc`, parsed from `piece`, is as that part of the whole input
+/// would be, dropping the block of the heading that ends it.
+///
+/// `version` is that of the pieces before, if any.
+fn in_place(
+    doc: &mut CifDocument,
+    piece: &Piece,
+    version: Option<CifVersion>,
+    options: &ParseOptions,
+) -> bool {
+    if let Some(next) = &piece.next_block {
+        if !drop_heading(doc, next, options) {
+            return false;
+        }
+    }
+    // Only the first piece has text before its blocks, and only the last
+    // may have text after them
+    let first = version.is_none();
+    let misplaced = (!first && (doc.preamble.is_some() || doc.bare_frames))
+        || (piece.next_block.is_some() && doc.trailer.is_some());
+    // A banner can hide the magic comment from the version check
+    !misplaced && version.is_none_or(|version| doc.version == version)
+}
+
+/// Drop the empty block named `next` that the heading ending a piece gives.
+///
+/// Returns false if the heading was misread, as when it is inside a value
+/// the piece cuts short.
+pub(crate) fn drop_heading(doc: &mut CifDocument, next: &str, options: &ParseOptions) -> bool {
+    match doc.blocks.last() {
+        Some(block)
+            if block.name == *next
+                && block.items.is_empty()
+                && block.loops.is_empty()
+                && block.frames.is_empty() =>
+        {
+            doc.blocks.pop();
+            true
+        }
+        // Unless a selection left it out, the heading was misread
+        _ => !options.select.is_empty(),
+    }
+}
+
+/// Add the document of a later piece to `whole`, moving its warnings
+/// `lines` lines down
+pub(crate) fn append(whole: &mut CifDocument, mut doc: CifDocument, lines: usize) {
+    for warning in &mut doc.warnings {
+        if let Some((line, column)) = warning.location {
+            warning.location = Some((line + lines, column));
+        }
+    }
+    whole.blocks.append(&mut doc.blocks);
+    whole.warnings.append(&mut doc.warnings);
+    whole.decimal_commas.append(&mut doc.decimal_commas);
+    whole.partial |= doc.partial;
+    whole.trailer = doc.trailer;
+}
+
+/// The block name in a `data_` line
+pub(crate) fn heading_name(line: &str) -> String {
+    let name = &line.trim_start()["data_".len()..];
+    name.split(|c: char| c.is_ascii_whitespace())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Whole blocks of the input, and the heading of the next one
+struct Piece {
+    bytes: Vec<u8>,
+    /// Whether `bytes` starts with [`MAGIC_2_0`], which is not in the input
+    magic: bool,
+    /// Line and byte offset of the input the piece starts at
+    first_line: usize,
+    first_byte: usize,
+    /// Name of the block whose heading ends the piece
+    next_block: Option<String>,
+}
+
+impl Piece {
+    /// Lines of the input before the piece, less the magic comment's
+    fn line_shift(&self) -> usize {
+        (self.first_line - 1).saturating_sub(usize::from(self.magic))
+    }
+
+    fn parse(&self, options: &ParseOptions) -> Result<CifDocument, CifError> {
+        let error = match CifDocument::from_bytes_with_options(&self.bytes, options) {
+            Ok(doc) => return Ok(doc),
+            Err(error) => error,
+        };
+        if self.first_line == 1 {
+            return Err(error);
+        }
+        let magic = if self.magic { MAGIC_2_0.len() } else { 0 };
+        let error = match error {
+            // The grammar's message quotes the piece's lines, so it keeps
+            // their numbering and says so
+            CifError::Syntax {
+                message, location, ..
+            } => CifError::Syntax {
+                message: format!(
+                    "in the part of the input starting at line {} (line numbers below count \
+                     from there)\n{message}",
+                    self.line_shift() + 1
+                ),
+                location,
+                span: None,
+            },
+            other => other,
+        };
+        // Locations in the piece as parsed, then in the input
+        let Some((line, column)) = error.location() else {
+            return Err(error);
+        };
+        let error = error.at_location(line.saturating_sub(usize::from(self.magic)).max(1), column);
+        Err(match std::str::from_utf8(&self.bytes[magic..]) {
+            Ok(text) => error.in_whole(text, self.first_line - 1, self.first_byte),
+            Err(_) => error.at_location(line + self.line_shift(), column),
+        })
+    }
+}
+
+/// Reader of the pieces of an input
+struct Pieces<R> {
+    reader: R,
+    /// Bytes read before a piece is cut at the next `data_` line
+    size: usize,
+    /// The `data_` line ending the last piece, which starts the next one
+    heading: Option<Vec<u8>>,
+    /// Lines and bytes read so far
+    lines_read: usize,
+    bytes_read: usize,
+    headings: Headings,
+    has_block: bool,
+}
+
+impl<R: BufRead> Pieces<R> {
+    fn new(reader: R, size: usize) -> Self {
+        Pieces {
+            reader,
+            size,
+            heading: None,
+            lines_read: 0,
+            bytes_read: 0,
+            headings: Headings::default(),
+            has_block: false,
+        }
+    }
+
+    /// Read the next piece, which ends at the end of the input if it has
+    /// no `next_block`
+    fn next_piece(&mut self) -> Result<Piece, CifError> {
+        let mut bytes = self.heading.take().unwrap_or_default();
+        let mut piece = Piece {
+            magic: false,
+            first_line: self.lines_read + 1 - line_count(&bytes),
+            first_byte: self.bytes_read - bytes.len(),
+            next_block: None,
+            bytes: Vec::new(),
+        };
+        loop {
+            let start = bytes.len();
+            if self.reader.read_until(b'\n', &mut bytes)? == 0 {
+                piece.bytes = bytes;
+                return Ok(piece);
+            }
+            let line = &bytes[start..];
+            self.lines_read += line_count(line);
+            self.bytes_read += line.len();
+            if self.headings.is_heading(line) {
+                if self.has_block && start >= self.size {
+                    piece.next_block = Some(heading_name(&String::from_utf8_lossy(line)));
+                    self.heading = Some(line.to_vec());
+                    piece.bytes = bytes;
+                    return Ok(piece);
+                }
+                self.has_block = true;
+            }
+        }
+    }
+
+    /// Read the blocks after `piece` onto it, at least as many bytes again
+    /// as it has, so that parsing it again each time it grows takes time
+    /// linear in its final size
+    fn grow(&mut self, piece: &mut Piece) -> Result<(), CifError> {
+        // The piece already ends with the heading the next one starts with
+        self.heading = None;
+        let size = std::mem::replace(&mut self.size, piece.bytes.len());
+        let next = self.next_piece();
+        self.size = size;
+        let next = next?;
+        piece.bytes.extend(next.bytes);
+        piece.next_block = next.next_block;
+        Ok(())
+    }
+}
+
+/// Finder of the `data_` lines an input can be cut at: those outside text
+/// fields and triple-quoted strings
+#[derive(Default)]
+pub(crate) struct Headings {
+    in_text_field: bool,
+    /// Quote of the triple-quoted string left open, if any
+    triple_quote: Option<u8>,
+}
+
+impl Headings {
+    /// Whether `line`, the next line of the input, is a block heading
+    pub(crate) fn is_heading(&mut self, line: &[u8]) -> bool {
+        if self.triple_quote.is_none() && line.starts_with(b";") {
+            self.in_text_field = !self.in_text_field;
+            return false;
+        }
+        if self.in_text_field {
+            return false;
+        }
+        let heading = self.triple_quote.is_none() && is_data_line(line);
+        // A stray triple quote only keeps the input from being cut
+        let mut i = 0;
+        while let Some(window) = line.get(i..i + 3) {
+            match (self.triple_quote, window) {
+                (None, [b'#', ..]) if i == 0 || line[i - 1].is_ascii_whitespace() => break,
+                (None, &[quote @ (b'"' | b'\''), second, third])
+                    if second == quote && third == quote =>
+                {
+                    self.triple_quote = Some(quote);
+                    i += 3;
+                }
+                (Some(quote), _) if window == [quote; 3] => {
+                    self.triple_quote = None;
+                    i += 3;
+                }
+                _ => i += 1,
+            }
+        }
+        heading
+    }
+}
+
+/// Lines in `bytes`, ended by LF, CRLF, or a lone CR, or left unended
+fn line_count(bytes: &[u8]) -> usize {
+    let ended = bytes
+        .iter()
+        .enumerate()
+        .filter(|&(i, &byte)| byte == b'\n' || (byte == b'\r' && bytes.get(i + 1) != Some(&b'\n')))
+        .count();
+    ended + usize::from(!matches!(bytes.last(), None | Some(b'\n' | b'\r')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::Conformance;
+
+    /// Parse `input` in pieces cut at every block, and whole
+    fn both(input: &str, options: &ParseOptions) -> [Result<CifDocument, CifError>; 2] {
+        [
+            parse_in_pieces(input.as_bytes(), options, 0, usize::MAX),
+            CifDocument::parse_with_options(input, options),
+        ]
+    }
+
+    #[test]
+    fn test_pieces_give_the_whole_document() {
+        let inputs = [
+            "# banner\ndata_a\n_x 1\ndata_b\nloop_ _y 1 2\ndata_c\n_z 3\n",
+            "#\\#CIF_2.0\ndata_a\n_x [1 2]\ndata_b\n_y {'k':1}\n",
+            // A heading inside a text field or a triple-quoted string
+            "data_a\n_x\n;\ndata_in\n;\ndata_b\n_y 2\n",
+            "#\\#CIF_2.0\ndata_a\n_x \"\"\"\ndata_in\n\"\"\"\ndata_b\n_y 2\n",
+            "data_a\r\n_x 1\r\ndata_b\r_y 2\rdata_c\n_z 3\n",
+        ];
+        for input in inputs {
+            let [pieces, whole] = both(input, &ParseOptions::default());
+            let (pieces, whole) = (pieces.unwrap(), whole.unwrap());
+            assert!(pieces.semantically_equal(&whole), "{input:?}");
+            assert_eq!(pieces.version, whole.version, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_later_pieces_keep_their_line_numbers() {
+        let options = ParseOptions::new().lenient(true);
+        let input = "data_a\n_x 1\ndata_b\r\n_y 'open\r\ndata_c\rloop_ _z _w 1 2 3\n";
+        let [pieces, whole] = both(input, &options);
+        let (pieces, whole) = (pieces.unwrap(), whole.unwrap());
+        assert_eq!(pieces.warnings, whole.warnings);
+        assert_eq!(pieces.warnings.len(), 2);
+
+        let input = "data_a\n_x 1\ndata_b\n_y 1\n_z\ndata_c\n";
+        let [pieces, whole] = both(input, &ParseOptions::default());
+        let (pieces, whole) = (pieces.unwrap_err(), whole.unwrap_err());
+        assert_eq!(pieces.to_string(), whole.to_string());
+        assert_eq!(pieces.span(), whole.span());
+        assert_eq!(pieces.location(), Some((6, 1)));
+    }
+
+    #[test]
+    fn test_syntax_errors_say_where_their_piece_starts() {
+        let input = "#\\#CIF_2.0\ndata_a\n_x 1\ndata_b\n_y [1 2\n";
+        let [pieces, whole] = both(input, &ParseOptions::default());
+        let (pieces, whole) = (pieces.unwrap_err(), whole.unwrap_err());
+        assert_eq!(pieces.location(), whole.location());
+        assert_eq!(pieces.byte_offset(), whole.byte_offset());
+        assert!(pieces
+            .to_string()
+            .contains("in the part of the input starting at line 3"));
+    }
+
+    #[test]
+    fn test_names_used_twice_in_different_pieces() {
+        let options = ParseOptions::new().conformance(Conformance::Strict11);
+        let input = "data_a\n_x 1\ndata_a\n_y 2\n";
+        let [pieces, whole] = both(input, &options);
+        assert_eq!(
+            pieces.unwrap_err().to_string(),
+            whole.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_failing_pieces_grow_only_to_the_limit() {
+        let mut input = "data_a\n_x 1\ndata_b\n_y [1 2\n".to_string();
+        for i in 0..100 {
+            input.push_str(&format!("data_c{i}\n_z {i}\n"));
+        }
+        let mut rest = input.as_bytes();
+        let error = parse_in_pieces(&mut rest, &ParseOptions::default(), 0, 64).unwrap_err();
+        let whole = CifDocument::parse(&input).unwrap_err();
+        assert_eq!(error.location(), whole.location());
+        // The rest of the input is left unread
+        assert!(rest.len() > input.len() / 2);
+    }
+
+    #[test]
+    fn test_line_count() {
+        assert_eq!(line_count(b""), 0);
+        assert_eq!(line_count(b"data_a\n"), 1);
+        assert_eq!(line_count(b"data_a\r\n"), 1);
+        assert_eq!(line_count(b"data_a\r_x 1\n"), 2);
+        assert_eq!(line_count(b"data_a"), 1);
+    }
+}
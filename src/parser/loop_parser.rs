@@ -1,8 +1,13 @@
 //! Loop structure parsing logic.
 
-use crate::ast::{CifLoop, CifValue, CifVersion};
+use crate::ast::{CifLoop, CifValue};
+use crate::conformance;
 use crate::error::CifError;
-use crate::parser::helpers::{extract_location, extract_text};
+use crate::parser::decimal_comma;
+use crate::parser::helpers::{
+    extract_location, extract_text, is_reserved_word, location_at, token_at,
+};
+use crate::parser::options::ParseContext;
 use crate::Rule;
 use pest::iterators::Pair;
 
@@ -18,13 +23,35 @@ use pest::iterators::Pair;
 /// # Error Conditions
 ///
 /// - [`CifError::InvalidStructure`]: No tags found
-/// - [`CifError::InvalidStructure`]: Values don't align with tags (wrong count)
+/// - [`CifError::LoopMismatch`]: Values don't align with tags (wrong count)
+/// - [`CifError::InvalidStructure`]: STAR `stop_` / nested loop constructs (strict mode)
+///
+/// # Reserved Words
+///
+/// An unquoted `data_…`, `save_…`, `loop_`, or `global_` always ends the loop,
+/// as the specification requires: such tokens must be quoted to be values.
+/// When the loop ends on an incomplete row, the reserved word was probably
+/// meant as a value, so the error points at it and says it needs quoting
+/// instead of reporting only the value count.
+///
+/// In lenient mode an incomplete last row is padded with `?` (unknown)
+/// values, with a warning naming the reserved word if one ended the loop,
+/// instead.
 ///
 /// # Empty Loops
 ///
 /// Loops with tags but no values are valid (represents an empty table).
-pub(crate) fn parse_loop(pair: Pair<Rule>, version: CifVersion) -> Result<CifLoop, CifError> {
+///
+/// # STAR Constructs
+///
+/// `stop_` and nested loops come from STAR (e.g. NMR-STAR) and are not valid CIF.
+/// In strict mode they are rejected with their location. In lenient mode the loop
+/// is closed at the first `stop_`, and any values following it are discarded with
+/// a warning, since their nesting level cannot be represented in CIF.
+pub(crate) fn parse_loop(pair: Pair<Rule>, ctx: &mut ParseContext) -> Result<CifLoop, CifError> {
     let loop_location = extract_location(&pair);
+    let input = pair.get_input();
+    let end = pair.as_span().end();
     let inner: Vec<_> = pair.into_inner().collect();
 
     // Collect all tag pairs (preserves individual tag locations)
@@ -35,13 +62,23 @@ pub(crate) fn parse_loop(pair: Pair<Rule>, version: CifVersion) -> Result<CifLoo
 
     // Validate tags exist
     if tag_pairs.is_empty() {
-        return Err(CifError::invalid_structure("Loop block has no tags")
-            .at_location(loop_location.0, loop_location.1));
+        return Err(CifError::invalid_structure(
+            "Loop has no tags: 'loop_' must be followed by at least one data name",
+        )
+        .at_location(loop_location.0, loop_location.1));
+    }
+
+    if ctx.strict_11() {
+        for tag_pair in &tag_pairs {
+            if let Some(error) = conformance::tag_error(tag_pair.as_str()) {
+                let (line, col) = extract_location(tag_pair);
+                return Err(error.at_location(line, col));
+            }
+        }
     }
 
     // Extract tag strings
-    let mut loop_ = CifLoop::new();
-    loop_.tags = tag_pairs.iter().map(|p| extract_text(p)).collect();
+    let mut loop_ = CifLoop::new(tag_pairs.iter().map(|p| extract_text(p)).collect());
 
     // Collect values
     let mut values = Vec::new();
@@ -51,11 +88,13 @@ pub(crate) fn parse_loop(pair: Pair<Rule>, version: CifVersion) -> Result<CifLoo
                 // Already processed
             }
             Rule::loop_values => {
-                collect_loop_values(inner_pair, &mut values, version)?;
+                collect_loop_values(inner_pair, &loop_.tags, &mut values, ctx)?;
             }
             Rule::loop_value | Rule::value => {
-                let value = crate::parser::value::parse_value(inner_pair.clone(), version)?;
-                values.push(value);
+                push_value(inner_pair, &loop_.tags, &mut values, ctx)?;
+            }
+            Rule::loop_end => {
+                handle_star_loop_end(inner_pair, ctx)?;
             }
             _rule => {
                 // Unknown rule - safely ignored
@@ -63,6 +102,37 @@ pub(crate) fn parse_loop(pair: Pair<Rule>, version: CifVersion) -> Result<CifLoo
         }
     }
 
+    let next = token_at(input, end);
+    let before = is_reserved_word(next).then_some(next);
+    let short = values.len() % loop_.tags.len();
+    if short != 0 && !ctx.options.lenient {
+        if let Some(word) = before {
+            let (line, col) = location_at(input, end);
+            return Err(CifError::LoopMismatch {
+                tags: loop_.tags.len(),
+                values: values.len(),
+                reserved_word: Some(word.to_string()),
+                location: Some((line, col)),
+                span: None,
+            });
+        }
+    } else if short != 0 {
+        let missing = loop_.tags.len() - short;
+        let padded = match before {
+            Some(word) => format!("the last row, before '{word}', was padded"),
+            None => "the last row was padded".to_string(),
+        };
+        ctx.warn(
+            format!(
+                "Loop has {} tags but {} values; {padded} with {missing} '?'",
+                loop_.tags.len(),
+                values.len(),
+            ),
+            location_at(input, end),
+        );
+        values.resize(values.len() + missing, CifValue::Unknown);
+    }
+
     organize_loop_values(&mut loop_, values, loop_location)?;
     Ok(loop_)
 }
@@ -70,14 +140,14 @@ pub(crate) fn parse_loop(pair: Pair<Rule>, version: CifVersion) -> Result<CifLoo
 /// Helper to collect values from loop_values rule
 fn collect_loop_values(
     pair: Pair<Rule>,
+    tags: &[String],
     values: &mut Vec<CifValue>,
-    version: CifVersion,
+    ctx: &mut ParseContext,
 ) -> Result<(), CifError> {
     for value_pair in pair.into_inner() {
         match value_pair.as_rule() {
             Rule::loop_value | Rule::value => {
-                let value = crate::parser::value::parse_value(value_pair, version)?;
-                values.push(value);
+                push_value(value_pair, tags, values, ctx)?;
             }
             _rule => {
                 // Unknown rule - safely ignored
@@ -87,6 +157,86 @@ fn collect_loop_values(
     Ok(())
 }
 
+/// Parse one loop value and append it, reading a decimal comma in a numeric
+/// column in lenient mode
+fn push_value(
+    pair: Pair<Rule>,
+    tags: &[String],
+    values: &mut Vec<CifValue>,
+    ctx: &mut ParseContext,
+) -> Result<(), CifError> {
+    let mut value = crate::parser::value::parse_value_in(pair.clone(), ctx)?;
+    let (row, col) = (values.len() / tags.len(), values.len() % tags.len());
+    decimal_comma::recover(&tags[col], Some(row), &mut value, &pair, ctx);
+    values.push(value);
+    Ok(())
+}
+
+/// Reject (strict) or flatten (lenient) STAR `stop_` terminators closing a loop.
+///
+/// The grammar captures every `stop_` token and any outer-level values between
+/// them, so nothing is silently attached to the wrong loop. Values after the
+/// last `stop_` mean a nested loop is still open when the loop ends, which is
+/// an error even in lenient mode.
+fn handle_star_loop_end(pair: Pair<Rule>, ctx: &mut ParseContext) -> Result<(), CifError> {
+    let mut stop_location = None;
+    let mut discarded = 0;
+    // The first value not followed by a `stop_`
+    let mut unclosed = None;
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::stop_token => {
+                stop_location.get_or_insert_with(|| extract_location(&inner_pair));
+                unclosed = None;
+            }
+            Rule::loop_values => {
+                let start = inner_pair.as_span().start();
+                unclosed = Some((
+                    extract_location(&inner_pair),
+                    token_at(inner_pair.get_input(), start).to_string(),
+                ));
+                discarded += inner_pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::loop_value)
+                    .count();
+            }
+            _rule => {
+                // Whitespace - safely ignored
+            }
+        }
+    }
+
+    let Some((line, col)) = stop_location else {
+        return Ok(());
+    };
+
+    if !ctx.options.lenient {
+        return Err(CifError::invalid_structure(
+            "Nested loops are not valid in CIF; this looks like a STAR/NMR-STAR file (found 'stop_')",
+        )
+        .at_location(line, col));
+    }
+
+    if let Some(((line, col), value)) = unclosed {
+        return Err(CifError::invalid_structure(format!(
+            "Nested loop is still open when the loop ends: no 'stop_' follows value '{value}'; \
+             this looks like a STAR/NMR-STAR file"
+        ))
+        .at_location(line, col));
+    }
+
+    let mut message = "STAR 'stop_' is not valid in CIF; loop closed at 'stop_'".to_string();
+    if discarded > 0 {
+        message.push_str(&format!(
+            " and {} nested-loop value(s) after it discarded",
+            discarded
+        ));
+    }
+    ctx.warn(message, (line, col));
+    Ok(())
+}
+
 /// Organize values into rows based on tag count.
 ///
 /// # Algorithm
@@ -107,7 +257,7 @@ fn collect_loop_values(
 ///
 /// - Total values must be divisible by tag count
 /// - Empty loops (0 values) are valid
-/// - Partial rows are rejected with [`CifError::InvalidStructure`]
+/// - Partial rows are rejected with [`CifError::LoopMismatch`]
 fn organize_loop_values(
     loop_: &mut CifLoop,
     values: Vec<CifValue>,
@@ -119,12 +269,13 @@ fn organize_loop_values(
 
     let tag_count = loop_.tags.len();
     if !values.len().is_multiple_of(tag_count) {
-        return Err(CifError::invalid_structure(format!(
-            "Loop has {} tags but {} values (not divisible)",
-            tag_count,
-            values.len()
-        ))
-        .at_location(location.0, location.1));
+        return Err(CifError::LoopMismatch {
+            tags: tag_count,
+            values: values.len(),
+            reserved_word: None,
+            location: Some(location),
+            span: None,
+        });
     }
 
     for row_values in values.chunks(tag_count) {
@@ -140,8 +291,7 @@ mod tests {
 
     #[test]
     fn test_organize_loop_values_valid() {
-        let mut loop_ = CifLoop::new();
-        loop_.tags = vec!["_col1".to_string(), "_col2".to_string()];
+        let mut loop_ = CifLoop::new(vec!["_col1".to_string(), "_col2".to_string()]);
 
         let values = vec![
             CifValue::Text("v1".to_string()),
@@ -158,8 +308,7 @@ mod tests {
 
     #[test]
     fn test_organize_loop_values_empty() {
-        let mut loop_ = CifLoop::new();
-        loop_.tags = vec!["_col1".to_string()];
+        let mut loop_ = CifLoop::new(vec!["_col1".to_string()]);
 
         organize_loop_values(&mut loop_, vec![], (1, 1)).unwrap();
         assert_eq!(loop_.len(), 0);
@@ -167,15 +316,14 @@ mod tests {
 
     #[test]
     fn test_organize_loop_values_misaligned() {
-        let mut loop_ = CifLoop::new();
-        loop_.tags = vec!["_col1".to_string(), "_col2".to_string()];
+        let mut loop_ = CifLoop::new(vec!["_col1".to_string(), "_col2".to_string()]);
 
         let values = vec![CifValue::Text("v1".to_string())]; // Only 1 value for 2 columns
 
         let result = organize_loop_values(&mut loop_, values, (42, 5));
         assert!(result.is_err());
         // Verify error includes location
-        if let Err(CifError::InvalidStructure { location, .. }) = result {
+        if let Err(CifError::LoopMismatch { location, .. }) = result {
             assert_eq!(location, Some((42, 5)));
         } else {
             panic!("Expected InvalidStructure error with location");
@@ -19,16 +19,46 @@
 //!
 //! # Module Organization
 //!
+//! - `bare`: Save frames written before any data block
+//! - `decimal_comma`: Recovering numbers written with a decimal comma
+//! - `dialect`: Hooks for non-standard tokens of vendor dialects
 //! - `helpers`: Common utility functions for parse tree traversal
+//! - `encoding`: Decoding bytes, byte-order marks, and line terminators
+//! - `incremental`: Parsing a document from a reader a piece at a time
+//! - `options`: Parser configuration ([`ParseOptions`]) and per-parse state
+//! - `parallel`: Parsing the blocks of one input on several threads
+//! - `prescan`: Lexical checks on the raw input before grammar parsing
+//! - `select`: Skipping unselected content for selective parsing
+//! - `statements`: Blocks, items, and loops from lexer tokens, for the
+//!   streaming reader and borrowed parsing
+//! - `trailer`: Non-CIF content before the first block and after the last
+//! - `stream`: Splitting concatenated CIF streams into one document per block
 //! - `value`: Parse individual CIF values
 //! - `loop_parser`: Parse loop structures
 //! - `block`: Parse data blocks and save frames
 //! - `document`: Parse complete CIF documents (entry point)
 
+pub mod bare;
 pub mod block;
+pub mod decimal_comma;
+pub mod dialect;
 pub mod document;
+pub(crate) mod encoding;
 pub mod helpers;
+pub(crate) mod incremental;
 pub mod loop_parser;
+pub mod options;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub(crate) mod prescan;
+pub(crate) mod select;
+pub(crate) mod statements;
+pub mod stream;
+mod trailer;
 pub mod value;
 
-pub use document::parse_file;
+pub use decimal_comma::DecimalComma;
+pub use dialect::{DialectAction, DialectHandler};
+pub use document::{parse_file, parse_file_with_options};
+pub use options::{DuplicateTags, ParseOptions, Selection};
+pub use stream::DocumentStream;
@@ -0,0 +1,307 @@
+//! Parser configuration and per-parse state.
+
+use crate::ast::CifVersion;
+use crate::conformance::Conformance;
+use crate::error::CifWarning;
+use crate::parser::decimal_comma::DecimalComma;
+use crate::parser::dialect::DialectHandler;
+use std::sync::Arc;
+
+/// Options controlling how CIF input is parsed.
+///
+/// The default options give **strict** parsing: anything outside the CIF
+/// specification is reported as a [`CifError`](crate::CifError). Lenient
+/// parsing recovers from known real-world problems instead, recording each
+/// recovery as a [`CifWarning`] on the resulting document. A text field
+/// left open is not recovered: it would take in the rest of the input, so it
+/// is reported as [`CifError::Unterminated`](crate::CifError::Unterminated)
+/// in either mode.
+///
+/// # Examples
+///
+/// ```
+/// use cif_parser::{Document, ParseOptions};
+///
+/// let options = ParseOptions::new().lenient(true);
+/// let doc = Document::parse_with_options("data_test\n_item value\n", &options).unwrap();
+/// assert!(doc.warnings.is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Recover from malformed input where possible, recording warnings
+    pub lenient: bool,
+    /// Parse only the selected content; all selections must match
+    pub select: Vec<Selection>,
+    /// What to do when a data item's tag is repeated in a block or frame
+    pub duplicate_tags: DuplicateTags,
+    /// Reader for non-standard tokens of a vendor dialect
+    pub dialect: Option<Arc<dyn DialectHandler>>,
+    /// Length in bytes above which text fields are kept as
+    /// [`CifValue::LargeText`](crate::CifValue::LargeText); `None` keeps
+    /// all text as ordinary strings
+    pub large_value_threshold: Option<usize>,
+    /// Which rules of the specification, beyond the grammar, are enforced
+    pub conformance: Conformance,
+    /// Accept save frames before the first data block, as in dictionary
+    /// fragments, even when not lenient
+    pub allow_bare_frames: bool,
+    /// Threads to parse data blocks on; 0 or 1 parses on the calling thread
+    pub threads: usize,
+}
+
+/// What to do with a data item whose tag already appeared in the same block
+/// or save frame (see [`ParseOptions::duplicate_tags`]).
+///
+/// Repeated tags are invalid CIF, but some old software writes
+/// `_publ_author_name` once per author instead of looping it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateTags {
+    /// Keep the last value, silently unless lenient, which records a
+    /// [`CifWarning`]
+    #[default]
+    Last,
+    /// Reject the document with a [`CifError`](crate::CifError)
+    Error,
+    /// Gather all the values, in order, into a one-column loop, recording a
+    /// [`CifWarning`]
+    Collapse,
+}
+
+/// Content to keep when parsing selectively (see [`ParseOptions::select`]).
+///
+/// Block names and tag prefixes match case-insensitively, as CIF names do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// Keep only the first data block; the rest of the input is never scanned
+    FirstBlockOnly,
+    /// Keep only blocks with these names
+    Blocks(Vec<String>),
+    /// Keep only items whose tags start with one of these prefixes, and loops
+    /// where any tag does (a kept loop keeps all of its columns)
+    TagPrefixes(Vec<String>),
+}
+
+impl ParseOptions {
+    /// Create the default (strict) options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable lenient (recovering) parsing
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Choose how repeated data item tags are handled.
+    ///
+    /// With [`DuplicateTags::Collapse`], the values end up in a one-column
+    /// loop, which [`CifBlock::get_all`](crate::CifBlock::get_all) returns
+    /// them from. Only repeated data items are collapsed: an item whose tag
+    /// is already a column of a loop in the file is kept apart from it, with
+    /// a warning.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Document, DuplicateTags, ParseOptions};
+    ///
+    /// let cif = "data_x\n_publ_author_name 'Smith, J.'\n_publ_author_name 'Jones, K.'\n";
+    /// let options = ParseOptions::new().duplicate_tags(DuplicateTags::Collapse);
+    /// let doc = Document::parse_with_options(cif, &options).unwrap();
+    /// let block = &doc.blocks[0];
+    ///
+    /// assert_eq!(block.loops[0].len(), 2);
+    /// assert_eq!(block.get_all("_publ_author_name").len(), 2);
+    /// assert_eq!(doc.warnings.len(), 1);
+    /// ```
+    pub fn duplicate_tags(mut self, policy: DuplicateTags) -> Self {
+        self.duplicate_tags = policy;
+        self
+    }
+
+    /// Parse only the selected content.
+    ///
+    /// Unselected blocks, items, and loops are skipped by a raw scan before
+    /// grammar parsing, so they cost little and are never allocated. Syntax
+    /// errors inside skipped content are not reported. Documents parsed this
+    /// way have [`partial`](crate::CifDocument::partial) set.
+    ///
+    /// Calling this more than once narrows the selection further.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Document, ParseOptions, Selection};
+    ///
+    /// let cif = "data_a\n_cell_length_a 5.0\n_title x\ndata_b\n_cell_length_a 6.0\n";
+    /// let options = ParseOptions::new()
+    ///     .select(Selection::FirstBlockOnly)
+    ///     .select(Selection::TagPrefixes(vec!["_cell_".to_string()]));
+    /// let doc = Document::parse_with_options(cif, &options).unwrap();
+    ///
+    /// assert!(doc.partial);
+    /// assert_eq!(doc.blocks.len(), 1);
+    /// assert_eq!(doc.blocks[0].items.len(), 1);
+    /// ```
+    pub fn select(mut self, selection: Selection) -> Self {
+        self.select.push(selection);
+        self
+    }
+
+    /// Read a vendor dialect's non-standard tokens with `handler`.
+    ///
+    /// See the [`dialect`](crate::parser::dialect) module for what the
+    /// handler is offered and what it can do.
+    pub fn dialect(mut self, handler: impl DialectHandler + 'static) -> Self {
+        self.dialect = Some(Arc::new(handler));
+        self
+    }
+
+    /// Keep text fields and triple-quoted strings longer than `bytes` as
+    /// [`CifValue::LargeText`](crate::CifValue::LargeText).
+    ///
+    /// Such values, typically embedded datasets like a base64-encoded map,
+    /// are copied out of the input once and then shared by every clone of
+    /// the document. They still read as text through
+    /// [`as_string`](crate::CifValue::as_string) and compare equal to the
+    /// same text; Python's `Value.to_python()` leaves them out unless read
+    /// with `Value.read_large()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Document, ParseOptions};
+    ///
+    /// let cif = "data_x\n_map_data\n;\nQUJDREVGR0g=\n;\n_title short\n";
+    /// let options = ParseOptions::new().large_value_threshold(8);
+    /// let doc = Document::parse_with_options(cif, &options).unwrap();
+    /// let block = &doc.blocks[0];
+    ///
+    /// assert!(block.get_item("_map_data").unwrap().is_large());
+    /// assert_eq!(block.get_item("_map_data").unwrap().as_string(), Some("QUJDREVGR0g="));
+    /// assert!(!block.get_item("_title").unwrap().is_large());
+    /// ```
+    pub fn large_value_threshold(mut self, bytes: usize) -> Self {
+        self.large_value_threshold = Some(bytes);
+        self
+    }
+
+    /// Enforce the rules of `conformance`.
+    ///
+    /// With [`Conformance::Strict11`], the first line, name, data name, or
+    /// value the CIF 1.1 specification does not allow is a
+    /// [`CifError::NonConformant`](crate::CifError::NonConformant), whatever
+    /// the other options say: a repeated data name is an error under any
+    /// [`duplicate_tags`](Self::duplicate_tags) policy. See the
+    /// [`conformance`](crate::conformance) module for the rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Conformance, Document, ParseOptions};
+    ///
+    /// let options = ParseOptions::new().conformance(Conformance::Strict11);
+    /// let error = Document::parse_with_options("data_a\n_x 1\n_x 2\n", &options).unwrap_err();
+    /// assert_eq!(error.code(), "E0012");
+    /// assert!(error.to_string().ends_with("ignoring case)"));
+    /// ```
+    pub fn conformance(mut self, conformance: Conformance) -> Self {
+        self.conformance = conformance;
+        self
+    }
+
+    /// Accept save frames written before any `data_` heading.
+    ///
+    /// Such frames, as in a DDLm dictionary fragment, are put in a synthetic
+    /// block named `_bare_frames_`, or after the file by
+    /// [`from_file`](crate::CifDocument::from_file), with a [`CifWarning`]
+    /// even in strict mode. The document's
+    /// [`bare_frames`](crate::CifDocument::bare_frames) flag is set, and the
+    /// frames are written back out without the heading. Lenient parsing
+    /// accepts bare frames whatever this says.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Document, ParseOptions};
+    ///
+    /// let fragment = "save_cell.length_a\n_definition.id '_cell.length_a'\nsave_\n";
+    /// assert!(Document::parse(fragment).is_err());
+    ///
+    /// let options = ParseOptions::new().allow_bare_frames(true);
+    /// let doc = Document::parse_with_options(fragment, &options).unwrap();
+    /// assert!(doc.bare_frames);
+    /// assert_eq!(doc.blocks[0].name, "_bare_frames_");
+    /// assert_eq!(doc.blocks[0].frames[0].name, "cell.length_a");
+    /// assert!(!doc.to_string().contains("data_"));
+    /// ```
+    pub fn allow_bare_frames(mut self, allow: bool) -> Self {
+        self.allow_bare_frames = allow;
+        self
+    }
+
+    /// Parse the data blocks of the input on `threads` threads.
+    ///
+    /// With the `parallel` feature, input with several data blocks is split
+    /// at `data_` lines outside text fields, into a few pieces per thread of
+    /// about the same size, which are parsed at once and joined in order.
+    /// The document is the one a parse on one thread gives, though lenient
+    /// warnings may come in a different order. If any piece fails, the whole
+    /// input is parsed again on the calling thread, so errors are the same
+    /// too. A single block cannot be split, and
+    /// [`Selection::FirstBlockOnly`] is always parsed on one thread.
+    ///
+    /// Without the feature, this has no effect. To use every CPU, pass
+    /// [`std::thread::available_parallelism`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::{Document, ParseOptions};
+    ///
+    /// let cif = "data_a\n_x 1\ndata_b\n_x 2\ndata_c\n_x 3\n";
+    /// let options = ParseOptions::new().threads(2);
+    /// let doc = Document::parse_with_options(cif, &options).unwrap();
+    /// assert_eq!(doc.blocks.len(), 3);
+    /// assert_eq!(doc.blocks[2].name, "c");
+    /// ```
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+/// State carried through a single parse: version, options, and collected warnings.
+pub(crate) struct ParseContext<'a> {
+    /// CIF version detected for the document being parsed
+    pub(crate) version: CifVersion,
+    /// Caller-supplied options
+    pub(crate) options: &'a ParseOptions,
+    /// Recoverable problems encountered so far (lenient mode only)
+    pub(crate) warnings: Vec<CifWarning>,
+    /// Values read from decimal-comma tokens so far (lenient mode only)
+    pub(crate) decimal_commas: Vec<DecimalComma>,
+}
+
+impl<'a> ParseContext<'a> {
+    pub(crate) fn new(version: CifVersion, options: &'a ParseOptions) -> Self {
+        Self {
+            version,
+            options,
+            warnings: Vec::new(),
+            decimal_commas: Vec::new(),
+        }
+    }
+
+    /// Whether the CIF 1.1 rules are enforced
+    pub(crate) fn strict_11(&self) -> bool {
+        self.options.conformance == Conformance::Strict11
+    }
+
+    /// Record a recoverable problem at the given (line, column)
+    pub(crate) fn warn(&mut self, message: impl Into<String>, location: (usize, usize)) {
+        self.warnings
+            .push(CifWarning::new(message).at_location(location.0, location.1));
+    }
+}
@@ -0,0 +1,149 @@
+//! Parsing the data blocks of one input on several threads.
+//!
+//! Blocks are independent once the version is known, so the input is cut at
+//! `data_` lines into pieces of about the same size, each holding whole
+//! blocks. The pieces are parsed on a pool of
+//! [`ParseOptions::threads`] threads and their documents joined in order.
+//!
+//! Each piece but the last also takes the heading of the block after it,
+//! so that its end is parsed as it is in the whole input (a short loop
+//! before another block is blamed on its heading, or padded leniently with
+//! a warning naming it), and the empty block it gives is dropped. Anything a piece still cannot
+//! judge alone makes the caller parse the input whole instead.
+
+use crate::ast::{CifDocument, CifVersion};
+use crate::parser::document::parse_normalized;
+use crate::parser::incremental::{append, drop_heading, heading_name, Headings};
+use crate::parser::options::{ParseOptions, Selection};
+use rayon::prelude::*;
+use std::ops::Range;
+
+/// Pieces per thread, so that a thread with small blocks can take more
+const PIECES_PER_THREAD: usize = 4;
+
+/// Whole blocks of the input, and the heading of the next one
+struct Piece {
+    range: Range<usize>,
+    /// Line of the input the piece starts on
+    first_line: usize,
+    /// Name of the block whose heading ends the piece
+    next_block: Option<String>,
+}
+
+/// Parse `input`, already normalized, on `options.threads` threads.
+///
+/// Returns `None` if the input cannot be split or a piece does not parse
+/// as it would in place, so that the caller parses it whole and reports
+/// the same errors a sequential parse would.
+pub(crate) fn parse(
+    input: &str,
+    version: CifVersion,
+    options: &ParseOptions,
+) -> Option<CifDocument> {
+    if options.select.contains(&Selection::FirstBlockOnly) {
+        return None;
+    }
+    let pieces = split(input, options.threads * PIECES_PER_THREAD);
+    if pieces.len() < 2 {
+        return None;
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()
+        .ok()?;
+    let docs: Vec<_> = pool.install(|| {
+        pieces
+            .par_iter()
+            .map(|piece| parse_normalized(&input[piece.range.clone()], version, options).ok())
+            .collect()
+    });
+
+    let last = pieces.len() - 1;
+    let mut merged: Option<CifDocument> = None;
+    for (index, (piece, doc)) in pieces.iter().zip(docs).enumerate() {
+        let mut doc = doc?;
+        if let Some(next) = &piece.next_block {
+            if !drop_heading(&mut doc, next, options) {
+                return None;
+            }
+        }
+        // Only the first piece has text before its blocks, and only the
+        // last may have text after them
+        let misplaced = (index > 0 && (doc.preamble.is_some() || doc.bare_frames))
+            || (index < last && doc.trailer.is_some());
+        // A banner can hide the magic comment from the version check
+        if misplaced || doc.version != version {
+            return None;
+        }
+        match merged.as_mut() {
+            Some(whole) => append(whole, doc, piece.first_line - 1),
+            None => merged = Some(doc),
+        }
+    }
+
+    // Checks across pieces, such as for block names used twice
+    let merged = merged?;
+    match merged.check_conformance(options.conformance).is_empty() {
+        true => Some(merged),
+        false => None,
+    }
+}
+
+/// Cut `input` at `data_` lines outside text fields and triple-quoted
+/// strings into at most about `count` pieces of about the same size
+fn split(input: &str, count: usize) -> Vec<Piece> {
+    let target = input.len() / count.max(1);
+    let mut pieces = Vec::new();
+    let mut piece = Piece {
+        range: 0..0,
+        first_line: 1,
+        next_block: None,
+    };
+    let mut headings = Headings::default();
+    let mut has_block = false;
+    let mut offset = 0;
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        if headings.is_heading(line.as_bytes()) {
+            // The first piece keeps the text before the first block
+            if has_block && offset - piece.range.start >= target {
+                piece.range.end = offset + line.len();
+                piece.next_block = Some(heading_name(line));
+                pieces.push(piece);
+                piece = Piece {
+                    range: offset..offset,
+                    first_line: index + 1,
+                    next_block: None,
+                };
+            }
+            has_block = true;
+        }
+        offset += line.len();
+    }
+    piece.range.end = input.len();
+    pieces.push(piece);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_keeps_text_fields_whole() {
+        let input = "#\\#CIF_2.0\ndata_a\n_x\n;\ndata_inside\n;\ndata_b\n_y 2\ndata_c\n_z 3\n";
+        let pieces = split(input, 8);
+        let starts: Vec<_> = pieces
+            .iter()
+            .map(|piece| (&input[piece.range.clone()][..6], piece.first_line))
+            .collect();
+        assert_eq!(starts, [("#\\#CIF", 1), ("data_b", 7), ("data_c", 9)]);
+        // Each piece ends with the next one's heading
+        assert!(input[pieces[0].range.clone()].ends_with(";\ndata_b\n"));
+        assert_eq!(pieces[1].next_block.as_deref(), Some("c"));
+        assert_eq!(pieces.last().unwrap().range.end, input.len());
+
+        // Small blocks are grouped to reach the target size
+        assert_eq!(split(input, 2).len(), 2);
+        assert_eq!(split(input, 1).len(), 1);
+    }
+}
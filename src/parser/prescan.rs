@@ -0,0 +1,405 @@
+//! Lexical pre-scans run over the raw input before the grammar.
+//!
+//! These checks need to see characters where a token can appear without
+//! building a parse tree, so they share a small scanner that skips comments,
+//! quoted strings, and text fields.
+
+use crate::error::CifError;
+use crate::lexer::Tokenizer;
+use crate::parser::document::MAX_NESTING_DEPTH;
+use crate::parser::options::ParseContext;
+
+/// A character outside comments, quoted strings, and text fields
+struct BareChar {
+    /// Byte offset into the input
+    index: usize,
+    ch: char,
+    /// 1-indexed line
+    line: usize,
+    /// 1-indexed column, counted in characters
+    column: usize,
+    /// Whether a value could start here
+    at_token_start: bool,
+}
+
+/// Name of a Unicode space character that CIF does not treat as whitespace.
+///
+/// Covers the Unicode space separators (category Zs) other than U+0020, plus
+/// the zero-width spaces word processors insert.
+fn unicode_space_name(ch: char) -> Option<&'static str> {
+    let name = match ch {
+        '\u{00A0}' => "NO-BREAK SPACE",
+        '\u{1680}' => "OGHAM SPACE MARK",
+        '\u{2000}' => "EN QUAD",
+        '\u{2001}' => "EM QUAD",
+        '\u{2002}' => "EN SPACE",
+        '\u{2003}' => "EM SPACE",
+        '\u{2004}' => "THREE-PER-EM SPACE",
+        '\u{2005}' => "FOUR-PER-EM SPACE",
+        '\u{2006}' => "SIX-PER-EM SPACE",
+        '\u{2007}' => "FIGURE SPACE",
+        '\u{2008}' => "PUNCTUATION SPACE",
+        '\u{2009}' => "THIN SPACE",
+        '\u{200A}' => "HAIR SPACE",
+        '\u{200B}' => "ZERO WIDTH SPACE",
+        '\u{202F}' => "NARROW NO-BREAK SPACE",
+        '\u{205F}' => "MEDIUM MATHEMATICAL SPACE",
+        '\u{2060}' => "WORD JOINER",
+        '\u{3000}' => "IDEOGRAPHIC SPACE",
+        '\u{FEFF}' => "ZERO WIDTH NO-BREAK SPACE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Whether `ch` separates tokens, counting Unicode spaces so that a stray
+/// U+00A0 before a quote or comment does not hide it from the scanner
+fn is_separator(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r') || unicode_space_name(ch).is_some()
+}
+
+/// Visit every character outside comments, quoted strings, and text fields.
+///
+/// Line terminators are consumed by the scanner and not visited. A text
+/// field opened at the start of a line and never closed is a
+/// [`CifError::Unterminated`] at its `;`, which the grammar would otherwise
+/// read as ordinary values.
+fn scan_bare(
+    input: &str,
+    mut visit: impl FnMut(BareChar) -> Result<(), CifError>,
+) -> Result<(), CifError> {
+    let mut chars = input.char_indices().peekable();
+    let mut line = 1;
+    let mut column = 0;
+    let mut prev: Option<char> = None;
+    // The line terminator before the last line starting with ';', past which
+    // no text field can close
+    let last_close = input
+        .rmatch_indices(['\n', '\r'])
+        .find(|&(end, _)| input[end + 1..].starts_with(';'))
+        .map_or(0, |(end, _)| end);
+
+    // Advance past a line terminator that has just been consumed as `ch`
+    macro_rules! newline {
+        ($ch:expr) => {
+            if $ch == '\r' && chars.peek().map(|&(_, c)| c) == Some('\n') {
+                chars.next();
+            }
+            line += 1;
+            column = 0;
+        };
+    }
+
+    while let Some((index, ch)) = chars.next() {
+        column += 1;
+        let at_token_start = prev.is_none_or(|p| is_separator(p) || matches!(p, '[' | '{' | ':'));
+
+        match ch {
+            '\n' | '\r' => {
+                newline!(ch);
+                prev = Some('\n');
+                continue;
+            }
+            // Text field: skip to the next line starting with ';'. As in the
+            // grammar, a value starting with ';' later in a line opens one
+            // too, if one is closed after it
+            ';' if column == 1 || (at_token_start && index < last_close) => {
+                let opened = (line, column);
+                let mut closed = false;
+                while let Some((_, c)) = chars.next() {
+                    column += 1;
+                    if c == '\n' || c == '\r' {
+                        newline!(c);
+                        if chars.peek().map(|&(_, c)| c) == Some(';') {
+                            chars.next();
+                            column = 1;
+                            closed = true;
+                            break;
+                        }
+                    }
+                }
+                if !closed {
+                    return Err(CifError::Unterminated {
+                        construct: "text field".to_string(),
+                        location: Some(opened),
+                        span: None,
+                    });
+                }
+            }
+            '#' if at_token_start => {
+                while chars.peek().is_some_and(|&(_, c)| c != '\n' && c != '\r') {
+                    chars.next();
+                }
+            }
+            '\'' | '"' if at_token_start => {
+                let quote = ch;
+                // As in the grammar, three quotes with no three to close them
+                // open an ordinary quoted string
+                let delimiter = if quote == '"' { "\"\"\"" } else { "'''" };
+                let triple =
+                    input[index..].starts_with(delimiter) && input[index + 3..].contains(delimiter);
+                if triple {
+                    // Triple-quoted string: may span lines, ends at the next triple quote
+                    chars.next();
+                    chars.next();
+                    column += 2;
+                    let mut run = 0;
+                    while let Some((_, c)) = chars.next() {
+                        column += 1;
+                        if c == '\n' || c == '\r' {
+                            newline!(c);
+                        }
+                        run = if c == quote { run + 1 } else { 0 };
+                        if run == 3 {
+                            break;
+                        }
+                    }
+                } else {
+                    // Quoted string: ends at the same quote followed by a delimiter,
+                    // or unterminated at the end of the line
+                    while let Some(&(_, c)) = chars.peek() {
+                        if c == '\n' || c == '\r' {
+                            break;
+                        }
+                        chars.next();
+                        column += 1;
+                        let next = chars.peek().map(|&(_, c)| c);
+                        if c == quote
+                            && next.is_none_or(|n| is_separator(n) || matches!(n, ']' | '}' | ':'))
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => visit(BareChar {
+                index,
+                ch,
+                line,
+                column,
+                at_token_start,
+            })?,
+        }
+        prev = Some(ch);
+    }
+
+    Ok(())
+}
+
+/// Reject input whose list/table nesting exceeds [`MAX_NESTING_DEPTH`].
+///
+/// Brackets count only where a value can start (after whitespace, another
+/// bracket, or a table colon), so ordinary text containing brackets is never
+/// mistaken for nesting.
+pub(crate) fn check_nesting_depth(input: &str) -> Result<(), CifError> {
+    let mut depth = 0usize;
+    scan_bare(input, |bare| {
+        match bare.ch {
+            '[' | '{' if bare.at_token_start => {
+                depth += 1;
+                if depth > MAX_NESTING_DEPTH {
+                    return Err(CifError::NestingTooDeep {
+                        max_depth: MAX_NESTING_DEPTH,
+                        location: Some((bare.line, bare.column)),
+                        span: None,
+                    });
+                }
+            }
+            ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        Ok(())
+    })
+}
+
+/// Handle Unicode space characters used where CIF expects whitespace.
+///
+/// Only space and tab are whitespace in CIF, so a U+00A0 pasted in from a word
+/// processor silently becomes part of an unquoted value or tag. Characters
+/// inside comments, quoted strings, and text fields are content and left alone.
+///
+/// Strict mode reports the first offending character. Lenient mode replaces
+/// each with an ordinary space, records a warning, and returns the normalized
+/// input (or `None` when nothing needed replacing).
+pub(crate) fn normalize_unicode_spaces(
+    input: &str,
+    ctx: &mut ParseContext,
+) -> Result<Option<String>, CifError> {
+    let mut found = Vec::new();
+    scan_bare(input, |bare| {
+        // A leading byte-order mark is allowed by the specification
+        if bare.index == 0 && bare.ch == '\u{FEFF}' {
+            return Ok(());
+        }
+        let Some(name) = unicode_space_name(bare.ch) else {
+            return Ok(());
+        };
+        if !ctx.options.lenient {
+            return Err(unicode_space_error(bare.ch, name, (bare.line, bare.column)));
+        }
+        let codepoint = format!("U+{:04X} {}", bare.ch as u32, name);
+        ctx.warn(
+            format!("{codepoint} used as whitespace; replaced with a space"),
+            (bare.line, bare.column),
+        );
+        found.push((bare.index, bare.ch.len_utf8()));
+        Ok(())
+    })?;
+
+    if found.is_empty() {
+        return Ok(None);
+    }
+
+    let mut normalized = String::with_capacity(input.len());
+    let mut last = 0;
+    for (index, len) in found {
+        normalized.push_str(&input[last..index]);
+        normalized.push(' ');
+        last = index + len;
+    }
+    normalized.push_str(&input[last..]);
+    Ok(Some(normalized))
+}
+
+/// Reject a Unicode space in `text`, a token outside quotes and text fields
+/// starting at `at`, as strict parsing does
+pub(crate) fn check_unicode_spaces(text: &str, at: (usize, usize)) -> Result<(), CifError> {
+    for (offset, ch) in text.chars().enumerate() {
+        if let Some(name) = unicode_space_name(ch) {
+            return Err(unicode_space_error(ch, name, (at.0, at.1 + offset)));
+        }
+    }
+    Ok(())
+}
+
+fn unicode_space_error(ch: char, name: &str, at: (usize, usize)) -> CifError {
+    CifError::invalid_structure(format!(
+        "U+{:04X} {name} is not whitespace in CIF; only space and tab separate tokens",
+        ch as u32
+    ))
+    .at_location(at.0, at.1)
+}
+
+/// Close quoted strings left open at the end of their line, in lenient mode.
+///
+/// A quoted string cannot span lines, so `_x 'abc` followed by a newline is a
+/// syntax error in strict mode. In lenient mode the missing quote is added
+/// after the last non-blank character of the line, with a warning. Returns
+/// `None` if no quote was added.
+pub(crate) fn close_open_quotes(input: &str, ctx: &mut ParseContext) -> Option<String> {
+    let mut closed = String::new();
+    // Tokenizing resumes after each added quote, where the rest of the line is
+    // blank, so every line is read once
+    let mut from = 0;
+    let mut first_line = 1;
+    loop {
+        let text = &input[from..];
+        let Some(error) = Tokenizer::new(text).find_map(Result::err) else {
+            break;
+        };
+        let (
+            CifError::Unterminated {
+                construct,
+                location: Some((line, col)),
+                ..
+            },
+            Some(start),
+        ) = (&error, error.offset_in(text))
+        else {
+            break;
+        };
+        // Triple-quoted strings may span lines, so their end cannot be guessed
+        let rest = &text[start..];
+        if construct != "quoted string" || rest.starts_with("'''") || rest.starts_with("\"\"\"") {
+            break;
+        }
+        let quote = &rest[..1];
+        let line_text = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let end = start + line_text.trim_end_matches([' ', '\t']).len();
+        first_line += line - 1;
+        ctx.warn(
+            format!(
+                "Quoted string is not closed on its line; {quote} added at the end of the line"
+            ),
+            (first_line, *col),
+        );
+        closed.push_str(&text[..end]);
+        closed.push_str(quote);
+        from += end;
+    }
+    if from == 0 {
+        return None;
+    }
+    closed.push_str(&input[from..]);
+    Some(closed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::CifVersion;
+    use crate::parser::options::ParseOptions;
+
+    fn bare_chars(input: &str) -> String {
+        let mut out = String::new();
+        scan_bare(input, |bare| {
+            out.push(bare.ch);
+            Ok(())
+        })
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_scan_skips_non_bare_regions() {
+        assert_eq!(bare_chars("_a 'x y' b # note\n_c"), "_a  b _c");
+        assert_eq!(bare_chars("_a\n;text\n[\n;\n_b"), "_a_b");
+        assert_eq!(bare_chars("_a '''x\n'y'\n''' b"), "_a  b");
+        assert_eq!(bare_chars("_a it's\n"), "_a it's");
+    }
+
+    #[test]
+    fn test_lenient_normalization_positions() {
+        let options = ParseOptions::new().lenient(true);
+        let mut ctx = ParseContext::new(CifVersion::V1_1, &options);
+        let input = "data_a\n_x\u{a0}1\n_y 'b\u{a0}c'\n";
+        let normalized = normalize_unicode_spaces(input, &mut ctx).unwrap();
+        assert_eq!(normalized.as_deref(), Some("data_a\n_x 1\n_y 'b\u{a0}c'\n"));
+        assert_eq!(ctx.warnings.len(), 1);
+        assert_eq!(ctx.warnings[0].location, Some((2, 3)));
+    }
+
+    #[test]
+    fn test_clean_input_is_not_copied() {
+        let options = ParseOptions::new().lenient(true);
+        let mut ctx = ParseContext::new(CifVersion::V1_1, &options);
+        let input = "\u{feff}data_a\n_x\t1\n";
+        assert!(normalize_unicode_spaces(input, &mut ctx).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_open_quotes_closed_at_line_end() {
+        let options = ParseOptions::new().lenient(true);
+        let mut ctx = ParseContext::new(CifVersion::V1_1, &options);
+        let input = "data_a\n_x 'abc  \n_y \"d e\n_z '''f\n";
+        let closed = close_open_quotes(input, &mut ctx);
+        assert_eq!(
+            closed.as_deref(),
+            Some("data_a\n_x 'abc'  \n_y \"d e\"\n_z '''f\n")
+        );
+        assert_eq!(ctx.warnings.len(), 2);
+        assert_eq!(ctx.warnings[1].location, Some((3, 4)));
+        assert!(close_open_quotes("data_a\n_x 'b'\n", &mut ctx).is_none());
+    }
+
+    #[test]
+    fn test_many_open_quotes_closed_in_one_pass() {
+        let options = ParseOptions::new().lenient(true);
+        let mut ctx = ParseContext::new(CifVersion::V1_1, &options);
+        let input = format!("data_a\n{}", "_x 'abc\n".repeat(8000));
+        let closed = close_open_quotes(&input, &mut ctx).unwrap();
+        assert_eq!(closed, format!("data_a\n{}", "_x 'abc'\n".repeat(8000)));
+        assert_eq!(ctx.warnings.len(), 8000);
+        assert_eq!(ctx.warnings[7999].location, Some((8001, 4)));
+    }
+}
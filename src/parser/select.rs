@@ -0,0 +1,392 @@
+//! Selective parsing: skipping unselected content before the grammar runs.
+//!
+//! [`reduce`] runs the [`lexer`] over the raw input and copies only the selected
+//! blocks, items, and loops into a smaller input for the grammar. Skipped
+//! content is replaced by the line breaks it spanned, so line and column
+//! numbers in errors and warnings still refer to the original input.
+//!
+//! The scanner only finds where each value ends; it never builds values. When
+//! it meets input it cannot tokenize (usually a syntax error), it gives up and
+//! the full input is parsed instead. [`apply`] then trims the parsed document,
+//! so the result is the same either way.
+
+use crate::ast::{CifBlock, CifDocument, CifLoop, CifVersion};
+use crate::lexer::{self, TokenKind, Tokenizer};
+use crate::parser::document::detect_version;
+use crate::parser::options::Selection;
+
+/// Whether the block at `index` (in file order) named `name` is selected
+pub(crate) fn keeps_block(select: &[Selection], index: usize, name: &str) -> bool {
+    select.iter().all(|selection| match selection {
+        Selection::FirstBlockOnly => index == 0,
+        Selection::Blocks(names) => names.iter().any(|n| n.eq_ignore_ascii_case(name)),
+        Selection::TagPrefixes(_) => true,
+    })
+}
+
+/// Whether an item or loop column with this tag is selected
+pub(crate) fn keeps_tag(select: &[Selection], tag: &str) -> bool {
+    select.iter().all(|selection| match selection {
+        Selection::TagPrefixes(prefixes) => prefixes.iter().any(|prefix| {
+            tag.as_bytes()
+                .get(..prefix.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(prefix.as_bytes()))
+        }),
+        _ => true,
+    })
+}
+
+fn keeps_loop(select: &[Selection], loop_: &CifLoop) -> bool {
+    loop_.tags.iter().any(|tag| keeps_tag(select, tag))
+}
+
+/// Remove unselected content from a parsed document and mark it partial
+pub(crate) fn apply(doc: &mut CifDocument, select: &[Selection]) {
+    let mut index = 0;
+    doc.blocks.retain(|block| {
+        index += 1;
+        keeps_block(select, index - 1, &block.name)
+    });
+
+    if select
+        .iter()
+        .any(|s| matches!(s, Selection::TagPrefixes(_)))
+    {
+        for block in &mut doc.blocks {
+            trim_block(block, select);
+        }
+    }
+    doc.partial = true;
+}
+
+fn trim_block(block: &mut CifBlock, select: &[Selection]) {
+    block.items.retain(|tag, _| keeps_tag(select, tag));
+    block.loops.retain(|loop_| keeps_loop(select, loop_));
+    for frame in &mut block.frames {
+        frame.items.retain(|tag, _| keeps_tag(select, tag));
+        frame.loops.retain(|loop_| keeps_loop(select, loop_));
+    }
+    block
+        .frames
+        .retain(|frame| !frame.items.is_empty() || !frame.loops.is_empty());
+}
+
+/// Copy the selected content of `input` into a smaller input with the same
+/// line numbering, or None if the input could not be scanned.
+pub(crate) fn reduce(input: &str, version: CifVersion, select: &[Selection]) -> Option<String> {
+    let mut reducer = Reducer::new(input);
+    reducer.run(version, select).ok()?;
+    Some(reducer.out)
+}
+
+/// The input could not be tokenized; parse all of it instead
+struct Unscannable;
+
+/// Byte span of a statement-level token (a whole list or table counts as
+/// one) and where it starts
+#[derive(Clone, Copy)]
+struct Token {
+    start: usize,
+    end: usize,
+    line: usize,
+    /// 1-indexed column, counted in characters
+    col: usize,
+}
+
+/// A reserved word at the start of a bare token
+#[derive(PartialEq)]
+enum Keyword {
+    Data,
+    Global,
+    Save,
+    Loop,
+    Stop,
+}
+
+fn keyword(text: &str) -> Option<Keyword> {
+    let starts = |word: &str| {
+        text.as_bytes()
+            .get(..word.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(word.as_bytes()))
+    };
+    if starts("data_") {
+        Some(Keyword::Data)
+    } else if starts("save_") {
+        Some(Keyword::Save)
+    } else if text.eq_ignore_ascii_case("loop_") {
+        Some(Keyword::Loop)
+    } else if text.eq_ignore_ascii_case("global_") {
+        Some(Keyword::Global)
+    } else if text.eq_ignore_ascii_case("stop_") {
+        Some(Keyword::Stop)
+    } else {
+        None
+    }
+}
+
+/// Whether a token at a value position ends the current statement instead
+fn ends_values(text: &str) -> bool {
+    text.starts_with('_') || keyword(text).is_some()
+}
+
+/// Number of line breaks in `text`, counting `\r\n` once
+fn line_breaks(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|&(i, &b)| b == b'\n' || (b == b'\r' && bytes.get(i + 1) != Some(&b'\n')))
+        .count()
+}
+
+/// Groups the lexer's tokens into statement-level tokens
+struct Scanner<'a> {
+    tokens: Tokenizer<'a>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        Scanner {
+            tokens: Tokenizer::new(input).skip_trivia(true),
+        }
+    }
+
+    fn lex(&mut self) -> Result<Option<lexer::Token<'a>>, Unscannable> {
+        self.tokens.next().transpose().map_err(|_| Unscannable)
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, Unscannable> {
+        let Some(first) = self.lex()? else {
+            return Ok(None);
+        };
+        let mut token = Token {
+            start: first.byte_range.start,
+            end: first.byte_range.end,
+            line: first.line,
+            col: first.col,
+        };
+        if first.kind != TokenKind::Delimiter {
+            return Ok(Some(token));
+        }
+
+        // A list or table, including anything nested inside it
+        let mut depth = 0usize;
+        let mut next = Some(first);
+        while let Some(inner) = next {
+            match inner.text {
+                "[" | "{" => depth += 1,
+                "]" | "}" => depth = depth.checked_sub(1).ok_or(Unscannable)?,
+                _ => {}
+            }
+            token.end = inner.byte_range.end;
+            if depth == 0 {
+                return Ok(Some(token));
+            }
+            next = self.lex()?;
+        }
+        Err(Unscannable)
+    }
+}
+
+/// Walks the token stream statement by statement, copying selected ones
+struct Reducer<'a> {
+    input: &'a str,
+    scanner: Scanner<'a>,
+    peeked: Option<Token>,
+    out: String,
+    /// Line the end of `out` is on (1-indexed, as in the input)
+    out_line: usize,
+    /// Characters in `out` since its last line break
+    out_column: usize,
+}
+
+impl<'a> Reducer<'a> {
+    fn new(input: &'a str) -> Self {
+        Reducer {
+            input,
+            scanner: Scanner::new(input),
+            peeked: None,
+            out: String::new(),
+            out_line: 1,
+            out_column: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, Unscannable> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.scanner.next(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<Token>, Unscannable> {
+        if self.peeked.is_none() {
+            self.peeked = self.scanner.next()?;
+        }
+        Ok(self.peeked)
+    }
+
+    fn text(&self, token: Token) -> &'a str {
+        &self.input[token.start..token.end]
+    }
+
+    /// Copy input from the start of `from` to `end`, at its original line and column
+    fn emit(&mut self, from: Token, end: usize) {
+        while self.out_line < from.line {
+            self.out.push('\n');
+            self.out_line += 1;
+            self.out_column = 0;
+        }
+        let column = from.col - 1;
+        for _ in self.out_column..column {
+            self.out.push(' ');
+        }
+
+        let text = &self.input[from.start..end];
+        self.out.push_str(text);
+        let breaks = line_breaks(text);
+        self.out_line += breaks;
+        self.out_column = match text.rfind(['\n', '\r']) {
+            Some(last) if breaks > 0 => text[last + 1..].chars().count(),
+            _ => column + text.chars().count(),
+        };
+    }
+
+    fn run(&mut self, version: CifVersion, select: &[Selection]) -> Result<(), Unscannable> {
+        // Keep a byte-order mark and the CIF 2.0 magic comment on the first
+        // line, if it is there: a piece of a CIF 2.0 file parsed on its own
+        // thread starts with a block
+        let bom = self.input.starts_with('\u{FEFF}');
+        if bom {
+            self.out.push('\u{FEFF}');
+            self.out_column = 1;
+        }
+        if version == CifVersion::V2_0 && detect_version(self.input) == CifVersion::V2_0 {
+            let start = if bom { '\u{FEFF}'.len_utf8() } else { 0 };
+            let end = self.input[start..]
+                .find(['\n', '\r'])
+                .map_or(self.input.len(), |i| start + i);
+            let heading = Token {
+                start,
+                end,
+                line: 1,
+                col: 1 + usize::from(bom),
+            };
+            self.emit(heading, end);
+        }
+
+        let first_only = select.contains(&Selection::FirstBlockOnly);
+        let mut blocks_seen = 0;
+        let mut keep_block = false;
+
+        while let Some(token) = self.next()? {
+            let text = self.text(token);
+            match keyword(text) {
+                Some(heading @ (Keyword::Data | Keyword::Global)) => {
+                    if first_only && blocks_seen > 0 {
+                        // Nothing after the first block can be selected
+                        break;
+                    }
+                    let name = if heading == Keyword::Data {
+                        &text["data_".len()..]
+                    } else {
+                        ""
+                    };
+                    keep_block = keeps_block(select, blocks_seen, name);
+                    blocks_seen += 1;
+                    if keep_block {
+                        self.emit(token, token.end);
+                    }
+                }
+                Some(Keyword::Save) if blocks_seen > 0 => {
+                    if keep_block {
+                        self.emit(token, token.end);
+                    }
+                }
+                Some(Keyword::Loop) if blocks_seen > 0 => self.loop_(token, keep_block, select)?,
+                None if text.starts_with('_') && blocks_seen > 0 => {
+                    let value = self.next()?.ok_or(Unscannable)?;
+                    if ends_values(self.text(value)) {
+                        return Err(Unscannable);
+                    }
+                    if keep_block && keeps_tag(select, text) {
+                        self.emit(token, value.end);
+                    }
+                }
+                // `stop_`, content outside a block, or a stray value
+                _ => return Err(Unscannable),
+            }
+        }
+        Ok(())
+    }
+
+    /// A loop from `loop_` to its last value, copied whole if any tag is selected
+    fn loop_(
+        &mut self,
+        start: Token,
+        keep_block: bool,
+        select: &[Selection],
+    ) -> Result<(), Unscannable> {
+        let mut keep = false;
+        let mut end = None;
+        while let Some(tag) = self.peek()?.filter(|t| self.text(*t).starts_with('_')) {
+            self.peeked = None;
+            keep |= keeps_tag(select, self.text(tag));
+            end = Some(tag.end);
+        }
+        let mut end = end.ok_or(Unscannable)?;
+
+        // Values run until the next tag or keyword; these are skipped unseen
+        while let Some(value) = self.peek()?.filter(|t| !ends_values(self.text(*t))) {
+            self.peeked = None;
+            end = value.end;
+        }
+
+        if keep_block && keep {
+            self.emit(start, end);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<&str> {
+        let mut scanner = Scanner::new(input);
+        let mut out = Vec::new();
+        while let Ok(Some(token)) = scanner.next() {
+            out.push(&input[token.start..token.end]);
+        }
+        out
+    }
+
+    #[test]
+    fn test_scanner_token_boundaries() {
+        assert_eq!(tokens("_a 'x y' b # c\n"), ["_a", "'x y'", "b"]);
+        assert_eq!(tokens("_a 'it's' \"q\""), ["_a", "'it's'", "\"q\""]);
+        assert_eq!(tokens("_a\n;x\n y\n;\n_b"), ["_a", ";x\n y\n;", "_b"]);
+        assert_eq!(tokens("_a '''x\n'y''' _b"), ["_a", "'''x\n'y'''", "_b"]);
+        assert_eq!(
+            tokens("_a [1 'b c' {'k':[2]}] _b"),
+            ["_a", "[1 'b c' {'k':[2]}]", "_b"]
+        );
+    }
+
+    #[test]
+    fn test_reduce_keeps_line_numbers() {
+        let input = "data_a\n_x 1\nloop_\n_y\n1\n2\n_keep 'v'\n";
+        let select = [Selection::TagPrefixes(vec!["_keep".to_string()])];
+        let reduced = reduce(input, CifVersion::V1_1, &select).unwrap();
+        assert_eq!(reduced, "data_a\n\n\n\n\n\n_keep 'v'");
+    }
+
+    #[test]
+    fn test_reduce_gives_up_on_bad_input() {
+        let select = [Selection::FirstBlockOnly];
+        assert!(reduce("data_a\n_x 'open\n", CifVersion::V1_1, &select).is_none());
+        assert!(reduce("_x 1\n", CifVersion::V1_1, &select).is_none());
+        assert!(reduce("data_a\nloop_\n_x\n1\nstop_\n", CifVersion::V1_1, &select).is_none());
+    }
+}
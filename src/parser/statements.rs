@@ -0,0 +1,591 @@
+//! Blocks, items, and loops from the lexer's tokens, without the grammar.
+//!
+//! [`CifReader`](crate::reader::CifReader) and
+//! [`CifDocumentRef::parse`](crate::CifDocumentRef::parse) read the
+//! [`lexer`](crate::lexer)'s tokens rather than the grammar's parse tree.
+//! [`Statements`] is the state machine they share: it checks that tokens come
+//! in a valid order, applies a [`Selection`], and hands what it finds to a
+//! [`Sink`], which gives events for the one and a borrowed document for the
+//! other. Lists and tables are read from their tokens by [`compound`].
+
+use crate::ast::CifVersion;
+use crate::borrowed::CifValueRef;
+use crate::error::CifError;
+use crate::lexer::{Token, TokenKind, Tokenizer};
+use crate::parser::encoding::normalize_line_terminators;
+use crate::parser::options::Selection;
+use crate::parser::prescan::check_unicode_spaces;
+use crate::parser::select::{keeps_block, keeps_tag};
+use crate::parser::value::{table_key_ref, token_value_ref};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::mem;
+
+/// Receiver of the content [`Statements`] finds, in file order.
+///
+/// Each `block_start` is matched by a `block_end`, each `frame_start` by a
+/// `frame_end`, and each `loop_start` by a `loop_end`, with the loop's rows
+/// in between. Unselected content is not handed over.
+pub(crate) trait Sink {
+    /// A block or frame name (without `data_` or `save_`), or a tag
+    type Name: AsRef<str>;
+    type Value;
+
+    fn block_start(&mut self, name: Self::Name);
+    fn block_end(&mut self);
+    fn frame_start(&mut self, name: Self::Name);
+    fn frame_end(&mut self);
+    fn item(&mut self, tag: Self::Name, value: Self::Value);
+    fn loop_start(&mut self, tags: Vec<Self::Name>);
+    fn loop_row(&mut self, row: Vec<Self::Value>);
+    fn loop_end(&mut self);
+}
+
+/// Where the reading is between blocks, items, and loops
+enum State<N, V> {
+    /// Expecting a tag, `loop_`, or a block or frame heading
+    Idle,
+    /// A tag waiting for its value
+    Item {
+        tag: N,
+        keep: bool,
+        at: (usize, usize),
+    },
+    /// Reading the tags after `loop_`
+    LoopTags { tags: Vec<N>, at: (usize, usize) },
+    /// Reading a loop's values
+    LoopValues {
+        width: usize,
+        keep: bool,
+        /// The row being filled, when the loop is kept
+        row: Vec<V>,
+        /// Values read so far
+        values: usize,
+    },
+}
+
+/// The state machine turning tokens into blocks, items, and loops.
+///
+/// The caller feeds it tokens with their position in the whole input: value
+/// tokens, and lists and tables read by [`compound`], through
+/// [`value`](Statements::value), and everything else through
+/// [`token`](Statements::token). The first error ends the reading.
+pub(crate) struct Statements<S: Sink> {
+    pub(crate) sink: S,
+    pub(crate) version: CifVersion,
+    pub(crate) select: Vec<Selection>,
+    state: State<S::Name, S::Value>,
+    blocks_seen: usize,
+    in_block: bool,
+    keep_block: bool,
+    /// The open save frame's name and where it began
+    frame: Option<(String, (usize, usize))>,
+    /// Set at a second block when only the first is selected
+    pub(crate) done: bool,
+}
+
+impl<S: Sink> Statements<S> {
+    pub(crate) fn new(sink: S, version: CifVersion) -> Self {
+        Statements {
+            sink,
+            version,
+            select: Vec::new(),
+            state: State::Idle,
+            blocks_seen: 0,
+            in_block: false,
+            keep_block: false,
+            frame: None,
+            done: false,
+        }
+    }
+
+    /// Take a token that is not a value or the start of a list or table;
+    /// `name` makes a name or tag from its text
+    pub(crate) fn token<'t>(
+        &mut self,
+        token: &Token<'t>,
+        at: (usize, usize),
+        name: impl FnOnce(&'t str) -> S::Name,
+    ) -> Result<(), CifError> {
+        match token.kind {
+            TokenKind::Tag => self.tag(name(token.text), at),
+            TokenKind::LoopKeyword if token.text.eq_ignore_ascii_case("stop_") => {
+                Err(CifError::invalid_structure(
+                    "Nested loops are not valid in CIF; this looks like a STAR/NMR-STAR file (found 'stop_')",
+                )
+                .at_location(at.0, at.1))
+            }
+            TokenKind::LoopKeyword => {
+                self.end_statement(Some(token.text), at)?;
+                self.require_block(token.text, at)?;
+                self.state = State::LoopTags {
+                    tags: Vec::new(),
+                    at,
+                };
+                Ok(())
+            }
+            TokenKind::BlockHeader => {
+                let heading = token.text;
+                let text = if heading.eq_ignore_ascii_case("global_") {
+                    &heading[heading.len()..]
+                } else if heading.len() == 5 && self.version == CifVersion::V2_0 {
+                    return Err(CifError::invalid_structure(
+                        "Empty data block name not allowed in CIF 2.0 (use 'global_' for global blocks)",
+                    )
+                    .at_location(at.0, at.1));
+                } else {
+                    &heading[5..]
+                };
+                self.block(heading, name(text), at)
+            }
+            TokenKind::SaveHeader => {
+                self.end_statement(Some(token.text), at)?;
+                self.require_block(token.text, at)?;
+                if let Some((name, _)) = &self.frame {
+                    return Err(CifError::invalid_structure(format!(
+                        "Save frame '{name}' is not closed by 'save_' before the next one"
+                    ))
+                    .at_location(at.0, at.1));
+                }
+                let text = &token.text[5..];
+                self.frame = Some((text.to_string(), at));
+                if self.keep_block {
+                    self.sink.frame_start(name(text));
+                }
+                Ok(())
+            }
+            TokenKind::SaveEnd => {
+                self.end_statement(Some(token.text), at)?;
+                if self.frame.take().is_none() {
+                    return Err(
+                        CifError::invalid_structure("'save_' does not close a save frame")
+                            .at_location(at.0, at.1),
+                    );
+                }
+                if self.keep_block {
+                    self.sink.frame_end();
+                }
+                Ok(())
+            }
+            TokenKind::Delimiter => Err(CifError::invalid_structure(format!(
+                "'{}' does not close a list or table",
+                token.text
+            ))
+            .at_location(at.0, at.1)),
+            TokenKind::QuotedString | TokenKind::TextField | TokenKind::UnquotedValue => {
+                unreachable!("values are taken by Statements::value")
+            }
+            TokenKind::Comment | TokenKind::Whitespace => Ok(()),
+        }
+    }
+
+    fn require_block(&self, text: &str, at: (usize, usize)) -> Result<(), CifError> {
+        if self.in_block {
+            Ok(())
+        } else {
+            Err(
+                CifError::invalid_structure(format!("'{text}' comes before the first data block"))
+                    .at_location(at.0, at.1),
+            )
+        }
+    }
+
+    fn tag(&mut self, tag: S::Name, at: (usize, usize)) -> Result<(), CifError> {
+        if let State::LoopTags { tags, .. } = &mut self.state {
+            tags.push(tag);
+            return Ok(());
+        }
+        self.end_statement(Some(tag.as_ref()), at)?;
+        self.require_block(tag.as_ref(), at)?;
+        self.state = State::Item {
+            keep: self.keep_block && keeps_tag(&self.select, tag.as_ref()),
+            tag,
+            at,
+        };
+        Ok(())
+    }
+
+    /// Take a value; `make` builds it, and is not called for skipped content
+    pub(crate) fn value(
+        &mut self,
+        text: &str,
+        at: (usize, usize),
+        make: impl FnOnce() -> Result<S::Value, CifError>,
+    ) -> Result<(), CifError> {
+        if matches!(self.state, State::LoopTags { .. }) {
+            self.start_loop()?;
+        }
+        match &mut self.state {
+            State::Idle => Err(CifError::invalid_structure(format!(
+                "'{}' is a value with no data name before it",
+                first_line_of(text)
+            ))
+            .at_location(at.0, at.1)),
+            State::Item { .. } => {
+                let State::Item { tag, keep, .. } = mem::replace(&mut self.state, State::Idle)
+                else {
+                    unreachable!("matched above")
+                };
+                if keep {
+                    let value = make()?;
+                    self.sink.item(tag, value);
+                }
+                Ok(())
+            }
+            State::LoopValues {
+                width,
+                keep,
+                row,
+                values,
+            } => {
+                *values += 1;
+                if *keep {
+                    row.push(make()?);
+                    if row.len() == *width {
+                        let row = mem::replace(row, Vec::with_capacity(*width));
+                        self.sink.loop_row(row);
+                    }
+                }
+                Ok(())
+            }
+            State::LoopTags { .. } => unreachable!("the loop was started above"),
+        }
+    }
+
+    /// Move from a loop's tags to its values
+    fn start_loop(&mut self) -> Result<(), CifError> {
+        let State::LoopTags { tags, at: loop_at } = mem::replace(&mut self.state, State::Idle)
+        else {
+            return Ok(());
+        };
+        if tags.is_empty() {
+            return Err(
+                CifError::invalid_structure("'loop_' must be followed by data names")
+                    .at_location(loop_at.0, loop_at.1),
+            );
+        }
+        let keep = self.keep_block && tags.iter().any(|tag| keeps_tag(&self.select, tag.as_ref()));
+        let width = tags.len();
+        if keep {
+            self.sink.loop_start(tags);
+        }
+        self.state = State::LoopValues {
+            width,
+            keep,
+            row: Vec::with_capacity(width),
+            values: 0,
+        };
+        Ok(())
+    }
+
+    /// End the item or loop being read, before `next` (`None` at the end of
+    /// input) at `at`
+    fn end_statement(&mut self, next: Option<&str>, at: (usize, usize)) -> Result<(), CifError> {
+        if matches!(self.state, State::LoopTags { .. }) {
+            self.start_loop()?;
+        }
+        match mem::replace(&mut self.state, State::Idle) {
+            State::Idle => Ok(()),
+            State::Item { tag, at, .. } => Err(CifError::invalid_structure(format!(
+                "Data name '{}' has no value",
+                tag.as_ref()
+            ))
+            .at_location(at.0, at.1)),
+            State::LoopValues {
+                width,
+                keep,
+                values,
+                ..
+            } => {
+                if !values.is_multiple_of(width) {
+                    return Err(CifError::LoopMismatch {
+                        tags: width,
+                        values,
+                        reserved_word: next.filter(|word| !word.starts_with('_')).map(String::from),
+                        location: Some(at),
+                        span: None,
+                    });
+                }
+                if keep {
+                    self.sink.loop_end();
+                }
+                Ok(())
+            }
+            State::LoopTags { .. } => unreachable!("the loop was started above"),
+        }
+    }
+
+    fn block(&mut self, heading: &str, name: S::Name, at: (usize, usize)) -> Result<(), CifError> {
+        self.end_statement(Some(heading), at)?;
+        self.end_block(at)?;
+        if self.blocks_seen > 0 && self.select.contains(&Selection::FirstBlockOnly) {
+            self.done = true;
+            return Ok(());
+        }
+        self.keep_block = keeps_block(&self.select, self.blocks_seen, name.as_ref());
+        self.blocks_seen += 1;
+        self.in_block = true;
+        if self.keep_block {
+            self.sink.block_start(name);
+        }
+        Ok(())
+    }
+
+    /// Close the current block, which must not have a save frame open
+    fn end_block(&mut self, at: (usize, usize)) -> Result<(), CifError> {
+        if let Some((name, frame_at)) = &self.frame {
+            return Err(CifError::invalid_structure(format!(
+                "Save frame '{name}' is not closed by 'save_' (it ends at line {})",
+                at.0
+            ))
+            .at_location(frame_at.0, frame_at.1));
+        }
+        if self.in_block && self.keep_block {
+            self.sink.block_end();
+        }
+        self.in_block = false;
+        Ok(())
+    }
+
+    /// Close everything still open at the end of input, at `at`
+    pub(crate) fn finish(&mut self, at: (usize, usize)) -> Result<(), CifError> {
+        self.done = true;
+        self.end_statement(None, at)?;
+        self.end_block(at)
+    }
+}
+
+/// The tokens of a run of input other than whitespace and comments, checked
+/// as the grammar checks them.
+///
+/// Lines count from `first_line` in errors, and from 1 in the tokens.
+pub(crate) struct Tokens<'t> {
+    tokens: Tokenizer<'t>,
+    text: &'t str,
+    first_line: usize,
+    previous: Option<Token<'t>>,
+}
+
+impl<'t> Tokens<'t> {
+    pub(crate) fn new(text: &'t str, first_line: usize) -> Self {
+        Tokens {
+            tokens: Tokenizer::new(text),
+            text,
+            first_line,
+            previous: None,
+        }
+    }
+
+    fn token(&mut self) -> Result<Option<Token<'t>>, CifError> {
+        for token in self.tokens.by_ref() {
+            let first_line = self.first_line;
+            let token = token.map_err(|error| match error.location() {
+                Some((line, col)) => error.at_location(line + first_line - 1, col),
+                None => error,
+            })?;
+            if token.kind == TokenKind::Whitespace {
+                continue;
+            }
+            let at = (token.line + self.first_line - 1, token.col);
+            check_token(&token, at)?;
+            check_separated(self.previous.as_ref(), &token, at)?;
+            self.previous = Some(token.clone());
+            if token.kind != TokenKind::Comment {
+                return Ok(Some(token));
+            }
+            if token.byte_range.end == self.text.len() {
+                return Err(CifError::invalid_structure(
+                    "A comment at the end of the input must end with a line terminator",
+                )
+                .at_location(at.0, at.1));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'t> Iterator for Tokens<'t> {
+    type Item = Result<Token<'t>, CifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.token().transpose()
+    }
+}
+
+/// Check a token at `at` as strict parsing checks the input before the
+/// grammar: only space and tab separate tokens, so a Unicode space outside
+/// quotes and text fields is an error
+fn check_token(token: &Token, at: (usize, usize)) -> Result<(), CifError> {
+    match token.kind {
+        TokenKind::QuotedString
+        | TokenKind::TextField
+        | TokenKind::Delimiter
+        | TokenKind::Comment
+        | TokenKind::Whitespace => Ok(()),
+        _ => check_unicode_spaces(token.text, at),
+    }
+}
+
+/// Check that `token`, at `at`, is separated from the token before it by
+/// whitespace, as the grammar requires except just inside a list or table
+/// and around a table key's `:`
+fn check_separated(
+    previous: Option<&Token>,
+    token: &Token,
+    at: (usize, usize),
+) -> Result<(), CifError> {
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    let delimiter = |token: &Token, texts: &[&str]| {
+        token.kind == TokenKind::Delimiter && texts.contains(&token.text)
+    };
+    if previous.byte_range.end != token.byte_range.start
+        || delimiter(previous, &["[", "{", ":"])
+        || delimiter(token, &["]", "}", ":"])
+    {
+        return Ok(());
+    }
+    Err(CifError::invalid_structure(format!(
+        "'{}' must be separated by whitespace from the '{}' before it",
+        first_line_of(token.text),
+        first_line_of(previous.text)
+    ))
+    .at_location(at.0, at.1))
+}
+
+/// How many tokens after an opening `[` or `{` come before its closing one
+///
+/// `tokens` ends with the closing delimiter or later, since input is only
+/// tokenized in runs that close every list and table.
+pub(crate) fn compound_len(
+    open: &str,
+    tokens: &[Token],
+    first_line: usize,
+) -> Result<usize, CifError> {
+    let mut opens = vec![open];
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Delimiter {
+            continue;
+        }
+        match token.text {
+            "[" | "{" => opens.push(token.text),
+            close @ ("]" | "}") => {
+                let open = opens.pop().unwrap_or_default();
+                if (open, close) != ("[", "]") && (open, close) != ("{", "}") {
+                    return Err(CifError::invalid_structure(format!(
+                        "'{close}' does not close the '{open}' before it"
+                    ))
+                    .at_location(token.line + first_line - 1, token.col));
+                }
+                if opens.is_empty() {
+                    return Ok(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(tokens.len())
+}
+
+/// The list or table opened by `open`, with the tokens inside it, up to the
+/// byte `close` of `source`. In CIF 1.1, which has no lists or tables, the
+/// value is the text, as the parser keeps it.
+///
+/// Token lines count from `first_line` of the input, for errors.
+pub(crate) fn compound<'t>(
+    open: &Token<'t>,
+    inner: &[Token<'t>],
+    close: usize,
+    source: &'t str,
+    version: CifVersion,
+    first_line: usize,
+) -> Result<CifValueRef<'t>, CifError> {
+    let value = compound_value(open.text, inner, version, first_line)?;
+    Ok(match version {
+        CifVersion::V2_0 => value,
+        _ => {
+            let raw = &source[open.byte_range.start..close];
+            match normalize_line_terminators(raw) {
+                Some(normalized) => CifValueRef::Text(Cow::Owned(normalized)),
+                None => CifValueRef::Text(Cow::Borrowed(raw)),
+            }
+        }
+    })
+}
+
+fn compound_value<'t>(
+    open: &str,
+    inner: &[Token<'t>],
+    version: CifVersion,
+    first_line: usize,
+) -> Result<CifValueRef<'t>, CifError> {
+    let mut values = Vec::new();
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < inner.len() {
+        let token = &inner[i];
+        let at = (token.line + first_line - 1, token.col);
+        let error =
+            |message: String| Err(CifError::invalid_structure(message).at_location(at.0, at.1));
+        i += 1;
+        if open == "{" {
+            if token.kind != TokenKind::QuotedString
+                || inner.get(i).map(|t| (t.kind, t.text)) != Some((TokenKind::Delimiter, ":"))
+            {
+                return error(format!(
+                    "Table entries must be a quoted key, ':', and a value; found '{}'",
+                    first_line_of(token.text)
+                ));
+            }
+            keys.push(Cow::Borrowed(table_key_ref(token.text)));
+            i += 1;
+            let Some(token) = inner.get(i) else {
+                return error("A table key must be followed by a value".to_string());
+            };
+            i += 1;
+            values.push(element(token, &inner[i..], version, first_line, &mut i)?);
+        } else {
+            values.push(element(token, &inner[i..], version, first_line, &mut i)?);
+        }
+    }
+    if open == "{" {
+        Ok(CifValueRef::Table(
+            keys.into_iter().zip(values).collect::<HashMap<_, _>>(),
+        ))
+    } else {
+        Ok(CifValueRef::List(values))
+    }
+}
+
+/// The list or table element starting at `token`, followed by `rest`;
+/// `next` is moved past the tokens of a nested list or table
+fn element<'t>(
+    token: &Token<'t>,
+    rest: &[Token<'t>],
+    version: CifVersion,
+    first_line: usize,
+    next: &mut usize,
+) -> Result<CifValueRef<'t>, CifError> {
+    let at = (token.line + first_line - 1, token.col);
+    match token.kind {
+        TokenKind::Delimiter if matches!(token.text, "[" | "{") => {
+            let len = compound_len(token.text, rest, first_line)?;
+            *next += len + 1;
+            compound_value(token.text, &rest[..len], version, first_line)
+        }
+        _ => token_value_ref(token.kind, token.text, version)
+            .unwrap_or_else(|| {
+                Err(CifError::invalid_structure(format!(
+                    "'{}' cannot be a list or table element",
+                    token.text
+                )))
+            })
+            .map_err(|error| error.at_location(at.0, at.1)),
+    }
+}
+
+/// The first line of a token, to quote in messages
+fn first_line_of(text: &str) -> &str {
+    text.lines().next().unwrap_or(text)
+}
@@ -0,0 +1,256 @@
+//! Splitting a stream of concatenated CIF files into one document per block.
+//!
+//! Database dumps and pipelines often concatenate many CIFs into one stream,
+//! with nothing between entries but the next `data_` line. [`DocumentStream`]
+//! reads such a stream line by line and parses each data block as its own
+//! document once the next one starts, so only one entry is in memory at a
+//! time. A broken entry gives an error for that entry alone; reading
+//! resumes at the next `data_` line.
+
+use crate::ast::CifDocument;
+use crate::error::CifError;
+use crate::parser::options::ParseOptions;
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+/// Lines an unterminated text field may run past a `data_` line inside it
+/// before that line is taken as the start of the next entry
+const MAX_TEXT_FIELD_LINES: usize = 1_000_000;
+
+/// Iterator over the data blocks of a concatenated CIF stream, each parsed
+/// as a single-block [`CifDocument`].
+///
+/// Created by [`CifDocument::parse_multi_stream`]. A new entry starts at
+/// every line beginning with `data_`, except inside text fields, where such
+/// lines are content (as in embedded FCF files). If a text field is never
+/// closed, the entry is cut at the first `data_` line inside it, so one
+/// missing `;` does not swallow the rest of the stream.
+///
+/// The CIF version comes from the nearest `#\#CIF_2.0` (or `#\#CIF_1.1`)
+/// magic comment at or before an entry, so a CIF 2.0 stream only needs it
+/// once at the top. Errors carry the line number, in the stream, of the
+/// entry they came from. An I/O error ends the iteration.
+pub struct DocumentStream<R> {
+    reader: R,
+    options: ParseOptions,
+    /// Lines read ahead of the current entry, to be scanned again
+    replay: VecDeque<Vec<u8>>,
+    /// The `data_` line that starts the next entry, and its line number
+    next_start: Option<(Vec<u8>, usize)>,
+    /// Magic comment that sets the version of the following entries
+    magic: Option<Vec<u8>>,
+    /// Number of lines read so far
+    line_number: usize,
+    done: bool,
+}
+
+/// The raw lines of one entry
+struct Entry {
+    lines: Vec<Vec<u8>>,
+    /// Stream line number of the `data_` line
+    start_line: usize,
+    /// Magic comment in force for this entry
+    magic: Option<Vec<u8>>,
+}
+
+impl<R: BufRead> DocumentStream<R> {
+    pub(crate) fn new(reader: R, options: ParseOptions) -> Self {
+        DocumentStream {
+            reader,
+            options,
+            replay: VecDeque::new(),
+            next_start: None,
+            magic: None,
+            line_number: 0,
+            done: false,
+        }
+    }
+
+    /// The next line, from the replay queue or the reader
+    fn read_line(&mut self) -> Result<Option<Vec<u8>>, CifError> {
+        if let Some(line) = self.replay.pop_front() {
+            return Ok(Some(line));
+        }
+        let mut line = Vec::new();
+        if self.reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if self.line_number == 0 {
+            if let Some(rest) = line.strip_prefix(b"\xEF\xBB\xBF") {
+                line = rest.to_vec();
+            }
+        }
+        self.line_number += 1;
+        Ok(Some(line))
+    }
+
+    /// Collect the next entry, starting with its `data_` line, or `None` at
+    /// the end of the stream
+    fn next_entry(&mut self) -> Result<Option<Entry>, CifError> {
+        // Skip to the first `data_` line; anything before it is preamble
+        while self.next_start.is_none() {
+            let Some(line) = self.read_line()? else {
+                return Ok(None);
+            };
+            if is_magic(&line) {
+                self.magic = Some(line);
+            } else if is_data_line(&line) {
+                // Replayed lines were counted when first read
+                let number = self.line_number - self.replay.len();
+                self.next_start = Some((line, number));
+            }
+        }
+        let (start, start_line) = self.next_start.take().expect("set above");
+        let magic = self.magic.clone();
+
+        let mut lines = vec![start];
+        let mut in_text_field = false;
+        // Position of the first `data_` line inside the open text field
+        let mut cut: Option<usize> = None;
+        while let Some(line) = self.read_line()? {
+            if line.first() == Some(&b';') {
+                in_text_field = !in_text_field;
+                cut = None;
+            } else if in_text_field {
+                if cut.is_none() && is_data_line(&line) {
+                    cut = Some(lines.len());
+                }
+                if cut.is_some_and(|cut| lines.len() - cut >= MAX_TEXT_FIELD_LINES) {
+                    lines.push(line);
+                    break;
+                }
+            } else if is_data_line(&line) {
+                let number = self.line_number - self.replay.len();
+                self.next_start = Some((line, number));
+                return Ok(Some(Entry {
+                    lines,
+                    start_line,
+                    magic,
+                }));
+            } else if is_magic(&line) {
+                self.magic = Some(line);
+                continue;
+            }
+            lines.push(line);
+        }
+
+        // End of stream (or the cap) inside a text field: cut the entry at
+        // the first `data_` line in it and scan the rest again
+        if let Some(cut) = cut.filter(|_| in_text_field) {
+            let rest = lines.split_off(cut);
+            for line in rest.into_iter().rev() {
+                self.replay.push_front(line);
+            }
+        }
+        Ok(Some(Entry {
+            lines,
+            start_line,
+            magic,
+        }))
+    }
+
+    /// Parse the lines of one entry
+    fn parse_entry(&self, entry: Entry) -> Result<CifDocument, CifError> {
+        let mut bytes = entry.magic.unwrap_or_default();
+        let offset = usize::from(!bytes.is_empty());
+        bytes.extend(entry.lines.into_iter().flatten());
+        CifDocument::from_bytes_with_options(&bytes, &self.options)
+            .map_err(|error| in_entry(error, entry.start_line - offset))
+    }
+}
+
+impl<R: BufRead> Iterator for DocumentStream<R> {
+    type Item = Result<CifDocument, CifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_entry() {
+            Ok(Some(entry)) => Some(self.parse_entry(entry)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Whether a line starts a data block
+pub(crate) fn is_data_line(line: &[u8]) -> bool {
+    let line = line.trim_ascii_start();
+    line.len() > 5 && line[..5].eq_ignore_ascii_case(b"data_")
+}
+
+/// Whether a line is a CIF version magic comment
+fn is_magic(line: &[u8]) -> bool {
+    line.starts_with(b"#\\#CIF_")
+}
+
+/// Give an entry's error locations as lines of the whole stream
+///
+/// `first_line` is the stream line number of the entry's first line.
+fn in_entry(error: CifError, first_line: usize) -> CifError {
+    let shift = first_line.saturating_sub(1);
+    let error = match error {
+        // The grammar's message quotes the entry's lines, so it keeps their
+        // numbering and says so
+        CifError::Syntax {
+            message, location, ..
+        } => CifError::Syntax {
+            message: format!(
+                "in the entry starting at line {first_line} of the stream (line numbers below \
+                 count from there)\n{message}"
+            ),
+            location,
+            span: None,
+        },
+        other => other,
+    };
+    match error.location() {
+        Some((line, column)) => error.at_location(line + shift, column),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(stream: &str) -> Vec<Result<String, String>> {
+        DocumentStream::new(stream.as_bytes(), ParseOptions::default())
+            .map(|doc| match doc {
+                Ok(doc) => Ok(doc.blocks[0].name.clone()),
+                Err(error) => Err(error.to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_data_lines_in_text_fields_are_content() {
+        let stream = "data_a\n_fcf\n;\ndata_embedded\n_x 1\n;\ndata_b\n_y 2\n";
+        assert_eq!(names(stream), [Ok("a".into()), Ok("b".into())]);
+    }
+
+    #[test]
+    fn test_unterminated_text_field_is_cut() {
+        let stream = "data_a\n_text\n;\nnever closed\ndata_b\n_y 2\ndata_c\n_z 3\n";
+        let names = names(stream);
+        assert_eq!(names.len(), 3);
+        assert!(names[0].is_err());
+        assert_eq!(names[1..], [Ok("b".into()), Ok("c".into())]);
+    }
+
+    #[test]
+    fn test_error_locations_are_stream_lines() {
+        let stream = "data_a\n_x 1\ndata_b\n_y\n";
+        let docs: Vec<_> =
+            DocumentStream::new(stream.as_bytes(), ParseOptions::default()).collect();
+        let error = docs[1].as_ref().unwrap_err().to_string();
+        assert!(error.contains("starting at line 3"), "{error}");
+    }
+}
@@ -0,0 +1,329 @@
+//! Non-CIF content before the first data block and after the last one.
+//!
+//! Instrument software prepends banners and archive tools append checksums,
+//! `#END` markers, or binary junk. In strict mode such content is an error at
+//! its position; in lenient mode it is cut off before the grammar runs and
+//! kept verbatim on the document.
+
+use crate::error::CifError;
+use crate::lexer::{Token, TokenKind, Tokenizer};
+use crate::parser::options::ParseContext;
+
+/// Content found outside the data blocks
+pub(crate) struct Outside {
+    /// Byte offset where the content starts
+    pub(crate) start: usize,
+    /// Byte offset just past the content
+    pub(crate) end: usize,
+    /// 1-indexed (line, column) of the first non-comment character
+    pub(crate) location: (usize, usize),
+    /// Whether the parser would reject the content anyway
+    pub(crate) rejected: bool,
+}
+
+/// Content before the first `data_` or `global_` heading, other than
+/// whitespace and comments (which covers the version magic). Blank lines
+/// just before the heading are not part of it.
+pub(crate) fn find_preamble(input: &str) -> Option<Outside> {
+    let mut first = None;
+    let mut offset = 0;
+    // End of the last line that was not blank
+    let mut end = 0;
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_start();
+        if is_heading(trimmed) {
+            let (start, location) = first?;
+            return Some(Outside {
+                start,
+                end,
+                location,
+                rejected: true,
+            });
+        }
+        if first.is_none() && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            let indent = line.len() - trimmed.len();
+            let column = line[..indent].chars().count() + 1;
+            first = Some((offset, (index + 1, column)));
+        }
+        offset += line.len();
+        if !trimmed.is_empty() {
+            end = offset;
+        }
+    }
+    // Without a heading there is no document to find the edge of
+    None
+}
+
+fn is_heading(line: &str) -> bool {
+    let lower = |n: usize| line.get(..n).map(str::to_ascii_lowercase);
+    lower(5).is_some_and(|s| s == "data_") || lower(7).is_some_and(|s| s == "global_")
+}
+
+/// Where the tokens seen so far leave the document
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    /// Between items; `pending` when a data name still needs its value
+    Items { pending: bool },
+    /// After `loop_`, reading its data names
+    LoopTags,
+    /// Reading loop rows
+    LoopValues,
+}
+
+/// Trailing content after the last complete item or loop.
+///
+/// A trailer starts at a value that belongs to no data name, at an `#END`
+/// marker comment, or at a loop value holding control characters, and runs
+/// to the end of the input as long as no data name, `loop_`, or heading
+/// follows. Only a trailer with something other than comments in it counts.
+///
+/// Values on the same line as an item's unquoted value are the rest of a
+/// value with spaces in it, not a trailer; the parser reports or, in lenient
+/// mode, joins them.
+pub(crate) fn find_trailer(input: &str, from: usize) -> Option<Outside> {
+    let mut state = State::Items { pending: false };
+    // Open `[` and `{`: a list or table is one value
+    let mut depth = 0usize;
+    let mut candidate: Option<Outside> = None;
+    let mut content = None;
+    // Line of the last item's unquoted value
+    let mut item_line = None;
+
+    for token in Tokenizer::new(&input[from..]) {
+        let token = match token {
+            Ok(token) => token,
+            // An unterminated quote inside junk ends the junk; anywhere
+            // else it is the parser's to report
+            Err(_) => break,
+        };
+        let start = from + token.byte_range.start;
+
+        match token.kind {
+            TokenKind::Whitespace => continue,
+            TokenKind::Comment => {
+                let complete = matches!(state, State::Items { pending: false } | State::LoopValues);
+                if candidate.is_none() && depth == 0 && complete && is_end_marker(token.text) {
+                    candidate = Some(outside(start, &token, false));
+                }
+                continue;
+            }
+            TokenKind::Tag
+            | TokenKind::LoopKeyword
+            | TokenKind::BlockHeader
+            | TokenKind::SaveHeader
+            | TokenKind::SaveEnd => {
+                // More CIF follows, so nothing so far was a trailer
+                candidate = None;
+                content = None;
+                item_line = None;
+                depth = 0;
+                state = match token.kind {
+                    TokenKind::Tag => match state {
+                        State::LoopTags => State::LoopTags,
+                        _ => State::Items { pending: true },
+                    },
+                    TokenKind::LoopKeyword if token.text.eq_ignore_ascii_case("loop_") => {
+                        State::LoopTags
+                    }
+                    // Values after a STAR `stop_` are the loop's, for the
+                    // parser to reject or discard
+                    TokenKind::LoopKeyword if token.text.eq_ignore_ascii_case("stop_") => {
+                        State::LoopValues
+                    }
+                    _ => State::Items { pending: false },
+                };
+                continue;
+            }
+            TokenKind::Delimiter => {
+                match token.text {
+                    "[" | "{" => depth += 1,
+                    "]" | "}" => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                if depth > 0 || matches!(token.text, "[" | "{" | ":") {
+                    content.get_or_insert((token.line, token.col));
+                    continue;
+                }
+            }
+            TokenKind::QuotedString | TokenKind::TextField | TokenKind::UnquotedValue => {
+                if depth > 0 {
+                    content.get_or_insert((token.line, token.col));
+                    continue;
+                }
+            }
+        }
+
+        // A whole value has been read
+        let continues_item =
+            state == (State::Items { pending: false }) && item_line == Some(token.line);
+        content.get_or_insert((token.line, token.col));
+        if candidate.is_none() && !continues_item {
+            match state {
+                State::Items { pending: false } => {
+                    candidate = Some(outside(start, &token, true));
+                }
+                State::LoopValues if has_control_characters(token.text) => {
+                    candidate = Some(outside(start, &token, false));
+                }
+                _ => {}
+            }
+        }
+        if candidate.is_none() {
+            content = None;
+        }
+        item_line = match state {
+            State::Items { pending: true } if token.kind == TokenKind::UnquotedValue => {
+                Some(token.line)
+            }
+            _ if continues_item => item_line,
+            _ => None,
+        };
+        state = match state {
+            State::Items { .. } => State::Items { pending: false },
+            State::LoopTags | State::LoopValues => State::LoopValues,
+        };
+    }
+
+    let mut trailer = candidate?;
+    trailer.location = content?;
+    trailer.end = input.len();
+    Some(trailer)
+}
+
+fn outside(start: usize, token: &Token, rejected: bool) -> Outside {
+    Outside {
+        start,
+        end: start,
+        location: (token.line, token.col),
+        rejected,
+    }
+}
+
+/// Whether a comment marks the end of the data, as in `#END`,
+/// `# END OF CIF`, or `#===END===`
+fn is_end_marker(comment: &str) -> bool {
+    let rest = comment[1..].trim_start_matches(['=', '-', ' ', '\t']);
+    rest.get(..3).is_some_and(|s| s.eq_ignore_ascii_case("end"))
+        && !rest[3..].starts_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+/// Control characters never appear in CIF text outside whitespace
+fn has_control_characters(text: &str) -> bool {
+    text.chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+}
+
+/// Input with its non-CIF edges removed
+#[derive(Default)]
+pub(crate) struct Stripped {
+    /// The input to parse, when it differs from the original
+    pub(crate) input: Option<String>,
+    pub(crate) preamble: Option<String>,
+    pub(crate) trailer: Option<String>,
+}
+
+/// Remove non-CIF content before the first block and after the last one.
+///
+/// Strict mode rejects the content at its position. The parser already
+/// rejects stray values with a message of its own, so those are left to it.
+/// Lenient mode warns and blanks the preamble to newlines, keeping line
+/// numbers, and cuts the trailer off.
+pub(crate) fn strip(input: &str, ctx: &mut ParseContext) -> Result<Stripped, CifError> {
+    let preamble = find_preamble(input);
+    let from = preamble.as_ref().map_or(0, |p| p.end);
+    let trailer = find_trailer(input, from);
+
+    if !ctx.options.lenient {
+        if let Some(p) = &preamble {
+            return Err(CifError::invalid_structure(
+                "Content before the first data block is not CIF",
+            )
+            .at_location(p.location.0, p.location.1));
+        }
+        if let Some(t) = trailer.as_ref().filter(|t| !t.rejected) {
+            return Err(CifError::invalid_structure(
+                "Content after the last data block is not CIF",
+            )
+            .at_location(t.location.0, t.location.1));
+        }
+        return Ok(Stripped::default());
+    }
+
+    if preamble.is_none() && trailer.is_none() {
+        return Ok(Stripped::default());
+    }
+    let mut kept = String::with_capacity(input.len());
+    let preamble = preamble.map(|p| {
+        ctx.warn(
+            "Content before the first data block is not CIF; kept as the preamble",
+            p.location,
+        );
+        kept.push_str(&input[..p.start]);
+        kept.extend(input[p.start..p.end].chars().filter(|&c| c == '\n'));
+        input[p.start..p.end].to_string()
+    });
+    let body_end = trailer.as_ref().map_or(input.len(), |t| t.start);
+    kept.push_str(&input[from..body_end]);
+    let trailer = trailer.map(|t| {
+        ctx.warn(
+            "Content after the last data block is not CIF; kept as the trailer",
+            t.location,
+        );
+        input[t.start..t.end].to_string()
+    });
+    Ok(Stripped {
+        input: Some(kept),
+        preamble,
+        trailer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer(input: &str) -> Option<&str> {
+        find_trailer(input, 0).map(|t| &input[t.start..t.end])
+    }
+
+    #[test]
+    fn test_end_markers() {
+        for marker in ["#END", "# END OF CIF", "#===END===", "#end_of_data"] {
+            assert!(is_end_marker(marker), "{marker}");
+        }
+        for comment in ["#", "#Endnote", "# ending soon", "#EN"] {
+            assert!(!is_end_marker(comment), "{comment}");
+        }
+    }
+
+    #[test]
+    fn test_trailer_edges() {
+        assert_eq!(trailer("data_a\n_x 1\n#END\n"), None);
+        assert_eq!(
+            trailer("data_a\n_x 1\n#END\nsum 12\n"),
+            Some("#END\nsum 12\n")
+        );
+        assert_eq!(trailer("data_a\n_x 1\nsum 12\n"), Some("sum 12\n"));
+        assert_eq!(trailer("data_a\n_x 1\n#END\ndata_b\n_x 2\n"), None);
+        assert_eq!(trailer("data_a\nloop_\n_x\n1\n2\n"), None);
+        assert_eq!(
+            trailer("data_a\nloop_\n_x\n1\n\u{1}\u{2}\n"),
+            Some("\u{1}\u{2}\n")
+        );
+        // A marker before the item's value is no marker
+        assert_eq!(trailer("data_a\n_x\n#END\n1\n"), None);
+        assert_eq!(trailer("#\\#CIF_2.0\ndata_a\n_x [1 2]\n"), None);
+    }
+
+    #[test]
+    fn test_preamble_edges() {
+        let preamble = |input: &str| find_preamble(input).map(|p| (p.start, p.end, p.location));
+        assert_eq!(preamble("#\\#CIF_2.0\n# note\ndata_a\n"), None);
+        assert_eq!(
+            preamble("Bruker\n\n  v2\n\ndata_a\n"),
+            Some((0, 13, (1, 1)))
+        );
+        assert_eq!(preamble("# c\n  Bruker\nDATA_a\n"), Some((4, 13, (2, 3))));
+        assert_eq!(preamble("no heading at all\n"), None);
+    }
+}
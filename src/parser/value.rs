@@ -12,10 +12,19 @@
 //! - No ambiguity or dynamic feature detection needed
 
 use crate::ast::{CifValue, CifVersion};
+use crate::borrowed::CifValueRef;
+use crate::conformance;
 use crate::error::CifError;
+use crate::lexer::TokenKind;
+use crate::parser::encoding::normalize_line_terminators;
+use crate::parser::helpers::extract_location;
+use crate::parser::options::ParseContext;
+use crate::uncertainty;
 use crate::Rule;
 use pest::iterators::Pair;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Parse a CIF value from a parse tree node with version awareness.
 ///
@@ -44,7 +53,11 @@ use std::collections::HashMap;
 /// ```
 pub fn parse_value(pair: Pair<Rule>, version: CifVersion) -> Result<CifValue, CifError> {
     match pair.as_rule() {
-        Rule::item_value | Rule::loop_value | Rule::value | Rule::data_value => {
+        Rule::item_value
+        | Rule::loop_value
+        | Rule::value
+        | Rule::data_value
+        | Rule::nested_value => {
             // Recursively parse the actual value inside
             let inner = pair.into_inner().next();
             if let Some(inner_pair) = inner {
@@ -88,9 +101,10 @@ pub fn parse_value(pair: Pair<Rule>, version: CifVersion) -> Result<CifValue, Ci
         }
 
         // CIF 1.1 and 2.0: Quoted strings
-        Rule::quoted_string | Rule::singlequoted | Rule::doublequoted => {
-            parse_quoted_string(pair, version)
-        }
+        Rule::quoted_string
+        | Rule::nested_quoted_string
+        | Rule::singlequoted
+        | Rule::doublequoted => parse_quoted_string(pair, version),
 
         // CIF 1.1 and 2.0: Text fields
         Rule::text_field | Rule::textfield => parse_text_field(pair),
@@ -107,6 +121,55 @@ pub fn parse_value(pair: Pair<Rule>, version: CifVersion) -> Result<CifValue, Ci
     }
 }
 
+/// Parse a value as [`parse_value`] does, except that a text field or
+/// triple-quoted string longer than the
+/// [large-value threshold](crate::ParseOptions::large_value_threshold)
+/// becomes [`CifValue::LargeText`], copied straight out of the input.
+///
+/// With [`Conformance::Strict11`](crate::Conformance::Strict11), an
+/// unquoted value starting with `$` is an error.
+pub(crate) fn parse_value_in(pair: Pair<Rule>, ctx: &ParseContext) -> Result<CifValue, CifError> {
+    if ctx.strict_11() {
+        if let Some(error) = conformance::unquoted_error(pair.as_str()) {
+            let (line, col) = extract_location(&pair);
+            return Err(error.at_location(line, col));
+        }
+    }
+    if let Some(threshold) = ctx.options.large_value_threshold {
+        if pair.as_str().len() > threshold {
+            if let Some(content) = delimited_content(&pair, ctx.version) {
+                if content.len() > threshold {
+                    return Ok(CifValue::LargeText(Arc::from(content)));
+                }
+            }
+        }
+    }
+    parse_value(pair, ctx.version)
+}
+
+/// The content of a text field or triple-quoted string, borrowed from the
+/// input; `None` for other values
+fn delimited_content<'i>(pair: &Pair<'i, Rule>, version: CifVersion) -> Option<&'i str> {
+    let mut pair = pair.clone();
+    loop {
+        match pair.as_rule() {
+            Rule::item_value
+            | Rule::loop_value
+            | Rule::value
+            | Rule::data_value
+            | Rule::nested_value => {
+                pair = pair.into_inner().next()?;
+            }
+            Rule::text_field | Rule::textfield => return Some(text_field_content(pair.as_str())),
+            Rule::triple_quoted_string if version == CifVersion::V2_0 => {
+                let text = pair.as_str();
+                return text.get(3..text.len().checked_sub(3)?);
+            }
+            _ => return None,
+        }
+    }
+}
+
 /// Parse a list value (CIF 2.0 only): `[value1 value2 value3]`
 ///
 /// Lists can contain any CIF value type, including nested lists and tables.
@@ -115,7 +178,11 @@ fn parse_list(pair: Pair<Rule>, version: CifVersion) -> Result<CifValue, CifErro
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::data_value | Rule::value | Rule::item_value | Rule::loop_value => {
+            Rule::data_value
+            | Rule::nested_value
+            | Rule::value
+            | Rule::item_value
+            | Rule::loop_value => {
                 let value = parse_value(inner_pair, version)?;
                 values.push(value);
             }
@@ -172,6 +239,7 @@ fn parse_table_entry(
                 key = extract_quoted_content(inner_pair.as_str());
             }
             Rule::data_value
+            | Rule::nested_value
             | Rule::value
             | Rule::item_value
             | Rule::loop_value
@@ -205,6 +273,7 @@ fn parse_triple_quoted(pair: Pair<Rule>) -> Result<CifValue, CifError> {
                 text.len()
             ),
             location: Some((span.start_pos().line_col().0, span.start_pos().line_col().1)),
+            span: None,
         });
     }
 
@@ -218,6 +287,7 @@ fn parse_triple_quoted(pair: Pair<Rule>) -> Result<CifValue, CifError> {
         return Err(CifError::InvalidStructure {
             message: "Triple-quoted string missing opening or closing delimiters".to_string(),
             location: Some((span.start_pos().line_col().0, span.start_pos().line_col().1)),
+            span: None,
         });
     };
 
@@ -231,24 +301,41 @@ fn parse_triple_quoted(pair: Pair<Rule>) -> Result<CifValue, CifError> {
 /// - **CIF 1.1**: Supports doubled-quote escaping (`'O''Brien'` → `O'Brien`)
 /// - **CIF 2.0**: Doubled quotes are invalid; use triple-quoted strings instead
 fn parse_quoted_string(pair: Pair<Rule>, version: CifVersion) -> Result<CifValue, CifError> {
-    let text = pair.as_str();
-    let span = pair.as_span();
-    let content = extract_quoted_content(text);
+    let (line, col) = pair.as_span().start_pos().line_col();
+    quoted_value(pair.as_str(), version).map_err(|error| error.at_location(line, col))
+}
+
+/// The value of a quoted string, quotes included; errors have no location
+fn quoted_value(text: &str, version: CifVersion) -> Result<CifValue, CifError> {
+    quoted_value_ref(text, version).map(CifValueRef::into_owned)
+}
+
+/// [`quoted_value`], borrowing the text from `text`
+fn quoted_value_ref(text: &str, version: CifVersion) -> Result<CifValueRef<'_>, CifError> {
+    let content = quoted_content(text);
 
     // VERSION GUARD: CIF 2.0 does not support doubled-quote escaping
     // Doubled quotes ('' or "") in CIF 2.0 are invalid - use triple quotes instead
     if version == CifVersion::V2_0 && (content.contains("''") || content.contains("\"\"")) {
-        return Err(CifError::InvalidStructure {
-            message: "Doubled-quote escaping ('''' or \"\"\"\") is not allowed in CIF 2.0. Use triple-quoted strings instead: '''...''' or \"\"\"...\"\"\"".to_string(),
-            location: Some((span.start_pos().line_col().0, span.start_pos().line_col().1)),
-        });
+        return Err(CifError::invalid_structure(
+            "Doubled-quote escaping ('''' or \"\"\"\") is not allowed in CIF 2.0. Use triple-quoted strings instead: '''...''' or \"\"\"...\"\"\"",
+        ));
     }
 
     // Try to parse as number first, fall back to text
-    if let Ok(num) = content.parse::<f64>() {
-        Ok(CifValue::Numeric(num))
-    } else {
-        Ok(CifValue::Text(content))
+    Ok(number_ref(content).unwrap_or(CifValueRef::Text(Cow::Borrowed(content))))
+}
+
+/// A number, with its su if written with one, or `None` for other text
+fn number_ref(text: &str) -> Option<CifValueRef<'static>> {
+    // Padded text is not a number, though uncertainty::parse would trim it
+    if text.trim() != text {
+        return None;
+    }
+    match uncertainty::parse(text)? {
+        (value, None) => Some(CifValueRef::Numeric(value)),
+        (value, Some(su)) if value.is_finite() => Some(CifValueRef::NumericSu { value, su }),
+        _ => None,
     }
 }
 
@@ -256,33 +343,85 @@ fn parse_quoted_string(pair: Pair<Rule>, version: CifVersion) -> Result<CifValue
 ///
 /// Text fields are multi-line strings delimited by semicolons at line starts.
 fn parse_text_field(pair: Pair<Rule>) -> Result<CifValue, CifError> {
-    let text = pair.as_str();
-
-    // Remove semicolon delimiters and surrounding whitespace
-    let content = text.trim_start_matches(';').trim_end_matches(';').trim();
+    Ok(CifValue::Text(
+        text_field_content(pair.as_str()).to_string(),
+    ))
+}
 
-    Ok(CifValue::Text(content.to_string()))
+/// A text field without its semicolon delimiters and surrounding whitespace
+fn text_field_content(text: &str) -> &str {
+    text.trim_start_matches(';').trim_end_matches(';').trim()
 }
 
 /// Parse an unquoted string (CIF 1.1 and 2.0)
 ///
 /// Handles special values (`?`, `.`) and numeric parsing.
 fn parse_unquoted(pair: Pair<Rule>) -> Result<CifValue, CifError> {
-    let text = pair.as_str().trim();
+    Ok(unquoted_value(pair.as_str().trim()))
+}
 
+/// The value of an unquoted string
+fn unquoted_value(text: &str) -> CifValue {
+    unquoted_value_ref(text).into_owned()
+}
+
+/// [`unquoted_value`], borrowing the text from `text`
+fn unquoted_value_ref(text: &str) -> CifValueRef<'_> {
     // Check for special values
     match text {
-        "?" => return Ok(CifValue::Unknown),
-        "." => return Ok(CifValue::NotApplicable),
-        _ => {}
+        "?" => CifValueRef::Unknown,
+        "." => CifValueRef::NotApplicable,
+        // Try to parse as number
+        _ => number_ref(text).unwrap_or(CifValueRef::Text(Cow::Borrowed(text))),
     }
+}
 
-    // Try to parse as number
-    if let Ok(num) = text.parse::<f64>() {
-        Ok(CifValue::Numeric(num))
-    } else {
-        Ok(CifValue::Text(text.to_string()))
-    }
+/// The value of one [`lexer`](crate::lexer) token, for readers that build
+/// values without the grammar
+///
+/// Only quoted strings, text fields, and unquoted values are values; other
+/// kinds give `None`. Errors have no location.
+pub(crate) fn token_value(
+    kind: TokenKind,
+    text: &str,
+    version: CifVersion,
+) -> Option<Result<CifValue, CifError>> {
+    token_value_ref(kind, text, version).map(|value| value.map(CifValueRef::into_owned))
+}
+
+/// [`token_value`], borrowing the text from `text`; only multi-line text
+/// with CR line terminators is copied, to rewrite them as LF
+pub(crate) fn token_value_ref(
+    kind: TokenKind,
+    text: &str,
+    version: CifVersion,
+) -> Option<Result<CifValueRef<'_>, CifError>> {
+    let multi_line = |content| match normalize_line_terminators(content) {
+        Some(normalized) => CifValueRef::Text(Cow::Owned(normalized)),
+        None => CifValueRef::Text(Cow::Borrowed(content)),
+    };
+    Some(match kind {
+        // CIF 1.1 has no triple quotes: as in the parser, the value is the text
+        TokenKind::QuotedString if is_triple_quoted(text) => Ok(match version {
+            CifVersion::V2_0 => multi_line(&text[3..text.len() - 3]),
+            CifVersion::V1_1 => multi_line(text),
+        }),
+        TokenKind::QuotedString => quoted_value_ref(text, version),
+        TokenKind::TextField => Ok(multi_line(text_field_content(text))),
+        TokenKind::UnquotedValue => Ok(unquoted_value_ref(text)),
+        _ => return None,
+    })
+}
+
+/// A table key's text without its quotes, borrowed from `text`
+pub(crate) fn table_key_ref(text: &str) -> &str {
+    quoted_content(text)
+}
+
+fn is_triple_quoted(text: &str) -> bool {
+    text.len() >= 6
+        && ((text.starts_with("\"\"\"") && text.ends_with("\"\"\""))
+            || (text.starts_with("'''") && text.ends_with("'''")))
 }
 
 /// Helper: Extract content from a quoted string (remove quotes)
@@ -291,21 +430,26 @@ fn parse_unquoted(pair: Pair<Rule>) -> Result<CifValue, CifError> {
 /// strings ('''...''' and """..."""). Triple-quotes are checked first to ensure
 /// correct parsing.
 fn extract_quoted_content(text: &str) -> String {
+    quoted_content(text).to_string()
+}
+
+/// [`extract_quoted_content`], borrowed from `text`
+fn quoted_content(text: &str) -> &str {
     let trimmed = text.trim();
 
     // Check for triple-quoted strings first (CIF 2.0)
     if (trimmed.starts_with("\"\"\"") && trimmed.ends_with("\"\"\"") && trimmed.len() >= 6)
         || (trimmed.starts_with("'''") && trimmed.ends_with("'''") && trimmed.len() >= 6)
     {
-        trimmed[3..trimmed.len() - 3].to_string()
+        &trimmed[3..trimmed.len() - 3]
     }
     // Then check for single/double quotes (CIF 1.1 and 2.0)
     else if (trimmed.starts_with('\'') && trimmed.ends_with('\''))
         || (trimmed.starts_with('"') && trimmed.ends_with('"'))
     {
-        trimmed[1..trimmed.len().saturating_sub(1)].to_string()
+        &trimmed[1..trimmed.len().saturating_sub(1)]
     } else {
-        trimmed.to_string()
+        trimmed
     }
 }
 
@@ -0,0 +1,314 @@
+//! Original values recorded by aggregators before normalizing a file.
+//!
+//! The Crystallography Open Database rewrites some values of the files it
+//! takes in, and keeps what the authors wrote under a prefixed tag:
+//! `_cod_original_cell_volume` holds the volume the file gave before COD
+//! recalculated `_cell_volume`, and `_cod_original_sg_symbol_H-M` the space
+//! group symbol before it was put in standard form. [`CifBlock::provenance_overrides`]
+//! pairs each such original with the current value and says whether they
+//! differ, and [`CifBlock::restore_original`] puts the original back.
+//!
+//! The prefixes are configurable through [`ProvenanceOptions`]; by default
+//! they are `_cod_original_` and `_iucr_original_`. What follows the prefix
+//! names the current tag (`_cod_original_cell_volume` → `_cell_volume`),
+//! except for COD's shorthands `sg_symbol_H-M`, `sg_symbol_Hall`,
+//! `formula_sum`, and `formula_units_Z`, which name the space group,
+//! formula, and Z tags.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let mut doc = Document::parse(
+//!     "data_x\n_cell_volume 1234.56(8)\n_cod_original_cell_volume 1234.9\n",
+//! )
+//! .unwrap();
+//! let block = &mut doc.blocks[0];
+//!
+//! let overrides = block.provenance_overrides();
+//! assert_eq!(overrides[0].tag, "_cell_volume");
+//! assert!(overrides[0].differs);
+//!
+//! block.restore_original("_cell_volume").unwrap();
+//! assert_eq!(block.get_item("_cell_volume").unwrap().as_numeric(), Some(1234.9));
+//! assert!(block.get_item("_audit_update_record").is_some());
+//! ```
+
+use crate::approx::{compare, Comparison};
+use crate::ast::{CifBlock, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::writer::inline_value;
+use std::fmt;
+
+/// Prefixes of tags holding original values, unless configured otherwise
+pub const DEFAULT_PREFIXES: &[&str] = &["_cod_original_", "_iucr_original_"];
+
+/// Names after a prefix that stand for a tag of another name, lowercased,
+/// with the tags they stand for in order of preference
+const SHORTHANDS: &[(&str, &[&str])] = &[
+    (
+        "sg_symbol_h-m",
+        &[
+            "_space_group_name_H-M_alt",
+            "_symmetry_space_group_name_H-M",
+        ],
+    ),
+    (
+        "sg_symbol_hall",
+        &["_space_group_name_Hall", "_symmetry_space_group_name_Hall"],
+    ),
+    ("formula_sum", &["_chemical_formula_sum"]),
+    ("formula_units_z", &["_cell_formula_units_Z"]),
+];
+
+/// Tag the audit entries of [`CifBlock::restore_original`] are added to
+const AUDIT_TAG: &str = "_audit_update_record";
+
+/// Settings for finding original values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenanceOptions {
+    /// Prefixes of tags holding original values, matched without regard to
+    /// case
+    pub prefixes: Vec<String>,
+    /// Numbers whose difference is at most this many sus are not flagged as
+    /// differing
+    pub n_sigma: f64,
+}
+
+impl Default for ProvenanceOptions {
+    fn default() -> Self {
+        ProvenanceOptions {
+            prefixes: DEFAULT_PREFIXES.iter().map(|p| p.to_string()).collect(),
+            n_sigma: 1.0,
+        }
+    }
+}
+
+impl ProvenanceOptions {
+    /// Create options with the default prefixes, flagging numbers that
+    /// differ by more than their su
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also look for original values under `prefix`, such as
+    /// `_ccdc_original_`
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefixes.push(prefix.into());
+        self
+    }
+
+    /// Look for original values only under these prefixes
+    pub fn prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set how many sus two numbers may differ by before they are flagged
+    pub fn n_sigma(mut self, n_sigma: f64) -> Self {
+        self.n_sigma = n_sigma;
+        self
+    }
+}
+
+/// An original value and the value that replaced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenanceOverride {
+    /// The tag holding the original, such as `_cod_original_cell_volume`
+    pub original_tag: String,
+    /// The tag of the current value, as written in the block if present
+    pub tag: String,
+    /// The original value
+    pub original: CifValue,
+    /// The current value, or `None` if the block has no such item
+    pub current: Option<CifValue>,
+    /// Whether the values differ: numbers by more than the allowed number
+    /// of sus, anything else as text. A missing current value differs.
+    pub differs: bool,
+    /// Difference of two numbers in sus, if both are numbers
+    pub sigmas: Option<f64>,
+}
+
+impl fmt::Display for ProvenanceOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let current = self.current.as_ref().map_or("missing".into(), text);
+        write!(
+            f,
+            "{}: {current}, originally {} ({})",
+            self.tag,
+            text(&self.original),
+            self.original_tag
+        )?;
+        if let Some(sigmas) = self.sigmas {
+            write!(f, ", {sigmas:.1}σ apart")?;
+        }
+        Ok(())
+    }
+}
+
+/// A value as CIF text
+fn text(value: &CifValue) -> String {
+    match value.as_string() {
+        Some(s) => s.trim().to_string(),
+        None => inline_value(value, CifVersion::V2_0),
+    }
+}
+
+/// Whether two values differ, and by how many sus if both are numbers
+fn difference(original: &CifValue, current: &CifValue, n_sigma: f64) -> (bool, Option<f64>) {
+    if let (Some(a), Some(b)) = (original.as_numeric_with_su(), current.as_numeric_with_su()) {
+        match compare(a, b, n_sigma) {
+            Comparison::Equal { sigmas } => return (false, Some(sigmas)),
+            Comparison::Differs { sigmas } => return (true, Some(sigmas)),
+            Comparison::Incomparable => {}
+        }
+    }
+    (text(original) != text(current), None)
+}
+
+impl CifBlock {
+    /// Original values recorded under the default prefixes, each with the
+    /// current value. See the [module documentation](crate::provenance).
+    pub fn provenance_overrides(&self) -> Vec<ProvenanceOverride> {
+        self.provenance_overrides_with_options(&ProvenanceOptions::default())
+    }
+
+    /// Original values recorded under the prefixes of `options`, in item
+    /// order
+    pub fn provenance_overrides_with_options(
+        &self,
+        options: &ProvenanceOptions,
+    ) -> Vec<ProvenanceOverride> {
+        let prefixes: Vec<String> = options.prefixes.iter().map(|p| p.to_lowercase()).collect();
+        let mut overrides = Vec::new();
+        for (original_tag, original) in &self.items {
+            let lower = original_tag.to_lowercase();
+            let Some(rest) = prefixes
+                .iter()
+                .find_map(|prefix| lower.strip_prefix(prefix.as_str()))
+                .filter(|rest| !rest.is_empty())
+            else {
+                continue;
+            };
+            // The name as written, so that `_cell_formula_units_Z` keeps its case
+            let rest = &original_tag[original_tag.len() - rest.len()..];
+            let (tag, current) = self.current_item(rest);
+            let (differs, sigmas) = match current {
+                Some(current) => difference(original, current, options.n_sigma),
+                None => (true, None),
+            };
+            overrides.push(ProvenanceOverride {
+                original_tag: original_tag.clone(),
+                tag,
+                original: original.clone(),
+                current: current.cloned(),
+                differs,
+                sigmas,
+            });
+        }
+        overrides
+    }
+
+    /// The tag an original value belongs to, and its current value
+    fn current_item(&self, name: &str) -> (String, Option<&CifValue>) {
+        let lower = name.to_lowercase();
+        let candidates: Vec<String> = match SHORTHANDS.iter().find(|(short, _)| *short == lower) {
+            Some((_, tags)) => tags.iter().map(|tag| tag.to_string()).collect(),
+            None => vec![format!("_{name}")],
+        };
+        candidates
+            .iter()
+            .find_map(|candidate| {
+                self.items
+                    .iter()
+                    .find(|(tag, _)| tag.eq_ignore_ascii_case(candidate))
+                    .map(|(tag, value)| (tag.clone(), Some(value)))
+            })
+            .unwrap_or_else(|| (candidates[0].clone(), None))
+    }
+
+    /// Put back the original value of `tag`, recorded under a default
+    /// prefix. See [`restore_original_with_options`](Self::restore_original_with_options).
+    pub fn restore_original(&mut self, tag: &str) -> Result<ProvenanceOverride, CifError> {
+        self.restore_original_with_options(tag, &ProvenanceOptions::default())
+    }
+
+    /// Put back the original value of `tag`, given as the current tag
+    /// (`_cell_volume`) or the tag holding the original
+    /// (`_cod_original_cell_volume`).
+    ///
+    /// The original becomes the value of the current tag and its own item is
+    /// removed. A line saying what was replaced is added to
+    /// `_audit_update_record`, as a new row if that is a loop column. Returns
+    /// the values as they were before the change.
+    ///
+    /// Fails if no original value is recorded for `tag`.
+    pub fn restore_original_with_options(
+        &mut self,
+        tag: &str,
+        options: &ProvenanceOptions,
+    ) -> Result<ProvenanceOverride, CifError> {
+        let restored = self
+            .provenance_overrides_with_options(options)
+            .into_iter()
+            .find(|o| o.tag.eq_ignore_ascii_case(tag) || o.original_tag.eq_ignore_ascii_case(tag))
+            .ok_or_else(|| CifError::InvalidValue {
+                kind: "tag without an original value",
+                value: tag.to_string(),
+            })?;
+        self.items.shift_remove(&restored.original_tag);
+        self.items
+            .insert(restored.tag.clone(), restored.original.clone());
+
+        let replaced = match &restored.current {
+            Some(current) => format!("replacing {}", text(current)),
+            None => "which was missing".to_string(),
+        };
+        self.add_audit_record(format!(
+            "Restored {} to {} from {}, {replaced}",
+            restored.tag,
+            text(&restored.original),
+            restored.original_tag
+        ));
+        Ok(restored)
+    }
+
+    fn add_audit_record(&mut self, entry: String) {
+        let column = self.loops.iter_mut().find_map(|loop_| {
+            let column = loop_
+                .tags
+                .iter()
+                .position(|t| t.eq_ignore_ascii_case(AUDIT_TAG))?;
+            Some((loop_, column))
+        });
+        if let Some((loop_, column)) = column {
+            let mut row = vec![CifValue::Unknown; loop_.tags.len()];
+            row[column] = CifValue::Text(entry);
+            loop_.values.push(row);
+            return;
+        }
+        let existing = self
+            .items
+            .iter_mut()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(AUDIT_TAG));
+        match existing {
+            Some((_, value)) => {
+                *value = match value.as_string().map(str::trim_end) {
+                    Some(old) if !old.trim().is_empty() => {
+                        CifValue::Text(format!("{old}\n{entry}"))
+                    }
+                    _ => CifValue::Text(entry),
+                }
+            }
+            None => {
+                self.items
+                    .insert(AUDIT_TAG.to_string(), CifValue::Text(entry));
+            }
+        }
+    }
+}
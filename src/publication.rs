@@ -0,0 +1,309 @@
+//! Publication metadata: authors, title, and journal reference.
+//!
+//! CIF files submitted to journals describe their own publication with the
+//! `_publ_*` and `_journal_*` items. [`CifBlock::publication`] gathers these
+//! into a [`Publication`], handling both the looped and single-item forms of
+//! `_publ_author_name`.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_paper
+//! _publ_section_title
+//! ;
+//! Crystal structure of
+//! urea
+//! ;
+//! _journal_year 2019
+//! _journal_paper_doi 10.1107/S0000000000000000
+//! loop_
+//! _publ_author_name
+//! 'Smith, Jane A.'
+//! 'Jones, B.'
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let publ = doc.first_block().unwrap().publication();
+//!
+//! assert_eq!(publ.title.as_deref(), Some("Crystal structure of urea"));
+//! assert_eq!(publ.authors[0].family, "Smith");
+//! assert_eq!(publ.authors[0].given.as_deref(), Some("Jane A."));
+//! assert_eq!(publ.year, Some(2019));
+//! ```
+
+use crate::ast::{CifBlock, CifValue};
+use crate::text::unfold;
+use crate::uncertainty;
+
+/// An author, split into family and given names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Author {
+    /// Name as written, with footnote markers removed
+    pub name: String,
+    /// Family name (the part before the comma)
+    pub family: String,
+    /// Given names or initials (the part after the comma), if any
+    pub given: Option<String>,
+    /// Postal address
+    pub address: Option<String>,
+    /// Email address
+    pub email: Option<String>,
+    /// ORCID identifier
+    pub orcid: Option<String>,
+}
+
+impl Author {
+    /// Parse a name using the CIF convention `Family, Given`.
+    ///
+    /// Footnote markers (`^a^`, `*`, `\dag`, `\ddag`, `†`, `‡`) are removed.
+    /// Names without a comma are split at the last space; single words are
+    /// taken as the family name.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::publication::Author;
+    ///
+    /// let author = Author::from_name("Smith, J. A.^a^");
+    /// assert_eq!(author.family, "Smith");
+    /// assert_eq!(author.given.as_deref(), Some("J. A."));
+    /// ```
+    pub fn from_name(raw: &str) -> Self {
+        let name = strip_footnote_markers(raw);
+        let (family, given) = match name.split_once(',') {
+            Some((family, given)) => (family.trim(), Some(given.trim())),
+            None => match name.rsplit_once(' ') {
+                Some((given, family)) => (family.trim(), Some(given.trim())),
+                None => (name.as_str(), None),
+            },
+        };
+
+        Author {
+            family: family.to_string(),
+            given: given.filter(|g| !g.is_empty()).map(str::to_string),
+            name: name.clone(),
+            address: None,
+            email: None,
+            orcid: None,
+        }
+    }
+}
+
+/// Publication metadata gathered from a data block.
+///
+/// Every field is optional in CIF, so absent or `?`/`.` values are `None`
+/// and missing authors give an empty list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Publication {
+    /// `_publ_section_title` with line folding removed and whitespace collapsed
+    pub title: Option<String>,
+    /// `_publ_author_*`, in file order
+    pub authors: Vec<Author>,
+    /// `_publ_contact_author_*`
+    pub contact_author: Option<Author>,
+    /// `_journal_name_full`
+    pub journal: Option<String>,
+    /// `_journal_volume`
+    pub volume: Option<String>,
+    /// `_journal_issue`
+    pub issue: Option<String>,
+    /// `_journal_year`
+    pub year: Option<i32>,
+    /// `_journal_page_first`
+    pub page_first: Option<String>,
+    /// `_journal_page_last`
+    pub page_last: Option<String>,
+    /// `_journal_paper_doi`
+    pub doi: Option<String>,
+}
+
+impl Publication {
+    /// Page range as `first-last`, or just the first page
+    pub fn pages(&self) -> Option<String> {
+        match (&self.page_first, &self.page_last) {
+            (Some(first), Some(last)) if first != last => Some(format!("{first}-{last}")),
+            (Some(first), _) => Some(first.clone()),
+            (None, Some(last)) => Some(last.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether no publication metadata was found
+    pub fn is_empty(&self) -> bool {
+        *self == Publication::default()
+    }
+}
+
+impl CifBlock {
+    /// Gather publication metadata (`_publ_*`, `_journal_*`) from this block.
+    ///
+    /// See the [module documentation](crate::publication) for an example.
+    pub fn publication(&self) -> Publication {
+        let text = |tag: &str| self.get_item(tag).and_then(value_text);
+
+        Publication {
+            title: text("_publ_section_title").map(|t| normalize_title(&t)),
+            authors: self.authors(),
+            contact_author: text("_publ_contact_author_name").map(|name| Author {
+                address: text("_publ_contact_author_address"),
+                email: text("_publ_contact_author_email"),
+                orcid: text("_publ_contact_author_id_orcid"),
+                ..Author::from_name(&name)
+            }),
+            journal: text("_journal_name_full"),
+            volume: text("_journal_volume"),
+            issue: text("_journal_issue"),
+            year: self
+                .get_item("_journal_year")
+                .and_then(CifValue::as_numeric)
+                .map(|y| y as i32),
+            page_first: text("_journal_page_first"),
+            page_last: text("_journal_page_last"),
+            doi: text("_journal_paper_doi"),
+        }
+    }
+
+    /// Authors from the `_publ_author_*` loop or single items
+    fn authors(&self) -> Vec<Author> {
+        if let Some(loop_) = self.find_loop("_publ_author_name") {
+            let column = |tag: &str, row: usize| loop_.get_by_tag(row, tag).and_then(value_text);
+            return (0..loop_.len())
+                .filter_map(|row| {
+                    let name = column("_publ_author_name", row)?;
+                    Some(Author {
+                        address: column("_publ_author_address", row),
+                        email: column("_publ_author_email", row),
+                        orcid: column("_publ_author_id_orcid", row),
+                        ..Author::from_name(&name)
+                    })
+                })
+                .collect();
+        }
+
+        let text = |tag: &str| self.get_item(tag).and_then(value_text);
+        text("_publ_author_name")
+            .map(|name| Author {
+                address: text("_publ_author_address"),
+                email: text("_publ_author_email"),
+                orcid: text("_publ_author_id_orcid"),
+                ..Author::from_name(&name)
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Text of a value, with numbers formatted back to strings and `?`/`.` as None
+pub(crate) fn value_text(value: &CifValue) -> Option<String> {
+    match value {
+        CifValue::Numeric(n) => Some(n.to_string()),
+        CifValue::NumericSu { value, su } => Some(uncertainty::format_exact(*value, *su)),
+        _ => value
+            .as_string()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from),
+    }
+}
+
+/// Remove CIF superscript footnote markers and dagger symbols from a name
+fn strip_footnote_markers(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('^') {
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 1..].find('^') {
+            Some(end) => &rest[start + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+
+    let out = out.replace("\\ddag", "").replace("\\dag", "");
+    out.trim()
+        .trim_end_matches(['*', '†', '‡'])
+        .trim()
+        .to_string()
+}
+
+/// Undo CIF 2.0 line folding and collapse runs of whitespace
+fn normalize_title(text: &str) -> String {
+    unfold(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_author_name_forms() {
+        let author = Author::from_name("van der Berg, Anna");
+        assert_eq!(author.family, "van der Berg");
+        assert_eq!(author.given.as_deref(), Some("Anna"));
+
+        let author = Author::from_name("Jane Smith");
+        assert_eq!(author.family, "Smith");
+        assert_eq!(author.given.as_deref(), Some("Jane"));
+
+        let author = Author::from_name("Consortium");
+        assert_eq!(author.family, "Consortium");
+        assert_eq!(author.given, None);
+    }
+
+    #[test]
+    fn test_footnote_markers_removed() {
+        assert_eq!(strip_footnote_markers("Smith, J.^a,b^"), "Smith, J.");
+        assert_eq!(strip_footnote_markers("Smith, J.*"), "Smith, J.");
+        assert_eq!(strip_footnote_markers("Smith, J.\\dag"), "Smith, J.");
+        assert_eq!(strip_footnote_markers("Smith, J.‡"), "Smith, J.");
+    }
+
+    #[test]
+    fn test_title_normalization() {
+        assert_eq!(
+            normalize_title("Crystal  structure\n  of X"),
+            "Crystal structure of X"
+        );
+        assert_eq!(
+            normalize_title("\\\nCrystal struc\\\nture of\nX"),
+            "Crystal structure of X"
+        );
+    }
+
+    #[test]
+    fn test_single_item_author_and_journal() {
+        let cif = "\
+data_paper
+_publ_author_name 'Doe, John*'
+_publ_author_email john@example.org
+_publ_contact_author_name 'Doe, John'
+_publ_contact_author_email john@example.org
+_journal_name_full 'Acta Crystallographica Section E'
+_journal_volume 75
+_journal_page_first 123
+_journal_page_last 130
+_journal_issue ?
+";
+        let doc = crate::CifDocument::parse(cif).unwrap();
+        let publ = doc.first_block().unwrap().publication();
+
+        assert_eq!(publ.authors.len(), 1);
+        assert_eq!(publ.authors[0].name, "Doe, John");
+        assert_eq!(publ.authors[0].email.as_deref(), Some("john@example.org"));
+        assert_eq!(publ.contact_author.as_ref().unwrap().family, "Doe");
+        assert_eq!(publ.volume.as_deref(), Some("75"));
+        assert_eq!(publ.issue, None);
+        assert_eq!(publ.pages().as_deref(), Some("123-130"));
+        assert!(!publ.is_empty());
+    }
+
+    #[test]
+    fn test_block_without_metadata() {
+        let doc = crate::CifDocument::parse("data_a\n_cell_length_a 5.0\n").unwrap();
+        assert!(doc.first_block().unwrap().publication().is_empty());
+    }
+}
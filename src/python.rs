@@ -3,28 +3,342 @@
 //! This module provides Python-native wrappers around the core CIF parsing
 //! functionality, following Python naming conventions and idioms.
 
-use crate::{CifBlock, CifDocument, CifError, CifFrame, CifLoop, CifValue, CifVersion};
-use pyo3::exceptions::{PyIOError, PyIndexError, PyKeyError, PyTypeError, PyValueError};
+use crate::approx::{Comparison, ValueComparison};
+use crate::category::{Category, CategoryResolver};
+use crate::convert::ConversionReport;
+use crate::corpus::extract_items;
+use crate::dictionary::{Definition, ValidationIssue};
+use crate::formatter::{format_str, FormatOptions};
+use crate::memory::LoopMemory;
+use crate::provenance::{ProvenanceOptions, ProvenanceOverride};
+use crate::redact::RedactionPolicy;
+use crate::sanity::SanityIssue;
+use crate::schema::TagReport;
+use crate::structure::{DEFAULT_SITE_TOLERANCE, DEFAULT_TIE_TOLERANCE};
+use crate::text::{Markup, NormalizeTextOptions};
+use crate::typed::ConversionError;
+use crate::uncertainty;
+use crate::undo::DocumentSnapshot;
+use crate::writer::{inline_value, CifStyle, CifWriter, FloatFormat, NumberFormat, WriteOptions};
+use crate::{
+    AdpType, AtomSite, Author, CacheOptions, ChainMap, CifBlock, CifDictionary, CifDocument,
+    CifError, CifFrame, CifLoop, CifValue, CifVersion, CoercionReport, DictionaryStack,
+    DocumentHandle, DocumentSource, DocumentStream, DuplicateTags, ElementSymbol, ExpectedType,
+    Experimental, ExtractOptions, FlattenOptions, IdentifierKind, Identifiers, Isotope, Kind,
+    MatchOptions, Measurement, MergeOptions, MergeStatistics, MergedReflections, ParseOptions,
+    PointGroupInfo, Publication, RefinementModel, ReflectionData, Selection, SharedBlock,
+    ShellBinning, SiteMatching, SiteSymmetry, SpaceGroup, Structure, StructureComparison, SymOp,
+    TagStatus, UnitCell, WalkEntry,
+};
+use indexmap::IndexMap;
+use pyo3::exceptions::{
+    PyBufferError, PyIOError, PyIndexError, PyKeyError, PyTypeError, PyUnicodeError, PyUserWarning,
+    PyValueError,
+};
+use pyo3::ffi;
 use pyo3::prelude::*;
-use pyo3::types::PyString;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyBool, PyDateTime, PyDict, PyList, PyString, PyTzInfo};
 use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CString, OsStr};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::UNIX_EPOCH;
 
 /// Convert a Rust CifError to a Python exception
+///
+/// The exception's message is the error's, with its code and any file
+/// path, and its `code`, `path`, `line`, and `column` attributes give them
+/// separately. See the table in `cif_parser::error` for the exception type
+/// of each code. An error raised while parsing also has the byte `offset`
+/// of the error in the input and an `excerpt` showing its line.
 fn cif_error_to_py_err(err: CifError) -> PyErr {
-    match err {
-        CifError::ParseError(msg) => PyValueError::new_err(format!("Parse error: {msg}")),
-        CifError::IoError(err) => PyIOError::new_err(format!("IO error: {err}")),
-        CifError::InvalidStructure { message, location } => {
-            if let Some((line, col)) = location {
-                PyValueError::new_err(format!(
-                    "Invalid structure at line {}, col {}: {}",
-                    line, col, message
-                ))
-            } else {
-                PyValueError::new_err(format!("Invalid CIF structure: {message}"))
+    let message = err.to_string();
+    let inner = match &err {
+        CifError::InFile { error, .. } => error.as_ref(),
+        other => other,
+    };
+    let py_err = match inner {
+        CifError::Io(_) => PyIOError::new_err(message),
+        CifError::Encoding { .. } => PyUnicodeError::new_err(message),
+        CifError::MissingTag { .. } => PyKeyError::new_err(message),
+        _ => PyValueError::new_err(message),
+    };
+    Python::attach(|py| {
+        let value = py_err.value(py);
+        let (line, column) = err.location().unzip();
+        // Setting attributes on a fresh exception cannot fail
+        let _ = value.setattr("code", err.code());
+        let _ = value.setattr("path", err.path().map(Path::as_os_str));
+        let _ = value.setattr("line", line);
+        let _ = value.setattr("column", column);
+        let _ = value.setattr("offset", err.byte_offset());
+        let _ = value.setattr("excerpt", err.excerpt());
+        if let CifError::InvalidQuery { position, .. } = inner {
+            let _ = value.setattr("position", position);
+        }
+    });
+    py_err
+}
+
+/// Convert an error opening or writing `path` to a Python `OSError` whose
+/// `path` attribute is the path
+fn io_error_to_py_err(err: std::io::Error, path: &Path) -> PyErr {
+    cif_error_to_py_err(CifError::from(err).with_path(path))
+}
+
+/// A typed read from a block, with a value that could not be read as a
+/// `ValueError` naming the tag
+fn conversion_result<T>(result: Result<Option<T>, ConversionError>) -> PyResult<Option<T>> {
+    result.map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// `os.fsdecode`, looked up on first use
+static FSDECODE: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+
+/// A path argument: a `str`, `bytes`, or any `os.PathLike`
+///
+/// Bytes are decoded as `os.fsdecode` does, so a name that is not valid
+/// UTF-8 reaches the file system unchanged.
+struct FsPath(PathBuf);
+
+impl FromPyObject<'_> for FsPath {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let fsdecode = FSDECODE.import(ob.py(), "os", "fsdecode")?;
+        fsdecode.call1((ob,))?.extract().map(FsPath)
+    }
+}
+
+/// Parse the `duplicate_tags` argument of the parse functions
+fn duplicate_tags_from_arg(duplicate_tags: &str) -> PyResult<DuplicateTags> {
+    match duplicate_tags {
+        "last" => Ok(DuplicateTags::Last),
+        "error" => Ok(DuplicateTags::Error),
+        "collapse" => Ok(DuplicateTags::Collapse),
+        other => Err(PyValueError::new_err(format!(
+            "duplicate_tags must be 'last', 'error', or 'collapse', got '{other}'"
+        ))),
+    }
+}
+
+/// Parse options for the selective-parsing, duplicate-tag, lenient, and
+/// large-value keyword arguments
+fn parse_options(
+    tags: Option<Vec<String>>,
+    blocks: Option<Vec<String>>,
+    first_block_only: bool,
+    duplicate_tags: &str,
+    lenient: bool,
+    large_value_threshold: Option<usize>,
+) -> PyResult<ParseOptions> {
+    let mut options = ParseOptions::new()
+        .duplicate_tags(duplicate_tags_from_arg(duplicate_tags)?)
+        .lenient(lenient);
+    if let Some(bytes) = large_value_threshold {
+        options = options.large_value_threshold(bytes);
+    }
+    if first_block_only {
+        options = options.select(Selection::FirstBlockOnly);
+    }
+    if let Some(blocks) = blocks {
+        options = options.select(Selection::Blocks(blocks));
+    }
+    if let Some(tags) = tags {
+        options = options.select(Selection::TagPrefixes(tags));
+    }
+    Ok(options)
+}
+
+/// How `to_dict` renders CIF values
+#[derive(Clone, Copy)]
+enum ValueMode {
+    /// Native Python types; `?` and `.` become None
+    Native,
+    /// Native Python types; `?` and `.` kept as the sentinel strings
+    Raw,
+}
+
+impl ValueMode {
+    fn from_arg(values: &str) -> PyResult<Self> {
+        match values {
+            "native" => Ok(ValueMode::Native),
+            "raw" => Ok(ValueMode::Raw),
+            other => Err(PyValueError::new_err(format!(
+                "values must be 'native' or 'raw', got '{other}'"
+            ))),
+        }
+    }
+}
+
+/// How `to_dict` lays out loops
+#[derive(Clone, Copy)]
+enum LoopLayout {
+    /// `{"tags": [...], "rows": [[...], ...]}`
+    Rows,
+    /// `{tag: [column values], ...}`
+    Columns,
+}
+
+impl LoopLayout {
+    fn from_arg(loops: &str) -> PyResult<Self> {
+        match loops {
+            "rows" => Ok(LoopLayout::Rows),
+            "columns" => Ok(LoopLayout::Columns),
+            other => Err(PyValueError::new_err(format!(
+                "loops must be 'rows' or 'columns', got '{other}'"
+            ))),
+        }
+    }
+}
+
+/// Parse the `markup` argument of `Value.normalized_text`
+fn markup_from_arg(markup: &str) -> PyResult<Markup> {
+    match markup {
+        "unicode" => Ok(Markup::Unicode),
+        "cif" => Ok(Markup::Cif),
+        "keep" => Ok(Markup::Keep),
+        other => Err(PyValueError::new_err(format!(
+            "markup must be 'unicode', 'cif', or 'keep', got '{other}'"
+        ))),
+    }
+}
+
+/// Options shared by the `to_dict` methods
+#[derive(Clone, Copy)]
+struct DictOptions {
+    values: ValueMode,
+    loops: LoopLayout,
+    include_loops: bool,
+    include_frames: bool,
+}
+
+/// Convert a value to a plain Python object without intermediate wrappers
+///
+/// Large text becomes None; `Value.read_large()` reads it on request.
+fn value_to_py(py: Python, value: &CifValue, mode: ValueMode) -> PyResult<Py<PyAny>> {
+    match value {
+        CifValue::Text(s) => Ok(PyString::new(py, s).into_any().unbind()),
+        CifValue::LargeText(_) => Ok(py.None()),
+        CifValue::Numeric(n) | CifValue::NumericSu { value: n, .. } => {
+            Ok(n.into_pyobject(py)?.into_any().unbind())
+        }
+        CifValue::Unknown => match mode {
+            ValueMode::Native => Ok(py.None()),
+            ValueMode::Raw => Ok(PyString::new(py, "?").into_any().unbind()),
+        },
+        CifValue::NotApplicable => match mode {
+            ValueMode::Native => Ok(py.None()),
+            ValueMode::Raw => Ok(PyString::new(py, ".").into_any().unbind()),
+        },
+        CifValue::List(values) => {
+            let list = PyList::empty(py);
+            for v in values {
+                list.append(value_to_py(py, v, mode)?)?;
+            }
+            Ok(list.into_any().unbind())
+        }
+        CifValue::Table(_) => {
+            let dict = PyDict::new(py);
+            for (k, v) in value.table_entries().unwrap_or_default() {
+                dict.set_item(k, value_to_py(py, v, mode)?)?;
+            }
+            Ok(dict.into_any().unbind())
+        }
+    }
+}
+
+fn items_to_py<'py>(
+    py: Python<'py>,
+    items: &IndexMap<String, CifValue>,
+    mode: ValueMode,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (tag, value) in items {
+        dict.set_item(tag, value_to_py(py, value, mode)?)?;
+    }
+    Ok(dict)
+}
+
+fn loop_to_py<'py>(
+    py: Python<'py>,
+    loop_: &CifLoop,
+    options: DictOptions,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    match options.loops {
+        LoopLayout::Rows => {
+            let rows = PyList::empty(py);
+            for row in &loop_.values {
+                let py_row = PyList::empty(py);
+                for value in row {
+                    py_row.append(value_to_py(py, value, options.values)?)?;
+                }
+                rows.append(py_row)?;
+            }
+            dict.set_item("tags", PyList::new(py, &loop_.tags)?)?;
+            dict.set_item("rows", rows)?;
+        }
+        LoopLayout::Columns => {
+            for (col, tag) in loop_.tags.iter().enumerate() {
+                let column = PyList::empty(py);
+                for row in &loop_.values {
+                    column.append(value_to_py(py, &row[col], options.values)?)?;
+                }
+                dict.set_item(tag, column)?;
             }
         }
     }
+    Ok(dict)
+}
+
+fn loops_to_py<'py>(
+    py: Python<'py>,
+    loops: &[CifLoop],
+    options: DictOptions,
+) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for loop_ in loops {
+        list.append(loop_to_py(py, loop_, options)?)?;
+    }
+    Ok(list)
+}
+
+fn frame_to_py<'py>(
+    py: Python<'py>,
+    frame: &CifFrame,
+    options: DictOptions,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &frame.name)?;
+    dict.set_item("items", items_to_py(py, &frame.items, options.values)?)?;
+    if options.include_loops {
+        dict.set_item("loops", loops_to_py(py, &frame.loops, options)?)?;
+    }
+    Ok(dict)
+}
+
+fn block_to_py<'py>(
+    py: Python<'py>,
+    block: &CifBlock,
+    options: DictOptions,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &block.name)?;
+    dict.set_item("items", items_to_py(py, &block.items, options.values)?)?;
+    if options.include_loops {
+        dict.set_item("loops", loops_to_py(py, &block.loops, options)?)?;
+    }
+    if options.include_frames {
+        let frames = PyList::empty(py);
+        for frame in &block.frames {
+            frames.append(frame_to_py(py, frame, options)?)?;
+        }
+        dict.set_item("frames", frames)?;
+    }
+    Ok(dict)
 }
 
 /// Python wrapper for CifVersion enum
@@ -80,11 +394,95 @@ impl From<CifVersion> for PyVersion {
     }
 }
 
+/// `collections.namedtuple("ValueSource", ...)`, created on first use
+static VALUE_SOURCE_TYPE: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+
+fn value_source_type(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+    VALUE_SOURCE_TYPE
+        .get_or_try_init(py, || {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("module", "cif_parser")?;
+            let namedtuple = py.import("collections")?.getattr("namedtuple")?;
+            namedtuple
+                .call(
+                    ("ValueSource", ("block", "frame", "tag", "row")),
+                    Some(&kwargs),
+                )
+                .map(Bound::unbind)
+        })
+        .map(|ty| ty.bind(py))
+}
+
+/// Where a value was read from
+#[derive(Clone)]
+struct ValueSource {
+    block: Option<String>,
+    frame: Option<String>,
+    tag: String,
+    /// Loop row, or None for a plain data item
+    row: Option<usize>,
+}
+
+/// The block and save frame that a wrapped loop or frame belongs to
+#[derive(Clone, Default)]
+struct Origin {
+    block: Option<String>,
+    frame: Option<String>,
+}
+
+impl Origin {
+    fn in_block(block: &str) -> Self {
+        Origin {
+            block: Some(block.to_string()),
+            frame: None,
+        }
+    }
+
+    fn source(&self, tag: &str, row: Option<usize>) -> ValueSource {
+        ValueSource {
+            block: self.block.clone(),
+            frame: self.frame.clone(),
+            tag: tag.to_string(),
+            row,
+        }
+    }
+
+    fn value(&self, value: &CifValue, tag: &str, row: Option<usize>) -> PyValue {
+        PyValue {
+            inner: value.clone(),
+            source: Some(self.source(tag, row)),
+        }
+    }
+}
+
+/// Every value of a tag in a block or frame: the data item, then each loop
+/// column containing it, with sources
+fn all_values(
+    origin: &Origin,
+    items: &IndexMap<String, CifValue>,
+    loops: &[CifLoop],
+    tag: &str,
+) -> Vec<PyValue> {
+    let item = items.get(tag).map(|v| origin.value(v, tag, None));
+    let columns = loops
+        .iter()
+        .filter_map(|loop_| loop_.get_column(tag))
+        .flat_map(|values| {
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(row, v)| origin.value(v, tag, Some(row)))
+        });
+    item.into_iter().chain(columns).collect()
+}
+
 /// Python wrapper for CifValue with Pythonic interface
 #[pyclass(name = "Value")]
 #[derive(Clone)]
 pub struct PyValue {
     inner: CifValue,
+    /// Set when the value was fetched from a block, frame, or loop
+    source: Option<ValueSource>,
 }
 
 #[pymethods]
@@ -95,10 +493,16 @@ impl PyValue {
         matches!(self.inner, CifValue::Text(_))
     }
 
+    /// Check if this is text kept as a large value (see `read_large()`)
+    #[getter]
+    fn is_large(&self) -> bool {
+        self.inner.is_large()
+    }
+
     /// Check if this is a numeric value
     #[getter]
     fn is_numeric(&self) -> bool {
-        matches!(self.inner, CifValue::Numeric(_))
+        self.inner.is_numeric()
     }
 
     /// Check if this is an unknown value (?)
@@ -125,10 +529,43 @@ impl PyValue {
         matches!(self.inner, CifValue::Table(_))
     }
 
-    /// Get the value as text (returns None if not a text value)
+    /// Get the value as text (returns None if not a text value, or if it is
+    /// a large value)
     #[getter]
     fn text(&self) -> Option<String> {
-        self.inner.as_string().map(|s| s.to_string())
+        match &self.inner {
+            CifValue::Text(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+
+    /// Read a large value's text (returns None if not a large value)
+    fn read_large(&self) -> Option<String> {
+        match &self.inner {
+            CifValue::LargeText(s) => Some(s.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Length of a large value's text in bytes (None if not a large value)
+    #[getter]
+    fn large_size(&self) -> Option<usize> {
+        match &self.inner {
+            CifValue::LargeText(s) => Some(s.len()),
+            _ => None,
+        }
+    }
+
+    /// Get the text cleaned up for display (returns None if not a text value)
+    ///
+    /// Joins hard-wrapped lines, collapses whitespace, and repairs text
+    /// mis-decoded as Windows-1252. `markup` is `"unicode"` to convert CIF
+    /// markup such as `\'e` and `^2^` to Unicode, `"cif"` for the reverse,
+    /// or `"keep"`.
+    #[pyo3(signature = (markup="unicode"))]
+    fn normalized_text(&self, markup: &str) -> PyResult<Option<String>> {
+        let options = NormalizeTextOptions::new().markup(markup_from_arg(markup)?);
+        Ok(self.inner.normalized_text(&options))
     }
 
     /// Get the value as a number (returns None if not numeric)
@@ -137,12 +574,27 @@ impl PyValue {
         self.inner.as_numeric()
     }
 
+    /// Get the value as a number with its su, as `(value, su)` (returns
+    /// None if it is not a number)
+    #[getter]
+    fn numeric_with_su(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.as_numeric_with_su()
+    }
+
+    /// Get the standard uncertainty of a number written like `1.234(5)`
+    /// (returns None if there is none)
+    #[getter]
+    fn su(&self) -> Option<f64> {
+        self.inner.as_numeric_with_su().and_then(|(_, su)| su)
+    }
+
     /// Get the value type as a string
     #[getter]
     fn value_type(&self) -> String {
         match self.inner {
             CifValue::Text(_) => "text".to_string(),
-            CifValue::Numeric(_) => "numeric".to_string(),
+            CifValue::LargeText(_) => "large_text".to_string(),
+            CifValue::Numeric(_) | CifValue::NumericSu { .. } => "numeric".to_string(),
             CifValue::Unknown => "unknown".to_string(),
             CifValue::NotApplicable => "not_applicable".to_string(),
             CifValue::List(_) => "list".to_string(),
@@ -150,37 +602,37 @@ impl PyValue {
         }
     }
 
-    /// Convert to Python native type
+    /// Convert to Python native type; None for a large value, which
+    /// `read_large()` reads instead
     fn to_python(&self, py: Python) -> PyResult<Py<PyAny>> {
-        match &self.inner {
-            CifValue::Text(s) => Ok(PyString::new(py, s).into_any().unbind()),
-            CifValue::Numeric(n) => Ok(n.into_pyobject(py)?.into_any().unbind()),
-            CifValue::Unknown => Ok(py.None()),
-            CifValue::NotApplicable => Ok(py.None()),
-            CifValue::List(values) => {
-                // Convert Vec<CifValue> to Python list
-                let py_list: Vec<Py<PyAny>> = values
-                    .iter()
-                    .map(|v| PyValue::from(v.clone()).to_python(py))
-                    .collect::<PyResult<Vec<_>>>()?;
-                Ok(py_list.into_pyobject(py)?.into_any().unbind())
-            }
-            CifValue::Table(map) => {
-                // Convert HashMap to Python dict
-                let py_dict: HashMap<String, Py<PyAny>> = map
-                    .iter()
-                    .map(|(k, v)| Ok((k.clone(), PyValue::from(v.clone()).to_python(py)?)))
-                    .collect::<PyResult<HashMap<_, _>>>()?;
-                Ok(py_dict.into_pyobject(py)?.into_any().unbind())
-            }
-        }
+        value_to_py(py, &self.inner, ValueMode::Native)
+    }
+
+    /// Where this value came from as `ValueSource(block, frame, tag, row)`,
+    /// or None for a detached value
+    #[getter]
+    fn source(&self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        let Some(source) = &self.source else {
+            return Ok(None);
+        };
+        let args = (
+            source.block.as_deref(),
+            source.frame.as_deref(),
+            source.tag.as_str(),
+            source.row,
+        );
+        value_source_type(py)?
+            .call1(args)
+            .map(|tuple| Some(tuple.unbind()))
     }
 
     /// String representation
     fn __str__(&self) -> String {
         match &self.inner {
             CifValue::Text(s) => format!("'{s}'"),
+            CifValue::LargeText(s) => format!("<large text, {} bytes>", s.len()),
             CifValue::Numeric(n) => n.to_string(),
+            CifValue::NumericSu { value, su } => uncertainty::format_exact(*value, *su),
             CifValue::Unknown => "?".to_string(),
             CifValue::NotApplicable => ".".to_string(),
             CifValue::List(values) => {
@@ -190,9 +642,12 @@ impl PyValue {
                     .collect();
                 format!("[{}]", items.join(" "))
             }
-            CifValue::Table(map) => {
-                let items: Vec<String> = map
-                    .iter()
+            CifValue::Table(_) => {
+                let items: Vec<String> = self
+                    .inner
+                    .table_entries()
+                    .unwrap_or_default()
+                    .into_iter()
                     .map(|(k, v)| format!("{}:{}", k, PyValue::from(v.clone()).__str__()))
                     .collect();
                 format!("{{{}}}", items.join(" "))
@@ -200,12 +655,22 @@ impl PyValue {
         }
     }
 
-    /// Debug representation
+    /// Debug representation, including the source tag when known
     fn __repr__(&self) -> String {
-        format!("Value({})", self.__str__())
+        match &self.source {
+            Some(ValueSource {
+                tag,
+                row: Some(row),
+                ..
+            }) => format!("Value({}, {}[{}])", self.__str__(), tag, row),
+            Some(ValueSource { tag, row: None, .. }) => {
+                format!("Value({}, {})", self.__str__(), tag)
+            }
+            None => format!("Value({})", self.__str__()),
+        }
     }
 
-    /// Python equality
+    /// Python equality (compares content only, not source)
     fn __eq__(&self, other: &PyValue) -> bool {
         self.inner == other.inner
     }
@@ -213,7 +678,10 @@ impl PyValue {
 
 impl From<CifValue> for PyValue {
     fn from(value: CifValue) -> Self {
-        PyValue { inner: value }
+        PyValue {
+            inner: value,
+            source: None,
+        }
     }
 }
 
@@ -222,10 +690,45 @@ impl From<CifValue> for PyValue {
 #[derive(Clone)]
 pub struct PyLoop {
     inner: CifLoop,
+    origin: Origin,
+    buffers: ColumnBuffers,
 }
 
 #[pymethods]
 impl PyLoop {
+    /// Create an empty loop with the given column tags, to fill with
+    /// `add_row()` and add to a block with `Block.add_loop()`
+    #[new]
+    #[pyo3(signature = (tags=Vec::new()))]
+    fn new(tags: Vec<String>) -> Self {
+        CifLoop::new(tags).into()
+    }
+
+    /// Append a row with one value per tag: numbers, text, None for `?`,
+    /// and lists and dicts for CIF 2.0
+    ///
+    /// Raises ValueError if the row has the wrong length.
+    fn add_row(&mut self, values: Vec<Bound<'_, PyAny>>) -> PyResult<()> {
+        let row = values.iter().map(value_from_py).collect::<PyResult<_>>()?;
+        self.inner.push_row(row).map_err(cif_error_to_py_err)?;
+        self.buffers = ColumnBuffers::default();
+        Ok(())
+    }
+
+    /// Append a column with one value per row; a loop without tags takes
+    /// its rows from the column
+    ///
+    /// Raises ValueError if the loop has the tag, or the number of values
+    /// is not the number of rows.
+    fn add_column(&mut self, tag: String, values: Vec<Bound<'_, PyAny>>) -> PyResult<()> {
+        let column = values.iter().map(value_from_py).collect::<PyResult<_>>()?;
+        self.inner
+            .add_column(tag, column)
+            .map_err(cif_error_to_py_err)?;
+        self.buffers = ColumnBuffers::default();
+        Ok(())
+    }
+
     /// Get the column tags (headers)
     #[getter]
     fn tags(&self) -> Vec<String> {
@@ -250,19 +753,57 @@ impl PyLoop {
 
     /// Get a value by row and column index
     fn get(&self, row: usize, col: usize) -> Option<PyValue> {
-        self.inner.get(row, col).map(|v| v.clone().into())
+        let value = self.inner.get(row, col)?;
+        Some(self.origin.value(value, &self.inner.tags[col], Some(row)))
     }
 
     /// Get a value by row index and tag name
     fn get_by_tag(&self, row: usize, tag: &str) -> Option<PyValue> {
-        self.inner.get_by_tag(row, tag).map(|v| v.clone().into())
+        self.inner
+            .get_by_tag(row, tag)
+            .map(|v| self.origin.value(v, tag, Some(row)))
     }
 
     /// Get all values for a specific tag as a list
     fn get_column(&self, tag: &str) -> Option<Vec<PyValue>> {
-        self.inner
-            .get_column(tag)
-            .map(|values| values.iter().map(|v| (*v).clone().into()).collect())
+        self.inner.get_column(tag).map(|values| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(row, v)| self.origin.value(v, tag, Some(row)))
+                .collect()
+        })
+    }
+
+    /// Get a numeric column as a read-only buffer of float64, without copying
+    ///
+    /// The numbers are converted once per column and cached on the loop;
+    /// `?` and `.` become NaN. Raises `KeyError` for an unknown tag and
+    /// `TypeError` naming the first row that is not a number.
+    fn column_buffer(&self, tag: &str) -> PyResult<PyColumnBuffer> {
+        let mut buffers = self.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(data) = buffers.get(tag) {
+            return Ok(PyColumnBuffer::new(tag, data.clone()));
+        }
+        let data: Arc<[f64]> = match self.inner.numeric_column(tag) {
+            None => {
+                return Err(PyKeyError::new_err(format!(
+                    "Tag '{tag}' not found in loop"
+                )))
+            }
+            Some(Err(row)) => {
+                let value = self
+                    .inner
+                    .get_by_tag(row, tag)
+                    .map_or_else(String::new, |v| inline_value(v, CifVersion::V1_1));
+                return Err(PyTypeError::new_err(format!(
+                    "Column '{tag}' is not numeric: row {row} is {value}"
+                )));
+            }
+            Some(Ok(numbers)) => numbers.into(),
+        };
+        buffers.insert(tag.to_string(), data.clone());
+        Ok(PyColumnBuffer::new(tag, data))
     }
 
     /// Iterate over rows
@@ -270,25 +811,122 @@ impl PyLoop {
         self.inner
             .values
             .iter()
-            .map(|row| row.iter().map(|v| v.clone().into()).collect())
+            .enumerate()
+            .map(|(r, row)| {
+                row.iter()
+                    .zip(&self.inner.tags)
+                    .map(|(v, tag)| self.origin.value(v, tag, Some(r)))
+                    .collect()
+            })
             .collect()
     }
 
-    /// Get a row as a dictionary mapping tags to values
-    fn get_row_dict(&self, row: usize) -> Option<HashMap<String, PyValue>> {
+    /// Get a row as a dictionary mapping tags to values, in column order
+    fn get_row_dict(&self, row: usize) -> Option<IndexMap<String, PyValue>> {
         if row >= self.inner.len() {
             return None;
         }
 
-        let mut result = HashMap::new();
+        let mut result = IndexMap::new();
         for (col, tag) in self.inner.tags.iter().enumerate() {
             if let Some(value) = self.inner.get(row, col) {
-                result.insert(tag.clone(), value.clone().into());
+                result.insert(tag.clone(), self.origin.value(value, tag, Some(row)));
             }
         }
         Some(result)
     }
 
+    /// Category prefix shared by all tags (`_atom_site_` or `_atom_site.`),
+    /// or None if the tags mix categories
+    #[getter]
+    fn common_prefix(&self) -> Option<String> {
+        self.inner.common_prefix().map(str::to_string)
+    }
+
+    /// Iterate over rows as dictionaries
+    ///
+    /// With `strip_prefix`, keys are tags without `common_prefix`
+    /// (`fract_x`), or full tags if there is none. With `native`, values are
+    /// plain Python objects (`?`/`.` become None); otherwise Value objects.
+    /// Columns are converted once, before iteration starts.
+    #[pyo3(signature = (native=true, strip_prefix=true))]
+    fn iter_dicts(
+        &self,
+        py: Python<'_>,
+        native: bool,
+        strip_prefix: bool,
+    ) -> PyResult<PyRowDictIterator> {
+        let prefix_len = match self.inner.common_prefix() {
+            Some(prefix) if strip_prefix => prefix.len(),
+            _ => 0,
+        };
+        let keys = self
+            .inner
+            .tags
+            .iter()
+            .map(|tag| PyString::new(py, &tag[prefix_len..]).unbind())
+            .collect();
+        let columns = self
+            .inner
+            .tags
+            .iter()
+            .enumerate()
+            .map(|(col, tag)| {
+                self.inner
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, values)| {
+                        if native {
+                            value_to_py(py, &values[col], ValueMode::Native)
+                        } else {
+                            let value = self.origin.value(&values[col], tag, Some(row));
+                            Ok(Py::new(py, value)?.into_any())
+                        }
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyRowDictIterator {
+            keys,
+            columns,
+            rows: self.inner.len(),
+            index: 0,
+        })
+    }
+
+    /// Convert to a pandas DataFrame with one column per tag
+    ///
+    /// Numeric columns become floats, sus dropped, with NaN for `?` and
+    /// `.`; other columns hold Python objects, with None for `?` and `.`.
+    /// `strip_prefix` names the columns as `iter_dicts()` does. Raises
+    /// ImportError without pandas.
+    #[pyo3(signature = (strip_prefix=false))]
+    fn to_pandas<'py>(&self, py: Python<'py>, strip_prefix: bool) -> PyResult<Bound<'py, PyAny>> {
+        let prefix_len = match self.inner.common_prefix() {
+            Some(prefix) if strip_prefix => prefix.len(),
+            _ => 0,
+        };
+        let columns = self.inner.tags.iter().map(|tag| &tag[prefix_len..]);
+        data_frame(py, &self.inner, columns)
+    }
+
+    /// Split the rows into one loop per value of the `tag` column, keyed by
+    /// that value's text; `keep_column=False` leaves the column out.
+    ///
+    /// Raises KeyError if the loop has no `tag` column.
+    #[pyo3(signature = (tag, keep_column=true))]
+    fn partition_by(&self, tag: &str, keep_column: bool) -> PyResult<IndexMap<String, PyLoop>> {
+        let parts = self
+            .inner
+            .partition_by(tag, keep_column)
+            .map_err(cif_error_to_py_err)?;
+        Ok(parts
+            .into_iter()
+            .map(|(id, part)| (id, PyLoop::from(part)))
+            .collect())
+    }
+
     /// Python iterator protocol
     fn __iter__(slf: PyRef<'_, Self>) -> PyResult<Py<PyLoopIterator>> {
         let py = slf.py();
@@ -321,9 +959,298 @@ impl PyLoop {
     }
 }
 
+impl PyLoop {
+    fn with_origin(loop_: &CifLoop, origin: Origin) -> Self {
+        PyLoop {
+            inner: loop_.clone(),
+            origin,
+            buffers: ColumnBuffers::default(),
+        }
+    }
+}
+
 impl From<CifLoop> for PyLoop {
     fn from(loop_: CifLoop) -> Self {
-        PyLoop { inner: loop_ }
+        PyLoop {
+            inner: loop_,
+            origin: Origin::default(),
+            buffers: ColumnBuffers::default(),
+        }
+    }
+}
+
+/// One category of a block read as a table, from `Block.category()`
+///
+/// Data items of the category are repeated on every row; values a short
+/// loop does not have are missing. Attributes are matched
+/// case-insensitively.
+#[pyclass(name = "Category", frozen)]
+pub struct PyCategory {
+    name: String,
+    tags: Vec<String>,
+    attributes: Vec<String>,
+    /// Whether each column is a data item rather than a loop column
+    items: Vec<bool>,
+    rows: Vec<Vec<Option<CifValue>>>,
+    origin: Origin,
+}
+
+#[pymethods]
+impl PyCategory {
+    /// Category name, without the leading underscore
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tags of the columns as spelled in the file: data items, then loop
+    /// columns
+    #[getter]
+    fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    /// Attributes of the columns (`Cartn_x` of `_atom_site.Cartn_x`)
+    #[getter]
+    fn attributes(&self) -> Vec<String> {
+        self.attributes.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The value of an attribute in a row, or None
+    fn get(&self, row: usize, attribute: &str) -> Option<PyValue> {
+        let column = self.column_index(attribute)?;
+        self.value(row, column)
+    }
+
+    /// Every value of an attribute, one per row, or None if the category
+    /// has no such attribute or a loop holding it is short
+    fn column(&self, attribute: &str) -> Option<Vec<PyValue>> {
+        let column = self.column_index(attribute)?;
+        (0..self.rows.len())
+            .map(|row| self.value(row, column))
+            .collect()
+    }
+
+    /// A row as a dictionary mapping attributes to values, skipping values
+    /// a short loop does not have
+    fn row(&self, row: usize) -> Option<IndexMap<String, PyValue>> {
+        if row >= self.rows.len() {
+            return None;
+        }
+        Some(
+            (0..self.attributes.len())
+                .filter_map(|column| {
+                    let value = self.value(row, column)?;
+                    Some((self.attributes[column].clone(), value))
+                })
+                .collect(),
+        )
+    }
+
+    /// Every row as a dictionary, as from `row()`
+    fn rows(&self) -> Vec<IndexMap<String, PyValue>> {
+        (0..self.rows.len())
+            .filter_map(|row| self.row(row))
+            .collect()
+    }
+
+    /// The table as a loop with the full tags, items repeated on every row
+    /// and `?` where a short loop has no value
+    fn to_loop(&self) -> PyLoop {
+        PyLoop::with_origin(&self.table(), self.origin.clone())
+    }
+
+    /// Convert to a pandas DataFrame with one column per attribute, as
+    /// `Loop.to_pandas()` does
+    fn to_pandas<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        data_frame(
+            py,
+            &self.table(),
+            self.attributes.iter().map(String::as_str),
+        )
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!("Category(name={:?}, rows={})", self.name, self.rows.len())
+    }
+}
+
+impl PyCategory {
+    fn new(category: &Category<'_>, block: &CifBlock) -> Self {
+        let tags: Vec<String> = category.tags().map(str::to_string).collect();
+        let attributes: Vec<String> = category.attributes().map(str::to_string).collect();
+        PyCategory {
+            name: category.name().trim_start_matches('_').to_string(),
+            items: tags
+                .iter()
+                .map(|tag| block.get_item(tag).is_some())
+                .collect(),
+            rows: (0..category.len())
+                .map(|row| {
+                    attributes
+                        .iter()
+                        .map(|attribute| category.get(row, attribute).cloned())
+                        .collect()
+                })
+                .collect(),
+            origin: Origin::in_block(&block.name),
+            tags,
+            attributes,
+        }
+    }
+
+    fn table(&self) -> CifLoop {
+        CifLoop {
+            tags: self.tags.clone(),
+            values: self
+                .rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|value| value.clone().unwrap_or(CifValue::Unknown))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    fn column_index(&self, attribute: &str) -> Option<usize> {
+        self.attributes
+            .iter()
+            .position(|other| other.eq_ignore_ascii_case(attribute))
+    }
+
+    /// A value with its source; data items have no row
+    fn value(&self, row: usize, column: usize) -> Option<PyValue> {
+        let value = self.rows.get(row)?[column].as_ref()?;
+        let source_row = (!self.items[column]).then_some(row);
+        Some(self.origin.value(value, &self.tags[column], source_row))
+    }
+}
+
+/// A pandas DataFrame of a loop's columns under the given names: floats
+/// with NaN for numeric columns, Python objects with None for the rest
+fn data_frame<'py, 'a>(
+    py: Python<'py>,
+    loop_: &CifLoop,
+    names: impl Iterator<Item = &'a str>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let pandas = py.import("pandas")?;
+    let data = PyDict::new(py);
+    for (col, (tag, name)) in loop_.tags.iter().zip(names).enumerate() {
+        let column = match loop_.numeric_column(tag) {
+            Some(Ok(numbers)) => numbers.into_pyobject(py)?.into_any(),
+            _ => {
+                let values = loop_
+                    .values
+                    .iter()
+                    .map(|row| value_to_py(py, &row[col], ValueMode::Native))
+                    .collect::<PyResult<Vec<_>>>()?;
+                PyList::new(py, values)?.into_any()
+            }
+        };
+        data.set_item(name, column)?;
+    }
+    pandas.getattr("DataFrame")?.call1((data,))
+}
+
+/// Numeric columns already converted for `Loop.column_buffer`, by tag.
+///
+/// A loop taken from a block is a snapshot, so entries never go stale; a
+/// block that is changed hands out new loops with empty caches, and
+/// `add_row` and `add_column` empty the cache of the loop they change.
+type ColumnBuffers = Arc<Mutex<HashMap<String, Arc<[f64]>>>>;
+
+/// A read-only float64 buffer over one loop column
+///
+/// Supports the buffer protocol, so `memoryview` and `numpy.frombuffer` read
+/// the numbers in place. The buffer owns its numbers, so it stays valid after
+/// the loop and document are gone.
+#[pyclass(name = "ColumnBuffer", frozen)]
+pub struct PyColumnBuffer {
+    tag: String,
+    data: Arc<[f64]>,
+    shape: [ffi::Py_ssize_t; 1],
+    strides: [ffi::Py_ssize_t; 1],
+}
+
+impl PyColumnBuffer {
+    fn new(tag: &str, data: Arc<[f64]>) -> Self {
+        PyColumnBuffer {
+            tag: tag.to_string(),
+            shape: [data.len() as ffi::Py_ssize_t],
+            strides: [size_of::<f64>() as ffi::Py_ssize_t],
+            data,
+        }
+    }
+}
+
+#[pymethods]
+impl PyColumnBuffer {
+    /// The tag of the column
+    #[getter]
+    fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Export the numbers as a one-dimensional, read-only buffer of doubles
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Column buffers are read-only"));
+        }
+        let this = slf.get();
+        let wants = |flag: c_int| flags & flag == flag;
+        // SAFETY: `view` is a valid Py_buffer supplied by the interpreter. The
+        // shape, strides, and numbers live in this object, which the view
+        // keeps alive through `obj` until the buffer is released.
+        unsafe {
+            (*view).buf = this.data.as_ptr() as *mut c_void;
+            (*view).len = (this.data.len() * size_of::<f64>()) as ffi::Py_ssize_t;
+            (*view).readonly = 1;
+            (*view).itemsize = size_of::<f64>() as ffi::Py_ssize_t;
+            (*view).format = if wants(ffi::PyBUF_FORMAT) {
+                c"d".as_ptr() as *mut c_char
+            } else {
+                ptr::null_mut()
+            };
+            (*view).ndim = 1;
+            (*view).shape = if wants(ffi::PyBUF_ND) {
+                this.shape.as_ptr() as *mut ffi::Py_ssize_t
+            } else {
+                ptr::null_mut()
+            };
+            (*view).strides = if wants(ffi::PyBUF_STRIDES) {
+                this.strides.as_ptr() as *mut ffi::Py_ssize_t
+            } else {
+                ptr::null_mut()
+            };
+            (*view).suboffsets = ptr::null_mut();
+            (*view).internal = ptr::null_mut();
+            (*view).obj = slf.into_any().into_ptr();
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+
+    fn __repr__(&self) -> String {
+        format!("ColumnBuffer('{}', {} values)", self.tag, self.data.len())
     }
 }
 
@@ -340,7 +1267,7 @@ impl PyLoopIterator {
         slf
     }
 
-    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<HashMap<String, PyValue>> {
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<IndexMap<String, PyValue>> {
         let py = slf.py();
         let current_index = slf.index;
 
@@ -363,11 +1290,47 @@ impl PyLoopIterator {
     }
 }
 
+/// Iterator over loop rows as dictionaries, from `Loop.iter_dicts`
+///
+/// Holds the converted columns, so each step only assembles a dict.
+#[pyclass]
+struct PyRowDictIterator {
+    keys: Vec<Py<PyString>>,
+    columns: Vec<Vec<Py<PyAny>>>,
+    rows: usize,
+    index: usize,
+}
+
+#[pymethods]
+impl PyRowDictIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        py: Python<'py>,
+    ) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let row = slf.index;
+        if row >= slf.rows {
+            return Ok(None);
+        }
+        slf.index += 1;
+        let dict = PyDict::new(py);
+        for (key, column) in slf.keys.iter().zip(&slf.columns) {
+            dict.set_item(key.bind(py), column[row].bind(py))?;
+        }
+        Ok(Some(dict))
+    }
+}
+
 /// Python wrapper for CifFrame
 #[pyclass(name = "Frame")]
 #[derive(Clone)]
 pub struct PyFrame {
     inner: CifFrame,
+    /// Name of the enclosing data block, if known
+    block: Option<String>,
 }
 
 #[pymethods]
@@ -386,15 +1349,22 @@ impl PyFrame {
 
     /// Get an item by key
     fn get_item(&self, key: &str) -> Option<PyValue> {
-        self.inner.items.get(key).map(|v| v.clone().into())
+        let origin = self.origin();
+        self.inner.get_item(key).map(|v| origin.value(v, key, None))
+    }
+
+    /// Get every value of a tag, from the data item or any loop column
+    fn get_all(&self, tag: &str) -> Vec<PyValue> {
+        all_values(&self.origin(), &self.inner.items, &self.inner.loops, tag)
     }
 
-    /// Get all items as a dictionary
-    fn items(&self) -> HashMap<String, PyValue> {
+    /// Get all items as a dictionary, in file order
+    fn items(&self) -> IndexMap<String, PyValue> {
+        let origin = self.origin();
         self.inner
             .items
             .iter()
-            .map(|(k, v)| (k.clone(), v.clone().into()))
+            .map(|(k, v)| (k.clone(), origin.value(v, k, None)))
             .collect()
     }
 
@@ -406,13 +1376,20 @@ impl PyFrame {
 
     /// Get a loop by index
     fn get_loop(&self, index: usize) -> Option<PyLoop> {
-        self.inner.loops.get(index).map(|l| l.clone().into())
+        self.inner
+            .loops
+            .get(index)
+            .map(|l| PyLoop::with_origin(l, self.origin()))
     }
 
     /// Get all loops
     #[getter]
     fn loops(&self) -> Vec<PyLoop> {
-        self.inner.loops.iter().map(|l| l.clone().into()).collect()
+        self.inner
+            .loops
+            .iter()
+            .map(|l| PyLoop::with_origin(l, self.origin()))
+            .collect()
     }
 
     /// String representation
@@ -436,17 +1413,40 @@ impl PyFrame {
     }
 }
 
+impl PyFrame {
+    fn in_block(frame: &CifFrame, block: &str) -> Self {
+        PyFrame {
+            inner: frame.clone(),
+            block: Some(block.to_string()),
+        }
+    }
+
+    fn origin(&self) -> Origin {
+        Origin {
+            block: self.block.clone(),
+            frame: Some(self.inner.name.clone()),
+        }
+    }
+}
+
 impl From<CifFrame> for PyFrame {
     fn from(frame: CifFrame) -> Self {
-        PyFrame { inner: frame }
+        PyFrame {
+            inner: frame,
+            block: None,
+        }
     }
 }
 
 /// Python wrapper for CifBlock with Pythonic interface
+///
+/// Blocks taken from a document share it through its handle, so a change made
+/// through any of them is seen by all. Each method reads one snapshot.
 #[pyclass(name = "Block")]
 #[derive(Clone)]
 pub struct PyBlock {
-    inner: CifBlock,
+    handle: DocumentHandle,
+    index: usize,
 }
 
 #[pymethods]
@@ -454,271 +1454,3702 @@ impl PyBlock {
     /// Get the block name
     #[getter]
     fn name(&self) -> String {
-        self.inner.name.clone()
+        self.block().name.clone()
     }
 
     /// Get all item keys
     #[getter]
     fn item_keys(&self) -> Vec<String> {
-        self.inner.items.keys().cloned().collect()
+        self.block().items.keys().cloned().collect()
     }
 
     /// Get an item by key
     fn get_item(&self, key: &str) -> Option<PyValue> {
-        self.inner.items.get(key).map(|v| v.clone().into())
+        let block = self.block();
+        let origin = Origin::in_block(&block.name);
+        block.get_item(key).map(|v| origin.value(v, key, None))
     }
 
-    /// Get all items as a dictionary
-    fn items(&self) -> HashMap<String, PyValue> {
-        self.inner
+    /// A data item as a float, dropping any su; None if absent, `?`, or `.`
+    ///
+    /// Raises ValueError if the value is not a number.
+    fn get_float(&self, tag: &str) -> PyResult<Option<f64>> {
+        conversion_result(self.block().get_f64(tag))
+    }
+
+    /// A data item as an int; None if absent, `?`, or `.`
+    ///
+    /// Raises ValueError if the value is not a whole number.
+    fn get_int(&self, tag: &str) -> PyResult<Option<i64>> {
+        conversion_result(self.block().get_i64(tag))
+    }
+
+    /// A data item as a string; None if absent, `?`, or `.`
+    ///
+    /// Numbers are formatted again in their shortest form, so `007` gives
+    /// "7"; only a text field keeps digits as written.
+    ///
+    /// Raises ValueError for a list or table.
+    fn get_str(&self, tag: &str) -> PyResult<Option<String>> {
+        conversion_result(self.block().get_str(tag))
+    }
+
+    /// A data item as a bool, from yes/no, y/n, true/false, or 1/0; None if
+    /// absent, `?`, or `.`
+    ///
+    /// Raises ValueError for any other value.
+    fn get_bool(&self, tag: &str) -> PyResult<Option<bool>> {
+        conversion_result(self.block().get_bool(tag))
+    }
+
+    /// Set a data item to a number, text, None for `?`, or a list or dict
+    /// for CIF 2.0; a new item goes after the others
+    ///
+    /// Raises ValueError if the tag is a loop column or not a data name.
+    fn __setitem__(&self, tag: String, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let value = value_from_py(value)?;
+        self.handle
+            .update_block(self.index, |block| block.set_item(tag, value))
+            .expect("blocks are never removed from a document handle")
+            .map(drop)
+            .map_err(cif_error_to_py_err)
+    }
+
+    /// Remove a data item; raises KeyError if there is none
+    fn __delitem__(&self, tag: &str) -> PyResult<()> {
+        self.handle
+            .update_block(self.index, |block| block.remove_item(tag))
+            .expect("blocks are never removed from a document handle")
+            .map(drop)
+            .ok_or_else(|| PyKeyError::new_err(format!("No data item '{tag}'")))
+    }
+
+    /// Add a copy of a loop after the others
+    ///
+    /// Raises ValueError if a tag of the loop is already a data item or a
+    /// column of another loop.
+    fn add_loop(&self, loop_: &PyLoop) -> PyResult<()> {
+        self.handle
+            .update_block(self.index, |block| {
+                block.add_loop(loop_.inner.clone()).map(drop)
+            })
+            .expect("blocks are never removed from a document handle")
+            .map_err(cif_error_to_py_err)
+    }
+
+    /// Get a data item, searching save frames too if `search_frames` is set
+    ///
+    /// The block's own items win; after that, the first frame in file order
+    /// with the tag does, and the value's source names that frame.
+    #[pyo3(signature = (tag, search_frames=false))]
+    fn get(&self, tag: &str, search_frames: bool) -> Option<PyValue> {
+        let block = self.block();
+        if block.get_item(tag).is_some() || !search_frames {
+            return self.get_item(tag);
+        }
+        block.frames.iter().find_map(|frame| {
+            let value = frame.get_item(tag)?;
+            let row = (!frame.items.contains_key(tag)).then_some(0);
+            Some(Self::frame_origin(&block, frame).value(value, tag, row))
+        })
+    }
+
+    /// Every data item and loop in the block and its save frames
+    ///
+    /// Returns `(path, tag, value)` tuples in walk order, where `path` names
+    /// the frames containing the entry (empty for the block itself). Loops
+    /// come as `(path, None, loop)`.
+    fn walk<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let block = self.block();
+        let list = PyList::empty(py);
+        for (path, entry) in block.walk() {
+            let origin = match block
+                .frames
+                .iter()
+                .find(|f| path.last() == Some(&f.name.as_str()))
+            {
+                Some(frame) => Self::frame_origin(&block, frame),
+                None => Origin::in_block(&block.name),
+            };
+            match entry {
+                WalkEntry::Item(tag, value) => {
+                    list.append((path, tag, origin.value(value, tag, None)))?
+                }
+                WalkEntry::Loop(loop_) => {
+                    list.append((path, None::<&str>, PyLoop::with_origin(loop_, origin)))?
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    /// Get every value of a tag, from the data item or any loop column
+    ///
+    /// Repeated items collapsed into a loop (`duplicate_tags="collapse"`)
+    /// come back in their original order.
+    fn get_all(&self, tag: &str) -> Vec<PyValue> {
+        let block = self.block();
+        let origin = Origin::in_block(&block.name);
+        all_values(&origin, &block.items, &block.loops, tag)
+    }
+
+    /// Get all items as a dictionary, in file order
+    fn items(&self) -> IndexMap<String, PyValue> {
+        let block = self.block();
+        let origin = Origin::in_block(&block.name);
+        block
             .items
             .iter()
-            .map(|(k, v)| (k.clone(), v.clone().into()))
+            .map(|(k, v)| (k.clone(), origin.value(v, k, None)))
+            .collect()
+    }
+
+    /// Get all items as (tag, value) pairs in file order
+    fn items_ordered(&self) -> Vec<(String, PyValue)> {
+        let block = self.block();
+        let origin = Origin::in_block(&block.name);
+        block
+            .iter_items()
+            .map(|(k, v)| (k.to_string(), origin.value(v, k, None)))
             .collect()
     }
 
+    /// Iterate over tags: data items in file order, then loop columns
+    fn __iter__(&self) -> PyTagIterator {
+        let tags: Vec<String> = self
+            .block()
+            .iter_all_tags()
+            .map(|(tag, _)| tag.to_string())
+            .collect();
+        PyTagIterator {
+            tags: tags.into_iter(),
+        }
+    }
+
     /// Get the number of loops
     #[getter]
     fn num_loops(&self) -> usize {
-        self.inner.loops.len()
+        self.block().loops.len()
     }
 
     /// Get a loop by index
     fn get_loop(&self, index: usize) -> Option<PyLoop> {
-        self.inner.loops.get(index).map(|l| l.clone().into())
+        let block = self.block();
+        block.loops.get(index).map(|l| Self::wrap_loop(&block, l))
     }
 
     /// Find a loop containing a specific tag
     fn find_loop(&self, tag: &str) -> Option<PyLoop> {
-        self.inner.find_loop(tag).map(|l| l.clone().into())
+        let block = self.block();
+        block
+            .block()
+            .find_loop(tag)
+            .map(|l| Self::wrap_loop(&block, l))
     }
 
     /// Get all loops
     #[getter]
     fn loops(&self) -> Vec<PyLoop> {
-        self.inner.loops.iter().map(|l| l.clone().into()).collect()
+        let block = self.block();
+        block
+            .loops
+            .iter()
+            .map(|l| Self::wrap_loop(&block, l))
+            .collect()
     }
 
     /// Get all loop tags
     fn get_loop_tags(&self) -> Vec<String> {
-        self.inner.get_loop_tags().into_iter().cloned().collect()
+        self.block().get_loop_tags().into_iter().cloned().collect()
+    }
+
+    /// One block per dataset, splitting every loop with an `id_tag` column
+    /// (such as `_diffrn_id`) by its value
+    ///
+    /// Each block has this block's items and frames, the loops without
+    /// `id_tag`, and its own rows of the loops with it.
+    fn datasets(&self, id_tag: &str) -> IndexMap<String, PyBlock> {
+        self.block()
+            .datasets(id_tag)
+            .into_iter()
+            .map(|(id, block)| (id, PyBlock::from(block)))
+            .collect()
     }
 
     /// Get the number of frames
     #[getter]
     fn num_frames(&self) -> usize {
-        self.inner.frames.len()
+        self.block().frames.len()
     }
 
     /// Get a frame by index
     fn get_frame(&self, index: usize) -> Option<PyFrame> {
-        self.inner.frames.get(index).map(|f| f.clone().into())
+        let block = self.block();
+        block
+            .frames
+            .get(index)
+            .map(|f| PyFrame::in_block(f, &block.name))
     }
 
     /// Get all frames
     #[getter]
     fn frames(&self) -> Vec<PyFrame> {
-        self.inner.frames.iter().map(|f| f.clone().into()).collect()
+        let block = self.block();
+        block
+            .frames
+            .iter()
+            .map(|f| PyFrame::in_block(f, &block.name))
+            .collect()
     }
 
-    /// String representation
-    fn __str__(&self) -> String {
-        format!(
-            "Block('{}', {} items, {} loops, {} frames)",
-            self.inner.name,
-            self.inner.items.len(),
-            self.inner.loops.len(),
-            self.inner.frames.len()
-        )
+    /// Convert the whole block to plain nested Python data in a single pass
+    ///
+    /// `values` is `'native'` (`?`/`.` become None) or `'raw'` (kept as strings);
+    /// `loops` is `'rows'` (`{tags, rows}`) or `'columns'` (`{tag: [values]}`).
+    #[pyo3(signature = (values="native", include_loops=true, include_frames=true, loops="rows"))]
+    fn to_dict<'py>(
+        &self,
+        py: Python<'py>,
+        values: &str,
+        include_loops: bool,
+        include_frames: bool,
+        loops: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let options = DictOptions {
+            values: ValueMode::from_arg(values)?,
+            loops: LoopLayout::from_arg(loops)?,
+            include_loops,
+            include_frames,
+        };
+        block_to_py(py, &self.block(), options)
     }
 
-    /// Debug representation
-    fn __repr__(&self) -> String {
-        format!(
-            "Block(name='{}', items={}, loops={}, frames={})",
-            self.inner.name,
-            self.inner.items.len(),
-            self.inner.loops.len(),
-            self.inner.frames.len()
-        )
+    /// Gather publication metadata (`_publ_*`, `_journal_*`)
+    fn publication(&self) -> PyPublication {
+        let block = self.block();
+        PyPublication {
+            inner: block.publication(),
+            id: block.name.clone(),
+        }
     }
-}
 
-impl From<CifBlock> for PyBlock {
-    fn from(block: CifBlock) -> Self {
-        PyBlock { inner: block }
+    /// Gather database codes and DOIs (`_database_code_*`, `_database_2`)
+    fn identifiers(&self) -> PyIdentifiers {
+        PyIdentifiers {
+            inner: self.block().identifiers(),
+        }
     }
-}
 
-/// Python wrapper for CifDocument with Pythonic interface
-#[pyclass(name = "Document")]
-#[derive(Clone)]
-pub struct PyDocument {
-    inner: CifDocument,
-}
+    /// Split loops that mix tag categories into one loop per category
+    ///
+    /// Returns one dict per split loop, with the original `tags`, the
+    /// resulting `categories`, and the `key` column copied into each loop
+    /// (or `None`). `categories` adds category names, such as `pd_proc`, to
+    /// the built-in core CIF ones.
+    #[pyo3(signature = (categories=None))]
+    fn repair_mixed_loops<'py>(
+        &self,
+        py: Python<'py>,
+        categories: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let splits = PyList::empty(py);
+        let resolver = category_resolver(categories);
+        let repaired = self
+            .handle
+            .update_block(self.index, |block| block.repair_mixed_loops_with(&resolver))
+            .unwrap_or_default();
+        for split in repaired {
+            let dict = PyDict::new(py);
+            dict.set_item("tags", split.tags)?;
+            dict.set_item("categories", split.categories)?;
+            dict.set_item("key", split.key)?;
+            splits.append(dict)?;
+        }
+        Ok(splits)
+    }
 
-#[pymethods]
-impl PyDocument {
-    /// Parse a CIF string
-    #[staticmethod]
-    fn parse(content: &str) -> PyResult<PyDocument> {
-        CifDocument::parse(content)
-            .map(|doc| PyDocument { inner: doc })
-            .map_err(cif_error_to_py_err)
+    /// One category as a table merging its data items and loop columns,
+    /// by name (`atom_site` or `_atom_site`), or `None` if the block has
+    /// no tags in it
+    ///
+    /// `categories` adds category names, such as `pd_proc`, to the
+    /// built-in core CIF ones.
+    #[pyo3(signature = (name, categories=None))]
+    fn category(&self, name: &str, categories: Option<Vec<String>>) -> Option<PyCategory> {
+        let block = self.block();
+        let resolver = category_resolver(categories);
+        let category = block.category_with(name, &resolver)?;
+        Some(PyCategory::new(&category, &block))
     }
 
-    /// Parse a CIF file
-    #[staticmethod]
-    fn from_file(path: &str) -> PyResult<PyDocument> {
-        CifDocument::from_file(path)
-            .map(|doc| PyDocument { inner: doc })
-            .map_err(cif_error_to_py_err)
+    /// `category(name, categories)` as a pandas DataFrame with one column
+    /// per attribute, or `None` if the block has no tags in it
+    #[pyo3(signature = (name, categories=None))]
+    fn category_to_pandas<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        categories: Option<Vec<String>>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.category(name, categories)
+            .map(|category| category.to_pandas(py))
+            .transpose()
     }
 
-    /// Get the CIF version of this document
+    /// Categories of the data items and loop columns, without the leading
+    /// underscore, in the order they first appear
+    fn categories(&self) -> Vec<String> {
+        self.block()
+            .categories()
+            .into_iter()
+            .map(|category| category.trim_start_matches('_').to_string())
+            .collect()
+    }
+
+    /// Unit cell with its sus, or `None` unless all six parameters are given
+    fn unit_cell(&self) -> Option<PyUnitCell> {
+        self.block().unit_cell().map(|inner| PyUnitCell { inner })
+    }
+
+    /// Symmetry operations of `_space_group_symop_operation_xyz` (or
+    /// `_symmetry_equiv_pos_as_xyz`), skipping any that cannot be parsed
+    fn symmetry_operations(&self) -> Vec<PySymOp> {
+        self.block()
+            .symmetry_operations()
+            .into_iter()
+            .map(|inner| PySymOp { inner })
+            .collect()
+    }
+
+    /// Space group named by the Hall symbol, Hermann–Mauguin symbol, or
+    /// space group number, tried in that order, or `None`
     ///
-    /// Returns the detected or explicitly set CIF version.
-    /// CIF 2.0 is indicated by the `#\#CIF_2.0` magic header.
-    /// Documents without this header default to CIF 1.1.
-    #[getter]
-    fn version(&self) -> PyVersion {
-        self.inner.version.into()
+    /// Use this for the operations when `symmetry_operations()` is empty.
+    /// The number is only used without a Hermann–Mauguin symbol.
+    fn space_group(&self) -> Option<PySpaceGroup> {
+        self.block()
+            .space_group()
+            .map(|inner| PySpaceGroup { inner })
     }
 
-    /// Check if this document is CIF 2.0
+    /// Point group of the symmetry operations, or `None` if there are none
+    /// or they do not form a point group
+    fn point_group(&self) -> Option<PyPointGroup> {
+        self.block()
+            .point_group()
+            .map(|inner| PyPointGroup { inner })
+    }
+
+    /// Atom sites with fractional coordinates, in file order, or `None`
+    /// without a unit cell
     ///
-    /// CIF 2.0 adds support for lists, tables, and other advanced features.
-    fn is_cif2(&self) -> bool {
-        matches!(self.inner.version, CifVersion::V2_0)
+    /// Each site is a dict with `label`, `type_symbol`, `fract`, `fract_su`,
+    /// `occupancy`, `u_iso`, `u_aniso`, `adp_type`, and `disorder_group`;
+    /// Cartesian coordinates are converted.
+    fn atom_sites<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyList>>> {
+        let Some(structure) = self.block().structure() else {
+            return Ok(None);
+        };
+        atom_site_list(py, &structure.sites).map(Some)
     }
 
-    /// Check if this document is CIF 1.1
-    fn is_cif1(&self) -> bool {
-        matches!(self.inner.version, CifVersion::V1_1)
+    /// Cell, symmetry operations, and atom sites, or `None` without a unit
+    /// cell
+    fn structure(&self) -> Option<PyStructure> {
+        self.block().structure().map(|inner| PyStructure { inner })
     }
 
-    /// Get the number of blocks
-    fn __len__(&self) -> usize {
-        self.inner.blocks.len()
+    /// Residues of a PDBx block in label and author numbering, or `None`
+    /// without `_atom_site` or `_pdbx_poly_seq_scheme`
+    fn chain_map(&self) -> Option<PyChainMap> {
+        self.block()
+            .chain_mapping()
+            .map(|inner| PyChainMap { inner })
     }
 
-    /// Get a block by index
-    fn get_block(&self, index: usize) -> Option<PyBlock> {
-        self.inner.blocks.get(index).map(|b| b.clone().into())
+    /// The `_atom_site` rows of the author's chain `auth_chain` with an
+    /// `auth_seq_id` from `auth_seq[0]` to `auth_seq[1]` inclusive (all if
+    /// not given), or `None` without an `_atom_site` loop
+    #[pyo3(signature = (auth_chain, auth_seq=None))]
+    fn select(&self, auth_chain: &str, auth_seq: Option<(i64, i64)>) -> Option<PyLoop> {
+        let (first, last) = auth_seq.unwrap_or((i64::MIN, i64::MAX));
+        let block = self.block();
+        block
+            .select_auth(auth_chain, first..=last)
+            .map(|l| Self::wrap_loop(&block, &l))
     }
 
-    /// Get a block by name
-    fn get_block_by_name(&self, name: &str) -> Option<PyBlock> {
-        self.inner.get_block(name).map(|b| b.clone().into())
+    /// Measured reflections from the `_diffrn_refln` or `_refln` loop, or
+    /// `None` if there is neither
+    fn reflections(&self) -> Option<PyReflectionData> {
+        self.block()
+            .reflections()
+            .map(|inner| PyReflectionData { inner })
     }
 
-    /// Get the first block
-    fn first_block(&self) -> Option<PyBlock> {
-        self.inner.first_block().map(|b| b.clone().into())
+    /// Orientation matrix UB as three rows, or `None` unless all nine
+    /// `_diffrn_orient_matrix_UB_ij` elements are given
+    fn orientation_matrix(&self) -> Option<Vec<Vec<f64>>> {
+        self.block()
+            .orientation_matrix()
+            .map(|ub| ub.iter().map(|row| row.to_vec()).collect())
     }
 
-    /// Get all blocks
-    #[getter]
-    fn blocks(&self) -> Vec<PyBlock> {
-        self.inner.blocks.iter().map(|b| b.clone().into()).collect()
+    /// Goniometer axes from imgCIF `_axis` and `_diffrn_measurement_axis`
+    /// loops, as dicts with `id`, `type`, `vector`, and `depends_on`
+    fn goniometer_axes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let axes = PyList::empty(py);
+        for axis in self.block().goniometer_axes() {
+            let dict = PyDict::new(py);
+            dict.set_item("id", axis.id)?;
+            dict.set_item("type", axis.kind)?;
+            dict.set_item("vector", axis.vector.map(|v| v.to_vec()))?;
+            dict.set_item("depends_on", axis.depends_on)?;
+            axes.append(dict)?;
+        }
+        Ok(axes)
     }
 
-    /// Get all block names
-    #[getter]
-    fn block_names(&self) -> Vec<String> {
-        self.inner.blocks.iter().map(|b| b.name.clone()).collect()
+    /// Compare |det(UB)| with 1/V of the unit cell
+    ///
+    /// Returns a dict with `determinant`, `reciprocal_volume`, `discrepancy`
+    /// (relative), `sigmas` (in σ of 1/V, or `None` without cell sus), and
+    /// `consistent`, or `None` without both a matrix and a cell.
+    #[pyo3(signature = (tolerance = 0.01))]
+    fn check_ub<'py>(
+        &self,
+        py: Python<'py>,
+        tolerance: f64,
+    ) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(check) = self.block().check_ub(tolerance) else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        dict.set_item("determinant", check.determinant)?;
+        dict.set_item("reciprocal_volume", check.reciprocal_volume)?;
+        dict.set_item("discrepancy", check.discrepancy)?;
+        dict.set_item("sigmas", check.sigmas)?;
+        dict.set_item("consistent", check.consistent)?;
+        Ok(Some(dict))
     }
 
-    /// Python iterator protocol
-    fn __iter__(slf: PyRef<'_, Self>) -> PyDocumentIterator {
-        PyDocumentIterator {
-            doc: slf.clone(),
-            index: 0,
+    /// Check cell, atom-site, and other values against fixed physical limits
+    ///
+    /// Returns one dict per suspect value, with its `tag`, loop `row` (or
+    /// `None`), `value` as written in CIF, and the `rule` it breaks.
+    fn sanity_check<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        issues_to_py(py, self.block().sanity_check())
+    }
+
+    /// Values of enumerated core tags outside their vocabulary
+    ///
+    /// Returns one dict per value, with its `tag`, loop `row` (or `None`),
+    /// `value` as written in CIF, the nearest allowed value as `suggestion`
+    /// (or `None`), and the `allowed` values.
+    fn check_vocabularies<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let issues = PyList::empty(py);
+        for issue in self.block().check_vocabularies() {
+            let dict = PyDict::new(py);
+            dict.set_item("tag", issue.tag)?;
+            dict.set_item("row", issue.row)?;
+            dict.set_item("value", issue.value)?;
+            dict.set_item("suggestion", issue.suggestion)?;
+            dict.set_item("allowed", issue.vocabulary.values.to_vec())?;
+            issues.append(dict)?;
         }
+        Ok(issues)
     }
 
-    /// Python getitem protocol (allows doc[0], doc["name"])
-    fn __getitem__(&self, key: &Bound<'_, PyAny>) -> PyResult<PyBlock> {
-        // Try to extract as signed integer first to handle negative indices
-        if let Ok(index) = key.extract::<isize>() {
-            let len = self.inner.blocks.len() as isize;
-            let actual_index = if index < 0 {
-                // Python-style negative indexing
-                let positive_index = len + index;
-                if positive_index < 0 {
-                    return Err(PyIndexError::new_err("Block index out of range"));
-                }
-                positive_index as usize
-            } else {
-                index as usize
-            };
+    /// Check the block's values against a dictionary stack
+    ///
+    /// Returns one dict per value that breaks its definition; see
+    /// `Document.validate`.
+    fn validate<'py>(
+        &self,
+        py: Python<'py>,
+        stack: &PyDictionaryStack,
+    ) -> PyResult<Bound<'py, PyList>> {
+        validation_issues_to_py(py, self.block().validate(&stack.inner))
+    }
 
-            self.inner
-                .blocks
-                .get(actual_index)
-                .map(|b| b.clone().into())
-                .ok_or_else(|| PyIndexError::new_err("Block index out of range"))
-        } else if let Ok(name) = key.extract::<String>() {
-            self.inner
-                .get_block(&name)
-                .map(|b| b.clone().into())
-                .ok_or_else(|| PyKeyError::new_err(format!("Block '{name}' not found")))
-        } else {
-            Err(PyTypeError::new_err("Block key must be int or str"))
+    /// Original values recorded by aggregators, each with the current value
+    ///
+    /// Finds items such as `_cod_original_cell_volume` under `prefixes`
+    /// (by default `_cod_original_` and `_iucr_original_`). Returns one dict
+    /// per original, with its `original_tag`, the current `tag`, the
+    /// `original` and `current` values (`current` is None if missing), and
+    /// whether they `differs`: numbers by more than `n_sigma` sus, given as
+    /// `sigmas`, and anything else as text.
+    #[pyo3(signature = (*, prefixes=None, n_sigma=1.0))]
+    fn provenance<'py>(
+        &self,
+        py: Python<'py>,
+        prefixes: Option<Vec<String>>,
+        n_sigma: f64,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let options = provenance_options(prefixes, n_sigma);
+        let overrides = PyList::empty(py);
+        for found in self.block().provenance_overrides_with_options(&options) {
+            overrides.append(provenance_override_to_py(py, &found)?)?;
+        }
+        Ok(overrides)
+    }
+
+    /// Put back the original value of a tag, such as `_cell_volume`
+    ///
+    /// The original replaces the current value, its own item is removed, and
+    /// a line saying what was replaced is added to `_audit_update_record`.
+    /// Returns the values as they were, as `provenance()` does; raises
+    /// ValueError if no original is recorded.
+    #[pyo3(signature = (tag, *, prefixes=None))]
+    fn restore_original<'py>(
+        &self,
+        py: Python<'py>,
+        tag: &str,
+        prefixes: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let options = provenance_options(prefixes, 1.0);
+        let restored = self
+            .handle
+            .update_block(self.index, |block| {
+                block.restore_original_with_options(tag, &options)
+            })
+            .expect("blocks are never removed from a document handle")
+            .map_err(cif_error_to_py_err)?;
+        provenance_override_to_py(py, &restored)
+    }
+
+    /// Gather the crystal, temperature, absorption, and density items
+    fn experimental(&self) -> PyExperimental {
+        PyExperimental {
+            inner: self.block().experimental(),
+        }
+    }
+
+    /// Gather the standard reflections, scan, and instruments of the
+    /// measurement
+    fn measurement(&self) -> PyMeasurement {
+        PyMeasurement {
+            inner: self.block().measurement(),
+        }
+    }
+
+    /// Gather the refinement model: counts, weights, extinction, Flack
+    /// parameter, hydrogen treatment, and restraints
+    fn refinement_model(&self) -> PyRefinementModel {
+        PyRefinementModel {
+            inner: self.block().refinement_model(),
         }
     }
 
+    /// Check that the values of each tag convert to the type the schema gives
+    ///
+    /// `schema` maps tags to `'float'`, `'float_su'` (a number with an
+    /// optional su), `'int'`, `'date'`, `'bool'`, or `'text'`, or to a list of
+    /// allowed codes. Every value is tried; see `CoercionReport` for the result.
+    fn check_schema(&self, schema: &Bound<'_, PyDict>) -> PyResult<PyCoercionReport> {
+        let mut tags = Vec::new();
+        // A named type, or the codes of an enumeration
+        let mut types: Vec<Result<ExpectedType<'static>, Vec<String>>> = Vec::new();
+        for (tag, expected) in schema.iter() {
+            tags.push(tag.extract::<String>()?);
+            types.push(match expected.extract::<String>() {
+                Ok(name) => Ok(expected_type(&name)?),
+                Err(_) => Err(expected.extract::<Vec<String>>().map_err(|_| {
+                    PyTypeError::new_err(
+                        "schema values must be a type name or a list of allowed codes",
+                    )
+                })?),
+            });
+        }
+        let codes: Vec<Vec<&str>> = types
+            .iter()
+            .map(|expected| match expected {
+                Ok(_) => Vec::new(),
+                Err(codes) => codes.iter().map(String::as_str).collect(),
+            })
+            .collect();
+        let schema: Vec<(&str, ExpectedType)> = tags
+            .iter()
+            .zip(&types)
+            .zip(&codes)
+            .map(|((tag, expected), codes)| {
+                let expected = match expected {
+                    Ok(expected) => *expected,
+                    Err(_) => ExpectedType::Enum(codes),
+                };
+                (tag.as_str(), expected)
+            })
+            .collect();
+        Ok(PyCoercionReport {
+            inner: self.block().coercion_report(&schema),
+        })
+    }
+
+    /// Translate an mmCIF structure into core CIF tags
+    ///
+    /// Tags that could not be translated are listed in a `UserWarning`, or
+    /// raise `ValueError` when `strict` is true.
+    #[pyo3(signature = (*, strict = false))]
+    fn to_core_cif(&self, py: Python<'_>, strict: bool) -> PyResult<PyBlock> {
+        conversion_to_py(py, self.block().to_core_cif(), strict)
+    }
+
+    /// Translate a core CIF structure into mmCIF tags
+    ///
+    /// Tags that could not be translated are listed in a `UserWarning`, or
+    /// raise `ValueError` when `strict` is true.
+    #[pyo3(signature = (*, strict = false))]
+    fn to_mmcif(&self, py: Python<'_>, strict: bool) -> PyResult<PyBlock> {
+        conversion_to_py(py, self.block().to_mmcif(), strict)
+    }
+
     /// String representation
     fn __str__(&self) -> String {
-        format!("Document({} blocks)", self.inner.blocks.len())
+        let block = self.block();
+        format!(
+            "Block('{}', {} items, {} loops, {} frames)",
+            block.name,
+            block.items.len(),
+            block.loops.len(),
+            block.frames.len()
+        )
     }
 
     /// Debug representation
     fn __repr__(&self) -> String {
-        let names: Vec<&str> = self.inner.blocks.iter().map(|b| b.name.as_str()).collect();
-        format!("Document(blocks={names:?})")
+        let block = self.block();
+        format!(
+            "Block(name='{}', items={}, loops={}, frames={})",
+            block.name,
+            block.items.len(),
+            block.loops.len(),
+            block.frames.len()
+        )
     }
 }
 
-/// Iterator for PyDocument
-#[pyclass]
-pub struct PyDocumentIterator {
-    doc: PyDocument,
-    index: usize,
+impl PyBlock {
+    /// The current version of this block
+    fn block(&self) -> SharedBlock {
+        self.handle
+            .block(self.index)
+            .expect("blocks are never removed from a document handle")
+    }
+
+    fn wrap_loop(block: &CifBlock, loop_: &CifLoop) -> PyLoop {
+        PyLoop::with_origin(loop_, Origin::in_block(&block.name))
+    }
+
+    fn frame_origin(block: &CifBlock, frame: &CifFrame) -> Origin {
+        Origin {
+            block: Some(block.name.clone()),
+            frame: Some(frame.name.clone()),
+        }
+    }
 }
 
-#[pymethods]
-impl PyDocumentIterator {
-    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
-        slf
+/// Return a converted block, warning about (or, when strict, raising on)
+/// anything that was not translated
+fn conversion_to_py(
+    py: Python<'_>,
+    result: Result<CifBlock, ConversionReport>,
+    strict: bool,
+) -> PyResult<PyBlock> {
+    match result {
+        Ok(block) => Ok(block.into()),
+        Err(report) if strict => Err(PyValueError::new_err(report.to_string())),
+        Err(report) => {
+            let message = CString::new(report.to_string())
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            let category = py.get_type::<PyUserWarning>();
+            PyErr::warn(py, category.as_any(), &message, 1)?;
+            Ok((*report.block).into())
+        }
     }
+}
 
-    fn __next__(&mut self) -> Option<PyBlock> {
-        if self.index < self.doc.inner.blocks.len() {
-            let block = self.doc.inner.blocks[self.index].clone().into();
-            self.index += 1;
-            Some(block)
-        } else {
-            None
+impl From<CifBlock> for PyBlock {
+    fn from(block: CifBlock) -> Self {
+        let mut doc = CifDocument::new();
+        doc.blocks.push(block);
+        PyBlock {
+            handle: doc.into(),
+            index: 0,
         }
     }
 }
 
-/// Module initialization function
-#[pymodule]
-fn _cif_parser(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+/// Python wrapper for a publication author
+#[pyclass(name = "Author", frozen)]
+#[derive(Clone)]
+pub struct PyAuthor {
+    inner: Author,
+}
+
+#[pymethods]
+impl PyAuthor {
+    /// Name as written, with footnote markers removed
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    /// Family name
+    #[getter]
+    fn family(&self) -> String {
+        self.inner.family.clone()
+    }
+
+    /// Given names or initials
+    #[getter]
+    fn given(&self) -> Option<String> {
+        self.inner.given.clone()
+    }
+
+    /// Postal address
+    #[getter]
+    fn address(&self) -> Option<String> {
+        self.inner.address.clone()
+    }
+
+    /// Email address
+    #[getter]
+    fn email(&self) -> Option<String> {
+        self.inner.email.clone()
+    }
+
+    /// ORCID identifier
+    #[getter]
+    fn orcid(&self) -> Option<String> {
+        self.inner.orcid.clone()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!("Author('{}')", self.inner.name)
+    }
+}
+
+impl From<Author> for PyAuthor {
+    fn from(author: Author) -> Self {
+        PyAuthor { inner: author }
+    }
+}
+
+/// Python wrapper for publication metadata
+#[pyclass(name = "Publication", frozen)]
+pub struct PyPublication {
+    inner: Publication,
+    /// Citation key for CSL output (the block name)
+    id: String,
+}
+
+#[pymethods]
+impl PyPublication {
+    /// Title with line folding removed and whitespace collapsed
+    #[getter]
+    fn title(&self) -> Option<String> {
+        self.inner.title.clone()
+    }
+
+    /// Authors in file order
+    #[getter]
+    fn authors(&self) -> Vec<PyAuthor> {
+        self.inner.authors.iter().cloned().map(Into::into).collect()
+    }
+
+    /// Contact author, if given
+    #[getter]
+    fn contact_author(&self) -> Option<PyAuthor> {
+        self.inner.contact_author.clone().map(Into::into)
+    }
+
+    /// Full journal name
+    #[getter]
+    fn journal(&self) -> Option<String> {
+        self.inner.journal.clone()
+    }
+
+    /// Journal volume
+    #[getter]
+    fn volume(&self) -> Option<String> {
+        self.inner.volume.clone()
+    }
+
+    /// Journal issue
+    #[getter]
+    fn issue(&self) -> Option<String> {
+        self.inner.issue.clone()
+    }
+
+    /// Publication year
+    #[getter]
+    fn year(&self) -> Option<i32> {
+        self.inner.year
+    }
+
+    /// Page range as `first-last`
+    #[getter]
+    fn pages(&self) -> Option<String> {
+        self.inner.pages()
+    }
+
+    /// DOI of the paper
+    #[getter]
+    fn doi(&self) -> Option<String> {
+        self.inner.doi.clone()
+    }
+
+    /// Convert to a Citation Style Language (CSL-JSON) item
+    fn to_csl_json<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let publ = &self.inner;
+        let item = PyDict::new(py);
+        item.set_item("id", &self.id)?;
+        item.set_item("type", "article-journal")?;
+        if let Some(title) = &publ.title {
+            item.set_item("title", title)?;
+        }
+        if !publ.authors.is_empty() {
+            let authors = PyList::empty(py);
+            for author in &publ.authors {
+                let name = PyDict::new(py);
+                name.set_item("family", &author.family)?;
+                if let Some(given) = &author.given {
+                    name.set_item("given", given)?;
+                }
+                authors.append(name)?;
+            }
+            item.set_item("author", authors)?;
+        }
+        let fields = [
+            ("container-title", publ.journal.clone()),
+            ("volume", publ.volume.clone()),
+            ("issue", publ.issue.clone()),
+            ("page", publ.pages()),
+            ("DOI", publ.doi.clone()),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                item.set_item(key, value)?;
+            }
+        }
+        if let Some(year) = publ.year {
+            let issued = PyDict::new(py);
+            issued.set_item("date-parts", vec![vec![year]])?;
+            item.set_item("issued", issued)?;
+        }
+        Ok(item)
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let title = match &self.inner.title {
+            Some(title) => format!("'{title}'"),
+            None => "None".to_string(),
+        };
+        format!(
+            "Publication(title={}, authors={})",
+            title,
+            self.inner.authors.len()
+        )
+    }
+}
+
+/// Python wrapper for database codes and DOIs
+#[pyclass(name = "Identifiers", frozen)]
+pub struct PyIdentifiers {
+    inner: Identifiers,
+}
+
+#[pymethods]
+impl PyIdentifiers {
+    /// Cambridge Structural Database refcode
+    #[getter]
+    fn csd(&self) -> Option<String> {
+        self.inner.csd.clone()
+    }
+
+    /// Inorganic Crystal Structure Database collection code
+    #[getter]
+    fn icsd(&self) -> Option<String> {
+        self.inner.icsd.clone()
+    }
+
+    /// Crystallography Open Database code
+    #[getter]
+    fn cod(&self) -> Option<String> {
+        self.inner.cod.clone()
+    }
+
+    /// CCDC deposition number, as written
+    #[getter]
+    fn ccdc_deposition(&self) -> Option<String> {
+        self.inner.ccdc_deposition.clone()
+    }
+
+    /// Protein Data Bank code
+    #[getter]
+    fn pdb(&self) -> Option<String> {
+        self.inner.pdb.clone()
+    }
+
+    /// DOI of the paper
+    #[getter]
+    fn doi(&self) -> Option<String> {
+        self.inner.doi.clone()
+    }
+
+    /// DOI of the dataset
+    #[getter]
+    fn dataset_doi(&self) -> Option<String> {
+        self.inner.dataset_doi.clone()
+    }
+
+    /// Other codes as `(source, code)` pairs
+    #[getter]
+    fn others(&self) -> Vec<(String, String)> {
+        self.inner.others.clone()
+    }
+
+    /// Identifiers that are present, keyed by kind
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for kind in IdentifierKind::ALL {
+            if let Some(code) = self.inner.get(kind) {
+                dict.set_item(kind.name(), code)?;
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Whether any identifier was found
+    fn __bool__(&self) -> bool {
+        !self.inner.is_empty()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let fields: Vec<String> = IdentifierKind::ALL
+            .into_iter()
+            .filter_map(|kind| Some(format!("{}='{}'", kind, self.inner.get(kind)?)))
+            .collect();
+        format!("Identifiers({})", fields.join(", "))
+    }
+}
+
+/// Sanity issues as dicts with `tag`, `row`, `value`, and `rule`
+fn issues_to_py(py: Python<'_>, found: Vec<SanityIssue>) -> PyResult<Bound<'_, PyList>> {
+    let issues = PyList::empty(py);
+    for issue in found {
+        let dict = PyDict::new(py);
+        dict.set_item("id", issue.id)?;
+        dict.set_item("tag", issue.tag)?;
+        dict.set_item("row", issue.row)?;
+        dict.set_item("value", issue.value)?;
+        dict.set_item("rule", issue.rule)?;
+        issues.append(dict)?;
+    }
+    Ok(issues)
+}
+
+fn provenance_options(prefixes: Option<Vec<String>>, n_sigma: f64) -> ProvenanceOptions {
+    let options = ProvenanceOptions::new().n_sigma(n_sigma);
+    match prefixes {
+        Some(prefixes) => options.prefixes(prefixes),
+        None => options,
+    }
+}
+
+fn provenance_override_to_py<'py>(
+    py: Python<'py>,
+    found: &ProvenanceOverride,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("original_tag", &found.original_tag)?;
+    dict.set_item("tag", &found.tag)?;
+    dict.set_item(
+        "original",
+        value_to_py(py, &found.original, ValueMode::Raw)?,
+    )?;
+    let current = match &found.current {
+        Some(current) => value_to_py(py, current, ValueMode::Raw)?,
+        None => py.None(),
+    };
+    dict.set_item("current", current)?;
+    dict.set_item("differs", found.differs)?;
+    dict.set_item("sigmas", found.sigmas)?;
+    Ok(dict)
+}
+
+fn validation_issues_to_py(
+    py: Python<'_>,
+    found: Vec<ValidationIssue>,
+) -> PyResult<Bound<'_, PyList>> {
+    let issues = PyList::empty(py);
+    for issue in found {
+        let dict = PyDict::new(py);
+        dict.set_item("block", issue.block)?;
+        dict.set_item("frame", issue.frame)?;
+        dict.set_item("tag", issue.tag)?;
+        dict.set_item("row", issue.row)?;
+        dict.set_item("value", issue.value)?;
+        dict.set_item("kind", issue.kind.name())?;
+        dict.set_item("message", issue.message)?;
+        dict.set_item("dictionary", issue.dictionary)?;
+        dict.set_item("definition", issue.definition)?;
+        dict.set_item("category", issue.category)?;
+        issues.append(dict)?;
+    }
+    Ok(issues)
+}
+
+fn definition_to_py<'py>(py: Python<'py>, definition: &Definition) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("id", &definition.id)?;
+    dict.set_item("dictionary", &definition.dictionary)?;
+    dict.set_item("frame", &definition.frame)?;
+    dict.set_item("category", &definition.category)?;
+    dict.set_item("type", &definition.contents)?;
+    dict.set_item("container", &definition.container)?;
+    dict.set_item("units", &definition.units)?;
+    dict.set_item("enumeration", &definition.enumeration)?;
+    dict.set_item("range", definition.range)?;
+    dict.set_item("description", &definition.description)?;
+    dict.set_item("aliases", &definition.aliases)?;
+    Ok(dict)
+}
+
+/// Python wrapper for experimental conditions
+///
+/// Numbers are `(value, su)` tuples, with `su` `None` when not given.
+#[pyclass(name = "Experimental", frozen)]
+pub struct PyExperimental {
+    inner: Experimental,
+}
+
+#[pymethods]
+impl PyExperimental {
+    /// Crystal colour
+    #[getter]
+    fn colour(&self) -> Option<String> {
+        self.inner.colour.clone()
+    }
+
+    /// Crystal habit (`_exptl_crystal_description`)
+    #[getter]
+    fn habit(&self) -> Option<String> {
+        self.inner.habit.clone()
+    }
+
+    /// Smallest crystal dimension, in mm
+    #[getter]
+    fn size_min(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.size_min
+    }
+
+    /// Middle crystal dimension, in mm
+    #[getter]
+    fn size_mid(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.size_mid
+    }
+
+    /// Largest crystal dimension, in mm
+    #[getter]
+    fn size_max(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.size_max
+    }
+
+    /// Volume of the crystal's bounding box, in mm³
+    #[getter]
+    fn crystal_volume(&self) -> Option<f64> {
+        self.inner.crystal_volume()
+    }
+
+    /// Temperature of the measurement, in K
+    #[getter]
+    fn temperature(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.temperature
+    }
+
+    /// Absorption coefficient μ, in mm⁻¹
+    #[getter]
+    fn absorption_mu(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.absorption_mu
+    }
+
+    /// Absorption correction type, such as `multi-scan`
+    #[getter]
+    fn absorption_correction(&self) -> Option<String> {
+        self.inner.absorption_correction.clone()
+    }
+
+    /// Minimum transmission factor
+    #[getter]
+    fn t_min(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.t_min
+    }
+
+    /// Maximum transmission factor
+    #[getter]
+    fn t_max(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.t_max
+    }
+
+    /// Measured density, in g/cm³
+    #[getter]
+    fn density_measured(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.density_measured
+    }
+
+    /// Density reported from the diffraction experiment, in g/cm³
+    #[getter]
+    fn density_calculated(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.density_calculated
+    }
+
+    /// Density from the formula weight, Z, and cell volume, in g/cm³
+    #[getter]
+    fn density_from_formula(&self) -> Option<f64> {
+        self.inner.density_from_formula
+    }
+
+    /// Values that disagree with each other, as dicts like `Block.sanity_check`
+    fn check<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        issues_to_py(py, self.inner.check())
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let exptl = &self.inner;
+        let mut fields = Vec::new();
+        if let Some(colour) = &exptl.colour {
+            fields.push(format!("colour='{colour}'"));
+        }
+        if let Some(habit) = &exptl.habit {
+            fields.push(format!("habit='{habit}'"));
+        }
+        if let Some((temperature, _)) = exptl.temperature {
+            fields.push(format!("temperature={temperature}"));
+        }
+        format!("Experimental({})", fields.join(", "))
+    }
+}
+
+/// Python wrapper for how the intensities were measured
+///
+/// Numbers are `(value, su)` tuples, with `su` `None` when not given.
+#[pyclass(name = "Measurement", frozen)]
+pub struct PyMeasurement {
+    inner: Measurement,
+}
+
+#[pymethods]
+impl PyMeasurement {
+    /// Number of standard reflections
+    #[getter]
+    fn standards_number(&self) -> Option<u32> {
+        self.inner.standards_number
+    }
+
+    /// Reflections measured between standards
+    #[getter]
+    fn standards_interval_count(&self) -> Option<u32> {
+        self.inner.standards_interval_count
+    }
+
+    /// Minutes between standards
+    #[getter]
+    fn standards_interval_time(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.standards_interval_time
+    }
+
+    /// Mean decrease in the intensity of the standards, in percent
+    #[getter]
+    fn standards_decay_percent(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.standards_decay_percent
+    }
+
+    /// Scan method, such as `\w/2\q scans`
+    #[getter]
+    fn scan_method(&self) -> Option<String> {
+        self.inner.scan_method.clone()
+    }
+
+    /// Scan width, in degrees
+    #[getter]
+    fn scan_width(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.scan_width
+    }
+
+    /// Scan time, in seconds per reflection or frame
+    #[getter]
+    fn scan_time(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.scan_time
+    }
+
+    /// Free text about the scans
+    #[getter]
+    fn details(&self) -> Option<String> {
+        self.inner.details.clone()
+    }
+
+    /// Diffractometer, such as `four-circle diffractometer`
+    #[getter]
+    fn device(&self) -> Option<String> {
+        self.inner.device.clone()
+    }
+
+    /// Make and model of the diffractometer
+    #[getter]
+    fn device_type(&self) -> Option<String> {
+        self.inner.device_type.clone()
+    }
+
+    /// Detector, such as `CCD area detector`
+    #[getter]
+    fn detector(&self) -> Option<String> {
+        self.inner.detector.clone()
+    }
+
+    /// Make and model of the detector
+    #[getter]
+    fn detector_type(&self) -> Option<String> {
+        self.inner.detector_type.clone()
+    }
+
+    /// Monochromator, such as `graphite`
+    #[getter]
+    fn monochromator(&self) -> Option<String> {
+        self.inner.monochromator.clone()
+    }
+
+    /// Reflections measured (`_diffrn_reflns_number`)
+    #[getter]
+    fn reflections_measured(&self) -> Option<u32> {
+        self.inner.reflections_measured
+    }
+
+    /// Estimated time spent measuring on a serial diffractometer, in seconds
+    #[getter]
+    fn total_exposure_estimate(&self) -> Option<f64> {
+        self.inner.total_exposure_estimate()
+    }
+
+    /// Signs of a poor measurement, as dicts like `Block.sanity_check`
+    ///
+    /// The standards are flagged when they decayed by more than
+    /// `decay_threshold` percent.
+    #[pyo3(signature = (decay_threshold = crate::measurement::DEFAULT_DECAY_THRESHOLD))]
+    fn warnings<'py>(&self, py: Python<'py>, decay_threshold: f64) -> PyResult<Bound<'py, PyList>> {
+        issues_to_py(
+            py,
+            self.inner
+                .decay_warning(decay_threshold)
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let measurement = &self.inner;
+        let mut fields = Vec::new();
+        if let Some(device_type) = &measurement.device_type {
+            fields.push(format!("device_type='{device_type}'"));
+        }
+        if let Some(method) = &measurement.scan_method {
+            fields.push(format!("scan_method='{method}'"));
+        }
+        if let Some((decay, _)) = measurement.standards_decay_percent {
+            fields.push(format!("standards_decay_percent={decay}"));
+        }
+        format!("Measurement({})", fields.join(", "))
+    }
+}
+
+/// Python wrapper for the refinement model of a block
+#[pyclass(name = "RefinementModel", frozen)]
+pub struct PyRefinementModel {
+    inner: RefinementModel,
+}
+
+#[pymethods]
+impl PyRefinementModel {
+    /// Number of refined parameters
+    #[getter]
+    fn parameters(&self) -> Option<u64> {
+        self.inner.parameters
+    }
+
+    /// Number of restraints
+    #[getter]
+    fn restraints(&self) -> Option<u64> {
+        self.inner.restraints
+    }
+
+    /// Number of constraints
+    #[getter]
+    fn constraints(&self) -> Option<u64> {
+        self.inner.constraints
+    }
+
+    /// Weighting scheme, such as `calc` or `sigma`
+    #[getter]
+    fn weighting_scheme(&self) -> Option<String> {
+        self.inner.weighting_scheme.clone()
+    }
+
+    /// Weighting scheme details, as written
+    #[getter]
+    fn weighting_details(&self) -> Option<String> {
+        self.inner.weighting_details.clone()
+    }
+
+    /// Coefficient a of a SHELXL weighting scheme `w=1/[σ²(Fo²)+(aP)²+bP]`
+    #[getter]
+    fn weight_a(&self) -> Option<f64> {
+        self.inner.weighting.map(|w| w.a)
+    }
+
+    /// Coefficient b of a SHELXL weighting scheme `w=1/[σ²(Fo²)+(aP)²+bP]`
+    #[getter]
+    fn weight_b(&self) -> Option<f64> {
+        self.inner.weighting.map(|w| w.b)
+    }
+
+    /// Extinction correction method
+    #[getter]
+    fn extinction_method(&self) -> Option<String> {
+        self.inner.extinction_method.clone()
+    }
+
+    /// Extinction coefficient
+    #[getter]
+    fn extinction_coef(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.extinction_coef
+    }
+
+    /// Flack parameter
+    #[getter]
+    fn flack(&self) -> Option<(f64, Option<f64>)> {
+        self.inner.flack
+    }
+
+    /// Hydrogen treatment code, such as `constr` or `mixed`
+    #[getter]
+    fn hydrogen_treatment(&self) -> Option<String> {
+        self.inner
+            .hydrogen_treatment
+            .as_ref()
+            .map(|h| h.code().to_string())
+    }
+
+    /// Free-text notes on the refinement (`_refine_special_details`)
+    #[getter]
+    fn special_details(&self) -> Option<String> {
+        self.inner.special_details.clone()
+    }
+
+    /// Rows of the `_restr_*` loops, as dicts with `kind`, `atoms`,
+    /// `target` (a (value, su) tuple or None), and `difference`
+    #[getter]
+    fn restraint_list<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for restraint in &self.inner.restraint_list {
+            let dict = PyDict::new(py);
+            dict.set_item("kind", restraint.kind)?;
+            dict.set_item("atoms", &restraint.atoms)?;
+            dict.set_item("target", restraint.target)?;
+            dict.set_item("difference", restraint.difference)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let model = &self.inner;
+        let mut fields = Vec::new();
+        if let Some(parameters) = model.parameters {
+            fields.push(format!("parameters={parameters}"));
+        }
+        if let Some(restraints) = model.restraints {
+            fields.push(format!("restraints={restraints}"));
+        }
+        if let Some(weighting) = model.weighting {
+            fields.push(format!(
+                "weight_a={}, weight_b={}",
+                weighting.a, weighting.b
+            ));
+        }
+        format!("RefinementModel({})", fields.join(", "))
+    }
+}
+
+/// Schema type for a name accepted by `Block.check_schema`
+fn expected_type(name: &str) -> PyResult<ExpectedType<'static>> {
+    Ok(match name {
+        "float" => ExpectedType::F64,
+        "float_su" => ExpectedType::F64WithSu,
+        "int" => ExpectedType::I64,
+        "date" => ExpectedType::Date,
+        "bool" => ExpectedType::Bool,
+        "text" => ExpectedType::Text,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown schema type '{other}'; expected 'float', 'float_su', 'int', \
+                 'date', 'bool', 'text', or a list of allowed codes"
+            )))
+        }
+    })
+}
+
+/// A tag's report as a dict of `status`, `expected`, `unknown`, and
+/// `mismatches` (dicts of `row` and `token`)
+fn tag_report_dict<'py>(py: Python<'py>, report: &TagReport) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("status", report.status.name())?;
+    dict.set_item("expected", &report.expected)?;
+    dict.set_item("unknown", report.unknown)?;
+    let mismatches = PyList::empty(py);
+    for mismatch in &report.mismatches {
+        let item = PyDict::new(py);
+        item.set_item("row", mismatch.row)?;
+        item.set_item("token", &mismatch.token)?;
+        mismatches.append(item)?;
+    }
+    dict.set_item("mismatches", mismatches)?;
+    Ok(dict)
+}
+
+/// Python wrapper for the result of checking a block against a schema
+#[pyclass(name = "CoercionReport", frozen)]
+pub struct PyCoercionReport {
+    inner: CoercionReport,
+}
+
+#[pymethods]
+impl PyCoercionReport {
+    /// Whether every tag is present, converts, and has a known value
+    #[getter]
+    fn ok(&self) -> bool {
+        self.inner.is_ok()
+    }
+
+    /// Tags that are not in the block
+    #[getter]
+    fn missing(&self) -> Vec<&str> {
+        self.inner.with_status(TagStatus::Missing).collect()
+    }
+
+    /// Tags whose values are all `?` or `.`
+    #[getter]
+    fn unknown(&self) -> Vec<&str> {
+        self.inner.with_status(TagStatus::Unknown).collect()
+    }
+
+    /// Tags with at least one value that does not convert
+    #[getter]
+    fn mismatched(&self) -> Vec<&str> {
+        self.inner.with_status(TagStatus::Mismatch).collect()
+    }
+
+    /// Every value that does not convert, as dicts of `tag`, `row` (or
+    /// `None`), `token`, and `expected`
+    #[getter]
+    fn mismatches<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for report in &self.inner.tags {
+            for mismatch in &report.mismatches {
+                let dict = PyDict::new(py);
+                dict.set_item("tag", &report.tag)?;
+                dict.set_item("row", mismatch.row)?;
+                dict.set_item("token", &mismatch.token)?;
+                dict.set_item("expected", &report.expected)?;
+                list.append(dict)?;
+            }
+        }
+        Ok(list)
+    }
+
+    /// Reports keyed by tag, in schema order
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for report in &self.inner.tags {
+            dict.set_item(&report.tag, tag_report_dict(py, report)?)?;
+        }
+        Ok(dict)
+    }
+
+    /// The report for one tag
+    fn __getitem__<'py>(&self, py: Python<'py>, tag: &str) -> PyResult<Bound<'py, PyDict>> {
+        let report = self
+            .inner
+            .get(tag)
+            .ok_or_else(|| PyKeyError::new_err(tag.to_string()))?;
+        tag_report_dict(py, report)
+    }
+
+    fn __bool__(&self) -> bool {
+        self.inner.is_ok()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        let count = |status| self.inner.with_status(status).count();
+        format!(
+            "CoercionReport(ok={}, missing={}, unknown={}, mismatched={})",
+            count(TagStatus::Ok),
+            count(TagStatus::Missing),
+            count(TagStatus::Unknown),
+            count(TagStatus::Mismatch)
+        )
+    }
+}
+
+/// Python wrapper for the result of comparing two numbers with sus
+#[pyclass(name = "Comparison", frozen)]
+pub struct PyComparison {
+    inner: Comparison,
+}
+
+#[pymethods]
+impl PyComparison {
+    /// Whether the numbers agree within `n_sigma`
+    #[getter]
+    fn equal(&self) -> bool {
+        self.inner.is_equal()
+    }
+
+    /// Absolute difference in σ, or None if the numbers are incomparable
+    #[getter]
+    fn sigmas(&self) -> Option<f64> {
+        self.inner.sigmas()
+    }
+
+    fn __bool__(&self) -> bool {
+        self.inner.is_equal()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Comparison({})", self.inner)
+    }
+}
+
+/// Python wrapper for a site-by-site comparison of two structures
+#[pyclass(name = "StructureComparison", frozen)]
+pub struct PyStructureComparison {
+    inner: StructureComparison,
+}
+
+#[pymethods]
+impl PyStructureComparison {
+    /// Matched atoms as dicts with `label_a`, `label_b`, `displacement` and
+    /// `distance` in Å, and the changes `u_iso` (None unless both give one)
+    /// and `occupancy`
+    #[getter]
+    fn sites<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for site in &self.inner.sites {
+            let dict = PyDict::new(py);
+            dict.set_item("label_a", &site.label_a)?;
+            dict.set_item("label_b", &site.label_b)?;
+            dict.set_item("displacement", site.displacement)?;
+            dict.set_item("distance", site.distance)?;
+            dict.set_item("u_iso", site.u_iso)?;
+            dict.set_item("occupancy", site.occupancy)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// Labels of atoms only in the first structure
+    #[getter]
+    fn only_in_a(&self) -> Vec<String> {
+        self.inner.only_in_a.clone()
+    }
+
+    /// Labels of atoms only in the second structure
+    #[getter]
+    fn only_in_b(&self) -> Vec<String> {
+        self.inner.only_in_b.clone()
+    }
+
+    /// Origin of the second structure relative to the first, fractional
+    #[getter]
+    fn origin_shift(&self) -> (f64, f64, f64) {
+        let [x, y, z] = self.inner.origin_shift;
+        (x, y, z)
+    }
+
+    /// RMS distance over the matched atoms in Å
+    #[getter]
+    fn rmsd(&self) -> f64 {
+        self.inner.rmsd
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "StructureComparison(matched={}, only_in_a={}, only_in_b={}, rmsd={:.4})",
+            self.inner.sites.len(),
+            self.inner.only_in_a.len(),
+            self.inner.only_in_b.len(),
+            self.inner.rmsd
+        )
+    }
+}
+
+/// Atom sites as dicts with `label`, `type_symbol`, `fract`, `cartn`,
+/// `fract_su`, `occupancy`, `u_iso`, `u_aniso`, `adp_type`, and `disorder_group`
+fn atom_site_list<'py>(py: Python<'py>, sites: &[AtomSite]) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for site in sites {
+        let dict = PyDict::new(py);
+        dict.set_item("label", &site.label)?;
+        dict.set_item("type_symbol", &site.type_symbol)?;
+        dict.set_item("fract", site.fract.map(|x| x.to_vec()))?;
+        dict.set_item("cartn", site.cartn.map(|x| x.to_vec()))?;
+        dict.set_item("fract_su", site.fract_su.to_vec())?;
+        dict.set_item("occupancy", site.occupancy)?;
+        dict.set_item("u_iso", site.u_iso)?;
+        dict.set_item("u_aniso", site.u_aniso.map(|u| u.to_vec()))?;
+        dict.set_item("adp_type", site.adp_type.as_ref().map(AdpType::code))?;
+        dict.set_item("disorder_group", &site.disorder_group)?;
+        list.append(dict)?;
+    }
+    Ok(list)
+}
+
+/// Python wrapper for a structure: cell, symmetry, and atom sites
+#[pyclass(name = "Structure", frozen)]
+pub struct PyStructure {
+    inner: Structure,
+}
+
+#[pymethods]
+impl PyStructure {
+    /// The unit cell
+    #[getter]
+    fn cell(&self) -> PyUnitCell {
+        PyUnitCell {
+            inner: self.inner.cell,
+        }
+    }
+
+    /// Number of symmetry operations (1 if the block lists none)
+    #[getter]
+    fn symmetry_count(&self) -> usize {
+        self.inner.symmetry.len()
+    }
+
+    /// Atom sites in file order, as dicts like `Block.atom_sites()`
+    #[getter]
+    fn sites<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        atom_site_list(py, &self.inner.sites)
+    }
+
+    /// Site symmetry and multiplicity of every atom site
+    ///
+    /// An operation stabilizes a site if it maps it to within `tolerance` Å
+    /// of itself, allowing lattice translations.
+    #[pyo3(signature = (tolerance=DEFAULT_SITE_TOLERANCE))]
+    fn site_symmetries(&self, tolerance: f64) -> Vec<PySiteSymmetry> {
+        self.inner
+            .site_symmetries(tolerance)
+            .into_iter()
+            .map(|inner| PySiteSymmetry { inner })
+            .collect()
+    }
+
+    /// Groups of differently labelled sites with identical coordinates,
+    /// U_iso, or anisotropic tensors (values and sus within `tolerance`), as
+    /// dicts with `quantity` (`coordinates`, `u_iso`, or `u_aniso`),
+    /// `labels`, `symmetry_required`, `across_parts`, and `documented`
+    #[pyo3(signature = (tolerance=DEFAULT_TIE_TOLERANCE))]
+    fn detect_ties<'py>(&self, py: Python<'py>, tolerance: f64) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for tie in self.inner.detect_ties(tolerance) {
+            let dict = PyDict::new(py);
+            dict.set_item("quantity", tie.quantity.name())?;
+            dict.set_item("labels", &tie.labels)?;
+            dict.set_item("symmetry_required", tie.symmetry_required)?;
+            dict.set_item("across_parts", tie.across_parts)?;
+            dict.set_item("documented", tie.documented)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// Whether `supercell` dropped the symmetry operations of the structure
+    /// it was built from
+    #[getter]
+    fn reduced_to_p1(&self) -> bool {
+        self.inner.reduced_to_p1
+    }
+
+    /// A copy with every site moved into the cell, coordinates in [0, 1)
+    fn wrap_coordinates(&self) -> PyStructure {
+        let mut inner = self.inner.clone();
+        inner.wrap_coordinates();
+        PyStructure { inner }
+    }
+
+    /// A copy with `shift` added to the fractional coordinates of every site
+    /// and the symmetry operations rewritten for the new origin
+    fn shift_origin(&self, shift: [f64; 3]) -> PyStructure {
+        let mut inner = self.inner.clone();
+        inner.shift_origin(shift);
+        PyStructure { inner }
+    }
+
+    /// The structure repeated `na × nb × nc` times, in P1, with every atom
+    /// of the cell generated from the symmetry operations and the copies
+    /// labelled `<label>_<n>`
+    fn supercell(&self, na: usize, nb: usize, nc: usize) -> PyResult<PyStructure> {
+        if na == 0 || nb == 0 || nc == 0 {
+            return Err(PyValueError::new_err(format!(
+                "supercell of {na}×{nb}×{nc} cells"
+            )));
+        }
+        Ok(PyStructure {
+            inner: self.inner.supercell(na, nb, nc),
+        })
+    }
+
+    /// The sites as extended XYZ, with Cartesian coordinates in Å
+    fn to_xyz(&self) -> String {
+        self.inner.to_xyz()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!(
+            "Structure({} sites, {} symmetry operations)",
+            self.inner.sites.len(),
+            self.inner.symmetry.len()
+        )
+    }
+}
+
+/// Python wrapper for the site symmetry of one atom site
+#[pyclass(name = "SiteSymmetry", frozen)]
+pub struct PySiteSymmetry {
+    inner: SiteSymmetry,
+}
+
+#[pymethods]
+impl PySiteSymmetry {
+    /// Label of the site
+    #[getter]
+    fn label(&self) -> &str {
+        &self.inner.label
+    }
+
+    /// Number of equivalent positions in the cell
+    #[getter]
+    fn multiplicity(&self) -> usize {
+        self.inner.multiplicity
+    }
+
+    /// Whether the site is on a special position
+    #[getter]
+    fn is_special(&self) -> bool {
+        self.inner.is_special()
+    }
+
+    /// Number of operations that map the site onto itself
+    #[getter]
+    fn stabilizer_order(&self) -> usize {
+        self.inner.stabilizer.len()
+    }
+
+    /// Point group symbol of the site symmetry, or `None` if the operations
+    /// found do not form a group
+    #[getter]
+    fn symbol(&self) -> Option<&'static str> {
+        self.inner.symbol
+    }
+
+    /// `_atom_site_site_symmetry_multiplicity` as given in the file
+    #[getter]
+    fn declared_multiplicity(&self) -> Option<usize> {
+        self.inner.declared_multiplicity
+    }
+
+    /// The declared and computed multiplicity, if they differ
+    #[getter]
+    fn mismatch(&self) -> Option<(usize, usize)> {
+        self.inner.mismatch()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!(
+            "SiteSymmetry('{}', multiplicity={}, symbol='{}')",
+            self.inner.label,
+            self.inner.multiplicity,
+            self.inner.symbol.unwrap_or("?")
+        )
+    }
+}
+
+/// Python wrapper for a symmetry operation
+#[pyclass(name = "SymOp", frozen, eq)]
+#[derive(PartialEq)]
+pub struct PySymOp {
+    inner: SymOp,
+}
+
+#[pymethods]
+impl PySymOp {
+    /// Parse an operation in `x,y,z` notation, such as `-x, y+1/2, -z+1/2`
+    ///
+    /// Raises ValueError if it cannot be parsed.
+    #[new]
+    fn new(xyz: &str) -> PyResult<Self> {
+        SymOp::parse(xyz)
+            .map(|inner| PySymOp { inner })
+            .ok_or_else(|| PyValueError::new_err(format!("'{xyz}' is not a symmetry operation")))
+    }
+
+    /// Rotation matrix as a 3×3 nested list of ints, as rows
+    #[getter]
+    fn rotation(&self) -> [[i32; 3]; 3] {
+        self.inner.rotation
+    }
+
+    /// Translation in fractions of the cell edges
+    #[getter]
+    fn translation(&self) -> [f64; 3] {
+        self.inner.translation
+    }
+
+    /// Apply the operation to fractional coordinates
+    fn apply(&self, frac: [f64; 3]) -> [f64; 3] {
+        self.inner.apply(frac)
+    }
+
+    /// The operation applying `other` first and then this one
+    fn compose(&self, other: &PySymOp) -> PySymOp {
+        PySymOp {
+            inner: self.inner.compose(&other.inner),
+        }
+    }
+
+    /// The operation in `x,y,z` notation
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!("SymOp('{}')", self.inner)
+    }
+}
+
+/// Python wrapper for a point group derived from symmetry operations
+#[pyclass(name = "PointGroup", frozen)]
+pub struct PyPointGroup {
+    inner: PointGroupInfo,
+}
+
+#[pymethods]
+impl PyPointGroup {
+    /// Hermann–Mauguin symbol
+    #[getter]
+    fn symbol(&self) -> &'static str {
+        self.inner.symbol
+    }
+
+    /// Number of distinct rotations
+    #[getter]
+    fn order(&self) -> usize {
+        self.inner.order
+    }
+
+    /// Laue class
+    #[getter]
+    fn laue_class(&self) -> &'static str {
+        self.inner.laue_class
+    }
+
+    /// Crystal system, in lowercase
+    #[getter]
+    fn crystal_system(&self) -> &'static str {
+        self.inner.crystal_system.name()
+    }
+
+    /// Whether the group contains the inversion
+    #[getter]
+    fn is_centrosymmetric(&self) -> bool {
+        self.inner.is_centrosymmetric
+    }
+
+    /// Whether the group has only proper rotations
+    #[getter]
+    fn is_chiral(&self) -> bool {
+        self.inner.is_chiral
+    }
+
+    /// Whether the group is polar
+    #[getter]
+    fn is_polar(&self) -> bool {
+        self.inner.is_polar
+    }
+
+    /// Conflicts with the cell metric or the declared space group number
+    #[getter]
+    fn warnings(&self) -> Vec<String> {
+        self.inner.warnings.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!(
+            "PointGroup('{}', {})",
+            self.inner.symbol, self.inner.crystal_system
+        )
+    }
+}
+
+/// Python wrapper for a space group and its symmetry operations
+#[pyclass(name = "SpaceGroup", frozen, eq)]
+#[derive(PartialEq)]
+pub struct PySpaceGroup {
+    inner: SpaceGroup,
+}
+
+#[pymethods]
+impl PySpaceGroup {
+    /// Generate a space group from a Hall symbol, such as `-P 2ybc`, or
+    /// `None` if it cannot be read
+    #[staticmethod]
+    fn from_hall(symbol: &str) -> Option<PySpaceGroup> {
+        SpaceGroup::from_hall(symbol).map(|inner| PySpaceGroup { inner })
+    }
+
+    /// Look up a Hermann–Mauguin symbol, such as `P 21/c` or `Fd-3m:2`, or
+    /// `None` if it is not in the table
+    #[staticmethod]
+    fn from_hermann_mauguin(symbol: &str) -> Option<PySpaceGroup> {
+        SpaceGroup::from_hermann_mauguin(symbol).map(|inner| PySpaceGroup { inner })
+    }
+
+    /// Standard setting of a space group number, or `None` outside 1–230
+    #[staticmethod]
+    fn from_number(number: u32) -> Option<PySpaceGroup> {
+        SpaceGroup::from_number(number).map(|inner| PySpaceGroup { inner })
+    }
+
+    /// International Tables number, or `None` for a Hall symbol not in the
+    /// table
+    #[getter]
+    fn number(&self) -> Option<u32> {
+        self.inner.number
+    }
+
+    /// Hermann–Mauguin symbol with its setting, such as `F d -3 m :2`, or
+    /// `None` for a Hall symbol not in the table
+    #[getter]
+    fn hermann_mauguin(&self) -> Option<&'static str> {
+        self.inner.hermann_mauguin
+    }
+
+    /// Hall symbol
+    #[getter]
+    fn hall(&self) -> &str {
+        &self.inner.hall
+    }
+
+    /// All symmetry operations, centring translations included
+    #[getter]
+    fn operations(&self) -> Vec<PySymOp> {
+        self.inner
+            .operations
+            .iter()
+            .map(|&inner| PySymOp { inner })
+            .collect()
+    }
+
+    /// Number of operations
+    fn __len__(&self) -> usize {
+        self.inner.operations.len()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!("SpaceGroup('{}')", self.inner.hall)
+    }
+}
+
+/// Python wrapper for the label and author numbering of a PDBx block
+#[pyclass(name = "ChainMap", frozen)]
+pub struct PyChainMap {
+    inner: ChainMap,
+}
+
+#[pymethods]
+impl PyChainMap {
+    /// `(label_asym_id, label_seq_id)` of an author residue, or `None`;
+    /// `label_seq_id` is `None` for ligands and waters
+    #[pyo3(signature = (chain, seq, ins_code=None))]
+    fn auth_to_label(
+        &self,
+        chain: &str,
+        seq: i64,
+        ins_code: Option<&str>,
+    ) -> Option<(String, Option<i64>)> {
+        self.inner
+            .auth_to_label(chain, seq, ins_code)
+            .map(|label| (label.asym.clone(), label.seq))
+    }
+
+    /// `(auth_asym_id, auth_seq_id, ins_code)` of each author residue with
+    /// these label ids; `seq=None` gives every ligand or water residue of
+    /// the entity instance
+    #[pyo3(signature = (asym, seq=None))]
+    fn label_to_auth(&self, asym: &str, seq: Option<i64>) -> Vec<(String, i64, Option<String>)> {
+        self.inner
+            .label_to_auth(asym, seq)
+            .iter()
+            .map(|auth| (auth.chain.clone(), auth.seq, auth.ins_code.clone()))
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!("ChainMap({} residues)", self.inner.len())
+    }
+}
+
+/// A reflection as `((h, k, l), intensity, sigma)`
+type ReflectionTuple = ((i32, i32, i32), f64, Option<f64>);
+
+/// Python wrapper for the measured reflections of a block
+#[pyclass(name = "ReflectionData", frozen)]
+pub struct PyReflectionData {
+    inner: ReflectionData,
+}
+
+#[pymethods]
+impl PyReflectionData {
+    /// Loop the reflections came from: `diffrn_refln` or `refln`
+    #[getter]
+    fn category(&self) -> &'static str {
+        self.inner.category
+    }
+
+    /// Reflections as `((h, k, l), intensity, sigma)` tuples
+    #[getter]
+    fn reflections(&self) -> Vec<ReflectionTuple> {
+        self.inner
+            .reflections
+            .iter()
+            .map(|r| ((r.hkl[0], r.hkl[1], r.hkl[2]), r.intensity, r.sigma))
+            .collect()
+    }
+
+    /// Merge symmetry-equivalent reflections under `point_group`
+    ///
+    /// Friedel mates are merged unless `anomalous`. `shells` is a number of
+    /// shells with equal numbers of unique reflections, or a list of
+    /// d-spacings in Å to split shells at.
+    #[pyo3(signature = (point_group, *, anomalous=false, shells=None))]
+    fn merge(
+        &self,
+        point_group: &PyPointGroup,
+        anomalous: bool,
+        shells: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyMergedReflections> {
+        let mut options = MergeOptions::new().anomalous(anomalous);
+        if let Some(shells) = shells {
+            options = options.shells(if let Ok(count) = shells.extract::<usize>() {
+                ShellBinning::Count(count)
+            } else if let Ok(limits) = shells.extract::<Vec<f64>>() {
+                ShellBinning::DSpacing(limits)
+            } else {
+                return Err(PyTypeError::new_err(
+                    "shells must be a number of shells or a list of d-spacings",
+                ));
+            });
+        }
+        Ok(PyMergedReflections {
+            inner: self.inner.merge_equivalents(&point_group.inner, &options),
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.reflections.len()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        format!(
+            "ReflectionData('{}', {} reflections)",
+            self.inner.category,
+            self.inner.reflections.len()
+        )
+    }
+}
+
+/// Merging statistics as a dict with the `MergeStatistics` field names
+fn merge_statistics_to_py<'py>(
+    py: Python<'py>,
+    stats: &MergeStatistics,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("d_max", stats.d_max)?;
+    dict.set_item("d_min", stats.d_min)?;
+    dict.set_item("observations", stats.observations)?;
+    dict.set_item("unique", stats.unique)?;
+    dict.set_item("multiplicity", stats.multiplicity)?;
+    dict.set_item("r_merge", stats.r_merge)?;
+    dict.set_item("r_meas", stats.r_meas)?;
+    dict.set_item("r_pim", stats.r_pim)?;
+    dict.set_item("mean_i_over_sigma", stats.mean_i_over_sigma)?;
+    Ok(dict)
+}
+
+/// Python wrapper for merged reflections and their statistics
+#[pyclass(name = "MergedReflections", frozen)]
+pub struct PyMergedReflections {
+    inner: MergedReflections,
+}
+
+#[pymethods]
+impl PyMergedReflections {
+    /// Σ|I − Ī| / ΣI over reflections measured more than once
+    #[getter]
+    fn r_merge(&self) -> Option<f64> {
+        self.inner.overall.r_merge
+    }
+
+    /// Multiplicity-independent R factor
+    #[getter]
+    fn r_meas(&self) -> Option<f64> {
+        self.inner.overall.r_meas
+    }
+
+    /// Precision-indicating R factor
+    #[getter]
+    fn r_pim(&self) -> Option<f64> {
+        self.inner.overall.r_pim
+    }
+
+    /// Mean Ī/σ(Ī) over the unique reflections
+    #[getter]
+    fn mean_i_over_sigma(&self) -> Option<f64> {
+        self.inner.overall.mean_i_over_sigma
+    }
+
+    /// Observations per unique reflection
+    #[getter]
+    fn multiplicity(&self) -> f64 {
+        self.inner.overall.multiplicity
+    }
+
+    /// Statistics over all reflections, as a dict
+    #[getter]
+    fn overall<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        merge_statistics_to_py(py, &self.inner.overall)
+    }
+
+    /// Statistics per resolution shell from low to high resolution, as
+    /// dicts with `d_max`, `d_min`, `observations`, `unique`,
+    /// `multiplicity`, `r_merge`, `r_meas`, `r_pim`, and `mean_i_over_sigma`
+    #[getter]
+    fn shells<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for shell in &self.inner.shells {
+            list.append(merge_statistics_to_py(py, shell)?)?;
+        }
+        Ok(list)
+    }
+
+    /// Unique reflections as dicts with `hkl`, `intensity`, `sigma`,
+    /// `multiplicity`, and `d_spacing`
+    #[getter]
+    fn reflections<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for reflection in &self.inner.reflections {
+            let dict = PyDict::new(py);
+            let [h, k, l] = reflection.hkl;
+            dict.set_item("hkl", (h, k, l))?;
+            dict.set_item("intensity", reflection.intensity)?;
+            dict.set_item("sigma", reflection.sigma)?;
+            dict.set_item("multiplicity", reflection.multiplicity)?;
+            dict.set_item("d_spacing", reflection.d_spacing)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.reflections.len()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let overall = &self.inner.overall;
+        let mut fields = vec![
+            format!("unique={}", overall.unique),
+            format!("multiplicity={:.2}", overall.multiplicity),
+        ];
+        if let Some(r_merge) = overall.r_merge {
+            fields.push(format!("r_merge={r_merge:.4}"));
+        }
+        format!("MergedReflections({})", fields.join(", "))
+    }
+}
+
+/// Python wrapper for a unit cell with standard uncertainties
+#[pyclass(name = "UnitCell", frozen)]
+pub struct PyUnitCell {
+    inner: UnitCell,
+}
+
+#[pymethods]
+impl PyUnitCell {
+    /// Length of a in Å
+    #[getter]
+    fn a(&self) -> f64 {
+        self.inner.a
+    }
+
+    /// Length of b in Å
+    #[getter]
+    fn b(&self) -> f64 {
+        self.inner.b
+    }
+
+    /// Length of c in Å
+    #[getter]
+    fn c(&self) -> f64 {
+        self.inner.c
+    }
+
+    /// Angle between b and c in degrees
+    #[getter]
+    fn alpha(&self) -> f64 {
+        self.inner.alpha
+    }
+
+    /// Angle between a and c in degrees
+    #[getter]
+    fn beta(&self) -> f64 {
+        self.inner.beta
+    }
+
+    /// Angle between a and b in degrees
+    #[getter]
+    fn gamma(&self) -> f64 {
+        self.inner.gamma
+    }
+
+    /// Sus of (a, b, c, alpha, beta, gamma), 0 when unknown
+    #[getter]
+    fn su(&self) -> (f64, f64, f64, f64, f64, f64) {
+        let [a, b, c, alpha, beta, gamma] = self.inner.su;
+        (a, b, c, alpha, beta, gamma)
+    }
+
+    /// Volume in Å³
+    #[getter]
+    fn volume(&self) -> f64 {
+        self.inner.volume()
+    }
+
+    /// Su of the volume, propagated from the cell sus
+    #[getter]
+    fn volume_su(&self) -> f64 {
+        self.inner.volume_with_su().1
+    }
+
+    /// Metric tensor G in Å², as a 3×3 nested list (`numpy.array(cell.metric_tensor)`)
+    #[getter]
+    fn metric_tensor(&self) -> [[f64; 3]; 3] {
+        self.inner.metric_tensor()
+    }
+
+    /// Reciprocal metric tensor G* in Å⁻², as a 3×3 nested list
+    #[getter]
+    fn reciprocal_metric_tensor(&self) -> [[f64; 3]; 3] {
+        self.inner.reciprocal_metric_tensor()
+    }
+
+    /// Matrix taking fractional to Cartesian coordinates, as a 3×3 nested
+    /// list whose columns are the cell vectors
+    #[getter]
+    fn orthogonalization_matrix(&self) -> [[f64; 3]; 3] {
+        self.inner.orthogonalization_matrix()
+    }
+
+    /// Matrix taking Cartesian to fractional coordinates, as a 3×3 nested
+    /// list whose rows are the reciprocal cell vectors
+    #[getter]
+    fn fractionalization_matrix(&self) -> [[f64; 3]; 3] {
+        self.inner.fractionalization_matrix()
+    }
+
+    /// Convert fractional coordinates to Cartesian coordinates in Å
+    fn frac_to_cart(&self, frac: [f64; 3]) -> [f64; 3] {
+        self.inner.frac_to_cart(frac)
+    }
+
+    /// Convert Cartesian coordinates in Å to fractional coordinates
+    fn cart_to_frac(&self, cart: [f64; 3]) -> [f64; 3] {
+        self.inner.cart_to_frac(cart)
+    }
+
+    /// Distance in Å between two fractional positions
+    ///
+    /// With `with_su=True`, returns `(distance, su)` with the su propagated
+    /// from the cell sus.
+    #[pyo3(signature = (a, b, *, with_su = false))]
+    fn distance<'py>(
+        &self,
+        py: Python<'py>,
+        a: [f64; 3],
+        b: [f64; 3],
+        with_su: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if with_su {
+            Ok(self
+                .inner
+                .distance_with_su(a, b)
+                .into_pyobject(py)?
+                .into_any())
+        } else {
+            Ok(self.inner.distance(a, b).into_pyobject(py)?.into_any())
+        }
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let cell = &self.inner;
+        format!(
+            "UnitCell(a={}, b={}, c={}, alpha={}, beta={}, gamma={})",
+            cell.a, cell.b, cell.c, cell.alpha, cell.beta, cell.gamma
+        )
+    }
+}
+
+/// Python wrapper for where a document was parsed from
+#[pyclass(name = "DocumentSource", frozen)]
+#[derive(Clone)]
+pub struct PyDocumentSource {
+    inner: DocumentSource,
+}
+
+#[pymethods]
+impl PyDocumentSource {
+    /// File the document was read from, if any
+    #[getter]
+    fn path(&self) -> Option<&OsStr> {
+        self.inner.path.as_deref().map(Path::as_os_str)
+    }
+
+    /// Length of the content in bytes
+    #[getter]
+    fn byte_len(&self) -> usize {
+        self.inner.byte_len
+    }
+
+    /// SHA-256 of the content, as lowercase hex
+    #[getter]
+    fn sha256(&self) -> String {
+        self.inner.sha256.clone()
+    }
+
+    /// When the document was parsed, as a UTC datetime
+    #[getter]
+    fn parsed_at<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDateTime>> {
+        let timestamp = self
+            .inner
+            .parsed_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        let utc = PyTzInfo::utc(py)?;
+        PyDateTime::from_timestamp(py, timestamp, Some(&utc))
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let path = match &self.inner.path {
+            Some(path) => format!("'{}'", path.display()),
+            None => "None".to_string(),
+        };
+        format!(
+            "DocumentSource(path={}, sha256='{}')",
+            path,
+            &self.inner.sha256[..12]
+        )
+    }
+}
+
+/// Python wrapper for CifDocument with Pythonic interface
+///
+/// Reads work on a snapshot of the document, and changes made through its
+/// blocks are serialized by the handle, so no thread sees a change half done.
+#[pyclass(name = "Document")]
+#[derive(Clone)]
+pub struct PyDocument {
+    handle: DocumentHandle,
+}
+
+#[pymethods]
+impl PyDocument {
+    /// Create an empty document, written as CIF `version` `"1.1"` or
+    /// `"2.0"`, to fill with `add_block()`
+    #[new]
+    #[pyo3(signature = (*, version="1.1"))]
+    fn new(version: &str) -> PyResult<Self> {
+        let version = match version {
+            "1.1" => CifVersion::V1_1,
+            "2.0" => CifVersion::V2_0,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "version must be '1.1' or '2.0', got '{other}'"
+                )))
+            }
+        };
+        Ok(PyDocument {
+            handle: CifDocument::new_with_version(version).into(),
+        })
+    }
+
+    /// Add an empty data block after the others and return it
+    ///
+    /// Raises ValueError if a block has the name already.
+    fn add_block(&self, name: String) -> PyResult<PyBlock> {
+        let index = self
+            .handle
+            .update(|doc| {
+                doc.add_block(CifBlock::new(name))?;
+                Ok(doc.blocks.len() - 1)
+            })
+            .map_err(cif_error_to_py_err)?;
+        Ok(self.block_at(index))
+    }
+
+    /// Parse a CIF string
+    ///
+    /// `tags` (tag prefixes), `blocks` (block names), and `first_block_only`
+    /// select part of the input; everything else is skipped unparsed.
+    /// `duplicate_tags` is `"last"` (keep the last value), `"error"`, or
+    /// `"collapse"` (gather repeated items into a one-column loop).
+    /// `lenient` recovers from malformed input with warnings, and keeps
+    /// non-CIF text around the blocks as `preamble` and `trailer`.
+    /// Text longer than `large_value_threshold` bytes is kept as a large
+    /// value, read with `Value.read_large()`.
+    #[staticmethod]
+    #[pyo3(signature = (content, *, tags=None, blocks=None, first_block_only=false, duplicate_tags="last", lenient=false, large_value_threshold=None))]
+    fn parse(
+        content: &str,
+        tags: Option<Vec<String>>,
+        blocks: Option<Vec<String>>,
+        first_block_only: bool,
+        duplicate_tags: &str,
+        lenient: bool,
+        large_value_threshold: Option<usize>,
+    ) -> PyResult<PyDocument> {
+        let options = parse_options(
+            tags,
+            blocks,
+            first_block_only,
+            duplicate_tags,
+            lenient,
+            large_value_threshold,
+        )?;
+        CifDocument::parse_with_options(content, &options)
+            .map(|doc| PyDocument { handle: doc.into() })
+            .map_err(cif_error_to_py_err)
+    }
+
+    /// Parse a CIF file, optionally selecting part of it as for `parse`
+    #[staticmethod]
+    #[pyo3(signature = (path, *, tags=None, blocks=None, first_block_only=false, duplicate_tags="last", lenient=false, large_value_threshold=None))]
+    fn from_file(
+        path: FsPath,
+        tags: Option<Vec<String>>,
+        blocks: Option<Vec<String>>,
+        first_block_only: bool,
+        duplicate_tags: &str,
+        lenient: bool,
+        large_value_threshold: Option<usize>,
+    ) -> PyResult<PyDocument> {
+        let options = parse_options(
+            tags,
+            blocks,
+            first_block_only,
+            duplicate_tags,
+            lenient,
+            large_value_threshold,
+        )?;
+        CifDocument::from_file_with_options(path.0, &options)
+            .map(|doc| PyDocument { handle: doc.into() })
+            .map_err(cif_error_to_py_err)
+    }
+
+    /// Get the CIF version of this document
+    ///
+    /// Returns the detected or explicitly set CIF version.
+    /// CIF 2.0 is indicated by the `#\#CIF_2.0` magic header.
+    /// Documents without this header default to CIF 1.1.
+    #[getter]
+    fn version(&self) -> PyVersion {
+        self.doc().version.into()
+    }
+
+    /// Check if this document is CIF 2.0
+    ///
+    /// CIF 2.0 adds support for lists, tables, and other advanced features.
+    fn is_cif2(&self) -> bool {
+        matches!(self.doc().version, CifVersion::V2_0)
+    }
+
+    /// Check if this document is CIF 1.1
+    fn is_cif1(&self) -> bool {
+        matches!(self.doc().version, CifVersion::V1_1)
+    }
+
+    /// Problems the parser recovered from, such as collapsed repeated tags
+    #[getter]
+    fn warnings(&self) -> Vec<String> {
+        self.doc().warnings.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// Whether only selected content was parsed (see `parse(tags=...)`)
+    #[getter]
+    fn partial(&self) -> bool {
+        self.doc().partial
+    }
+
+    /// Non-CIF text before the first block, kept when parsed leniently
+    #[getter]
+    fn preamble(&self) -> Option<String> {
+        self.doc().preamble.clone()
+    }
+
+    /// Non-CIF text after the last block, kept when parsed leniently
+    #[getter]
+    fn trailer(&self) -> Option<String> {
+        self.doc().trailer.clone()
+    }
+
+    /// Whether the first block was made up to hold a fragment's bare save
+    /// frames, which are written back without its heading
+    #[getter]
+    fn bare_frames(&self) -> bool {
+        self.doc().bare_frames
+    }
+
+    /// Where the document was parsed from (None for parsed strings)
+    #[getter]
+    fn source(&self) -> Option<PyDocumentSource> {
+        self.doc()
+            .source
+            .clone()
+            .map(|inner| PyDocumentSource { inner })
+    }
+
+    /// Kinds of file this is, e.g. `["small-molecule"]`, guessed from its tags
+    ///
+    /// The kinds are `small-molecule`, `mmcif`, `structure-factors`,
+    /// `powder`, `dictionary`, `magcif`, `imgcif`, and `template`; a file
+    /// may match several or none.
+    fn kind(&self) -> Vec<&'static str> {
+        self.doc().kind().kinds().map(Kind::name).collect()
+    }
+
+    /// Get the number of blocks
+    fn __len__(&self) -> usize {
+        self.doc().blocks.len()
+    }
+
+    /// Get a block by index
+    fn get_block(&self, index: usize) -> Option<PyBlock> {
+        (index < self.doc().blocks.len()).then(|| self.block_at(index))
+    }
+
+    /// Get a block by name
+    fn get_block_by_name(&self, name: &str) -> Option<PyBlock> {
+        let index = self.doc().blocks.iter().position(|b| b.name == name)?;
+        Some(self.block_at(index))
+    }
+
+    /// Get the first block
+    fn first_block(&self) -> Option<PyBlock> {
+        self.get_block(0)
+    }
+
+    /// The block the shortcuts below work on
+    ///
+    /// By default this is the first block holding data (a structure,
+    /// reflections, a cell, ...), skipping a leading global or publication
+    /// block, or else the first block. With `strict=True` it must be the only
+    /// block, or the only one holding data. Raises ValueError, naming the
+    /// blocks, if the document is empty or the choice is ambiguous.
+    #[pyo3(signature = (*, strict=false))]
+    fn main_block(&self, strict: bool) -> PyResult<PyBlock> {
+        let doc = self.doc();
+        if doc.blocks.is_empty() {
+            return Err(PyValueError::new_err("Document has no data blocks"));
+        }
+        let data: Vec<usize> = (0..doc.blocks.len())
+            .filter(|&index| doc.blocks[index].holds_data())
+            .collect();
+        let index = match (strict, data.as_slice()) {
+            _ if doc.blocks.len() == 1 => 0,
+            (_, [index]) | (false, [index, ..]) => *index,
+            (false, []) => 0,
+            (true, _) => {
+                let holding = match data.len() {
+                    0 => "none holds data".to_string(),
+                    n => {
+                        let names: Vec<&str> = data
+                            .iter()
+                            .map(|&index| doc.blocks[index].name.as_str())
+                            .collect();
+                        format!("{n} hold data ({})", names.join(", "))
+                    }
+                };
+                return Err(PyValueError::new_err(format!(
+                    "Document has {} blocks ({}) and {holding}; choose one with doc[name]",
+                    doc.blocks.len(),
+                    block_list(&doc),
+                )));
+            }
+        };
+        Ok(self.block_at(index))
+    }
+
+    /// The only block; raises ValueError, naming the blocks, unless there is
+    /// exactly one
+    fn only_block(&self) -> PyResult<PyBlock> {
+        let doc = self.doc();
+        match doc.blocks.len() {
+            1 => Ok(self.block_at(0)),
+            0 => Err(PyValueError::new_err("Document has no data blocks")),
+            n => Err(PyValueError::new_err(format!(
+                "Document has {n} blocks ({}), not one",
+                block_list(&doc)
+            ))),
+        }
+    }
+
+    /// `main_block(strict=strict).get(tag, search_frames)`
+    #[pyo3(signature = (tag, search_frames=false, *, strict=false))]
+    fn get(&self, tag: &str, search_frames: bool, strict: bool) -> PyResult<Option<PyValue>> {
+        Ok(self.main_block(strict)?.get(tag, search_frames))
+    }
+
+    /// `main_block(strict=strict).find_loop(tag)`
+    #[pyo3(signature = (tag, *, strict=false))]
+    fn find_loop(&self, tag: &str, strict: bool) -> PyResult<Option<PyLoop>> {
+        Ok(self.main_block(strict)?.find_loop(tag))
+    }
+
+    /// `main_block(strict=strict).atom_sites()`
+    #[pyo3(signature = (*, strict=false))]
+    fn atom_sites<'py>(
+        &self,
+        py: Python<'py>,
+        strict: bool,
+    ) -> PyResult<Option<Bound<'py, PyList>>> {
+        self.main_block(strict)?.atom_sites(py)
+    }
+
+    /// `main_block(strict=strict).unit_cell()`
+    #[pyo3(signature = (*, strict=false))]
+    fn unit_cell(&self, strict: bool) -> PyResult<Option<PyUnitCell>> {
+        Ok(self.main_block(strict)?.unit_cell())
+    }
+
+    /// `main_block(strict=strict).point_group()`
+    #[pyo3(signature = (*, strict=false))]
+    fn point_group(&self, strict: bool) -> PyResult<Option<PyPointGroup>> {
+        Ok(self.main_block(strict)?.point_group())
+    }
+
+    /// `main_block(strict=strict).category(name)`
+    #[pyo3(signature = (name, *, strict=false))]
+    fn category(&self, name: &str, strict: bool) -> PyResult<Option<PyCategory>> {
+        Ok(self.main_block(strict)?.category(name, None))
+    }
+
+    /// `main_block(strict=strict).space_group()`
+    #[pyo3(signature = (*, strict=false))]
+    fn space_group(&self, strict: bool) -> PyResult<Option<PySpaceGroup>> {
+        Ok(self.main_block(strict)?.space_group())
+    }
+
+    /// Find the first block with the given identifier
+    ///
+    /// `kind` is one of "csd", "icsd", "cod", "ccdc_deposition", "pdb",
+    /// "doi" or "dataset_doi".
+    fn find_by_identifier(&self, kind: &str, code: &str) -> PyResult<Option<PyBlock>> {
+        let kind: IdentifierKind = kind.parse().map_err(PyValueError::new_err)?;
+        let doc = self.doc();
+        let Some(found) = doc.find_by_identifier(kind, code) else {
+            return Ok(None);
+        };
+        let index = doc
+            .blocks
+            .iter()
+            .position(|block| std::ptr::eq(block, found));
+        Ok(index.map(|index| self.block_at(index)))
+    }
+
+    /// Get all blocks
+    #[getter]
+    fn blocks(&self) -> Vec<PyBlock> {
+        (0..self.doc().blocks.len())
+            .map(|index| self.block_at(index))
+            .collect()
+    }
+
+    /// Get all block names
+    #[getter]
+    fn block_names(&self) -> Vec<String> {
+        self.doc().blocks.iter().map(|b| b.name.clone()).collect()
+    }
+
+    /// Convert the whole document to plain nested Python data
+    ///
+    /// Returns `{"version": "1.1" | "2.0", "blocks": [...]}` where each block has
+    /// the same layout as `Block.to_dict()`.
+    #[pyo3(signature = (values="native", include_loops=true, include_frames=true, loops="rows"))]
+    fn to_dict<'py>(
+        &self,
+        py: Python<'py>,
+        values: &str,
+        include_loops: bool,
+        include_frames: bool,
+        loops: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let doc = self.doc();
+        let options = DictOptions {
+            values: ValueMode::from_arg(values)?,
+            loops: LoopLayout::from_arg(loops)?,
+            include_loops,
+            include_frames,
+        };
+        let blocks = PyList::empty(py);
+        for block in &doc.blocks {
+            blocks.append(block_to_py(py, block, options)?)?;
+        }
+        let dict = PyDict::new(py);
+        let version = match doc.version {
+            CifVersion::V1_1 => "1.1",
+            CifVersion::V2_0 => "2.0",
+        };
+        dict.set_item("version", version)?;
+        dict.set_item("blocks", blocks)?;
+        Ok(dict)
+    }
+
+    /// The document as CIF-JSON text, the COMCIFS JSON convention
+    ///
+    /// Names are lowercased and every data name has a list of values; `?`
+    /// is the string `"\\?"` and `.` is `null`. Raises `ValueError` if two
+    /// blocks, or two frames of a block, have names differing only in case.
+    fn to_json(&self) -> PyResult<String> {
+        self.doc().to_cif_json().map_err(cif_error_to_py_err)
+    }
+
+    /// Estimate the memory used by the parsed document, in bytes
+    ///
+    /// Returns `{"total_bytes": int, "blocks": [...], "largest_loops": [...]}`.
+    /// Each block has `name`, `bytes`, `items_bytes`, `frames_bytes`, and its
+    /// `loops`; each loop has `block`, `frame`, `first_tag`, `rows`,
+    /// `columns`, and `bytes`. This counts the Rust-side data only.
+    fn memory_footprint<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let report = self.doc().memory_footprint();
+        let blocks = PyList::empty(py);
+        for block in report.blocks {
+            let dict = PyDict::new(py);
+            dict.set_item("name", block.name)?;
+            dict.set_item("bytes", block.bytes)?;
+            dict.set_item("items_bytes", block.items_bytes)?;
+            dict.set_item("frames_bytes", block.frames_bytes)?;
+            dict.set_item("loops", loop_memory_to_py(py, block.loops)?)?;
+            blocks.append(dict)?;
+        }
+        let dict = PyDict::new(py);
+        dict.set_item("total_bytes", report.total_bytes)?;
+        dict.set_item("blocks", blocks)?;
+        dict.set_item(
+            "largest_loops",
+            loop_memory_to_py(py, report.largest_loops)?,
+        )?;
+        Ok(dict)
+    }
+
+    /// Flatten the document into one dict per value, for search indexing
+    ///
+    /// Each dict has `path` (such as `"x/_atom_site_label[3]"`), `block`,
+    /// `frame`, `tag`, `row`, `text`, `truncated`, `number`, and `kind`.
+    /// Loop values are left out with `include_loops=False`; text longer
+    /// than `max_text_len` characters is cut and ends in `…`.
+    #[pyo3(signature = (*, include_loops=true, max_text_len=None, lowercase_tags=false))]
+    fn flatten<'py>(
+        &self,
+        py: Python<'py>,
+        include_loops: bool,
+        max_text_len: Option<usize>,
+        lowercase_tags: bool,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let options = flatten_options(include_loops, max_text_len, lowercase_tags);
+        let entries = PyList::empty(py);
+        for entry in self.doc().flatten(&options) {
+            let dict = PyDict::new(py);
+            dict.set_item("path", entry.path)?;
+            dict.set_item("block", entry.block)?;
+            dict.set_item("frame", entry.frame)?;
+            dict.set_item("tag", entry.tag)?;
+            dict.set_item("row", entry.row)?;
+            dict.set_item("text", entry.text)?;
+            dict.set_item("truncated", entry.truncated)?;
+            dict.set_item("number", entry.number)?;
+            dict.set_item("kind", entry.kind.name())?;
+            entries.append(dict)?;
+        }
+        Ok(entries)
+    }
+
+    /// Write `flatten()`'s entries to `path` as newline-delimited JSON, one
+    /// object per line, for bulk indexing
+    #[pyo3(signature = (path, *, include_loops=true, max_text_len=None, lowercase_tags=false))]
+    fn to_ndjson(
+        &self,
+        path: FsPath,
+        include_loops: bool,
+        max_text_len: Option<usize>,
+        lowercase_tags: bool,
+    ) -> PyResult<()> {
+        let options = flatten_options(include_loops, max_text_len, lowercase_tags);
+        let FsPath(path) = path;
+        let file = File::create(&path).map_err(|e| io_error_to_py_err(e, &path))?;
+        self.doc()
+            .to_ndjson(BufWriter::new(file), &options)
+            .map_err(|e| io_error_to_py_err(e, &path))
+    }
+
+    /// Write the document to `path` as CIF text.
+    ///
+    /// `float_format` maps tag patterns such as `'_atom_site_fract_*'` to
+    /// how their numbers are written: an int for that many decimals, `'.4f'`
+    /// for four decimals, `'.6g'` for six significant digits, or
+    /// `'shortest'`; the pattern `'*'` sets the format for all other
+    /// numbers. With `su_rounding`, numbers with an `_su` item or column are
+    /// rounded to its precision. Numbers are otherwise written in their
+    /// shortest round-trip form.
+    ///
+    /// Raises ValueError, writing nothing, if a number is infinite or NaN.
+    #[pyo3(signature = (path, *, style="core", float_format=None, su_rounding=false))]
+    fn save(
+        &self,
+        path: FsPath,
+        style: &str,
+        float_format: Option<&Bound<'_, PyDict>>,
+        su_rounding: bool,
+    ) -> PyResult<()> {
+        let style = match style {
+            "core" => CifStyle::Core,
+            "mmcif" => CifStyle::Mmcif,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "style must be 'core' or 'mmcif', got '{other}'"
+                )))
+            }
+        };
+        let options = WriteOptions::new()
+            .style(style)
+            .float_format(float_format_from_py(float_format, su_rounding)?);
+        let doc = self.doc();
+        doc.check_integrity().map_err(cif_error_to_py_err)?;
+        let FsPath(path) = path;
+        let file = File::create(&path).map_err(|e| io_error_to_py_err(e, &path))?;
+        doc.write_to(BufWriter::new(file), &options)
+            .map_err(|e| io_error_to_py_err(e, &path))
+    }
+
+    /// Write the document to `path` as CIF text, as `str(doc)` gives it;
+    /// `save()` takes layout and number options
+    ///
+    /// Raises ValueError, writing nothing, if a number is infinite or NaN.
+    fn write(&self, path: FsPath) -> PyResult<()> {
+        let FsPath(path) = path;
+        self.doc().to_file(path).map_err(cif_error_to_py_err)
+    }
+
+    /// Take a read-only snapshot of the document, for `restore()`
+    fn snapshot(&self) -> PySnapshot {
+        PySnapshot {
+            inner: self.doc().snapshot(),
+        }
+    }
+
+    /// Put back the contents recorded by `snapshot()`, replacing everything
+    /// in the document
+    fn restore(&self, snapshot: &PySnapshot) {
+        self.handle.update(|doc| doc.restore(&snapshot.inner));
+    }
+
+    /// A context manager that puts the document back as it was if the
+    /// `with` block raises
+    ///
+    /// ```python
+    /// with doc.transaction():
+    ///     doc.redact("pre_publication")
+    ///     check(doc)  # raising here undoes the redaction
+    /// ```
+    fn transaction(&self) -> PyTransaction {
+        PyTransaction {
+            doc: self.clone(),
+            snapshot: None,
+        }
+    }
+
+    /// Check every block against a dictionary stack
+    ///
+    /// Returns one dict per value that breaks its definition, with its
+    /// `block`, `frame` (or `None`), `tag`, loop `row` (or `None`), `value`,
+    /// `kind` ("type", "enumeration", or "range"), and `message`, and the
+    /// `dictionary`, save frame (`definition`), and `category` of the
+    /// definition used. Tags no dictionary defines are not checked.
+    fn validate<'py>(
+        &self,
+        py: Python<'py>,
+        stack: &PyDictionaryStack,
+    ) -> PyResult<Bound<'py, PyList>> {
+        validation_issues_to_py(py, self.doc().validate(&stack.inner))
+    }
+
+    /// Values picked by a selector such as
+    /// `"block[*].loop(_atom_site).rows[occupancy<1].label"`
+    ///
+    /// Returns one dict per value, with its `block`, `tag`, loop `row` (or
+    /// `None` for an item), and `value` as a native Python value. Raises
+    /// ValueError, with the 1-indexed `position` of the problem, if the
+    /// selector does not parse.
+    fn query<'py>(&self, py: Python<'py>, selector: &str) -> PyResult<Bound<'py, PyList>> {
+        let hits = self.doc().query(selector).map_err(cif_error_to_py_err)?;
+        let list = PyList::empty(py);
+        for hit in hits {
+            let dict = PyDict::new(py);
+            dict.set_item("block", hit.block)?;
+            dict.set_item("tag", hit.tag)?;
+            dict.set_item("row", hit.row)?;
+            dict.set_item("value", value_to_py(py, &hit.value, ValueMode::Native)?)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// Redact sensitive values in place and report what was changed
+    ///
+    /// `profile` is "pre_publication" or "strip_embedded_files", or a list
+    /// of them; `tags` adds tag patterns using `*` and `?`. Values of
+    /// matching tags become `?`, or with `remove=True` the items and loop
+    /// columns are removed. Returns one dict per changed item or column,
+    /// with `block`, `frame`, `tag`, `values`, and `removed`.
+    #[pyo3(signature = (profile=None, *, tags=None, remove=false))]
+    fn redact<'py>(
+        &self,
+        py: Python<'py>,
+        profile: Option<&Bound<'py, PyAny>>,
+        tags: Option<Vec<String>>,
+        remove: bool,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let profiles: Vec<String> = match profile {
+            None => Vec::new(),
+            Some(profile) => match profile.extract::<String>() {
+                Ok(name) => vec![name],
+                Err(_) => profile.extract()?,
+            },
+        };
+        let mut policy = RedactionPolicy::new().remove_items(remove);
+        for name in profiles {
+            policy = policy.profile(name.parse().map_err(PyValueError::new_err)?);
+        }
+        for tag in tags.unwrap_or_default() {
+            policy = policy.tag(tag);
+        }
+
+        let report = self.handle.update(|doc| doc.redact(&policy));
+        let redactions = PyList::empty(py);
+        for redaction in report.redactions {
+            let dict = PyDict::new(py);
+            dict.set_item("block", redaction.block)?;
+            dict.set_item("frame", redaction.frame)?;
+            dict.set_item("tag", redaction.tag)?;
+            dict.set_item("values", redaction.values)?;
+            dict.set_item("removed", redaction.removed)?;
+            redactions.append(dict)?;
+        }
+        Ok(redactions)
+    }
+
+    /// Python iterator protocol
+    fn __iter__(slf: PyRef<'_, Self>) -> PyDocumentIterator {
+        PyDocumentIterator {
+            doc: slf.clone(),
+            index: 0,
+        }
+    }
+
+    /// Python getitem protocol (allows doc[0], doc["name"])
+    fn __getitem__(&self, key: &Bound<'_, PyAny>) -> PyResult<PyBlock> {
+        let doc = self.doc();
+        // Try to extract as signed integer first to handle negative indices
+        if let Ok(index) = key.extract::<isize>() {
+            let len = doc.blocks.len() as isize;
+            let actual_index = if index < 0 {
+                // Python-style negative indexing
+                let positive_index = len + index;
+                if positive_index < 0 {
+                    return Err(PyIndexError::new_err("Block index out of range"));
+                }
+                positive_index as usize
+            } else {
+                index as usize
+            };
+
+            (actual_index < doc.blocks.len())
+                .then(|| self.block_at(actual_index))
+                .ok_or_else(|| PyIndexError::new_err("Block index out of range"))
+        } else if let Ok(name) = key.extract::<String>() {
+            self.get_block_by_name(&name)
+                .ok_or_else(|| PyKeyError::new_err(format!("Block '{name}' not found")))
+        } else {
+            Err(PyTypeError::new_err("Block key must be int or str"))
+        }
+    }
+
+    /// The document as CIF text
+    fn __str__(&self) -> String {
+        self.doc().to_string()
+    }
+
+    /// Debug representation
+    fn __repr__(&self) -> String {
+        let doc = self.doc();
+        let names: Vec<&str> = doc.blocks.iter().map(|b| b.name.as_str()).collect();
+        format!("Document(blocks={names:?})")
+    }
+}
+
+impl PyDocument {
+    /// The current version of the document
+    fn doc(&self) -> Arc<CifDocument> {
+        self.handle.snapshot()
+    }
+
+    /// A block sharing this document's handle
+    fn block_at(&self, index: usize) -> PyBlock {
+        PyBlock {
+            handle: self.handle.clone(),
+            index,
+        }
+    }
+}
+
+/// Block names for error messages: `a, b, c`, or the first few and a count
+fn block_list(doc: &CifDocument) -> String {
+    const SHOWN: usize = 5;
+    let mut names: Vec<&str> = doc
+        .blocks
+        .iter()
+        .take(SHOWN)
+        .map(|b| b.name.as_str())
+        .collect();
+    let more = doc.blocks.len().saturating_sub(SHOWN);
+    let more = format!("{more} more");
+    if doc.blocks.len() > SHOWN {
+        names.push(&more);
+    }
+    names.join(", ")
+}
+
+/// Convert loop memory records to a list of dicts
+fn loop_memory_to_py<'py>(py: Python<'py>, loops: Vec<LoopMemory>) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for loop_ in loops {
+        let dict = PyDict::new(py);
+        dict.set_item("block", loop_.block)?;
+        dict.set_item("frame", loop_.frame)?;
+        dict.set_item("first_tag", loop_.first_tag)?;
+        dict.set_item("rows", loop_.rows)?;
+        dict.set_item("columns", loop_.columns)?;
+        dict.set_item("bytes", loop_.bytes)?;
+        list.append(dict)?;
+    }
+    Ok(list)
+}
+
+/// Iterator over the tags of a block, taken when iteration starts
+#[pyclass]
+pub struct PyTagIterator {
+    tags: std::vec::IntoIter<String>,
+}
+
+#[pymethods]
+impl PyTagIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<String> {
+        self.tags.next()
+    }
+}
+
+/// Iterator over the entries of a concatenated CIF stream
+#[pyclass]
+pub struct PyStreamIterator {
+    entries: DocumentStream<BufReader<File>>,
+    path: PathBuf,
+    /// How to report entries that fail to parse: "warn", "ignore", or "raise"
+    errors: String,
+}
+
+#[pymethods]
+impl PyStreamIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyDocument>> {
+        loop {
+            let entries = &mut self.entries;
+            let error = match py.detach(|| entries.next()) {
+                None => return Ok(None),
+                Some(Ok(doc)) => return Ok(Some(PyDocument { handle: doc.into() })),
+                Some(Err(error)) => error.with_path(&self.path),
+            };
+            match self.errors.as_str() {
+                "ignore" => {}
+                "warn" => {
+                    let message = CString::new(error.to_string())
+                        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                    let category = py.get_type::<PyUserWarning>();
+                    PyErr::warn(py, category.as_any(), &message, 1)?;
+                }
+                _ => return Err(cif_error_to_py_err(error)),
+            }
+        }
+    }
+}
+
+/// A Python value as a CIF value: numbers, text, None for `?`, and lists
+/// and dicts of these for CIF 2.0
+fn value_from_py(obj: &Bound<'_, PyAny>) -> PyResult<CifValue> {
+    if obj.is_none() {
+        return Ok(CifValue::Unknown);
+    }
+    if let Ok(text) = obj.cast::<PyString>() {
+        return Ok(CifValue::Text(text.to_str()?.to_string()));
+    }
+    if obj.is_instance_of::<PyBool>() {
+        return Err(PyTypeError::new_err(
+            "bool has no CIF form; write 'yes'/'no' or 1/0 explicitly",
+        ));
+    }
+    if let Ok(number) = obj.extract::<f64>() {
+        return Ok(CifValue::Numeric(number));
+    }
+    if let Ok(list) = obj.cast::<PyList>() {
+        return list
+            .iter()
+            .map(|item| value_from_py(&item))
+            .collect::<PyResult<_>>()
+            .map(CifValue::List);
+    }
+    if let Ok(dict) = obj.cast::<PyDict>() {
+        let mut table = HashMap::new();
+        for (key, value) in dict.iter() {
+            table.insert(key.extract::<String>()?, value_from_py(&value)?);
+        }
+        return Ok(CifValue::Table(table));
+    }
+    Err(PyTypeError::new_err(format!(
+        "cannot write a {} as a CIF value",
+        obj.get_type().name()?
+    )))
+}
+
+/// Options for `Document.flatten` and `Document.to_ndjson`
+fn flatten_options(
+    include_loops: bool,
+    max_text_len: Option<usize>,
+    lowercase_tags: bool,
+) -> FlattenOptions {
+    let options = FlattenOptions::new()
+        .include_loops(include_loops)
+        .lowercase_tags(lowercase_tags);
+    match max_text_len {
+        Some(max) => options.max_text_len(max),
+        None => options,
+    }
+}
+
+/// The number formats of `Document.save`, from a dict of tag patterns
+fn float_format_from_py(
+    formats: Option<&Bound<'_, PyDict>>,
+    su_rounding: bool,
+) -> PyResult<FloatFormat> {
+    let mut float_format = FloatFormat::new().su_rounding(su_rounding);
+    for (pattern, format) in formats.into_iter().flat_map(|d| d.iter()) {
+        let pattern: String = pattern.extract()?;
+        let format = number_format_from_py(&format)?;
+        float_format = match pattern.as_str() {
+            "*" => float_format.default_format(format),
+            _ => float_format.tag(pattern, format),
+        };
+    }
+    Ok(float_format)
+}
+
+/// A number format: an int of decimals, `'.Nf'`, `'.Ng'`, or `'shortest'`
+fn number_format_from_py(format: &Bound<'_, PyAny>) -> PyResult<NumberFormat> {
+    if let Ok(decimals) = format.extract::<u32>() {
+        return Ok(NumberFormat::Decimals(decimals));
+    }
+    let spec: String = format.extract().map_err(|_| {
+        PyValueError::new_err("a number format must be an int or a str such as '.4f'")
+    })?;
+    let parsed = match spec.as_str() {
+        "shortest" => Some(NumberFormat::Shortest),
+        _ => spec.strip_prefix('.').and_then(|rest| {
+            let (digits, kind) = rest.split_at(rest.len().checked_sub(1)?);
+            let digits: u32 = digits.parse().ok()?;
+            match kind {
+                "f" => Some(NumberFormat::Decimals(digits)),
+                "g" if digits > 0 => Some(NumberFormat::Significant(digits)),
+                _ => None,
+            }
+        }),
+    };
+    parsed.ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "unknown number format '{spec}': expected '.Nf', '.Ng', or 'shortest'"
+        ))
+    })
+}
+
+/// An error from `CifWriter`: misuse becomes ValueError, anything else IOError
+fn writer_error_to_py_err(err: std::io::Error) -> PyErr {
+    if err.kind() == std::io::ErrorKind::InvalidInput {
+        PyValueError::new_err(err.to_string())
+    } else {
+        PyIOError::new_err(err.to_string())
+    }
+}
+
+/// Streaming CIF writer to a file, for loops too large to build as a
+/// document
+#[pyclass(name = "Writer")]
+pub struct PyWriter {
+    /// None once closed
+    writer: Option<CifWriter<BufWriter<File>>>,
+}
+
+impl PyWriter {
+    fn writer(&mut self) -> PyResult<&mut CifWriter<BufWriter<File>>> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("the writer is closed"))
+    }
+}
+
+#[pymethods]
+impl PyWriter {
+    /// Open `path` for writing
+    ///
+    /// `version` is `"1.1"` or `"2.0"` (needed for list and dict values);
+    /// `style="mmcif"` leaves out the CIF 1.1 header and writes a `#` line
+    /// after each category.
+    #[new]
+    #[pyo3(signature = (path, *, version="1.1", style="core"))]
+    fn new(path: FsPath, version: &str, style: &str) -> PyResult<Self> {
+        let FsPath(path) = path;
+        let version = match version {
+            "1.1" => CifVersion::V1_1,
+            "2.0" => CifVersion::V2_0,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "version must be '1.1' or '2.0', got '{other}'"
+                )))
+            }
+        };
+        let style = match style {
+            "core" => CifStyle::Core,
+            "mmcif" => CifStyle::Mmcif,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "style must be 'core' or 'mmcif', got '{other}'"
+                )))
+            }
+        };
+        let file = File::create(&path).map_err(|e| io_error_to_py_err(e, &path))?;
+        let options = WriteOptions::new().style(style);
+        Ok(PyWriter {
+            writer: Some(CifWriter::new(BufWriter::new(file), options).version(version)),
+        })
+    }
+
+    /// Start a data block
+    fn begin_block(&mut self, name: &str) -> PyResult<()> {
+        self.writer()?
+            .begin_block(name)
+            .map_err(writer_error_to_py_err)
+    }
+
+    /// Write one data item; `value` is a number, str, None (for `?`), or for
+    /// CIF 2.0 a list or dict of these
+    fn write_item(&mut self, tag: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let value = value_from_py(value)?;
+        self.writer()?
+            .write_item(tag, &value)
+            .map_err(writer_error_to_py_err)
+    }
+
+    /// Start a loop with these column tags
+    fn begin_loop(&mut self, tags: Vec<String>) -> PyResult<()> {
+        self.writer()?
+            .begin_loop(&tags)
+            .map_err(writer_error_to_py_err)
+    }
+
+    /// Write one loop row, one value per column
+    fn write_row(&mut self, values: Vec<Bound<'_, PyAny>>) -> PyResult<()> {
+        let values: Vec<CifValue> = values.iter().map(value_from_py).collect::<PyResult<_>>()?;
+        self.writer()?
+            .write_row(&values)
+            .map_err(writer_error_to_py_err)
+    }
+
+    /// End the open loop
+    fn end_loop(&mut self) -> PyResult<()> {
+        self.writer()?.end_loop().map_err(writer_error_to_py_err)
+    }
+
+    /// End the open block
+    fn end_block(&mut self) -> PyResult<()> {
+        self.writer()?.end_block().map_err(writer_error_to_py_err)
+    }
+
+    /// Flush and close the file; fails if a block or loop is still open.
+    /// Closing twice does nothing.
+    fn close(&mut self) -> PyResult<()> {
+        match self.writer.take() {
+            Some(writer) => writer.finish().map(drop).map_err(writer_error_to_py_err),
+            None => Ok(()),
+        }
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Close on leaving the `with` block; after an exception the file is
+    /// closed without checking that blocks were ended
+    fn __exit__(
+        &mut self,
+        exc_type: Option<Bound<'_, PyAny>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        if exc_type.is_some() {
+            self.writer = None;
+            return Ok(false);
+        }
+        self.close()?;
+        Ok(false)
+    }
+}
+
+/// Python wrapper for a read-only snapshot of a document
+#[pyclass(name = "Snapshot", frozen)]
+pub struct PySnapshot {
+    inner: DocumentSnapshot,
+}
+
+#[pymethods]
+impl PySnapshot {
+    /// Block names, in order
+    #[getter]
+    fn block_names(&self) -> Vec<String> {
+        self.inner.block_names().map(str::to_string).collect()
+    }
+
+    /// Estimated bytes held by the snapshot
+    #[getter]
+    fn bytes(&self) -> usize {
+        self.inner.bytes()
+    }
+
+    /// A new document with the snapshot's contents
+    fn to_document(&self) -> PyDocument {
+        PyDocument {
+            handle: self.inner.to_document().into(),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Snapshot(blocks={:?})", self.block_names())
+    }
+}
+
+/// Context manager returned by `Document.transaction()`
+#[pyclass(name = "Transaction")]
+pub struct PyTransaction {
+    doc: PyDocument,
+    snapshot: Option<DocumentSnapshot>,
+}
+
+#[pymethods]
+impl PyTransaction {
+    /// Snapshot the document and return it
+    fn __enter__(&mut self) -> PyDocument {
+        self.snapshot = Some(self.doc.doc().snapshot());
+        self.doc.clone()
+    }
+
+    /// Restore the snapshot if the `with` block raised; the exception is
+    /// not suppressed
+    fn __exit__(
+        &mut self,
+        exc_type: Option<Bound<'_, PyAny>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> bool {
+        if let (Some(_), Some(snapshot)) = (exc_type, self.snapshot.take()) {
+            self.doc.handle.update(|doc| doc.restore(&snapshot));
+        }
+        false
+    }
+}
+
+/// Python wrapper for a DDLm dictionary
+#[pyclass(name = "Dictionary", frozen)]
+pub struct PyDictionary {
+    inner: Arc<CifDictionary>,
+}
+
+#[pymethods]
+impl PyDictionary {
+    /// Parse a dictionary from text
+    #[staticmethod]
+    fn parse(content: &str) -> PyResult<PyDictionary> {
+        CifDictionary::parse(content)
+            .map(|dictionary| PyDictionary {
+                inner: Arc::new(dictionary),
+            })
+            .map_err(cif_error_to_py_err)
+    }
+
+    /// Parse a dictionary file, such as `cif_core.dic`
+    #[staticmethod]
+    fn from_file(path: FsPath) -> PyResult<PyDictionary> {
+        CifDictionary::from_file(path.0)
+            .map(|dictionary| PyDictionary {
+                inner: Arc::new(dictionary),
+            })
+            .map_err(cif_error_to_py_err)
+    }
+
+    /// `_dictionary.title`, or the data block's name without one
+    #[getter]
+    fn title(&self) -> &str {
+        &self.inner.title
+    }
+
+    /// `_dictionary.version`, if given
+    #[getter]
+    fn version(&self) -> Option<&str> {
+        self.inner.version.as_deref()
+    }
+
+    /// The definition of a tag, in any case and by any of its names, or
+    /// `None`
+    fn get<'py>(&self, py: Python<'py>, tag: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.inner
+            .get(tag)
+            .map(|definition| definition_to_py(py, definition))
+            .transpose()
+    }
+
+    fn __contains__(&self, tag: &str) -> bool {
+        self.inner.get(tag).is_some()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Dictionary(title={:?}, definitions={})",
+            self.inner.title,
+            self.inner.len()
+        )
+    }
+}
+
+/// Python wrapper for layered dictionaries, later ones overriding earlier
+/// ones tag by tag
+#[pyclass(name = "DictionaryStack")]
+pub struct PyDictionaryStack {
+    inner: DictionaryStack,
+}
+
+#[pymethods]
+impl PyDictionaryStack {
+    /// Create a stack from dictionaries, bottom first
+    #[new]
+    #[pyo3(signature = (dictionaries=None))]
+    fn new(dictionaries: Option<Vec<PyRef<'_, PyDictionary>>>) -> Self {
+        let mut inner = DictionaryStack::new();
+        for dictionary in dictionaries.unwrap_or_default() {
+            inner.push(Arc::clone(&dictionary.inner));
+        }
+        PyDictionaryStack { inner }
+    }
+
+    /// Add a dictionary on top
+    fn push(&mut self, dictionary: &PyDictionary) {
+        self.inner.push(Arc::clone(&dictionary.inner));
+    }
+
+    /// Titles of the dictionaries, bottom first
+    #[getter]
+    fn titles(&self) -> Vec<String> {
+        self.inner
+            .dictionaries()
+            .iter()
+            .map(|dictionary| dictionary.title.clone())
+            .collect()
+    }
+
+    /// The definition of a tag in force, with its `type`, `units`,
+    /// `enumeration`, `range`, and `description`, and the `dictionary` and
+    /// save `frame` it is from; `None` if no dictionary defines the tag
+    fn describe<'py>(&self, py: Python<'py>, tag: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.inner
+            .definition_of(tag)
+            .map(|definition| definition_to_py(py, definition))
+            .transpose()
+    }
+
+    /// Every definition of a tag, the one in force first
+    fn definitions_of<'py>(&self, py: Python<'py>, tag: &str) -> PyResult<Bound<'py, PyList>> {
+        let definitions = PyList::empty(py);
+        for definition in self.inner.definitions_of(tag) {
+            definitions.append(definition_to_py(py, definition)?)?;
+        }
+        Ok(definitions)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.dictionaries().len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DictionaryStack({:?})", self.titles())
+    }
+}
+
+/// Iterator for PyDocument
+#[pyclass]
+pub struct PyDocumentIterator {
+    doc: PyDocument,
+    index: usize,
+}
+
+#[pymethods]
+impl PyDocumentIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<PyBlock> {
+        if self.index < self.doc.doc().blocks.len() {
+            let block = self.doc.block_at(self.index);
+            self.index += 1;
+            Some(block)
+        } else {
+            None
+        }
+    }
+}
+
+/// Module initialization function
+#[pymodule]
+fn _cif_parser(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyVersion>()?;
     m.add_class::<PyDocument>()?;
+    m.add_class::<PyDocumentSource>()?;
+    m.add_class::<PySnapshot>()?;
+    m.add_class::<PyTransaction>()?;
+    m.add_class::<PyDictionary>()?;
+    m.add_class::<PyDictionaryStack>()?;
     m.add_class::<PyDocumentIterator>()?;
+    m.add_class::<PyStreamIterator>()?;
+    m.add_class::<PyWriter>()?;
+    m.add_class::<PyTagIterator>()?;
     m.add_class::<PyBlock>()?;
     m.add_class::<PyLoop>()?;
+    m.add_class::<PyCategory>()?;
     m.add_class::<PyLoopIterator>()?;
+    m.add_class::<PyColumnBuffer>()?;
+    m.add_class::<PyRowDictIterator>()?;
     m.add_class::<PyFrame>()?;
     m.add_class::<PyValue>()?;
+    m.add_class::<PyAuthor>()?;
+    m.add_class::<PyPublication>()?;
+    m.add_class::<PyIdentifiers>()?;
+    m.add_class::<PyExperimental>()?;
+    m.add_class::<PyMeasurement>()?;
+    m.add_class::<PyRefinementModel>()?;
+    m.add_class::<PySymOp>()?;
+    m.add_class::<PyPointGroup>()?;
+    m.add_class::<PySpaceGroup>()?;
+    m.add_class::<PyChainMap>()?;
+    m.add_class::<PyStructure>()?;
+    m.add_class::<PySiteSymmetry>()?;
+    m.add_class::<PyReflectionData>()?;
+    m.add_class::<PyMergedReflections>()?;
+    m.add_class::<PyUnitCell>()?;
+    m.add_class::<PyComparison>()?;
+    m.add_class::<PyStructureComparison>()?;
+    m.add_class::<PyCoercionReport>()?;
+    m.add("ValueSource", value_source_type(m.py())?)?;
 
     // Convenience functions
     m.add_function(wrap_pyfunction!(parse, m)?)?;
     m.add_function(wrap_pyfunction!(parse_file, m)?)?;
+    m.add_function(wrap_pyfunction!(iter_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(extract, m)?)?;
+    m.add_function(wrap_pyfunction!(compare, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_structures, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_element, m)?)?;
+    m.add_function(wrap_pyfunction!(category_of, m)?)?;
+    m.add_function(wrap_pyfunction!(format, m)?)?;
 
     // Module metadata
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
@@ -730,12 +5161,239 @@ fn _cif_parser(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
 
 /// Convenience function for parsing CIF content
 #[pyfunction]
-fn parse(content: &str) -> PyResult<PyDocument> {
-    PyDocument::parse(content)
+#[pyo3(signature = (content, *, tags=None, blocks=None, first_block_only=false, duplicate_tags="last", lenient=false, large_value_threshold=None))]
+fn parse(
+    content: &str,
+    tags: Option<Vec<String>>,
+    blocks: Option<Vec<String>>,
+    first_block_only: bool,
+    duplicate_tags: &str,
+    lenient: bool,
+    large_value_threshold: Option<usize>,
+) -> PyResult<PyDocument> {
+    PyDocument::parse(
+        content,
+        tags,
+        blocks,
+        first_block_only,
+        duplicate_tags,
+        lenient,
+        large_value_threshold,
+    )
 }
 
 /// Convenience function for parsing CIF files
+///
+/// With `cache_dir`, parsed documents are kept on disk keyed by content hash
+/// and an unchanged file is loaded without parsing; a leading `~` is expanded.
+#[pyfunction]
+#[pyo3(signature = (path, *, tags=None, blocks=None, first_block_only=false, duplicate_tags="last", lenient=false, large_value_threshold=None, cache_dir=None))]
+#[allow(clippy::too_many_arguments)]
+fn parse_file(
+    path: FsPath,
+    tags: Option<Vec<String>>,
+    blocks: Option<Vec<String>>,
+    first_block_only: bool,
+    duplicate_tags: &str,
+    lenient: bool,
+    large_value_threshold: Option<usize>,
+    cache_dir: Option<FsPath>,
+) -> PyResult<PyDocument> {
+    let FsPath(path) = path;
+    let options = parse_options(
+        tags,
+        blocks,
+        first_block_only,
+        duplicate_tags,
+        lenient,
+        large_value_threshold,
+    )?;
+    let Some(cache_dir) = cache_dir else {
+        return CifDocument::from_file_with_options(path, &options)
+            .map(|doc| PyDocument { handle: doc.into() })
+            .map_err(cif_error_to_py_err);
+    };
+    let cache = CacheOptions::new(expand_home(cache_dir.0));
+    CifDocument::from_file_cached_with_options(path, &options, &cache)
+        .map(|doc| PyDocument { handle: doc.into() })
+        .map_err(cif_error_to_py_err)
+}
+
+/// Expand a leading `~` to the home directory, as Python's
+/// `os.path.expanduser` does
+fn expand_home(path: PathBuf) -> PathBuf {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return path;
+    };
+    match std::env::var_os("HOME").map(PathBuf::from) {
+        Some(home) if rest.as_os_str().is_empty() => home,
+        Some(home) => home.join(rest),
+        None => path,
+    }
+}
+
+/// Iterate over the entries of a file of concatenated CIFs
+///
+/// Yields one single-block `Document` per `data_` block, reading the file as
+/// it goes. An entry that fails to parse is reported according to `errors`:
+/// "warn" (a `UserWarning`, then carry on), "ignore", or "raise".
+#[pyfunction]
+#[pyo3(signature = (path, *, errors="warn"))]
+fn iter_stream(path: FsPath, errors: &str) -> PyResult<PyStreamIterator> {
+    let FsPath(path) = path;
+    let errors = match errors {
+        "warn" | "ignore" | "raise" => errors.to_string(),
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "errors must be 'warn', 'ignore', or 'raise', not '{other}'"
+            )))
+        }
+    };
+    let file = File::open(&path).map_err(|e| io_error_to_py_err(e, &path))?;
+    Ok(PyStreamIterator {
+        entries: CifDocument::parse_multi_stream(BufReader::new(file)),
+        path,
+        errors,
+    })
+}
+
+/// Extract single-valued tags from many files, on several threads
+///
+/// Returns one dict per data block with `path`, `block`, `error`, and each
+/// tag; a file that fails to parse gives one dict with its error.
+#[pyfunction]
+#[pyo3(signature = (paths, tags, *, first_block_only=false, threads=None))]
+fn extract<'py>(
+    py: Python<'py>,
+    paths: Vec<FsPath>,
+    tags: Vec<String>,
+    first_block_only: bool,
+    threads: Option<usize>,
+) -> PyResult<Bound<'py, PyList>> {
+    let mut options = ExtractOptions::new().first_block_only(first_block_only);
+    if let Some(threads) = threads {
+        if threads == 0 {
+            return Err(PyValueError::new_err("threads must be at least 1"));
+        }
+        options = options.threads(threads);
+    }
+    let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+    let paths: Vec<PathBuf> = paths.into_iter().map(|FsPath(path)| path).collect();
+    let records = py.detach(|| extract_items(&paths, &tags, &options));
+
+    let list = PyList::empty(py);
+    for record in records {
+        let dict = PyDict::new(py);
+        dict.set_item("path", record.path.as_os_str())?;
+        dict.set_item("block", record.block)?;
+        dict.set_item("error", record.error.map(|error| error.to_string()))?;
+        for (tag, value) in record.values {
+            dict.set_item(tag, value.map(PyValue::from))?;
+        }
+        list.append(dict)?;
+    }
+    Ok(list)
+}
+
+/// Read an atom type symbol such as `Fe3+`, `O-2` or `D`
+///
+/// Returns a dict with the `element` symbol, `atomic_number`, `charge`, and
+/// the `isotope` mass number for `D` and `T`.
 #[pyfunction]
-fn parse_file(path: &str) -> PyResult<PyDocument> {
-    PyDocument::from_file(path)
+fn parse_element<'py>(py: Python<'py>, symbol: &str) -> PyResult<Bound<'py, PyDict>> {
+    let parsed = ElementSymbol::parse(symbol).map_err(cif_error_to_py_err)?;
+    let dict = PyDict::new(py);
+    dict.set_item("element", parsed.element.symbol())?;
+    dict.set_item("atomic_number", parsed.element.atomic_number())?;
+    dict.set_item("charge", parsed.charge)?;
+    dict.set_item("isotope", parsed.isotope.map(Isotope::mass_number))?;
+    Ok(dict)
+}
+
+/// Category of an underscore-style or mmCIF-style tag, lowercased and
+/// without the leading underscore
+///
+/// `categories` adds category names, such as `pd_proc`, to the built-in core
+/// CIF ones.
+#[pyfunction]
+#[pyo3(signature = (tag, categories=None))]
+fn category_of(tag: &str, categories: Option<Vec<String>>) -> String {
+    let category = category_resolver(categories).category_of(tag);
+    category.trim_start_matches('_').to_string()
+}
+
+/// Format CIF text canonically, keeping its comments
+///
+/// Items come in tag order with their values lined up, loop values in
+/// columns, and each comment before what it was written with. Formatting
+/// the result again gives the same text.
+#[pyfunction]
+#[pyo3(signature = (text, *, align=true, comments=true))]
+fn format(text: &str, align: bool, comments: bool) -> PyResult<String> {
+    let options = FormatOptions::new().align(align).comments(comments);
+    format_str(text, &options).map_err(cif_error_to_py_err)
+}
+
+fn category_resolver(categories: Option<Vec<String>>) -> CategoryResolver {
+    let mut resolver = CategoryResolver::new();
+    for category in categories.unwrap_or_default() {
+        resolver.register(&category);
+    }
+    resolver
+}
+
+/// Compare two numbers, each with an optional su
+///
+/// The difference is measured in the sus combined in quadrature; without
+/// any su, `relative_tolerance` of the larger magnitude counts as `n_sigma`.
+#[pyfunction]
+#[pyo3(signature = (a, b, *, su_a=None, su_b=None, n_sigma=3.0, relative_tolerance=1e-3))]
+fn compare(
+    a: f64,
+    b: f64,
+    su_a: Option<f64>,
+    su_b: Option<f64>,
+    n_sigma: f64,
+    relative_tolerance: f64,
+) -> PyComparison {
+    let inner = ValueComparison::new()
+        .relative_tolerance(relative_tolerance)
+        .compare((a, su_a), (b, su_b), n_sigma);
+    PyComparison { inner }
+}
+
+/// Compare the structures of two blocks atom by atom
+///
+/// Sites are paired `by="label"` or `by="proximity"` (same element, at most
+/// `max_distance` Å apart); `origin_shift=True` fits the origin along polar
+/// axes first. Raises ValueError if a block has no unit cell.
+#[pyfunction]
+#[pyo3(signature = (block_a, block_b, *, by="label", max_distance=0.5, origin_shift=false))]
+fn compare_structures(
+    block_a: &PyBlock,
+    block_b: &PyBlock,
+    by: &str,
+    max_distance: f64,
+    origin_shift: bool,
+) -> PyResult<PyStructureComparison> {
+    let matching = match by {
+        "label" => SiteMatching::Label,
+        "proximity" => SiteMatching::Proximity { max_distance },
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "by must be 'label' or 'proximity', not '{other}'"
+            )))
+        }
+    };
+    let structure = |block: &PyBlock| {
+        let block = block.block();
+        block.structure().ok_or_else(|| {
+            PyValueError::new_err(format!("Block '{}' has no unit cell", block.name))
+        })
+    };
+    let options = MatchOptions::new()
+        .matching(matching)
+        .origin_shift(origin_shift);
+    let inner = crate::compare_structures(&structure(block_a)?, &structure(block_b)?, &options);
+    Ok(PyStructureComparison { inner })
 }
@@ -0,0 +1,598 @@
+//! A compact selector language for pulling values out of documents.
+//!
+//! A query picks blocks, then an item or a loop, then optionally rows of the
+//! loop by the values in their columns, then the columns to return:
+//!
+//! ```text
+//! block[*].loop(_atom_site).rows[occupancy<1]._atom_site_label
+//! ```
+//!
+//! The parts are:
+//!
+//! - `block[...]`: the blocks to look in, by name, by a glob with `*` and
+//!   `?` in it, by index (`block[0]`, or `block[-1]` for the last), or all
+//!   of them (`block[*]`). Names match without regard to case. Without a
+//!   block selector, every block is searched.
+//! - A tag, such as `_cell_length_a`: the item, or every value of the loop
+//!   column, with that tag. A glob, such as `_cell_length_*`, matches every
+//!   such tag.
+//! - `loop(...)`: the loop holding a tag, or the loop of a category, as in
+//!   `loop(_atom_site)`.
+//! - `.rows[...]`: the rows of the loop where a column compares with a
+//!   value. Columns are named by their full tag or by what follows the
+//!   loop's category (`occupancy` for `_atom_site_occupancy`). The operators
+//!   are `=`, `!=`, `<`, `<=`, `>`, `>=`, and `~` for a glob, which ignores
+//!   case; the value is a number, a word, or a quoted string. Conditions
+//!   joined with `&` must all hold.
+//! - The columns to return after the loop, as `.label` or
+//!   `.(label, occupancy)`; all of them if none are named.
+//!
+//! A number matches values that read as numbers, ignoring their su; other
+//! values never match it, not even with `!=`. Likewise `?` and `.` values
+//! match only `=?` and `=.`, and a column the loop lacks matches no row.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{query, Document};
+//!
+//! let doc = Document::parse(
+//!     "data_a\nloop_\n_atom_site_label\n_atom_site_occupancy\nC1 1.0\nO1 0.5(1)\nN1 ?\n",
+//! )
+//! .unwrap();
+//! let hits = query(&doc, "block[*].loop(_atom_site).rows[occupancy<1]._atom_site_label").unwrap();
+//!
+//! assert_eq!(hits.len(), 1);
+//! assert_eq!(hits[0].value.as_string(), Some("O1"));
+//! assert_eq!((hits[0].block.as_str(), hits[0].row), ("a", Some(1)));
+//!
+//! let error = query(&doc, "block[*].loop(_atom_site").unwrap_err();
+//! assert!(error.to_string().contains("at character 25"));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifLoop, CifValue, CifVersion};
+use crate::category::category_of;
+use crate::error::CifError;
+use crate::redact::glob_match;
+use crate::uncertainty;
+use crate::writer::inline_value;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A value found by a query, with where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryHit {
+    /// Name of the block
+    pub block: String,
+    /// Tag of the item or loop column, as written
+    pub tag: String,
+    /// Loop row, or `None` for a data item
+    pub row: Option<usize>,
+    pub value: CifValue,
+}
+
+impl fmt::Display for QueryHit {
+    /// `block tag = value`, with the row after the tag as `[2]` for a loop
+    /// value, and the value as CIF text
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.block, self.tag)?;
+        if let Some(row) = self.row {
+            write!(f, "[{row}]")?;
+        }
+        match self.value.as_string() {
+            Some(text) => write!(f, " = {}", text.trim()),
+            None => write!(f, " = {}", inline_value(&self.value, CifVersion::V2_0)),
+        }
+    }
+}
+
+/// Which blocks a query looks in
+#[derive(Debug, Clone, PartialEq)]
+enum BlockSelector {
+    All,
+    /// By position; negative counts from the end
+    Index(isize),
+    /// By name, or by a glob
+    Name(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Glob,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Number(f64),
+    Text(String),
+    Unknown,
+    NotApplicable,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    column: String,
+    operator: Operator,
+    literal: Literal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    /// Items and loop columns with a tag, or matching a glob
+    Tag(String),
+    Loop {
+        /// A tag of the loop, or its category
+        selector: String,
+        conditions: Vec<Condition>,
+        /// Columns to return; all of them if empty
+        columns: Vec<String>,
+    },
+}
+
+/// A parsed query. See the [module documentation](self) for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    blocks: BlockSelector,
+    target: Target,
+}
+
+/// Run a query over a document. See the [module documentation](self).
+///
+/// Fails if the query does not parse, with the position of the problem.
+pub fn query(doc: &CifDocument, selector: &str) -> Result<Vec<QueryHit>, CifError> {
+    Ok(Query::parse(selector)?.evaluate(doc))
+}
+
+impl CifDocument {
+    /// Run a [query](crate::query) over the document
+    pub fn query(&self, selector: &str) -> Result<Vec<QueryHit>, CifError> {
+        query(self, selector)
+    }
+}
+
+impl Query {
+    /// Parse a query, failing with the character position of the first
+    /// problem
+    pub fn parse(selector: &str) -> Result<Self, CifError> {
+        let mut parser = Parser {
+            query: selector,
+            chars: selector.chars().collect(),
+            at: 0,
+        };
+        let query = parser.query()?;
+        parser.skip_spaces();
+        if parser.at < parser.chars.len() {
+            return Err(parser.error("unexpected text after the query"));
+        }
+        Ok(query)
+    }
+
+    /// The values the query selects, block by block in document order
+    pub fn evaluate(&self, doc: &CifDocument) -> Vec<QueryHit> {
+        let mut hits = Vec::new();
+        for block in self.blocks(doc) {
+            match &self.target {
+                Target::Tag(pattern) => tag_hits(block, pattern, &mut hits),
+                Target::Loop {
+                    selector,
+                    conditions,
+                    columns,
+                } => {
+                    for loop_ in block.loops.iter().filter(|l| selects(l, selector)) {
+                        loop_hits(block, loop_, conditions, columns, &mut hits);
+                    }
+                }
+            }
+        }
+        hits
+    }
+
+    fn blocks<'d>(&self, doc: &'d CifDocument) -> Vec<&'d CifBlock> {
+        match &self.blocks {
+            BlockSelector::All => doc.blocks.iter().collect(),
+            BlockSelector::Index(index) => {
+                let index = match usize::try_from(*index) {
+                    Ok(index) => Some(index),
+                    Err(_) => doc.blocks.len().checked_sub(index.unsigned_abs()),
+                };
+                index.and_then(|i| doc.blocks.get(i)).into_iter().collect()
+            }
+            BlockSelector::Name(pattern) => doc
+                .blocks
+                .iter()
+                .filter(|block| glob_match(pattern, &block.name))
+                .collect(),
+        }
+    }
+}
+
+fn tag_hits(block: &CifBlock, pattern: &str, hits: &mut Vec<QueryHit>) {
+    for (tag, value) in &block.items {
+        if glob_match(pattern, tag) {
+            hits.push(hit(block, tag, None, value));
+        }
+    }
+    for loop_ in &block.loops {
+        for (column, tag) in loop_.tags.iter().enumerate() {
+            if glob_match(pattern, tag) {
+                for (row, values) in loop_.values.iter().enumerate() {
+                    hits.push(hit(block, tag, Some(row), &values[column]));
+                }
+            }
+        }
+    }
+}
+
+fn loop_hits(
+    block: &CifBlock,
+    loop_: &CifLoop,
+    conditions: &[Condition],
+    columns: &[String],
+    hits: &mut Vec<QueryHit>,
+) {
+    let wanted: Vec<usize> = match columns {
+        [] => (0..loop_.tags.len()).collect(),
+        names => names
+            .iter()
+            .filter_map(|n| column_index(loop_, n))
+            .collect(),
+    };
+    // A condition on a column the loop lacks holds for no row
+    let conditions: Option<Vec<(usize, &Condition)>> = conditions
+        .iter()
+        .map(|c| column_index(loop_, &c.column).map(|i| (i, c)))
+        .collect();
+    let Some(conditions) = conditions else {
+        return;
+    };
+    for (row, values) in loop_.values.iter().enumerate() {
+        if conditions.iter().all(|(i, c)| c.holds(&values[*i])) {
+            for &column in &wanted {
+                hits.push(hit(block, &loop_.tags[column], Some(row), &values[column]));
+            }
+        }
+    }
+}
+
+fn hit(block: &CifBlock, tag: &str, row: Option<usize>, value: &CifValue) -> QueryHit {
+    QueryHit {
+        block: block.name.clone(),
+        tag: tag.to_string(),
+        row,
+        value: value.clone(),
+    }
+}
+
+/// Whether a loop holds `selector` as a tag, or is of that category
+fn selects(loop_: &CifLoop, selector: &str) -> bool {
+    loop_
+        .tags
+        .iter()
+        .any(|tag| tag.eq_ignore_ascii_case(selector))
+        || loop_
+            .tags
+            .first()
+            .is_some_and(|tag| category_of(tag).eq_ignore_ascii_case(selector))
+}
+
+/// The column named by its full tag, or by what follows the loop's category
+fn column_index(loop_: &CifLoop, name: &str) -> Option<usize> {
+    if name.starts_with('_') {
+        return loop_.tags.iter().position(|t| t.eq_ignore_ascii_case(name));
+    }
+    let category = category_of(loop_.tags.first()?);
+    loop_.tags.iter().position(|tag| {
+        tag.get(category.len() + 1..)
+            .is_some_and(|rest| rest.eq_ignore_ascii_case(name))
+    })
+}
+
+impl Condition {
+    fn holds(&self, value: &CifValue) -> bool {
+        match (&self.literal, value) {
+            (Literal::Unknown, CifValue::Unknown)
+            | (Literal::NotApplicable, CifValue::NotApplicable) => self.operator == Operator::Eq,
+            (Literal::Unknown | Literal::NotApplicable, _) => self.operator == Operator::Ne,
+            (_, CifValue::Unknown | CifValue::NotApplicable) => false,
+            (Literal::Number(n), _) => match value.as_numeric_with_su() {
+                Some((v, _)) => v.partial_cmp(n).is_some_and(|o| self.operator.accepts(o)),
+                None => false,
+            },
+            (Literal::Text(text), _) => {
+                let value = value_text(value);
+                match self.operator {
+                    Operator::Glob => glob_match(text, &value),
+                    operator => operator.accepts(value.as_str().cmp(text.as_str())),
+                }
+            }
+        }
+    }
+}
+
+impl Operator {
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Ne => ordering != Ordering::Equal,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Le => ordering != Ordering::Greater,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Ge => ordering != Ordering::Less,
+            Operator::Glob => ordering == Ordering::Equal,
+        }
+    }
+}
+
+/// A value as text, for comparing with a word
+fn value_text(value: &CifValue) -> String {
+    match value {
+        CifValue::Numeric(n) => n.to_string(),
+        CifValue::NumericSu { value, su } => uncertainty::format_exact(*value, *su),
+        other => other.as_string().unwrap_or_default().to_string(),
+    }
+}
+
+struct Parser<'q> {
+    query: &'q str,
+    chars: Vec<char>,
+    /// Index of the next character
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> CifError {
+        CifError::InvalidQuery {
+            message: message.into(),
+            query: self.query.to_string(),
+            position: self.at + 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.at).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.at += 1;
+        }
+    }
+
+    /// Consume `expected` if it comes next
+    fn eat(&mut self, expected: &str) -> bool {
+        let end = self.at + expected.chars().count();
+        let next: String = self.chars[self.at..end.min(self.chars.len())]
+            .iter()
+            .collect();
+        let found = next.eq_ignore_ascii_case(expected);
+        if found {
+            self.at = end;
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), CifError> {
+        match self.eat(expected) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected '{expected}'"))),
+        }
+    }
+
+    /// Characters up to, not including, any of `stops` or whitespace
+    fn word(&mut self, stops: &[char]) -> String {
+        let start = self.at;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !stops.contains(&c))
+        {
+            self.at += 1;
+        }
+        self.chars[start..self.at].iter().collect()
+    }
+
+    fn query(&mut self) -> Result<Query, CifError> {
+        self.skip_spaces();
+        let blocks = if self.eat("block[") {
+            let blocks = self.block_selector()?;
+            self.expect(".")?;
+            blocks
+        } else {
+            BlockSelector::All
+        };
+        let target = if self.eat("loop(") {
+            self.loop_target()?
+        } else {
+            Target::Tag(self.tag("a tag, 'loop(', or 'block['")?)
+        };
+        Ok(Query { blocks, target })
+    }
+
+    fn block_selector(&mut self) -> Result<BlockSelector, CifError> {
+        self.skip_spaces();
+        let start = self.at;
+        let name = self.word(&[']']);
+        if name.is_empty() {
+            return Err(self.error("expected a block name, index, or '*'"));
+        }
+        self.skip_spaces();
+        self.expect("]")?;
+        let selector = match name.as_str() {
+            "*" => BlockSelector::All,
+            _ if name.starts_with(|c: char| c.is_ascii_digit() || c == '-') => match name.parse() {
+                Ok(index) => BlockSelector::Index(index),
+                Err(_) => {
+                    self.at = start;
+                    return Err(self.error(format!("'{name}' is not a block index")));
+                }
+            },
+            _ => BlockSelector::Name(name),
+        };
+        Ok(selector)
+    }
+
+    /// A tag or glob starting with `_`
+    fn tag(&mut self, expected: &str) -> Result<String, CifError> {
+        if self.peek() != Some('_') {
+            return Err(self.error(format!("expected {expected}")));
+        }
+        Ok(self.word(&['(', ')', '[', ']', ',', '&']))
+    }
+
+    fn loop_target(&mut self) -> Result<Target, CifError> {
+        self.skip_spaces();
+        let selector = self.tag("a tag or category")?;
+        self.skip_spaces();
+        self.expect(")")?;
+        let mut conditions = Vec::new();
+        let mut columns = Vec::new();
+        while self.eat(".") {
+            if self.eat("rows[") {
+                loop {
+                    conditions.push(self.condition()?);
+                    self.skip_spaces();
+                    if !self.eat("&") {
+                        break;
+                    }
+                }
+                self.expect("]")?;
+            } else if self.eat("(") {
+                loop {
+                    self.skip_spaces();
+                    columns.push(self.column()?);
+                    self.skip_spaces();
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.expect(")")?;
+                break;
+            } else {
+                columns.push(self.column()?);
+                break;
+            }
+        }
+        Ok(Target::Loop {
+            selector,
+            conditions,
+            columns,
+        })
+    }
+
+    fn column(&mut self) -> Result<String, CifError> {
+        let column = self.word(&['(', ')', '[', ']', ',', '&', '=', '!', '<', '>', '~']);
+        match column.is_empty() {
+            true => Err(self.error("expected a column")),
+            false => Ok(column),
+        }
+    }
+
+    fn condition(&mut self) -> Result<Condition, CifError> {
+        self.skip_spaces();
+        let column = self.column()?;
+        self.skip_spaces();
+        let operator = [
+            ("!=", Operator::Ne),
+            ("<=", Operator::Le),
+            (">=", Operator::Ge),
+            ("==", Operator::Eq),
+            ("=", Operator::Eq),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+            ("~", Operator::Glob),
+        ]
+        .into_iter()
+        .find_map(|(symbol, operator)| self.eat(symbol).then_some(operator))
+        .ok_or_else(|| self.error("expected one of = != < <= > >= ~"))?;
+        self.skip_spaces();
+        let literal = self.literal(operator == Operator::Glob)?;
+        Ok(Condition {
+            column,
+            operator,
+            literal,
+        })
+    }
+
+    /// A value to compare with; a `pattern` is always text
+    fn literal(&mut self, pattern: bool) -> Result<Literal, CifError> {
+        if let Some(quote @ ('\'' | '"')) = self.peek() {
+            let start = self.at;
+            self.at += 1;
+            let text_start = self.at;
+            while self.peek().is_some_and(|c| c != quote) {
+                self.at += 1;
+            }
+            if self.peek().is_none() {
+                self.at = start;
+                return Err(self.error("unterminated quoted value"));
+            }
+            let text = self.chars[text_start..self.at].iter().collect();
+            self.at += 1;
+            return Ok(Literal::Text(text));
+        }
+        let word = self.word(&[']', '&']);
+        Ok(match word.as_str() {
+            "" => return Err(self.error("expected a value")),
+            _ if pattern => Literal::Text(word),
+            "?" => Literal::Unknown,
+            "." => Literal::NotApplicable,
+            _ => match word.parse() {
+                Ok(number) => Literal::Number(number),
+                Err(_) => Literal::Text(word),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(selector: &str) -> usize {
+        match Query::parse(selector).unwrap_err() {
+            CifError::InvalidQuery { position, .. } => position,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_each_part() {
+        let query = Query::parse(
+            "block[-1].loop(_atom_site).rows[occupancy<1 & label~'C*'].(label, occupancy)",
+        )
+        .unwrap();
+        assert_eq!(query.blocks, BlockSelector::Index(-1));
+        let Target::Loop {
+            selector,
+            conditions,
+            columns,
+        } = query.target
+        else {
+            panic!("{:?}", query.target);
+        };
+        assert_eq!(selector, "_atom_site");
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[1].operator, Operator::Glob);
+        assert_eq!(conditions[1].literal, Literal::Text("C*".into()));
+        assert_eq!(columns, ["label", "occupancy"]);
+
+        let query = Query::parse("block[ab*]._cell_length_?").unwrap();
+        assert_eq!(query.blocks, BlockSelector::Name("ab*".into()));
+        assert_eq!(query.target, Target::Tag("_cell_length_?".into()));
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        assert_eq!(position("block[*]"), 9);
+        assert_eq!(position("block[*].cell"), 10);
+        assert_eq!(position("loop(_atom_site).rows[occupancy 1]"), 33);
+        assert_eq!(position("loop(_atom_site).rows[label='C1]"), 29);
+        assert_eq!(position("block[1x]._a"), 7);
+        assert_eq!(position("_a junk"), 4);
+    }
+}
@@ -0,0 +1,326 @@
+//! Reading CIF as a stream of events, without building a document.
+//!
+//! Parsing a large mmCIF file into a [`CifDocument`](crate::CifDocument)
+//! holds every value of every category at once. [`CifReader`] instead reads
+//! its input a few lines at a time and hands out an [`Event`] for each block,
+//! save frame, data item, and loop row as it meets them, so memory use does
+//! not grow with the file. It is the reading counterpart of
+//! [`CifWriter`](crate::writer::CifWriter).
+//!
+//! Values are read as the parser reads them. With
+//! [`select`](CifReader::select), unselected blocks, items, and loops are
+//! tokenized only to find where they end, and give no events.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::reader::{CifReader, Event};
+//! use cif_parser::Selection;
+//!
+//! let cif = "data_1abc\n_entry.id 1ABC\nloop_\n_atom_site.id\n_atom_site.type_symbol\n\
+//!            1 N\n2 C\n";
+//! let reader = CifReader::new(cif.as_bytes())
+//!     .select(Selection::TagPrefixes(vec!["_atom_site.".into()]));
+//!
+//! let mut elements = Vec::new();
+//! for event in reader {
+//!     if let Event::LoopRow(row) = event.unwrap() {
+//!         elements.push(row[1].as_string().unwrap().to_string());
+//!     }
+//! }
+//! assert_eq!(elements, ["N", "C"]);
+//! ```
+
+use crate::ast::{CifValue, CifVersion};
+use crate::error::CifError;
+use crate::lexer::{Token, TokenKind};
+use crate::parser::document::{detect_version, MAX_NESTING_DEPTH};
+use crate::parser::encoding::normalize_line_terminators;
+use crate::parser::options::Selection;
+use crate::parser::statements::{compound, compound_len, Sink, Statements, Tokens};
+use crate::parser::value::token_value;
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::mem;
+
+/// One piece of CIF content, in file order.
+///
+/// Each `BlockStart` is matched by a `BlockEnd`, each `FrameStart` by a
+/// `FrameEnd`, and each `LoopStart` by a `LoopEnd`, with the loop's rows in
+/// between.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A data block begins; `name` is without `data_`, and empty for `global_`
+    BlockStart { name: String },
+    /// The current data block ends
+    BlockEnd,
+    /// A save frame begins; `name` is without `save_`
+    FrameStart { name: String },
+    /// The current save frame ends
+    FrameEnd,
+    /// A data item outside any loop
+    Item { tag: String, value: CifValue },
+    /// A loop begins, with these column tags
+    LoopStart { tags: Vec<String> },
+    /// One row of the current loop, one value per tag
+    LoopRow(Vec<CifValue>),
+    /// The current loop ends
+    LoopEnd,
+}
+
+/// Pull reader giving the [`Event`]s of CIF text, one at a time.
+///
+/// The CIF version comes from a `#\#CIF_2.0` magic comment on the first
+/// line. Text fields, multi-line strings, and CIF 2.0 lists and tables are
+/// read whole however many lines they span; everything else is read line by
+/// line.
+///
+/// The first error ends the iteration. Errors carry the line and column in
+/// the input; the events before one are still given first.
+///
+/// Checks that need the whole block, such as for duplicate tags, are left
+/// to the consumer.
+pub struct CifReader<R: BufRead> {
+    reader: R,
+    /// Lines read but not yet tokenized
+    pending: String,
+    /// How many lines `pending` holds
+    pending_lines: usize,
+    /// Lines read so far
+    lines_read: usize,
+    /// Blocks, items, and loops read, with the events not yet given
+    statements: Statements<VecDeque<Event>>,
+    error: Option<CifError>,
+}
+
+impl<R: BufRead> CifReader<R> {
+    /// Read CIF from `reader`
+    pub fn new(reader: R) -> Self {
+        CifReader {
+            reader,
+            pending: String::new(),
+            pending_lines: 0,
+            lines_read: 0,
+            statements: Statements::new(VecDeque::new(), CifVersion::V1_1),
+            error: None,
+        }
+    }
+
+    /// Give events only for the selected content, as
+    /// [`ParseOptions::select`](crate::ParseOptions::select) keeps it.
+    ///
+    /// Calling this more than once narrows the selection further. With
+    /// [`Selection::FirstBlockOnly`], reading stops at the second block.
+    pub fn select(mut self, selection: Selection) -> Self {
+        self.statements.select.push(selection);
+        self
+    }
+
+    /// The CIF version of the input, known once the first event is read
+    pub fn version(&self) -> CifVersion {
+        self.statements.version
+    }
+
+    /// Read the next line onto `pending`; false at the end of input
+    fn read_line(&mut self) -> Result<bool, CifError> {
+        let mut line = Vec::new();
+        if self.reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(false);
+        }
+        let number = self.lines_read + 1;
+        let line = String::from_utf8(line).map_err(|_| {
+            CifError::encoding(format!("Line {number} is not valid UTF-8")).at_location(number, 1)
+        })?;
+        // A lone CR ends a line too, so one read can hold several
+        let line = normalize_line_terminators(&line).unwrap_or(line);
+        let lines = line.matches('\n').count() + usize::from(!line.ends_with('\n'));
+        if self.lines_read == 0 {
+            self.statements.version = detect_version(&line);
+        }
+        self.lines_read += lines;
+        self.pending_lines += lines;
+        self.pending.push_str(&line);
+        Ok(true)
+    }
+
+    /// Read and handle the next lines that tokenize on their own
+    fn fill(&mut self) -> Result<(), CifError> {
+        if !self.read_line()? {
+            return self.finish();
+        }
+        let mut eof = false;
+        loop {
+            let first_line = self.lines_read - self.pending_lines + 1;
+            let pending = mem::take(&mut self.pending);
+            match complete_tokens(&pending, first_line, eof) {
+                Ok(Some(tokens)) => {
+                    let result = self.handle_tokens(&pending, &tokens, first_line);
+                    self.pending = pending;
+                    self.pending.clear();
+                    self.pending_lines = 0;
+                    return result;
+                }
+                Ok(None) => self.pending = pending,
+                Err(error) => return Err(error),
+            }
+            // Read as many lines again, so a long text field is tokenized
+            // a few times rather than once per line
+            for _ in 0..self.pending_lines {
+                if !self.read_line()? {
+                    eof = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_tokens(
+        &mut self,
+        source: &str,
+        tokens: &[Token],
+        first_line: usize,
+    ) -> Result<(), CifError> {
+        let version = self.statements.version;
+        let mut i = 0;
+        while i < tokens.len() && !self.statements.done {
+            let token = &tokens[i];
+            let at = (token.line + first_line - 1, token.col);
+            i += 1;
+            match token.kind {
+                TokenKind::QuotedString | TokenKind::TextField | TokenKind::UnquotedValue => {
+                    self.statements.value(token.text, at, || {
+                        token_value(token.kind, token.text, version)
+                            .expect("a value token")
+                            .map_err(|error| error.at_location(at.0, at.1))
+                    })?;
+                }
+                TokenKind::Delimiter if matches!(token.text, "[" | "{") => {
+                    let end = i + compound_len(token.text, &tokens[i..], first_line)?;
+                    let inner = &tokens[i..end];
+                    let close = tokens.get(end).map_or(source.len(), |t| t.byte_range.end);
+                    self.statements.value(token.text, at, || {
+                        compound(token, inner, close, source, version, first_line)
+                            .map(|value| value.into_owned())
+                    })?;
+                    i = end + 1;
+                }
+                _ => self.statements.token(token, at, String::from)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Close everything still open at the end of input
+    fn finish(&mut self) -> Result<(), CifError> {
+        let at = (self.lines_read.max(1), 1);
+        self.statements.finish(at)
+    }
+}
+
+impl<R: BufRead> Iterator for CifReader<R> {
+    type Item = Result<Event, CifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.statements.sink.pop_front() {
+                return Some(Ok(event));
+            }
+            if let Some(error) = self.error.take() {
+                return Some(Err(error));
+            }
+            if self.statements.done {
+                return None;
+            }
+            if let Err(error) = self.fill() {
+                self.statements.done = true;
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// The tokens of `text`, or `None` if it ends inside a token, list, or table
+/// and more input may follow. `first_line` is the line number of its first
+/// line, for errors.
+fn complete_tokens(
+    text: &str,
+    first_line: usize,
+    eof: bool,
+) -> Result<Option<Vec<Token<'_>>>, CifError> {
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut opened = (0, 0);
+    for token in Tokens::new(text, first_line) {
+        let token = match token {
+            Ok(token) => token,
+            Err(CifError::Unterminated { .. }) if !eof => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        if token.kind == TokenKind::Delimiter {
+            match token.text {
+                "[" | "{" => {
+                    if depth == 0 {
+                        opened = (token.line + first_line - 1, token.col);
+                    }
+                    depth += 1;
+                    if depth > MAX_NESTING_DEPTH {
+                        return Err(CifError::NestingTooDeep {
+                            max_depth: MAX_NESTING_DEPTH,
+                            location: Some((token.line + first_line - 1, token.col)),
+                            span: None,
+                        });
+                    }
+                }
+                "]" | "}" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        tokens.push(token);
+    }
+    match (depth, eof) {
+        (0, _) => Ok(Some(tokens)),
+        (_, false) => Ok(None),
+        (_, true) => Err(CifError::Unterminated {
+            construct: "list or table".to_string(),
+            location: Some(opened),
+            span: None,
+        }),
+    }
+}
+
+impl Sink for VecDeque<Event> {
+    type Name = String;
+    type Value = CifValue;
+
+    fn block_start(&mut self, name: String) {
+        self.push_back(Event::BlockStart { name });
+    }
+
+    fn block_end(&mut self) {
+        self.push_back(Event::BlockEnd);
+    }
+
+    fn frame_start(&mut self, name: String) {
+        self.push_back(Event::FrameStart { name });
+    }
+
+    fn frame_end(&mut self) {
+        self.push_back(Event::FrameEnd);
+    }
+
+    fn item(&mut self, tag: String, value: CifValue) {
+        self.push_back(Event::Item { tag, value });
+    }
+
+    fn loop_start(&mut self, tags: Vec<String>) {
+        self.push_back(Event::LoopStart { tags });
+    }
+
+    fn loop_row(&mut self, row: Vec<CifValue>) {
+        self.push_back(Event::LoopRow(row));
+    }
+
+    fn loop_end(&mut self) {
+        self.push_back(Event::LoopEnd);
+    }
+}
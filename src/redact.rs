@@ -0,0 +1,685 @@
+//! Removing sensitive content before a file is shared.
+//!
+//! A [`RedactionPolicy`] is a set of tag patterns, taken from built-in
+//! [`Profile`]s or given directly, where `*` matches any run of characters
+//! and `?` any one character, compared case-insensitively. Values of
+//! matching tags are replaced with `?`, or with
+//! [`remove_items`](RedactionPolicy::remove_items) the items and loop
+//! columns are removed. Every change is listed in a [`RedactionReport`].
+//!
+//! There are three ways to apply a policy:
+//!
+//! - [`CifDocument::redact`] changes a parsed document in place;
+//! - [`WriteOptions::redact`](crate::writer::WriteOptions::redact) writes a
+//!   redacted copy and leaves the document alone;
+//! - [`redact_text`] edits CIF text directly, so everything it does not
+//!   redact, comments and layout included, is kept byte for byte.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::redact::{Profile, RedactionPolicy};
+//! use cif_parser::{Document, Value};
+//!
+//! let cif = "\
+//! data_x
+//! _publ_contact_author_email a.person@example.org
+//! _cell_length_a 5.4
+//! loop_
+//! _publ_author_name
+//! _publ_author_address
+//! 'Person, A.' 'Somewhere'
+//! ";
+//! let mut doc = Document::parse(cif).unwrap();
+//! let report = doc.redact(&Profile::PrePublication.into());
+//!
+//! assert_eq!(report.redactions.len(), 3);
+//! let block = &doc.blocks[0];
+//! assert_eq!(block.get_item("_publ_contact_author_email"), Some(&Value::Unknown));
+//! assert_eq!(block.get_item("_cell_length_a"), Some(&Value::Numeric(5.4)));
+//!
+//! let policy = RedactionPolicy::new().tag("_publ_author_*").remove_items(true);
+//! doc.redact(&policy);
+//! assert!(doc.blocks[0].loops.is_empty());
+//! ```
+
+use crate::ast::{CifDocument, CifLoop, CifValue};
+use crate::error::CifError;
+use crate::lexer::{Token, TokenKind, Tokenizer};
+use indexmap::IndexMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A built-in set of tag patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// People and free text that should not leave the lab before
+    /// publication: author and contact names, addresses, emails, and
+    /// phone numbers, and `*_special_details` fields
+    PrePublication,
+    /// Files embedded by refinement programs: SHELX `.res`, `.hkl`, and
+    /// `.fab` files with their checksums, and the older `_iucr_refine_*`
+    /// instruction and reflection fields
+    StripEmbeddedFiles,
+}
+
+impl Profile {
+    /// Every profile
+    pub const ALL: [Profile; 2] = [Profile::PrePublication, Profile::StripEmbeddedFiles];
+
+    /// The name used by the command line and Python, such as
+    /// `pre_publication`
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::PrePublication => "pre_publication",
+            Profile::StripEmbeddedFiles => "strip_embedded_files",
+        }
+    }
+
+    /// The tag patterns of this profile
+    pub fn patterns(self) -> &'static [&'static str] {
+        match self {
+            Profile::PrePublication => &[
+                "_publ_author*",
+                "_publ_contact*",
+                "_audit_author*",
+                "_audit_contact_author*",
+                "_*special_details",
+            ],
+            Profile::StripEmbeddedFiles => &[
+                "_shelx*_file",
+                "_shelx*_checksum",
+                "_iucr_refine_instructions_details",
+                "_iucr_refine_reflections_details",
+                "_iucr_refine_fcf_details",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Profile::ALL
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<_> = Profile::ALL.iter().map(|p| p.name()).collect();
+                format!(
+                    "Unknown redaction profile '{s}'; expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Which tags to redact, and how.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionPolicy {
+    /// Tag patterns; `*` matches any run of characters, `?` any one
+    pub patterns: Vec<String>,
+    /// Remove matching items and loop columns instead of replacing their
+    /// values with `?`
+    pub remove_items: bool,
+}
+
+impl RedactionPolicy {
+    /// A policy that redacts nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the patterns of a built-in profile
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.patterns
+            .extend(profile.patterns().iter().map(|p| p.to_string()));
+        self
+    }
+
+    /// Add a tag pattern, such as `_publ_author_*` or `_exptl_*_details`
+    pub fn tag(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Remove matching items and loop columns rather than replacing their
+    /// values with `?`. A loop left with no columns is removed.
+    pub fn remove_items(mut self, remove: bool) -> Self {
+        self.remove_items = remove;
+        self
+    }
+
+    /// Whether `tag` matches one of the patterns
+    pub fn matches(&self, tag: &str) -> bool {
+        self.patterns.iter().any(|pattern| glob_match(pattern, tag))
+    }
+}
+
+impl From<Profile> for RedactionPolicy {
+    fn from(profile: Profile) -> Self {
+        RedactionPolicy::new().profile(profile)
+    }
+}
+
+/// Whether `text` matches `pattern`, ignoring ASCII case
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let text: Vec<char> = text.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and the text position it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// One tag whose values were redacted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redaction {
+    /// Name of the data block
+    pub block: String,
+    /// Name of the save frame, if the tag is in one
+    pub frame: Option<String>,
+    /// The tag, as written
+    pub tag: String,
+    /// Number of values replaced with `?` or removed; values that were
+    /// already `?` are left alone and not counted
+    pub values: usize,
+    /// Whether the item or loop column was removed
+    pub removed: bool,
+}
+
+impl fmt::Display for Redaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "data_{}", self.block)?;
+        if let Some(frame) = &self.frame {
+            write!(f, " save_{frame}")?;
+        }
+        let action = if self.removed {
+            "removed"
+        } else {
+            "replaced with ?"
+        };
+        let plural = if self.values == 1 { "" } else { "s" };
+        write!(f, " {}: {} value{plural} {action}", self.tag, self.values)
+    }
+}
+
+/// What a [`RedactionPolicy`] changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionReport {
+    /// One entry per redacted item or loop column: by block and save
+    /// frame, items before loops from [`CifDocument::redact`], in file
+    /// order from [`redact_text`]
+    pub redactions: Vec<Redaction>,
+}
+
+impl RedactionReport {
+    /// Whether nothing was changed
+    pub fn is_empty(&self) -> bool {
+        self.redactions.is_empty()
+    }
+
+    /// Total number of values replaced or removed
+    pub fn values(&self) -> usize {
+        self.redactions.iter().map(|r| r.values).sum()
+    }
+}
+
+impl fmt::Display for RedactionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for redaction in &self.redactions {
+            writeln!(f, "{redaction}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the values being redacted are
+struct Container<'a> {
+    block: &'a str,
+    frame: Option<&'a str>,
+}
+
+impl Container<'_> {
+    fn redaction(&self, tag: &str, values: usize, removed: bool) -> Redaction {
+        Redaction {
+            block: self.block.to_string(),
+            frame: self.frame.map(str::to_string),
+            tag: tag.to_string(),
+            values,
+            removed,
+        }
+    }
+}
+
+/// Redact the items and loops of one block or frame
+fn redact_container(
+    items: &mut IndexMap<String, CifValue>,
+    loops: &mut Vec<CifLoop>,
+    container: Container,
+    policy: &RedactionPolicy,
+    report: &mut RedactionReport,
+) {
+    let tags: Vec<String> = items
+        .keys()
+        .filter(|tag| policy.matches(tag))
+        .cloned()
+        .collect();
+    for tag in tags {
+        if policy.remove_items {
+            items.shift_remove(&tag);
+            report.redactions.push(container.redaction(&tag, 1, true));
+        } else if items[&tag] != CifValue::Unknown {
+            items[&tag] = CifValue::Unknown;
+            report.redactions.push(container.redaction(&tag, 1, false));
+        }
+    }
+
+    for loop_ in loops.iter_mut() {
+        let columns: Vec<usize> = (0..loop_.tags.len())
+            .filter(|&i| policy.matches(&loop_.tags[i]))
+            .collect();
+        for &i in &columns {
+            let tag = &loop_.tags[i];
+            if policy.remove_items {
+                report
+                    .redactions
+                    .push(container.redaction(tag, loop_.values.len(), true));
+                continue;
+            }
+            let mut replaced = 0;
+            for row in &mut loop_.values {
+                if row[i] != CifValue::Unknown {
+                    row[i] = CifValue::Unknown;
+                    replaced += 1;
+                }
+            }
+            if replaced > 0 {
+                report
+                    .redactions
+                    .push(container.redaction(tag, replaced, false));
+            }
+        }
+        if policy.remove_items && !columns.is_empty() {
+            let keep = |i: &usize| !columns.contains(i);
+            loop_.tags = (0..loop_.tags.len())
+                .filter(keep)
+                .map(|i| loop_.tags[i].clone())
+                .collect();
+            for row in &mut loop_.values {
+                *row = (0..row.len())
+                    .filter(keep)
+                    .map(|i| row[i].clone())
+                    .collect();
+            }
+        }
+    }
+    loops.retain(|loop_| !loop_.tags.is_empty());
+}
+
+impl CifDocument {
+    /// Apply `policy` to every block and save frame, and report what was
+    /// changed.
+    ///
+    /// See the [module documentation](crate::redact) for how tags are
+    /// matched; [`redact_text`] does the same to CIF text without
+    /// reformatting it.
+    pub fn redact(&mut self, policy: &RedactionPolicy) -> RedactionReport {
+        let mut report = RedactionReport::default();
+        for block in &mut self.blocks {
+            let container = Container {
+                block: &block.name,
+                frame: None,
+            };
+            redact_container(
+                &mut block.items,
+                &mut block.loops,
+                container,
+                policy,
+                &mut report,
+            );
+            for frame in &mut block.frames {
+                let container = Container {
+                    block: &block.name,
+                    frame: Some(&frame.name),
+                };
+                redact_container(
+                    &mut frame.items,
+                    &mut frame.loops,
+                    container,
+                    policy,
+                    &mut report,
+                );
+            }
+        }
+        report
+    }
+}
+
+/// What to do with one token of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Drop,
+    /// Write `?` in its place
+    Unknown,
+}
+
+/// The token indices of the value starting at `start` in `significant`: one
+/// token, or a CIF 2.0 list or table from its opening to its closing
+/// delimiter
+fn value_group(tokens: &[Token], significant: &[usize], start: usize) -> Vec<usize> {
+    let mut group = Vec::new();
+    let mut depth = 0usize;
+    for &index in &significant[start..] {
+        group.push(index);
+        let token = &tokens[index];
+        if token.kind == TokenKind::Delimiter {
+            match token.text {
+                "[" | "{" => depth += 1,
+                "]" | "}" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        if depth == 0 {
+            break;
+        }
+    }
+    group
+}
+
+/// Whether a token is a value, or part of one
+fn is_value(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::QuotedString
+            | TokenKind::TextField
+            | TokenKind::UnquotedValue
+            | TokenKind::Delimiter
+    )
+}
+
+/// Mark `group` to be replaced by `?`; returns whether it changed anything
+fn replace_group(edits: &mut [Edit], tokens: &[Token], group: &[usize]) -> bool {
+    if let [only] = group {
+        let token = &tokens[*only];
+        if token.kind == TokenKind::UnquotedValue && token.text == "?" {
+            return false;
+        }
+    }
+    for (n, &index) in group.iter().enumerate() {
+        edits[index] = if n == 0 { Edit::Unknown } else { Edit::Drop };
+    }
+    true
+}
+
+/// Apply `policy` to CIF text, changing nothing but the redacted values.
+///
+/// Replaced values become `?` in place. Removed items, loop columns, and
+/// loops are cut out along with the spacing before them, and lines left
+/// empty by a removal are dropped; every other line is kept exactly as it
+/// was. The report lists what [`CifDocument::redact`] would change in the
+/// parsed text. Returns an error for text that cannot be tokenized.
+///
+/// # Examples
+///
+/// ```
+/// use cif_parser::redact::{redact_text, Profile};
+///
+/// let cif = "data_x\n# contact\n_publ_contact_author_name  'Person, A.'\n_cell_length_a   5.40\n";
+/// let (text, report) = redact_text(cif, &Profile::PrePublication.into()).unwrap();
+///
+/// assert_eq!(text, "data_x\n# contact\n_publ_contact_author_name  ?\n_cell_length_a   5.40\n");
+/// assert_eq!(report.values(), 1);
+/// ```
+pub fn redact_text(
+    input: &str,
+    policy: &RedactionPolicy,
+) -> Result<(String, RedactionReport), CifError> {
+    let tokens: Vec<Token> = Tokenizer::new(input).collect::<Result<_, _>>()?;
+    let significant: Vec<usize> = (0..tokens.len())
+        .filter(|&i| !tokens[i].kind.is_trivia())
+        .collect();
+    let mut edits = vec![Edit::Keep; tokens.len()];
+    let mut report = RedactionReport::default();
+    let mut block = String::new();
+    let mut frame: Option<String> = None;
+
+    let mut s = 0;
+    while s < significant.len() {
+        let token = &tokens[significant[s]];
+        let container = Container {
+            block: &block,
+            frame: frame.as_deref(),
+        };
+        match token.kind {
+            TokenKind::BlockHeader => {
+                block = header_name(token.text, "data_");
+                frame = None;
+                s += 1;
+            }
+            TokenKind::SaveHeader => {
+                frame = Some(header_name(token.text, "save_"));
+                s += 1;
+            }
+            TokenKind::SaveEnd => {
+                frame = None;
+                s += 1;
+            }
+            TokenKind::Tag => {
+                let tag = significant[s];
+                let group = match significant.get(s + 1) {
+                    Some(&next) if is_value(tokens[next].kind) => {
+                        value_group(&tokens, &significant, s + 1)
+                    }
+                    _ => Vec::new(),
+                };
+                s += 1 + group.len();
+                if !policy.matches(tokens[tag].text) {
+                    continue;
+                }
+                if policy.remove_items {
+                    edits[tag] = Edit::Drop;
+                    group.iter().for_each(|&i| edits[i] = Edit::Drop);
+                    let redaction = container.redaction(tokens[tag].text, 1, true);
+                    report.redactions.push(redaction);
+                } else if replace_group(&mut edits, &tokens, &group) {
+                    let redaction = container.redaction(tokens[tag].text, 1, false);
+                    report.redactions.push(redaction);
+                }
+            }
+            TokenKind::LoopKeyword if token.text.eq_ignore_ascii_case("loop_") => {
+                let keyword = significant[s];
+                s += 1;
+                let mut tags = Vec::new();
+                while let Some(&index) = significant.get(s) {
+                    if tokens[index].kind != TokenKind::Tag {
+                        break;
+                    }
+                    tags.push(index);
+                    s += 1;
+                }
+                let mut groups = Vec::new();
+                while s < significant.len() && is_value(tokens[significant[s]].kind) {
+                    let group = value_group(&tokens, &significant, s);
+                    s += group.len();
+                    groups.push(group);
+                }
+
+                let mut removed = 0;
+                for (column, &tag) in tags.iter().enumerate() {
+                    if !policy.matches(tokens[tag].text) {
+                        continue;
+                    }
+                    let cells = groups.iter().skip(column).step_by(tags.len());
+                    if policy.remove_items {
+                        edits[tag] = Edit::Drop;
+                        let mut values = 0;
+                        for group in cells {
+                            group.iter().for_each(|&i| edits[i] = Edit::Drop);
+                            values += 1;
+                        }
+                        removed += 1;
+                        let redaction = container.redaction(tokens[tag].text, values, true);
+                        report.redactions.push(redaction);
+                    } else {
+                        let replaced = cells
+                            .filter(|group| replace_group(&mut edits, &tokens, group))
+                            .count();
+                        if replaced > 0 {
+                            let redaction = container.redaction(tokens[tag].text, replaced, false);
+                            report.redactions.push(redaction);
+                        }
+                    }
+                }
+                if removed > 0 && removed == tags.len() {
+                    edits[keyword] = Edit::Drop;
+                }
+            }
+            _ => s += 1,
+        }
+    }
+    Ok((apply_edits(&tokens, &edits), report))
+}
+
+/// The name in a `data_` or `save_` heading
+fn header_name(text: &str, keyword: &str) -> String {
+    text.get(keyword.len()..).unwrap_or_default().to_string()
+}
+
+/// Output built line by line, so that lines emptied by a removal can be
+/// left out
+#[derive(Default)]
+struct Lines {
+    out: String,
+    line: String,
+    /// Whether something was removed from `line`
+    edited: bool,
+    /// Whether the spacing after a removal at the start of `line` is skipped
+    skip_space: bool,
+    /// Whether the last line written was blank, and whether lines have been
+    /// left out since
+    last_blank: bool,
+    left_out: bool,
+}
+
+impl Lines {
+    /// End the current line, with a line terminator unless it is the last
+    fn end_line(&mut self, terminated: bool) {
+        let blank = self.line.trim().is_empty();
+        if self.edited && blank {
+            self.left_out = true;
+        } else if blank && self.last_blank && self.left_out {
+            // Removing the content between two blank lines leaves one
+        } else {
+            self.out.push_str(&self.line);
+            if terminated {
+                self.out.push('\n');
+            }
+            self.last_blank = blank;
+            self.left_out = false;
+        }
+        self.line.clear();
+        self.edited = false;
+        self.skip_space = false;
+    }
+}
+
+/// The input with `edits` applied, leaving out lines that only a removal
+/// made blank
+fn apply_edits(tokens: &[Token], edits: &[Edit]) -> String {
+    let mut lines = Lines::default();
+    for (token, &edit) in tokens.iter().zip(edits) {
+        match edit {
+            Edit::Drop => {
+                let kept = lines.line.trim_end_matches([' ', '\t']).len();
+                lines.line.truncate(kept);
+                lines.edited = true;
+                lines.skip_space = lines.line.is_empty();
+            }
+            Edit::Unknown => {
+                lines.line.push('?');
+                lines.skip_space = false;
+            }
+            Edit::Keep if token.kind == TokenKind::Whitespace => {
+                for piece in token.text.split_inclusive('\n') {
+                    let (content, terminated) = match piece.strip_suffix('\n') {
+                        Some(content) => (content, true),
+                        None => (piece, false),
+                    };
+                    if lines.skip_space {
+                        lines.line.push_str(content.trim_start_matches([' ', '\t']));
+                    } else {
+                        lines.line.push_str(content);
+                    }
+                    if terminated {
+                        lines.end_line(true);
+                    }
+                }
+            }
+            Edit::Keep => {
+                lines.line.push_str(token.text);
+                lines.skip_space = false;
+            }
+        }
+    }
+    if !lines.line.is_empty() {
+        lines.end_line(false);
+    }
+    lines.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("_publ_author*", "_PUBL_AUTHOR_NAME"));
+        assert!(glob_match("_publ_author*", "_publ_author.name"));
+        assert!(glob_match("_*special_details", "_refine_special_details"));
+        assert!(glob_match("_shelx*_file", "_shelx_res_file"));
+        assert!(glob_match("_cell_length_?", "_cell_length_b"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("_cell_length_?", "_cell_length_ab"));
+        assert!(!glob_match(
+            "_*special_details",
+            "_refine_special_details_x"
+        ));
+        assert!(!glob_match("_shelx*_file", "_shelx_res_checksum"));
+    }
+
+    #[test]
+    fn test_profile_names() {
+        for profile in Profile::ALL {
+            assert_eq!(profile.name().parse::<Profile>(), Ok(profile));
+        }
+        assert!("pre-publication".parse::<Profile>().is_err());
+    }
+}
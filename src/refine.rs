@@ -0,0 +1,380 @@
+//! The refinement model: parameters, restraints, weights, and hydrogens.
+//!
+//! [`CifBlock::refinement_model`] gathers what a structure report says about
+//! how the model was refined: the numbers of parameters, restraints, and
+//! constraints, the weighting scheme, extinction, the Flack parameter, and
+//! the treatment of hydrogen atoms. The coefficients of the SHELXL weighting
+//! scheme `w = 1/[σ²(Fo²) + (aP)² + bP]` are read out of
+//! `_refine_ls_weighting_details` after its CIF markup is converted, and
+//! restraints listed in `_restr_*` loops are collected too.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::refine::HydrogenTreatment;
+//! use cif_parser::Document;
+//!
+//! let cif = r"
+//! data_shelx
+//! _refine_ls_number_parameters 235
+//! _refine_ls_number_restraints 1
+//! _refine_ls_weighting_scheme calc
+//! _refine_ls_weighting_details
+//!  'w=1/[\s^2^(Fo^2^)+(0.0478P)^2^+0.2987P] where P=(Fo^2^+2Fc^2^)/3'
+//! _refine_ls_hydrogen_treatment constr
+//! _refine_ls_abs_structure_Flack 0.02(4)
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let model = doc.first_block().unwrap().refinement_model();
+//!
+//! assert_eq!(model.parameters, Some(235));
+//! let weights = model.weighting.unwrap();
+//! assert_eq!((weights.a, weights.b), (0.0478, 0.2987));
+//! assert_eq!(model.hydrogen_treatment, Some(HydrogenTreatment::Constrained));
+//! assert_eq!(model.flack, Some((0.02, Some(0.04))));
+//! ```
+
+use crate::ast::{CifBlock, CifValue};
+use crate::publication::value_text;
+use crate::text::from_cif_markup;
+use std::fmt;
+
+/// How hydrogen atoms were refined (`_refine_ls_hydrogen_treatment`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HydrogenTreatment {
+    /// `refall`: coordinates and displacement parameters refined
+    RefinedAll,
+    /// `refxyz`: coordinates refined only
+    RefinedCoordinates,
+    /// `refU`: displacement parameters refined only
+    RefinedDisplacement,
+    /// `noref`: not refined
+    NotRefined,
+    /// `constr`: constrained, typically riding on their parent atoms
+    Constrained,
+    /// `mixed`: some refined, some constrained
+    Mixed,
+    /// `undef`: not defined by the report
+    Undefined,
+    /// Any other code, as written
+    Other(String),
+}
+
+impl HydrogenTreatment {
+    /// Classify a code as written in CIF; case is ignored
+    pub fn from_code(code: &str) -> Self {
+        match code.to_ascii_lowercase().as_str() {
+            "refall" => HydrogenTreatment::RefinedAll,
+            "refxyz" => HydrogenTreatment::RefinedCoordinates,
+            "refu" => HydrogenTreatment::RefinedDisplacement,
+            "noref" => HydrogenTreatment::NotRefined,
+            "constr" => HydrogenTreatment::Constrained,
+            "mixed" => HydrogenTreatment::Mixed,
+            "undef" => HydrogenTreatment::Undefined,
+            _ => HydrogenTreatment::Other(code.to_string()),
+        }
+    }
+
+    /// The code as the core dictionary writes it
+    pub fn code(&self) -> &str {
+        match self {
+            HydrogenTreatment::RefinedAll => "refall",
+            HydrogenTreatment::RefinedCoordinates => "refxyz",
+            HydrogenTreatment::RefinedDisplacement => "refU",
+            HydrogenTreatment::NotRefined => "noref",
+            HydrogenTreatment::Constrained => "constr",
+            HydrogenTreatment::Mixed => "mixed",
+            HydrogenTreatment::Undefined => "undef",
+            HydrogenTreatment::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for HydrogenTreatment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Coefficients of the SHELXL weighting scheme
+/// `w = 1/[σ²(Fo²) + (aP)² + bP]`, with `P = (Fo² + 2Fc²)/3`.
+///
+/// A term missing from the written scheme has a coefficient of 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weighting {
+    /// Coefficient of the `(aP)²` term
+    pub a: f64,
+    /// Coefficient of the `bP` term
+    pub b: f64,
+}
+
+impl Weighting {
+    /// Read the coefficients from a weighting scheme as written in
+    /// `_refine_ls_weighting_details`.
+    ///
+    /// CIF markup such as `\s^2^` is converted first and spaces are ignored.
+    /// Returns `None` for anything but the `w = 1/[σ²(Fo²) + ...]` form, or
+    /// if it has a term other than `(aP)²` and `bP`.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::refine::Weighting;
+    ///
+    /// let w = Weighting::parse("w=1/[\\s^2^(Fo^2^)+(0.0322P)^2^]").unwrap();
+    /// assert_eq!((w.a, w.b), (0.0322, 0.0));
+    /// assert_eq!(Weighting::parse("w=1/(\\s^2^(F)+0.0001F^2^)"), None);
+    /// ```
+    pub fn parse(details: &str) -> Option<Self> {
+        let text: String = from_cif_markup(details)
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let rest = &text[text.find("w=1/")? + 4..];
+        let close = match rest.chars().next()? {
+            '[' => ']',
+            '(' => ')',
+            _ => return None,
+        };
+        let body = &rest[1..rest.find(close)?];
+        let mut terms = split_terms(body).into_iter();
+        if !matches!(terms.next()?, "σ²(Fo²)" | "σ²(F²)") {
+            return None;
+        }
+        let mut weighting = Weighting { a: 0.0, b: 0.0 };
+        for term in terms {
+            if let Some(a) = term.strip_prefix('(').and_then(|t| t.strip_suffix("P)²")) {
+                weighting.a = a.parse().ok()?;
+            } else if let Some(b) = term.strip_suffix('P') {
+                weighting.b = b.parse().ok()?;
+            } else {
+                return None;
+            }
+        }
+        Some(weighting)
+    }
+}
+
+/// Split `text` at the `+` signs outside parentheses
+fn split_terms(text: &str) -> Vec<&str> {
+    let mut terms = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            '+' if depth == 0 => {
+                terms.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push(&text[start..]);
+    terms
+}
+
+/// One restraint from a `_restr_*` loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restraint {
+    /// What is restrained: `distance`, `angle`, or `torsion`
+    pub kind: &'static str,
+    /// Labels of the atoms involved, in order
+    pub atoms: Vec<String>,
+    /// Target value, with the su it is restrained to if given
+    pub target: Option<(f64, Option<f64>)>,
+    /// Difference between the target and the refined value
+    pub difference: Option<f64>,
+}
+
+/// The refinement model gathered from a data block.
+///
+/// Numbers come with their su, if written with one. Absent values and
+/// `?`/`.` are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefinementModel {
+    /// `_refine_ls_number_parameters`
+    pub parameters: Option<u64>,
+    /// `_refine_ls_number_restraints`
+    pub restraints: Option<u64>,
+    /// `_refine_ls_number_constraints`
+    pub constraints: Option<u64>,
+    /// `_refine_ls_weighting_scheme`, such as `calc` or `sigma`
+    pub weighting_scheme: Option<String>,
+    /// `_refine_ls_weighting_details`, as written
+    pub weighting_details: Option<String>,
+    /// Coefficients of a SHELXL weighting scheme in the details
+    pub weighting: Option<Weighting>,
+    /// `_refine_ls_extinction_method`
+    pub extinction_method: Option<String>,
+    /// `_refine_ls_extinction_coef`
+    pub extinction_coef: Option<(f64, Option<f64>)>,
+    /// `_refine_ls_abs_structure_Flack`
+    pub flack: Option<(f64, Option<f64>)>,
+    /// `_refine_ls_hydrogen_treatment`
+    pub hydrogen_treatment: Option<HydrogenTreatment>,
+    /// `_refine_special_details`, the free-text notes on the refinement
+    pub special_details: Option<String>,
+    /// Rows of the `_restr_distance`, `_restr_angle`, and `_restr_torsion`
+    /// loops
+    pub restraint_list: Vec<Restraint>,
+}
+
+/// Spellings of each item: core, mmCIF, then DDLm where they exist
+const PARAMETERS: &[&str] = &[
+    "_refine_ls_number_parameters",
+    "_refine.ls_number_parameters",
+    "_refine_ls.number_parameters",
+];
+const RESTRAINTS: &[&str] = &[
+    "_refine_ls_number_restraints",
+    "_refine.ls_number_restraints",
+    "_refine_ls.number_restraints",
+];
+const CONSTRAINTS: &[&str] = &[
+    "_refine_ls_number_constraints",
+    "_refine.ls_number_constraints",
+    "_refine_ls.number_constraints",
+];
+const WEIGHTING_SCHEME: &[&str] = &[
+    "_refine_ls_weighting_scheme",
+    "_refine.ls_weighting_scheme",
+    "_refine_ls.weighting_scheme",
+];
+const WEIGHTING_DETAILS: &[&str] = &[
+    "_refine_ls_weighting_details",
+    "_refine.ls_weighting_details",
+    "_refine_ls.weighting_details",
+];
+const EXTINCTION_METHOD: &[&str] = &[
+    "_refine_ls_extinction_method",
+    "_refine.ls_extinction_method",
+    "_refine_ls.extinction_method",
+];
+const EXTINCTION_COEF: &[&str] = &[
+    "_refine_ls_extinction_coef",
+    "_refine.ls_extinction_coef",
+    "_refine_ls.extinction_coef",
+];
+const FLACK: &[&str] = &[
+    "_refine_ls_abs_structure_Flack",
+    "_refine.ls_abs_structure_Flack",
+    "_refine_ls.abs_structure_Flack",
+];
+const HYDROGENS: &[&str] = &[
+    "_refine_ls_hydrogen_treatment",
+    "_refine.ls_hydrogen_treatment",
+    "_refine_ls.hydrogen_treatment",
+];
+pub(crate) const SPECIAL_DETAILS: &[&str] = &["_refine_special_details", "_refine.details"];
+
+/// Restraint loops and the number of atoms each restraint names
+const RESTRAINT_KINDS: &[(&str, usize)] = &[("distance", 2), ("angle", 3), ("torsion", 4)];
+
+impl CifBlock {
+    /// Gather the refinement model from this block.
+    ///
+    /// Core names such as `_refine_ls_number_parameters` are read, as are
+    /// their mmCIF and DDLm spellings. See the
+    /// [module documentation](crate::refine) for an example.
+    pub fn refinement_model(&self) -> RefinementModel {
+        let text = |tags: &[&str]| {
+            tags.iter()
+                .find_map(|tag| self.get_item(tag).and_then(value_text))
+        };
+        let count = |tags: &[&str]| {
+            let (value, _) = self.number_with_su(tags)?;
+            (value >= 0.0 && value.fract() == 0.0).then_some(value as u64)
+        };
+        let weighting_details = text(WEIGHTING_DETAILS);
+
+        RefinementModel {
+            parameters: count(PARAMETERS),
+            restraints: count(RESTRAINTS),
+            constraints: count(CONSTRAINTS),
+            weighting_scheme: text(WEIGHTING_SCHEME),
+            weighting: weighting_details.as_deref().and_then(Weighting::parse),
+            weighting_details,
+            extinction_method: text(EXTINCTION_METHOD),
+            extinction_coef: self.number_with_su(EXTINCTION_COEF),
+            flack: self.number_with_su(FLACK),
+            hydrogen_treatment: text(HYDROGENS).map(|code| HydrogenTreatment::from_code(&code)),
+            special_details: text(SPECIAL_DETAILS),
+            restraint_list: self.restraints(),
+        }
+    }
+
+    /// The rows of the restraint loops, distances first
+    fn restraints(&self) -> Vec<Restraint> {
+        let mut restraints = Vec::new();
+        for &(kind, n_atoms) in RESTRAINT_KINDS {
+            let prefix = format!("_restr_{kind}_");
+            let Some(loop_) = self.find_loop(&format!("{prefix}atom_site_label_1")) else {
+                continue;
+            };
+            let column = |name: &str| loop_.get_column(&format!("{prefix}{name}"));
+            let labels: Vec<Vec<&CifValue>> = (1..=n_atoms)
+                .filter_map(|i| column(&format!("atom_site_label_{i}")))
+                .collect();
+            let targets = column("target");
+            let weights = column("target_weight_param");
+            let differences = column("diff");
+            for row in 0..loop_.len() {
+                restraints.push(Restraint {
+                    kind,
+                    atoms: labels
+                        .iter()
+                        .filter_map(|labels| value_text(labels[row]))
+                        .collect(),
+                    target: targets.as_ref().and_then(|t| {
+                        let (target, su) = t[row].as_numeric_with_su()?;
+                        let weight = weights.as_ref().and_then(|w| w[row].as_numeric());
+                        Some((target, su.or(weight)))
+                    }),
+                    difference: differences.as_ref().and_then(|d| d[row].as_numeric()),
+                });
+            }
+        }
+        restraints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighting_forms() {
+        let shelx = "w=1/[\\s^2^(Fo^2^)+(0.0478P)^2^+0.2987P] where P=(Fo^2^+2Fc^2^)/3";
+        assert_eq!(
+            Weighting::parse(shelx),
+            Some(Weighting {
+                a: 0.0478,
+                b: 0.2987
+            })
+        );
+        assert_eq!(
+            Weighting::parse("calc w = 1/[\\s^2^(Fo^2^) + (0.1P)^2^ + 12.5P]"),
+            Some(Weighting { a: 0.1, b: 12.5 })
+        );
+        assert_eq!(
+            Weighting::parse("w=1/[\\s^2^(Fo^2^)]"),
+            Some(Weighting { a: 0.0, b: 0.0 })
+        );
+        assert_eq!(Weighting::parse("w=1/[\\s^2^(Fo^2^)+0.01Fo^2^]"), None);
+        assert_eq!(Weighting::parse("unit weights"), None);
+    }
+
+    #[test]
+    fn test_hydrogen_codes() {
+        assert_eq!(
+            HydrogenTreatment::from_code("refU"),
+            HydrogenTreatment::RefinedDisplacement
+        );
+        assert_eq!(HydrogenTreatment::from_code("REFU").code(), "refU");
+        assert_eq!(
+            HydrogenTreatment::from_code("riding"),
+            HydrogenTreatment::Other("riding".to_string())
+        );
+    }
+}
@@ -0,0 +1,532 @@
+//! Reflection lists and the merging of symmetry-equivalent reflections.
+//!
+//! [`CifBlock::reflections`] reads the measured intensities of a block:
+//! the unmerged `_diffrn_refln_*` loop if there is one, otherwise the
+//! `_refln_*` loop, as written by integration programs such as PETS.
+//! [`ReflectionData::merge_equivalents`] then maps each index to a
+//! representative of its symmetry-equivalent set, averages the intensities
+//! of each set with inverse-variance weights, and reports the usual
+//! merging statistics (R_merge, R_meas, R_pim, mean I/σ, multiplicity)
+//! overall and per resolution shell.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::reflections::{MergeOptions, ShellBinning};
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_p2
+//! _cell_length_a 5
+//! _cell_length_b 6
+//! _cell_length_c 7
+//! _cell_angle_alpha 90
+//! _cell_angle_beta 100
+//! _cell_angle_gamma 90
+//! loop_
+//! _space_group_symop_operation_xyz
+//! x,y,z
+//! -x,y,-z
+//! loop_
+//! _refln_index_h
+//! _refln_index_k
+//! _refln_index_l
+//! _refln_intensity_meas
+//! _refln_intensity_sigma
+//! 1 2 3 110 8
+//! -1 2 -3 90 8
+//! 1 -2 3 100 8
+//! 2 0 0 50 4
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let block = doc.first_block().unwrap();
+//! let group = block.point_group().unwrap();
+//!
+//! let merged = block
+//!     .reflections()
+//!     .unwrap()
+//!     .merge_equivalents(&group, &MergeOptions::new().shells(ShellBinning::Count(1)));
+//! assert_eq!(merged.reflections.len(), 2);
+//! assert_eq!(merged.reflections[0].hkl, [1, 2, 3]);
+//! assert_eq!(merged.reflections[0].intensity, 100.0);
+//! assert_eq!(merged.reflections[0].multiplicity, 3);
+//! // Σ|I − Ī| / ΣI over the 1 2 3 set
+//! assert_eq!(merged.overall.r_merge, Some(20.0 / 300.0));
+//! assert_eq!(merged.overall.multiplicity, 2.0);
+//! ```
+
+use crate::ast::{CifBlock, CifLoop, CifValue};
+use crate::cell::UnitCell;
+use crate::symmetry::PointGroupInfo;
+use std::collections::HashMap;
+
+/// One measured reflection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reflection {
+    /// Miller indices
+    pub hkl: [i32; 3],
+    /// Measured intensity
+    pub intensity: f64,
+    /// Su of the intensity, if given
+    pub sigma: Option<f64>,
+}
+
+/// The measured reflections of a block, with the cell they are indexed on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReflectionData {
+    /// The reflections in file order
+    pub reflections: Vec<Reflection>,
+    /// The unit cell, for resolution; shells need it
+    pub cell: Option<UnitCell>,
+    /// Loop the reflections came from: `diffrn_refln` or `refln`
+    pub category: &'static str,
+}
+
+/// How merged reflections are divided into resolution shells.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellBinning {
+    /// This many shells holding equal numbers of unique reflections
+    Count(usize),
+    /// Shells between these d-spacings in Å: `[2.0, 1.0]` gives the shells
+    /// d > 2, 2 ≥ d > 1, and d ≤ 1
+    DSpacing(Vec<f64>),
+}
+
+/// Options for [`ReflectionData::merge_equivalents`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeOptions {
+    /// Keep Friedel mates (hkl and −h−k−l) apart, unless the point group
+    /// itself relates them
+    pub anomalous: bool,
+    /// Resolution shells for the per-shell statistics
+    pub shells: ShellBinning,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        MergeOptions {
+            anomalous: false,
+            shells: ShellBinning::Count(10),
+        }
+    }
+}
+
+impl MergeOptions {
+    /// Friedel mates merged, ten shells of equal count
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep Friedel mates apart
+    pub fn anomalous(mut self, anomalous: bool) -> Self {
+        self.anomalous = anomalous;
+        self
+    }
+
+    /// Set the resolution shells
+    pub fn shells(mut self, shells: ShellBinning) -> Self {
+        self.shells = shells;
+        self
+    }
+}
+
+/// One symmetry-unique reflection after merging.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergedReflection {
+    /// The representative indices: the largest of the equivalent set,
+    /// comparing h, then k, then l
+    pub hkl: [i32; 3],
+    /// Weighted mean intensity
+    pub intensity: f64,
+    /// Su of the mean, `1/√Σw`; `None` unless every observation has a
+    /// positive su
+    pub sigma: Option<f64>,
+    /// Number of observations merged
+    pub multiplicity: usize,
+    /// Resolution in Å, if the cell is known
+    pub d_spacing: Option<f64>,
+}
+
+/// Merging statistics over all reflections or one resolution shell.
+///
+/// The R factors sum `|I − Ī|` over the observations of every unique
+/// reflection measured more than once, divided by the sum of those
+/// observations' intensities; R_meas weights each reflection's sum by
+/// `√(n/(n−1))` and R_pim by `√(1/(n−1))`. They are `None` when no
+/// reflection was measured twice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergeStatistics {
+    /// Lowest resolution (largest d) in Å, if the cell is known
+    pub d_max: Option<f64>,
+    /// Highest resolution (smallest d) in Å, if the cell is known
+    pub d_min: Option<f64>,
+    /// Number of observations
+    pub observations: usize,
+    /// Number of unique reflections
+    pub unique: usize,
+    /// Observations per unique reflection
+    pub multiplicity: f64,
+    /// Σ|I − Ī| / ΣI
+    pub r_merge: Option<f64>,
+    /// Multiplicity-independent R factor
+    pub r_meas: Option<f64>,
+    /// Precision-indicating R factor
+    pub r_pim: Option<f64>,
+    /// Mean of Ī/σ(Ī) over the unique reflections with an su
+    pub mean_i_over_sigma: Option<f64>,
+}
+
+/// The result of [`ReflectionData::merge_equivalents`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedReflections {
+    /// Unique reflections, sorted by indices
+    pub reflections: Vec<MergedReflection>,
+    /// Statistics over all reflections
+    pub overall: MergeStatistics,
+    /// Statistics per shell from low to high resolution; empty without a
+    /// cell, and empty shells are left out
+    pub shells: Vec<MergeStatistics>,
+}
+
+/// A merged reflection with the sums its R factors need
+struct Group {
+    merged: MergedReflection,
+    /// Σ|I − Ī| over the observations
+    deviation: f64,
+    /// ΣI over the observations
+    total: f64,
+}
+
+impl ReflectionData {
+    /// The representative of `hkl` under `point_group`: the largest of its
+    /// equivalents, with Friedel mates included unless `anomalous`
+    pub fn representative(
+        point_group: &PointGroupInfo,
+        hkl: [i32; 3],
+        anomalous: bool,
+    ) -> [i32; 3] {
+        let mut best = hkl;
+        for rotation in &point_group.rotations {
+            let image = [0, 1, 2].map(|j| (0..3).map(|i| hkl[i] * rotation[i][j]).sum::<i32>());
+            best = best.max(image);
+            if !anomalous {
+                best = best.max(image.map(|h| -h));
+            }
+        }
+        best
+    }
+
+    /// Merge symmetry-equivalent reflections and compute merging statistics.
+    ///
+    /// Observations of each unique reflection are averaged with weights
+    /// `1/σ²` when all of them have a positive su, and equally otherwise.
+    pub fn merge_equivalents(
+        &self,
+        point_group: &PointGroupInfo,
+        options: &MergeOptions,
+    ) -> MergedReflections {
+        let mut sets: HashMap<[i32; 3], Vec<&Reflection>> = HashMap::new();
+        for reflection in &self.reflections {
+            let key = Self::representative(point_group, reflection.hkl, options.anomalous);
+            sets.entry(key).or_default().push(reflection);
+        }
+        let mut groups: Vec<Group> = sets
+            .into_iter()
+            .map(|(hkl, observations)| {
+                let d_spacing = self.cell.map(|cell| cell.d_spacing(hkl));
+                merge_set(hkl, &observations, d_spacing)
+            })
+            .collect();
+        groups.sort_by_key(|group| group.merged.hkl);
+
+        let all: Vec<&Group> = groups.iter().collect();
+        let overall = statistics(&all);
+        let shells = if self.cell.is_some() {
+            shells(&groups, &options.shells)
+                .iter()
+                .map(|shell| statistics(shell))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        MergedReflections {
+            reflections: groups.into_iter().map(|group| group.merged).collect(),
+            overall,
+            shells,
+        }
+    }
+}
+
+/// Average one set of equivalent observations
+fn merge_set(hkl: [i32; 3], observations: &[&Reflection], d_spacing: Option<f64>) -> Group {
+    let weighted = observations
+        .iter()
+        .all(|o| o.sigma.is_some_and(|sigma| sigma > 0.0));
+    let weight = |o: &Reflection| match o.sigma {
+        Some(sigma) if weighted => 1.0 / (sigma * sigma),
+        _ => 1.0,
+    };
+    let weights: f64 = observations.iter().map(|o| weight(o)).sum();
+    let intensity = observations
+        .iter()
+        .map(|o| weight(o) * o.intensity)
+        .sum::<f64>()
+        / weights;
+    Group {
+        merged: MergedReflection {
+            hkl,
+            intensity,
+            sigma: weighted.then(|| 1.0 / weights.sqrt()),
+            multiplicity: observations.len(),
+            d_spacing,
+        },
+        deviation: observations
+            .iter()
+            .map(|o| (o.intensity - intensity).abs())
+            .sum(),
+        total: observations.iter().map(|o| o.intensity).sum(),
+    }
+}
+
+/// Divide the groups into resolution shells, low resolution first
+fn shells<'a>(groups: &'a [Group], binning: &ShellBinning) -> Vec<Vec<&'a Group>> {
+    let d = |group: &Group| group.merged.d_spacing.unwrap_or(f64::NAN);
+    let mut sorted: Vec<&Group> = groups.iter().collect();
+    sorted.sort_by(|a, b| d(b).total_cmp(&d(a)));
+    let mut shells: Vec<Vec<&Group>> = match binning {
+        ShellBinning::Count(count) => {
+            let count = (*count).min(sorted.len());
+            (0..count)
+                .map(|i| sorted[i * sorted.len() / count..(i + 1) * sorted.len() / count].to_vec())
+                .collect()
+        }
+        ShellBinning::DSpacing(limits) => {
+            let mut limits = limits.clone();
+            limits.sort_by(|a, b| b.total_cmp(a));
+            let mut shells = vec![Vec::new(); limits.len() + 1];
+            for group in sorted {
+                let shell = limits
+                    .iter()
+                    .take_while(|&&limit| d(group) <= limit)
+                    .count();
+                shells[shell].push(group);
+            }
+            shells
+        }
+    };
+    shells.retain(|shell| !shell.is_empty());
+    shells
+}
+
+/// Merging statistics over some merged reflections
+fn statistics(groups: &[&Group]) -> MergeStatistics {
+    let observations: usize = groups.iter().map(|g| g.merged.multiplicity).sum();
+    let (mut merge, mut meas, mut pim, mut total) = (0.0, 0.0, 0.0, 0.0);
+    for group in groups.iter().filter(|g| g.merged.multiplicity > 1) {
+        let n = group.merged.multiplicity as f64;
+        merge += group.deviation;
+        meas += (n / (n - 1.0)).sqrt() * group.deviation;
+        pim += (1.0 / (n - 1.0)).sqrt() * group.deviation;
+        total += group.total;
+    }
+    let repeated = groups.iter().any(|g| g.merged.multiplicity > 1);
+    let ratio = |sum: f64| (repeated && total != 0.0).then(|| sum / total);
+
+    let i_over_sigma: Vec<f64> = groups
+        .iter()
+        .filter_map(|g| Some(g.merged.intensity / g.merged.sigma?))
+        .collect();
+    let d_spacings = groups.iter().filter_map(|g| g.merged.d_spacing);
+    MergeStatistics {
+        d_max: d_spacings.clone().reduce(f64::max),
+        d_min: d_spacings.reduce(f64::min),
+        observations,
+        unique: groups.len(),
+        multiplicity: if groups.is_empty() {
+            0.0
+        } else {
+            observations as f64 / groups.len() as f64
+        },
+        r_merge: ratio(merge),
+        r_meas: ratio(meas),
+        r_pim: ratio(pim),
+        mean_i_over_sigma: (!i_over_sigma.is_empty())
+            .then(|| i_over_sigma.iter().sum::<f64>() / i_over_sigma.len() as f64),
+    }
+}
+
+/// An intensity column with the su columns that may go with it
+type Columns = (&'static str, &'static [&'static str]);
+
+/// Reflection loops in order of preference, with their intensity and su
+/// columns
+const SOURCES: &[(&str, &[Columns])] = &[
+    (
+        "diffrn_refln",
+        &[("intensity_net", &["intensity_u", "intensity_sigma"])],
+    ),
+    (
+        "refln",
+        &[
+            ("intensity_meas", &["intensity_sigma"]),
+            ("F_squared_meas", &["F_squared_sigma"]),
+        ],
+    ),
+];
+
+impl CifBlock {
+    /// The measured reflections of this block.
+    ///
+    /// Reads the unmerged `_diffrn_refln_*` loop (`intensity_net` with
+    /// `intensity_u` or `intensity_sigma`) if present, otherwise `_refln_*`
+    /// (`intensity_meas` with `intensity_sigma`, or `F_squared_meas` with
+    /// `F_squared_sigma`), in either core or mmCIF spelling. Rows whose
+    /// indices are not integers or whose intensity is not a number are
+    /// skipped, as is `0 0 0`. Returns `None` if there is no such loop.
+    pub fn reflections(&self) -> Option<ReflectionData> {
+        for &(category, columns) in SOURCES {
+            for separator in ['_', '.'] {
+                let tag = |name: &str| format!("_{category}{separator}{name}");
+                let Some(loop_) = self.find_loop(&tag("index_h")) else {
+                    continue;
+                };
+                for &(intensity, sigmas) in columns {
+                    let Some(intensities) = loop_.get_column(&tag(intensity)) else {
+                        continue;
+                    };
+                    let sigmas = sigmas
+                        .iter()
+                        .find_map(|sigma| loop_.get_column(&tag(sigma)));
+                    return Some(ReflectionData {
+                        reflections: read_rows(loop_, &tag, &intensities, sigmas.as_deref()),
+                        cell: self.unit_cell(),
+                        category,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+fn read_rows(
+    loop_: &CifLoop,
+    tag: &dyn Fn(&str) -> String,
+    intensities: &[&CifValue],
+    sigmas: Option<&[&CifValue]>,
+) -> Vec<Reflection> {
+    let indices: Vec<Vec<&CifValue>> = ["index_h", "index_k", "index_l"]
+        .iter()
+        .filter_map(|name| loop_.get_column(&tag(name)))
+        .collect();
+    if indices.len() < 3 {
+        return Vec::new();
+    }
+    let index = |value: &CifValue| {
+        let n = value.as_numeric()?;
+        (n.fract() == 0.0 && n.abs() <= f64::from(i32::MAX)).then_some(n as i32)
+    };
+    (0..loop_.len())
+        .filter_map(|row| {
+            let hkl = [
+                index(indices[0][row])?,
+                index(indices[1][row])?,
+                index(indices[2][row])?,
+            ];
+            if hkl == [0, 0, 0] {
+                return None;
+            }
+            Some(Reflection {
+                hkl,
+                intensity: intensities[row].as_numeric()?,
+                sigma: sigmas.and_then(|s| s[row].as_numeric()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetry::{point_group, SymOp};
+
+    fn group(ops: &[&str]) -> PointGroupInfo {
+        let ops: Vec<SymOp> = ops.iter().map(|op| SymOp::parse(op).unwrap()).collect();
+        point_group(&ops).unwrap()
+    }
+
+    #[test]
+    fn test_representatives() {
+        // 2/m with b unique
+        let p2m = group(&["x,y,z", "-x,y,-z", "-x,-y,-z", "x,-y,z"]);
+        for hkl in [[1, 2, 3], [-1, 2, -3], [-1, -2, -3], [1, -2, 3]] {
+            assert_eq!(ReflectionData::representative(&p2m, hkl, false), [1, 2, 3]);
+        }
+        assert_eq!(
+            ReflectionData::representative(&p2m, [1, 2, -3], false),
+            [1, 2, -3]
+        );
+
+        // In 2, Friedel mates are only merged without anomalous
+        let p2 = group(&["x,y,z", "-x,y,-z"]);
+        assert_eq!(
+            ReflectionData::representative(&p2, [1, -2, 3], false),
+            [1, 2, 3]
+        );
+        assert_eq!(
+            ReflectionData::representative(&p2, [1, -2, 3], true),
+            [1, -2, 3]
+        );
+
+        // Rotations act on indices from the right: in 3 with hexagonal
+        // axes, x,y,z -> -y,x-y,z takes h k l to k −h−k l
+        let p3 = group(&["x,y,z", "-y,x-y,z", "-x+y,-x,z"]);
+        let set: Vec<[i32; 3]> = [[1, 0, 0], [0, -1, 0], [-1, 1, 0]]
+            .iter()
+            .map(|&hkl| ReflectionData::representative(&p3, hkl, true))
+            .collect();
+        assert_eq!(set, vec![[1, 0, 0]; 3]);
+        assert_eq!(
+            ReflectionData::representative(&p3, [0, 1, 0], true),
+            [1, -1, 0]
+        );
+    }
+
+    #[test]
+    fn test_r_factors() {
+        let data = ReflectionData {
+            reflections: [
+                (1, 90.0),
+                (1, 110.0),
+                (2, 50.0),
+                (2, 50.0),
+                (2, 80.0),
+                (3, 7.0),
+            ]
+            .iter()
+            .map(|&(h, intensity)| Reflection {
+                hkl: [h, 0, 0],
+                intensity,
+                sigma: None,
+            })
+            .collect(),
+            cell: None,
+            category: "refln",
+        };
+        let merged = data.merge_equivalents(&group(&["x,y,z"]), &MergeOptions::new());
+        let stats = merged.overall;
+        assert_eq!((stats.observations, stats.unique), (6, 3));
+        assert_eq!(merged.reflections[1].intensity, 60.0);
+        assert!(merged.shells.is_empty());
+
+        // Deviations 20 (n = 2) and 40 (n = 3) over ΣI = 380
+        let close = |x: Option<f64>, y: f64| assert!((x.unwrap() - y).abs() < 1e-12);
+        close(stats.r_merge, 60.0 / 380.0);
+        close(
+            stats.r_meas,
+            (20.0 * 2f64.sqrt() + 40.0 * 1.5f64.sqrt()) / 380.0,
+        );
+        close(stats.r_pim, (20.0 + 40.0 * 0.5f64.sqrt()) / 380.0);
+        assert_eq!(stats.mean_i_over_sigma, None);
+    }
+}
@@ -0,0 +1,706 @@
+//! Check results in formats that CI systems read.
+//!
+//! A [`Report`] collects [`Finding`]s from parsing (errors and lenient-mode
+//! warnings), from [`CifBlock::sanity_check`](crate::CifBlock::sanity_check),
+//! and from schema checks ([`CoercionReport`]), for one or more files.
+//! [`Report::locate`] finds the line and column of each finding's value in
+//! the file's text, and the report can then be written as plain JSON
+//! ([`Report::to_json`]), as SARIF 2.1.0 for code-scanning tools that
+//! annotate the CIF file itself ([`Report::to_sarif`]), or as JUnit XML so
+//! that a CI system lists each finding as a test case
+//! ([`Report::to_junit_xml`]). `cif check --format` prints the same.
+//!
+//! Every finding has a rule ID, such as `sanity/cell-length`, that stays
+//! the same across releases; [`RULES`] lists them all.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::report::Report;
+//! use cif_parser::Document;
+//!
+//! let cif = "data_pm\n_cell_length_a 1234.5\n_cell_length_b 12.3\n";
+//! let doc = Document::parse(cif).unwrap();
+//!
+//! let mut report = Report::new();
+//! report.begin_file("pm.cif");
+//! for block in &doc.blocks {
+//!     report.add_sanity_issues(&block.name, &block.sanity_check());
+//! }
+//! report.locate(cif);
+//!
+//! let finding = &report.findings[0];
+//! assert_eq!(finding.rule_id, "sanity/cell-length");
+//! assert_eq!(finding.location, Some((2, 16)));
+//! assert!(report.has_errors());
+//! assert!(report.to_sarif().contains("\"startLine\": 2"));
+//! ```
+
+use crate::error::{CifError, CifWarning};
+use crate::lexer::{TokenKind, Tokenizer};
+use crate::sanity::SanityIssue;
+use crate::schema::{CoercionReport, TagStatus};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Every rule ID a finding can have, with a one-line description.
+///
+/// IDs are never renamed or reused; new checks add new IDs.
+pub const RULES: &[(&str, &str)] = &[
+    ("parse/error", "The file is not valid CIF"),
+    (
+        "parse/warning",
+        "The parser deviated from the specification to read the file",
+    ),
+    (
+        "sanity/cell-length",
+        "Cell lengths must be in (0.5, 1000) Å",
+    ),
+    ("sanity/cell-angle", "Cell angles must be in (0, 180)°"),
+    (
+        "sanity/cell-volume",
+        "The cell volume must agree with the cell parameters",
+    ),
+    ("sanity/occupancy", "Occupancies must be in (0, 1.001]"),
+    (
+        "sanity/fract-coordinate",
+        "Fractional coordinates must be in (−1, 2)",
+    ),
+    ("sanity/u-iso", "U(iso) must be in (−0.01, 1) Å²"),
+    (
+        "sanity/wavelength",
+        "The wavelength must suit the radiation",
+    ),
+    ("sanity/formula-units", "Z must be a positive integer"),
+    (
+        "sanity/formula-weight",
+        "The formula weight must agree with the formula sum",
+    ),
+    (
+        "sanity/site-multiplicity",
+        "Site multiplicities must agree with the symmetry operations",
+    ),
+    (
+        "sanity/density",
+        "The density must agree with the formula weight, Z, and volume",
+    ),
+    ("sanity/transmission", "T_min must not exceed T_max"),
+    (
+        "sanity/vocabulary",
+        "Enumerated values must be in the core dictionary's vocabulary",
+    ),
+    ("schema/missing", "A tag the schema requires is absent"),
+    (
+        "schema/unknown",
+        "A tag the schema requires has only ? or .",
+    ),
+    (
+        "schema/mismatch",
+        "A value does not convert to the type the schema gives",
+    ),
+];
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Informational
+    Note,
+    /// Suspicious, but the data can still be used
+    Warning,
+    /// Wrong; `cif check` fails
+    Error,
+}
+
+impl Severity {
+    /// `note`, `warning`, or `error`, as SARIF spells the levels
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// One problem found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Stable rule ID, such as `sanity/occupancy`; see [`RULES`]
+    pub rule_id: String,
+    /// How serious it is
+    pub severity: Severity,
+    /// What is wrong, for people
+    pub message: String,
+    /// The file, as given to [`Report::begin_file`]
+    pub file: Option<PathBuf>,
+    /// Data block name, without `data_`
+    pub block: Option<String>,
+    /// The tag whose value is at fault
+    pub tag: Option<String>,
+    /// Loop row of the value, or `None` for a data item
+    pub row: Option<usize>,
+    /// 1-indexed line and column, counted in characters
+    pub location: Option<(usize, usize)>,
+}
+
+impl Finding {
+    /// A finding with no file, block, or location
+    pub fn new(rule_id: impl Into<String>, severity: Severity, message: impl Into<String>) -> Self {
+        Finding {
+            rule_id: rule_id.into(),
+            severity,
+            message: message.into(),
+            file: None,
+            block: None,
+            tag: None,
+            row: None,
+            location: None,
+        }
+    }
+
+    /// `data_name`, `data_name/_tag`, or `data_name/_tag[row]`
+    fn logical_location(&self) -> Option<String> {
+        let mut name = format!("data_{}", self.block.as_ref()?);
+        if let Some(tag) = &self.tag {
+            let _ = write!(name, "/{tag}");
+        }
+        if let Some(row) = self.row {
+            let _ = write!(name, "[{row}]");
+        }
+        Some(name)
+    }
+}
+
+/// Findings for one or more files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// The files checked, in order, including those with no findings
+    pub files: Vec<PathBuf>,
+    /// What was found, in the order added
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// An empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start on a file; findings added after this refer to it
+    pub fn begin_file(&mut self, path: impl AsRef<Path>) {
+        self.files.push(path.as_ref().to_path_buf());
+    }
+
+    /// Add a finding, in the current file unless it names one
+    pub fn add(&mut self, mut finding: Finding) {
+        if finding.file.is_none() {
+            finding.file = self.files.last().cloned();
+        }
+        self.findings.push(finding);
+    }
+
+    /// The file does not parse
+    pub fn add_error(&mut self, error: &CifError) {
+        let message = match error {
+            CifError::InFile { error, .. } => error.to_string(),
+            other => other.to_string(),
+        };
+        let mut finding = Finding::new("parse/error", Severity::Error, message);
+        finding.location = error.location();
+        self.add(finding);
+    }
+
+    /// Warnings from lenient parsing
+    pub fn add_warnings(&mut self, warnings: &[CifWarning]) {
+        for warning in warnings {
+            let mut finding =
+                Finding::new("parse/warning", Severity::Warning, warning.message.clone());
+            finding.location = warning.location;
+            self.add(finding);
+        }
+    }
+
+    /// Issues from [`CifBlock::sanity_check`](crate::CifBlock::sanity_check)
+    /// or [`Experimental::check`](crate::Experimental::check) on `block`
+    pub fn add_sanity_issues(&mut self, block: &str, issues: &[SanityIssue]) {
+        for issue in issues {
+            let rule_id = format!("sanity/{}", issue.id);
+            let mut finding = Finding::new(rule_id, Severity::Error, issue.to_string());
+            finding.block = Some(block.to_string());
+            finding.tag = Some(issue.tag.clone());
+            finding.row = issue.row;
+            self.add(finding);
+        }
+    }
+
+    /// Tags of a schema check on `block` that are missing, unknown, or do
+    /// not convert; one finding per mismatched value
+    pub fn add_coercion_report(&mut self, block: &str, report: &CoercionReport) {
+        for tag in &report.tags {
+            let mut found = |rule_id: &str, severity, message: String, row| {
+                let mut finding = Finding::new(rule_id, severity, message);
+                finding.block = Some(block.to_string());
+                finding.tag = Some(tag.tag.clone());
+                finding.row = row;
+                self.add(finding);
+            };
+            match tag.status {
+                TagStatus::Ok => {}
+                TagStatus::Missing => found(
+                    "schema/missing",
+                    Severity::Error,
+                    format!("{} is missing", tag.tag),
+                    None,
+                ),
+                TagStatus::Unknown => found(
+                    "schema/unknown",
+                    Severity::Warning,
+                    format!("{} has no known value", tag.tag),
+                    None,
+                ),
+                TagStatus::Mismatch => {
+                    for mismatch in &tag.mismatches {
+                        found(
+                            "schema/mismatch",
+                            Severity::Error,
+                            format!(
+                                "{} = {}: expected {}",
+                                tag.tag, mismatch.token, tag.expected
+                            ),
+                            mismatch.row,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find where the current file's findings are, from its text.
+    ///
+    /// A finding on a value points at the value; one on a tag that is not
+    /// in the file, or on a block only, points at the block header. Findings
+    /// that already have a location keep it.
+    pub fn locate(&mut self, content: &str) {
+        let file = self.files.last().cloned();
+        let wanted: Vec<&mut Finding> = self
+            .findings
+            .iter_mut()
+            .filter(|f| f.file == file && f.location.is_none() && f.block.is_some())
+            .collect();
+        if wanted.is_empty() {
+            return;
+        }
+        let spans = value_spans(content, false);
+        for finding in wanted {
+            let block = finding.block.as_deref().unwrap_or_default().to_lowercase();
+            let value = finding.tag.as_ref().and_then(|tag| {
+                spans
+                    .values
+                    .get(&(block.clone(), tag.to_lowercase(), finding.row))
+            });
+            finding.location = value.or_else(|| spans.blocks.get(&block)).copied();
+        }
+    }
+
+    /// Whether any finding is an error
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|f| f.severity == Severity::Error)
+    }
+
+    /// The report as a JSON object: `{"files": [...], "findings": [...]}`,
+    /// each finding with `rule_id`, `severity`, `message`, `file`, `block`,
+    /// `tag`, `row`, `line`, and `column` (null when not known)
+    pub fn to_json(&self) -> String {
+        let findings: Vec<serde_json::Value> = self
+            .findings
+            .iter()
+            .map(|f| {
+                json!({
+                    "rule_id": f.rule_id,
+                    "severity": f.severity.name(),
+                    "message": f.message,
+                    "file": f.file.as_deref().map(path_text),
+                    "block": f.block,
+                    "tag": f.tag,
+                    "row": f.row,
+                    "line": f.location.map(|(line, _)| line),
+                    "column": f.location.map(|(_, col)| col),
+                })
+            })
+            .collect();
+        let report = json!({
+            "files": self.files.iter().map(|p| path_text(p)).collect::<Vec<_>>(),
+            "findings": findings,
+        });
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    }
+
+    /// The report as a SARIF 2.1.0 log with one run.
+    ///
+    /// Results point at the file by relative URI and at the line and
+    /// column of the value when [`locate`](Self::locate) found them; the
+    /// block, tag, and row are given as the logical location. The rules
+    /// used are described in the tool's driver.
+    pub fn to_sarif(&self) -> String {
+        let mut used: Vec<&str> = self.findings.iter().map(|f| f.rule_id.as_str()).collect();
+        used.sort_unstable();
+        used.dedup();
+        let rules: Vec<serde_json::Value> = used
+            .iter()
+            .map(|&id| match RULES.iter().find(|&&(known, _)| known == id) {
+                Some((_, description)) => json!({
+                    "id": id,
+                    "shortDescription": { "text": description },
+                }),
+                None => json!({ "id": id }),
+            })
+            .collect();
+        let results: Vec<serde_json::Value> = self
+            .findings
+            .iter()
+            .map(|f| {
+                let mut location = serde_json::Map::new();
+                if let Some(file) = &f.file {
+                    let mut physical = json!({
+                        "artifactLocation": { "uri": uri(file) },
+                    });
+                    if let Some((line, col)) = f.location {
+                        physical["region"] = json!({ "startLine": line, "startColumn": col });
+                    }
+                    location.insert("physicalLocation".into(), physical);
+                }
+                if let Some(name) = f.logical_location() {
+                    location.insert(
+                        "logicalLocations".into(),
+                        json!([{ "fullyQualifiedName": name }]),
+                    );
+                }
+                let mut result = json!({
+                    "ruleId": f.rule_id,
+                    "level": f.severity.name(),
+                    "message": { "text": f.message },
+                });
+                if !location.is_empty() {
+                    result["locations"] = json!([location]);
+                }
+                result
+            })
+            .collect();
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "cif-parser",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules,
+                    }
+                },
+                "artifacts": self
+                    .files
+                    .iter()
+                    .map(|file| json!({ "location": { "uri": uri(file) } }))
+                    .collect::<Vec<_>>(),
+                "results": results,
+            }],
+        });
+        serde_json::to_string_pretty(&log).unwrap_or_default()
+    }
+
+    /// The report as JUnit XML: a test suite per file, and a test case per
+    /// finding.
+    ///
+    /// Errors are failures; warnings and notes pass, with the message as
+    /// output. A file with no findings has a single passing case, `check`.
+    pub fn to_junit_xml(&self) -> String {
+        let failures = self
+            .findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count();
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            out,
+            "<testsuites name=\"cif check\" tests=\"{}\" failures=\"{failures}\">",
+            self.files
+                .iter()
+                .map(|file| self.in_file(file).count().max(1))
+                .sum::<usize>()
+        );
+        for file in &self.files {
+            let name = xml_escape(&path_text(file));
+            let findings: Vec<&Finding> = self.in_file(file).collect();
+            let failed = findings
+                .iter()
+                .filter(|f| f.severity == Severity::Error)
+                .count();
+            let _ = writeln!(
+                out,
+                "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{failed}\">",
+                findings.len().max(1)
+            );
+            if findings.is_empty() {
+                let _ = writeln!(out, "    <testcase classname=\"{name}\" name=\"check\"/>");
+            }
+            for f in findings {
+                let mut case = f.rule_id.clone();
+                if let Some(location) = f.logical_location() {
+                    let _ = write!(case, " {location}");
+                }
+                let _ = writeln!(
+                    out,
+                    "    <testcase classname=\"{name}\" name=\"{}\">",
+                    xml_escape(&case)
+                );
+                let mut detail = path_text(file);
+                if let Some((line, col)) = f.location {
+                    let _ = write!(detail, ":{line}:{col}");
+                }
+                let _ = write!(detail, ": {}", f.message);
+                if f.severity == Severity::Error {
+                    let _ = writeln!(
+                        out,
+                        "      <failure type=\"{}\" message=\"{}\">{}</failure>",
+                        f.rule_id,
+                        xml_escape(&f.message),
+                        xml_escape(&detail)
+                    );
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "      <system-out>{}: {}</system-out>",
+                        f.severity.name(),
+                        xml_escape(&detail)
+                    );
+                }
+                out.push_str("    </testcase>\n");
+            }
+            out.push_str("  </testsuite>\n");
+        }
+        out.push_str("</testsuites>\n");
+        out
+    }
+
+    fn in_file<'a>(&'a self, file: &'a Path) -> impl Iterator<Item = &'a Finding> {
+        self.findings
+            .iter()
+            .filter(move |f| f.file.as_deref() == Some(file))
+    }
+}
+
+/// Positions of block headers and values in a file's text, keyed by
+/// lowercase block name, lowercase tag, and loop row
+#[derive(Default)]
+pub(crate) struct Spans {
+    pub(crate) blocks: HashMap<String, (usize, usize)>,
+    pub(crate) values: HashMap<(String, String, Option<usize>), (usize, usize)>,
+}
+
+/// Walk the tokens of `content`, recording where each block and value
+/// starts. Save frames are skipped; a CIF 2.0 list or table counts as one
+/// value, at its opening bracket. Stops quietly at text that does not
+/// tokenize.
+///
+/// Loop rows count from 0 in each loop, or with `continue_rows`, carry on
+/// from the rows of earlier loops in the block with the same tag.
+pub(crate) fn value_spans(content: &str, continue_rows: bool) -> Spans {
+    let mut spans = Spans::default();
+    let mut block = String::new();
+    let mut in_frame = false;
+    let mut item: Option<String> = None;
+    // Loop tags, whether they are still being read, and values seen
+    let mut loop_: Option<(Vec<String>, bool, usize)> = None;
+    // Rows of each tag in earlier loops, and where this loop's rows start
+    let mut rows_seen: HashMap<(String, String), usize> = HashMap::new();
+    let mut first_row: Vec<usize> = Vec::new();
+    let mut depth = 0usize;
+
+    for token in Tokenizer::new(content).skip_trivia(true) {
+        let Ok(token) = token else { break };
+        let at = (token.line, token.col);
+        let starts_value = match token.kind {
+            TokenKind::BlockHeader => {
+                // The parser gives a `global_` block an empty name
+                block = match token.text.eq_ignore_ascii_case("global_") {
+                    true => String::new(),
+                    false => token.text.get(5..).unwrap_or_default().to_lowercase(),
+                };
+                spans.blocks.entry(block.clone()).or_insert(at);
+                (item, loop_, in_frame) = (None, None, false);
+                false
+            }
+            TokenKind::SaveHeader | TokenKind::SaveEnd => {
+                in_frame = token.kind == TokenKind::SaveHeader;
+                (item, loop_) = (None, None);
+                false
+            }
+            TokenKind::LoopKeyword => {
+                loop_ = Some((Vec::new(), true, 0));
+                item = None;
+                false
+            }
+            TokenKind::Tag => {
+                match &mut loop_ {
+                    Some((tags, true, _)) => tags.push(token.text.to_lowercase()),
+                    _ => {
+                        loop_ = None;
+                        item = Some(token.text.to_lowercase());
+                    }
+                }
+                false
+            }
+            TokenKind::Delimiter => {
+                let opens = matches!(token.text, "[" | "{");
+                let starts = opens && depth == 0;
+                if opens {
+                    depth += 1;
+                } else if matches!(token.text, "]" | "}") {
+                    depth = depth.saturating_sub(1);
+                }
+                starts
+            }
+            TokenKind::QuotedString | TokenKind::TextField | TokenKind::UnquotedValue => depth == 0,
+            TokenKind::Comment | TokenKind::Whitespace => false,
+        };
+        if !starts_value || in_frame {
+            continue;
+        }
+        if let Some(tag) = item.take() {
+            spans.values.entry((block.clone(), tag, None)).or_insert(at);
+        } else if let Some((tags, reading, count)) = &mut loop_ {
+            if *reading {
+                *reading = false;
+                first_row = tags
+                    .iter()
+                    .map(|tag| {
+                        let seen = rows_seen.get(&(block.clone(), tag.clone()));
+                        seen.filter(|_| continue_rows).copied().unwrap_or(0)
+                    })
+                    .collect();
+            }
+            if !tags.is_empty() {
+                let column = *count % tags.len();
+                let key = (block.clone(), tags[column].clone());
+                let row = first_row[column] + *count / tags.len();
+                rows_seen.insert(key.clone(), row + 1);
+                spans.values.entry((key.0, key.1, Some(row))).or_insert(at);
+                *count += 1;
+            }
+        }
+    }
+    spans
+}
+
+/// A path as text, with `/` separators
+fn path_text(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// A URI for a path, percent-encoding what needs it: a relative reference
+/// for a relative path, and a `file:` URI for an absolute one
+fn uri(path: &Path) -> String {
+    let text = path_text(path);
+    let mut out = String::new();
+    if path.is_absolute() {
+        out.push_str(if text.starts_with('/') {
+            "file://"
+        } else {
+            "file:///"
+        });
+    }
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#10;"),
+            c if c.is_control() && c != '\t' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_spans() {
+        let cif = "\
+#\\#CIF_2.0
+data_A
+_single 1
+_list [1 [2 3] 4]
+loop_
+_x _y
+a b
+c
+  d
+save_f
+_single 2
+save_
+data_b
+_single 'three'
+";
+        let spans = value_spans(cif, false);
+        let at = |block: &str, tag: &str, row| spans.values.get(&(block.into(), tag.into(), row));
+        assert_eq!(spans.blocks["a"], (2, 1));
+        assert_eq!(at("a", "_single", None), Some(&(3, 9)));
+        assert_eq!(at("a", "_list", None), Some(&(4, 7)));
+        assert_eq!(at("a", "_x", Some(1)), Some(&(8, 1)));
+        assert_eq!(at("a", "_y", Some(1)), Some(&(9, 3)));
+        assert_eq!(at("b", "_single", None), Some(&(14, 9)));
+        assert_eq!(spans.values.len(), 7);
+    }
+
+    #[test]
+    fn test_every_sanity_id_has_a_rule() {
+        for id in [
+            "cell-length",
+            "cell-angle",
+            "cell-volume",
+            "occupancy",
+            "fract-coordinate",
+            "u-iso",
+            "wavelength",
+            "formula-units",
+            "formula-weight",
+            "density",
+            "transmission",
+            "vocabulary",
+        ] {
+            let rule = format!("sanity/{id}");
+            assert!(RULES.iter().any(|&(known, _)| known == rule), "{rule}");
+        }
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(
+            xml_escape("a < b & \"c\"\n"),
+            "a &lt; b &amp; &quot;c&quot;&#10;"
+        );
+        assert_eq!(uri(Path::new("dir/my file%.cif")), "dir/my%20file%25.cif");
+        if cfg!(unix) {
+            assert_eq!(uri(Path::new("/data/x.cif")), "file:///data/x.cif");
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! Built-in sanity checks for common crystallographic quantities.
+//!
+//! [`CifBlock::sanity_check`] applies fixed, physically motivated limits
+//! that need no dictionary: cell lengths and angles, occupancies, fractional
+//! coordinates, isotropic displacement parameters, the wavelength, Z, and the
+//! consistency of the reported cell volume and formula weight with the values
+//! they derive from, and the values of enumerated tags such as
+//! `_atom_site_adp_type`. Values outside these limits are almost always typing or
+//! unit errors, such as a cell length in picometres.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_pm
+//! _cell_length_a 1234.5
+//! _cell_length_b 12.345
+//! _cell_length_c 12.345
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let issues = doc.first_block().unwrap().sanity_check();
+//!
+//! // a was given in picometres
+//! assert_eq!(issues.len(), 1);
+//! assert_eq!(
+//!     issues[0].to_string(),
+//!     "_cell_length_a = 1234.5: cell length must be in (0.5, 1000) Å"
+//! );
+//! ```
+
+use crate::approx::{Comparison, ValueComparison};
+use crate::ast::{CifBlock, CifValue, CifVersion};
+use crate::publication::value_text;
+use crate::structure::{DEFAULT_SITE_TOLERANCE, MULTIPLICITY_TAGS};
+use crate::uncertainty;
+use crate::writer::inline_value;
+use std::fmt;
+
+/// A value that breaks one of the [`CifBlock::sanity_check`] rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanityIssue {
+    /// Short name of the rule, such as `cell-length`; stable across releases
+    pub id: &'static str,
+    /// The tag the value belongs to
+    pub tag: String,
+    /// Loop row of the value, or `None` for a data item
+    pub row: Option<usize>,
+    /// The value, as it would be written in CIF
+    pub value: String,
+    /// The rule it breaks
+    pub rule: String,
+}
+
+impl fmt::Display for SanityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(f, "{}[{}]", self.tag, row)?,
+            None => write!(f, "{}", self.tag)?,
+        }
+        write!(f, " = {}: {}", self.value, self.rule)
+    }
+}
+
+/// A range rule: core and mmCIF tags, what they are, the open lower bound,
+/// the upper bound (closed if the flag is set), and the unit
+struct Range {
+    id: &'static str,
+    tags: &'static [&'static str],
+    name: &'static str,
+    min: f64,
+    max: f64,
+    max_inclusive: bool,
+    unit: &'static str,
+}
+
+const RANGES: &[Range] = &[
+    Range {
+        id: "cell-length",
+        tags: &[
+            "_cell_length_a",
+            "_cell_length_b",
+            "_cell_length_c",
+            "_cell.length_a",
+            "_cell.length_b",
+            "_cell.length_c",
+        ],
+        name: "cell length",
+        min: 0.5,
+        max: 1000.0,
+        max_inclusive: false,
+        unit: " Å",
+    },
+    Range {
+        id: "cell-angle",
+        tags: &[
+            "_cell_angle_alpha",
+            "_cell_angle_beta",
+            "_cell_angle_gamma",
+            "_cell.angle_alpha",
+            "_cell.angle_beta",
+            "_cell.angle_gamma",
+        ],
+        name: "cell angle",
+        min: 0.0,
+        max: 180.0,
+        max_inclusive: false,
+        unit: "°",
+    },
+    Range {
+        id: "occupancy",
+        tags: &["_atom_site_occupancy", "_atom_site.occupancy"],
+        name: "occupancy",
+        min: 0.0,
+        max: 1.001,
+        max_inclusive: true,
+        unit: "",
+    },
+    Range {
+        id: "fract-coordinate",
+        tags: &[
+            "_atom_site_fract_x",
+            "_atom_site_fract_y",
+            "_atom_site_fract_z",
+            "_atom_site.fract_x",
+            "_atom_site.fract_y",
+            "_atom_site.fract_z",
+        ],
+        name: "fractional coordinate",
+        min: -1.0,
+        max: 2.0,
+        max_inclusive: false,
+        unit: "",
+    },
+    Range {
+        id: "u-iso",
+        tags: &["_atom_site_U_iso_or_equiv", "_atom_site.U_iso_or_equiv"],
+        name: "U(iso)",
+        min: -0.01,
+        max: 1.0,
+        max_inclusive: false,
+        unit: " Å²",
+    },
+];
+
+const WAVELENGTH: Range = Range {
+    id: "wavelength",
+    tags: &[
+        "_diffrn_radiation_wavelength",
+        "_diffrn_radiation_wavelength.wavelength",
+    ],
+    name: "wavelength",
+    min: 0.1,
+    max: 10.0,
+    max_inclusive: false,
+    unit: " Å",
+};
+
+/// Electron wavelengths are far shorter: 0.1 Å is about 15 kV
+const ELECTRON_WAVELENGTH: Range = Range {
+    name: "electron wavelength",
+    min: 0.001,
+    max: 0.1,
+    ..WAVELENGTH
+};
+
+/// Largest difference accepted between a reported cell volume or formula
+/// weight and the one computed from the cell or formula, in σ
+const CONSISTENCY_SIGMAS: f64 = 3.0;
+
+/// The same, relative, for values without sus
+const CONSISTENCY_TOLERANCE: f64 = 0.001;
+
+/// Every value of a tag, from the data item and any loop columns, with its row
+pub(crate) fn values<'a>(block: &'a CifBlock, tag: &str) -> Vec<(Option<usize>, &'a CifValue)> {
+    let mut values: Vec<_> = block
+        .items
+        .get(tag)
+        .map(|v| (None, v))
+        .into_iter()
+        .collect();
+    for loop_ in &block.loops {
+        if let Some(column) = loop_.get_column(tag) {
+            values.extend(
+                column
+                    .into_iter()
+                    .enumerate()
+                    .map(|(row, v)| (Some(row), v)),
+            );
+        }
+    }
+    values
+}
+
+/// Whether the radiation is electrons, from `_diffrn_radiation_probe` or
+/// `_diffrn_radiation_type` (e.g. `'electrons 200 keV'`)
+fn is_electron_diffraction(block: &CifBlock) -> bool {
+    [
+        "_diffrn_radiation_probe",
+        "_diffrn_radiation.probe",
+        "_diffrn_radiation_type",
+        "_diffrn_radiation.type",
+    ]
+    .iter()
+    .filter_map(|tag| block.get_item(tag)?.as_string())
+    .any(|text| text.to_ascii_lowercase().starts_with("electron"))
+}
+
+/// The first numeric value of the first of `tags` present, with its tag
+/// and su (0 if none)
+fn number<'a>(block: &'a CifBlock, tags: &[&'a str]) -> Option<(&'a str, &'a CifValue, f64, f64)> {
+    tags.iter().find_map(|&tag| {
+        let value = block.items.get(tag)?;
+        let (number, su) = value.as_numeric_with_su()?;
+        Some((tag, value, number, su.unwrap_or(0.0)))
+    })
+}
+
+fn issue(
+    id: &'static str,
+    tag: &str,
+    row: Option<usize>,
+    value: &CifValue,
+    rule: String,
+) -> SanityIssue {
+    SanityIssue {
+        id,
+        tag: tag.to_string(),
+        row,
+        value: inline_value(value, CifVersion::V1_1),
+        rule,
+    }
+}
+
+impl CifBlock {
+    /// Check common quantities against fixed physical limits.
+    ///
+    /// The rules are:
+    ///
+    /// - cell lengths in (0.5, 1000) Å and angles in (0, 180)°
+    /// - `_cell_volume` within 3σ of the volume from the cell parameters,
+    ///   combining the sus of both, or within 0.1% if neither has an su
+    /// - occupancies in (0, 1.001]
+    /// - fractional coordinates in (−1, 2)
+    /// - `_atom_site_U_iso_or_equiv` in (−0.01, 1) Å²
+    /// - wavelengths in (0.1, 10) Å, or (0.001, 0.1) Å when the radiation is
+    ///   electrons
+    /// - `_cell_formula_units_Z` a positive integer
+    /// - `_chemical_formula_weight` within 0.1% of the weight of
+    ///   `_chemical_formula_sum`
+    /// - `_atom_site_site_symmetry_multiplicity` equal to the multiplicity
+    ///   of the site under the block's symmetry operations (see
+    ///   [`Structure::site_symmetries`](crate::Structure::site_symmetries))
+    /// - enumerated values such as `_atom_site_adp_type` in their
+    ///   vocabulary, ignoring case (see
+    ///   [`check_vocabularies`](Self::check_vocabularies))
+    ///
+    /// Both core and mmCIF tags are checked, in data items and loops. Values
+    /// that should be numbers but are not are reported; `?` and `.` are
+    /// skipped. An empty list means nothing looked wrong.
+    pub fn sanity_check(&self) -> Vec<SanityIssue> {
+        let mut issues = Vec::new();
+
+        let wavelength = if is_electron_diffraction(self) {
+            &ELECTRON_WAVELENGTH
+        } else {
+            &WAVELENGTH
+        };
+        for range in RANGES.iter().chain([wavelength]) {
+            let close = if range.max_inclusive { ']' } else { ')' };
+            for &tag in range.tags {
+                for (row, value) in values(self, tag) {
+                    if matches!(value, CifValue::Unknown | CifValue::NotApplicable) {
+                        continue;
+                    }
+                    let rule = match value.as_numeric_with_su() {
+                        None => format!("{} must be a number", range.name),
+                        Some((x, _))
+                            if x <= range.min
+                                || x > range.max
+                                || (x == range.max && !range.max_inclusive) =>
+                        {
+                            format!(
+                                "{} must be in ({}, {}{close}{}",
+                                range.name, range.min, range.max, range.unit
+                            )
+                        }
+                        Some(_) => continue,
+                    };
+                    issues.push(issue(range.id, tag, row, value, rule));
+                }
+            }
+        }
+
+        if let Some((tag, value, z, _)) =
+            number(self, &["_cell_formula_units_Z", "_cell.formula_units_Z"])
+        {
+            if z <= 0.0 || z.fract() != 0.0 {
+                issues.push(issue(
+                    "formula-units",
+                    tag,
+                    None,
+                    value,
+                    "Z must be a positive integer".into(),
+                ));
+            }
+        }
+
+        if let (Some((tag, value, reported, su)), Some(cell)) = (
+            number(self, &["_cell_volume", "_cell.volume"]),
+            self.unit_cell(),
+        ) {
+            let (volume, volume_su) = cell.volume_with_su();
+            let comparison = ValueComparison::new()
+                .relative_tolerance(CONSISTENCY_TOLERANCE)
+                .compare(
+                    (reported, Some(su)),
+                    (volume, Some(volume_su)),
+                    CONSISTENCY_SIGMAS,
+                );
+            if let Comparison::Differs { sigmas } = comparison {
+                let rule = if su.hypot(volume_su) > 0.0 {
+                    format!(
+                        "cell volume differs by {sigmas:.1}σ from {} Å³ from the cell parameters",
+                        uncertainty::format(volume, volume_su)
+                    )
+                } else {
+                    format!(
+                        "cell volume must be within 0.1% of {volume:.2} Å³ from the cell parameters"
+                    )
+                };
+                issues.push(issue("cell-volume", tag, None, value, rule));
+            }
+        }
+
+        let weight = self.chemical_formula().and_then(|formula| formula.weight());
+        if let (Some((tag, value, reported, _)), Some(weight)) = (
+            number(
+                self,
+                &["_chemical_formula_weight", "_chemical_formula.weight"],
+            ),
+            weight,
+        ) {
+            let comparison = ValueComparison::new()
+                .relative_tolerance(CONSISTENCY_TOLERANCE)
+                .compare((reported, None), (weight, None), CONSISTENCY_SIGMAS);
+            if !comparison.is_equal() {
+                let rule = format!(
+                    "formula weight must be within 0.1% of {weight:.2} from the formula sum"
+                );
+                issues.push(issue("formula-weight", tag, None, value, rule));
+            }
+        }
+
+        if !self.symmetry_operations().is_empty() {
+            if let Some(structure) = self.structure() {
+                let sites = structure.site_symmetries(DEFAULT_SITE_TOLERANCE);
+                let label = |row: usize| {
+                    ["_atom_site_label", "_atom_site.id"]
+                        .iter()
+                        .find_map(|tag| {
+                            let loop_ = self.find_loop(tag)?;
+                            value_text(loop_.get_by_tag(row, tag)?)
+                        })
+                };
+                for tag in MULTIPLICITY_TAGS {
+                    for (row, value) in values(self, tag) {
+                        let Some(site) = row
+                            .and_then(label)
+                            .and_then(|label| sites.iter().find(|site| site.label == label))
+                        else {
+                            continue;
+                        };
+                        if let Some((_, computed)) = site.mismatch() {
+                            let rule = format!(
+                                "site multiplicity must be {computed} from the symmetry \
+                                 operations (site symmetry {})",
+                                site.symbol.unwrap_or("?")
+                            );
+                            issues.push(issue("site-multiplicity", tag, row, value, rule));
+                        }
+                    }
+                }
+            }
+        }
+
+        issues.extend(self.check_vocabularies().into_iter().map(SanityIssue::from));
+        issues
+    }
+}
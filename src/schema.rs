@@ -0,0 +1,366 @@
+//! Dry-run type checks of a block against an application schema.
+//!
+//! A pipeline that reads a handful of tags into typed fields needs to know,
+//! before it starts, which of them are missing, unknown (`?` or `.`), or not
+//! convertible. [`CifBlock::coercion_report`] tries every conversion without
+//! stopping at the first failure and reports each tag, with the offending
+//! token and loop row of every mismatch. It is a lightweight alternative to
+//! dictionary validation for schemas that applications define themselves.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::schema::{ExpectedType, TagStatus};
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_x
+//! _cell_length_a 10.234(3)
+//! _space_group_IT_number 14.5
+//! loop_
+//! _atom_site_label
+//! _atom_site_calc_flag
+//! C1 d
+//! C2 maybe
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let report = doc.blocks[0].coercion_report(&[
+//!     ("_cell_length_a", ExpectedType::F64WithSu),
+//!     ("_space_group_IT_number", ExpectedType::I64),
+//!     ("_atom_site_calc_flag", ExpectedType::Enum(&["d", "calc", "c", "dum"])),
+//!     ("_exptl_crystal_colour", ExpectedType::Text),
+//! ]);
+//!
+//! assert!(!report.is_ok());
+//! assert_eq!(report.get("_cell_length_a").unwrap().status, TagStatus::Ok);
+//! assert_eq!(report.get("_exptl_crystal_colour").unwrap().status, TagStatus::Missing);
+//! let flags = report.get("_atom_site_calc_flag").unwrap();
+//! assert_eq!(flags.mismatches[0].row, Some(1));
+//! assert_eq!(flags.mismatches[0].token, "maybe");
+//! ```
+
+use crate::ast::{CifBlock, CifValue, CifVersion};
+use crate::typed::number_with_su;
+use crate::writer::inline_value;
+use std::fmt;
+
+/// The type a tag's values should convert to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedType<'a> {
+    /// A plain number, without an su
+    F64,
+    /// A number with an optional standard uncertainty, such as `5.431(2)`
+    F64WithSu,
+    /// A whole number that fits in an `i64`
+    I64,
+    /// A date, `YYYY-MM-DD`, optionally followed by `T` and a time
+    Date,
+    /// `yes`/`no`, `y`/`n`, `true`/`false`, or `1`/`0`, in any case
+    Bool,
+    /// Any text; numbers count as text too, lists and tables do not
+    Text,
+    /// One of the given codes, compared without regard to case
+    Enum(&'a [&'a str]),
+}
+
+impl ExpectedType<'_> {
+    /// Whether `value` converts to this type; `?` and `.` are handled by the
+    /// caller
+    fn accepts(&self, value: &CifValue) -> bool {
+        // Text and large text are read alike
+        match (self, value, value.as_string()) {
+            (ExpectedType::F64, ..) => matches!(value, CifValue::Numeric(_)),
+            (ExpectedType::F64WithSu, ..) => number_with_su(value).is_some(),
+            (ExpectedType::I64, CifValue::Numeric(n), _) => {
+                n.fract() == 0.0 && (i64::MIN as f64..=i64::MAX as f64).contains(n)
+            }
+            (ExpectedType::I64, _, Some(s)) => s.trim().parse::<i64>().is_ok(),
+            (ExpectedType::Date, _, Some(s)) => is_date(s.trim()),
+            (ExpectedType::Bool, CifValue::Numeric(n), _) => *n == 0.0 || *n == 1.0,
+            (ExpectedType::Bool, _, Some(s)) => {
+                let s = s.trim().to_ascii_lowercase();
+                matches!(s.as_str(), "yes" | "no" | "y" | "n" | "true" | "false")
+            }
+            (ExpectedType::Text, _, text) => text.is_some() || value.is_numeric(),
+            (ExpectedType::Enum(codes), _, text) if text.is_some() || value.is_numeric() => {
+                let token = token(value);
+                codes.iter().any(|code| code.eq_ignore_ascii_case(&token))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ExpectedType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpectedType::F64 => write!(f, "number"),
+            ExpectedType::F64WithSu => write!(f, "number with optional su"),
+            ExpectedType::I64 => write!(f, "integer"),
+            ExpectedType::Date => write!(f, "date"),
+            ExpectedType::Bool => write!(f, "boolean"),
+            ExpectedType::Text => write!(f, "text"),
+            ExpectedType::Enum(codes) => write!(f, "one of {}", codes.join(", ")),
+        }
+    }
+}
+
+/// Outcome for one tag of a [`CoercionReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagStatus {
+    /// Every value converts, though some may be `?` or `.`
+    Ok,
+    /// The tag is not in the block
+    Missing,
+    /// Every value is `?` or `.`
+    Unknown,
+    /// At least one value does not convert
+    Mismatch,
+}
+
+impl TagStatus {
+    /// Lowercase name, as used by the Python bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            TagStatus::Ok => "ok",
+            TagStatus::Missing => "missing",
+            TagStatus::Unknown => "unknown",
+            TagStatus::Mismatch => "mismatch",
+        }
+    }
+}
+
+/// A value that does not convert to the expected type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Loop row of the value, or `None` for a data item
+    pub row: Option<usize>,
+    /// The value, as it would be written in CIF
+    pub token: String,
+}
+
+/// What [`CifBlock::coercion_report`] found for one tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagReport {
+    /// The tag, as given in the schema
+    pub tag: String,
+    /// Description of the expected type
+    pub expected: String,
+    pub status: TagStatus,
+    /// Number of `?` and `.` values
+    pub unknown: usize,
+    /// Values that do not convert, in row order
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl fmt::Display for TagReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            TagStatus::Ok => write!(f, "{}: ok", self.tag),
+            TagStatus::Missing => write!(f, "{}: missing", self.tag),
+            TagStatus::Unknown => write!(f, "{}: unknown", self.tag),
+            TagStatus::Mismatch => {
+                let tokens: Vec<String> = self
+                    .mismatches
+                    .iter()
+                    .map(|m| match m.row {
+                        Some(row) => format!("{} (row {row})", m.token),
+                        None => m.token.clone(),
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "{}: expected {}, found {}",
+                    self.tag,
+                    self.expected,
+                    tokens.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Result of checking a block against a schema, one entry per schema tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoercionReport {
+    /// Reports in schema order
+    pub tags: Vec<TagReport>,
+}
+
+impl CoercionReport {
+    /// Whether every tag is present, converts, and has at least one known value
+    pub fn is_ok(&self) -> bool {
+        self.tags.iter().all(|tag| tag.status == TagStatus::Ok)
+    }
+
+    /// The report for a tag, matched without regard to case
+    pub fn get(&self, tag: &str) -> Option<&TagReport> {
+        self.tags.iter().find(|t| t.tag.eq_ignore_ascii_case(tag))
+    }
+
+    /// Tags with the given status
+    pub fn with_status(&self, status: TagStatus) -> impl Iterator<Item = &str> {
+        self.tags
+            .iter()
+            .filter(move |t| t.status == status)
+            .map(|t| t.tag.as_str())
+    }
+}
+
+impl fmt::Display for CoercionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for tag in &self.tags {
+            writeln!(f, "{tag}")?;
+        }
+        Ok(())
+    }
+}
+
+impl CifBlock {
+    /// Try to convert the values of each schema tag to its expected type,
+    /// reporting every failure instead of stopping at the first.
+    ///
+    /// Tags are matched without regard to case, as data items or loop
+    /// columns. See the [module documentation](crate::schema) for an example.
+    pub fn coercion_report(&self, schema: &[(&str, ExpectedType)]) -> CoercionReport {
+        let tags = schema
+            .iter()
+            .map(|&(tag, expected)| self.check_tag(tag, expected))
+            .collect();
+        CoercionReport { tags }
+    }
+
+    fn check_tag(&self, tag: &str, expected: ExpectedType) -> TagReport {
+        let values: Vec<(Option<usize>, &CifValue)> = match self
+            .items
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(tag))
+        {
+            Some((_, value)) => vec![(None, value)],
+            None => self
+                .loops
+                .iter()
+                .find_map(|loop_| {
+                    let col = loop_
+                        .tags
+                        .iter()
+                        .position(|t| t.eq_ignore_ascii_case(tag))?;
+                    Some(
+                        loop_
+                            .values
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(row, values)| Some((Some(row), values.get(col)?)))
+                            .collect(),
+                    )
+                })
+                .unwrap_or_default(),
+        };
+        let present = values.len();
+
+        let mut unknown = 0;
+        let mut mismatches = Vec::new();
+        for (row, value) in values {
+            if matches!(value, CifValue::Unknown | CifValue::NotApplicable) {
+                unknown += 1;
+            } else if !expected.accepts(value) {
+                mismatches.push(Mismatch {
+                    row,
+                    token: token(value),
+                });
+            }
+        }
+
+        let status = if present == 0 {
+            TagStatus::Missing
+        } else if !mismatches.is_empty() {
+            TagStatus::Mismatch
+        } else if unknown == present {
+            TagStatus::Unknown
+        } else {
+            TagStatus::Ok
+        };
+        TagReport {
+            tag: tag.to_string(),
+            expected: expected.to_string(),
+            status,
+            unknown,
+            mismatches,
+        }
+    }
+}
+
+/// A value as written in CIF, without quotes around text
+fn token(value: &CifValue) -> String {
+    match value {
+        CifValue::Text(s) => s.clone(),
+        CifValue::LargeText(s) => s.to_string(),
+        other => inline_value(other, CifVersion::V2_0),
+    }
+}
+
+/// Whether text is a `YYYY-MM-DD` date, optionally followed by `T` and a time
+fn is_date(s: &str) -> bool {
+    let date = match s.split_once(['T', 't']) {
+        Some((date, time)) if !time.is_empty() => date,
+        Some(_) => return false,
+        None => s,
+    };
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+    let number = |part: &str, len: usize| {
+        (part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| part.parse::<u32>().ok())
+            .flatten()
+    };
+    let (Some(year), Some(month), Some(day)) = (number(year, 4), number(month, 2), number(day, 2))
+    else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepts(expected: ExpectedType, token: &str) -> bool {
+        expected.accepts(&CifValue::parse_value(token))
+    }
+
+    #[test]
+    fn test_dates() {
+        assert!(is_date("2024-02-29"));
+        assert!(is_date("2023-11-05T14:30:00"));
+        assert!(!is_date("2023-02-29"));
+        assert!(!is_date("2023-13-01"));
+        assert!(!is_date("23-01-01"));
+        assert!(!is_date("2023-01-01T"));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert!(accepts(ExpectedType::I64, "14"));
+        assert!(!accepts(ExpectedType::I64, "14.5"));
+        assert!(!accepts(ExpectedType::F64, "5.431(2)"));
+        assert!(accepts(ExpectedType::F64WithSu, "5.431(2)"));
+        assert!(accepts(ExpectedType::Bool, "Yes"));
+        assert!(accepts(ExpectedType::Bool, "0"));
+        assert!(!accepts(ExpectedType::Bool, "2"));
+        assert!(accepts(ExpectedType::Text, "12"));
+        assert!(accepts(ExpectedType::Enum(&["Uani", "Uiso"]), "uiso"));
+        assert!(!accepts(ExpectedType::Enum(&["Uani", "Uiso"]), "Biso"));
+        let list = CifValue::List(vec![CifValue::Numeric(1.0)]);
+        assert!(!ExpectedType::Text.accepts(&list));
+    }
+}
@@ -0,0 +1,249 @@
+//! Thread-safe shared handles into a parsed document.
+//!
+//! Parsed documents are immutable plain data, so they can be wrapped in an
+//! [`Arc`] once and read from any number of threads. The handles in this
+//! module hold the `Arc` plus indices, making them cheap to clone and to move
+//! across thread boundaries without copying any CIF data.
+//!
+//! # Thread Safety
+//!
+//! [`CifDocument`] and all of its contents ([`CifBlock`], [`CifLoop`],
+//! [`CifFrame`], [`CifValue`]) are `Send + Sync`. They contain no interior
+//! mutability, so shared references can be read concurrently without locking.
+//! Any future lazily-computed state (indexes, caches) must use `OnceLock` or
+//! `RwLock` to keep this guarantee; the compile-time assertions at the bottom
+//! of this module enforce it.
+//!
+//! # Mutation
+//!
+//! A shared document is never changed in place while anyone else can see it.
+//! [`CifDocument::make_mut_block`] copies the document first if other handles
+//! refer to it, so existing [`SharedBlock`]s and [`SharedLoop`]s stay valid
+//! snapshots of the old version.
+//!
+//! [`DocumentHandle`] adds a lock around the current version for documents
+//! that several threads both read and change, as the Python bindings do:
+//!
+//! - Changes made through [`DocumentHandle::update`] are serialized, and each
+//!   one is seen completely or not at all.
+//! - A [`DocumentHandle::snapshot`] is an immutable version that later changes
+//!   never affect; readers hold no lock while using it.
+//! - If a change panics, the lock is not poisoned for other users, but that
+//!   change may have been partly applied.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::{Document, SharedBlock};
+//! use std::thread;
+//!
+//! let doc = Document::parse("data_test\nloop_\n_x\n1\n2\n3\n").unwrap();
+//! let shared = doc.into_shared();
+//!
+//! let handles: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let block = SharedBlock::new(shared.clone(), 0).unwrap();
+//!         thread::spawn(move || block.find_loop("_x").unwrap().len())
+//!     })
+//!     .collect();
+//!
+//! for handle in handles {
+//!     assert_eq!(handle.join().unwrap(), 3);
+//! }
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue};
+use std::ops::Deref;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A cheap, thread-safe handle to one block of a shared document.
+///
+/// Dereferences to the underlying [`CifBlock`].
+#[derive(Debug, Clone)]
+pub struct SharedBlock {
+    doc: Arc<CifDocument>,
+    block_index: usize,
+}
+
+impl SharedBlock {
+    /// Create a handle to the block at `block_index`, or `None` if out of range
+    pub fn new(doc: Arc<CifDocument>, block_index: usize) -> Option<Self> {
+        if block_index < doc.blocks.len() {
+            Some(SharedBlock { doc, block_index })
+        } else {
+            None
+        }
+    }
+
+    /// Create a handle to the first block with the given name
+    pub fn by_name(doc: Arc<CifDocument>, name: &str) -> Option<Self> {
+        let block_index = doc.blocks.iter().position(|b| b.name == name)?;
+        Some(SharedBlock { doc, block_index })
+    }
+
+    /// The shared document this block belongs to
+    pub fn document(&self) -> &Arc<CifDocument> {
+        &self.doc
+    }
+
+    /// Index of this block within the document
+    pub fn index(&self) -> usize {
+        self.block_index
+    }
+
+    /// Borrow the underlying block
+    pub fn block(&self) -> &CifBlock {
+        &self.doc.blocks[self.block_index]
+    }
+
+    /// Create a handle to the loop at `loop_index` within this block
+    pub fn loop_at(&self, loop_index: usize) -> Option<SharedLoop> {
+        if loop_index < self.block().loops.len() {
+            Some(SharedLoop {
+                block: self.clone(),
+                loop_index,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Create a handle to the loop containing `tag`
+    pub fn find_loop(&self, tag: &str) -> Option<SharedLoop> {
+        let loop_index = self
+            .block()
+            .loops
+            .iter()
+            .position(|l| l.tags.iter().any(|t| t == tag))?;
+        Some(SharedLoop {
+            block: self.clone(),
+            loop_index,
+        })
+    }
+}
+
+impl Deref for SharedBlock {
+    type Target = CifBlock;
+
+    fn deref(&self) -> &CifBlock {
+        self.block()
+    }
+}
+
+/// A cheap, thread-safe handle to one loop of a shared document.
+///
+/// Dereferences to the underlying [`CifLoop`].
+#[derive(Debug, Clone)]
+pub struct SharedLoop {
+    block: SharedBlock,
+    loop_index: usize,
+}
+
+impl SharedLoop {
+    /// The block handle this loop belongs to
+    pub fn block(&self) -> &SharedBlock {
+        &self.block
+    }
+
+    /// Index of this loop within its block
+    pub fn index(&self) -> usize {
+        self.loop_index
+    }
+
+    /// Borrow the underlying loop
+    pub fn get_loop(&self) -> &CifLoop {
+        &self.block.block().loops[self.loop_index]
+    }
+}
+
+impl Deref for SharedLoop {
+    type Target = CifLoop;
+
+    fn deref(&self) -> &CifLoop {
+        self.get_loop()
+    }
+}
+
+/// A document that can be read and changed from many threads.
+///
+/// Clones share the same document. Readers take a [`snapshot`](Self::snapshot)
+/// and writers go through [`update`](Self::update), which copies the document
+/// only while a snapshot of the current version is still alive.
+///
+/// # Examples
+///
+/// ```
+/// use cif_parser::{Document, DocumentHandle};
+///
+/// let handle = DocumentHandle::new(Document::parse("data_a\n_x 1\n").unwrap());
+/// let before = handle.block(0).unwrap();
+///
+/// handle.update_block(0, |block| block.name = "b".into());
+///
+/// assert_eq!(before.name, "a");
+/// assert_eq!(handle.block(0).unwrap().name, "b");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DocumentHandle {
+    current: Arc<RwLock<Arc<CifDocument>>>,
+}
+
+impl DocumentHandle {
+    /// Wrap a document in a new handle
+    pub fn new(doc: CifDocument) -> Self {
+        doc.into_shared().into()
+    }
+
+    /// The current version of the document
+    pub fn snapshot(&self) -> Arc<CifDocument> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// A handle to the block at `index` in the current version, or `None` if
+    /// out of range
+    pub fn block(&self, index: usize) -> Option<SharedBlock> {
+        SharedBlock::new(self.snapshot(), index)
+    }
+
+    /// Change the document, waiting for any other change to finish first
+    pub fn update<R>(&self, f: impl FnOnce(&mut CifDocument) -> R) -> R {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        f(Arc::make_mut(&mut current))
+    }
+
+    /// Change the block at `index`, or return `None` if out of range
+    pub fn update_block<R>(&self, index: usize, f: impl FnOnce(&mut CifBlock) -> R) -> Option<R> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        CifDocument::make_mut_block(&mut current, index).map(f)
+    }
+}
+
+impl From<Arc<CifDocument>> for DocumentHandle {
+    fn from(doc: Arc<CifDocument>) -> Self {
+        DocumentHandle {
+            current: Arc::new(RwLock::new(doc)),
+        }
+    }
+}
+
+impl From<CifDocument> for DocumentHandle {
+    fn from(doc: CifDocument) -> Self {
+        DocumentHandle::new(doc)
+    }
+}
+
+// Compile-time guarantee that the data model stays shareable across threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CifDocument>();
+    assert_send_sync::<CifBlock>();
+    assert_send_sync::<CifFrame>();
+    assert_send_sync::<CifLoop>();
+    assert_send_sync::<CifValue>();
+    assert_send_sync::<SharedBlock>();
+    assert_send_sync::<SharedLoop>();
+    assert_send_sync::<DocumentHandle>();
+};
@@ -0,0 +1,828 @@
+//! Space groups from their Hall symbols, Hermann–Mauguin symbols or
+//! International Tables numbers.
+//!
+//! Many CIFs name the space group but leave out the
+//! `_space_group_symop_operation_xyz` loop. [`SpaceGroup::from_hall`]
+//! expands any Hall symbol into its operations by generating the group from
+//! the matrices the symbol names. A bundled table maps the Hermann–Mauguin
+//! symbols and numbers of the 230 groups to Hall symbols. It covers the
+//! standard settings, both origin choices, hexagonal and rhombohedral axes,
+//! and the other monoclinic cell choices and unique axis c.
+//!
+//! [`CifBlock::space_group`] resolves the symbol tags of a block.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::SpaceGroup;
+//!
+//! let group = SpaceGroup::from_hermann_mauguin("P 21/n").unwrap();
+//! assert_eq!(group.number, Some(14));
+//! assert_eq!(group.hall, "-P 2yn");
+//!
+//! let ops: Vec<String> = group.operations.iter().map(|op| op.to_string()).collect();
+//! assert_eq!(ops, ["x,y,z", "-x,-y,-z", "-x+1/2,y+1/2,-z+1/2", "x+1/2,-y+1/2,z+1/2"]);
+//! ```
+
+use crate::ast::CifBlock;
+use crate::publication::value_text;
+use crate::symmetry::SymOp;
+
+/// A space group and all of its symmetry operations
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpaceGroup {
+    /// International Tables number, if the Hall symbol is in the table
+    pub number: Option<u32>,
+    /// Hermann–Mauguin symbol, with the setting after a colon where the
+    /// group has more than one (`F d -3 m :2`), if the Hall symbol is in
+    /// the table
+    pub hermann_mauguin: Option<&'static str>,
+    /// Hall symbol the operations were generated from
+    pub hall: String,
+    /// Operations, identity first, followed by the same operations plus
+    /// each centring translation; translations are reduced to `[0, 1)`
+    pub operations: Vec<SymOp>,
+}
+
+impl SpaceGroup {
+    /// Generate a space group from a Hall symbol, such as `-P 2ybc` or
+    /// `P 31 2c (0 0 1)`.
+    ///
+    /// Any Hall symbol is accepted, not just those in the table, including
+    /// the `-I 4bd;2ab;3` form Jana2006 writes; an origin shift must be
+    /// given in twelfths, as in `(0 0 1)`. A symbol that is not in the table
+    /// but generates the same operations as an entry takes that entry's
+    /// number and Hermann–Mauguin symbol. Returns `None` if the symbol
+    /// cannot be read or does not generate a finite group.
+    pub fn from_hall(symbol: &str) -> Option<SpaceGroup> {
+        let hall = normalize_hall(symbol)?;
+        let operations = generate(&hall)?;
+        let lattice = hall.trim_start_matches('-').chars().next();
+        let entry = SPACE_GROUPS
+            .iter()
+            .find(|entry| entry.2 == hall)
+            .or_else(|| {
+                SPACE_GROUPS.iter().find(|entry| {
+                    entry.2.trim_start_matches('-').chars().next() == lattice
+                        && generate(entry.2).is_some_and(|other| {
+                            other.len() == operations.len()
+                                && other.iter().all(|op| operations.contains(op))
+                        })
+                })
+            });
+        Some(SpaceGroup {
+            number: entry.map(|entry| entry.0),
+            hermann_mauguin: entry.map(|entry| entry.1),
+            hall,
+            operations,
+        })
+    }
+
+    /// Look up a Hermann–Mauguin symbol, such as `P 21/c`, `P 1 21/n 1`,
+    /// `Fd-3m:2` or `R -3 :R`.
+    ///
+    /// Spaces and case are ignored, and monoclinic symbols may be full or
+    /// short. Without a setting after a colon, origin choice 1 and
+    /// hexagonal axes are used. The `e` glide symbols (`C m c e`) and their
+    /// older forms (`C m c a`) are both recognised. Returns `None` for
+    /// symbols not in the table, which include orthorhombic groups in
+    /// settings other than the standard one.
+    pub fn from_hermann_mauguin(symbol: &str) -> Option<SpaceGroup> {
+        let (base, setting) = split_setting(symbol);
+        let mut key = hermann_mauguin_key(base);
+        if let Some((_, old)) = E_GLIDES.iter().find(|(new, _)| *new == key) {
+            key = old.to_string();
+        }
+        let setting = setting.map(str::to_ascii_lowercase);
+        let entry = SPACE_GROUPS.iter().find(|(number, name, _)| {
+            let (name_base, name_setting) = split_setting(name);
+            let same_setting = match (&setting, name_setting) {
+                (Some(setting), Some(name_setting)) => setting.eq_ignore_ascii_case(name_setting),
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+            same_setting
+                && (hermann_mauguin_key(name_base) == key
+                    || (3..=15).contains(number) && short_key(name_base) == key)
+        })?;
+        entry_group(entry)
+    }
+
+    /// The standard setting of a space group by number, with origin choice
+    /// 1 and hexagonal axes where there is a choice.
+    pub fn from_number(number: u32) -> Option<SpaceGroup> {
+        SPACE_GROUPS
+            .iter()
+            .find(|entry| entry.0 == number)
+            .and_then(entry_group)
+    }
+}
+
+impl CifBlock {
+    /// The space group named by `_space_group_name_Hall`,
+    /// `_space_group_name_H-M_alt` or `_space_group_IT_number` (or their
+    /// older `_symmetry_` and mmCIF forms), tried in that order.
+    ///
+    /// This is for blocks without a symmetry operation loop, which this
+    /// method does not read (see [`CifBlock::symmetry_operations`]). A
+    /// Hall symbol gives the setting exactly; a Hermann–Mauguin symbol is
+    /// looked up with [`SpaceGroup::from_hermann_mauguin`]. The number is
+    /// only used when there is no Hermann–Mauguin symbol, since a symbol
+    /// missing from the table may name a setting other than the standard
+    /// one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse("data_x\n_space_group_name_H-M_alt 'C 1 2/c 1'\n").unwrap();
+    /// let group = doc.blocks[0].space_group().unwrap();
+    /// assert_eq!(group.number, Some(15));
+    /// assert_eq!(group.operations.len(), 8);
+    /// ```
+    pub fn space_group(&self) -> Option<SpaceGroup> {
+        let text = |tags: &[&str]| {
+            tags.iter()
+                .find_map(|tag| self.get_item(tag).and_then(value_text))
+        };
+        if let Some(group) = text(&HALL_TAGS).and_then(|hall| SpaceGroup::from_hall(&hall)) {
+            return Some(group);
+        }
+        if let Some(symbol) = text(&HERMANN_MAUGUIN_TAGS) {
+            return SpaceGroup::from_hermann_mauguin(&symbol);
+        }
+        let number = NUMBER_TAGS
+            .iter()
+            .find_map(|tag| self.get_item(tag)?.as_numeric())?;
+        if number.fract() != 0.0 || number < 1.0 {
+            return None;
+        }
+        SpaceGroup::from_number(number as u32)
+    }
+}
+
+const HALL_TAGS: [&str; 4] = [
+    "_space_group_name_Hall",
+    "_space_group.name_Hall",
+    "_symmetry_space_group_name_Hall",
+    "_symmetry.space_group_name_Hall",
+];
+
+const HERMANN_MAUGUIN_TAGS: [&str; 4] = [
+    "_space_group_name_H-M_alt",
+    "_space_group.name_H-M_alt",
+    "_symmetry_space_group_name_H-M",
+    "_symmetry.space_group_name_H-M",
+];
+
+const NUMBER_TAGS: [&str; 4] = [
+    "_space_group_IT_number",
+    "_space_group.IT_number",
+    "_symmetry_Int_Tables_number",
+    "_symmetry.Int_Tables_number",
+];
+
+fn entry_group(entry: &(u32, &'static str, &'static str)) -> Option<SpaceGroup> {
+    Some(SpaceGroup {
+        number: Some(entry.0),
+        hermann_mauguin: Some(entry.1),
+        hall: entry.2.to_string(),
+        operations: generate(entry.2)?,
+    })
+}
+
+/// Split `F d -3 m :2` into the symbol and the setting
+fn split_setting(symbol: &str) -> (&str, Option<&str>) {
+    match symbol.split_once(':') {
+        Some((base, setting)) => (base, Some(setting.trim())),
+        None => (symbol, None),
+    }
+}
+
+/// A Hermann–Mauguin symbol without spaces or underscores, in lower case
+fn hermann_mauguin_key(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The short form of a full monoclinic symbol: `P 1 21/c 1` to `p21/c`
+fn short_key(symbol: &str) -> String {
+    let mut words = symbol.split_whitespace();
+    let lattice = words.next().unwrap_or_default();
+    let axes: String = words.filter(|word| *word != "1").collect();
+    hermann_mauguin_key(&format!("{lattice}{axes}"))
+}
+
+/// Collapse the spaces of a Hall symbol and upper-case its lattice letter
+fn normalize_hall(symbol: &str) -> Option<String> {
+    let mut words = symbol.split_whitespace();
+    let lattice = words.next()?.to_ascii_uppercase();
+    Some(
+        std::iter::once(lattice)
+            .chain(words.map(str::to_string))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// An operation with its translation in twelfths of the cell edges
+type Op = ([[i32; 3]; 3], [i32; 3]);
+
+const IDENTITY: [[i32; 3]; 3] = [[1, 0, 0], [0, 1, 0], [0, 0, 1]];
+
+/// The largest space group, `F m -3 m`, has 192 operations
+const MAX_OPERATIONS: usize = 192;
+
+/// Generate the operations of a Hall symbol
+fn generate(hall: &str) -> Option<Vec<SymOp>> {
+    let (symbols, shift) = match hall.split_once('(') {
+        Some((symbols, shift)) => (symbols, Some(origin_shift(shift)?)),
+        None => (hall, None),
+    };
+    let mut words = symbols
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|word| !word.is_empty());
+    let lattice = words.next()?;
+    let (centric, lattice) = match lattice.strip_prefix('-') {
+        Some(lattice) => (true, lattice),
+        None => (false, lattice),
+    };
+    let centring = centring_vectors(lattice)?;
+
+    let mut generators: Vec<Op> = centring[1..].iter().map(|&t| (IDENTITY, t)).collect();
+    if centric {
+        generators.push((negate(&IDENTITY), [0; 3]));
+    }
+    let mut previous = None;
+    for (index, word) in words.enumerate() {
+        let (op, order, axis) = matrix_symbol(word, index, previous)?;
+        generators.push(op);
+        previous = Some((order, axis));
+    }
+    let group = close(&generators)?;
+
+    // Operations that differ only by a centring translation are listed
+    // once, then repeated with each translation, as International Tables
+    // and symmetry operation loops list them
+    let mut representatives: Vec<Op> = Vec::new();
+    for op in &group {
+        let repeated = representatives
+            .iter()
+            .any(|rep| rep.0 == op.0 && centring.iter().any(|t| reduce(add(rep.1, *t)) == op.1));
+        if !repeated {
+            representatives.push(*op);
+        }
+    }
+    let operations = centring
+        .iter()
+        .flat_map(|t| {
+            representatives
+                .iter()
+                .map(move |(rotation, translation)| (*rotation, reduce(add(*translation, *t))))
+        })
+        .map(|(rotation, translation)| {
+            // A change of origin by v turns (W, w) into (W, w + v - Wv)
+            let translation = match shift {
+                Some(v) => reduce(add(add(translation, v), negate_vector(apply(&rotation, v)))),
+                None => translation,
+            };
+            SymOp {
+                rotation,
+                translation: translation.map(|t| t as f64 / 12.0),
+            }
+        })
+        .collect();
+    Some(operations)
+}
+
+/// All products of the generators, identity first
+fn close(generators: &[Op]) -> Option<Vec<Op>> {
+    let mut group = vec![(IDENTITY, [0; 3])];
+    let mut next = 0;
+    while next < group.len() {
+        let a = group[next];
+        for b in generators {
+            let product = (multiply(&a.0, &b.0), reduce(add(apply(&a.0, b.1), a.1)));
+            if !group.contains(&product) {
+                if group.len() == MAX_OPERATIONS {
+                    return None;
+                }
+                group.push(product);
+            }
+        }
+        next += 1;
+    }
+    Some(group)
+}
+
+/// Centring translations of a lattice symbol, the zero vector first
+fn centring_vectors(lattice: &str) -> Option<Vec<[i32; 3]>> {
+    let vectors: &[[i32; 3]] = match lattice {
+        "P" => &[],
+        "A" => &[[0, 6, 6]],
+        "B" => &[[6, 0, 6]],
+        "C" => &[[6, 6, 0]],
+        "I" => &[[6, 6, 6]],
+        "R" => &[[8, 4, 4], [4, 8, 8]],
+        "S" => &[[4, 4, 8], [8, 8, 4]],
+        "T" => &[[4, 8, 4], [8, 4, 8]],
+        "F" => &[[0, 6, 6], [6, 0, 6], [6, 6, 0]],
+        _ => return None,
+    };
+    Some(
+        std::iter::once([0; 3])
+            .chain(vectors.iter().copied())
+            .collect(),
+    )
+}
+
+/// Read one matrix symbol of a Hall symbol, such as `2ybc`, `-4`, `31` or
+/// `2"`: the operation, its order and its axis.
+///
+/// `index` is the position of the symbol, and `previous` the order and axis
+/// of the symbol before it, which decide the axis when none is given.
+fn matrix_symbol(
+    word: &str,
+    index: usize,
+    previous: Option<(u32, char)>,
+) -> Option<(Op, u32, char)> {
+    let (improper, word) = match word.strip_prefix('-') {
+        Some(word) => (true, word),
+        None => (false, word),
+    };
+    let mut chars = word.chars();
+    let order = chars.next()?.to_digit(10)?;
+    let mut axis = None;
+    let mut screw = 0;
+    let mut translation = [0; 3];
+    for c in chars {
+        match c {
+            'x' | 'y' | 'z' | '\'' | '"' | '*' => axis = Some(c),
+            '1'..='5' => screw = c.to_digit(10)?,
+            _ => translation = add(translation, translation_symbol(c)?),
+        }
+    }
+
+    let axis = match axis {
+        Some(axis) => axis,
+        None => match (index, order, previous) {
+            (_, 1, _) | (0, _, _) => 'z',
+            (1, 2, Some((2 | 4, _))) => 'x',
+            (1, 2, Some((3 | 6, _))) => '\'',
+            (2, 3, _) => '*',
+            _ => return None,
+        },
+    };
+    let reference = match previous {
+        Some((_, axis @ ('x' | 'y'))) => axis,
+        _ => 'z',
+    };
+    let mut rotation = rotation_matrix(order, axis, reference)?;
+    if improper {
+        rotation = negate(&rotation);
+    }
+    if screw > 0 {
+        if screw >= order {
+            return None;
+        }
+        let along = match axis {
+            'x' => 0,
+            'y' => 1,
+            'z' => 2,
+            _ => return None,
+        };
+        translation[along] += (12 * screw / order) as i32;
+    }
+    Some(((rotation, reduce(translation)), order, axis))
+}
+
+/// Translation of a Hall translation symbol, in twelfths
+fn translation_symbol(symbol: char) -> Option<[i32; 3]> {
+    Some(match symbol {
+        'a' => [6, 0, 0],
+        'b' => [0, 6, 0],
+        'c' => [0, 0, 6],
+        'n' => [6, 6, 6],
+        'u' => [3, 0, 0],
+        'v' => [0, 3, 0],
+        'w' => [0, 0, 3],
+        'd' => [3, 3, 3],
+        _ => return None,
+    })
+}
+
+/// Rotation of the given order about an axis; `'` and `"` are the 2-fold
+/// axes along the face diagonals perpendicular to `reference`
+fn rotation_matrix(order: u32, axis: char, reference: char) -> Option<[[i32; 3]; 3]> {
+    Some(match (order, axis, reference) {
+        (1, _, _) => IDENTITY,
+        (2, 'x', _) => [[1, 0, 0], [0, -1, 0], [0, 0, -1]],
+        (2, 'y', _) => [[-1, 0, 0], [0, 1, 0], [0, 0, -1]],
+        (2, 'z', _) => [[-1, 0, 0], [0, -1, 0], [0, 0, 1]],
+        (3, 'x', _) => [[1, 0, 0], [0, 0, -1], [0, 1, -1]],
+        (3, 'y', _) => [[-1, 0, 1], [0, 1, 0], [-1, 0, 0]],
+        (3, 'z', _) => [[0, -1, 0], [1, -1, 0], [0, 0, 1]],
+        (4, 'x', _) => [[1, 0, 0], [0, 0, -1], [0, 1, 0]],
+        (4, 'y', _) => [[0, 0, 1], [0, 1, 0], [-1, 0, 0]],
+        (4, 'z', _) => [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
+        (6, 'x', _) => [[1, 0, 0], [0, 1, -1], [0, 1, 0]],
+        (6, 'y', _) => [[0, 0, 1], [0, 1, 0], [-1, 0, 1]],
+        (6, 'z', _) => [[1, -1, 0], [1, 0, 0], [0, 0, 1]],
+        (2, '\'', 'x') => [[-1, 0, 0], [0, 0, -1], [0, -1, 0]],
+        (2, '\'', 'y') => [[0, 0, -1], [0, -1, 0], [-1, 0, 0]],
+        (2, '\'', _) => [[0, -1, 0], [-1, 0, 0], [0, 0, -1]],
+        (2, '"', 'x') => [[-1, 0, 0], [0, 0, 1], [0, 1, 0]],
+        (2, '"', 'y') => [[0, 0, 1], [0, -1, 0], [1, 0, 0]],
+        (2, '"', _) => [[0, 1, 0], [1, 0, 0], [0, 0, -1]],
+        (3, '*', _) => [[0, 0, 1], [1, 0, 0], [0, 1, 0]],
+        _ => return None,
+    })
+}
+
+/// Read the origin shift `0 0 1)` after the opening parenthesis
+fn origin_shift(text: &str) -> Option<[i32; 3]> {
+    let text = text.trim().strip_suffix(')')?;
+    let parts: Vec<i32> = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    <[i32; 3]>::try_from(parts).ok()
+}
+
+fn multiply(a: &[[i32; 3]; 3], b: &[[i32; 3]; 3]) -> [[i32; 3]; 3] {
+    let mut product = [[0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+fn apply(m: &[[i32; 3]; 3], v: [i32; 3]) -> [i32; 3] {
+    [0, 1, 2].map(|i| (0..3).map(|k| m[i][k] * v[k]).sum())
+}
+
+fn add(a: [i32; 3], b: [i32; 3]) -> [i32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn negate(m: &[[i32; 3]; 3]) -> [[i32; 3]; 3] {
+    m.map(|row| row.map(|x| -x))
+}
+
+fn negate_vector(v: [i32; 3]) -> [i32; 3] {
+    v.map(|x| -x)
+}
+
+fn reduce(v: [i32; 3]) -> [i32; 3] {
+    v.map(|x| x.rem_euclid(12))
+}
+
+/// New `e` glide symbols and the older symbols the table uses, as keys
+const E_GLIDES: [(&str, &str); 5] = [
+    ("aem2", "abm2"),
+    ("aea2", "aba2"),
+    ("cmce", "cmca"),
+    ("cmme", "cmma"),
+    ("ccce", "ccca"),
+];
+
+/// Number, Hermann–Mauguin symbol and Hall symbol of each setting. The
+/// standard setting of each group comes first: unique axis b and cell
+/// choice 1, origin choice 1, hexagonal axes.
+#[rustfmt::skip]
+const SPACE_GROUPS: &[(u32, &str, &str)] = &[
+    (1, "P 1", "P 1"),
+    (2, "P -1", "-P 1"),
+    (3, "P 1 2 1", "P 2y"),
+    (3, "P 1 1 2", "P 2"),
+    (4, "P 1 21 1", "P 2yb"),
+    (4, "P 1 1 21", "P 2c"),
+    (5, "C 1 2 1", "C 2y"),
+    (5, "A 1 2 1", "A 2y"),
+    (5, "I 1 2 1", "I 2y"),
+    (5, "A 1 1 2", "A 2"),
+    (5, "B 1 1 2", "B 2"),
+    (5, "I 1 1 2", "I 2"),
+    (6, "P 1 m 1", "P -2y"),
+    (6, "P 1 1 m", "P -2"),
+    (7, "P 1 c 1", "P -2yc"),
+    (7, "P 1 n 1", "P -2yac"),
+    (7, "P 1 a 1", "P -2ya"),
+    (7, "P 1 1 a", "P -2a"),
+    (7, "P 1 1 n", "P -2ab"),
+    (7, "P 1 1 b", "P -2b"),
+    (8, "C 1 m 1", "C -2y"),
+    (8, "A 1 m 1", "A -2y"),
+    (8, "I 1 m 1", "I -2y"),
+    (8, "A 1 1 m", "A -2"),
+    (8, "B 1 1 m", "B -2"),
+    (8, "I 1 1 m", "I -2"),
+    (9, "C 1 c 1", "C -2yc"),
+    (9, "A 1 n 1", "A -2yab"),
+    (9, "I 1 a 1", "I -2ya"),
+    (9, "A 1 a 1", "A -2ya"),
+    (9, "C 1 n 1", "C -2yac"),
+    (9, "I 1 c 1", "I -2yc"),
+    (9, "A 1 1 a", "A -2a"),
+    (9, "B 1 1 n", "B -2ab"),
+    (9, "I 1 1 b", "I -2b"),
+    (10, "P 1 2/m 1", "-P 2y"),
+    (10, "P 1 1 2/m", "-P 2"),
+    (11, "P 1 21/m 1", "-P 2yb"),
+    (11, "P 1 1 21/m", "-P 2c"),
+    (12, "C 1 2/m 1", "-C 2y"),
+    (12, "A 1 2/m 1", "-A 2y"),
+    (12, "I 1 2/m 1", "-I 2y"),
+    (12, "A 1 1 2/m", "-A 2"),
+    (12, "B 1 1 2/m", "-B 2"),
+    (12, "I 1 1 2/m", "-I 2"),
+    (13, "P 1 2/c 1", "-P 2yc"),
+    (13, "P 1 2/n 1", "-P 2yac"),
+    (13, "P 1 2/a 1", "-P 2ya"),
+    (13, "P 1 1 2/a", "-P 2a"),
+    (13, "P 1 1 2/n", "-P 2ab"),
+    (13, "P 1 1 2/b", "-P 2b"),
+    (14, "P 1 21/c 1", "-P 2ybc"),
+    (14, "P 1 21/n 1", "-P 2yn"),
+    (14, "P 1 21/a 1", "-P 2yab"),
+    (14, "P 1 1 21/a", "-P 2ac"),
+    (14, "P 1 1 21/n", "-P 2n"),
+    (14, "P 1 1 21/b", "-P 2bc"),
+    (15, "C 1 2/c 1", "-C 2yc"),
+    (15, "A 1 2/n 1", "-A 2yab"),
+    (15, "I 1 2/a 1", "-I 2ya"),
+    (15, "A 1 2/a 1", "-A 2ya"),
+    (15, "C 1 2/n 1", "-C 2yac"),
+    (15, "I 1 2/c 1", "-I 2yc"),
+    (15, "A 1 1 2/a", "-A 2a"),
+    (15, "B 1 1 2/n", "-B 2ab"),
+    (15, "I 1 1 2/b", "-I 2b"),
+    (16, "P 2 2 2", "P 2 2"),
+    (17, "P 2 2 21", "P 2c 2"),
+    (18, "P 21 21 2", "P 2 2ab"),
+    (19, "P 21 21 21", "P 2ac 2ab"),
+    (20, "C 2 2 21", "C 2c 2"),
+    (21, "C 2 2 2", "C 2 2"),
+    (22, "F 2 2 2", "F 2 2"),
+    (23, "I 2 2 2", "I 2 2"),
+    (24, "I 21 21 21", "I 2b 2c"),
+    (25, "P m m 2", "P 2 -2"),
+    (26, "P m c 21", "P 2c -2"),
+    (27, "P c c 2", "P 2 -2c"),
+    (28, "P m a 2", "P 2 -2a"),
+    (29, "P c a 21", "P 2c -2ac"),
+    (30, "P n c 2", "P 2 -2bc"),
+    (31, "P m n 21", "P 2ac -2"),
+    (32, "P b a 2", "P 2 -2ab"),
+    (33, "P n a 21", "P 2c -2n"),
+    (34, "P n n 2", "P 2 -2n"),
+    (35, "C m m 2", "C 2 -2"),
+    (36, "C m c 21", "C 2c -2"),
+    (37, "C c c 2", "C 2 -2c"),
+    (38, "A m m 2", "A 2 -2"),
+    (39, "A b m 2", "A 2 -2c"),
+    (40, "A m a 2", "A 2 -2a"),
+    (41, "A b a 2", "A 2 -2ac"),
+    (42, "F m m 2", "F 2 -2"),
+    (43, "F d d 2", "F 2 -2d"),
+    (44, "I m m 2", "I 2 -2"),
+    (45, "I b a 2", "I 2 -2c"),
+    (46, "I m a 2", "I 2 -2a"),
+    (47, "P m m m", "-P 2 2"),
+    (48, "P n n n :1", "P 2 2 -1n"),
+    (48, "P n n n :2", "-P 2ab 2bc"),
+    (49, "P c c m", "-P 2 2c"),
+    (50, "P b a n :1", "P 2 2 -1ab"),
+    (50, "P b a n :2", "-P 2ab 2b"),
+    (51, "P m m a", "-P 2a 2a"),
+    (52, "P n n a", "-P 2a 2bc"),
+    (53, "P m n a", "-P 2ac 2"),
+    (54, "P c c a", "-P 2a 2ac"),
+    (55, "P b a m", "-P 2 2ab"),
+    (56, "P c c n", "-P 2ab 2ac"),
+    (57, "P b c m", "-P 2c 2b"),
+    (58, "P n n m", "-P 2 2n"),
+    (59, "P m m n :1", "P 2 2ab -1ab"),
+    (59, "P m m n :2", "-P 2ab 2a"),
+    (60, "P b c n", "-P 2n 2ab"),
+    (61, "P b c a", "-P 2ac 2ab"),
+    (62, "P n m a", "-P 2ac 2n"),
+    (63, "C m c m", "-C 2c 2"),
+    (64, "C m c a", "-C 2bc 2"),
+    (65, "C m m m", "-C 2 2"),
+    (66, "C c c m", "-C 2 2c"),
+    (67, "C m m a", "-C 2b 2"),
+    (68, "C c c a :1", "C 2 2 -1bc"),
+    (68, "C c c a :2", "-C 2b 2bc"),
+    (69, "F m m m", "-F 2 2"),
+    (70, "F d d d :1", "F 2 2 -1d"),
+    (70, "F d d d :2", "-F 2uv 2vw"),
+    (71, "I m m m", "-I 2 2"),
+    (72, "I b a m", "-I 2 2c"),
+    (73, "I b c a", "-I 2b 2c"),
+    (74, "I m m a", "-I 2b 2"),
+    (75, "P 4", "P 4"),
+    (76, "P 41", "P 4w"),
+    (77, "P 42", "P 4c"),
+    (78, "P 43", "P 4cw"),
+    (79, "I 4", "I 4"),
+    (80, "I 41", "I 4bw"),
+    (81, "P -4", "P -4"),
+    (82, "I -4", "I -4"),
+    (83, "P 4/m", "-P 4"),
+    (84, "P 42/m", "-P 4c"),
+    (85, "P 4/n :1", "P 4ab -1ab"),
+    (85, "P 4/n :2", "-P 4a"),
+    (86, "P 42/n :1", "P 4n -1n"),
+    (86, "P 42/n :2", "-P 4bc"),
+    (87, "I 4/m", "-I 4"),
+    (88, "I 41/a :1", "I 4bw -1bw"),
+    (88, "I 41/a :2", "-I 4ad"),
+    (89, "P 4 2 2", "P 4 2"),
+    (90, "P 4 21 2", "P 4ab 2ab"),
+    (91, "P 41 2 2", "P 4w 2c"),
+    (92, "P 41 21 2", "P 4abw 2nw"),
+    (93, "P 42 2 2", "P 4c 2"),
+    (94, "P 42 21 2", "P 4n 2n"),
+    (95, "P 43 2 2", "P 4cw 2c"),
+    (96, "P 43 21 2", "P 4nw 2abw"),
+    (97, "I 4 2 2", "I 4 2"),
+    (98, "I 41 2 2", "I 4bw 2bw"),
+    (99, "P 4 m m", "P 4 -2"),
+    (100, "P 4 b m", "P 4 -2ab"),
+    (101, "P 42 c m", "P 4c -2c"),
+    (102, "P 42 n m", "P 4n -2n"),
+    (103, "P 4 c c", "P 4 -2c"),
+    (104, "P 4 n c", "P 4 -2n"),
+    (105, "P 42 m c", "P 4c -2"),
+    (106, "P 42 b c", "P 4c -2ab"),
+    (107, "I 4 m m", "I 4 -2"),
+    (108, "I 4 c m", "I 4 -2c"),
+    (109, "I 41 m d", "I 4bw -2"),
+    (110, "I 41 c d", "I 4bw -2c"),
+    (111, "P -4 2 m", "P -4 2"),
+    (112, "P -4 2 c", "P -4 2c"),
+    (113, "P -4 21 m", "P -4 2ab"),
+    (114, "P -4 21 c", "P -4 2n"),
+    (115, "P -4 m 2", "P -4 -2"),
+    (116, "P -4 c 2", "P -4 -2c"),
+    (117, "P -4 b 2", "P -4 -2ab"),
+    (118, "P -4 n 2", "P -4 -2n"),
+    (119, "I -4 m 2", "I -4 -2"),
+    (120, "I -4 c 2", "I -4 -2c"),
+    (121, "I -4 2 m", "I -4 2"),
+    (122, "I -4 2 d", "I -4 2bw"),
+    (123, "P 4/m m m", "-P 4 2"),
+    (124, "P 4/m c c", "-P 4 2c"),
+    (125, "P 4/n b m :1", "P 4 2 -1ab"),
+    (125, "P 4/n b m :2", "-P 4a 2b"),
+    (126, "P 4/n n c :1", "P 4 2 -1n"),
+    (126, "P 4/n n c :2", "-P 4a 2bc"),
+    (127, "P 4/m b m", "-P 4 2ab"),
+    (128, "P 4/m n c", "-P 4 2n"),
+    (129, "P 4/n m m :1", "P 4ab 2ab -1ab"),
+    (129, "P 4/n m m :2", "-P 4a 2a"),
+    (130, "P 4/n c c :1", "P 4ab 2n -1ab"),
+    (130, "P 4/n c c :2", "-P 4a 2ac"),
+    (131, "P 42/m m c", "-P 4c 2"),
+    (132, "P 42/m c m", "-P 4c 2c"),
+    (133, "P 42/n b c :1", "P 4n 2c -1n"),
+    (133, "P 42/n b c :2", "-P 4ac 2b"),
+    (134, "P 42/n n m :1", "P 4n 2 -1n"),
+    (134, "P 42/n n m :2", "-P 4ac 2bc"),
+    (135, "P 42/m b c", "-P 4c 2ab"),
+    (136, "P 42/m n m", "-P 4n 2n"),
+    (137, "P 42/n m c :1", "P 4n 2n -1n"),
+    (137, "P 42/n m c :2", "-P 4ac 2a"),
+    (138, "P 42/n c m :1", "P 4n 2ab -1n"),
+    (138, "P 42/n c m :2", "-P 4ac 2ac"),
+    (139, "I 4/m m m", "-I 4 2"),
+    (140, "I 4/m c m", "-I 4 2c"),
+    (141, "I 41/a m d :1", "I 4bw 2bw -1bw"),
+    (141, "I 41/a m d :2", "-I 4bd 2"),
+    (142, "I 41/a c d :1", "I 4bw 2aw -1bw"),
+    (142, "I 41/a c d :2", "-I 4bd 2c"),
+    (143, "P 3", "P 3"),
+    (144, "P 31", "P 31"),
+    (145, "P 32", "P 32"),
+    (146, "R 3 :H", "R 3"),
+    (146, "R 3 :R", "P 3*"),
+    (147, "P -3", "-P 3"),
+    (148, "R -3 :H", "-R 3"),
+    (148, "R -3 :R", "-P 3*"),
+    (149, "P 3 1 2", "P 3 2"),
+    (150, "P 3 2 1", "P 3 2\""),
+    (151, "P 31 1 2", "P 31 2c (0 0 1)"),
+    (152, "P 31 2 1", "P 31 2\""),
+    (153, "P 32 1 2", "P 32 2c (0 0 -1)"),
+    (154, "P 32 2 1", "P 32 2\""),
+    (155, "R 3 2 :H", "R 3 2\""),
+    (155, "R 3 2 :R", "P 3* 2"),
+    (156, "P 3 m 1", "P 3 -2\""),
+    (157, "P 3 1 m", "P 3 -2"),
+    (158, "P 3 c 1", "P 3 -2\"c"),
+    (159, "P 3 1 c", "P 3 -2c"),
+    (160, "R 3 m :H", "R 3 -2\""),
+    (160, "R 3 m :R", "P 3* -2"),
+    (161, "R 3 c :H", "R 3 -2\"c"),
+    (161, "R 3 c :R", "P 3* -2n"),
+    (162, "P -3 1 m", "-P 3 2"),
+    (163, "P -3 1 c", "-P 3 2c"),
+    (164, "P -3 m 1", "-P 3 2\""),
+    (165, "P -3 c 1", "-P 3 2\"c"),
+    (166, "R -3 m :H", "-R 3 2\""),
+    (166, "R -3 m :R", "-P 3* 2"),
+    (167, "R -3 c :H", "-R 3 2\"c"),
+    (167, "R -3 c :R", "-P 3* 2n"),
+    (168, "P 6", "P 6"),
+    (169, "P 61", "P 61"),
+    (170, "P 65", "P 65"),
+    (171, "P 62", "P 62"),
+    (172, "P 64", "P 64"),
+    (173, "P 63", "P 6c"),
+    (174, "P -6", "P -6"),
+    (175, "P 6/m", "-P 6"),
+    (176, "P 63/m", "-P 6c"),
+    (177, "P 6 2 2", "P 6 2"),
+    (178, "P 61 2 2", "P 61 2 (0 0 -1)"),
+    (179, "P 65 2 2", "P 65 2 (0 0 1)"),
+    (180, "P 62 2 2", "P 62 2c (0 0 1)"),
+    (181, "P 64 2 2", "P 64 2c (0 0 -1)"),
+    (182, "P 63 2 2", "P 6c 2c"),
+    (183, "P 6 m m", "P 6 -2"),
+    (184, "P 6 c c", "P 6 -2c"),
+    (185, "P 63 c m", "P 6c -2"),
+    (186, "P 63 m c", "P 6c -2c"),
+    (187, "P -6 m 2", "P -6 2"),
+    (188, "P -6 c 2", "P -6c 2"),
+    (189, "P -6 2 m", "P -6 -2"),
+    (190, "P -6 2 c", "P -6c -2c"),
+    (191, "P 6/m m m", "-P 6 2"),
+    (192, "P 6/m c c", "-P 6 2c"),
+    (193, "P 63/m c m", "-P 6c 2"),
+    (194, "P 63/m m c", "-P 6c 2c"),
+    (195, "P 2 3", "P 2 2 3"),
+    (196, "F 2 3", "F 2 2 3"),
+    (197, "I 2 3", "I 2 2 3"),
+    (198, "P 21 3", "P 2ac 2ab 3"),
+    (199, "I 21 3", "I 2b 2c 3"),
+    (200, "P m -3", "-P 2 2 3"),
+    (201, "P n -3 :1", "P 2 2 3 -1n"),
+    (201, "P n -3 :2", "-P 2ab 2bc 3"),
+    (202, "F m -3", "-F 2 2 3"),
+    (203, "F d -3 :1", "F 2 2 3 -1d"),
+    (203, "F d -3 :2", "-F 2uv 2vw 3"),
+    (204, "I m -3", "-I 2 2 3"),
+    (205, "P a -3", "-P 2ac 2ab 3"),
+    (206, "I a -3", "-I 2b 2c 3"),
+    (207, "P 4 3 2", "P 4 2 3"),
+    (208, "P 42 3 2", "P 4n 2 3"),
+    (209, "F 4 3 2", "F 4 2 3"),
+    (210, "F 41 3 2", "F 4d 2 3"),
+    (211, "I 4 3 2", "I 4 2 3"),
+    (212, "P 43 3 2", "P 4acd 2ab 3"),
+    (213, "P 41 3 2", "P 4bd 2ab 3"),
+    (214, "I 41 3 2", "I 4bd 2c 3"),
+    (215, "P -4 3 m", "P -4 2 3"),
+    (216, "F -4 3 m", "F -4 2 3"),
+    (217, "I -4 3 m", "I -4 2 3"),
+    (218, "P -4 3 n", "P -4n 2 3"),
+    (219, "F -4 3 c", "F -4a 2 3"),
+    (220, "I -4 3 d", "I -4bd 2c 3"),
+    (221, "P m -3 m", "-P 4 2 3"),
+    (222, "P n -3 n :1", "P 4 2 3 -1n"),
+    (222, "P n -3 n :2", "-P 4a 2bc 3"),
+    (223, "P m -3 n", "-P 4n 2 3"),
+    (224, "P n -3 m :1", "P 4n 2 3 -1n"),
+    (224, "P n -3 m :2", "-P 4bc 2bc 3"),
+    (225, "F m -3 m", "-F 4 2 3"),
+    (226, "F m -3 c", "-F 4a 2 3"),
+    (227, "F d -3 m :1", "F 4d 2 3 -1d"),
+    (227, "F d -3 m :2", "-F 4vw 2vw 3"),
+    (228, "F d -3 c :1", "F 4d 2 3 -1ad"),
+    (228, "F d -3 c :2", "-F 4ud 2vw 3"),
+    (229, "I m -3 m", "-I 4 2 3"),
+    (230, "I a -3 d", "-I 4bd 2c 3"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_entry_looks_itself_up() {
+        for (number, symbol, hall) in SPACE_GROUPS {
+            let group = SpaceGroup::from_hermann_mauguin(symbol).unwrap();
+            assert_eq!(group.number, Some(*number), "{symbol}");
+            assert_eq!(group.hall, *hall, "{symbol}");
+            assert_eq!(SpaceGroup::from_hall(hall).unwrap(), group, "{hall}");
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Site-by-site comparison of two versions of a structure.
+//!
+//! [`compare_structures`] pairs the atom sites of two structures, by label
+//! or by proximity, and reports how far each matched atom moved (in Å,
+//! allowing for symmetry and lattice translations), how its displacement
+//! parameter and occupancy changed, which atoms are in only one structure,
+//! and the RMSD over the matched atoms.
+//!
+//! Both structures are first reduced to one representative per set of
+//! symmetry-equivalent sites, using the operations of whichever lists more
+//! of them, so a structure expanded to P1 compares cleanly with its
+//! asymmetric unit.
+//!
+//! In polar space groups the origin along the polar axes is arbitrary, and
+//! two refinements of the same crystal may have fixed it differently. With
+//! [`MatchOptions::origin_shift`] the shift along those axes that best
+//! superposes the structures is found and applied before comparing.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::structure::{compare_structures, MatchOptions};
+//! use cif_parser::Document;
+//!
+//! let cif = |x: f64| {
+//!     format!(
+//!         "data_x\n_cell_length_a 10\n_cell_length_b 10\n_cell_length_c 10\n\
+//!          _cell_angle_alpha 90\n_cell_angle_beta 90\n_cell_angle_gamma 90\n\
+//!          loop_\n_atom_site_label\n_atom_site_fract_x\n_atom_site_fract_y\n_atom_site_fract_z\n\
+//!          C1 {x} 0.2 0.3\nO1 0.5 0.5 0.5\n"
+//!     )
+//! };
+//! let a = Document::parse(&cif(0.10)).unwrap().blocks[0].structure().unwrap();
+//! let b = Document::parse(&cif(0.12)).unwrap().blocks[0].structure().unwrap();
+//! let comparison = compare_structures(&a, &b, &MatchOptions::new());
+//!
+//! assert_eq!(comparison.sites.len(), 2);
+//! assert!((comparison.sites[0].distance - 0.2).abs() < 1e-9);
+//! assert!((comparison.rmsd - 0.02f64.sqrt()).abs() < 1e-9);
+//! ```
+
+use super::{AtomSite, Structure};
+use crate::cell::UnitCell;
+use crate::element::Element;
+use crate::symmetry::SymOp;
+
+/// How [`compare_structures`] pairs the sites of the two structures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SiteMatching {
+    /// Sites with the same label
+    Label,
+    /// Closest sites of the same element, at most `max_distance` Å apart;
+    /// for structures whose labels were changed
+    Proximity {
+        /// Largest distance between matched sites, in Å
+        max_distance: f64,
+    },
+}
+
+/// Options for [`compare_structures`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchOptions {
+    /// How sites are paired
+    pub matching: SiteMatching,
+    /// Fit the origin along polar axes before comparing
+    pub origin_shift: bool,
+    /// Distance in Å under which two symmetry-equivalent sites of one
+    /// structure are the same site
+    pub tolerance: f64,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions {
+            matching: SiteMatching::Label,
+            origin_shift: false,
+            tolerance: 0.05,
+        }
+    }
+}
+
+impl MatchOptions {
+    /// Match by label, without an origin shift
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how sites are paired
+    pub fn matching(mut self, matching: SiteMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    /// Fit the origin along the polar axes of the space group, if any,
+    /// before comparing
+    pub fn origin_shift(mut self, shift: bool) -> Self {
+        self.origin_shift = shift;
+        self
+    }
+
+    /// Set the distance under which symmetry-equivalent sites coincide
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+/// How one atom differs between the two structures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteDifference {
+    /// Label in the first structure
+    pub label_a: String,
+    /// Label in the second structure
+    pub label_b: String,
+    /// Cartesian displacement from the first position to the nearest
+    /// equivalent of the second, in Å
+    pub displacement: [f64; 3],
+    /// Length of the displacement, in Å
+    pub distance: f64,
+    /// Change in U(iso or equiv), in Å², if both structures give one
+    pub u_iso: Option<f64>,
+    /// Change in occupancy
+    pub occupancy: f64,
+}
+
+/// The result of [`compare_structures`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureComparison {
+    /// Matched atoms, in the order of the first structure
+    pub sites: Vec<SiteDifference>,
+    /// Labels of atoms only in the first structure
+    pub only_in_a: Vec<String>,
+    /// Labels of atoms only in the second structure
+    pub only_in_b: Vec<String>,
+    /// Origin of the second structure relative to the first, in fractions
+    /// of the cell edges; zero unless fitted with
+    /// [`MatchOptions::origin_shift`]
+    pub origin_shift: [f64; 3],
+    /// Root-mean-square distance over the matched atoms, in Å
+    pub rmsd: f64,
+}
+
+/// Compare two versions of a structure atom by atom.
+///
+/// Positions are compared in the cell of `a`; see the
+/// [module documentation](self) for how sites are reduced and matched.
+pub fn compare_structures(
+    a: &Structure,
+    b: &Structure,
+    options: &MatchOptions,
+) -> StructureComparison {
+    let ops = if a.symmetry.len() >= b.symmetry.len() {
+        &a.symmetry
+    } else {
+        &b.symmetry
+    };
+    let context = Context {
+        cell: &a.cell,
+        ops,
+        polar: if options.origin_shift {
+            polar_projector(ops)
+        } else {
+            [[0.0; 3]; 3]
+        },
+    };
+    let sites_a = asymmetric_unit(&a.sites, &a.cell, ops, options.tolerance);
+    let sites_b = asymmetric_unit(&b.sites, &b.cell, ops, options.tolerance);
+
+    let (pairs, shift) = match options.matching {
+        SiteMatching::Label => {
+            let pairs: Vec<(usize, usize)> = sites_a
+                .iter()
+                .enumerate()
+                .filter_map(|(i, site)| {
+                    let j = sites_b.iter().position(|other| other.label == site.label)?;
+                    Some((i, j))
+                })
+                .collect();
+            let shift = context.fit_shift(&sites_a, &sites_b, &pairs, [0.0; 3]);
+            (pairs, shift)
+        }
+        SiteMatching::Proximity { max_distance } => {
+            context.match_by_proximity(&sites_a, &sites_b, max_distance)
+        }
+    };
+
+    let mut matched_a = vec![false; sites_a.len()];
+    let mut matched_b = vec![false; sites_b.len()];
+    let mut sites = Vec::with_capacity(pairs.len());
+    for &(i, j) in &pairs {
+        matched_a[i] = true;
+        matched_b[j] = true;
+        let (site_a, site_b) = (sites_a[i], sites_b[j]);
+        let delta = context.nearest(fract(site_a), fract(site_b), shift);
+        let displacement = a.cell.frac_to_cart(delta);
+        sites.push(SiteDifference {
+            label_a: site_a.label.clone(),
+            label_b: site_b.label.clone(),
+            displacement,
+            distance: norm(displacement),
+            u_iso: site_a.u_iso.zip(site_b.u_iso).map(|(ua, ub)| ub - ua),
+            occupancy: site_b.occupancy - site_a.occupancy,
+        });
+    }
+    let rmsd = if sites.is_empty() {
+        0.0
+    } else {
+        (sites.iter().map(|s| s.distance * s.distance).sum::<f64>() / sites.len() as f64).sqrt()
+    };
+    let unmatched = |sites: &[&AtomSite], matched: &[bool]| {
+        sites
+            .iter()
+            .zip(matched)
+            .filter(|(_, &matched)| !matched)
+            .map(|(site, _)| site.label.clone())
+            .collect()
+    };
+
+    StructureComparison {
+        only_in_a: unmatched(&sites_a, &matched_a),
+        only_in_b: unmatched(&sites_b, &matched_b),
+        sites,
+        // The shift applied to b undoes b's origin shift
+        origin_shift: shift.map(|t| {
+            let t = -t - (-t).round();
+            if t == 0.0 {
+                0.0
+            } else {
+                t
+            }
+        }),
+        rmsd,
+    }
+}
+
+/// Indices of matched sites in the two structures
+type Pairs = Vec<(usize, usize)>;
+
+/// What site pairs are compared in
+struct Context<'a> {
+    cell: &'a UnitCell,
+    ops: &'a [SymOp],
+    /// Projector onto the directions the origin may move along
+    polar: [[f64; 3]; 3],
+}
+
+impl Context<'_> {
+    /// Fractional vector from `from` to the nearest equivalent of `to`
+    /// moved by `shift`
+    fn nearest(&self, from: [f64; 3], to: [f64; 3], shift: [f64; 3]) -> [f64; 3] {
+        self.ops
+            .iter()
+            .map(|op| {
+                let image = op.apply(to);
+                let delta = [0, 1, 2].map(|i| image[i] + shift[i] - from[i]);
+                delta.map(|d| d - d.round())
+            })
+            .min_by(|x, y| {
+                let length = |d| self.cell.distance([0.0; 3], d);
+                length(*x).total_cmp(&length(*y))
+            })
+            .unwrap_or([0.0; 3])
+    }
+
+    /// Distance in Å between `from` and the nearest equivalent of `to`
+    fn distance(&self, from: [f64; 3], to: [f64; 3], shift: [f64; 3]) -> f64 {
+        self.cell.distance([0.0; 3], self.nearest(from, to, shift))
+    }
+
+    /// The allowed part of a fractional vector
+    fn project(&self, v: [f64; 3]) -> [f64; 3] {
+        crate::cell::apply(&self.polar, v)
+    }
+
+    /// Least-squares origin shift for matched pairs, starting from `shift`
+    fn fit_shift(
+        &self,
+        sites_a: &[&AtomSite],
+        sites_b: &[&AtomSite],
+        pairs: &[(usize, usize)],
+        mut shift: [f64; 3],
+    ) -> [f64; 3] {
+        if pairs.is_empty() || self.polar == [[0.0; 3]; 3] {
+            return shift;
+        }
+        // The nearest images depend on the shift, so refine it twice
+        for _ in 0..2 {
+            let mut mean = [0.0; 3];
+            for &(i, j) in pairs {
+                let delta = self.nearest(fract(sites_a[i]), fract(sites_b[j]), shift);
+                for k in 0..3 {
+                    mean[k] += delta[k] / pairs.len() as f64;
+                }
+            }
+            let correction = self.project(mean);
+            shift = [0, 1, 2].map(|k| shift[k] - correction[k]);
+        }
+        shift
+    }
+
+    /// Pair sites by proximity, trying every origin shift that puts the
+    /// first site of `a` on a site of `b` of its element
+    fn match_by_proximity(
+        &self,
+        sites_a: &[&AtomSite],
+        sites_b: &[&AtomSite],
+        max_distance: f64,
+    ) -> (Pairs, [f64; 3]) {
+        let mut shifts = vec![[0.0; 3]];
+        if let Some(first) = sites_a.first().filter(|_| self.polar != [[0.0; 3]; 3]) {
+            let element = first.element();
+            for site in sites_b.iter().filter(|s| s.element() == element) {
+                let delta = self.project(self.nearest(fract(first), fract(site), [0.0; 3]));
+                shifts.push(delta.map(|d| -d));
+            }
+        }
+
+        let score = |pairs: &[(usize, usize)], shift: [f64; 3]| {
+            let sum: f64 = pairs
+                .iter()
+                .map(|&(i, j)| {
+                    self.distance(fract(sites_a[i]), fract(sites_b[j]), shift)
+                        .powi(2)
+                })
+                .sum();
+            (pairs.len(), sum)
+        };
+        let mut best: Option<(Pairs, [f64; 3])> = None;
+        for shift in shifts {
+            let pairs = self.closest_pairs(sites_a, sites_b, shift, max_distance);
+            let shift = self.fit_shift(sites_a, sites_b, &pairs, shift);
+            let pairs = self.closest_pairs(sites_a, sites_b, shift, max_distance);
+            let better = match &best {
+                None => true,
+                Some((best_pairs, best_shift)) => {
+                    let (count, sum) = score(&pairs, shift);
+                    let (best_count, best_sum) = score(best_pairs, *best_shift);
+                    count > best_count || (count == best_count && sum < best_sum - 1e-12)
+                }
+            };
+            if better {
+                best = Some((pairs, shift));
+            }
+        }
+        let (mut pairs, shift) = best.unwrap_or_default();
+        pairs.sort_unstable();
+        (pairs, shift)
+    }
+
+    /// Greedily pair the closest sites of the same element first
+    fn closest_pairs(
+        &self,
+        sites_a: &[&AtomSite],
+        sites_b: &[&AtomSite],
+        shift: [f64; 3],
+        max_distance: f64,
+    ) -> Pairs {
+        let elements_b: Vec<Option<Element>> = sites_b.iter().map(|s| s.element()).collect();
+        let mut candidates = Vec::new();
+        for (i, site_a) in sites_a.iter().enumerate() {
+            let element = site_a.element();
+            for (j, site_b) in sites_b.iter().enumerate() {
+                if elements_b[j] != element {
+                    continue;
+                }
+                let distance = self.distance(fract(site_a), fract(site_b), shift);
+                if distance <= max_distance {
+                    candidates.push((distance, i, j));
+                }
+            }
+        }
+        candidates.sort_by(|x, y| x.0.total_cmp(&y.0).then((x.1, x.2).cmp(&(y.1, y.2))));
+
+        let mut used_a = vec![false; sites_a.len()];
+        let mut used_b = vec![false; sites_b.len()];
+        let mut pairs = Vec::new();
+        for (_, i, j) in candidates {
+            if !used_a[i] && !used_b[j] {
+                used_a[i] = true;
+                used_b[j] = true;
+                pairs.push((i, j));
+            }
+        }
+        pairs
+    }
+}
+
+/// Fractional coordinates of a site kept by [`asymmetric_unit`], which
+/// leaves out sites without them
+fn fract(site: &AtomSite) -> [f64; 3] {
+    site.fract.unwrap_or_default()
+}
+
+/// The first of each set of symmetry-equivalent sites of the same element,
+/// leaving out sites without fractional coordinates
+fn asymmetric_unit<'a>(
+    sites: &'a [AtomSite],
+    cell: &UnitCell,
+    ops: &[SymOp],
+    tolerance: f64,
+) -> Vec<&'a AtomSite> {
+    let context = Context {
+        cell,
+        ops,
+        polar: [[0.0; 3]; 3],
+    };
+    let mut kept: Vec<&AtomSite> = Vec::new();
+    for site in sites {
+        let Some(site_fract) = site.fract else {
+            continue;
+        };
+        let element = site.element();
+        let duplicate = kept.iter().any(|other| {
+            other.element() == element
+                && context.distance(site_fract, fract(other), [0.0; 3]) < tolerance
+        });
+        if !duplicate {
+            kept.push(site);
+        }
+    }
+    kept
+}
+
+/// Projector onto the vectors every rotation leaves unchanged: the mean of
+/// the rotation matrices. Zero unless the point group is polar.
+fn polar_projector(ops: &[SymOp]) -> [[f64; 3]; 3] {
+    let mut rotations: Vec<[[i32; 3]; 3]> = ops.iter().map(|op| op.rotation).collect();
+    rotations.sort_unstable();
+    rotations.dedup();
+    let n = rotations.len().max(1) as f64;
+    [0, 1, 2]
+        .map(|i| [0, 1, 2].map(|j| rotations.iter().map(|r| f64::from(r[i][j])).sum::<f64>() / n))
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(xyz: &[&str]) -> Vec<SymOp> {
+        xyz.iter().map(|op| SymOp::parse(op).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_polar_projector() {
+        // P 21: the origin floats along b
+        let p21 = polar_projector(&ops(&["x,y,z", "-x,y+1/2,-z"]));
+        assert_eq!(p21, [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]]);
+        // P -1 is not polar
+        assert_eq!(polar_projector(&ops(&["x,y,z", "-x,-y,-z"])), [[0.0; 3]; 3]);
+        // P 1: anywhere
+        let p1 = polar_projector(&ops(&["x,y,z"]));
+        assert_eq!(p1, [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    }
+}
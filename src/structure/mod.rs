@@ -0,0 +1,400 @@
+//! A crystal structure: the cell, the symmetry operations, and the atom sites.
+//!
+//! [`CifBlock::structure`] gathers what geometric work on a structure needs
+//! from the `_cell_*`, symmetry-operation, `_atom_site_*`, and
+//! `_atom_site_aniso_*` items of a block, in their core or mmCIF spellings.
+//! Sites are kept in file order.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_rutile
+//! _cell_length_a 4.5937
+//! _cell_length_b 4.5937
+//! _cell_length_c 2.9587
+//! _cell_angle_alpha 90
+//! _cell_angle_beta 90
+//! _cell_angle_gamma 90
+//! loop_
+//! _atom_site_label
+//! _atom_site_type_symbol
+//! _atom_site_fract_x
+//! _atom_site_fract_y
+//! _atom_site_fract_z
+//! _atom_site_U_iso_or_equiv
+//! Ti1 Ti4+ 0 0 0 0.0060(2)
+//! O1 O2- 0.3053(2) 0.3053(2) 0 0.0071(3)
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let structure = doc.blocks[0].structure().unwrap();
+//!
+//! assert_eq!(structure.symmetry.len(), 1);
+//! assert_eq!(structure.sites[1].label, "O1");
+//! assert_eq!(structure.sites[1].fract, Some([0.3053, 0.3053, 0.0]));
+//! assert_eq!(structure.sites[1].element().unwrap().symbol(), "O");
+//! ```
+
+pub mod compare;
+pub mod site_symmetry;
+pub mod ties;
+pub mod transform;
+
+use crate::ast::{CifBlock, CifLoop, CifValue};
+use crate::atom_label::AtomLabel;
+use crate::cell::UnitCell;
+use crate::element::{Element, ElementSymbol};
+use crate::publication::value_text;
+use crate::refine::SPECIAL_DETAILS;
+use crate::symmetry::SymOp;
+use crate::vocab::AdpType;
+
+pub use compare::{
+    compare_structures, MatchOptions, SiteDifference, SiteMatching, StructureComparison,
+};
+pub use site_symmetry::{SiteSymmetry, DEFAULT_SITE_TOLERANCE};
+pub use ties::{Tie, TiedQuantity, DEFAULT_TIE_TOLERANCE};
+
+/// `B = 8π²U`
+const B_PER_U: f64 = 8.0 * std::f64::consts::PI * std::f64::consts::PI;
+
+/// Tags for the declared multiplicity of a site, core then mmCIF
+pub(crate) const MULTIPLICITY_TAGS: [&str; 3] = [
+    "_atom_site_site_symmetry_multiplicity",
+    "_atom_site_symmetry_multiplicity",
+    "_atom_site.site_symmetry_multiplicity",
+];
+
+/// One atom site of a structure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtomSite {
+    /// `_atom_site_label`
+    pub label: String,
+    /// `_atom_site_type_symbol`, if given
+    pub type_symbol: Option<String>,
+    /// Fractional coordinates; `None` for a site given only in Cartesian
+    /// coordinates in a block without a unit cell
+    pub fract: Option<[f64; 3]>,
+    /// Cartesian coordinates in Å, if given
+    pub cartn: Option<[f64; 3]>,
+    /// Sus of the fractional coordinates (0 when unknown)
+    pub fract_su: [f64; 3],
+    /// Site occupancy, 1 when not given
+    pub occupancy: f64,
+    /// Isotropic or equivalent displacement parameter U in Å², converted from
+    /// B if only that is given
+    pub u_iso: Option<f64>,
+    /// Su of the displacement parameter (0 when unknown)
+    pub u_iso_su: f64,
+    /// Anisotropic displacement parameters U11, U22, U33, U12, U13, U23 in
+    /// Å², from the `_atom_site_aniso` row of the site, converted from B if
+    /// only that is given
+    pub u_aniso: Option<[f64; 6]>,
+    /// Sus of the anisotropic displacement parameters (0 when unknown)
+    pub u_aniso_su: [f64; 6],
+    /// `_atom_site_adp_type`, if given
+    pub adp_type: Option<AdpType>,
+    /// `_atom_site_site_symmetry_multiplicity` (or the older
+    /// `_atom_site_symmetry_multiplicity`), if given
+    pub symmetry_multiplicity: Option<usize>,
+    /// `_atom_site_disorder_group`, the disorder part of the site, if given
+    pub disorder_group: Option<String>,
+}
+
+impl AtomSite {
+    /// The element, from the type symbol or else the label
+    pub fn element(&self) -> Option<Element> {
+        if let Some(symbol) = &self.type_symbol {
+            if let Ok(parsed) = ElementSymbol::parse(symbol) {
+                return Some(parsed.element);
+            }
+        }
+        Element::from_symbol(&AtomLabel::parse(&self.label).element?)
+    }
+}
+
+/// A crystal structure read from a data block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure {
+    /// The unit cell
+    pub cell: UnitCell,
+    /// Symmetry operations; those of the [space group](CifBlock::space_group)
+    /// if the block lists none, or just the identity if it names none either
+    pub symmetry: Vec<SymOp>,
+    /// Atom sites with coordinates, in file order. Those read from a block
+    /// all have fractional coordinates; methods of the structure leave out
+    /// any without.
+    pub sites: Vec<AtomSite>,
+    /// Whether a [`supercell`](Structure::supercell) dropped the symmetry
+    /// operations of the structure it was built from, leaving P1
+    pub reduced_to_p1: bool,
+    /// `_refine_special_details`, where refinement programs' users describe
+    /// constraints, if given
+    pub refine_special_details: Option<String>,
+}
+
+impl Structure {
+    /// Read a structure from a block.
+    ///
+    /// Sites come from the loop with `_atom_site_label` (or mmCIF's
+    /// `_atom_site.id`); those without three fractional coordinates, or
+    /// Cartesian ones to convert, are left out. Anisotropic displacement
+    /// parameters come from the loop with `_atom_site_aniso_label` (or
+    /// `_atom_site_anisotrop.id`), by label. Returns `None` if the block
+    /// has no [unit cell](CifBlock::unit_cell).
+    pub fn from_block(block: &CifBlock) -> Option<Structure> {
+        let cell = block.unit_cell()?;
+        let mut symmetry = block.symmetry_operations();
+        if symmetry.is_empty() {
+            symmetry = block
+                .space_group()
+                .map_or_else(|| vec![SymOp::identity()], |group| group.operations);
+        }
+        Some(Structure {
+            sites: atom_sites(block, Some(&cell)),
+            cell,
+            symmetry,
+            reduced_to_p1: false,
+            refine_special_details: SPECIAL_DETAILS
+                .iter()
+                .find_map(|tag| block.get_item(tag).and_then(value_text)),
+        })
+    }
+
+    /// The sites as extended XYZ, with Cartesian coordinates in Å and the
+    /// cell vectors in the `Lattice` of the comment line.
+    ///
+    /// Only the listed sites are written; use [`supercell(1, 1,
+    /// 1)`](Structure::supercell) first for every atom of the cell. Sites
+    /// without a known element are written with their label.
+    pub fn to_xyz(&self) -> String {
+        let m = self.cell.orthogonalization_matrix();
+        // The columns of the matrix are the cell vectors
+        let lattice: Vec<String> = (0..3)
+            .flat_map(|col| (0..3).map(move |row| format!("{:.6}", m[row][col])))
+            .collect();
+        let atoms: Vec<String> = self
+            .sites
+            .iter()
+            .filter_map(|site| {
+                let species = site
+                    .element()
+                    .map_or(site.label.as_str(), |element| element.symbol());
+                let [x, y, z] = self.cell.frac_to_cart(site.fract?);
+                Some(format!("{species} {x:.6} {y:.6} {z:.6}\n"))
+            })
+            .collect();
+        format!(
+            "{}\nLattice=\"{}\" Properties=species:S:1:pos:R:3 pbc=\"T T T\"\n{}",
+            atoms.len(),
+            lattice.join(" "),
+            atoms.concat()
+        )
+    }
+}
+
+impl CifBlock {
+    /// The cell, symmetry, and atom sites of this block, or `None` without a
+    /// unit cell.
+    ///
+    /// See [`Structure::from_block`].
+    pub fn structure(&self) -> Option<Structure> {
+        Structure::from_block(self)
+    }
+
+    /// The atom sites of this block, in file order, read as for
+    /// [`Structure::from_block`] but without needing a unit cell.
+    ///
+    /// Sites given only in Cartesian coordinates are converted with the
+    /// block's unit cell; without one, they are kept with
+    /// [`fract`](AtomSite::fract) unset. Returns an empty list if the block
+    /// has no `_atom_site` loop.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let cif = "data_x\nloop_\n_atom_site.id\n_atom_site.type_symbol\n\
+    ///            _atom_site.fract_x\n_atom_site.fract_y\n_atom_site.fract_z\n\
+    ///            _atom_site.B_iso_or_equiv\n1 Na 0 0 0 1.5\n";
+    /// let doc = Document::parse(cif).unwrap();
+    /// let sites = doc.blocks[0].atom_sites();
+    ///
+    /// assert_eq!(sites[0].label, "1");
+    /// assert_eq!(sites[0].type_symbol.as_deref(), Some("Na"));
+    /// assert_eq!(sites[0].occupancy, 1.0);
+    /// assert!((sites[0].u_iso.unwrap() - 1.5 / (8.0 * std::f64::consts::PI.powi(2))).abs() < 1e-12);
+    /// ```
+    pub fn atom_sites(&self) -> Vec<AtomSite> {
+        atom_sites(self, self.unit_cell().as_ref())
+    }
+}
+
+/// The atom sites of a block, with their anisotropic displacement
+/// parameters; converting Cartesian coordinates needs `cell`
+fn atom_sites(block: &CifBlock, cell: Option<&UnitCell>) -> Vec<AtomSite> {
+    let mut sites = ["_atom_site_label", "_atom_site.id"]
+        .iter()
+        .find_map(|tag| block.find_loop(tag))
+        .map(|loop_| read_sites(loop_, cell))
+        .unwrap_or_default();
+    if let Some(loop_) = ["_atom_site_aniso_label", "_atom_site_anisotrop.id"]
+        .iter()
+        .find_map(|tag| block.find_loop(tag))
+    {
+        read_aniso(loop_, &mut sites);
+    }
+    sites
+}
+
+/// The atom sites of an `_atom_site` loop
+fn read_sites(loop_: &CifLoop, cell: Option<&UnitCell>) -> Vec<AtomSite> {
+    // The core tag, then the mmCIF one
+    let column = |names: [&str; 2], row: usize| {
+        names
+            .iter()
+            .find_map(|tag| match loop_.get_by_tag(row, tag) {
+                Some(CifValue::Unknown | CifValue::NotApplicable) | None => None,
+                value => value,
+            })
+    };
+    let number = |names: [&str; 2], row: usize| column(names, row)?.as_numeric_with_su();
+    let axes = ["x", "y", "z"];
+
+    (0..loop_.len())
+        .filter_map(|row| {
+            let label = value_text(column(["_atom_site_label", "_atom_site.id"], row)?)?;
+            let fract = axes.map(|axis| {
+                number(
+                    [
+                        &format!("_atom_site_fract_{axis}"),
+                        &format!("_atom_site.fract_{axis}"),
+                    ],
+                    row,
+                )
+            });
+            let cartn = match axes.map(|axis| {
+                number(
+                    [
+                        &format!("_atom_site_Cartn_{axis}"),
+                        &format!("_atom_site.Cartn_{axis}"),
+                    ],
+                    row,
+                )
+            }) {
+                [Some(x), Some(y), Some(z)] => Some([x.0, y.0, z.0]),
+                _ => None,
+            };
+            let (fract, fract_su) = match fract {
+                [Some(x), Some(y), Some(z)] => (
+                    Some([x.0, y.0, z.0]),
+                    [x, y, z].map(|(_, su)| su.unwrap_or(0.0)),
+                ),
+                // Converted when there is a cell, else left unset
+                _ => (
+                    cell.zip(cartn).map(|(cell, xyz)| cell.cart_to_frac(xyz)),
+                    [0.0; 3],
+                ),
+            };
+            if fract.is_none() && cartn.is_none() {
+                return None;
+            }
+            let u_iso = match number(
+                ["_atom_site_U_iso_or_equiv", "_atom_site.U_iso_or_equiv"],
+                row,
+            ) {
+                Some(u) => Some(u),
+                None => number(
+                    ["_atom_site_B_iso_or_equiv", "_atom_site.B_iso_or_equiv"],
+                    row,
+                )
+                .map(|(b, su)| (b / B_PER_U, su.map(|su| su / B_PER_U))),
+            };
+            Some(AtomSite {
+                label,
+                type_symbol: column(["_atom_site_type_symbol", "_atom_site.type_symbol"], row)
+                    .and_then(value_text),
+                fract,
+                cartn,
+                fract_su,
+                occupancy: number(["_atom_site_occupancy", "_atom_site.occupancy"], row)
+                    .map_or(1.0, |(occupancy, _)| occupancy),
+                u_iso: u_iso.map(|(u, _)| u),
+                u_iso_su: u_iso.and_then(|(_, su)| su).unwrap_or(0.0),
+                u_aniso: None,
+                u_aniso_su: [0.0; 6],
+                adp_type: column(["_atom_site_adp_type", "_atom_site.adp_type"], row)
+                    .and_then(value_text)
+                    .map(|code| AdpType::from_code(&code)),
+                symmetry_multiplicity: MULTIPLICITY_TAGS
+                    .iter()
+                    .find_map(|tag| loop_.get_by_tag(row, tag)?.as_numeric())
+                    .filter(|&m| m >= 1.0 && m.fract() == 0.0)
+                    .map(|m| m as usize),
+                disorder_group: column(
+                    ["_atom_site_disorder_group", "_atom_site.disorder_group"],
+                    row,
+                )
+                .and_then(value_text),
+            })
+        })
+        .collect()
+}
+
+/// Tensor components in the order of [`AtomSite::u_aniso`], as core and
+/// mmCIF name them
+const ANISO_COMPONENTS: [(&str, &str); 6] = [
+    ("11", "[1][1]"),
+    ("22", "[2][2]"),
+    ("33", "[3][3]"),
+    ("12", "[1][2]"),
+    ("13", "[1][3]"),
+    ("23", "[2][3]"),
+];
+
+/// Add the anisotropic displacement parameters of an `_atom_site_aniso`
+/// loop to the sites with the labels it gives. A row without all six U
+/// (or B) components is skipped.
+fn read_aniso(loop_: &CifLoop, sites: &mut [AtomSite]) {
+    let number = |row: usize, tags: [String; 2]| {
+        tags.iter()
+            .find_map(|tag| loop_.get_by_tag(row, tag)?.as_numeric_with_su())
+    };
+    for row in 0..loop_.len() {
+        let Some(label) = ["_atom_site_aniso_label", "_atom_site_anisotrop.id"]
+            .iter()
+            .find_map(|tag| value_text(loop_.get_by_tag(row, tag)?))
+        else {
+            continue;
+        };
+        let Some(site) = sites.iter_mut().find(|site| site.label == label) else {
+            continue;
+        };
+        let tensor = |kind: &str, scale: f64| {
+            let components = ANISO_COMPONENTS.map(|(core, mmcif)| {
+                number(
+                    row,
+                    [
+                        format!("_atom_site_aniso_{kind}_{core}"),
+                        format!("_atom_site_anisotrop.{kind}{mmcif}"),
+                    ],
+                )
+            });
+            components
+                .iter()
+                .all(Option::is_some)
+                .then(|| components.map(|c| c.unwrap_or((0.0, None))))
+                .map(|c| {
+                    (
+                        c.map(|(value, _)| value / scale),
+                        c.map(|(_, su)| su.unwrap_or(0.0) / scale),
+                    )
+                })
+        };
+        if let Some((u, su)) = tensor("U", 1.0).or_else(|| tensor("B", B_PER_U)) {
+            site.u_aniso = Some(u);
+            site.u_aniso_su = su;
+        }
+    }
+}
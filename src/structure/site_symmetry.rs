@@ -0,0 +1,142 @@
+//! Site symmetry and multiplicity of the atom sites of a structure.
+//!
+//! A site is on a special position when some symmetry operations other than
+//! the identity map it onto itself, give or take a lattice translation.
+//! Those operations form its stabilizer (the site-symmetry group), and the
+//! site has `|G| / |stabilizer|` equivalent positions in the cell, its
+//! Wyckoff multiplicity. [`Structure::site_symmetries`] works this out for
+//! every site and compares it with `_atom_site_site_symmetry_multiplicity`
+//! where the file gives one; a disagreement usually means the occupancy or
+//! the multiplicity was not updated when a site was moved.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::structure::DEFAULT_SITE_TOLERANCE;
+//! use cif_parser::Document;
+//!
+//! // Rutile, P 42/m n m: Ti on 2a (m.mm), O on 4f (m.2m)
+//! let mut cif = String::from(
+//!     "data_rutile\n_cell_length_a 4.5937\n_cell_length_b 4.5937\n\
+//!      _cell_length_c 2.9587\n_cell_angle_alpha 90\n_cell_angle_beta 90\n\
+//!      _cell_angle_gamma 90\nloop_\n_space_group_symop_operation_xyz\n",
+//! );
+//! for op in [
+//!     "x,y,z", "-x,-y,z", "-y+1/2,x+1/2,z+1/2", "y+1/2,-x+1/2,z+1/2",
+//!     "-x+1/2,y+1/2,-z+1/2", "x+1/2,-y+1/2,-z+1/2", "y,x,-z", "-y,-x,-z",
+//!     "-x,-y,-z", "x,y,-z", "y+1/2,-x+1/2,-z+1/2", "-y+1/2,x+1/2,-z+1/2",
+//!     "x+1/2,-y+1/2,z+1/2", "-x+1/2,y+1/2,z+1/2", "-y,-x,z", "y,x,z",
+//! ] {
+//!     cif.push_str(&format!("'{op}'\n"));
+//! }
+//! cif.push_str(
+//!     "loop_\n_atom_site_label\n_atom_site_fract_x\n_atom_site_fract_y\n\
+//!      _atom_site_fract_z\nTi1 0 0 0\nO1 0.3053 0.3053 0\n",
+//! );
+//! let structure = Document::parse(&cif).unwrap().blocks[0].structure().unwrap();
+//! let sites = structure.site_symmetries(DEFAULT_SITE_TOLERANCE);
+//!
+//! assert_eq!(sites[0].multiplicity, 2);
+//! assert_eq!(sites[0].symbol, Some("mmm"));
+//! assert_eq!(sites[1].multiplicity, 4);
+//! assert_eq!(sites[1].symbol, Some("mm2"));
+//! assert!(sites[1].is_special());
+//! ```
+
+use super::Structure;
+use crate::symmetry::{point_group, SymOp};
+
+/// Default for how far, in Å, an image of a site may be from the site and
+/// still count as the same position: enough for coordinates given to four
+/// decimals, such as 0.3333 for 1/3
+pub const DEFAULT_SITE_TOLERANCE: f64 = 0.01;
+
+/// The site symmetry of one atom site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteSymmetry {
+    /// `_atom_site_label` of the site
+    pub label: String,
+    /// Operations that map the site onto itself, give or take a lattice
+    /// translation, in the order of [`Structure::symmetry`]
+    pub stabilizer: Vec<SymOp>,
+    /// Number of equivalent positions in the cell, `|G| / |stabilizer|`
+    pub multiplicity: usize,
+    /// Hermann–Mauguin symbol of the stabilizer's point group, such as `m`
+    /// or `-3m`, without the orientation of ITA's oriented symbols; `None`
+    /// if the operations found do not form a point group, which means the
+    /// tolerance is too loose for the site
+    pub symbol: Option<&'static str>,
+    /// `_atom_site_site_symmetry_multiplicity` as given in the file
+    pub declared_multiplicity: Option<usize>,
+}
+
+impl SiteSymmetry {
+    /// Whether the site is on a special position: it has a stabilizer
+    /// larger than the identity
+    pub fn is_special(&self) -> bool {
+        self.stabilizer.len() > 1
+    }
+
+    /// The declared and computed multiplicity, if the file gives one that
+    /// differs from the one computed
+    pub fn mismatch(&self) -> Option<(usize, usize)> {
+        self.declared_multiplicity
+            .filter(|&declared| declared != self.multiplicity)
+            .map(|declared| (declared, self.multiplicity))
+    }
+}
+
+impl Structure {
+    /// The site symmetry of every atom site, in the order of
+    /// [`sites`](Structure::sites).
+    ///
+    /// An operation stabilizes a site if it maps it to within `tolerance` Å
+    /// of itself, allowing lattice translations. Operations repeated in the
+    /// list, up to a lattice translation, are counted once. If the block
+    /// lists no symmetry operations, every site has multiplicity 1.
+    /// A site without fractional coordinates has an empty stabilizer.
+    pub fn site_symmetries(&self, tolerance: f64) -> Vec<SiteSymmetry> {
+        let mut ops: Vec<SymOp> = Vec::new();
+        for op in &self.symmetry {
+            let repeated = ops.iter().any(|other| {
+                other.rotation == op.rotation
+                    && (0..3).all(|i| {
+                        let shift = other.translation[i] - op.translation[i];
+                        (shift - shift.round()).abs() < 1e-6
+                    })
+            });
+            if !repeated {
+                ops.push(*op);
+            }
+        }
+
+        self.sites
+            .iter()
+            .map(|site| {
+                let stabilizer: Vec<SymOp> = ops
+                    .iter()
+                    .filter(|op| {
+                        let Some(fract) = site.fract else {
+                            return false;
+                        };
+                        let image = op.apply(fract);
+                        let delta = [0, 1, 2].map(|i| {
+                            let d = image[i] - fract[i];
+                            d - d.round()
+                        });
+                        self.cell.distance([0.0; 3], delta) < tolerance
+                    })
+                    .copied()
+                    .collect();
+                let order = stabilizer.len().max(1);
+                SiteSymmetry {
+                    label: site.label.clone(),
+                    multiplicity: ops.len().max(1) / order,
+                    symbol: point_group(&stabilizer).map(|group| group.symbol),
+                    stabilizer,
+                    declared_multiplicity: site.symmetry_multiplicity,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,292 @@
+//! Atom sites whose parameters a refinement constrained to be equal.
+//!
+//! SHELXL's `EXYZ` gives atoms the same coordinates and `EADP` the same
+//! displacement parameters, typically for the parts of a disordered group
+//! or for two elements sharing a site. A constrained parameter is one
+//! refined variable, so the atoms it ties have identical values in the
+//! CIF, and identical sus. [`Structure::detect_ties`] finds groups of
+//! differently labelled sites whose coordinates, isotropic displacement
+//! parameters, or anisotropic tensors are identical in this way.
+//!
+//! Values without an su were not refined, so riding hydrogens that share a
+//! U_iso are not reported. The exception is coordinates fixed by symmetry:
+//! atoms on the same special position with no free coordinate coincide by
+//! symmetry alone, and are reported as
+//! [`symmetry_required`](Tie::symmetry_required) rather than as a
+//! constraint.
+//!
+//! When the block has `_refine_special_details`, each group says whether
+//! the text names all of its atoms, as a description of the constraints
+//! would.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::structure::{TiedQuantity, DEFAULT_TIE_TOLERANCE};
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_disorder
+//! _cell_length_a 10
+//! _cell_length_b 11
+//! _cell_length_c 12
+//! _cell_angle_alpha 90
+//! _cell_angle_beta 90
+//! _cell_angle_gamma 90
+//! loop_
+//! _atom_site_label
+//! _atom_site_fract_x
+//! _atom_site_fract_y
+//! _atom_site_fract_z
+//! _atom_site_U_iso_or_equiv
+//! _atom_site_disorder_group
+//! Cl1A 0.1234(2) 0.2345(3) 0.3456(2) 0.041(2) 1
+//! Cl1B 0.1234(2) 0.2345(3) 0.3456(2) 0.044(3) 2
+//! ";
+//! let structure = Document::parse(cif).unwrap().blocks[0].structure().unwrap();
+//! let ties = structure.detect_ties(DEFAULT_TIE_TOLERANCE);
+//!
+//! assert_eq!(ties.len(), 1);
+//! assert_eq!(ties[0].quantity, TiedQuantity::Coordinates);
+//! assert_eq!(ties[0].labels, ["Cl1A", "Cl1B"]);
+//! assert!(ties[0].across_parts && !ties[0].symmetry_required);
+//! ```
+
+use super::{SiteSymmetry, Structure, DEFAULT_SITE_TOLERANCE};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Default for how far tied values, and their sus, may differ: far below
+/// the last digit a CIF gives, so that only identical values count
+pub const DEFAULT_TIE_TOLERANCE: f64 = 1e-9;
+
+/// What a group of atoms shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiedQuantity {
+    /// Fractional coordinates, as with SHELXL's `EXYZ`
+    Coordinates,
+    /// The isotropic displacement parameter, as with `EADP`; compared only
+    /// between sites without anisotropic parameters
+    UIso,
+    /// The anisotropic displacement tensor, as with `EADP`
+    UAniso,
+}
+
+impl TiedQuantity {
+    /// `coordinates`, `u_iso`, or `u_aniso`
+    pub fn name(self) -> &'static str {
+        match self {
+            TiedQuantity::Coordinates => "coordinates",
+            TiedQuantity::UIso => "u_iso",
+            TiedQuantity::UAniso => "u_aniso",
+        }
+    }
+
+    /// The SHELXL instruction that ties this quantity
+    pub fn shelx_instruction(self) -> &'static str {
+        match self {
+            TiedQuantity::Coordinates => "EXYZ",
+            TiedQuantity::UIso | TiedQuantity::UAniso => "EADP",
+        }
+    }
+}
+
+impl fmt::Display for TiedQuantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A group of atom sites with identical values of one quantity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tie {
+    /// What the sites share
+    pub quantity: TiedQuantity,
+    /// Labels of the sites, in file order
+    pub labels: Vec<String>,
+    /// Whether symmetry alone makes the values equal: coordinates of sites
+    /// on the same special position, with no free coordinate
+    pub symmetry_required: bool,
+    /// Whether the sites are in more than one disorder group
+    pub across_parts: bool,
+    /// Whether `_refine_special_details` names every site of the group;
+    /// `None` if the block has no such text. Only constraints need
+    /// describing.
+    pub documented: Option<bool>,
+}
+
+impl Tie {
+    /// Whether the sites were tied by a refinement constraint rather than
+    /// by symmetry
+    pub fn is_constraint(&self) -> bool {
+        !self.symmetry_required
+    }
+}
+
+impl fmt::Display for Tie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} tied: {}", self.quantity, self.labels.join(", "))?;
+        if self.symmetry_required {
+            return write!(f, " (by symmetry)");
+        }
+        write!(f, " ({})", self.quantity.shelx_instruction())?;
+        if self.documented == Some(false) {
+            write!(f, ", not described in _refine_special_details")?;
+        }
+        Ok(())
+    }
+}
+
+/// A value and its su
+type Measured = (f64, f64);
+
+impl Structure {
+    /// Groups of differently labelled sites whose coordinates, isotropic
+    /// displacement parameters, or anisotropic tensors are identical. See
+    /// the [module documentation](crate::structure::ties).
+    ///
+    /// Values are identical when they, and their sus, differ by at most
+    /// `tolerance`. Coordinates are compared as given, without symmetry or
+    /// lattice translations. Groups are listed coordinates first, then
+    /// isotropic and anisotropic displacement parameters, each in the order
+    /// of their first site.
+    pub fn detect_ties(&self, tolerance: f64) -> Vec<Tie> {
+        let free: Vec<usize> = self
+            .site_symmetries(DEFAULT_SITE_TOLERANCE)
+            .iter()
+            .map(free_coordinates)
+            .collect();
+        let refined = |values: &[Measured]| values.iter().any(|&(_, su)| su > 0.0);
+
+        let coordinates: Vec<Option<Vec<Measured>>> = self
+            .sites
+            .iter()
+            .zip(&free)
+            .map(|(site, &free)| {
+                let values: Vec<Measured> = site.fract?.into_iter().zip(site.fract_su).collect();
+                (refined(&values) || free == 0).then_some(values)
+            })
+            .collect();
+        let u_iso: Vec<Option<Vec<Measured>>> = self
+            .sites
+            .iter()
+            .map(|site| match (site.u_aniso, site.u_iso) {
+                (None, Some(u)) if site.u_iso_su > 0.0 => Some(vec![(u, site.u_iso_su)]),
+                _ => None,
+            })
+            .collect();
+        let u_aniso: Vec<Option<Vec<Measured>>> = self
+            .sites
+            .iter()
+            .map(|site| {
+                let values: Vec<Measured> =
+                    site.u_aniso?.into_iter().zip(site.u_aniso_su).collect();
+                refined(&values).then_some(values)
+            })
+            .collect();
+
+        let words = self.refine_special_details.as_deref().map(words);
+        let mut ties = Vec::new();
+        for (quantity, values) in [
+            (TiedQuantity::Coordinates, coordinates),
+            (TiedQuantity::UIso, u_iso),
+            (TiedQuantity::UAniso, u_aniso),
+        ] {
+            for members in self.groups(&values, tolerance) {
+                let labels: Vec<String> = members
+                    .iter()
+                    .map(|&i| self.sites[i].label.clone())
+                    .collect();
+                let parts: HashSet<Option<&str>> = members
+                    .iter()
+                    .map(|&i| self.sites[i].disorder_group.as_deref())
+                    .collect();
+                ties.push(Tie {
+                    quantity,
+                    symmetry_required: quantity == TiedQuantity::Coordinates
+                        && members.iter().all(|&i| free[i] == 0),
+                    across_parts: parts.len() > 1,
+                    documented: words.as_ref().map(|words| {
+                        labels
+                            .iter()
+                            .all(|label| words.contains(&label.to_lowercase()))
+                    }),
+                    labels,
+                });
+            }
+        }
+        ties
+    }
+
+    /// Groups of sites, by index, whose values match the first site of the
+    /// group; sites without values, and sites with a label already in the
+    /// group, are left out
+    fn groups(&self, values: &[Option<Vec<Measured>>], tolerance: f64) -> Vec<Vec<usize>> {
+        let matches = |a: &[Measured], b: &[Measured]| {
+            a.iter().zip(b).all(|(&(va, sa), &(vb, sb))| {
+                (va - vb).abs() <= tolerance && (sa - sb).abs() <= tolerance
+            })
+        };
+        let mut grouped = vec![false; values.len()];
+        let mut groups = Vec::new();
+        for (i, first) in values.iter().enumerate() {
+            let Some(first) = first.as_deref().filter(|_| !grouped[i]) else {
+                continue;
+            };
+            let mut members = vec![i];
+            for (j, other) in values.iter().enumerate().skip(i + 1) {
+                let Some(other) = other.as_deref().filter(|_| !grouped[j]) else {
+                    continue;
+                };
+                let label = &self.sites[j].label;
+                if matches(first, other) && members.iter().all(|&m| self.sites[m].label != *label) {
+                    members.push(j);
+                    grouped[j] = true;
+                }
+            }
+            if members.len() > 1 {
+                groups.push(members);
+            }
+        }
+        groups
+    }
+}
+
+/// Number of coordinates of a site that symmetry leaves free: the rank of
+/// the projection onto the fixed space of its stabilizer, which is the
+/// average trace of the stabilizer's rotations
+fn free_coordinates(site: &SiteSymmetry) -> usize {
+    if site.stabilizer.is_empty() {
+        return 3;
+    }
+    let trace: i32 = site
+        .stabilizer
+        .iter()
+        .map(|op| (0..3).map(|i| op.rotation[i][i]).sum::<i32>())
+        .sum();
+    (f64::from(trace) / site.stabilizer.len() as f64)
+        .round()
+        .max(0.0) as usize
+}
+
+/// The lowercased words of free text, split at anything that cannot be
+/// part of an atom label
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || matches!(c, '\'' | '_' | '"' | '*')))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_keep_primed_labels() {
+        let found = words("EXYZ C1A C1B; O1' and O1'' were constrained (EADP).");
+        for word in ["exyz", "c1a", "c1b", "o1'", "o1''", "eadp"] {
+            assert!(found.contains(word), "{word}");
+        }
+    }
+}
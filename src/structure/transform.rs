@@ -0,0 +1,190 @@
+//! Lattice manipulations of a structure: wrapping coordinates into the cell,
+//! shifting the origin, and building supercells.
+//!
+//! [`Structure::supercell`] gives a structure in P1: it first generates every
+//! atom of the cell from the symmetry operations, then repeats the cell, so
+//! the result lists each atom of the supercell once. The labels of the
+//! copies are the original label with a running number, `O1_1`, `O1_2`, ...
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "\
+//! data_cscl
+//! _cell_length_a 4.12
+//! _cell_length_b 4.12
+//! _cell_length_c 4.12
+//! _cell_angle_alpha 90
+//! _cell_angle_beta 90
+//! _cell_angle_gamma 90
+//! loop_
+//! _atom_site_label
+//! _atom_site_fract_x
+//! _atom_site_fract_y
+//! _atom_site_fract_z
+//! Cs1 0 0 0
+//! Cl1 0.5 0.5 1.5
+//! ";
+//! let mut structure = Document::parse(cif).unwrap().blocks[0].structure().unwrap();
+//! structure.wrap_coordinates();
+//! assert_eq!(structure.sites[1].fract, Some([0.5, 0.5, 0.5]));
+//!
+//! let supercell = structure.supercell(2, 2, 1);
+//! assert_eq!(supercell.sites.len(), 8);
+//! assert_eq!(supercell.cell.a, 8.24);
+//! assert_eq!(supercell.sites[1].label, "Cs1_2");
+//! assert_eq!(supercell.sites[1].fract, Some([0.0, 0.5, 0.0]));
+//! ```
+
+use super::{AtomSite, Structure};
+use crate::structure::DEFAULT_SITE_TOLERANCE;
+use crate::symmetry::SymOp;
+
+/// `x` moved into [0, 1) by a whole number
+fn wrap(x: f64) -> f64 {
+    let wrapped = x - x.floor();
+    // A tiny negative x rounds to 1 above
+    if wrapped >= 1.0 {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+impl Structure {
+    /// Move every site into the cell, with fractional coordinates in [0, 1).
+    ///
+    /// Each coordinate changes by a whole number, so the atoms and the
+    /// distances between them, allowing lattice translations, are unchanged.
+    pub fn wrap_coordinates(&mut self) {
+        for site in &mut self.sites {
+            site.fract = site.fract.map(|fract| fract.map(wrap));
+        }
+    }
+
+    /// Add `shift` to the fractional coordinates of every site, moving the
+    /// origin to `-shift`.
+    ///
+    /// The symmetry operations are rewritten for the new origin, so they
+    /// still map the sites onto each other. Their translations are brought
+    /// into [0, 1); the site coordinates are not wrapped.
+    pub fn shift_origin(&mut self, shift: [f64; 3]) {
+        for site in &mut self.sites {
+            site.fract = site
+                .fract
+                .map(|fract| [0, 1, 2].map(|i| fract[i] + shift[i]));
+        }
+        // x' = R(x - s) + t + s = Rx + (t + s - Rs)
+        for op in &mut self.symmetry {
+            let rotated = SymOp {
+                rotation: op.rotation,
+                translation: [0.0; 3],
+            }
+            .apply(shift);
+            op.translation = [0, 1, 2].map(|i| wrap(op.translation[i] + shift[i] - rotated[i]));
+        }
+    }
+
+    /// The structure repeated `na × nb × nc` times, in P1.
+    ///
+    /// Every atom of the cell is generated from the symmetry operations,
+    /// with images within [`DEFAULT_SITE_TOLERANCE`] of each other counted
+    /// once, and placed in the cell before the cell is repeated. The sites of
+    /// the result follow the original ones in order, each followed by its
+    /// other copies, and are labelled `<label>_<n>` with `n` counting from
+    /// one. Occupancies and displacement parameters carry over, and the
+    /// cell lengths and their sus are multiplied. If the structure had
+    /// symmetry operations other than the identity,
+    /// [`reduced_to_p1`](Structure::reduced_to_p1) is set on the result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `na`, `nb`, or `nc` is zero.
+    pub fn supercell(&self, na: usize, nb: usize, nc: usize) -> Structure {
+        assert!(
+            na > 0 && nb > 0 && nc > 0,
+            "supercell of {na}×{nb}×{nc} cells"
+        );
+        let repeats = [na, nb, nc].map(|n| n as f64);
+        let mut cell = self.cell;
+        cell.a *= repeats[0];
+        cell.b *= repeats[1];
+        cell.c *= repeats[2];
+        for (su, n) in cell.su.iter_mut().zip(repeats) {
+            *su *= n;
+        }
+
+        let mut sites = Vec::new();
+        for site in &self.sites {
+            let Some(fract) = site.fract else {
+                continue;
+            };
+            let mut images: Vec<(SymOp, [f64; 3])> = Vec::new();
+            for op in &self.symmetry {
+                let image = op.apply(fract).map(wrap);
+                let seen = images.iter().any(|(_, other)| {
+                    let delta = [0, 1, 2].map(|i| {
+                        let d = image[i] - other[i];
+                        d - d.round()
+                    });
+                    self.cell.distance([0.0; 3], delta) < DEFAULT_SITE_TOLERANCE
+                });
+                if !seen {
+                    images.push((*op, image));
+                }
+            }
+
+            let mut copy = 0;
+            for (op, image) in images {
+                // Rotations permute and negate the axes (up to the x-y of
+                // hexagonal cells), so the sus add in quadrature
+                let su = op.rotation.map(|row| {
+                    (0..3)
+                        .map(|j| f64::from(row[j] * row[j]) * site.fract_su[j].powi(2))
+                        .sum::<f64>()
+                        .sqrt()
+                });
+                for i in 0..na {
+                    for j in 0..nb {
+                        for k in 0..nc {
+                            copy += 1;
+                            let offset = [i, j, k].map(|n| n as f64);
+                            sites.push(AtomSite {
+                                label: format!("{}_{copy}", site.label),
+                                fract: Some([0, 1, 2].map(|m| (image[m] + offset[m]) / repeats[m])),
+                                cartn: None,
+                                fract_su: [0, 1, 2].map(|m| su[m] / repeats[m]),
+                                symmetry_multiplicity: None,
+                                ..site.clone()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Structure {
+            cell,
+            symmetry: vec![SymOp::identity()],
+            sites,
+            reduced_to_p1: self.reduced_to_p1
+                || self.symmetry.iter().any(|op| *op != SymOp::identity()),
+            refine_special_details: self.refine_special_details.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap(1.25), 0.25);
+        assert_eq!(wrap(-0.25), 0.75);
+        assert_eq!(wrap(-1e-17), 0.0);
+        assert_eq!(wrap(1.0), 0.0);
+    }
+}
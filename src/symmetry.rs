@@ -0,0 +1,729 @@
+//! Symmetry operations and the point groups they generate.
+//!
+//! [`SymOp::parse`] reads an operation in the `x,y,z` notation of
+//! `_space_group_symop_operation_xyz`, and [`point_group`] strips the
+//! translations from a list of operations and identifies the crystallographic
+//! point group from how many rotations of each kind it contains. The point
+//! group gives the Laue class and crystal system, and answers whether a
+//! structure is centrosymmetric, chiral (has no improper rotations), or polar.
+//!
+//! [`CifBlock::point_group`] does this for the operations in a block and
+//! checks the result against the cell metric and the declared space group
+//! number.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::symmetry::{point_group, CrystalSystem, SymOp};
+//!
+//! // P 21/c
+//! let ops: Vec<SymOp> = ["x,y,z", "-x,y+1/2,-z+1/2", "-x,-y,-z", "x,-y+1/2,z+1/2"]
+//!     .iter()
+//!     .map(|op| SymOp::parse(op).unwrap())
+//!     .collect();
+//! let group = point_group(&ops).unwrap();
+//!
+//! assert_eq!(group.symbol, "2/m");
+//! assert_eq!(group.crystal_system, CrystalSystem::Monoclinic);
+//! assert!(group.is_centrosymmetric);
+//! assert!(!group.is_chiral);
+//! ```
+
+use crate::ast::CifBlock;
+use crate::cell::UnitCell;
+use crate::error::CifWarning;
+use crate::publication::value_text;
+use std::fmt;
+
+/// A symmetry operation: a rotation (proper or improper) in the basis of
+/// the cell, followed by a translation in fractions of the cell edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymOp {
+    /// Rotation matrix, as rows; row `i` gives new coordinate `i`
+    pub rotation: [[i32; 3]; 3],
+    /// Translation vector
+    pub translation: [f64; 3],
+}
+
+impl SymOp {
+    /// The identity operation, `x,y,z`
+    pub fn identity() -> SymOp {
+        SymOp {
+            rotation: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+            translation: [0.0; 3],
+        }
+    }
+
+    /// Apply the operation to fractional coordinates
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::symmetry::SymOp;
+    ///
+    /// let op = SymOp::parse("-x, y+1/2, -z").unwrap();
+    /// assert_eq!(op.apply([0.25, 0.25, 0.1]), [-0.25, 0.75, -0.1]);
+    /// ```
+    pub fn apply(&self, frac: [f64; 3]) -> [f64; 3] {
+        [0, 1, 2].map(|i| {
+            let row = self.rotation[i];
+            f64::from(row[0]) * frac[0]
+                + f64::from(row[1]) * frac[1]
+                + f64::from(row[2]) * frac[2]
+                + self.translation[i]
+        })
+    }
+
+    /// The operation applying `other` first and then this one.
+    ///
+    /// Translations are kept as they come out, not reduced into the cell.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::symmetry::SymOp;
+    ///
+    /// let screw = SymOp::parse("-x, y+1/2, -z").unwrap();
+    /// let twice = screw.compose(&screw);
+    /// assert_eq!(twice.to_string(), "x,y+1,z");
+    /// assert_eq!(twice.apply([0.1, 0.2, 0.3]), screw.apply(screw.apply([0.1, 0.2, 0.3])));
+    /// ```
+    pub fn compose(&self, other: &SymOp) -> SymOp {
+        SymOp {
+            rotation: multiply(&self.rotation, &other.rotation),
+            translation: self.apply(other.translation),
+        }
+    }
+
+    /// Parse an operation such as `-x+1/2, y, -z` or `x-y,x,z+0.5`.
+    ///
+    /// Letters may be upper or lower case, translations may be fractions or
+    /// decimals on either side, and coefficients may be written `2x` or
+    /// `2*x`. Returns `None` unless there are exactly three components.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::symmetry::SymOp;
+    ///
+    /// let op = SymOp::parse("-x+1/2, y, 1/2-z").unwrap();
+    /// assert_eq!(op.rotation, [[-1, 0, 0], [0, 1, 0], [0, 0, -1]]);
+    /// assert_eq!(op.translation, [0.5, 0.0, 0.5]);
+    /// ```
+    pub fn parse(xyz: &str) -> Option<SymOp> {
+        let components: Vec<&str> = xyz.split(',').collect();
+        if components.len() != 3 {
+            return None;
+        }
+        let mut op = SymOp {
+            rotation: [[0; 3]; 3],
+            translation: [0.0; 3],
+        };
+        for (i, component) in components.iter().enumerate() {
+            let (row, shift) = parse_component(component)?;
+            op.rotation[i] = row;
+            op.translation[i] = shift;
+        }
+        Some(op)
+    }
+
+    /// Kind of rotation: `1`, `2`, `3`, `4`, `6` for proper rotations and
+    /// `-1`, `-2` (a mirror), `-3`, `-4`, `-6` for improper ones, or `None`
+    /// if the matrix is not a crystallographic rotation
+    pub fn rotation_type(&self) -> Option<i32> {
+        let r = &self.rotation;
+        let det = r[0][0] * (r[1][1] * r[2][2] - r[1][2] * r[2][1])
+            - r[0][1] * (r[1][0] * r[2][2] - r[1][2] * r[2][0])
+            + r[0][2] * (r[1][0] * r[2][1] - r[1][1] * r[2][0]);
+        let trace = r[0][0] + r[1][1] + r[2][2];
+        let kind = match (det, trace) {
+            (1, 3) => 1,
+            (1, -1) => 2,
+            (1, 0) => 3,
+            (1, 1) => 4,
+            (1, 2) => 6,
+            (-1, -3) => -1,
+            (-1, 1) => -2,
+            (-1, 0) => -3,
+            (-1, -1) => -4,
+            (-1, -2) => -6,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+impl fmt::Display for SymOp {
+    /// The operation in `x,y,z` notation, e.g. `-x,y+1/2,-z+1/2`, with
+    /// translations as fractions where they are twelfths
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (row, shift)) in self.rotation.iter().zip(self.translation).enumerate() {
+            let mut component = String::new();
+            for (coefficient, axis) in row.iter().zip(["x", "y", "z"]) {
+                match coefficient {
+                    0 => {}
+                    1 => component += &format!("+{axis}"),
+                    -1 => component += &format!("-{axis}"),
+                    n => component += &format!("{n:+}{axis}"),
+                }
+            }
+            if shift != 0.0 {
+                component += &format_shift(shift);
+            }
+            let component = component.strip_prefix('+').unwrap_or(&component);
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(if component.is_empty() { "0" } else { component })?;
+        }
+        Ok(())
+    }
+}
+
+/// A signed translation as a fraction, e.g. `+1/2` or `-1/3`, or a decimal
+/// if it is not a whole number of twelfths
+fn format_shift(shift: f64) -> String {
+    let twelfths = shift * 12.0;
+    if (twelfths - twelfths.round()).abs() > 1e-9 {
+        return format!("{shift:+}");
+    }
+    let twelfths = twelfths.round() as i64;
+    let divisor = gcd(twelfths.abs(), 12);
+    match 12 / divisor {
+        1 => format!("{:+}", twelfths / divisor),
+        denominator => format!("{:+}/{denominator}", twelfths / divisor),
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// One row of the rotation matrix and the translation, from one component
+fn parse_component(component: &str) -> Option<([i32; 3], f64)> {
+    let text: String = component
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut row = [0; 3];
+    let mut shift = 0.0;
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let (sign, body) = match rest.as_bytes()[0] {
+            b'-' => (-1, &rest[1..]),
+            b'+' => (1, &rest[1..]),
+            _ => (1, rest),
+        };
+        let end = body.find(['+', '-']).unwrap_or(body.len());
+        let term = &body[..end];
+        rest = &body[end..];
+
+        match term.char_indices().last()? {
+            (at, axis @ ('x' | 'y' | 'z')) => {
+                let coefficient = match term[..at].trim_end_matches('*') {
+                    "" => 1,
+                    digits => digits.parse().ok()?,
+                };
+                row[(axis as u8 - b'x') as usize] += sign * coefficient;
+            }
+            _ => shift += f64::from(sign) * parse_number(term)?,
+        }
+    }
+    Some((row, shift))
+}
+
+/// A decimal or a fraction such as `1/2`
+fn parse_number(text: &str) -> Option<f64> {
+    match text.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator: f64 = denominator.parse().ok()?;
+            (denominator != 0.0).then_some(numerator.parse::<f64>().ok()? / denominator)
+        }
+        None => text.parse().ok(),
+    }
+}
+
+/// The seven crystal systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrystalSystem {
+    /// No symmetry constraint on the cell
+    Triclinic,
+    /// One twofold axis or mirror
+    Monoclinic,
+    /// Three perpendicular twofold axes or mirrors
+    Orthorhombic,
+    /// One fourfold axis
+    Tetragonal,
+    /// One threefold axis
+    Trigonal,
+    /// One sixfold axis
+    Hexagonal,
+    /// Four threefold axes
+    Cubic,
+}
+
+impl CrystalSystem {
+    /// Lowercase name, as used by `_space_group_crystal_system`
+    pub fn name(self) -> &'static str {
+        match self {
+            CrystalSystem::Triclinic => "triclinic",
+            CrystalSystem::Monoclinic => "monoclinic",
+            CrystalSystem::Orthorhombic => "orthorhombic",
+            CrystalSystem::Tetragonal => "tetragonal",
+            CrystalSystem::Trigonal => "trigonal",
+            CrystalSystem::Hexagonal => "hexagonal",
+            CrystalSystem::Cubic => "cubic",
+        }
+    }
+
+    /// The crystal system of a space group number (1–230)
+    pub fn from_space_group_number(number: u32) -> Option<CrystalSystem> {
+        POINT_GROUPS
+            .iter()
+            .find(|group| (group.space_groups.0..=group.space_groups.1).contains(&number))
+            .map(|group| group.system)
+    }
+
+    /// Whether a cell has the metric this system requires.
+    ///
+    /// Lengths are compared with a relative tolerance of
+    /// [`LENGTH_TOLERANCE`] and angles with an absolute tolerance of
+    /// [`ANGLE_TOLERANCE`] degrees. Monoclinic cells may have any unique
+    /// axis, and trigonal cells may use hexagonal or rhombohedral axes.
+    pub fn fits_cell(self, cell: &UnitCell) -> bool {
+        let same = |x: f64, y: f64| (x - y).abs() <= LENGTH_TOLERANCE * x.abs().max(y.abs());
+        let angle = |x: f64, target: f64| (x - target).abs() <= ANGLE_TOLERANCE;
+        let right = [cell.alpha, cell.beta, cell.gamma]
+            .iter()
+            .filter(|&&a| angle(a, 90.0))
+            .count();
+        let hexagonal = same(cell.a, cell.b) && right >= 2 && angle(cell.gamma, 120.0);
+        match self {
+            CrystalSystem::Triclinic => true,
+            CrystalSystem::Monoclinic => right >= 2,
+            CrystalSystem::Orthorhombic => right == 3,
+            CrystalSystem::Tetragonal => right == 3 && same(cell.a, cell.b),
+            CrystalSystem::Trigonal => {
+                let rhombohedral = same(cell.a, cell.b)
+                    && same(cell.b, cell.c)
+                    && angle(cell.alpha, cell.beta)
+                    && angle(cell.beta, cell.gamma);
+                hexagonal || rhombohedral
+            }
+            CrystalSystem::Hexagonal => hexagonal,
+            CrystalSystem::Cubic => right == 3 && same(cell.a, cell.b) && same(cell.b, cell.c),
+        }
+    }
+}
+
+impl fmt::Display for CrystalSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Relative tolerance for equal cell lengths in [`CrystalSystem::fits_cell`]
+pub const LENGTH_TOLERANCE: f64 = 1e-3;
+
+/// Tolerance in degrees for cell angles in [`CrystalSystem::fits_cell`]
+pub const ANGLE_TOLERANCE: f64 = 0.05;
+
+/// A crystallographic point group and what follows from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointGroupInfo {
+    /// Hermann–Mauguin symbol, such as `2/m` or `-42m`
+    pub symbol: &'static str,
+    /// Number of distinct rotations
+    pub order: usize,
+    /// Laue class: the point group with an inversion centre added
+    pub laue_class: &'static str,
+    /// Crystal system
+    pub crystal_system: CrystalSystem,
+    /// Whether the group contains the inversion
+    pub is_centrosymmetric: bool,
+    /// Whether the group has only proper rotations, so that a structure
+    /// in it can be chiral
+    pub is_chiral: bool,
+    /// Whether the group leaves a direction unmoved, so that the crystal
+    /// can be pyroelectric
+    pub is_polar: bool,
+    /// The distinct rotations, in the order first met; they act on
+    /// fractional coordinates as [`SymOp::rotation`] does, and on Miller
+    /// indices as a row vector multiplied from the left
+    pub rotations: Vec<[[i32; 3]; 3]>,
+    /// Conflicts with the cell metric or the declared space group, from
+    /// [`CifBlock::point_group`]
+    pub warnings: Vec<CifWarning>,
+}
+
+/// Identify the point group of a list of symmetry operations.
+///
+/// Translations are ignored and repeated rotations (from centring or
+/// different translations) are counted once. Returns `None` if an
+/// operation is not a crystallographic rotation or the rotations do not
+/// form one of the 32 crystallographic point groups, for example because
+/// the list is incomplete.
+pub fn point_group(ops: &[SymOp]) -> Option<PointGroupInfo> {
+    let mut rotations: Vec<[[i32; 3]; 3]> = Vec::new();
+    for op in ops {
+        if !rotations.contains(&op.rotation) {
+            rotations.push(op.rotation);
+        }
+    }
+    // Closure under composition, so that a census match is a real group
+    for a in &rotations {
+        for b in &rotations {
+            if !rotations.contains(&multiply(a, b)) {
+                return None;
+            }
+        }
+    }
+
+    let mut census = [0usize; 10];
+    for rotation in &rotations {
+        let op = SymOp {
+            rotation: *rotation,
+            translation: [0.0; 3],
+        };
+        census[census_index(op.rotation_type()?)] += 1;
+    }
+    let group = POINT_GROUPS.iter().find(|group| group.census == census)?;
+
+    Some(PointGroupInfo {
+        symbol: group.symbol,
+        order: rotations.len(),
+        laue_class: group.laue_class,
+        crystal_system: group.system,
+        is_centrosymmetric: census[census_index(-1)] > 0,
+        is_chiral: census[..5].iter().all(|&n| n == 0),
+        is_polar: POLAR.contains(&group.symbol),
+        rotations,
+        warnings: Vec::new(),
+    })
+}
+
+impl CifBlock {
+    /// Symmetry operations from `_space_group_symop_operation_xyz`, or the
+    /// older `_symmetry_equiv_pos_as_xyz`, or their mmCIF forms.
+    ///
+    /// Operations that cannot be parsed are skipped.
+    pub fn symmetry_operations(&self) -> Vec<SymOp> {
+        let tags = [
+            "_space_group_symop_operation_xyz",
+            "_space_group_symop.operation_xyz",
+            "_symmetry_equiv_pos_as_xyz",
+            "_symmetry_equiv.pos_as_xyz",
+        ];
+        tags.iter()
+            .map(|tag| self.get_all(tag))
+            .find(|values| !values.is_empty())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(value_text)
+            .filter_map(|xyz| SymOp::parse(&xyz))
+            .collect()
+    }
+
+    /// Identify the point group of this block's symmetry operations.
+    ///
+    /// The crystal system is checked against the cell metric (see
+    /// [`CrystalSystem::fits_cell`]), and the point group against the
+    /// declared space group number, if any; conflicts are reported in
+    /// [`PointGroupInfo::warnings`]. Returns `None` if the block has no
+    /// operations or they do not form a point group.
+    pub fn point_group(&self) -> Option<PointGroupInfo> {
+        let mut info = point_group(&self.symmetry_operations())?;
+
+        if let Some(cell) = self.unit_cell() {
+            if !info.crystal_system.fits_cell(&cell) {
+                info.warnings.push(CifWarning::new(format!(
+                    "Symmetry operations give a {} point group ({}), but the cell \
+                     ({} {} {} {} {} {}) does not have {} metric",
+                    info.crystal_system,
+                    info.symbol,
+                    cell.a,
+                    cell.b,
+                    cell.c,
+                    cell.alpha,
+                    cell.beta,
+                    cell.gamma,
+                    info.crystal_system
+                )));
+            }
+        }
+
+        let number = [
+            "_space_group_IT_number",
+            "_space_group.IT_number",
+            "_symmetry_Int_Tables_number",
+            "_symmetry.Int_Tables_number",
+        ]
+        .iter()
+        .find_map(|tag| self.get_item(tag)?.as_numeric());
+        if let Some(number) = number {
+            let declared = POINT_GROUPS.iter().find(|group| {
+                (group.space_groups.0..=group.space_groups.1).contains(&(number as u32))
+            });
+            match declared {
+                Some(group) if group.symbol == info.symbol => {}
+                Some(group) => info.warnings.push(CifWarning::new(format!(
+                    "Space group number {number} belongs to point group {} ({}), \
+                     but the symmetry operations give {} ({})",
+                    group.symbol, group.system, info.symbol, info.crystal_system
+                ))),
+                None => info.warnings.push(CifWarning::new(format!(
+                    "Space group number {number} is not between 1 and 230"
+                ))),
+            }
+        }
+
+        Some(info)
+    }
+}
+
+/// Product of two rotation matrices
+fn multiply(a: &[[i32; 3]; 3], b: &[[i32; 3]; 3]) -> [[i32; 3]; 3] {
+    let mut product = [[0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+/// Position of a rotation type in a census: -6, -4, -3, -2, -1, 1, 2, 3, 4, 6
+fn census_index(rotation_type: i32) -> usize {
+    match rotation_type {
+        -6 => 0,
+        -4 => 1,
+        -3 => 2,
+        -2 => 3,
+        -1 => 4,
+        1 => 5,
+        2 => 6,
+        3 => 7,
+        4 => 8,
+        _ => 9,
+    }
+}
+
+/// The polar point groups
+const POLAR: [&str; 10] = ["1", "2", "m", "mm2", "4", "4mm", "3", "3m", "6", "6mm"];
+
+/// A row of the point group table
+struct PointGroup {
+    symbol: &'static str,
+    /// Number of rotations of each type, in the order of [`census_index`]
+    census: [usize; 10],
+    laue_class: &'static str,
+    system: CrystalSystem,
+    /// First and last space group numbers with this point group
+    space_groups: (u32, u32),
+}
+
+const fn row(
+    symbol: &'static str,
+    census: [usize; 10],
+    laue_class: &'static str,
+    system: CrystalSystem,
+    first: u32,
+    last: u32,
+) -> PointGroup {
+    PointGroup {
+        symbol,
+        census,
+        laue_class,
+        system,
+        space_groups: (first, last),
+    }
+}
+
+/// The 32 crystallographic point groups, identified by their rotation
+/// census (International Tables for Crystallography, Vol. A, Table 10.1.2)
+#[rustfmt::skip]
+const POINT_GROUPS: [PointGroup; 32] = {
+    use CrystalSystem::*;
+    [
+        //   symbol   -6 -4 -3 -2 -1  1  2  3  4  6
+        row("1",     [0, 0, 0, 0, 0, 1, 0, 0, 0, 0], "-1", Triclinic, 1, 1),
+        row("-1",    [0, 0, 0, 0, 1, 1, 0, 0, 0, 0], "-1", Triclinic, 2, 2),
+        row("2",     [0, 0, 0, 0, 0, 1, 1, 0, 0, 0], "2/m", Monoclinic, 3, 5),
+        row("m",     [0, 0, 0, 1, 0, 1, 0, 0, 0, 0], "2/m", Monoclinic, 6, 9),
+        row("2/m",   [0, 0, 0, 1, 1, 1, 1, 0, 0, 0], "2/m", Monoclinic, 10, 15),
+        row("222",   [0, 0, 0, 0, 0, 1, 3, 0, 0, 0], "mmm", Orthorhombic, 16, 24),
+        row("mm2",   [0, 0, 0, 2, 0, 1, 1, 0, 0, 0], "mmm", Orthorhombic, 25, 46),
+        row("mmm",   [0, 0, 0, 3, 1, 1, 3, 0, 0, 0], "mmm", Orthorhombic, 47, 74),
+        row("4",     [0, 0, 0, 0, 0, 1, 1, 0, 2, 0], "4/m", Tetragonal, 75, 80),
+        row("-4",    [0, 2, 0, 0, 0, 1, 1, 0, 0, 0], "4/m", Tetragonal, 81, 82),
+        row("4/m",   [0, 2, 0, 1, 1, 1, 1, 0, 2, 0], "4/m", Tetragonal, 83, 88),
+        row("422",   [0, 0, 0, 0, 0, 1, 5, 0, 2, 0], "4/mmm", Tetragonal, 89, 98),
+        row("4mm",   [0, 0, 0, 4, 0, 1, 1, 0, 2, 0], "4/mmm", Tetragonal, 99, 110),
+        row("-42m",  [0, 2, 0, 2, 0, 1, 3, 0, 0, 0], "4/mmm", Tetragonal, 111, 122),
+        row("4/mmm", [0, 2, 0, 5, 1, 1, 5, 0, 2, 0], "4/mmm", Tetragonal, 123, 142),
+        row("3",     [0, 0, 0, 0, 0, 1, 0, 2, 0, 0], "-3", Trigonal, 143, 146),
+        row("-3",    [0, 0, 2, 0, 1, 1, 0, 2, 0, 0], "-3", Trigonal, 147, 148),
+        row("32",    [0, 0, 0, 0, 0, 1, 3, 2, 0, 0], "-3m", Trigonal, 149, 155),
+        row("3m",    [0, 0, 0, 3, 0, 1, 0, 2, 0, 0], "-3m", Trigonal, 156, 161),
+        row("-3m",   [0, 0, 2, 3, 1, 1, 3, 2, 0, 0], "-3m", Trigonal, 162, 167),
+        row("6",     [0, 0, 0, 0, 0, 1, 1, 2, 0, 2], "6/m", Hexagonal, 168, 173),
+        row("-6",    [2, 0, 0, 1, 0, 1, 0, 2, 0, 0], "6/m", Hexagonal, 174, 174),
+        row("6/m",   [2, 0, 2, 1, 1, 1, 1, 2, 0, 2], "6/m", Hexagonal, 175, 176),
+        row("622",   [0, 0, 0, 0, 0, 1, 7, 2, 0, 2], "6/mmm", Hexagonal, 177, 182),
+        row("6mm",   [0, 0, 0, 6, 0, 1, 1, 2, 0, 2], "6/mmm", Hexagonal, 183, 186),
+        row("-6m2",  [2, 0, 0, 4, 0, 1, 3, 2, 0, 0], "6/mmm", Hexagonal, 187, 190),
+        row("6/mmm", [2, 0, 2, 7, 1, 1, 7, 2, 0, 2], "6/mmm", Hexagonal, 191, 194),
+        row("23",    [0, 0, 0, 0, 0, 1, 3, 8, 0, 0], "m-3", Cubic, 195, 199),
+        row("m-3",   [0, 0, 8, 3, 1, 1, 3, 8, 0, 0], "m-3", Cubic, 200, 206),
+        row("432",   [0, 0, 0, 0, 0, 1, 9, 8, 6, 0], "m-3m", Cubic, 207, 214),
+        row("-43m",  [0, 6, 0, 6, 0, 1, 3, 8, 0, 0], "m-3m", Cubic, 215, 220),
+        row("m-3m",  [0, 6, 8, 9, 1, 1, 9, 8, 6, 0], "m-3m", Cubic, 221, 230),
+    ]
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All rotations generated by the given operations
+    fn generate(generators: &[&str]) -> Vec<SymOp> {
+        let generators: Vec<SymOp> = generators
+            .iter()
+            .map(|g| SymOp::parse(g).unwrap())
+            .collect();
+        let mut group = vec![SymOp::parse("x,y,z").unwrap()];
+        let mut i = 0;
+        while i < group.len() {
+            for generator in &generators {
+                let rotation = multiply(&group[i].rotation, &generator.rotation);
+                if group.iter().all(|op| op.rotation != rotation) {
+                    group.push(SymOp {
+                        rotation,
+                        translation: [0.0; 3],
+                    });
+                }
+            }
+            i += 1;
+        }
+        group
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for xyz in [
+            "x,y,z",
+            "-x,y+1/2,-z+1/2",
+            "x-y,x,z+1/6",
+            "-y,x-y,z-1/3",
+            "2x,0,z+0.1",
+        ] {
+            let op = SymOp::parse(xyz).unwrap();
+            assert_eq!(op.to_string(), xyz);
+        }
+        assert_eq!(
+            SymOp::parse("1/2+x, y, 0.25-z").unwrap().to_string(),
+            "x+1/2,y,-z+1/4"
+        );
+    }
+
+    #[test]
+    fn test_parse_forms() {
+        let op = SymOp::parse("X-Y, 2*x, +z+0.25").unwrap();
+        assert_eq!(op.rotation, [[1, -1, 0], [2, 0, 0], [0, 0, 1]]);
+        assert_eq!(op.translation, [0.0, 0.0, 0.25]);
+        let op = SymOp::parse("1/2+x,-1/3-y,z-1/6").unwrap();
+        assert_eq!(op.rotation, [[1, 0, 0], [0, -1, 0], [0, 0, 1]]);
+        assert!((op.translation[1] + 1.0 / 3.0).abs() < 1e-12);
+        assert!((op.translation[2] + 1.0 / 6.0).abs() < 1e-12);
+
+        assert_eq!(SymOp::parse("x,y"), None);
+        assert_eq!(SymOp::parse("x,y,"), None);
+        assert_eq!(SymOp::parse("x,y,w"), None);
+        assert_eq!(SymOp::parse("x,y,z+1/0"), None);
+    }
+
+    #[test]
+    fn test_all_32_point_groups() {
+        let inversion = "-x,-y,-z";
+        let cases: [(&str, &[&str]); 32] = [
+            ("1", &[]),
+            ("-1", &[inversion]),
+            ("2", &["-x,y,-z"]),
+            ("m", &["x,-y,z"]),
+            ("2/m", &["-x,y,-z", inversion]),
+            ("222", &["-x,-y,z", "-x,y,-z"]),
+            ("mm2", &["-x,-y,z", "x,-y,z"]),
+            ("mmm", &["-x,-y,z", "-x,y,-z", inversion]),
+            ("4", &["-y,x,z"]),
+            ("-4", &["y,-x,-z"]),
+            ("4/m", &["-y,x,z", inversion]),
+            ("422", &["-y,x,z", "x,-y,-z"]),
+            ("4mm", &["-y,x,z", "x,-y,z"]),
+            ("-42m", &["y,-x,-z", "x,-y,-z"]),
+            ("4/mmm", &["-y,x,z", "x,-y,-z", inversion]),
+            ("3", &["-y,x-y,z"]),
+            ("-3", &["-y,x-y,z", inversion]),
+            ("32", &["-y,x-y,z", "y,x,-z"]),
+            ("3m", &["-y,x-y,z", "-y,-x,z"]),
+            ("-3m", &["-y,x-y,z", "y,x,-z", inversion]),
+            ("6", &["x-y,x,z"]),
+            ("-6", &["-y,x-y,z", "x,y,-z"]),
+            ("6/m", &["x-y,x,z", inversion]),
+            ("622", &["x-y,x,z", "y,x,-z"]),
+            ("6mm", &["x-y,x,z", "-y,-x,z"]),
+            ("-6m2", &["-y,x-y,z", "x,y,-z", "-y,-x,z"]),
+            ("6/mmm", &["x-y,x,z", "y,x,-z", inversion]),
+            ("23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+            ("m-3", &["-x,-y,z", "-x,y,-z", "z,x,y", inversion]),
+            ("432", &["-y,x,z", "z,x,y"]),
+            ("-43m", &["y,-x,-z", "z,x,y"]),
+            ("m-3m", &["-y,x,z", "z,x,y", inversion]),
+        ];
+        for (symbol, generators) in cases {
+            let group = point_group(&generate(generators)).unwrap();
+            assert_eq!(group.symbol, symbol);
+            let row = POINT_GROUPS
+                .iter()
+                .find(|row| row.symbol == symbol)
+                .unwrap();
+            assert_eq!(group.order, row.census.iter().sum::<usize>(), "{symbol}");
+        }
+    }
+
+    #[test]
+    fn test_incomplete_list_is_not_a_group() {
+        let ops: Vec<SymOp> = ["x,y,z", "-y,x,z"]
+            .iter()
+            .map(|op| SymOp::parse(op).unwrap())
+            .collect();
+        assert_eq!(point_group(&ops), None);
+    }
+
+    #[test]
+    fn test_cell_metric() {
+        let hexagonal = UnitCell::new(3.2, 3.2, 5.2, 90.0, 90.0, 120.0);
+        assert!(CrystalSystem::Hexagonal.fits_cell(&hexagonal));
+        assert!(CrystalSystem::Trigonal.fits_cell(&hexagonal));
+        assert!(!CrystalSystem::Orthorhombic.fits_cell(&hexagonal));
+
+        let rhombohedral = UnitCell::new(5.4, 5.4, 5.4, 55.2, 55.2, 55.2);
+        assert!(CrystalSystem::Trigonal.fits_cell(&rhombohedral));
+        assert!(!CrystalSystem::Cubic.fits_cell(&rhombohedral));
+
+        let unique_a = UnitCell::new(5.0, 6.0, 7.0, 95.0, 90.0, 90.0);
+        assert!(CrystalSystem::Monoclinic.fits_cell(&unique_a));
+        assert!(!CrystalSystem::Tetragonal.fits_cell(&unique_a));
+
+        assert_eq!(
+            CrystalSystem::from_space_group_number(14),
+            Some(CrystalSystem::Monoclinic)
+        );
+        assert_eq!(CrystalSystem::from_space_group_number(231), None);
+    }
+}
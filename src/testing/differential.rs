@@ -0,0 +1,414 @@
+//! Differential testing of the writer against other CIF readers.
+//!
+//! A round trip through this crate alone cannot catch a quoting or folding
+//! bug that the parser shares with the writer: a value written in a way
+//! only we read back correctly still compares equal. [`compare_with_external`]
+//! writes a document, reads the output back with this crate and with each
+//! [`ExternalTool`], and reports every value that any of them reads
+//! differently from what was written.
+//!
+//! The external readers are found through environment variables, so the
+//! same test runs everywhere and uses whatever tools a machine has:
+//!
+//! | Variable   | Value                                      | Invoked as |
+//! |------------|--------------------------------------------|------------|
+//! | `GEMMI_CLI` | the `gemmi` program                        | `gemmi cif2json --comcifs IN OUT` |
+//! | `PYCIFRW`  | a Python interpreter with PyCifRW installed | `python -c SCRIPT IN` |
+//!
+//! Items and loop values of every block are compared; save frames, and CIF
+//! 2.0 lists and tables, are compared only by this crate's own round trip.
+//! Tags and block names are compared case-insensitively. Other readers
+//! disagree on how to report `?`, `.`, and numbers, so a JSON `null` stands
+//! for either null, and a number matches text that parses to the same value.
+//! Text is compared without leading and trailing whitespace, which the parser
+//! trims from text fields.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::testing::differential::{compare_with_external, ExternalTool};
+//! use cif_parser::Document;
+//!
+//! let doc = Document::parse("data_x\n_a 'it''s'\nloop_\n_b\n'data_y'\n?\n").unwrap();
+//! let divergences = compare_with_external(&doc, &ExternalTool::from_env()).unwrap();
+//! assert!(divergences.is_empty(), "{divergences:?}");
+//! ```
+
+use crate::ast::{CifDocument, CifValue, CifVersion};
+use crate::uncertainty;
+use crate::writer::inline_value;
+use indexmap::IndexMap;
+use serde_json::Value as Json;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, process};
+
+/// Reads a CIF file with PyCifRW and prints `{block: {tag: value}}` as JSON
+const PYCIFRW_SCRIPT: &str = "\
+import json, sys
+from CifFile import ReadCif
+cf = ReadCif(sys.argv[1], grammar='auto')
+out = {}
+for name in cf.keys():
+    block = cf[name]
+    out[name] = {tag: block[tag] for tag in block.keys()}
+json.dump(out, sys.stdout, default=str)
+";
+
+/// Another program that reads CIF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalTool {
+    /// The `gemmi` command-line program, run as `gemmi cif2json --comcifs`
+    Gemmi(PathBuf),
+    /// A Python interpreter with PyCifRW installed
+    PyCifRw(PathBuf),
+}
+
+impl ExternalTool {
+    /// The tools named by the `GEMMI_CLI` and `PYCIFRW` environment
+    /// variables; empty when neither is set.
+    pub fn from_env() -> Vec<ExternalTool> {
+        let var = |name| env::var_os(name).filter(|value| !value.is_empty());
+        let mut tools = Vec::new();
+        if let Some(gemmi) = var("GEMMI_CLI") {
+            tools.push(ExternalTool::Gemmi(gemmi.into()));
+        }
+        if let Some(python) = var("PYCIFRW") {
+            tools.push(ExternalTool::PyCifRw(python.into()));
+        }
+        tools
+    }
+
+    /// The tool's name, as reported in [`Divergence::reader`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExternalTool::Gemmi(_) => "gemmi",
+            ExternalTool::PyCifRw(_) => "PyCifRW",
+        }
+    }
+
+    /// Read the CIF file at `path` into JSON
+    fn read(&self, path: &Path) -> Result<Json, String> {
+        let output = match self {
+            ExternalTool::Gemmi(program) => {
+                let json_path = path.with_extension("json");
+                let status = Command::new(program)
+                    .args(["cif2json", "--comcifs"])
+                    .arg(path)
+                    .arg(&json_path)
+                    .output()
+                    .map_err(|e| format!("cannot run {}: {e}", program.display()))?;
+                let json = status
+                    .status
+                    .success()
+                    .then(|| fs::read(&json_path))
+                    .transpose()
+                    .map_err(|e| format!("cannot read gemmi output: {e}"));
+                let _ = fs::remove_file(&json_path);
+                match json? {
+                    Some(json) => json,
+                    None => return Err(failure(self, &status.stderr)),
+                }
+            }
+            ExternalTool::PyCifRw(python) => {
+                let output = Command::new(python)
+                    .args(["-c", PYCIFRW_SCRIPT])
+                    .arg(path)
+                    .output()
+                    .map_err(|e| format!("cannot run {}: {e}", python.display()))?;
+                if !output.status.success() {
+                    return Err(failure(self, &output.stderr));
+                }
+                output.stdout
+            }
+        };
+        serde_json::from_slice(&output)
+            .map_err(|e| format!("{} wrote invalid JSON: {e}", self.name()))
+    }
+}
+
+/// The error for a tool that exited unsuccessfully
+fn failure(tool: &ExternalTool, stderr: &[u8]) -> String {
+    format!(
+        "{} could not read the written document: {}",
+        tool.name(),
+        String::from_utf8_lossy(stderr).trim()
+    )
+}
+
+/// A value that a reader read differently from how it was written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// `"cif_parser"` for this crate's own round trip, or the
+    /// [name](ExternalTool::name) of the external tool
+    pub reader: String,
+    /// Name of the block
+    pub block: String,
+    /// The tag, as written
+    pub tag: String,
+    /// Row of a loop value; `None` for an item
+    pub row: Option<usize>,
+    /// The value as this crate holds it, in CIF syntax
+    pub ours: String,
+    /// What the reader made of it; `<missing>` if it found no such value
+    pub theirs: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: data_{} {}", self.reader, self.block, self.tag)?;
+        if let Some(row) = self.row {
+            write!(f, " row {row}")?;
+        }
+        write!(f, ": wrote {}, read {}", self.ours, self.theirs)
+    }
+}
+
+/// A value as one reader read it
+enum Cell {
+    /// Read by this crate
+    Ours(CifValue),
+    /// Read by an external tool
+    Json(Json),
+}
+
+impl Cell {
+    /// Whether the reader read `written` as written; `None` if the value
+    /// is not compared
+    fn agrees(&self, written: &CifValue) -> Option<bool> {
+        let json = match self {
+            Cell::Ours(value) => return Some(value.semantically_equal(written)),
+            Cell::Json(json) => json,
+        };
+        let number = |text: &str| text.trim().parse::<f64>().ok();
+        Some(match (written, json) {
+            (CifValue::List(_) | CifValue::Table(_), _) => return None,
+            (CifValue::Unknown, Json::Null) => true,
+            (CifValue::Unknown, Json::String(s)) => s == "?" || s == "\\?",
+            (CifValue::NotApplicable, Json::Null | Json::Bool(false)) => true,
+            (CifValue::NotApplicable, Json::String(s)) => s == ".",
+            (CifValue::Numeric(n), Json::Number(m)) => m.as_f64() == Some(*n),
+            (CifValue::Numeric(n), Json::String(s)) => number(s) == Some(*n),
+            (CifValue::NumericSu { value, su }, Json::String(s)) => {
+                uncertainty::parse(s) == Some((*value, Some(*su)))
+            }
+            (CifValue::Text(_) | CifValue::LargeText(_), Json::Number(m)) => {
+                written.as_string().and_then(number) == m.as_f64()
+            }
+            (CifValue::Text(_) | CifValue::LargeText(_), Json::String(s)) => {
+                written.as_string().map(str::trim) == Some(normalize(s).trim())
+            }
+            _ => false,
+        })
+    }
+
+    /// The value in CIF syntax, or a tool's value as JSON
+    fn describe(&self, version: CifVersion) -> String {
+        match self {
+            Cell::Ours(value) => inline_value(value, version),
+            Cell::Json(json) => json.to_string(),
+        }
+    }
+}
+
+/// Line endings as the parser reads them
+fn normalize(text: &str) -> String {
+    text.replace("\r\n", "\n")
+}
+
+/// Every item and loop column of a document, by block name and tag; an
+/// item is a column of one value. External tools' names are lowercased.
+type Extracted = IndexMap<String, IndexMap<String, Vec<Cell>>>;
+
+fn extract_ours(doc: CifDocument) -> Extracted {
+    let mut extracted = Extracted::new();
+    for block in doc.blocks {
+        let columns = extracted.entry(block.name).or_default();
+        for (tag, value) in block.items {
+            columns.entry(tag).or_default().push(Cell::Ours(value));
+        }
+        for loop_ in block.loops {
+            for (i, tag) in loop_.tags.iter().enumerate() {
+                let column = loop_.values.iter().map(|row| Cell::Ours(row[i].clone()));
+                columns.entry(tag.clone()).or_default().extend(column);
+            }
+        }
+    }
+    extracted
+}
+
+/// The blocks of a tool's JSON output: gemmi's CIF-JSON wraps them in a
+/// `CIF-JSON` object with metadata, PyCifRW's output is the blocks alone
+fn extract_json(json: Json) -> Extracted {
+    let blocks = match json {
+        Json::Object(mut top) => match top.remove("CIF-JSON") {
+            Some(Json::Object(inner)) => inner,
+            _ => top,
+        },
+        _ => return Extracted::new(),
+    };
+    blocks
+        .into_iter()
+        .filter_map(|(name, block)| match block {
+            Json::Object(tags) => Some((name, tags)),
+            _ => None,
+        })
+        .map(|(name, tags)| {
+            let name = name.to_lowercase();
+            let name = name
+                .strip_prefix("data_")
+                .map(str::to_string)
+                .unwrap_or(name);
+            let columns = tags
+                .into_iter()
+                .filter(|(tag, _)| tag.starts_with('_'))
+                .map(|(tag, values)| {
+                    let column = match values {
+                        Json::Array(values) => values.into_iter().map(Cell::Json).collect(),
+                        value => vec![Cell::Json(value)],
+                    };
+                    (tag.to_lowercase(), column)
+                })
+                .collect();
+            (name, columns)
+        })
+        .collect()
+}
+
+/// Compare every item and loop value of `doc` with what `reader` read.
+///
+/// A tag repeated in a block, or in blocks of the same name, is one column
+/// in `read`; each occurrence is compared with the next of its values.
+fn compare(doc: &CifDocument, reader: &str, read: &Extracted, found: &mut Vec<Divergence>) {
+    let mut consumed: HashMap<(String, String), usize> = HashMap::new();
+    for block in &doc.blocks {
+        let columns = read
+            .get(&block.name)
+            .or_else(|| read.get(&block.name.to_lowercase()));
+        let mut check = |tag: &str, row, written: &CifValue| {
+            let index = consumed
+                .entry((block.name.clone(), tag.to_string()))
+                .or_default();
+            let theirs = columns
+                .and_then(|columns| {
+                    columns
+                        .get(tag)
+                        .or_else(|| columns.get(&tag.to_lowercase()))
+                })
+                .and_then(|cells| cells.get(*index));
+            *index += 1;
+            match theirs.map(|cell| cell.agrees(written)) {
+                Some(Some(true) | None) => {}
+                _ => found.push(Divergence {
+                    reader: reader.to_string(),
+                    block: block.name.clone(),
+                    tag: tag.to_string(),
+                    row,
+                    ours: inline_value(written, doc.version),
+                    theirs: theirs
+                        .map_or("<missing>".to_string(), |cell| cell.describe(doc.version)),
+                }),
+            }
+        };
+
+        for (tag, written) in &block.items {
+            check(tag, None, written);
+        }
+        for loop_ in &block.loops {
+            for (i, tag) in loop_.tags.iter().enumerate() {
+                for (row, values) in loop_.values.iter().enumerate() {
+                    check(tag, Some(row), &values[i]);
+                }
+            }
+        }
+    }
+}
+
+/// A temporary file name unique to this process and call
+fn temp_path() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("cif_parser_differential_{}_{n}.cif", process::id()))
+}
+
+/// Write `doc`, read the output back with this crate and with each of
+/// `tools`, and report every value that a reader did not read as written.
+///
+/// Returns an error if the written text cannot be parsed at all, or a tool
+/// cannot be run or rejects the file; the message names the tool and
+/// includes the written text.
+pub fn compare_with_external(
+    doc: &CifDocument,
+    tools: &[ExternalTool],
+) -> Result<Vec<Divergence>, String> {
+    let text = doc.to_string();
+    let reparsed = CifDocument::parse(&text)
+        .map_err(|e| format!("written document failed to parse: {e}\n--- written ---\n{text}"))?;
+
+    let mut found = Vec::new();
+    compare(doc, "cif_parser", &extract_ours(reparsed), &mut found);
+    if tools.is_empty() {
+        return Ok(found);
+    }
+
+    let path = temp_path();
+    fs::write(&path, &text).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    let results: Vec<_> = tools.iter().map(|tool| (tool, tool.read(&path))).collect();
+    let _ = fs::remove_file(&path);
+    for (tool, result) in results {
+        let json = result.map_err(|e| format!("{e}\n--- written ---\n{text}"))?;
+        compare(doc, tool.name(), &extract_json(json), &mut found);
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn divergences(cif: &str, json: &str) -> Vec<Divergence> {
+        let doc = CifDocument::parse(cif).unwrap();
+        let mut found = Vec::new();
+        compare(
+            &doc,
+            "tool",
+            &extract_json(serde_json::from_str(json).unwrap()),
+            &mut found,
+        );
+        found
+    }
+
+    #[test]
+    fn test_reader_conventions_are_tolerated() {
+        let cif = "data_X\n_A 1.50\n_b ?\n_c .\nloop_\n_d\n'007'\n;\n text\n;\n";
+        // gemmi's CIF-JSON: lowercase names, wrapped, numbers quoted
+        let gemmi = r#"{"CIF-JSON": {"Metadata": {}, "x": {
+            "_a": ["1.5"], "_b": ["\\?"], "_c": [null], "_d": [7, "text"]}}}"#;
+        assert_eq!(divergences(cif, gemmi), []);
+        // PyCifRW: scalars for items, '?' and '.' as text
+        let pycifrw = r#"{"x": {"_a": "1.50", "_b": "?", "_c": ".", "_d": ["007", "text"]}}"#;
+        assert_eq!(divergences(cif, pycifrw), []);
+    }
+
+    #[test]
+    fn test_differences_are_reported_with_the_value() {
+        let found = divergences(
+            "data_x\nloop_\n_a\n'a b'\nc\n",
+            r#"{"x": {"_a": ["a", "b", "c"]}}"#,
+        );
+        let report: Vec<String> = found.iter().map(Divergence::to_string).collect();
+        assert_eq!(
+            report,
+            [
+                "tool: data_x _a row 0: wrote 'a b', read \"a\"",
+                "tool: data_x _a row 1: wrote c, read \"b\""
+            ]
+        );
+
+        let found = divergences("data_x\n_a 1\n", r#"{"y": {"_a": 1}}"#);
+        assert_eq!(found[0].theirs, "<missing>");
+    }
+}
@@ -0,0 +1,450 @@
+//! Property-testing support: random documents and round-trip assertions.
+//!
+//! Enabled by the `testing` feature. The strategies here generate arbitrary
+//! but valid [`CifDocument`]s for use with [`proptest`], and
+//! [`assert_round_trip`] checks that writing a document and parsing it back
+//! gives a [semantically equal](CifDocument::semantically_equal) document.
+//!
+//! Generated text is drawn from fragments that exercise every way the writer
+//! can lay out a value: bare, single- and double-quoted, triple-quoted (CIF 2.0),
+//! and semicolon text fields. Lists and tables are generated only for CIF 2.0.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::testing::{arb_document, check_round_trip};
+//! use proptest::test_runner::{Config, TestCaseError, TestRunner};
+//!
+//! let mut runner = TestRunner::new(Config::with_cases(8));
+//! runner
+//!     .run(&arb_document(), |doc| {
+//!         check_round_trip(&doc).map_err(TestCaseError::fail)
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! For reproducible runs in CI, [`check_seed`] runs a fixed number of cases
+//! from a given seed and reports the offending document on failure.
+//!
+//! [`generate_mmcif`] writes a synthetic mmCIF file of any size, for
+//! benchmarks that need a large input without keeping one in the repository.
+//!
+//! [`differential`] compares what other CIF readers make of the writer's
+//! output with what this crate meant to write.
+
+pub mod differential;
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
+use proptest::collection::{hash_map, vec};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Pieces that generated text is assembled from.
+///
+/// Together they force each quoting style: embedded quotes of one or both
+/// kinds, a leading `#` or `_`, reserved words, brackets (which CIF 2.0 cannot
+/// leave bare), number-like text, and non-ASCII characters.
+const TEXT_FRAGMENTS: &[&str] = &[
+    "C1",
+    "Zr4+",
+    "O2'",
+    "it's",
+    "say \"hi\"",
+    "'",
+    "\"",
+    "x'",
+    "y\"",
+    "#",
+    "#note",
+    "_tag",
+    "data_x",
+    "loop_",
+    "save_y",
+    "global_",
+    "[1]",
+    "{a}",
+    "1.5",
+    "-3",
+    "?",
+    ".",
+    "x;y",
+    "$ref",
+    "é",
+    "Å",
+    "αβ",
+    "a'b\"c",
+];
+
+/// Arbitrary text, on one line or several.
+///
+/// Fragments are joined by single spaces, so the text has no leading or
+/// trailing whitespace and no doubled quotes. Lines never start with `;`.
+pub fn arb_text() -> impl Strategy<Value = String> {
+    prop_oneof![
+        5 => arb_line(),
+        2 => arb_lines(),
+        1 => Just(String::new()),
+    ]
+}
+
+/// One line of text fragments joined by single spaces
+fn arb_line() -> impl Strategy<Value = String> {
+    vec(prop::sample::select(TEXT_FRAGMENTS), 1..5).prop_map(|words| words.join(" "))
+}
+
+/// Two to four lines of text, as a text field holds
+fn arb_lines() -> impl Strategy<Value = String> {
+    vec(arb_line(), 2..4).prop_map(|lines| lines.join("\n"))
+}
+
+/// Arbitrary number, including integers, zero, and extreme magnitudes
+pub fn arb_number() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        (-1000i32..1000).prop_map(f64::from),
+        prop::num::f64::NORMAL,
+        Just(0.0),
+    ]
+}
+
+/// Arbitrary number with a standard uncertainty.
+///
+/// The value is any [`arb_number`], and the su is one to two significant
+/// digits from 10⁻²⁰ times its magnitude up to a hundred times it, so the
+/// writer has to keep up to seventeen digits of either, with an exponent
+/// for the smallest and largest.
+pub fn arb_numeric_su() -> impl Strategy<Value = CifValue> {
+    (arb_number(), 1u32..100, -20i32..3)
+        .prop_map(|(value, digits, scale)| {
+            let magnitude = if value == 0.0 { 1.0 } else { value.abs() };
+            let su = f64::from(digits) * 10f64.powi(scale) * magnitude;
+            CifValue::NumericSu { value, su }
+        })
+        .prop_filter(
+            "su must be finite",
+            |value| matches!(value, CifValue::NumericSu { su, .. } if su.is_finite()),
+        )
+}
+
+/// Arbitrary tag: `_` followed by lowercase letters, digits, `_`, and `.`
+pub fn arb_tag() -> impl Strategy<Value = String> {
+    "_[a-z][a-z0-9_.]{0,11}"
+}
+
+/// Arbitrary block or frame name
+pub fn arb_name() -> impl Strategy<Value = String> {
+    "[a-z0-9][a-z0-9_]{0,9}"
+}
+
+/// Arbitrary value valid in `version`.
+///
+/// Every [`CifValue`] variant is generated. CIF 2.0 values may be lists and
+/// tables nested up to three levels deep.
+pub fn arb_value(version: CifVersion) -> BoxedStrategy<CifValue> {
+    let leaf = prop_oneof![
+        4 => arb_text().prop_map(CifValue::Text),
+        // The parser only makes large values out of text fields
+        1 => arb_lines().prop_map(|text| CifValue::LargeText(text.into())),
+        3 => arb_number().prop_map(CifValue::Numeric),
+        2 => arb_numeric_su(),
+        1 => Just(CifValue::Unknown),
+        1 => Just(CifValue::NotApplicable),
+    ];
+    match version {
+        CifVersion::V1_1 => leaf.boxed(),
+        CifVersion::V2_0 => leaf
+            .prop_recursive(3, 24, 4, |inner| {
+                prop_oneof![
+                    vec(inner.clone(), 0..4).prop_map(CifValue::List),
+                    hash_map("[A-Za-z0-9 _]{0,8}", inner, 0..4).prop_map(CifValue::Table),
+                ]
+            })
+            .boxed(),
+    }
+}
+
+/// Arbitrary loop with one to four columns and up to four rows
+pub fn arb_loop(version: CifVersion) -> impl Strategy<Value = CifLoop> {
+    (vec(arb_tag(), 1..5), 0usize..5)
+        .prop_filter("loop tags must be unique", |(tags, _)| all_unique(tags))
+        .prop_flat_map(move |(tags, rows)| {
+            let width = tags.len();
+            (Just(tags), vec(vec(arb_value(version), width), rows))
+        })
+        .prop_map(|(tags, values)| {
+            let mut loop_ = CifLoop::new(tags);
+            loop_.values = values;
+            loop_
+        })
+}
+
+/// Arbitrary save frame with items and loops
+pub fn arb_frame(version: CifVersion) -> impl Strategy<Value = CifFrame> {
+    (
+        arb_name(),
+        hash_map(arb_tag(), arb_value(version), 0..5),
+        vec(arb_loop(version), 0..3),
+    )
+        .prop_filter("frame tags must be unique", |(_, items, loops)| {
+            tags_unique(items.keys(), loops)
+        })
+        .prop_map(|(name, items, loops)| {
+            let mut frame = CifFrame::new(name);
+            frame.items = items.into_iter().collect();
+            frame.loops = loops;
+            frame
+        })
+}
+
+/// Arbitrary data block with items, loops, and save frames
+pub fn arb_block(version: CifVersion) -> impl Strategy<Value = CifBlock> {
+    (
+        arb_name(),
+        hash_map(arb_tag(), arb_value(version), 0..6),
+        vec(arb_loop(version), 0..3),
+        vec(arb_frame(version), 0..3),
+    )
+        .prop_filter(
+            "block tags and frame names must be unique",
+            |(_, items, loops, frames)| {
+                tags_unique(items.keys(), loops)
+                    && all_unique(&frames.iter().map(|f| &f.name).collect::<Vec<_>>())
+            },
+        )
+        .prop_map(|(name, items, loops, frames)| {
+            let mut block = CifBlock::new(name);
+            block.items = items.into_iter().collect();
+            block.loops = loops;
+            block.frames = frames;
+            block
+        })
+}
+
+/// Arbitrary document of either CIF version
+pub fn arb_document() -> impl Strategy<Value = CifDocument> {
+    prop_oneof![Just(CifVersion::V1_1), Just(CifVersion::V2_0)].prop_flat_map(arb_document_in)
+}
+
+/// Arbitrary document of the given CIF version
+pub fn arb_document_in(version: CifVersion) -> impl Strategy<Value = CifDocument> {
+    vec(arb_block(version), 0..4)
+        .prop_filter("block names must be unique", |blocks| {
+            all_unique(&blocks.iter().map(|b| &b.name).collect::<Vec<_>>())
+        })
+        .prop_map(move |blocks| {
+            let mut doc = CifDocument::new_with_version(version);
+            doc.blocks = blocks;
+            doc
+        })
+}
+
+/// Write `doc`, parse it back, and compare.
+///
+/// On failure the message includes the written text, so the offending
+/// document can be reproduced directly.
+pub fn check_round_trip(doc: &CifDocument) -> Result<(), String> {
+    let text = doc.to_string();
+    let reparsed = CifDocument::parse(&text)
+        .map_err(|e| format!("written document failed to parse: {e}\n--- written ---\n{text}"))?;
+    if reparsed.semantically_equal(doc) {
+        Ok(())
+    } else {
+        Err(format!(
+            "re-parsed document differs from the original\n--- written ---\n{text}\
+             --- original ---\n{doc:#?}\n--- re-parsed ---\n{reparsed:#?}"
+        ))
+    }
+}
+
+/// Panic unless `doc` survives writing and re-parsing unchanged.
+///
+/// # Panics
+///
+/// Panics with the written text when [`check_round_trip`] fails.
+pub fn assert_round_trip(doc: &CifDocument) {
+    if let Err(message) = check_round_trip(doc) {
+        panic!("round trip failed: {message}");
+    }
+}
+
+/// Round-trip `cases` random documents generated from `seed`.
+///
+/// The same seed always generates the same documents, so a seed that fails
+/// in CI fails the same way locally. On failure the error names the seed and
+/// shows the smallest failing document proptest could shrink to.
+pub fn check_seed(seed: u64, cases: u32) -> Result<(), String> {
+    let config = Config {
+        cases,
+        failure_persistence: None,
+        ..Config::default()
+    };
+    let seed_bytes: Vec<u8> = seed.to_le_bytes().repeat(4);
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed_bytes);
+    let mut runner = TestRunner::new_with_rng(config, rng);
+
+    runner
+        .run(&arb_document(), |doc| {
+            check_round_trip(&doc).map_err(TestCaseError::fail)
+        })
+        .map_err(|err| match err {
+            TestError::Fail(reason, doc) => {
+                format!("seed {seed}: {reason}\n--- minimal document ---\n{doc}")
+            }
+            TestError::Abort(reason) => format!("seed {seed}: aborted: {reason}"),
+        })
+}
+
+/// Residues the synthetic chain is built from, as (name, side-chain atoms)
+const RESIDUES: &[(&str, &[(&str, &str)])] = &[
+    ("GLY", &[]),
+    ("ALA", &[("CB", "C")]),
+    ("SER", &[("CB", "C"), ("OG", "O")]),
+    ("CYS", &[("CB", "C"), ("SG", "S")]),
+    ("VAL", &[("CB", "C"), ("CG1", "C"), ("CG2", "C")]),
+    ("THR", &[("CB", "C"), ("OG1", "O"), ("CG2", "C")]),
+];
+
+/// A synthetic mmCIF file with `n_atoms` atom sites and `n_reflections`
+/// reflections.
+///
+/// The atoms form one protein chain, residue by residue, folded into
+/// helices along c that stand side by side in a P 21 21 21 cell; the
+/// reflections are indexed in order with made-up amplitudes. The output
+/// depends only on the arguments, so benchmarks that use it are comparable
+/// between runs.
+///
+/// # Examples
+///
+/// ```
+/// use cif_parser::testing::generate_mmcif;
+/// use cif_parser::Document;
+///
+/// let doc = Document::parse(&generate_mmcif(100, 50)).unwrap();
+/// let block = &doc.blocks[0];
+/// assert_eq!(block.find_loop("_atom_site.id").unwrap().len(), 100);
+/// assert_eq!(block.find_loop("_refln.index_h").unwrap().len(), 50);
+/// ```
+pub fn generate_mmcif(n_atoms: usize, n_reflections: usize) -> String {
+    // Fixed-seed linear congruential generator, uniform in [0, 1)
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut random = move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    let mut cif = String::with_capacity(100 * n_atoms + 40 * n_reflections + 1024);
+    cif.push_str(
+        "data_SYNTHETIC\n\
+         #\n\
+         _entry.id SYNTHETIC\n\
+         #\n\
+         _cell.entry_id SYNTHETIC\n\
+         _cell.length_a 61.400\n\
+         _cell.length_b 72.800\n\
+         _cell.length_c 95.300\n\
+         _cell.angle_alpha 90.00\n\
+         _cell.angle_beta 90.00\n\
+         _cell.angle_gamma 90.00\n\
+         #\n\
+         _symmetry.entry_id SYNTHETIC\n\
+         _symmetry.space_group_name_H-M 'P 21 21 21'\n\
+         #\n\
+         loop_\n\
+         _atom_site.group_PDB\n\
+         _atom_site.id\n\
+         _atom_site.type_symbol\n\
+         _atom_site.label_atom_id\n\
+         _atom_site.label_alt_id\n\
+         _atom_site.label_comp_id\n\
+         _atom_site.label_asym_id\n\
+         _atom_site.label_entity_id\n\
+         _atom_site.label_seq_id\n\
+         _atom_site.pdbx_PDB_ins_code\n\
+         _atom_site.Cartn_x\n\
+         _atom_site.Cartn_y\n\
+         _atom_site.Cartn_z\n\
+         _atom_site.occupancy\n\
+         _atom_site.B_iso_or_equiv\n\
+         _atom_site.pdbx_formal_charge\n\
+         _atom_site.auth_seq_id\n\
+         _atom_site.auth_comp_id\n\
+         _atom_site.auth_asym_id\n\
+         _atom_site.auth_atom_id\n\
+         _atom_site.pdbx_PDB_model_num\n",
+    );
+
+    let backbone = [("N", "N"), ("CA", "C"), ("C", "C"), ("O", "O")];
+    let mut id = 0;
+    let mut seq = 0;
+    while id < n_atoms {
+        seq += 1;
+        let (residue, side_chain) = RESIDUES[(seq - 1) % RESIDUES.len()];
+        // Helices of 60 residues on a 7 × 8 grid, so every atom is in the
+        // cell; 3.6 residues per turn, rising 1.5 Å per residue
+        let (helix, step) = ((seq - 1) / 60, (seq - 1) % 60);
+        let angle = step as f64 * 100f64.to_radians();
+        let (x0, y0, z0) = (
+            4.4 + 8.8 * (helix % 7) as f64 + 2.3 * angle.cos(),
+            4.5 + 9.1 * (helix / 7 % 8) as f64 + 2.3 * angle.sin(),
+            3.0 + 1.5 * step as f64,
+        );
+        for &(atom, element) in backbone.iter().chain(side_chain) {
+            if id == n_atoms {
+                break;
+            }
+            id += 1;
+            let _ = writeln!(
+                cif,
+                "ATOM {id} {element} {atom} . {residue} A 1 {seq} ? {:.3} {:.3} {:.3} 1.00 {:.2} ? \
+                 {seq} {residue} A {atom} 1",
+                x0 + 1.5 * (random() - 0.5),
+                y0 + 1.5 * (random() - 0.5),
+                z0 + 1.5 * (random() - 0.5),
+                10.0 + 30.0 * random(),
+            );
+        }
+    }
+
+    cif.push_str(
+        "#\n\
+         loop_\n\
+         _refln.crystal_id\n\
+         _refln.wavelength_id\n\
+         _refln.scale_group_code\n\
+         _refln.index_h\n\
+         _refln.index_k\n\
+         _refln.index_l\n\
+         _refln.status\n\
+         _refln.F_meas_au\n\
+         _refln.F_meas_sigma_au\n",
+    );
+    // Count through h, k, l from the origin, l fastest, skipping 0 0 0
+    let side = (n_reflections as f64 + 1.0).cbrt().ceil() as i64;
+    for n in 1..=n_reflections as i64 {
+        let (h, k, l) = (n / (side * side), n / side % side, n % side);
+        let f = 50.0 + 950.0 * random();
+        let _ = writeln!(
+            cif,
+            "1 1 1 {h} {k} {l} o {f:.1} {:.1}",
+            (0.02 + 0.05 * random()) * f
+        );
+    }
+    cif.push_str("#\n");
+    cif
+}
+
+fn all_unique<T: AsRef<str>>(names: &[T]) -> bool {
+    let mut seen = HashSet::new();
+    names
+        .iter()
+        .all(|name| seen.insert(name.as_ref().to_ascii_lowercase()))
+}
+
+/// Whether item tags and loop tags are distinct from each other
+fn tags_unique<'a>(items: impl Iterator<Item = &'a String>, loops: &'a [CifLoop]) -> bool {
+    let tags: Vec<&String> = items.chain(loops.iter().flat_map(|l| &l.tags)).collect();
+    all_unique(&tags)
+}
@@ -0,0 +1,705 @@
+//! Cleanup of free-text values: line wrapping, whitespace, encoding
+//! artifacts, and CIF markup.
+//!
+//! Text fields such as `_publ_section_title` or `_exptl_special_details` are
+//! usually hard-wrapped, and write special characters with the IUCr markup
+//! conventions: `\'e` for é, `\a` for α, `\%` for °, `^2^` for a superscript
+//! and `~2~` for a subscript. [`normalize`] turns such text into plain
+//! Unicode paragraphs; [`to_cif_markup`] goes the other way for writing.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::text::{normalize, NormalizeTextOptions};
+//!
+//! let raw = "\nThe  structure of Ni(H~2~O)~6~^2+^ at 100 K:\nthe angle \\b is\n90\\%.\n";
+//! let text = normalize(raw, &NormalizeTextOptions::default());
+//! assert_eq!(text, "The structure of Ni(H₂O)₆²⁺ at 100 K: the angle β is 90°.");
+//! ```
+
+use crate::ast::CifValue;
+
+/// What [`normalize`] does with markup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Markup {
+    /// Convert CIF markup to Unicode
+    #[default]
+    Unicode,
+    /// Convert Unicode characters that have a markup form to CIF markup
+    Cif,
+    /// Leave markup and special characters as they are
+    Keep,
+}
+
+/// Options for [`normalize`]. All cleanups are enabled by default.
+#[derive(Debug, Clone)]
+pub struct NormalizeTextOptions {
+    /// Undo CIF 2.0 line folding and join hard-wrapped lines; paragraphs
+    /// (separated by blank lines) are kept apart by one blank line
+    pub unwrap_paragraphs: bool,
+    /// Collapse runs of spaces and tabs, and trim lines
+    pub collapse_whitespace: bool,
+    /// Repair UTF-8 text that was decoded as Windows-1252, such as `â€™`
+    /// for `’`
+    pub fix_encoding: bool,
+    /// How to treat markup
+    pub markup: Markup,
+}
+
+impl Default for NormalizeTextOptions {
+    fn default() -> Self {
+        NormalizeTextOptions {
+            unwrap_paragraphs: true,
+            collapse_whitespace: true,
+            fix_encoding: true,
+            markup: Markup::Unicode,
+        }
+    }
+}
+
+impl NormalizeTextOptions {
+    /// Create the default options, with every cleanup enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable joining of hard-wrapped lines
+    pub fn unwrap_paragraphs(mut self, unwrap: bool) -> Self {
+        self.unwrap_paragraphs = unwrap;
+        self
+    }
+
+    /// Enable or disable collapsing of whitespace
+    pub fn collapse_whitespace(mut self, collapse: bool) -> Self {
+        self.collapse_whitespace = collapse;
+        self
+    }
+
+    /// Enable or disable repair of mis-decoded text
+    pub fn fix_encoding(mut self, fix: bool) -> Self {
+        self.fix_encoding = fix;
+        self
+    }
+
+    /// Set how markup is treated
+    pub fn markup(mut self, markup: Markup) -> Self {
+        self.markup = markup;
+        self
+    }
+}
+
+/// Clean up a free-text value according to `options`.
+///
+/// Leading and trailing blank lines are always removed.
+pub fn normalize(text: &str, options: &NormalizeTextOptions) -> String {
+    let mut text = if options.fix_encoding {
+        fix_encoding(text)
+    } else {
+        text.to_string()
+    };
+    if options.unwrap_paragraphs {
+        text = unwrap_paragraphs(&unfold(&text));
+    }
+    if options.collapse_whitespace {
+        text = text
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    let text = text.trim_matches(['\n', '\r']);
+    match options.markup {
+        Markup::Unicode => from_cif_markup(text),
+        Markup::Cif => to_cif_markup(text),
+        Markup::Keep => text.to_string(),
+    }
+}
+
+impl CifValue {
+    /// Get a text value cleaned up with [`normalize`]; `None` for other
+    /// values.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::text::NormalizeTextOptions;
+    /// use cif_parser::CifValue;
+    ///
+    /// let value = CifValue::Text("\nCrystal structure of\nCaF~2~\n".to_string());
+    /// let options = NormalizeTextOptions::default();
+    /// assert_eq!(
+    ///     value.normalized_text(&options).as_deref(),
+    ///     Some("Crystal structure of CaF₂")
+    /// );
+    /// ```
+    pub fn normalized_text(&self, options: &NormalizeTextOptions) -> Option<String> {
+        self.as_string().map(|s| normalize(s, options))
+    }
+}
+
+/// Undo CIF 2.0 line folding.
+///
+/// A folded text field starts with a `\` alone on its first line; every line
+/// ending in `\` then continues onto the next without a break. Other text is
+/// returned unchanged.
+pub(crate) fn unfold(text: &str) -> String {
+    match text.strip_prefix('\\') {
+        Some(rest)
+            if rest
+                .trim_start_matches([' ', '\t'])
+                .starts_with(['\n', '\r']) =>
+        {
+            let mut joined = String::with_capacity(rest.len());
+            for line in rest.trim_start().lines() {
+                match line.trim_end().strip_suffix('\\') {
+                    Some(folded) => joined.push_str(folded),
+                    None => {
+                        joined.push_str(line);
+                        joined.push('\n');
+                    }
+                }
+            }
+            joined
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Join the lines of each paragraph with a space
+fn unwrap_paragraphs(text: &str) -> String {
+    let mut paragraphs: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in text.lines() {
+        let line = line.trim();
+        match paragraphs.last_mut() {
+            Some(current) if line.is_empty() => {
+                if !current.is_empty() {
+                    paragraphs.push(Vec::new());
+                }
+            }
+            Some(current) => current.push(line),
+            None => unreachable!("starts with one paragraph"),
+        }
+    }
+    paragraphs
+        .iter()
+        .filter(|lines| !lines.is_empty())
+        .map(|lines| lines.join(" "))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Windows-1252 characters for bytes 0x80 to 0x9F; the five undefined bytes
+/// are commonly decoded to the C1 control with the same value
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+/// The Windows-1252 byte for a character, if it has one
+fn cp1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0..=0xff => Some(c as u8),
+        _ => CP1252_HIGH
+            .iter()
+            .position(|&h| h == c)
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+/// Re-decode runs of characters that are UTF-8 bytes read as Windows-1252.
+///
+/// Only complete, valid UTF-8 sequences are replaced, so genuine Latin-1
+/// text such as `café` is left alone.
+fn fix_encoding(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let lead = cp1252_byte(chars[i]).filter(|b| (0xc2..=0xf4).contains(b));
+        let length = match lead {
+            Some(0xc2..=0xdf) => 2,
+            Some(0xe0..=0xef) => 3,
+            Some(_) => 4,
+            None => 1,
+        };
+        if length > 1 && i + length <= chars.len() {
+            let bytes: Option<Vec<u8>> = chars[i..i + length]
+                .iter()
+                .map(|&c| cp1252_byte(c))
+                .collect();
+            if let Some(decoded) = bytes.and_then(|b| String::from_utf8(b).ok()) {
+                out.push_str(&decoded);
+                i += length;
+                continue;
+            }
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Greek letters, written `\a` to `\z` and `\A` to `\Z`
+const GREEK: &[(char, char)] = &[
+    ('a', 'α'),
+    ('b', 'β'),
+    ('c', 'χ'),
+    ('d', 'δ'),
+    ('e', 'ε'),
+    ('f', 'φ'),
+    ('g', 'γ'),
+    ('h', 'η'),
+    ('i', 'ι'),
+    ('k', 'κ'),
+    ('l', 'λ'),
+    ('m', 'μ'),
+    ('n', 'ν'),
+    ('o', 'ο'),
+    ('p', 'π'),
+    ('q', 'θ'),
+    ('r', 'ρ'),
+    ('s', 'σ'),
+    ('t', 'τ'),
+    ('u', 'υ'),
+    ('w', 'ω'),
+    ('x', 'ξ'),
+    ('y', 'ψ'),
+    ('z', 'ζ'),
+    ('A', 'Α'),
+    ('B', 'Β'),
+    ('C', 'Χ'),
+    ('D', 'Δ'),
+    ('E', 'Ε'),
+    ('F', 'Φ'),
+    ('G', 'Γ'),
+    ('H', 'Η'),
+    ('I', 'Ι'),
+    ('K', 'Κ'),
+    ('L', 'Λ'),
+    ('M', 'Μ'),
+    ('N', 'Ν'),
+    ('O', 'Ο'),
+    ('P', 'Π'),
+    ('Q', 'Θ'),
+    ('R', 'Ρ'),
+    ('S', 'Σ'),
+    ('T', 'Τ'),
+    ('U', 'Υ'),
+    ('W', 'Ω'),
+    ('X', 'Ξ'),
+    ('Y', 'Ψ'),
+    ('Z', 'Ζ'),
+];
+
+/// Accents, written `\'e`: the markup character, its combining mark, and
+/// the precomposed forms of the letters that have one
+const ACCENTS: &[(char, char, &str, &str)] = &[
+    (
+        '\'',
+        '\u{301}',
+        "aeiouyAEIOUYcCnNsSzZlLrR",
+        "áéíóúýÁÉÍÓÚÝćĆńŃśŚźŹĺĹŕŔ",
+    ),
+    ('`', '\u{300}', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    (
+        '^',
+        '\u{302}',
+        "aeiouAEIOUcCgGhHjJsSwWyY",
+        "âêîôûÂÊÎÔÛĉĈĝĜĥĤĵĴŝŜŵŴŷŶ",
+    ),
+    ('"', '\u{308}', "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ('~', '\u{303}', "aeinouAEINOU", "ãẽĩñõũÃẼĨÑÕŨ"),
+    (';', '\u{328}', "aeiuAEIU", "ąęįųĄĘĮŲ"),
+    ('>', '\u{30b}', "ouOU", "őűŐŰ"),
+    ('=', '\u{304}', "aeiouAEIOU", "āēīōūĀĒĪŌŪ"),
+    ('.', '\u{307}', "cegzCEGZI", "ċėġżĊĖĠŻİ"),
+    ('<', '\u{30c}', "cdenrszCDENRSZ", "čďěňřšžČĎĚŇŘŠŽ"),
+    (',', '\u{327}', "cgklnrstCGKLNRST", "çģķļņŗşţÇĢĶĻŅŖŞŢ"),
+    ('(', '\u{306}', "aeguAEGU", "ăĕğŭĂĔĞŬ"),
+];
+
+/// Other letters with a two-character code after the backslash
+const LETTERS: &[(&str, char)] = &[
+    ("%a", 'å'),
+    ("%A", 'Å'),
+    ("/o", 'ø'),
+    ("/O", 'Ø'),
+    ("/l", 'ł'),
+    ("/L", 'Ł'),
+    ("/d", 'đ'),
+    ("/D", 'Đ'),
+    ("?i", 'ı'),
+    ("&s", 'ß'),
+];
+
+/// Symbols written with two backslashes, such as `\\times`. Longer names
+/// come first so that `\\simeq` is not read as `\\sim` followed by `eq`.
+const SYMBOLS: &[(&str, char)] = &[
+    ("rightarrow", '→'),
+    ("leftarrow", '←'),
+    ("square", '□'),
+    ("langle", '⟨'),
+    ("rangle", '⟩'),
+    ("simeq", '≃'),
+    ("infty", '∞'),
+    ("times", '×'),
+    ("sim", '∼'),
+    ("neq", '≠'),
+    ("ddb", '≅'),
+    ("db", '='),
+    ("tb", '≡'),
+];
+
+/// Superscript forms, written `^...^`
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('n', 'ⁿ'),
+    ('i', 'ⁱ'),
+];
+
+/// Subscript forms, written `~...~`
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('o', 'ₒ'),
+    ('x', 'ₓ'),
+    ('h', 'ₕ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('p', 'ₚ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+];
+
+/// Look up `c` in the first column of a table
+fn lookup(table: &[(char, char)], c: char) -> Option<char> {
+    table.iter().find(|(from, _)| *from == c).map(|(_, to)| *to)
+}
+
+/// Look up `c` in the second column of a table
+fn reverse(table: &[(char, char)], c: char) -> Option<char> {
+    table.iter().find(|(_, to)| *to == c).map(|(from, _)| *from)
+}
+
+/// Convert CIF markup to Unicode.
+///
+/// Handles the IUCr conventions: Greek letters (`\a`), accents (`\'e`,
+/// `` \`e ``, `\^e`, `\"u`, `\~n`, `\;a`, `\>o`, `\=a`, `\.z`, `\<c`, `\,c`,
+/// `\(g`), special letters (`\%a`, `\/o`, `\?i`, `\&s`), the degree sign
+/// (`\%`), symbols (`\\times`, `\\infty`, `\\db`, ...), `+-` and `-+`, and
+/// `^...^` superscripts and `~...~` subscripts. A superscript or subscript
+/// containing a character with no Unicode form is left as written, as is
+/// anything else that is not markup.
+///
+/// # Examples
+/// ```
+/// use cif_parser::text::from_cif_markup;
+///
+/// assert_eq!(from_cif_markup("Schr\\\"odinger"), "Schrödinger");
+/// assert_eq!(from_cif_markup("1.54 \\%A, R~int~ = 0.03"), "1.54 Å, R~int~ = 0.03");
+/// ```
+pub fn from_cif_markup(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (converted, consumed) = match c {
+            '\\' => backslash_markup(rest),
+            '^' => script(rest, '^', SUPERSCRIPTS),
+            '~' => script(rest, '~', SUBSCRIPTS),
+            '+' if rest.starts_with("+-") => (Some('±'.to_string()), 2),
+            '-' if rest.starts_with("-+") => (Some('∓'.to_string()), 2),
+            _ => (None, c.len_utf8()),
+        };
+        match converted {
+            Some(converted) => out.push_str(&converted),
+            None => out.push_str(&rest[..consumed]),
+        }
+        rest = &rest[consumed..];
+    }
+    out
+}
+
+/// Convert the markup at the start of `text`, which starts with `\`.
+/// Returns the Unicode text (or `None` to copy the input) and the number of
+/// bytes consumed.
+fn backslash_markup(text: &str) -> (Option<String>, usize) {
+    let after = &text[1..];
+    if let Some(name) = after.strip_prefix('\\') {
+        return match SYMBOLS.iter().find(|(n, _)| name.starts_with(n)) {
+            Some((n, symbol)) => (Some(symbol.to_string()), 2 + n.len()),
+            None => (None, 2),
+        };
+    }
+    if let Some((code, letter)) = LETTERS.iter().find(|(code, _)| after.starts_with(code)) {
+        return (Some(letter.to_string()), 1 + code.len());
+    }
+    let mut chars = after.chars();
+    let (Some(first), second) = (chars.next(), chars.next()) else {
+        return (None, 1);
+    };
+    if first == '%' {
+        return (Some('°'.to_string()), 2);
+    }
+    if let Some(greek) = lookup(GREEK, first) {
+        return (Some(greek.to_string()), 2);
+    }
+    let accent = ACCENTS.iter().find(|(mark, ..)| *mark == first);
+    match (accent, second) {
+        (Some((_, combining, bases, composed)), Some(base)) if base.is_alphabetic() => {
+            let accented = match bases.chars().position(|b| b == base) {
+                Some(i) => composed.chars().nth(i).map(String::from),
+                None => None,
+            };
+            let accented = accented.unwrap_or_else(|| format!("{base}{combining}"));
+            (Some(accented), 2 + base.len_utf8())
+        }
+        _ => (None, 1),
+    }
+}
+
+/// Convert a `^...^` or `~...~` run at the start of `text`
+fn script(text: &str, delimiter: char, table: &[(char, char)]) -> (Option<String>, usize) {
+    let inner = &text[1..];
+    let Some(end) = inner.find(delimiter) else {
+        return (None, 1);
+    };
+    let content = &inner[..end];
+    let converted: Option<String> = content
+        .chars()
+        .map(|c| lookup(table, if c == '−' { '-' } else { c }))
+        .collect();
+    match converted {
+        Some(converted) if !content.is_empty() => (Some(converted), end + 2),
+        // Leave the whole run as written rather than re-reading its closing
+        // delimiter as an opening one
+        _ if !content.is_empty() && !content.contains(char::is_whitespace) => (None, end + 2),
+        _ => (None, 1),
+    }
+}
+
+/// Convert Unicode characters to CIF markup, for writing.
+///
+/// The reverse of [`from_cif_markup`] for every character that has a
+/// markup form; letters with a combining accent are written with the
+/// accent's markup too. Other text, including ASCII, is unchanged, except
+/// that the `=` written for `\\db` cannot be told apart from an equals sign
+/// and stays as it is.
+///
+/// # Examples
+/// ```
+/// use cif_parser::text::to_cif_markup;
+///
+/// assert_eq!(to_cif_markup("Å, 90°, Ni(H₂O)₆²⁺"), "\\%A, 90\\%, Ni(H~2~O)~6~^2+^");
+/// ```
+pub fn to_cif_markup(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if let Some(plain) = reverse(SUPERSCRIPTS, c) {
+            out.push('^');
+            out.push(plain);
+            while let Some(next) = chars.peek().and_then(|&n| reverse(SUPERSCRIPTS, n)) {
+                out.push(next);
+                chars.next();
+            }
+            out.push('^');
+            continue;
+        }
+        if let Some(plain) = reverse(SUBSCRIPTS, c) {
+            out.push('~');
+            out.push(plain);
+            while let Some(next) = chars.peek().and_then(|&n| reverse(SUBSCRIPTS, n)) {
+                out.push(next);
+                chars.next();
+            }
+            out.push('~');
+            continue;
+        }
+        if let Some(&(mark, ..)) = chars
+            .peek()
+            .and_then(|&n| ACCENTS.iter().find(|(_, combining, ..)| *combining == n))
+        {
+            chars.next();
+            out.extend(['\\', mark, c]);
+            continue;
+        }
+        out.push_str(&char_markup(c).unwrap_or_else(|| c.to_string()));
+    }
+    out
+}
+
+/// Markup for a single character, if it has one
+fn char_markup(c: char) -> Option<String> {
+    if c.is_ascii() {
+        return None;
+    }
+    if let Some(letter) = reverse(GREEK, c) {
+        return Some(format!("\\{letter}"));
+    }
+    match c {
+        'µ' => return Some("\\m".to_string()),
+        'ς' => return Some("\\s".to_string()),
+        '°' => return Some("\\%".to_string()),
+        '±' => return Some("+-".to_string()),
+        '∓' => return Some("-+".to_string()),
+        _ => {}
+    }
+    if let Some((code, _)) = LETTERS.iter().find(|(_, letter)| *letter == c) {
+        return Some(format!("\\{code}"));
+    }
+    if let Some((name, _)) = SYMBOLS.iter().find(|(_, symbol)| *symbol == c) {
+        return Some(format!("\\\\{name}"));
+    }
+    ACCENTS.iter().find_map(|(mark, _, bases, composed)| {
+        let i = composed.chars().position(|a| a == c)?;
+        Some(format!("\\{mark}{}", bases.chars().nth(i)?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Markup and its Unicode form, converted in both directions
+    const ROUND_TRIP: &[(&str, &str)] = &[
+        ("\\a\\b\\g\\d\\q\\m\\p\\s\\w", "αβγδθμπσω"),
+        ("\\A\\D\\G\\Q\\S\\W\\F\\Y", "ΑΔΓΘΣΩΦΨ"),
+        (
+            "\\c\\e\\f\\h\\i\\k\\l\\n\\o\\r\\t\\u\\x\\y\\z",
+            "χεφηικλνορτυξψζ",
+        ),
+        ("Caf\\'e", "Café"),
+        ("\\`a la", "à la"),
+        ("f\\^ete", "fête"),
+        ("M\\\"uller", "Müller"),
+        ("Pe\\~na", "Peña"),
+        ("\\;a", "ą"),
+        ("Erd\\>os", "Erdős"),
+        ("\\=a", "ā"),
+        ("\\.z", "ż"),
+        ("Dvo\\<r\\'ak", "Dvořák"),
+        ("Fran\\,cois", "François"),
+        ("\\(g", "ğ"),
+        ("\\%Angstr\\\"om", "Ångström"),
+        ("\\%a", "å"),
+        ("S\\/oren", "Søren"),
+        ("\\/O", "Ø"),
+        ("\\/l\\/L", "łŁ"),
+        ("\\/d\\/D", "đĐ"),
+        ("\\?i", "ı"),
+        ("Stra\\&se", "Straße"),
+        ("120\\%", "120°"),
+        ("1.5 +- 0.2", "1.5 ± 0.2"),
+        ("-+", "∓"),
+        ("2 \\\\times 3", "2 × 3"),
+        ("\\\\infty", "∞"),
+        ("\\\\neq", "≠"),
+        ("\\\\sim", "∼"),
+        ("\\\\simeq", "≃"),
+        ("\\\\rightarrow \\\\leftarrow", "→ ←"),
+        ("\\\\langle\\\\rangle", "⟨⟩"),
+        ("\\\\square", "□"),
+        ("C\\\\tbN", "C≡N"),
+        ("\\\\ddb", "≅"),
+        ("m^2^", "m²"),
+        ("SO~4~^2-^", "SO₄²⁻"),
+        ("R~int~", "R~int~"),
+        ("10^-3^", "10⁻³"),
+        ("x~(n)~", "x₍ₙ₎"),
+    ];
+
+    #[test]
+    fn test_markup_table() {
+        for (markup, unicode) in ROUND_TRIP {
+            assert_eq!(from_cif_markup(markup), *unicode, "{markup}");
+            assert_eq!(to_cif_markup(unicode), *markup, "{unicode}");
+        }
+    }
+
+    #[test]
+    fn test_accent_tables_line_up() {
+        for (mark, _, bases, composed) in ACCENTS {
+            assert_eq!(bases.chars().count(), composed.chars().count(), "{mark}");
+        }
+    }
+
+    #[test]
+    fn test_one_way_markup() {
+        for (markup, unicode) in [
+            ("C\\\\dbO", "C=O"),
+            ("\\j", "\\j"),
+            ("\\'1", "\\'1"),
+            ("a^b c^d", "a^b c^d"),
+            ("2^x^", "2^x^"),
+            ("~", "~"),
+            ("\\'w", "w\u{301}"),
+            ("back\\", "back\\"),
+        ] {
+            assert_eq!(from_cif_markup(markup), unicode, "{markup}");
+        }
+        assert_eq!(to_cif_markup("e\u{301}"), "\\'e");
+        assert_eq!(to_cif_markup("5 µm"), "5 \\mm");
+    }
+
+    #[test]
+    fn test_fix_encoding() {
+        assert_eq!(fix_encoding("donâ€™t â€œquoteâ€\u{9d}"), "don’t “quote”");
+        assert_eq!(fix_encoding("cafÃ© â€“ 90Â°"), "café – 90°");
+        assert_eq!(fix_encoding("café"), "café");
+        assert_eq!(fix_encoding("Ã"), "Ã");
+    }
+
+    #[test]
+    fn test_unwrap_paragraphs() {
+        let text = "\n  First line\n  wrapped here.\n\n\n  Second\n  paragraph.\n";
+        let options = NormalizeTextOptions::new().markup(Markup::Keep);
+        assert_eq!(
+            normalize(text, &options),
+            "First line wrapped here.\n\nSecond paragraph."
+        );
+
+        let folded = "\\\nCrystal struc\\\nture of\nX\n";
+        assert_eq!(normalize(folded, &options), "Crystal structure of X");
+    }
+
+    #[test]
+    fn test_options_can_be_disabled() {
+        let text = "a  b\nc";
+        let keep_lines = NormalizeTextOptions::new().unwrap_paragraphs(false);
+        assert_eq!(normalize(text, &keep_lines), "a b\nc");
+        let nothing = keep_lines.collapse_whitespace(false);
+        assert_eq!(normalize(text, &nothing), text);
+        let cif = NormalizeTextOptions::new().markup(Markup::Cif);
+        assert_eq!(normalize("90°\n", &cif), "90\\%");
+    }
+}
@@ -0,0 +1,380 @@
+//! Reading values as Rust types.
+//!
+//! [`FromCifValue`] is implemented by types that can be read from a single
+//! [`CifValue`], and [`CifBlock::get_as`] reads a tag as any of them. The
+//! built-in implementations cover numbers, integers, booleans, text, numbers
+//! with their standard uncertainty, and whitespace-separated vectors; for
+//! other types, implement the trait (see `examples/typed_values.rs`).
+//!
+//! A [`Converter`] does the same job as a value rather than a type, for
+//! conversions that need configuration, such as a target unit. Closures
+//! taking a `&CifValue` are converters.
+//!
+//! Errors carry the tag and the token that could not be read.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let cif = "data_x\n_cell_length_a 5.431(2)\n_refine_ls_hydrogen_treatment yes\n\
+//!            _exptl_crystal_face_hkl '1 0 0'\n_diffrn_ambient_temperature ?\n";
+//! let doc = Document::parse(cif).unwrap();
+//! let block = doc.first_block().unwrap();
+//!
+//! assert_eq!(block.get_as::<f64>("_cell_length_a").unwrap(), Some(5.431));
+//! assert_eq!(
+//!     block.get_as::<(f64, Option<f64>)>("_cell_length_a").unwrap(),
+//!     Some((5.431, Some(0.002)))
+//! );
+//! assert_eq!(block.get_as::<bool>("_refine_ls_hydrogen_treatment").unwrap(), Some(true));
+//! assert_eq!(
+//!     block.get_as::<[f64; 3]>("_exptl_crystal_face_hkl").unwrap(),
+//!     Some([1.0, 0.0, 0.0])
+//! );
+//! // Unknown values are an error unless asked for as an Option
+//! assert!(block.get_as::<f64>("_diffrn_ambient_temperature").is_err());
+//! assert_eq!(
+//!     block.get_as::<Option<f64>>("_diffrn_ambient_temperature").unwrap(),
+//!     Some(None)
+//! );
+//! ```
+
+use crate::ast::{CifBlock, CifValue, CifVersion};
+use crate::uncertainty;
+use std::error::Error;
+use std::fmt;
+
+/// A value that could not be read as the type asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    /// Tag the value was read from, when known
+    pub tag: Option<String>,
+    /// The value as written in CIF
+    pub raw: String,
+    /// What the value was expected to be, e.g. "a number"
+    pub expected: String,
+}
+
+impl ConversionError {
+    /// Error for `value`, which is not `expected` (e.g. "a Miller index")
+    pub fn new(value: &CifValue, expected: impl Into<String>) -> Self {
+        ConversionError {
+            tag: None,
+            raw: raw_token(value),
+            expected: expected.into(),
+        }
+    }
+
+    /// Record the tag the value was read from
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+}
+
+/// A value as written in CIF, without quotes around text
+fn raw_token(value: &CifValue) -> String {
+    match value {
+        CifValue::Text(s) => s.clone(),
+        CifValue::LargeText(s) => s.to_string(),
+        other => crate::writer::inline_value(other, CifVersion::V2_0),
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(tag) = &self.tag {
+            write!(f, "{tag}: ")?;
+        }
+        write!(f, "'{}' is not {}", self.raw, self.expected)
+    }
+}
+
+impl Error for ConversionError {}
+
+/// A type that can be read from one CIF value.
+///
+/// Implementations should fail with [`ConversionError::new`]; the tag is
+/// added by [`CifBlock::get_as`].
+pub trait FromCifValue: Sized {
+    /// Read `value` as this type
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError>;
+}
+
+/// Something that reads CIF values as `T`.
+///
+/// Implemented by closures `Fn(&CifValue) -> Result<T, ConversionError>`
+/// and, for every [`FromCifValue`] type, by [`Native`].
+pub trait Converter<T> {
+    /// Read `value` as a `T`
+    fn convert(&self, value: &CifValue) -> Result<T, ConversionError>;
+}
+
+impl<T, F> Converter<T> for F
+where
+    F: Fn(&CifValue) -> Result<T, ConversionError>,
+{
+    fn convert(&self, value: &CifValue) -> Result<T, ConversionError> {
+        self(value)
+    }
+}
+
+/// The [`Converter`] that uses a type's own [`FromCifValue`] implementation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Native;
+
+impl<T: FromCifValue> Converter<T> for Native {
+    fn convert(&self, value: &CifValue) -> Result<T, ConversionError> {
+        T::from_cif_value(value)
+    }
+}
+
+impl CifBlock {
+    /// Read a data item as `T`.
+    ///
+    /// Gives `Ok(None)` when the tag is absent, and an error naming the tag
+    /// and its value when the value cannot be read as `T`. `?` and `.` are
+    /// errors unless `T` is an `Option`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let doc = Document::parse("data_x\n_cell_formula_units_Z 4\n_symmetry_cell_setting ?\n").unwrap();
+    /// let block = doc.first_block().unwrap();
+    ///
+    /// assert_eq!(block.get_as::<i64>("_cell_formula_units_Z").unwrap(), Some(4));
+    /// assert_eq!(block.get_as::<i64>("_cell_measurement_reflns_used").unwrap(), None);
+    /// let error = block.get_as::<String>("_symmetry_cell_setting").unwrap_err();
+    /// assert_eq!(error.to_string(), "_symmetry_cell_setting: '?' is not text");
+    /// ```
+    pub fn get_as<T: FromCifValue>(&self, tag: &str) -> Result<Option<T>, ConversionError> {
+        self.get_with(tag, &Native)
+    }
+
+    /// Read a data item with a [`Converter`].
+    ///
+    /// As [`get_as`](Self::get_as), for conversions that need more than the
+    /// target type.
+    pub fn get_with<T>(
+        &self,
+        tag: &str,
+        converter: &impl Converter<T>,
+    ) -> Result<Option<T>, ConversionError> {
+        self.get_item(tag)
+            .map(|value| converter.convert(value).map_err(|e| e.with_tag(tag)))
+            .transpose()
+    }
+
+    /// Read a data item as a number, dropping any su.
+    ///
+    /// Unlike [`get_as`](Self::get_as), `?` and `.` give `Ok(None)`, as an
+    /// absent tag does; so do the other `get_*` shorthands. Use `get_as` to
+    /// tell them apart.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let doc = Document::parse("data_x\n_cell_length_a 5.431(2)\n_cell_length_b ?\n").unwrap();
+    /// let block = doc.first_block().unwrap();
+    ///
+    /// assert_eq!(block.get_f64("_cell_length_a").unwrap(), Some(5.431));
+    /// assert_eq!(block.get_f64("_cell_length_b").unwrap(), None);
+    /// assert_eq!(block.get_f64("_cell_length_c").unwrap(), None);
+    /// ```
+    pub fn get_f64(&self, tag: &str) -> Result<Option<f64>, ConversionError> {
+        self.get_known(tag)
+    }
+
+    /// Read a data item as a whole number; `?` and `.` give `Ok(None)`
+    pub fn get_i64(&self, tag: &str) -> Result<Option<i64>, ConversionError> {
+        self.get_known(tag)
+    }
+
+    /// Read a data item as text; `?` and `.` give `Ok(None)`.
+    ///
+    /// Numbers are not kept as written but formatted again, in their shortest
+    /// form: `007` reads as `"7"` and `1.50` as `"1.5"`. Codes that look like
+    /// numbers keep their digits only in a text field.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cif_parser::Document;
+    /// let doc = Document::parse("data_x\n_a 1.50\n_b\n;007\n;\n").unwrap();
+    /// let block = doc.first_block().unwrap();
+    ///
+    /// assert_eq!(block.get_str("_a").unwrap().as_deref(), Some("1.5"));
+    /// assert_eq!(block.get_str("_b").unwrap().as_deref(), Some("007"));
+    /// ```
+    pub fn get_str(&self, tag: &str) -> Result<Option<String>, ConversionError> {
+        self.get_known(tag)
+    }
+
+    /// Read a data item as a yes/no value; `?` and `.` give `Ok(None)`
+    pub fn get_bool(&self, tag: &str) -> Result<Option<bool>, ConversionError> {
+        self.get_known(tag)
+    }
+
+    /// [`get_as`](Self::get_as), with `?` and `.` read as absent
+    fn get_known<T: FromCifValue>(&self, tag: &str) -> Result<Option<T>, ConversionError> {
+        self.get_as::<Option<T>>(tag).map(Option::flatten)
+    }
+}
+
+/// A number and its su, also read from text such as a text field holding
+/// `5.431(2)`
+pub(crate) fn number_with_su(value: &CifValue) -> Option<(f64, Option<f64>)> {
+    value
+        .as_numeric_with_su()
+        .or_else(|| value.as_string().and_then(uncertainty::parse))
+}
+
+impl FromCifValue for f64 {
+    /// A number, or a number with an su (which is dropped)
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        number_with_su(value)
+            .map(|(number, _)| number)
+            .ok_or_else(|| ConversionError::new(value, "a number"))
+    }
+}
+
+impl FromCifValue for i64 {
+    /// A whole number, with or without an su
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        match number_with_su(value) {
+            Some((number, _)) if number.fract() == 0.0 && number.abs() < i64::MAX as f64 => {
+                Ok(number as i64)
+            }
+            _ => Err(ConversionError::new(value, "an integer")),
+        }
+    }
+}
+
+impl FromCifValue for bool {
+    /// `yes`/`no`, `y`/`n`, or `true`/`false` in any case, or `1`/`0`
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        let text = match value {
+            CifValue::Numeric(n) if *n == 1.0 => return Ok(true),
+            CifValue::Numeric(n) if *n == 0.0 => return Ok(false),
+            _ => value
+                .as_string()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase(),
+        };
+        match text.as_str() {
+            "yes" | "y" | "true" => Ok(true),
+            "no" | "n" | "false" => Ok(false),
+            _ => Err(ConversionError::new(value, "a yes/no value")),
+        }
+    }
+}
+
+impl FromCifValue for String {
+    /// Text, or a number formatted again in its shortest form
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        match value {
+            CifValue::Text(s) => Ok(s.clone()),
+            CifValue::LargeText(s) => Ok(s.to_string()),
+            CifValue::Numeric(_) | CifValue::NumericSu { .. } => Ok(raw_token(value)),
+            _ => Err(ConversionError::new(value, "text")),
+        }
+    }
+}
+
+impl FromCifValue for (f64, Option<f64>) {
+    /// A number and its su, if written with one
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        number_with_su(value).ok_or_else(|| ConversionError::new(value, "a number"))
+    }
+}
+
+impl FromCifValue for Vec<f64> {
+    /// Whitespace-separated numbers in text such as `'1 0 0'`, a CIF 2.0
+    /// list of numbers, or a single number
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        let numbers = match value {
+            CifValue::Numeric(n) | CifValue::NumericSu { value: n, .. } => Some(vec![*n]),
+            CifValue::List(values) => values.iter().map(CifValue::as_numeric).collect(),
+            _ => value.as_string().and_then(|s| {
+                s.split_whitespace()
+                    .map(|token| uncertainty::parse(token).map(|(number, _)| number))
+                    .collect()
+            }),
+        };
+        numbers.ok_or_else(|| ConversionError::new(value, "a list of numbers"))
+    }
+}
+
+impl FromCifValue for [f64; 3] {
+    /// Three numbers, read as for `Vec<f64>`
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        Vec::<f64>::from_cif_value(value)
+            .ok()
+            .and_then(|numbers| numbers.try_into().ok())
+            .ok_or_else(|| ConversionError::new(value, "three numbers"))
+    }
+}
+
+impl<T: FromCifValue> FromCifValue for Option<T> {
+    /// `None` for `?` and `.`, otherwise as for `T`
+    fn from_cif_value(value: &CifValue) -> Result<Self, ConversionError> {
+        match value {
+            CifValue::Unknown | CifValue::NotApplicable => Ok(None),
+            _ => T::from_cif_value(value).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read<T: FromCifValue>(token: &str) -> Result<T, ConversionError> {
+        T::from_cif_value(&CifValue::parse_value(token))
+    }
+
+    #[test]
+    fn test_raw_token_is_kept() {
+        assert_eq!(read::<f64>("abc").unwrap_err().raw, "abc");
+        assert_eq!(read::<bool>("2.5").unwrap_err().raw, "2.5");
+        assert_eq!(read::<i64>("?").unwrap_err().raw, "?");
+        let list = CifValue::List(vec![CifValue::Numeric(1.0), CifValue::Text("x".into())]);
+        assert_eq!(<[f64; 3]>::from_cif_value(&list).unwrap_err().raw, "[1 x]");
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(read::<i64>("-12").unwrap(), -12);
+        assert_eq!(read::<i64>("12(3)").unwrap(), 12);
+        assert!(read::<i64>("1.5").is_err());
+        assert!(read::<i64>("1e300").is_err());
+    }
+
+    #[test]
+    fn test_vectors() {
+        assert_eq!(read::<Vec<f64>>("'1 -1 0.5(2)'").unwrap(), [1.0, -1.0, 0.5]);
+        assert_eq!(read::<Vec<f64>>("7").unwrap(), [7.0]);
+        assert!(read::<Vec<f64>>("'1 a'").is_err());
+        assert!(read::<[f64; 3]>("'1 0'").is_err());
+    }
+
+    #[test]
+    fn test_closure_converter() {
+        let kelvin = |value: &CifValue| {
+            let text = value.as_string().unwrap_or_default();
+            text.strip_suffix('K')
+                .and_then(|n| n.trim().parse::<f64>().ok())
+                .ok_or_else(|| ConversionError::new(value, "a temperature in K"))
+        };
+        let block = crate::Document::parse("data_x\n_t '293 K'\n_u 'warm'\n")
+            .unwrap()
+            .blocks
+            .remove(0);
+        assert_eq!(block.get_with("_t", &kelvin).unwrap(), Some(293.0));
+        assert_eq!(
+            block.get_with("_u", &kelvin).unwrap_err().to_string(),
+            "_u: 'warm' is not a temperature in K"
+        );
+    }
+}
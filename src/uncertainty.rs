@@ -0,0 +1,250 @@
+//! Standard uncertainties: CIF notation and first-order propagation.
+//!
+//! CIF writes a measured number with its standard uncertainty (su, formerly
+//! "esd") in parentheses, scaled to the last digits of the number: `5.431(2)`
+//! means 5.431 ± 0.002. [`parse`] and [`format`] convert this notation, and
+//! [`propagate`] carries independent uncertainties through any formula.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::uncertainty;
+//!
+//! let (a, su_a) = uncertainty::parse("5.431(2)").unwrap();
+//! assert_eq!((a, su_a), (5.431, Some(0.002)));
+//!
+//! // Area of a rectangle with sides 5.431(2) and 3.000(4)
+//! let (area, su) = uncertainty::propagate(&[(a, 0.002), (3.0, 0.004)], |x| x[0] * x[1]);
+//! assert!((area - 16.293).abs() < 1e-9);
+//! assert_eq!(uncertainty::format(area, su), "16.29(2)");
+//! ```
+
+/// Split a CIF number such as `1.234(5)` into its value and su.
+///
+/// Numbers without an su give `None` for it. The su is scaled by the
+/// decimal places and exponent of the number, so `1.5e-3(4)` is
+/// 0.0015 ± 0.0004. Returns `None` for anything that is not a number.
+pub fn parse(s: &str) -> Option<(f64, Option<f64>)> {
+    let s = s.trim();
+    let Some((number, su)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) else {
+        return s.parse().ok().map(|value| (value, None));
+    };
+    if su.is_empty() || !su.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value: f64 = number.parse().ok()?;
+    let (mantissa, exponent) = match number.find(['e', 'E']) {
+        Some(i) => (&number[..i], number[i + 1..].parse::<i32>().ok()?),
+        None => (number, 0),
+    };
+    let decimals = mantissa.split_once('.').map_or(0, |(_, f)| f.len()) as i32;
+    // Read as a decimal, so that the same su always gives the same f64
+    // however it is scaled: `0.350(6)` and `0.3500(60)` agree
+    let su: f64 = format!("{su}e{}", exponent - decimals).parse().ok()?;
+    Some((value, Some(su)))
+}
+
+/// Write `value` with its su in CIF notation, e.g. `1.234(5)`.
+///
+/// The su is quoted to two digits when it would otherwise start with a 1
+/// (the "rule of 19"), and to one digit otherwise; the value is rounded to
+/// match. A zero or non-finite su gives the plain value.
+pub fn format(value: f64, su: f64) -> String {
+    let Some(decimals) = su_decimals(su) else {
+        return value.to_string();
+    };
+    let digits = (su * 10f64.powi(decimals as i32)).round();
+    format!("{value:.*}({digits})", decimals as usize)
+}
+
+/// Write `value` with its su in CIF notation without rounding either.
+///
+/// Both are written down to the last digit of whichever of their shortest
+/// round-trip forms goes further, so the text parses back to the same
+/// pair: `(5.431, 0.0015)` gives `5.4310(15)`. Numbers too small or too
+/// large to write plainly get an exponent, as `3.1e-12(2)`. This is how the
+/// writer keeps a [`CifValue::NumericSu`](crate::CifValue::NumericSu).
+pub fn format_exact(value: f64, su: f64) -> String {
+    if !(value.is_finite() && su.is_finite()) {
+        return value.to_string();
+    }
+    let (value_digits, value_exponent) = shortest_digits(value);
+    let (su_digits, su_exponent) = shortest_digits(su);
+    // Exponent of the last digit written
+    let last = (value_exponent + 1 - value_digits.len() as i32)
+        .min(su_exponent + 1 - su_digits.len() as i32);
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+    let plain = (value == 0.0 || value_exponent >= -5)
+        && value_exponent.max(su_exponent) < PLAIN_DIGITS
+        && last >= -PLAIN_DIGITS;
+    if plain {
+        // Whole numbers are written to the units
+        let last = last.min(0);
+        let first = value_exponent.max(0);
+        let text = place_digits(&value_digits, value_exponent, first, last, Some(0));
+        let su_text = place_digits(&su_digits, su_exponent, su_exponent.max(last), last, None);
+        return format!("{sign}{text}({su_text})");
+    }
+    // A zero value takes the su's exponent, so its digits stay few
+    let first = if value == 0.0 {
+        su_exponent
+    } else {
+        value_exponent
+    };
+    let mantissa = place_digits(&value_digits, value_exponent, first, last, Some(first));
+    let su_text = place_digits(&su_digits, su_exponent, su_exponent.max(last), last, None);
+    format!("{sign}{mantissa}e{first}({su_text})")
+}
+
+/// Powers of ten past which [`format_exact`] writes an exponent, as it
+/// also does for values below 10⁻⁵
+const PLAIN_DIGITS: i32 = 16;
+
+/// The digits of the shortest text that reads back as `x`, and the
+/// exponent of the first: 0.0015 gives `("15", -3)`
+fn shortest_digits(x: f64) -> (String, i32) {
+    let text = format!("{:e}", x.abs());
+    let (mantissa, exponent) = text.split_once('e').expect("{:e} writes an exponent");
+    let digits = mantissa.replace('.', "");
+    (
+        digits,
+        exponent.parse().expect("{:e} writes an integer exponent"),
+    )
+}
+
+/// `digits`, whose first is at power of ten `exponent`, written from power
+/// `first` down to power `last`, padded with zeros, with a decimal point
+/// after power `point` if any digits follow it
+fn place_digits(digits: &str, exponent: i32, first: i32, last: i32, point: Option<i32>) -> String {
+    let mut text = String::new();
+    for power in (last..=first).rev() {
+        let digit = usize::try_from(exponent - power)
+            .ok()
+            .and_then(|index| digits.as_bytes().get(index))
+            .map_or('0', |&digit| digit as char);
+        text.push(digit);
+        if Some(power) == point && power > last {
+            text.push('.');
+        }
+    }
+    text
+}
+
+/// Decimal places that show `su` to one or two digits by the rule of 19,
+/// or `None` for a zero or non-finite su
+pub(crate) fn su_decimals(su: f64) -> Option<u32> {
+    if !(su.is_finite() && su > 0.0) {
+        return None;
+    }
+    let mut decimals = (-su.log10()).ceil().max(0.0) as u32;
+    let scaled = su * 10f64.powi(decimals as i32);
+    if scaled < 1.95 && (scaled - scaled.round()).abs() > 1e-6 {
+        decimals += 1;
+    }
+    Some(decimals)
+}
+
+/// Evaluate `f` and its su from independent values and their sus.
+///
+/// Uses first-order propagation, `σ_f² = Σ (∂f/∂x_i · σ_i)²`, with the
+/// partial derivatives taken by central differences. Correlations between
+/// the inputs are ignored. Returns `(f(x), σ_f)`.
+pub fn propagate(values: &[(f64, f64)], f: impl Fn(&[f64]) -> f64) -> (f64, f64) {
+    let mut x: Vec<f64> = values.iter().map(|&(value, _)| value).collect();
+    let result = f(&x);
+
+    let mut variance = 0.0;
+    for (i, &(value, su)) in values.iter().enumerate() {
+        if su == 0.0 {
+            continue;
+        }
+        let step = value.abs().max(1.0) * 1e-6;
+        x[i] = value + step;
+        let above = f(&x);
+        x[i] = value - step;
+        let below = f(&x);
+        x[i] = value;
+        let derivative = (above - below) / (2.0 * step);
+        variance += (derivative * su).powi(2);
+    }
+    (result, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("5.431(2)"), Some((5.431, Some(0.002))));
+        assert_eq!(parse("12(3)"), Some((12.0, Some(3.0))));
+        assert_eq!(parse("7.25"), Some((7.25, None)));
+        let (value, su) = parse("1.5e-3(4)").unwrap();
+        assert_eq!(value, 1.5e-3);
+        assert!((su.unwrap() - 4e-4).abs() < 1e-12);
+        assert_eq!(parse("C1"), None);
+        assert_eq!(parse("1.0()"), None);
+        assert_eq!(parse("1.0(a)"), None);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(5.431, 0.002), "5.431(2)");
+        assert_eq!(format(5.431, 0.0015), "5.4310(15)");
+        assert_eq!(format(90.0, 0.001), "90.000(1)");
+        assert_eq!(format(1234.0, 15.0), "1234(15)");
+        assert_eq!(format(2.5, 0.0), "2.5");
+    }
+
+    #[test]
+    fn test_format_exact_round_trips() {
+        for text in [
+            "5.431(2)",
+            "5.4310(15)",
+            "12.3(10)",
+            "12(3)",
+            "-0.0015(4)",
+            "90.000(1)",
+        ] {
+            let (value, su) = parse(text).unwrap();
+            let written = format_exact(value, su.unwrap());
+            assert_eq!(written, text);
+            assert_eq!(parse(&written), Some((value, su)));
+        }
+        assert_eq!(format_exact(1.5e-3, 4e-4), "0.0015(4)");
+        assert_eq!(format_exact(1200.0, 100.0), "1200(100)");
+        assert_eq!(format_exact(3.1e-12, 2e-13), "3.1e-12(2)");
+        assert_eq!(format_exact(1.23456789012, 3e-11), "1.23456789012(3)");
+        assert_eq!(format_exact(6.02214076e23, 1e15), "6.02214076e23(1)");
+    }
+
+    #[test]
+    fn test_format_exact_reads_back_at_any_scale() {
+        let pairs = [
+            (3.1e-12, 2e-13),
+            (-4.5e-300, 1e-301),
+            (1.23456789012, 3e-11),
+            (0.1 + 0.2, 1e-17),
+            (6.02214076e23, 1e15),
+            (1.5e300, 2.5e299),
+            (0.0, 1e-20),
+            (7.0, 0.0),
+            (2.0, 5e-324),
+        ];
+        for (value, su) in pairs {
+            let written = format_exact(value, su);
+            assert_eq!(parse(&written), Some((value, Some(su))), "{written}");
+        }
+        assert_eq!(parse("0.35067000(6000)"), parse("0.35067(6)"));
+    }
+
+    #[test]
+    fn test_propagate_matches_analytic() {
+        // f = x·y² has σ_f² = (y²σ_x)² + (2xyσ_y)²
+        let (x, y, sx, sy) = (3.0, 2.0, 0.1, 0.05);
+        let (value, su) = propagate(&[(x, sx), (y, sy)], |v| v[0] * v[1] * v[1]);
+        let expected = ((y * y * sx).powi(2) + (2.0 * x * y * sy).powi(2)).sqrt();
+        assert_eq!(value, 12.0);
+        assert!((su - expected).abs() < 1e-8);
+    }
+}
@@ -0,0 +1,487 @@
+//! Snapshots of a document, and undo and redo for editors.
+//!
+//! [`CifDocument::snapshot`] records a read-only [`DocumentSnapshot`] that
+//! [`CifDocument::restore`] puts back, exactly as it was: writing the
+//! restored document gives the same bytes as writing the original.
+//!
+//! Snapshots share their parts: blocks, and each block's loops, are held
+//! behind an [`Arc`]. [`CifDocument::snapshot_from`] takes a snapshot that
+//! reuses every block and loop of an earlier one that has not changed since,
+//! so after an edit to one item only that block's items are copied, and a
+//! large loop is copied only when the loop itself changed. Parts are matched
+//! by block name and loop position and compared exactly, so `1.0` replacing
+//! `1` counts as a change.
+//!
+//! [`UndoStack`] keeps a bounded history built on this. Call
+//! [`push`](UndoStack::push) before each change; [`undo`](UndoStack::undo)
+//! and [`redo`](UndoStack::redo) move through the history. The stack drops
+//! its oldest snapshots beyond a number of steps or a memory budget, counting
+//! shared parts once.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::undo::UndoStack;
+//! use cif_parser::{Document, Value};
+//!
+//! let mut doc = Document::parse("data_a\n_title before\n").unwrap();
+//! let mut history = UndoStack::new(100);
+//!
+//! history.push(&doc);
+//! doc.blocks[0]
+//!     .items
+//!     .insert("_title".into(), Value::Text("after".into()));
+//!
+//! assert!(history.undo(&mut doc));
+//! assert_eq!(doc.blocks[0].get_item("_title").unwrap().as_string(), Some("before"));
+//! assert!(history.redo(&mut doc));
+//! assert_eq!(doc.blocks[0].get_item("_title").unwrap().as_string(), Some("after"));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue};
+use crate::memory::{frame_bytes, items_bytes, loop_bytes};
+use indexmap::IndexMap;
+use std::collections::{HashSet, VecDeque};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// A read-only copy of a document, taken by [`CifDocument::snapshot`].
+///
+/// Cheap to clone: clones share everything.
+#[derive(Debug, Clone)]
+pub struct DocumentSnapshot {
+    /// The document without its blocks: version, warnings, source, and
+    /// the rest
+    header: Arc<CifDocument>,
+    blocks: Vec<Arc<BlockSnapshot>>,
+}
+
+/// One block of a snapshot, its loops shared separately
+#[derive(Debug)]
+struct BlockSnapshot {
+    name: String,
+    items: IndexMap<String, CifValue>,
+    /// Each loop with its heap bytes
+    loops: Vec<(Arc<CifLoop>, usize)>,
+    frames: Vec<CifFrame>,
+    /// Heap bytes of everything but the loops
+    bytes: usize,
+}
+
+impl BlockSnapshot {
+    /// Snapshot `block`, reusing the loops of `base` that are unchanged
+    fn new(block: &CifBlock, base: Option<&BlockSnapshot>) -> Self {
+        let loops = block
+            .loops
+            .iter()
+            .enumerate()
+            .map(|(i, loop_)| match base.and_then(|base| base.loops.get(i)) {
+                Some((shared, bytes)) if same_loop(shared, loop_) => (shared.clone(), *bytes),
+                _ => (Arc::new(loop_.clone()), loop_bytes(loop_)),
+            })
+            .collect();
+        let bytes = size_of::<BlockSnapshot>()
+            + block.name.capacity()
+            + items_bytes(&block.items)
+            + block.loops.len() * size_of::<(Arc<CifLoop>, usize)>()
+            + block.frames.iter().map(frame_bytes).sum::<usize>();
+        BlockSnapshot {
+            name: block.name.clone(),
+            items: block.items.clone(),
+            loops,
+            frames: block.frames.clone(),
+            bytes,
+        }
+    }
+
+    /// Whether `block` is exactly this block
+    fn matches(&self, block: &CifBlock) -> bool {
+        self.name == block.name
+            && same_items(&self.items, &block.items)
+            && self.loops.len() == block.loops.len()
+            && self
+                .loops
+                .iter()
+                .zip(&block.loops)
+                .all(|((a, _), b)| same_loop(a, b))
+            && self.frames.len() == block.frames.len()
+            && self.frames.iter().zip(&block.frames).all(|(a, b)| {
+                a.name == b.name
+                    && same_items(&a.items, &b.items)
+                    && a.loops.len() == b.loops.len()
+                    && a.loops.iter().zip(&b.loops).all(|(x, y)| same_loop(x, y))
+            })
+    }
+
+    fn to_block(&self) -> CifBlock {
+        CifBlock {
+            name: self.name.clone(),
+            items: self.items.clone(),
+            loops: self.loops.iter().map(|(l, _)| CifLoop::clone(l)).collect(),
+            frames: self.frames.clone(),
+        }
+    }
+}
+
+/// Items equal in order, tag spelling, and value
+fn same_items(a: &IndexMap<String, CifValue>, b: &IndexMap<String, CifValue>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|((ta, va), (tb, vb))| ta == tb && same_value(va, vb))
+}
+
+fn same_loop(a: &CifLoop, b: &CifLoop) -> bool {
+    a.tags == b.tags
+        && a.values.len() == b.values.len()
+        && a.values.iter().zip(&b.values).all(|(ra, rb)| {
+            ra.len() == rb.len() && ra.iter().zip(rb).all(|(x, y)| same_value(x, y))
+        })
+}
+
+/// Values that write the same: unlike `==`, large and plain text differ,
+/// and numbers are compared bit for bit, so `-0` is not `0`
+fn same_value(a: &CifValue, b: &CifValue) -> bool {
+    match (a, b) {
+        (CifValue::Text(x), CifValue::Text(y)) => x == y,
+        (CifValue::LargeText(x), CifValue::LargeText(y)) => Arc::ptr_eq(x, y) || x == y,
+        (CifValue::Numeric(x), CifValue::Numeric(y)) => x.to_bits() == y.to_bits(),
+        (CifValue::NumericSu { value: x, su: p }, CifValue::NumericSu { value: y, su: q }) => {
+            x.to_bits() == y.to_bits() && p.to_bits() == q.to_bits()
+        }
+        (CifValue::Unknown, CifValue::Unknown) => true,
+        (CifValue::NotApplicable, CifValue::NotApplicable) => true,
+        (CifValue::List(x), CifValue::List(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(p, q)| same_value(p, q))
+        }
+        (CifValue::Table(x), CifValue::Table(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(key, p)| y.get(key).is_some_and(|q| same_value(p, q)))
+        }
+        _ => false,
+    }
+}
+
+/// The document's fields other than its blocks
+fn header(doc: &CifDocument) -> CifDocument {
+    CifDocument {
+        blocks: Vec::new(),
+        version: doc.version,
+        warnings: doc.warnings.clone(),
+        source: doc.source.clone(),
+        partial: doc.partial,
+        preamble: doc.preamble.clone(),
+        trailer: doc.trailer.clone(),
+        bare_frames: doc.bare_frames,
+        decimal_commas: doc.decimal_commas.clone(),
+    }
+}
+
+fn same_header(a: &CifDocument, b: &CifDocument) -> bool {
+    a.version == b.version
+        && a.warnings == b.warnings
+        && a.source == b.source
+        && a.partial == b.partial
+        && a.preamble == b.preamble
+        && a.trailer == b.trailer
+        && a.bare_frames == b.bare_frames
+        && a.decimal_commas == b.decimal_commas
+}
+
+impl DocumentSnapshot {
+    /// Number of blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Whether the snapshot has no blocks
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Block names, in order
+    pub fn block_names(&self) -> impl Iterator<Item = &str> {
+        self.blocks.iter().map(|b| b.name.as_str())
+    }
+
+    /// A new document with the snapshot's contents
+    pub fn to_document(&self) -> CifDocument {
+        let mut doc = header(&self.header);
+        doc.blocks = self.blocks.iter().map(|b| b.to_block()).collect();
+        doc
+    }
+
+    /// Whether block `index` of this snapshot and of `other` is the same
+    /// shared copy, so that keeping both costs nothing extra
+    pub fn shares_block(&self, other: &DocumentSnapshot, index: usize) -> bool {
+        match (self.blocks.get(index), other.blocks.get(index)) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Whether loop `loop_index` of block `index` is shared with `other`
+    pub fn shares_loop(&self, other: &DocumentSnapshot, index: usize, loop_index: usize) -> bool {
+        let loop_at = |snapshot: &DocumentSnapshot| {
+            snapshot
+                .blocks
+                .get(index)
+                .and_then(|b| b.loops.get(loop_index))
+                .map(|(l, _)| Arc::as_ptr(l))
+        };
+        matches!((loop_at(self), loop_at(other)), (Some(a), Some(b)) if a == b)
+    }
+
+    /// Estimated bytes held by this snapshot alone, counted as
+    /// [`CifDocument::memory_footprint`] counts them
+    pub fn bytes(&self) -> usize {
+        shared_bytes([self])
+    }
+
+    /// Bytes of the parts first seen here, adding their pointers to `seen`
+    fn bytes_unseen(&self, seen: &mut HashSet<*const ()>) -> usize {
+        let mut bytes = size_of::<DocumentSnapshot>();
+        if seen.insert(Arc::as_ptr(&self.header).cast()) {
+            bytes += self.header.memory_footprint().total_bytes;
+        }
+        bytes += self.blocks.capacity() * size_of::<Arc<BlockSnapshot>>();
+        for block in &self.blocks {
+            if seen.insert(Arc::as_ptr(block).cast()) {
+                bytes += block.bytes;
+            }
+            for (loop_, loop_bytes) in &block.loops {
+                if seen.insert(Arc::as_ptr(loop_).cast()) {
+                    bytes += size_of::<CifLoop>() + loop_bytes;
+                }
+            }
+        }
+        bytes
+    }
+}
+
+/// Bytes held by `snapshots` together, each shared part counted once
+fn shared_bytes<'a>(snapshots: impl IntoIterator<Item = &'a DocumentSnapshot>) -> usize {
+    let mut seen = HashSet::new();
+    snapshots
+        .into_iter()
+        .map(|snapshot| snapshot.bytes_unseen(&mut seen))
+        .sum()
+}
+
+impl CifDocument {
+    /// Take a read-only snapshot of the document, for
+    /// [`restore`](Self::restore) to put back.
+    ///
+    /// This copies the whole document; use
+    /// [`snapshot_from`](Self::snapshot_from) to share what an earlier
+    /// snapshot already holds.
+    pub fn snapshot(&self) -> DocumentSnapshot {
+        DocumentSnapshot {
+            header: Arc::new(header(self)),
+            blocks: self
+                .blocks
+                .iter()
+                .map(|block| Arc::new(BlockSnapshot::new(block, None)))
+                .collect(),
+        }
+    }
+
+    /// Take a snapshot that shares every block and loop left unchanged since
+    /// `base` was taken.
+    ///
+    /// A block is matched with the block of `base` at the same position if
+    /// it has the same name, otherwise the first with its name; its loops
+    /// are matched by position. Only changed parts are copied, though each
+    /// part is compared with `base` in full.
+    pub fn snapshot_from(&self, base: &DocumentSnapshot) -> DocumentSnapshot {
+        let header = match same_header(self, &base.header) {
+            true => base.header.clone(),
+            false => Arc::new(header(self)),
+        };
+        let blocks = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let earlier = base
+                    .blocks
+                    .get(i)
+                    .filter(|b| b.name == block.name)
+                    .or_else(|| base.blocks.iter().find(|b| b.name == block.name));
+                match earlier {
+                    Some(earlier) if earlier.matches(block) => earlier.clone(),
+                    _ => Arc::new(BlockSnapshot::new(block, earlier.map(|b| &**b))),
+                }
+            })
+            .collect();
+        DocumentSnapshot { header, blocks }
+    }
+
+    /// Put back the contents recorded in `snapshot`, replacing everything in
+    /// this document
+    pub fn restore(&mut self, snapshot: &DocumentSnapshot) {
+        *self = snapshot.to_document();
+    }
+}
+
+/// A bounded history of snapshots for undo and redo.
+///
+/// Each snapshot shares unchanged blocks and loops with the one before it.
+/// When the history grows past [`max_depth`](Self::max_depth) steps or the
+/// [`max_bytes`](Self::max_bytes) budget, the oldest steps are dropped; the
+/// latest step is always kept.
+#[derive(Debug, Clone)]
+pub struct UndoStack {
+    undo: VecDeque<DocumentSnapshot>,
+    redo: Vec<DocumentSnapshot>,
+    max_depth: usize,
+    max_bytes: Option<usize>,
+}
+
+impl UndoStack {
+    /// Create an empty history keeping at most `max_depth` undo steps, and
+    /// no memory limit
+    pub fn new(max_depth: usize) -> Self {
+        UndoStack {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_depth: max_depth.max(1),
+            max_bytes: None,
+        }
+    }
+
+    /// Also drop the oldest steps while the history holds more than
+    /// `max_bytes`, as estimated by [`memory_bytes`](Self::memory_bytes)
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// The most undo steps kept
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Record `doc` as it is before a change. Clears the redo steps.
+    pub fn push(&mut self, doc: &CifDocument) {
+        let snapshot = self.share(doc);
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+        self.trim();
+    }
+
+    /// Go back one step, saving the current state for [`redo`](Self::redo).
+    /// Returns `false`, leaving `doc` alone, if there is nothing to undo.
+    pub fn undo(&mut self, doc: &mut CifDocument) -> bool {
+        let Some(previous) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(doc.snapshot_from(&previous));
+        doc.restore(&previous);
+        true
+    }
+
+    /// Go forward one step undone by [`undo`](Self::undo). Returns `false`,
+    /// leaving `doc` alone, if there is nothing to redo.
+    pub fn redo(&mut self, doc: &mut CifDocument) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(doc.snapshot_from(&next));
+        doc.restore(&next);
+        self.trim();
+        true
+    }
+
+    /// Whether there is a step to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is a step to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Number of steps that can be undone
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Number of steps that can be redone
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Forget every step
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Estimated bytes held by the history, each block and loop shared
+    /// between snapshots counted once
+    pub fn memory_bytes(&self) -> usize {
+        shared_bytes(self.undo.iter().chain(&self.redo))
+    }
+
+    /// Snapshot `doc`, sharing with the latest snapshot held
+    fn share(&self, doc: &CifDocument) -> DocumentSnapshot {
+        match self.undo.back().or(self.redo.last()) {
+            Some(base) => doc.snapshot_from(base),
+            None => doc.snapshot(),
+        }
+    }
+
+    /// Drop the oldest undo steps beyond the limits
+    fn trim(&mut self) {
+        while self.undo.len() > self.max_depth {
+            self.undo.pop_front();
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            while self.undo.len() > 1 && self.memory_bytes() > max_bytes {
+                self.undo.pop_front();
+            }
+        }
+    }
+}
+
+impl Default for UndoStack {
+    /// A history of 100 steps
+    fn default() -> Self {
+        UndoStack::new(100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_value_is_exact() {
+        assert!(same_value(&CifValue::Numeric(1.0), &CifValue::Numeric(1.0)));
+        assert!(!same_value(
+            &CifValue::Numeric(0.0),
+            &CifValue::Numeric(-0.0)
+        ));
+        assert!(!same_value(
+            &CifValue::Text("x".into()),
+            &CifValue::LargeText("x".into())
+        ));
+        assert!(!same_value(
+            &CifValue::Text("1".into()),
+            &CifValue::Numeric(1.0)
+        ));
+    }
+
+    #[test]
+    fn test_item_order_is_a_change() {
+        let doc = CifDocument::parse("data_a\n_x 1\n_y 2\n").unwrap();
+        let base = doc.snapshot();
+        let mut reordered = doc.clone();
+        reordered.blocks[0].items.swap_indices(0, 1);
+        assert!(!reordered.snapshot_from(&base).shares_block(&base, 0));
+        assert!(doc.snapshot_from(&base).shares_block(&base, 0));
+    }
+}
@@ -0,0 +1,303 @@
+//! Converting CIF 1.1 documents to CIF 2.0.
+//!
+//! CIF 1.1 is ASCII, so its text spells accented letters, Greek letters,
+//! and superscripts with markup: `\'e`, `\%a`, `^2^`. CIF 2.0 is UTF-8 and
+//! can hold the characters themselves. [`CifDocument::upgrade_to_cif2`]
+//! returns a copy of a document marked as CIF 2.0, with the markup of every
+//! text value turned into Unicode by [`from_cif_markup`] unless
+//! [`UpgradeOptions::keep_markup`] is set. Written out, the copy starts with
+//! the `#\#CIF_2.0` header and is quoted by the CIF 2.0 rules.
+//!
+//! The [`UpgradeReport`] lists every value that changed, with its text
+//! before and after, and every value CIF 2.0 cannot hold: text with
+//! characters outside the CIF 2.0 character set, such as control
+//! characters, or with a line starting with `;` that neither a text field
+//! nor a triple-quoted string can enclose.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::upgrade::UpgradeOptions;
+//! use cif_parser::{CifVersion, Document};
+//!
+//! let cif = "\
+//! data_x
+//! _publ_contact_author_name 'Ren\\'e Dupr\\'e'
+//! _chemical_formula_moiety 'Cu^2+^, 2(Cl^-^)'
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let (upgraded, report) = doc.upgrade_to_cif2(&UpgradeOptions::new());
+//!
+//! assert_eq!(upgraded.version, CifVersion::V2_0);
+//! let block = &upgraded.blocks[0];
+//! assert_eq!(block.get_item("_publ_contact_author_name").unwrap().as_string(), Some("René Dupré"));
+//! assert_eq!(block.get_item("_chemical_formula_moiety").unwrap().as_string(), Some("Cu²⁺, 2(Cl⁻)"));
+//! assert_eq!(report.changes.len(), 2);
+//! assert!(upgraded.to_string().starts_with("#\\#CIF_2.0"));
+//! ```
+
+use crate::ast::{CifDocument, CifLoop, CifValue, CifVersion};
+use crate::text::from_cif_markup;
+use crate::writer::text_writable;
+use indexmap::IndexMap;
+use std::fmt;
+
+/// Settings for [`CifDocument::upgrade_to_cif2`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpgradeOptions {
+    /// Leave CIF markup in text as written rather than converting it to
+    /// Unicode
+    pub keep_markup: bool,
+}
+
+impl UpgradeOptions {
+    /// Create options that convert markup to Unicode
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether markup is left as written
+    pub fn keep_markup(mut self, keep: bool) -> Self {
+        self.keep_markup = keep;
+        self
+    }
+}
+
+/// Where a value is in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValuePlace {
+    /// Name of the data block
+    pub block: String,
+    /// Name of the save frame, if the value is in one
+    pub frame: Option<String>,
+    /// The tag, as written
+    pub tag: String,
+    /// Loop row, counting from 0, or `None` for a data item
+    pub row: Option<usize>,
+}
+
+impl fmt::Display for ValuePlace {
+    /// `data_x save_y _tag[2]`, without the frame for a value outside one
+    /// and without the row for a data item
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "data_{}", self.block)?;
+        if let Some(frame) = &self.frame {
+            write!(f, " save_{frame}")?;
+        }
+        write!(f, " {}", self.tag)?;
+        if let Some(row) = self.row {
+            write!(f, "[{row}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A text value that the upgrade changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeChange {
+    /// Where the value is
+    pub place: ValuePlace,
+    /// The text as it was
+    pub before: String,
+    /// The text as it is now
+    pub after: String,
+}
+
+impl fmt::Display for UpgradeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?} -> {:?}", self.place, self.before, self.after)
+    }
+}
+
+/// A text value CIF 2.0 cannot hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeProblem {
+    /// Where the value is
+    pub place: ValuePlace,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for UpgradeProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.place, self.message)
+    }
+}
+
+/// What [`CifDocument::upgrade_to_cif2`] changed and found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpgradeReport {
+    /// Every changed value, by block and save frame, items before loops,
+    /// loop values row by row
+    pub changes: Vec<UpgradeChange>,
+    /// Every value CIF 2.0 cannot hold, in the same order
+    pub problems: Vec<UpgradeProblem>,
+}
+
+impl UpgradeReport {
+    /// Whether no value was changed and none has a problem
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.problems.is_empty()
+    }
+}
+
+impl fmt::Display for UpgradeReport {
+    /// One line per change, then one per problem
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        for problem in &self.problems {
+            writeln!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl CifDocument {
+    /// A copy of this document as CIF 2.0, with CIF markup in text values
+    /// turned into Unicode. See the [module documentation](crate::upgrade).
+    ///
+    /// Text values are converted, including those inside lists and tables;
+    /// numbers, `?`, `.`, tags, and block names are left alone. Parse
+    /// warnings are carried over.
+    pub fn upgrade_to_cif2(&self, options: &UpgradeOptions) -> (CifDocument, UpgradeReport) {
+        let mut upgraded = self.clone();
+        upgraded.version = CifVersion::V2_0;
+        let mut report = UpgradeReport::default();
+        for block in &mut upgraded.blocks {
+            let mut upgrader = Upgrader {
+                options,
+                report: &mut report,
+                block: &block.name,
+                frame: None,
+            };
+            upgrader.container(&mut block.items, &mut block.loops);
+            for frame in &mut block.frames {
+                upgrader.frame = Some(&frame.name);
+                upgrader.container(&mut frame.items, &mut frame.loops);
+            }
+        }
+        (upgraded, report)
+    }
+}
+
+/// Upgrades the values of one block and its save frames
+struct Upgrader<'a> {
+    options: &'a UpgradeOptions,
+    report: &'a mut UpgradeReport,
+    block: &'a str,
+    frame: Option<&'a str>,
+}
+
+impl Upgrader<'_> {
+    fn container(&mut self, items: &mut IndexMap<String, CifValue>, loops: &mut [CifLoop]) {
+        for (tag, value) in items.iter_mut() {
+            self.value(value, tag, None);
+        }
+        for loop_ in loops {
+            for (row, values) in loop_.values.iter_mut().enumerate() {
+                for (tag, value) in loop_.tags.iter().zip(values) {
+                    self.value(value, tag, Some(row));
+                }
+            }
+        }
+    }
+
+    fn value(&mut self, value: &mut CifValue, tag: &str, row: Option<usize>) {
+        let place = || ValuePlace {
+            block: self.block.to_string(),
+            frame: self.frame.map(str::to_string),
+            tag: tag.to_string(),
+            row,
+        };
+        match value {
+            CifValue::Text(_) | CifValue::LargeText(_) => {
+                let text = value.as_string().unwrap_or_default();
+                if !self.options.keep_markup {
+                    let converted = from_cif_markup(text);
+                    if converted != text {
+                        let before = text.to_string();
+                        // Large text stays in its shared form
+                        *value = match value {
+                            CifValue::LargeText(_) => CifValue::LargeText(converted.into()),
+                            _ => CifValue::Text(converted),
+                        };
+                        self.report.changes.push(UpgradeChange {
+                            place: place(),
+                            before,
+                            after: value.as_string().unwrap_or_default().to_string(),
+                        });
+                    }
+                }
+                if let Some(message) = problem(value.as_string().unwrap_or_default()) {
+                    self.report.problems.push(UpgradeProblem {
+                        place: place(),
+                        message,
+                    });
+                }
+            }
+            CifValue::List(values) => {
+                for member in values {
+                    self.value(member, tag, row);
+                }
+            }
+            CifValue::Table(entries) => {
+                // In key order, as the writer writes them, so that the
+                // report does not depend on hashing
+                let mut members: Vec<_> = entries.iter_mut().collect();
+                members.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                for (_, member) in members {
+                    self.value(member, tag, row);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether CIF 2.0 allows `c` anywhere in a file: anything but control
+/// characters, surrogates, and noncharacters
+fn is_cif2_char(c: char) -> bool {
+    match c {
+        '\t' | '\n' | '\r' | ' '..='~' => true,
+        '\u{A0}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}' => true,
+        '\u{10000}'.. => (c as u32) & 0xFFFE != 0xFFFE,
+        _ => false,
+    }
+}
+
+/// Why CIF 2.0 cannot hold `text`, if it cannot
+fn problem(text: &str) -> Option<String> {
+    if let Some(c) = text.chars().find(|&c| !is_cif2_char(c)) {
+        return Some(format!(
+            "U+{:04X} is not in the CIF 2.0 character set",
+            c as u32
+        ));
+    }
+    (!text_writable(text, CifVersion::V2_0)).then(|| {
+        "a line starts with ';' and the text holds both ''' and \"\"\", so no text field \
+         or quoted string can enclose it"
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cif2_character_set() {
+        assert!(is_cif2_char('é') && is_cif2_char('\u{1F600}') && is_cif2_char('\t'));
+        for c in [
+            '\u{7}',
+            '\u{7F}',
+            '\u{85}',
+            '\u{FDD0}',
+            '\u{FFFE}',
+            '\u{1FFFF}',
+        ] {
+            assert!(!is_cif2_char(c), "{c:?}");
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! Closed vocabularies of core CIF values.
+//!
+//! Many core tags take one of a fixed list of values: `_atom_site_adp_type`
+//! is one of `Uani`, `Uiso`, `Uovl`, `Umpe`, `Bani`, `Biso`, or `Bovl`,
+//! `_refine_ls_hydrogen_treatment` one of `refall`, `refxyz`, `refU`, and so
+//! on. [`VOCABULARIES`] lists them as the core dictionary does, with the
+//! mmCIF spellings of their tags. Values are matched ignoring case, so
+//! `UANI` is `Uani`; [`Vocabulary::canonical`] gives the dictionary's
+//! spelling.
+//!
+//! [`CifBlock::check_vocabularies`] reports values that are in no
+//! vocabulary, with the nearest allowed value as a suggestion when one is
+//! close. The same issues appear in
+//! [`CifBlock::sanity_check`](crate::CifBlock::sanity_check) under the ID
+//! `vocabulary`. Typed extractors use enums where a vocabulary exists, such
+//! as [`AdpType`] for [`AtomSite::adp_type`](crate::AtomSite::adp_type).
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::vocab::{vocabulary_for, AdpType};
+//! use cif_parser::Document;
+//!
+//! let adp = vocabulary_for("_atom_site_adp_type").unwrap();
+//! assert_eq!(adp.canonical("UANI"), Some("Uani"));
+//! assert_eq!(AdpType::from_code("uiso"), AdpType::Uiso);
+//!
+//! let cif = "\
+//! data_x
+//! loop_
+//! _atom_site_label
+//! _atom_site_adp_type
+//! C1 Uani
+//! C2 Uanl
+//! ";
+//! let doc = Document::parse(cif).unwrap();
+//! let issues = doc.first_block().unwrap().check_vocabularies();
+//!
+//! assert_eq!(issues.len(), 1);
+//! assert_eq!((issues[0].row, issues[0].suggestion), (Some(1), Some("Uani")));
+//! ```
+
+use crate::ast::{CifBlock, CifValue, CifVersion};
+use crate::sanity::{values, SanityIssue};
+use crate::writer::inline_value;
+use std::fmt;
+
+/// The values a set of tags may take.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Vocabulary {
+    /// Short name, such as `adp-type`
+    pub name: &'static str,
+    /// Core and mmCIF tags that take these values
+    pub tags: &'static [&'static str],
+    /// The values as the core dictionary spells them
+    pub values: &'static [&'static str],
+}
+
+/// Every built-in vocabulary, from the core and symmetry dictionaries
+pub const VOCABULARIES: &[Vocabulary] = &[
+    Vocabulary {
+        name: "adp-type",
+        tags: &["_atom_site_adp_type", "_atom_site.adp_type"],
+        values: &["Uani", "Uiso", "Uovl", "Umpe", "Bani", "Biso", "Bovl"],
+    },
+    Vocabulary {
+        name: "calc-flag",
+        tags: &["_atom_site_calc_flag", "_atom_site.calc_flag"],
+        values: &["d", "calc", "c", "dum"],
+    },
+    Vocabulary {
+        name: "hydrogen-treatment",
+        tags: &[
+            "_refine_ls_hydrogen_treatment",
+            "_refine.ls_hydrogen_treatment",
+        ],
+        values: &[
+            "refall", "refxyz", "refU", "noref", "constr", "mixed", "undef",
+        ],
+    },
+    Vocabulary {
+        name: "structure-factor-coef",
+        tags: &[
+            "_refine_ls_structure_factor_coef",
+            "_refine.ls_structure_factor_coef",
+        ],
+        values: &["F", "Fsqd", "Inet"],
+    },
+    Vocabulary {
+        name: "matrix-type",
+        tags: &["_refine_ls_matrix_type", "_refine.ls_matrix_type"],
+        values: &[
+            "full",
+            "fullcycle",
+            "atomblock",
+            "userblock",
+            "diagonal",
+            "sparse",
+        ],
+    },
+    Vocabulary {
+        name: "weighting-scheme",
+        tags: &["_refine_ls_weighting_scheme", "_refine.ls_weighting_scheme"],
+        values: &["sigma", "unit", "calc"],
+    },
+    Vocabulary {
+        name: "absorption-correction",
+        tags: &[
+            "_exptl_absorpt_correction_type",
+            "_exptl.absorpt_correction_type",
+        ],
+        values: &[
+            "analytical",
+            "cylinder",
+            "empirical",
+            "gaussian",
+            "integration",
+            "multi-scan",
+            "none",
+            "numerical",
+            "psi-scan",
+            "refdelf",
+            "sphere",
+        ],
+    },
+    Vocabulary {
+        name: "colour-lustre",
+        tags: &[
+            "_exptl_crystal_colour_lustre",
+            "_exptl_crystal.colour_lustre",
+        ],
+        values: &["metallic", "dull", "clear"],
+    },
+    Vocabulary {
+        name: "colour-modifier",
+        tags: &[
+            "_exptl_crystal_colour_modifier",
+            "_exptl_crystal.colour_modifier",
+        ],
+        values: &[
+            "light",
+            "dark",
+            "whitish",
+            "blackish",
+            "grayish",
+            "brownish",
+            "reddish",
+            "pinkish",
+            "orangish",
+            "yellowish",
+            "greenish",
+            "bluish",
+        ],
+    },
+    Vocabulary {
+        name: "colour-primary",
+        tags: &[
+            "_exptl_crystal_colour_primary",
+            "_exptl_crystal.colour_primary",
+        ],
+        values: &[
+            "colourless",
+            "white",
+            "black",
+            "gray",
+            "brown",
+            "red",
+            "pink",
+            "orange",
+            "yellow",
+            "green",
+            "blue",
+            "violet",
+        ],
+    },
+    Vocabulary {
+        name: "centring-type",
+        tags: &["_space_group_centring_type", "_space_group.centring_type"],
+        values: &["P", "A", "B", "C", "F", "I", "R", "H"],
+    },
+    Vocabulary {
+        name: "crystal-system",
+        tags: &[
+            "_space_group_crystal_system",
+            "_space_group.crystal_system",
+            "_symmetry_cell_setting",
+            "_symmetry.cell_setting",
+        ],
+        values: &[
+            "triclinic",
+            "monoclinic",
+            "orthorhombic",
+            "tetragonal",
+            "rhombohedral",
+            "trigonal",
+            "hexagonal",
+            "cubic",
+        ],
+    },
+];
+
+/// Largest edit distance at which a value is suggested
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// The vocabulary of `tag`, ignoring case
+pub fn vocabulary_for(tag: &str) -> Option<&'static Vocabulary> {
+    VOCABULARIES
+        .iter()
+        .find(|vocab| vocab.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+}
+
+impl Vocabulary {
+    /// The dictionary's spelling of `value`, if it is in the vocabulary
+    /// ignoring case
+    pub fn canonical(&self, value: &str) -> Option<&'static str> {
+        self.values
+            .iter()
+            .copied()
+            .find(|allowed| allowed.eq_ignore_ascii_case(value))
+    }
+
+    /// The allowed value nearest to `value` by edit distance, ignoring case,
+    /// if it is at most two edits away and fewer edits than `value` has
+    /// characters
+    pub fn suggest(&self, value: &str) -> Option<&'static str> {
+        let value = value.to_lowercase();
+        let length = value.chars().count();
+        self.values
+            .iter()
+            .map(|&allowed| (edit_distance(&value, &allowed.to_lowercase()), allowed))
+            .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE && distance < length)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, allowed)| allowed)
+    }
+}
+
+/// Levenshtein distance between two strings, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// A value outside its tag's vocabulary (see
+/// [`CifBlock::check_vocabularies`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct VocabIssue {
+    /// The tag, as written in the block
+    pub tag: String,
+    /// Loop row of the value, or `None` for a data item
+    pub row: Option<usize>,
+    /// The value, as it would be written in CIF
+    pub value: String,
+    /// The nearest allowed value, if one is close
+    pub suggestion: Option<&'static str>,
+    /// The vocabulary the value should be in
+    pub vocabulary: &'static Vocabulary,
+}
+
+impl VocabIssue {
+    /// What is wrong, such as `must be one of Uani, Uiso, ...; did you mean
+    /// Uani?`
+    pub fn rule(&self) -> String {
+        let mut rule = format!("must be one of {}", self.vocabulary.values.join(", "));
+        if let Some(suggestion) = self.suggestion {
+            rule.push_str(&format!("; did you mean {suggestion}?"));
+        }
+        rule
+    }
+}
+
+impl fmt::Display for VocabIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(f, "{}[{}]", self.tag, row)?,
+            None => write!(f, "{}", self.tag)?,
+        }
+        write!(f, " = {}: {}", self.value, self.rule())
+    }
+}
+
+impl From<VocabIssue> for SanityIssue {
+    fn from(issue: VocabIssue) -> Self {
+        SanityIssue {
+            id: "vocabulary",
+            rule: issue.rule(),
+            tag: issue.tag,
+            row: issue.row,
+            value: issue.value,
+        }
+    }
+}
+
+impl CifBlock {
+    /// Values of enumerated core tags that are not in their
+    /// [vocabulary](VOCABULARIES), in the order of the vocabularies.
+    ///
+    /// Case is ignored, so `UANI` passes. Data items and loop columns are
+    /// checked; `?` and `.` are skipped. An empty list means every
+    /// enumerated value was allowed.
+    pub fn check_vocabularies(&self) -> Vec<VocabIssue> {
+        let mut issues = Vec::new();
+        for vocabulary in VOCABULARIES {
+            for &tag in vocabulary.tags {
+                for (row, value) in values(self, tag) {
+                    let text = match value {
+                        CifValue::Unknown | CifValue::NotApplicable => continue,
+                        CifValue::Text(_) | CifValue::LargeText(_) => {
+                            value.as_string().unwrap_or_default().trim().to_string()
+                        }
+                        _ => inline_value(value, CifVersion::V1_1),
+                    };
+                    if vocabulary.canonical(&text).is_some() {
+                        continue;
+                    }
+                    issues.push(VocabIssue {
+                        tag: tag.to_string(),
+                        row,
+                        value: inline_value(value, CifVersion::V1_1),
+                        suggestion: vocabulary.suggest(&text),
+                        vocabulary,
+                    });
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Displacement parameter type of an atom site (`_atom_site_adp_type`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdpType {
+    /// `Uani`: anisotropic U
+    Uani,
+    /// `Uiso`: isotropic U
+    Uiso,
+    /// `Uovl`: overall U
+    Uovl,
+    /// `Umpe`: U from multipole expansion
+    Umpe,
+    /// `Bani`: anisotropic B
+    Bani,
+    /// `Biso`: isotropic B
+    Biso,
+    /// `Bovl`: overall B
+    Bovl,
+    /// Any other code, as written
+    Other(String),
+}
+
+impl AdpType {
+    /// Classify a code as written in CIF; case is ignored
+    pub fn from_code(code: &str) -> Self {
+        match code.to_ascii_lowercase().as_str() {
+            "uani" => AdpType::Uani,
+            "uiso" => AdpType::Uiso,
+            "uovl" => AdpType::Uovl,
+            "umpe" => AdpType::Umpe,
+            "bani" => AdpType::Bani,
+            "biso" => AdpType::Biso,
+            "bovl" => AdpType::Bovl,
+            _ => AdpType::Other(code.to_string()),
+        }
+    }
+
+    /// The code as the core dictionary writes it
+    pub fn code(&self) -> &str {
+        match self {
+            AdpType::Uani => "Uani",
+            AdpType::Uiso => "Uiso",
+            AdpType::Uovl => "Uovl",
+            AdpType::Umpe => "Umpe",
+            AdpType::Bani => "Bani",
+            AdpType::Biso => "Biso",
+            AdpType::Bovl => "Bovl",
+            AdpType::Other(code) => code,
+        }
+    }
+
+    /// Whether the displacement is anisotropic
+    pub fn is_anisotropic(&self) -> bool {
+        matches!(self, AdpType::Uani | AdpType::Bani)
+    }
+}
+
+impl fmt::Display for AdpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("uani", "uani"), 0);
+        assert_eq!(edit_distance("uanl", "uani"), 1);
+        assert_eq!(edit_distance("multiscan", "multi-scan"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("colorless", "colourless"), 1);
+    }
+
+    #[test]
+    fn test_vocabularies_match_their_enums() {
+        let adp = vocabulary_for("_atom_site.adp_type").unwrap();
+        for value in adp.values {
+            assert_eq!(AdpType::from_code(value).code(), *value);
+        }
+        let hydrogens = vocabulary_for("_refine_ls_hydrogen_treatment").unwrap();
+        for value in hydrogens.values {
+            let treatment = crate::HydrogenTreatment::from_code(value);
+            assert_eq!(treatment.code(), *value);
+        }
+    }
+}
@@ -5,7 +5,7 @@
 
 use crate::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use wasm_bindgen::prelude::*;
 
 // Console logging for debugging
@@ -66,7 +66,8 @@ pub struct JsCifValue {
     text_value: Option<String>,
     numeric_value: Option<f64>,
     list_value: Option<Vec<JsCifValue>>,
-    table_value: Option<HashMap<String, JsCifValue>>,
+    /// Sorted by key, so the entries come out in the same order every time
+    table_value: Option<BTreeMap<String, JsCifValue>>,
 }
 
 #[wasm_bindgen]
@@ -168,7 +169,14 @@ impl From<&CifValue> for JsCifValue {
                 list_value: None,
                 table_value: None,
             },
-            CifValue::Numeric(n) => JsCifValue {
+            CifValue::LargeText(s) => JsCifValue {
+                value_type: "Text".to_string(),
+                text_value: Some(s.to_string()),
+                numeric_value: None,
+                list_value: None,
+                table_value: None,
+            },
+            CifValue::Numeric(n) | CifValue::NumericSu { value: n, .. } => JsCifValue {
                 value_type: "Numeric".to_string(),
                 text_value: None,
                 numeric_value: Some(*n),
@@ -471,27 +479,13 @@ impl JsCifDocument {
                 Ok(JsCifDocument { inner: doc })
             }
             Err(e) => {
-                // Format error message with location info if available
-                let error_msg = match e {
-                    crate::CifError::ParseError(msg) => {
-                        format!("Parse error: {}", msg)
-                    }
-                    crate::CifError::IoError(err) => {
-                        format!("IO error: {}", err)
-                    }
-                    crate::CifError::InvalidStructure { message, location } => {
-                        if let Some((line, col)) = location {
-                            format!(
-                                "Invalid structure at line {}, col {}: {}",
-                                line, col, message
-                            )
-                        } else {
-                            format!("Invalid structure: {}", message)
-                        }
-                    }
-                };
+                // The message has the code and location; the code is also
+                // the error's `code` property
+                let error_msg = e.to_string();
                 console_log!("{}", error_msg);
-                Err(js_sys::Error::new(&error_msg).into())
+                let error = js_sys::Error::new(&error_msg);
+                let _ = js_sys::Reflect::set(&error, &"code".into(), &e.code().into());
+                Err(error.into())
             }
         }
     }
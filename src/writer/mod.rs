@@ -0,0 +1,1218 @@
+//! Serializing documents back to CIF text.
+//!
+//! [`CifDocument`] implements [`Display`](fmt::Display), so `doc.to_string()`
+//! produces CIF text that parses back to a semantically equal document (see
+//! [`CifDocument::semantically_equal`]).
+//!
+//! Each value is written in the simplest form that survives re-parsing:
+//! bare if possible, then quoted, then as a semicolon text field. Items are
+//! written in the order the block stores them, which for a parsed document
+//! is file order, unless [`WriteOptions::sort_items`] asks for tag order.
+//!
+//! Loops come after the items of their block or frame, so a loop with no
+//! rows is always followed by another `loop_`, a frame, a block, or the end
+//! of input, and its bare header reads back as an empty loop.
+//!
+//! [`CifDocument::to_string_with_options`] can instead lay out mmCIF blocks
+//! the way PDBx files are written ([`CifStyle::Mmcif`]), and round numbers
+//! as [`FloatFormat`] asks.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::Document;
+//!
+//! let doc = Document::parse("data_test\n_name 'two words'\n_cell_length_a 5.43\n").unwrap();
+//! let text = doc.to_string();
+//!
+//! assert!(text.contains("_name 'two words'"));
+//! assert!(Document::parse(&text).unwrap().semantically_equal(&doc));
+//! ```
+
+use crate::ast::{CifBlock, CifDocument, CifFrame, CifLoop, CifValue, CifVersion};
+use crate::category::category_of;
+use crate::corpus::long_path;
+use crate::error::CifError;
+use crate::formatter::{Anchor, Comments, Scope};
+use crate::redact::RedactionPolicy;
+use crate::uncertainty;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+mod number;
+mod stream;
+
+pub use number::{FloatFormat, NumberFormat};
+pub use stream::CifWriter;
+
+use number::{item_su, row_su, shortest, su_columns};
+
+/// How a single value is laid out in the output
+enum Token<'a> {
+    /// Fits on the current line
+    Inline(String),
+    /// Needs a semicolon-delimited text field on its own lines; written
+    /// straight from the value, so large text is never copied
+    TextField(&'a str),
+}
+
+/// Output layout for [`CifDocument::to_string_with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CifStyle {
+    /// Items, then loops, as `Display` writes them
+    #[default]
+    Core,
+    /// The PDBx/mmCIF layout for blocks whose tags are all dotted: one
+    /// section per category in the conventional order, single-row
+    /// categories as items and others as loops, with a `#` line after each.
+    /// Other blocks are written in the core style.
+    Mmcif,
+}
+
+/// Layout choices the [formatter](crate::formatter) makes on top of the
+/// plain writer; the default lays out text exactly as `Display` does.
+#[derive(Debug, Default)]
+pub(crate) struct Layout<'a> {
+    /// Pad tags so that item values line up, and loop values into columns
+    pub align: bool,
+    /// A blank line before each loop and save frame
+    pub spacing: bool,
+    /// Comments to write back before what they were found with
+    pub comments: Option<&'a Comments>,
+}
+
+impl Layout<'_> {
+    /// Write the comments kept for `anchor`, one per line
+    fn comments(&self, f: &mut impl Write, anchor: impl FnOnce() -> Anchor) -> fmt::Result {
+        let Some(comments) = self.comments else {
+            return Ok(());
+        };
+        for comment in comments.get(&anchor()) {
+            writeln!(f, "{comment}")?;
+        }
+        Ok(())
+    }
+
+    fn blank_line(&self, f: &mut impl Write) -> fmt::Result {
+        if self.spacing {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for writing CIF text.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// Layout of the output
+    pub style: CifStyle,
+    /// Write the columns of `_atom_site` loops in a canonical order
+    pub atom_site_column_order: bool,
+    /// Write back a document's preamble and trailer around its blocks
+    pub preamble_and_trailer: bool,
+    /// Redact the document as it is written
+    pub redaction: Option<RedactionPolicy>,
+    /// How to write numbers
+    pub float_format: FloatFormat,
+    /// Write the items of each block and frame in tag order
+    pub sort_items: bool,
+}
+
+impl WriteOptions {
+    /// Create options for the default core style
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output layout
+    pub fn style(mut self, style: CifStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Write the columns of `_atom_site` loops in a canonical order (label,
+    /// type symbol, coordinates, displacement, occupancy, ...) whatever order
+    /// they were added in. Columns without a place in that order follow, in
+    /// their original order.
+    ///
+    /// Together with
+    /// [`CifBlock::canonical_atom_site_order`](crate::CifBlock::canonical_atom_site_order)
+    /// this makes the output of programmatically built structures diffable.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::writer::WriteOptions;
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse(
+    ///     "data_x\nloop_\n_atom_site_fract_x\n_atom_site_occupancy\n_atom_site_label\n0.5 1 C1\n",
+    /// )
+    /// .unwrap();
+    /// let text = doc.to_string_with_options(&WriteOptions::new().atom_site_column_order(true));
+    ///
+    /// assert!(text.contains("_atom_site_label\n_atom_site_fract_x\n_atom_site_occupancy\nC1 0.5 1\n"));
+    /// ```
+    pub fn atom_site_column_order(mut self, canonical: bool) -> Self {
+        self.atom_site_column_order = canonical;
+        self
+    }
+
+    /// Write the non-CIF [`preamble`](CifDocument::preamble) after the
+    /// version header and the [`trailer`](CifDocument::trailer) after the
+    /// last block, verbatim. Off by default, as the output is then not CIF
+    /// and only parses leniently.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::writer::WriteOptions;
+    /// use cif_parser::{Document, ParseOptions};
+    ///
+    /// let cif = "data_x\n_a 1\n#END\nCHECKSUM 8f3a\n";
+    /// let doc = Document::parse_with_options(cif, &ParseOptions::new().lenient(true)).unwrap();
+    /// let text = doc.to_string_with_options(&WriteOptions::new().preamble_and_trailer(true));
+    ///
+    /// assert!(text.ends_with("_a 1\n#END\nCHECKSUM 8f3a\n"));
+    /// ```
+    pub fn preamble_and_trailer(mut self, keep: bool) -> Self {
+        self.preamble_and_trailer = keep;
+        self
+    }
+
+    /// Write the document as [redacted](CifDocument::redact) by `policy`,
+    /// leaving the document itself unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::redact::Profile;
+    /// use cif_parser::writer::WriteOptions;
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse("data_x\n_shelx_res_file\n;TITL x\n;\n_cell_length_a 5\n").unwrap();
+    /// let options = WriteOptions::new().redact(Profile::StripEmbeddedFiles.into());
+    /// let text = doc.to_string_with_options(&options);
+    ///
+    /// assert!(text.contains("_shelx_res_file ?\n") && !text.contains("TITL"));
+    /// assert!(doc.to_string().contains("TITL"));
+    /// ```
+    pub fn redact(mut self, policy: RedactionPolicy) -> Self {
+        self.redaction = Some(policy);
+        self
+    }
+
+    /// Write numbers as `format` asks instead of in their shortest
+    /// round-trip form. Numbers inside lists and tables are always written
+    /// in the shortest form.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::writer::{FloatFormat, NumberFormat, WriteOptions};
+    /// use cif_parser::{Document, Value};
+    ///
+    /// let mut doc = Document::parse("data_x\n_cell_length_a_su 0.0012\n").unwrap();
+    /// doc.blocks[0].items.insert("_cell_length_a".into(), Value::Numeric(5.4307123));
+    /// let format = FloatFormat::new().su_rounding(true);
+    /// let text = doc.to_string_with_options(&WriteOptions::new().float_format(format));
+    ///
+    /// assert!(text.contains("_cell_length_a 5.4307\n"));
+    /// ```
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Write the items of each block and frame in tag order instead of the
+    /// order they are stored in. In the mmCIF style this orders the items
+    /// within each category.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::writer::WriteOptions;
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse("data_x\n_b 2\n_a 1\n").unwrap();
+    /// let text = doc.to_string_with_options(&WriteOptions::new().sort_items(true));
+    ///
+    /// assert!(text.ends_with("_a 1\n_b 2\n"));
+    /// assert!(doc.to_string().ends_with("_b 2\n_a 1\n"));
+    /// ```
+    pub fn sort_items(mut self, sort: bool) -> Self {
+        self.sort_items = sort;
+        self
+    }
+}
+
+impl fmt::Display for CifDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_document(f, self, &WriteOptions::default(), &Layout::default())
+    }
+}
+
+impl CifDocument {
+    /// Write the document as CIF text with the given options.
+    ///
+    /// With default options this is the same as `to_string()`. In the mmCIF
+    /// style the `#\#CIF_1.1` header is left out, as PDBx files have none.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::writer::{CifStyle, WriteOptions};
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse("data_1abc\n_cell.length_a 50\n_entry.id 1ABC\n").unwrap();
+    /// let text = doc.to_string_with_options(&WriteOptions::new().style(CifStyle::Mmcif));
+    ///
+    /// assert_eq!(text, "data_1abc\n#\n_entry.id 1ABC\n#\n_cell.length_a 50\n#\n");
+    /// ```
+    pub fn to_string_with_options(&self, options: &WriteOptions) -> String {
+        let mut out = String::new();
+        write_document(&mut out, self, options, &Layout::default())
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Write the document as CIF text to a file, socket, or other writer.
+    ///
+    /// The text is streamed rather than built up first, so a document with
+    /// [large values](CifValue::LargeText) is written without another copy
+    /// of them. Wrap unbuffered writers such as `File` in a `BufWriter`.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::writer::WriteOptions;
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse("data_x\n_title test\n").unwrap();
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out, &WriteOptions::new()).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(out).unwrap(), doc.to_string());
+    /// ```
+    pub fn write_to(&self, out: impl io::Write, options: &WriteOptions) -> io::Result<()> {
+        let mut adapter = IoWriter { out, error: None };
+        match write_document(&mut adapter, self, options, &Layout::default()) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatting failed"))),
+        }
+    }
+
+    /// Write the document to a file as CIF text, as `to_string()` would.
+    ///
+    /// Nothing is written if a value cannot be represented in CIF, such as
+    /// a NaN or text with a line too long to break (see
+    /// [`check_integrity`](Self::check_integrity)); I/O errors carry the
+    /// path.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cif_parser::Document;
+    ///
+    /// let doc = Document::parse("data_x\n_title test\n").unwrap();
+    /// doc.to_file("out.cif").unwrap();
+    /// ```
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CifError> {
+        let path = path.as_ref();
+        self.check_integrity()?;
+        let file = File::create(long_path(path)).map_err(|e| CifError::from(e).with_path(path))?;
+        self.write_to(BufWriter::new(file), &WriteOptions::default())
+            .map_err(|e| CifError::from(e).with_path(path))
+    }
+}
+
+/// `fmt::Write` over an `io::Write`, keeping the first I/O error
+struct IoWriter<W> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+pub(crate) fn write_document(
+    f: &mut impl Write,
+    doc: &CifDocument,
+    options: &WriteOptions,
+    layout: &Layout,
+) -> fmt::Result {
+    let redacted;
+    let doc = match &options.redaction {
+        Some(policy) => {
+            let mut copy = doc.clone();
+            copy.redact(policy);
+            redacted = copy;
+            &redacted
+        }
+        None => doc,
+    };
+    let mmcif = options.style == CifStyle::Mmcif;
+    match doc.version {
+        CifVersion::V1_1 if mmcif => {}
+        CifVersion::V1_1 => writeln!(f, "#\\#CIF_1.1")?,
+        CifVersion::V2_0 => writeln!(f, "#\\#CIF_2.0")?,
+    }
+    let edges = options.preamble_and_trailer;
+    if let Some(preamble) = doc.preamble.as_deref().filter(|_| edges) {
+        f.write_str(preamble)?;
+    }
+    for (i, block) in doc.blocks.iter().enumerate() {
+        let scope = Scope::block(i);
+        if i == 0 && doc.bare_frames && block.items.is_empty() && block.loops.is_empty() {
+            // A fragment's frames go back out without the made-up heading
+            writeln!(f)?;
+            layout.comments(f, || Anchor::Block(i))?;
+            write_frames(f, &block.frames, scope, doc.version, options, layout)?;
+        } else if mmcif && is_dotted(block) {
+            if i > 0 || doc.version == CifVersion::V2_0 {
+                writeln!(f)?;
+            }
+            layout.comments(f, || Anchor::Block(i))?;
+            write_mmcif_block(f, block, scope, doc.version, options, layout)?;
+        } else {
+            writeln!(f)?;
+            layout.comments(f, || Anchor::Block(i))?;
+            write_block(f, block, scope, doc.version, options, layout)?;
+        }
+    }
+    if layout
+        .comments
+        .is_some_and(|c| !c.get(&Anchor::End).is_empty())
+    {
+        layout.blank_line(f)?;
+        layout.comments(f, || Anchor::End)?;
+    }
+    if let Some(trailer) = doc.trailer.as_deref().filter(|_| edges) {
+        f.write_str(trailer)?;
+    }
+    Ok(())
+}
+
+fn write_block(
+    f: &mut impl Write,
+    block: &CifBlock,
+    scope: Scope,
+    version: CifVersion,
+    options: &WriteOptions,
+    layout: &Layout,
+) -> fmt::Result {
+    write_heading(f, block)?;
+    write_items(f, &block.items, scope, version, options, layout)?;
+    for (i, loop_) in block.loops.iter().enumerate() {
+        write_loop(f, loop_, (scope, i), version, options, layout)?;
+    }
+    write_frames(f, &block.frames, scope, version, options, layout)
+}
+
+/// The heading of `block`: `global_` for the global block, whose name is
+/// empty, and `data_` with the name for any other
+fn write_heading(f: &mut impl Write, block: &CifBlock) -> fmt::Result {
+    match block.name.as_str() {
+        "" => writeln!(f, "global_"),
+        name => writeln!(f, "data_{name}"),
+    }
+}
+
+fn write_frames(
+    f: &mut impl Write,
+    frames: &[CifFrame],
+    block: Scope,
+    version: CifVersion,
+    options: &WriteOptions,
+    layout: &Layout,
+) -> fmt::Result {
+    for (i, frame) in frames.iter().enumerate() {
+        let scope = block.frame(i);
+        layout.blank_line(f)?;
+        layout.comments(f, || Anchor::Frame(scope))?;
+        writeln!(f, "save_{}", frame.name)?;
+        write_items(f, &frame.items, scope, version, options, layout)?;
+        for (i, loop_) in frame.loops.iter().enumerate() {
+            write_loop(f, loop_, (scope, i), version, options, layout)?;
+        }
+        layout.comments(f, || Anchor::FrameEnd(scope))?;
+        writeln!(f, "save_")?;
+    }
+    Ok(())
+}
+
+/// Write the items of a block or frame, in tag order if the options ask for
+/// it, with their values lined up if the layout does
+fn write_items(
+    f: &mut impl Write,
+    items: &IndexMap<String, CifValue>,
+    scope: Scope,
+    version: CifVersion,
+    options: &WriteOptions,
+    layout: &Layout,
+) -> fmt::Result {
+    let mut tags: Vec<&String> = items.keys().collect();
+    if options.sort_items {
+        tags.sort();
+    }
+    let format = &options.float_format;
+    let token = |tag: &str| tagged_token(&items[tag], tag, || item_su(items, tag), version, format);
+    let width = match layout.align {
+        true => tags
+            .iter()
+            .filter(|tag| matches!(token(tag), Token::Inline(_)))
+            .map(|tag| tag.chars().count())
+            .max()
+            .unwrap_or(0),
+        false => 0,
+    };
+    for tag in tags {
+        layout.comments(f, || Anchor::Item(scope, tag.to_lowercase()))?;
+        match token(tag) {
+            Token::Inline(text) => write_inline_item(f, tag, width, &text)?,
+            Token::TextField(text) => writeln!(f, "{tag}\n;{text}\n;")?,
+        }
+    }
+    Ok(())
+}
+
+/// Write one data item, with its value on the same line unless it needs a
+/// text field
+fn write_item(f: &mut impl Write, tag: &str, token: Token<'_>) -> fmt::Result {
+    match token {
+        Token::Inline(text) => write_inline_item(f, tag, 0, &text),
+        Token::TextField(text) => writeln!(f, "{tag}\n;{text}\n;"),
+    }
+}
+
+/// Write a tag padded to `width` and its value on one line, or the value on
+/// the next line if together they would pass [`MAX_LINE_LENGTH`]
+fn write_inline_item(f: &mut impl Write, tag: &str, width: usize, text: &str) -> fmt::Result {
+    let tag_width = width.max(tag.chars().count());
+    let first_line = text.lines().next().unwrap_or_default();
+    match tag_width + 1 + first_line.chars().count() > MAX_LINE_LENGTH {
+        true => writeln!(f, "{tag}\n{text}"),
+        false => writeln!(f, "{tag:<width$} {text}"),
+    }
+}
+
+/// PDBx categories in the order wwPDB files list them; others follow
+/// alphabetically
+const MMCIF_CATEGORY_ORDER: &[&str] = &[
+    "_entry",
+    "_audit",
+    "_audit_conform",
+    "_database_2",
+    "_pdbx_database_status",
+    "_audit_author",
+    "_citation",
+    "_citation_author",
+    "_cell",
+    "_symmetry",
+    "_space_group",
+    "_space_group_symop",
+    "_entity",
+    "_entity_poly",
+    "_entity_poly_seq",
+    "_entity_src_gen",
+    "_entity_src_nat",
+    "_struct_ref",
+    "_struct_ref_seq",
+    "_chem_comp",
+    "_exptl",
+    "_exptl_crystal",
+    "_exptl_crystal_grow",
+    "_diffrn",
+    "_diffrn_detector",
+    "_diffrn_radiation",
+    "_diffrn_radiation_wavelength",
+    "_diffrn_source",
+    "_reflns",
+    "_reflns_shell",
+    "_refine",
+    "_refine_hist",
+    "_refine_ls_restr",
+    "_refine_ls_shell",
+    "_struct",
+    "_struct_keywords",
+    "_struct_asym",
+    "_struct_conf",
+    "_struct_conf_type",
+    "_struct_conn",
+    "_struct_sheet",
+    "_struct_site",
+    "_atom_sites",
+    "_atom_type",
+    "_atom_site",
+    "_atom_site_anisotrop",
+    "_pdbx_poly_seq_scheme",
+    "_pdbx_nonpoly_scheme",
+    "_pdbx_struct_oper_list",
+];
+
+/// Whether every tag of a block is an mmCIF `_category.attribute` name
+fn is_dotted(block: &CifBlock) -> bool {
+    let mut tags = block
+        .items
+        .keys()
+        .chain(block.loops.iter().flat_map(|loop_| &loop_.tags))
+        .peekable();
+    tags.peek().is_some() && tags.all(|tag| tag.contains('.'))
+}
+
+/// The lowercased category of a dotted tag (`_atom_site.id` → `_atom_site`)
+fn mmcif_category(tag: &str) -> String {
+    tag.split_once('.')
+        .map_or(tag, |(category, _)| category)
+        .to_lowercase()
+}
+
+/// The items and loops of one category, in output order
+#[derive(Default)]
+struct Section<'a> {
+    /// Items with their su, if numbers are rounded to it
+    pairs: Vec<(&'a str, &'a CifValue, Option<f64>)>,
+    /// Loops with their index in the block
+    loops: Vec<(usize, &'a CifLoop)>,
+}
+
+fn write_mmcif_block(
+    f: &mut impl Write,
+    block: &CifBlock,
+    scope: Scope,
+    version: CifVersion,
+    options: &WriteOptions,
+    layout: &Layout,
+) -> fmt::Result {
+    write_heading(f, block)?;
+    writeln!(f, "#")?;
+
+    let format = &options.float_format;
+    let mut sections: HashMap<String, Section> = HashMap::new();
+    let mut tags: Vec<&String> = block.items.keys().collect();
+    if options.sort_items {
+        tags.sort();
+    }
+    for tag in tags {
+        let section = sections.entry(mmcif_category(tag)).or_default();
+        let su = format
+            .su_rounding
+            .then(|| item_su(&block.items, tag))
+            .flatten();
+        section.pairs.push((tag, &block.items[tag], su));
+    }
+    for (i, loop_) in block
+        .loops
+        .iter()
+        .enumerate()
+        .filter(|(_, loop_)| !loop_.tags.is_empty())
+    {
+        let section = sections.entry(mmcif_category(&loop_.tags[0])).or_default();
+        // A single-row category is written as items
+        if loop_.len() == 1 {
+            let row = &loop_.values[0];
+            let su_columns = match format.su_rounding {
+                true => su_columns(&loop_.tags),
+                false => Vec::new(),
+            };
+            section.pairs.extend(
+                loop_
+                    .tags
+                    .iter()
+                    .zip(row)
+                    .enumerate()
+                    .map(|(i, (tag, value))| (tag.as_str(), value, row_su(row, &su_columns, i))),
+            );
+        } else {
+            section.loops.push((i, loop_));
+        }
+    }
+
+    let mut categories: Vec<&String> = sections.keys().collect();
+    categories.sort_by_key(|category| {
+        let rank = MMCIF_CATEGORY_ORDER
+            .iter()
+            .position(|known| known == category)
+            .unwrap_or(MMCIF_CATEGORY_ORDER.len());
+        (rank, *category)
+    });
+    for category in categories {
+        let section = &sections[category];
+        if !section.pairs.is_empty() {
+            let width = section.pairs.iter().map(|(tag, ..)| tag.len()).max();
+            for &(tag, value, su) in &section.pairs {
+                match tagged_token(value, tag, || su, version, format) {
+                    Token::Inline(text) => write_inline_item(f, tag, width.unwrap_or(0), &text)?,
+                    Token::TextField(text) => writeln!(f, "{tag}\n;{text}\n;")?,
+                }
+            }
+            writeln!(f, "#")?;
+        }
+        for &(i, loop_) in &section.loops {
+            write_loop(f, loop_, (scope, i), version, options, layout)?;
+            writeln!(f, "#")?;
+        }
+    }
+
+    write_frames(f, &block.frames, scope, version, options, layout)
+}
+
+/// Core `_atom_site` attributes in the order they are conventionally written
+const ATOM_SITE_ORDER: &[&str] = &[
+    "label",
+    "type_symbol",
+    "fract_x",
+    "fract_y",
+    "fract_z",
+    "cartn_x",
+    "cartn_y",
+    "cartn_z",
+    "u_iso_or_equiv",
+    "b_iso_or_equiv",
+    "adp_type",
+    "occupancy",
+    "site_symmetry_multiplicity",
+    "site_symmetry_order",
+    "wyckoff_symbol",
+    "calc_flag",
+    "refinement_flags",
+    "refinement_flags_posn",
+    "refinement_flags_adp",
+    "refinement_flags_occupancy",
+    "disorder_assembly",
+    "disorder_group",
+];
+
+/// mmCIF `_atom_site` attributes in the order wwPDB files list them
+const MMCIF_ATOM_SITE_ORDER: &[&str] = &[
+    "group_pdb",
+    "id",
+    "type_symbol",
+    "label_atom_id",
+    "label_alt_id",
+    "label_comp_id",
+    "label_asym_id",
+    "label_entity_id",
+    "label_seq_id",
+    "pdbx_pdb_ins_code",
+    "cartn_x",
+    "cartn_y",
+    "cartn_z",
+    "occupancy",
+    "b_iso_or_equiv",
+    "pdbx_formal_charge",
+    "auth_seq_id",
+    "auth_comp_id",
+    "auth_asym_id",
+    "auth_atom_id",
+    "pdbx_pdb_model_num",
+];
+
+/// Order in which to write the columns of a loop
+fn column_order(loop_: &CifLoop, options: &WriteOptions) -> Vec<usize> {
+    let mut columns: Vec<usize> = (0..loop_.tags.len()).collect();
+    let is_atom_site = loop_
+        .tags
+        .iter()
+        .all(|tag| category_of(tag) == "_atom_site");
+    if !options.atom_site_column_order || !is_atom_site {
+        return columns;
+    }
+    columns.sort_by_key(|&i| {
+        let tag = loop_.tags[i].to_ascii_lowercase();
+        let (order, attribute) = match tag.split_once('.') {
+            Some((_, attribute)) => (MMCIF_ATOM_SITE_ORDER, attribute),
+            None => (
+                ATOM_SITE_ORDER,
+                tag.strip_prefix("_atom_site_").unwrap_or_default(),
+            ),
+        };
+        order
+            .iter()
+            .position(|known| *known == attribute)
+            .unwrap_or(order.len())
+    });
+    columns
+}
+
+/// Longest line CIF 1.1 allows, in characters. Aligned rows that would be
+/// longer are written unpadded, rows longer still are broken across lines,
+/// and so are long lists and tables. Text with a longer line of its own
+/// cannot be broken, and [`CifDocument::check_integrity`] rejects it.
+const MAX_LINE_LENGTH: usize = 2048;
+
+/// Write a loop; a loop with no rows keeps its header. A loop with no tags
+/// cannot be written as CIF and is skipped.
+fn write_loop<'a>(
+    f: &mut impl Write,
+    loop_: &'a CifLoop,
+    (scope, index): (Scope, usize),
+    version: CifVersion,
+    options: &WriteOptions,
+    layout: &Layout,
+) -> fmt::Result {
+    if loop_.tags.is_empty() {
+        return Ok(());
+    }
+    let columns = column_order(loop_, options);
+    let format = &options.float_format;
+    let su_columns = match format.su_rounding {
+        true => su_columns(&loop_.tags),
+        false => Vec::new(),
+    };
+    let row_tokens = |row: &'a Vec<CifValue>| -> Vec<Token<'a>> {
+        columns
+            .iter()
+            .filter_map(|&col| Some((col, row.get(col)?)))
+            .map(|(col, value)| {
+                let su = || row_su(row, &su_columns, col);
+                tagged_token(value, &loop_.tags[col], su, version, format)
+            })
+            .collect()
+    };
+    let widths = match layout.align {
+        true => column_widths(loop_.values.iter().map(row_tokens), columns.len()),
+        false => Vec::new(),
+    };
+    layout.blank_line(f)?;
+    layout.comments(f, || Anchor::Loop(scope, index))?;
+    writeln!(f, "loop_")?;
+    for &col in &columns {
+        writeln!(f, "{}", loop_.tags[col])?;
+    }
+    for (row, values) in loop_.values.iter().enumerate() {
+        layout.comments(f, || Anchor::Row(scope, index, row))?;
+        write_row(f, row_tokens(values), &widths)?;
+    }
+    Ok(())
+}
+
+/// Width of the widest inline value in each column, or nothing if padding
+/// every column to its width would make rows too long
+fn column_widths<'a>(rows: impl Iterator<Item = Vec<Token<'a>>>, columns: usize) -> Vec<usize> {
+    let mut widths = vec![0; columns];
+    for tokens in rows {
+        for (width, token) in widths.iter_mut().zip(&tokens) {
+            if let Token::Inline(text) = token {
+                *width = (*width).max(text.chars().count());
+            }
+        }
+    }
+    let total = widths.iter().sum::<usize>() + columns.saturating_sub(1);
+    if total > MAX_LINE_LENGTH {
+        widths.clear();
+    }
+    widths
+}
+
+/// Write the values of one loop row on one line, breaking it around text
+/// fields and where it would pass [`MAX_LINE_LENGTH`]. Values are padded to
+/// the column `widths`, if any.
+fn write_row<'a>(
+    f: &mut impl Write,
+    tokens: impl IntoIterator<Item = Token<'a>>,
+    widths: &[usize],
+) -> fmt::Result {
+    let mut line = String::new();
+    let mut length = 0;
+    for (i, token) in tokens.into_iter().enumerate() {
+        match token {
+            Token::Inline(text) => {
+                let width = widths.get(i).copied().unwrap_or(0);
+                let added = width.max(text.chars().count());
+                if !line.is_empty() && length + 1 + added > MAX_LINE_LENGTH {
+                    writeln!(f, "{}", line.trim_end())?;
+                    line.clear();
+                    length = 0;
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                    length += 1;
+                }
+                write!(line, "{text:<width$}")?;
+                length += added;
+            }
+            Token::TextField(text) => {
+                // A text field must start at the beginning of a line
+                if !line.is_empty() {
+                    writeln!(f, "{}", line.trim_end())?;
+                    line.clear();
+                    length = 0;
+                }
+                writeln!(f, ";{text}\n;")?;
+            }
+        }
+    }
+    if !line.is_empty() {
+        writeln!(f, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// The token for a value of `tag`, with numbers written as `format` asks.
+/// `su` gives the su of the value, and is only called for su rounding.
+/// A number that carries its own su is written as it was read.
+fn tagged_token<'a>(
+    value: &'a CifValue,
+    tag: &str,
+    su: impl FnOnce() -> Option<f64>,
+    version: CifVersion,
+    format: &FloatFormat,
+) -> Token<'a> {
+    match value {
+        CifValue::Numeric(n) if !format.is_shortest() => {
+            let su = format.su_rounding.then(su).flatten();
+            Token::Inline(format.format(tag, *n, su))
+        }
+        _ => value_token(value, version),
+    }
+}
+
+fn value_token(value: &CifValue, version: CifVersion) -> Token<'_> {
+    match value {
+        CifValue::Text(s) => text_token(s, version),
+        CifValue::LargeText(s) => multiline_token(s, version),
+        other => Token::Inline(inline_value(other, version)),
+    }
+}
+
+fn text_token(s: &str, version: CifVersion) -> Token<'_> {
+    if s.contains(['\n', '\r']) {
+        return multiline_token(s, version);
+    }
+    match inline_text(s, version) {
+        // Text too long for one line goes in a text field, if it can
+        Some(text) if text.chars().count() > MAX_LINE_LENGTH && fits_text_field(s) => {
+            Token::TextField(s)
+        }
+        Some(text) => Token::Inline(text),
+        None => Token::TextField(s),
+    }
+}
+
+/// A text field, or in CIF 2.0 a triple-quoted string when a line of the
+/// text starts with `;` and would close the field early
+fn multiline_token(s: &str, version: CifVersion) -> Token<'_> {
+    if fits_text_field(s) {
+        return Token::TextField(s);
+    }
+    match version {
+        CifVersion::V2_0 => triple_quoted(s).map_or(Token::TextField(s), Token::Inline),
+        CifVersion::V1_1 => Token::TextField(s),
+    }
+}
+
+/// Whether no line after the first starts with `;`
+fn fits_text_field(s: &str) -> bool {
+    !s.contains("\n;") && !s.contains("\r;")
+}
+
+/// Whether text can be written in `version` and read back as itself
+pub(crate) fn text_writable(s: &str, version: CifVersion) -> bool {
+    fits_text_field(s) || (version == CifVersion::V2_0 && triple_quoted(s).is_some())
+}
+
+/// The token for a value written through `Display`: numbers and `?`/`.` as
+/// they are, anything else as text
+fn display_token(s: &str, version: CifVersion) -> Token<'_> {
+    let plain = !s.is_empty() && !s.contains(char::is_whitespace) && !s.starts_with(['\'', '"']);
+    match CifValue::parse_value(s) {
+        CifValue::Numeric(_)
+        | CifValue::NumericSu { .. }
+        | CifValue::Unknown
+        | CifValue::NotApplicable
+            if plain =>
+        {
+            Token::Inline(s.to_string())
+        }
+        _ => text_token(s, version),
+    }
+}
+
+/// One data item as CIF text, on one line unless the value needs a text field
+pub(crate) fn item_text(tag: &str, value: &CifValue, version: CifVersion) -> String {
+    match value_token(value, version) {
+        Token::Inline(text) => format!("{tag} {text}"),
+        Token::TextField(text) => format!("{tag}\n;{text}\n;\n"),
+    }
+}
+
+/// Format a value that must stay on one token (list and table members).
+///
+/// Text that no quoting can hold, such as a member with a newline, `'''`
+/// and `"""`, is put in single quotes and will not read back;
+/// [`CifDocument::check_integrity`] rejects it.
+pub(crate) fn inline_value(value: &CifValue, version: CifVersion) -> String {
+    match value {
+        CifValue::Text(s) => inline_text(s, version).unwrap_or_else(|| format!("'{s}'")),
+        CifValue::LargeText(s) => inline_text(s, version).unwrap_or_else(|| format!("'{s}'")),
+        CifValue::Numeric(n) => shortest(*n),
+        // The su is dropped when CifDocument::check_integrity would reject it
+        CifValue::NumericSu { value, su } if value.is_finite() && su.is_finite() && *su >= 0.0 => {
+            uncertainty::format_exact(*value, *su)
+        }
+        CifValue::NumericSu { value, .. } => shortest(*value),
+        CifValue::Unknown => "?".to_string(),
+        CifValue::NotApplicable => ".".to_string(),
+        CifValue::List(values) => {
+            let items: Vec<String> = values.iter().map(|v| inline_value(v, version)).collect();
+            format!("[{}]", join_members(&items))
+        }
+        CifValue::Table(_) => {
+            let entries = value.table_entries().unwrap_or_default();
+            let items: Vec<String> = entries
+                .into_iter()
+                .map(|(k, v)| {
+                    let key = quoted_text(k, version).unwrap_or_else(|| format!("'{k}'"));
+                    format!("{}:{}", key, inline_value(v, version))
+                })
+                .collect();
+            format!("{{{}}}", join_members(&items))
+        }
+    }
+}
+
+/// List or table members separated by spaces, or one to a line when that
+/// would pass [`MAX_LINE_LENGTH`]
+fn join_members(items: &[String]) -> String {
+    let one_line = items
+        .iter()
+        .map(|item| item.chars().count() + 1)
+        .sum::<usize>()
+        + 1;
+    match one_line > MAX_LINE_LENGTH {
+        true => items.join("\n"),
+        false => items.join(" "),
+    }
+}
+
+/// Why `value` would break the [`MAX_LINE_LENGTH`] once written in
+/// `version`, if it would: a text field or token with a longer line
+pub(super) fn line_length_problem(value: &CifValue, version: CifVersion) -> Option<String> {
+    // Nothing shorter can make a line too long, whatever quoting it takes
+    let short = |s: &str| s.len() + 6 <= MAX_LINE_LENGTH;
+    match value {
+        CifValue::Text(s) if short(s) => return None,
+        CifValue::LargeText(s) if short(s) => return None,
+        CifValue::List(_) | CifValue::Table(_) | CifValue::Text(_) | CifValue::LargeText(_) => {}
+        _ => return None,
+    }
+    let longest = match value_token(value, version) {
+        Token::Inline(text) => longest_line(&text),
+        // The first line follows the opening `;`
+        Token::TextField(text) => longest_line(text).max(first_line_length(text) + 1),
+    };
+    (longest > MAX_LINE_LENGTH).then(|| {
+        format!("has a line of {longest} characters, longer than the {MAX_LINE_LENGTH} CIF allows")
+    })
+}
+
+/// Why a member of the list or table `value` cannot be written in
+/// `version`, if one cannot: text or a table key that no quoting can hold
+pub(super) fn member_problem(value: &CifValue, version: CifVersion) -> Option<String> {
+    let members: Vec<&CifValue> = match value {
+        CifValue::List(values) => values.iter().collect(),
+        CifValue::Table(_) => {
+            let entries = value.table_entries().unwrap_or_default();
+            if let Some((key, _)) = entries
+                .iter()
+                .find(|(key, _)| quoted_text(key, version).is_none())
+            {
+                return Some(format!(
+                    "has a table key that no CIF quoting can hold: {key:?}"
+                ));
+            }
+            entries.into_iter().map(|(_, value)| value).collect()
+        }
+        _ => return None,
+    };
+    members
+        .into_iter()
+        .find_map(|member| match member.as_string() {
+            Some(s) if inline_text(s, version).is_none() => Some(format!(
+                "has a list or table member that no CIF quoting can hold: {s:?}"
+            )),
+            _ => member_problem(member, version),
+        })
+}
+
+/// Characters in the longest line of `text`
+fn longest_line(text: &str) -> usize {
+    text.split(['\n', '\r'])
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Characters in the first line of `text`
+fn first_line_length(text: &str) -> usize {
+    text.split(['\n', '\r'])
+        .next()
+        .map_or(0, |line| line.chars().count())
+}
+
+/// Text as a bare or quoted token, or None if it needs a text field
+fn inline_text(s: &str, version: CifVersion) -> Option<String> {
+    if is_bare_safe(s) {
+        Some(s.to_string())
+    } else {
+        quoted_text(s, version)
+    }
+}
+
+/// Whether text can be written without quotes and still read back as itself
+fn is_bare_safe(s: &str) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    let lower = s.to_ascii_lowercase();
+    !matches!(first, '_' | '\'' | '"' | '#' | '$' | ';')
+        && !s.chars().any(|c| {
+            c.is_whitespace()
+                || matches!(c, '[' | ']' | '{' | '}' | '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+        })
+        && !["data_", "save_", "loop_", "global_", "stop_"]
+            .iter()
+            .any(|keyword| lower.starts_with(keyword))
+        && s != "?"
+        && s != "."
+        // Numeric-looking text is quoted to signal that it was text
+        && matches!(CifValue::parse_value(s), CifValue::Text(_))
+}
+
+/// Text in quotes, choosing delimiters the content cannot terminate early
+fn quoted_text(s: &str, version: CifVersion) -> Option<String> {
+    if s.contains(['\n', '\r']) {
+        // Only CIF 2.0 triple quotes may span lines
+        return match version {
+            CifVersion::V1_1 => None,
+            CifVersion::V2_0 => triple_quoted(s),
+        };
+    }
+    for quote in ['\'', '"'] {
+        let quoted = format!("{quote}{s}{quote}");
+        let fits = match version {
+            // CIF 2.0 strings cannot contain their delimiter, and doubled quotes
+            // of either kind are rejected as CIF 1.1-style escapes
+            CifVersion::V2_0 => !s.contains(quote) && !s.contains("''") && !s.contains("\"\""),
+            // CIF 1.1 strings end only at a delimiter followed by whitespace,
+            // and must not open like a triple-quoted string
+            CifVersion::V1_1 => {
+                !ends_quote_early(s, quote) && !quoted.starts_with(&quote.to_string().repeat(3))
+            }
+        };
+        if fits {
+            return Some(quoted);
+        }
+    }
+    match version {
+        CifVersion::V1_1 => None,
+        CifVersion::V2_0 => triple_quoted(s),
+    }
+}
+
+fn ends_quote_early(s: &str, quote: char) -> bool {
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == quote && chars.peek().is_some_and(|&n| n.is_whitespace() || n == '#') {
+            return true;
+        }
+    }
+    false
+}
+
+fn triple_quoted(s: &str) -> Option<String> {
+    ["'''", "\"\"\""].into_iter().find_map(|delim| {
+        let quote = &delim[..1];
+        (!s.contains(delim) && !s.ends_with(quote)).then(|| format!("{delim}{s}{delim}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(value: &str, version: CifVersion) -> String {
+        match value_token(&CifValue::Text(value.to_string()), version) {
+            Token::Inline(text) => text,
+            Token::TextField(text) => format!(";{text}\n;"),
+        }
+    }
+
+    #[test]
+    fn test_text_quoting_choices() {
+        let v1 = CifVersion::V1_1;
+        assert_eq!(token("plain", v1), "plain");
+        assert_eq!(token("two words", v1), "'two words'");
+        assert_eq!(token("it's", v1), "it's");
+        assert_eq!(token("it's me", v1), "'it's me'");
+        assert_eq!(token("it' s", v1), "\"it' s\"");
+        assert_eq!(token("'a' \"b\" ", v1), ";'a' \"b\" \n;");
+        assert_eq!(token("_tag", v1), "'_tag'");
+        assert_eq!(token("Data_x", v1), "'Data_x'");
+        assert_eq!(token("?", v1), "'?'");
+        assert_eq!(token("1.5", v1), "'1.5'");
+        assert_eq!(token("", v1), "''");
+        assert_eq!(token("'", v1), "\"'\"");
+        assert_eq!(token("''x", v1), "\"''x\"");
+        assert_eq!(token("a\nb", v1), ";a\nb\n;");
+    }
+
+    #[test]
+    fn test_cif2_quoting_choices() {
+        let v2 = CifVersion::V2_0;
+        assert_eq!(token("it's me", v2), "\"it's me\"");
+        assert_eq!(token("'a' \"b\"", v2), r#"''''a' "b"'''"#);
+        assert_eq!(token("[x]", v2), "'[x]'");
+        assert_eq!(token("a\n;b", v2), "'''a\n;b'''");
+        assert_eq!(token("a\n;b", CifVersion::V1_1), ";a\n;b\n;");
+        assert!(!text_writable("a\n;b", CifVersion::V1_1));
+        assert!(!text_writable("''' \"\"\"\n;", v2));
+    }
+
+    #[test]
+    fn test_list_closing_quote_is_not_padded() {
+        let list = CifValue::List(vec![
+            CifValue::Numeric(1.0),
+            CifValue::Text("a b".to_string()),
+        ]);
+        assert_eq!(inline_value(&list, CifVersion::V2_0), "[1 'a b']");
+    }
+
+    #[test]
+    fn test_number_formatting() {
+        assert_eq!(shortest(14.0), "14");
+        assert_eq!(shortest(-0.5), "-0.5");
+        assert_eq!(shortest(1e300), "1e300");
+        assert_eq!(shortest(1.5e-7), "1.5e-7");
+        assert_eq!(shortest(f64::NAN), "?");
+        assert_eq!(shortest(f64::NEG_INFINITY), "?");
+    }
+
+    #[test]
+    fn test_loop_with_text_field() {
+        let mut loop_ = CifLoop::new(vec!["_a".to_string(), "_b".to_string(), "_c".to_string()]);
+        loop_.values = vec![vec![
+            CifValue::Numeric(1.0),
+            CifValue::Text("line one\nline two".to_string()),
+            CifValue::Unknown,
+        ]];
+        let mut out = String::new();
+        let layout = Layout::default();
+        let place = (Scope::block(0), 0);
+        write_loop(
+            &mut out,
+            &loop_,
+            place,
+            CifVersion::V1_1,
+            &WriteOptions::new(),
+            &layout,
+        )
+        .unwrap();
+        assert_eq!(out, "loop_\n_a\n_b\n_c\n1\n;line one\nline two\n;\n?\n");
+    }
+}
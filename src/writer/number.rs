@@ -0,0 +1,355 @@
+//! How the writer spells numbers.
+//!
+//! A number read from a file keeps no record of how it was written, so the
+//! writer chooses a form for every [`CifValue::Numeric`]. By default that is
+//! the shortest text that reads back as the same `f64`, bit for bit.
+//! [`FloatFormat`] instead rounds to a number of significant digits or
+//! decimals, for all numbers or per tag pattern, and can round a number to
+//! the precision of its su.
+//!
+//! CIF has no way to write an infinite or NaN value. The writer writes them
+//! as `?`; [`CifDocument::check_integrity`] finds them beforehand.
+//!
+//! # Examples
+//!
+//! ```
+//! use cif_parser::writer::{FloatFormat, NumberFormat, WriteOptions};
+//! use cif_parser::{Document, Value};
+//!
+//! let mut doc = Document::parse("data_x\n_atom_site_fract_x 0\n_cell_angle_beta 90\n").unwrap();
+//! let block = &mut doc.blocks[0];
+//! block.items.insert("_atom_site_fract_x".into(), Value::Numeric(1.0 / 3.0));
+//! block.items.insert("_cell_angle_beta".into(), Value::Numeric(101.123456));
+//!
+//! let format = FloatFormat::new()
+//!     .tag("_atom_site_fract_*", NumberFormat::Decimals(5))
+//!     .tag("_cell_angle_*", NumberFormat::Decimals(4));
+//! let text = doc.to_string_with_options(&WriteOptions::new().float_format(format));
+//!
+//! assert!(text.contains("_atom_site_fract_x 0.33333\n"));
+//! assert!(text.contains("_cell_angle_beta 101.1235\n"));
+//! assert!(doc.to_string().contains("_atom_site_fract_x 0.3333333333333333\n"));
+//! ```
+
+use super::{line_length_problem, member_problem};
+use crate::ast::{CifDocument, CifLoop, CifValue, CifVersion};
+use crate::error::CifError;
+use crate::redact::glob_match;
+use crate::uncertainty;
+use indexmap::IndexMap;
+
+/// How to write a number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// The shortest text that reads back as the same `f64`
+    #[default]
+    Shortest,
+    /// Rounded to this many significant digits (at least one), in exponent
+    /// form when the number is too large or small to write them plainly
+    Significant(u32),
+    /// Rounded to this many decimal places
+    Decimals(u32),
+}
+
+impl NumberFormat {
+    /// Write `n`, which must be finite
+    fn apply(self, n: f64) -> String {
+        match self {
+            NumberFormat::Shortest => shortest(n),
+            NumberFormat::Significant(digits) => significant(n, digits.max(1)),
+            NumberFormat::Decimals(decimals) => format!("{n:.*}", decimals as usize),
+        }
+    }
+}
+
+/// How to write the numbers of a document, as set by
+/// [`WriteOptions::float_format`](super::WriteOptions::float_format).
+///
+/// The format for a number is, in order of precedence:
+///
+/// 1. the format of a tag pattern matching its tag: a pattern without
+///    wildcards first, then the longest pattern, then the first one added;
+/// 2. the precision of its su, with [`su_rounding`](Self::su_rounding) on;
+/// 3. the [default](Self::default_format).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FloatFormat {
+    /// Format of numbers no other rule applies to
+    pub default: NumberFormat,
+    /// Formats by tag pattern, matched without regard to case; `*` stands
+    /// for any run of characters and `?` for any one character
+    pub tags: Vec<(String, NumberFormat)>,
+    /// Round a number to the precision of its su: the value of the item
+    /// named after it with `_su` appended, or of that column in the same
+    /// loop row. The su is taken to two digits if it would start with a 1,
+    /// and to one otherwise, as in [`uncertainty::format`].
+    pub su_rounding: bool,
+}
+
+impl FloatFormat {
+    /// Write every number in its shortest round-trip form
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write numbers no other rule applies to in this format
+    pub fn default_format(mut self, format: NumberFormat) -> Self {
+        self.default = format;
+        self
+    }
+
+    /// Write the numbers of tags matching `pattern`, such as
+    /// `_atom_site_fract_*`, in this format
+    pub fn tag(mut self, pattern: impl Into<String>, format: NumberFormat) -> Self {
+        self.tags.push((pattern.into(), format));
+        self
+    }
+
+    /// Round numbers to the precision of their su
+    pub fn su_rounding(mut self, on: bool) -> Self {
+        self.su_rounding = on;
+        self
+    }
+
+    /// The format for numbers of `tag` from the tag patterns, if any
+    /// matches
+    pub fn format_for(&self, tag: &str) -> Option<NumberFormat> {
+        let exact = self.tags.iter().find(|(pattern, _)| {
+            !pattern.contains(['*', '?']) && pattern.eq_ignore_ascii_case(tag)
+        });
+        let best = exact.or_else(|| {
+            self.tags
+                .iter()
+                .filter(|(pattern, _)| glob_match(pattern, tag))
+                .fold(
+                    None,
+                    |best: Option<&(String, NumberFormat)>, candidate| match best {
+                        Some(best) if literal_len(&best.0) >= literal_len(&candidate.0) => {
+                            Some(best)
+                        }
+                        _ => Some(candidate),
+                    },
+                )
+        });
+        best.map(|(_, format)| *format)
+    }
+
+    /// Write `n`, a value of `tag` whose su is `su`, if known. Infinite and
+    /// NaN values are written as `?`.
+    pub fn format(&self, tag: &str, n: f64, su: Option<f64>) -> String {
+        if !n.is_finite() {
+            return "?".to_string();
+        }
+        if let Some(format) = self.format_for(tag) {
+            return format.apply(n);
+        }
+        match su
+            .filter(|_| self.su_rounding)
+            .and_then(uncertainty::su_decimals)
+        {
+            Some(decimals) => format!("{n:.*}", decimals as usize),
+            None => self.default.apply(n),
+        }
+    }
+
+    /// Whether every number is written in its shortest form, so that no
+    /// tag or su needs looking up
+    pub(super) fn is_shortest(&self) -> bool {
+        self.default == NumberFormat::Shortest && self.tags.is_empty() && !self.su_rounding
+    }
+}
+
+/// Characters of a pattern other than wildcards
+fn literal_len(pattern: &str) -> usize {
+    pattern.chars().filter(|c| !matches!(c, '*' | '?')).count()
+}
+
+/// Shortest form that reads back as the same number, or `?` for a number
+/// CIF cannot write.
+///
+/// Rust's float formatting never consults the process locale, so the
+/// decimal separator is a period whatever `LC_NUMERIC` says.
+pub(super) fn shortest(n: f64) -> String {
+    if !n.is_finite() {
+        "?".to_string()
+    } else if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{n}")
+    } else {
+        format!("{n:?}")
+    }
+}
+
+/// `n` to `digits` significant digits, plainly unless that would need
+/// trailing zeros before the point or more than four leading zeros after it
+fn significant(n: f64, digits: u32) -> String {
+    if n == 0.0 {
+        return format!("{n:.*}", digits as usize - 1);
+    }
+    // Exponent after rounding, so that 9.99 to two digits counts as 10
+    let exponential = format!("{n:.*e}", digits as usize - 1);
+    let exponent: i32 = exponential
+        .split_once('e')
+        .and_then(|(_, e)| e.parse().ok())
+        .unwrap_or(0);
+    if exponent < -5 || exponent >= digits as i32 {
+        exponential
+    } else {
+        format!("{n:.*}", (digits as i32 - 1 - exponent) as usize)
+    }
+}
+
+/// The su of a value, from the value of its `_su` item or column
+fn su_value(value: &CifValue) -> Option<f64> {
+    value.as_numeric_with_su().map(|(su, _)| su)
+}
+
+/// The name of the item holding the su of `tag`
+fn su_tag(tag: &str) -> String {
+    format!("{tag}_su")
+}
+
+/// The su of item `tag` among `items`
+pub(super) fn item_su(items: &IndexMap<String, CifValue>, tag: &str) -> Option<f64> {
+    let name = su_tag(tag);
+    items
+        .iter()
+        .find(|(other, _)| other.eq_ignore_ascii_case(&name))
+        .and_then(|(_, value)| su_value(value))
+}
+
+/// For each column of a loop, the column holding its su, if any
+pub(super) fn su_columns<T: AsRef<str>>(tags: &[T]) -> Vec<Option<usize>> {
+    tags.iter()
+        .map(|tag| {
+            let name = su_tag(tag.as_ref());
+            tags.iter()
+                .position(|other| other.as_ref().eq_ignore_ascii_case(&name))
+        })
+        .collect()
+}
+
+/// The su of column `column` in `row`, given the loop's [`su_columns`]
+pub(super) fn row_su(row: &[CifValue], su_columns: &[Option<usize>], column: usize) -> Option<f64> {
+    su_columns
+        .get(column)
+        .copied()
+        .flatten()
+        .and_then(|su| row.get(su))
+        .and_then(su_value)
+}
+
+impl CifDocument {
+    /// Check that every value can be written as CIF.
+    ///
+    /// Infinite and NaN numbers cannot: CIF's number grammar has no form
+    /// for them, and the writer writes them as `?`. Neither can a negative or
+    /// non-finite su, which the writer drops. Lists and tables are checked
+    /// member by member, and a member or table key that no quoting can hold
+    /// is an error. Text with a line longer than the 2048 characters CIF
+    /// allows is an error too, as the writer has no way to break it.
+    ///
+    /// # Examples
+    /// ```
+    /// use cif_parser::{CifError, Document, Value};
+    ///
+    /// let mut doc = Document::parse("data_x\n_cell_volume 100\n").unwrap();
+    /// assert!(doc.check_integrity().is_ok());
+    ///
+    /// doc.blocks[0].items.insert("_cell_volume".into(), Value::Numeric(f64::NAN));
+    /// let error = doc.check_integrity().unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "[E0008] Invalid CIF structure: _cell_volume in data_x is NaN, which CIF cannot represent"
+    /// );
+    /// ```
+    pub fn check_integrity(&self) -> Result<(), CifError> {
+        for block in &self.blocks {
+            let container = format!("data_{}", block.name);
+            check_container(&container, &block.items, &block.loops, self.version)?;
+            for frame in &block.frames {
+                let container = format!("save_{} in data_{}", frame.name, block.name);
+                check_container(&container, &frame.items, &frame.loops, self.version)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_container(
+    container: &str,
+    items: &IndexMap<String, CifValue>,
+    loops: &[CifLoop],
+    version: CifVersion,
+) -> Result<(), CifError> {
+    for (tag, value) in items {
+        check_written(value, version, &|| format!("{tag} in {container}"))?;
+    }
+    for loop_ in loops {
+        for (row, values) in loop_.values.iter().enumerate() {
+            for (tag, value) in loop_.tags.iter().zip(values) {
+                check_written(value, version, &|| {
+                    format!("{tag} in row {} of {container}", row + 1)
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fail if `value` cannot be written in `version`: a number [`check_value`]
+/// rejects, a member with no quoting, or a line too long for CIF
+fn check_written(
+    value: &CifValue,
+    version: CifVersion,
+    place: &dyn Fn() -> String,
+) -> Result<(), CifError> {
+    check_value(value, place)?;
+    match member_problem(value, version).or_else(|| line_length_problem(value, version)) {
+        Some(problem) => Err(CifError::invalid_structure(format!(
+            "{} {problem}",
+            place()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Fail if `value`, or any member of it, is a number CIF cannot write
+fn check_value(value: &CifValue, place: &dyn Fn() -> String) -> Result<(), CifError> {
+    match value {
+        CifValue::Numeric(n) | CifValue::NumericSu { value: n, .. } if !n.is_finite() => {
+            let what = if n.is_nan() { "NaN" } else { "infinite" };
+            Err(CifError::invalid_structure(format!(
+                "{} is {what}, which CIF cannot represent",
+                place()
+            )))
+        }
+        CifValue::NumericSu { su, .. } if !(su.is_finite() && *su >= 0.0) => {
+            Err(CifError::invalid_structure(format!(
+                "{} has su {su}, which CIF cannot represent",
+                place()
+            )))
+        }
+        CifValue::List(values) => values.iter().try_for_each(|v| check_value(v, place)),
+        CifValue::Table(_) => value
+            .table_entries()
+            .unwrap_or_default()
+            .into_iter()
+            .try_for_each(|(_, v)| check_value(v, place)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_significant_digits() {
+        assert_eq!(significant(1234.5678, 3), "1.23e3");
+        assert_eq!(significant(123.456, 3), "123");
+        assert_eq!(significant(9.996, 3), "10.0");
+        assert_eq!(significant(0.000123456, 2), "0.00012");
+        assert_eq!(significant(1.5e-9, 2), "1.5e-9");
+        assert_eq!(significant(-0.5, 1), "-0.5");
+        assert_eq!(significant(0.0, 3), "0.00");
+    }
+}
@@ -0,0 +1,403 @@
+//! Writing CIF text piece by piece, without building a document.
+//!
+//! Exporting a million computed reflections through a [`CifDocument`] would
+//! first hold them all as [`CifValue`]s. [`CifWriter`] instead writes each
+//! item and loop row as it is given, with the same quoting as the document
+//! writer, so memory use does not grow with the output. Numbers are written
+//! as the options' [`FloatFormat`] asks, with su rounding taking the su from
+//! a column of the same row; items are written before their `_su` items
+//! can be seen, and are not rounded to them.
+//!
+//! [`CifDocument`]: crate::CifDocument
+
+use super::number::{row_su, su_columns};
+use super::{
+    display_token, tagged_token, write_item, write_row, CifStyle, FloatFormat, IoWriter,
+    WriteOptions,
+};
+use crate::ast::{CifValue, CifVersion};
+use crate::category::category_of;
+use std::fmt::{self, Display, Write as _};
+use std::io;
+
+/// Where the writer is in the document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Between blocks
+    Outside,
+    /// Inside a block, outside any loop
+    Block,
+    /// Inside a loop with this many columns
+    Loop { columns: usize },
+}
+
+/// Streaming CIF writer over any [`io::Write`].
+///
+/// Calls must follow the structure of a CIF file: [`begin_block`], then
+/// items and loops, then [`end_block`]; a loop is [`begin_loop`], its rows,
+/// then [`end_loop`]. A call out of order, a malformed tag or block name,
+/// or a row of the wrong width is an error of kind
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) and writes nothing.
+///
+/// Output goes straight to the writer, so wrap a `File` in a `BufWriter`;
+/// it is flushed at the end of each loop and block. In the
+/// [mmCIF style](CifStyle::Mmcif) a `#` line follows the block header and
+/// each category, but items and columns are written in the order given.
+///
+/// # Examples
+///
+/// ```
+/// use cif_parser::writer::{CifWriter, WriteOptions};
+/// use cif_parser::{CifValue, Document};
+///
+/// let mut writer = CifWriter::new(Vec::new(), WriteOptions::new());
+/// writer.begin_block("hkl").unwrap();
+/// writer
+///     .write_item("_diffrn_radiation_wavelength", &CifValue::Numeric(0.71073))
+///     .unwrap();
+/// writer
+///     .begin_loop(&["_refln_index_h", "_refln_index_k", "_refln_index_l", "_refln_F_squared_meas"])
+///     .unwrap();
+/// for (h, k, l, i) in [(1, 0, 0, 152.3), (0, 1, 1, 88.75)] {
+///     writer.write_row_display(&[&h, &k, &l, &i]).unwrap();
+/// }
+/// writer.end_loop().unwrap();
+/// writer.end_block().unwrap();
+/// let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+///
+/// let doc = Document::parse(&text).unwrap();
+/// assert_eq!(doc.blocks[0].loops[0].len(), 2);
+/// assert!(text.contains("1 0 0 152.3\n0 1 1 88.75\n"));
+/// ```
+///
+/// [`begin_block`]: Self::begin_block
+/// [`end_block`]: Self::end_block
+/// [`begin_loop`]: Self::begin_loop
+/// [`end_loop`]: Self::end_loop
+pub struct CifWriter<W: io::Write> {
+    out: W,
+    version: CifVersion,
+    mmcif: bool,
+    state: State,
+    /// Whether the version header has been written
+    started: bool,
+    /// Category of the last item written, in the mmCIF style
+    category: Option<String>,
+    float_format: FloatFormat,
+    /// Tags of the open loop, and the column holding each one's su
+    loop_tags: Vec<String>,
+    loop_su_columns: Vec<Option<usize>>,
+}
+
+impl<W: io::Write> CifWriter<W> {
+    /// Write CIF 1.1 to `out`
+    pub fn new(out: W, options: WriteOptions) -> Self {
+        CifWriter {
+            out,
+            version: CifVersion::V1_1,
+            mmcif: options.style == CifStyle::Mmcif,
+            state: State::Outside,
+            started: false,
+            category: None,
+            float_format: options.float_format,
+            loop_tags: Vec::new(),
+            loop_su_columns: Vec::new(),
+        }
+    }
+
+    /// Write this CIF version instead; CIF 2.0 allows list and table values
+    /// and triple-quoted strings
+    pub fn version(mut self, version: CifVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Start a data block named `name`
+    pub fn begin_block(&mut self, name: &str) -> io::Result<()> {
+        self.expect(
+            State::Outside,
+            "begin_block",
+            "the previous block is still open",
+        )?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(misuse(format!("invalid block name '{name}'")));
+        }
+        let mmcif = self.mmcif;
+        self.header()?;
+        self.emit(|f| {
+            if !mmcif {
+                writeln!(f)?;
+            }
+            writeln!(f, "data_{name}")?;
+            if mmcif {
+                writeln!(f, "#")?;
+            }
+            Ok(())
+        })?;
+        self.state = State::Block;
+        Ok(())
+    }
+
+    /// Write one data item of the open block
+    pub fn write_item(&mut self, tag: &str, value: &CifValue) -> io::Result<()> {
+        self.expect(
+            State::Block,
+            "write_item",
+            "a block must be open outside any loop",
+        )?;
+        check_tag(tag)?;
+        let category = self.mmcif.then(|| category_of(tag));
+        let new_section = matches!((&self.category, &category), (Some(last), Some(c)) if last != c);
+        let token = tagged_token(value, tag, || None, self.version, &self.float_format);
+        self.emit(|f| {
+            if new_section {
+                writeln!(f, "#")?;
+            }
+            write_item(f, tag, token)
+        })?;
+        self.category = category;
+        Ok(())
+    }
+
+    /// Start a loop with these column tags
+    pub fn begin_loop<T: AsRef<str>>(&mut self, tags: &[T]) -> io::Result<()> {
+        self.expect(
+            State::Block,
+            "begin_loop",
+            "a block must be open outside any loop",
+        )?;
+        if tags.is_empty() {
+            return Err(misuse("a loop needs at least one tag".to_string()));
+        }
+        for tag in tags {
+            check_tag(tag.as_ref())?;
+        }
+        let new_section = self.category.is_some();
+        self.emit(|f| {
+            if new_section {
+                writeln!(f, "#")?;
+            }
+            writeln!(f, "loop_")?;
+            for tag in tags {
+                writeln!(f, "{}", tag.as_ref())?;
+            }
+            Ok(())
+        })?;
+        self.category = None;
+        self.loop_tags = tags.iter().map(|tag| tag.as_ref().to_string()).collect();
+        self.loop_su_columns = su_columns(&self.loop_tags);
+        self.state = State::Loop {
+            columns: tags.len(),
+        };
+        Ok(())
+    }
+
+    /// Write one row of the open loop, one value per column
+    pub fn write_row(&mut self, values: &[CifValue]) -> io::Result<()> {
+        self.check_row(values.len())?;
+        let tokens: Vec<_> = values
+            .iter()
+            .zip(&self.loop_tags)
+            .enumerate()
+            .map(|(i, (value, tag))| {
+                let su = || row_su(values, &self.loop_su_columns, i);
+                tagged_token(value, tag, su, self.version, &self.float_format)
+            })
+            .collect();
+        self.emit(|f| write_row(f, tokens, &[]))
+    }
+
+    /// Write one row of the open loop from values that implement
+    /// [`Display`], such as numbers, without converting them to
+    /// [`CifValue`]s first.
+    ///
+    /// A value that displays as a number (with or without an su, such as
+    /// `1.234(5)`), `?`, or `.` is written as it is; anything else is written
+    /// as text, quoted if need be.
+    pub fn write_row_display(&mut self, values: &[&dyn Display]) -> io::Result<()> {
+        self.check_row(values.len())?;
+        let texts: Vec<String> = values.iter().map(ToString::to_string).collect();
+        let version = self.version;
+        self.emit(|f| write_row(f, texts.iter().map(|s| display_token(s, version)), &[]))
+    }
+
+    /// End the open loop
+    pub fn end_loop(&mut self) -> io::Result<()> {
+        if !matches!(self.state, State::Loop { .. }) {
+            return Err(misuse("end_loop called with no loop open".to_string()));
+        }
+        if self.mmcif {
+            self.emit(|f| writeln!(f, "#"))?;
+        }
+        self.state = State::Block;
+        self.flush()
+    }
+
+    /// End the open block
+    pub fn end_block(&mut self) -> io::Result<()> {
+        self.expect(
+            State::Block,
+            "end_block",
+            "the open loop must be ended first",
+        )?;
+        if self.category.take().is_some() {
+            self.emit(|f| writeln!(f, "#"))?;
+        }
+        self.state = State::Outside;
+        self.flush()
+    }
+
+    /// Finish writing and return the underlying writer, flushed.
+    ///
+    /// Fails if a block or loop is still open.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.expect(
+            State::Outside,
+            "finish",
+            "the open block must be ended first",
+        )?;
+        self.header()?;
+        self.flush()?;
+        Ok(self.out)
+    }
+
+    /// Write the version header, unless already written
+    fn header(&mut self) -> io::Result<()> {
+        if self.started {
+            return Ok(());
+        }
+        self.started = true;
+        let header = match self.version {
+            CifVersion::V1_1 if self.mmcif => return Ok(()),
+            CifVersion::V1_1 => "#\\#CIF_1.1\n",
+            CifVersion::V2_0 => "#\\#CIF_2.0\n",
+        };
+        self.out.write_all(header.as_bytes())
+    }
+
+    /// Run a formatting step against the output, turning a failure back
+    /// into the I/O error behind it
+    fn emit(&mut self, step: impl FnOnce(&mut IoWriter<&mut W>) -> fmt::Result) -> io::Result<()> {
+        let mut out = IoWriter {
+            out: &mut self.out,
+            error: None,
+        };
+        match step(&mut out) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(out
+                .error
+                .unwrap_or_else(|| io::Error::other("formatting failed"))),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Fail unless the writer is in `state`
+    fn expect(&self, state: State, call: &str, requirement: &str) -> io::Result<()> {
+        if self.state == state {
+            Ok(())
+        } else {
+            Err(misuse(format!("{call} called out of order: {requirement}")))
+        }
+    }
+
+    /// Fail unless a loop of `width` columns is open
+    fn check_row(&self, width: usize) -> io::Result<()> {
+        match self.state {
+            State::Loop { columns } if columns == width => Ok(()),
+            State::Loop { columns } => Err(misuse(format!(
+                "row has {width} values but the loop has {columns} columns"
+            ))),
+            _ => Err(misuse("write_row called with no loop open".to_string())),
+        }
+    }
+}
+
+/// Fail unless `tag` is a data name: `_` followed by non-blank characters
+fn check_tag(tag: &str) -> io::Result<()> {
+    if tag.len() > 1 && tag.starts_with('_') && !tag.contains(char::is_whitespace) {
+        Ok(())
+    } else {
+        Err(misuse(format!("invalid tag '{tag}'")))
+    }
+}
+
+fn misuse(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(f: impl FnOnce(&mut CifWriter<Vec<u8>>) -> io::Result<()>) -> String {
+        let mut writer = CifWriter::new(Vec::new(), WriteOptions::new());
+        f(&mut writer).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_quoting_matches_document_writer() {
+        let text = written(|w| {
+            w.begin_block("x")?;
+            w.write_item("_name", &CifValue::Text("two words".into()))?;
+            w.begin_loop(&["_a", "_b"])?;
+            w.write_row(&[CifValue::Text("1.5".into()), CifValue::Text("a\nb".into())])?;
+            w.write_row_display(&[&"it's here", &"1.5(2)"])?;
+            w.end_loop()?;
+            w.end_block()
+        });
+        assert_eq!(
+            text,
+            "#\\#CIF_1.1\n\ndata_x\n_name 'two words'\nloop_\n_a\n_b\n'1.5'\n;a\nb\n;\n'it's here' 1.5(2)\n"
+        );
+    }
+
+    #[test]
+    fn test_mmcif_sections() {
+        let mut writer = CifWriter::new(Vec::new(), WriteOptions::new().style(CifStyle::Mmcif));
+        writer.begin_block("1abc").unwrap();
+        writer
+            .write_item("_entry.id", &CifValue::Text("1ABC".into()))
+            .unwrap();
+        writer
+            .write_item("_cell.length_a", &CifValue::Numeric(50.0))
+            .unwrap();
+        writer
+            .write_item("_cell.length_b", &CifValue::Numeric(60.0))
+            .unwrap();
+        writer.begin_loop(&["_atom_site.id"]).unwrap();
+        writer.write_row_display(&[&1]).unwrap();
+        writer.end_loop().unwrap();
+        writer.end_block().unwrap();
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            text,
+            "data_1abc\n#\n_entry.id 1ABC\n#\n_cell.length_a 50\n_cell.length_b 60\n#\nloop_\n_atom_site.id\n1\n#\n"
+        );
+    }
+
+    #[test]
+    fn test_out_of_order_calls_are_rejected() {
+        let mut writer = CifWriter::new(Vec::new(), WriteOptions::new());
+        let error = writer.write_item("_a", &CifValue::Unknown).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("write_item called out of order"));
+
+        writer.begin_block("x").unwrap();
+        assert!(writer.begin_block("y").is_err());
+        assert!(writer.write_item("a", &CifValue::Unknown).is_err());
+        writer.begin_loop(&["_a", "_b"]).unwrap();
+        let error = writer.write_row(&[CifValue::Unknown]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "row has 1 values but the loop has 2 columns"
+        );
+        assert!(writer.end_block().is_err());
+        writer.end_loop().unwrap();
+        assert!(writer.end_loop().is_err());
+        assert!(writer.write_row(&[]).is_err());
+    }
+}
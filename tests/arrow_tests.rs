@@ -0,0 +1,166 @@
+// tests/arrow_tests.rs
+// Loops written as Arrow IPC files and read back with the arrow crates
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, Int64Type};
+use arrow_array::{Array, RecordBatch};
+use arrow_ipc::reader::FileReader;
+use arrow_schema::DataType;
+use cif_parser::arrow::ArrowOptions;
+use cif_parser::Document;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// A fresh directory under the target directory for this test's output
+fn output_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn read_back(path: &Path) -> RecordBatch {
+    let reader = FileReader::try_new(File::open(path).unwrap(), None).unwrap();
+    let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 1);
+    batches.into_iter().next().unwrap()
+}
+
+#[test]
+fn test_round_trip_values_and_nulls() {
+    let doc = Document::from_file(fixture_path("arrow/mixed.cif")).unwrap();
+    let block = &doc.blocks[0];
+    let path = output_dir("arrow_round_trip").join("atom_site.arrow");
+    let options = ArrowOptions::new()
+        .block(&block.name)
+        .source(doc.source.as_ref().unwrap());
+    block.loops[0].write_arrow_ipc(&path, &options).unwrap();
+
+    let batch = read_back(&path);
+    assert_eq!(batch.num_rows(), 4);
+    let schema = batch.schema();
+    let types: Vec<(&str, &DataType)> = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().as_str(), field.data_type()))
+        .collect();
+    assert_eq!(
+        types,
+        [
+            ("_atom_site_label", &DataType::Utf8),
+            ("_atom_site_type_symbol", &DataType::Utf8),
+            ("_atom_site_fract_x", &DataType::Float64),
+            ("_atom_site_fract_x_su", &DataType::Float64),
+            ("_atom_site_fract_y", &DataType::Float64),
+            ("_atom_site_fract_z", &DataType::Float64),
+            ("_atom_site_fract_z_su", &DataType::Float64),
+            ("_atom_site_occupancy", &DataType::Float64),
+            ("_atom_site_site_symmetry_multiplicity", &DataType::Int64),
+            ("_atom_site_refinement_flags_posn", &DataType::Utf8),
+        ]
+    );
+
+    let column = |name: &str| batch.column_by_name(name).unwrap();
+    let labels = column("_atom_site_label").as_string::<i32>();
+    assert_eq!(labels.value(3), "N1");
+
+    let x = column("_atom_site_fract_x").as_primitive::<Float64Type>();
+    let x_su = column("_atom_site_fract_x_su").as_primitive::<Float64Type>();
+    assert_eq!(x.value(1), 0.1234);
+    assert!((x_su.value(1) - 0.0005).abs() < 1e-12);
+    // Values without an su have a null su
+    assert!(x_su.is_null(0));
+    assert_eq!(x_su.null_count(), 3);
+
+    // ? and . are both null
+    let y = column("_atom_site_fract_y").as_primitive::<Float64Type>();
+    assert!(y.is_null(2));
+    let occupancy = column("_atom_site_occupancy").as_primitive::<Float64Type>();
+    assert!(occupancy.is_null(1));
+    assert_eq!(occupancy.value(2), 0.5);
+    let multiplicity = column("_atom_site_site_symmetry_multiplicity").as_primitive::<Int64Type>();
+    assert_eq!(multiplicity.value(1), 32);
+    assert!(multiplicity.is_null(2));
+
+    // Numbers and text share a Utf8 column
+    let flags = column("_atom_site_refinement_flags_posn").as_string::<i32>();
+    assert!(flags.is_null(0));
+    assert_eq!(
+        (flags.value(1), flags.value(2), flags.value(3)),
+        ("1", "P", "D G")
+    );
+
+    let metadata = schema.metadata();
+    assert_eq!(metadata["cif.block"], "mixed");
+    assert_eq!(metadata["cif.sha256"], doc.source.as_ref().unwrap().sha256);
+    assert!(metadata["cif.source"].ends_with("mixed.cif"));
+    let tags: Vec<String> = serde_json::from_str(&metadata["cif.tags"]).unwrap();
+    assert_eq!(tags, block.loops[0].tags);
+}
+
+#[test]
+fn test_without_su_columns_or_source() {
+    let doc = Document::from_file(fixture_path("arrow/mixed.cif")).unwrap();
+    let batch = doc.blocks[0].loops[0]
+        .to_record_batch(&ArrowOptions::new().su_columns(false))
+        .unwrap();
+    assert_eq!(batch.num_columns(), 8);
+    assert!(batch.column_by_name("_atom_site_fract_x_su").is_none());
+    let schema = batch.schema();
+    assert!(!schema.metadata().contains_key("cif.block"));
+    assert!(!schema.metadata().contains_key("cif.sha256"));
+}
+
+#[test]
+fn test_cli_writes_one_loop_or_all() {
+    let cif = env!("CARGO_BIN_EXE_cif");
+    let file = fixture_path("arrow/mixed.cif");
+    let dir = output_dir("arrow_cli");
+
+    let single = dir.join("atom_site.arrow");
+    let status = Command::new(cif)
+        .args(["arrow", "--loop", "_atom_site", "-o"])
+        .arg(&single)
+        .arg(&file)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(read_back(&single).num_columns(), 10);
+
+    let all = dir.join("all");
+    let status = Command::new(cif)
+        .args(["arrow", "--all-loops", "-o"])
+        .arg(&all)
+        .arg(&file)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let mut names: Vec<String> = fs::read_dir(&all)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["mixed.atom_site.arrow", "mixed.space_group_symop.arrow"]
+    );
+    let symops = read_back(&all.join("mixed.space_group_symop.arrow"));
+    assert_eq!(symops.num_rows(), 2);
+    assert_eq!(symops.schema().field(0).data_type(), &DataType::Int64);
+
+    let missing = Command::new(cif)
+        .args(["arrow", "--loop", "_refln", "-o"])
+        .arg(dir.join("refln.arrow"))
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("no loop _refln"));
+}
@@ -2,7 +2,7 @@
 //!
 //! Tests block name handling, case preservation, and item/loop/frame access
 
-use cif_parser::Document;
+use cif_parser::{Document, WalkEntry};
 
 #[test]
 fn test_block_name_case_preservation() {
@@ -37,3 +37,98 @@ fn test_block_item_access() {
     assert!(block.items.contains_key("_item1"));
     assert!(block.items.contains_key("_item2"));
 }
+
+/// An instrument CIF with per-scan metadata in save frames; `_scan_id` is in
+/// both frames and `_title` in the block and the second frame
+const SCANS: &str = "data_run
+_title 'whole run'
+loop_
+_axis_id
+omega
+phi
+save_scan1
+_scan_id 1
+loop_
+_scan_point
+0.0
+0.5
+save_
+save_scan2
+_scan_id 2
+_title 'second scan'
+_scan_detector pilatus
+save_
+";
+
+#[test]
+fn test_recursive_lookup_precedence() {
+    let doc = Document::parse(SCANS).unwrap();
+    let block = &doc.blocks[0];
+
+    assert!(block.get_item("_scan_detector").is_none());
+    let detector = block.get_item_recursive("_scan_detector").unwrap();
+    assert_eq!(detector.as_string(), Some("pilatus"));
+    // The block wins over a frame, and the first frame over later ones
+    let title = block.get_item_recursive("_title").unwrap();
+    assert_eq!(title.as_string(), Some("whole run"));
+    assert_eq!(
+        block.get_item_recursive("_scan_id").unwrap().as_numeric(),
+        Some(1.0)
+    );
+    assert!(block.get_item_recursive("_missing").is_none());
+
+    assert_eq!(block.find_loop_recursive("_axis_id").unwrap().len(), 2);
+    let points = block.find_loop_recursive("_scan_point").unwrap();
+    assert_eq!(points.tags, ["_scan_point"]);
+    assert!(block.find_loop("_scan_point").is_none());
+}
+
+#[test]
+fn test_recursive_lookup_reports_collisions() {
+    let doc = Document::parse(SCANS).unwrap();
+    let block = &doc.blocks[0];
+
+    let hits = block.get_item_recursive_all("_scan_id");
+    let found: Vec<_> = hits
+        .iter()
+        .map(|(path, value)| (path.clone(), value.as_numeric()))
+        .collect();
+    assert_eq!(
+        found,
+        [(vec!["scan1"], Some(1.0)), (vec!["scan2"], Some(2.0))]
+    );
+
+    let titles = block.get_item_recursive_all("_title");
+    assert_eq!(titles.len(), 2);
+    assert!(titles[0].0.is_empty());
+    assert_eq!(titles[1].0, ["scan2"]);
+    assert!(block.get_item_recursive_all("_missing").is_empty());
+}
+
+#[test]
+fn test_walk_visits_block_then_frames() {
+    let doc = Document::parse(SCANS).unwrap();
+    let entries: Vec<(Vec<&str>, String)> = doc.blocks[0]
+        .walk()
+        .map(|(path, entry)| {
+            let what = match entry {
+                WalkEntry::Item(tag, _) => tag.to_string(),
+                WalkEntry::Loop(loop_) => format!("loop {}", loop_.tags[0]),
+            };
+            (path, what)
+        })
+        .collect();
+    let expected: Vec<(Vec<&str>, String)> = [
+        (vec![], "_title"),
+        (vec![], "loop _axis_id"),
+        (vec!["scan1"], "_scan_id"),
+        (vec!["scan1"], "loop _scan_point"),
+        (vec!["scan2"], "_scan_id"),
+        (vec!["scan2"], "_title"),
+        (vec!["scan2"], "_scan_detector"),
+    ]
+    .into_iter()
+    .map(|(path, what)| (path, what.to_string()))
+    .collect();
+    assert_eq!(entries, expected);
+}
@@ -150,3 +150,52 @@ fn test_as_table_keys() {
     let text = CifValue::Text("hello".to_string());
     assert!(text.as_table_keys().is_none());
 }
+
+// ========================================================================
+// Standard Uncertainty Tests
+// ========================================================================
+
+#[test]
+fn test_numbers_keep_their_su() {
+    let cif = "#\\#CIF_2.0\ndata_test\n_a 5.4310(15)\n_b '12(3)'\n_c [1.5(2) 7]\n_d 5.431\n";
+    let doc = CifDocument::parse(cif).unwrap();
+    let block = doc.first_block().unwrap();
+
+    let a = block.get_item("_a").unwrap();
+    assert_eq!(
+        a,
+        &CifValue::NumericSu {
+            value: 5.431,
+            su: 0.0015
+        }
+    );
+    assert!(a.is_numeric());
+    assert_eq!(a.as_numeric(), Some(5.431));
+    assert_eq!(a.as_numeric_with_su(), Some((5.431, Some(0.0015))));
+    assert_eq!(
+        block.get_item("_b").unwrap().as_numeric_with_su(),
+        Some((12.0, Some(3.0)))
+    );
+    let list = block.get_item("_c").unwrap().as_list().unwrap();
+    assert_eq!(list[0].as_numeric_with_su(), Some((1.5, Some(0.2))));
+    assert_eq!(
+        block.get_item("_d").unwrap().as_numeric_with_su(),
+        Some((5.431, None))
+    );
+
+    // Written back digit for digit, so the su is not rounded away
+    let written = doc.to_string();
+    assert!(written.contains("5.4310(15)"), "{written}");
+    assert!(written.contains("[1.5(2) 7]"), "{written}");
+    let reparsed = CifDocument::parse(&written).unwrap();
+    assert_eq!(reparsed.first_block().unwrap().items, block.items);
+}
+
+#[test]
+fn test_su_needs_a_number() {
+    for text in ["C1(2)", "1.0()", "1.0(a)", "inf(1)"] {
+        let value = CifValue::parse_value(text);
+        assert_eq!(value, CifValue::Text(text.to_string()), "{text}");
+        assert!(!value.is_numeric());
+    }
+}
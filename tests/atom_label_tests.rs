@@ -0,0 +1,57 @@
+// tests/atom_label_tests.rs
+// Element guesses from atom labels agree with the type symbols in real files
+
+use cif_parser::{AtomLabel, Document};
+use std::path::Path;
+
+#[test]
+fn test_label_elements_match_type_symbols() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    let mut checked = 0;
+    for dir in std::fs::read_dir(root).unwrap() {
+        for entry in std::fs::read_dir(dir.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            // Protein atom names (`CA`, `CD1`, and `CL` for chloride) cannot be
+            // told apart by the label alone, so the lysozyme file is left out
+            if path.extension().and_then(|e| e.to_str()) != Some("cif")
+                || path.to_string_lossy().contains("lysozyme")
+            {
+                continue;
+            }
+            let Ok(doc) = Document::from_file(&path) else {
+                continue;
+            };
+            for block in &doc.blocks {
+                let Some(sites) = block.find_loop("_atom_site_type_symbol") else {
+                    continue;
+                };
+                let (Some(labels), Some(types)) = (
+                    sites.get_column("_atom_site_label"),
+                    sites.get_column("_atom_site_type_symbol"),
+                ) else {
+                    continue;
+                };
+                for (label, symbol) in labels.iter().zip(types) {
+                    let (Some(label), Some(symbol)) = (label.as_string(), symbol.as_string())
+                    else {
+                        continue;
+                    };
+                    // Type symbols may carry a charge, as in `O2-`
+                    let element: String = symbol
+                        .chars()
+                        .take_while(char::is_ascii_alphabetic)
+                        .collect();
+                    let parsed = AtomLabel::parse(label);
+                    assert_eq!(
+                        parsed.element.as_deref(),
+                        Some(element.as_str()),
+                        "{label} in {}",
+                        path.display()
+                    );
+                    checked += 1;
+                }
+            }
+        }
+    }
+    assert!(checked > 100, "only {checked} labels checked");
+}
@@ -0,0 +1,86 @@
+// tests/atom_site_tests.rs
+// Reading the atom sites of a block, with or without a unit cell
+
+use cif_parser::Document;
+use std::path::Path;
+
+#[test]
+fn test_sites_of_a_real_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/example_cifs/paracetamol/ccdc_paracetamol.cif");
+    let doc = Document::from_file(path).unwrap();
+    let block = doc.blocks.iter().find(|b| b.unit_cell().is_some()).unwrap();
+    let sites = block.atom_sites();
+
+    assert_eq!(sites, block.structure().unwrap().sites);
+    let o1 = &sites[0];
+    assert_eq!(o1.label, "O1");
+    assert_eq!(o1.type_symbol.as_deref(), Some("O"));
+    assert_eq!(o1.fract, Some([0.23811, 0.7634, -0.36882]));
+    assert_eq!(o1.cartn, None);
+    assert!((o1.fract_su[1] - 0.0007).abs() < 1e-12);
+    assert_eq!(o1.u_iso, Some(0.0106));
+    assert_eq!(o1.occupancy, 1.0);
+}
+
+#[test]
+fn test_sites_without_a_cell() {
+    let cif = "data_x\nloop_\n_atom_site_label\n_atom_site_fract_x\n_atom_site_fract_y\n\
+               _atom_site_fract_z\n_atom_site_occupancy\n_atom_site_B_iso_or_equiv\n\
+               Na1 0 0 0 0.5 ?\nCl1 0.5 0.5 0.5 1 .\n";
+    let doc = Document::parse(cif).unwrap();
+    let block = &doc.blocks[0];
+    assert!(block.structure().is_none());
+
+    let sites = block.atom_sites();
+    assert_eq!(sites.len(), 2);
+    assert_eq!(sites[0].occupancy, 0.5);
+    assert_eq!(sites[0].u_iso, None);
+    assert_eq!(sites[1].fract, Some([0.5; 3]));
+    assert_eq!(sites[1].element().unwrap().symbol(), "Cl");
+}
+
+#[test]
+fn test_mmcif_cartesian_sites_are_converted_with_a_cell() {
+    let sites = "loop_\n_atom_site.id\n_atom_site.type_symbol\n_atom_site.Cartn_x\n\
+                 _atom_site.Cartn_y\n_atom_site.Cartn_z\n_atom_site.occupancy\n\
+                 1 N 2.5 5 7.5 0.75\n";
+
+    let cell = "_cell.length_a 10\n_cell.length_b 10\n_cell.length_c 10\n\
+                _cell.angle_alpha 90\n_cell.angle_beta 90\n_cell.angle_gamma 90\n";
+    let doc = Document::parse(&format!("data_x\n{cell}{sites}")).unwrap();
+    let sites = doc.blocks[0].atom_sites();
+    assert_eq!(sites[0].label, "1");
+    assert_eq!(sites[0].occupancy, 0.75);
+    assert_eq!(sites[0].cartn, Some([2.5, 5.0, 7.5]));
+    for (x, expected) in sites[0].fract.unwrap().iter().zip([0.25, 0.5, 0.75]) {
+        assert!((x - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_mmcif_cartesian_sites_without_a_cell() {
+    let cif = "data_1abc\nloop_\n_atom_site.group_PDB\n_atom_site.id\n\
+               _atom_site.type_symbol\n_atom_site.Cartn_x\n_atom_site.Cartn_y\n\
+               _atom_site.Cartn_z\n_atom_site.occupancy\n_atom_site.B_iso_or_equiv\n\
+               ATOM 1 N 11.104 6.134 -6.504 1.00 30.00\n\
+               ATOM 2 C 11.639 6.071 -5.147 0.50 ?\n";
+    let doc = Document::parse(cif).unwrap();
+    let block = &doc.blocks[0];
+    assert!(block.structure().is_none());
+
+    let sites = block.atom_sites();
+    assert_eq!(sites.len(), 2);
+    assert_eq!(sites[0].label, "1");
+    assert_eq!(sites[0].fract, None);
+    assert_eq!(sites[0].cartn, Some([11.104, 6.134, -6.504]));
+    assert_eq!(sites[1].element().unwrap().symbol(), "C");
+    assert_eq!(sites[1].occupancy, 0.5);
+    assert_eq!(sites[1].u_iso, None);
+}
+
+#[test]
+fn test_no_atom_site_loop() {
+    let doc = Document::parse("data_x\n_cell_length_a 5\n").unwrap();
+    assert!(doc.blocks[0].atom_sites().is_empty());
+}
@@ -0,0 +1,280 @@
+// tests/bcif_tests.rs
+// BinaryCIF decoding and encoding, behind the bcif feature
+
+use cif_parser::{CifDocument, CifError, CifValue, Document};
+use rmpv::Value as Msg;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> CifDocument {
+    Document::from_file(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn map(entries: Vec<(&str, Msg)>) -> Msg {
+    Msg::Map(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+}
+
+fn byte_array(kind: i64) -> Msg {
+    map(vec![("kind", "ByteArray".into()), ("type", kind.into())])
+}
+
+fn column(name: &str, encoding: Vec<Msg>, data: Vec<u8>, mask: Msg) -> Msg {
+    map(vec![
+        ("name", name.into()),
+        (
+            "data",
+            map(vec![
+                ("encoding", Msg::Array(encoding)),
+                ("data", Msg::Binary(data)),
+            ]),
+        ),
+        ("mask", mask),
+    ])
+}
+
+fn file(columns: Vec<Msg>, rows: i64) -> Vec<u8> {
+    let category = map(vec![
+        ("name", "_demo".into()),
+        ("columns", Msg::Array(columns)),
+        ("rowCount", rows.into()),
+    ]);
+    let block = map(vec![
+        ("header", "test".into()),
+        ("categories", Msg::Array(vec![category])),
+    ]);
+    let file = map(vec![
+        ("version", "0.3.0".into()),
+        ("encoder", "hand".into()),
+        ("dataBlocks", Msg::Array(vec![block])),
+    ]);
+    let mut bytes = Vec::new();
+    rmpv::encode::write_value(&mut bytes, &file).unwrap();
+    bytes
+}
+
+fn numbers(doc: &CifDocument, tag: &str) -> Vec<f64> {
+    let loop_ = &doc.blocks[0].loops[0];
+    (0..loop_.len())
+        .map(|row| loop_.get_by_tag(row, tag).unwrap().as_numeric().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_round_trip_keeps_every_mmcif_value() {
+    let doc = fixture("mmcif_fragment.cif");
+    let back = CifDocument::from_bcif(&doc.to_bcif().unwrap()).unwrap();
+    let (block, other) = (&doc.blocks[0], &back.blocks[0]);
+    assert_eq!(other.name, "1CRN");
+
+    for (tag, value) in &block.items {
+        assert_eq!(other.get_item(tag), Some(value), "{tag}");
+    }
+    assert_eq!(other.loops.len(), block.loops.len());
+    for loop_ in &block.loops {
+        let found = other
+            .loops
+            .iter()
+            .find(|l| l.tags == loop_.tags)
+            .unwrap_or_else(|| panic!("no loop of {}", loop_.tags[0]));
+        assert_eq!(found.values, loop_.values, "{}", loop_.tags[0]);
+    }
+}
+
+#[test]
+fn test_undotted_tags_round_trip() {
+    let doc = Document::parse(
+        "data_x\n_x 1\n_cell_length_a 5.4\nloop_\n_foo\na\nb\n\
+         loop_\n_atom_site_label\n_atom_site_fract_x\nC 0.1\nO 0.2\n",
+    )
+    .unwrap();
+    let back = CifDocument::from_bcif(&doc.to_bcif().unwrap()).unwrap();
+    let block = &back.blocks[0];
+    // A tag that is a category alone comes back unchanged
+    assert_eq!(block.get_item("_x"), Some(&CifValue::Numeric(1.0)));
+    assert_eq!(
+        block.get_item("_cell.length_a"),
+        Some(&CifValue::Numeric(5.4))
+    );
+    let tags: Vec<&[String]> = block.loops.iter().map(|l| l.tags.as_slice()).collect();
+    assert_eq!(
+        tags,
+        [
+            &["_foo".to_string()][..],
+            &[
+                "_atom_site.label".to_string(),
+                "_atom_site.fract_x".to_string()
+            ][..],
+        ]
+    );
+    assert_eq!(block.loops[0].values, doc.blocks[0].loops[0].values);
+}
+
+#[test]
+fn test_integers_of_any_size_and_sign_round_trip() {
+    let mut cif = "data_x\nloop_\n_demo.id\n_demo.value\n_demo.mixed\n".to_string();
+    let values = [0, -1, 127, 128, -129, 40000, -70000, 2_000_000_000, 5, 5, 5];
+    for (i, v) in values.iter().enumerate() {
+        cif += &format!("{} {v} {}\n", i + 1, if i % 2 == 0 { "1" } else { "A1" });
+    }
+    let doc = Document::parse(&cif).unwrap();
+    let back = CifDocument::from_bcif(&doc.to_bcif().unwrap()).unwrap();
+    let expected: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+    assert_eq!(numbers(&back, "_demo.value"), expected);
+    assert_eq!(
+        back.blocks[0].loops[0].values,
+        doc.blocks[0].loops[0].values
+    );
+
+    // Consecutive ids pack to about a byte per run
+    let ids: String = (1..=1000).map(|i| format!("{i}\n")).collect();
+    let doc = Document::parse(&format!("data_x\nloop_\n_demo.id\n{ids}")).unwrap();
+    assert!(doc.to_bcif().unwrap().len() < 300);
+}
+
+#[test]
+fn test_each_encoding_decodes() {
+    let int32 = |values: &[i32]| values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let columns = vec![
+        column(
+            "fixed",
+            vec![
+                map(vec![("kind", "FixedPoint".into()), ("factor", 100.into())]),
+                byte_array(3),
+            ],
+            int32(&[150, -25, 0, 1000]),
+            Msg::Nil,
+        ),
+        column(
+            "quantized",
+            vec![
+                map(vec![
+                    ("kind", "IntervalQuantization".into()),
+                    ("min", 0.0.into()),
+                    ("max", 1.0.into()),
+                    ("numSteps", 11.into()),
+                ]),
+                byte_array(4),
+            ],
+            vec![0, 5, 10, 2],
+            Msg::Nil,
+        ),
+        column(
+            "packed",
+            vec![
+                map(vec![
+                    ("kind", "IntegerPacking".into()),
+                    ("byteCount", 1.into()),
+                    ("isUnsigned", false.into()),
+                    ("srcSize", 4.into()),
+                ]),
+                byte_array(1),
+            ],
+            [127i8, 3, -128, -1, 5, 0].map(|b| b as u8).to_vec(),
+            Msg::Nil,
+        ),
+        column(
+            "run",
+            vec![
+                map(vec![("kind", "Delta".into()), ("origin", 10.into())]),
+                map(vec![("kind", "RunLength".into()), ("srcSize", 4.into())]),
+                byte_array(3),
+            ],
+            int32(&[1, 2, 3, 2]),
+            Msg::Nil,
+        ),
+        column(
+            "text",
+            vec![map(vec![
+                ("kind", "StringArray".into()),
+                ("dataEncoding", Msg::Array(vec![byte_array(1)])),
+                ("stringData", "NCA".into()),
+                ("offsetEncoding", Msg::Array(vec![byte_array(4)])),
+                ("offsets", Msg::Binary(vec![0, 1, 3])),
+            ])],
+            [0i8, 1, -1, -1].map(|b| b as u8).to_vec(),
+            map(vec![
+                ("encoding", Msg::Array(vec![byte_array(4)])),
+                ("data", Msg::Binary(vec![0, 0, 1, 2])),
+            ]),
+        ),
+    ];
+    let doc = CifDocument::from_bcif(&file(columns, 4)).unwrap();
+    assert_eq!(doc.blocks[0].name, "test");
+    assert_eq!(numbers(&doc, "_demo.fixed"), [1.5, -0.25, 0.0, 10.0]);
+    assert_eq!(numbers(&doc, "_demo.quantized"), [0.0, 0.5, 1.0, 0.2]);
+    assert_eq!(numbers(&doc, "_demo.packed"), [130.0, -129.0, 5.0, 0.0]);
+    assert_eq!(numbers(&doc, "_demo.run"), [11.0, 12.0, 15.0, 18.0]);
+    let loop_ = &doc.blocks[0].loops[0];
+    let text: Vec<_> = (0..4)
+        .map(|row| loop_.get_by_tag(row, "_demo.text").unwrap().clone())
+        .collect();
+    assert_eq!(
+        text,
+        [
+            CifValue::Text("N".into()),
+            CifValue::Text("CA".into()),
+            CifValue::NotApplicable,
+            CifValue::Unknown,
+        ]
+    );
+
+    // One row gives data items
+    let doc = CifDocument::from_bcif(&file(
+        vec![column(
+            "a",
+            vec![byte_array(33)],
+            2.5f64.to_le_bytes().to_vec(),
+            Msg::Nil,
+        )],
+        1,
+    ))
+    .unwrap();
+    assert_eq!(
+        doc.blocks[0].get_item("_demo.a"),
+        Some(&CifValue::Numeric(2.5))
+    );
+}
+
+#[test]
+fn test_invalid_input_is_an_error() {
+    assert!(CifDocument::from_bcif(b"data_x\n_a 1\n").is_err());
+
+    let unknown = column(
+        "a",
+        vec![map(vec![("kind", "Zip".into())])],
+        vec![1],
+        Msg::Nil,
+    );
+    let error = CifDocument::from_bcif(&file(vec![unknown], 1)).unwrap_err();
+    assert!(
+        error.to_string().contains("unknown encoding Zip"),
+        "{error}"
+    );
+
+    let short = column("a", vec![byte_array(4)], vec![1, 2], Msg::Nil);
+    assert!(CifDocument::from_bcif(&file(vec![short], 3)).is_err());
+
+    // What BinaryCIF cannot hold
+    let list = Document::parse("#\\#CIF_2.0\ndata_x\n_a.b [1 2]\n").unwrap();
+    assert!(matches!(list.to_bcif(), Err(CifError::InvalidValue { .. })));
+    let frames = Document::parse("data_x\nsave_f\n_a.b 1\nsave_\n").unwrap();
+    assert!(frames.to_bcif().is_err());
+}
+
+#[test]
+fn test_from_file_reads_bcif_by_extension() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("1crn_fragment.bcif");
+    let doc = fixture("mmcif_fragment.cif");
+    std::fs::write(&path, doc.to_bcif().unwrap()).unwrap();
+
+    let read = Document::from_file(&path).unwrap();
+    assert_eq!(
+        read.blocks[0].get_item("_entry.id"),
+        doc.blocks[0].get_item("_entry.id")
+    );
+    assert_eq!(read.source.unwrap().path.as_deref(), Some(path.as_path()));
+}
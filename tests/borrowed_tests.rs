@@ -0,0 +1,165 @@
+// tests/borrowed_tests.rs
+// Borrowed parsing: the same documents as the grammar, without copying text
+
+use cif_parser::corpus::cif_files;
+use cif_parser::{CifDocument, CifDocumentRef, CifError, CifValue, CifValueRef, CifVersion};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Whether `text` is a slice of `input`
+fn borrows_from(text: &str, input: &str) -> bool {
+    let range = input.as_bytes().as_ptr_range();
+    range.contains(&text.as_ptr()) && text.len() <= input.len()
+}
+
+/// Assert that borrowed parsing gives what the grammar gives for `text`
+fn assert_parses_as_the_grammar_does(text: &str, name: &str) -> bool {
+    match (CifDocument::parse(text), CifDocumentRef::parse(text)) {
+        (Ok(owned), Ok(borrowed)) => {
+            // Debug shows item order and value kinds, which semantic
+            // equality does not compare
+            assert_eq!(
+                format!("{:?}", borrowed.into_owned()),
+                format!("{owned:?}"),
+                "{name}"
+            );
+            true
+        }
+        (Err(owned), Err(borrowed)) => {
+            assert_eq!(borrowed.to_string(), owned.to_string(), "{name}");
+            false
+        }
+        (owned, borrowed) => panic!(
+            "{name}: grammar {:?}, borrowed {:?}",
+            owned.err(),
+            borrowed.err()
+        ),
+    }
+}
+
+#[test]
+fn test_every_example_parses_as_the_grammar_does() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut compared = 0;
+    for path in cif_files(root).unwrap() {
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if assert_parses_as_the_grammar_does(&text, &path.display().to_string()) {
+            compared += 1;
+        }
+    }
+    assert!(compared > 50, "{compared}");
+}
+
+#[test]
+fn test_edge_cases_parse_as_the_grammar_does() {
+    for cif in [
+        "data_a\n_x\n",
+        "data_a\n_x 1\n_y 2\n_x 3\n",
+        "data_a\n_a '''a'''\n",
+        "#\\#CIF_2.0\ndata_a\n_a '''a'''\n",
+        "data_a\n_a 'x'y' _b \"\"\"q\n",
+        "data_a\n_a ;x\n;\n",
+        "data_a\n_a 1#c\n",
+        "data_a\n_a 1 # no line terminator",
+        "data_a\nloop_\n_x\n1 2\n",
+        "#\\#CIF_2.0\ndata_\n_a 1\n",
+        "#\\#CIF_2.0\ndata_a\n_a [1 2}\n",
+        "data_a\n_a\u{a0}1\n",
+    ] {
+        assert_parses_as_the_grammar_does(cif, cif);
+    }
+}
+
+#[test]
+fn test_names_and_text_borrow_from_the_input() {
+    let cif = "data_x\n_name 'O''Brien'\n_note\n;first\nsecond\n;\nsave_frame\n_a.b 1\nsave_\n\
+               loop_\n_atom_site_label\n_atom_site_occupancy\nC1 1.0\nC2 ?\n";
+    let doc = CifDocumentRef::parse(cif).unwrap();
+    let block = doc.first_block().unwrap();
+    assert!(borrows_from(block.name, cif));
+    for (tag, value) in &block.items {
+        assert!(borrows_from(tag, cif));
+        assert!(matches!(value, CifValueRef::Text(Cow::Borrowed(text)) if borrows_from(text, cif)));
+    }
+    assert_eq!(block.get_item("_name").unwrap().as_str(), Some("O''Brien"));
+    assert_eq!(
+        block.get_item("_note").unwrap().as_str(),
+        Some("first\nsecond")
+    );
+    assert_eq!(
+        block.get_frame("frame").unwrap().get_item("_a.b"),
+        Some(&CifValueRef::Numeric(1.0))
+    );
+
+    let atoms = block.find_loop("_atom_site_label").unwrap();
+    assert_eq!(atoms.len(), 2);
+    assert_eq!(
+        atoms.get_by_tag(1, "_atom_site_occupancy"),
+        Some(&CifValueRef::Unknown)
+    );
+
+    // CR line terminators in a text field are rewritten, so it is copied
+    let doc = CifDocumentRef::parse("data_x\r\n_note\r\n;a\r\nb\r\n;\r\n_id c1\r\n").unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(
+        block.get_item("_note"),
+        Some(&CifValueRef::Text(Cow::Owned("a\nb".into())))
+    );
+    assert!(matches!(
+        block.get_item("_id"),
+        Some(CifValueRef::Text(Cow::Borrowed("c1")))
+    ));
+}
+
+#[test]
+fn test_cif2_lists_and_tables() {
+    let cif = "#\\#CIF_2.0\ndata_x\n_list [1 'a' [? .]]\n_table {'k':\"\"\"v\"\"\" 'n':2}\n";
+    let doc = CifDocumentRef::parse(cif).unwrap();
+    assert_eq!(doc.version, CifVersion::V2_0);
+    let owned = doc.into_owned();
+    assert!(owned.semantically_equal(&CifDocument::parse(cif).unwrap()));
+    let table = owned.blocks[0].get_item("_table").unwrap();
+    assert_eq!(table.as_table_get("k"), Some(&CifValue::Text("v".into())));
+
+    // CIF 1.1 keeps the brackets as text
+    let doc = CifDocumentRef::parse("data_x\n_a [1 2]\n").unwrap();
+    assert_eq!(
+        doc.blocks[0].get_item("_a").unwrap().as_str(),
+        Some("[1 2]")
+    );
+}
+
+#[test]
+fn test_malformed_input_is_an_error_with_its_location() {
+    let error = CifDocumentRef::parse("data_x\nloop_\n_a\n_b\n1 2 3\n").unwrap_err();
+    assert!(
+        matches!(
+            error,
+            CifError::LoopMismatch {
+                tags: 2,
+                values: 3,
+                ..
+            }
+        ),
+        "{error:?}"
+    );
+
+    let error = CifDocumentRef::parse("_a 1\n").unwrap_err();
+    assert_eq!(error.location(), Some((1, 1)));
+    let error = CifDocumentRef::parse("data_x\n_a\n").unwrap_err();
+    assert_eq!(error.location(), Some((3, 1)));
+    let error = CifDocumentRef::parse("data_x\nsave_f\n_a 1\n").unwrap_err();
+    assert!(error.to_string().contains("not closed"), "{error}");
+
+    let deep = format!(
+        "#\\#CIF_2.0\ndata_x\n_a {}{}\n",
+        "[".repeat(200),
+        "]".repeat(200)
+    );
+    assert!(matches!(
+        CifDocumentRef::parse(&deep),
+        Err(CifError::NestingTooDeep { .. })
+    ));
+}
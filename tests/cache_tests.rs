@@ -0,0 +1,193 @@
+// tests/cache_tests.rs
+// Cached loads: hits skip parsing, damaged entries are rewritten, old entries evicted
+
+use cif_parser::cache::{self, CacheOptions, CacheStatus};
+use cif_parser::{Document, ParseOptions, Selection};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const PARACETAMOL: &str = "tests/example_cifs/paracetamol/ccdc_paracetamol.cif";
+
+/// A fresh scratch directory for one test
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cif-cache-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "cifc"))
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Mark a cache entry as last used `seconds` ago
+fn used_ago(entry: &Path, seconds: u64) {
+    let file = File::options().write(true).open(entry).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(seconds))
+        .unwrap();
+}
+
+#[test]
+fn test_second_load_skips_parsing() {
+    let dir = scratch("hit");
+    let options = CacheOptions::new(dir.join("cache"));
+    let default = ParseOptions::default();
+
+    let (parsed, status) = cache::load(PARACETAMOL, &default, &options).unwrap();
+    assert_eq!(status, CacheStatus::Miss);
+    let (cached, status) = cache::load(PARACETAMOL, &default, &options).unwrap();
+    assert_eq!(status, CacheStatus::Hit);
+
+    assert!(cached.semantically_equal(&parsed));
+    assert!(cached.semantically_equal(&Document::from_file(PARACETAMOL).unwrap()));
+    let source = cached.source.unwrap();
+    assert_eq!(source.path.as_deref(), Some(Path::new(PARACETAMOL)));
+    assert_eq!(source.sha256, parsed.source.unwrap().sha256);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_changed_content_misses() {
+    let dir = scratch("changed");
+    let options = CacheOptions::new(dir.join("cache"));
+    let path = dir.join("a.cif");
+
+    fs::write(&path, "data_a\n_cell_length_a 5.0\n").unwrap();
+    let doc = Document::from_file_cached(&path, &options).unwrap();
+    assert_eq!(
+        doc.blocks[0]
+            .get_item("_cell_length_a")
+            .unwrap()
+            .as_numeric(),
+        Some(5.0)
+    );
+
+    fs::write(&path, "data_a\n_cell_length_a 6.0\n").unwrap();
+    let (doc, status) = cache::load(&path, &ParseOptions::default(), &options).unwrap();
+    assert_eq!(status, CacheStatus::Miss);
+    assert_eq!(
+        doc.blocks[0]
+            .get_item("_cell_length_a")
+            .unwrap()
+            .as_numeric(),
+        Some(6.0)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_options_are_cached_separately() {
+    let dir = scratch("options");
+    let options = CacheOptions::new(dir.join("cache"));
+    let first = ParseOptions::new().select(Selection::TagPrefixes(vec!["_cell".into()]));
+
+    cache::load(PARACETAMOL, &ParseOptions::default(), &options).unwrap();
+    let (doc, status) = cache::load(PARACETAMOL, &first, &options).unwrap();
+    assert_eq!(status, CacheStatus::Miss);
+    assert!(doc.partial);
+    let (doc, status) = cache::load(PARACETAMOL, &first, &options).unwrap();
+    assert_eq!(status, CacheStatus::Hit);
+    assert!(doc.partial);
+    assert!(doc.blocks[0].loops.is_empty());
+    assert_eq!(entries(&options.dir).len(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_corrupt_entry_is_rewritten() {
+    let dir = scratch("corrupt");
+    let options = CacheOptions::new(dir.join("cache"));
+    let default = ParseOptions::default();
+    cache::load(PARACETAMOL, &default, &options).unwrap();
+
+    let entry = entries(&options.dir).remove(0);
+    let mut bytes = fs::read(&entry).unwrap();
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xff;
+    fs::write(&entry, &bytes).unwrap();
+
+    let (doc, status) = cache::load(PARACETAMOL, &default, &options).unwrap();
+    assert_eq!(status, CacheStatus::Invalid);
+    assert!(doc.semantically_equal(&Document::from_file(PARACETAMOL).unwrap()));
+    let (_, status) = cache::load(PARACETAMOL, &default, &options).unwrap();
+    assert_eq!(status, CacheStatus::Hit);
+
+    fs::write(&entry, b"not a cache entry").unwrap();
+    let (_, status) = cache::load(PARACETAMOL, &default, &options).unwrap();
+    assert_eq!(status, CacheStatus::Invalid);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_least_recently_used_entries_are_evicted() {
+    let dir = scratch("evict");
+    let default = ParseOptions::default();
+    let paths: Vec<_> = (0..3)
+        .map(|i| {
+            let path = dir.join(format!("{i}.cif"));
+            fs::write(&path, format!("data_{i}\n_note '{}'\n", "x".repeat(1000))).unwrap();
+            path
+        })
+        .collect();
+
+    // Room for two entries but not three
+    let unbounded = CacheOptions::new(dir.join("sizing"));
+    cache::load(&paths[0], &default, &unbounded).unwrap();
+    let entry_size = fs::metadata(&entries(&unbounded.dir)[0]).unwrap().len();
+    let options = CacheOptions::new(dir.join("cache")).max_bytes(entry_size * 5 / 2);
+
+    cache::load(&paths[0], &default, &options).unwrap();
+    let first = entries(&options.dir).remove(0);
+    used_ago(&first, 300);
+    cache::load(&paths[1], &default, &options).unwrap();
+    let second = entries(&options.dir)
+        .into_iter()
+        .find(|e| *e != first)
+        .unwrap();
+    used_ago(&second, 200);
+    // Using the first entry again makes the second the least recently used
+    let (_, status) = cache::load(&paths[0], &default, &options).unwrap();
+    assert_eq!(status, CacheStatus::Hit);
+    cache::load(&paths[2], &default, &options).unwrap();
+
+    assert_eq!(entries(&options.dir).len(), 2);
+    let status = |path| cache::load(path, &default, &options).unwrap().1;
+    assert_eq!(status(&paths[0]), CacheStatus::Hit);
+    assert_eq!(status(&paths[2]), CacheStatus::Hit);
+    assert_eq!(status(&paths[1]), CacheStatus::Miss);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unwritable_cache_still_loads() {
+    let dir = scratch("unwritable");
+    // A file where the cache directory should be
+    let blocker = dir.join("cache");
+    fs::write(&blocker, "").unwrap();
+    let options = CacheOptions::new(&blocker);
+
+    let (doc, status) = cache::load(PARACETAMOL, &ParseOptions::default(), &options).unwrap();
+    assert_eq!(status, CacheStatus::Miss);
+    assert!(!doc.blocks.is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_parse_errors_are_not_cached() {
+    let dir = scratch("error");
+    let options = CacheOptions::new(dir.join("cache"));
+    let path = dir.join("bad.cif");
+    fs::write(&path, "data_a\n_x 'unterminated\n").unwrap();
+
+    let error = Document::from_file_cached(&path, &options).unwrap_err();
+    assert!(error.to_string().contains("bad.cif"), "{error}");
+    assert!(!options.dir.exists() || entries(&options.dir).is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,159 @@
+// tests/canonical_order_tests.rs
+// Structures built in different orders write byte-identical CIF once put in canonical order
+
+use cif_parser::writer::WriteOptions;
+use cif_parser::{Block, Document, ElementOrder, Loop, Value};
+
+/// Atom sites as (label, type symbol, x, occupancy)
+const SITES: &[(&str, &str, f64, f64)] = &[
+    ("C10", "C", 0.10, 1.0),
+    ("O1", "O", 0.20, 1.0),
+    ("C2", "C", 0.30, 1.0),
+    ("Cu1", "Cu2+", 0.40, 0.5),
+    ("C1", "C", 0.50, 1.0),
+    ("H1A", "H", 0.60, 1.0),
+];
+
+/// Build a block, adding items, columns, and rows in the given orders
+fn build(item_order: &[usize], column_order: &[usize], row_order: &[usize]) -> Document {
+    let mut block = Block::new("built".to_string());
+    let items = [
+        ("_cell_length_a", Value::Numeric(5.0)),
+        (
+            "_chemical_formula_sum",
+            Value::Text("C4 H O Cu".to_string()),
+        ),
+        ("_space_group_name_H-M_alt", Value::Text("P 1".to_string())),
+    ];
+    for &i in item_order {
+        block
+            .items
+            .insert(items[i].0.to_string(), items[i].1.clone());
+    }
+
+    let tags = [
+        "_atom_site_label",
+        "_atom_site_type_symbol",
+        "_atom_site_fract_x",
+        "_atom_site_occupancy",
+        "_atom_site_my_note",
+    ];
+    let mut sites = Loop::new(column_order.iter().map(|&c| tags[c].to_string()).collect());
+    let mut aniso = Loop::new(vec![
+        "_atom_site_aniso_label".to_string(),
+        "_atom_site_aniso_U_11".to_string(),
+    ]);
+    for &r in row_order {
+        let (label, symbol, x, occupancy) = SITES[r];
+        let row = [
+            Value::Text(label.to_string()),
+            Value::Text(symbol.to_string()),
+            Value::Numeric(x),
+            Value::Numeric(occupancy),
+            Value::Text(format!("site {r}")),
+        ];
+        sites
+            .push_row(column_order.iter().map(|&c| row[c].clone()).collect())
+            .unwrap();
+        if symbol != "H" {
+            aniso
+                .push_row(vec![
+                    Value::Text(label.to_string()),
+                    Value::Numeric(x / 10.0),
+                ])
+                .unwrap();
+        }
+    }
+    block.loops.push(sites);
+    block.loops.push(aniso);
+
+    let mut doc = Document::new();
+    doc.blocks.push(block);
+    doc
+}
+
+fn canonical_text(mut doc: Document) -> String {
+    doc.blocks[0].canonical_atom_site_order(ElementOrder::HeavyFirst);
+    let options = WriteOptions::new()
+        .atom_site_column_order(true)
+        .sort_items(true);
+    doc.to_string_with_options(&options)
+}
+
+#[test]
+fn test_build_order_does_not_change_output() {
+    let first = build(&[0, 1, 2], &[0, 1, 2, 3, 4], &[0, 1, 2, 3, 4, 5]);
+    let second = build(&[2, 0, 1], &[4, 3, 2, 0, 1], &[5, 3, 4, 2, 1, 0]);
+    assert_ne!(first.to_string(), second.to_string());
+
+    let text = canonical_text(first);
+    assert_eq!(text, canonical_text(second));
+
+    // Heaviest element first, labels in natural order, unknown column last
+    let expected = "\
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_occupancy
+_atom_site_my_note
+Cu1 Cu2+ 0.4 0.5 'site 3'
+O1 O 0.2 1 'site 1'
+C1 C 0.5 1 'site 4'
+C2 C 0.3 1 'site 2'
+C10 C 0.1 1 'site 0'
+H1A H 0.6 1 'site 5'
+loop_
+_atom_site_aniso_label
+_atom_site_aniso_U_11
+Cu1 0.04
+O1 0.02
+C1 0.05
+C2 0.03
+C10 0.01
+";
+    assert!(text.contains(expected), "{text}");
+    assert!(Document::parse(&text).is_ok());
+}
+
+#[test]
+fn test_alphabetical_element_order() {
+    let mut doc = build(&[0, 1, 2], &[0, 1, 2, 3, 4], &[0, 1, 2, 3, 4, 5]);
+    doc.blocks[0].canonical_atom_site_order(ElementOrder::Alphabetical);
+    let labels: Vec<_> = doc.blocks[0].loops[0]
+        .get_column("_atom_site_label")
+        .unwrap()
+        .into_iter()
+        .filter_map(|v| v.as_string())
+        .collect();
+    assert_eq!(labels, ["C1", "C2", "C10", "Cu1", "H1A", "O1"]);
+}
+
+#[test]
+fn test_example_file_keeps_every_site() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/example_cifs/paracetamol/ccdc_paracetamol.cif"
+    );
+    let original = Document::from_file(path).unwrap();
+    let mut sorted = original.clone();
+    for block in &mut sorted.blocks {
+        block.canonical_atom_site_order(ElementOrder::HeavyFirst);
+    }
+    let options = WriteOptions::new().atom_site_column_order(true);
+    let reparsed = Document::parse(&sorted.to_string_with_options(&options)).unwrap();
+    for (before, after) in original.blocks.iter().zip(&reparsed.blocks) {
+        let labels = |block: &Block| {
+            let mut labels: Vec<String> = block
+                .find_loop("_atom_site_label")
+                .map(|l| l.get_column("_atom_site_label").unwrap())
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|v| v.as_string().map(str::to_string))
+                .collect();
+            labels.sort();
+            labels
+        };
+        assert_eq!(labels(before), labels(after));
+    }
+}
@@ -0,0 +1,348 @@
+// tests/category_tests.rs
+// Categories read as tables, and splitting loops that mix categories into
+// joinable single-category loops
+
+use cif_parser::category::{category_of, CategoryResolver};
+use cif_parser::{Block, CifLoop, Document, Value};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> Block {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+#[test]
+fn test_mixed_loop_is_split_into_joinable_loops() {
+    let mut block = fixture("mixed_loop.cif");
+    let original = block.loops[0].clone();
+    let splits = block.repair_mixed_loops();
+
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].tags, original.tags);
+    assert_eq!(splits[0].categories, ["_atom_site", "_atom_site_aniso"]);
+    assert_eq!(splits[0].key.as_deref(), Some("_atom_site_label"));
+
+    // The symmetry loop is untouched and stays after the split loops
+    assert_eq!(block.loops.len(), 3);
+    assert!(block.loops[2].tags[0].starts_with("_symmetry"));
+
+    // Each loop now holds a single category
+    for loop_ in &block.loops {
+        let categories: HashSet<String> = loop_.tags.iter().map(|t| category_of(t)).collect();
+        assert_eq!(categories.len(), 1, "{:?}", loop_.tags);
+    }
+
+    let sites = block.find_loop("_atom_site_label").unwrap();
+    let aniso = block.find_loop("_atom_site_aniso_label").unwrap();
+    assert_eq!(sites.tags.len(), 6);
+    assert_eq!(
+        aniso.tags,
+        [
+            "_atom_site_aniso_label",
+            "_atom_site_aniso_U_11",
+            "_atom_site_aniso_U_22",
+            "_atom_site_aniso_U_33"
+        ]
+    );
+
+    // Joining on the label gives back every original row
+    for (row, original_row) in original.rows().enumerate() {
+        let label = sites.get_by_tag(row, "_atom_site_label").unwrap();
+        let aniso_row = (0..aniso.len())
+            .find(|&r| aniso.get_by_tag(r, "_atom_site_aniso_label") == Some(label))
+            .unwrap();
+        for (column, tag) in original.tags.iter().enumerate() {
+            let value = sites
+                .get_by_tag(row, tag)
+                .or_else(|| aniso.get_by_tag(aniso_row, tag));
+            assert_eq!(value, Some(&original_row[column]), "{tag}");
+        }
+    }
+    assert_eq!(
+        aniso.get_by_tag(1, "_atom_site_aniso_U_11"),
+        Some(&Value::NumericSu {
+            value: 0.015,
+            su: 0.0007
+        })
+    );
+}
+
+#[test]
+fn test_existing_key_is_not_duplicated() {
+    let cif = "\
+data_x
+loop_
+_atom_site_label
+_atom_site_fract_x
+_atom_site_aniso_label
+_atom_site_aniso_U_11
+C1 0.1 C1 0.02
+";
+    let mut block = Document::parse(cif).unwrap().blocks.remove(0);
+    let splits = block.repair_mixed_loops();
+    assert_eq!(splits[0].key, None);
+    assert_eq!(
+        block.loops[1].tags,
+        ["_atom_site_aniso_label", "_atom_site_aniso_U_11"]
+    );
+}
+
+#[test]
+fn test_well_formed_blocks_are_unchanged() {
+    let mut block = fixture("simple.cif");
+    let before = block.clone();
+    assert!(block.repair_mixed_loops().is_empty());
+    assert_eq!(block.loops.len(), before.loops.len());
+    for (a, b) in block.loops.iter().zip(&before.loops) {
+        assert_eq!(a.tags, b.tags);
+    }
+}
+
+#[test]
+fn test_custom_category_function() {
+    let mut loop_ = fixture("mixed_loop.cif").loops.remove(0);
+    // Treat U_iso as part of the ADP category, keyed by position only
+    loop_.tags.retain(|t| t != "_atom_site_label");
+    for row in &mut loop_.values {
+        row.remove(0);
+    }
+    let parts = loop_.split_by_category(|tag| {
+        if tag.contains("_U_") {
+            "adp".to_string()
+        } else {
+            "site".to_string()
+        }
+    });
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].tags.len(), 4);
+    assert_eq!(parts[1].tags.len(), 4);
+    assert_eq!(parts[1].len(), 3);
+}
+
+#[test]
+fn test_common_prefix() {
+    let prefix = |tags: &[&str]| {
+        let loop_ = CifLoop::new(tags.iter().map(|t| t.to_string()).collect());
+        loop_.common_prefix().map(str::to_string)
+    };
+    assert_eq!(
+        prefix(&["_atom_site_label", "_atom_site_fract_x"]).as_deref(),
+        Some("_atom_site_")
+    );
+    assert_eq!(
+        prefix(&["_atom_site.id", "_Atom_Site.Cartn_x"]).as_deref(),
+        Some("_atom_site.")
+    );
+    // The longest known core category wins, so aniso loops strip the whole name
+    assert_eq!(
+        prefix(&["_atom_site_aniso_label", "_atom_site_aniso_U_11"]).as_deref(),
+        Some("_atom_site_aniso_")
+    );
+    // Mixed categories, mixed styles, and bare category names have none
+    assert_eq!(prefix(&["_atom_site_label", "_atom_site_aniso_U_11"]), None);
+    assert_eq!(prefix(&["_atom_site.id", "_atom_site_label"]), None);
+    assert_eq!(prefix(&["_symmetry_cell_setting", "_symmetry"]), None);
+    assert_eq!(prefix(&[]), None);
+}
+
+#[test]
+fn test_category_of_real_tags() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/category/tags.tsv");
+    let table = std::fs::read_to_string(path).unwrap();
+    let mut count = 0;
+    for line in table.lines().filter(|line| !line.starts_with('#')) {
+        let (tag, category) = line.split_once('\t').unwrap();
+        assert_eq!(category_of(tag), format!("_{category}"), "{tag}");
+        count += 1;
+    }
+    assert!(count > 300, "{count}");
+}
+
+#[test]
+fn test_resolver_with_registered_categories() {
+    let mut resolver = CategoryResolver::new();
+    assert_eq!(resolver.category_of("_pd_meas_intensity_total"), "_pd");
+    resolver.register("_PD_meas");
+    resolver.register("pd_meas");
+    assert_eq!(resolver.categories().filter(|c| *c == "pd_meas").count(), 1);
+    assert_eq!(resolver.category_of("_pd_meas_intensity_total"), "_pd_meas");
+    assert_eq!(resolver.category_of("_pd_proc_2theta_corrected"), "_pd");
+    // The free function keeps to the built-in table
+    assert_eq!(category_of("_pd_meas_intensity_total"), "_pd");
+
+    let mut block = Document::parse(
+        "data_x\nloop_\n_pd_meas_2theta_scan\n_pd_meas_intensity_total\n_pd_proc_intensity_net\n10 5 4\n",
+    )
+    .unwrap()
+    .blocks
+    .remove(0);
+    assert!(block.clone().repair_mixed_loops().is_empty());
+    resolver.register("pd_proc");
+    let splits = block.repair_mixed_loops_with(&resolver);
+    assert_eq!(splits[0].categories, ["_pd_meas", "_pd_proc"]);
+    assert_eq!(block.loops.len(), 2);
+}
+
+#[test]
+fn test_resolver_reads_dictionaries() {
+    let ddl1 = Document::parse(
+        "\
+data_pd_proc_[]
+_name '_pd_proc_[]'
+_category category_overview
+data_pd_proc_2theta_corrected
+_name '_pd_proc_2theta_corrected'
+_category pd_proc
+data_pd_calc_intensity_net
+_name '_pd_calc_intensity_net'
+_category pd_calc
+",
+    )
+    .unwrap();
+    let mut resolver = CategoryResolver::new();
+    assert_eq!(resolver.register_dictionary(&ddl1), 2);
+    assert_eq!(resolver.register_dictionary(&ddl1), 0);
+    assert_eq!(resolver.category_of("_pd_calc_intensity_net"), "_pd_calc");
+    assert!(!resolver.categories().any(|c| c == "category_overview"));
+
+    let ddlm = Document::parse(
+        "\
+#\\#CIF_2.0
+data_MS_DIC
+save_MS_SPECIAL
+_definition.id MS_SPECIAL
+_definition.scope Category
+save_
+save_atom_site_fourier_wave_vector
+_definition.id ATOM_SITE_FOURIER_WAVE_VECTOR
+_definition.scope Category
+save_
+save_atom_site_fourier_wave_vector.seq_id
+_definition.id '_atom_site_Fourier_wave_vector.seq_id'
+save_
+",
+    )
+    .unwrap();
+    assert_eq!(resolver.register_dictionary(&ddlm), 2);
+    assert_eq!(
+        resolver.category_of("_atom_site_Fourier_wave_vector_seq_id"),
+        "_atom_site_fourier_wave_vector"
+    );
+}
+
+#[test]
+fn test_irregular_tags_have_no_common_prefix() {
+    let loop_ = CifLoop::new(vec![
+        "_diffrn_measured_fraction_theta_full".to_string(),
+        "_diffrn_measured_fraction_theta_max".to_string(),
+    ]);
+    assert_eq!(loop_.common_prefix(), None);
+}
+
+#[test]
+fn test_mmcif_categories_as_tables() {
+    let block = fixture("mmcif_fragment.cif");
+
+    let atoms = block.category("atom_site").unwrap();
+    assert_eq!(atoms.name(), "_atom_site");
+    assert_eq!(atoms.len(), 10);
+    assert_eq!(atoms.attributes().nth(10), Some("Cartn_x"));
+    assert_eq!(atoms.get(7, "label_alt_id").unwrap().as_string(), Some("A"));
+    let x = atoms.column("cartn_x").unwrap();
+    assert_eq!(x.len(), 10);
+    assert_eq!(x[9].as_numeric(), Some(10.25));
+    // The anisotropic parameters are a category of their own
+    assert!(!atoms.has_attribute("U[1][1]"));
+    assert_eq!(block.category("_ATOM_SITE_ANISOTROP").unwrap().len(), 2);
+
+    // Data items make a table of one row
+    let cell = block.category("_cell").unwrap();
+    assert_eq!(cell.len(), 1);
+    assert_eq!(cell.get(0, "length_b").unwrap().as_numeric(), Some(18.65));
+    assert!(cell.get(1, "length_b").is_none());
+    assert_eq!(
+        cell.row(0).unwrap()[..2],
+        [
+            ("entry_id", &Value::Text("1CRN".into())),
+            ("length_a", &Value::Numeric(40.96))
+        ]
+    );
+
+    assert!(block.category("pdbx_struct_oper_list").is_none());
+    assert_eq!(
+        block.categories()[..5],
+        [
+            "_entry",
+            "_cell",
+            "_symmetry",
+            "_chemical_formula",
+            "_atom_sites"
+        ]
+    );
+}
+
+#[test]
+fn test_core_categories_as_tables() {
+    let cif = "data_x
+_atom_type_symbol C
+_atom_site_refinement_flags P
+loop_
+_atom_site_label
+_atom_site_fract_x
+_atom_site_aniso_U_11
+C1 0.1 0.02
+C2 0.2 0.03
+loop_
+_atom_site_U_iso_or_equiv
+0.01
+";
+    let doc = Document::parse(cif).unwrap();
+    let atoms = doc.blocks[0].category("atom_site").unwrap();
+
+    // The item is repeated on every row, and the short loop leaves a gap
+    assert_eq!(
+        atoms.attributes().collect::<Vec<_>>(),
+        ["refinement_flags", "label", "fract_x", "U_iso_or_equiv"]
+    );
+    assert_eq!(atoms.len(), 2);
+    assert_eq!(
+        atoms.get(1, "refinement_flags").unwrap().as_string(),
+        Some("P")
+    );
+    assert!(atoms.get(1, "u_iso_or_equiv").is_none());
+    assert!(atoms.column("U_iso_or_equiv").is_none());
+    assert_eq!(atoms.row(1).unwrap().len(), 3);
+
+    let table = atoms.to_loop();
+    assert_eq!(table.tags[0], "_atom_site_refinement_flags");
+    assert_eq!(table.values[1][0], Value::Text("P".into()));
+    assert_eq!(
+        table.get_by_tag(1, "_atom_site_U_iso_or_equiv"),
+        Some(&Value::Unknown)
+    );
+
+    assert_eq!(
+        doc.blocks[0]
+            .category("atom_site_aniso")
+            .unwrap()
+            .column("U_11")
+            .unwrap()[1],
+        &Value::Numeric(0.03)
+    );
+}
+
+#[test]
+fn test_category_with_registered_categories() {
+    let doc = Document::parse("data_x\nloop_\n_pd_proc_2theta\n_pd_meas_counts\n10 4\n").unwrap();
+    let block = &doc.blocks[0];
+    assert!(block.category("pd_proc").is_none());
+
+    let mut resolver = CategoryResolver::new();
+    resolver.register("pd_proc");
+    let proc_ = block.category_with("pd_proc", &resolver).unwrap();
+    assert_eq!(proc_.tags().collect::<Vec<_>>(), ["_pd_proc_2theta"]);
+    assert_eq!(proc_.attributes().collect::<Vec<_>>(), ["2theta"]);
+}
@@ -0,0 +1,92 @@
+// tests/cell_tests.rs
+// Unit cell su propagation checked against the values published in real CIFs
+
+use cif_parser::{Document, UnitCell};
+use std::path::Path;
+
+fn cell_and_volume(file: &str) -> (UnitCell, (f64, f64)) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/example_cifs")
+        .join(file);
+    let doc = Document::from_file(path).unwrap();
+    // The cell is in the first data block after the global one
+    let block = doc.blocks.iter().find(|b| b.unit_cell().is_some()).unwrap();
+    let (volume, su) = block
+        .get_item("_cell_volume")
+        .and_then(|v| v.as_numeric_with_su())
+        .unwrap();
+    (block.unit_cell().unwrap(), (volume, su.unwrap()))
+}
+
+#[test]
+fn test_volume_su_matches_published_values() {
+    for file in [
+        "albite/jana2006_albite.cif",
+        "abiraterone_acetate/abiraterone_acetate.cif",
+        "LuAg/jana2020_LuAG.cif",
+    ] {
+        let (cell, (published, published_su)) = cell_and_volume(file);
+        let (volume, su) = cell.volume_with_su();
+        assert!(
+            (volume - published).abs() <= published_su,
+            "{file}: volume {volume} != {published}({published_su})"
+        );
+        // Published sus are rounded to one or two digits
+        assert!(
+            (su / published_su - 1.0).abs() < 0.25,
+            "{file}: su {su} != {published_su}"
+        );
+    }
+}
+
+#[test]
+fn test_cubic_distance_su_scales_with_cell_length() {
+    let (cell, _) = cell_and_volume("LuAg/jana2020_LuAG.cif");
+    // Al2 to Al3: both on special positions, so only the cell contributes
+    let (d, su) = cell.distance_with_su([0.25, 0.625, 0.5], [0.5, 0.5, 0.5]);
+    let fraction = (0.25_f64.powi(2) + 0.125_f64.powi(2)).sqrt();
+    assert!((d - 11.9104 * fraction).abs() < 1e-9);
+    // ∂d/∂a = Δx²·a/d, and likewise for b and c with their own sus
+    let expected = 0.0004 * (0.25_f64.powi(4) + 0.125_f64.powi(4)).sqrt() / fraction;
+    assert!((su - expected).abs() < 1e-9, "{su} != {expected}");
+}
+
+#[test]
+fn test_fractional_cartesian_round_trip() {
+    let (cell, _) = cell_and_volume("albite/jana2006_albite.cif");
+    let frac = [0.2687, 0.0047, 0.2161];
+    let back = cell.cart_to_frac(cell.frac_to_cart(frac));
+    for (a, b) in frac.iter().zip(back) {
+        assert!((a - b).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_metric_tensor_and_matrices_agree() {
+    let (cell, _) = cell_and_volume("albite/jana2006_albite.cif");
+    let m = cell.orthogonalization_matrix();
+    let f = cell.fractionalization_matrix();
+    let g = cell.metric_tensor();
+    let g_star = cell.reciprocal_metric_tensor();
+    for i in 0..3 {
+        for j in 0..3 {
+            // G = MᵀM, and F = M⁻¹
+            let mtm: f64 = (0..3).map(|k| m[k][i] * m[k][j]).sum();
+            assert!((g[i][j] - mtm).abs() < 1e-9);
+            let mf: f64 = (0..3).map(|k| m[i][k] * f[k][j]).sum();
+            assert!((mf - f64::from(u8::from(i == j))).abs() < 1e-12);
+        }
+    }
+    // det G = V², and 1/d² = hᵀG*h
+    let det = g[0][0] * (g[1][1] * g[2][2] - g[1][2] * g[2][1])
+        - g[0][1] * (g[1][0] * g[2][2] - g[1][2] * g[2][0])
+        + g[0][2] * (g[1][0] * g[2][1] - g[1][1] * g[2][0]);
+    assert!((det.sqrt() - cell.volume()).abs() < 1e-9);
+    let h = [1.0, -2.0, 3.0];
+    let inverse_d2: f64 = (0..3)
+        .flat_map(|i| (0..3).map(move |j| (i, j)))
+        .map(|(i, j)| h[i] * g_star[i][j] * h[j])
+        .sum();
+    let d = cell.d_spacing([1, -2, 3]);
+    assert!((inverse_d2 - 1.0 / (d * d)).abs() < 1e-12);
+}
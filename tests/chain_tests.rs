@@ -0,0 +1,107 @@
+// tests/chain_tests.rs
+// Label and author residue numbering of PDBx atom sites
+
+use cif_parser::{AuthResidue, Block, Document, LabelResidue, Value};
+use std::path::PathBuf;
+
+fn antibody() -> Block {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/chains/antibody_fragment.cif");
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+fn label(asym: &str, seq: Option<i64>) -> LabelResidue {
+    LabelResidue {
+        asym: asym.to_string(),
+        seq,
+    }
+}
+
+fn auth(chain: &str, seq: i64, ins_code: Option<&str>) -> AuthResidue {
+    AuthResidue {
+        chain: chain.to_string(),
+        seq,
+        ins_code: ins_code.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_insertion_codes() {
+    let map = antibody().chain_mapping().unwrap();
+    assert_eq!(map.auth_to_label("H", 52, None), Some(&label("A", Some(3))));
+    assert_eq!(
+        map.auth_to_label("H", 52, Some("A")),
+        Some(&label("A", Some(4)))
+    );
+    assert_eq!(
+        map.auth_to_label("H", 52, Some("B")),
+        Some(&label("A", Some(5)))
+    );
+    assert_eq!(map.auth_to_label("H", 53, None), Some(&label("A", Some(6))));
+    assert_eq!(map.auth_to_label("H", 52, Some("C")), None);
+
+    assert_eq!(map.label_to_auth("A", Some(4)), [auth("H", 52, Some("A"))]);
+    assert_eq!(map.label_to_auth("A", Some(6)), [auth("H", 53, None)]);
+    // Label chains are not author chains
+    assert!(map.label_to_auth("H", Some(1)).is_empty());
+    assert_eq!(map.auth_to_label("A", 50, None), None);
+}
+
+#[test]
+fn test_heteroatoms_and_unobserved_residues() {
+    let map = antibody().chain_mapping().unwrap();
+    // One author chain holds the protein, a sugar, and waters
+    assert_eq!(map.auth_to_label("H", 301, None), Some(&label("C", None)));
+    assert_eq!(map.auth_to_label("H", 402, None), Some(&label("D", None)));
+    assert_eq!(
+        map.label_to_auth("D", None),
+        [auth("H", 401, None), auth("H", 402, None)]
+    );
+    assert_eq!(map.label_to_auth("E", None), [auth("L", 201, None)]);
+
+    // From _pdbx_poly_seq_scheme only: the residue has no atoms
+    assert_eq!(map.auth_to_label("L", 4, None), Some(&label("B", Some(4))));
+    // 6 + 3 residues of chain H and L with atoms, the sugar, 3 waters, and LYS 4
+    assert_eq!(map.len(), 14);
+}
+
+#[test]
+fn test_select_auth() {
+    let block = antibody();
+    let selected = block.select_auth("H", 52..=53).unwrap();
+    let ids: Vec<f64> = selected
+        .get_column("_atom_site.id")
+        .unwrap()
+        .into_iter()
+        .filter_map(Value::as_numeric)
+        .collect();
+    assert_eq!(ids, [5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0]);
+    assert_eq!(selected.tags, block.loops[0].tags);
+
+    let waters = block.select_auth("H", 400..=499).unwrap();
+    assert_eq!(waters.len(), 2);
+    assert!(block.select_auth("A", 1..=10).unwrap().is_empty());
+}
+
+#[test]
+fn test_label_only_files() {
+    let cif = "\
+data_x
+loop_
+_atom_site.id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+1 A 1
+2 A 2
+3 B 1
+";
+    let block = &Document::parse(cif).unwrap().blocks[0];
+    // Without author ids there is nothing to map, but residues can be selected
+    assert!(block.chain_mapping().is_none());
+    assert_eq!(block.select_auth("A", 2..=5).unwrap().len(), 1);
+    assert!(Document::parse("data_x\n_cell_length_a 5\n")
+        .unwrap()
+        .blocks[0]
+        .select_auth("A", 1..=1)
+        .is_none());
+}
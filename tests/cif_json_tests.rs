@@ -0,0 +1,173 @@
+// tests/cif_json_tests.rs
+// Documents written as CIF-JSON and read back
+
+use cif_parser::cif_json::SCHEMA_URI;
+use cif_parser::{CifDocument, CifError, CifFrame, CifValue, CifVersion, Document};
+use serde_json::{json, Value as Json};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> CifDocument {
+    Document::from_file(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn cif_json(doc: &CifDocument) -> Json {
+    serde_json::from_str(&doc.to_cif_json().unwrap()).unwrap()
+}
+
+#[test]
+fn test_layout() {
+    let doc = fixture("simple.cif");
+    let json = cif_json(&doc);
+    let top = &json["CIF-JSON"];
+    assert_eq!(
+        top["Metadata"],
+        json!({
+            "cif-version": "1.1",
+            "schema-name": "CIF-JSON",
+            "schema-version": "1.0.0",
+            "schema-uri": SCHEMA_URI,
+        })
+    );
+    let block = &top["simple_test"];
+    assert_eq!(block["_cell_length_a"], json!([10]));
+    assert_eq!(block["_space_group_name_h-m_alt"], json!(["P 21/c"]));
+    assert_eq!(block["_atom_site_label"], json!(["C1", "C2", "O1", "N1"]));
+    assert_eq!(block["_atom_site_fract_x"][0], json!(0.1));
+    // Data names keep the order they were written in
+    let tags: Vec<&String> = block.as_object().unwrap().keys().collect();
+    assert_eq!(tags[..2], ["_cell_length_a", "_cell_length_b"]);
+}
+
+#[test]
+fn test_special_values_and_cif2_values() {
+    let doc = Document::parse(
+        "#\\#CIF_2.0\ndata_X\n_a ?\n_b .\n_c '?'\n_d [1 ? .]\n_e {'k':'v' 'n':2}\n",
+    )
+    .unwrap();
+    let json = cif_json(&doc);
+    let block = &json["CIF-JSON"]["x"];
+    assert_eq!(json["CIF-JSON"]["Metadata"]["cif-version"], "2.0");
+    assert_eq!(block["_a"], json!(["\\?"]));
+    assert_eq!(block["_b"], json!([null]));
+    assert_eq!(block["_c"], json!(["?"]));
+    assert_eq!(block["_d"], json!([[1, "\\?", null]]));
+    assert_eq!(block["_e"], json!([{"k": "v", "n": 2}]));
+
+    let back = CifDocument::from_cif_json(&doc.to_cif_json().unwrap()).unwrap();
+    assert_eq!(back.version, CifVersion::V2_0);
+    for tag in ["_a", "_b", "_c", "_d", "_e"] {
+        assert_eq!(
+            back.blocks[0].get_item(tag),
+            doc.blocks[0].get_item(tag),
+            "{tag}"
+        );
+    }
+}
+
+#[test]
+fn test_round_trip_groups_loops_by_category() {
+    let doc = fixture("mmcif_fragment.cif");
+    let back = CifDocument::from_cif_json(&doc.to_cif_json().unwrap()).unwrap();
+    let (block, original) = (&back.blocks[0], &doc.blocks[0]);
+    // Names come back lowercased
+    let lowercase = |tags: &mut dyn Iterator<Item = &String>| -> Vec<String> {
+        tags.map(|tag| tag.to_lowercase()).collect()
+    };
+    assert_eq!(block.name, "1crn");
+    assert_eq!(
+        lowercase(&mut block.items.keys()),
+        lowercase(&mut original.items.keys())
+    );
+    assert!(block.items.values().eq(original.items.values()));
+    assert_eq!(block.loops.len(), original.loops.len());
+    for (loop_, other) in block.loops.iter().zip(&original.loops) {
+        assert_eq!(
+            lowercase(&mut loop_.tags.iter()),
+            lowercase(&mut other.tags.iter())
+        );
+        assert_eq!(loop_.values, other.values);
+    }
+}
+
+#[test]
+fn test_frames() {
+    let doc = fixture("dictionary/core_mini.dic");
+    let json = cif_json(&doc);
+    let frames = &json["CIF-JSON"]["core_mini"]["Frames"];
+    assert_eq!(frames["cell"]["_definition.scope"], json!(["Category"]));
+
+    let back = CifDocument::from_cif_json(&doc.to_cif_json().unwrap()).unwrap();
+    assert_eq!(back.blocks[0].frames.len(), doc.blocks[0].frames.len());
+    assert_eq!(
+        back.blocks[0].frames[0].items["_definition.scope"],
+        CifValue::Text("Category".into())
+    );
+}
+
+#[test]
+fn test_reading_other_writers() {
+    // Every value a string, as gemmi and PyCifRW write them
+    let json = r#"{"CIF-JSON": {"Metadata": {"cif-version": "1.1"}, "x": {
+        "_cell_length_a": ["5.431"], "_cell_length_a_su": ["0.002"],
+        "_atom_site_label": ["Si1", "O1"], "_atom_site_fract_x": ["0.5", "0.25(3)"],
+        "_atom_type_symbol": ["Si", "O"]}}}"#;
+    let doc = CifDocument::from_cif_json(json).unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(
+        block.get_item("_cell_length_a"),
+        Some(&CifValue::Numeric(5.431))
+    );
+    // Loop columns of the same length stay apart if their categories differ
+    assert_eq!(block.loops.len(), 2);
+    assert_eq!(
+        block.loops[0].tags,
+        ["_atom_site_label", "_atom_site_fract_x"]
+    );
+    assert_eq!(
+        block.loops[0].get(1, 1),
+        Some(&CifValue::NumericSu {
+            value: 0.25,
+            su: 0.03
+        })
+    );
+}
+
+#[test]
+fn test_invalid_input() {
+    for text in [
+        "{",
+        "[]",
+        r#"{"x": {}}"#,
+        r#"{"CIF-JSON": {"x": {"_a": 1}}}"#,
+    ] {
+        let error = CifDocument::from_cif_json(text).unwrap_err();
+        assert!(
+            matches!(error, CifError::InvalidStructure { .. }),
+            "{text}: {error}"
+        );
+    }
+}
+
+#[test]
+fn test_names_differing_only_in_case() {
+    let doc = Document::parse("data_a\nloop_ _x 1 2\ndata_A\n_y 3\n").unwrap();
+    let error = doc.to_cif_json().unwrap_err();
+    assert!(matches!(error, CifError::InvalidStructure { .. }));
+    assert!(error
+        .to_string()
+        .contains("data_A has the CIF-JSON key \"a\""));
+
+    let mut doc = Document::parse("data_d\nsave_s\n_x 1\nsave_\n").unwrap();
+    doc.blocks[0].frames.push(CifFrame::new("S".into()));
+    let error = doc.to_cif_json().unwrap_err();
+    assert!(error.to_string().contains("save_S in data_d"), "{error}");
+
+    // Names differing in more than case are kept apart
+    doc.blocks[0].frames[1].name = "t".into();
+    assert!(doc.to_cif_json().is_ok());
+}
@@ -0,0 +1,113 @@
+// tests/compression_tests.rs
+// Compressed files in from_file, behind the gzip, bzip2 and zstd features
+
+use cif_parser::compression::Compression;
+use cif_parser::{CifDocument, CifError, Document};
+use std::io::Write;
+use std::path::PathBuf;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mmcif_fragment.cif")
+}
+
+fn gzip(content: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn bzip2(content: &[u8]) -> Vec<u8> {
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zstd(content: &[u8]) -> Vec<u8> {
+    zstd::stream::encode_all(content, 0).unwrap()
+}
+
+/// Write `content` to a file of this name in the test directory
+fn write(name: &str, content: &[u8]) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_each_format_reads_as_the_plain_file() {
+    let plain = std::fs::read(fixture_path()).unwrap();
+    let expected = Document::from_file(fixture_path()).unwrap();
+    for (name, content) in [
+        ("fragment.cif.gz", gzip(&plain)),
+        ("fragment.cif.bz2", bzip2(&plain)),
+        ("fragment.cif.zst", zstd(&plain)),
+    ] {
+        let path = write(name, &content);
+        let doc = Document::from_file(&path).unwrap();
+        assert!(doc.semantically_equal(&expected), "{name}");
+
+        // The source is the file on disk
+        let source = doc.source.unwrap();
+        assert_eq!(source.path.as_deref(), Some(path.as_path()));
+        assert_eq!(source.byte_len, content.len());
+    }
+}
+
+#[test]
+fn test_format_is_found_from_content_or_extension() {
+    let cif = b"data_a\n_x 1\n";
+    // Compressed content under a plain name
+    let path = write("misnamed.cif", &zstd(cif));
+    assert_eq!(Document::from_file(path).unwrap().blocks[0].name, "a");
+
+    assert_eq!(
+        Compression::from_magic(&bzip2(cif)),
+        Some(Compression::Bzip2)
+    );
+    assert_eq!(Compression::from_magic(cif), None);
+    assert_eq!(
+        Compression::from_extension("X.CIF.GZ".as_ref()),
+        Some(Compression::Gzip)
+    );
+    assert_eq!(Compression::from_extension("x.cif".as_ref()), None);
+
+    // Readers are decompressed from their first bytes too
+    let doc = Document::from_reader(&bzip2(cif)[..]).unwrap();
+    assert_eq!(doc.blocks[0].name, "a");
+
+    // Concatenated gzip members read as one file
+    let mut members = gzip(b"data_a\n_x 1\n");
+    members.extend(gzip(b"data_b\n_y 2\n"));
+    let doc = Document::from_file(write("members.cif.gz", &members)).unwrap();
+    assert_eq!(doc.blocks.len(), 2);
+}
+
+#[test]
+fn test_corrupt_file_is_an_error_with_its_path() {
+    let mut content = gzip(b"data_a\n_x 1\n");
+    let middle = content.len() / 2;
+    content.truncate(middle);
+    let path = write("truncated.cif.gz", &content);
+    let error = Document::from_file(&path).unwrap_err();
+    assert!(
+        matches!(&error, CifError::InFile { error, .. } if matches!(**error, CifError::Io(_))),
+        "{error:?}"
+    );
+    assert_eq!(error.path(), Some(path.as_path()));
+}
+
+#[test]
+fn test_compressed_bcif_and_bare_frames_use_the_inner_name() {
+    let doc = Document::from_file(fixture_path()).unwrap();
+    let path = write("fragment.bcif.gz", &gzip(&doc.to_bcif().unwrap()));
+    let read = CifDocument::from_file(path).unwrap();
+    assert_eq!(
+        read.blocks[0].get_item("_cell.length_a"),
+        doc.blocks[0].get_item("_cell.length_a")
+    );
+
+    let path = write("core_frag.dic.gz", &gzip(b"save_a\n_x 1\nsave_\n"));
+    let options = cif_parser::ParseOptions::new().allow_bare_frames(true);
+    let doc = CifDocument::from_file_with_options(path, &options).unwrap();
+    assert_eq!(doc.blocks[0].name, "core_frag");
+}
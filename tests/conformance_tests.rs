@@ -0,0 +1,314 @@
+// tests/conformance_tests.rs
+// Strict CIF 1.1 conformance: one fixture per rule, each rejected with the
+// rule and the clause it breaks
+
+use cif_parser::{
+    Block, CifError, CifStyle, Conformance, ConformanceRule, Document, Loop, ParseOptions, Value,
+    WriteOptions,
+};
+use std::path::PathBuf;
+use std::process::Command;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/conformance")
+        .join(name)
+}
+
+fn strict() -> ParseOptions {
+    ParseOptions::new().conformance(Conformance::Strict11)
+}
+
+/// The rule, location, and message of the strict error for a fixture,
+/// which parses without the strict mode
+fn violation(name: &str) -> (ConformanceRule, Option<(usize, usize)>, String) {
+    let path = fixture_path(name);
+    assert!(Document::from_file(&path).is_ok(), "{name}");
+    let error = Document::from_file_with_options(&path, &strict()).unwrap_err();
+    assert_eq!(error.code(), "E0012");
+    let message = error.to_string();
+    match error {
+        CifError::InFile { error, .. } => match *error {
+            CifError::NonConformant { rule, location, .. } => (rule, location, message),
+            other => panic!("expected a conformance error, got {other:?}"),
+        },
+        other => panic!("expected a file error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_conforming_file() {
+    let doc = Document::from_file_with_options(fixture_path("conforming.cif"), &strict()).unwrap();
+    assert!(doc.check_conformance(Conformance::Strict11).is_empty());
+}
+
+#[test]
+fn test_line_length() {
+    let (rule, location, message) = violation("line_too_long.cif");
+    assert_eq!(rule, ConformanceRule::LineLength);
+    assert_eq!(location, Some((3, 2049)));
+    assert!(message.contains("Line has 2056 characters"), "{message}");
+    assert!(message.ends_with("(CIF 1.1 syntax, line length: at most 2048 characters)"));
+}
+
+#[test]
+fn test_tag_length() {
+    let (rule, location, message) = violation("tag_too_long.cif");
+    assert_eq!(rule, ConformanceRule::TagLength);
+    assert_eq!(location, Some((4, 1)));
+    assert!(message.contains("has 81 characters"), "{message}");
+    assert!(message.ends_with("(CIF 1.1 syntax, data names: at most 75 characters)"));
+}
+
+#[test]
+fn test_ascii() {
+    let (rule, location, message) = violation("non_ascii.cif");
+    assert_eq!(rule, ConformanceRule::Ascii);
+    assert_eq!(location, Some((3, 24)));
+    assert!(message.contains("U+00C5"), "{message}");
+    assert!(message
+        .ends_with("(CIF 1.1 syntax, character set: printable ASCII, tab, and newline only)"));
+}
+
+#[test]
+fn test_container_name() {
+    let (rule, location, message) = violation("block_name.cif");
+    assert_eq!(rule, ConformanceRule::ContainerName);
+    assert_eq!(location, Some((2, 1)));
+    assert!(message.contains("Data block has no name"), "{message}");
+    assert!(message.ends_with(
+        "(CIF 1.1 syntax, data block and save frame names: unique, 1 to 75 non-blank characters)"
+    ));
+}
+
+#[test]
+fn test_duplicate_tag() {
+    let clause = "(CIF 1.1 syntax, data names: unique within a data block or save frame, \
+                  ignoring case)";
+
+    // Rejected as soon as it is repeated, whatever the duplicate-tag policy
+    let (rule, location, message) = violation("duplicate_tag.cif");
+    assert_eq!(rule, ConformanceRule::DuplicateTag);
+    assert_eq!(location, Some((5, 1)));
+    assert!(message.ends_with(clause));
+
+    // Only the whole block shows a repeat in different case, in a loop
+    let (rule, location, message) = violation("duplicate_tag_case.cif");
+    assert_eq!(rule, ConformanceRule::DuplicateTag);
+    assert_eq!(location, None);
+    assert!(
+        message.contains("Data name '_CELL_LENGTH_A' appears twice in data_twice"),
+        "{message}"
+    );
+    assert!(message.ends_with(clause));
+}
+
+#[test]
+fn test_lossy_value() {
+    let (rule, location, message) = violation("lossy_value.cif");
+    assert_eq!(rule, ConformanceRule::LossyValue);
+    assert_eq!(location, None);
+    assert!(
+        message.contains("Value of '_refine_ls_matrix' is a list"),
+        "{message}"
+    );
+    assert!(message.ends_with(
+        "(CIF 1.1 syntax, values: no lists or tables, and no line of a text field \
+         may start with a semicolon)"
+    ));
+}
+
+#[test]
+fn test_reserved_word() {
+    let (rule, location, message) = violation("reserved_word.cif");
+    assert_eq!(rule, ConformanceRule::ReservedWord);
+    assert_eq!(location, Some((7, 1)));
+    assert!(message.contains("'$Cl1'"), "{message}");
+    assert!(message.ends_with(
+        "(CIF 1.1 syntax, reserved characters: an unquoted value may not start with '$')"
+    ));
+}
+
+#[test]
+fn test_check_built_document() {
+    let mut block = Block::new("built".to_string());
+    block.items.insert(
+        "_publ_section_comment".to_string(),
+        Value::Text("first line\n; starts a line".to_string()),
+    );
+    block
+        .items
+        .insert(format!("_{}", "b".repeat(75)), Value::Numeric(1.0));
+    let mut loop_ = Loop::new(vec!["_atom_site_label".to_string()]);
+    loop_
+        .push_row(vec![Value::Text("Fe€".to_string())])
+        .unwrap();
+    block.loops.push(loop_);
+    let mut doc = Document::new();
+    doc.blocks.push(block.clone());
+    doc.blocks.push(Block::new("BUILT".to_string()));
+
+    assert!(doc.check_conformance(Conformance::Unchecked).is_empty());
+    let rules: Vec<ConformanceRule> = doc
+        .check_conformance(Conformance::Strict11)
+        .into_iter()
+        .map(|error| match error {
+            CifError::NonConformant {
+                rule,
+                location: None,
+                ..
+            } => rule,
+            other => panic!("expected a conformance error, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            ConformanceRule::TagLength,
+            ConformanceRule::LossyValue,
+            ConformanceRule::Ascii,
+            ConformanceRule::ContainerName,
+        ]
+    );
+}
+
+#[test]
+fn test_written_lines_fit_the_line_length() {
+    let mut block = Block::new("long".to_string());
+    let tag = format!("_{}", "t".repeat(70));
+    block.items.insert(tag, Value::Text("v".repeat(2000)));
+    // Quoted, this is one character too long for a line
+    let quoted = format!("{} b", "a".repeat(2045));
+    block
+        .items
+        .insert("_quoted".to_string(), Value::Text(quoted.clone()));
+    let tags: Vec<String> = (0..40).map(|i| format!("_wide.col_{i}")).collect();
+    let mut loop_ = Loop::new(tags);
+    for row in 0..3 {
+        let mut values: Vec<Value> = (0..39)
+            .map(|i| Value::Text(format!("{row}{i}{}", "x".repeat(100))))
+            .collect();
+        values.push(Value::Text(quoted.clone()));
+        loop_.push_row(values).unwrap();
+    }
+    block.loops.push(loop_);
+    let mut doc = Document::new();
+    doc.blocks.push(block);
+
+    for options in [
+        WriteOptions::new(),
+        WriteOptions::new().style(CifStyle::Mmcif),
+    ] {
+        let text = doc.to_string_with_options(&options);
+        for (number, line) in text.lines().enumerate() {
+            let length = line.chars().count();
+            assert!(
+                length <= 2048,
+                "line {} has {length} characters",
+                number + 1
+            );
+        }
+        assert!(text.contains(&format!("\n;{quoted}\n;")));
+        let parsed = Document::parse_with_options(&text, &strict()).unwrap();
+        assert!(parsed.semantically_equal(&doc));
+    }
+}
+
+#[test]
+fn test_lines_too_long_to_break_are_errors() {
+    let written = |value: Value| {
+        let mut block = Block::new("long".to_string());
+        block.items.insert("_value".to_string(), value);
+        let mut doc = Document::new();
+        doc.blocks.push(block);
+        doc
+    };
+
+    // No whitespace to break at, so a text field would hold one long line
+    let error = written(Value::Text("v".repeat(3000)))
+        .check_integrity()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[E0008] Invalid CIF structure: _value in data_long has a line of 3001 characters, \
+         longer than the 2048 CIF allows"
+    );
+    let field = format!("short\n{}\nshort", "w".repeat(2100));
+    let error = written(Value::Text(field)).check_integrity().unwrap_err();
+    assert!(
+        error.to_string().contains("a line of 2100 characters"),
+        "{error}"
+    );
+    let path = std::env::temp_dir().join(format!("long_line_{}.cif", std::process::id()));
+    assert!(written(Value::Text("v".repeat(3000)))
+        .to_file(&path)
+        .is_err());
+    assert!(!path.exists());
+
+    // A long list goes one member to a line instead
+    let numbers = (0..1000).map(|i| Value::Numeric(i as f64)).collect();
+    let mut doc = written(Value::List(numbers));
+    doc.version = cif_parser::CifVersion::V2_0;
+    assert!(doc.check_integrity().is_ok());
+    let text = doc.to_string();
+    assert!(text.lines().all(|line| line.chars().count() <= 2048));
+    assert!(Document::parse(&text).unwrap().semantically_equal(&doc));
+}
+
+#[test]
+fn test_members_no_quoting_can_hold_are_errors() {
+    let written = |value: Value| {
+        let mut block = Block::new("x".to_string());
+        block.items.insert("_value".to_string(), value);
+        let mut doc = Document::new();
+        doc.version = cif_parser::CifVersion::V2_0;
+        doc.blocks.push(block);
+        doc
+    };
+
+    let stuck = "a '''\nb \"\"\"".to_string();
+    let list = Value::List(vec![Value::Text("ok".into()), Value::Text(stuck.clone())]);
+    let error = written(list).check_integrity().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("_value in data_x has a list or table member that no CIF quoting can hold"),
+        "{error}"
+    );
+    let nested = Value::List(vec![Value::Table(
+        [("k".to_string(), Value::Text(stuck.clone()))].into(),
+    )]);
+    assert!(written(nested).check_integrity().is_err());
+    let key = Value::Table([(stuck, Value::Numeric(1.0))].into());
+    let error = written(key).check_integrity().unwrap_err();
+    assert!(error.to_string().contains("has a table key"), "{error}");
+
+    // Either triple quote alone can hold the newline
+    let held = Value::List(vec![Value::Text("a '''\nb".into())]);
+    let doc = written(held);
+    assert!(doc.check_integrity().is_ok());
+    assert!(Document::parse(&doc.to_string())
+        .unwrap()
+        .semantically_equal(&doc));
+}
+
+#[test]
+fn test_cli_strict_flag() {
+    let cif = env!("CARGO_BIN_EXE_cif");
+    let file = fixture_path("reserved_word.cif");
+
+    let output = Command::new(cif).arg("check").arg(&file).output().unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(cif)
+        .args(["check", "--strict-1.1"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[E0012] Error at line 7, column 1"),
+        "{stderr}"
+    );
+}
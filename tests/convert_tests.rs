@@ -0,0 +1,220 @@
+// tests/convert_tests.rs
+// Translation between mmCIF and core CIF structure descriptions
+
+use cif_parser::convert::{core_to_mmcif, mmcif_to_core};
+use cif_parser::{Block, Document, Loop, Value};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> Block {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+fn numbers(loop_: &Loop, tag: &str) -> Vec<f64> {
+    loop_
+        .get_column(tag)
+        .unwrap()
+        .into_iter()
+        .map(|v| v.as_numeric().unwrap())
+        .collect()
+}
+
+fn texts(loop_: &Loop, tag: &str) -> Vec<String> {
+    loop_
+        .get_column(tag)
+        .unwrap()
+        .into_iter()
+        .map(|v| match v {
+            Value::Text(s) => s.clone(),
+            Value::Numeric(n) => n.to_string(),
+            other => format!("{other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_mmcif_to_core_reports_untranslatable_tags() {
+    let block = fixture("mmcif_fragment.cif");
+    let report = mmcif_to_core(&block).unwrap_err();
+
+    let tags: Vec<&str> = report.issues.iter().map(|i| i.tag.as_str()).collect();
+    assert_eq!(tags, ["_atom_site.label_entity_id"]);
+    assert!(report.to_string().contains("no core CIF equivalent"));
+}
+
+#[test]
+fn test_mmcif_to_core_translates_structure() {
+    let block = fixture("mmcif_fragment.cif");
+    let core = *mmcif_to_core(&block).unwrap_err().block;
+
+    assert_eq!(
+        core.get_item("_cell_length_a"),
+        Some(&Value::Numeric(40.96))
+    );
+    assert_eq!(
+        core.get_item("_cell_angle_beta"),
+        Some(&Value::NumericSu {
+            value: 90.77,
+            su: 0.02
+        })
+    );
+    assert_eq!(
+        core.get_item("_cell_formula_units_Z"),
+        Some(&Value::Numeric(2.0))
+    );
+    assert_eq!(
+        core.get_item("_symmetry_space_group_name_H-M"),
+        Some(&Value::Text("P 1 21 1".to_string()))
+    );
+    assert!(core.get_item("_chemical_formula_sum").is_some());
+
+    let atoms = core.find_loop("_atom_site_label").unwrap();
+    assert_eq!(atoms.len(), 10);
+    let labels = texts(atoms, "_atom_site_label");
+    assert_eq!(labels[0], "N_THR1_A");
+    assert_eq!(labels[7], "OG1_THR2_A_A");
+    assert_eq!(labels[8], "OG1_THR2_A_B");
+    // Author ids win over label ids for the water
+    assert_eq!(labels[9], "O_HOH101_A");
+
+    // x = a·fx + c·cos(β)·fz, y = b·fy, z = c·sin(β)·fz
+    let fx = numbers(atoms, "_atom_site_fract_x")[0];
+    let fy = numbers(atoms, "_atom_site_fract_y")[0];
+    let fz = numbers(atoms, "_atom_site_fract_z")[0];
+    let beta = 90.77_f64.to_radians();
+    assert!((40.96 * fx + 22.52 * beta.cos() * fz - 17.047).abs() < 1e-4);
+    assert!((18.65 * fy - 14.099).abs() < 1e-4);
+    assert!((22.52 * beta.sin() * fz - 3.625).abs() < 1e-4);
+
+    let aniso = core.find_loop("_atom_site_aniso_label").unwrap();
+    assert_eq!(
+        texts(aniso, "_atom_site_aniso_label"),
+        ["N_THR1_A", "CA_THR1_A"]
+    );
+    assert_eq!(aniso.tags.len(), 8);
+}
+
+#[test]
+fn test_protein_fragment_round_trip() {
+    let block = fixture("mmcif_fragment.cif");
+    let core = *mmcif_to_core(&block).unwrap_err().block;
+    let mmcif = core_to_mmcif(&core).unwrap();
+
+    let original = block.find_loop("_atom_site.id").unwrap();
+    let restored = mmcif.find_loop("_atom_site.id").unwrap();
+    assert_eq!(restored.len(), original.len());
+    for axis in ["x", "y", "z"] {
+        let tag = format!("_atom_site.Cartn_{axis}");
+        for (a, b) in numbers(original, &tag).iter().zip(numbers(restored, &tag)) {
+            assert!((a - b).abs() < 1e-4, "{tag}: {a} != {b}");
+        }
+    }
+    for tag in [
+        "_atom_site.group_PDB",
+        "_atom_site.auth_atom_id",
+        "_atom_site.auth_comp_id",
+        "_atom_site.auth_seq_id",
+        "_atom_site.auth_asym_id",
+        "_atom_site.label_alt_id",
+        "_atom_site.occupancy",
+        "_atom_site.B_iso_or_equiv",
+    ] {
+        assert_eq!(texts(original, tag), texts(restored, tag), "{tag}");
+    }
+
+    let original = block.find_loop("_atom_site_anisotrop.id").unwrap();
+    let restored = mmcif.find_loop("_atom_site_anisotrop.id").unwrap();
+    for tag in original.tags.iter().filter(|t| t.contains("U[")) {
+        for (a, b) in numbers(original, tag).iter().zip(numbers(restored, tag)) {
+            assert!((a - b).abs() < 1e-5, "{tag}: {a} != {b}");
+        }
+    }
+
+    assert_eq!(
+        mmcif.get_item("_cell.angle_beta_esd"),
+        Some(&Value::Numeric(0.02))
+    );
+    assert_eq!(
+        mmcif.get_item("_entry.id"),
+        Some(&Value::Text("1CRN".into()))
+    );
+}
+
+#[test]
+fn test_core_structure_to_mmcif() {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs/quartz/ccdc_quartz.cif");
+    let block = Document::from_file(path).unwrap().blocks.remove(0);
+    let report = core_to_mmcif(&block).unwrap_err();
+    let mmcif = &report.block;
+
+    let atoms = mmcif.find_loop("_atom_site.Cartn_x").unwrap();
+    let source = block.find_loop("_atom_site_label").unwrap();
+    assert_eq!(atoms.len(), source.len());
+    assert_eq!(
+        texts(atoms, "_atom_site.label_atom_id"),
+        texts(source, "_atom_site_label")
+    );
+    assert!(texts(atoms, "_atom_site.group_PDB")
+        .iter()
+        .all(|g| g == "HETATM"));
+
+    // And back: Cartesian coordinates are written to 1e-5 Å
+    let core = mmcif_to_core(mmcif).unwrap();
+    let restored = core.find_loop("_atom_site_label").unwrap();
+    for axis in ["x", "y", "z"] {
+        let tag = format!("_atom_site_fract_{axis}");
+        let expected = source.get_column(&tag).unwrap();
+        for (value, restored) in expected.iter().zip(numbers(restored, &tag)) {
+            let expected = value.as_numeric().unwrap();
+            assert!((expected - restored).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn test_coordinates_without_cell_are_reported() {
+    let cif = "\
+data_x
+loop_
+_atom_site.id
+_atom_site.label_atom_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+1 N 1.0 2.0 3.0
+";
+    let block = Document::parse(cif).unwrap().blocks.remove(0);
+    let report = mmcif_to_core(&block).unwrap_err();
+    let tags: Vec<&str> = report.issues.iter().map(|i| i.tag.as_str()).collect();
+    assert_eq!(
+        tags,
+        [
+            "_atom_site.Cartn_x",
+            "_atom_site.Cartn_y",
+            "_atom_site.Cartn_z"
+        ]
+    );
+    assert!(report.issues[0].reason.contains("unit cell"));
+    assert!(report.block.find_loop("_atom_site_label").is_some());
+}
+
+#[test]
+fn test_only_first_model_is_translated() {
+    let cif = "\
+data_nmr
+loop_
+_atom_site.id
+_atom_site.label_atom_id
+_atom_site.pdbx_PDB_model_num
+1 N 1
+2 N 2
+";
+    let block = Document::parse(cif).unwrap().blocks.remove(0);
+    let report = mmcif_to_core(&block).unwrap_err();
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.issues[0].tag, "_atom_site.pdbx_PDB_model_num");
+    assert_eq!(report.block.loops[0].len(), 1);
+}
@@ -0,0 +1,101 @@
+// tests/corpus_tests.rs
+// Tag censuses over the example files, built whole and in merged parts
+
+use cif_parser::corpus::{census_files, cif_files, TagCensus};
+use cif_parser::Document;
+use std::path::{Path, PathBuf};
+
+/// The example files small enough to parse quickly in debug builds
+fn example_files() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    let files = cif_files(dir).unwrap();
+    files
+        .into_iter()
+        .filter(|path| path.metadata().unwrap().len() < 50_000)
+        .collect()
+}
+
+fn example_documents() -> Vec<Document> {
+    example_files()
+        .iter()
+        .filter_map(|path| Document::from_file(path).ok())
+        .collect()
+}
+
+fn census_of(docs: &[Document], cap: usize) -> TagCensus {
+    let mut census = TagCensus::new().distinct_cap(cap);
+    for doc in docs {
+        census.add(doc);
+    }
+    census
+}
+
+#[test]
+fn test_merged_halves_equal_whole() {
+    let docs = example_documents();
+    assert!(docs.len() > 4);
+    // A small cap makes some tags overflow in one half but not the other
+    for cap in [5, 1000] {
+        let whole = census_of(&docs, cap);
+        let (first, second) = docs.split_at(docs.len() / 2);
+        let mut merged = census_of(first, cap);
+        merged.merge(census_of(second, cap));
+        assert_eq!(merged, whole, "cap {cap}");
+
+        // Merging is order-independent
+        let mut reversed = census_of(second, cap);
+        reversed.merge(census_of(first, cap));
+        assert_eq!(reversed, whole, "cap {cap}");
+    }
+}
+
+#[test]
+fn test_parallel_census_matches_serial() {
+    let files = example_files();
+    let (serial, serial_errors) = census_files(&files, 1);
+    let (parallel, parallel_errors) = census_files(&files, 4);
+    assert_eq!(parallel, serial);
+    assert_eq!(parallel_errors.len(), serial_errors.len());
+    assert_eq!(serial.documents as usize + serial_errors.len(), files.len());
+}
+
+#[test]
+fn test_census_counts() {
+    let docs = example_documents();
+    let census = census_of(&docs, 1000);
+    assert_eq!(census.documents as usize, docs.len());
+
+    // Every example file has a cell, as an item (text when it has an su)
+    let cell = &census.tags["_cell_length_a"];
+    assert_eq!(cell.documents, census.documents);
+    assert_eq!((cell.as_item, cell.in_loop), (cell.values, 0));
+    assert_eq!(cell.numeric + cell.text, cell.values);
+
+    // Atom labels are looped text with many distinct values
+    let labels = &census.tags["_atom_site_label"];
+    assert_eq!(labels.as_item, 0);
+    assert_eq!(labels.numeric_ratio(), Some(0.0));
+    assert!(labels.distinct_count().unwrap() > 20);
+    assert_eq!(labels.examples.len(), 3);
+    assert!(labels.values >= labels.distinct_count().unwrap() as u64);
+}
+
+#[test]
+fn test_reports() {
+    let census = census_of(&example_documents(), 1000);
+    let csv = census.to_csv();
+    assert!(csv.starts_with("tag,documents,"));
+    let cell = &census.tags["_cell_length_a"];
+    let row = format!(
+        "\n_cell_length_a,{},{},0,{},",
+        cell.documents, cell.as_item, cell.values
+    );
+    assert!(csv.contains(&row), "{csv}");
+
+    let json: serde_json::Value = serde_json::from_str(&census.to_json()).unwrap();
+    assert_eq!(json["documents"], census.documents);
+    assert_eq!(
+        json["tags"]["_cell_length_a"]["documents"],
+        census.tags["_cell_length_a"].documents
+    );
+}
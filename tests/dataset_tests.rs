@@ -0,0 +1,102 @@
+// tests/dataset_tests.rs
+// Loops and blocks split into datasets by an id column
+
+use cif_parser::{Block, CifError, Document, Loop, Value};
+use std::path::PathBuf;
+
+fn two_scans() -> Block {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/datasets/two_scans.cif");
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+fn numbers(loop_: &Loop, tag: &str) -> Vec<f64> {
+    loop_
+        .get_column(tag)
+        .unwrap()
+        .into_iter()
+        .filter_map(Value::as_numeric)
+        .collect()
+}
+
+#[test]
+fn test_partition_keeps_row_order() {
+    let block = two_scans();
+    let scans = block.loops[1].partition_by("_diffrn_id", true).unwrap();
+    assert_eq!(scans.keys().collect::<Vec<_>>(), ["scan1", "scan2"]);
+    assert_eq!(
+        numbers(&scans["scan1"], "_pd_meas_counts_total"),
+        [102.0, 98.0, 131.0]
+    );
+    assert_eq!(
+        numbers(&scans["scan2"], "_pd_meas_2theta_scan"),
+        [20.0, 20.02, 20.04]
+    );
+    assert_eq!(scans["scan2"].tags, block.loops[1].tags);
+
+    let without = block.loops[1].partition_by("_diffrn_id", false).unwrap();
+    assert_eq!(
+        without["scan1"].tags,
+        ["_pd_meas_2theta_scan", "_pd_meas_counts_total"]
+    );
+    assert_eq!(without["scan1"].values[2].len(), 2);
+}
+
+#[test]
+fn test_partition_by_missing_tag() {
+    let error = two_scans().loops[3]
+        .partition_by("_diffrn_id", true)
+        .unwrap_err();
+    assert!(matches!(&error, CifError::MissingTag { tag } if tag == "_diffrn_id"));
+    assert_eq!(error.code(), "E0011");
+
+    let empty = Loop::new(vec!["_scan".to_string()]);
+    assert!(empty.partition_by("_scan", true).unwrap().is_empty());
+}
+
+#[test]
+fn test_numeric_and_unknown_ids() {
+    let doc = Document::parse("data_x\nloop_\n_scan\n_t\n1 295\n? 300\n1.0 310\n. 320\n").unwrap();
+    let parts = doc.blocks[0].loops[0].partition_by("_scan", false).unwrap();
+    assert_eq!(parts.keys().collect::<Vec<_>>(), ["1", "?", "."]);
+    assert_eq!(numbers(&parts["1"], "_t"), [295.0, 310.0]);
+}
+
+#[test]
+fn test_datasets_of_a_block() {
+    let block = two_scans();
+    let datasets = block.datasets("_diffrn_id");
+    assert_eq!(datasets.keys().collect::<Vec<_>>(), ["scan1", "scan2"]);
+
+    let scan1 = &datasets["scan1"];
+    assert_eq!(scan1.name, "lab6_heating");
+    assert_eq!(scan1.items, block.items);
+    let loop_sizes: Vec<(&str, usize)> = scan1
+        .loops
+        .iter()
+        .map(|l| (l.tags[l.tags.len() - 1].as_str(), l.len()))
+        .collect();
+    assert_eq!(
+        loop_sizes,
+        [
+            ("_diffrn_ambient_temperature", 1),
+            ("_pd_meas_counts_total", 3),
+            ("_pd_peak_2theta_centroid", 2),
+            ("_atom_type_symbol", 2),
+        ]
+    );
+
+    // The peak list is only for the first scan
+    let scan2 = &datasets["scan2"];
+    assert!(scan2.find_loop("_pd_peak_2theta_centroid").is_none());
+    assert_eq!(
+        numbers(
+            scan2.find_loop("_diffrn_ambient_temperature").unwrap(),
+            "_diffrn_ambient_temperature"
+        ),
+        [450.0]
+    );
+    assert_eq!(scan2.find_loop("_atom_type_symbol").unwrap().len(), 2);
+
+    assert!(block.datasets("_pd_block_id").is_empty());
+}
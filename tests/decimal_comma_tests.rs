@@ -0,0 +1,162 @@
+// tests/decimal_comma_tests.rs
+// Numbers written with a decimal comma: text in strict mode, recovered leniently
+
+use cif_parser::{CifValue, Document, ParseOptions};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/decimal_comma")
+        .join(name)
+}
+
+fn lenient() -> ParseOptions {
+    ParseOptions::new().lenient(true)
+}
+
+fn text(value: &str) -> CifValue {
+    CifValue::Text(value.to_string())
+}
+
+#[test]
+fn test_cell_stays_text_in_strict_mode() {
+    let doc = Document::from_file(fixture("cell.cif")).unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(block.get_item("_cell_length_a"), Some(&text("5,6402(3)")));
+    assert_eq!(
+        block.get_item("_diffrn_radiation_wavelength"),
+        Some(&text("1,5406"))
+    );
+    assert!(doc.warnings.is_empty());
+    assert!(doc.decimal_commas.is_empty());
+}
+
+#[test]
+fn test_cell_is_recovered_leniently() {
+    let doc = Document::from_file_with_options(fixture("cell.cif"), &lenient()).unwrap();
+    let block = &doc.blocks[0];
+
+    // Tokens with an su read as the period form would, as a number with an su
+    let a = block.get_item("_cell_length_a").unwrap();
+    assert_eq!(
+        a,
+        &CifValue::NumericSu {
+            value: 5.6402,
+            su: 0.0003
+        }
+    );
+    assert_eq!(
+        block.get_item("_cell_angle_beta"),
+        Some(&CifValue::Numeric(90.0))
+    );
+    assert_eq!(
+        block.get_item("_diffrn_radiation_wavelength"),
+        Some(&CifValue::Numeric(1.5406))
+    );
+    assert_eq!(
+        block.get_item("_audit_creation_method"),
+        Some(&text("WinGX 2014,1 (de_DE)"))
+    );
+
+    assert_eq!(doc.warnings.len(), 6);
+    assert_eq!(doc.warnings[0].location, Some((4, 16)));
+    assert!(doc.warnings[0].message.contains("'5,6402(3)'"));
+
+    let record = &doc.decimal_commas[5];
+    assert_eq!(record.block, "nacl_de");
+    assert_eq!(record.frame, None);
+    assert_eq!(record.tag, "_diffrn_radiation_wavelength");
+    assert_eq!(record.row, None);
+    assert_eq!(record.original, "1,5406");
+}
+
+#[test]
+fn test_coordinate_loop_is_recovered_leniently() {
+    let doc = Document::from_file_with_options(fixture("coordinates.cif"), &lenient()).unwrap();
+    let atoms = &doc.blocks[0].loops[0];
+
+    let column = |tag: &str| -> Vec<Option<f64>> {
+        atoms
+            .get_column(tag)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.as_numeric_with_su().map(|(n, _)| n))
+            .collect()
+    };
+    assert_eq!(
+        column("_atom_site_fract_x"),
+        [Some(0.0), Some(0.5), Some(0.2512)]
+    );
+    assert_eq!(
+        column("_atom_site_fract_y"),
+        [Some(0.0), Some(0.5), Some(-0.1034)]
+    );
+    assert_eq!(
+        column("_atom_site_occupancy"),
+        [Some(1.0), Some(1.0), Some(0.5)]
+    );
+
+    // Labels are not numbers, whatever they look like
+    assert_eq!(atoms.get_by_tag(2, "_atom_site_label"), Some(&text("O1,a")));
+
+    assert_eq!(doc.warnings.len(), 9);
+    let rows: Vec<_> = doc.decimal_commas.iter().map(|r| r.row).collect();
+    assert_eq!(rows, [0, 1, 1, 1, 2, 2, 2, 2, 2].map(Some));
+}
+
+#[test]
+fn test_strict_and_lenient_agree_on_period_input() {
+    let cif = "data_x\n_cell_length_a 5.6402(3)\n_diffrn_radiation_wavelength 1.5406\n";
+    let strict = Document::parse(cif).unwrap();
+    let lenient = Document::parse_with_options(cif, &lenient()).unwrap();
+    assert!(strict.semantically_equal(&lenient));
+    assert!(lenient.decimal_commas.is_empty());
+}
+
+#[test]
+fn test_save_frames_are_recorded() {
+    let cif = "data_x\nsave_f\n_cell_length_a 5,5\nsave_\n";
+    let doc = Document::parse_with_options(cif, &lenient()).unwrap();
+    assert_eq!(
+        doc.blocks[0].frames[0].items["_cell_length_a"],
+        CifValue::Numeric(5.5)
+    );
+    assert_eq!(doc.decimal_commas[0].block, "x");
+    assert_eq!(doc.decimal_commas[0].frame.as_deref(), Some("f"));
+}
+
+#[test]
+fn test_restore_gives_the_strict_document() {
+    for name in ["cell.cif", "coordinates.cif"] {
+        let strict = Document::from_file(fixture(name)).unwrap();
+        let mut doc = Document::from_file_with_options(fixture(name), &lenient()).unwrap();
+        doc.restore_decimal_commas();
+        assert!(doc.decimal_commas.is_empty());
+        assert!(doc.semantically_equal(&strict), "{name}");
+        assert_eq!(doc.to_string(), strict.to_string());
+    }
+}
+
+#[test]
+fn test_restore_leaves_edited_values_alone() {
+    let cif = "data_x\n_cell_length_a 5,5\n_cell_length_b 6,5\n";
+    let mut doc = Document::parse_with_options(cif, &lenient()).unwrap();
+    doc.blocks[0]
+        .items
+        .insert("_cell_length_a".to_string(), CifValue::Numeric(5.6));
+    doc.restore_decimal_commas();
+    assert_eq!(
+        doc.blocks[0].items["_cell_length_a"],
+        CifValue::Numeric(5.6)
+    );
+    assert_eq!(doc.blocks[0].items["_cell_length_b"], text("6,5"));
+}
+
+#[test]
+fn test_written_numbers_use_a_period() {
+    let doc = Document::from_file_with_options(fixture("cell.cif"), &lenient()).unwrap();
+    let written = doc.to_string();
+    assert!(written.contains("_diffrn_radiation_wavelength 1.5406\n"));
+    assert!(written.contains("_cell_length_a 5.6402(3)\n"));
+    assert!(written.contains("_cell_angle_beta 90\n"));
+}
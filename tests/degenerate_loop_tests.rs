@@ -0,0 +1,169 @@
+// tests/degenerate_loop_tests.rs
+// Loops with no rows, no tags, or only unknown values
+
+use cif_parser::category::category_of;
+use cif_parser::{CifError, Document, Loop, ParseOptions, Selection, SharedBlock, Value};
+use std::sync::Arc;
+
+const TEMPLATE: &str = "\
+data_template
+_cell_length_a ?
+loop_
+_atom_site_label
+_atom_site_fract_x
+loop_
+_atom_type_symbol
+save_frame
+loop_
+_geom_bond_distance
+save_
+";
+
+fn empty_loop() -> Loop {
+    let doc = Document::parse(TEMPLATE).unwrap();
+    doc.blocks[0].find_loop("_atom_site_label").unwrap().clone()
+}
+
+#[test]
+fn test_zero_row_loops_are_parsed() {
+    let doc = Document::parse(TEMPLATE).unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(block.loops.len(), 2);
+    assert_eq!(block.frames[0].loops.len(), 1);
+    assert!(block.loops.iter().all(|l| l.is_empty()));
+
+    // A zero-row loop ending the input without a newline
+    let doc = Document::parse("data_x\nloop_\n_a\n_b").unwrap();
+    assert_eq!(doc.blocks[0].loops[0].tags, ["_a", "_b"]);
+}
+
+#[test]
+fn test_zero_row_accessors() {
+    let loop_ = empty_loop();
+    assert_eq!(loop_.len(), 0);
+    assert!(loop_.is_empty());
+    assert_eq!(loop_.tags.len(), 2);
+    assert_eq!(loop_.get(0, 0), None);
+    assert_eq!(loop_.get_by_tag(0, "_atom_site_label"), None);
+    assert_eq!(loop_.get_column("_atom_site_label"), Some(vec![]));
+    assert_eq!(loop_.get_column("_missing"), None);
+    assert_eq!(loop_.rows().count(), 0);
+    assert!(loop_.semantically_equal(&empty_loop()));
+
+    let doc = Arc::new(Document::parse(TEMPLATE).unwrap());
+    let block = &doc.blocks[0];
+    assert!(block.find_loop("_atom_type_symbol").is_some());
+    assert_eq!(block.get_loop_tags().len(), 3);
+
+    let shared = SharedBlock::new(doc.clone(), 0).unwrap();
+    let shared_loop = shared.find_loop("_atom_site_fract_x").unwrap();
+    assert!(shared_loop.is_empty());
+}
+
+#[test]
+fn test_new_loop_and_push_row() {
+    let mut loop_ = Loop::new(vec!["_a".to_string(), "_b".to_string()]);
+    assert!(loop_.is_empty());
+    assert_eq!(loop_.get_column("_b"), Some(vec![]));
+
+    loop_
+        .push_row(vec![Value::Numeric(1.0), Value::Unknown])
+        .unwrap();
+    assert_eq!(loop_.len(), 1);
+    assert_eq!(loop_.get_by_tag(0, "_b"), Some(&Value::Unknown));
+
+    let err = loop_.push_row(vec![Value::Numeric(2.0)]).unwrap_err();
+    assert!(matches!(
+        err,
+        CifError::RowLength {
+            expected: 2,
+            found: 1
+        }
+    ));
+    assert_eq!(loop_.len(), 1);
+
+    let mut tagless = Loop::default();
+    assert!(tagless.push_row(vec![]).is_err());
+}
+
+#[test]
+fn test_writer_keeps_zero_row_headers() {
+    let doc = Document::parse(TEMPLATE).unwrap();
+    let text = doc.to_string();
+    assert!(text.contains("loop_\n_atom_site_label\n_atom_site_fract_x\n"));
+    assert!(text.contains("loop_\n_geom_bond_distance\nsave_"));
+
+    let reparsed = Document::parse(&text).unwrap();
+    assert!(reparsed.semantically_equal(&doc));
+}
+
+#[test]
+fn test_writer_skips_tagless_loops() {
+    let mut doc = Document::parse("data_x\n_a 1\n").unwrap();
+    doc.blocks[0].loops.push(Loop::default());
+    let text = doc.to_string();
+    assert!(!text.contains("loop_"));
+    assert!(Document::parse(&text).is_ok());
+}
+
+#[test]
+fn test_tagless_loop_is_rejected() {
+    for input in [
+        "data_x\nloop_\n",
+        "data_x\nloop_\ndata_y\n",
+        "data_x\nloop_\nloop_\n_a\n1\n",
+        "data_x\n_a 1\nloop_\n1 2\n",
+    ] {
+        for options in [ParseOptions::new(), ParseOptions::new().lenient(true)] {
+            let err = Document::parse_with_options(input, &options).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    CifError::InvalidStructure {
+                        location: Some(_),
+                        ..
+                    }
+                ),
+                "{input:?}: {err:?}"
+            );
+            assert!(err.to_string().contains("no tags"), "{input:?}: {err}");
+        }
+    }
+}
+
+#[test]
+fn test_all_unknown_row() {
+    let doc = Document::parse("data_x\nloop_\n_a\n_b\n_c\n? ? ?\n").unwrap();
+    let loop_ = &doc.blocks[0].loops[0];
+    assert_eq!(loop_.len(), 1);
+    assert!(loop_
+        .rows()
+        .next()
+        .unwrap()
+        .iter()
+        .all(|v| *v == Value::Unknown));
+    assert_eq!(loop_.get_column("_b"), Some(vec![&Value::Unknown]));
+
+    let reparsed = Document::parse(&doc.to_string()).unwrap();
+    assert!(reparsed.semantically_equal(&doc));
+}
+
+#[test]
+fn test_selective_parse_keeps_zero_row_loops() {
+    let options = ParseOptions::new().select(Selection::TagPrefixes(vec!["_atom_".to_string()]));
+    let doc = Document::parse_with_options(TEMPLATE, &options).unwrap();
+    assert_eq!(doc.blocks[0].loops.len(), 2);
+    assert!(doc.blocks[0].items.is_empty());
+}
+
+#[test]
+fn test_split_zero_row_mixed_loop() {
+    let doc = Document::parse("data_x\nloop_\n_atom_site_label\n_atom_site_aniso_U_11\n").unwrap();
+    let parts = doc.blocks[0].loops[0].split_by_category(category_of);
+    assert_eq!(parts.len(), 2);
+    assert!(parts.iter().all(|p| p.is_empty()));
+    assert_eq!(
+        parts[1].tags,
+        ["_atom_site_aniso_label", "_atom_site_aniso_U_11"]
+    );
+}
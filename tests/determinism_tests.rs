@@ -0,0 +1,207 @@
+// tests/determinism_tests.rs
+// Every report and export is byte-identical between runs and between processes
+
+use cif_parser::arrow::ArrowOptions;
+use cif_parser::cache::{self, CacheOptions};
+use cif_parser::convert::{core_to_mmcif, mmcif_to_core};
+use cif_parser::corpus::{census_files, extract_csv, TagCensus};
+use cif_parser::lexer::Tokenizer;
+use cif_parser::schema::ExpectedType;
+use cif_parser::{
+    classify, extract_items, CifStyle, Document, ElementOrder, ExtractOptions, ParseOptions,
+    WriteOptions,
+};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where the child process writes its outputs
+const OUT_VAR: &str = "CIF_DETERMINISM_OUT";
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/determinism/tables.cif")
+}
+
+fn example(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/example_cifs")
+        .join(path)
+}
+
+fn lenient() -> ParseOptions {
+    ParseOptions::new().lenient(true)
+}
+
+/// Everything the crate reports about the fixture, one section per output
+fn all_outputs(scratch: &Path) -> String {
+    let lenient = lenient();
+    let source = fs::read_to_string(fixture()).unwrap();
+    let doc = Document::parse_with_options(&source, &lenient).unwrap();
+    let block = &doc.blocks[0];
+    let mut out = String::new();
+    let mut section = |name: &str, text: String| {
+        writeln!(out, "== {name}\n{text}").unwrap();
+    };
+
+    section("display", doc.to_string());
+    section(
+        "write options",
+        doc.to_string_with_options(
+            &WriteOptions::new()
+                .style(CifStyle::Mmcif)
+                .atom_site_column_order(true)
+                .preamble_and_trailer(true),
+        ),
+    );
+    section("warnings", format!("{:?}", doc.warnings));
+    section("tags", format!("{:?}", doc.all_tags().collect::<Vec<_>>()));
+    section("walk", format!("{:?}", block.walk().collect::<Vec<_>>()));
+    for tag in ["_audit_conform_table", "_audit_sorted_table"] {
+        let value = block.get_item(tag).unwrap();
+        section(
+            tag,
+            format!("{:?}", value.as_table_keys().map(Vec::from_iter)),
+        );
+        section(tag, format!("{:?}", value.table_entries()));
+    }
+    section("memory", format!("{:?}", doc.memory_footprint()));
+    section("sanity", format!("{:?}", block.sanity_check()));
+    section(
+        "coercion",
+        format!(
+            "{:?}",
+            block.coercion_report(&[
+                ("_cell_length_a", ExpectedType::F64WithSu),
+                ("_journal_year", ExpectedType::I64),
+                ("_audit_conform_table", ExpectedType::Text),
+            ])
+        ),
+    );
+    section("kind", format!("{:?}", classify(&doc)));
+    section("identifiers", format!("{:?}", block.identifiers()));
+    section("publication", format!("{:?}", block.publication()));
+    section("point group", format!("{:?}", block.point_group()));
+    section("cell", format!("{:?}", block.unit_cell()));
+    section(
+        "typed",
+        format!("{:?}", block.get_as::<Vec<f64>>("_exptl_crystal_face_hkl")),
+    );
+    section("to mmcif", format!("{:?}", core_to_mmcif(block)));
+    if let Ok(mmcif) = core_to_mmcif(block) {
+        section("back to core", format!("{:?}", mmcif_to_core(&mmcif)));
+    }
+    let mut reordered = block.clone();
+    reordered.canonical_atom_site_order(ElementOrder::HeavyFirst);
+    section("canonical order", format!("{:?}", reordered.loops));
+
+    let tokens: Vec<_> = Tokenizer::new(&source)
+        .map(|t| t.map(|t| (t.kind, t.text, t.line, t.col)))
+        .collect();
+    section("tokens", format!("{tokens:?}"));
+
+    let mut census = TagCensus::new();
+    census.add(&doc);
+    section("census csv", census.to_csv());
+    section("census json", census.to_json());
+    let (census, errors) = census_files(
+        &[
+            example("quartz/ccdc_quartz.cif"),
+            example("urea/cod_urea.cif"),
+        ],
+        2,
+    );
+    section("census files", format!("{}{errors:?}", census.to_json()));
+
+    let tags = [
+        "_cell_length_a",
+        "_audit_conform_table",
+        "_publ_author_name",
+    ];
+    let records = extract_items(&[fixture()], &tags, &ExtractOptions::new().lenient(true));
+    section("extract", extract_csv(&records, &tags));
+
+    // The Arrow file records the block and source in its schema metadata
+    let read = Document::from_file_with_options(fixture(), &lenient).unwrap();
+    let options = ArrowOptions::new()
+        .block(&read.blocks[0].name)
+        .source(read.source.as_ref().unwrap());
+    let arrow = scratch.join("loop.arrow");
+    read.blocks[0].loops[0]
+        .write_arrow_ipc(&arrow, &options)
+        .unwrap();
+    section("arrow", format!("{:?}", fs::read(&arrow).unwrap()));
+
+    // The cache entry is written from the parsed document
+    let cache_dir = scratch.join("cache");
+    let _ = fs::remove_dir_all(&cache_dir);
+    cache::load(fixture(), &lenient, &CacheOptions::new(&cache_dir)).unwrap();
+    for entry in fs::read_dir(&cache_dir).unwrap() {
+        let entry = entry.unwrap().path();
+        section("cache entry", format!("{:?}", fs::read(entry).unwrap()));
+    }
+
+    out
+}
+
+/// A fresh scratch directory for one test
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cif-determinism-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_outputs_repeat_in_process() {
+    let dir = scratch("repeat");
+    let first = all_outputs(&dir);
+    let second = all_outputs(&dir);
+    assert_eq!(first, second);
+    assert!(first.contains("cache entry"), "{first}");
+}
+
+#[test]
+fn test_table_keys_are_sorted() {
+    let doc =
+        Document::parse_with_options(&fs::read_to_string(fixture()).unwrap(), &lenient()).unwrap();
+    let table = doc.blocks[0].get_item("_audit_sorted_table").unwrap();
+    assert_eq!(
+        table.as_table_keys().unwrap().collect::<Vec<_>>(),
+        ["e", "f", "g", "h", "i", "j", "k"]
+    );
+    assert!(doc
+        .to_string()
+        .contains("{'e':7 'f':6 'g':5 'h':4 'i':3 'j':2 'k':1}"));
+}
+
+/// Hash maps are seeded per process, so orders that depend on them differ
+/// between runs even when they happen to agree within one
+#[test]
+fn test_outputs_repeat_across_processes() {
+    let dir = scratch("processes");
+    let run = |n: usize| {
+        let out = dir.join(format!("run{n}.txt"));
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args(["write_outputs", "--exact", "--ignored", "--test-threads=1"])
+            .env(OUT_VAR, &out)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "{status:?}");
+        fs::read_to_string(out).unwrap()
+    };
+    let first = run(1);
+    assert_eq!(first, run(2));
+    assert_eq!(first, all_outputs(&dir));
+}
+
+/// Run by `test_outputs_repeat_across_processes` in a child process
+#[test]
+#[ignore = "helper for test_outputs_repeat_across_processes"]
+fn write_outputs() {
+    if let Ok(path) = std::env::var(OUT_VAR) {
+        let path = PathBuf::from(path);
+        let outputs = all_outputs(path.parent().unwrap());
+        fs::write(path, outputs).unwrap();
+    }
+}
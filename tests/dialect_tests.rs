@@ -0,0 +1,114 @@
+// tests/dialect_tests.rs
+// Dialect handlers reading non-standard tokens, with a toy vendor dialect
+
+use cif_parser::parser::dialect::{Statement, UnprefixedTags};
+use cif_parser::{DialectAction, DialectHandler, Document, ParseOptions, Value};
+use std::path::Path;
+
+const VENDOR: &str = "tests/fixtures/dialect/vendor.cif";
+
+/// `$table NAME [MODE]` lines become `_vendor_table_NAME MODE` items;
+/// everything else is read as by [`UnprefixedTags`]
+#[derive(Debug)]
+struct ToyDialect;
+
+impl DialectHandler for ToyDialect {
+    fn unrecognized(&self, statement: &Statement<'_>) -> DialectAction {
+        if statement.token != "$table" {
+            return UnprefixedTags.unrecognized(statement);
+        }
+        let mut words = statement.text.split_whitespace().skip(1);
+        let Some(name) = words.next() else {
+            return DialectAction::Reject;
+        };
+        let mode = words
+            .next()
+            .map_or(Value::Unknown, |m| Value::Text(m.into()));
+        DialectAction::Items {
+            consumed: statement.text.len(),
+            items: vec![(format!("_vendor_table_{name}"), mode)],
+            warning: Some(format!("Read '{}' as a table name", statement.text)),
+        }
+    }
+}
+
+fn vendor() -> String {
+    std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(VENDOR)).unwrap()
+}
+
+#[test]
+fn test_vendor_file_needs_a_dialect() {
+    for options in [ParseOptions::new(), ParseOptions::new().lenient(true)] {
+        let error = Document::parse_with_options(&vendor(), &options).unwrap_err();
+        assert!(error.to_string().contains("line 5, column 1"), "{error}");
+    }
+}
+
+#[test]
+fn test_toy_dialect_reads_the_vendor_file() {
+    let options = ParseOptions::new().dialect(ToyDialect);
+    let doc = Document::parse_with_options(&vendor(), &options).unwrap();
+    let block = &doc.blocks[0];
+
+    assert_eq!(
+        block.get_item("_diffrn_radiation_wavelength"),
+        Some(&Value::Numeric(0.02508))
+    );
+    assert_eq!(
+        block.get_item("_vendor_table_reflections"),
+        Some(&Value::Unknown)
+    );
+    assert_eq!(
+        block.get_item("_vendor_table_frames"),
+        Some(&Value::Text("precession".into()))
+    );
+    assert_eq!(block.loops.len(), 2);
+    assert_eq!(block.loops[0].len(), 3);
+
+    let warnings: Vec<_> = doc
+        .warnings
+        .iter()
+        .map(|w| (w.location, w.message.as_str()))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            (
+                Some((5, 1)),
+                "Data name 'diffrn_radiation_wavelength' has no leading underscore; \
+                 read as '_diffrn_radiation_wavelength'"
+            ),
+            (
+                Some((6, 1)),
+                "Data name 'diffrn_ambient_temperature' has no leading underscore; \
+                 read as '_diffrn_ambient_temperature'"
+            ),
+            (Some((8, 1)), "Read '$table reflections' as a table name"),
+            (
+                Some((17, 1)),
+                "Read '$table frames precession' as a table name"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_dialect_output_round_trips_as_plain_cif() {
+    let options = ParseOptions::new().dialect(ToyDialect);
+    let doc = Document::parse_with_options(&vendor(), &options).unwrap();
+
+    let reread = Document::parse(&doc.to_string()).unwrap();
+    assert!(reread.semantically_equal(&doc));
+    assert!(reread.warnings.is_empty());
+}
+
+#[test]
+fn test_rejected_tokens_are_still_errors() {
+    let cif = "data_a\n_x 1\nstray 2\n";
+    let options = ParseOptions::new().dialect(ToyDialect);
+    let error = Document::parse_with_options(cif, &options).unwrap_err();
+    assert!(error.to_string().contains("'stray'"), "{error}");
+
+    let cif = "data_a\n$table\n";
+    assert!(Document::parse_with_options(cif, &options).is_err());
+}
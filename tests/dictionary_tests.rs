@@ -0,0 +1,169 @@
+// tests/dictionary_tests.rs
+// Layered DDLm dictionaries and which definition each validation issue used
+
+use cif_parser::dictionary::{CifDictionary, DictionaryStack, ValidationKind};
+use cif_parser::Document;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/dictionary")
+        .join(name)
+}
+
+fn core() -> CifDictionary {
+    CifDictionary::from_file(fixture("core_mini.dic")).unwrap()
+}
+
+fn local() -> CifDictionary {
+    CifDictionary::from_file(fixture("local.dic")).unwrap()
+}
+
+#[test]
+fn test_read_dictionary() {
+    let core = core();
+    assert_eq!(core.title, "CORE_MINI");
+    assert_eq!(core.version.as_deref(), Some("3.3.0"));
+    // Category frames are not item definitions
+    assert_eq!(core.len(), 4);
+
+    let a = core.get("_cell_length_a").unwrap();
+    assert_eq!(a.id, "_cell.length_a");
+    assert_eq!(a.frame, "cell.length_a");
+    assert_eq!(a.category.as_deref(), Some("cell"));
+    assert_eq!(a.contents.as_deref(), Some("Real"));
+    assert_eq!(a.units.as_deref(), Some("angstroms"));
+    assert_eq!(a.range, Some((Some(1.0), None)));
+    assert_eq!(
+        a.description.as_deref(),
+        Some("Unit-cell length a corresponding to the structure reported.")
+    );
+
+    let flag = core.get("_atom_site.calc_flag").unwrap();
+    assert_eq!(flag.enumeration, ["d", "calc", "c", "dum"]);
+
+    // Every alias and any case finds the definition
+    for tag in [
+        "_space_group.IT_number",
+        "_space_group_it_number",
+        "_SYMMETRY_INT_TABLES_NUMBER",
+    ] {
+        assert_eq!(core.get(tag).unwrap().frame, "space_group.it_number");
+    }
+    assert!(core.get("_cell.length_b").is_none());
+}
+
+#[test]
+fn test_later_dictionary_overrides_per_tag() {
+    let stack: DictionaryStack = [core(), local()].into_iter().collect();
+
+    let flag = stack.definition_of("_atom_site_calc_flag").unwrap();
+    assert_eq!(flag.dictionary, "LOCAL_EXT");
+    assert_eq!(flag.frame, "local_atom_site.calc_flag");
+    assert!(flag.enumeration.iter().any(|state| state == "riding"));
+
+    let all = stack.definitions_of("_atom_site_calc_flag");
+    let sources: Vec<&str> = all.iter().map(|d| d.dictionary.as_str()).collect();
+    assert_eq!(sources, ["LOCAL_EXT", "CORE_MINI"]);
+
+    // Tags the local dictionary does not define still come from the core
+    let a = stack.definition_of("_cell_length_a").unwrap();
+    assert_eq!(a.dictionary, "CORE_MINI");
+    assert_eq!(
+        stack.definition_of("_local.oven_count").unwrap().frame,
+        "local.oven_count"
+    );
+    assert!(stack.definition_of("_undefined_item").is_none());
+}
+
+#[test]
+fn test_validate_with_core_only() {
+    let doc = Document::from_file(fixture("sample.cif")).unwrap();
+    let mut stack = DictionaryStack::new();
+    stack.push(core());
+    let issues = doc.validate(&stack);
+
+    let found: Vec<(&str, &str, Option<usize>, ValidationKind)> = issues
+        .iter()
+        .map(|i| (i.block.as_str(), i.tag.as_str(), i.row, i.kind))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "sample",
+                "_space_group_IT_number",
+                None,
+                ValidationKind::Range
+            ),
+            (
+                "sample",
+                "_symmetry_Int_Tables_number",
+                None,
+                ValidationKind::Type
+            ),
+            (
+                "sample",
+                "_atom_site_calc_flag",
+                Some(1),
+                ValidationKind::Enumeration
+            ),
+            (
+                "sample",
+                "_atom_site_calc_flag",
+                Some(3),
+                ValidationKind::Enumeration
+            ),
+            ("second", "_cell.length_a", None, ValidationKind::Type),
+        ]
+    );
+    let riding = &issues[2];
+    assert_eq!(riding.value, "riding");
+    assert_eq!(riding.dictionary, "CORE_MINI");
+    assert_eq!(riding.definition, "atom_site.calc_flag");
+    assert_eq!(riding.category.as_deref(), Some("atom_site"));
+    assert_eq!(
+        riding.to_string(),
+        "data_sample _atom_site_calc_flag row 1: 'riding' should be one of d, calc, c, dum \
+         (CORE_MINI, save_atom_site.calc_flag)"
+    );
+}
+
+#[test]
+fn test_validate_with_local_extension() {
+    let doc = Document::from_file(fixture("sample.cif")).unwrap();
+    let stack: DictionaryStack = [core(), local()].into_iter().collect();
+    let issues = doc.validate(&stack);
+
+    // 'riding' is allowed by the local definition, which also defines a
+    // count the core dictionary does not know
+    assert!(!issues.iter().any(|i| i.value == "riding"));
+    let guess = issues.iter().find(|i| i.value == "guess").unwrap();
+    assert_eq!(guess.row, Some(3));
+    assert_eq!(
+        (guess.dictionary.as_str(), guess.definition.as_str()),
+        ("LOCAL_EXT", "local_atom_site.calc_flag")
+    );
+    assert!(guess.message.contains("riding"));
+
+    let count = issues
+        .iter()
+        .find(|i| i.tag == "_local.oven_count")
+        .unwrap();
+    assert_eq!(count.kind, ValidationKind::Range);
+    assert_eq!(count.dictionary, "LOCAL_EXT");
+
+    let range = issues
+        .iter()
+        .find(|i| i.tag == "_space_group_IT_number")
+        .unwrap();
+    assert_eq!(range.dictionary, "CORE_MINI");
+    assert_eq!(range.message, "should be in the range 1:230");
+    assert_eq!(issues.len(), 5);
+}
+
+#[test]
+fn test_missing_data_block() {
+    let err = CifDictionary::parse("# nothing here\n").unwrap_err();
+    assert!(err.to_string().contains("no data block"));
+}
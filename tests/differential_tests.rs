@@ -0,0 +1,86 @@
+// tests/differential_tests.rs
+// Every fixture written and read back by this crate and any external readers
+
+use cif_parser::corpus::cif_files;
+use cif_parser::testing::differential::{compare_with_external, Divergence, ExternalTool};
+use cif_parser::{Document, Value};
+use std::path::{Path, PathBuf};
+
+/// Fixture files that parse; some fixtures are malformed on purpose
+fn documents() -> Vec<(PathBuf, Document)> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    cif_files(root)
+        .unwrap()
+        .into_iter()
+        .filter_map(|path| Document::from_file(&path).ok().map(|doc| (path, doc)))
+        .collect()
+}
+
+#[test]
+fn test_fixtures_read_back_as_written() {
+    // Uses GEMMI_CLI and PYCIFRW when set; this crate alone otherwise
+    let tools = ExternalTool::from_env();
+    let documents = documents();
+    assert!(documents.len() > 50);
+    for (path, doc) in documents {
+        let divergences = compare_with_external(&doc, &tools)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        let report: Vec<String> = divergences.iter().map(Divergence::to_string).collect();
+        assert!(
+            report.is_empty(),
+            "{}:\n{}",
+            path.display(),
+            report.join("\n")
+        );
+    }
+}
+
+#[test]
+fn test_escaping_edge_cases_read_back_as_written() {
+    let mut doc = Document::parse("data_q\n_a x\n").unwrap();
+    let block = &mut doc.blocks[0];
+    for (tag, text) in [
+        ("_quote", "it's"),
+        ("_both", "'both' \"kinds\""),
+        ("_reserved", "loop_"),
+        ("_data", "data_x"),
+        ("_leading_underscore", "_not_a_tag"),
+        ("_hash", "#not a comment"),
+        ("_semicolon", ";start"),
+        ("_question", "?"),
+        ("_dot", "."),
+        ("_lines", "first\nsecond"),
+        ("_number_text", "007"),
+    ] {
+        block
+            .items
+            .insert(tag.to_string(), Value::Text(text.to_string()));
+    }
+    let divergences = compare_with_external(&doc, &ExternalTool::from_env()).unwrap();
+    assert!(divergences.is_empty(), "{divergences:#?}");
+}
+
+#[test]
+fn test_divergence_names_reader_and_value() {
+    let divergence = Divergence {
+        reader: "gemmi".to_string(),
+        block: "q".to_string(),
+        tag: "_atom_site_label".to_string(),
+        row: Some(3),
+        ours: "'C 1'".to_string(),
+        theirs: "\"C\"".to_string(),
+    };
+    assert_eq!(
+        divergence.to_string(),
+        "gemmi: data_q _atom_site_label row 3: wrote 'C 1', read \"C\""
+    );
+}
+
+#[test]
+fn test_tools_from_env() {
+    let tools = ExternalTool::from_env();
+    assert_eq!(
+        tools.iter().any(|tool| tool.name() == "gemmi"),
+        std::env::var_os("GEMMI_CLI").is_some_and(|v| !v.is_empty())
+    );
+}
@@ -0,0 +1,122 @@
+// tests/diffrn_tests.rs
+// Orientation matrices and goniometer axes from PETS2 output and imgCIF headers
+
+use cif_parser::{Block, Document};
+use std::path::Path;
+
+fn first_block(path: &str) -> Block {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+#[test]
+fn test_orientation_matrix_element_order() {
+    let block = first_block("tests/example_cifs/albite/pets2_albite.cif_pets");
+    let ub = block.orientation_matrix().unwrap();
+    assert_eq!(ub[0], [-0.13002, 0.03768, -0.02551]);
+    assert_eq!(ub[1], [0.08430, 0.14265, 0.03024]);
+    assert_eq!(ub[2], [-0.03030, -0.06252, -0.15420]);
+}
+
+#[test]
+fn test_ub_matches_refined_cell() {
+    for file in [
+        "tests/example_cifs/albite/pets2_albite.cif_pets",
+        "tests/example_cifs/mordenite/mordenite_s1m1_filter_dyn_s3m7.cif_pets",
+    ] {
+        let check = first_block(file).check_ub(1e-3).unwrap();
+        assert!(check.consistent, "{file}: {check:?}");
+        assert!(
+            (check.determinant - check.reciprocal_volume).abs() < 1e-3 * check.reciprocal_volume
+        );
+    }
+}
+
+#[test]
+fn test_ub_discrepancy_is_reported() {
+    // The glycine UB and reported cell disagree by about 1%
+    let block = first_block("tests/example_cifs/glycine/pets2_glycine.cif_pets");
+    let check = block.check_ub(1e-3).unwrap();
+    assert!(!check.consistent);
+    assert!(
+        check.discrepancy < -0.005 && check.discrepancy > -0.02,
+        "{check:?}"
+    );
+    assert!(block.check_ub(0.02).unwrap().consistent);
+}
+
+#[test]
+fn test_ub_discrepancy_in_sigmas() {
+    // det(UB) = 1/125 Å⁻³, against a cell of 5.01(1)³ Å³: 1/V is 4.78e-5
+    // lower, with an su of √3·a²·σ(a)/V² = 2.75e-5
+    let mut cif = String::from("data_x\n");
+    for (length, angle) in ["a", "b", "c"].iter().zip(["alpha", "beta", "gamma"]) {
+        cif.push_str(&format!(
+            "_cell_length_{length} 5.01(1)\n_cell_angle_{angle} 90\n"
+        ));
+    }
+    for i in 1..=3 {
+        for j in 1..=3 {
+            let element = if i == j { 0.2 } else { 0.0 };
+            cif.push_str(&format!("_diffrn_orient_matrix_UB_{i}{j} {element}\n"));
+        }
+    }
+    let doc = Document::parse(&cif).unwrap();
+    let check = doc.blocks[0].check_ub(1e-3).unwrap();
+    assert!(!check.consistent);
+    let sigmas = check.sigmas.unwrap();
+    assert!((sigmas - 1.74).abs() < 0.01, "{check:?}");
+
+    // The PETS2 cell has no sus
+    let block = first_block("tests/example_cifs/albite/pets2_albite.cif_pets");
+    assert_eq!(block.check_ub(1e-3).unwrap().sigmas, None);
+}
+
+#[test]
+fn test_check_ub_needs_matrix_and_cell() {
+    let doc =
+        Document::parse("data_x\n_cell_length_a 5\n_diffrn_orient_matrix_UB_11 0.2\n").unwrap();
+    assert_eq!(doc.blocks[0].orientation_matrix(), None);
+    assert_eq!(doc.blocks[0].check_ub(0.01), None);
+}
+
+#[test]
+fn test_imgcif_goniometer_axes() {
+    let block = first_block("tests/fixtures/imgcif_axes.cif");
+    let axes = block.goniometer_axes();
+    let ids: Vec<_> = axes.iter().map(|a| a.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["GONIOMETER_OMEGA", "GONIOMETER_KAPPA", "GONIOMETER_PHI"]
+    );
+
+    assert_eq!(axes[0].kind.as_deref(), Some("rotation"));
+    assert_eq!(axes[0].depends_on, None);
+    assert_eq!(axes[0].vector, Some([1.0, 0.0, 0.0]));
+    assert_eq!(axes[1].depends_on.as_deref(), Some("GONIOMETER_OMEGA"));
+    assert_eq!(axes[1].vector, Some([0.76604, 0.64279, 0.0]));
+}
+
+#[test]
+fn test_axes_without_measurement_loop_use_equipment() {
+    let mut block = first_block("tests/fixtures/imgcif_axes.cif");
+    block
+        .loops
+        .retain(|l| l.tags[0] != "_diffrn_measurement_axis.measurement_id");
+    let ids: Vec<_> = block.goniometer_axes().into_iter().map(|a| a.id).collect();
+    assert_eq!(
+        ids,
+        ["GONIOMETER_OMEGA", "GONIOMETER_KAPPA", "GONIOMETER_PHI"]
+    );
+}
+
+#[test]
+fn test_measurement_axes_without_axis_loop() {
+    let cif = "data_x\nloop_\n_diffrn_measurement_axis_measurement_id\n\
+               _diffrn_measurement_axis_axis_id\nGONIO omega\nGONIO phi\n";
+    let doc = Document::parse(cif).unwrap();
+    let axes = doc.blocks[0].goniometer_axes();
+    assert_eq!(axes.len(), 2);
+    assert_eq!(axes[1].id, "phi");
+    assert_eq!((axes[1].kind.clone(), axes[1].vector), (None, None));
+}
@@ -0,0 +1,130 @@
+// tests/duplicate_tag_tests.rs
+// Repeated data item tags under each DuplicateTags policy
+
+use cif_parser::{CifError, Document, DuplicateTags, ParseOptions, Value};
+use std::path::Path;
+
+fn parse(policy: DuplicateTags) -> Result<Document, CifError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/repeated_authors.cif");
+    Document::from_file_with_options(path, &ParseOptions::new().duplicate_tags(policy))
+}
+
+fn texts(values: Vec<&Value>) -> Vec<&str> {
+    values.into_iter().filter_map(Value::as_string).collect()
+}
+
+#[test]
+fn test_collapse_builds_one_column_loop() {
+    let doc = parse(DuplicateTags::Collapse).unwrap();
+    let block = &doc.blocks[0];
+
+    assert!(!block.items.contains_key("_publ_author_name"));
+    let loop_ = block.find_loop("_publ_author_name").unwrap();
+    assert_eq!(loop_.tags, ["_publ_author_name"]);
+    assert_eq!(
+        texts(loop_.get_column("_publ_author_name").unwrap()),
+        ["Smith, Jane A.", "Jones, K.", "O'Brien, P. Q."]
+    );
+    // Items between the repeats are untouched
+    assert!(block.get_item("_publ_author_address").is_some());
+}
+
+#[test]
+fn test_collapse_records_one_warning_per_tag() {
+    let doc = parse(DuplicateTags::Collapse).unwrap();
+    let messages: Vec<_> = doc.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(messages.len(), 2, "{messages:?}");
+    assert!(messages[0].contains("'_publ_author_name'"));
+    assert!(messages[1].contains("'_publ_section_comment'"));
+    // Reported at the second occurrence
+    assert_eq!(doc.warnings[0].location.map(|(line, _)| line), Some(16));
+}
+
+#[test]
+fn test_get_item_reads_only_data_items() {
+    let doc = parse(DuplicateTags::Collapse).unwrap();
+    assert_eq!(doc.blocks[0].get_item("_publ_author_name"), None);
+
+    let frame = doc.blocks[1].get_frame("details").unwrap();
+    assert_eq!(frame.get_item("_publ_section_comment"), None);
+    assert_eq!(
+        texts(frame.get_all("_publ_section_comment")),
+        ["First remark.", "Second remark."]
+    );
+
+    // Nor does a loop the file wrote with a single column give an item
+    let doc = Document::parse("data_x\nloop_ _publ_author_name 'Smith, J.' 'Doe, A.'\n").unwrap();
+    assert_eq!(doc.blocks[0].get_item("_publ_author_name"), None);
+    assert_eq!(doc.blocks[0].get_all("_publ_author_name").len(), 2);
+}
+
+#[test]
+fn test_collapse_leaves_written_loops_alone() {
+    let cif = "data_x\nloop_ _publ_author_name 'Smith, J.' 'Doe, A.'\n_publ_author_name 'Extra'\n";
+    let options = ParseOptions::new().duplicate_tags(DuplicateTags::Collapse);
+    let doc = Document::parse_with_options(cif, &options).unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(block.loops[0].len(), 2);
+    assert_eq!(
+        block
+            .get_item("_publ_author_name")
+            .and_then(Value::as_string),
+        Some("Extra")
+    );
+    assert_eq!(doc.warnings.len(), 1);
+    assert!(doc.warnings[0].message.contains("already a loop column"));
+    assert_eq!(doc.warnings[0].location.map(|(line, _)| line), Some(3));
+}
+
+#[test]
+fn test_get_all_is_uniform() {
+    let doc = parse(DuplicateTags::Collapse).unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(texts(block.get_all("_publ_author_name")).len(), 3);
+    assert_eq!(
+        texts(block.get_all("_publ_contact_author_name")),
+        ["Smith, Jane A."]
+    );
+    assert!(block.get_all("_missing").is_empty());
+
+    // The same accessor works on the last-wins parse
+    let doc = parse(DuplicateTags::Last).unwrap();
+    assert_eq!(
+        texts(doc.blocks[0].get_all("_publ_author_name")),
+        ["O'Brien, P. Q."]
+    );
+}
+
+#[test]
+fn test_collapsed_authors_feed_publication() {
+    let doc = parse(DuplicateTags::Collapse).unwrap();
+    let authors = doc.blocks[0].publication().authors;
+    assert_eq!(authors.len(), 3);
+    assert_eq!(authors[2].family, "O'Brien");
+}
+
+#[test]
+fn test_default_keeps_last_value_silently() {
+    let doc = parse(DuplicateTags::default()).unwrap();
+    let author = doc.blocks[0].get_item("_publ_author_name").unwrap();
+    assert_eq!(author.as_string(), Some("O'Brien, P. Q."));
+    assert!(doc.warnings.is_empty());
+    assert!(doc.blocks[0].loops.is_empty());
+}
+
+#[test]
+fn test_error_policy_rejects_repeats() {
+    let err = parse(DuplicateTags::Error).unwrap_err().to_string();
+    assert!(err.contains("Duplicate tag '_publ_author_name'"), "{err}");
+    assert!(err.contains("line 16"), "{err}");
+}
+
+#[test]
+fn test_collapsed_document_round_trips() {
+    let doc = parse(DuplicateTags::Collapse).unwrap();
+    let reparsed = Document::parse(&doc.to_string()).unwrap();
+    assert_eq!(
+        reparsed.blocks[0].get_all("_publ_author_name"),
+        doc.blocks[0].get_all("_publ_author_name")
+    );
+}
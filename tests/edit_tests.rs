@@ -0,0 +1,159 @@
+// tests/edit_tests.rs
+// Changing documents and building them from scratch, keeping them writable
+
+use cif_parser::{CifBlock, CifError, CifLoop, CifValue, CifVersion, Document, DocumentBuilder};
+
+fn text(s: &str) -> CifValue {
+    CifValue::Text(s.to_string())
+}
+
+#[test]
+fn test_edits_round_trip() {
+    let mut doc = Document::parse("data_x\n_title old\n_cell_length_a 5\n").unwrap();
+    let block = &mut doc.blocks[0];
+
+    assert_eq!(
+        block.set_item("_title", text("new")).unwrap(),
+        Some(text("old"))
+    );
+    assert_eq!(
+        block
+            .set_item("_cell_volume", CifValue::Numeric(125.0))
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        block.remove_item("_cell_length_a"),
+        Some(CifValue::Numeric(5.0))
+    );
+    assert_eq!(block.remove_item("_cell_length_a"), None);
+    let keys: Vec<&str> = block.items.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["_title", "_cell_volume"]);
+
+    let mut atoms = CifLoop::new(vec!["_atom_site_label".to_string()]);
+    atoms.add_row(vec![text("C1")]).unwrap();
+    atoms.add_row(vec![text("O1")]).unwrap();
+    atoms
+        .add_column(
+            "_atom_site_occupancy",
+            vec![CifValue::Numeric(1.0), CifValue::Unknown],
+        )
+        .unwrap();
+    block.add_loop(atoms).unwrap();
+
+    doc.add_block(CifBlock::new("y".to_string())).unwrap();
+    doc.blocks[1].set_item("_note", text("two words")).unwrap();
+
+    let reparsed = Document::parse(&doc.to_string()).unwrap();
+    let block = &reparsed.blocks[0];
+    assert_eq!(block.get_item("_title"), Some(&text("new")));
+    assert!(block.get_item("_cell_length_a").is_none());
+    let atoms = block.find_loop("_atom_site_occupancy").unwrap();
+    assert_eq!(atoms.get_by_tag(1, "_atom_site_label"), Some(&text("O1")));
+    assert_eq!(
+        atoms.get_by_tag(1, "_atom_site_occupancy"),
+        Some(&CifValue::Unknown)
+    );
+    assert_eq!(
+        reparsed.blocks[1].get_item("_note"),
+        Some(&text("two words"))
+    );
+}
+
+#[test]
+fn test_edits_that_would_break_the_document_are_refused() {
+    let mut doc = Document::parse("data_x\n_title t\nloop_\n_a\n_b\n1 2\n").unwrap();
+    let block = &mut doc.blocks[0];
+
+    let error = block.set_item("_a", CifValue::Unknown).unwrap_err();
+    assert!(matches!(error, CifError::DuplicateTag { ref tag, .. } if tag == "_a"));
+    assert_eq!(error.code(), "E0005");
+    assert_eq!(
+        block.set_item("title", text("t")).unwrap_err().code(),
+        "E0008"
+    );
+
+    for tags in [vec!["_title"], vec!["_b", "_c"], vec!["_c", "_c"]] {
+        let loop_ = CifLoop::new(tags.iter().map(|t| t.to_string()).collect());
+        assert_eq!(
+            block.add_loop(loop_).unwrap_err().code(),
+            "E0005",
+            "{tags:?}"
+        );
+    }
+    let mut ragged = CifLoop::new(vec!["_c".to_string(), "_d".to_string()]);
+    ragged.values.push(vec![CifValue::Unknown]);
+    assert!(matches!(
+        block.add_loop(ragged),
+        Err(CifError::RowLength {
+            expected: 2,
+            found: 1
+        })
+    ));
+    assert!(block.add_loop(CifLoop::new(Vec::new())).is_err());
+    assert_eq!(block.loops.len(), 1);
+
+    let loop_ = &mut block.loops[0];
+    assert_eq!(
+        loop_
+            .add_column("_a", vec![CifValue::Unknown])
+            .unwrap_err()
+            .code(),
+        "E0005"
+    );
+    assert!(loop_.add_column("_c", Vec::new()).is_err());
+    assert_eq!(loop_.tags, ["_a", "_b"]);
+    assert_eq!(
+        loop_.add_row(vec![CifValue::Unknown]).unwrap_err().code(),
+        "E0010"
+    );
+    assert_eq!(loop_.len(), 1);
+
+    assert!(doc.add_block(CifBlock::new("x".to_string())).is_err());
+    assert!(doc
+        .add_block(CifBlock::new("two words".to_string()))
+        .is_err());
+    assert_eq!(doc.blocks.len(), 1);
+}
+
+#[test]
+fn test_builder() {
+    let doc = DocumentBuilder::new()
+        .block("first")
+        .item("_title", text("Built"))
+        .loop_(
+            &["_n", "_name"],
+            [
+                vec![CifValue::Numeric(1.0), text("one")],
+                vec![CifValue::Numeric(2.0), text("two")],
+            ],
+        )
+        .block("second")
+        .item("_list", CifValue::List(vec![CifValue::Numeric(1.0)]))
+        .version(CifVersion::V2_0)
+        .build()
+        .unwrap();
+    let reparsed = Document::parse(&doc.to_string()).unwrap();
+    assert_eq!(reparsed.version, CifVersion::V2_0);
+    assert_eq!(reparsed.blocks.len(), 2);
+    assert_eq!(reparsed.blocks[0].find_loop("_n").unwrap().len(), 2);
+    assert!(reparsed.blocks[1]
+        .get_item("_list")
+        .unwrap()
+        .as_list()
+        .is_some());
+
+    // The first error is kept
+    let error = DocumentBuilder::new()
+        .block("x")
+        .item("_a", CifValue::Unknown)
+        .loop_(&["_a"], [vec![CifValue::Unknown]])
+        .block("x")
+        .build()
+        .unwrap_err();
+    assert!(matches!(error, CifError::DuplicateTag { ref tag, .. } if tag == "_a"));
+    assert!(DocumentBuilder::new()
+        .item("_a", CifValue::Unknown)
+        .build()
+        .is_err());
+}
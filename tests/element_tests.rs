@@ -0,0 +1,100 @@
+// tests/element_tests.rs
+// Atom type symbols as written in real files, read into element, charge, and isotope
+
+use cif_parser::{Document, ElementOrder, ElementSymbol, Isotope};
+
+/// Type symbols seen in COD and ICSD entries, with the element, charge, and
+/// isotope they mean
+const VARIANTS: &[(&str, &str, Option<i8>, Option<Isotope>)] = &[
+    // Plain symbols, in any case
+    ("C", "C", None, None),
+    ("Cl", "Cl", None, None),
+    ("CL", "Cl", None, None),
+    ("cl", "Cl", None, None),
+    ("cO", "Co", None, None),
+    ("ZN", "Zn", None, None),
+    ("Og", "Og", None, None),
+    // Charge after the digits, as the dictionaries write it
+    ("O2-", "O", Some(-2), None),
+    ("Fe3+", "Fe", Some(3), None),
+    ("FE2+", "Fe", Some(2), None),
+    ("Si4+", "Si", Some(4), None),
+    ("N3-", "N", Some(-3), None),
+    ("Mn7+", "Mn", Some(7), None),
+    ("Cu1+", "Cu", Some(1), None),
+    ("O1-", "O", Some(-1), None),
+    ("Fe0+", "Fe", Some(0), None),
+    // Sign before the digits
+    ("O-2", "O", Some(-2), None),
+    ("Fe+3", "Fe", Some(3), None),
+    ("cu+2", "Cu", Some(2), None),
+    ("O-1", "O", Some(-1), None),
+    // Bare signs
+    ("Na+", "Na", Some(1), None),
+    ("Cl-", "Cl", Some(-1), None),
+    ("K+", "K", Some(1), None),
+    // Hydrogen isotopes
+    ("D", "H", None, Some(Isotope::Deuterium)),
+    ("d", "H", None, Some(Isotope::Deuterium)),
+    ("D+", "H", Some(1), Some(Isotope::Deuterium)),
+    ("T", "H", None, Some(Isotope::Tritium)),
+    ("H", "H", None, None),
+    // Not to be confused with the isotopes
+    ("Dy3+", "Dy", Some(3), None),
+    ("Ds", "Ds", None, None),
+    ("Ta", "Ta", None, None),
+    ("Tb3+", "Tb", Some(3), None),
+    // Padding left by fixed-width writers
+    (" Fe3+ ", "Fe", Some(3), None),
+];
+
+/// Type symbols that name no element, or no whole charge
+const NONSENSE: &[&str] = &[
+    "", "?", "Wat", "Ow", "Q", "Xx", "O2", "Fe3", "Fe3+2", "O--", "O2-+", "Fe2.5+", "H(SDS)",
+    "Fe 3+", "3+", "Fe999+",
+];
+
+#[test]
+fn test_real_world_variants() {
+    for &(token, symbol, charge, isotope) in VARIANTS {
+        let parsed =
+            ElementSymbol::parse(token).unwrap_or_else(|e| panic!("{token:?} failed: {e}"));
+        assert_eq!(parsed.element.symbol(), symbol, "{token:?}");
+        assert_eq!(parsed.charge, charge, "{token:?}");
+        assert_eq!(parsed.isotope, isotope, "{token:?}");
+    }
+}
+
+#[test]
+fn test_nonsense_is_rejected_with_the_token() {
+    for &token in NONSENSE {
+        let message = match ElementSymbol::parse(token) {
+            Ok(parsed) => panic!("{token:?} gave {parsed:?}"),
+            Err(e) => e.to_string(),
+        };
+        assert!(message.contains(&format!("'{token}'")), "{message}");
+    }
+}
+
+#[test]
+fn test_canonical_form() {
+    let canonical = |token| ElementSymbol::parse(token).unwrap().to_string();
+    assert_eq!(canonical("FE+3"), "Fe3+");
+    assert_eq!(canonical("O-2"), "O2-");
+    assert_eq!(canonical("Na1+"), "Na+");
+    assert_eq!(canonical("d"), "D");
+}
+
+#[test]
+fn test_upper_case_type_symbols_sort_by_their_element() {
+    // Read naively, `FE3+` starts with fluorine
+    let cif = "data_x\nloop_\n_atom_site_label\n_atom_site_type_symbol\n\
+               X1 O-2\nX2 FE3+\nX3 F\nX4 D\n";
+    let mut doc = Document::parse(cif).unwrap();
+    let block = &mut doc.blocks[0];
+    block.canonical_atom_site_order(ElementOrder::HeavyFirst);
+
+    let labels = block.loops[0].get_column("_atom_site_label").unwrap();
+    let labels: Vec<_> = labels.iter().filter_map(|v| v.as_string()).collect();
+    assert_eq!(labels, ["X2", "X3", "X1", "X4"]);
+}
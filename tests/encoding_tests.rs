@@ -0,0 +1,118 @@
+// tests/encoding_tests.rs
+// Byte-order marks, UTF-16, and CR/LF/CRLF newlines all give the same document
+
+use cif_parser::{CifError, Document, DuplicateTags, ParseOptions};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/encodings")
+        .join(name)
+}
+
+fn lenient() -> ParseOptions {
+    ParseOptions::new().lenient(true)
+}
+
+#[test]
+fn test_every_variant_parses_the_same() {
+    let reference = Document::from_file(fixture("utf8_lf.cif")).unwrap();
+    let comment = reference
+        .first_block()
+        .unwrap()
+        .get_item("_publ_section_comment")
+        .unwrap();
+    assert_eq!(
+        comment.as_string(),
+        Some("First line of the comment.\nSecond line, after a line break.")
+    );
+
+    for name in [
+        "utf8_crlf.cif",
+        "utf8_cr.cif",
+        "utf8_mixed.cif",
+        "utf8_bom_lf.cif",
+        "utf8_bom_crlf.cif",
+        "utf16le_crlf.cif",
+        "utf16be_lf.cif",
+    ] {
+        let doc = Document::from_file_with_options(fixture(name), &lenient()).unwrap();
+        assert!(doc.semantically_equal(&reference), "{name}");
+        let block = doc.first_block().unwrap();
+        assert_eq!(block.name, "encoding", "{name}");
+        assert_eq!(
+            block.get_item("_publ_section_comment"),
+            Some(comment),
+            "{name}"
+        );
+        let utf16 = name.starts_with("utf16");
+        assert_eq!(doc.warnings.len(), usize::from(utf16), "{name}");
+    }
+}
+
+#[test]
+fn test_strict_utf8_variants_need_no_options() {
+    for name in [
+        "utf8_crlf.cif",
+        "utf8_cr.cif",
+        "utf8_mixed.cif",
+        "utf8_bom_crlf.cif",
+    ] {
+        let doc = Document::from_file(fixture(name)).unwrap();
+        assert!(doc.warnings.is_empty(), "{name}");
+    }
+}
+
+#[test]
+fn test_strict_rejects_utf16() {
+    for (name, endian) in [("utf16le_crlf.cif", "little"), ("utf16be_lf.cif", "big")] {
+        let error = Document::from_file(fixture(name)).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("File is UTF-16 encoded"), "{message}");
+        assert!(message.contains(endian), "{message}");
+        assert_eq!(error.path(), Some(fixture(name).as_path()));
+    }
+}
+
+#[test]
+fn test_utf16_warning() {
+    let doc = Document::from_file_with_options(fixture("utf16le_crlf.cif"), &lenient()).unwrap();
+    assert_eq!(
+        doc.warnings[0].message,
+        "File is UTF-16 encoded (little-endian); transcoded to UTF-8"
+    );
+}
+
+#[test]
+fn test_bom_without_magic_comment() {
+    let doc = Document::parse("\u{FEFF}data_a\n_x 1\n").unwrap();
+    assert_eq!(doc.blocks[0].name, "a");
+}
+
+#[test]
+fn test_locations_ignore_newline_style() {
+    let options = ParseOptions::new().duplicate_tags(DuplicateTags::Error);
+    for newline in ["\n", "\r\n", "\r"] {
+        let cif = ["data_a", "_x 1", "_y 2", "_x 3", ""].join(newline);
+        let error = Document::parse_with_options(&cif, &options).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                CifError::DuplicateTag {
+                    location: Some((4, 1)),
+                    ..
+                }
+            ),
+            "{newline:?}: {error}"
+        );
+    }
+}
+
+#[test]
+fn test_invalid_utf8_is_located() {
+    let error = Document::from_bytes(b"data_a\r\n_x 1\r\n_y caf\xE9\r\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[E0003] Error at line 3, column 7: File is not valid UTF-8: invalid byte 0xE9"
+    );
+}
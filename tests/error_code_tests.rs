@@ -0,0 +1,206 @@
+// tests/error_code_tests.rs
+// Each CifError variant, its stable code, its message, and its source
+
+use cif_parser::lexer::Tokenizer;
+use cif_parser::{
+    CifDocumentRef, CifError, Conformance, Document, DuplicateTags, ElementSymbol, Loop,
+    ParseOptions, Query, Value,
+};
+use std::error::Error;
+use std::path::PathBuf;
+
+fn missing_file() -> CifError {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/does-not-exist.cif");
+    Document::from_file(path).unwrap_err()
+}
+
+fn parse_error(input: &str) -> CifError {
+    Document::parse(input).unwrap_err()
+}
+
+/// The tokenizer's error for input it cannot split into tokens
+fn token_error(input: &str) -> CifError {
+    Tokenizer::new(input).find_map(Result::err).unwrap()
+}
+
+#[test]
+fn test_every_variant_has_its_code() {
+    let strict_duplicates = ParseOptions::new().duplicate_tags(DuplicateTags::Error);
+    let cases = [
+        (parse_error("data_a\n_x 'a' 'b' 'c\n"), "E0001"),
+        (missing_file(), "E0002"),
+        (
+            Document::from_bytes(b"data_a\n_x caf\xE9\n").unwrap_err(),
+            "E0003",
+        ),
+        (parse_error("data_a\n_x\n;never closed\n"), "E0004"),
+        (
+            Document::parse_with_options("data_a\n_x 1\n_x 2\n", &strict_duplicates).unwrap_err(),
+            "E0005",
+        ),
+        (parse_error("data_a\nloop_\n_x\n_y\n1 2 3\n"), "E0006"),
+        (
+            parse_error(&format!(
+                "#\\#CIF_2.0\ndata_a\n_x {}{}\n",
+                "[".repeat(1000),
+                "]".repeat(1000)
+            )),
+            "E0007",
+        ),
+        (parse_error("data_a\n_x 1\n2\n"), "E0008"),
+        (ElementSymbol::parse("Wat").unwrap_err(), "E0009"),
+        (
+            Loop::new(vec!["_a".to_string()])
+                .push_row(vec![Value::Unknown, Value::Unknown])
+                .unwrap_err(),
+            "E0010",
+        ),
+        (
+            Loop::new(vec!["_a".to_string()])
+                .partition_by("_b", true)
+                .unwrap_err(),
+            "E0011",
+        ),
+        (
+            Document::parse_with_options(
+                "data_a\n_x $y\n",
+                &ParseOptions::new().conformance(Conformance::Strict11),
+            )
+            .unwrap_err(),
+            "E0012",
+        ),
+        (Query::parse("block[").unwrap_err(), "E0013"),
+    ];
+    for (error, code) in cases {
+        assert_eq!(error.code(), code, "{error:?}");
+        let message = error.to_string();
+        let message = match error.path() {
+            Some(path) => message
+                .strip_prefix(&format!("{}: ", path.display()))
+                .unwrap()
+                .to_string(),
+            None => message,
+        };
+        assert!(message.starts_with(&format!("[{code}] ")), "{message}");
+    }
+}
+
+#[test]
+fn test_unclosed_text_field_is_unterminated_everywhere() {
+    let lenient = ParseOptions::new().lenient(true);
+    for input in ["data_a\n_t\n;\nabc\n", "data_a\n_t\n;abc\n_u 1\n"] {
+        let errors = [
+            parse_error(input),
+            CifDocumentRef::parse(input).unwrap_err(),
+            Document::parse_with_options(input, &lenient).unwrap_err(),
+        ];
+        for error in errors {
+            assert!(
+                matches!(
+                    &error,
+                    CifError::Unterminated { construct, location: Some((3, 1)), .. }
+                        if construct == "text field"
+                ),
+                "{input:?}: {error}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_variants_carry_context() {
+    let error = parse_error("data_a\n_x\n;never closed\n");
+    assert!(matches!(
+        &error,
+        CifError::Unterminated { construct, location: Some((3, 1)), .. } if construct == "text field"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "[E0004] Error at line 3, column 1: Unterminated text field"
+    );
+
+    let error = ElementSymbol::parse("Wat").unwrap_err();
+    assert!(matches!(
+        &error,
+        CifError::InvalidValue { kind: "atom type symbol", value } if value == "Wat"
+    ));
+    assert_eq!(error.to_string(), "[E0009] Invalid atom type symbol 'Wat'");
+    assert_eq!(error.location(), None);
+
+    // Grammar errors keep the parser's own rendering of the line
+    let error = parse_error("data_a\n_x 'a' 'b' 'c\n");
+    assert!(error.location().is_some());
+    assert!(error.to_string().starts_with("[E0001] Parse error: "));
+}
+
+#[test]
+fn test_file_errors_keep_code_and_chain_sources() {
+    let error = missing_file();
+    assert!(error.path().unwrap().ends_with("does-not-exist.cif"));
+    assert_eq!(error.code(), "E0002");
+
+    // InFile -> Io -> std::io::Error
+    let io = error.source().unwrap();
+    assert!(io.to_string().starts_with("[E0002] IO error: "));
+    let cause = io.source().unwrap();
+    assert_eq!(
+        cause.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::NotFound
+    );
+    assert!(cause.source().is_none());
+
+    // Errors found by the parser have no underlying cause
+    assert!(parse_error("data_a\n_x 1\n2\n").source().is_none());
+}
+
+#[test]
+fn test_offsets_and_excerpts_point_into_the_input() {
+    // A byte-order mark, CRLF line ends, and a multi-byte character before
+    // the error all count in bytes, not columns
+    let input = "\u{FEFF}data_é\r\n_x 1\r\n_y 'a' é\r\n";
+    let error = parse_error(input);
+    let (line, col) = error.location().unwrap();
+    let offset = error.byte_offset().unwrap();
+    assert_eq!(line, 3);
+    assert_eq!(
+        input[offset..].chars().count() + col - 1,
+        "_y 'a' é\r\n".chars().count()
+    );
+    let excerpt = error.excerpt().unwrap();
+    assert!(excerpt.starts_with("  |\n3 | _y 'a' é\n  | "), "{excerpt}");
+    assert!(excerpt.ends_with('^'));
+
+    // Recorded when raised, so a file's error has them without the file
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("error_span.cif");
+    std::fs::write(&path, "data_a\nloop_\n_x\n_y\n1 2 3\n").unwrap();
+    let error = Document::from_file(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(error.byte_offset(), Some(7));
+    assert_eq!(error.excerpt(), Some("  |\n2 | loop_\n  | ^"));
+
+    // Errors without a location, or located past the input, have neither
+    assert_eq!(missing_file().excerpt(), None);
+    let error = parse_error("data_a\nloop_\n_x\n_y\n1 2 3\n");
+    assert_eq!(error.with_source("data_a").byte_offset(), None);
+
+    // Errors from the tokenizer are given their input afterwards
+    let input = "data_a\n_x\n;never closed\n";
+    let error = token_error(input);
+    assert_eq!(error.byte_offset(), None);
+    assert_eq!(error.with_source(input).byte_offset(), Some(10));
+}
+
+#[test]
+fn test_cli_check_shows_the_line() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("error_excerpt.cif");
+    std::fs::write(&path, "data_a\nloop_\n_x\n_y\n1 2 3\n").unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cif"))
+        .arg("check")
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.ends_with("  |\n2 | loop_\n  | ^\n"), "{stderr}");
+}
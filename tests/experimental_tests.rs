@@ -0,0 +1,87 @@
+// tests/experimental_tests.rs
+// Experimental conditions in core and mmCIF spellings, and the density check
+
+use cif_parser::{Document, ParseOptions, Selection};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/experimental")
+        .join(name)
+}
+
+#[test]
+fn test_core_names() {
+    let doc = Document::from_file(fixture("core.cif")).unwrap();
+    let exptl = doc.first_block().unwrap().experimental();
+
+    assert_eq!(exptl.colour.as_deref(), Some("colourless"));
+    assert_eq!(exptl.habit.as_deref(), Some("plate"));
+    assert_eq!(
+        (exptl.size_min, exptl.size_mid, exptl.size_max),
+        (Some((0.08, None)), Some((0.25, None)), Some((0.42, None)))
+    );
+    assert!((exptl.crystal_volume().unwrap() - 0.0084).abs() < 1e-12);
+    assert_eq!(exptl.temperature, Some((150.0, Some(2.0))));
+    assert_eq!(exptl.absorption_mu, Some((0.099, None)));
+    assert_eq!(exptl.absorption_correction.as_deref(), Some("multi-scan"));
+    assert_eq!(
+        (exptl.t_min, exptl.t_max),
+        (Some((0.912, None)), Some((0.992, None)))
+    );
+    assert_eq!(exptl.density_measured, Some((1.31, Some(0.01))));
+    assert_eq!(exptl.density_calculated, Some((1.306, None)));
+    assert!((exptl.density_from_formula.unwrap() - 1.3061).abs() < 1e-4);
+    assert!(exptl.check().is_empty());
+}
+
+#[test]
+fn test_mmcif_names_and_discrepancies() {
+    let doc = Document::from_file(fixture("mmcif.cif")).unwrap();
+    let exptl = doc.first_block().unwrap().experimental();
+
+    assert_eq!(exptl.habit.as_deref(), Some("prism"));
+    assert_eq!(exptl.temperature, Some((100.0, Some(1.0))));
+    assert_eq!(exptl.absorption_correction.as_deref(), Some("none"));
+    // The volume comes from the cell parameters
+    assert!((exptl.density_from_formula.unwrap() - 0.8028).abs() < 1e-4);
+
+    let issues: Vec<String> = exptl.check().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        issues,
+        [
+            "_exptl_crystal_density_diffrn = 1.45: density must be within 1% of 0.803 g/cm³ \
+             from the formula weight, Z, and cell volume",
+            "_exptl_absorpt_correction_T_min = 0.98: T_min must not exceed T_max (0.95)",
+        ]
+    );
+}
+
+#[test]
+fn test_missing_size_fields() {
+    let path = "tests/example_cifs/abiraterone_acetate/abiraterone_acetate.cif";
+    let options = ParseOptions::new().select(Selection::Blocks(vec![
+        "abiraterone_acetate_dynamical".to_string(),
+    ]));
+    let doc = Document::from_file_with_options(path, &options).unwrap();
+    let exptl = doc.first_block().unwrap().experimental();
+
+    // Written as ?, like the colour and absorption correction
+    assert_eq!(
+        (exptl.size_min, exptl.size_mid, exptl.size_max),
+        (None, None, None)
+    );
+    assert_eq!(exptl.crystal_volume(), None);
+    assert_eq!(exptl.colour, None);
+    assert_eq!(exptl.absorption_correction, None);
+    assert_eq!(exptl.temperature, Some((100.0, None)));
+    assert_eq!(exptl.density_calculated, Some((1.1897, None)));
+    assert!(exptl.check().is_empty());
+
+    let partial = Document::parse("data_x\n_exptl_crystal_size_max 0.3\n").unwrap();
+    let exptl = partial.first_block().unwrap().experimental();
+    assert_eq!(exptl.size_max, Some((0.3, None)));
+    assert_eq!(exptl.crystal_volume(), None);
+    assert_eq!(exptl.density_from_formula, None);
+    assert!(exptl.check().is_empty());
+}
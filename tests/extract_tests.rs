@@ -0,0 +1,111 @@
+// tests/extract_tests.rs
+// Extracting single-valued tags from many files, with errors kept inline
+
+use cif_parser::corpus::{cif_files, extract_csv};
+use cif_parser::{extract_items, ExtractOptions, Value};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+const TAGS: [&str; 3] = [
+    "_cell_volume",
+    "_space_group_IT_number",
+    "_chemical_name_common",
+];
+
+#[test]
+fn test_one_record_per_block() {
+    let records = extract_items(
+        &[fixture("extract_blocks.cif")],
+        &TAGS,
+        &ExtractOptions::new(),
+    );
+    assert_eq!(records.len(), 2);
+
+    let first = &records[0];
+    assert_eq!(first.block.as_deref(), Some("first"));
+    assert_eq!(first.values.keys().collect::<Vec<_>>(), TAGS);
+    assert_eq!(
+        first.values["_cell_volume"]
+            .as_ref()
+            .unwrap()
+            .as_numeric_with_su(),
+        Some((325.4, Some(0.4)))
+    );
+    // Matched ignoring case
+    assert_eq!(
+        first.values["_space_group_IT_number"],
+        Some(Value::Numeric(14.0))
+    );
+
+    // A one-row loop gives its value; a longer loop gives none
+    let second = &records[1];
+    assert_eq!(second.values["_cell_volume"], Some(Value::Numeric(410.2)));
+    assert_eq!(second.values["_space_group_IT_number"], None);
+    assert_eq!(second.values["_chemical_name_common"], None);
+}
+
+#[test]
+fn test_first_block_only() {
+    let options = ExtractOptions::new().first_block_only(true);
+    let records = extract_items(&[fixture("extract_blocks.cif")], &TAGS, &options);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].block.as_deref(), Some("first"));
+}
+
+#[test]
+fn test_errors_do_not_stop_the_batch() {
+    let paths = [
+        fixture("missing.cif"),
+        fixture("encodings/utf16le_crlf.cif"),
+        fixture("extract_blocks.cif"),
+    ];
+    let records = extract_items(&paths, &TAGS, &ExtractOptions::new().threads(2));
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].path, paths[0]);
+    assert!(records[0].error.is_some());
+    assert!(records[1]
+        .error
+        .as_ref()
+        .unwrap()
+        .to_string()
+        .contains("UTF-16"));
+    assert!(records[1].values.values().all(Option::is_none));
+    assert!(records[2..].iter().all(|record| record.error.is_none()));
+
+    // Lenient parsing transcodes the UTF-16 file
+    let records = extract_items(&paths[1..2], &TAGS, &ExtractOptions::new().lenient(true));
+    assert_eq!(records[0].block.as_deref(), Some("encoding"));
+}
+
+#[test]
+fn test_threads_do_not_change_the_result() {
+    let files =
+        cif_files(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs")).unwrap();
+    let serial = extract_items(&files, &TAGS, &ExtractOptions::new().threads(1));
+    let parallel = extract_items(&files, &TAGS, &ExtractOptions::new().threads(4));
+    assert_eq!(extract_csv(&serial, &TAGS), extract_csv(&parallel, &TAGS));
+    assert!(serial
+        .iter()
+        .any(|record| record.values["_cell_volume"].is_some()));
+}
+
+#[test]
+fn test_csv() {
+    let paths = [fixture("extract_blocks.cif")];
+    let records = extract_items(&paths, &TAGS, &ExtractOptions::new());
+    let csv = extract_csv(&records, &TAGS);
+    let path = paths[0].display();
+    assert_eq!(
+        csv,
+        format!(
+            "path,block,_cell_volume,_space_group_IT_number,_chemical_name_common,error\n\
+             {path},first,325.4(4),14,two words,\n\
+             {path},second,410.2,,,\n"
+        )
+    );
+}
@@ -0,0 +1,234 @@
+// tests/flatten_tests.rs
+// Flat key-value entries for search indexing, and finding them in the file
+
+use cif_parser::corpus::cif_files;
+use cif_parser::flatten::{locate, FlatEntry, FlattenOptions, ValueKind, TRUNCATION_MARK};
+use cif_parser::{uncertainty, Document};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/flatten/search.cif")
+}
+
+fn entry<'a>(entries: &'a [FlatEntry], path: &str) -> &'a FlatEntry {
+    entries
+        .iter()
+        .find(|e| e.path == path)
+        .unwrap_or_else(|| panic!("no entry {path}"))
+}
+
+/// The text from a 1-based line and column to the end
+fn at(content: &str, (line, col): (usize, usize)) -> String {
+    let rest: Vec<&str> = content.split_inclusive('\n').skip(line - 1).collect();
+    rest.concat().chars().skip(col - 1).collect()
+}
+
+#[test]
+fn test_paths_and_values() {
+    let doc = Document::from_file(fixture()).unwrap();
+    let entries = doc.flatten(&FlattenOptions::new());
+
+    let a = entry(&entries, "Sample_1/_cell_length_a");
+    assert_eq!((a.text.as_str(), a.number), ("6.1224(4)", Some(6.1224)));
+    assert_eq!(
+        (a.kind, a.row, a.frame.as_deref()),
+        (ValueKind::Numeric, None, None)
+    );
+
+    let volume = entry(&entries, "Sample_1/_cell_volume");
+    assert_eq!(
+        (volume.text.as_str(), volume.kind),
+        ("362.13", ValueKind::Numeric)
+    );
+    assert_eq!(volume.number, Some(362.13));
+
+    let unknown = entry(&entries, "Sample_1/_diffrn_ambient_temperature");
+    assert_eq!(
+        (unknown.text.as_str(), unknown.kind),
+        ("?", ValueKind::Unknown)
+    );
+    assert_eq!(unknown.number, None);
+    let dot = entry(&entries, "Sample_1/_refine_ls_hydrogen_treatment");
+    assert_eq!(dot.kind, ValueKind::NotApplicable);
+
+    let list = entry(&entries, "Sample_1/_atom_type_list");
+    assert_eq!(
+        (list.text.as_str(), list.kind),
+        ("[Cu S O H]", ValueKind::List)
+    );
+
+    let label = entry(&entries, "Sample_1/_atom_site_label[2]");
+    assert_eq!((label.text.as_str(), label.row), ("O1", Some(2)));
+    let x = entry(&entries, "Sample_1/_atom_site_fract_x[1]");
+    assert_eq!((x.kind, x.number), (ValueKind::Numeric, Some(0.0137)));
+
+    let frame = entry(&entries, "Sample_1/save_scan_settings/_scan_exposure_time");
+    assert_eq!(frame.frame.as_deref(), Some("scan_settings"));
+    assert_eq!(frame.tag, "_scan_exposure_time");
+
+    assert_eq!(entry(&entries, "second/_Cell_Length_A").number, Some(3.5));
+}
+
+#[test]
+fn test_repeated_loop_tag_continues_rows() {
+    let doc = Document::from_file(fixture()).unwrap();
+    let entries = doc.flatten(&FlattenOptions::new());
+
+    let ids: Vec<(&str, &str)> = entries
+        .iter()
+        .filter(|e| e.tag == "_diffrn_scan_id")
+        .map(|e| (e.path.as_str(), e.text.as_str()))
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            ("Sample_1/_diffrn_scan_id[0]", "1"),
+            ("Sample_1/_diffrn_scan_id[1]", "2"),
+            ("Sample_1/_diffrn_scan_id[2]", "3"),
+        ]
+    );
+    assert_eq!(
+        entry(&entries, "Sample_1/_diffrn_scan_axis[0]").text,
+        "omega"
+    );
+}
+
+#[test]
+fn test_options() {
+    let doc = Document::from_file(fixture()).unwrap();
+
+    let items = doc.flatten(&FlattenOptions::new().include_loops(false));
+    assert!(items.iter().all(|e| e.row.is_none()));
+    assert_eq!(items.len(), 9);
+
+    let lower = doc.flatten(&FlattenOptions::new().lowercase_tags(true));
+    let a = entry(&lower, "second/_cell_length_a");
+    assert_eq!(a.tag, "_cell_length_a");
+    // Block names are kept as written
+    assert!(lower.iter().any(|e| e.path.starts_with("Sample_1/")));
+
+    let short = doc.flatten(&FlattenOptions::new().max_text_len(10));
+    let abstract_ = entry(&short, "Sample_1/_publ_section_abstract");
+    assert!(abstract_.truncated);
+    assert_eq!(abstract_.text, format!("Blue tricl{TRUNCATION_MARK}"));
+    let name = entry(&short, "Sample_1/_chemical_name_common");
+    assert_eq!(
+        name.text.chars().count(),
+        10 + TRUNCATION_MARK.chars().count()
+    );
+    let volume = entry(&short, "Sample_1/_cell_volume");
+    assert!(!volume.truncated);
+}
+
+#[test]
+fn test_ndjson() {
+    let doc = Document::from_file(fixture()).unwrap();
+    let options = FlattenOptions::new();
+    let mut out = Vec::new();
+    doc.to_ndjson(&mut out, &options).unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let entries = doc.flatten(&options);
+    assert_eq!(lines.len(), entries.len());
+    assert!(text.ends_with('\n'));
+
+    let volume = lines
+        .iter()
+        .find(|line| line["path"] == "Sample_1/_cell_volume")
+        .unwrap();
+    assert_eq!(volume["number"], 362.13);
+    assert_eq!(volume["kind"], "numeric");
+    assert_eq!(volume["row"], serde_json::Value::Null);
+    assert_eq!(volume["truncated"], false);
+}
+
+#[test]
+fn test_located_values_match() {
+    let content = fs::read_to_string(fixture()).unwrap();
+    let doc = Document::parse(&content).unwrap();
+    let entries = doc.flatten(&FlattenOptions::new().lowercase_tags(true));
+    let locations = locate(&content, &entries);
+
+    for (entry, location) in entries.iter().zip(&locations) {
+        if entry.frame.is_some() {
+            assert_eq!(*location, None);
+            continue;
+        }
+        let location = location.unwrap_or_else(|| panic!("{} not found", entry.path));
+        let source = at(&content, location);
+        match entry.kind {
+            ValueKind::Text | ValueKind::Unknown | ValueKind::NotApplicable => {
+                let unquoted = source.trim_start_matches(['\'', '"', ';']).trim_start();
+                let first_line = entry.text.trim_start().lines().next().unwrap_or_default();
+                assert!(
+                    source.starts_with(&entry.text) || unquoted.starts_with(first_line),
+                    "{} at {location:?}: {source}",
+                    entry.path
+                );
+            }
+            ValueKind::Numeric => {
+                let token = source.split_whitespace().next().unwrap();
+                let token = token.trim_matches(['\'', '"']);
+                let number = uncertainty::parse(token).map(|(number, _)| number);
+                assert_eq!(number, entry.number, "{}", entry.path);
+            }
+            ValueKind::List | ValueKind::Table => assert!(source.starts_with(['[', '{'])),
+        }
+    }
+    // The third scan id is the first row of the second loop
+    let repeated = entries
+        .iter()
+        .position(|e| e.path == "Sample_1/_diffrn_scan_id[2]")
+        .unwrap();
+    assert_eq!(locations[repeated], Some((25, 2)));
+}
+
+#[test]
+fn test_fixture_paths_are_unique_and_located() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut checked = 0;
+    for path in cif_files(root).unwrap() {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(doc) = Document::parse(&content) else {
+            continue;
+        };
+        let entries = doc.flatten(&FlattenOptions::new());
+        let mut paths = HashSet::new();
+        let mut blocks = HashSet::new();
+        for block in &doc.blocks {
+            // Paths can only be unique within distinct block names
+            blocks.insert(block.name.to_lowercase());
+        }
+        if blocks.len() < doc.blocks.len() {
+            continue;
+        }
+        for entry in &entries {
+            assert!(
+                paths.insert(entry.path.to_lowercase()),
+                "{}: {} twice",
+                path.display(),
+                entry.path
+            );
+        }
+        let located = locate(&content, &entries);
+        for (entry, location) in entries.iter().zip(located) {
+            assert_eq!(
+                location.is_some(),
+                entry.frame.is_none(),
+                "{}: {}",
+                path.display(),
+                entry.path
+            );
+        }
+        checked += 1;
+    }
+    assert!(checked > 50);
+}
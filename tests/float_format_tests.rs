@@ -0,0 +1,248 @@
+// tests/float_format_tests.rs
+// Writing computed numbers: shortest round-trip, rounding rules, and non-finite values
+
+use cif_parser::writer::CifWriter;
+use cif_parser::{Document, FloatFormat, NumberFormat, Value, WriteOptions};
+use std::path::Path;
+
+fn fixture() -> Document {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/float_format/refined.cif");
+    Document::from_file(path).unwrap()
+}
+
+/// The fixture after a refinement has replaced its numbers with computed ones
+fn refined() -> Document {
+    let mut doc = fixture();
+    let block = &mut doc.blocks[0];
+    block
+        .items
+        .insert("_cell_length_a".into(), Value::Numeric(10.523456789));
+    block
+        .items
+        .insert("_cell_angle_beta".into(), Value::Numeric(101.123456789));
+    block
+        .items
+        .insert("_cell_volume".into(), Value::Numeric(1234.56789));
+    let atoms = &mut block.loops[0];
+    atoms.values[0][1] = Value::Numeric(0.123456789);
+    atoms.values[0][3] = Value::Numeric(0.987654321);
+    atoms.values[1][1] = Value::Numeric(1.0 / 3.0);
+    atoms.values[1][4] = Value::Numeric(0.0234567);
+    doc
+}
+
+fn written(doc: &Document, format: FloatFormat) -> String {
+    doc.to_string_with_options(&WriteOptions::new().float_format(format))
+}
+
+#[test]
+fn test_shortest_form_round_trips_bit_for_bit() {
+    let numbers = [
+        0.1 + 0.2,
+        1.0 / 3.0,
+        -0.0,
+        1e22,
+        123456789012345680.0,
+        1e-300,
+        f64::MIN_POSITIVE,
+        5e-324,
+        f64::MAX,
+        -2.5e-8,
+    ];
+    let mut doc = Document::parse("data_x\n").unwrap();
+    for (i, &n) in numbers.iter().enumerate() {
+        doc.blocks[0]
+            .items
+            .insert(format!("_value_{i}"), Value::Numeric(n));
+    }
+
+    let read = Document::parse(&doc.to_string()).unwrap();
+    for (i, &n) in numbers.iter().enumerate() {
+        let value = read.blocks[0].get_item(&format!("_value_{i}")).unwrap();
+        let back = value.as_numeric().unwrap();
+        assert_eq!(back.to_bits(), n.to_bits(), "{n:?} came back as {back:?}");
+    }
+}
+
+#[test]
+fn test_numbers_with_su_read_back_at_any_scale() {
+    let cif = "data_x\n_tiny 3.1e-12(2)\n_huge 6.02214076e23(1)\n\
+               _precise 1.23456789012(3)\n_plain 5.4310(15)\n";
+    let doc = Document::parse(cif).unwrap();
+    let written = doc.to_string();
+    assert!(written.contains("_tiny 3.1e-12(2)\n"), "{written}");
+    assert!(written.contains("_precise 1.23456789012(3)\n"), "{written}");
+
+    let read = Document::parse(&written).unwrap();
+    for (tag, value) in &doc.blocks[0].items {
+        assert!(matches!(value, Value::NumericSu { .. }), "{tag}");
+        assert_eq!(read.blocks[0].get_item(tag), Some(value), "{tag}");
+    }
+
+    // Computed pairs too, with every written value and su read back exactly
+    let mut doc = Document::parse("data_x\n").unwrap();
+    let pairs = [(-4.5e-300, 1e-301), (1.5e300, 2.5e299), (0.1 + 0.2, 1e-17)];
+    for (i, &(value, su)) in pairs.iter().enumerate() {
+        doc.blocks[0]
+            .items
+            .insert(format!("_value_{i}"), Value::NumericSu { value, su });
+    }
+    let read = Document::parse(&doc.to_string()).unwrap();
+    assert_eq!(read.blocks[0].items, doc.blocks[0].items);
+}
+
+#[test]
+fn test_each_mode() {
+    let doc = refined();
+
+    let text = written(&doc, FloatFormat::new());
+    assert!(text.contains("_cell_length_a 10.523456789\n"));
+
+    let text = written(
+        &doc,
+        FloatFormat::new().default_format(NumberFormat::Decimals(2)),
+    );
+    assert!(text.contains("_cell_length_a 10.52\n"));
+    assert!(text.contains("_diffrn_radiation_wavelength 0.71\n"));
+    assert!(text.contains("C1 0.12 0.00 0.99 0.01\n"));
+
+    let text = written(
+        &doc,
+        FloatFormat::new().default_format(NumberFormat::Significant(3)),
+    );
+    assert!(text.contains("_cell_length_a 10.5\n"));
+    assert!(text.contains("_cell_volume 1.23e3\n"));
+    assert!(text.contains("_cell_length_a_su 0.00120\n"));
+    assert!(text.contains("O1 0.333 0.000400 0.400 0.0235\n"));
+
+    // Rounded to the su: 0.0012 keeps two digits, 0.00023 and 0.0004 one
+    let text = written(&doc, FloatFormat::new().su_rounding(true));
+    assert!(text.contains("_cell_length_a 10.5235\n"));
+    assert!(text.contains("_cell_angle_beta 101.123456789\n"));
+    assert!(text.contains("C1 0.1235 0.00023 0.987654321 0.01\n"));
+    assert!(text.contains("O1 0.3333 0.0004 0.4 0.0234567\n"));
+
+    // Each written form reads back as a number
+    let read = Document::parse(&text).unwrap();
+    assert_eq!(
+        read.blocks[0]
+            .get_item("_cell_length_a")
+            .unwrap()
+            .as_numeric(),
+        Some(10.5235)
+    );
+}
+
+#[test]
+fn test_tag_map_precedence() {
+    let doc = refined();
+    let format = FloatFormat::new()
+        .default_format(NumberFormat::Significant(2))
+        .su_rounding(true)
+        .tag("_atom_site_*", NumberFormat::Decimals(3))
+        .tag("_atom_site_fract_*", NumberFormat::Decimals(5))
+        .tag("_cell_*", NumberFormat::Decimals(1))
+        .tag("_CELL_ANGLE_BETA", NumberFormat::Decimals(4))
+        .tag("_cell_angle_*", NumberFormat::Decimals(0));
+    let text = written(&doc, format);
+
+    // An exact tag beats any pattern, and a longer pattern a shorter one
+    assert!(text.contains("_cell_angle_beta 101.1235\n"));
+    assert!(text.contains("C1 0.12346 0.00023 0.98765 0.010\n"));
+    // The tag map beats su rounding, which beats the default
+    assert!(text.contains("_cell_length_a 10.5\n"));
+    assert!(text.contains("_diffrn_radiation_wavelength 0.71\n"));
+
+    // Of equally long patterns, the first added wins
+    assert_eq!(
+        FloatFormat::new()
+            .tag("_a*", NumberFormat::Decimals(1))
+            .tag("_a?", NumberFormat::Decimals(2))
+            .format_for("_ab"),
+        Some(NumberFormat::Decimals(1))
+    );
+    assert_eq!(FloatFormat::new().format_for("_ab"), None);
+}
+
+#[test]
+fn test_non_finite_numbers_are_rejected() {
+    let mut doc = refined();
+    assert!(doc.check_integrity().is_ok());
+
+    doc.blocks[0].loops[0].values[1][3] = Value::Numeric(f64::INFINITY);
+    let error = doc.check_integrity().unwrap_err();
+    assert_eq!(error.code(), "E0008");
+    assert_eq!(
+        error.to_string(),
+        "[E0008] Invalid CIF structure: _atom_site_fract_y in row 2 of data_refined is infinite, which CIF cannot represent"
+    );
+    // The writer never writes them
+    let text = doc.to_string();
+    assert!(text.contains("O1 0.3333333333333333 0.0004 ? 0.0234567\n"));
+    assert!(!text.to_lowercase().contains("inf"));
+
+    doc.blocks[0].loops[0].values[1][3] = Value::Numeric(0.4);
+    doc.blocks[0].items.insert(
+        "_refine_values".into(),
+        Value::List(vec![Value::Numeric(1.0), Value::Numeric(f64::NAN)]),
+    );
+    let error = doc.check_integrity().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("_refine_values in data_refined is NaN"));
+    assert!(written(&doc, FloatFormat::new()).contains("_refine_values [1 ?]\n"));
+
+    // Rust reads `nan` and `inf` as numbers, so parsed text can hold them too
+    let parsed = Document::parse("data_x\n_cell_volume inf\n").unwrap();
+    assert!(parsed.check_integrity().is_err());
+}
+
+#[test]
+fn test_to_file_writes_only_representable_documents() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("float_format_to_file.cif");
+    let mut doc = refined();
+    doc.to_file(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), doc.to_string());
+    std::fs::remove_file(&path).unwrap();
+
+    doc.blocks[0]
+        .items
+        .insert("_cell_volume".into(), Value::Numeric(f64::NAN));
+    assert_eq!(doc.to_file(&path).unwrap_err().code(), "E0008");
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_streaming_writer_uses_float_format() {
+    let format = FloatFormat::new()
+        .tag("_refln_F_*", NumberFormat::Decimals(2))
+        .su_rounding(true);
+    let mut writer = CifWriter::new(Vec::new(), WriteOptions::new().float_format(format));
+    writer.begin_block("hkl").unwrap();
+    writer
+        .write_item("_diffrn_radiation_wavelength", &Value::Numeric(0.71073))
+        .unwrap();
+    writer
+        .begin_loop(&[
+            "_refln_index_h",
+            "_refln_F_meas",
+            "_refln_phase",
+            "_refln_phase_su",
+        ])
+        .unwrap();
+    writer
+        .write_row(&[
+            Value::Numeric(1.0),
+            Value::Numeric(152.346),
+            Value::Numeric(12.3456),
+            Value::Numeric(0.25),
+        ])
+        .unwrap();
+    writer.end_loop().unwrap();
+    writer.end_block().unwrap();
+    let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    assert!(text.contains("_diffrn_radiation_wavelength 0.71073\n"));
+    assert!(text.contains("1 152.35 12.3 0.25\n"));
+}
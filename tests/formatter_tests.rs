@@ -0,0 +1,204 @@
+// tests/formatter_tests.rs
+// Canonical formatting: idempotent, semantically lossless, and comments kept
+
+use cif_parser::corpus::cif_files;
+use cif_parser::formatter::{format_str, is_formatted, FormatOptions};
+use cif_parser::lexer::{TokenKind, Tokenizer};
+use cif_parser::testing::arb_document;
+use cif_parser::{Document, Version, WriteOptions};
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/formatter")
+        .join(name)
+}
+
+/// Comments of CIF text, without the version line
+fn comments(text: &str) -> Vec<String> {
+    Tokenizer::new(text)
+        .map(Result::unwrap)
+        .filter(|t| t.kind == TokenKind::Comment && !t.text.starts_with("#\\#CIF_"))
+        .map(|t| t.text.trim_end().to_string())
+        .collect()
+}
+
+/// Format `input` twice, and check that the second pass changes nothing,
+/// that the meaning is kept, and that no comment is lost
+fn check_format(input: &str) -> Result<String, String> {
+    let options = FormatOptions::new();
+    let once = format_str(input, &options).map_err(|e| e.to_string())?;
+    let twice = format_str(&once, &options).map_err(|e| e.to_string())?;
+    if twice != once {
+        return Err(format!(
+            "not idempotent\n--- once ---\n{once}--- twice ---\n{twice}"
+        ));
+    }
+    let original = Document::parse(input).unwrap();
+    if !Document::parse(&once)
+        .unwrap()
+        .semantically_equal(&original)
+    {
+        return Err(format!("meaning changed\n--- formatted ---\n{once}"));
+    }
+    let (mut before, mut after) = (comments(input), comments(&once));
+    before.sort();
+    after.sort();
+    if before != after {
+        return Err(format!("comments changed\n--- formatted ---\n{once}"));
+    }
+    Ok(once)
+}
+
+#[test]
+fn test_commented_fixture() {
+    let input = fs::read_to_string(fixture("commented.cif")).unwrap();
+    let formatted = check_format(&input).unwrap();
+    let expected = fs::read_to_string(fixture("commented_formatted.cif")).unwrap();
+    assert_eq!(formatted, expected);
+
+    assert!(is_formatted(&expected, &FormatOptions::new()).unwrap());
+    assert!(!is_formatted(&input, &FormatOptions::new()).unwrap());
+}
+
+#[test]
+fn test_comments_in_lists_and_rows() {
+    let input = fs::read_to_string(fixture("lists.cif")).unwrap();
+    let formatted = check_format(&input).unwrap();
+
+    // Comments inside a list go before its item or row
+    assert!(formatted.contains("# inside a list\n_tags [a b c]\n"));
+    assert!(formatted.contains("# second row\n# trailing\n2 "));
+    assert!(formatted.contains("# in a list in the third row\n3 [4 5]\n"));
+}
+
+#[test]
+fn test_alignment() {
+    let input = "data_x\n_b 2\n_long_tag 1\nloop_\n_p\n_q\n1 2\n333 'a b'\n";
+    let aligned = format_str(input, &FormatOptions::new()).unwrap();
+    assert!(aligned.contains("_b        2\n_long_tag 1\n"));
+    assert!(aligned.contains("\n1   2\n333 'a b'\n"));
+
+    let plain = format_str(input, &FormatOptions::new().align(false)).unwrap();
+    assert!(plain.contains("_b 2\n_long_tag 1\n"));
+    assert!(plain.contains("\n1 2\n333 'a b'\n"));
+}
+
+#[test]
+fn test_formatter_drives_the_writer() {
+    // Without alignment or comments, only blank lines set it apart
+    let options = FormatOptions::new().align(false).comments(false);
+    let input = fs::read_to_string(fixture("commented.cif")).unwrap();
+    let formatted = format_str(&input, &options).unwrap();
+    let written = Document::parse(&input)
+        .unwrap()
+        .to_string_with_options(&WriteOptions::new().sort_items(true));
+
+    let lines = |text: &str| -> Vec<String> {
+        text.lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    assert_eq!(lines(&formatted), lines(&written));
+    assert!(comments(&formatted).is_empty());
+}
+
+/// Larger example files take seconds each to parse in debug builds
+const MAX_CORPUS_FILE: u64 = 64 * 1024;
+
+#[test]
+fn test_fixture_corpus_is_idempotent() {
+    let mut checked = 0;
+    for root in ["tests/fixtures", "tests/example_cifs"] {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(root);
+        for path in cif_files(root).unwrap() {
+            if fs::metadata(&path).unwrap().len() > MAX_CORPUS_FILE {
+                continue;
+            }
+            let Ok(input) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if Document::parse(&input).is_err() {
+                continue;
+            }
+            if let Err(message) = check_format(&input) {
+                panic!("{}: {message}", path.display());
+            }
+            checked += 1;
+        }
+    }
+    assert!(checked > 60);
+}
+
+/// Put a comment before every heading, tag, and loop row of CIF 1.1 text,
+/// and after some values
+fn with_comments(text: &str) -> String {
+    let mut out = String::new();
+    let mut in_text_field = false;
+    for (i, line) in text.lines().enumerate() {
+        if line.starts_with(';') {
+            in_text_field = !in_text_field;
+        } else if !in_text_field && !line.is_empty() && !line.starts_with('#') {
+            out.push_str(&format!("# comment {i}\n"));
+            if i % 3 == 0 {
+                out.push_str(&format!("{line} # trailing {i}\n"));
+                continue;
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn test_generated_documents() {
+    let config = Config {
+        cases: 128,
+        failure_persistence: None,
+        ..Config::default()
+    };
+    let mut runner = TestRunner::new(config);
+    runner
+        .run(&arb_document(), |doc| {
+            let text = doc.to_string();
+            check_format(&text).map_err(TestCaseError::fail)?;
+            // Multi-line strings could swallow the comments in CIF 2.0
+            if doc.version == Version::V1_1 {
+                check_format(&with_comments(&text)).map_err(TestCaseError::fail)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_cli_check_and_write() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("unformatted.cif");
+    fs::copy(fixture("commented.cif"), &path).unwrap();
+    let cif = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cif"))
+            .args(args)
+            .arg(&path)
+            .output()
+            .unwrap()
+    };
+
+    let check = cif(&["fmt", "--check"]);
+    assert_eq!(check.status.code(), Some(1));
+    assert!(String::from_utf8(check.stdout)
+        .unwrap()
+        .contains("not formatted"));
+
+    assert!(cif(&["fmt", "--write"]).status.success());
+    let expected = fs::read_to_string(fixture("commented_formatted.cif")).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+    assert!(cif(&["fmt", "--check"]).status.success());
+
+    let printed = cif(&["fmt"]);
+    assert_eq!(String::from_utf8(printed.stdout).unwrap(), expected);
+}
@@ -0,0 +1,82 @@
+// tests/fragment_tests.rs
+// Dictionary fragments written as bare save frames, with no data block
+
+use cif_parser::dictionary::CifDictionary;
+use cif_parser::{Document, ParseOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fragment/diffrn_extras.dic")
+}
+
+#[test]
+fn test_bare_frames_need_permission() {
+    let error = Document::from_file(fixture()).unwrap_err();
+    assert_eq!(error.location(), Some((5, 1)));
+    assert!(error.to_string().contains("allow_bare_frames"));
+
+    let lenient = Document::from_file_with_options(fixture(), &ParseOptions::new().lenient(true));
+    assert!(lenient.unwrap().bare_frames);
+}
+
+#[test]
+fn test_fragment_frames_go_in_a_block_named_after_the_file() {
+    let options = ParseOptions::new().allow_bare_frames(true);
+    let doc = Document::from_file_with_options(fixture(), &options).unwrap();
+
+    assert!(doc.bare_frames);
+    assert_eq!(doc.blocks.len(), 1);
+    let block = &doc.blocks[0];
+    assert_eq!(block.name, "diffrn_extras");
+    assert!(block.items.is_empty());
+    let frames: Vec<&str> = block.frames.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(frames, ["diffrn.oven_setpoint", "diffrn.oven_atmosphere"]);
+
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].location, Some((5, 1)));
+
+    // Parsed from text, the block has the fixed name
+    let text = fs::read_to_string(fixture()).unwrap();
+    let doc = Document::parse_with_options(&text, &options).unwrap();
+    assert_eq!(doc.blocks[0].name, "_bare_frames_");
+}
+
+#[test]
+fn test_round_trip_keeps_bare_layout() {
+    let options = ParseOptions::new().allow_bare_frames(true);
+    let doc = Document::from_file_with_options(fixture(), &options).unwrap();
+
+    let written = doc.to_string();
+    assert!(!written.contains("data_"));
+    assert!(written.starts_with("#\\#CIF_2.0\n\nsave_diffrn.oven_setpoint\n"));
+
+    let reread = Document::parse_with_options(&written, &options).unwrap();
+    assert!(reread.bare_frames);
+    assert_eq!(reread.blocks[0].frames.len(), 2);
+    assert_eq!(reread.to_string(), written);
+
+    // Once the block has items of its own, it needs its heading
+    let mut edited = doc.clone();
+    edited.blocks[0].items.insert(
+        "_dictionary.title".into(),
+        cif_parser::Value::Text("x".into()),
+    );
+    assert!(edited.to_string().contains("data_diffrn_extras\n"));
+}
+
+#[test]
+fn test_dictionary_reads_fragments() {
+    let dictionary = CifDictionary::from_file(fixture()).unwrap();
+    assert_eq!(dictionary.title, "diffrn_extras");
+    assert_eq!(dictionary.len(), 2);
+
+    let setpoint = dictionary.get("_diffrn_oven_setpoint").unwrap();
+    assert_eq!(setpoint.id, "_diffrn.oven_setpoint");
+    assert_eq!(setpoint.units.as_deref(), Some("kelvins"));
+
+    let text = fs::read_to_string(fixture()).unwrap();
+    let parsed = CifDictionary::parse(&text).unwrap();
+    assert_eq!(parsed.title, "_bare_frames_");
+    assert!(parsed.get("_diffrn.oven_atmosphere").is_some());
+}
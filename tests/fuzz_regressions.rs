@@ -0,0 +1,104 @@
+// tests/fuzz_regressions.rs
+// Distilled inputs from fuzzing: every one must produce Ok or Err, never a panic
+
+use cif_parser::{CifValue, Document, ParseOptions};
+
+/// Parse in both strict and lenient mode; reaching the end means no panic occurred
+fn parse_both(input: &str) -> bool {
+    let strict = Document::parse(input);
+    let lenient = Document::parse_with_options(input, &ParseOptions::new().lenient(true));
+    strict.is_ok() || lenient.is_ok()
+}
+
+#[test]
+fn test_input_ending_mid_token() {
+    for input in [
+        "data_a\n_x 'unterminated",
+        "data_a\n_x \"unterminated",
+        "data_a\n_x\n;unterminated text field",
+        "#\\#CIF_2.0\ndata_a\n_x '''unterminated",
+        "#\\#CIF_2.0\ndata_a\n_x [1 2",
+        "#\\#CIF_2.0\ndata_a\n_x {'k':",
+        "data_",
+        "data_a\nsave_",
+        "data_a\nloop_",
+    ] {
+        parse_both(input);
+    }
+}
+
+#[test]
+fn test_lone_carriage_returns() {
+    assert!(parse_both("data_a\r_x 1\r_y 2\r"));
+    assert!(parse_both("data_a\r_x\r;text\r;\r"));
+    parse_both("\r");
+    parse_both("data_a\r\r\r_x");
+}
+
+#[test]
+fn test_tag_as_last_byte() {
+    parse_both("data_a\n_");
+    parse_both("data_a\n_x");
+    parse_both("data_a\nloop_\n_x");
+}
+
+#[test]
+fn test_loop_with_zero_tags() {
+    assert!(Document::parse("data_a\nloop_\n").is_err());
+    assert!(Document::parse("data_a\nloop_\n1 2 3\n").is_err());
+    parse_both("data_a\nloop_ loop_ loop_");
+}
+
+#[test]
+fn test_multibyte_utf8_adjacent_to_delimiters() {
+    for input in [
+        "data_é\n_x é\n",
+        "data_a\n_é 'é'\n",
+        "data_a\n_x 'é'é\n",
+        "data_a\n_x\n;é\n;é\n",
+        "data_a\n_x \"日本\"\n",
+        "#\\#CIF_2.0\ndata_a\n_x [é]\n",
+        "#\\#CIF_2.0\ndata_a\n_x {'é':é}\n",
+        "DATA_\u{212a}\n_x 1\n",
+        "\u{feff}data_a\n_x 1\n",
+    ] {
+        parse_both(input);
+    }
+}
+
+#[test]
+fn test_deeply_nested_lists_are_rejected_not_overflowed() {
+    let depth = 100_000;
+    let input = format!(
+        "#\\#CIF_2.0\ndata_a\n_x {}{}\n",
+        "[".repeat(depth),
+        "]".repeat(depth)
+    );
+    assert!(Document::parse(&input).is_err());
+
+    let unterminated = format!("data_a\n_x {}\n", "{".repeat(depth));
+    assert!(Document::parse(&unterminated).is_err());
+}
+
+#[test]
+fn test_huge_repeated_values() {
+    let values = "1 ".repeat(200_001);
+    let input = format!("data_a\nloop_\n_x\n_y\n{values}\n");
+    // Odd value count for two tags
+    assert!(Document::parse(&input).is_err());
+
+    let long_value = "9".repeat(100_000);
+    let doc = Document::parse(&format!("data_a\n_x {long_value}\n")).unwrap();
+    assert!(doc.blocks[0].get_item("_x").is_some());
+}
+
+#[test]
+fn test_parse_value_lone_quotes() {
+    assert_eq!(CifValue::parse_value("'"), CifValue::Text("'".to_string()));
+    assert_eq!(
+        CifValue::parse_value("\""),
+        CifValue::Text("\"".to_string())
+    );
+    assert_eq!(CifValue::parse_value("''"), CifValue::Text(String::new()));
+    assert_eq!(CifValue::parse_value(";"), CifValue::Text(String::new()));
+}
@@ -0,0 +1,97 @@
+// tests/identifier_tests.rs
+// Database codes and DOIs from COD, CSD-derived, ICSD and PDBx files
+
+use cif_parser::{Document, IdentifierKind};
+use std::path::Path;
+
+fn load(path: &str) -> Document {
+    Document::from_file(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+}
+
+#[test]
+fn test_cod_file() {
+    let doc = load("tests/example_cifs/urea/cod_urea.cif");
+    let ids = doc.first_block().unwrap().identifiers();
+
+    assert_eq!(ids.cod.as_deref(), Some("9011998"));
+    assert_eq!(ids.doi.as_deref(), Some("10.1107/S0021889875010965"));
+    assert_eq!(ids.csd, None);
+    // Written as 0012843, but read as a number
+    assert_eq!(
+        ids.others,
+        [("_database_code_amcsd".to_string(), "12843".to_string())]
+    );
+
+    assert!(doc
+        .find_by_identifier(IdentifierKind::Cod, "9011998")
+        .is_some());
+    assert!(doc
+        .find_by_identifier(IdentifierKind::Doi, "doi:10.1107/s0021889875010965")
+        .is_some());
+}
+
+#[test]
+fn test_csd_derived_file() {
+    let doc = load("tests/fixtures/identifiers/csd_paracetamol.cif");
+    let ids = doc.first_block().unwrap().identifiers();
+
+    assert_eq!(ids.csd.as_deref(), Some("HXACAN01"));
+    assert_eq!(ids.ccdc_deposition.as_deref(), Some("CCDC 1169420"));
+    assert_eq!(ids.doi.as_deref(), Some("10.1107/S0567740876005669"));
+    assert!(ids.others.is_empty());
+
+    for (kind, code) in [
+        (IdentifierKind::Csd, "hxacan01"),
+        (IdentifierKind::CcdcDeposition, "1169420"),
+        (IdentifierKind::CcdcDeposition, "CCDC 1169420"),
+    ] {
+        let block = doc.find_by_identifier(kind, code);
+        assert_eq!(block.map(|b| b.name.as_str()), Some("HXACAN01"), "{kind}");
+    }
+    assert!(doc
+        .find_by_identifier(IdentifierKind::Csd, "HXACAN")
+        .is_none());
+}
+
+#[test]
+fn test_looped_and_ddlm_codes() {
+    let doc = load("tests/fixtures/identifiers/icsd_looped.cif");
+
+    let looped = doc.get_block("52411-ICSD").unwrap().identifiers();
+    assert_eq!(looped.icsd.as_deref(), Some("52411"));
+    assert_eq!(
+        looped.others,
+        [("_database_code_PDF".to_string(), "01-070-3755".to_string())]
+    );
+
+    let ddlm = doc.get_block("quartz_ddlm").unwrap().identifiers();
+    assert_eq!(ddlm.icsd.as_deref(), Some("52412"));
+    assert_eq!(
+        ddlm.dataset_doi.as_deref(),
+        Some("https://doi.org/10.5517/ccdc.csd.cc1abcde")
+    );
+
+    let found = doc.find_by_identifier(IdentifierKind::Icsd, "052412");
+    assert_eq!(found.unwrap().name, "quartz_ddlm");
+    let found = doc.find_by_identifier(IdentifierKind::DatasetDoi, "10.5517/CCDC.CSD.CC1ABCDE");
+    assert_eq!(found.unwrap().name, "quartz_ddlm");
+}
+
+#[test]
+fn test_pdbx_database_2() {
+    let doc = load("tests/fixtures/identifiers/pdbx_database_2.cif");
+    let ids = doc.first_block().unwrap().identifiers();
+
+    assert_eq!(ids.pdb.as_deref(), Some("1CRN"));
+    assert_eq!(ids.dataset_doi.as_deref(), Some("10.2210/pdb1crn/pdb"));
+    assert_eq!(ids.doi.as_deref(), Some("10.1038/290107a0"));
+    assert_eq!(
+        ids.others,
+        [("WWPDB".to_string(), "D_1000173897".to_string())]
+    );
+    assert_eq!(ids.get(IdentifierKind::Pdb), Some("1CRN"));
+
+    assert!(doc
+        .find_by_identifier(IdentifierKind::Pdb, "1crn")
+        .is_some());
+}
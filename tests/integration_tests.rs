@@ -1,7 +1,7 @@
 // tests/integration_tests.rs
 // Integration tests for complete CIF file parsing
 
-use cif_parser::{Document, Value};
+use cif_parser::{Document, DocumentSource, ParseOptions, Value};
 use std::path::PathBuf;
 
 // Helper to get test fixtures path
@@ -428,6 +428,73 @@ fn test_parse_from_file() {
     }
 }
 
+#[test]
+fn test_file_source_recorded() {
+    let path = fixture_path("whitespace_clean.cif");
+    let doc = Document::from_file(&path).unwrap();
+    let source = doc.source.as_ref().expect("from_file sets the source");
+
+    assert_eq!(source.path.as_deref(), Some(path.as_path()));
+    assert_eq!(
+        source.byte_len,
+        std::fs::metadata(&path).unwrap().len() as usize
+    );
+    assert_eq!(source.sha256.len(), 64);
+
+    // Cloning keeps the source; writing never emits it
+    let copy = doc.clone();
+    assert_eq!(copy.source, doc.source);
+    assert!(!doc.to_string().contains(&source.sha256));
+}
+
+#[test]
+fn test_file_errors_carry_path() {
+    let path = fixture_path("whitespace_unicode.cif");
+    let err = Document::from_file(&path).unwrap_err();
+    assert_eq!(err.path(), Some(path.as_path()));
+    assert!(err.to_string().starts_with(&path.display().to_string()));
+
+    let missing = fixture_path("does_not_exist.cif");
+    let err = Document::from_file(&missing).unwrap_err();
+    assert_eq!(err.path(), Some(missing.as_path()));
+}
+
+#[test]
+fn test_set_source_tags_warnings() {
+    let content = "data_a\nloop_\n_x\n1\nstop_\n";
+    let options = ParseOptions::new().lenient(true);
+    let mut doc = Document::parse_with_options(content, &options).unwrap();
+    assert!(doc.source.is_none());
+
+    doc.set_source(DocumentSource::new(content.as_bytes()).with_path("batch/a.cif"));
+    let warning = doc.warnings[0].to_string();
+    assert!(
+        warning.starts_with("batch/a.cif: Warning at line"),
+        "{warning}"
+    );
+}
+
+#[test]
+fn test_unicode_whitespace_fixture_matches_clean_twin() {
+    let clean = Document::from_file(fixture_path("whitespace_clean.cif")).unwrap();
+    let dirty_path = fixture_path("whitespace_unicode.cif");
+
+    // Strict parsing refuses the word-processor spaces
+    assert!(Document::from_file(&dirty_path).is_err());
+
+    let content = std::fs::read_to_string(&dirty_path).unwrap();
+    let options = ParseOptions::new().lenient(true);
+    let dirty = Document::parse_with_options(&content, &options).unwrap();
+    assert!(!dirty.warnings.is_empty());
+
+    let (clean, dirty) = (&clean.blocks[0], &dirty.blocks[0]);
+    assert_eq!(dirty.name, clean.name);
+    assert_eq!(dirty.items, clean.items);
+    assert_eq!(dirty.loops.len(), clean.loops.len());
+    assert_eq!(dirty.loops[0].tags, clean.loops[0].tags);
+    assert_eq!(dirty.loops[0].values, clean.loops[0].values);
+}
+
 // Performance test for large files
 #[test]
 #[ignore] // This is a performance test, run manually
@@ -473,20 +540,27 @@ value1
 
     if let Err(err) = result {
         // Check that error contains location info
-        if let CifError::InvalidStructure { message, location } = &err {
-            assert!(message.contains("Loop has 2 tags but 1 values"));
+        if let CifError::LoopMismatch {
+            tags,
+            values,
+            location,
+            ..
+        } = &err
+        {
+            assert_eq!((*tags, *values), (2, 1));
             assert!(location.is_some());
             let (line, col) = location.unwrap();
             assert_eq!(line, 3); // loop_ starts on line 3
             assert!(col > 0);
         } else {
-            panic!("Expected InvalidStructure error");
+            panic!("Expected LoopMismatch error");
         }
 
         // Test that error message formatting includes location
         let error_message = format!("{}", err);
         assert!(error_message.contains("Error at line 3"));
         assert!(error_message.contains("column"));
+        assert!(error_message.contains("Loop has 2 tags but 1 values"));
     } else {
         panic!("Expected error");
     }
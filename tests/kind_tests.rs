@@ -0,0 +1,110 @@
+// tests/kind_tests.rs
+// Classifying files by kind, one small fixture per kind
+
+use cif_parser::{Document, Kind};
+use std::path::Path;
+
+fn kinds(name: &str) -> Vec<Kind> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/kinds")
+        .join(name);
+    Document::from_file(path).unwrap().kind().kinds().collect()
+}
+
+#[test]
+fn test_one_kind_per_fixture() {
+    for (name, kind) in [
+        ("small_molecule.cif", Kind::SmallMolecule),
+        ("mmcif.cif", Kind::Mmcif),
+        ("structure_factors.cif", Kind::StructureFactors),
+        ("powder.cif", Kind::Powder),
+        ("dictionary.cif", Kind::Dictionary),
+        ("imgcif.cif", Kind::ImgCif),
+        ("template.cif", Kind::Template),
+    ] {
+        assert_eq!(kinds(name), [kind], "{name}");
+    }
+}
+
+#[test]
+fn test_magnetic_structure_is_also_a_structure() {
+    assert_eq!(kinds("magcif.cif"), [Kind::SmallMolecule, Kind::MagCif]);
+}
+
+#[test]
+fn test_evidence_names_the_tags() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/kinds/mmcif.cif");
+    let kind = Document::from_file(path).unwrap().kind();
+    assert_eq!(
+        kind.evidence(Kind::Mmcif).unwrap(),
+        ["_entity.id", "_entity.pdbx_description", "_entity.type"]
+    );
+    assert_eq!(kind.evidence(Kind::SmallMolecule), None);
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/kinds/template.cif");
+    let kind = Document::from_file(path).unwrap().kind();
+    assert_eq!(
+        kind.to_string(),
+        "template (_cell_length_a, _cell_length_b, _cell_length_c)"
+    );
+}
+
+#[test]
+fn test_structure_factors_need_no_atom_sites() {
+    // A refinement result with its reflections is a structure, not an hkl file
+    let cif = "data_x\n_cell_length_a 5\nloop_\n_atom_site_label\n_atom_site_fract_x\nC1 0.1\n\
+               loop_\n_refln_index_h\n_refln_index_k\n_refln_index_l\n1 0 0\n0 1 0\n0 0 1\n";
+    let kind = Document::parse(cif).unwrap().kind();
+    assert_eq!(kind.kinds().collect::<Vec<_>>(), [Kind::SmallMolecule]);
+}
+
+#[test]
+fn test_unrecognised_file() {
+    let kind = Document::parse("data_x\n_note hello\n").unwrap().kind();
+    assert!(kind.is_empty());
+    assert_eq!(kind.to_string(), "unknown");
+}
+
+#[test]
+fn test_real_files() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    let doc = Document::from_file(root.join("paracetamol/ccdc_paracetamol.cif")).unwrap();
+    assert_eq!(
+        doc.kind().kinds().collect::<Vec<_>>(),
+        [Kind::SmallMolecule]
+    );
+}
+
+#[test]
+fn test_main_block_skips_publication_block() {
+    let cif = "data_global\n_publ_section_title 'Two polymorphs'\n_pd_block_id 'x|y'\n\
+               data_form_a\n_cell_length_a 5\n\
+               data_form_b\nloop_\n_atom_site_label\n_atom_site_fract_x\nC1 0.1\n";
+    let doc = Document::parse(cif).unwrap();
+    let holding: Vec<&str> = doc.data_blocks().map(|b| b.name.as_str()).collect();
+    assert_eq!(holding, ["form_a"]);
+    assert_eq!(doc.main_block().unwrap().name, "form_a");
+
+    let metadata = Document::parse("data_a\n_publ_section_title x\ndata_b\n_journal_year 2020\n");
+    assert_eq!(metadata.unwrap().main_block().unwrap().name, "a");
+    assert!(Document::parse("").unwrap().main_block().is_none());
+}
+
+#[test]
+fn test_every_kind_fixture_holds_data() {
+    for name in [
+        "small_molecule.cif",
+        "mmcif.cif",
+        "structure_factors.cif",
+        "powder.cif",
+        "dictionary.cif",
+        "imgcif.cif",
+        "magcif.cif",
+    ] {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/kinds")
+            .join(name);
+        let doc = Document::from_file(path).unwrap();
+        assert!(doc.blocks[0].holds_data(), "{name}");
+    }
+}
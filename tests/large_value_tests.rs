@@ -0,0 +1,228 @@
+// tests/large_value_tests.rs
+// Text above the large-value threshold: stored once, shared, and streamed
+
+use cif_parser::schema::ExpectedType;
+use cif_parser::writer::WriteOptions;
+use cif_parser::{CifValue, Document, ParseOptions};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Tracks live and peak heap bytes, so tests can see how many copies of a
+/// large value exist at once
+struct PeakAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(live, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+/// Memory measurements must not overlap
+static MEASURING: Mutex<()> = Mutex::new(());
+
+/// Peak heap bytes allocated by `f` beyond what was live before it, and its result
+fn peak_during<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = LIVE.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let result = f();
+    (PEAK.load(Ordering::SeqCst) - before, result)
+}
+
+/// A block with a base64-like text field of about `bytes` bytes
+fn document_with_map(bytes: usize) -> String {
+    let line = "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVphYmNkZWZnaGlqa2xtbm9wcXJzdHV2d3h5ejAxMjM0\n";
+    let mut cif = String::with_capacity(bytes + 200);
+    cif.push_str("data_map\n_cell_length_a 5.43\n_map_data\n;\n");
+    while cif.len() < bytes {
+        cif.push_str(line);
+    }
+    cif.push_str(";\n_title 'after the map'\n");
+    cif
+}
+
+fn large(bytes: usize) -> ParseOptions {
+    ParseOptions::new().large_value_threshold(bytes)
+}
+
+#[test]
+fn test_text_above_threshold_is_large() {
+    let cif = document_with_map(10_000);
+    let doc = Document::parse_with_options(&cif, &large(1_000)).unwrap();
+    let block = &doc.blocks[0];
+
+    let map = block.get_item("_map_data").unwrap();
+    assert!(map.is_large());
+    assert!(map.as_string().unwrap().starts_with("QUJD"));
+    assert!(map.as_string().unwrap().ends_with("ejAxMjM0"));
+    assert!(!block.get_item("_title").unwrap().is_large());
+    assert_eq!(
+        block.get_item("_cell_length_a").unwrap().as_numeric(),
+        Some(5.43)
+    );
+
+    // Equal to the same document read without the threshold
+    let plain = Document::parse(&cif).unwrap();
+    assert!(!plain.blocks[0].get_item("_map_data").unwrap().is_large());
+    assert_eq!(plain.blocks[0].get_item("_map_data"), Some(map));
+    assert!(doc.semantically_equal(&plain));
+}
+
+#[test]
+fn test_loop_values_and_triple_quotes() {
+    let long = "x".repeat(50);
+    let cif = format!(
+        "#\\#CIF_2.0\ndata_x\nloop_\n_id\n_data\n1 short\n2\n;\n{long}\n;\n_note '''{long}'''\n_short '''abc'''\n"
+    );
+    let doc = Document::parse_with_options(&cif, &large(20)).unwrap();
+    let block = &doc.blocks[0];
+
+    let column = block.loops[0].get_column("_data").unwrap();
+    assert!(!column[0].is_large());
+    assert!(column[1].is_large());
+    assert_eq!(column[1], &CifValue::Text(long.clone()));
+    assert!(block.get_item("_note").unwrap().is_large());
+    assert!(!block.get_item("_short").unwrap().is_large());
+}
+
+#[test]
+fn test_large_text_reads_as_text() {
+    // Only text fields are stored as large text
+    let field = |tag: &str, text: &str| format!("{tag}\n;{text}\n;\n");
+    let cif = [
+        "data_x\n".to_string(),
+        field("_title", "a long title"),
+        field("_flag", "true"),
+        field("_date", "2024-01-15"),
+        field("_count", "1234"),
+        field("_vector", "1 0 0(2)"),
+        field("_length", "5.431(2)"),
+        field("_kind", "Uani_x"),
+    ]
+    .concat();
+    let doc = Document::parse_with_options(&cif, &large(3)).unwrap();
+    let block = &doc.blocks[0];
+    assert!(block.items.values().all(CifValue::is_large));
+
+    assert_eq!(
+        block.get_str("_title").unwrap().as_deref(),
+        Some("a long title")
+    );
+    assert_eq!(
+        block.get_as::<String>("_kind").unwrap().as_deref(),
+        Some("Uani_x")
+    );
+    assert_eq!(block.get_bool("_flag").unwrap(), Some(true));
+    assert_eq!(
+        block.get_as::<Vec<f64>>("_vector").unwrap(),
+        Some(vec![1.0, 0.0, 0.0])
+    );
+    assert_eq!(
+        block.get_as::<(f64, Option<f64>)>("_length").unwrap(),
+        Some((5.431, Some(0.002)))
+    );
+
+    let report = block.coercion_report(&[
+        ("_title", ExpectedType::Text),
+        ("_flag", ExpectedType::Bool),
+        ("_date", ExpectedType::Date),
+        ("_count", ExpectedType::I64),
+        ("_length", ExpectedType::F64WithSu),
+        ("_kind", ExpectedType::Enum(&["uani_x", "uiso_x"])),
+    ]);
+    assert!(report.is_ok(), "{report}");
+}
+
+#[test]
+fn test_clones_share_the_text() {
+    let doc = Document::parse_with_options(&document_with_map(5_000), &large(100)).unwrap();
+    let copy = doc.clone();
+    let (CifValue::LargeText(a), CifValue::LargeText(b)) = (
+        doc.blocks[0].get_item("_map_data").unwrap(),
+        copy.blocks[0].get_item("_map_data").unwrap(),
+    ) else {
+        panic!("expected large values");
+    };
+    assert!(std::sync::Arc::ptr_eq(a, b));
+    assert_eq!(
+        format!("{:?}", doc.blocks[0].items["_map_data"]),
+        format!("LargeText(<{} bytes>)", a.len())
+    );
+}
+
+#[test]
+fn test_written_text_is_the_same() {
+    let cif = document_with_map(5_000);
+    let doc = Document::parse_with_options(&cif, &large(100)).unwrap();
+    let plain = Document::parse(&cif).unwrap();
+    assert_eq!(doc.to_string(), plain.to_string());
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out, &WriteOptions::new()).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), plain.to_string());
+
+    let reparsed = Document::parse_with_options(&doc.to_string(), &large(100)).unwrap();
+    assert_eq!(reparsed.blocks[0].items, doc.blocks[0].items);
+}
+
+#[test]
+fn test_memory_footprint_counts_large_text() {
+    let doc = Document::parse_with_options(&document_with_map(50_000), &large(100)).unwrap();
+    assert!(doc.memory_footprint().total_bytes >= 50_000);
+}
+
+/// Parsing copies a large value out of the input once, plus a little parser
+/// bookkeeping per line; cloning the document and writing it out copy it
+/// not at all
+fn check_single_copy(bytes: usize) {
+    let _guard = MEASURING.lock().unwrap_or_else(|e| e.into_inner());
+    let cif = document_with_map(bytes);
+    let options = large(1 << 20);
+
+    let (parse_peak, doc) = peak_during(|| Document::parse_with_options(&cif, &options).unwrap());
+    let size = doc.blocks[0].items["_map_data"].as_string().unwrap().len();
+    assert!(size > bytes * 9 / 10);
+    assert!(
+        parse_peak < size * 5 / 4,
+        "parsing peaked at {parse_peak} bytes for a {size}-byte value"
+    );
+
+    let (write_peak, _) = peak_during(|| {
+        let copy = doc.clone();
+        copy.write_to(std::io::sink(), &WriteOptions::new())
+            .unwrap();
+    });
+    assert!(
+        write_peak < size / 100,
+        "cloning and writing peaked at {write_peak} bytes for a {size}-byte value"
+    );
+}
+
+#[test]
+fn test_large_value_is_copied_once() {
+    check_single_copy(4_000_000);
+}
+
+// Slow in a debug build; run with
+// `cargo test --release --test large_value_tests -- --ignored`
+#[test]
+#[ignore]
+fn test_100_mb_value_is_copied_once() {
+    check_single_copy(100_000_000);
+}
@@ -0,0 +1,150 @@
+// tests/lenient_tests.rs
+// Recovery from common hand-editing mistakes in lenient mode
+
+use cif_parser::{CifError, CifValue, Document, ParseOptions};
+
+fn lenient(input: &str) -> Document {
+    Document::parse_with_options(input, &ParseOptions::new().lenient(true)).unwrap()
+}
+
+fn warning_locations(doc: &Document) -> Vec<Option<(usize, usize)>> {
+    doc.warnings
+        .iter()
+        .map(|warning| warning.location)
+        .collect()
+}
+
+#[test]
+fn test_unquoted_spaces_read_as_one_value() {
+    let input = "data_a\n_name Sodium chloride  (rock salt)\n_z 4\n";
+    assert!(Document::parse(input).is_err());
+
+    let doc = lenient(input);
+    let block = &doc.blocks[0];
+    assert_eq!(
+        block.get_item("_name"),
+        Some(&CifValue::Text("Sodium chloride  (rock salt)".to_string()))
+    );
+    assert_eq!(
+        block.get_item("_z").and_then(CifValue::as_numeric),
+        Some(4.0)
+    );
+    assert_eq!(warning_locations(&doc), [Some((2, 14))]);
+    assert!(doc.warnings[0].message.contains("'_name'"));
+}
+
+#[test]
+fn test_stray_values_on_later_lines_are_still_errors() {
+    let options = ParseOptions::new().lenient(true);
+    // Quoted values and values on another line are not guessed at
+    for input in [
+        "data_a\n_name 'Sodium' chloride\n_z 4\n",
+        "data_a\n_name Sodium\nchloride\n_z 4\n",
+        "data_a\n_name Sodium chloride\nrock salt\n_z 4\n",
+    ] {
+        let error = Document::parse_with_options(input, &options).unwrap_err();
+        assert!(
+            matches!(error, CifError::InvalidStructure { .. }),
+            "{input:?}: {error:?}"
+        );
+    }
+}
+
+#[test]
+fn test_unclosed_quotes_closed_at_end_of_line() {
+    let input = "data_a\n_note 'it was hot  \n_title \"Sodium chloride\n_z 4\n";
+    assert!(Document::parse(input).is_err());
+
+    let doc = lenient(input);
+    let block = &doc.blocks[0];
+    assert_eq!(
+        block.get_item("_note").and_then(CifValue::as_string),
+        Some("it was hot")
+    );
+    assert_eq!(
+        block.get_item("_title").and_then(CifValue::as_string),
+        Some("Sodium chloride")
+    );
+    assert_eq!(warning_locations(&doc), [Some((2, 7)), Some((3, 8))]);
+}
+
+#[test]
+fn test_short_last_loop_row_padded_with_unknown() {
+    let input = "data_a\nloop_\n_x\n_y\n_z\n1 2 3\n4 5\n";
+    assert!(matches!(
+        Document::parse(input).unwrap_err(),
+        CifError::LoopMismatch { .. }
+    ));
+
+    let doc = lenient(input);
+    let loop_ = &doc.blocks[0].loops[0];
+    assert_eq!(loop_.len(), 2);
+    assert_eq!(loop_.get_by_tag(1, "_z"), Some(&CifValue::Unknown));
+    assert_eq!(doc.warnings.len(), 1);
+    assert!(doc.warnings[0].message.contains("3 tags but 5 values"));
+}
+
+#[test]
+fn test_short_row_before_next_block_padded_with_unknown() {
+    let input = "data_a\nloop_\n_x\n_y\n1 2 3\ndata_b\n_z 1\n";
+    assert!(matches!(
+        Document::parse(input).unwrap_err(),
+        CifError::LoopMismatch {
+            reserved_word: Some(_),
+            ..
+        }
+    ));
+
+    let doc = lenient(input);
+    let loop_ = &doc.blocks[0].loops[0];
+    assert_eq!(loop_.len(), 2);
+    assert_eq!(loop_.get_by_tag(1, "_y"), Some(&CifValue::Unknown));
+    assert_eq!(
+        doc.blocks[1].get_item("_z").and_then(CifValue::as_numeric),
+        Some(1.0)
+    );
+    assert_eq!(warning_locations(&doc), [Some((6, 1))]);
+    assert!(doc.warnings[0].message.contains("before 'data_b'"));
+}
+
+#[test]
+fn test_unterminated_text_field_is_still_an_error() {
+    let options = ParseOptions::new().lenient(true);
+    let input = "data_a\n_note\n;\nnever closed\n_x 1\n";
+    let error = Document::parse_with_options(input, &options).unwrap_err();
+    assert!(matches!(error, CifError::Unterminated { .. }), "{error}");
+}
+
+#[test]
+fn test_repeated_tag_warns_and_keeps_last_value() {
+    let input = "data_a\n_x 1\n_x 2\n";
+    assert!(Document::parse(input).unwrap().warnings.is_empty());
+
+    let doc = lenient(input);
+    assert_eq!(
+        doc.blocks[0].get_item("_x").and_then(CifValue::as_numeric),
+        Some(2.0)
+    );
+    assert_eq!(warning_locations(&doc), [Some((3, 1))]);
+}
+
+#[test]
+fn test_recoveries_in_save_frames() {
+    let doc = lenient("data_a\nsave_f\n_name Sodium chloride\n_note 'open\nsave_\n");
+    let frame = &doc.blocks[0].frames[0];
+    assert_eq!(
+        frame.items.get("_name"),
+        Some(&CifValue::Text("Sodium chloride".to_string()))
+    );
+    assert_eq!(
+        frame.items.get("_note").and_then(CifValue::as_string),
+        Some("open")
+    );
+    assert_eq!(doc.warnings.len(), 2);
+}
+
+#[test]
+fn test_clean_files_have_no_warnings() {
+    let doc = lenient("data_a\n_name 'Sodium chloride'\nloop_ _x _y 1 2 3 4\n");
+    assert!(doc.warnings.is_empty());
+}
@@ -0,0 +1,141 @@
+// tests/lexer_tests.rs
+// Exact token streams for tricky input, and agreement with the parser on real files
+
+use cif_parser::lexer::{Token, TokenKind, Tokenizer};
+use cif_parser::Document;
+use std::path::Path;
+
+use TokenKind::*;
+
+fn tokens(input: &str) -> Vec<Token<'_>> {
+    Tokenizer::new(input).map(Result::unwrap).collect()
+}
+
+/// (kind, text, line, col) of every token, trivia included
+fn stream(input: &str) -> Vec<(TokenKind, &str, usize, usize)> {
+    tokens(input)
+        .into_iter()
+        .map(|t| (t.kind, t.text, t.line, t.col))
+        .collect()
+}
+
+#[test]
+fn test_embedded_quotes() {
+    let input = "_a 'O''Brien' \"it's\" 'a'b' don't";
+    assert_eq!(
+        stream(input),
+        [
+            (Tag, "_a", 1, 1),
+            (Whitespace, " ", 1, 3),
+            (QuotedString, "'O''Brien'", 1, 4),
+            (Whitespace, " ", 1, 14),
+            (QuotedString, "\"it's\"", 1, 15),
+            (Whitespace, " ", 1, 21),
+            (QuotedString, "'a'b'", 1, 22),
+            (Whitespace, " ", 1, 27),
+            (UnquotedValue, "don't", 1, 28),
+        ]
+    );
+}
+
+#[test]
+fn test_semicolon_text_fields() {
+    let input = "_a\n;line one\n 'a; b'\n;\n_b x;y\n";
+    assert_eq!(
+        stream(input),
+        [
+            (Tag, "_a", 1, 1),
+            (Whitespace, "\n", 1, 3),
+            (TextField, ";line one\n 'a; b'\n;", 2, 1),
+            (Whitespace, "\n", 4, 2),
+            (Tag, "_b", 5, 1),
+            (Whitespace, " ", 5, 3),
+            (UnquotedValue, "x;y", 5, 4),
+            (Whitespace, "\n", 5, 7),
+        ]
+    );
+}
+
+#[test]
+fn test_crlf_and_cr_newlines() {
+    let input = "data_a\r\n_x 1 # note\r\n;\r\ntext\r\n;\r_y\r\n";
+    assert_eq!(
+        stream(input),
+        [
+            (BlockHeader, "data_a", 1, 1),
+            (Whitespace, "\r\n", 1, 7),
+            (Tag, "_x", 2, 1),
+            (Whitespace, " ", 2, 3),
+            (UnquotedValue, "1", 2, 4),
+            (Whitespace, " ", 2, 5),
+            (Comment, "# note", 2, 6),
+            (Whitespace, "\r\n", 2, 12),
+            (TextField, ";\r\ntext\r\n;", 3, 1),
+            (Whitespace, "\r", 5, 2),
+            (Tag, "_y", 6, 1),
+            (Whitespace, "\r\n", 6, 3),
+        ]
+    );
+    let text_field = &tokens(input)[8];
+    assert_eq!(&input[text_field.byte_range.clone()], text_field.text);
+}
+
+#[test]
+fn test_reserved_words() {
+    let input = "global_ data_A save_frame loop_ _t stop_ save_ Data_B";
+    let kinds: Vec<_> = Tokenizer::new(input)
+        .skip_trivia(true)
+        .map(|t| t.unwrap().kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            BlockHeader,
+            BlockHeader,
+            SaveHeader,
+            LoopKeyword,
+            Tag,
+            LoopKeyword,
+            SaveEnd,
+            BlockHeader
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_text_field() {
+    let result: Result<Vec<_>, _> = Tokenizer::new("data_a\n_x\n;never closed\n").collect();
+    let message = result.unwrap_err().to_string();
+    assert!(message.contains("text field"), "{message}");
+    assert!(message.contains("line 3"), "{message}");
+}
+
+#[test]
+fn test_tags_agree_with_parser_on_example_files() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    let mut checked = 0;
+    for dir in std::fs::read_dir(root).unwrap() {
+        for entry in std::fs::read_dir(dir.unwrap().path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("cif") {
+                continue;
+            }
+            let Ok(doc) = Document::from_file(&path) else {
+                continue;
+            };
+            let input = std::fs::read_to_string(&path).unwrap();
+            let tokens: Vec<_> = Tokenizer::new(&input)
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+
+            let joined: String = tokens.iter().map(|t| t.text).collect();
+            assert_eq!(joined, input, "{}", path.display());
+
+            let lexed = tokens.iter().filter(|t| t.kind == Tag).count();
+            let parsed: usize = doc.blocks.iter().map(|b| b.iter_all_tags().count()).sum();
+            assert_eq!(lexed, parsed, "{}", path.display());
+            checked += 1;
+        }
+    }
+    assert!(checked > 5);
+}
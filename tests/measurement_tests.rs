@@ -0,0 +1,143 @@
+// tests/measurement_tests.rs
+// Standard reflections, scans, and instruments from serial and area-detector files
+
+use cif_parser::measurement::DEFAULT_DECAY_THRESHOLD;
+use cif_parser::Document;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/measurement")
+        .join(name)
+}
+
+#[test]
+fn test_serial_diffractometer() {
+    let doc = Document::from_file(fixture("cad4.cif")).unwrap();
+    let measurement = doc.first_block().unwrap().measurement();
+
+    assert_eq!(measurement.standards_number, Some(3));
+    assert_eq!(measurement.standards_interval_count, Some(200));
+    assert_eq!(measurement.standards_interval_time, Some((120.0, None)));
+    let (decay, su) = measurement.standards_decay_percent.unwrap();
+    assert_eq!(decay, 14.2);
+    assert!((su.unwrap() - 0.3).abs() < 1e-12);
+    assert_eq!(measurement.scan_method.as_deref(), Some("\\w/2\\q scans"));
+    let (width, su) = measurement.scan_width.unwrap();
+    assert_eq!(width, 0.8);
+    assert!((su.unwrap() - 0.05).abs() < 1e-12);
+    assert_eq!(measurement.scan_time, Some((45.0, None)));
+    assert_eq!(
+        measurement.device.as_deref(),
+        Some("four-circle diffractometer")
+    );
+    assert_eq!(
+        measurement.device_type.as_deref(),
+        Some("Enraf-Nonius CAD-4")
+    );
+    assert_eq!(
+        measurement.detector.as_deref(),
+        Some("scintillation counter")
+    );
+    assert_eq!(measurement.detector_type, None);
+    assert_eq!(measurement.monochromator.as_deref(), Some("graphite"));
+    assert_eq!(measurement.reflections_measured, Some(2484));
+
+    // 2484 reflections and 3 standards after each of 12 full intervals
+    assert_eq!(
+        measurement.total_exposure_estimate(),
+        Some((2484.0 + 36.0) * 45.0)
+    );
+}
+
+#[test]
+fn test_decay_warning() {
+    let doc = Document::from_file(fixture("cad4.cif")).unwrap();
+    let measurement = doc.first_block().unwrap().measurement();
+
+    let warnings = measurement.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].id, "standards-decay");
+    assert_eq!(warnings[0].tag, "_diffrn_standards_decay_%");
+    assert_eq!(warnings[0].value, "14.2(3)");
+    assert!(warnings[0].rule.contains("10%"));
+
+    assert!(measurement.decay_warning(15.0).is_none());
+    assert!(measurement.decay_warning(DEFAULT_DECAY_THRESHOLD).is_some());
+}
+
+#[test]
+fn test_area_detector() {
+    let doc = Document::from_file(fixture("ccd.cif")).unwrap();
+    let measurement = doc.first_block().unwrap().measurement();
+
+    assert_eq!(measurement.standards_number, Some(0));
+    assert_eq!(measurement.standards_interval_count, None);
+    assert_eq!(measurement.standards_interval_time, None);
+    assert_eq!(measurement.standards_decay_percent, None);
+    assert_eq!(
+        measurement.scan_method.as_deref(),
+        Some("\\f and \\w scans")
+    );
+    assert!(measurement
+        .details
+        .as_deref()
+        .unwrap()
+        .contains("10 s each"));
+    assert_eq!(measurement.detector.as_deref(), Some("CCD area detector"));
+    assert_eq!(measurement.detector_type.as_deref(), Some("Bruker APEX-II"));
+    assert_eq!(
+        measurement.monochromator.as_deref(),
+        Some("multilayer optics")
+    );
+    assert_eq!(measurement.reflections_measured, Some(18422));
+
+    // No scan time, so no estimate, and nothing to warn about
+    assert_eq!(measurement.total_exposure_estimate(), None);
+    assert!(measurement.warnings().is_empty());
+}
+
+#[test]
+fn test_decay_percent_spelling_and_mmcif_names() {
+    let cif = "\
+data_percent
+_diffrn_standards_decay_percent -11.5
+_diffrn_standards_number 2
+
+data_mmcif
+_diffrn_standards.number 3
+_diffrn_standards.interval_count 150
+_diffrn_standards.decay_% 1.2
+_diffrn_measurement.method 'omega scans'
+_diffrn_measurement.scan_time 20
+_diffrn_detector.detector 'scintillation counter'
+_diffrn_radiation.monochromator graphite
+_diffrn_reflns.number 300
+";
+    let doc = Document::parse(cif).unwrap();
+
+    let percent = doc.blocks[0].measurement();
+    assert_eq!(percent.standards_decay_percent, Some((-11.5, None)));
+    // An increase of the standards is flagged as well as a decrease
+    assert_eq!(percent.warnings().len(), 1);
+
+    let mmcif = doc.blocks[1].measurement();
+    assert_eq!(mmcif.standards_number, Some(3));
+    assert_eq!(mmcif.standards_interval_count, Some(150));
+    assert_eq!(mmcif.standards_decay_percent, Some((1.2, None)));
+    assert_eq!(mmcif.scan_method.as_deref(), Some("omega scans"));
+    assert_eq!(mmcif.detector.as_deref(), Some("scintillation counter"));
+    assert_eq!(mmcif.monochromator.as_deref(), Some("graphite"));
+    assert_eq!(mmcif.total_exposure_estimate(), Some(306.0 * 20.0));
+    assert!(mmcif.warnings().is_empty());
+}
+
+#[test]
+fn test_empty_block() {
+    let doc = Document::parse("data_empty\n_cell_length_a 5.0\n").unwrap();
+    let measurement = doc.first_block().unwrap().measurement();
+
+    assert_eq!(measurement, Default::default());
+    assert_eq!(measurement.total_exposure_estimate(), None);
+    assert!(measurement.warnings().is_empty());
+}
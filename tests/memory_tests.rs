@@ -0,0 +1,105 @@
+// tests/memory_tests.rs
+// Memory footprint reports checked against documents of known size
+
+use cif_parser::{Block, Document, Frame, Loop, Value};
+use std::mem::size_of;
+
+const ROWS: usize = 1000;
+const COLUMNS: usize = 4;
+/// Every text value is exactly this long
+const TEXT: &str = "abcdefgh";
+
+/// A document with one large loop of text, one small numeric loop in a
+/// frame, and a few items, all allocated to exact capacity
+fn synthetic() -> Document {
+    let mut block = Block::new("synthetic".to_string());
+    let mut big = Loop::new((0..COLUMNS).map(|c| format!("_big_{c}")).collect());
+    for _ in 0..ROWS {
+        big.push_row(vec![Value::Text(TEXT.to_string()); COLUMNS])
+            .unwrap();
+    }
+    big.values.shrink_to_fit();
+    block.loops.push(big);
+
+    let mut frame = Frame::new("frame".to_string());
+    let mut small = Loop::new(vec!["_small_n".to_string()]);
+    for n in 0..10 {
+        small.push_row(vec![Value::Numeric(n as f64)]).unwrap();
+    }
+    frame.loops.push(small);
+    block.frames.push(frame);
+
+    for n in 0..10 {
+        block
+            .items
+            .insert(format!("_item_{n}"), Value::Text(TEXT.to_string()));
+    }
+
+    let mut doc = Document::new();
+    doc.blocks.push(block);
+    doc
+}
+
+fn assert_close(actual: usize, expected: usize, tolerance: f64) {
+    let ratio = actual as f64 / expected as f64;
+    assert!(
+        (ratio - 1.0).abs() <= tolerance,
+        "{actual} bytes is not within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn test_loop_bytes_match_storage() {
+    let report = synthetic().memory_footprint();
+    let big = &report.blocks[0].loops[0];
+    assert_eq!((big.rows, big.columns), (ROWS, COLUMNS));
+
+    let cells = ROWS * COLUMNS;
+    let expected = cells * (size_of::<Value>() + TEXT.len()) + ROWS * size_of::<Vec<Value>>();
+    // Only the tags are unaccounted for
+    assert_close(big.bytes, expected, 0.01);
+}
+
+#[test]
+fn test_totals_are_consistent() {
+    let doc = synthetic();
+    let report = doc.memory_footprint();
+    let block = &report.blocks[0];
+
+    let loops: usize = block.loops.iter().map(|l| l.bytes).sum();
+    assert!(block.bytes >= block.items_bytes + loops + block.frames_bytes);
+    assert!(report.total_bytes >= block.bytes);
+    // The big loop dominates the document
+    assert_close(report.total_bytes, loops, 0.05);
+    // Ten items of about 8 + 8 bytes each, plus the hash table
+    assert!(block.items_bytes > 10 * (TEXT.len() + "_item_0".len()));
+    assert!(block.items_bytes < 2048, "{}", block.items_bytes);
+}
+
+#[test]
+fn test_largest_loops_include_frames() {
+    let report = synthetic().memory_footprint();
+    let names: Vec<_> = report
+        .largest_loops
+        .iter()
+        .map(|l| (l.first_tag.as_str(), l.frame.as_deref()))
+        .collect();
+    assert_eq!(names, [("_big_0", None), ("_small_n", Some("frame"))]);
+    assert!(report.blocks[0].frames_bytes >= report.largest_loops[1].bytes);
+}
+
+#[test]
+fn test_capacity_is_counted() {
+    let mut doc = synthetic();
+    let before = doc.memory_footprint().blocks[0].loops[0].bytes;
+    doc.blocks[0].loops[0].values.reserve_exact(ROWS);
+    let after = doc.memory_footprint().blocks[0].loops[0].bytes;
+    assert_eq!(after - before, ROWS * size_of::<Vec<Value>>());
+}
+
+#[test]
+fn test_report_display() {
+    let text = synthetic().memory_footprint().to_string();
+    assert!(text.starts_with("Total: "), "{text}");
+    assert!(text.contains("data_synthetic/save_frame _small_n (10 rows x 1 columns)"));
+}
@@ -0,0 +1,74 @@
+// tests/mmap_tests.rs
+// Memory-mapped files, behind the mmap feature
+
+use cif_parser::mmap::MappedFile;
+use cif_parser::{CifError, Document};
+use std::io::Write;
+use std::path::PathBuf;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mmcif_fragment.cif")
+}
+
+/// Write `content` to a file of this name in the test directory
+fn write(name: &str, content: &[u8]) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_mapped_file_reads_as_from_file() {
+    let expected = Document::from_file(fixture_path()).unwrap();
+    let doc = Document::from_file_mmap(fixture_path()).unwrap();
+    assert!(doc.semantically_equal(&expected));
+    let (source, expected_source) = (doc.source.unwrap(), expected.source.clone().unwrap());
+    assert_eq!(source.path, expected_source.path);
+    assert_eq!(source.sha256, expected_source.sha256);
+
+    let file = MappedFile::open(fixture_path()).unwrap();
+    assert_eq!(file.path(), fixture_path());
+    let text = file.as_str().unwrap();
+    let borrowed = file.parse().unwrap();
+    let name = borrowed.blocks[0].name;
+    assert!(text.as_bytes().as_ptr_range().contains(&name.as_ptr()));
+    assert!(borrowed.into_owned().semantically_equal(&expected));
+
+    // An empty file maps and has no blocks
+    let doc = Document::from_file_mmap(write("empty.cif", b"")).unwrap();
+    assert!(doc.blocks.is_empty());
+}
+
+#[test]
+fn test_compressed_file_is_decompressed_first() {
+    let cif = b"data_a\n_x 1\n";
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(cif).unwrap();
+    let path = write("mapped.cif.gz", &encoder.finish().unwrap());
+
+    let doc = Document::from_file_mmap(&path).unwrap();
+    assert_eq!(doc.blocks[0].name, "a");
+    // It cannot be borrowed from the map itself
+    let error = MappedFile::open(&path).unwrap().parse().unwrap_err();
+    assert!(error.to_string().contains("gzip"), "{error}");
+}
+
+#[test]
+fn test_errors_carry_the_path() {
+    let missing = fixture_path().with_file_name("missing.cif");
+    let error = Document::from_file_mmap(&missing).unwrap_err();
+    assert!(
+        matches!(&error, CifError::InFile { error, .. } if matches!(**error, CifError::Io(_))),
+        "{error:?}"
+    );
+    assert_eq!(error.path(), Some(missing.as_path()));
+
+    let path = write("latin1.cif", b"data_a\n_name 'Andr\xe9'\n");
+    let error = Document::from_file_mmap(&path).unwrap_err();
+    assert_eq!(error.path(), Some(path.as_path()));
+    assert_eq!(error.location(), Some((2, 12)));
+
+    let path = write("unclosed.cif", b"data_a\nloop_\n_x\n_y\n1\n");
+    let error = Document::from_file_mmap(&path).unwrap_err();
+    assert_eq!(error.path(), Some(path.as_path()));
+}
@@ -0,0 +1,116 @@
+// tests/mmcif_style_tests.rs
+// Writing dotted-tag blocks in the PDBx/mmCIF layout
+
+use cif_parser::{Block, CifStyle, Document, WriteOptions};
+use std::fs;
+use std::path::PathBuf;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn mmcif(doc: &Document) -> String {
+    doc.to_string_with_options(&WriteOptions::new().style(CifStyle::Mmcif))
+}
+
+fn document(blocks: Vec<Block>) -> Document {
+    let mut doc = Document::new();
+    doc.blocks = blocks;
+    doc
+}
+
+#[test]
+fn test_converted_structure_matches_golden_file() {
+    // The expected file is laid out the way gemmi writes PDBx files:
+    // a `#` after the heading and after every category, and single-row
+    // categories as items. Alignment is not compared.
+    let core = Document::from_file(fixture_path("simple.cif")).unwrap();
+    let doc = document(vec![core.blocks[0].to_mmcif().unwrap()]);
+    let expected = fs::read_to_string(fixture_path("simple_mmcif_expected.cif")).unwrap();
+
+    let written = mmcif(&doc);
+    assert_eq!(
+        written.split_whitespace().collect::<Vec<_>>(),
+        expected.split_whitespace().collect::<Vec<_>>(),
+        "{written}"
+    );
+}
+
+#[test]
+fn test_categories_in_conventional_order() {
+    let doc = Document::from_file(fixture_path("mmcif_fragment.cif")).unwrap();
+    let written = mmcif(&doc);
+
+    let first_tags: Vec<&str> = written
+        .split("#\n")
+        .filter_map(|section| section.lines().find(|line| line.starts_with('_')))
+        .map(|line| line.split(['.', ' ']).next().unwrap())
+        .collect();
+    assert_eq!(
+        first_tags[..4],
+        ["_entry", "_cell", "_symmetry", "_atom_sites"]
+    );
+    // Known categories come first, others alphabetically after
+    let atom_site = first_tags.iter().position(|&c| c == "_atom_site").unwrap();
+    let formula = first_tags
+        .iter()
+        .position(|&c| c == "_chemical_formula")
+        .unwrap();
+    assert!(atom_site < formula, "{first_tags:?}");
+
+    // Every section ends with a divider, and the output reads back
+    assert!(written.starts_with("data_1CRN\n#\n"));
+    assert!(written.ends_with("#\n"));
+    assert!(!written.contains("#\\#CIF_1.1"));
+    let reparsed = Document::parse(&written).unwrap();
+    assert!(reparsed.semantically_equal(&doc));
+}
+
+#[test]
+fn test_single_row_loops_become_items() {
+    let cif = "data_x
+loop_
+_struct.entry_id
+_struct.title
+1ABC 'A small protein'
+loop_
+_struct_asym.id
+_struct_asym.entity_id
+A 1
+B 2
+loop_
+_pdbx_empty.id
+";
+    let doc = Document::parse(cif).unwrap();
+    let written = mmcif(&doc);
+    assert!(
+        written.contains("_struct.entry_id 1ABC\n_struct.title    'A small protein'\n#\n"),
+        "{written}"
+    );
+    assert!(written.contains("loop_\n_struct_asym.id\n_struct_asym.entity_id\nA 1\nB 2\n#\n"));
+    // A loop with no rows keeps its header
+    assert!(written.contains("loop_\n_pdbx_empty.id\n#\n"));
+
+    let reparsed = Document::parse(&written).unwrap();
+    let block = &reparsed.blocks[0];
+    assert_eq!(
+        block.get_item("_struct.title").unwrap().as_string(),
+        Some("A small protein")
+    );
+    assert_eq!(block.find_loop("_struct_asym.id").unwrap().len(), 2);
+    assert!(block.find_loop("_pdbx_empty.id").unwrap().is_empty());
+}
+
+#[test]
+fn test_core_blocks_keep_core_layout() {
+    let doc = Document::from_file(fixture_path("simple.cif")).unwrap();
+    let mut written = mmcif(&doc);
+    // Only the header differs
+    written.insert_str(0, "#\\#CIF_1.1\n");
+    assert_eq!(written, doc.to_string());
+
+    let default = doc.to_string_with_options(&WriteOptions::new());
+    assert_eq!(default, doc.to_string());
+}
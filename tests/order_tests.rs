@@ -0,0 +1,106 @@
+// tests/order_tests.rs
+// Ordered iteration over block and frame tags, locked against a fixture
+
+use cif_parser::{Document, TagLocation, WriteOptions};
+use std::path::Path;
+
+fn fixture() -> Document {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ordered_items.cif");
+    Document::from_file(path).unwrap()
+}
+
+#[test]
+fn test_items_in_file_order() {
+    let doc = fixture();
+    let block = doc.first_block().unwrap();
+    let tags: Vec<&str> = block.iter_items().map(|(tag, _)| tag).collect();
+    assert_eq!(
+        tags,
+        [
+            "_symmetry_space_group_name_H-M",
+            "_cell_length_c",
+            "_cell_length_a",
+            "_cell_length_b",
+            "_chemical_name_common",
+            "_audit_creation_method",
+        ]
+    );
+    let (_, value) = block.iter_items().nth(2).unwrap();
+    assert_eq!(value.as_numeric(), Some(7.41));
+}
+
+#[test]
+fn test_all_tags_items_then_loop_columns() {
+    let doc = fixture();
+    let block = doc.first_block().unwrap();
+    let tags: Vec<_> = block.iter_all_tags().collect();
+    assert_eq!(
+        tags,
+        [
+            ("_symmetry_space_group_name_H-M", TagLocation::Item),
+            ("_cell_length_c", TagLocation::Item),
+            ("_cell_length_a", TagLocation::Item),
+            ("_cell_length_b", TagLocation::Item),
+            ("_chemical_name_common", TagLocation::Item),
+            ("_audit_creation_method", TagLocation::Item),
+            ("_atom_site_label", TagLocation::Loop(0)),
+            ("_atom_site_fract_x", TagLocation::Loop(0)),
+            ("_geom_bond_atom_site_label_1", TagLocation::Loop(1)),
+            ("_geom_bond_atom_site_label_2", TagLocation::Loop(1)),
+            ("_geom_bond_distance", TagLocation::Loop(1)),
+        ]
+    );
+}
+
+#[test]
+fn test_frame_order() {
+    let doc = fixture();
+    let frame = doc.first_block().unwrap().get_frame("restraint").unwrap();
+    let items: Vec<&str> = frame.iter_items().map(|(tag, _)| tag).collect();
+    assert_eq!(items, ["_restr_target", "_restr_atom_a"]);
+    let tags: Vec<_> = frame.iter_all_tags().collect();
+    assert_eq!(tags[2], ("_restr_pair_label", TagLocation::Loop(0)));
+    assert_eq!(tags.len(), 3);
+}
+
+#[test]
+fn test_order_survives_clone_and_edits() {
+    let doc = fixture();
+    let mut block = doc.first_block().unwrap().clone();
+    block.items.shift_remove("_cell_length_c");
+    block.items.insert(
+        "_cell_volume".to_string(),
+        cif_parser::Value::Numeric(786.5),
+    );
+    let tags: Vec<&str> = block.iter_items().map(|(tag, _)| tag).collect();
+    assert_eq!(
+        tags[..2],
+        ["_symmetry_space_group_name_H-M", "_cell_length_a"]
+    );
+    assert_eq!(tags.last(), Some(&"_cell_volume"));
+}
+
+#[test]
+fn test_order_survives_writing() {
+    let doc = fixture();
+    let reread = Document::parse(&doc.to_string()).unwrap();
+    let tags = |doc: &Document| -> Vec<String> {
+        let block = doc.first_block().unwrap();
+        let frame = block.get_frame("restraint").unwrap();
+        block
+            .iter_items()
+            .chain(frame.iter_items())
+            .map(|(tag, _)| tag.to_string())
+            .collect()
+    };
+    assert_eq!(tags(&reread), tags(&doc));
+
+    let sorted = doc.to_string_with_options(&WriteOptions::new().sort_items(true));
+    let sorted = Document::parse(&sorted).unwrap();
+    let block = sorted.first_block().unwrap();
+    let items: Vec<&str> = block.iter_items().map(|(tag, _)| tag).take(3).collect();
+    assert_eq!(
+        items,
+        ["_audit_creation_method", "_cell_length_a", "_cell_length_b"]
+    );
+}
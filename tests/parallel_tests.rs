@@ -0,0 +1,103 @@
+// tests/parallel_tests.rs
+// Parsing the blocks of one input on several threads, behind the parallel
+// feature: the same documents and errors as on one thread
+
+use cif_parser::corpus::cif_files;
+use cif_parser::{Conformance, Document, ParseOptions, Selection};
+use std::path::Path;
+
+/// Parse `input` on one thread and on four, and check both agree
+fn assert_same(input: &str, options: &ParseOptions) -> Option<Document> {
+    let sequential = Document::parse_with_options(input, options);
+    let parallel = Document::parse_with_options(input, &options.clone().threads(4));
+    match (sequential, parallel) {
+        (Ok(sequential), Ok(parallel)) => {
+            assert!(parallel.semantically_equal(&sequential));
+            assert_eq!(parallel.preamble, sequential.preamble);
+            assert_eq!(parallel.trailer, sequential.trailer);
+            assert_eq!(parallel.partial, sequential.partial);
+            let mut warnings = [sequential.warnings, parallel.warnings.clone()];
+            for list in &mut warnings {
+                list.sort_by_key(|w| (w.location, w.message.clone()));
+            }
+            assert_eq!(warnings[0], warnings[1]);
+            Some(parallel)
+        }
+        (Err(sequential), Err(parallel)) => {
+            assert_eq!(parallel.to_string(), sequential.to_string());
+            None
+        }
+        (sequential, parallel) => panic!("{:?} but {:?}", sequential.err(), parallel.err()),
+    }
+}
+
+#[test]
+fn test_every_example_parses_as_on_one_thread() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    for path in cif_files(root).unwrap() {
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            if assert_same(&text, &ParseOptions::default()).is_none() {
+                assert_same(&text, &ParseOptions::new().lenient(true));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_blocks_keep_their_order_version_and_lines() {
+    let blocks: String = (0..40)
+        .map(|i| format!("data_b{i}\n_list ['x' {i}]\n_text\n;\ndata_not_a_block\n;\n"))
+        .collect();
+    let cif = format!("#\\#CIF_2.0\n# banner comment\n{blocks}");
+    let doc = assert_same(&cif, &ParseOptions::default()).unwrap();
+    let names: Vec<_> = doc.blocks.iter().map(|b| b.name.clone()).collect();
+    assert_eq!(names.len(), 40);
+    assert_eq!(names[39], "b39");
+    assert!(doc.blocks[39]
+        .get_item("_list")
+        .unwrap()
+        .as_list()
+        .is_some());
+
+    // Warnings in late blocks point at lines of the whole input
+    let lenient = ParseOptions::new().lenient(true);
+    let broken = format!("{blocks}data_last\nloop_\n_a\n_b\n1\n");
+    let doc = assert_same(&broken, &lenient).unwrap();
+    let lines = broken.lines().count();
+    assert!(doc
+        .warnings
+        .iter()
+        .any(|w| w.location.unwrap().0 >= lines - 4));
+
+    // Selections apply to every piece
+    let selected = ParseOptions::new().select(Selection::Blocks(vec!["b7".into(), "b33".into()]));
+    let doc = assert_same(&cif, &selected).unwrap();
+    assert_eq!(doc.blocks.len(), 2);
+    assert!(doc.partial);
+    assert_same(&cif, &ParseOptions::new().select(Selection::FirstBlockOnly));
+}
+
+#[test]
+fn test_errors_are_those_of_one_thread() {
+    let blocks: String = (0..20).map(|i| format!("data_b{i}\n_x {i}\n")).collect();
+    // A syntax error in a late block
+    assert!(assert_same(
+        &format!("{blocks}data_bad\n_y 'open\n"),
+        &ParseOptions::default()
+    )
+    .is_none());
+    // A block name used twice, in different pieces
+    let strict = ParseOptions::new().conformance(Conformance::Strict11);
+    assert!(assert_same(&format!("{blocks}data_b0\n_z 1\n"), &strict).is_none());
+    // Junk after the first blocks is not a trailer
+    let lenient = ParseOptions::new().lenient(true);
+    assert_same(
+        &format!("data_a\n_x 1\n{blocks}#END\nnot cif\n{blocks}"),
+        &lenient,
+    );
+    // A banner hiding the magic comment
+    assert_same(
+        &format!("Banner text\n#\\#CIF_2.0\n{blocks}data_l\n_l [1 2]\n"),
+        &lenient,
+    );
+}
@@ -8,8 +8,9 @@
 //! - Empty container name validation
 //! - CIF 2.0 feature gating (lists, tables, triple-quoted strings)
 //! - Reserved character handling
+//! - STAR-only constructs (`stop_`, nested loops)
 
-use cif_parser::{CifDocument, CifVersion};
+use cif_parser::{CifDocument, CifError, CifVersion, ParseOptions};
 
 // ========================================================================
 // Version Detection Tests
@@ -141,6 +142,51 @@ fn test_tables_as_text_in_cif1() {
     assert_eq!(item.as_string(), Some("{not_a_table}"));
 }
 
+#[test]
+fn test_quoted_members_close_before_brackets_in_cif2() {
+    // CIF 2.0: A closing quote may be followed directly by `]` or `}`
+    let cif2 = "#\\#CIF_2.0\ndata_test\n_l ['a' \"b\"]\n_t {'k':'v' \"n\":\"w\"}\n\
+                _nested [['x'] {'k':\"y\"}]\n_q 'it's']'\nloop_\n_m\n['c' 'd']\n";
+    let doc = CifDocument::parse(cif2).unwrap();
+    let block = doc.first_block().unwrap();
+
+    let list = block.items.get("_l").unwrap().as_list().unwrap();
+    let members: Vec<_> = list.iter().map(|v| v.as_string().unwrap()).collect();
+    assert_eq!(members, ["a", "b"]);
+
+    let table = block.items.get("_t").unwrap().as_table().unwrap();
+    assert_eq!(table.get("k").unwrap().as_string(), Some("v"));
+    assert_eq!(table.get("n").unwrap().as_string(), Some("w"));
+
+    let nested = block.items.get("_nested").unwrap().as_list().unwrap();
+    assert_eq!(nested[0].as_list().unwrap()[0].as_string(), Some("x"));
+    let inner = nested[1].as_table().unwrap();
+    assert_eq!(inner.get("k").unwrap().as_string(), Some("y"));
+
+    // Outside a list or table, `]` after a quote is still part of the string
+    assert_eq!(block.items.get("_q").unwrap().as_string(), Some("it's']"));
+
+    let looped = block.find_loop("_m").unwrap();
+    let members = looped.get(0, 0).unwrap().as_list().unwrap();
+    assert_eq!(members[1].as_string(), Some("d"));
+}
+
+#[test]
+fn test_quoted_members_stay_text_in_cif1() {
+    // CIF 1.1: The same bracketed values are kept as raw text
+    let cif1 = "data_test\n_l ['a' 'b']\n_t {'k':'v'}\n";
+    let doc = CifDocument::parse(cif1).unwrap();
+    let block = doc.first_block().unwrap();
+    assert_eq!(
+        block.items.get("_l").unwrap().as_string(),
+        Some("['a' 'b']")
+    );
+    assert_eq!(
+        block.items.get("_t").unwrap().as_string(),
+        Some("{'k':'v'}")
+    );
+}
+
 // ========================================================================
 // CIF 2.0 Feature Gating: Triple-Quoted Strings
 // ========================================================================
@@ -310,3 +356,172 @@ fn test_reserved_characters_must_be_quoted() {
         Some("{text}")
     );
 }
+
+// ========================================================================
+// STAR Constructs (stop_ and nested loops)
+// ========================================================================
+
+/// An NMR-STAR style nested loop, as found in files mislabeled as CIF
+const NMR_STAR_NESTED_LOOP: &str = "\
+data_nmr
+loop_
+    _Atom_chem_shift.ID
+    _Atom_chem_shift.Atom_ID
+    loop_
+        _Coupling.ID
+        _Coupling.Val
+    1 HA
+        1 7.2
+        2 3.1
+    stop_
+    2 HB
+        3 5.0
+    stop_
+stop_
+_Entry.ID 15000
+";
+
+#[test]
+fn test_nested_star_loop_rejected_with_location() {
+    let err = CifDocument::parse(NMR_STAR_NESTED_LOOP).unwrap_err();
+    match err {
+        CifError::InvalidStructure {
+            message, location, ..
+        } => {
+            assert!(message.contains("Nested loops are not valid in CIF"));
+            assert!(message.contains("STAR/NMR-STAR"));
+            // Points at the first stop_
+            assert_eq!(location, Some((11, 5)));
+        }
+        other => panic!("Expected InvalidStructure, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_stop_after_flat_loop_rejected() {
+    let cif = "data_test\nloop_\n_a\n_b\n1 2\n3 4\nstop_\n_c 5\n";
+    let err = CifDocument::parse(cif).unwrap_err();
+    assert!(matches!(
+        err,
+        CifError::InvalidStructure {
+            location: Some((7, 1)),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_stop_flattened_in_lenient_mode() {
+    let cif = "data_test\nloop_\n_a\n_b\n1 2\n3 4\nstop_\n_c 5\n";
+    let options = ParseOptions::new().lenient(true);
+    let doc = CifDocument::parse_with_options(cif, &options).unwrap();
+    let block = doc.first_block().unwrap();
+
+    assert_eq!(block.loops[0].len(), 2);
+    assert_eq!(block.get_item("_c").unwrap().as_numeric(), Some(5.0));
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].location, Some((7, 1)));
+}
+
+#[test]
+fn test_nested_star_loop_flattened_in_lenient_mode() {
+    let options = ParseOptions::new().lenient(true);
+    let doc = CifDocument::parse_with_options(NMR_STAR_NESTED_LOOP, &options).unwrap();
+    let block = doc.first_block().unwrap();
+
+    // The loop is closed at the first stop_; trailing nested values are dropped, not misfiled
+    let inner = block.find_loop("_Coupling.ID").unwrap();
+    assert_eq!(inner.len(), 3);
+    assert_eq!(
+        block.get_item("_Entry.ID").unwrap().as_numeric(),
+        Some(15000.0)
+    );
+
+    assert_eq!(doc.warnings.len(), 1);
+    assert!(doc.warnings[0].message.contains("4 nested-loop value(s)"));
+}
+
+#[test]
+fn test_unclosed_nested_star_loop_rejected_in_lenient_mode() {
+    // The second packet's nested loop and the outer loop have no stop_
+    let unclosed = NMR_STAR_NESTED_LOOP.replace("    stop_\nstop_\n", "");
+    let options = ParseOptions::new().lenient(true);
+    for cif in [unclosed.clone(), unclosed.replace("_Entry.ID 15000\n", "")] {
+        let err = CifDocument::parse_with_options(&cif, &options).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Nested loop is still open"), "{message}");
+        assert!(message.contains("'2'"), "{message}");
+        assert_eq!(err.location(), Some((12, 5)));
+    }
+}
+
+#[test]
+fn test_stop_is_not_accepted_as_a_value() {
+    // Quoted, it is ordinary data
+    let doc = CifDocument::parse("data_test\n_item 'stop_'\n").unwrap();
+    assert_eq!(
+        doc.first_block()
+            .unwrap()
+            .get_item("_item")
+            .unwrap()
+            .as_string(),
+        Some("stop_")
+    );
+}
+
+// ========================================================================
+// Whitespace (tabs and Unicode space characters)
+// ========================================================================
+
+#[test]
+fn test_tabs_are_whitespace() {
+    let cif = "data_test\n_a\t1\n\t_b\t'x y'\t# note\nloop_\t_c\t_d\n1\t2\n\t3 4\n";
+    let doc = CifDocument::parse(cif).unwrap();
+    let block = doc.first_block().unwrap();
+
+    assert_eq!(block.get_item("_a").unwrap().as_numeric(), Some(1.0));
+    assert_eq!(block.get_item("_b").unwrap().as_string(), Some("x y"));
+    assert_eq!(block.loops[0].tags, vec!["_c", "_d"]);
+    assert_eq!(block.loops[0].len(), 2);
+}
+
+#[test]
+fn test_unicode_space_rejected_with_codepoint() {
+    let cif = "data_test\n_cell_length_a\u{a0}5.43\n";
+    let err = CifDocument::parse(cif).unwrap_err();
+    match err {
+        CifError::InvalidStructure {
+            message, location, ..
+        } => {
+            assert!(message.contains("U+00A0 NO-BREAK SPACE"));
+            assert_eq!(location, Some((2, 15)));
+        }
+        other => panic!("Expected InvalidStructure, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_unicode_space_normalized_in_lenient_mode() {
+    let cif = "data_test\n_cell_length_a\u{a0}5.43\u{200b}\n_b 1\n";
+    let options = ParseOptions::new().lenient(true);
+    let doc = CifDocument::parse_with_options(cif, &options).unwrap();
+    let block = doc.first_block().unwrap();
+
+    assert_eq!(
+        block.get_item("_cell_length_a").unwrap().as_numeric(),
+        Some(5.43)
+    );
+    assert_eq!(doc.warnings.len(), 2);
+    assert!(doc.warnings[1].message.contains("U+200B ZERO WIDTH SPACE"));
+    assert_eq!(doc.warnings[1].location, Some((2, 20)));
+}
+
+#[test]
+fn test_unicode_space_inside_strings_is_content() {
+    let cif = "#\\#CIF_2.0\ndata_test\n_a 'b\u{a0}c'\n_b\n;x\u{a0}y\n;\n# z\u{a0}\n";
+    let doc = CifDocument::parse(cif).unwrap();
+    let block = doc.first_block().unwrap();
+
+    assert_eq!(block.get_item("_a").unwrap().as_string(), Some("b\u{a0}c"));
+    assert_eq!(block.get_item("_b").unwrap().as_string(), Some("x\u{a0}y"));
+}
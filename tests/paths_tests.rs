@@ -0,0 +1,122 @@
+// tests/paths_tests.rs
+// File names that are not valid UTF-8, and paths in errors and scans
+
+use cif_parser::corpus::{cif_files, long_path};
+use cif_parser::{extract_items, CifError, Document, ExtractOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A fresh scratch directory for one test
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cif-paths-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// `caf\xe9.cif`, Latin-1 for "café", which is not valid UTF-8
+#[cfg(unix)]
+fn latin1_name() -> std::ffi::OsString {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    OsString::from_vec(b"caf\xe9.cif".to_vec())
+}
+
+/// Write a small CIF under a name that is not valid UTF-8, or `None` where
+/// the file system refuses such names (as macOS does)
+#[cfg(unix)]
+fn non_utf8_file(dir: &Path) -> Option<PathBuf> {
+    let path = dir.join(latin1_name());
+    fs::write(&path, "data_cafe\n_cell_length_a 5.431\n").ok()?;
+    Some(path)
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_file_name_parses() {
+    let dir = scratch("parse");
+    let Some(path) = non_utf8_file(&dir) else {
+        return;
+    };
+    assert!(path.to_str().is_none());
+
+    let doc = Document::from_file(&path).unwrap();
+    assert_eq!(doc.blocks[0].name, "cafe");
+    assert_eq!(doc.source.unwrap().path.as_deref(), Some(path.as_path()));
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_error_keeps_non_utf8_path() {
+    let dir = scratch("error");
+    let path = dir.join(latin1_name());
+
+    let err = Document::from_file(&path).unwrap_err();
+    assert_eq!(err.path(), Some(path.as_path()));
+    assert!(matches!(&err, CifError::InFile { error, .. } if matches!(**error, CifError::Io(_))));
+    // The message shows the name as well as it can
+    assert!(err.to_string().contains("caf\u{fffd}.cif"), "{err}");
+
+    if fs::write(&path, "data_x\n_a 'open\n").is_ok() {
+        let err = Document::from_file(&path).unwrap_err();
+        assert_eq!(err.path(), Some(path.as_path()));
+    }
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scanner_and_extract_keep_paths() {
+    let dir = scratch("scan");
+    let Some(path) = non_utf8_file(&dir) else {
+        return;
+    };
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(
+        dir.join("sub/plain.cif"),
+        "data_plain\n_cell_length_a 3.0\n",
+    )
+    .unwrap();
+
+    let files = cif_files(&dir).unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files.contains(&path));
+
+    let records = extract_items(&files, &["_cell_length_a"], &ExtractOptions::new());
+    let record = records.iter().find(|r| r.path == path).unwrap();
+    assert_eq!(record.block.as_deref(), Some("cafe"));
+    assert!(record.error.is_none());
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_scanner_error_names_directory() {
+    let missing = scratch("missing").join("not-here");
+    let err = cif_files(&missing).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("not-here"), "{err}");
+}
+
+#[test]
+fn test_long_path_leaves_short_paths_alone() {
+    let path = Path::new("tests/fixtures/flatten/search.cif");
+    assert_eq!(long_path(path), path);
+}
+
+#[cfg(windows)]
+#[test]
+fn test_long_path_prefix() {
+    let deep: PathBuf = std::iter::repeat("a".repeat(50)).take(6).collect();
+    let long = long_path(&deep);
+    assert!(long.as_os_str().to_string_lossy().starts_with(r"\\?\"));
+    assert!(long.ends_with(&deep));
+
+    let share = Path::new(r"\\server\share").join(&deep);
+    let long = long_path(&share);
+    assert!(long
+        .as_os_str()
+        .to_string_lossy()
+        .starts_with(r"\\?\UNC\server\share\"));
+}
@@ -0,0 +1,150 @@
+// tests/placeholder_text_tests.rs
+// Quoted '?' and '.' are text; only the bare tokens are unknown or not applicable
+
+use cif_parser::schema::TagStatus;
+use cif_parser::{CifValue, Document, ExpectedType};
+use std::path::Path;
+
+fn text(value: &str) -> CifValue {
+    CifValue::Text(value.to_string())
+}
+
+fn markers() -> Document {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/placeholder_text.cif");
+    Document::from_file(path).unwrap()
+}
+
+#[test]
+fn test_quoted_markers_parse_as_text() {
+    let doc = markers();
+    let block = &doc.blocks[0];
+    assert_eq!(block.get_item("_chemical_name_common"), Some(&text("?")));
+    assert_eq!(
+        block.get_item("_chemical_name_systematic"),
+        Some(&text("."))
+    );
+    assert_eq!(block.get_item("_publ_section_comment"), Some(&text("?")));
+    assert_eq!(
+        block.get_item("_chemical_melting_point"),
+        Some(&CifValue::Unknown)
+    );
+    assert_eq!(
+        block.get_item("_chemical_compound_source"),
+        Some(&CifValue::NotApplicable)
+    );
+
+    let atoms = &block.loops[0];
+    assert_eq!(
+        atoms.get_column("_atom_site_calc_flag").unwrap(),
+        [&text("?"), &CifValue::Unknown, &text("?")]
+    );
+    assert_eq!(
+        atoms.get_column("_atom_site_refinement_flags").unwrap(),
+        [&CifValue::NotApplicable, &text("."), &text("P")]
+    );
+
+    // CIF 2.0 triple quotes and a one-line parse give the same
+    let cif2 = "#\\#CIF_2.0\ndata_x\n_a '''?'''\n_b \"\"\".\"\"\"\n_c ?\n";
+    let doc = Document::parse(cif2).unwrap();
+    let items: Vec<&CifValue> = doc.blocks[0].items.values().collect();
+    assert_eq!(items, [&text("?"), &text("."), &CifValue::Unknown]);
+    assert_eq!(CifValue::parse_value("'?'"), text("?"));
+    assert_eq!(CifValue::parse_value("?"), CifValue::Unknown);
+}
+
+#[test]
+fn test_round_trip_keeps_the_distinction() {
+    let doc = markers();
+    for version in ["1.1", "2.0"] {
+        let mut doc = doc.clone();
+        if version == "2.0" {
+            doc.version = cif_parser::CifVersion::V2_0;
+        }
+        let written = doc.to_string();
+        assert!(written.contains("_chemical_name_common '?'"), "{written}");
+        assert!(
+            written.contains("_chemical_name_systematic '.'"),
+            "{written}"
+        );
+        assert!(written.contains("_chemical_melting_point ?"), "{written}");
+        let back = Document::parse(&written).unwrap();
+        let (back, block) = (&back.blocks[0], &doc.blocks[0]);
+        assert_eq!(back.items, block.items, "{written}");
+        assert_eq!(back.loops[0].values, block.loops[0].values, "{written}");
+    }
+
+    // Text built in code is quoted the same way
+    let mut doc = Document::parse("data_x\n_a x\n").unwrap();
+    doc.blocks[0].items.insert("_a".into(), text("?"));
+    doc.blocks[0].items.insert("_b".into(), CifValue::Unknown);
+    let written = doc.to_string();
+    assert!(written.contains("_a '?'\n_b ?"), "{written}");
+}
+
+#[test]
+fn test_typed_getters_read_quoted_markers_as_text() {
+    let doc = markers();
+    let block = &doc.blocks[0];
+    assert_eq!(
+        block
+            .get_as::<Option<String>>("_chemical_name_common")
+            .unwrap(),
+        Some(Some("?".to_string()))
+    );
+    assert_eq!(
+        block
+            .get_as::<Option<String>>("_chemical_melting_point")
+            .unwrap(),
+        Some(None)
+    );
+    assert_eq!(
+        block.get_as::<String>("_chemical_name_systematic").unwrap(),
+        Some(".".to_string())
+    );
+    assert!(block.get_as::<String>("_chemical_compound_source").is_err());
+    // Text is not a number, even when it looks like a marker
+    assert!(block
+        .get_as::<Option<f64>>("_chemical_name_common")
+        .is_err());
+}
+
+#[test]
+fn test_columns_and_schema_treat_text_as_present() {
+    let doc = markers();
+    let block = &doc.blocks[0];
+    let atoms = &block.loops[0];
+    // Bare markers become NaN; text is not a number
+    let occupancy = atoms
+        .numeric_column("_atom_site_occupancy")
+        .unwrap()
+        .unwrap();
+    assert!(occupancy[1].is_nan());
+    assert_eq!(atoms.numeric_column("_atom_site_calc_flag"), Some(Err(0)));
+
+    let report = block.coercion_report(&[
+        ("_chemical_name_common", ExpectedType::Text),
+        ("_chemical_melting_point", ExpectedType::F64),
+        ("_atom_site_calc_flag", ExpectedType::Enum(&["d", "calc"])),
+    ]);
+    assert_eq!(
+        report.get("_chemical_name_common").unwrap().status,
+        TagStatus::Ok
+    );
+    assert_eq!(
+        report.get("_chemical_melting_point").unwrap().status,
+        TagStatus::Unknown
+    );
+    let flags = report.get("_atom_site_calc_flag").unwrap();
+    assert_eq!(flags.status, TagStatus::Mismatch);
+    let rows: Vec<Option<usize>> = flags.mismatches.iter().map(|m| m.row).collect();
+    assert_eq!(rows, [Some(0), Some(2)]);
+}
+
+#[test]
+fn test_extracted_csv_quotes_text_markers() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/placeholder_text.cif");
+    let tags = ["_chemical_name_common", "_chemical_melting_point"];
+    let records = cif_parser::extract_items(&[path], &tags, &cif_parser::ExtractOptions::new());
+    let csv = cif_parser::corpus::extract_csv(&records, &tags);
+    assert!(csv.ends_with(",markers,'?',?,\n"), "{csv}");
+}
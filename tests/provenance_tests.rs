@@ -0,0 +1,140 @@
+// tests/provenance_tests.rs
+// Original values kept by aggregators, compared with and restored over the current ones
+
+use cif_parser::provenance::ProvenanceOptions;
+use cif_parser::{Document, Value};
+use std::path::Path;
+
+fn fixture() -> Document {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/provenance/cod_normalized.cif");
+    Document::from_file(path).unwrap()
+}
+
+#[test]
+fn test_overrides_pair_originals_with_current_values() {
+    let doc = fixture();
+    let overrides = doc.blocks[0].provenance_overrides();
+    let found: Vec<(&str, &str, bool)> = overrides
+        .iter()
+        .map(|o| (o.original_tag.as_str(), o.tag.as_str(), o.differs))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            // 636.4 is within the su of 636.3(2)
+            ("_cod_original_cell_volume", "_cell_volume", false),
+            (
+                "_cod_original_sg_symbol_H-M",
+                "_symmetry_space_group_name_H-M",
+                true
+            ),
+            // Only trailing space differs
+            ("_cod_original_formula_sum", "_chemical_formula_sum", false),
+            ("_iucr_original_cell_length_a", "_cell_length_a", true),
+        ]
+    );
+
+    let volume = &overrides[0];
+    assert!((volume.sigmas.unwrap() - 0.5).abs() < 1e-9);
+    let symbol = &overrides[1];
+    assert_eq!(symbol.original, Value::Text("P 21/c".into()));
+    assert_eq!(symbol.current, Some(Value::Text("P 1 21/c 1".into())));
+    assert_eq!(symbol.sigmas, None);
+    assert_eq!(
+        symbol.to_string(),
+        "_symmetry_space_group_name_H-M: P 1 21/c 1, originally P 21/c \
+         (_cod_original_sg_symbol_H-M)"
+    );
+    // The su of the current a makes the difference 12.5σ
+    assert!(overrides[3].sigmas.unwrap() > 12.0);
+}
+
+#[test]
+fn test_configured_prefixes_and_tolerance() {
+    let doc = fixture();
+    let block = &doc.blocks[0];
+
+    let cod_only = ProvenanceOptions::new().prefixes(["_cod_original_"]);
+    assert_eq!(block.provenance_overrides_with_options(&cod_only).len(), 3);
+
+    let strict = ProvenanceOptions::new().n_sigma(0.1);
+    let overrides = block.provenance_overrides_with_options(&strict);
+    assert!(overrides[0].differs);
+
+    let mut doc = Document::parse("data_x\n_ccdc_original_cell_volume 99\n").unwrap();
+    let options = ProvenanceOptions::new().prefix("_ccdc_original_");
+    let overrides = doc.blocks[0].provenance_overrides_with_options(&options);
+    // A missing current value counts as differing
+    assert_eq!(overrides[0].current, None);
+    assert!(overrides[0].differs);
+
+    let restored = doc.blocks[0]
+        .restore_original_with_options("_cell_volume", &options)
+        .unwrap();
+    assert_eq!(restored.current, None);
+    assert_eq!(
+        doc.blocks[0].get_item("_audit_update_record"),
+        Some(&Value::Text(
+            "Restored _cell_volume to 99 from _ccdc_original_cell_volume, which was missing".into()
+        ))
+    );
+}
+
+#[test]
+fn test_restore_space_group_symbol() {
+    let mut doc = fixture();
+    let block = &mut doc.blocks[0];
+
+    let restored = block
+        .restore_original("_symmetry_space_group_name_H-M")
+        .unwrap();
+    assert_eq!(restored.original_tag, "_cod_original_sg_symbol_H-M");
+    assert_eq!(
+        block.get_item("_symmetry_space_group_name_H-M"),
+        Some(&Value::Text("P 21/c".into()))
+    );
+    assert!(block.get_item("_cod_original_sg_symbol_H-M").is_none());
+    assert_eq!(
+        block
+            .get_item("_audit_update_record")
+            .and_then(Value::as_string),
+        Some(
+            "Restored _symmetry_space_group_name_H-M to P 21/c from \
+             _cod_original_sg_symbol_H-M, replacing P 1 21/c 1"
+        )
+    );
+    assert!(!block
+        .provenance_overrides()
+        .iter()
+        .any(|o| o.tag == "_symmetry_space_group_name_H-M"));
+
+    // The tag holding the original works too, and entries accumulate
+    block.restore_original("_cod_original_cell_volume").unwrap();
+    let record = block.get_item("_audit_update_record").unwrap();
+    assert_eq!(record.as_string().unwrap().lines().count(), 2);
+
+    let err = block.restore_original("_cell_length_b").unwrap_err();
+    assert!(err.to_string().contains("_cell_length_b"));
+
+    // The restored block still writes and parses back
+    let reparsed = Document::parse(&doc.to_string()).unwrap();
+    assert!(reparsed.semantically_equal(&doc));
+}
+
+#[test]
+fn test_audit_record_loop_gets_a_row() {
+    let mut doc = Document::parse(
+        "data_x\n_cell_volume 10\n_cod_original_cell_volume 12\n\
+         loop_\n_audit_update_record\n_audit_creation_date\n'first change' 2001-01-01\n",
+    )
+    .unwrap();
+    doc.blocks[0].restore_original("_cell_volume").unwrap();
+
+    let audit = doc.blocks[0].find_loop("_audit_update_record").unwrap();
+    assert_eq!(audit.len(), 2);
+    assert_eq!(
+        audit.get_column("_audit_creation_date").unwrap()[1],
+        &Value::Unknown
+    );
+}
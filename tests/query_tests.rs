@@ -0,0 +1,206 @@
+// tests/query_tests.rs
+// The query selector language: each part, combinations, and errors
+
+use cif_parser::{query, uncertainty, CifError, Document, Query, QueryHit};
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/query/two_phases.cif";
+
+fn fixture() -> Document {
+    Document::from_file(Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURE)).unwrap()
+}
+
+/// Each hit as `block tag row=value`
+fn show(hits: &[QueryHit]) -> Vec<String> {
+    hits.iter()
+        .map(|hit| {
+            let value = match (hit.value.as_string(), hit.value.as_numeric_with_su()) {
+                (Some(text), _) => text.to_string(),
+                (None, Some((number, Some(su)))) => uncertainty::format_exact(number, su),
+                _ => format!("{:?}", hit.value),
+            };
+            match hit.row {
+                Some(row) => format!("{} {}[{row}]={value}", hit.block, hit.tag),
+                None => format!("{} {}={value}", hit.block, hit.tag),
+            }
+        })
+        .collect()
+}
+
+fn run(selector: &str) -> Vec<String> {
+    show(&query(&fixture(), selector).unwrap())
+}
+
+#[test]
+fn test_block_selectors() {
+    let every = run("_cell_length_a");
+    assert_eq!(every.len(), 2);
+    assert_eq!(run("block[*]._cell_length_a"), every);
+
+    assert_eq!(run("block[0]._cell_length_a"), [every[0].clone()]);
+    assert_eq!(run("block[PHASE_B]._cell_length_a"), [every[1].clone()]);
+    assert_eq!(run("block[phase_*]._cell_length_a"), every);
+    assert_eq!(
+        run("block[-1]._diffrn_ambient_temperature"),
+        ["experiment _diffrn_ambient_temperature=100(2)"]
+    );
+    assert!(run("block[7]._cell_length_a").is_empty());
+    assert!(run("block[-7]._cell_length_a").is_empty());
+}
+
+#[test]
+fn test_item_and_column_tags() {
+    assert_eq!(
+        run("block[0]._cell_length_?"),
+        [
+            "phase_a _cell_length_a=5.4307(2)",
+            "phase_a _cell_length_b=12.114(3)",
+            "phase_a _cell_length_c=9.871(2)",
+        ]
+    );
+    // A looped tag gives every row
+    assert_eq!(
+        run("_atom_type_description"),
+        [
+            "phase_a _atom_type_description[0]=Carbon",
+            "phase_a _atom_type_description[1]=Oxygen",
+        ]
+    );
+}
+
+#[test]
+fn test_loop_selectors_and_columns() {
+    // By category, by a tag of the loop, and in mmCIF form
+    let labels = run("block[0].loop(_atom_site)._atom_site_label");
+    assert_eq!(labels.len(), 5);
+    assert_eq!(run("block[0].loop(_atom_site_fract_x).label"), labels);
+    assert_eq!(
+        run("loop(_atom_site).label"),
+        [
+            labels.clone(),
+            vec![
+                "phase_b _atom_site.label[0]=Fe1".to_string(),
+                "phase_b _atom_site.label[1]=S1".to_string(),
+            ]
+        ]
+        .concat()
+    );
+
+    // Several columns, row by row, and every column without any named
+    assert_eq!(
+        run("block[1].loop(_atom_site).(label, _atom_site.occupancy)"),
+        [
+            "phase_b _atom_site.label[0]=Fe1",
+            "phase_b _atom_site.occupancy[0]=Numeric(1.0)",
+            "phase_b _atom_site.label[1]=S1",
+            "phase_b _atom_site.occupancy[1]=0.9(1)",
+        ]
+    );
+    assert_eq!(run("block[1].loop(_atom_site)").len(), 6);
+    assert!(run("loop(_atom_site_aniso)").is_empty());
+}
+
+#[test]
+fn test_row_predicates() {
+    let labels = |predicate: &str| -> Vec<String> {
+        query(
+            &fixture(),
+            &format!("block[0].loop(_atom_site).rows[{predicate}].label"),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.value.as_string().unwrap().to_string())
+        .collect()
+    };
+    assert_eq!(labels("occupancy<1"), ["C2", "N1"]);
+    assert_eq!(labels("occupancy >= 1"), ["C1", "H1"]);
+    assert_eq!(labels("occupancy=0.5"), ["C2"]);
+    assert_eq!(labels("type_symbol=C"), ["C1", "C2"]);
+    assert_eq!(labels("type_symbol != C"), ["O1", "N1", "H1"]);
+    assert_eq!(labels("calc_flag='calc'"), ["H1"]);
+    assert_eq!(labels("label~c*"), ["C1", "C2"]);
+    assert_eq!(labels("fract_x>0.3 & fract_x<0.5"), ["O1", "N1"]);
+    assert_eq!(labels("type_symbol=C & occupancy<1 & label~'C?'"), ["C2"]);
+}
+
+#[test]
+fn test_predicates_on_missing_values() {
+    let labels = |predicate: &str| -> Vec<String> {
+        query(
+            &fixture(),
+            &format!("block[0].loop(_atom_site).rows[{predicate}].label"),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.value.as_string().unwrap().to_string())
+        .collect()
+    };
+    // `?` is neither below nor above a number, nor different from one
+    assert!(!labels("occupancy<1").contains(&"O1".to_string()));
+    assert!(!labels("occupancy!=1").contains(&"O1".to_string()));
+    assert_eq!(labels("occupancy=?"), ["O1"]);
+    assert_eq!(labels("occupancy!=?").len(), 4);
+    assert_eq!(labels("calc_flag=."), ["N1"]);
+    assert_eq!(labels("calc_flag!=d"), ["H1"]);
+    // Text never compares with a number
+    assert!(labels("type_symbol<5").is_empty());
+    // A column the loop lacks holds for no row
+    assert!(labels("U_iso_or_equiv<0.1").is_empty());
+    assert!(labels("U_iso_or_equiv=?").is_empty());
+}
+
+#[test]
+fn test_errors_point_at_the_character() {
+    let error = Query::parse("block[*].loop(_atom_site).rows[occupancy 1]").unwrap_err();
+    assert_eq!(error.code(), "E0013");
+    assert!(matches!(error, CifError::InvalidQuery { position: 42, .. }));
+    let expected = format!(
+        "[E0013] Invalid query at character 42: expected one of = != < <= > >= ~\n  \
+         block[*].loop(_atom_site).rows[occupancy 1]\n  {}^",
+        " ".repeat(41)
+    );
+    assert_eq!(error.to_string(), expected);
+
+    let position = |selector: &str| match Query::parse(selector).unwrap_err() {
+        CifError::InvalidQuery { position, .. } => position,
+        other => panic!("{other}"),
+    };
+    assert_eq!(position(""), 1);
+    assert_eq!(position("block[]._a"), 7);
+    assert_eq!(position("block[0]_a"), 9);
+    assert_eq!(position("loop(atom_site)"), 6);
+    assert_eq!(position("loop(_atom_site).rows[label=]"), 29);
+    assert_eq!(position("loop(_atom_site).(label,)"), 25);
+    assert_eq!(position("_a extra"), 4);
+}
+
+#[test]
+fn test_cli_prints_hits() {
+    let cif = |selector: &str| {
+        Command::new(env!("CARGO_BIN_EXE_cif"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args(["query", selector, FIXTURE])
+            .output()
+            .unwrap()
+    };
+
+    let found = cif("block[*].loop(_atom_site).rows[occupancy<1].label");
+    assert!(found.status.success());
+    assert_eq!(
+        String::from_utf8(found.stdout).unwrap(),
+        format!(
+            "{FIXTURE}: phase_a _atom_site_label[1] = C2\n\
+             {FIXTURE}: phase_a _atom_site_label[3] = N1\n\
+             {FIXTURE}: phase_b _atom_site.label[1] = S1\n"
+        )
+    );
+
+    assert_eq!(cif("_refine_ls_R_factor_gt").status.code(), Some(1));
+
+    let invalid = cif("block[*.label");
+    assert_eq!(invalid.status.code(), Some(2));
+    assert!(String::from_utf8(invalid.stderr)
+        .unwrap()
+        .contains("Invalid query at character"));
+}
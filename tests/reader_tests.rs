@@ -0,0 +1,228 @@
+// tests/reader_tests.rs
+// Streaming through CIF as events: agreement with the parser, selection, and errors
+
+use cif_parser::reader::{CifReader, Event};
+use cif_parser::{CifBlock, CifFrame, CifLoop, CifValue, CifVersion, Document, Selection};
+use std::path::{Path, PathBuf};
+
+fn events(cif: &str) -> Vec<Event> {
+    CifReader::new(cif.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn error(cif: &str) -> String {
+    let result: Result<Vec<Event>, _> = CifReader::new(cif.as_bytes()).collect();
+    result.unwrap_err().to_string()
+}
+
+fn text(s: &str) -> CifValue {
+    CifValue::Text(s.to_string())
+}
+
+/// Put a document back together from its events
+fn rebuild(cif: &str) -> Document {
+    let mut reader = CifReader::new(cif.as_bytes());
+    let mut blocks: Vec<CifBlock> = Vec::new();
+    let mut frame: Option<CifFrame> = None;
+    let mut loop_: Option<CifLoop> = None;
+    for event in reader.by_ref() {
+        let block = blocks.last_mut();
+        match event.unwrap() {
+            Event::BlockStart { name } => blocks.push(CifBlock::new(name)),
+            Event::FrameStart { name } => frame = Some(CifFrame::new(name)),
+            Event::FrameEnd => block.unwrap().frames.push(frame.take().unwrap()),
+            Event::Item { tag, value } => {
+                let items = match &mut frame {
+                    Some(frame) => &mut frame.items,
+                    None => &mut block.unwrap().items,
+                };
+                // A repeated tag's last value goes where it was written, as
+                // the parser puts it
+                items.shift_remove(&tag);
+                items.insert(tag, value);
+            }
+            Event::LoopStart { tags } => loop_ = Some(CifLoop::new(tags)),
+            Event::LoopRow(row) => loop_.as_mut().unwrap().values.push(row),
+            Event::LoopEnd => match &mut frame {
+                Some(frame) => frame.loops.push(loop_.take().unwrap()),
+                None => block.unwrap().loops.push(loop_.take().unwrap()),
+            },
+            Event::BlockEnd => assert!(frame.is_none() && loop_.is_none()),
+        }
+    }
+    let mut doc = Document::parse("").unwrap();
+    doc.blocks = blocks;
+    doc.version = reader.version();
+    doc
+}
+
+fn fixtures(dir: &Path, found: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            fixtures(&path, found);
+        } else if path.extension().is_some_and(|ext| ext == "cif") {
+            found.push(path);
+        }
+    }
+}
+
+#[test]
+fn test_events_agree_with_the_parser() {
+    let mut paths = Vec::new();
+    fixtures(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"),
+        &mut paths,
+    );
+    let mut compared = 0;
+    for path in paths {
+        let Ok(cif) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(parsed) = Document::parse(&cif) else {
+            continue;
+        };
+        if parsed.preamble.is_some() || parsed.trailer.is_some() || parsed.bare_frames {
+            continue;
+        }
+        let rebuilt = rebuild(&cif);
+        assert!(
+            rebuilt.semantically_equal(&parsed),
+            "{}: {:?}",
+            path.display(),
+            rebuilt.blocks
+        );
+        assert_eq!(
+            rebuilt.to_string(),
+            parsed.to_string(),
+            "{}",
+            path.display()
+        );
+        compared += 1;
+    }
+    assert!(compared > 50, "only {compared} fixtures compared");
+}
+
+#[test]
+fn test_event_order() {
+    let cif = "data_a\n_x 1\nloop_\n_y\n_z\n1 2\n3 4\nsave_f\n_w ?\nsave_\ndata_b\n";
+    assert_eq!(
+        events(cif),
+        [
+            Event::BlockStart { name: "a".into() },
+            Event::Item {
+                tag: "_x".into(),
+                value: CifValue::Numeric(1.0)
+            },
+            Event::LoopStart {
+                tags: vec!["_y".into(), "_z".into()]
+            },
+            Event::LoopRow(vec![CifValue::Numeric(1.0), CifValue::Numeric(2.0)]),
+            Event::LoopRow(vec![CifValue::Numeric(3.0), CifValue::Numeric(4.0)]),
+            Event::LoopEnd,
+            Event::FrameStart { name: "f".into() },
+            Event::Item {
+                tag: "_w".into(),
+                value: CifValue::Unknown
+            },
+            Event::FrameEnd,
+            Event::BlockEnd,
+            Event::BlockStart { name: "b".into() },
+            Event::BlockEnd,
+        ]
+    );
+
+    // CIF 1.1 has no lists; as in the parser, the value is the text
+    assert_eq!(
+        events("data_a\n_x [1\n2]\n")[1],
+        Event::Item {
+            tag: "_x".into(),
+            value: text("[1\n2]")
+        }
+    );
+}
+
+#[test]
+fn test_values_spanning_lines() {
+    let cif = "#\\#CIF_2.0\ndata_a\n_text\n;\nline 1\ndata_inside\n;\n_list [1\n [2 3]\n]\n\
+               _table {'k':\"\"\"v\n w\"\"\"}\n";
+    let mut reader = CifReader::new(cif.as_bytes());
+    let events: Vec<Event> = reader.by_ref().map(Result::unwrap).collect();
+    assert_eq!(reader.version(), CifVersion::V2_0);
+    assert_eq!(
+        events[1],
+        Event::Item {
+            tag: "_text".into(),
+            value: text("line 1\ndata_inside")
+        }
+    );
+    assert_eq!(
+        events[2],
+        Event::Item {
+            tag: "_list".into(),
+            value: CifValue::List(vec![
+                CifValue::Numeric(1.0),
+                CifValue::List(vec![CifValue::Numeric(2.0), CifValue::Numeric(3.0)]),
+            ])
+        }
+    );
+    let Event::Item { value, .. } = &events[3] else {
+        panic!("{events:?}");
+    };
+    assert_eq!(value.as_table_get("k"), Some(&text("v\n w")));
+    assert_eq!(events.len(), 5);
+}
+
+#[test]
+fn test_selection_skips_content() {
+    let cif = "data_a\n_cell_length_a 5\n_title x\nloop_\n_atom_site_label\nC1\n\
+               loop_\n_refln_index_h\n1\n2\ndata_b\n_cell_length_a 6\n";
+    let prefixes = Selection::TagPrefixes(vec!["_CELL_".into(), "_atom_site".into()]);
+    let kept: Vec<Event> = CifReader::new(cif.as_bytes())
+        .select(prefixes)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(kept.len(), 9);
+    assert!(!kept
+        .iter()
+        .any(|e| matches!(e, Event::Item { tag, .. } if tag == "_title")));
+    assert!(!kept.contains(&Event::LoopRow(vec![CifValue::Numeric(1.0)])));
+
+    let first: Vec<Event> = CifReader::new(cif.as_bytes())
+        .select(Selection::FirstBlockOnly)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(first.last(), Some(&Event::BlockEnd));
+    assert_eq!(first.iter().filter(|e| **e == Event::BlockEnd).count(), 1);
+
+    let named: Vec<Event> = CifReader::new(cif.as_bytes())
+        .select(Selection::Blocks(vec!["B".into()]))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(named[0], Event::BlockStart { name: "b".into() });
+    assert_eq!(named.len(), 3);
+}
+
+#[test]
+fn test_errors_have_input_locations() {
+    assert_eq!(
+        error("data_a\n_x 1\n_y\n_z 2\n"),
+        "[E0008] Error at line 3, column 1: Invalid CIF structure: Data name '_y' has no value"
+    );
+    assert!(error("data_a\nloop_\n_x\n_y\n1 2\n3\n").contains("E0006"));
+    assert!(error("data_a\n_x\n;\nnever closed\n").contains("line 3"));
+    assert!(error("_x 1\n").contains("before the first data block"));
+    assert!(error("data_a\nsave_f\n_x 1\ndata_b\n").contains("'f' is not closed"));
+    assert!(error("data_a\nloop_\n_x\n1\nstop_\n").contains("STAR"));
+
+    // Events before the error still come first
+    let results: Vec<_> = CifReader::new("data_a\n_x 1\n_y 'a''b'\n1 2\n".as_bytes()).collect();
+    assert_eq!(results.len(), 4);
+    assert!(results[2].is_ok());
+    assert!(results[3]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("line 4"));
+}
@@ -0,0 +1,247 @@
+// tests/redact_tests.rs
+// Redacting tags by profile and pattern, in documents, on writing, and in text
+
+use cif_parser::redact::{redact_text, Profile, Redaction, RedactionPolicy, RedactionReport};
+use cif_parser::writer::WriteOptions;
+use cif_parser::{Document, Value};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn fixture() -> String {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/redact/prepublication.cif");
+    fs::read_to_string(path).unwrap()
+}
+
+/// The redactions, in file order, whichever way they were made
+fn sorted(report: RedactionReport) -> Vec<Redaction> {
+    let mut redactions = report.redactions;
+    let text = fixture();
+    redactions.sort_by_key(|r| {
+        text.find(&format!("{}\n", r.tag))
+            .or_else(|| text.find(&format!("{} ", r.tag)))
+    });
+    redactions
+}
+
+fn both_profiles() -> RedactionPolicy {
+    RedactionPolicy::from(Profile::PrePublication).profile(Profile::StripEmbeddedFiles)
+}
+
+#[test]
+fn test_pre_publication_profile_replaces_values() {
+    let mut doc = Document::parse(&fixture()).unwrap();
+    let original = doc.clone();
+    let report = doc.redact(&Profile::PrePublication.into());
+
+    let tags: Vec<&str> = report.redactions.iter().map(|r| r.tag.as_str()).collect();
+    // The contact phone and refinement details were already `?`
+    assert_eq!(
+        tags,
+        [
+            "_publ_contact_author_name",
+            "_publ_contact_author_address",
+            "_publ_contact_author_email",
+            "_exptl_special_details",
+            "_publ_author_name",
+            "_publ_author_address",
+        ]
+    );
+    assert_eq!(report.values(), 8);
+    assert!(report
+        .redactions
+        .iter()
+        .all(|r| !r.removed && r.block == "compound_7"));
+
+    let block = &doc.blocks[0];
+    assert_eq!(
+        block.get_item("_publ_contact_author_email"),
+        Some(&Value::Unknown)
+    );
+    let authors = block.find_loop("_publ_author_name").unwrap();
+    assert!(authors
+        .values
+        .iter()
+        .flatten()
+        .all(|v| *v == Value::Unknown));
+    // Everything else is untouched
+    let kept = &original.blocks[0];
+    for tag in ["_cell_length_a", "_chemical_formula_sum", "_shelx_res_file"] {
+        assert_eq!(block.get_item(tag), kept.get_item(tag), "{tag}");
+    }
+    assert!(block.loops[1].semantically_equal(&kept.loops[1]));
+}
+
+#[test]
+fn test_remove_items_drops_items_columns_and_empty_loops() {
+    let mut doc = Document::parse(&fixture()).unwrap();
+    let report = doc.redact(&both_profiles().remove_items(true));
+
+    assert!(report.redactions.iter().all(|r| r.removed));
+    let authors = report
+        .redactions
+        .iter()
+        .find(|r| r.tag == "_publ_author_name")
+        .unwrap();
+    assert_eq!(authors.values, 2);
+
+    let block = &doc.blocks[0];
+    let tags: Vec<&str> = block.all_tags().collect();
+    assert!(tags
+        .iter()
+        .all(|tag| !tag.starts_with("_publ_") && !tag.starts_with("_shelx_")));
+    assert!(!tags.contains(&"_refine_special_details"));
+    assert_eq!(block.loops.len(), 1);
+    assert!(block.get_item("_cell_length_b").is_some());
+}
+
+#[test]
+fn test_custom_patterns_and_frames() {
+    let cif = "data_x\n_Cell_Length_A 5\n_cell_length_b 6\nsave_f\n_cell_length_a 7\nsave_\n";
+    let mut doc = Document::parse(cif).unwrap();
+    let report = doc.redact(&RedactionPolicy::new().tag("_CELL_LENGTH_?"));
+
+    assert_eq!(report.values(), 3);
+    assert_eq!(
+        report.to_string(),
+        "data_x _Cell_Length_A: 1 value replaced with ?\n\
+         data_x _cell_length_b: 1 value replaced with ?\n\
+         data_x save_f _cell_length_a: 1 value replaced with ?\n"
+    );
+    assert_eq!(
+        doc.blocks[0].frames[0].items["_cell_length_a"],
+        Value::Unknown
+    );
+
+    let unknown = "pre-publication".parse::<Profile>().unwrap_err();
+    assert!(unknown.contains("pre_publication"), "{unknown}");
+}
+
+#[test]
+fn test_writer_redacts_a_copy() {
+    let doc = Document::parse(&fixture()).unwrap();
+    let options = WriteOptions::new().redact(both_profiles());
+    let text = doc.to_string_with_options(&options);
+
+    assert!(!text.contains("ann.person"));
+    assert!(!text.contains("TITL"));
+    assert!(text.contains("_shelx_res_file ?"));
+    // The document itself keeps its values
+    assert!(doc.to_string().contains("ann.person"));
+
+    let mut redacted = doc.clone();
+    redacted.redact(&both_profiles());
+    assert!(Document::parse(&text)
+        .unwrap()
+        .semantically_equal(&redacted));
+}
+
+#[test]
+fn test_text_redaction_keeps_everything_else_byte_identical() {
+    let input = fixture();
+    let (text, report) = redact_text(&input, &both_profiles()).unwrap();
+
+    let mut doc = Document::parse(&input).unwrap();
+    assert_eq!(report.redactions, sorted(doc.redact(&both_profiles())));
+    assert!(Document::parse(&text).unwrap().semantically_equal(&doc));
+
+    // Only the redacted values differ; every other line is as it was
+    let expected = input
+        .replace("'Person, Ann'   'Some University'", "?   ?")
+        .replace("'Other, Bo'     'Some University'", "?     ?")
+        .replace(
+            "'Person, Ann'\n_publ_contact_author_address\n;\nDepartment of Chemistry\nSome University\n;\n",
+            "?\n_publ_contact_author_address\n?\n",
+        )
+        .replace("ann.person@example.org", "?")
+        .replace(
+            ";\n Crystal mounted in oil; unpublished synthesis route 7b.\n;\n",
+            "?\n",
+        )
+        .replace(
+            ";\nTITL compound_7 in P2(1)/c\nCELL 0.71073 5.4321 7.8901 12.345 90 95.12 90\nEND\n;\n",
+            "?\n",
+        )
+        .replace("81234", "?")
+        .replace(
+            ";\n   1   0   0  123.45   2.34\n   0   0   0    0.00   0.00\n;\n",
+            "?\n",
+        )
+        .replace("4096", "?");
+    assert_eq!(text, expected);
+}
+
+#[test]
+fn test_text_removal_cuts_lines_cleanly() {
+    let input = fixture();
+    let policy = both_profiles().remove_items(true);
+    let (text, report) = redact_text(&input, &policy).unwrap();
+
+    let mut doc = Document::parse(&input).unwrap();
+    assert_eq!(report.redactions, sorted(doc.redact(&policy)));
+    assert!(Document::parse(&text).unwrap().semantically_equal(&doc));
+    for gone in ["_publ_", "_shelx_", "loop_\n\n", "TITL", "Crystal mounted"] {
+        assert!(!text.contains(gone), "{gone}");
+    }
+
+    // Comments, blank lines, and the layout of kept content survive
+    let atom_sites = &input[input.find("loop_\n  _atom_site_label").unwrap()..];
+    let atom_sites = &atom_sites[..atom_sites.find("\n\n").unwrap() + 2];
+    assert!(text.contains(atom_sites));
+    assert!(text.starts_with(
+        "#\\#CIF_1.1\n# Draft structure, not yet submitted\ndata_compound_7\n\
+         _audit_creation_method          'SHELXL-2018/3'\n\n_chemical_formula_sum"
+    ));
+    assert!(text.contains("_cell_length_a                  5.4321(3)   # from 2045 reflections\n"));
+}
+
+#[test]
+fn test_text_removal_of_some_loop_columns() {
+    let input = "data_x\nloop_\n_a\n_secret\n_b\n1 s1 2\n3 'two words' 4\n";
+    let (text, report) = redact_text(
+        input,
+        &RedactionPolicy::new().tag("_secret").remove_items(true),
+    )
+    .unwrap();
+
+    assert_eq!(text, "data_x\nloop_\n_a\n_b\n1 2\n3 4\n");
+    assert_eq!(report.values(), 2);
+}
+
+#[test]
+fn test_cli_redacts_to_a_file() {
+    let input =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/redact/prepublication.cif");
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join("redacted.cif");
+    let result = Command::new(env!("CARGO_BIN_EXE_cif"))
+        .args([
+            "redact",
+            "--profile",
+            "pre_publication",
+            "--tag",
+            "_shelx_*",
+        ])
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("data_compound_7 _publ_author_name: 2 values replaced with ?"));
+    let (expected, _) = redact_text(
+        &fixture(),
+        &RedactionPolicy::from(Profile::PrePublication).tag("_shelx_*"),
+    )
+    .unwrap();
+    assert_eq!(fs::read_to_string(output).unwrap(), expected);
+
+    let result = Command::new(env!("CARGO_BIN_EXE_cif"))
+        .args(["redact", "--profile", "pre-publication"])
+        .arg(&input)
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(2));
+}
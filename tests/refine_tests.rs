@@ -0,0 +1,112 @@
+// tests/refine_tests.rs
+// Refinement model from SHELXL and Jana2006 reports
+
+use cif_parser::{Document, HydrogenTreatment, Restraint, Weighting};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/refine")
+        .join(name)
+}
+
+#[test]
+fn test_shelxl_chiral() {
+    let doc = Document::from_file(fixture("shelxl_chiral.cif")).unwrap();
+    let model = doc.first_block().unwrap().refinement_model();
+
+    assert_eq!(model.parameters, Some(235));
+    assert_eq!(model.restraints, Some(0));
+    assert_eq!(model.constraints, None);
+    assert_eq!(model.weighting_scheme.as_deref(), Some("calc"));
+    assert_eq!(
+        model.weighting,
+        Some(Weighting {
+            a: 0.0478,
+            b: 0.2987
+        })
+    );
+    assert_eq!(model.extinction_method.as_deref(), Some("none"));
+    assert_eq!(model.extinction_coef, None);
+    assert_eq!(model.flack, Some((-0.02, Some(0.04))));
+    assert_eq!(
+        model.hydrogen_treatment,
+        Some(HydrogenTreatment::Constrained)
+    );
+    assert!(model
+        .special_details
+        .unwrap()
+        .starts_with("Refinement of F^2^ against ALL reflections."));
+    assert!(model.restraint_list.is_empty());
+}
+
+#[test]
+fn test_shelxl_restrained() {
+    let doc = Document::from_file(fixture("shelxl_restrained.cif")).unwrap();
+    let model = doc.first_block().unwrap().refinement_model();
+
+    assert_eq!(
+        (model.parameters, model.restraints, model.constraints),
+        (Some(318), Some(42), Some(0))
+    );
+    let weighting = model.weighting.unwrap();
+    assert_eq!(weighting.a, 0.1024);
+    assert_eq!(weighting.b, 12.4516);
+    assert_eq!(model.extinction_coef, Some((0.0041, Some(0.0007))));
+    assert_eq!(model.hydrogen_treatment, Some(HydrogenTreatment::Mixed));
+    assert_eq!(model.flack, None);
+
+    assert_eq!(model.restraint_list.len(), 3);
+    assert_eq!(
+        model.restraint_list[1],
+        Restraint {
+            kind: "distance",
+            atoms: vec!["C11A".to_string(), "C12A".to_string()],
+            target: Some((1.54, Some(0.01))),
+            difference: Some(-0.012),
+        }
+    );
+    let angle = &model.restraint_list[2];
+    assert_eq!(angle.kind, "angle");
+    assert_eq!(angle.atoms, ["O1", "C11", "C12"]);
+    assert_eq!(angle.target, Some((109.5, Some(1.0))));
+}
+
+#[test]
+fn test_jana_weighting_is_not_shelxl() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/example_cifs/abiraterone_acetate/abiraterone_acetate.cif");
+    let doc = Document::from_file(path).unwrap();
+    let model = doc
+        .get_block("abiraterone_acetate_dynamical")
+        .unwrap()
+        .refinement_model();
+
+    assert_eq!(model.weighting_scheme.as_deref(), Some("sigma"));
+    assert_eq!(
+        model.weighting_details.as_deref(),
+        Some("w=1/(\\s^2^(F)+0.0001F^2^)")
+    );
+    assert_eq!(model.weighting, None);
+    assert_eq!(
+        (model.parameters, model.restraints, model.constraints),
+        (Some(370), Some(0), Some(132))
+    );
+    assert_eq!(model.flack, None);
+
+    // The kinematical refinement used SHELXL-style extinction
+    let kinematical = doc
+        .get_block("abiraterone_acetate_kinematical")
+        .unwrap()
+        .refinement_model();
+    assert_eq!(kinematical.extinction_coef, Some((1220.0, Some(150.0))));
+}
+
+#[test]
+fn test_empty_block() {
+    let doc = Document::parse("data_x\n_cell_length_a 5\n").unwrap();
+    assert_eq!(
+        doc.first_block().unwrap().refinement_model(),
+        Default::default()
+    );
+}
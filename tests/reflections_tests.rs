@@ -0,0 +1,168 @@
+// tests/reflections_tests.rs
+// Reading reflection lists and merging symmetry-equivalent reflections
+
+use cif_parser::reflections::{MergeOptions, MergeStatistics, ShellBinning};
+use cif_parser::{CifBlock, Document};
+
+fn glycine() -> (Document, Document) {
+    let pets = Document::from_file("tests/example_cifs/glycine/pets2_glycine.cif_pets").unwrap();
+    let expected = Document::from_file("tests/fixtures/reflections/glycine_merging.cif").unwrap();
+    (pets, expected)
+}
+
+fn number(block: &CifBlock, tag: &str) -> f64 {
+    block.get_item(tag).unwrap().as_numeric().unwrap()
+}
+
+/// Compare with statistics quoted to 3 decimals for d, 2 for multiplicity
+/// and I/σ, and 4 for the R factors
+fn assert_matches(actual: &MergeStatistics, expected: [f64; 9]) {
+    let [d_low, d_high, measured, unique, multiplicity, r_merge, r_meas, r_pim, i_over_sigma] =
+        expected;
+    let close = |x: f64, y: f64, tolerance: f64| {
+        assert!((x - y).abs() <= tolerance, "{x} vs {y} in {actual:?}");
+    };
+    close(actual.d_max.unwrap(), d_low, 5e-4);
+    close(actual.d_min.unwrap(), d_high, 5e-4);
+    assert_eq!(actual.observations as f64, measured);
+    assert_eq!(actual.unique as f64, unique);
+    close(actual.multiplicity, multiplicity, 5e-3);
+    close(actual.r_merge.unwrap(), r_merge, 5e-5);
+    close(actual.r_meas.unwrap(), r_meas, 5e-5);
+    close(actual.r_pim.unwrap(), r_pim, 5e-5);
+    close(actual.mean_i_over_sigma.unwrap(), i_over_sigma, 5e-3);
+}
+
+#[test]
+fn test_glycine_matches_reported_statistics() {
+    let (pets, expected) = glycine();
+    let expected = &expected.blocks[0];
+    let group = expected.point_group().unwrap();
+    assert_eq!(group.symbol, "2/m");
+
+    let data = pets.blocks[0].reflections().unwrap();
+    assert_eq!(data.category, "refln");
+    assert_eq!(data.reflections.len(), 9242);
+    let options = MergeOptions::new().shells(ShellBinning::DSpacing(vec![2.0, 1.2, 0.9, 0.7]));
+    let merged = data.merge_equivalents(&group, &options);
+
+    let overall = [
+        "_reflns.d_resolution_low",
+        "_reflns.d_resolution_high",
+        "_reflns.pdbx_number_measured_all",
+        "_reflns.number_obs",
+        "_reflns.pdbx_redundancy",
+        "_reflns.pdbx_Rmerge_I_obs",
+        "_reflns.pdbx_Rrim_I_all",
+        "_reflns.pdbx_Rpim_I_all",
+        "_reflns.pdbx_netI_over_sigmaI",
+    ]
+    .map(|tag| number(expected, tag));
+    assert_matches(&merged.overall, overall);
+
+    let shells = &expected.loops[1];
+    assert_eq!(merged.shells.len(), shells.len());
+    for (row, actual) in merged.shells.iter().enumerate() {
+        let values: Vec<f64> = (0..shells.tags.len())
+            .map(|column| shells.get(row, column).unwrap().as_numeric().unwrap())
+            .collect();
+        assert_matches(actual, values.try_into().unwrap());
+    }
+}
+
+#[test]
+fn test_equal_count_shells() {
+    let (pets, expected) = glycine();
+    let group = expected.blocks[0].point_group().unwrap();
+    let merged = pets.blocks[0]
+        .reflections()
+        .unwrap()
+        .merge_equivalents(&group, &MergeOptions::new());
+
+    assert_eq!(merged.shells.len(), 10);
+    let unique: Vec<usize> = merged.shells.iter().map(|s| s.unique).collect();
+    assert_eq!(unique.iter().sum::<usize>(), 736);
+    assert!(unique.iter().all(|&n| n == 73 || n == 74));
+    assert_eq!(
+        merged.shells.iter().map(|s| s.observations).sum::<usize>(),
+        9242
+    );
+    for pair in merged.shells.windows(2) {
+        assert!(pair[0].d_min.unwrap() >= pair[1].d_max.unwrap());
+    }
+}
+
+#[test]
+fn test_anomalous_keeps_friedel_mates_apart() {
+    let cif = "\
+data_p21
+loop_
+_space_group_symop_operation_xyz
+x,y,z
+-x,y+1/2,-z
+loop_
+_diffrn_refln_index_h
+_diffrn_refln_index_k
+_diffrn_refln_index_l
+_diffrn_refln_intensity_net
+_diffrn_refln_intensity_u
+1 1 1 100 8
+-1 1 -1 104 8
+-1 -1 -1 120 8
+1 -1 1 116 8
+0 0 0 5 1
+1 1 ? 50 5
+";
+    let doc = Document::parse(cif).unwrap();
+    let block = doc.first_block().unwrap();
+    let group = block.point_group().unwrap();
+    let data = block.reflections().unwrap();
+    assert_eq!(data.category, "diffrn_refln");
+    assert_eq!(data.reflections.len(), 4);
+    assert!(data.cell.is_none());
+
+    let merged = data.merge_equivalents(&group, &MergeOptions::new());
+    assert_eq!(merged.reflections.len(), 1);
+    assert_eq!(merged.reflections[0].intensity, 110.0);
+    assert!(merged.shells.is_empty());
+
+    let anomalous = data.merge_equivalents(&group, &MergeOptions::new().anomalous(true));
+    let pairs: Vec<([i32; 3], f64)> = anomalous
+        .reflections
+        .iter()
+        .map(|r| (r.hkl, r.intensity))
+        .collect();
+    assert_eq!(pairs, vec![([1, -1, 1], 118.0), ([1, 1, 1], 102.0)]);
+    assert_eq!(anomalous.overall.r_merge, Some(8.0 / 440.0));
+    let sigma = anomalous.reflections[0].sigma.unwrap();
+    assert!((sigma - 8.0 / 2f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn test_mmcif_structure_factor_squares() {
+    let cif = "\
+data_1ABC
+loop_
+_refln.index_h
+_refln.index_k
+_refln.index_l
+_refln.F_squared_meas
+_refln.F_squared_sigma
+1 0 0 10.0 1.0
+-1 0 0 12.0 .
+";
+    let doc = Document::parse(cif).unwrap();
+    let data = doc.first_block().unwrap().reflections().unwrap();
+    assert_eq!(data.reflections[1].sigma, None);
+
+    let ops = [cif_parser::SymOp::identity()];
+    let group = cif_parser::symmetry::point_group(&ops).unwrap();
+    let merged = data.merge_equivalents(&group, &MergeOptions::new());
+    // Unweighted when an su is missing
+    assert_eq!(merged.reflections[0].intensity, 11.0);
+    assert_eq!(merged.reflections[0].sigma, None);
+    assert_eq!(merged.overall.mean_i_over_sigma, None);
+
+    let nothing = Document::parse("data_x\n_cell_length_a 5\n").unwrap();
+    assert!(nothing.first_block().unwrap().reflections().is_none());
+}
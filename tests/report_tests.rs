@@ -0,0 +1,185 @@
+// tests/report_tests.rs
+// Check reports written as JSON, SARIF, and JUnit XML for CI
+
+use cif_parser::report::{Finding, Report, Severity};
+use cif_parser::{Document, ExpectedType, ParseOptions};
+use std::fs;
+use std::path::PathBuf;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Check one file the way `cif check --sanity` does, naming it by its
+/// path relative to the crate so that the output does not depend on
+/// where the repository is
+fn check(report: &mut Report, name: &str, options: &ParseOptions) {
+    report.begin_file(format!("tests/fixtures/{name}"));
+    let content = fs::read_to_string(fixture_path(name)).unwrap();
+    match Document::parse_with_options(&content, options) {
+        Ok(doc) => {
+            report.add_warnings(&doc.warnings);
+            for block in &doc.blocks {
+                report.add_sanity_issues(&block.name, &block.sanity_check());
+            }
+        }
+        Err(error) => report.add_error(&error),
+    }
+    report.locate(&content);
+}
+
+/// Sanity issues, lenient-parse warnings, a parse error, schema findings,
+/// and a clean file
+fn sample() -> Report {
+    let strict = ParseOptions::new();
+    let mut report = Report::new();
+    check(&mut report, "sanity_issues.cif", &strict);
+    check(
+        &mut report,
+        "decimal_comma/cell.cif",
+        &ParseOptions::new().lenient(true),
+    );
+    check(&mut report, "report/stray_value.cif", &strict);
+
+    report.begin_file("tests/fixtures/schema/typed.cif");
+    let content = fs::read_to_string(fixture_path("schema/typed.cif")).unwrap();
+    let doc = Document::parse(&content).unwrap();
+    let coercion = doc.blocks[0].coercion_report(&[
+        ("_audit_creation_date", ExpectedType::Date),
+        ("_cell_measurement_temperature", ExpectedType::F64WithSu),
+        ("_diffrn_radiation_wavelength", ExpectedType::F64WithSu),
+        ("_atom_site_calc_flag", ExpectedType::Enum(&["d", "calc"])),
+    ]);
+    report.add_coercion_report("typed", &coercion);
+    report.locate(&content);
+
+    check(&mut report, "simple.cif", &strict);
+    report
+}
+
+#[test]
+fn test_json_matches_golden_file() {
+    let written: serde_json::Value = serde_json::from_str(&sample().to_json()).unwrap();
+    let expected = fs::read_to_string(fixture_path("report/expected.json")).unwrap();
+    let expected: serde_json::Value = serde_json::from_str(&expected).unwrap();
+    assert_eq!(written, expected, "{written:#}");
+}
+
+#[test]
+fn test_findings_point_at_values() {
+    let report = sample();
+    let find = |rule_id: &str| {
+        report
+            .findings
+            .iter()
+            .find(|f| f.rule_id == rule_id)
+            .unwrap_or_else(|| panic!("no {rule_id}"))
+    };
+
+    let length = find("sanity/cell-length");
+    assert_eq!(length.tag.as_deref(), Some("_cell_length_b"));
+    assert_eq!(length.location, Some((4, 32)));
+    // Loop values are found by row
+    let coordinate = find("sanity/fract-coordinate");
+    assert_eq!(coordinate.row, Some(1));
+    assert_eq!(coordinate.location, Some((24, 4)));
+
+    assert_eq!(find("parse/warning").location, Some((4, 16)));
+    let error = find("parse/error");
+    assert_eq!(error.severity, Severity::Error);
+    assert!(error.location.is_some());
+
+    // A missing tag points at its block
+    assert_eq!(find("schema/missing").location, Some((1, 1)));
+    assert_eq!(find("schema/unknown").severity, Severity::Warning);
+    assert_eq!(find("schema/mismatch").location, Some((2, 35)));
+    assert!(report.has_errors());
+}
+
+#[test]
+fn test_sarif_validates_against_schema() {
+    let sarif: serde_json::Value = serde_json::from_str(&sample().to_sarif()).unwrap();
+    let schema = fs::read_to_string(fixture_path("report/sarif-2.1.0-subset.schema.json")).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&schema).unwrap();
+    let validator = jsonschema::draft7::new(&schema).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(&sarif)
+        .map(|e| format!("{} at {}", e, e.instance_path()))
+        .collect();
+    assert!(errors.is_empty(), "{errors:#?}");
+    let mut misspelt = sarif.clone();
+    misspelt["runs"][0]["results"][0]["rule_id"] = "sanity/cell-length".into();
+    assert!(!validator.is_valid(&misspelt));
+
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "cif-parser");
+    assert_eq!(run["artifacts"].as_array().unwrap().len(), 5);
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "sanity/cell-length");
+    assert_eq!(result["level"], "error");
+    let location = &result["locations"][0];
+    assert_eq!(
+        location["physicalLocation"]["artifactLocation"]["uri"],
+        "tests/fixtures/sanity_issues.cif"
+    );
+    assert_eq!(location["physicalLocation"]["region"]["startLine"], 4);
+    assert_eq!(
+        location["logicalLocations"][0]["fullyQualifiedName"],
+        "data_issues/_cell_length_b"
+    );
+
+    // Every rule referenced by a result is described once
+    let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    for result in run["results"].as_array().unwrap() {
+        assert!(rules.contains(&result["ruleId"].as_str().unwrap()));
+    }
+}
+
+#[test]
+fn test_junit_lists_findings_as_test_cases() {
+    let xml = sample().to_junit_xml();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    assert_eq!(xml.matches("<testsuite ").count(), 5);
+    assert!(
+        xml.contains("<testsuite name=\"tests/fixtures/simple.cif\" tests=\"1\" failures=\"0\"")
+    );
+    assert!(xml.contains("name=\"check\"/>"));
+    assert!(xml.contains("message=\"_cell_length_b = 1200"));
+    // Warnings are listed, but do not fail
+    assert_eq!(xml.matches("<system-out>warning: ").count(), 7);
+    assert_eq!(
+        xml.matches("<failure ").count(),
+        sample()
+            .findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count()
+    );
+}
+
+#[test]
+fn test_findings_without_locations() {
+    let mut report = Report::new();
+    report.begin_file("notes.cif");
+    report.add(Finding::new(
+        "parse/error",
+        Severity::Note,
+        "a note & <more>",
+    ));
+    assert!(!report.has_errors());
+    assert!(report.to_junit_xml().contains("a note &amp; &lt;more&gt;"));
+
+    let sarif: serde_json::Value = serde_json::from_str(&report.to_sarif()).unwrap();
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["level"], "note");
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "notes.cif");
+    assert!(location.get("region").is_none());
+}
@@ -0,0 +1,195 @@
+// tests/reserved_word_tests.rs
+// Values spelled like reserved words: quoted as data, unquoted as boundaries,
+// and the errors for unquoted reserved words that were probably meant as values
+
+use cif_parser::{CifError, Document, ParseOptions, Value};
+use std::path::Path;
+
+fn fixture(name: &str) -> Document {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    Document::from_file(path).unwrap()
+}
+
+fn error(input: &str) -> (String, Option<(usize, usize)>) {
+    match Document::parse(input) {
+        Err(CifError::InvalidStructure {
+            message, location, ..
+        }) => (message, location),
+        other => panic!("expected a structure error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_quoted_reserved_words_are_values() {
+    let doc = fixture("reserved_words_quoted.cif");
+    assert_eq!(doc.blocks.len(), 1);
+    let block = &doc.blocks[0];
+
+    let text = |tag: &str| block.get_item(tag).and_then(Value::as_string);
+    assert_eq!(text("_note_block"), Some("data_sample"));
+    assert_eq!(text("_note_frame"), Some("save_frame1"));
+    assert_eq!(text("_note_loop"), Some("loop_"));
+    assert_eq!(text("_note_global"), Some("global_"));
+    assert_eq!(text("_note_stop"), Some("stop_"));
+    // Only the exact keyword is reserved for loop_, global_ and stop_
+    assert_eq!(text("_note_lookalike"), Some("loop_x"));
+
+    let words: Vec<_> = block
+        .find_loop("_keyword.word")
+        .unwrap()
+        .get_column("_keyword.word")
+        .unwrap()
+        .into_iter()
+        .filter_map(Value::as_string)
+        .collect();
+    assert_eq!(words, ["data_", "save_", "loop_", "global_", "stop_"]);
+}
+
+#[test]
+fn test_unquoted_reserved_words_end_loops() {
+    let doc = fixture("reserved_words_boundaries.cif");
+    let names: Vec<_> = doc.blocks.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["", "first", "second"]);
+
+    let first = &doc.blocks[1];
+    // Ended by loop_
+    assert_eq!(first.find_loop("_atom_site_label").unwrap().len(), 2);
+    // Ended by save_fragment
+    assert_eq!(first.find_loop("_bond_atom_1").unwrap().len(), 1);
+    // Ended by the closing save_, so the next item belongs to the block
+    let frame = &first.frames[0];
+    assert_eq!(frame.name, "fragment");
+    assert_eq!(frame.loops[0].len(), 2);
+    assert!(first.get_item("_after_frame").is_some());
+    assert!(frame.get_item("_after_frame").is_none());
+    // Ended by data_second
+    assert!(doc.blocks[2].get_item("_cell_length_a").is_some());
+}
+
+#[test]
+fn test_stop_ends_loop_in_lenient_mode() {
+    let input = "data_x\nloop_\n_a\n1\n2\nstop_\n_b 3\n";
+    assert!(Document::parse(input).is_err());
+
+    let doc = Document::parse_with_options(input, &ParseOptions::new().lenient(true)).unwrap();
+    let block = &doc.blocks[0];
+    assert_eq!(block.find_loop("_a").unwrap().len(), 2);
+    assert!(block.get_item("_b").is_some());
+}
+
+#[test]
+fn test_incomplete_row_blames_reserved_word() {
+    for word in ["data_oops", "save_oops", "loop_", "global_"] {
+        let input = format!("data_x\nloop_\n_a\n_b\n1 2\n3 {word}\n4 5\n");
+        let error = Document::parse(&input).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains(&format!("'{word}'")), "{message}");
+        assert!(message.contains("must be quoted"), "{message}");
+        assert!(
+            matches!(
+                error,
+                CifError::LoopMismatch {
+                    tags: 2,
+                    values: 3,
+                    reserved_word: Some(ref reserved),
+                    location: Some((6, 3)),
+                    ..
+                } if reserved == word
+            ),
+            "{word}"
+        );
+    }
+}
+
+#[test]
+fn test_incomplete_row_without_reserved_word_keeps_count_error() {
+    let error = Document::parse("data_x\nloop_\n_a\n_b\n1 2\n3\n_c 4\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[E0006] Error at line 2, column 1: Loop has 2 tags but 3 values (not divisible)"
+    );
+    assert!(matches!(
+        error,
+        CifError::LoopMismatch {
+            reserved_word: None,
+            location: Some((2, 1)),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_values_after_reserved_word_in_complete_row() {
+    // With one column every row is complete, so the stray values give it away
+    let (message, location) = error("data_x\nloop_\n_a\n1\ndata_y\n2\n");
+    assert!(
+        message.contains("Value '2' is outside any loop"),
+        "{message}"
+    );
+    assert!(message.contains("'data_y'"), "{message}");
+    assert_eq!(location, Some((6, 1)));
+
+    let (message, _) = error("data_x\nloop_\n_a\n1\nsave_y\n2\n");
+    assert!(
+        message.contains("Save frame 'y' is not closed"),
+        "{message}"
+    );
+    assert!(message.contains("'save_y'"), "{message}");
+}
+
+#[test]
+fn test_stray_values_after_content_have_no_hint() {
+    let (message, location) = error("data_x\n_a 1\n2\n");
+    assert_eq!(
+        message,
+        "Value '2' is outside any loop and has no data name"
+    );
+    assert_eq!(location, Some((3, 1)));
+}
+
+#[test]
+fn test_item_with_reserved_word_value() {
+    for word in ["data_y", "save_y", "loop_", "global_", "stop_"] {
+        let (message, location) = error(&format!("data_x\n_a {word}\n_b 1\n"));
+        assert_eq!(
+            message,
+            format!(
+                "Data item '_a' has no value: '{word}' is a reserved word, \
+                 which must be quoted if it is the value"
+            )
+        );
+        assert_eq!(location, Some((2, 4)));
+    }
+}
+
+#[test]
+fn test_writer_quotes_reserved_words() {
+    let values = [
+        "data_x", "DATA_x", "save_y", "save_", "loop_", "Loop_", "global_", "stop_",
+    ];
+    let mut input = String::from("data_x\nloop_\n_word\n");
+    for value in values {
+        input.push_str(&format!("'{value}'\n"));
+    }
+    let doc = Document::parse(&input).unwrap();
+    let written = doc.to_string();
+    let reparsed = Document::parse(&written).unwrap();
+
+    let column: Vec<_> = reparsed.blocks[0]
+        .find_loop("_word")
+        .unwrap()
+        .get_column("_word")
+        .unwrap()
+        .into_iter()
+        .filter_map(Value::as_string)
+        .collect();
+    assert_eq!(column, values);
+    // Every value line is quoted
+    let body = written.split_once("_word\n").unwrap().1;
+    assert!(
+        body.lines().all(|line| line.starts_with(['\'', '"'])),
+        "{written}"
+    );
+}
@@ -0,0 +1,109 @@
+// tests/round_trip_props.rs
+// Writing a document and parsing it back must give a semantically equal document
+
+use cif_parser::testing::{assert_round_trip, check_seed, generate_mmcif};
+use cif_parser::Document;
+use std::path::Path;
+
+/// Fixed seeds so every run generates the same documents. When a seed fails,
+/// its message shows the offending document; add a distilled regression test
+/// for it rather than removing the seed.
+const SEEDS: &[u64] = &[1, 2, 3, 42, 382, 1999, 0xC1F, 0xDEAD_BEEF];
+
+/// Random documents generated per seed
+const CASES_PER_SEED: u32 = 64;
+
+#[test]
+fn test_seed_corpus_round_trips() {
+    for &seed in SEEDS {
+        if let Err(message) = check_seed(seed, CASES_PER_SEED) {
+            panic!("{message}");
+        }
+    }
+}
+
+#[test]
+fn test_example_files_round_trip() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    for file in [
+        "quartz/ccdc_quartz.cif",
+        "quartz/pycifrw_quartz.cif",
+        "urea/cod_urea.cif",
+        "paracetamol/ccdc_paracetamol.cif",
+        "xanthine/pycifrw_xanthine.cif",
+        "glycine/jana2006_glycine.cif",
+        "LuAg/crystalmaker_LuAG.cif",
+    ] {
+        let doc = Document::from_file(root.join(file)).unwrap();
+        assert_round_trip(&doc);
+    }
+}
+
+#[test]
+fn test_quoting_edge_cases_round_trip() {
+    let cif1 = "\
+data_q
+_a 'it''s'
+_b \"x' y\"
+_c
+;
+'both' \"kinds\"
+;
+_d '''
+loop_
+_e
+_f
+'data_x' \"a b\"
+;
+multi
+line
+;
+.
+";
+    assert_round_trip(&Document::parse(cif1).unwrap());
+
+    let cif2 = "\
+#\\#CIF_2.0
+data_q
+_a [1 'two words' \"it's\" [?.] {'k':'''a'b\"c'''}]
+_b '''x'y\"z'''
+_c {'': [] 'space key':{}}
+";
+    assert_round_trip(&Document::parse(cif2).unwrap());
+}
+
+#[test]
+fn test_generated_mmcif_round_trips() {
+    let cif = generate_mmcif(500, 300);
+    assert_eq!(cif, generate_mmcif(500, 300));
+
+    let doc = Document::parse(&cif).unwrap();
+    assert!(doc.warnings.is_empty());
+    assert_round_trip(&doc);
+
+    // Readable as a structure, with every atom inside the cell
+    let structure = doc.blocks[0].structure().unwrap();
+    assert_eq!(structure.sites.len(), 500);
+    assert_eq!(structure.sites[1].label, "2");
+    assert_eq!(structure.sites[1].type_symbol.as_deref(), Some("C"));
+    assert!(structure.sites.iter().all(|site| site
+        .fract
+        .unwrap()
+        .iter()
+        .all(|&f| (0.0..1.0).contains(&f))));
+}
+
+#[test]
+fn test_global_block_round_trips() {
+    for cif in [
+        "global_\n_a 1\ndata_x\n_b 2\n",
+        "#\\#CIF_2.0\nglobal_\n_a [1 2]\ndata_x\n_b 2\n",
+    ] {
+        let doc = Document::parse(cif).unwrap();
+        assert_eq!(doc.blocks[0].name, "");
+        let written = doc.to_string();
+        assert!(written.contains("global_\n"), "{written}");
+        assert_eq!(Document::parse(&written).unwrap().blocks[0].name, "");
+        assert_round_trip(&doc);
+    }
+}
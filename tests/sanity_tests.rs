@@ -0,0 +1,163 @@
+// tests/sanity_tests.rs
+// Built-in range and consistency checks on crystallographic quantities
+
+use cif_parser::{Block, Document};
+use std::path::Path;
+
+fn block(path: &str) -> Block {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+#[test]
+fn test_each_rule_is_reported() {
+    let issues = block("tests/fixtures/sanity_issues.cif").sanity_check();
+    let found: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.tag.as_str(), issue.row, issue.value.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("_cell_length_b", None, "1200"),
+            ("_cell_angle_beta", None, "-90"),
+            ("_atom_site_occupancy", Some(2), "0"),
+            ("_atom_site_fract_x", Some(1), "2.5"),
+            ("_atom_site_fract_y", Some(3), "abc"),
+            ("_atom_site_fract_z", Some(1), "-1.5"),
+            ("_atom_site_U_iso_or_equiv", Some(1), "1.7"),
+            ("_diffrn_radiation_wavelength", None, "71.073"),
+            ("_cell_formula_units_Z", None, "2.5"),
+            ("_cell_volume", None, "4000000"),
+            ("_chemical_formula_weight", None, "80"),
+        ]
+    );
+}
+
+#[test]
+fn test_issues_name_the_rule() {
+    let issues = block("tests/fixtures/sanity_issues.cif").sanity_check();
+    let rule = |tag: &str| {
+        issues
+            .iter()
+            .find(|issue| issue.tag == tag)
+            .map(|issue| issue.rule.as_str())
+            .unwrap()
+    };
+    assert_eq!(
+        rule("_cell_length_b"),
+        "cell length must be in (0.5, 1000) Å"
+    );
+    assert_eq!(
+        rule("_atom_site_occupancy"),
+        "occupancy must be in (0, 1.001]"
+    );
+    assert_eq!(
+        rule("_atom_site_fract_y"),
+        "fractional coordinate must be a number"
+    );
+    assert_eq!(
+        rule("_cell_formula_units_Z"),
+        "Z must be a positive integer"
+    );
+    assert_eq!(
+        rule("_cell_volume"),
+        "cell volume differs by 493.3σ from 4887900(1800) Å³ from the cell parameters"
+    );
+    assert!(rule("_chemical_formula_weight").contains("of 78.11"));
+    assert_eq!(
+        issues[2].to_string(),
+        "_atom_site_occupancy[2] = 0: occupancy must be in (0, 1.001]"
+    );
+}
+
+#[test]
+fn test_electron_wavelengths() {
+    let cif = "data_ed\n_diffrn_radiation_probe electron\n_diffrn_radiation_wavelength 0.0251\n";
+    let doc = Document::parse(cif).unwrap();
+    assert_eq!(doc.blocks[0].sanity_check(), []);
+
+    // The same wavelength for X-rays is far too short
+    let doc = Document::parse(&cif.replace("electron", "x-ray")).unwrap();
+    assert_eq!(
+        doc.blocks[0].sanity_check()[0].rule,
+        "wavelength must be in (0.1, 10) Å"
+    );
+
+    let doc = Document::parse(&cif.replace("0.0251", "0.71073")).unwrap();
+    assert_eq!(
+        doc.blocks[0].sanity_check()[0].rule,
+        "electron wavelength must be in (0.001, 0.1) Å"
+    );
+}
+
+#[test]
+fn test_volume_within_combined_su() {
+    // The cell gives 324.5 ± 4.1 Å³, so 325(4) and 335 are within 3σ
+    let cif = "data_albite
+_cell_length_a 7.13(6)
+_cell_length_b 7.38(5)
+_cell_length_c 7.64(4)
+_cell_angle_alpha 115.17(11)
+_cell_angle_beta 107.2(3)
+_cell_angle_gamma 100.60(19)
+_cell_volume 325(4)
+";
+    let doc = Document::parse(cif).unwrap();
+    assert_eq!(doc.blocks[0].sanity_check(), []);
+    let doc = Document::parse(&cif.replace("325(4)", "335")).unwrap();
+    assert_eq!(doc.blocks[0].sanity_check(), []);
+
+    let doc = Document::parse(&cif.replace("325(4)", "340")).unwrap();
+    assert_eq!(
+        doc.blocks[0].sanity_check()[0].rule,
+        "cell volume differs by 3.8σ from 325(4) Å³ from the cell parameters"
+    );
+
+    // Without any sus, 325 is 0.15% from 324.50
+    let bare = cif.replace("325(4)", "325");
+    let bare: String = bare
+        .lines()
+        .map(|line| line.split('(').next().unwrap().to_string() + "\n")
+        .collect();
+    let doc = Document::parse(&bare).unwrap();
+    assert_eq!(
+        doc.blocks[0].sanity_check()[0].rule,
+        "cell volume must be within 0.1% of 324.50 Å³ from the cell parameters"
+    );
+}
+
+#[test]
+fn test_mmcif_tags() {
+    let cif = "data_mm
+_cell.length_a 0.3
+_cell.length_b 10
+_cell.length_c 10
+loop_
+_atom_site.id
+_atom_site.occupancy
+1 1.0
+2 -0.5
+";
+    let issues = Document::parse(cif).unwrap().blocks[0].sanity_check();
+    let tags: Vec<_> = issues.iter().map(|i| (i.tag.as_str(), i.row)).collect();
+    assert_eq!(
+        tags,
+        [("_cell.length_a", None), ("_atom_site.occupancy", Some(1))]
+    );
+}
+
+#[test]
+fn test_real_files_pass() {
+    for path in [
+        "tests/example_cifs/paracetamol/ccdc_paracetamol.cif",
+        "tests/example_cifs/glycine/jana2006_glycine.cif",
+        "tests/example_cifs/urea/cod_urea.cif",
+        "tests/example_cifs/quartz/ccdc_quartz.cif",
+    ] {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        for block in Document::from_file(&path).unwrap().blocks {
+            assert_eq!(block.sanity_check(), [], "{}", path.display());
+        }
+    }
+}
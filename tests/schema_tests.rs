@@ -0,0 +1,85 @@
+// tests/schema_tests.rs
+// Coercion reports list every missing, unknown, and mismatched value
+
+use cif_parser::schema::{ExpectedType, Mismatch, TagStatus};
+use cif_parser::Document;
+
+fn fixture() -> Document {
+    Document::from_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/schema/typed.cif"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_every_failure_is_reported() {
+    let doc = fixture();
+    let report = doc.blocks[0].coercion_report(&[
+        ("_audit_creation_date", ExpectedType::Date),
+        ("_cell_length_a", ExpectedType::F64WithSu),
+        ("_cell_measurement_temperature", ExpectedType::F64),
+        ("_space_group_IT_number", ExpectedType::I64),
+        ("_exptl_absorpt_correction_type", ExpectedType::Text),
+        ("_atom_site_fract_x", ExpectedType::F64WithSu),
+        ("_atom_site_occupancy", ExpectedType::F64),
+        (
+            "_atom_site_adp_type",
+            ExpectedType::Enum(&["Uani", "Uiso", "Uovl"]),
+        ),
+        (
+            "_atom_site_calc_flag",
+            ExpectedType::Enum(&["d", "calc", "c", "dum"]),
+        ),
+        ("_diffrn_radiation_wavelength", ExpectedType::F64),
+    ]);
+    let status = |tag| report.get(tag).unwrap().status;
+
+    assert_eq!(status("_audit_creation_date"), TagStatus::Mismatch);
+    assert_eq!(status("_cell_length_a"), TagStatus::Ok);
+    assert_eq!(status("_cell_measurement_temperature"), TagStatus::Unknown);
+    assert_eq!(status("_space_group_IT_number"), TagStatus::Ok);
+    assert_eq!(status("_exptl_absorpt_correction_type"), TagStatus::Ok);
+    assert_eq!(status("_atom_site_fract_x"), TagStatus::Ok);
+    assert_eq!(status("_atom_site_adp_type"), TagStatus::Ok);
+    assert_eq!(status("_diffrn_radiation_wavelength"), TagStatus::Missing);
+
+    // Both failures in the column are listed, not just the first; the `?`
+    // is counted separately
+    let occupancy = report.get("_atom_site_occupancy").unwrap();
+    assert_eq!(occupancy.status, TagStatus::Mismatch);
+    assert_eq!(occupancy.unknown, 1);
+    assert_eq!(
+        occupancy.mismatches,
+        [Mismatch {
+            row: Some(4),
+            token: "0.5(1)".to_string()
+        }]
+    );
+    let flags = report.get("_atom_site_calc_flag").unwrap();
+    assert_eq!(flags.mismatches[0].row, Some(3));
+    assert_eq!(flags.mismatches[0].token, "riding");
+
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.with_status(TagStatus::Mismatch).collect::<Vec<_>>(),
+        [
+            "_audit_creation_date",
+            "_atom_site_occupancy",
+            "_atom_site_calc_flag"
+        ]
+    );
+    assert!(report
+        .to_string()
+        .contains("_audit_creation_date: expected date, found 2023-02-30\n"));
+}
+
+#[test]
+fn test_tags_match_without_case() {
+    let doc = fixture();
+    let report = doc.blocks[0].coercion_report(&[
+        ("_SPACE_GROUP_IT_NUMBER", ExpectedType::I64),
+        ("_Atom_Site_Label", ExpectedType::Text),
+    ]);
+    assert!(report.is_ok(), "{report}");
+}
@@ -0,0 +1,236 @@
+// tests/selection_tests.rs
+// Selective parsing must give the same content as a full parse followed by filtering
+
+use cif_parser::testing::arb_document;
+use cif_parser::{CifError, Document, ParseOptions, Selection};
+use proptest::prelude::*;
+use std::path::Path;
+
+const MULTI_BLOCK: &str = "\
+data_first
+_cell_length_a 5.0
+_cell_length_b 6.0
+_title 'first block'
+loop_
+_atom_site_label
+_atom_site_fract_x
+C1 0.1
+C2 0.2
+loop_
+_symmetry_equiv_pos_as_xyz
+x,y,z
+
+data_second
+_cell_length_a 7.0
+_title 'second block'
+
+data_third
+_cell_length_a 8.0
+";
+
+fn tags(prefixes: &[&str]) -> Selection {
+    Selection::TagPrefixes(prefixes.iter().map(|p| p.to_string()).collect())
+}
+
+fn blocks(names: &[&str]) -> Selection {
+    Selection::Blocks(names.iter().map(|n| n.to_string()).collect())
+}
+
+fn parse_selected(input: &str, selections: &[Selection]) -> Result<Document, CifError> {
+    let options = selections
+        .iter()
+        .cloned()
+        .fold(ParseOptions::new(), ParseOptions::select);
+    Document::parse_with_options(input, &options)
+}
+
+/// What a selective parse should produce: the full document trimmed by hand
+fn filtered(mut doc: Document, selections: &[Selection]) -> Document {
+    let keeps_tag = |tag: &str| {
+        selections.iter().all(|selection| match selection {
+            Selection::TagPrefixes(prefixes) => prefixes
+                .iter()
+                .any(|p| tag.to_lowercase().starts_with(&p.to_lowercase())),
+            _ => true,
+        })
+    };
+    let has_tag_filter = selections
+        .iter()
+        .any(|s| matches!(s, Selection::TagPrefixes(_)));
+
+    let mut index = 0;
+    doc.blocks.retain(|block| {
+        let keep = selections.iter().all(|selection| match selection {
+            Selection::FirstBlockOnly => index == 0,
+            Selection::Blocks(names) => names.iter().any(|n| n.eq_ignore_ascii_case(&block.name)),
+            Selection::TagPrefixes(_) => true,
+        });
+        index += 1;
+        keep
+    });
+    if has_tag_filter {
+        for block in &mut doc.blocks {
+            block.items.retain(|tag, _| keeps_tag(tag));
+            block.loops.retain(|l| l.tags.iter().any(|t| keeps_tag(t)));
+            for frame in &mut block.frames {
+                frame.items.retain(|tag, _| keeps_tag(tag));
+                frame.loops.retain(|l| l.tags.iter().any(|t| keeps_tag(t)));
+            }
+            block
+                .frames
+                .retain(|f| !f.items.is_empty() || !f.loops.is_empty());
+        }
+    }
+    doc
+}
+
+#[test]
+fn test_no_selection_is_not_partial() {
+    let doc = Document::parse(MULTI_BLOCK).unwrap();
+    assert!(!doc.partial);
+    assert_eq!(doc.blocks.len(), 3);
+}
+
+#[test]
+fn test_first_block_only() {
+    let doc = parse_selected(MULTI_BLOCK, &[Selection::FirstBlockOnly]).unwrap();
+    assert!(doc.partial);
+    assert_eq!(doc.blocks.len(), 1);
+    assert_eq!(doc.blocks[0].name, "first");
+    assert_eq!(doc.blocks[0].items.len(), 3);
+    assert_eq!(doc.blocks[0].loops.len(), 2);
+}
+
+#[test]
+fn test_named_blocks_match_case_insensitively() {
+    let doc = parse_selected(MULTI_BLOCK, &[blocks(&["THIRD", "second"])]).unwrap();
+    let names: Vec<_> = doc.blocks.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["second", "third"]);
+}
+
+#[test]
+fn test_tag_prefixes_keep_matching_items_and_whole_loops() {
+    let doc = parse_selected(MULTI_BLOCK, &[tags(&["_cell_", "_ATOM_SITE_FRACT"])]).unwrap();
+    assert_eq!(doc.blocks.len(), 3);
+
+    let first = &doc.blocks[0];
+    assert_eq!(first.items.len(), 2);
+    assert!(first.get_item("_title").is_none());
+    assert_eq!(first.loops.len(), 1);
+    // The loop matched on one column but keeps all of them
+    assert_eq!(first.loops[0].tags.len(), 2);
+    assert_eq!(first.loops[0].len(), 2);
+    assert!(first.find_loop("_atom_site_label").is_some());
+}
+
+#[test]
+fn test_selections_intersect() {
+    let doc = parse_selected(
+        MULTI_BLOCK,
+        &[blocks(&["second", "third"]), tags(&["_title"])],
+    )
+    .unwrap();
+    assert_eq!(doc.blocks.len(), 2);
+    assert_eq!(doc.blocks[0].items.len(), 1);
+    assert!(doc.blocks[1].items.is_empty());
+}
+
+#[test]
+fn test_frames_without_selected_content_are_dropped() {
+    let input = "\
+data_dict
+_dictionary_title test
+save_kept
+_cell_length_a 1.0
+save_
+save_dropped
+_title x
+save_
+";
+    let doc = parse_selected(input, &[tags(&["_cell_"])]).unwrap();
+    let block = &doc.blocks[0];
+    assert!(block.items.is_empty());
+    assert_eq!(block.frames.len(), 1);
+    assert_eq!(block.frames[0].name, "kept");
+}
+
+#[test]
+fn test_errors_in_kept_content_report_original_lines() {
+    let input = "data_a\n_skipped 'x'\n_skipped_too 1\n_cell_length_a\n";
+    let err = parse_selected(input, &[tags(&["_cell_"])]).unwrap_err();
+    let full = Document::parse(input).unwrap_err();
+    assert_eq!(err.to_string(), full.to_string());
+}
+
+#[test]
+fn test_errors_in_skipped_content_are_not_reported() {
+    let input = "data_a\n_cell_length_a 5.0\ndata_b\n_title 'unterminated\n";
+    assert!(Document::parse(input).is_err());
+
+    let doc = parse_selected(input, &[Selection::FirstBlockOnly]).unwrap();
+    assert_eq!(doc.blocks.len(), 1);
+    assert_eq!(doc.blocks[0].items.len(), 1);
+}
+
+#[test]
+fn test_unscannable_input_falls_back_to_full_parse() {
+    // The stray closing bracket cannot be tokenized by the scanner, so the
+    // whole input is parsed and the grammar reports it
+    let input = "#\\#CIF_2.0\ndata_a\n_cell_length_a 5.0\n_list [1 2]]\n";
+    let selected = parse_selected(input, &[tags(&["_cell_"])]);
+    let full = Document::parse(input);
+    assert_eq!(selected.is_err(), full.is_err());
+}
+
+#[test]
+fn test_cif2_and_bom_are_preserved() {
+    let input = "\u{feff}#\\#CIF_2.0\ndata_a\n_list [1 2 {'k':3}]\n_cell_length_a 5.0\n";
+    let doc = parse_selected(input, &[tags(&["_list"])]).unwrap();
+    assert_eq!(doc.version, cif_parser::CifVersion::V2_0);
+    assert_eq!(doc.blocks[0].items.len(), 1);
+}
+
+#[test]
+fn test_example_files_match_filtered_full_parse() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs");
+    let selections = [tags(&["_cell_", "_symmetry_", "_space_group_"])];
+    for file in [
+        "quartz/ccdc_quartz.cif",
+        "urea/cod_urea.cif",
+        "paracetamol/ccdc_paracetamol.cif",
+        "glycine/jana2006_glycine.cif",
+    ] {
+        let path = root.join(file);
+        let options = ParseOptions::new().select(selections[0].clone());
+        let selected = Document::from_file_with_options(&path, &options).unwrap();
+        let expected = filtered(Document::from_file(&path).unwrap(), &selections);
+        assert!(selected.semantically_equal(&expected), "{file}");
+    }
+}
+
+fn arb_selections() -> impl Strategy<Value = Vec<Selection>> {
+    let selection = prop_oneof![
+        Just(Selection::FirstBlockOnly),
+        proptest::collection::vec("[a-z]{1,3}", 1..3).prop_map(Selection::Blocks),
+        proptest::collection::vec("_[a-z]{0,2}", 1..3).prop_map(Selection::TagPrefixes),
+    ];
+    proptest::collection::vec(selection, 1..3)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig { cases: 128, failure_persistence: None, ..ProptestConfig::default() })]
+
+    #[test]
+    fn prop_selective_parse_matches_filtered_full_parse(
+        doc in arb_document(),
+        selections in arb_selections(),
+    ) {
+        let text = doc.to_string();
+        let selected = parse_selected(&text, &selections).unwrap();
+        let expected = filtered(Document::parse(&text).unwrap(), &selections);
+        prop_assert!(
+            selected.semantically_equal(&expected),
+            "selections {:?}\n--- written ---\n{}", selections, text
+        );
+    }
+}
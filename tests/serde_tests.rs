@@ -0,0 +1,80 @@
+// tests/serde_tests.rs
+// Documents serialized with serde and read back, behind the serde feature
+
+use cif_parser::{CifDocument, CifValue, Document};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> CifDocument {
+    Document::from_file(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn round_trip(doc: &CifDocument) -> CifDocument {
+    let json = serde_json::to_string(doc).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_json_round_trip_keeps_items_loops_and_frames() {
+    for name in [
+        "mmcif_fragment.cif",
+        "ordered_items.cif",
+        "dictionary/core_mini.dic",
+    ] {
+        let doc = fixture(name);
+        let back = round_trip(&doc);
+        assert!(back.semantically_equal(&doc), "{name}");
+        for (block, original) in back.blocks.iter().zip(&doc.blocks) {
+            // Items keep the order they were written in
+            assert!(block.items.keys().eq(original.items.keys()), "{name}");
+            assert_eq!(block.frames.len(), original.frames.len(), "{name}");
+        }
+    }
+}
+
+#[test]
+fn test_values_and_version() {
+    let doc = Document::parse(
+        "#\\#CIF_2.0\ndata_x\n_list [1 'a' ?]\n_table {'k':.}\n_unknown ?\n_na .\n_text '?'\n",
+    )
+    .unwrap();
+    let back = round_trip(&doc);
+    assert_eq!(back.version, doc.version);
+    let block = &back.blocks[0];
+    assert_eq!(
+        block.get_item("_list"),
+        Some(&CifValue::List(vec![
+            CifValue::Numeric(1.0),
+            CifValue::Text("a".into()),
+            CifValue::Unknown
+        ]))
+    );
+    assert_eq!(block.get_item("_table"), doc.blocks[0].get_item("_table"));
+    assert_eq!(block.get_item("_unknown"), Some(&CifValue::Unknown));
+    assert_eq!(block.get_item("_na"), Some(&CifValue::NotApplicable));
+    // A quoted question mark is text, not the unknown marker
+    assert_eq!(block.get_item("_text"), Some(&CifValue::Text("?".into())));
+}
+
+#[test]
+fn test_source_is_not_serialized() {
+    let doc = fixture("simple.cif");
+    assert!(doc.source.is_some());
+    let json = serde_json::to_value(&doc).unwrap();
+    assert!(json.get("source").is_none());
+    assert!(round_trip(&doc).source.is_none());
+}
+
+#[test]
+fn test_missing_document_fields_take_their_defaults() {
+    let json = r#"{"blocks": [{"name": "x", "items": {"_a": {"Numeric": 1.5}},
+                   "loops": [], "frames": []}]}"#;
+    let doc: CifDocument = serde_json::from_str(json).unwrap();
+    assert_eq!(doc.blocks[0].get_item("_a"), Some(&CifValue::Numeric(1.5)));
+    assert!(doc.warnings.is_empty());
+    assert!(!doc.partial);
+}
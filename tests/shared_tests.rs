@@ -0,0 +1,149 @@
+// tests/shared_tests.rs
+// Concurrent read access to shared documents, and copy-on-write mutation
+
+use cif_parser::{Document, DocumentHandle, SharedBlock, Value};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+fn atom_document(rows: usize) -> Document {
+    let mut cif = String::from(
+        "data_shared\n_cell_length_a 10.0\nloop_\n_atom_site_label\n_atom_site_fract_x\n",
+    );
+    for i in 0..rows {
+        cif.push_str(&format!("C{} {}\n", i, i as f64 / rows as f64));
+    }
+    Document::parse(&cif).unwrap()
+}
+
+#[test]
+fn test_shared_handles_index_into_document() {
+    let shared = atom_document(3).into_shared();
+
+    let block = SharedBlock::by_name(shared.clone(), "shared").unwrap();
+    assert_eq!(block.index(), 0);
+    assert_eq!(
+        block.get_item("_cell_length_a").unwrap().as_numeric(),
+        Some(10.0)
+    );
+
+    let loop_ = block.find_loop("_atom_site_fract_x").unwrap();
+    assert_eq!(loop_.index(), 0);
+    assert_eq!(loop_.len(), 3);
+    assert!(Arc::ptr_eq(loop_.block().document(), &shared));
+
+    assert!(SharedBlock::new(shared.clone(), 1).is_none());
+    assert!(block.loop_at(1).is_none());
+    assert!(block.find_loop("_missing").is_none());
+}
+
+#[test]
+fn test_many_threads_read_columns_from_one_document() {
+    const THREADS: usize = 16;
+    const ROWS: usize = 500;
+
+    let shared = atom_document(ROWS).into_shared();
+    let expected: f64 = shared.blocks[0].loops[0]
+        .get_column("_atom_site_fract_x")
+        .unwrap()
+        .iter()
+        .map(|v| v.as_numeric().unwrap())
+        .sum();
+
+    // All threads start reading at the same time to maximise overlap
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let loop_ = SharedBlock::new(shared.clone(), 0)
+                .unwrap()
+                .find_loop("_atom_site_fract_x")
+                .unwrap();
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                let mut total = 0.0;
+                for _ in 0..10 {
+                    total = loop_
+                        .get_column("_atom_site_fract_x")
+                        .unwrap()
+                        .iter()
+                        .map(|v| v.as_numeric().unwrap())
+                        .sum();
+                }
+                total
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+
+    // Handles were dropped with their threads, leaving only our reference
+    assert_eq!(Arc::strong_count(&shared), 1);
+}
+
+#[test]
+fn test_make_mut_block_copies_only_when_shared() {
+    let mut shared = atom_document(2).into_shared();
+    let unique = Arc::as_ptr(&shared);
+    Document::make_mut_block(&mut shared, 0).unwrap().name = "first".into();
+    // Nobody else could see the document, so it was changed in place
+    assert_eq!(Arc::as_ptr(&shared), unique);
+
+    let reader = SharedBlock::new(shared.clone(), 0).unwrap();
+    let loop_ = reader.find_loop("_atom_site_label").unwrap();
+    let block = Document::make_mut_block(&mut shared, 0).unwrap();
+    block.name = "second".into();
+    block.loops.clear();
+
+    assert!(!Arc::ptr_eq(&shared, reader.document()));
+    assert_eq!(reader.name, "first");
+    assert_eq!(loop_.len(), 2);
+    assert_eq!(shared.blocks[0].name, "second");
+    assert!(Document::make_mut_block(&mut shared, 1).is_none());
+}
+
+#[test]
+fn test_document_handle_readers_see_whole_updates() {
+    const ROWS: usize = 200;
+    const UPDATES: usize = 50;
+
+    let handle = DocumentHandle::new(atom_document(ROWS));
+    let writer = {
+        let handle = handle.clone();
+        thread::spawn(move || {
+            for round in 1..=UPDATES {
+                // Every row of a version holds the same label
+                handle.update_block(0, |block| {
+                    for row in &mut block.loops[0].values {
+                        row[0] = Value::Text(format!("round{round}"));
+                    }
+                });
+            }
+        })
+    };
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                let mut seen = 0;
+                while seen < UPDATES {
+                    let loop_ = handle.block(0).unwrap().loop_at(0).unwrap();
+                    let labels = loop_.get_column("_atom_site_label").unwrap();
+                    assert_eq!(labels.len(), ROWS);
+                    if let Some(label) = labels[0].as_string().and_then(|l| l.strip_prefix("round"))
+                    {
+                        assert!(labels.iter().all(|l| l == &labels[0]), "torn update");
+                        seen = label.parse().unwrap();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    writer.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert!(handle.update_block(1, |_| ()).is_none());
+}
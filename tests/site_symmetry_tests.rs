@@ -0,0 +1,149 @@
+// tests/site_symmetry_tests.rs
+// Site symmetry and multiplicity of atom sites, checked against the file
+
+use cif_parser::structure::DEFAULT_SITE_TOLERANCE;
+use cif_parser::{Block, Document, SiteSymmetry};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> Block {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name);
+    Document::from_file(path).unwrap().blocks.remove(0)
+}
+
+fn site_symmetries(block: &Block) -> Vec<SiteSymmetry> {
+    block
+        .structure()
+        .unwrap()
+        .site_symmetries(DEFAULT_SITE_TOLERANCE)
+}
+
+#[test]
+fn test_rock_salt() {
+    let block = fixture("fixtures/site_symmetry/nacl.cif");
+    let mut structure = block.structure().unwrap();
+    assert_eq!(structure.symmetry.len(), 192);
+
+    for site in structure.site_symmetries(DEFAULT_SITE_TOLERANCE) {
+        assert_eq!(site.multiplicity, 4, "{}", site.label);
+        assert_eq!(site.stabilizer.len(), 48);
+        assert_eq!(site.symbol, Some("m-3m"));
+        assert!(site.is_special());
+        assert_eq!(site.mismatch(), None);
+    }
+
+    // The tetrahedral holes (8c), the middle of the edges of the
+    // tetrahedra (48g), and a general position (192l)
+    for (fract, multiplicity, symbol) in [
+        ([0.25, 0.25, 0.25], 8, "-43m"),
+        ([0.1, 0.25, 0.25], 48, "mm2"),
+        ([0.1, 0.2, 0.35], 192, "1"),
+    ] {
+        structure.sites[0].fract = Some(fract);
+        let site = &structure.site_symmetries(DEFAULT_SITE_TOLERANCE)[0];
+        assert_eq!(site.multiplicity, multiplicity, "{fract:?}");
+        assert_eq!(site.symbol, Some(symbol), "{fract:?}");
+        assert_eq!(site.is_special(), multiplicity < 192);
+    }
+}
+
+#[test]
+fn test_rutile() {
+    let sites = site_symmetries(&fixture("fixtures/site_symmetry/rutile.cif"));
+    let summary: Vec<(&str, usize, Option<&str>, bool)> = sites
+        .iter()
+        .map(|s| (s.label.as_str(), s.multiplicity, s.symbol, s.is_special()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("Ti1", 2, Some("mmm"), true),
+            ("O1", 4, Some("mm2"), true),
+            ("O2", 16, Some("1"), false),
+        ]
+    );
+    // O1 declares the multiplicity of Ti1
+    assert_eq!(sites[0].mismatch(), None);
+    assert_eq!(sites[1].mismatch(), Some((2, 4)));
+    assert_eq!(sites[2].mismatch(), None);
+}
+
+#[test]
+fn test_mismatch_is_a_sanity_issue() {
+    let issues = fixture("fixtures/site_symmetry/rutile.cif").sanity_check();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].id, "site-multiplicity");
+    assert_eq!(issues[0].row, Some(1));
+    assert_eq!(
+        issues[0].to_string(),
+        "_atom_site_site_symmetry_multiplicity[1] = 2: site multiplicity must be 4 \
+         from the symmetry operations (site symmetry mm2)"
+    );
+    assert!(fixture("fixtures/site_symmetry/nacl.cif")
+        .sanity_check()
+        .is_empty());
+}
+
+#[test]
+fn test_declared_multiplicities_in_examples() {
+    // Garnet, I a -3 d, with its 96 operations: Lu on 24c, Al on 16a and
+    // 24d, O on a general position
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/example_cifs/LuAg/jana2020_LuAG.cif");
+    let doc = Document::from_file(path).unwrap();
+    let sites = site_symmetries(doc.main_block().unwrap());
+    let multiplicities: Vec<usize> = sites.iter().map(|s| s.multiplicity).collect();
+    assert_eq!(multiplicities, [24, 24, 16, 96]);
+    assert!(sites.iter().all(|s| s.mismatch().is_none()));
+    assert_eq!(sites[2].symbol, Some("-3"));
+
+    for path in [
+        "example_cifs/natrolite/ccdc_natrolite.cif",
+        "example_cifs/quartz/pycifrw_quartz.cif",
+    ] {
+        let doc = Document::from_file(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join(path),
+        )
+        .unwrap();
+        for block in &doc.blocks {
+            for site in site_symmetries(block) {
+                assert!(
+                    site.declared_multiplicity.is_some(),
+                    "{path} {}",
+                    site.label
+                );
+                assert_eq!(site.mismatch(), None, "{path} {}", site.label);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_without_symmetry_operations() {
+    let cif = "\
+data_p1
+_cell_length_a 5
+_cell_length_b 5
+_cell_length_c 5
+_cell_angle_alpha 90
+_cell_angle_beta 90
+_cell_angle_gamma 90
+loop_
+_atom_site_label
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+_atom_site_site_symmetry_multiplicity
+Na1 0 0 0 4
+";
+    let block = Document::parse(cif).unwrap().blocks.remove(0);
+    let sites = site_symmetries(&block);
+    assert_eq!(sites[0].multiplicity, 1);
+    assert_eq!(sites[0].symbol, Some("1"));
+    assert!(!sites[0].is_special());
+    // Without operations there is nothing to check the declared value against
+    assert!(block.sanity_check().is_empty());
+}
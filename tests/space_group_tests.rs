@@ -0,0 +1,226 @@
+// tests/space_group_tests.rs
+// Space groups from Hall and Hermann-Mauguin symbols and numbers
+
+use cif_parser::symmetry::point_group;
+use cif_parser::{CrystalSystem, Document, SpaceGroup, SymOp};
+use std::path::Path;
+
+fn load(path: &str) -> Document {
+    Document::from_file(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+}
+
+/// Equal up to a lattice translation
+fn same(a: &SymOp, b: &SymOp) -> bool {
+    a.rotation == b.rotation
+        && (0..3).all(|i| {
+            let d = a.translation[i] - b.translation[i];
+            (d - d.round()).abs() < 1e-6
+        })
+}
+
+#[test]
+fn test_every_number_gives_a_closed_group_of_its_crystal_system() {
+    for number in 1..=230 {
+        let group = SpaceGroup::from_number(number).unwrap();
+        assert_eq!(group.number, Some(number));
+        assert_eq!(group.operations[0], SymOp::identity(), "{number}");
+
+        let info = point_group(&group.operations).unwrap();
+        assert_eq!(
+            Some(info.crystal_system),
+            CrystalSystem::from_space_group_number(number),
+            "{number}"
+        );
+        for a in &group.operations {
+            for b in &group.operations {
+                let product = a.compose(b);
+                assert!(
+                    group.operations.iter().any(|op| same(op, &product)),
+                    "{number}: {a} ∘ {b}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_symbols_match_the_operation_loops_of_real_files() {
+    let paths = [
+        "example_cifs/albite/jana2006_albite.cif",
+        "example_cifs/glycine/jana2006_glycine.cif",
+        "example_cifs/xanthine/pycifrw_xanthine.cif",
+        "example_cifs/paracetamol/ccdc_paracetamol.cif",
+        "example_cifs/natrolite/ccdc_natrolite.cif",
+        "example_cifs/quartz/ccdc_quartz.cif",
+        "example_cifs/quartz/pycifrw_quartz.cif",
+        "example_cifs/urea/cod_urea.cif",
+        "example_cifs/LuAg/crystalmaker_LuAG.cif",
+        "fixtures/symmetry/tetragonal_rutile.cif",
+        "fixtures/symmetry/hexagonal_magnesium.cif",
+    ];
+    for path in paths {
+        let doc = load(&format!("tests/{path}"));
+        // Jana files start with a global block without symmetry
+        let block = doc
+            .blocks
+            .iter()
+            .find(|block| !block.symmetry_operations().is_empty())
+            .unwrap();
+        let listed = block.symmetry_operations();
+        let group = block
+            .space_group()
+            .unwrap_or_else(|| panic!("{path}: no space group"));
+        assert!(group.number.is_some(), "{path}: {}", group.hall);
+        assert_eq!(group.operations.len(), listed.len(), "{path}");
+        for op in &listed {
+            assert!(
+                group.operations.iter().any(|other| same(op, other)),
+                "{path}: {op} is not in {}",
+                group.hall
+            );
+        }
+    }
+}
+
+#[test]
+fn test_hermann_mauguin_spellings() {
+    let p21c = SpaceGroup::from_hermann_mauguin("P 21/c").unwrap();
+    for symbol in ["P21/c", "P 1 21/c 1", "p 21/c", "P2_1/c"] {
+        assert_eq!(
+            SpaceGroup::from_hermann_mauguin(symbol).as_ref(),
+            Some(&p21c)
+        );
+    }
+    assert_eq!(p21c.hermann_mauguin, Some("P 1 21/c 1"));
+
+    let cmce = SpaceGroup::from_hermann_mauguin("C m c e").unwrap();
+    assert_eq!(cmce.number, Some(64));
+    assert_eq!(cmce.hermann_mauguin, Some("C m c a"));
+
+    // Unique axis c
+    let group = SpaceGroup::from_hermann_mauguin("P 1 1 21/b").unwrap();
+    assert_eq!((group.number, group.hall.as_str()), (Some(14), "-P 2bc"));
+
+    // Orthorhombic settings other than the standard one are not in the table
+    assert!(SpaceGroup::from_hermann_mauguin("P n 21 a").is_none());
+    assert!(SpaceGroup::from_hermann_mauguin("X 1").is_none());
+}
+
+#[test]
+fn test_origin_choices_and_rhombohedral_axes() {
+    let inversion = |group: &SpaceGroup| {
+        let centre = SymOp::parse("-x,-y,-z").unwrap();
+        group.operations.contains(&centre)
+    };
+    let first = SpaceGroup::from_hermann_mauguin("F d -3 m").unwrap();
+    let second = SpaceGroup::from_hermann_mauguin("Fd-3m:2").unwrap();
+    assert_eq!(first.hermann_mauguin, Some("F d -3 m :1"));
+    assert_eq!(second.hermann_mauguin, Some("F d -3 m :2"));
+    assert_eq!(
+        (first.operations.len(), second.operations.len()),
+        (192, 192)
+    );
+    // Origin choice 2 puts the origin on an inversion centre
+    assert!(!inversion(&first));
+    assert!(inversion(&second));
+    assert_eq!(SpaceGroup::from_number(227), Some(first));
+
+    let hexagonal = SpaceGroup::from_hermann_mauguin("R -3 m").unwrap();
+    let rhombohedral = SpaceGroup::from_hermann_mauguin("R -3 m :R").unwrap();
+    assert_eq!(hexagonal.operations.len(), 36);
+    assert_eq!(rhombohedral.operations.len(), 12);
+    assert_eq!(rhombohedral.hall, "-P 3* 2");
+}
+
+#[test]
+fn test_hall_symbols() {
+    // An origin shift, in twelfths
+    let group = SpaceGroup::from_hall("P 31 2c (0 0 1)").unwrap();
+    assert_eq!(group.number, Some(151));
+    let ops: Vec<String> = group.operations.iter().map(SymOp::to_string).collect();
+    assert!(ops.contains(&"-y,-x,-z+2/3".to_string()), "{ops:?}");
+
+    // Jana2006 separates the matrices with semicolons and spells out axes
+    let jana = SpaceGroup::from_hall("-I 4bd;2ab;3").unwrap();
+    assert_eq!(jana.number, Some(230));
+    assert_eq!(jana.hermann_mauguin, Some("I a -3 d"));
+    assert_eq!(jana.hall, "-I 4bd;2ab;3");
+    // The Hall symbols of the larger example files
+    for (hall, number) in [
+        ("-P 2yabc", 14),
+        ("P 2xab;2ybc;2zac", 19),
+        ("-C -2x;-2yc;-2zc", 63),
+        ("P 4nw 2abw", 96),
+    ] {
+        assert_eq!(
+            SpaceGroup::from_hall(hall).unwrap().number,
+            Some(number),
+            "{hall}"
+        );
+    }
+
+    // Generating the same operations as an entry is enough
+    let group = SpaceGroup::from_hall("P -2y -1").unwrap();
+    assert_eq!(group.hermann_mauguin, Some("P 1 2/m 1"));
+
+    // A Hall symbol outside the table still gives its operations
+    let group = SpaceGroup::from_hall("P 2x").unwrap();
+    assert_eq!((group.number, group.operations.len()), (None, 2));
+    let group = SpaceGroup::from_hall("-P 2x 2yc (1 2 3)").unwrap();
+    assert_eq!((group.number, group.operations.len()), (None, 8));
+
+    assert!(SpaceGroup::from_hall("Q 2").is_none());
+    assert!(SpaceGroup::from_hall("P 5").is_none());
+    // 6-fold and 4-fold axes at right angles generate no finite group
+    assert!(SpaceGroup::from_hall("P 6 4x").is_none());
+}
+
+#[test]
+fn test_block_space_group() {
+    let parse = |cif: &str| Document::parse(cif).unwrap().blocks.remove(0);
+
+    // The Hall symbol wins over the Hermann-Mauguin symbol and the number
+    let block = parse(
+        "data_a\n_space_group_name_Hall '-P 2yn'\n\
+         _space_group_name_H-M_alt 'P 21/c'\n_space_group_IT_number 15\n",
+    );
+    assert_eq!(block.space_group().unwrap().hall, "-P 2yn");
+
+    let block = parse("data_a\n_symmetry_space_group_name_H-M 'P n m a'\n");
+    assert_eq!(block.space_group().unwrap().number, Some(62));
+
+    let block = parse("data_a\n_space_group.IT_number 19\n");
+    assert_eq!(block.space_group().unwrap().operations.len(), 4);
+
+    // A symbol missing from the table may be a non-standard setting, so
+    // the number is not used instead
+    let block = parse("data_a\n_space_group_name_H-M_alt 'P n 21 a'\n_space_group_IT_number 33\n");
+    assert!(block.space_group().is_none());
+
+    assert!(parse("data_a\n_cell_length_a 5\n").space_group().is_none());
+}
+
+#[test]
+fn test_structure_uses_the_space_group_without_an_operation_loop() {
+    let cif = "data_nacl
+_cell_length_a 5.64
+_cell_length_b 5.64
+_cell_length_c 5.64
+_cell_angle_alpha 90
+_cell_angle_beta 90
+_cell_angle_gamma 90
+_space_group_name_H-M_alt 'F m -3 m'
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+Na1 Na 0 0 0
+Cl1 Cl 0.5 0.5 0.5
+";
+    let doc = Document::parse(cif).unwrap();
+    let structure = doc.blocks[0].structure().unwrap();
+    assert_eq!(structure.symmetry.len(), 192);
+    assert_eq!(structure.supercell(1, 1, 1).sites.len(), 8);
+}
@@ -0,0 +1,145 @@
+// tests/stream_tests.rs
+// Concatenated CIF streams: one document per entry, recovering from errors;
+// and whole documents from readers
+
+use cif_parser::{CifError, Document};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+fn fixture() -> BufReader<File> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/concatenated.cif");
+    BufReader::new(File::open(path).unwrap())
+}
+
+#[test]
+fn test_broken_entry_leaves_others_intact() {
+    let entries: Vec<_> = Document::parse_multi_stream(fixture()).collect();
+    assert_eq!(entries.len(), 4);
+
+    let first = entries[0].as_ref().unwrap();
+    assert_eq!(first.blocks.len(), 1);
+    assert_eq!(first.blocks[0].name, "1000000");
+    assert_eq!(first.blocks[0].loops[0].len(), 2);
+
+    // The atom site loop of the second entry is missing a value; its
+    // location is given as a line of the whole stream
+    let error = entries[1].as_ref().unwrap_err().to_string();
+    assert!(error.contains("line 24"), "{error}");
+    assert!(error.contains("3 tags but 5 values"), "{error}");
+
+    let third = entries[2].as_ref().unwrap();
+    assert_eq!(third.blocks[0].name, "1000002");
+    let fcf = third.blocks[0]
+        .get_item("_iucr_refine_fcf_details")
+        .unwrap();
+    assert!(fcf.as_string().unwrap().contains("data_embedded_fcf"));
+    assert_eq!(
+        third.blocks[0]
+            .get_item("_cell_length_a")
+            .unwrap()
+            .as_numeric(),
+        Some(5.5)
+    );
+
+    assert_eq!(entries[3].as_ref().unwrap().blocks[0].name, "1000003");
+}
+
+#[test]
+fn test_entries_match_whole_file_parse() {
+    let mut text = String::new();
+    fixture().read_to_string(&mut text).unwrap();
+    // Without the broken entry the whole file parses, block for block
+    let start = text.find("data_1000001").unwrap();
+    let end = text.find("data_1000002").unwrap();
+    text.replace_range(start..end, "");
+    let whole = Document::parse(&text).unwrap();
+
+    let streamed: Vec<Document> = Document::parse_multi_stream(text.as_bytes())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(streamed.len(), whole.blocks.len());
+    for (doc, block) in streamed.iter().zip(&whole.blocks) {
+        assert!(doc.blocks[0].semantically_equal(block), "{}", block.name);
+    }
+}
+
+/// A reader that fails once its content is used up
+struct FailingTail<'a>(&'a [u8]);
+
+impl Read for FailingTail<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(std::io::Error::other("connection reset"));
+        }
+        self.0.read(buf)
+    }
+}
+
+#[test]
+fn test_entries_arrive_before_the_stream_ends() {
+    let reader = BufReader::new(FailingTail(b"data_a\n_x 1\ndata_b\n_y 2\n"));
+    let mut entries = Document::parse_multi_stream(reader);
+
+    assert_eq!(entries.next().unwrap().unwrap().blocks[0].name, "a");
+    assert!(matches!(entries.next(), Some(Err(CifError::Io(_)))));
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn test_cif2_magic_applies_to_every_entry() {
+    let stream = "#\\#CIF_2.0\ndata_a\n_list [1 2]\ndata_b\n_table {'k':1}\n";
+    let entries: Vec<Document> = Document::parse_multi_stream(stream.as_bytes())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+        .iter()
+        .all(|doc| doc.version == cif_parser::CifVersion::V2_0));
+}
+
+#[test]
+fn test_from_reader_matches_from_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mmcif_fragment.cif");
+    let expected = Document::from_file(&path).unwrap();
+    let doc = Document::from_reader(BufReader::new(File::open(&path).unwrap())).unwrap();
+    assert!(doc.semantically_equal(&expected));
+    assert!(doc.source.is_none());
+
+    let options = cif_parser::ParseOptions::new().lenient(true);
+    let doc = Document::from_reader_with_options(&b"junk\ndata_a\n_x 1\n"[..], &options).unwrap();
+    assert_eq!(doc.blocks[0].name, "a");
+    assert!(Document::from_reader(&b"junk\ndata_a\n_x 1\n"[..]).is_err());
+}
+
+#[test]
+fn test_from_reader_gives_io_errors() {
+    let reader = BufReader::new(FailingTail(b"data_a\n_x 1\n"));
+    assert!(matches!(
+        Document::from_reader(reader),
+        Err(CifError::Io(_))
+    ));
+}
+
+#[test]
+fn test_from_reader_matches_parse_past_the_first_piece() {
+    let mut input = String::from("#\\#CIF_2.0\n");
+    for block in 0..25 {
+        input.push_str(&format!("data_b{block}\n_cell [1 2 3]\n_note\n;\n"));
+        input.push_str(&format!("{}\n", "x".repeat(40)).repeat(1200));
+        input.push_str(";\nloop_ _id _x\n");
+        for row in 0..50 {
+            input.push_str(&format!("{row} {}.5\n", row * block));
+        }
+    }
+    assert!(input.len() > 1 << 20);
+    let doc = Document::from_reader(input.as_bytes()).unwrap();
+    assert!(doc.semantically_equal(&Document::parse(&input).unwrap()));
+
+    // An error near the end is reported as parsing the whole input does
+    input.push_str("loop_ _y _z\n1\n");
+    let error = Document::from_reader(input.as_bytes()).unwrap_err();
+    let expected = Document::parse(&input).unwrap_err();
+    assert_eq!(error.to_string(), expected.to_string());
+    assert_eq!(error.span(), expected.span());
+}
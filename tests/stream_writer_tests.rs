@@ -0,0 +1,185 @@
+// tests/stream_writer_tests.rs
+// Writing large loops row by row with CifWriter
+
+use cif_parser::lexer::{TokenKind, Tokenizer};
+use cif_parser::writer::{CifWriter, WriteOptions};
+use cif_parser::{CifValue, CifVersion, Document};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Tracks live and peak heap bytes
+struct PeakAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(live, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+/// Counts the bytes and lines written, keeping none of them
+#[derive(Default)]
+struct Counter {
+    bytes: usize,
+    lines: usize,
+}
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        self.lines += buf.iter().filter(|&&b| b == b'\n').count();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+const TAGS: [&str; 5] = [
+    "_refln_index_h",
+    "_refln_index_k",
+    "_refln_index_l",
+    "_refln_F_squared_calc",
+    "_refln_phase_calc",
+];
+
+/// Write a block with one reflection loop of `rows` rows
+fn write_reflections<W: Write>(out: W, rows: usize) -> io::Result<W> {
+    let mut writer = CifWriter::new(out, WriteOptions::new());
+    writer.begin_block("calc")?;
+    writer.write_item("_diffrn_radiation_wavelength", &CifValue::Numeric(0.0251))?;
+    writer.begin_loop(&TAGS)?;
+    for i in 0..rows {
+        let (h, k, l) = (
+            i as i64 % 101 - 50,
+            (i / 101) as i64 % 101 - 50,
+            (i / 10201) as i64,
+        );
+        let intensity = (i % 9973) as f64 * 0.25;
+        let phase = (i % 360) as f64;
+        writer.write_row_display(&[&h, &k, &l, &intensity, &phase])?;
+    }
+    writer.end_loop()?;
+    writer.end_block()?;
+    writer.finish()
+}
+
+#[test]
+fn test_million_rows_in_bounded_memory() {
+    let rows = 1_000_000;
+    let before = LIVE.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let counter = write_reflections(BufWriter::new(Counter::default()), rows)
+        .unwrap()
+        .into_inner()
+        .map_err(|e| e.into_error())
+        .unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - before;
+
+    // Header, blank line, data_ line, item, loop_ and tags, then the rows
+    assert_eq!(counter.lines, 4 + TAGS.len() + 1 + rows);
+    assert!(counter.bytes > 10 * rows);
+    assert!(peak < 64 * 1024, "writing peaked at {peak} bytes");
+}
+
+#[test]
+fn test_output_reparses() {
+    let rows = 20_000;
+    let text = String::from_utf8(write_reflections(Vec::new(), rows).unwrap()).unwrap();
+    let doc = Document::parse(&text).unwrap();
+    let block = doc.first_block().unwrap();
+
+    assert_eq!(
+        block
+            .get_item("_diffrn_radiation_wavelength")
+            .unwrap()
+            .as_numeric(),
+        Some(0.0251)
+    );
+    let loop_ = block.find_loop("_refln_index_h").unwrap();
+    assert_eq!(loop_.len(), rows);
+    assert_eq!(loop_.tags, TAGS);
+    assert_eq!(
+        loop_
+            .get_by_tag(101, "_refln_index_k")
+            .unwrap()
+            .as_numeric(),
+        Some(-49.0)
+    );
+    assert_eq!(
+        loop_
+            .get_by_tag(7, "_refln_F_squared_calc")
+            .unwrap()
+            .as_numeric(),
+        Some(1.75)
+    );
+}
+
+#[test]
+fn test_same_text_as_document_writer() {
+    let doc = Document::parse(
+        "#\\#CIF_2.0\ndata_x\n_a 'two words'\n_b [1 2]\n_c\n;\nline one\nline two\n;\n\
+         loop_\n_id\n_note\n1 ?\n2 \"it's\"\n3 .\n",
+    )
+    .unwrap();
+    let block = doc.first_block().unwrap();
+
+    let mut writer = CifWriter::new(Vec::new(), WriteOptions::new()).version(CifVersion::V2_0);
+    writer.begin_block(&block.name).unwrap();
+    let mut tags: Vec<&String> = block.items.keys().collect();
+    tags.sort();
+    for tag in tags {
+        writer.write_item(tag, &block.items[tag]).unwrap();
+    }
+    let loop_ = &block.loops[0];
+    writer.begin_loop(&loop_.tags).unwrap();
+    for row in &loop_.values {
+        writer.write_row(row).unwrap();
+    }
+    writer.end_loop().unwrap();
+    writer.end_block().unwrap();
+    let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    assert_eq!(text, doc.to_string());
+}
+
+#[test]
+fn test_empty_output_has_header() {
+    let writer = CifWriter::new(Vec::new(), WriteOptions::new());
+    assert_eq!(writer.finish().unwrap(), b"#\\#CIF_1.1\n");
+}
+
+/// Parsing a million-row loop takes more memory than a test should (the
+/// parse tree is many times the size of the text), so the full output is
+/// checked with the tokenizer, and a smaller one parsed in
+/// `test_output_reparses`
+#[test]
+fn test_million_rows_tokenize() {
+    let rows = 1_000_000;
+    let text = String::from_utf8(write_reflections(Vec::new(), rows).unwrap()).unwrap();
+    let mut values = 0;
+    for token in Tokenizer::new(&text).skip_trivia(true) {
+        if token.unwrap().kind == TokenKind::UnquotedValue {
+            values += 1;
+        }
+    }
+    // The wavelength, then one value per column of each row
+    assert_eq!(values, 1 + TAGS.len() * rows);
+}
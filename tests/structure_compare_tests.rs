@@ -0,0 +1,159 @@
+// tests/structure_compare_tests.rs
+// Comparing two versions of a structure site by site
+
+use cif_parser::{compare_structures, Document, MatchOptions, SiteMatching, Structure};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/structure")
+        .join(name)
+}
+
+fn structure(name: &str) -> Structure {
+    let doc = Document::from_file(fixture(name)).unwrap();
+    doc.blocks[0].structure().unwrap()
+}
+
+/// A P 21 structure, with `dy` added to every y and atoms renamed by `label`
+fn p21(dy: f64, label: impl Fn(&str) -> String) -> Structure {
+    let sites = [
+        ("S1", 0.2134, 0.1021, 0.3317),
+        ("O1", 0.3561, 0.2284, 0.4190),
+        ("O2", 0.0427, 0.1676, 0.2052),
+        ("C1", 0.2790, -0.1236, 0.3895),
+    ];
+    let mut cif = String::from(
+        "data_p21\n\
+         _cell_length_a 5.1\n_cell_length_b 6.2\n_cell_length_c 7.3\n\
+         _cell_angle_alpha 90\n_cell_angle_beta 101.5\n_cell_angle_gamma 90\n\
+         loop_\n_space_group_symop_operation_xyz\n'x, y, z'\n'-x, y+1/2, -z'\n\
+         loop_\n_atom_site_label\n_atom_site_type_symbol\n\
+         _atom_site_fract_x\n_atom_site_fract_y\n_atom_site_fract_z\n",
+    );
+    for (name, x, y, z) in sites {
+        let symbol = name.trim_end_matches(char::is_numeric);
+        cif += &format!("{} {symbol} {x} {:.4} {z}\n", label(name), y + dy);
+    }
+    Document::parse(&cif).unwrap().blocks[0]
+        .structure()
+        .unwrap()
+}
+
+#[test]
+fn test_identical_structures_match_exactly() {
+    let rutile = structure("rutile.cif");
+    let comparison = compare_structures(&rutile, &rutile, &MatchOptions::new());
+    assert_eq!(comparison.sites.len(), 2);
+    assert!(comparison.sites.iter().all(|s| s.distance == 0.0));
+    assert!(comparison.only_in_a.is_empty() && comparison.only_in_b.is_empty());
+    assert_eq!(comparison.rmsd, 0.0);
+}
+
+#[test]
+fn test_expanded_structure_reduces_to_asymmetric_unit() {
+    let au = structure("rutile.cif");
+    let p1 = structure("rutile_p1.cif");
+    assert_eq!(p1.sites.len(), 6);
+
+    let comparison = compare_structures(&au, &p1, &MatchOptions::new());
+    assert!(comparison.only_in_a.is_empty());
+    assert!(comparison.only_in_b.is_empty());
+    assert_eq!(comparison.sites.len(), 2);
+
+    let ti = &comparison.sites[0];
+    assert_eq!((ti.label_a.as_str(), ti.label_b.as_str()), ("Ti1", "Ti1"));
+    assert!(ti.distance < 1e-9);
+    assert!((ti.u_iso.unwrap() - 0.0002).abs() < 1e-12);
+    assert_eq!(ti.occupancy, 0.0);
+
+    // O1 moved 0.001 along both a and b
+    let o = &comparison.sites[1];
+    let expected = 0.001 * 4.5937 * 2f64.sqrt();
+    assert!((o.distance - expected).abs() < 1e-9);
+    assert!((o.displacement[0] - 0.0045937).abs() < 1e-9);
+    assert!((o.occupancy + 0.02).abs() < 1e-12);
+    assert!((comparison.rmsd - expected / 2f64.sqrt()).abs() < 1e-9);
+
+    // The same, the other way round
+    let reverse = compare_structures(&p1, &au, &MatchOptions::new());
+    assert_eq!(reverse.sites.len(), 2);
+    assert!((reverse.rmsd - comparison.rmsd).abs() < 1e-12);
+}
+
+#[test]
+fn test_origin_shift_along_polar_axis_is_recovered() {
+    let a = p21(0.0, str::to_string);
+    let b = p21(0.13, |name| format!("{name}A"));
+
+    let options = MatchOptions::new()
+        .matching(SiteMatching::Proximity { max_distance: 0.5 })
+        .origin_shift(true);
+    let comparison = compare_structures(&a, &b, &options);
+    assert_eq!(comparison.sites.len(), 4);
+    assert!(comparison.rmsd < 1e-9);
+    assert!((comparison.origin_shift[1] - 0.13).abs() < 1e-9);
+    assert_eq!(comparison.origin_shift[0], 0.0);
+    assert_eq!(comparison.origin_shift[2], 0.0);
+    let pairs: Vec<_> = comparison
+        .sites
+        .iter()
+        .map(|s| (s.label_a.as_str(), s.label_b.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [("S1", "S1A"), ("O1", "O1A"), ("O2", "O2A"), ("C1", "C1A")]
+    );
+
+    // By label the shift is fitted too
+    let b = p21(0.13, str::to_string);
+    let by_label = compare_structures(&a, &b, &MatchOptions::new().origin_shift(true));
+    assert!(by_label.rmsd < 1e-9);
+    assert!((by_label.origin_shift[1] - 0.13).abs() < 1e-9);
+
+    // Without fitting, every atom is 0.13 b away
+    let fixed = compare_structures(&a, &b, &MatchOptions::new());
+    assert!((fixed.rmsd - 0.13 * 6.2).abs() < 1e-9);
+    assert_eq!(fixed.origin_shift, [0.0; 3]);
+}
+
+#[test]
+fn test_origin_is_fixed_in_centrosymmetric_groups() {
+    let rutile = structure("rutile.cif");
+    let comparison = compare_structures(&rutile, &rutile, &MatchOptions::new().origin_shift(true));
+    assert_eq!(comparison.origin_shift, [0.0; 3]);
+    assert_eq!(comparison.rmsd, 0.0);
+}
+
+#[test]
+fn test_unmatched_atoms_are_listed() {
+    let a = p21(0.0, str::to_string);
+    let mut b = p21(0.0, str::to_string);
+    b.sites.retain(|site| site.label != "O2");
+    b.sites[0].label = "S2".to_string();
+
+    let comparison = compare_structures(&a, &b, &MatchOptions::new());
+    assert_eq!(comparison.sites.len(), 2);
+    assert_eq!(comparison.only_in_a, ["S1", "O2"]);
+    assert_eq!(comparison.only_in_b, ["S2"]);
+
+    // By proximity the renamed atom is found
+    let options = MatchOptions::new().matching(SiteMatching::Proximity { max_distance: 0.5 });
+    let comparison = compare_structures(&a, &b, &options);
+    assert_eq!(comparison.sites.len(), 3);
+    assert_eq!(comparison.sites[0].label_b, "S2");
+    assert_eq!(comparison.only_in_a, ["O2"]);
+    assert!(comparison.only_in_b.is_empty());
+}
+
+#[test]
+fn test_symmetry_equivalent_positions_match() {
+    // The same atom written at a different equivalent position
+    let a = p21(0.0, str::to_string);
+    let mut b = a.clone();
+    let [x, y, z] = b.sites[0].fract.unwrap();
+    b.sites[0].fract = Some([-x + 1.0, y + 0.5, -z]);
+
+    let comparison = compare_structures(&a, &b, &MatchOptions::new());
+    assert!(comparison.rmsd < 1e-9);
+}
@@ -0,0 +1,182 @@
+// tests/structure_transform_tests.rs
+// Wrapping coordinates, shifting the origin, and building supercells
+
+use cif_parser::structure::DEFAULT_SITE_TOLERANCE;
+use cif_parser::{Document, Structure};
+use std::path::PathBuf;
+
+fn structure(name: &str) -> Structure {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    Document::from_file(path).unwrap().blocks[0]
+        .structure()
+        .unwrap()
+}
+
+/// Shortest distance between each pair of sites, allowing lattice translations
+fn distances(structure: &Structure) -> Vec<f64> {
+    let sites = &structure.sites;
+    let mut distances = Vec::new();
+    for (i, a) in sites.iter().enumerate() {
+        for b in &sites[i + 1..] {
+            let (a, b) = (a.fract.unwrap(), b.fract.unwrap());
+            let delta = [0, 1, 2].map(|k| {
+                let d = b[k] - a[k];
+                d - d.round()
+            });
+            distances.push(structure.cell.distance([0.0; 3], delta));
+        }
+    }
+    distances
+}
+
+#[test]
+fn test_wrap_keeps_distances() {
+    let cif = "\
+data_wrap
+_cell_length_a 5.1
+_cell_length_b 6.2
+_cell_length_c 7.3
+_cell_angle_alpha 90
+_cell_angle_beta 101.5
+_cell_angle_gamma 90
+loop_
+_atom_site_label
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+C1 1.1 -0.2 0.3
+C2 -0.05 2.75 0.999
+O1 0.4 0.5 -1.0
+";
+    let mut structure = Document::parse(cif).unwrap().blocks[0].structure().unwrap();
+    let before = distances(&structure);
+    structure.wrap_coordinates();
+
+    for site in &structure.sites {
+        assert!(site.fract.unwrap().iter().all(|&x| (0.0..1.0).contains(&x)));
+    }
+    assert_eq!(structure.sites[2].fract, Some([0.4, 0.5, 0.0]));
+    for (before, after) in before.iter().zip(distances(&structure)) {
+        assert!((before - after).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_shift_origin_moves_operations_with_sites() {
+    let mut structure = structure("site_symmetry/rutile.cif");
+    let before: Vec<usize> = structure
+        .site_symmetries(DEFAULT_SITE_TOLERANCE)
+        .iter()
+        .map(|site| site.multiplicity)
+        .collect();
+    let distances_before = distances(&structure);
+
+    structure.shift_origin([0.25, -0.1, 0.5]);
+    assert_eq!(structure.sites[0].fract, Some([0.25, -0.1, 0.5]));
+    // -x,-y,z about the new origin
+    assert_eq!(structure.symmetry[1].translation, [0.5, 0.8, 0.0]);
+    for op in &structure.symmetry {
+        assert!(op.translation.iter().all(|&t| (0.0..1.0).contains(&t)));
+    }
+
+    let after: Vec<usize> = structure
+        .site_symmetries(DEFAULT_SITE_TOLERANCE)
+        .iter()
+        .map(|site| site.multiplicity)
+        .collect();
+    assert_eq!(before, after);
+    for (before, after) in distances_before.iter().zip(distances(&structure)) {
+        assert!((before - after).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_supercell_of_rock_salt() {
+    let structure = structure("site_symmetry/nacl.cif");
+    assert!(!structure.reduced_to_p1);
+
+    // Na and Cl each on a 4-fold position of F m -3 m
+    let cell = structure.supercell(1, 1, 1);
+    assert_eq!(cell.sites.len(), 8);
+    assert_eq!(cell.symmetry.len(), 1);
+    assert!(cell.reduced_to_p1);
+
+    let supercell = structure.supercell(2, 2, 1);
+    assert_eq!(supercell.sites.len(), 32);
+    assert!(supercell.reduced_to_p1);
+    let volume = structure.cell.volume();
+    assert!((supercell.cell.volume() - 4.0 * volume).abs() < 1e-9 * volume);
+    assert_eq!(supercell.cell.c, structure.cell.c);
+
+    let labels: Vec<&str> = supercell.sites.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels[0], "Na1_1");
+    assert_eq!(labels[15], "Na1_16");
+    assert_eq!(labels[16], "Cl1_1");
+    // Labels are unique
+    let mut unique = labels.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), 32);
+
+    // Each atom of the supercell is its own site, and the nearest
+    // neighbour distance is a/2
+    for site in supercell.site_symmetries(DEFAULT_SITE_TOLERANCE) {
+        assert_eq!(site.multiplicity, 1);
+    }
+    let shortest = distances(&supercell)
+        .into_iter()
+        .fold(f64::INFINITY, f64::min);
+    assert!((shortest - structure.cell.a / 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_supercell_keeps_site_parameters() {
+    let structure = structure("site_symmetry/rutile.cif");
+    let supercell = structure.supercell(1, 1, 3);
+    // 2 Ti, 4 O1, 16 O2 in each cell
+    assert_eq!(supercell.sites.len(), 3 * (2 + 4 + 16));
+    for site in &supercell.sites {
+        let original = structure
+            .sites
+            .iter()
+            .find(|s| site.label.starts_with(&format!("{}_", s.label)))
+            .unwrap();
+        assert_eq!(site.occupancy, original.occupancy);
+        assert_eq!(site.u_iso, original.u_iso);
+        assert_eq!(site.type_symbol, original.type_symbol);
+        assert_eq!(site.symmetry_multiplicity, None);
+        assert!((0.0..1.0).contains(&site.fract.unwrap()[2]));
+    }
+}
+
+#[test]
+fn test_to_xyz() {
+    let cif = "\
+data_xyz
+_cell_length_a 4
+_cell_length_b 5
+_cell_length_c 6
+_cell_angle_alpha 90
+_cell_angle_beta 90
+_cell_angle_gamma 90
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+Fe1 Fe2+ 0.5 0 0
+Q1 ? 0 0.5 0.5
+";
+    let structure = Document::parse(cif).unwrap().blocks[0].structure().unwrap();
+    assert_eq!(
+        structure.to_xyz(),
+        "2\n\
+         Lattice=\"4.000000 0.000000 0.000000 0.000000 5.000000 0.000000 \
+         0.000000 0.000000 6.000000\" Properties=species:S:1:pos:R:3 pbc=\"T T T\"\n\
+         Fe 2.000000 0.000000 0.000000\n\
+         Q1 0.000000 2.500000 3.000000\n"
+    );
+}
@@ -0,0 +1,145 @@
+// tests/symmetry_tests.rs
+// Point groups of real structures in all seven crystal systems
+
+use cif_parser::{CrystalSystem, Document, SymOp};
+use std::path::Path;
+
+fn load(path: &str) -> Document {
+    Document::from_file(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+}
+
+#[test]
+fn test_crystal_systems() {
+    let cases = [
+        (
+            "example_cifs/albite/jana2006_albite.cif",
+            "-1",
+            CrystalSystem::Triclinic,
+        ),
+        (
+            "example_cifs/carbamazepine/jana2006_carbamazepine.cif",
+            "2/m",
+            CrystalSystem::Monoclinic,
+        ),
+        (
+            "example_cifs/abiraterone_acetate/abiraterone_acetate.cif",
+            "222",
+            CrystalSystem::Orthorhombic,
+        ),
+        (
+            "example_cifs/natrolite/ccdc_natrolite.cif",
+            "mm2",
+            CrystalSystem::Orthorhombic,
+        ),
+        (
+            "example_cifs/paracetamol/ccdc_paracetamol.cif",
+            "mmm",
+            CrystalSystem::Orthorhombic,
+        ),
+        (
+            "fixtures/symmetry/tetragonal_rutile.cif",
+            "4/mmm",
+            CrystalSystem::Tetragonal,
+        ),
+        (
+            "example_cifs/quartz/ccdc_quartz.cif",
+            "32",
+            CrystalSystem::Trigonal,
+        ),
+        (
+            "fixtures/symmetry/hexagonal_magnesium.cif",
+            "6/mmm",
+            CrystalSystem::Hexagonal,
+        ),
+        (
+            "example_cifs/urea/cod_urea.cif",
+            "m-3m",
+            CrystalSystem::Cubic,
+        ),
+        (
+            "example_cifs/LuAg/crystalmaker_LuAG.cif",
+            "m-3m",
+            CrystalSystem::Cubic,
+        ),
+    ];
+    for (path, symbol, system) in cases {
+        let doc = load(&format!("tests/{path}"));
+        // Jana files start with a global block without symmetry
+        let group = doc.blocks.iter().find_map(|block| block.point_group());
+        let group = group.unwrap_or_else(|| panic!("{path}: no point group"));
+        assert_eq!(group.symbol, symbol, "{path}");
+        assert_eq!(group.crystal_system, system, "{path}");
+        assert!(group.warnings.is_empty(), "{path}: {:?}", group.warnings);
+    }
+}
+
+#[test]
+fn test_laue_class_and_properties() {
+    let doc = load("tests/example_cifs/quartz/ccdc_quartz.cif");
+    let quartz = doc.first_block().unwrap().point_group().unwrap();
+    assert_eq!(quartz.laue_class, "-3m");
+    assert!(quartz.is_chiral);
+    assert!(!quartz.is_centrosymmetric);
+    assert!(!quartz.is_polar);
+
+    let doc = load("tests/example_cifs/natrolite/ccdc_natrolite.cif");
+    let natrolite = doc.first_block().unwrap().point_group().unwrap();
+    assert_eq!(natrolite.laue_class, "mmm");
+    assert!(natrolite.is_polar);
+    assert!(!natrolite.is_chiral);
+
+    let doc = load("tests/fixtures/symmetry/hexagonal_magnesium.cif");
+    let magnesium = doc.first_block().unwrap().point_group().unwrap();
+    assert_eq!(magnesium.order, 24);
+    assert!(magnesium.is_centrosymmetric);
+}
+
+#[test]
+fn test_conflicts_are_warnings() {
+    let doc = load("tests/fixtures/symmetry/conflicts.cif");
+    let group = doc.first_block().unwrap().point_group().unwrap();
+    assert_eq!(group.symbol, "6/mmm");
+
+    let messages: Vec<&str> = group.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(messages.len(), 2, "{messages:?}");
+    assert!(messages[0].contains("does not have hexagonal metric"));
+    assert!(messages[1].contains("Space group number 62 belongs to point group mmm"));
+}
+
+#[test]
+fn test_block_without_operations() {
+    let doc = Document::parse("data_a\n_cell_length_a 5\n").unwrap();
+    assert!(doc.first_block().unwrap().point_group().is_none());
+}
+
+#[test]
+fn test_operations_of_a_real_file_are_closed_under_composition() {
+    let doc = load("tests/example_cifs/carbamazepine/jana2006_carbamazepine.cif");
+    let ops = doc
+        .blocks
+        .iter()
+        .map(|block| block.symmetry_operations())
+        .find(|ops| !ops.is_empty())
+        .unwrap();
+    assert_eq!(ops.len(), 4);
+
+    // Equal up to a lattice translation
+    let same = |a: &SymOp, b: &SymOp| {
+        a.rotation == b.rotation
+            && (0..3).all(|i| {
+                let d = a.translation[i] - b.translation[i];
+                (d - d.round()).abs() < 1e-9
+            })
+    };
+    for a in &ops {
+        for b in &ops {
+            let product = a.compose(b);
+            assert!(ops.iter().any(|op| same(op, &product)), "{a} ∘ {b}");
+            let point = [0.12, 0.34, 0.56];
+            let (direct, twice) = (product.apply(point), a.apply(b.apply(point)));
+            assert!((0..3).all(|i| (direct[i] - twice[i]).abs() < 1e-12));
+        }
+        // Each operation reads back from its own notation
+        assert_eq!(SymOp::parse(&a.to_string()).as_ref(), Some(a));
+    }
+}
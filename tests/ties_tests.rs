@@ -0,0 +1,103 @@
+// tests/ties_tests.rs
+// Detecting sites tied by EXYZ/EADP-style constraints, apart from symmetry coincidences
+
+use cif_parser::structure::{Structure, Tie, TiedQuantity, DEFAULT_TIE_TOLERANCE};
+use cif_parser::Document;
+use std::path::Path;
+
+fn structure() -> Structure {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ties/disordered.cif");
+    Document::from_file(path).unwrap().blocks[0]
+        .structure()
+        .unwrap()
+}
+
+fn find<'a>(ties: &'a [Tie], quantity: TiedQuantity, label: &str) -> Option<&'a Tie> {
+    ties.iter()
+        .find(|tie| tie.quantity == quantity && tie.labels.iter().any(|l| l == label))
+}
+
+#[test]
+fn test_aniso_tensors_are_read() {
+    let structure = structure();
+    let fe = &structure.sites[2];
+    assert_eq!(
+        fe.u_aniso,
+        Some([0.0141, 0.0162, 0.0150, 0.0012, 0.0031, -0.0004])
+    );
+    assert!((fe.u_aniso_su[0] - 0.0003).abs() < 1e-12);
+    assert!((fe.u_iso_su - 0.0002).abs() < 1e-12);
+    assert_eq!(structure.sites[4].disorder_group.as_deref(), Some("1"));
+    assert_eq!(structure.sites[0].u_aniso, None);
+}
+
+#[test]
+fn test_constraints_are_told_from_symmetry() {
+    let ties = structure().detect_ties(DEFAULT_TIE_TOLERANCE);
+    let summary: Vec<(TiedQuantity, Vec<&str>)> = ties
+        .iter()
+        .map(|tie| {
+            (
+                tie.quantity,
+                tie.labels.iter().map(String::as_str).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (TiedQuantity::Coordinates, vec!["Na1", "K1"]),
+            (TiedQuantity::Coordinates, vec!["Fe1", "Co1"]),
+            (TiedQuantity::UIso, vec!["O9A", "O9B"]),
+            (TiedQuantity::UAniso, vec!["Fe1", "Co1"]),
+            (TiedQuantity::UAniso, vec!["C5A", "C5B"]),
+        ]
+    );
+
+    // Both on the inversion centre, which fixes every coordinate
+    let na = find(&ties, TiedQuantity::Coordinates, "Na1").unwrap();
+    assert!(na.symmetry_required && !na.is_constraint());
+    assert_eq!(na.to_string(), "coordinates tied: Na1, K1 (by symmetry)");
+
+    let fe = find(&ties, TiedQuantity::Coordinates, "Fe1").unwrap();
+    assert!(fe.is_constraint() && !fe.across_parts);
+    assert_eq!(fe.documented, Some(true));
+    assert_eq!(fe.to_string(), "coordinates tied: Fe1, Co1 (EXYZ)");
+
+    let c5 = find(&ties, TiedQuantity::UAniso, "C5A").unwrap();
+    assert!(c5.across_parts);
+    assert_eq!(c5.documented, Some(true));
+
+    let o9 = find(&ties, TiedQuantity::UIso, "O9A").unwrap();
+    assert!(o9.across_parts);
+    assert_eq!(o9.documented, Some(false));
+    assert_eq!(
+        o9.to_string(),
+        "u_iso tied: O9A, O9B (EADP), not described in _refine_special_details"
+    );
+}
+
+#[test]
+fn test_unrefined_and_near_values_are_not_ties() {
+    let structure = structure();
+    let ties = structure.detect_ties(DEFAULT_TIE_TOLERANCE);
+
+    // Riding hydrogens share a U_iso without an su
+    assert!(find(&ties, TiedQuantity::UIso, "H1A").is_none());
+    // O10 has O9A's U_iso with another su, and its coordinates differ in
+    // the last digit
+    assert!(find(&ties, TiedQuantity::UIso, "O10").is_none());
+    assert!(find(&ties, TiedQuantity::Coordinates, "O10").is_none());
+
+    let loose = structure.detect_ties(1e-3);
+    let o9 = find(&loose, TiedQuantity::Coordinates, "O9A").unwrap();
+    assert_eq!(o9.labels, ["O9A", "O10"]);
+}
+
+#[test]
+fn test_no_special_details() {
+    let mut structure = structure();
+    structure.refine_special_details = None;
+    let ties = structure.detect_ties(DEFAULT_TIE_TOLERANCE);
+    assert!(ties.iter().all(|tie| tie.documented.is_none()));
+}
@@ -0,0 +1,197 @@
+// tests/trailer_tests.rs
+// Non-CIF text before the first block and after the last: rejected, or kept leniently
+
+use cif_parser::writer::WriteOptions;
+use cif_parser::{CifError, CifVersion, Document, ParseOptions};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/trailer")
+        .join(name)
+}
+
+fn lenient() -> ParseOptions {
+    ParseOptions::new().lenient(true)
+}
+
+fn strict_error(input: &str) -> (String, Option<(usize, usize)>) {
+    match Document::parse(input).unwrap_err() {
+        CifError::InvalidStructure {
+            message, location, ..
+        } => (message, location),
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+#[test]
+fn test_end_marker_trailer_is_an_error_in_strict_mode() {
+    let error = Document::from_file(fixture("end_marker.cif")).unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("line 27, column 1"), "{message}");
+    assert!(
+        message.contains("Content after the last data block is not CIF"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_end_marker_trailer_is_kept_leniently() {
+    let doc = Document::from_file_with_options(fixture("end_marker.cif"), &lenient()).unwrap();
+    assert_eq!(
+        doc.trailer.as_deref(),
+        Some(
+            "#END\n\
+             #------------------------------------------------------------------------------\n\
+             CHECKSUM  MD5  4c5dd84d7f55a1f7a3cfa2b3f1d7e2c9\n\
+             Archived 2016-02-20 by cifarchive 2.3\n"
+        )
+    );
+    assert_eq!(doc.preamble, None);
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].location, Some((27, 1)));
+
+    // The checksum is not read as two more atom sites
+    let atoms = &doc.blocks[0].loops[0];
+    assert_eq!(atoms.len(), 4);
+    assert_eq!(
+        atoms
+            .get_by_tag(3, "_atom_site_label")
+            .and_then(|v| v.as_string()),
+        Some("C1")
+    );
+}
+
+#[test]
+fn test_instrument_banner_is_an_error_in_strict_mode() {
+    let error = Document::from_file(fixture("instrument_banner.cif")).unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("line 1, column 1"), "{message}");
+    assert!(
+        message.contains("Content before the first data block is not CIF"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_instrument_banner_is_kept_leniently() {
+    let doc =
+        Document::from_file_with_options(fixture("instrument_banner.cif"), &lenient()).unwrap();
+    assert_eq!(
+        doc.preamble.as_deref(),
+        Some(
+            "*** Bruker APEX3 v2019.1-0 ***\n\
+             Exported: 2019-07-11 14:32  Operator: xtal\n\n\
+             #\\#CIF_1.1\n"
+        )
+    );
+    assert_eq!(doc.trailer, None);
+    assert_eq!(doc.blocks[0].name, "sad");
+    assert_eq!(doc.blocks[0].items.len(), 5);
+}
+
+#[test]
+fn test_locations_after_a_preamble_are_unchanged() {
+    let cif = "Banner line\nmore banner\ndata_a\n_x 1\n_x 2\n";
+    let options = lenient().duplicate_tags(cif_parser::DuplicateTags::Collapse);
+    let doc = Document::parse_with_options(cif, &options).unwrap();
+    let locations: Vec<_> = doc.warnings.iter().map(|w| w.location).collect();
+    assert_eq!(locations, [Some((1, 1)), Some((5, 1))]);
+}
+
+#[test]
+fn test_comments_and_magic_are_not_a_preamble_or_trailer() {
+    let cif = "#\\#CIF_2.0\n# Written by hand\n\ndata_a\n_x [1 2]\n#END\n# end of file\n";
+    for options in [ParseOptions::new(), lenient()] {
+        let doc = Document::parse_with_options(cif, &options).unwrap();
+        assert_eq!(doc.version, CifVersion::V2_0);
+        assert_eq!((doc.preamble, doc.trailer), (None, None));
+        assert!(doc.warnings.is_empty());
+    }
+}
+
+#[test]
+fn test_end_marker_between_blocks_is_not_a_trailer() {
+    let cif = "data_a\n_x 1\n#END\ndata_b\n_x 2\n#END\n";
+    let doc = Document::parse(cif).unwrap();
+    assert_eq!(doc.blocks.len(), 2);
+    assert_eq!(doc.trailer, None);
+}
+
+#[test]
+fn test_stray_values_at_the_end() {
+    // Strict mode keeps the parser's own message
+    let (message, location) = strict_error("data_a\n_x 1\nCHECKSUM 1234\n");
+    assert_eq!(
+        message,
+        "Value 'CHECKSUM' is outside any loop and has no data name"
+    );
+    assert_eq!(location, Some((3, 1)));
+
+    let doc = Document::parse_with_options("data_a\n_x 1\nCHECKSUM 1234\n", &lenient()).unwrap();
+    assert_eq!(doc.trailer.as_deref(), Some("CHECKSUM 1234\n"));
+    assert_eq!(doc.blocks[0].items.len(), 1);
+}
+
+#[test]
+fn test_binary_junk_after_a_loop() {
+    let cif = "data_a\nloop_\n_x\n1\n2\n\u{0}\u{7f}\u{1b}PK\u{3}\u{4}\n";
+    let (message, location) = strict_error(cif);
+    assert_eq!(message, "Content after the last data block is not CIF");
+    assert_eq!(location, Some((6, 1)));
+
+    let doc = Document::parse_with_options(cif, &lenient()).unwrap();
+    assert_eq!(doc.blocks[0].loops[0].len(), 2);
+    assert_eq!(
+        doc.trailer.as_deref(),
+        Some("\u{0}\u{7f}\u{1b}PK\u{3}\u{4}\n")
+    );
+}
+
+#[test]
+fn test_invalid_utf8_is_accepted_only_in_the_trailer() {
+    let mut bytes = b"data_a\n_x 1\n#END\n".to_vec();
+    bytes.extend_from_slice(b"\xFF\xFE\x00junk\n");
+    assert!(Document::from_bytes(&bytes).is_err());
+
+    let doc = Document::from_bytes_with_options(&bytes, &lenient()).unwrap();
+    assert_eq!(
+        doc.trailer.as_deref(),
+        Some("#END\n\u{FFFD}\u{FFFD}\u{0}junk\n")
+    );
+    assert_eq!(doc.warnings.len(), 2);
+
+    // A bad byte inside the data is still an error
+    let bytes = b"data_a\n_x caf\xE9\n#END\n\xFF\n";
+    let error = Document::from_bytes_with_options(bytes, &lenient()).unwrap_err();
+    assert!(error.to_string().contains("0xE9"), "{error}");
+}
+
+#[test]
+fn test_banner_before_cif2_magic() {
+    let cif = "Exported by Olex2\n#\\#CIF_2.0\ndata_a\n_x [1 2]\n";
+    let doc = Document::parse_with_options(cif, &lenient()).unwrap();
+    assert_eq!(doc.version, CifVersion::V2_0);
+    assert!(doc.blocks[0].get_item("_x").unwrap().as_list().is_some());
+}
+
+#[test]
+fn test_writer_re_emits_preamble_and_trailer() {
+    let cif = "Bruker APEX3\ndata_a\n_x 1\n#END\nCHECKSUM 1234\n";
+    let doc = Document::parse_with_options(cif, &lenient()).unwrap();
+
+    let plain = doc.to_string();
+    assert!(
+        !plain.contains("Bruker") && !plain.contains("CHECKSUM"),
+        "{plain}"
+    );
+
+    let kept = doc.to_string_with_options(&WriteOptions::new().preamble_and_trailer(true));
+    assert!(kept.starts_with("#\\#CIF_1.1\nBruker APEX3\n"), "{kept}");
+    assert!(kept.ends_with("#END\nCHECKSUM 1234\n"), "{kept}");
+
+    let reread = Document::parse_with_options(&kept, &lenient()).unwrap();
+    assert!(reread.semantically_equal(&doc));
+    assert_eq!(reread.trailer, doc.trailer);
+    assert_eq!(reread.preamble.as_deref(), Some("Bruker APEX3\n"));
+}
@@ -0,0 +1,169 @@
+// tests/typed_tests.rs
+// Reading tags of a real file as Rust types, and the errors when they don't fit
+
+use cif_parser::{CifBlock, CifValue, ConversionError, Document, FromCifValue};
+use cif_parser::{ParseOptions, Selection};
+
+const ALBITE: &str = "tests/example_cifs/albite/jana2006_albite.cif";
+
+/// The dynamical refinement's block, without its large loops
+fn albite() -> CifBlock {
+    let options = ParseOptions::new()
+        .select(Selection::Blocks(vec!["albite_dynamical".to_string()]))
+        .select(Selection::TagPrefixes(
+            [
+                "_cell_",
+                "_chemical_",
+                "_diffrn_ambient",
+                "_refine_ls_hydrogen",
+            ]
+            .map(String::from)
+            .to_vec(),
+        ));
+    let mut doc = Document::from_file_with_options(ALBITE, &options).unwrap();
+    doc.blocks.remove(0)
+}
+
+#[test]
+fn test_built_in_types_from_a_real_file() {
+    let block = albite();
+
+    assert_eq!(block.get_as::<f64>("_cell_length_a").unwrap(), Some(7.13));
+    let (volume, su) = block
+        .get_as::<(f64, Option<f64>)>("_cell_volume")
+        .unwrap()
+        .unwrap();
+    assert_eq!(volume, 325.0);
+    assert!((su.unwrap() - 4.0).abs() < 1e-12);
+    assert_eq!(
+        block.get_as::<i64>("_cell_formula_units_Z").unwrap(),
+        Some(2)
+    );
+    assert_eq!(
+        block.get_as::<String>("_chemical_formula_sum").unwrap(),
+        Some("Na Al Si3 O8".to_string())
+    );
+    // Numbers read as text give the token back
+    assert_eq!(
+        block
+            .get_as::<String>("_diffrn_ambient_temperature")
+            .unwrap(),
+        Some("293".to_string())
+    );
+    assert_eq!(block.get_as::<f64>("_no_such_tag").unwrap(), None);
+}
+
+#[test]
+fn test_errors_carry_tag_and_token() {
+    let block = albite();
+
+    let error = block
+        .get_as::<bool>("_refine_ls_hydrogen_treatment")
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ConversionError {
+            tag: Some("_refine_ls_hydrogen_treatment".to_string()),
+            raw: "undef".to_string(),
+            expected: "a yes/no value".to_string(),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "_refine_ls_hydrogen_treatment: 'undef' is not a yes/no value"
+    );
+
+    let error = block
+        .get_as::<[f64; 3]>("_chemical_formula_sum")
+        .unwrap_err();
+    assert_eq!(error.raw, "Na Al Si3 O8");
+    let error = block.get_as::<i64>("_cell_length_a").unwrap_err();
+    assert_eq!(error.raw, "7.13(6)");
+}
+
+#[test]
+fn test_vectors_from_text_and_lists() {
+    let doc = Document::parse(
+        "#\\#CIF_2.0\ndata_x\n_hkl '1 0 0'\n_list [0.5 0.25 0]\n_one 3\n_pair '1 2'\n",
+    )
+    .unwrap();
+    let block = doc.first_block().unwrap();
+    assert_eq!(
+        block.get_as::<[f64; 3]>("_hkl").unwrap(),
+        Some([1.0, 0.0, 0.0])
+    );
+    assert_eq!(
+        block.get_as::<[f64; 3]>("_list").unwrap(),
+        Some([0.5, 0.25, 0.0])
+    );
+    assert_eq!(block.get_as::<Vec<f64>>("_one").unwrap(), Some(vec![3.0]));
+    assert_eq!(
+        block.get_as::<Vec<f64>>("_pair").unwrap(),
+        Some(vec![1.0, 2.0])
+    );
+    let error = block.get_as::<[f64; 3]>("_pair").unwrap_err();
+    assert_eq!(error.to_string(), "_pair: '1 2' is not three numbers");
+}
+
+#[test]
+fn test_booleans_and_unknowns() {
+    for (token, expected) in [("yes", true), ("Y", true), ("TRUE", true), ("1", true)] {
+        assert_eq!(
+            bool::from_cif_value(&CifValue::parse_value(token)),
+            Ok(expected)
+        );
+    }
+    for (token, expected) in [("no", false), ("n", false), ("False", false), ("0", false)] {
+        assert_eq!(
+            bool::from_cif_value(&CifValue::parse_value(token)),
+            Ok(expected)
+        );
+    }
+    for token in ["?", "."] {
+        let value = CifValue::parse_value(token);
+        assert_eq!(Option::<bool>::from_cif_value(&value), Ok(None));
+        assert_eq!(f64::from_cif_value(&value).unwrap_err().raw, token);
+    }
+}
+
+#[test]
+fn test_shorthands_read_unknowns_as_absent() {
+    let block = albite();
+    assert_eq!(block.get_f64("_cell_length_a").unwrap(), Some(7.13));
+    assert_eq!(block.get_i64("_cell_formula_units_Z").unwrap(), Some(2));
+    assert_eq!(
+        block.get_str("_chemical_formula_sum").unwrap().as_deref(),
+        Some("Na Al Si3 O8")
+    );
+    assert_eq!(block.get_f64("_no_such_tag").unwrap(), None);
+    assert!(block.get_bool("_refine_ls_hydrogen_treatment").is_err());
+
+    let doc = Document::parse("data_x\n_a ?\n_b .\n_c yes\n_d 2.5\n").unwrap();
+    let block = doc.first_block().unwrap();
+    for tag in ["_a", "_b"] {
+        assert_eq!(block.get_f64(tag).unwrap(), None);
+        assert_eq!(block.get_i64(tag).unwrap(), None);
+        assert_eq!(block.get_str(tag).unwrap(), None);
+        assert_eq!(block.get_bool(tag).unwrap(), None);
+    }
+    assert_eq!(block.get_bool("_c").unwrap(), Some(true));
+    assert_eq!(
+        block.get_i64("_d").unwrap_err().to_string(),
+        "_d: '2.5' is not an integer"
+    );
+}
+
+#[test]
+fn test_get_str_formats_numbers_again() {
+    let input = "data_x\n_a 007\n_b '007'\n_c\n;007\n;\n_d 1E3\n_e 3.1e-12(2)\n";
+    let doc = Document::parse(input).unwrap();
+    let block = doc.first_block().unwrap();
+    let text = |tag| block.get_str(tag).unwrap();
+
+    // Quoting does not keep a code that looks like a number; a text field does
+    assert_eq!(text("_a").as_deref(), Some("7"));
+    assert_eq!(text("_b").as_deref(), Some("7"));
+    assert_eq!(text("_c").as_deref(), Some("007"));
+    assert_eq!(text("_d").as_deref(), Some("1000"));
+    assert_eq!(text("_e").as_deref(), Some("3.1e-12(2)"));
+}
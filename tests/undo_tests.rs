@@ -0,0 +1,145 @@
+// tests/undo_tests.rs
+// Snapshots, restore, and undo/redo history over edited documents
+
+use cif_parser::undo::UndoStack;
+use cif_parser::{Document, Value};
+use std::path::Path;
+
+fn fixture() -> Document {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/undo/edit.cif");
+    Document::from_file(path).unwrap()
+}
+
+/// Change an item in every block and add a row to the large loop
+fn edit(doc: &mut Document) {
+    doc.blocks[0]
+        .items
+        .insert("_cell_length_a".into(), Value::Numeric(5.7));
+    doc.blocks[0].loops[1]
+        .push_row(vec![
+            Value::Numeric(4.0),
+            Value::Numeric(2.0),
+            Value::Numeric(0.0),
+            Value::Numeric(3.1),
+        ])
+        .unwrap();
+    doc.blocks[1].items.shift_remove("_publ_section_comment");
+    doc.blocks[2].frames[0]
+        .items
+        .insert("_frame_item".into(), Value::Text("two".into()));
+    doc.blocks[2].name = "renamed".into();
+}
+
+#[test]
+fn test_restore_writes_identical_output() {
+    let mut doc = fixture();
+    let original = doc.to_string();
+    let snapshot = doc.snapshot();
+
+    edit(&mut doc);
+    assert_ne!(doc.to_string(), original);
+
+    doc.restore(&snapshot);
+    assert_eq!(doc.to_string(), original);
+    assert_eq!(
+        snapshot.block_names().collect::<Vec<_>>(),
+        ["sample", "second", "third"]
+    );
+}
+
+#[test]
+fn test_snapshots_share_unchanged_parts() {
+    let mut doc = fixture();
+    let base = doc.snapshot();
+
+    doc.blocks[0]
+        .items
+        .insert("_cell_length_a".into(), Value::Numeric(5.7));
+    let after = doc.snapshot_from(&base);
+
+    // The edited block is copied, but not its loops
+    assert!(!after.shares_block(&base, 0));
+    assert!(after.shares_loop(&base, 0, 0));
+    assert!(after.shares_loop(&base, 0, 1));
+    assert!(after.shares_block(&base, 1));
+    assert!(after.shares_block(&base, 2));
+
+    doc.blocks[0].loops[1].values[0][3] = Value::Numeric(12.6);
+    let edited_loop = doc.snapshot_from(&after);
+    assert!(edited_loop.shares_loop(&after, 0, 0));
+    assert!(!edited_loop.shares_loop(&after, 0, 1));
+
+    // A block that moved is still found by name
+    let mut moved = doc.clone();
+    moved.blocks.swap(1, 2);
+    let swapped = moved.snapshot_from(&edited_loop);
+    assert!(swapped.shares_block(&edited_loop, 0));
+    assert_eq!(swapped.block_names().nth(1), Some("third"));
+    moved.blocks.swap(1, 2);
+    let back = moved.snapshot_from(&swapped);
+    assert!(back.shares_block(&edited_loop, 1));
+    assert!(back.shares_block(&edited_loop, 2));
+}
+
+#[test]
+fn test_undo_and_redo() {
+    let mut doc = fixture();
+    let original = doc.to_string();
+    let mut history = UndoStack::new(10);
+    assert!(!history.undo(&mut doc));
+
+    history.push(&doc);
+    edit(&mut doc);
+    let edited = doc.to_string();
+    history.push(&doc);
+    doc.blocks.truncate(1);
+    let truncated = doc.to_string();
+    assert_eq!((history.undo_len(), history.redo_len()), (2, 0));
+
+    assert!(history.undo(&mut doc));
+    assert_eq!(doc.to_string(), edited);
+    assert!(history.undo(&mut doc));
+    assert_eq!(doc.to_string(), original);
+    assert!(!history.can_undo());
+
+    assert!(history.redo(&mut doc));
+    assert_eq!(doc.to_string(), edited);
+    assert!(history.redo(&mut doc));
+    assert_eq!(doc.to_string(), truncated);
+    assert!(!history.redo(&mut doc));
+
+    // A new change forgets what could be redone
+    history.undo(&mut doc);
+    history.push(&doc);
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn test_depth_and_memory_limits() {
+    let mut doc = fixture();
+    let mut history = UndoStack::new(3);
+    for i in 0..5 {
+        history.push(&doc);
+        doc.blocks[1]
+            .items
+            .insert("_cell_length_a".into(), Value::Numeric(f64::from(i)));
+    }
+    assert_eq!(history.undo_len(), 3);
+
+    // Steps that change one small item share the rest of the document
+    let one = doc.snapshot().bytes();
+    assert!(history.memory_bytes() < 2 * one);
+
+    let mut budget = UndoStack::new(100).max_bytes(one + one / 2);
+    for i in 0..5 {
+        budget.push(&doc);
+        doc.blocks[0].loops[1].values[0][3] = Value::Numeric(f64::from(i));
+        doc.blocks[0].items.insert(
+            "_chemical_name_common".into(),
+            Value::Text("x".repeat(4096)),
+        );
+    }
+    assert!(budget.undo_len() < 5);
+    assert!(budget.undo_len() >= 1);
+    assert!(budget.memory_bytes() <= one + one / 2 || budget.undo_len() == 1);
+}
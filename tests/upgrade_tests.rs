@@ -0,0 +1,159 @@
+// tests/upgrade_tests.rs
+// Converting CIF 1.1 documents with markup to CIF 2.0 with Unicode text
+
+use cif_parser::upgrade::{UpgradeOptions, ValuePlace};
+use cif_parser::{CifValue, CifVersion, Document};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn fixture() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/upgrade/legacy.cif")
+}
+
+fn text<'a>(doc: &'a Document, tag: &str, row: usize) -> &'a str {
+    let block = &doc.blocks[0];
+    match block.get_item(tag) {
+        Some(value) => value.as_string().unwrap(),
+        None => block
+            .find_loop(tag)
+            .unwrap()
+            .get_by_tag(row, tag)
+            .unwrap()
+            .as_string()
+            .unwrap(),
+    }
+}
+
+#[test]
+fn test_upgrade_round_trips_as_cif2_unicode() {
+    let doc = Document::from_file(fixture()).unwrap();
+    assert_eq!(doc.version, CifVersion::V1_1);
+    let (upgraded, report) = doc.upgrade_to_cif2(&UpgradeOptions::new());
+    assert!(report.problems.is_empty());
+
+    let written = upgraded.to_string();
+    assert!(written.starts_with("#\\#CIF_2.0\n"));
+    let reparsed = Document::parse(&written).unwrap();
+    assert_eq!(reparsed.version, CifVersion::V2_0);
+
+    assert_eq!(text(&reparsed, "_publ_author_name", 0), "Müller, Jürgen");
+    assert_eq!(text(&reparsed, "_publ_author_name", 1), "Dupré, René");
+    assert_eq!(
+        text(&reparsed, "_publ_author_address", 1),
+        "Université de Genève"
+    );
+    assert_eq!(
+        text(&reparsed, "_chemical_formula_moiety", 0),
+        "C12 H10 Cu N2 O4²⁺, 2(Cl O4⁻)"
+    );
+    assert!(text(&reparsed, "_publ_section_title", 0).contains("Bis(μ-oxalato)copper(II)"));
+    assert!(text(&reparsed, "_publ_section_title", 0).ends_with("1.95 Å"));
+    // Numbers and plain text are untouched
+    assert_eq!(
+        reparsed.blocks[0].get_item("_cell_length_a"),
+        doc.blocks[0].get_item("_cell_length_a")
+    );
+    assert_eq!(text(&reparsed, "_publ_author_name", 2), "Smith, Anne");
+}
+
+#[test]
+fn test_report_lists_every_change() {
+    let doc = Document::from_file(fixture()).unwrap();
+    let (_, report) = doc.upgrade_to_cif2(&UpgradeOptions::new());
+    let places: Vec<String> = report
+        .changes
+        .iter()
+        .map(|change| change.place.to_string())
+        .collect();
+    assert_eq!(
+        places,
+        [
+            "data_cu_complex _chemical_formula_moiety",
+            "data_cu_complex _publ_section_title",
+            "data_cu_complex _publ_author_name[0]",
+            "data_cu_complex _publ_author_address[0]",
+            "data_cu_complex _publ_author_name[1]",
+            "data_cu_complex _publ_author_address[1]",
+        ]
+    );
+    let name = &report.changes[4];
+    assert_eq!(name.before, "Dupr\\'e, Ren\\'e");
+    assert_eq!(name.after, "Dupré, René");
+    assert_eq!(
+        name.to_string(),
+        r#"data_cu_complex _publ_author_name[1]: "Dupr\\'e, Ren\\'e" -> "Dupré, René""#
+    );
+}
+
+#[test]
+fn test_keep_markup() {
+    let doc = Document::from_file(fixture()).unwrap();
+    let (upgraded, report) = doc.upgrade_to_cif2(&UpgradeOptions::new().keep_markup(true));
+    assert!(report.is_empty());
+    assert_eq!(upgraded.version, CifVersion::V2_0);
+    assert_eq!(text(&upgraded, "_publ_author_name", 1), "Dupr\\'e, Ren\\'e");
+}
+
+#[test]
+fn test_unwritable_text_is_a_problem() {
+    let mut doc = Document::parse("data_x\n_note ok\n").unwrap();
+    let text = "uses ''' and \"\"\"\n; and a line starting with a semicolon";
+    doc.blocks[0]
+        .items
+        .insert("_note".to_string(), CifValue::Text(text.to_string()));
+    let (_, report) = doc.upgrade_to_cif2(&UpgradeOptions::new());
+    assert_eq!(report.problems.len(), 1);
+    assert_eq!(
+        report.problems[0].place,
+        ValuePlace {
+            block: "x".to_string(),
+            frame: None,
+            tag: "_note".to_string(),
+            row: None,
+        }
+    );
+
+    // With only one kind of triple quote, CIF 2.0 can quote it
+    let text = "a line\n;starting with a semicolon";
+    doc.blocks[0]
+        .items
+        .insert("_note".to_string(), CifValue::Text(text.to_string()));
+    let (upgraded, report) = doc.upgrade_to_cif2(&UpgradeOptions::new());
+    assert!(report.problems.is_empty());
+    let reparsed = Document::parse(&upgraded.to_string()).unwrap();
+    assert_eq!(
+        reparsed.blocks[0].get_item("_note").unwrap().as_string(),
+        Some(text)
+    );
+}
+
+#[test]
+fn test_cli_upgrade() {
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join("upgraded.cif");
+    let result = Command::new(env!("CARGO_BIN_EXE_cif"))
+        .args(["upgrade", "--to", "2.0"])
+        .arg(fixture())
+        .arg("-o")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 6);
+    assert!(stderr.contains("_publ_author_name[1]: \"Dupr\\\\'e, Ren\\\\'e\" -> \"Dupré, René\""));
+
+    let reparsed = Document::from_file(&output).unwrap();
+    assert_eq!(reparsed.version, CifVersion::V2_0);
+    assert_eq!(text(&reparsed, "_publ_author_name", 0), "Müller, Jürgen");
+    assert!(fs::read_to_string(&output)
+        .unwrap()
+        .starts_with("#\\#CIF_2.0"));
+
+    let result = Command::new(env!("CARGO_BIN_EXE_cif"))
+        .args(["upgrade", "--to", "1.1"])
+        .arg(fixture())
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(2));
+}
@@ -0,0 +1,116 @@
+// tests/vocab_tests.rs
+// Enumerated core values: case-insensitive matching, typo suggestions, and enums
+
+use cif_parser::vocab::{vocabulary_for, AdpType, VOCABULARIES};
+use cif_parser::{Document, HydrogenTreatment};
+use std::path::Path;
+
+fn document() -> Document {
+    Document::from_file(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/vocab/typos.cif"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_out_of_vocabulary_values_with_suggestions() {
+    let doc = document();
+    let issues = doc.blocks[0].check_vocabularies();
+    let found: Vec<(&str, Option<usize>, &str, Option<&str>)> = issues
+        .iter()
+        .map(|i| (i.tag.as_str(), i.row, i.value.as_str(), i.suggestion))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("_atom_site_adp_type", Some(2), "Uanl", Some("Uani")),
+            ("_atom_site_calc_flag", Some(3), "riding", None),
+            ("_refine_ls_weighting_scheme", None, "calcd", Some("calc")),
+            (
+                "_exptl_absorpt_correction_type",
+                None,
+                "multiscan",
+                Some("multi-scan")
+            ),
+            (
+                "_exptl_crystal_colour_primary",
+                None,
+                "colorless",
+                Some("colourless")
+            ),
+            // One letter is too short to guess at
+            ("_space_group_centring_type", None, "X", None),
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "_atom_site_adp_type[2] = Uanl: must be one of Uani, Uiso, Uovl, Umpe, \
+         Bani, Biso, Bovl; did you mean Uani?"
+    );
+
+    let mmcif = doc.blocks[1].check_vocabularies();
+    assert_eq!(mmcif.len(), 1);
+    assert_eq!(mmcif[0].tag, "_atom_site.adp_type");
+    assert_eq!(mmcif[0].suggestion, Some("Biso"));
+}
+
+#[test]
+fn test_case_is_ignored() {
+    let adp = vocabulary_for("_ATOM_SITE_ADP_TYPE").unwrap();
+    assert_eq!(adp.canonical("UANI"), Some("Uani"));
+    assert_eq!(adp.canonical("uiso"), Some("Uiso"));
+    assert_eq!(adp.canonical("Uanl"), None);
+
+    let hydrogens = vocabulary_for("_refine.ls_hydrogen_treatment").unwrap();
+    assert_eq!(hydrogens.canonical("REFU"), Some("refU"));
+    assert!(vocabulary_for("_cell_length_a").is_none());
+}
+
+#[test]
+fn test_every_vocabulary_is_consistent() {
+    for vocabulary in VOCABULARIES {
+        for &tag in vocabulary.tags {
+            assert_eq!(vocabulary_for(tag), Some(vocabulary), "{tag}");
+        }
+        for &value in vocabulary.values {
+            assert_eq!(vocabulary.canonical(value), Some(value));
+            assert_eq!(vocabulary.suggest(value), Some(value));
+        }
+    }
+}
+
+#[test]
+fn test_issues_in_sanity_check() {
+    let doc = document();
+    let issues = doc.blocks[0].sanity_check();
+    let vocabulary: Vec<_> = issues.iter().filter(|i| i.id == "vocabulary").collect();
+    assert_eq!(vocabulary.len(), 6);
+    assert_eq!(vocabulary[0].tag, "_atom_site_adp_type");
+    assert_eq!(vocabulary[0].row, Some(2));
+    assert!(vocabulary[0].rule.ends_with("did you mean Uani?"));
+}
+
+#[test]
+fn test_typed_extractors_use_enums() {
+    let doc = document();
+    let structure = doc.blocks[0].structure().unwrap();
+    let adp: Vec<Option<AdpType>> = structure.sites.iter().map(|s| s.adp_type.clone()).collect();
+    assert_eq!(
+        adp,
+        [
+            Some(AdpType::Uani),
+            Some(AdpType::Uiso),
+            Some(AdpType::Other("Uanl".into())),
+            Some(AdpType::Uiso),
+            None,
+        ]
+    );
+    assert_eq!(adp[0].as_ref().unwrap().code(), "Uani");
+    assert!(adp[0].as_ref().unwrap().is_anisotropic());
+
+    let model = doc.blocks[0].refinement_model();
+    assert_eq!(
+        model.hydrogen_treatment,
+        Some(HydrogenTreatment::Constrained)
+    );
+}